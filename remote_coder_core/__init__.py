@@ -0,0 +1,165 @@
+"""Embeddable orchestration engine of Remote Coder.
+
+This package is the supported public API for running Remote Coder inside
+another program instead of as the Slack daemon: sessions, the router, the
+agent runner, and the agent adapters. It does not import the Slack adapter,
+so `slack_sdk` is never loaded; bring your own `IChatAdapter` instead (set
+its `capabilities` to a `ChatCapabilities` for the platform's message length
+limit and whether it has threads, buttons, and file uploads)::
+
+    import asyncio
+
+    from remote_coder_core import GitHubManager, IChatAdapter, Router, SessionManager, load_config
+
+
+    class ConsoleAdapter(IChatAdapter):
+        async def send_message(self, channel, thread_ts, text):
+            print(text)
+            return None
+
+        async def start(self):
+            pass
+
+        async def stop(self):
+            pass
+
+
+    async def main():
+        config = load_config("~/.remote-coder", require_slack=False)
+        router = Router(SessionManager(), config, GitHubManager(config.github_token), config.config_dir)
+        router.bind_adapter(ConsoleAdapter())
+        await router.check_agent_clis()
+        # The first message of a thread starts a session for the project mapped to the channel
+        await router.handle_message({"channel": "C1", "channel_name": "my-project", "text": "hi", "ts": "1"})
+        await router.handle_message(
+            {"channel": "C1", "channel_name": "my-project", "text": "add a README", "thread_ts": "1"}
+        )
+
+
+    asyncio.run(main())
+
+To follow sessions from your program, subscribe to
+`session_manager.events`, e.g. `events.subscribe(on_end, SessionEnded)`;
+handlers get `SessionCreated`, `RunStarted`, `RunFinished`, `ChecksFinished`,
+`PrOpened`, `PrMerged`, and `SessionEnded` events and may be coroutine
+functions.
+
+To run an agent without the router, build an adapter for an `Agent` and
+call `await adapter.run(task_text=..., project_path=..., session_id=...,
+conversation_history=[])`; it returns an `AgentResult`.
+
+Names not listed in `__all__` (anything only reachable through `src.*`) are
+internal and may change between releases.
+"""
+
+from src.agent_adapters import (
+    AgentAdapter,
+    AgentResult,
+    ClaudeAdapter,
+    CodexAdapter,
+    FileEdit,
+    GeminiAdapter,
+    HttpAgentAdapter,
+    MockAdapter,
+    OllamaAdapter,
+    OpenRouterAdapter,
+    WasmAgentAdapter,
+)
+from src.agent_adapters.base import StructuredOutput
+from src.chat_adapters.i_chat_adapter import ChatCapabilities, IChatAdapter
+from src.core import (
+    Agent,
+    AgentNotAllowed,
+    AgentNotFound,
+    AgentType,
+    CommandNotFound,
+    Config,
+    ConfigError,
+    GitHubError,
+    GitHubRepoConfig,
+    ProcessError,
+    Project,
+    ProjectNotFound,
+    PullRequestRef,
+    PullRequestRole,
+    RemoteCoderError,
+    Router,
+    Session,
+    SessionManager,
+    SessionNotFound,
+    SessionStatus,
+    SlackError,
+    WorkingDirMode,
+    load_config,
+)
+from src.core.agent_runner import AgentTaskRunner
+from src.core.events import (
+    ChecksFinished,
+    EventBus,
+    PrMerged,
+    PrOpened,
+    RunFinished,
+    RunStarted,
+    SessionCreated,
+    SessionEnded,
+    SessionEvent,
+)
+from src.core.models import ConversationMessage
+from src.github import GitHubManager
+
+__all__ = [
+    # Orchestration
+    "Router",
+    "SessionManager",
+    "AgentTaskRunner",
+    "IChatAdapter",
+    "ChatCapabilities",
+    "GitHubManager",
+    # Session lifecycle events
+    "EventBus",
+    "SessionEvent",
+    "SessionCreated",
+    "RunStarted",
+    "RunFinished",
+    "ChecksFinished",
+    "PrOpened",
+    "PrMerged",
+    "SessionEnded",
+    # Configuration and domain models
+    "Config",
+    "load_config",
+    "Agent",
+    "AgentType",
+    "ConversationMessage",
+    "GitHubRepoConfig",
+    "Project",
+    "PullRequestRef",
+    "PullRequestRole",
+    "Session",
+    "SessionStatus",
+    "WorkingDirMode",
+    # Agent adapters
+    "AgentAdapter",
+    "AgentResult",
+    "FileEdit",
+    "StructuredOutput",
+    "ClaudeAdapter",
+    "CodexAdapter",
+    "GeminiAdapter",
+    "HttpAgentAdapter",
+    "MockAdapter",
+    "OllamaAdapter",
+    "OpenRouterAdapter",
+    "WasmAgentAdapter",
+    # Errors
+    "RemoteCoderError",
+    "AgentNotAllowed",
+    "AgentNotFound",
+    "CommandNotFound",
+    "ConfigError",
+    "GitHubError",
+    "ProcessError",
+    "ProjectNotFound",
+    "SessionNotFound",
+    "SlackError",
+]
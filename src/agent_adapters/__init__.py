@@ -4,6 +4,11 @@ from .base import AgentAdapter, AgentResult, FileEdit
 from .claude_adapter import ClaudeAdapter
 from .codex_adapter import CodexAdapter
 from .gemini_adapter import GeminiAdapter
+from .http_adapter import HttpAgentAdapter
+from .mock_adapter import MockAdapter
+from .ollama_adapter import OllamaAdapter
+from .openrouter_adapter import OpenRouterAdapter
+from .wasm_adapter import WasmAgentAdapter
 
 __all__ = [
     "AgentAdapter",
@@ -12,4 +17,9 @@ __all__ = [
     "ClaudeAdapter",
     "CodexAdapter",
     "GeminiAdapter",
+    "HttpAgentAdapter",
+    "MockAdapter",
+    "OllamaAdapter",
+    "OpenRouterAdapter",
+    "WasmAgentAdapter",
 ]
@@ -2,14 +2,55 @@
 
 from __future__ import annotations
 
+import asyncio
 import json
 import logging
+import os
 from abc import ABC, abstractmethod
+from contextlib import asynccontextmanager, contextmanager
+from contextvars import ContextVar
 from dataclasses import dataclass, field
-from typing import Any, Dict, List, Sequence
+from pathlib import Path
+from typing import (
+    TYPE_CHECKING,
+    Any,
+    AsyncIterator,
+    Awaitable,
+    Callable,
+    Dict,
+    FrozenSet,
+    Iterable,
+    Iterator,
+    List,
+    Mapping,
+    Optional,
+    Sequence,
+    Tuple,
+)
+
+from .bubblewrap import sandboxed_command
+from .confirmations import PromptWatcher, watch_output
+from .containers import agent_container_argv, container_name, current_container, remove_container
+from .dev_env import check_dev_env_sandbox, current_dev_env, dev_env_command
+from .limits import cgroup_command, describe_limit_exit, rlimit_setter, uses_cgroup
+from .terminal import close_pty, open_pty, read_pty, terminal_env
+from .windows import IS_WINDOWS, create_process, interrupt_signal, kill_process_tree
+
+if TYPE_CHECKING:
+    from ..core.models import ConfirmPrompts, ProcessLimits, SandboxConfig
 
 LOGGER = logging.getLogger(__name__)
 
+# Receives an agent's text as it is produced; a line is finished at each newline
+OutputFn = Callable[[str], None]
+
+# CLI processes of the run in progress, so the runner can interrupt them at the soft timeout
+_RUN_PROCESSES: ContextVar[Optional[List[asyncio.subprocess.Process]]] = ContextVar("run_processes", default=None)
+# Told about each of them as it starts, e.g. to journal its pid
+_PROCESS_OBSERVER: ContextVar[Optional[Callable[[asyncio.subprocess.Process], None]]] = ContextVar(
+    "process_observer", default=None
+)
+
 
 @dataclass
 class FileEdit:
@@ -40,6 +81,8 @@ class AgentResult:
     session_context: Dict[str, Any] = field(default_factory=dict)
     raw_output: str = ""
     structured_output: StructuredOutput | None = None
+    token_usage: Dict[str, int] = field(default_factory=dict)  # {"input_tokens": ..., "output_tokens": ...}
+    exit_code: Optional[int] = None  # Of the agent's CLI process; set by the runner, None without one
 
 
 def _extract_json_from_text(text: str, start_pos: int) -> str | None:
@@ -128,6 +171,13 @@ def parse_structured_output(text: str) -> StructuredOutput | None:
 class AgentAdapter(ABC):
     """Base adapter interface for CLI-based agents."""
 
+    # Per-run parameters besides `model` that `run` accepts as keyword arguments (see `RunOverrides`)
+    supported_overrides: FrozenSet[str] = frozenset()
+    # Whether `run` accepts `on_output: OutputFn` and reports the agent's text while it works
+    streams_output: bool = False
+    # Whether `run` accepts `on_progress: ProgressFn` and reports tool calls as they start and finish (see `progress`)
+    reports_progress: bool = False
+
     @abstractmethod
     async def run(
         self,
@@ -139,3 +189,169 @@ class AgentAdapter(ABC):
     ) -> AgentResult:
         """Execute a one-shot task with the underlying agent."""
 
+
+def emit_output(on_output: Optional[OutputFn], segments: Iterable[str]) -> None:
+    """Pass finished text segments to `on_output`, one line each."""
+    if on_output:
+        for segment in segments:
+            on_output(f"{segment}\n")
+
+
+@contextmanager
+def track_run_processes() -> Iterator[List[asyncio.subprocess.Process]]:
+    """Collect the agent processes started in this context, including by tasks created inside it."""
+    processes: List[asyncio.subprocess.Process] = []
+    token = _RUN_PROCESSES.set(processes)
+    try:
+        yield processes
+    finally:
+        _RUN_PROCESSES.reset(token)
+
+
+@contextmanager
+def observe_run_processes(observer: Callable[[asyncio.subprocess.Process], None]) -> Iterator[None]:
+    """Call `observer` with every agent process started in this context, including by tasks created inside it."""
+    token = _PROCESS_OBSERVER.set(observer)
+    try:
+        yield
+    finally:
+        _PROCESS_OBSERVER.reset(token)
+
+
+def register_run_process(process: asyncio.subprocess.Process) -> None:
+    """Make a process working on the current run known to `track_run_processes` and `observe_run_processes`."""
+    processes = _RUN_PROCESSES.get()
+    if processes is not None and process not in processes:
+        processes.append(process)
+    observer = _PROCESS_OBSERVER.get()
+    if observer is not None:
+        observer(process)
+
+
+def interrupt_processes(processes: Iterable[asyncio.subprocess.Process]) -> int:
+    """Send SIGINT (CTRL_BREAK_EVENT on Windows) to the processes still running, like Ctrl-C would.
+
+    Returns how many got it.
+    """
+    interrupted = 0
+    for process in processes:
+        if process.returncode is not None:
+            continue
+        try:
+            process.send_signal(interrupt_signal())
+        except ProcessLookupError:
+            continue
+        interrupted += 1
+    return interrupted
+
+
+@asynccontextmanager
+async def spawn_cli(
+    command: Sequence[str],
+    *,
+    cwd: Path,
+    env: Mapping[str, str],
+    stdin: bool = True,
+    terminal: Optional[Tuple[int, int]] = None,
+    confirm_prompts: Optional[ConfirmPrompts] = None,
+    limits: Optional[ProcessLimits] = None,
+    sandbox: Optional[SandboxConfig] = None,
+) -> AsyncIterator[asyncio.subprocess.Process]:
+    """Start an agent CLI with piped output, killing it if the run ends early (`!stop` or a timeout).
+
+    With a `terminal` size (columns, rows) its stdout, and its stdin unless `stdin` pipes the
+    prompt in, is a pseudo-terminal instead; `process.stdout` reads it like a pipe. With
+    `confirm_prompts` the output is watched for them and the answers are written to its stdin
+    (piped even when `stdin` is False and there is no terminal). `limits` caps its CPU time,
+    memory, and open files (see `limits.py`), and a bubblewrap `sandbox` hides the rest of the
+    host from it (see `bubblewrap.py`). Inside `run_agents_in_container` the CLI runs in a
+    container of the project's image instead, which is removed afterwards (see `containers.py`),
+    and inside `run_in_dev_env` it starts in the project's development environment (see `dev_env.py`).
+    """
+    from ..core.privacy import PRIVACY
+
+    container = current_container()
+    name: Optional[str] = None
+    if container is not None:
+        if terminal:
+            LOGGER.warning("Agent CLIs in a container get piped output; ignoring the terminal setting")
+            terminal = None
+        name = container_name()
+        command = agent_container_argv(container, command, cwd, name, limits)
+        LOGGER.info("Running agent CLI in container %s of %s", name, container.sandbox.image)
+    else:
+        dev_env = current_dev_env()
+        check_dev_env_sandbox(dev_env, sandbox)
+        command = dev_env_command(sandboxed_command(command, cwd, sandbox), dev_env)
+    if terminal and IS_WINDOWS:
+        LOGGER.warning("Windows has no pseudo-terminals; the agent CLI gets piped output")
+        terminal = None
+    master = slave = None
+    if terminal:
+        master, slave = open_pty(terminal)
+        env = terminal_env(env, terminal)
+    pipe_stdin = stdin or (confirm_prompts is not None and not terminal)
+    cgroup = container is None and uses_cgroup(limits)
+    try:
+        process = await create_process(
+            *(cgroup_command(command, limits) if cgroup and limits else command),
+            stdin=asyncio.subprocess.PIPE if pipe_stdin else slave,
+            stdout=slave if terminal else asyncio.subprocess.PIPE,
+            stderr=asyncio.subprocess.PIPE,
+            cwd=str(cwd),
+            env=PRIVACY.cli_env(env),
+            preexec_fn=rlimit_setter(limits, cgroup=cgroup) if container is None else None,
+        )
+    except BaseException:
+        if master is not None:
+            os.close(master)
+        raise
+    finally:
+        if slave is not None:
+            os.close(slave)  # The child holds its own copy; the output ends when it closes it
+    if master is not None:
+        process.stdout = read_pty(master)
+    pump: Optional[asyncio.Task] = None
+    if confirm_prompts is not None and process.stdout is not None:
+        watcher = PromptWatcher(confirm_prompts, _stdin_writer(process, None if pipe_stdin else master))
+        process.stdout, pump = watch_output(process.stdout, watcher)
+    register_run_process(process)
+    try:
+        yield process
+    finally:
+        if process.returncode is None:
+            LOGGER.info("Killing agent process %s before it finished", process.pid)
+            if IS_WINDOWS:
+                await kill_process_tree(process)
+            else:
+                process.kill()
+            await process.wait()
+        else:
+            reason = describe_limit_exit(process.returncode, limits)
+            if reason:
+                LOGGER.warning("Agent process %s was stopped by %s", process.pid, reason)
+        if pump is not None and not pump.done():
+            pump.cancel()
+        if master is not None:
+            close_pty(master)
+        if container is not None and name is not None:
+            # Killing the runtime client does not stop the container itself
+            await remove_container(container.sandbox.runtime, name)
+
+
+def _stdin_writer(process: asyncio.subprocess.Process, master: Optional[int]) -> Callable[[bytes], Awaitable[None]]:
+    """Writes to the process's stdin pipe, or to its terminal when stdin is the terminal."""
+
+    async def _write(data: bytes) -> None:
+        try:
+            if master is not None:
+                os.write(master, data)
+            elif process.stdin is not None and not process.stdin.is_closing():
+                process.stdin.write(data)
+                await process.stdin.drain()
+            else:
+                LOGGER.warning("Agent process %s has no open stdin to answer its prompt", process.pid)
+        except (BrokenPipeError, ConnectionResetError, OSError) as exc:
+            LOGGER.warning("Failed to answer the prompt of agent process %s: %s", process.pid, exc)
+
+    return _write
@@ -0,0 +1,106 @@
+"""Lightweight sandboxing with bubblewrap, for hosts without Docker.
+
+`sandbox: bubblewrap` starts a command through `bwrap` in new namespaces.
+The command sees a read-only view of the system directories (`/usr`, `/lib`,
+`/etc`, ...), a private `/tmp`, `/proc`, and `/dev`, and writable access to
+its working directory only. Nothing else from the home directory or other
+projects is visible. Agent CLIs also get their install directory read-only
+(found through PATH, e.g. an nvm prefix) and the writable state paths listed
+in `writable_paths` (by default where the agent type keeps its login and
+sessions, e.g. `~/.claude`). Files named in the command outside the working
+directory, such as the prompt file or MCP config, are mounted read-only.
+The network namespace is unshared unless `network: true`, which CLI agents
+get by default because they call their model's API. The namespace's processes
+all die with bubblewrap, so killing it on a timeout stops everything it ran.
+"""
+
+from __future__ import annotations
+
+import os
+import shutil
+from typing import TYPE_CHECKING, Dict, List, Sequence, Tuple
+
+from .containers import _command_files
+
+if TYPE_CHECKING:
+    from pathlib import Path
+
+    from ..core.models import SandboxConfig
+
+BWRAP = "bwrap"
+# Mounted read-only when they exist; the rest of the host filesystem is hidden
+SYSTEM_PATHS = ("/usr", "/bin", "/sbin", "/lib", "/lib32", "/lib64", "/etc", "/opt", "/nix/store")
+# Default writable_paths per agent type: where its CLI keeps logins, settings, and sessions
+CLI_STATE_PATHS: Dict[str, Tuple[str, ...]] = {
+    "claude": ("~/.claude", "~/.claude.json", "~/.config/claude"),
+    "codex": ("~/.codex",),
+    "gemini": ("~/.gemini",),
+}
+
+
+class BubblewrapUnavailable(RuntimeError):
+    """`bwrap` is not installed, so a command that must be sandboxed cannot run."""
+
+
+def sandboxed_command(command: Sequence[str], cwd: Path, sandbox: SandboxConfig | None) -> List[str]:
+    """`command` started through bubblewrap when `sandbox` asks for it, else unchanged."""
+    from ..core.models import SandboxMode
+
+    if sandbox is None or sandbox.mode != SandboxMode.BUBBLEWRAP:
+        return list(command)
+    return bubblewrap_argv(sandbox, command, cwd)
+
+
+def bubblewrap_argv(sandbox: SandboxConfig, command: Sequence[str], cwd: Path) -> List[str]:
+    """The `bwrap` invocation that runs `command` in `cwd` with only `cwd` writable."""
+    bwrap = shutil.which(BWRAP)
+    if bwrap is None:
+        raise BubblewrapUnavailable(f"The bubblewrap sandbox needs `{BWRAP}` on PATH; install bubblewrap")
+    workdir = str(cwd.resolve())
+    argv = [bwrap, "--die-with-parent", "--unshare-all"]
+    if sandbox.network:
+        argv.append("--share-net")
+    for path in SYSTEM_PATHS:
+        argv.extend(["--ro-bind-try", path, path])
+    argv.extend(["--proc", "/proc", "--dev", "/dev", "--tmpfs", "/tmp"])
+    if sandbox.network and os.path.islink("/etc/resolv.conf"):
+        # Often a symlink into /run, which is not mounted
+        resolv = os.path.realpath("/etc/resolv.conf")
+        argv.extend(["--ro-bind-try", resolv, resolv])
+    for path in _install_prefixes(command[0] if command else ""):
+        argv.extend(["--ro-bind", path, path])
+    for path in sandbox.read_only_paths:
+        path = os.path.expanduser(path)
+        argv.extend(["--ro-bind-try", path, path])
+    for path in _command_files(command, workdir):
+        argv.extend(["--ro-bind", path, path])
+    for path in sandbox.writable_paths:
+        path = os.path.expanduser(path)
+        argv.extend(["--bind-try", path, path])
+    argv.extend(["--bind", workdir, workdir, "--chdir", workdir, "--", *command])
+    return argv
+
+
+def _install_prefixes(executable: str) -> List[str]:
+    """Directories an executable outside the system paths is installed in, e.g. `~/.nvm/versions/node/v20`.
+
+    The prefix (the parent of its `bin/`) usually also holds its runtime and libraries.
+    """
+    found = shutil.which(executable) if executable else None
+    if found is None:
+        return []
+    home = os.path.expanduser("~")
+    prefixes = []
+    for path in (os.path.abspath(found), os.path.realpath(found)):
+        prefix = os.path.dirname(os.path.dirname(path))
+        if prefix in ("", os.sep, home):
+            # E.g. ~/bin/claude: showing the whole home directory would defeat the sandbox
+            prefix = os.path.dirname(path)
+        if any(_within(prefix, system) for system in SYSTEM_PATHS):
+            continue
+        prefixes.append(prefix)
+    return list(dict.fromkeys(prefixes))
+
+
+def _within(path: str, root: str) -> bool:
+    return path == root or path.startswith(root + os.sep)
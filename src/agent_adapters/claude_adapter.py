@@ -6,12 +6,28 @@ import asyncio
 import json
 import logging
 import os
+import tempfile
 from pathlib import Path
-from typing import Any, Dict, Iterable, Sequence
+from typing import Any, Dict, Iterable, Optional, Sequence
 
 from ..core.model_mapping import get_cli_model_name
 from ..core.models import Agent, AgentType, WorkingDirMode
-from .base import AgentAdapter, AgentResult, FileEdit, parse_structured_output
+from .base import (
+    AgentAdapter,
+    AgentResult,
+    FileEdit,
+    OutputFn,
+    emit_output,
+    parse_structured_output,
+    register_run_process,
+    spawn_cli,
+)
+from .command_template import RenderedCommand, render_command, uses_placeholder
+from .containers import current_container
+from .dev_env import current_dev_env
+from .mcp import TOOL_NAME_PREFIX, claude_mcp_config
+from .progress import ProgressFn, emit_progress, parse_claude_event
+from .warm_pool import DEFAULT_IDLE_SECS, WarmPool
 
 LOGGER = logging.getLogger(__name__)
 
@@ -19,10 +35,14 @@ LOGGER = logging.getLogger(__name__)
 class ClaudeAdapter(AgentAdapter):
     """Executes single Claude Code runs via the CLI."""
 
-    def __init__(self, agent: Agent) -> None:
+    streams_output = True
+    reports_progress = True
+
+    def __init__(self, agent: Agent, warm_pool: Optional[WarmPool] = None) -> None:
         if agent.type != AgentType.CLAUDE:
             raise ValueError(f"ClaudeAdapter requires a CLAUDE agent, got {agent.type}")
         self._agent = agent
+        self._warm_pool = warm_pool
 
     async def run(
         self,
@@ -32,86 +52,223 @@ class ClaudeAdapter(AgentAdapter):
         session_id: str,
         conversation_history: Sequence[Dict[str, Any]],
         model: str | None = None,
+        on_output: Optional[OutputFn] = None,
+        on_progress: Optional[ProgressFn] = None,
     ) -> AgentResult:
-        command = self._build_command(session_id, model)
         workdir = self._resolve_workdir(project_path)
+        if self._uses_warm_pool():
+            return await self._run_warm(task_text, workdir, session_id, model, on_output, on_progress)
+        mcp_config_path = self._write_mcp_config()
+        rendered = render_command(
+            self._agent.command,
+            task_text=task_text,
+            workdir=workdir,
+            model=get_cli_model_name("claude", model) if model else None,
+            session_id=session_id,
+        )
+        try:
+            return await self._run_cli(
+                rendered, task_text, workdir, session_id, model, mcp_config_path, on_output, on_progress
+            )
+        finally:
+            rendered.cleanup()
+            if mcp_config_path:
+                mcp_config_path.unlink(missing_ok=True)
+
+    async def _run_cli(
+        self,
+        rendered: RenderedCommand,
+        task_text: str,
+        workdir: Path,
+        session_id: str,
+        model: str | None,
+        mcp_config_path: Optional[Path],
+        on_output: Optional[OutputFn] = None,
+        on_progress: Optional[ProgressFn] = None,
+    ) -> AgentResult:
+        command = self._build_command(session_id, model, mcp_config_path, rendered)
         env = {**os.environ, **self._agent.env}
 
         LOGGER.info("Running Claude one-shot command in %s", workdir)
-        process = await asyncio.create_subprocess_exec(
-            *command,
-            stdin=asyncio.subprocess.PIPE,
-            stdout=asyncio.subprocess.PIPE,
-            stderr=asyncio.subprocess.PIPE,
-            cwd=str(workdir),
+        async with spawn_cli(
+            command,
+            cwd=workdir,
             env=env,
+            terminal=self._agent.terminal_size,
+            confirm_prompts=self._agent.confirm_prompts,
+            limits=self._agent.limits,
+            sandbox=self._agent.sandbox,
+        ) as process:
+            assert process.stdin is not None
+            if not rendered.prompt_in_file:
+                process.stdin.write(task_text.encode("utf-8") + b"\n")
+                await process.stdin.drain()
+            if self._agent.confirm_prompts is None:
+                process.stdin.close()  # Otherwise kept open to answer prompts (the prompt is in a file)
+
+            raw_events: list[str] = []
+            text_chunks: list[str] = []
+            file_edits: list[FileEdit] = []
+            errors: list[str] = []
+            token_usage: Dict[str, int] = {}
+
+            assert process.stderr is not None
+            stderr_task = asyncio.create_task(process.stderr.read())
+
+            # Read stdout in chunks to avoid readline() buffer limit issues
+            stdout_stream = process.stdout
+            if stdout_stream:
+                buffer = ""
+                chunk_size = 10 * 1024 * 1024  # 10MB chunks
+
+                while True:
+                    chunk = await stdout_stream.read(chunk_size)
+                    if not chunk:
+                        break
+
+                    buffer += chunk.decode("utf-8", errors="replace")
+
+                    # Process complete lines from buffer
+                    while "\n" in buffer:
+                        line, buffer = buffer.split("\n", 1)
+                        decoded = line.strip()
+                        raw_events.append(decoded)
+
+                        if not decoded:
+                            continue
+
+                        parsed = self._parse_json(decoded)
+                        if parsed:
+                            self._apply_event(
+                                parsed, text_chunks, file_edits, errors, token_usage, on_output, on_progress
+                            )
+
+                # Process any remaining data in buffer
+                if buffer.strip():
+                    decoded = buffer.strip()
+                    raw_events.append(decoded)
+                    parsed = self._parse_json(decoded)
+                    if parsed:
+                        self._apply_event(parsed, text_chunks, file_edits, errors, token_usage, on_output, on_progress)
+
+            return_code = await process.wait()
+            stderr_raw = await stderr_task
+            stderr_output = stderr_raw.decode("utf-8", errors="replace").strip()
+            if stderr_output:
+                errors.append(stderr_output)
+
+            success = return_code == 0
+            return self._build_result(success, raw_events, text_chunks, file_edits, errors, token_usage, stderr_output)
+
+    def _uses_warm_pool(self) -> bool:
+        # The per-run MCP config and prompt file do not outlive a single turn; containers and dev shells neither
+        return bool(
+            self._warm_pool is not None
+            and current_container() is None
+            and current_dev_env() is None
+            and self._agent.options.get("warm_pool")
+            and not self._agent.mcp_servers
+            and not uses_placeholder(self._agent.command, "prompt_file")
         )
 
-        assert process.stdin is not None
-        process.stdin.write(task_text.encode("utf-8") + b"\n")
-        await process.stdin.drain()
-        process.stdin.close()
+    async def _run_warm(
+        self,
+        task_text: str,
+        workdir: Path,
+        session_id: str,
+        model: str | None,
+        on_output: Optional[OutputFn] = None,
+        on_progress: Optional[ProgressFn] = None,
+    ) -> AgentResult:
+        """Send the prompt to the session's long-lived Claude process and read events until its `result`."""
+        assert self._warm_pool is not None
+        rendered = render_command(
+            self._agent.command,
+            task_text=task_text,
+            workdir=workdir,
+            model=get_cli_model_name("claude", model) if model else None,
+            session_id=session_id,
+        )
+        command = self._build_command(session_id, model, None, rendered) + ["--input-format", "stream-json"]
+        # A changed command (e.g. a new model or a config reload) gets its own process
+        key = (session_id, str(workdir), *command)
+        warm = await self._warm_pool.acquire(
+            key,
+            command,
+            cwd=workdir,
+            env={**os.environ, **self._agent.env},
+            idle_secs=float(self._agent.options.get("warm_idle_secs", DEFAULT_IDLE_SECS)),
+            limits=self._agent.limits,
+            sandbox=self._agent.sandbox,
+        )
 
         raw_events: list[str] = []
         text_chunks: list[str] = []
         file_edits: list[FileEdit] = []
         errors: list[str] = []
-
-        assert process.stderr is not None
-        stderr_task = asyncio.create_task(process.stderr.read())
-
-        # Read stdout in chunks to avoid readline() buffer limit issues
-        stdout_stream = process.stdout
-        if stdout_stream:
-            buffer = ""
-            chunk_size = 10 * 1024 * 1024  # 10MB chunks
-
-            while True:
-                chunk = await stdout_stream.read(chunk_size)
-                if not chunk:
-                    break
-
-                buffer += chunk.decode("utf-8", errors="replace")
-
-                # Process complete lines from buffer
-                while "\n" in buffer:
-                    line, buffer = buffer.split("\n", 1)
+        token_usage: Dict[str, int] = {}
+        result_event: Optional[Dict[str, Any]] = None
+
+        LOGGER.info("Sending turn to warm Claude process in %s", workdir)
+        async with warm.lock:
+            register_run_process(warm.process)
+            try:
+                content = [{"type": "text", "text": task_text}]
+                await warm.send_line(json.dumps({"type": "user", "message": {"role": "user", "content": content}}))
+                while result_event is None:
+                    line = await warm.read_line()
+                    if line is None:
+                        break
                     decoded = line.strip()
                     raw_events.append(decoded)
-
-                    if not decoded:
-                        continue
-
-                    parsed = self._parse_json(decoded)
+                    parsed = self._parse_json(decoded) if decoded else None
                     if not parsed:
                         continue
-
-                    segments = self._extract_text_segments(parsed)
-                    if segments:
-                        text_chunks.extend(segments)
-                    file_edits.extend(self._extract_file_edits(parsed))
-                    errors.extend(self._extract_errors(parsed))
-
-            # Process any remaining data in buffer
-            if buffer.strip():
-                decoded = buffer.strip()
-                raw_events.append(decoded)
-                parsed = self._parse_json(decoded)
-                if parsed:
-                    segments = self._extract_text_segments(parsed)
-                    if segments:
-                        text_chunks.extend(segments)
-                    file_edits.extend(self._extract_file_edits(parsed))
-                    errors.extend(self._extract_errors(parsed))
-
-        return_code = await process.wait()
-        stderr_raw = await stderr_task
-        stderr_output = stderr_raw.decode("utf-8", errors="replace").strip()
-        if stderr_output:
-            errors.append(stderr_output)
-
-        success = return_code == 0
-
+                    self._apply_event(parsed, text_chunks, file_edits, errors, token_usage, on_output, on_progress)
+                    if parsed.get("type") == "result":
+                        result_event = parsed
+            except BaseException:
+                # A half-read turn leaves the process out of step; start fresh next time
+                await self._warm_pool.discard(key)
+                raise
+
+        stderr_output = ""
+        if result_event is None:
+            await self._warm_pool.discard(key)
+            stderr_output = warm.stderr_text()
+            errors.append(stderr_output or "The warm Claude process exited before finishing the turn")
+        success = result_event is not None and not result_event.get("is_error")
+        return self._build_result(success, raw_events, text_chunks, file_edits, errors, token_usage, stderr_output)
+
+    def _apply_event(
+        self,
+        parsed: Dict[str, Any],
+        text_chunks: list[str],
+        file_edits: list[FileEdit],
+        errors: list[str],
+        token_usage: Dict[str, int],
+        on_output: Optional[OutputFn] = None,
+        on_progress: Optional[ProgressFn] = None,
+    ) -> None:
+        segments = self._extract_text_segments(parsed)
+        if segments:
+            text_chunks.extend(segments)
+            emit_output(on_output, segments)
+        emit_progress(on_progress, parse_claude_event(parsed))
+        file_edits.extend(self._extract_file_edits(parsed))
+        errors.extend(self._extract_errors(parsed))
+        token_usage.update(self._extract_usage(parsed))
+
+    def _build_result(
+        self,
+        success: bool,
+        raw_events: list[str],
+        text_chunks: list[str],
+        file_edits: list[FileEdit],
+        errors: list[str],
+        token_usage: Dict[str, int],
+        stderr_output: str,
+    ) -> AgentResult:
         output_text = "\n".join(chunk for chunk in text_chunks if chunk).strip()
         raw_output = "\n".join(raw_events + ([stderr_output] if stderr_output else []))
         structured_output = parse_structured_output(output_text or raw_output)
@@ -124,19 +281,40 @@ class ClaudeAdapter(AgentAdapter):
             session_context={},
             raw_output=raw_output,
             structured_output=structured_output,
+            token_usage=token_usage,
         )
 
-    def _build_command(self, session_id: str, model: str | None) -> list[str]:
+    def _write_mcp_config(self) -> Optional[Path]:
+        """Write the agent's MCP servers to a temporary --mcp-config file for one run."""
+        if not self._agent.mcp_servers:
+            return None
+        handle, path = tempfile.mkstemp(prefix="remote-coder-mcp-", suffix=".json")
+        with os.fdopen(handle, "w", encoding="utf-8") as fp:
+            json.dump(claude_mcp_config(self._agent.mcp_servers), fp)
+        return Path(path)
+
+    def _build_command(
+        self,
+        session_id: str,
+        model: str | None,
+        mcp_config_path: Optional[Path] = None,
+        rendered: Optional[RenderedCommand] = None,
+    ) -> list[str]:
         # Claude's CLI refuses to reuse session IDs between concurrent runs, and
         # our stateless architecture already feeds prior history manually, so we
         # skip passing --session-id entirely.
-        command = list(self._agent.command)
+        command = list(rendered.args if rendered else self._agent.command)
 
         # Inject model flag if specified
-        if model:
+        if model and not (rendered and rendered.model_templated):
             cli_model = get_cli_model_name("claude", model)
             command.extend(["--model", cli_model])
 
+        if mcp_config_path:
+            # Non-interactive runs cannot prompt for MCP tool permissions, so allow the configured servers
+            allowed = ",".join(f"{TOOL_NAME_PREFIX}{server.name}" for server in self._agent.mcp_servers)
+            command.extend(["--mcp-config", str(mcp_config_path), "--allowedTools", allowed])
+
         return command
 
     def _resolve_workdir(self, project_path: str) -> Path:
@@ -234,3 +412,16 @@ class ClaudeAdapter(AgentAdapter):
                 if isinstance(message, str):
                     errors.append(message)
         return errors
+
+    def _extract_usage(self, payload: Dict[str, Any]) -> Dict[str, int]:
+        # The final `result` event of stream-json output carries the run's token usage
+        if payload.get("type") != "result":
+            return {}
+        usage = payload.get("usage")
+        if not isinstance(usage, dict):
+            return {}
+        input_tokens = sum(
+            int(usage.get(key) or 0)
+            for key in ("input_tokens", "cache_creation_input_tokens", "cache_read_input_tokens")
+        )
+        return {"input_tokens": input_tokens, "output_tokens": int(usage.get("output_tokens") or 0)}
@@ -7,11 +7,14 @@ import json
 import logging
 import os
 from pathlib import Path
-from typing import Any, Dict, Sequence
+from typing import Any, Dict, Optional, Sequence
 
 from ..core.model_mapping import get_cli_model_name
 from ..core.models import Agent, AgentType, WorkingDirMode
-from .base import AgentAdapter, AgentResult, FileEdit, parse_structured_output
+from .base import AgentAdapter, AgentResult, FileEdit, OutputFn, emit_output, parse_structured_output, spawn_cli
+from .command_template import RenderedCommand, render_command
+from .progress import ProgressFn, emit_progress, parse_codex_event
+from .mcp import codex_mcp_overrides
 
 LOGGER = logging.getLogger(__name__)
 
@@ -19,6 +22,10 @@ LOGGER = logging.getLogger(__name__)
 class CodexAdapter(AgentAdapter):
     """Executes Codex CLI commands in one-shot mode."""
 
+    supported_overrides = frozenset({"reasoning_effort"})
+    streams_output = True
+    reports_progress = True
+
     def __init__(self, agent: Agent) -> None:
         if agent.type != AgentType.CODEX:
             raise ValueError(f"CodexAdapter requires a CODEX agent, got {agent.type}")
@@ -32,109 +39,149 @@ class CodexAdapter(AgentAdapter):
         session_id: str,
         conversation_history: Sequence[Dict[str, Any]],
         model: str | None = None,
+        reasoning_effort: str | None = None,
+        on_output: Optional[OutputFn] = None,
+        on_progress: Optional[ProgressFn] = None,
+    ) -> AgentResult:
+        workdir = self._resolve_workdir(project_path)
+        cli_model = get_cli_model_name("codex", model) if model else None
+        rendered = render_command(
+            self._agent.command, task_text=task_text, workdir=workdir, model=cli_model, session_id=session_id
+        )
+        try:
+            return await self._run_cli(
+                rendered, task_text, workdir, cli_model, reasoning_effort, on_output, on_progress
+            )
+        finally:
+            rendered.cleanup()
+
+    async def _run_cli(
+        self,
+        rendered: RenderedCommand,
+        task_text: str,
+        workdir: Path,
+        cli_model: str | None,
+        reasoning_effort: str | None = None,
+        on_output: Optional[OutputFn] = None,
+        on_progress: Optional[ProgressFn] = None,
     ) -> AgentResult:
-        command = list(self._agent.command)
+        command = list(rendered.args)
 
         # Inject model flag if specified
-        if model:
-            cli_model = get_cli_model_name("codex", model)
+        if cli_model and not rendered.model_templated:
             command.extend(["-m", cli_model])
-        workdir = self._resolve_workdir(project_path)
+        if reasoning_effort:
+            command.extend(["-c", f"model_reasoning_effort={reasoning_effort}"])
+        command.extend(codex_mcp_overrides(self._agent.mcp_servers))
         env = {**os.environ, **self._agent.env}
 
         LOGGER.info("Running Codex one-shot command in %s", workdir)
-        process = await asyncio.create_subprocess_exec(
-            *command,
-            stdin=asyncio.subprocess.PIPE,
-            stdout=asyncio.subprocess.PIPE,
-            stderr=asyncio.subprocess.PIPE,
-            cwd=str(workdir),
+        async with spawn_cli(
+            command,
+            cwd=workdir,
             env=env,
-        )
-
-        assert process.stdin is not None
-        stdin_payload = (task_text + "\n").encode("utf-8")
-        process.stdin.write(stdin_payload)
-        await process.stdin.drain()
-        process.stdin.close()
-
-        raw_events: list[str] = []
-        text_chunks: list[str] = []
-        file_edits: list[FileEdit] = []
-        errors: list[str] = []
-
-        assert process.stderr is not None
-        stderr_task = asyncio.create_task(process.stderr.read())
-
-        # Read stdout in chunks to avoid readline() buffer limit issues
-        stdout_stream = process.stdout
-        if stdout_stream:
-            buffer = ""
-            chunk_size = 10 * 1024 * 1024  # 10MB chunks
-
-            while True:
-                chunk = await stdout_stream.read(chunk_size)
-                if not chunk:
-                    break
-
-                buffer += chunk.decode("utf-8", errors="replace")
-
-                # Process complete lines from buffer
-                while "\n" in buffer:
-                    line, buffer = buffer.split("\n", 1)
-                    decoded = line.strip()
+            terminal=self._agent.terminal_size,
+            confirm_prompts=self._agent.confirm_prompts,
+            limits=self._agent.limits,
+            sandbox=self._agent.sandbox,
+        ) as process:
+            assert process.stdin is not None
+            if not rendered.prompt_in_file:
+                stdin_payload = (task_text + "\n").encode("utf-8")
+                process.stdin.write(stdin_payload)
+                await process.stdin.drain()
+            if self._agent.confirm_prompts is None:
+                process.stdin.close()  # Otherwise kept open to answer prompts (the prompt is in a file)
+
+            raw_events: list[str] = []
+            text_chunks: list[str] = []
+            file_edits: list[FileEdit] = []
+            errors: list[str] = []
+            token_usage: Dict[str, int] = {}
+
+            assert process.stderr is not None
+            stderr_task = asyncio.create_task(process.stderr.read())
+
+            # Read stdout in chunks to avoid readline() buffer limit issues
+            stdout_stream = process.stdout
+            if stdout_stream:
+                buffer = ""
+                chunk_size = 10 * 1024 * 1024  # 10MB chunks
+
+                while True:
+                    chunk = await stdout_stream.read(chunk_size)
+                    if not chunk:
+                        break
+
+                    buffer += chunk.decode("utf-8", errors="replace")
+
+                    # Process complete lines from buffer
+                    while "\n" in buffer:
+                        line, buffer = buffer.split("\n", 1)
+                        decoded = line.strip()
+                        raw_events.append(decoded)
+
+                        if not decoded:
+                            continue
+
+                        parsed = self._parse_json(decoded)
+                        if parsed:
+                            LOGGER.debug(f"Parsed Codex JSON event: {parsed}")
+                            segments = self._extract_text_segments(parsed)
+                            text_chunks.extend(segments)
+                            emit_output(on_output, segments)
+                            emit_progress(on_progress, parse_codex_event(parsed))
+                            file_edits.extend(self._extract_file_edits(parsed))
+                            errors.extend(self._extract_errors(parsed))
+                            self._accumulate_usage(token_usage, parsed)
+                        else:
+                            text_chunks.append(decoded)
+                            emit_output(on_output, [decoded])
+
+                # Process any remaining data in buffer
+                if buffer.strip():
+                    decoded = buffer.strip()
                     raw_events.append(decoded)
-
-                    if not decoded:
-                        continue
-
                     parsed = self._parse_json(decoded)
                     if parsed:
                         LOGGER.debug(f"Parsed Codex JSON event: {parsed}")
-                        text_chunks.extend(self._extract_text_segments(parsed))
+                        segments = self._extract_text_segments(parsed)
+                        text_chunks.extend(segments)
+                        emit_output(on_output, segments)
+                        emit_progress(on_progress, parse_codex_event(parsed))
                         file_edits.extend(self._extract_file_edits(parsed))
                         errors.extend(self._extract_errors(parsed))
+                        self._accumulate_usage(token_usage, parsed)
                     else:
                         text_chunks.append(decoded)
-
-            # Process any remaining data in buffer
-            if buffer.strip():
-                decoded = buffer.strip()
-                raw_events.append(decoded)
-                parsed = self._parse_json(decoded)
-                if parsed:
-                    LOGGER.debug(f"Parsed Codex JSON event: {parsed}")
-                    text_chunks.extend(self._extract_text_segments(parsed))
-                    file_edits.extend(self._extract_file_edits(parsed))
-                    errors.extend(self._extract_errors(parsed))
-                else:
-                    text_chunks.append(decoded)
-
-        return_code = await process.wait()
-        stderr_raw = await stderr_task
-        stderr_output = stderr_raw.decode("utf-8", errors="replace").strip()
-
-        success = return_code == 0
-
-        # Only treat stderr as an error if the command actually failed
-        if not success and stderr_output:
-            errors.append(stderr_output)
-
-        output_text = "\n".join(chunk for chunk in text_chunks if chunk).strip()
-        if not output_text and raw_events:
-            output_text = "\n".join(raw_events).strip()
-        raw_output = "\n".join(raw_events + ([stderr_output] if stderr_output else []))
-        structured_output = parse_structured_output(output_text or raw_output)
-
-        return AgentResult(
-            success=success,
-            output_text=output_text,
-            file_edits=file_edits,
-            errors=[err for err in errors if err],
-            session_context={},
-            raw_output=raw_output,
-            structured_output=structured_output,
-        )
+                        emit_output(on_output, [decoded])
+
+            return_code = await process.wait()
+            stderr_raw = await stderr_task
+            stderr_output = stderr_raw.decode("utf-8", errors="replace").strip()
+
+            success = return_code == 0
+
+            # Only treat stderr as an error if the command actually failed
+            if not success and stderr_output:
+                errors.append(stderr_output)
+
+            output_text = "\n".join(chunk for chunk in text_chunks if chunk).strip()
+            if not output_text and raw_events:
+                output_text = "\n".join(raw_events).strip()
+            raw_output = "\n".join(raw_events + ([stderr_output] if stderr_output else []))
+            structured_output = parse_structured_output(output_text or raw_output)
+
+            return AgentResult(
+                success=success,
+                output_text=output_text,
+                file_edits=file_edits,
+                errors=[err for err in errors if err],
+                session_context={},
+                raw_output=raw_output,
+                structured_output=structured_output,
+                token_usage=token_usage,
+            )
 
     def _resolve_workdir(self, project_path: str) -> Path:
         if self._agent.working_dir_mode == WorkingDirMode.PROJECT:
@@ -229,3 +276,13 @@ class CodexAdapter(AgentAdapter):
                 if isinstance(message, str):
                     errors.append(message)
         return errors
+
+    def _accumulate_usage(self, totals: Dict[str, int], payload: Dict[str, Any]) -> None:
+        """Add token usage from `turn.completed` events to the running totals."""
+        if payload.get("type") != "turn.completed":
+            return
+        usage = payload.get("usage")
+        if not isinstance(usage, dict):
+            return
+        for key in ("input_tokens", "output_tokens"):
+            totals[key] = totals.get(key, 0) + int(usage.get(key) or 0)
@@ -0,0 +1,81 @@
+"""Placeholders in CLI agents' `command` lists, resolved when the command is spawned.
+
+    {{prompt_file}}  path of a temporary file holding the task text; the prompt
+                     is then no longer sent on stdin (or appended as gemini's
+                     trailing argument)
+    {{workdir}}      directory the agent runs in
+    {{model}}        CLI name of the selected model; replaces the model flag
+                     the adapter would otherwise add
+    {{session_id}}   Remote Coder session id
+
+Placeholders can be embedded in a larger argument, e.g. `--prompt-file={{prompt_file}}`.
+"""
+
+from __future__ import annotations
+
+import re
+import tempfile
+from dataclasses import dataclass
+from pathlib import Path
+from typing import Iterable, List, Optional, Sequence
+
+PLACEHOLDER = re.compile(r"\{\{\s*(\w+)\s*\}\}")
+TEMPLATE_VARIABLES = ("prompt_file", "workdir", "model", "session_id")
+
+
+def placeholders(command: Iterable[str]) -> set[str]:
+    """Names of all placeholders used in `command`."""
+    return {match.group(1) for arg in command for match in PLACEHOLDER.finditer(arg)}
+
+
+def unknown_placeholders(command: Iterable[str]) -> List[str]:
+    return sorted(placeholders(command) - set(TEMPLATE_VARIABLES))
+
+
+def uses_placeholder(command: Iterable[str], name: str) -> bool:
+    return name in placeholders(command)
+
+
+@dataclass
+class RenderedCommand:
+    """A command with its placeholders filled in, plus the prompt file it may reference."""
+
+    args: List[str]
+    prompt_file: Optional[Path] = None
+    model_templated: bool = False
+
+    @property
+    def prompt_in_file(self) -> bool:
+        return self.prompt_file is not None
+
+    def cleanup(self) -> None:
+        if self.prompt_file:
+            self.prompt_file.unlink(missing_ok=True)
+
+
+def render_command(
+    command: Sequence[str],
+    *,
+    task_text: str,
+    workdir: Path,
+    model: Optional[str],
+    session_id: str,
+) -> RenderedCommand:
+    """Fill in the placeholders of `command`; call `cleanup()` on the result once the process exits."""
+    used = placeholders(command)
+    prompt_file: Optional[Path] = None
+    if "prompt_file" in used:
+        with tempfile.NamedTemporaryFile(
+            "w", prefix="remote-coder-prompt-", suffix=".md", delete=False, encoding="utf-8"
+        ) as handle:
+            handle.write(task_text)
+        prompt_file = Path(handle.name)
+
+    values = {
+        "prompt_file": str(prompt_file or ""),
+        "workdir": str(workdir),
+        "model": model or "",
+        "session_id": session_id,
+    }
+    args = [PLACEHOLDER.sub(lambda match: values.get(match.group(1), match.group(0)), arg) for arg in command]
+    return RenderedCommand(args=args, prompt_file=prompt_file, model_templated="model" in used)
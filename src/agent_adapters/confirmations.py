@@ -0,0 +1,104 @@
+"""Answer the yes/no prompts of semi-interactive agent CLIs from the chat thread.
+
+Agents with `confirm_prompts` have their stdout watched while it is passed on
+to the adapter. When the last line the CLI printed matches one of the
+patterns (and nothing follows it, so the CLI is waiting), the prompt and the
+lines before it are handed to the run's `ConfirmFn`, which asks in the thread,
+and the configured answer is written to the CLI's stdin. Prompts are denied
+when nobody answers, or when there is nobody to ask (e.g. `!bench` runs).
+"""
+
+from __future__ import annotations
+
+import asyncio
+import codecs
+import logging
+import re
+from contextlib import contextmanager
+from contextvars import ContextVar
+from typing import TYPE_CHECKING, Awaitable, Callable, Iterator, Optional, Tuple
+
+if TYPE_CHECKING:
+    from ..core.models import ConfirmPrompts
+
+LOGGER = logging.getLogger(__name__)
+
+# Asks the user about a prompt; True approves, False or None (no answer) denies
+ConfirmFn = Callable[[str], Awaitable[Optional[bool]]]
+# Writes bytes to the CLI's stdin
+RespondFn = Callable[[bytes], Awaitable[None]]
+
+READ_CHUNK_BYTES = 64 * 1024
+# Output kept to show with a prompt: the prompt line and what the CLI printed just before it
+CONTEXT_LINES = 12
+CONTEXT_CHARS = 2500
+
+_CONFIRM: ContextVar[Optional[ConfirmFn]] = ContextVar("confirm", default=None)
+
+
+@contextmanager
+def answer_confirmations(ask: ConfirmFn) -> Iterator[None]:
+    """Let agent processes started in this context ask about their confirmation prompts with `ask`."""
+    token = _CONFIRM.set(ask)
+    try:
+        yield
+    finally:
+        _CONFIRM.reset(token)
+
+
+class PromptWatcher:
+    """Finds confirmation prompts in a CLI's output and answers them."""
+
+    def __init__(self, prompts: ConfirmPrompts, respond: RespondFn, ask: Optional[ConfirmFn] = None) -> None:
+        self._prompts = prompts
+        self._patterns = [re.compile(pattern, re.IGNORECASE) for pattern in prompts.patterns]
+        self._respond = respond
+        self._ask = ask if ask is not None else _CONFIRM.get()
+        self._decoder = codecs.getincrementaldecoder("utf-8")(errors="replace")
+        self._text = ""  # Output since the last answered prompt
+
+    async def feed(self, data: bytes) -> None:
+        self._text = (self._text + self._decoder.decode(data))[-CONTEXT_CHARS:]
+        lines = [line.rstrip("\r") for line in self._text.rstrip().split("\n")]
+        if not lines or not any(pattern.search(lines[-1]) for pattern in self._patterns):
+            return
+        question = "\n".join(lines[-CONTEXT_LINES:]).strip()
+        self._text = ""
+        approved = await self._decide(question)
+        answer = self._prompts.approve if approved else self._prompts.deny
+        LOGGER.info("Answering agent prompt %r with %r", question.splitlines()[-1], answer)
+        await self._respond(f"{answer}\n".encode("utf-8"))
+
+    async def _decide(self, question: str) -> bool:
+        if self._ask is None:
+            LOGGER.info("Nobody to ask about agent prompt %r; denying it", question.splitlines()[-1])
+            return False
+        try:
+            return bool(await self._ask(question))
+        except Exception:
+            LOGGER.exception("Failed to ask about agent prompt; denying it")
+            return False
+
+
+def watch_output(source: asyncio.StreamReader, watcher: PromptWatcher) -> Tuple[asyncio.StreamReader, asyncio.Task]:
+    """A reader with `source`'s output that `watcher` looks at on the way; the task copies it over."""
+    sink = asyncio.StreamReader(limit=2**26)
+
+    async def _pump() -> None:
+        try:
+            while True:
+                data = await source.read(READ_CHUNK_BYTES)
+                if not data:
+                    break
+                sink.feed_data(data)  # The adapter shows the prompt while it is being asked about
+                await watcher.feed(data)
+        except asyncio.CancelledError:
+            sink.feed_eof()
+            raise
+        except Exception as exc:
+            sink.set_exception(exc)
+            return
+        sink.feed_eof()
+
+    return sink, asyncio.create_task(_pump())
+
@@ -0,0 +1,151 @@
+"""Run agent CLIs inside a container instead of on the host.
+
+Projects with `sandbox: {mode: docker, image: ...}` run their agent's CLI
+through `docker run` (or podman): the working directory is mounted at the
+same path, so paths in the prompt and in the CLI's output stay valid, and
+nothing else of the host is visible except files named in the command (the
+prompt file, an MCP config), which are mounted read-only. The container runs
+as the daemon's user with HOME=/tmp and only the agent's `env` and the
+sandbox's `env_passthrough` variables. Output streams through the runtime's
+stdout and stderr like a local CLI's; SIGINT at a soft timeout is proxied to
+the container, and the container is removed when the run ends, however it
+ends. The CLI must be installed in the image. An agent's `limits` become the
+container's limits (`--memory`, `--cpus`, `--ulimit`), since rlimits set on
+the runtime client would not reach the CLI.
+"""
+
+from __future__ import annotations
+
+import asyncio
+import logging
+import os
+import uuid
+from contextlib import contextmanager
+from contextvars import ContextVar
+from dataclasses import dataclass
+from pathlib import Path
+from typing import TYPE_CHECKING, Iterator, List, Optional, Sequence, Tuple
+
+if TYPE_CHECKING:
+    from ..core.models import ProcessLimits, SandboxConfig
+
+LOGGER = logging.getLogger(__name__)
+
+CONTAINER_NAME_PREFIX = "remote-coder-agent-"
+CONTAINER_PIDS_LIMIT = 1024
+CONTAINER_HOME = "/tmp"
+# Set for every run: the terminal settings some CLIs read
+ALWAYS_PASSED_ENV = ("TERM", "COLUMNS", "LINES", "LANG", "TZ")
+
+
+@dataclass(frozen=True)
+class AgentContainer:
+    """The container agent processes of the current run are started in."""
+
+    sandbox: SandboxConfig
+    env_names: Tuple[str, ...] = ()  # Variables copied into the container, besides ALWAYS_PASSED_ENV
+
+
+_CONTAINER: ContextVar[Optional[AgentContainer]] = ContextVar("agent_container", default=None)
+
+
+@contextmanager
+def run_agents_in_container(container: AgentContainer) -> Iterator[None]:
+    """Start agent processes of this context in `container` (see `spawn_cli`)."""
+    token = _CONTAINER.set(container)
+    try:
+        yield
+    finally:
+        _CONTAINER.reset(token)
+
+
+def current_container() -> Optional[AgentContainer]:
+    return _CONTAINER.get()
+
+
+def container_name() -> str:
+    return f"{CONTAINER_NAME_PREFIX}{uuid.uuid4().hex[:12]}"
+
+
+def agent_container_argv(
+    container: AgentContainer,
+    command: Sequence[str],
+    cwd: Path,
+    name: str,
+    limits: Optional[ProcessLimits] = None,
+) -> List[str]:
+    """The `docker run` invocation that runs `command` in `cwd` inside the container."""
+    from ..core.privacy import PRIVACY
+
+    sandbox = container.sandbox
+    if not sandbox.image:
+        raise ValueError("Running agents in a container requires an image")
+    workdir = str(cwd.resolve())
+    argv = [
+        sandbox.runtime,
+        "run",
+        "--rm",
+        "--interactive",
+        "--name",
+        name,
+        "--volume",
+        f"{workdir}:{workdir}",
+        "--workdir",
+        workdir,
+        "--pids-limit",
+        str(CONTAINER_PIDS_LIMIT),
+        "--env",
+        f"HOME={CONTAINER_HOME}",
+    ]
+    if hasattr(os, "getuid"):
+        # Files the agent creates stay owned by the daemon user
+        argv.extend(["--user", f"{os.getuid()}:{os.getgid()}"])
+    if not sandbox.network:
+        argv.extend(["--network", "none"])
+    memory_mb = sandbox.memory_mb or (limits.memory_mb if limits else None)
+    if memory_mb:
+        argv.extend(["--memory", f"{memory_mb}m"])
+    if limits and limits.cpu_percent:
+        argv.extend(["--cpus", f"{limits.cpu_percent / 100:g}"])
+    if limits and limits.cpu_secs:
+        argv.extend(["--ulimit", f"cpu={limits.cpu_secs}"])
+    if limits and limits.open_files:
+        argv.extend(["--ulimit", f"nofile={limits.open_files}"])
+    for path in _command_files(command, workdir):
+        argv.extend(["--volume", f"{path}:{path}:ro"])
+    for var in dict.fromkeys((*ALWAYS_PASSED_ENV, *container.env_names)):
+        # `--env NAME` copies the value from the runtime's environment without putting it in argv
+        argv.extend(["--env", var])
+    for var, value in {**PRIVACY.cli_env({}), **sandbox.env}.items():
+        argv.extend(["--env", f"{var}={value}"])
+    argv.extend([sandbox.image, *command])
+    return argv
+
+
+async def remove_container(runtime: str, name: str) -> None:
+    """Force-remove a container; it is usually gone already (`--rm`) unless the runtime client was killed."""
+    try:
+        process = await asyncio.create_subprocess_exec(
+            runtime,
+            "rm",
+            "--force",
+            name,
+            stdout=asyncio.subprocess.DEVNULL,
+            stderr=asyncio.subprocess.DEVNULL,
+        )
+        await process.wait()
+    except OSError as exc:
+        LOGGER.warning("Failed to remove agent container %s: %s", name, exc)
+
+
+def _command_files(command: Sequence[str], workdir: str) -> List[str]:
+    """Absolute paths of existing files in `command` outside `workdir`, e.g. the prompt file."""
+    files = []
+    for arg in command:
+        _, _, value = arg.rpartition("=") if arg.startswith("--") else ("", "", arg)
+        if not os.path.isabs(value) or not os.path.isfile(value):
+            continue
+        path = os.path.realpath(value)
+        if not path.startswith(workdir + os.sep):
+            files.append(path)
+    return list(dict.fromkeys(files))
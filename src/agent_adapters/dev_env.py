@@ -0,0 +1,138 @@
+"""Run agent CLIs and project steps inside a project's development environment.
+
+Projects with `env:` start their agent's CLI, the shell tool of API agents,
+and their setup, verify, teardown, format, and hook steps with the toolchain
+the repository pins:
+
+- `env: nix` runs them through `nix develop <flake> --command ...`. The flake
+  is the one in the working directory unless `flake:` names another (e.g.
+  `.#ci` for a `devShells.<system>.ci`). Flakes only see files git tracks,
+  and the first run of a changed flake builds the shell, which counts against
+  the run's or step's timeout.
+- `env: mise` runs them through `mise exec`, which activates the runtimes of
+  the repository's `.mise.toml` or `.tool-versions`. mise refuses a config
+  nobody trusted (`mise trust`) unless the project sets `trust: true`, which
+  also installs missing runtimes without asking (again within the timeout).
+- `env: asdf` puts asdf's shims first on PATH, so `node`, `python`, etc.
+  resolve to the versions in `.tool-versions`; those must be installed with
+  `asdf install` beforehand.
+
+The environment is entered on the host, around `bwrap` when the command is
+sandboxed. `nix develop` evaluates the repository's flake and runs its
+`shellHook` as it starts, and `mise exec` runs the hooks of the repository's
+config, which would happen outside the sandbox, so neither can be combined
+with `sandbox: bubblewrap`, neither the project's (a config error) nor an
+agent's (the run fails to start; see `check_dev_env_sandbox`). asdf only puts
+its shims on PATH, so they run inside bwrap; its installs under the home
+directory must be listed in `read_only_paths`. Container sandboxes bring their own toolchain and cannot be
+combined with any of them.
+"""
+from __future__ import annotations
+
+import os
+import shutil
+from contextlib import contextmanager
+from contextvars import ContextVar
+from typing import TYPE_CHECKING, Iterator, List, Optional, Sequence
+
+if TYPE_CHECKING:
+    from ..core.models import DevEnvironment, SandboxConfig
+
+NIX = "nix"
+MISE = "mise"
+ASDF = "asdf"
+# Flakes may not be enabled in the host's nix.conf; a checkout with uncommitted changes is the usual case here
+NIX_DEVELOP_FLAGS = ("--extra-experimental-features", "nix-command flakes", "--no-warn-dirty")
+
+_DEV_ENV: ContextVar[Optional[DevEnvironment]] = ContextVar("dev_env", default=None)
+
+
+# Files `mise exec` reads the tool versions from; asdf only reads `.tool-versions`
+MISE_CONFIG_FILES = (".mise.toml", "mise.toml", ".config/mise.toml", ".tool-versions")
+TOOL_VERSIONS_FILE = ".tool-versions"
+
+
+class DevEnvUnavailable(RuntimeError):
+    """The tool behind a project's `env` is not installed, so the project cannot run its commands."""
+
+
+@contextmanager
+def run_in_dev_env(dev_env: Optional[DevEnvironment]) -> Iterator[None]:
+    """Start agent processes and shell tools of this context in `dev_env` (see `spawn_cli`)."""
+    token = _DEV_ENV.set(dev_env)
+    try:
+        yield
+    finally:
+        _DEV_ENV.reset(token)
+
+
+def current_dev_env() -> Optional[DevEnvironment]:
+    return _DEV_ENV.get()
+
+
+def asdf_shims_dir() -> str:
+    return os.path.join(os.environ.get("ASDF_DATA_DIR") or os.path.expanduser("~/.asdf"), "shims")
+
+
+def dev_env_tool(dev_env: DevEnvironment) -> str:
+    """The executable that enters `dev_env`, or for asdf the shims directory."""
+    from ..core.models import DevEnvKind
+
+    if dev_env.kind == DevEnvKind.MISE:
+        return MISE
+    if dev_env.kind == DevEnvKind.ASDF:
+        return asdf_shims_dir()
+    return NIX
+
+
+def dev_env_available(dev_env: DevEnvironment) -> bool:
+    from ..core.models import DevEnvKind
+
+    if dev_env.kind == DevEnvKind.ASDF:
+        return os.path.isdir(asdf_shims_dir())
+    return shutil.which(dev_env_tool(dev_env)) is not None
+
+
+def dev_env_sandbox_problem(dev_env: DevEnvironment, sandbox: Optional[SandboxConfig]) -> Optional[str]:
+    """Why `dev_env` cannot be entered for a command run in `sandbox`, or None."""
+    from ..core.models import DevEnvKind, SandboxMode
+
+    if sandbox is None or sandbox.mode != SandboxMode.BUBBLEWRAP or dev_env.kind == DevEnvKind.ASDF:
+        return None
+    runs = (
+        "`nix develop` would evaluate the repository's flake and run its shellHook"
+        if dev_env.kind == DevEnvKind.NIX
+        else "`mise exec` would run the hooks of the repository's mise config"
+    )
+    return f"`env: {dev_env.kind.value}` cannot be combined with a bubblewrap sandbox: {runs} outside it, on the host"
+
+
+def check_dev_env_sandbox(dev_env: Optional[DevEnvironment], sandbox: Optional[SandboxConfig]) -> None:
+    """Raise DevEnvUnavailable before anything starts if `dev_env` would run unsandboxed around `sandbox`."""
+    problem = dev_env_sandbox_problem(dev_env, sandbox) if dev_env is not None else None
+    if problem:
+        raise DevEnvUnavailable(problem)
+
+
+def dev_env_command(command: Sequence[str], dev_env: Optional[DevEnvironment]) -> List[str]:
+    """`command` started in `dev_env`, or unchanged without one."""
+    from ..core.models import DevEnvKind
+
+    if dev_env is None:
+        return list(command)
+    if dev_env.kind == DevEnvKind.ASDF:
+        shims = asdf_shims_dir()
+        if not os.path.isdir(shims):
+            raise DevEnvUnavailable(f"The project's `env: asdf` needs asdf's shims in `{shims}`; install asdf")
+        path = os.environ.get("PATH", os.defpath)
+        return ["env", f"PATH={shims}{os.pathsep}{path}", *command]
+    tool = shutil.which(dev_env_tool(dev_env))
+    if tool is None:
+        name = "Nix" if dev_env.kind == DevEnvKind.NIX else "mise"
+        raise DevEnvUnavailable(
+            f"The project's `env: {dev_env.kind.value}` needs `{dev_env_tool(dev_env)}` on PATH; install {name}"
+        )
+    if dev_env.kind == DevEnvKind.MISE:
+        # --yes trusts the repository's config and installs missing tools without prompting
+        return [tool, *(["--yes"] if dev_env.trust else []), "exec", "--", *command]
+    return [tool, "develop", dev_env.flake or ".", *NIX_DEVELOP_FLAGS, "--command", *command]
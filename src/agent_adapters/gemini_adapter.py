@@ -7,11 +7,13 @@ import json
 import logging
 import os
 from pathlib import Path
-from typing import Any, Dict, Sequence
+from typing import Any, Dict, Optional, Sequence
 
 from ..core.model_mapping import get_cli_model_name
 from ..core.models import Agent, AgentType, WorkingDirMode
-from .base import AgentAdapter, AgentResult, FileEdit, parse_structured_output
+from .base import AgentAdapter, AgentResult, FileEdit, OutputFn, emit_output, parse_structured_output, spawn_cli
+from .command_template import RenderedCommand, render_command
+from .progress import ProgressFn, emit_progress, parse_gemini_event
 
 LOGGER = logging.getLogger(__name__)
 
@@ -19,6 +21,9 @@ LOGGER = logging.getLogger(__name__)
 class GeminiAdapter(AgentAdapter):
     """Executes Gemini CLI commands in one-shot mode."""
 
+    streams_output = True
+    reports_progress = True
+
     def __init__(self, agent: Agent) -> None:
         if agent.type != AgentType.GEMINI:
             raise ValueError(f"GeminiAdapter requires a GEMINI agent, got {agent.type}")
@@ -32,61 +37,119 @@ class GeminiAdapter(AgentAdapter):
         session_id: str,
         conversation_history: Sequence[Dict[str, Any]],
         model: str | None = None,
+        on_output: Optional[OutputFn] = None,
+        on_progress: Optional[ProgressFn] = None,
+    ) -> AgentResult:
+        workdir = self._resolve_workdir(project_path)
+        # Skip the model for "auto" to let CLI auto-select
+        cli_model = get_cli_model_name("gemini", model) if model and model != "auto" else None
+        rendered = render_command(
+            self._agent.command, task_text=task_text, workdir=workdir, model=cli_model, session_id=session_id
+        )
+        try:
+            return await self._run_cli(rendered, task_text, workdir, cli_model, on_output, on_progress)
+        finally:
+            rendered.cleanup()
+
+    async def _run_cli(
+        self,
+        rendered: RenderedCommand,
+        task_text: str,
+        workdir: Path,
+        cli_model: str | None,
+        on_output: Optional[OutputFn] = None,
+        on_progress: Optional[ProgressFn] = None,
     ) -> AgentResult:
         # Gemini CLI takes the prompt as a positional argument for one-shot mode
-        command = list(self._agent.command)
+        command = list(rendered.args)
 
         # Inject model flag if specified (before the prompt)
-        # Skip -m flag for "auto" to let CLI auto-select
-        if model and model != "auto":
-            cli_model = get_cli_model_name("gemini", model)
+        if cli_model and not rendered.model_templated:
             command.extend(["-m", cli_model])
 
-        command.append(task_text)
+        if not rendered.prompt_in_file:
+            command.append(task_text)
 
-        workdir = self._resolve_workdir(project_path)
         env = {**os.environ, **self._agent.env}
 
-        process = await asyncio.create_subprocess_exec(
-            *command,
-            stdout=asyncio.subprocess.PIPE,
-            stderr=asyncio.subprocess.PIPE,
-            cwd=str(workdir),
+        async with spawn_cli(
+            command,
+            cwd=workdir,
             env=env,
-        )
-
-        raw_events: list[str] = []
-        text_chunks: list[str] = []
-        file_edits: list[FileEdit] = []
-        errors: list[str] = []
-        streaming_buffer = ""  # Buffer for delta messages
-
-        assert process.stderr is not None
-        stderr_task = asyncio.create_task(process.stderr.read())
-
-        # Read stdout in chunks to avoid readline() buffer limit issues
-        stdout_stream = process.stdout
-        if stdout_stream:
-            buffer = ""
-            chunk_size = 10 * 1024 * 1024  # 10MB chunks
-
-            while True:
-                chunk = await stdout_stream.read(chunk_size)
-                if not chunk:
-                    break
-
-                buffer += chunk.decode("utf-8", errors="replace")
+            stdin=False,
+            terminal=self._agent.terminal_size,
+            confirm_prompts=self._agent.confirm_prompts,
+            limits=self._agent.limits,
+            sandbox=self._agent.sandbox,
+        ) as process:
+            raw_events: list[str] = []
+            text_chunks: list[str] = []
+            file_edits: list[FileEdit] = []
+            errors: list[str] = []
+            streaming_buffer = ""  # Buffer for delta messages
+
+            assert process.stderr is not None
+            stderr_task = asyncio.create_task(process.stderr.read())
+
+            # Read stdout in chunks to avoid readline() buffer limit issues
+            stdout_stream = process.stdout
+            if stdout_stream:
+                buffer = ""
+                chunk_size = 10 * 1024 * 1024  # 10MB chunks
+
+                while True:
+                    chunk = await stdout_stream.read(chunk_size)
+                    if not chunk:
+                        break
+
+                    buffer += chunk.decode("utf-8", errors="replace")
+
+                    # Process complete lines from buffer
+                    while "\n" in buffer:
+                        line, buffer = buffer.split("\n", 1)
+                        decoded = line.strip()
+                        raw_events.append(decoded)
+
+                        if not decoded:
+                            continue
+
+                        # Try to parse as JSON
+                        parsed = self._parse_json(decoded)
+                        if parsed:
+                            # Handle streaming delta messages
+                            if parsed.get("type") == "message" and parsed.get("role") == "assistant":
+                                content = parsed.get("content", "")
+                                is_delta = parsed.get("delta", False)
+
+                                if is_delta:
+                                    # Accumulate delta chunks
+                                    streaming_buffer += content
+                                    if on_output and content:
+                                        on_output(content)
+                                else:
+                                    # Complete message (not a delta)
+                                    if streaming_buffer:
+                                        # Flush any buffered delta content first
+                                        text_chunks.append(streaming_buffer)
+                                        streaming_buffer = ""
+                                        if on_output:
+                                            on_output("\n")  # End the line the deltas streamed
+                                    if content:
+                                        text_chunks.append(content)
+                                        emit_output(on_output, [content])
+
+                            emit_progress(on_progress, parse_gemini_event(parsed))
+                            file_edits.extend(self._extract_file_edits(parsed))
+                            errors.extend(self._extract_errors(parsed))
+                        else:
+                            # If not JSON, treat as plain text output
+                            text_chunks.append(decoded)
+                            emit_output(on_output, [decoded])
 
-                # Process complete lines from buffer
-                while "\n" in buffer:
-                    line, buffer = buffer.split("\n", 1)
-                    decoded = line.strip()
+                # Process any remaining data in buffer
+                if buffer.strip():
+                    decoded = buffer.strip()
                     raw_events.append(decoded)
-
-                    if not decoded:
-                        continue
-
-                    # Try to parse as JSON
                     parsed = self._parse_json(decoded)
                     if parsed:
                         # Handle streaming delta messages
@@ -95,79 +158,57 @@ class GeminiAdapter(AgentAdapter):
                             is_delta = parsed.get("delta", False)
 
                             if is_delta:
-                                # Accumulate delta chunks
                                 streaming_buffer += content
+                                if on_output and content:
+                                    on_output(content)
                             else:
-                                # Complete message (not a delta)
                                 if streaming_buffer:
-                                    # Flush any buffered delta content first
                                     text_chunks.append(streaming_buffer)
                                     streaming_buffer = ""
+                                    if on_output:
+                                        on_output("\n")
                                 if content:
                                     text_chunks.append(content)
+                                    emit_output(on_output, [content])
 
+                        emit_progress(on_progress, parse_gemini_event(parsed))
                         file_edits.extend(self._extract_file_edits(parsed))
                         errors.extend(self._extract_errors(parsed))
                     else:
-                        # If not JSON, treat as plain text output
                         text_chunks.append(decoded)
-
-            # Process any remaining data in buffer
-            if buffer.strip():
-                decoded = buffer.strip()
-                raw_events.append(decoded)
-                parsed = self._parse_json(decoded)
-                if parsed:
-                    # Handle streaming delta messages
-                    if parsed.get("type") == "message" and parsed.get("role") == "assistant":
-                        content = parsed.get("content", "")
-                        is_delta = parsed.get("delta", False)
-
-                        if is_delta:
-                            streaming_buffer += content
-                        else:
-                            if streaming_buffer:
-                                text_chunks.append(streaming_buffer)
-                                streaming_buffer = ""
-                            if content:
-                                text_chunks.append(content)
-
-                    file_edits.extend(self._extract_file_edits(parsed))
-                    errors.extend(self._extract_errors(parsed))
-                else:
-                    text_chunks.append(decoded)
-
-        # Flush any remaining buffered content
-        if streaming_buffer:
-            text_chunks.append(streaming_buffer)
-
-        return_code = await process.wait()
-        stderr_raw = await stderr_task
-        stderr_output = stderr_raw.decode("utf-8", errors="replace").strip()
-
-        # Filter out informational messages that aren't actual errors
-        if stderr_output and not self._is_informational_stderr(stderr_output):
-            errors.append(stderr_output)
-
-        success = return_code == 0
-
-        # Combine all text chunks (already properly assembled from deltas)
-        # Each chunk is a complete message, so join with newlines
-        output_text = "\n".join(chunk for chunk in text_chunks if chunk).strip()
-        if not output_text and raw_events:
-            output_text = "\n".join(raw_events).strip()
-        raw_output = "\n".join(raw_events + ([stderr_output] if stderr_output else []))
-        structured_output = parse_structured_output(output_text or raw_output)
-
-        return AgentResult(
-            success=success,
-            output_text=output_text,
-            file_edits=file_edits,
-            errors=[err for err in errors if err],
-            session_context={},
-            raw_output=raw_output,
-            structured_output=structured_output,
-        )
+                        emit_output(on_output, [decoded])
+
+            # Flush any remaining buffered content
+            if streaming_buffer:
+                text_chunks.append(streaming_buffer)
+
+            return_code = await process.wait()
+            stderr_raw = await stderr_task
+            stderr_output = stderr_raw.decode("utf-8", errors="replace").strip()
+
+            # Filter out informational messages that aren't actual errors
+            if stderr_output and not self._is_informational_stderr(stderr_output):
+                errors.append(stderr_output)
+
+            success = return_code == 0
+
+            # Combine all text chunks (already properly assembled from deltas)
+            # Each chunk is a complete message, so join with newlines
+            output_text = "\n".join(chunk for chunk in text_chunks if chunk).strip()
+            if not output_text and raw_events:
+                output_text = "\n".join(raw_events).strip()
+            raw_output = "\n".join(raw_events + ([stderr_output] if stderr_output else []))
+            structured_output = parse_structured_output(output_text or raw_output)
+
+            return AgentResult(
+                success=success,
+                output_text=output_text,
+                file_edits=file_edits,
+                errors=[err for err in errors if err],
+                session_context={},
+                raw_output=raw_output,
+                structured_output=structured_output,
+            )
 
     def _resolve_workdir(self, project_path: str) -> Path:
         if self._agent.working_dir_mode == WorkingDirMode.PROJECT:
@@ -0,0 +1,183 @@
+"""Adapter for self-hosted agent services reachable over HTTP.
+
+The service receives the task and conversation context as JSON and answers
+with a unified diff plus a summary; Remote Coder applies the diff to the
+project itself, so the service never needs access to the working tree.
+
+Request body::
+
+    {"session_id": "...", "model": "..." | null, "prompt": "...",
+     "conversation_history": [{"role": "...", "content": "..."}],
+     "project": {"name": "...", "head": "<commit sha>" | null}}
+
+Response body::
+
+    {"summary": "...", "patch": "<unified diff>" | null,
+     "pr_title": "..." (optional), "pr_summary": ["..."] (optional),
+     "success": true (optional, defaults to true),
+     "errors": ["..."] (optional)}
+"""
+
+from __future__ import annotations
+
+import asyncio
+import json
+import logging
+import os
+import re
+import subprocess
+from pathlib import Path
+from typing import Any, Dict, List, Optional, Sequence
+
+import aiohttp
+
+from ..core.models import Agent, AgentType, WorkingDirMode
+from .base import AgentAdapter, AgentResult, FileEdit, StructuredOutput
+from .tool_loop import post_json
+
+LOGGER = logging.getLogger(__name__)
+
+DEFAULT_REQUEST_TIMEOUT_SECS = 600
+DIFF_TARGET = re.compile(r"^\+\+\+ (?:b/)?(.+)$", re.MULTILINE)
+DIFF_SOURCE = re.compile(r"^--- (?:a/)?(.+)$", re.MULTILINE)
+
+
+def file_edits_from_patch(patch: str) -> List[FileEdit]:
+    """Derive FileEdit entries from the file headers of a unified diff."""
+    edits: List[FileEdit] = []
+    sources = DIFF_SOURCE.findall(patch)
+    targets = DIFF_TARGET.findall(patch)
+    for source, target in zip(sources, targets):
+        source, target = source.strip(), target.strip()
+        if target == "/dev/null":
+            edits.append(FileEdit(path=source, type="delete"))
+        elif source == "/dev/null":
+            edits.append(FileEdit(path=target, type="create"))
+        else:
+            edits.append(FileEdit(path=target, type="edit"))
+    return edits
+
+
+async def result_from_patch_response(data: Dict[str, Any], workdir: Path, *, source: str) -> AgentResult:
+    """Apply the `patch` of a summary+patch response (see module docstring) and build the AgentResult."""
+    summary = str(data.get("summary") or "").strip()
+    patch = data.get("patch") or ""
+    errors: List[str] = [str(error) for error in data.get("errors") or [] if error]
+    file_edits: List[FileEdit] = []
+    if patch:
+        if not isinstance(patch, str):
+            errors.append(f"The {source} returned a non-string patch")
+        else:
+            patch = patch if patch.endswith("\n") else f"{patch}\n"
+            apply_error = await apply_patch(workdir, patch)
+            if apply_error:
+                errors.append(f"Failed to apply patch from the {source}: {apply_error}")
+            else:
+                file_edits = file_edits_from_patch(patch)
+
+    structured = None
+    if data.get("pr_title"):
+        pr_summary = data.get("pr_summary") or []
+        structured = StructuredOutput(
+            slack_message=summary,
+            pr_title=str(data["pr_title"]),
+            pr_summary=[str(item) for item in pr_summary] if isinstance(pr_summary, list) else [],
+        )
+
+    return AgentResult(
+        success=bool(data.get("success", True)) and not errors,
+        output_text=summary,
+        file_edits=file_edits,
+        errors=errors,
+        session_context={},
+        raw_output=json.dumps(data),
+        structured_output=structured,
+    )
+
+
+async def head_commit(workdir: Path) -> Optional[str]:
+    result = await _run_git(workdir, ["rev-parse", "HEAD"])
+    return result.stdout.strip() if result.returncode == 0 else None
+
+
+async def apply_patch(workdir: Path, patch: str) -> Optional[str]:
+    """Apply a unified diff to the working tree; returns git's error output on failure."""
+    result = await _run_git(workdir, ["apply", "-"], stdin=patch)
+    if result.returncode != 0:
+        return (result.stderr or result.stdout).strip() or "git apply failed"
+    return None
+
+
+async def _run_git(cwd: Path, args: list[str], stdin: Optional[str] = None) -> subprocess.CompletedProcess:
+    def _execute() -> subprocess.CompletedProcess:
+        return subprocess.run(
+            ["git", *args],
+            cwd=str(cwd),
+            input=stdin,
+            capture_output=True,
+            text=True,
+            check=False,
+        )
+
+    return await asyncio.to_thread(_execute)
+
+
+class HttpAgentAdapter(AgentAdapter):
+    """Sends tasks to an HTTP agent service and applies the patch it returns."""
+
+    def __init__(self, agent: Agent) -> None:
+        if agent.type != AgentType.HTTP:
+            raise ValueError(f"HttpAgentAdapter requires an HTTP agent, got {agent.type}")
+        if not agent.endpoint:
+            raise ValueError(f"HTTP agent {agent.id} has no endpoint configured")
+        self._agent = agent
+
+    async def run(
+        self,
+        *,
+        task_text: str,
+        project_path: str,
+        session_id: str,
+        conversation_history: Sequence[Dict[str, Any]],
+        model: str | None = None,
+    ) -> AgentResult:
+        workdir = self._resolve_workdir(project_path)
+        headers = {"Content-Type": "application/json"}
+        if self._agent.api_key_env:
+            api_key = self._agent.env.get(self._agent.api_key_env) or os.getenv(self._agent.api_key_env)
+            if not api_key:
+                return AgentResult(
+                    success=False,
+                    output_text="",
+                    errors=[f"{self._agent.api_key_env} is not set; cannot call agent service."],
+                )
+            headers["Authorization"] = f"Bearer {api_key}"
+
+        payload = {
+            "session_id": session_id,
+            "model": model or self._agent.models.get("default"),
+            "prompt": task_text,
+            "conversation_history": list(conversation_history),
+            "project": {"name": workdir.name, "head": await head_commit(workdir)},
+        }
+        timeout = aiohttp.ClientTimeout(
+            total=float(self._agent.options.get("request_timeout_secs", DEFAULT_REQUEST_TIMEOUT_SECS))
+        )
+
+        LOGGER.info("Posting task for session %s to HTTP agent %s", session_id, self._agent.endpoint)
+        try:
+            async with aiohttp.ClientSession(timeout=timeout) as http:
+                data = await post_json(http, str(self._agent.endpoint), payload, headers)
+        except (aiohttp.ClientError, asyncio.TimeoutError, ValueError) as exc:
+            return AgentResult(success=False, output_text="", errors=[f"Agent service request failed: {exc}"])
+
+        if not isinstance(data, dict):
+            return AgentResult(success=False, output_text="", errors=["Agent service returned a non-object response"])
+        return await result_from_patch_response(data, workdir, source="agent service")
+
+    def _resolve_workdir(self, project_path: str) -> Path:
+        if self._agent.working_dir_mode == WorkingDirMode.PROJECT:
+            return Path(project_path)
+        if self._agent.fixed_path:
+            return self._agent.fixed_path
+        raise ValueError("Fixed working directory required for HTTP agent adapter")
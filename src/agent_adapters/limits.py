@@ -0,0 +1,95 @@
+"""Resource limits for agent CLI processes.
+
+Agents with `limits` run with rlimits set in the child before it starts:
+`cpu_secs` (RLIMIT_CPU, CPU time; the kernel sends SIGXCPU past it),
+`open_files` (RLIMIT_NOFILE), and `memory_mb` (RLIMIT_AS, address space).
+Rlimits apply to each process separately, and subprocesses inherit them, so
+a build an agent starts gets its own budget. With `cgroup: true` the CLI is
+started through `systemd-run --user --scope` instead, which puts the whole
+process tree in a transient cgroup: `memory_mb` becomes its MemoryMax (the
+kernel kills the tree past it) and `cpu_percent` its CPUQuota (100 is one
+core). MemoryMax suits Node-based CLIs better than RLIMIT_AS, which counts
+address space they reserve but never use. Without systemd-run on PATH the
+cgroup is skipped with a warning and the rlimits still apply. Windows has
+neither, so there `limits` are ignored with a warning.
+"""
+
+from __future__ import annotations
+
+import logging
+import shutil
+import signal
+from typing import TYPE_CHECKING, Callable, List, Optional, Sequence
+
+from .windows import IS_WINDOWS
+
+if TYPE_CHECKING:
+    from ..core.models import ProcessLimits
+
+LOGGER = logging.getLogger(__name__)
+
+SYSTEMD_RUN = "systemd-run"
+
+
+def uses_cgroup(limits: Optional[ProcessLimits]) -> bool:
+    """Whether `limits` asks for a cgroup and one can be created here."""
+    if limits is None or not limits.cgroup:
+        return False
+    if shutil.which(SYSTEMD_RUN) is None:
+        LOGGER.warning("%s is not on PATH; running the agent without a cgroup (rlimits still apply)", SYSTEMD_RUN)
+        return False
+    return True
+
+
+def cgroup_command(command: Sequence[str], limits: ProcessLimits) -> List[str]:
+    """`command` started in a transient systemd scope with the cgroup limits of `limits`.
+
+    The scope runs the command in place of systemd-run, so the process keeps its pid and signals reach it.
+    """
+    properties: List[str] = []
+    if limits.memory_mb:
+        properties.extend(["--property", f"MemoryMax={limits.memory_mb}M", "--property", "MemorySwapMax=0"])
+    if limits.cpu_percent:
+        properties.extend(["--property", f"CPUQuota={limits.cpu_percent}%"])
+    return [SYSTEMD_RUN, "--user", "--scope", "--quiet", "--collect", *properties, "--", *command]
+
+
+def rlimit_setter(limits: Optional[ProcessLimits], *, cgroup: bool = False) -> Optional[Callable[[], None]]:
+    """A `preexec_fn` setting the rlimits of `limits`; memory is left to the cgroup when there is one."""
+    if limits is None:
+        return None
+    if IS_WINDOWS:
+        LOGGER.warning("Process limits are not supported on Windows; running the agent without them")
+        return None
+    import resource
+
+    wanted = []
+    if limits.cpu_secs:
+        wanted.append((resource.RLIMIT_CPU, limits.cpu_secs))
+    if limits.open_files:
+        wanted.append((resource.RLIMIT_NOFILE, limits.open_files))
+    if limits.memory_mb and not cgroup:
+        wanted.append((resource.RLIMIT_AS, limits.memory_mb * 1024 * 1024))
+    if not wanted:
+        return None
+
+    def _apply() -> None:
+        for kind, value in wanted:
+            _, hard = resource.getrlimit(kind)
+            # An unprivileged process cannot raise its hard limit, only stay below it
+            if hard != resource.RLIM_INFINITY:
+                value = min(value, hard)
+            resource.setrlimit(kind, (value, value))
+
+    return _apply
+
+
+def describe_limit_exit(returncode: Optional[int], limits: Optional[ProcessLimits]) -> Optional[str]:
+    """Which limit most likely ended a process that died with `returncode`, if any."""
+    if limits is None or returncode is None or returncode >= 0:
+        return None
+    if returncode == -signal.SIGXCPU and limits.cpu_secs:
+        return f"its cpu_secs limit ({limits.cpu_secs}s of CPU time)"
+    if returncode == -signal.SIGKILL and limits.cgroup and limits.memory_mb:
+        return f"its memory_mb limit ({limits.memory_mb} MB), if it was not killed for another reason"
+    return None
@@ -0,0 +1,221 @@
+"""MCP (Model Context Protocol) server wiring for agent runs.
+
+CLI agents that speak MCP (Claude Code, Codex) launch the servers themselves;
+we only translate the `mcp_servers` section of agents.yaml into their config
+flags. API-backed agents run the tool loop inside Remote Coder, so for them
+`McpToolset` starts the stdio servers for the duration of a run, exposes their
+tools to the model, and shuts them down afterwards.
+"""
+
+from __future__ import annotations
+
+import asyncio
+import json
+import logging
+import os
+from pathlib import Path
+from typing import Any, Dict, List, Optional, Sequence
+
+from ..core.models import McpServer
+
+LOGGER = logging.getLogger(__name__)
+
+MCP_PROTOCOL_VERSION = "2024-11-05"
+MCP_REQUEST_TIMEOUT_SECS = 60
+MCP_SHUTDOWN_TIMEOUT_SECS = 5
+# Tool names exposed to the model look like mcp__<server>__<tool>, matching Claude Code
+TOOL_NAME_PREFIX = "mcp__"
+MAX_MCP_OUTPUT_CHARS = 20_000
+STREAM_LIMIT_BYTES = 10 * 1024 * 1024
+
+
+class McpError(Exception):
+    """Raised when an MCP server fails to start or answers with an error."""
+
+
+def claude_mcp_config(servers: Sequence[McpServer]) -> Dict[str, Any]:
+    """Build the JSON document passed to `claude --mcp-config`."""
+    entries: Dict[str, Any] = {}
+    for server in servers:
+        if server.url:
+            entries[server.name] = {"type": "http", "url": server.url}
+        else:
+            entries[server.name] = {
+                "command": server.command[0],
+                "args": list(server.command[1:]),
+                "env": dict(server.env),
+            }
+    return {"mcpServers": entries}
+
+
+def codex_mcp_overrides(servers: Sequence[McpServer]) -> List[str]:
+    """Build `-c mcp_servers.<name>.*=...` overrides for the Codex CLI (values are TOML)."""
+    args: List[str] = []
+    for server in servers:
+        key = f"mcp_servers.{server.name}"
+        if server.url:
+            args.extend(["-c", f"{key}.url={json.dumps(server.url)}"])
+            continue
+        args.extend(["-c", f"{key}.command={json.dumps(server.command[0])}"])
+        args.extend(["-c", f"{key}.args={json.dumps(list(server.command[1:]))}"])
+        if server.env:
+            env_table = ", ".join(f"{json.dumps(k)} = {json.dumps(v)}" for k, v in server.env.items())
+            args.extend(["-c", f"{key}.env={{{env_table}}}"])
+    return args
+
+
+class McpStdioClient:
+    """Minimal MCP client speaking newline-delimited JSON-RPC over a server's stdio."""
+
+    def __init__(self, server: McpServer, cwd: Path) -> None:
+        self.server = server
+        self._cwd = cwd
+        self._process: Optional[asyncio.subprocess.Process] = None
+        self._next_id = 1
+
+    async def start(self) -> None:
+        LOGGER.info("Starting MCP server %s: %s", self.server.name, " ".join(self.server.command))
+        try:
+            self._process = await asyncio.create_subprocess_exec(
+                *self.server.command,
+                stdin=asyncio.subprocess.PIPE,
+                stdout=asyncio.subprocess.PIPE,
+                stderr=asyncio.subprocess.DEVNULL,
+                cwd=str(self._cwd),
+                env={**os.environ, **self.server.env},
+                limit=STREAM_LIMIT_BYTES,
+            )
+        except OSError as exc:
+            raise McpError(f"Failed to start MCP server {self.server.name}: {exc}") from exc
+
+        await self._request(
+            "initialize",
+            {
+                "protocolVersion": MCP_PROTOCOL_VERSION,
+                "capabilities": {},
+                "clientInfo": {"name": "remote-coder", "version": "0"},
+            },
+        )
+        await self._notify("notifications/initialized")
+
+    async def list_tools(self) -> List[Dict[str, Any]]:
+        result = await self._request("tools/list", {})
+        tools = result.get("tools") or []
+        return [tool for tool in tools if isinstance(tool, dict) and tool.get("name")]
+
+    async def call_tool(self, name: str, arguments: Dict[str, Any]) -> str:
+        result = await self._request("tools/call", {"name": name, "arguments": arguments})
+        parts = []
+        for item in result.get("content") or []:
+            if isinstance(item, dict) and item.get("type") == "text":
+                parts.append(str(item.get("text") or ""))
+            elif isinstance(item, dict):
+                parts.append(f"[{item.get('type', 'unknown')} content omitted]")
+        output = "\n".join(parts)
+        if result.get("isError"):
+            output = f"Error: {output}"
+        return output[:MAX_MCP_OUTPUT_CHARS]
+
+    async def close(self) -> None:
+        process = self._process
+        self._process = None
+        if not process or process.returncode is not None:
+            return
+        if process.stdin:
+            process.stdin.close()
+        try:
+            await asyncio.wait_for(process.wait(), timeout=MCP_SHUTDOWN_TIMEOUT_SECS)
+        except asyncio.TimeoutError:
+            process.kill()
+            await process.wait()
+
+    async def _notify(self, method: str) -> None:
+        await self._write({"jsonrpc": "2.0", "method": method})
+
+    async def _request(self, method: str, params: Dict[str, Any]) -> Dict[str, Any]:
+        request_id = self._next_id
+        self._next_id += 1
+        await self._write({"jsonrpc": "2.0", "id": request_id, "method": method, "params": params})
+        try:
+            return await asyncio.wait_for(self._read_response(request_id), timeout=MCP_REQUEST_TIMEOUT_SECS)
+        except asyncio.TimeoutError as exc:
+            raise McpError(f"MCP server {self.server.name} did not answer {method}") from exc
+
+    async def _write(self, message: Dict[str, Any]) -> None:
+        if not self._process or not self._process.stdin:
+            raise McpError(f"MCP server {self.server.name} is not running")
+        self._process.stdin.write(json.dumps(message).encode("utf-8") + b"\n")
+        await self._process.stdin.drain()
+
+    async def _read_response(self, request_id: int) -> Dict[str, Any]:
+        assert self._process and self._process.stdout
+        while True:
+            line = await self._process.stdout.readline()
+            if not line:
+                raise McpError(f"MCP server {self.server.name} exited unexpectedly")
+            try:
+                message = json.loads(line)
+            except json.JSONDecodeError:
+                continue
+            # Skip notifications and requests initiated by the server
+            if not isinstance(message, dict) or message.get("id") != request_id or "method" in message:
+                continue
+            if "error" in message:
+                error = message["error"] or {}
+                raise McpError(f"MCP server {self.server.name}: {error.get('message', error)}")
+            result = message.get("result")
+            return result if isinstance(result, dict) else {}
+
+
+class McpToolset:
+    """Runs the stdio MCP servers of an API-backed agent for one run."""
+
+    def __init__(self, servers: Sequence[McpServer], cwd: Path) -> None:
+        self._servers = servers
+        self._cwd = cwd
+        self._clients: List[McpStdioClient] = []
+        self._routes: Dict[str, tuple[McpStdioClient, str]] = {}
+        self.schemas: List[Dict[str, Any]] = []
+
+    async def __aenter__(self) -> "McpToolset":
+        for server in self._servers:
+            if server.url:
+                LOGGER.warning("Skipping MCP server %s: only stdio servers are supported for API agents", server.name)
+                continue
+            client = McpStdioClient(server, self._cwd)
+            self._clients.append(client)
+            try:
+                await client.start()
+                tools = await client.list_tools()
+            except McpError:
+                LOGGER.warning("MCP server %s unavailable for this run", server.name, exc_info=True)
+                continue
+            for tool in tools:
+                exposed = f"{TOOL_NAME_PREFIX}{server.name}__{tool['name']}"
+                self._routes[exposed] = (client, tool["name"])
+                self.schemas.append(
+                    {
+                        "type": "function",
+                        "function": {
+                            "name": exposed,
+                            "description": str(tool.get("description") or ""),
+                            "parameters": tool.get("inputSchema") or {"type": "object", "properties": {}},
+                        },
+                    }
+                )
+        return self
+
+    async def __aexit__(self, *exc_info: Any) -> None:
+        await asyncio.gather(*(client.close() for client in self._clients), return_exceptions=True)
+        self._clients.clear()
+        self._routes.clear()
+
+    def handles(self, name: str) -> bool:
+        return name in self._routes
+
+    async def call(self, name: str, arguments: Dict[str, Any]) -> str:
+        client, tool_name = self._routes[name]
+        try:
+            return await client.call_tool(tool_name, arguments)
+        except McpError as exc:
+            return f"Error: {exc}"
@@ -0,0 +1,129 @@
+"""Mock agent adapter for demos and tests.
+
+Answers with canned output after a configurable delay instead of calling a
+model, so the full Slack -> branch -> PR loop can run without an agent CLI or
+API key, and router tests get deterministic results. All options are
+optional::
+
+    delay_secs: 2             # wait before answering (default 1)
+    stream: ["...", "..."]    # progress lines printed one by one during the delay
+    summary: "..."            # reply text (default echoes the request)
+    patch: "<unified diff>"   # applied to the project like an http agent's patch
+    files: {path: contents}   # written to the project as-is
+    pr_title: "..."           # with pr_summary: [...], used for the PR
+    success: false            # report the run as failed, with `errors: [...]`
+
+Without `patch` or `files`, each run appends the request to MOCK_AGENT.md so
+there is always a change to commit.
+"""
+
+from __future__ import annotations
+
+import asyncio
+import logging
+import re
+from pathlib import Path
+from typing import Any, Dict, List, Optional, Sequence
+
+from ..core.models import Agent, AgentType, WorkingDirMode
+from .base import AgentAdapter, AgentResult, FileEdit, OutputFn, emit_output
+from .http_adapter import result_from_patch_response
+
+LOGGER = logging.getLogger(__name__)
+
+DEFAULT_DELAY_SECS = 1.0
+DEFAULT_LOG_FILE = "MOCK_AGENT.md"
+PASSTHROUGH_OPTIONS = ("summary", "patch", "pr_title", "pr_summary", "success", "errors")
+# The runner wraps the request as "CURRENT ASK:\nUSER:\n<text>\nProvide your answer..."
+CURRENT_ASK = re.compile(r"CURRENT ASK:\nUSER:\n(.*?)\nProvide your answer", re.DOTALL)
+
+
+def current_ask(task_text: str) -> str:
+    """Pull the user's request out of the task text built by the agent runner."""
+    match = CURRENT_ASK.search(task_text)
+    return (match.group(1) if match else task_text).strip()
+
+
+class MockAdapter(AgentAdapter):
+    """Returns canned output and edits without running a real agent."""
+
+    streams_output = True
+
+    def __init__(self, agent: Agent) -> None:
+        if agent.type != AgentType.MOCK:
+            raise ValueError(f"MockAdapter requires a mock agent, got {agent.type}")
+        self._agent = agent
+
+    async def run(
+        self,
+        *,
+        task_text: str,
+        project_path: str,
+        session_id: str,
+        conversation_history: Sequence[Dict[str, Any]],
+        model: str | None = None,
+        on_output: Optional[OutputFn] = None,
+    ) -> AgentResult:
+        workdir = self._resolve_workdir(project_path)
+        options = self._agent.options
+        delay = float(options.get("delay_secs", DEFAULT_DELAY_SECS))
+        LOGGER.info("Mock agent %s answering session %s in %.1fs", self._agent.id, session_id, delay)
+        lines = [str(line) for line in options.get("stream") or []]
+        pause = delay / (len(lines) + 1)
+        for line in lines:
+            if pause > 0:
+                await asyncio.sleep(pause)
+            emit_output(on_output, [line])
+        if pause > 0:
+            await asyncio.sleep(pause)
+
+        ask = current_ask(task_text)
+        data: Dict[str, Any] = {key: options[key] for key in PASSTHROUGH_OPTIONS if key in options}
+        data.setdefault("summary", f"Mock agent handled: {ask}")
+
+        file_edits: List[FileEdit] = []
+        errors: List[str] = []
+        if options.get("files"):
+            file_edits, errors = _write_files(workdir, options["files"])
+        elif not options.get("patch"):
+            file_edits = [_append_to_log(workdir, ask)]
+        if errors:
+            data["errors"] = [*(data.get("errors") or []), *errors]
+
+        result = await result_from_patch_response(data, workdir, source="mock agent")
+        result.file_edits = file_edits + result.file_edits
+        return result
+
+    def _resolve_workdir(self, project_path: str) -> Path:
+        if self._agent.working_dir_mode == WorkingDirMode.PROJECT:
+            return Path(project_path)
+        if self._agent.fixed_path:
+            return self._agent.fixed_path
+        raise ValueError("Fixed working directory required for mock agent adapter")
+
+
+def _write_files(workdir: Path, files: Dict[str, Any]) -> tuple[List[FileEdit], List[str]]:
+    root = workdir.resolve()
+    edits: List[FileEdit] = []
+    errors: List[str] = []
+    for rel_path, contents in files.items():
+        target = (root / str(rel_path)).resolve()
+        if not target.is_relative_to(root):
+            errors.append(f"Mock agent file {rel_path} is outside the project")
+            continue
+        existed = target.exists()
+        target.parent.mkdir(parents=True, exist_ok=True)
+        target.write_text(str(contents))
+        edits.append(FileEdit(path=str(rel_path), type="edit" if existed else "create"))
+    return edits, errors
+
+
+def _append_to_log(workdir: Path, ask: str) -> FileEdit:
+    log_path = workdir / DEFAULT_LOG_FILE
+    existed = log_path.exists()
+    first_line = ask.splitlines()[0] if ask else "(empty request)"
+    with log_path.open("a", encoding="utf-8") as handle:
+        if not existed:
+            handle.write("# Mock agent runs\n\n")
+        handle.write(f"- {first_line}\n")
+    return FileEdit(path=DEFAULT_LOG_FILE, type="edit" if existed else "create")
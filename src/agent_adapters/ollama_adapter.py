@@ -0,0 +1,68 @@
+"""Ollama adapter implementation.
+
+Talks to a local (or LAN) Ollama server through its native `/api/chat`
+endpoint so runs can happen offline and at no cost. Tool calling requires a
+model that supports it (e.g. qwen2.5-coder, llama3.1).
+"""
+
+from __future__ import annotations
+
+from typing import Any, Dict, Optional
+
+import aiohttp
+
+from ..core.models import AgentType
+from .tool_loop import ToolLoopAdapter, post_json
+
+DEFAULT_ENDPOINT = "http://localhost:11434"
+
+
+class OllamaAdapter(ToolLoopAdapter):
+    """Executes tasks against an Ollama server with project-scoped tools."""
+
+    agent_type = AgentType.OLLAMA
+    provider_name = "Ollama"
+    # Local models can be slow, especially on first load
+    default_request_timeout_secs = 900
+    supported_overrides = frozenset({"temperature"})
+
+    async def _post_completion(
+        self,
+        http: aiohttp.ClientSession,
+        api_key: Optional[str],
+        model_id: str,
+        messages: list[Dict[str, Any]],
+        tools: list[Dict[str, Any]],
+        sampling: Optional[Dict[str, Any]] = None,
+    ) -> Dict[str, Any]:
+        endpoint = (self._agent.endpoint or DEFAULT_ENDPOINT).rstrip("/")
+        payload: Dict[str, Any] = {
+            "model": model_id,
+            "messages": messages,
+            "tools": tools,
+            "stream": False,
+        }
+        model_options: Dict[str, Any] = {}
+        context_size = self._agent.options.get("context_size")
+        if context_size:
+            model_options["num_ctx"] = int(context_size)
+        if sampling and "temperature" in sampling:
+            model_options["temperature"] = sampling["temperature"]
+        if model_options:
+            payload["options"] = model_options
+        headers = {"Content-Type": "application/json"}
+        # Only needed when the server sits behind an authenticating proxy
+        if api_key:
+            headers["Authorization"] = f"Bearer {api_key}"
+        return await post_json(http, f"{endpoint}/api/chat", payload, headers)
+
+    def _first_message(self, data: Dict[str, Any]) -> Dict[str, Any] | None:
+        message = data.get("message")
+        return message if isinstance(message, dict) else None
+
+    def _accumulate_usage(self, totals: Dict[str, int], data: Dict[str, Any]) -> None:
+        totals["input_tokens"] = totals.get("input_tokens", 0) + int(data.get("prompt_eval_count") or 0)
+        totals["output_tokens"] = totals.get("output_tokens", 0) + int(data.get("eval_count") or 0)
+
+    def _tool_result_message(self, call: Dict[str, Any], name: str, output: str) -> Dict[str, Any]:
+        return {"role": "tool", "tool_name": name, "content": output}
@@ -0,0 +1,63 @@
+"""OpenRouter API adapter implementation.
+
+Calls OpenRouter's OpenAI-compatible chat completions endpoint; the agent loop
+itself lives in `ToolLoopAdapter`.
+"""
+
+from __future__ import annotations
+
+from typing import Any, Dict, Optional
+
+import aiohttp
+
+from ..core.models import AgentType
+from .tool_loop import ToolLoopAdapter, post_json
+
+DEFAULT_ENDPOINT = "https://openrouter.ai/api/v1"
+DEFAULT_API_KEY_ENV = "OPENROUTER_API_KEY"
+
+
+class OpenRouterAdapter(ToolLoopAdapter):
+    """Executes tasks through OpenRouter's chat completions API with project-scoped tools."""
+
+    agent_type = AgentType.OPENROUTER
+    provider_name = "OpenRouter"
+    required_api_key_env = DEFAULT_API_KEY_ENV
+    supported_overrides = frozenset({"temperature", "reasoning_effort"})
+
+    async def _post_completion(
+        self,
+        http: aiohttp.ClientSession,
+        api_key: Optional[str],
+        model_id: str,
+        messages: list[Dict[str, Any]],
+        tools: list[Dict[str, Any]],
+        sampling: Optional[Dict[str, Any]] = None,
+    ) -> Dict[str, Any]:
+        endpoint = (self._agent.endpoint or DEFAULT_ENDPOINT).rstrip("/")
+        payload: Dict[str, Any] = {"model": model_id, "messages": messages, "tools": tools}
+        sampling = sampling or {}
+        if "temperature" in sampling:
+            payload["temperature"] = sampling["temperature"]
+        if "reasoning_effort" in sampling:
+            payload["reasoning"] = {"effort": sampling["reasoning_effort"]}
+        headers = {
+            "Authorization": f"Bearer {api_key}",
+            "Content-Type": "application/json",
+            "X-Title": "Remote Coder",
+        }
+        return await post_json(http, f"{endpoint}/chat/completions", payload, headers)
+
+    def _first_message(self, data: Dict[str, Any]) -> Dict[str, Any] | None:
+        choices = data.get("choices")
+        if not isinstance(choices, list) or not choices:
+            return None
+        message = choices[0].get("message") if isinstance(choices[0], dict) else None
+        return message if isinstance(message, dict) else None
+
+    def _accumulate_usage(self, totals: Dict[str, int], data: Dict[str, Any]) -> None:
+        usage = data.get("usage")
+        if not isinstance(usage, dict):
+            return
+        totals["input_tokens"] = totals.get("input_tokens", 0) + int(usage.get("prompt_tokens") or 0)
+        totals["output_tokens"] = totals.get("output_tokens", 0) + int(usage.get("completion_tokens") or 0)
@@ -0,0 +1,155 @@
+"""Tool calls of agent CLIs as a common progress model.
+
+Agents whose CLI emits JSON events (Claude's `stream-json`, `codex exec
+--json`, Gemini's `stream-json`) report each tool call twice: when it starts
+and when it finishes. The parsers here turn those events into
+`ProgressEvent`s (read a file, edit a file, run a command, search) that an
+adapter passes to `on_progress`; the runner shows them as a checklist in the
+run's live message (see `src/core/progress.py`). Events a parser does not
+know are ignored.
+"""
+
+from __future__ import annotations
+
+from dataclasses import dataclass
+from enum import Enum
+from typing import Any, Callable, Dict, Iterable, List, Mapping, Optional
+
+MAX_TARGET_CHARS = 80
+
+
+class StepKind(str, Enum):
+    READ = "read"
+    EDIT = "edit"
+    COMMAND = "command"
+    SEARCH = "search"
+    OTHER = "other"  # `target` is the tool's name
+
+
+class StepStatus(str, Enum):
+    RUNNING = "running"
+    DONE = "done"
+    FAILED = "failed"
+
+
+@dataclass(frozen=True)
+class ProgressEvent:
+    """A tool call starting (with its kind and target) or finishing (`step_id` refers back to the start)."""
+
+    step_id: str
+    kind: StepKind = StepKind.OTHER
+    target: str = ""
+    status: StepStatus = StepStatus.RUNNING
+
+
+ProgressFn = Callable[[ProgressEvent], None]
+
+# Tool name -> (kind, input keys holding the target, the first one set wins)
+CLAUDE_TOOLS: Dict[str, tuple[StepKind, tuple[str, ...]]] = {
+    "Read": (StepKind.READ, ("file_path",)),
+    "NotebookRead": (StepKind.READ, ("notebook_path",)),
+    "Edit": (StepKind.EDIT, ("file_path",)),
+    "MultiEdit": (StepKind.EDIT, ("file_path",)),
+    "Write": (StepKind.EDIT, ("file_path",)),
+    "NotebookEdit": (StepKind.EDIT, ("notebook_path",)),
+    "Bash": (StepKind.COMMAND, ("command",)),
+    "Grep": (StepKind.SEARCH, ("pattern",)),
+    "Glob": (StepKind.SEARCH, ("pattern",)),
+    "LS": (StepKind.SEARCH, ("path",)),
+    "WebSearch": (StepKind.SEARCH, ("query",)),
+}
+GEMINI_TOOLS: Dict[str, tuple[StepKind, tuple[str, ...]]] = {
+    "read_file": (StepKind.READ, ("file_path", "absolute_path", "path")),
+    "read_many_files": (StepKind.READ, ("paths",)),
+    "write_file": (StepKind.EDIT, ("file_path",)),
+    "replace": (StepKind.EDIT, ("file_path",)),
+    "run_shell_command": (StepKind.COMMAND, ("command",)),
+    "glob": (StepKind.SEARCH, ("pattern",)),
+    "search_file_content": (StepKind.SEARCH, ("pattern",)),
+    "list_directory": (StepKind.SEARCH, ("path",)),
+    "google_web_search": (StepKind.SEARCH, ("query",)),
+}
+
+
+def emit_progress(on_progress: Optional[ProgressFn], events: Iterable[ProgressEvent]) -> None:
+    if on_progress:
+        for event in events:
+            on_progress(event)
+
+
+def _target(arguments: Any, keys: Iterable[str]) -> str:
+    if not isinstance(arguments, Mapping):
+        return ""
+    for key in keys:
+        value = arguments.get(key)
+        if isinstance(value, list):
+            value = ", ".join(str(item) for item in value)
+        if isinstance(value, str) and value.strip():
+            text = " ".join(value.split())
+            return text if len(text) <= MAX_TARGET_CHARS else text[: MAX_TARGET_CHARS - 1] + "…"
+    return ""
+
+
+def _started(
+    step_id: Any, name: str, arguments: Any, tools: Mapping[str, tuple[StepKind, tuple[str, ...]]]
+) -> ProgressEvent:
+    kind, keys = tools.get(name, (StepKind.OTHER, ()))
+    target = _target(arguments, keys) if kind != StepKind.OTHER else name
+    return ProgressEvent(step_id=str(step_id), kind=kind, target=target or name)
+
+
+def parse_claude_event(payload: Mapping[str, Any]) -> List[ProgressEvent]:
+    """`tool_use` blocks of assistant messages start steps; `tool_result` blocks of user messages finish them."""
+    message = payload.get("message")
+    if payload.get("type") not in ("assistant", "user") or not isinstance(message, Mapping):
+        return []
+    content = message.get("content")
+    events: List[ProgressEvent] = []
+    for block in content if isinstance(content, list) else []:
+        if not isinstance(block, Mapping):
+            continue
+        if block.get("type") == "tool_use" and block.get("id"):
+            events.append(_started(block["id"], str(block.get("name") or "tool"), block.get("input"), CLAUDE_TOOLS))
+        elif block.get("type") == "tool_result" and block.get("tool_use_id"):
+            status = StepStatus.FAILED if block.get("is_error") else StepStatus.DONE
+            events.append(ProgressEvent(step_id=str(block["tool_use_id"]), status=status))
+    return events
+
+
+def parse_codex_event(payload: Mapping[str, Any]) -> List[ProgressEvent]:
+    """`item.started` and `item.completed` for commands, file changes, MCP tool calls, and web searches."""
+    event_type = payload.get("type")
+    item = payload.get("item")
+    if event_type not in ("item.started", "item.completed") or not isinstance(item, Mapping) or not item.get("id"):
+        return []
+    item_type = item.get("type")
+    if item_type == "command_execution":
+        kind, target = StepKind.COMMAND, _target(item, ("command",))
+    elif item_type == "file_change":
+        changes = item.get("changes") if isinstance(item.get("changes"), list) else []
+        paths = [change["path"] for change in changes if isinstance(change, Mapping) and change.get("path")]
+        kind, target = StepKind.EDIT, _target({"paths": paths}, ("paths",))
+    elif item_type == "mcp_tool_call":
+        kind, target = StepKind.OTHER, str(item.get("tool") or "tool")
+    elif item_type == "web_search":
+        kind, target = StepKind.SEARCH, _target(item, ("query",))
+    else:
+        return []
+    status = StepStatus.RUNNING
+    if event_type == "item.completed":
+        failed = item.get("status") == "failed" or item.get("exit_code") not in (None, 0)
+        status = StepStatus.FAILED if failed else StepStatus.DONE
+    return [ProgressEvent(step_id=str(item["id"]), kind=kind, target=target or str(item_type), status=status)]
+
+
+def parse_gemini_event(payload: Mapping[str, Any]) -> List[ProgressEvent]:
+    """`tool_use` events start steps and `tool_result` events finish them."""
+    tool_id = payload.get("tool_id")
+    if not tool_id:
+        return []
+    if payload.get("type") == "tool_use":
+        return [_started(tool_id, str(payload.get("tool_name") or "tool"), payload.get("parameters"), GEMINI_TOOLS)]
+    if payload.get("type") == "tool_result":
+        status = StepStatus.FAILED if payload.get("status") == "error" else StepStatus.DONE
+        return [ProgressEvent(step_id=str(tool_id), status=status)]
+    return []
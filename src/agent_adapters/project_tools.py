@@ -0,0 +1,164 @@
+"""File and shell tools for API-backed agents, confined to the project directory."""
+
+from __future__ import annotations
+
+import logging
+from pathlib import Path
+from typing import Any, Dict, List, Optional
+
+from ..core.models import SandboxConfig
+from ..core.sandbox import Sandbox
+from .base import FileEdit
+from .dev_env import current_dev_env
+
+LOGGER = logging.getLogger(__name__)
+
+MAX_READ_BYTES = 200_000
+MAX_TOOL_OUTPUT_CHARS = 20_000
+DEFAULT_SHELL_TIMEOUT_SECS = 120
+
+# OpenAI-compatible tool schemas advertised to the model
+TOOL_SCHEMAS: List[Dict[str, Any]] = [
+    {
+        "type": "function",
+        "function": {
+            "name": "read_file",
+            "description": "Read a UTF-8 text file from the project.",
+            "parameters": {
+                "type": "object",
+                "properties": {"path": {"type": "string", "description": "Path relative to the project root"}},
+                "required": ["path"],
+            },
+        },
+    },
+    {
+        "type": "function",
+        "function": {
+            "name": "write_file",
+            "description": "Create or overwrite a text file in the project.",
+            "parameters": {
+                "type": "object",
+                "properties": {
+                    "path": {"type": "string", "description": "Path relative to the project root"},
+                    "content": {"type": "string", "description": "Full new file contents"},
+                },
+                "required": ["path", "content"],
+            },
+        },
+    },
+    {
+        "type": "function",
+        "function": {
+            "name": "list_files",
+            "description": "List entries of a directory in the project.",
+            "parameters": {
+                "type": "object",
+                "properties": {"path": {"type": "string", "description": "Directory relative to the project root"}},
+            },
+        },
+    },
+    {
+        "type": "function",
+        "function": {
+            "name": "run_shell",
+            "description": "Run a shell command with the project root as working directory.",
+            "parameters": {
+                "type": "object",
+                "properties": {"command": {"type": "string"}},
+                "required": ["command"],
+            },
+        },
+    },
+]
+
+
+class ToolError(Exception):
+    """Raised when a tool call is invalid or tries to leave the project."""
+
+
+class ProjectToolbox:
+    """Executes tool calls against a single project root and records file edits."""
+
+    def __init__(
+        self,
+        root: Path,
+        shell_timeout_secs: int = DEFAULT_SHELL_TIMEOUT_SECS,
+        sandbox: Optional[SandboxConfig] = None,
+    ) -> None:
+        self._root = Path(root).resolve()
+        self._shell_timeout_secs = shell_timeout_secs
+        # Shell tools follow the project's development shell, like CLI agents do
+        self._sandbox = Sandbox(sandbox, current_dev_env())
+        self.file_edits: List[FileEdit] = []
+
+    async def execute(self, name: str, arguments: Dict[str, Any]) -> str:
+        """Run a tool by name. Errors are returned as text so the model can recover."""
+        try:
+            if name == "read_file":
+                return self._read_file(arguments)
+            if name == "write_file":
+                return self._write_file(arguments)
+            if name == "list_files":
+                return self._list_files(arguments)
+            if name == "run_shell":
+                return await self._run_shell(arguments)
+            raise ToolError(f"Unknown tool `{name}`")
+        except ToolError as exc:
+            return f"Error: {exc}"
+        except OSError as exc:
+            return f"Error: {exc}"
+
+    def resolve_path(self, raw_path: Any) -> Path:
+        """Resolve a tool-supplied path, rejecting anything outside the project root."""
+        if not isinstance(raw_path, str) or not raw_path.strip():
+            raise ToolError("A non-empty `path` argument is required")
+        candidate = (self._root / raw_path).resolve()
+        if candidate != self._root and self._root not in candidate.parents:
+            raise ToolError(f"Path `{raw_path}` is outside the project directory")
+        return candidate
+
+    def _read_file(self, arguments: Dict[str, Any]) -> str:
+        path = self.resolve_path(arguments.get("path"))
+        if not path.is_file():
+            raise ToolError(f"`{arguments.get('path')}` is not a file")
+        data = path.read_bytes()[:MAX_READ_BYTES]
+        return data.decode("utf-8", errors="replace")
+
+    def _write_file(self, arguments: Dict[str, Any]) -> str:
+        path = self.resolve_path(arguments.get("path"))
+        content = arguments.get("content")
+        if not isinstance(content, str):
+            raise ToolError("`content` must be a string")
+        existed = path.exists()
+        path.parent.mkdir(parents=True, exist_ok=True)
+        path.write_text(content, encoding="utf-8")
+        relative = str(path.relative_to(self._root))
+        self.file_edits.append(FileEdit(path=relative, type="edit" if existed else "create"))
+        return f"Wrote {len(content)} characters to {relative}"
+
+    def _list_files(self, arguments: Dict[str, Any]) -> str:
+        raw_path = arguments.get("path") or "."
+        path = self.resolve_path(raw_path)
+        if not path.is_dir():
+            raise ToolError(f"`{raw_path}` is not a directory")
+        entries = []
+        for entry in sorted(path.iterdir()):
+            if entry.name == ".git":
+                continue
+            entries.append(f"{entry.name}/" if entry.is_dir() else entry.name)
+        return "\n".join(entries) or "(empty directory)"
+
+    async def _run_shell(self, arguments: Dict[str, Any]) -> str:
+        command = arguments.get("command")
+        if not isinstance(command, str) or not command.strip():
+            raise ToolError("A non-empty `command` argument is required")
+
+        LOGGER.info("API agent running shell command in %s: %s", self._root, command)
+        result = await self._sandbox.run(command, self._root, self._shell_timeout_secs, kind="shell")
+        if result.timed_out:
+            return f"Command timed out after {self._shell_timeout_secs}s"
+
+        output = result.output
+        if len(output) > MAX_TOOL_OUTPUT_CHARS:
+            output = output[-MAX_TOOL_OUTPUT_CHARS:]
+        return f"exit code {result.returncode}\n{output}"
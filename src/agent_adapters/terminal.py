@@ -0,0 +1,108 @@
+"""Pseudo-terminals for agent CLIs that behave differently without a TTY.
+
+Some CLIs only flush output line by line, or only skip their interactive
+prompts, when stdout is a terminal. Agents with `spawn_mode: pty` get a
+pseudo-terminal as stdout (and as stdin when the prompt is not piped in). Its
+output is read back through an `asyncio.StreamReader`, so adapters consume it
+like a pipe: the terminal is set up without echo or CRLF translation, and
+escape sequences (colors, cursor moves) are removed before they reach the
+adapter's parser. Nobody watches the terminal, so it never resizes; it is
+made wide (`pty_size`, default 200×50, also exported as COLUMNS and LINES) so
+CLIs that format to the terminal width don't wrap JSON or long lines.
+"""
+
+from __future__ import annotations
+
+import asyncio
+import errno
+import os
+import re
+import struct
+from typing import Dict, Mapping, Optional, Tuple
+
+READ_CHUNK_BYTES = 64 * 1024
+
+# CSI sequences (colors, cursor moves), OSC sequences (window titles, links), and two-byte escapes
+ESCAPE_SEQUENCE = re.compile(rb"\x1b\[[0-?]*[ -/]*[@-~]|\x1b\][^\x07\x1b]*(?:\x07|\x1b\\)|\x1b[@-Z\\-_]")
+
+
+def open_pty(size: Tuple[int, int]) -> Tuple[int, int]:
+    """A (master, slave) pair sized `size` (columns, rows), without echo or CRLF output translation."""
+    # Unix only; Windows has no pseudo-terminals of this kind
+    import fcntl
+    import termios
+
+    master, slave = os.openpty()
+    attrs = termios.tcgetattr(slave)
+    attrs[1] &= ~termios.ONLCR  # oflag: keep "\n" as is
+    attrs[3] &= ~(termios.ECHO | termios.ICANON)  # lflag: don't echo input back into the output
+    termios.tcsetattr(slave, termios.TCSANOW, attrs)
+    columns, rows = size
+    fcntl.ioctl(slave, termios.TIOCSWINSZ, struct.pack("HHHH", rows, columns, 0, 0))
+    return master, slave
+
+
+def terminal_env(env: Mapping[str, str], size: Tuple[int, int]) -> Dict[str, str]:
+    """`env` with the terminal's size, and a TERM if the daemon had none."""
+    columns, rows = size
+    return {"TERM": "xterm-256color", **env, "COLUMNS": str(columns), "LINES": str(rows)}
+
+
+class _EscapeFilter:
+    """Removes escape sequences from a byte stream, holding back one cut off at a chunk's end."""
+
+    def __init__(self) -> None:
+        self._pending = b""
+
+    def feed(self, data: bytes) -> bytes:
+        data = self._pending + data
+        self._pending = b""
+        start = data.rfind(b"\x1b")
+        if start != -1 and not ESCAPE_SEQUENCE.match(data, start) and len(data) - start < 64:
+            data, self._pending = data[:start], data[start:]
+        return ESCAPE_SEQUENCE.sub(b"", data)
+
+    def flush(self) -> bytes:
+        rest, self._pending = self._pending, b""
+        return rest
+
+
+def read_pty(master: int, loop: Optional[asyncio.AbstractEventLoop] = None) -> asyncio.StreamReader:
+    """A reader fed with the output written to the terminal until its last writer closes it.
+
+    Reading the master side fails with EIO (rather than returning b"") once the
+    child has exited, which counts as the end of the output.
+    """
+    loop = loop or asyncio.get_running_loop()
+    reader = asyncio.StreamReader(limit=2**26, loop=loop)
+    escapes = _EscapeFilter()
+    os.set_blocking(master, False)
+
+    def _on_readable() -> None:
+        try:
+            data = os.read(master, READ_CHUNK_BYTES)
+        except BlockingIOError:
+            return
+        except OSError as exc:
+            if exc.errno != errno.EIO:
+                reader.set_exception(exc)
+                loop.remove_reader(master)
+                return
+            data = b""
+        if data:
+            reader.feed_data(escapes.feed(data))
+            return
+        loop.remove_reader(master)
+        reader.feed_data(escapes.flush())
+        reader.feed_eof()
+
+    loop.add_reader(master, _on_readable)
+    return reader
+
+
+def close_pty(master: int, loop: Optional[asyncio.AbstractEventLoop] = None) -> None:
+    (loop or asyncio.get_running_loop()).remove_reader(master)
+    try:
+        os.close(master)
+    except OSError:
+        pass
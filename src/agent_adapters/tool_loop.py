@@ -0,0 +1,213 @@
+"""Shared agent loop for API-backed adapters.
+
+Instead of delegating to a CLI, these adapters call a chat API, execute the
+tool calls the model asks for (restricted to the project directory), and feed
+the results back until the model produces a final answer. Subclasses only
+describe the wire format of their provider.
+"""
+
+from __future__ import annotations
+
+import json
+import logging
+import os
+from abc import abstractmethod
+from pathlib import Path
+from typing import Any, Dict, Optional, Sequence
+
+import aiohttp
+
+from ..core.models import Agent, AgentType, WorkingDirMode
+from .base import AgentAdapter, AgentResult, parse_structured_output
+from .mcp import McpToolset
+from .project_tools import DEFAULT_SHELL_TIMEOUT_SECS, TOOL_SCHEMAS, ProjectToolbox
+
+LOGGER = logging.getLogger(__name__)
+
+DEFAULT_MAX_TOOL_ITERATIONS = 25
+
+SYSTEM_PROMPT = (
+    "You are a coding agent working directly in a git repository. "
+    "Use the provided tools to inspect and edit files and to run commands. "
+    "All paths are relative to the repository root and you cannot access anything outside it. "
+    "When you are done, reply without calling any tools."
+)
+
+
+class ToolLoopAdapter(AgentAdapter):
+    """Base class for adapters that run the tool-calling loop themselves."""
+
+    agent_type: AgentType
+    provider_name: str
+    default_request_timeout_secs: int = 300
+    # Env var that must hold an API key; None means the provider does not need one
+    required_api_key_env: Optional[str] = None
+
+    def __init__(self, agent: Agent) -> None:
+        if agent.type != self.agent_type:
+            raise ValueError(
+                f"{type(self).__name__} requires an {self.agent_type.name} agent, got {agent.type}"
+            )
+        self._agent = agent
+
+    async def run(
+        self,
+        *,
+        task_text: str,
+        project_path: str,
+        session_id: str,
+        conversation_history: Sequence[Dict[str, Any]],
+        model: str | None = None,
+        temperature: float | None = None,
+        reasoning_effort: str | None = None,
+    ) -> AgentResult:
+        model_id = model or self._agent.models.get("default")
+        sampling = {"temperature": temperature, "reasoning_effort": reasoning_effort}
+        sampling = {name: value for name, value in sampling.items() if value is not None}
+        if not model_id:
+            return AgentResult(
+                success=False,
+                output_text="",
+                errors=[f"No model configured for agent {self._agent.id}"],
+            )
+
+        api_key_env = self._agent.api_key_env or self.required_api_key_env
+        api_key = (self._agent.env.get(api_key_env) or os.getenv(api_key_env)) if api_key_env else None
+        if self.required_api_key_env and not api_key:
+            return AgentResult(
+                success=False,
+                output_text="",
+                errors=[f"{api_key_env} is not set; cannot call {self.provider_name}."],
+            )
+
+        workdir = self._resolve_workdir(project_path)
+        options = self._agent.options
+        toolbox = ProjectToolbox(
+            workdir,
+            shell_timeout_secs=int(options.get("shell_timeout_secs", DEFAULT_SHELL_TIMEOUT_SECS)),
+            sandbox=self._agent.sandbox,
+        )
+        max_iterations = int(options.get("max_tool_iterations", DEFAULT_MAX_TOOL_ITERATIONS))
+        timeout = aiohttp.ClientTimeout(
+            total=float(options.get("request_timeout_secs", self.default_request_timeout_secs))
+        )
+
+        messages: list[Dict[str, Any]] = [
+            {"role": "system", "content": SYSTEM_PROMPT},
+            {"role": "user", "content": task_text},
+        ]
+        raw_responses: list[str] = []
+        errors: list[str] = []
+        token_usage: Dict[str, int] = {}
+        final_text = ""
+        success = False
+
+        LOGGER.info("Running %s model %s in %s", self.provider_name, model_id, workdir)
+        # MCP servers live exactly as long as the run
+        async with McpToolset(self._agent.mcp_servers, workdir) as mcp, aiohttp.ClientSession(
+            timeout=timeout
+        ) as http:
+            tools = [*TOOL_SCHEMAS, *mcp.schemas]
+            for _ in range(max_iterations):
+                try:
+                    data = await self._post_completion(http, api_key, model_id, messages, tools, sampling)
+                except (aiohttp.ClientError, ValueError) as exc:
+                    errors.append(f"{self.provider_name} request failed: {exc}")
+                    break
+
+                raw_responses.append(json.dumps(data))
+                self._accumulate_usage(token_usage, data)
+
+                message = self._first_message(data)
+                if message is None:
+                    errors.append(f"{self.provider_name} returned no message")
+                    break
+                messages.append(message)
+
+                tool_calls = message.get("tool_calls") or []
+                if not tool_calls:
+                    final_text = str(message.get("content") or "").strip()
+                    success = True
+                    break
+
+                for call in tool_calls:
+                    messages.append(await self._run_tool_call(toolbox, mcp, call))
+            else:
+                errors.append(f"Stopped after {max_iterations} tool iterations without a final answer")
+
+        raw_output = "\n".join(raw_responses)
+        return AgentResult(
+            success=success and not errors,
+            output_text=final_text,
+            file_edits=toolbox.file_edits,
+            errors=errors,
+            session_context={},
+            raw_output=raw_output,
+            structured_output=parse_structured_output(final_text) if final_text else None,
+            token_usage=token_usage,
+        )
+
+    @abstractmethod
+    async def _post_completion(
+        self,
+        http: aiohttp.ClientSession,
+        api_key: Optional[str],
+        model_id: str,
+        messages: list[Dict[str, Any]],
+        tools: list[Dict[str, Any]],
+        sampling: Optional[Dict[str, Any]] = None,
+    ) -> Dict[str, Any]:
+        """Send the conversation so far, advertising `tools`, and return the decoded response body.
+
+        `sampling` holds the run's `temperature` and `reasoning_effort` overrides
+        among `supported_overrides`.
+        """
+
+    @abstractmethod
+    def _first_message(self, data: Dict[str, Any]) -> Dict[str, Any] | None:
+        """Extract the assistant message from a response body."""
+
+    @abstractmethod
+    def _accumulate_usage(self, totals: Dict[str, int], data: Dict[str, Any]) -> None:
+        """Add the response's token counts to `totals` (input_tokens/output_tokens)."""
+
+    def _tool_result_message(self, call: Dict[str, Any], name: str, output: str) -> Dict[str, Any]:
+        return {"role": "tool", "tool_call_id": call.get("id"), "content": output}
+
+    async def _run_tool_call(
+        self, toolbox: ProjectToolbox, mcp: McpToolset, call: Dict[str, Any]
+    ) -> Dict[str, Any]:
+        function = call.get("function") or {}
+        name = str(function.get("name") or "")
+        arguments = function.get("arguments") or {}
+        # OpenAI-style APIs send arguments as a JSON string, others as an object
+        if isinstance(arguments, str):
+            try:
+                arguments = json.loads(arguments or "{}")
+            except json.JSONDecodeError:
+                arguments = None
+        if not isinstance(arguments, dict):
+            output = "Error: tool arguments must be a JSON object"
+        elif mcp.handles(name):
+            output = await mcp.call(name, arguments)
+        else:
+            output = await toolbox.execute(name, arguments)
+        return self._tool_result_message(call, name, output)
+
+    def _resolve_workdir(self, project_path: str) -> Path:
+        if self._agent.working_dir_mode == WorkingDirMode.PROJECT:
+            return Path(project_path)
+        if self._agent.fixed_path:
+            return self._agent.fixed_path
+        raise ValueError(f"Fixed working directory required for {self.provider_name} adapter")
+
+
+async def post_json(
+    http: aiohttp.ClientSession, url: str, payload: Dict[str, Any], headers: Dict[str, str]
+) -> Dict[str, Any]:
+    """POST a JSON payload and decode the JSON response, raising ValueError on HTTP errors."""
+    async with http.post(url, json=payload, headers=headers) as response:
+        body = await response.text()
+        if response.status >= 400:
+            raise ValueError(f"HTTP {response.status}: {body[:500]}")
+        return json.loads(body)
@@ -0,0 +1,189 @@
+"""Pool of long-lived agent CLI processes, one per session.
+
+Agents with `options.warm_pool: true` keep their CLI running between the turns
+of a session and get each prompt over stdin instead of a fresh process per
+message, so follow-ups start faster and keep the CLI's in-process context.
+Only Claude supports it (through `--input-format stream-json`). A process is
+stopped once it has been idle for `options.warm_idle_secs` (default 600), when
+its turn fails or is cancelled, or when the daemon shuts down.
+"""
+
+from __future__ import annotations
+
+import asyncio
+import logging
+import time
+from collections import deque
+from pathlib import Path
+from typing import TYPE_CHECKING, Callable, Deque, Dict, Mapping, Optional, Sequence, Tuple
+
+from ..core.privacy import PRIVACY
+from .bubblewrap import sandboxed_command
+from .limits import cgroup_command, rlimit_setter, uses_cgroup
+from .windows import IS_WINDOWS, create_process, kill_process_tree
+
+if TYPE_CHECKING:
+    from ..core.models import ProcessLimits, SandboxConfig
+
+LOGGER = logging.getLogger(__name__)
+
+DEFAULT_IDLE_SECS = 600
+EVICT_INTERVAL_SECS = 30
+STOP_TIMEOUT_SECS = 5
+# stream-json events can carry whole files, far beyond asyncio's 64KB default line limit
+STREAM_LIMIT_BYTES = 64 * 1024 * 1024
+STDERR_TAIL_LINES = 50
+
+# (session_id, *anything that must match for a process to be reused)
+PoolKey = Tuple[str, ...]
+Clock = Callable[[], float]
+
+
+class WarmProcess:
+    """A running CLI process that takes one prompt per line on stdin."""
+
+    def __init__(self, process: asyncio.subprocess.Process, *, idle_secs: float, clock: Clock) -> None:
+        self.process = process
+        self.idle_secs = idle_secs
+        # Held for the duration of a turn; busy processes are never evicted
+        self.lock = asyncio.Lock()
+        self._clock = clock
+        self.last_used = clock()
+        self._stderr_lines: Deque[str] = deque(maxlen=STDERR_TAIL_LINES)
+        self._stderr_task = asyncio.create_task(self._drain_stderr())
+
+    @property
+    def alive(self) -> bool:
+        return self.process.returncode is None
+
+    def idle_for(self) -> float:
+        return self._clock() - self.last_used
+
+    async def send_line(self, text: str) -> None:
+        assert self.process.stdin is not None
+        self.last_used = self._clock()
+        self.process.stdin.write(text.encode("utf-8") + b"\n")
+        await self.process.stdin.drain()
+
+    async def read_line(self) -> Optional[str]:
+        """Next line of stdout, or None once the process has closed it."""
+        assert self.process.stdout is not None
+        line = await self.process.stdout.readline()
+        self.last_used = self._clock()
+        if not line:
+            return None
+        return line.decode("utf-8", errors="replace")
+
+    def stderr_text(self) -> str:
+        return "\n".join(self._stderr_lines).strip()
+
+    async def stop(self) -> None:
+        if self.alive and IS_WINDOWS:
+            # Terminating the CLI would leave the processes it started running
+            await kill_process_tree(self.process)
+            await self.process.wait()
+        elif self.alive:
+            self.process.terminate()
+            try:
+                await asyncio.wait_for(self.process.wait(), STOP_TIMEOUT_SECS)
+            except asyncio.TimeoutError:
+                self.process.kill()
+                await self.process.wait()
+        self._stderr_task.cancel()
+
+    async def _drain_stderr(self) -> None:
+        # Keep reading so a chatty CLI never blocks on a full stderr pipe
+        assert self.process.stderr is not None
+        while True:
+            line = await self.process.stderr.readline()
+            if not line:
+                return
+            self._stderr_lines.append(line.decode("utf-8", errors="replace").rstrip())
+
+
+class WarmPool:
+    """Starts, reuses, and evicts warm processes."""
+
+    def __init__(self, *, clock: Clock = time.monotonic, evict_interval_secs: float = EVICT_INTERVAL_SECS) -> None:
+        self._clock = clock
+        self._evict_interval_secs = evict_interval_secs
+        self._processes: Dict[PoolKey, WarmProcess] = {}
+        self._evictor: Optional[asyncio.Task] = None
+
+    def __len__(self) -> int:
+        return len(self._processes)
+
+    async def acquire(
+        self,
+        key: PoolKey,
+        command: Sequence[str],
+        *,
+        cwd: Path,
+        env: Mapping[str, str],
+        idle_secs: float = DEFAULT_IDLE_SECS,
+        limits: Optional[ProcessLimits] = None,
+        sandbox: Optional[SandboxConfig] = None,
+    ) -> WarmProcess:
+        """Return the live process for `key`, starting `command` if there is none.
+
+        `limits` apply to the process for its whole life, so a `cpu_secs` budget is shared by all its turns.
+        A bubblewrap `sandbox` is set up once for the process and kept across turns.
+        """
+        warm = self._processes.get(key)
+        if warm and warm.alive:
+            return warm
+        if warm:
+            LOGGER.info("Warm process for session %s exited (code %s); restarting", key[0], warm.process.returncode)
+            await self.discard(key)
+
+        LOGGER.info("Starting warm agent process for session %s in %s", key[0], cwd)
+        command = sandboxed_command(command, cwd, sandbox)
+        cgroup = uses_cgroup(limits)
+        process = await create_process(
+            *(cgroup_command(command, limits) if cgroup and limits else command),
+            stdin=asyncio.subprocess.PIPE,
+            stdout=asyncio.subprocess.PIPE,
+            stderr=asyncio.subprocess.PIPE,
+            cwd=str(cwd),
+            env=PRIVACY.cli_env(env),
+            limit=STREAM_LIMIT_BYTES,
+            preexec_fn=rlimit_setter(limits, cgroup=cgroup),
+        )
+        warm = WarmProcess(process, idle_secs=idle_secs, clock=self._clock)
+        self._processes[key] = warm
+        if self._evictor is None or self._evictor.done():
+            self._evictor = asyncio.create_task(self._evict_loop())
+        return warm
+
+    async def discard(self, key: PoolKey) -> None:
+        warm = self._processes.pop(key, None)
+        if warm:
+            await warm.stop()
+
+    async def discard_session(self, session_id: str) -> None:
+        for key in [key for key in self._processes if key[0] == session_id]:
+            await self.discard(key)
+
+    async def evict_idle(self) -> int:
+        """Stop processes idle for longer than their timeout; returns how many were stopped."""
+        expired = [
+            key
+            for key, warm in self._processes.items()
+            if not warm.alive or (not warm.lock.locked() and warm.idle_for() >= warm.idle_secs)
+        ]
+        for key in expired:
+            LOGGER.info("Evicting idle warm agent process for session %s", key[0])
+            await self.discard(key)
+        return len(expired)
+
+    async def close_all(self) -> None:
+        if self._evictor:
+            self._evictor.cancel()
+            self._evictor = None
+        for key in list(self._processes):
+            await self.discard(key)
+
+    async def _evict_loop(self) -> None:
+        while self._processes:
+            await asyncio.sleep(self._evict_interval_secs)
+            await self.evict_idle()
@@ -0,0 +1,90 @@
+"""Adapter for agents implemented as WASM plugins (see `wasm_plugins`)."""
+
+from __future__ import annotations
+
+import asyncio
+import dataclasses
+import logging
+from pathlib import Path
+from typing import Any, Dict, Optional, Sequence
+
+from ..core.models import Agent, AgentType, WorkingDirMode
+from .base import AgentAdapter, AgentResult
+from .http_adapter import head_commit, result_from_patch_response
+from .project_tools import DEFAULT_SHELL_TIMEOUT_SECS, ProjectToolbox
+from .wasm_plugins import DEFAULT_PLUGIN_TIMEOUT_SECS, PluginError, WasmPlugin
+
+LOGGER = logging.getLogger(__name__)
+
+# run_shell is opt-in: it would let a plugin step outside the WASM sandbox
+DEFAULT_PLUGIN_TOOLS = frozenset({"read_file", "write_file", "list_files"})
+
+
+class WasmAgentAdapter(AgentAdapter):
+    """Runs a WASM plugin with project-scoped tools and applies the patch it returns."""
+
+    def __init__(self, agent: Agent, plugin: Optional[WasmPlugin]) -> None:
+        if agent.type != AgentType.WASM:
+            raise ValueError(f"WasmAgentAdapter requires a WASM agent, got {agent.type}")
+        self._agent = agent
+        self._plugin = plugin
+
+    async def run(
+        self,
+        *,
+        task_text: str,
+        project_path: str,
+        session_id: str,
+        conversation_history: Sequence[Dict[str, Any]],
+        model: str | None = None,
+    ) -> AgentResult:
+        plugin_name = self._agent.plugin or self._agent.id
+        if self._plugin is None:
+            return AgentResult(
+                success=False,
+                output_text="",
+                errors=[f"WASM plugin `{plugin_name}` was not found in the plugins directory."],
+            )
+
+        workdir = self._resolve_workdir(project_path)
+        options = self._agent.options
+        toolbox = ProjectToolbox(
+            workdir,
+            shell_timeout_secs=int(options.get("shell_timeout_secs", DEFAULT_SHELL_TIMEOUT_SECS)),
+            sandbox=self._agent.sandbox,
+        )
+        allowed_tools = set(DEFAULT_PLUGIN_TOOLS)
+        if options.get("allow_shell"):
+            allowed_tools.add("run_shell")
+        loop = asyncio.get_running_loop()
+
+        def _call_tool(name: str, arguments: Dict[str, Any]) -> str:
+            # Called from the plugin's worker thread; the toolbox lives on the event loop
+            if name not in allowed_tools:
+                return f"Error: tool {name!r} is not available to this plugin"
+            return asyncio.run_coroutine_threadsafe(toolbox.execute(name, arguments), loop).result()
+
+        request = {
+            "session_id": session_id,
+            "model": model or self._agent.models.get("default"),
+            "prompt": task_text,
+            "conversation_history": list(conversation_history),
+            "project": {"name": workdir.name, "head": await head_commit(workdir)},
+        }
+        timeout_secs = float(options.get("timeout_secs", DEFAULT_PLUGIN_TIMEOUT_SECS))
+
+        LOGGER.info("Running WASM plugin %s for session %s", plugin_name, session_id)
+        try:
+            data = await asyncio.to_thread(self._plugin.run, request, _call_tool, timeout_secs)
+        except PluginError as exc:
+            return AgentResult(success=False, output_text="", errors=[str(exc)])
+
+        result = await result_from_patch_response(data, workdir, source=f"plugin {plugin_name}")
+        return dataclasses.replace(result, file_edits=[*toolbox.file_edits, *result.file_edits])
+
+    def _resolve_workdir(self, project_path: str) -> Path:
+        if self._agent.working_dir_mode == WorkingDirMode.PROJECT:
+            return Path(project_path)
+        if self._agent.fixed_path:
+            return self._agent.fixed_path
+        raise ValueError("Fixed working directory required for WASM agent adapter")
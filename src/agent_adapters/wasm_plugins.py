@@ -0,0 +1,271 @@
+"""Loader for agent adapters shipped as WebAssembly plugins.
+
+Plugins are `*.wasm` files in `<config dir>/plugins/`, discovered at startup
+and run with wasmtime (install with `pip install remote-coder[wasm]`). A
+plugin only sees what the host hands it: the task as JSON and, through the
+`call_tool` import, the same project-scoped file tools API agents get.
+
+ABI version 1. Strings cross the boundary as UTF-8 JSON in guest memory;
+functions returning a string return an i64 packing `(ptr << 32) | len`.
+
+Guest exports:
+
+- `memory`
+- `rc_alloc(len: i32) -> i32`: allocate `len` bytes for the host to write into
+- `rc_manifest() -> i64`: `{"name": "...", "abi_version": 1, "description": "..."}`
+- `rc_run(ptr: i32, len: i32) -> i64`: takes the request, returns the response
+
+Host imports (module `remote_coder`, both optional):
+
+- `log(ptr: i32, len: i32)`: write a message to the daemon log
+- `call_tool(ptr: i32, len: i32) -> i64`: run `{"name": ..., "arguments": {...}}`
+  and return the tool output as a JSON string
+
+The request and response bodies are the ones used by `http` agents (see
+`http_adapter`): the plugin answers with a summary and, optionally, a patch
+that Remote Coder applies to the project. WASI imports are satisfied with an
+empty WASI context (no filesystem, network, or environment).
+"""
+
+from __future__ import annotations
+
+import json
+import logging
+import threading
+import time
+from dataclasses import dataclass
+from pathlib import Path
+from typing import Any, Callable, Dict, Optional
+
+LOGGER = logging.getLogger(__name__)
+
+PLUGIN_ABI_VERSION = 1
+PLUGINS_DIR_NAME = "plugins"
+HOST_MODULE = "remote_coder"
+WASI_MODULE_PREFIX = "wasi_"
+REQUIRED_EXPORTS = ("memory", "rc_alloc", "rc_manifest", "rc_run")
+DEFAULT_PLUGIN_TIMEOUT_SECS = 600
+# The epoch ticker advances once per interval; run deadlines are counted in ticks
+EPOCH_TICK_SECS = 1.0
+
+ToolCallFn = Callable[[str, Dict[str, Any]], str]
+
+
+class PluginError(Exception):
+    """Raised when a plugin cannot be loaded or fails during a call."""
+
+
+def pack_ptr_len(ptr: int, length: int) -> int:
+    return (ptr << 32) | length
+
+
+def unpack_ptr_len(value: int) -> tuple[int, int]:
+    value &= 0xFFFF_FFFF_FFFF_FFFF
+    return value >> 32, value & 0xFFFF_FFFF
+
+
+@dataclass(frozen=True)
+class PluginManifest:
+    name: str
+    abi_version: int
+    description: str = ""
+
+
+def _import_wasmtime() -> Any:
+    try:
+        import wasmtime
+    except ImportError as exc:
+        raise PluginError(
+            "The `wasmtime` package is required for WASM plugins; install remote-coder[wasm]"
+        ) from exc
+    return wasmtime
+
+
+class WasmPlugin:
+    """A compiled plugin module; each call runs in a fresh instance."""
+
+    def __init__(self, path: Path, engine: Any, module: Any, needs_wasi: bool) -> None:
+        self.path = path
+        self._engine = engine
+        self._module = module
+        self._needs_wasi = needs_wasi
+        self._ticker: Optional[threading.Thread] = None
+        self._ticker_lock = threading.Lock()
+        self.manifest = self._read_manifest()
+
+    @property
+    def name(self) -> str:
+        return self.manifest.name
+
+    @classmethod
+    def load(cls, path: Path) -> "WasmPlugin":
+        wasmtime = _import_wasmtime()
+        config = wasmtime.Config()
+        config.epoch_interruption = True
+        engine = wasmtime.Engine(config)
+        try:
+            module = wasmtime.Module.from_file(engine, str(path))
+        except wasmtime.WasmtimeError as exc:
+            raise PluginError(f"Failed to compile {path.name}: {exc}") from exc
+
+        exported = {export.name for export in module.exports}
+        missing = [name for name in REQUIRED_EXPORTS if name not in exported]
+        if missing:
+            raise PluginError(f"{path.name} does not export {', '.join(missing)}")
+        needs_wasi = any(item.module.startswith(WASI_MODULE_PREFIX) for item in module.imports)
+        return cls(path, engine, module, needs_wasi)
+
+    def run(self, request: Dict[str, Any], call_tool: ToolCallFn, timeout_secs: float) -> Dict[str, Any]:
+        """Call `rc_run` with `request`. Blocks; run it in a worker thread."""
+        instance = _PluginInstance(self, call_tool, timeout_secs)
+        response = instance.call_json("rc_run", request)
+        if not isinstance(response, dict):
+            raise PluginError(f"Plugin {self.name} returned a non-object response")
+        return response
+
+    def _read_manifest(self) -> PluginManifest:
+        instance = _PluginInstance(self, _no_tools, DEFAULT_PLUGIN_TIMEOUT_SECS)
+        raw = instance.call_json("rc_manifest")
+        if not isinstance(raw, dict):
+            raise PluginError(f"{self.path.name}: rc_manifest must return a JSON object")
+        abi_version = raw.get("abi_version")
+        if abi_version != PLUGIN_ABI_VERSION:
+            raise PluginError(
+                f"{self.path.name} targets plugin ABI {abi_version}, this daemon supports {PLUGIN_ABI_VERSION}"
+            )
+        return PluginManifest(
+            name=str(raw.get("name") or self.path.stem),
+            abi_version=abi_version,
+            description=str(raw.get("description") or ""),
+        )
+
+    def _ensure_ticker(self) -> None:
+        with self._ticker_lock:
+            if self._ticker and self._ticker.is_alive():
+                return
+            engine = self._engine
+
+            def _tick() -> None:
+                while True:
+                    time.sleep(EPOCH_TICK_SECS)
+                    engine.increment_epoch()
+
+            self._ticker = threading.Thread(target=_tick, name=f"wasm-epoch-{self.path.stem}", daemon=True)
+            self._ticker.start()
+
+    def _new_store(self, timeout_secs: float) -> Any:
+        wasmtime = _import_wasmtime()
+        self._ensure_ticker()
+        store = wasmtime.Store(self._engine)
+        store.set_epoch_deadline(max(1, int(timeout_secs / EPOCH_TICK_SECS)))
+        if self._needs_wasi:
+            store.set_wasi(wasmtime.WasiConfig())
+        return store
+
+
+def _no_tools(name: str, arguments: Dict[str, Any]) -> str:
+    return "Error: tools are not available while loading the plugin"
+
+
+class _PluginInstance:
+    """One instantiation of a plugin with the host imports bound to `call_tool`."""
+
+    def __init__(self, plugin: WasmPlugin, call_tool: ToolCallFn, timeout_secs: float) -> None:
+        wasmtime = _import_wasmtime()
+        self._wasmtime = wasmtime
+        self._plugin = plugin
+        self._call_tool = call_tool
+        self._store = plugin._new_store(timeout_secs)
+
+        linker = wasmtime.Linker(plugin._engine)
+        if plugin._needs_wasi:
+            linker.define_wasi()
+        i32, i64 = wasmtime.ValType.i32(), wasmtime.ValType.i64()
+        linker.define_func(HOST_MODULE, "log", wasmtime.FuncType([i32, i32], []), self._host_log, access_caller=True)
+        linker.define_func(
+            HOST_MODULE, "call_tool", wasmtime.FuncType([i32, i32], [i64]), self._host_call_tool, access_caller=True
+        )
+        try:
+            instance = linker.instantiate(self._store, plugin._module)
+        except (wasmtime.WasmtimeError, wasmtime.Trap) as exc:
+            raise PluginError(f"Failed to instantiate {plugin.path.name}: {exc}") from exc
+        exports = instance.exports(self._store)
+        self._memory = exports["memory"]
+        self._alloc = exports["rc_alloc"]
+        self._exports = exports
+
+    def call_json(self, export: str, payload: Any = None) -> Any:
+        wasmtime = self._wasmtime
+        try:
+            if payload is None:
+                packed = self._exports[export](self._store)
+            else:
+                ptr, length = self._write(self._store, json.dumps(payload))
+                packed = self._exports[export](self._store, ptr, length)
+            text = self._read(self._store, packed)
+        except (wasmtime.WasmtimeError, wasmtime.Trap) as exc:
+            raise PluginError(f"Plugin {self._plugin.path.name} failed in {export}: {exc}") from exc
+        try:
+            return json.loads(text)
+        except json.JSONDecodeError as exc:
+            raise PluginError(f"Plugin {self._plugin.path.name} returned invalid JSON from {export}") from exc
+
+    def _write(self, store: Any, text: str) -> tuple[int, int]:
+        data = text.encode("utf-8")
+        ptr = self._alloc(store, len(data))
+        self._memory.write(store, data, ptr)
+        return ptr, len(data)
+
+    def _read(self, store: Any, packed: int) -> str:
+        return self._read_raw(store, *unpack_ptr_len(packed))
+
+    def _read_raw(self, store: Any, ptr: int, length: int) -> str:
+        return bytes(self._memory.read(store, ptr, ptr + length)).decode("utf-8", errors="replace")
+
+    def _host_log(self, caller: Any, ptr: int, length: int) -> None:
+        LOGGER.info("[plugin %s] %s", self._plugin.path.stem, self._read_raw(caller, ptr, length))
+
+    def _host_call_tool(self, caller: Any, ptr: int, length: int) -> int:
+        try:
+            call = json.loads(self._read_raw(caller, ptr, length))
+        except json.JSONDecodeError:
+            call = None
+        if isinstance(call, dict) and isinstance(call.get("arguments", {}), dict):
+            output = self._call_tool(str(call.get("name") or ""), call.get("arguments") or {})
+        else:
+            output = 'Error: call_tool expects {"name": ..., "arguments": {...}}'
+        ptr, length = self._write(caller, json.dumps(output))
+        return pack_ptr_len(ptr, length)
+
+
+def discover_plugins(plugins_dir: Path) -> Dict[str, WasmPlugin]:
+    """Load every `*.wasm` file in `plugins_dir`, keyed by manifest name. Broken plugins are skipped."""
+    if not plugins_dir.is_dir():
+        return {}
+    paths = sorted(plugins_dir.glob("*.wasm"))
+    if not paths:
+        return {}
+    try:
+        _import_wasmtime()
+    except PluginError as exc:
+        LOGGER.warning("Ignoring %d plugin(s) in %s: %s", len(paths), plugins_dir, exc)
+        return {}
+
+    plugins: Dict[str, WasmPlugin] = {}
+    for path in paths:
+        try:
+            plugin = WasmPlugin.load(path)
+        except PluginError as exc:
+            LOGGER.warning("Skipping plugin %s: %s", path.name, exc)
+            continue
+        if plugin.name in plugins:
+            LOGGER.warning(
+                "Skipping plugin %s: name %s is already provided by %s",
+                path.name,
+                plugin.name,
+                plugins[plugin.name].path.name,
+            )
+            continue
+        LOGGER.info("Loaded WASM plugin %s from %s", plugin.name, path.name)
+        plugins[plugin.name] = plugin
+    return plugins
@@ -0,0 +1,167 @@
+"""Starting, interrupting, and killing processes on Windows.
+
+The process handling elsewhere assumes Unix; on Windows (`IS_WINDOWS`) this
+module stands in for the parts that differ:
+
+- Programs are found through PATHEXT. Agent CLIs installed with npm are
+  `.cmd` shims, which CreateProcess only runs through `cmd.exe`, where `&`,
+  `|`, or `%` in a prompt would be read as shell syntax. `windows_command`
+  therefore reads the shim and starts `node <script> ...` directly; other
+  `.cmd` and `.bat` files go through `cmd.exe` with every argument escaped
+  for it, and `.ps1` scripts through `powershell -File`.
+- There is no SIGINT to send to another process. Processes start in their
+  own process group (CREATE_NEW_PROCESS_GROUP), so the soft timeout sends
+  CTRL_BREAK_EVENT instead, which Node and Python CLIs treat like Ctrl+C.
+- Killing a process does not kill what it started, and there are no process
+  groups to kill; `kill_process_tree` runs `taskkill /T /F` on the tree.
+- There are no pseudo-terminals (`spawn_mode: pty` falls back to pipes), no
+  rlimits (an agent's `limits` are ignored with a warning), and no
+  bubblewrap; container sandboxes work with Docker Desktop.
+- Paths in the config may be written Git Bash style (`/c/Users/me/code`);
+  `native_path` turns them into `C:\\Users\\me\\code`.
+"""
+
+from __future__ import annotations
+
+import asyncio
+import logging
+import os
+import re
+import shutil
+import signal
+import subprocess
+import sys
+from pathlib import Path
+from typing import Any, Callable, List, Optional, Sequence, Union
+
+LOGGER = logging.getLogger(__name__)
+
+IS_WINDOWS = sys.platform == "win32"
+
+# The script an npm (cmd-shim) `.cmd` file runs with node: "%_prog%"  "%dp0%\node_modules\...\cli.js" %*
+_NPM_SHIM_SCRIPT = re.compile(r'"%~?dp0%?\\([^"]+\.[cm]?js)"\s+%\*')
+# Shims in `node_modules\.bin` pass `%*` through `cmd.exe` a second time
+_NPM_BIN_SHIM = re.compile(r"node_modules[\\/]\.bin[\\/][^\\/]+\.cmd$", re.IGNORECASE)
+# Characters `cmd.exe` gives a meaning to, escaped with `^`
+_CMD_META = re.compile(r'([()\][%!^"`<>&|;, *?])')
+_MSYS_DRIVE = re.compile(r"^/([a-zA-Z])(?:/|$)")
+BATCH_SUFFIXES = (".cmd", ".bat")
+POWERSHELL_SUFFIX = ".ps1"
+PROCESS_QUERY_LIMITED_INFORMATION = 0x1000
+STILL_ACTIVE = 259
+
+# Either an argv for `create_subprocess_exec` or a command line for `cmd.exe` (`create_subprocess_shell`)
+WindowsCommand = Union[List[str], str]
+
+
+def native_path(raw: str) -> str:
+    """`raw` with a Git Bash drive prefix (`/c/...`) turned into a Windows one (`C:\\...`) on Windows."""
+    if not IS_WINDOWS:
+        return raw
+    match = _MSYS_DRIVE.match(raw)
+    if match is None:
+        return raw
+    return f"{match.group(1).upper()}:\\{raw[match.end():]}".replace("/", "\\")
+
+
+def interrupt_signal() -> int:
+    """The signal that asks a CLI to stop like Ctrl+C would."""
+    return signal.CTRL_BREAK_EVENT if IS_WINDOWS else signal.SIGINT  # type: ignore[attr-defined]
+
+
+def windows_command(command: Sequence[str], *, which: Callable[[str], Optional[str]] = shutil.which) -> WindowsCommand:
+    """How to start `command` on Windows without `cmd.exe` reading its arguments, when that can be avoided."""
+    if not command:
+        return list(command)
+    program, *args = command
+    found = which(program)
+    if found is None:
+        return list(command)  # Let CreateProcess report the missing program
+    suffix = Path(found).suffix.lower()
+    if suffix == POWERSHELL_SUFFIX:
+        return ["powershell.exe", "-NoProfile", "-ExecutionPolicy", "Bypass", "-File", found, *args]
+    if suffix not in BATCH_SUFFIXES:
+        return [found, *args]
+    script = _npm_shim_script(Path(found))
+    if script is not None:
+        local_node = Path(found).parent / "node.exe"
+        node = str(local_node) if local_node.is_file() else which("node") or "node"
+        return [node, str(script), *args]
+    twice = _NPM_BIN_SHIM.search(found) is not None
+    return " ".join(_cmd_escape(part, twice=twice) for part in [found, *args])
+
+
+def _npm_shim_script(shim: Path) -> Optional[Path]:
+    try:
+        text = shim.read_text(encoding="utf-8", errors="replace")
+    except OSError:
+        return None
+    match = _NPM_SHIM_SCRIPT.search(text)
+    if match is None:
+        return None
+    script = shim.parent / match.group(1).replace("\\", os.sep)
+    return script if script.is_file() else None
+
+
+def _cmd_escape(arg: str, *, twice: bool = False) -> str:
+    """`arg` quoted for the program (MSVC rules) and then escaped for `cmd.exe`."""
+    # Backslashes before a quote, and at the end before the closing quote, are doubled
+    arg = re.sub(r'(\\*)"', r'\1\1\\"', arg)
+    arg = re.sub(r"(\\*)$", r"\1\1", arg)
+    arg = _CMD_META.sub(r"^\1", f'"{arg}"')
+    return _CMD_META.sub(r"^\1", arg) if twice else arg
+
+
+async def create_process(*command: str, **options: Any) -> asyncio.subprocess.Process:
+    """`asyncio.create_subprocess_exec`, with the program started as `windows_command` says on Windows."""
+    if not IS_WINDOWS:
+        return await asyncio.create_subprocess_exec(*command, **options)
+    options.pop("preexec_fn", None)
+    options.pop("start_new_session", None)
+    group = subprocess.CREATE_NEW_PROCESS_GROUP  # type: ignore[attr-defined]
+    options["creationflags"] = options.get("creationflags", 0) | group
+    prepared = windows_command(command)
+    if isinstance(prepared, str):
+        return await asyncio.create_subprocess_shell(prepared, **options)
+    return await asyncio.create_subprocess_exec(*prepared, **options)
+
+
+async def kill_process_tree(process: asyncio.subprocess.Process) -> None:
+    """Kill `process` and everything it started (only needed on Windows; Unix kills process groups)."""
+    if process.returncode is not None:
+        return
+    try:
+        killer = await asyncio.create_subprocess_exec(
+            "taskkill",
+            "/T",
+            "/F",
+            "/PID",
+            str(process.pid),
+            stdout=asyncio.subprocess.DEVNULL,
+            stderr=asyncio.subprocess.DEVNULL,
+        )
+        await killer.wait()
+    except OSError as exc:
+        LOGGER.warning("Could not run taskkill for process %s: %s", process.pid, exc)
+    if process.returncode is None:
+        try:
+            process.kill()
+        except ProcessLookupError:
+            pass
+
+
+def process_exists(pid: int) -> bool:
+    """Whether a process with `pid` is running, without `os.kill(pid, 0)`, which terminates it on Windows."""
+    import ctypes
+
+    kernel32 = ctypes.windll.kernel32  # type: ignore[attr-defined]
+    handle = kernel32.OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, False, pid)
+    if not handle:
+        return False
+    try:
+        code = ctypes.c_ulong()
+        if not kernel32.GetExitCodeProcess(handle, ctypes.byref(code)):
+            return False
+        return code.value == STILL_ACTIVE
+    finally:
+        kernel32.CloseHandle(handle)
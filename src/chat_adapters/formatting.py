@@ -0,0 +1,65 @@
+"""Clean up agent output for the chat it is posted to.
+
+Agent CLIs color their output with ANSI escape codes and write Markdown.
+Escape codes show up as garbage in every chat, so `strip_ansi` removes them
+(and other control characters). Slack does not render Markdown but its own
+mrkdwn, so `to_mrkdwn` converts what agents commonly use: `**bold**`,
+`~~strike~~`, `# headings` (bold lines), `[links](url)`, and `*`/`+`
+bullets, and drops the language tag of code fences, which Slack would
+show as text. Code stays as it is. Single `*stars*` are left alone,
+since Slack reads them as bold, which is how Remote Coder's own messages
+use them. GitHub renders Markdown, so its adapter only strips escapes.
+"""
+
+from __future__ import annotations
+
+import re
+
+from ..core.output_buffer import visible_line
+
+# CSI sequences (colors, cursor moves), OSC sequences (window titles, links), and two-byte escapes
+ANSI_ESCAPE = re.compile(r"\x1b\[[0-?]*[ -/]*[@-~]|\x1b\][^\x07\x1b]*(?:\x07|\x1b\\)|\x1b[@-Z\\-_]")
+# Control characters other than tab and newline
+_CONTROL = re.compile(r"[\x00-\x08\x0b\x0c\x0e-\x1f\x7f]")
+
+# Fenced blocks and inline code, which are passed through unchanged
+_CODE = re.compile(r"```.*?(?:```|\Z)|`[^`\n]+`", re.S)
+_FENCE_LANGUAGE = re.compile(r"\A```[\w+#.-]+\n")
+
+_BOLD = re.compile(r"\*\*(?=\S)(.+?)(?<=\S)\*\*")
+_STRIKE = re.compile(r"~~(?=\S)(.+?)(?<=\S)~~")
+_HEADING = re.compile(r"^[ ]{0,3}#{1,6}[ \t]+(.+?)[ \t]*#*[ \t]*$", re.M)
+_LINK = re.compile(r"!?\[([^\]\n]+)\]\((https?://[^\s)]+|mailto:[^\s)]+)\)")
+_BULLET = re.compile(r"^([ \t]*)[*+][ \t]+", re.M)
+
+
+def strip_ansi(text: str) -> str:
+    """`text` without escape sequences and control characters; lines redrawn after a `\\r` show their last state."""
+    text = ANSI_ESCAPE.sub("", text).replace("\r\n", "\n")
+    if "\r" in text:
+        text = "\n".join(visible_line(line) if "\r" in line else line for line in text.split("\n"))
+    return _CONTROL.sub("", text)
+
+
+def _prose_to_mrkdwn(text: str) -> str:
+    text = _HEADING.sub(lambda match: f"*{match.group(1).strip('*')}*", text)
+    text = _BOLD.sub(r"*\1*", text)
+    text = _STRIKE.sub(r"~\1~", text)
+    text = _LINK.sub(r"<\2|\1>", text)
+    return _BULLET.sub(r"\1• ", text)
+
+
+def to_mrkdwn(text: str) -> str:
+    """Markdown as Slack mrkdwn, leaving code blocks and inline code untouched."""
+    parts = []
+    position = 0
+    for match in _CODE.finditer(text):
+        parts.append(_prose_to_mrkdwn(text[position : match.start()]))
+        parts.append(_FENCE_LANGUAGE.sub("```\n", match.group(0)))
+        position = match.end()
+    parts.append(_prose_to_mrkdwn(text[position:]))
+    return "".join(parts)
+
+
+def format_for_slack(text: str) -> str:
+    return to_mrkdwn(strip_ansi(text))
@@ -0,0 +1,266 @@
+"""GitHub Discussions adapter: design conversations in a repository's discussions.
+
+Projects with `discussions:` in projects.yaml (and a `github` section) are
+polled every `REMOTE_CODER_DISCUSSIONS_POLL_SECS` through the GraphQL API.
+A discussion opened by one of `REMOTE_CODER_GITHUB_ALLOWED_USERS` (in the
+configured category, if any) starts a session whose channel is
+`github:<owner>/<repo>` and whose thread is the discussion number; its
+comments and their replies are the session's messages, and the bot answers
+with comments of its own. Only activity after the daemon started is read.
+What such a session does differs from a Slack one; see
+`src/core/discussions.py`.
+"""
+
+from __future__ import annotations
+
+import asyncio
+import logging
+from datetime import datetime, timezone
+from typing import Any, Dict, List, Optional, Set, Tuple
+
+from .formatting import strip_ansi
+from .i_chat_adapter import ChatCapabilities, IChatAdapter
+from ..core.config import Config
+from ..core.discussions import DISCUSSION_CHANNEL_PREFIX, discussion_channel, is_discussion_channel
+from ..core.errors import GitHubError, SlackError
+from ..core.models import Project
+from ..core.router import Router
+from ..github import GitHubManager
+
+LOGGER = logging.getLogger(__name__)
+
+# GitHub rejects comment bodies longer than this
+MAX_COMMENT_CHARS = 65_000
+DISCUSSIONS_PER_POLL = 25
+COMMENTS_PER_DISCUSSION = 50
+REPLIES_PER_COMMENT = 20
+
+DISCUSSIONS_QUERY = """
+query($owner: String!, $repo: String!, $discussions: Int!, $comments: Int!, $replies: Int!) {
+  repository(owner: $owner, name: $repo) {
+    discussions(first: $discussions, orderBy: {field: UPDATED_AT, direction: DESC}) {
+      nodes {
+        id
+        number
+        title
+        body
+        url
+        createdAt
+        author { login }
+        category { name }
+        comments(last: $comments) {
+          nodes {
+            id
+            body
+            createdAt
+            author { login }
+            replies(last: $replies) {
+              nodes { id body createdAt author { login } }
+            }
+          }
+        }
+      }
+    }
+  }
+}
+"""
+DISCUSSION_ID_QUERY = """
+query($owner: String!, $repo: String!, $number: Int!) {
+  repository(owner: $owner, name: $repo) { discussion(number: $number) { id } }
+}
+"""
+ADD_COMMENT_MUTATION = """
+mutation($discussion: ID!, $body: String!) {
+  addDiscussionComment(input: {discussionId: $discussion, body: $body}) { comment { id } }
+}
+"""
+UPDATE_COMMENT_MUTATION = """
+mutation($comment: ID!, $body: String!) {
+  updateDiscussionComment(input: {commentId: $comment, body: $body}) { comment { id } }
+}
+"""
+
+
+def _timestamp(moment: datetime) -> str:
+    """GitHub's ISO 8601 format, which sorts like the moments it stands for."""
+    return moment.astimezone(timezone.utc).strftime("%Y-%m-%dT%H:%M:%SZ")
+
+
+def _login(node: Dict[str, Any]) -> str:
+    return str((node.get("author") or {}).get("login") or "")
+
+
+def _clip(text: str) -> str:
+    return text if len(text) <= MAX_COMMENT_CHARS else text[: MAX_COMMENT_CHARS - 1] + "…"
+
+
+class GitHubDiscussionsAdapter(IChatAdapter):
+    """Polls project discussions and hands new posts to the router as messages."""
+
+    # Comments have no buttons or attachments; replies to a discussion are its comments
+    capabilities = ChatCapabilities(max_message_chars=MAX_COMMENT_CHARS)
+
+    def __init__(
+        self,
+        github_manager: GitHubManager,
+        router: Router,
+        config: Config,
+        *,
+        started_at: Optional[datetime] = None,
+    ) -> None:
+        self._github = github_manager
+        self._router = router
+        self._config = config
+        self._since = _timestamp(started_at or datetime.now(timezone.utc))
+        self._stop_event = asyncio.Event()
+        # Node ids of discussions, comments, and replies already handled (including the bot's own comments)
+        self._seen: Set[str] = set()
+        # (channel, discussion number) -> discussion node id, for posting comments
+        self._discussion_ids: Dict[Tuple[str, str], str] = {}
+        self._tasks: Set[asyncio.Task] = set()
+
+    def update_config(self, config: Config) -> None:
+        """Pick up projects and allowed users after `!reload-projects`."""
+        self._config = config
+
+    def projects(self) -> List[Project]:
+        """Projects whose discussions are polled, each once."""
+        unique = {project.id: project for project in self._config.projects.values()}
+        return [project for project in unique.values() if project.discussions and project.github]
+
+    async def start(self) -> None:
+        LOGGER.info("Polling GitHub Discussions of %d project(s)", len(self.projects()))
+        while not self._stop_event.is_set():
+            await self.poll()
+            try:
+                await asyncio.wait_for(self._stop_event.wait(), timeout=self._config.discussions_poll_secs)
+            except asyncio.TimeoutError:
+                pass
+
+    async def stop(self) -> None:
+        self._stop_event.set()
+        for task in list(self._tasks):
+            task.cancel()
+        await self.wait_idle()
+
+    async def poll(self) -> None:
+        """Hand every post made since the last poll to the router.
+
+        Each discussion's posts are handled in order in a task of their own, so an agent run in one
+        discussion does not hold up the others (or a `!cancel` posted while it runs).
+        """
+        for project in self.projects():
+            try:
+                events = await self._project_events(project)
+            except GitHubError as exc:
+                LOGGER.warning("Cannot read the discussions of %s: %s", project.id, exc)
+                continue
+            threads: Dict[str, List[Dict[str, Any]]] = {}
+            for event in events:
+                threads.setdefault(event.get("thread_ts") or event["ts"], []).append(event)
+            for thread_events in threads.values():
+                task = asyncio.create_task(self._handle_in_order(thread_events))
+                self._tasks.add(task)
+                task.add_done_callback(self._tasks.discard)
+
+    async def wait_idle(self) -> None:
+        """Wait until the posts handed to the router so far are handled."""
+        while self._tasks:
+            await asyncio.gather(*list(self._tasks), return_exceptions=True)
+
+    async def _handle_in_order(self, events: List[Dict[str, Any]]) -> None:
+        for event in events:
+            try:
+                await self._router.handle_message(event)
+            except Exception:
+                LOGGER.exception("Failed to handle discussion post %s in %s", event.get("ts"), event.get("channel"))
+
+    async def _project_events(self, project: Project) -> List[Dict[str, Any]]:
+        assert project.github and project.discussions
+        data = await self._github.graphql(
+            DISCUSSIONS_QUERY,
+            {
+                "owner": project.github.owner,
+                "repo": project.github.repo,
+                "discussions": DISCUSSIONS_PER_POLL,
+                "comments": COMMENTS_PER_DISCUSSION,
+                "replies": REPLIES_PER_COMMENT,
+            },
+        )
+        channel = discussion_channel(project.github.full_name)
+        allowed = set(self._config.github_allowed_users)
+        category = project.discussions.category
+        events: List[Tuple[str, Dict[str, Any]]] = []
+        for discussion in ((data.get("repository") or {}).get("discussions") or {}).get("nodes") or []:
+            if category and ((discussion.get("category") or {}).get("name") or "").lower() != category.lower():
+                continue
+            number = str(discussion["number"])
+            # Comments by allowed users on a stranger's discussion would otherwise run that stranger's request
+            author = _login(discussion)
+            if author not in allowed:
+                LOGGER.debug("Ignoring discussion #%s by %s in %s", number, author or "unknown", channel)
+                continue
+            self._discussion_ids[(channel, number)] = discussion["id"]
+            base = {"channel": channel, "channel_name": project.channel_name, "discussion_url": discussion.get("url")}
+            opening = f"{discussion.get('title') or ''}\n\n{discussion.get('body') or ''}"
+            posts = [(discussion, {"ts": number, "text": opening})]
+            for comment in (discussion.get("comments") or {}).get("nodes") or []:
+                posts.append((comment, {"ts": comment["id"], "thread_ts": number, "text": comment.get("body") or ""}))
+                for reply in (comment.get("replies") or {}).get("nodes") or []:
+                    posts.append((reply, {"ts": reply["id"], "thread_ts": number, "text": reply.get("body") or ""}))
+            for node, fields in posts:
+                if node["id"] in self._seen or str(node.get("createdAt") or "") <= self._since:
+                    continue
+                self._seen.add(node["id"])
+                login = _login(node)
+                if login not in allowed:
+                    LOGGER.debug("Ignoring discussion post by %s in %s", login or "unknown", channel)
+                    continue
+                event = {**base, **fields, "user": login, "text": fields["text"].strip()}
+                events.append((str(node["createdAt"]), event))
+        return [event for _, event in sorted(events, key=lambda item: item[0])]
+
+    async def send_message(self, channel: str, thread_ts: Optional[str], text: str) -> Optional[str]:
+        """Comment on discussion `thread_ts`; returns the comment's node id.
+
+        Failures raise SlackError, the chat error type the router and output stream handle.
+        """
+        if not is_discussion_channel(channel) or not thread_ts:
+            LOGGER.warning("Cannot post outside a discussion thread (%s, %s); dropping: %s", channel, thread_ts, text)
+            return None
+        try:
+            discussion_id = await self._discussion_id(channel, thread_ts)
+            body = _clip(strip_ansi(text))
+            data = await self._github.graphql(ADD_COMMENT_MUTATION, {"discussion": discussion_id, "body": body})
+        except GitHubError as exc:
+            raise SlackError(f"Failed to comment on discussion {channel}#{thread_ts}: {exc}") from exc
+        comment_id = ((data.get("addDiscussionComment") or {}).get("comment") or {}).get("id")
+        if comment_id:
+            self._seen.add(comment_id)
+        return comment_id
+
+    def thread_link(self, channel: str, thread_ts: str) -> Optional[str]:
+        """The discussion's web page."""
+        if not is_discussion_channel(channel):
+            return None
+        return f"https://github.com/{channel[len(DISCUSSION_CHANNEL_PREFIX) :]}/discussions/{thread_ts}"
+
+    async def update_message(self, channel: str, ts: str, text: str) -> bool:
+        """Edit a comment the bot posted."""
+        try:
+            await self._github.graphql(UPDATE_COMMENT_MUTATION, {"comment": ts, "body": _clip(strip_ansi(text))})
+        except GitHubError as exc:
+            raise SlackError(f"Failed to edit discussion comment {ts}: {exc}") from exc
+        return True
+
+    async def _discussion_id(self, channel: str, number: str) -> str:
+        cached = self._discussion_ids.get((channel, number))
+        if cached:
+            return cached
+        owner, _, repo = channel[len(DISCUSSION_CHANNEL_PREFIX) :].partition("/")
+        data = await self._github.graphql(DISCUSSION_ID_QUERY, {"owner": owner, "repo": repo, "number": int(number)})
+        discussion = (data.get("repository") or {}).get("discussion") or {}
+        if not discussion.get("id"):
+            raise GitHubError(f"discussion {number} not found")
+        self._discussion_ids[(channel, number)] = discussion["id"]
+        return discussion["id"]
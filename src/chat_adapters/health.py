@@ -0,0 +1,254 @@
+"""Connection health tracking for chat adapters.
+
+Adapters record socket connects/disconnects and incoming event timestamps in a
+`ConnectionHealth`; `!status --system` renders a snapshot of it, and
+`DisconnectWatchdog` alerts operators when the connection stays down for too
+long (the bot cannot tell anyone through the chat socket while it is down, so
+alerts go out as DMs over the Web API and/or to a webhook).
+"""
+
+from __future__ import annotations
+
+import asyncio
+import logging
+import time
+from collections import deque
+from dataclasses import dataclass
+from typing import Awaitable, Callable, Deque, Optional
+
+import aiohttp
+
+LOGGER = logging.getLogger(__name__)
+
+DEFAULT_DISCONNECT_ALERT_SECS = 300
+DEFAULT_WATCHDOG_INTERVAL_SECS = 10
+# Number of recent events used for the average event lag
+EVENT_LAG_WINDOW = 100
+WEBHOOK_TIMEOUT_SECS = 10
+
+Clock = Callable[[], float]
+AlertFn = Callable[[str], Awaitable[None]]
+ProbeFn = Callable[[], Awaitable[bool]]
+
+
+@dataclass(frozen=True)
+class ConnectionHealthSnapshot:
+    """Point-in-time view of a chat adapter connection."""
+
+    connected: bool
+    started_at: float
+    connected_since: Optional[float]
+    disconnected_since: Optional[float]
+    connects: int
+    disconnects: int
+    reconnect_attempts: int
+    failed_connects: int
+    events_received: int
+    last_event_at: Optional[float]
+    last_event_lag_secs: Optional[float]
+    avg_event_lag_secs: Optional[float]
+    max_event_lag_secs: Optional[float]
+
+
+class ConnectionHealth:
+    """Accumulates connection metrics reported by a chat adapter."""
+
+    def __init__(self, clock: Clock = time.time) -> None:
+        self._clock = clock
+        self._started_at = clock()
+        self._connected = False
+        self._connected_since: Optional[float] = None
+        # Counted from startup so a bot that never connects still shows as down
+        self._disconnected_since: Optional[float] = self._started_at
+        self._connects = 0
+        self._disconnects = 0
+        self._connect_attempts = 0
+        self._failed_connects = 0
+        self._events_received = 0
+        self._last_event_at: Optional[float] = None
+        self._lags: Deque[float] = deque(maxlen=EVENT_LAG_WINDOW)
+        self._max_lag: Optional[float] = None
+
+    @property
+    def connected(self) -> bool:
+        return self._connected
+
+    def record_connect_attempt(self) -> None:
+        self._connect_attempts += 1
+
+    def record_connect_failed(self) -> None:
+        self._failed_connects += 1
+
+    def record_connected(self) -> None:
+        if self._connected:
+            return
+        self._connected = True
+        self._connects += 1
+        self._connected_since = self._clock()
+        self._disconnected_since = None
+
+    def record_disconnected(self) -> None:
+        if not self._connected:
+            return
+        self._connected = False
+        self._disconnects += 1
+        self._connected_since = None
+        self._disconnected_since = self._clock()
+
+    def record_event(self, event_time: Optional[float]) -> None:
+        """Record an incoming event; `event_time` is when the platform generated it (epoch seconds)."""
+        now = self._clock()
+        self._events_received += 1
+        self._last_event_at = now
+        if event_time is None:
+            return
+        lag = max(0.0, now - float(event_time))
+        self._lags.append(lag)
+        self._max_lag = lag if self._max_lag is None else max(self._max_lag, lag)
+
+    def disconnected_for(self) -> Optional[float]:
+        """Seconds since the connection went down, or None while connected."""
+        if self._connected or self._disconnected_since is None:
+            return None
+        return self._clock() - self._disconnected_since
+
+    def snapshot(self) -> ConnectionHealthSnapshot:
+        return ConnectionHealthSnapshot(
+            connected=self._connected,
+            started_at=self._started_at,
+            connected_since=self._connected_since,
+            disconnected_since=self._disconnected_since,
+            connects=self._connects,
+            disconnects=self._disconnects,
+            # Every attempt after the first is the adapter trying to get back online
+            reconnect_attempts=max(0, self._connect_attempts - 1),
+            failed_connects=self._failed_connects,
+            events_received=self._events_received,
+            last_event_at=self._last_event_at,
+            last_event_lag_secs=self._lags[-1] if self._lags else None,
+            avg_event_lag_secs=sum(self._lags) / len(self._lags) if self._lags else None,
+            max_event_lag_secs=self._max_lag,
+        )
+
+
+def format_duration(seconds: float) -> str:
+    seconds = int(max(0, seconds))
+    days, rest = divmod(seconds, 86400)
+    hours, rest = divmod(rest, 3600)
+    minutes, secs = divmod(rest, 60)
+    if days:
+        return f"{days}d {hours}h {minutes}m"
+    if hours:
+        return f"{hours}h {minutes}m"
+    if minutes:
+        return f"{minutes}m {secs}s"
+    return f"{secs}s"
+
+
+def format_connection_health(snapshot: ConnectionHealthSnapshot, now: Optional[float] = None) -> str:
+    """Render a snapshot for `!status --system`."""
+    now = time.time() if now is None else now
+    if snapshot.connected and snapshot.connected_since is not None:
+        state = f"connected for {format_duration(now - snapshot.connected_since)}"
+    elif snapshot.disconnected_since is not None:
+        state = f"DISCONNECTED for {format_duration(now - snapshot.disconnected_since)}"
+    else:
+        state = "disconnected"
+
+    lines = [
+        f"Uptime: {format_duration(now - snapshot.started_at)}",
+        f"Chat connection: {state}",
+        (
+            f"Connects: {snapshot.connects}, disconnects: {snapshot.disconnects}, "
+            f"reconnect attempts: {snapshot.reconnect_attempts} ({snapshot.failed_connects} failed)"
+        ),
+    ]
+    if snapshot.last_event_at is None:
+        lines.append("Events received: 0")
+    else:
+        lines.append(
+            f"Events received: {snapshot.events_received} "
+            f"(last {format_duration(now - snapshot.last_event_at)} ago)"
+        )
+    if snapshot.avg_event_lag_secs is not None:
+        lines.append(
+            f"Event lag: last {snapshot.last_event_lag_secs:.1f}s, "
+            f"avg {snapshot.avg_event_lag_secs:.1f}s, max {snapshot.max_event_lag_secs:.1f}s"
+        )
+    return "\n".join(lines)
+
+
+class DisconnectWatchdog:
+    """Polls the connection and alerts once it has been down longer than a threshold."""
+
+    def __init__(
+        self,
+        health: ConnectionHealth,
+        *,
+        probe: ProbeFn,
+        alert: AlertFn,
+        threshold_secs: float,
+        interval_secs: float = DEFAULT_WATCHDOG_INTERVAL_SECS,
+    ) -> None:
+        self._health = health
+        self._probe = probe
+        self._alert = alert
+        self._threshold_secs = threshold_secs
+        self._interval_secs = interval_secs
+        self._alerted = False
+
+    async def check(self) -> None:
+        """Run one probe and send an alert or recovery notice if the state warrants it."""
+        try:
+            connected = await self._probe()
+        except Exception:  # pragma: no cover - defensive
+            LOGGER.debug("Connection probe failed", exc_info=True)
+            connected = False
+
+        if connected:
+            self._health.record_connected()
+            if self._alerted:
+                self._alerted = False
+                await self._send("Remote Coder reconnected to chat.")
+            return
+
+        self._health.record_disconnected()
+        down_for = self._health.disconnected_for()
+        if self._alerted or down_for is None or down_for < self._threshold_secs:
+            return
+        self._alerted = True
+        LOGGER.warning("Chat connection has been down for %s", format_duration(down_for))
+        await self._send(
+            f"Remote Coder has been disconnected from chat for {format_duration(down_for)}. "
+            "It keeps retrying; check the daemon logs and network if this persists."
+        )
+
+    async def run(self, stop_event: asyncio.Event) -> None:
+        while not stop_event.is_set():
+            await self.check()
+            try:
+                await asyncio.wait_for(stop_event.wait(), timeout=self._interval_secs)
+            except asyncio.TimeoutError:
+                continue
+
+    async def _send(self, text: str) -> None:
+        try:
+            await self._alert(text)
+        except Exception:
+            LOGGER.warning("Failed to deliver connection alert", exc_info=True)
+
+
+async def post_webhook_alert(url: str, text: str) -> None:
+    """POST `{"text": ...}` to a webhook (the format Slack/Mattermost incoming webhooks expect).
+
+    Raises EgressBlocked in strict privacy mode.
+    """
+    from ..core.privacy import PRIVACY, Egress
+
+    PRIVACY.require(Egress.ALERT_WEBHOOK)
+    timeout = aiohttp.ClientTimeout(total=WEBHOOK_TIMEOUT_SECS)
+    async with aiohttp.ClientSession(timeout=timeout) as http:
+        async with http.post(url, json={"text": text}) as response:
+            if response.status >= 400:
+                body = await response.text()
+                raise ValueError(f"Webhook returned HTTP {response.status}: {body[:200]}")
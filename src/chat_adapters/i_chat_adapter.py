@@ -3,17 +3,48 @@
 from __future__ import annotations
 
 import abc
-from typing import Optional
+from dataclasses import dataclass
+from typing import Any, Optional
+
+
+@dataclass(frozen=True)
+class ChatCapabilities:
+    """What a chat platform can show, consulted by the router before it renders or posts.
+
+    Messages longer than `max_message_chars` are split into several; without
+    threads replies are posted top-level. Buttons gate collapsed diffs,
+    confirmation prompts, and run summaries, file upload gates `!patch` and
+    diff snippets.
+    """
+
+    max_message_chars: int = 4000
+    supports_threads: bool = True
+    supports_buttons: bool = False
+    supports_file_upload: bool = False
+
+
+def capabilities_of(adapter: Any) -> ChatCapabilities:
+    """The adapter's `capabilities`, or ones guessed from its optional methods for adapters without them."""
+    capabilities = getattr(adapter, "capabilities", None)
+    if isinstance(capabilities, ChatCapabilities):
+        return capabilities
+    return ChatCapabilities(
+        supports_buttons=hasattr(adapter, "send_diff") or hasattr(adapter, "send_confirmation"),
+        supports_file_upload=hasattr(adapter, "upload_file"),
+    )
 
 
 class IChatAdapter(abc.ABC):
     """Abstraction for chat platform integrations (Slack, Discord, etc.)."""
 
+    # Override with the platform's limits; the defaults fit a plain text chat with threads
+    capabilities: ChatCapabilities = ChatCapabilities()
+
     @abc.abstractmethod
     async def send_message(
-        self, channel: str, thread_ts: str, text: str
+        self, channel: str, thread_ts: Optional[str], text: str
     ) -> Optional[str]:
-        """Send a message to a channel/thread.
+        """Send a message to a channel/thread (a new top-level message when `thread_ts` is None).
 
         Returns:
             The message timestamp/ID if available, None otherwise.
@@ -3,68 +3,350 @@
 from __future__ import annotations
 
 import asyncio
+import json
 import logging
-from typing import Any, Dict, Optional
+import uuid
+from collections import OrderedDict
+from typing import Any, Dict, Optional, Sequence, Tuple
 
+import aiohttp
 from slack_sdk.errors import SlackApiError
 from slack_sdk.socket_mode.aiohttp import SocketModeClient
 from slack_sdk.socket_mode.request import SocketModeRequest
 from slack_sdk.socket_mode.response import SocketModeResponse
 from slack_sdk.web.async_client import AsyncWebClient
 
-from .i_chat_adapter import IChatAdapter
+from .health import (
+    DEFAULT_DISCONNECT_ALERT_SECS,
+    ConnectionHealth,
+    ConnectionHealthSnapshot,
+    DisconnectWatchdog,
+    post_webhook_alert,
+)
+from .formatting import format_for_slack
+from .i_chat_adapter import ChatCapabilities, IChatAdapter
+from ..core.commands.hints import (
+    CommandHint,
+    command_text_from_slash,
+    merge_slash_commands,
+    slack_slash_commands,
+)
+from ..core.diff_render import FileDiff, format_diff_overview, snippet_filename
 from ..core.errors import SlackError
+from ..core.privacy import PRIVACY, Egress
+from ..core.resources import BoundedCache
 from ..core.router import Router
+from ..core.run_summary import NextStep
 
 LOGGER = logging.getLogger(__name__)
 
+# Uploaded files larger than this are not downloaded for commands like !apply
+MAX_DOWNLOAD_BYTES = 1_000_000
+# Slack allows 50 blocks per message; leave room for the overview and overflow note
+MAX_DIFF_FILE_BLOCKS = 45
+# Expandable file diffs kept in memory; older buttons stop working
+DIFF_CACHE_SIZE = 500
+DIFF_EXPAND_ACTION_ID = "remote_coder_diff_expand"
+CONFIRM_APPROVE_ACTION_ID = "remote_coder_confirm_approve"
+CONFIRM_DENY_ACTION_ID = "remote_coder_confirm_deny"
+NEXT_STEP_ACTION_ID = "remote_coder_next_step"
+OPEN_LINK_ACTION_ID = "remote_coder_open_link"
+
+
+class _TrackedSocketModeClient(SocketModeClient):
+    """Socket Mode client that reports connection attempts to a `ConnectionHealth`.
+
+    The SDK reconnects by calling `connect()` again, so every call after the
+    first one is a reconnection attempt.
+    """
+
+    def __init__(self, *args: Any, health: ConnectionHealth, **kwargs: Any) -> None:
+        super().__init__(*args, **kwargs)
+        self.health = health
+
+    async def connect(self) -> None:
+        self.health.record_connect_attempt()
+        try:
+            await super().connect()
+        except Exception:
+            self.health.record_connect_failed()
+            raise
+        self.health.record_connected()
+
 
 class SlackAdapter(IChatAdapter):
+    # Slack truncates messages past 40,000 characters but recommends keeping them under 4,000
+    capabilities = ChatCapabilities(max_message_chars=4000, supports_buttons=True, supports_file_upload=True)
+
     def __init__(
         self,
         bot_token: str,
         app_token: str,
         allowed_user_ids: list[str],
         router: Router,
+        *,
+        alert_user_ids: Optional[list[str]] = None,
+        alert_webhook_url: Optional[str] = None,
+        disconnect_alert_secs: int = DEFAULT_DISCONNECT_ALERT_SECS,
+        cache_size: int = 0,
+        app_id: Optional[str] = None,
+        config_token: Optional[str] = None,
     ) -> None:
+        """`cache_size` bounds the channel-name and diff caches (0 keeps the defaults).
+
+        `app_id` and `config_token` (an app configuration token) let `register_commands` update the app's
+        slash commands; without them commands are only registered by pasting `remote-coder commands`.
+        """
+        self._bot_token = bot_token
         self._web_client = AsyncWebClient(token=bot_token)
-        self._client = SocketModeClient(app_token=app_token, web_client=self._web_client)
+        self._health = ConnectionHealth()
+        self._client = _TrackedSocketModeClient(
+            app_token=app_token, web_client=self._web_client, health=self._health
+        )
         self._router = router
         self._allowed_user_ids = allowed_user_ids
+        self._alert_user_ids = list(alert_user_ids or [])
+        self._alert_webhook_url = alert_webhook_url
+        self._app_id = app_id
+        self._config_token = config_token
         self._stop_event = asyncio.Event()
-        self._channel_name_cache: Dict[str, str] = {}
+        self._channel_name_cache: BoundedCache[str, str] = BoundedCache(cache_size)
+        # User id -> IANA timezone from the Slack profile ("" when the profile has none)
+        self._user_tz_cache: BoundedCache[str, str] = BoundedCache(cache_size)
+        self._diff_cache_size = min(cache_size, DIFF_CACHE_SIZE) if cache_size else DIFF_CACHE_SIZE
+        self._bot_user_id: Optional[str] = None
+        # The workspace's web address (`https://<team>.slack.com/`), for thread links
+        self._team_url: Optional[str] = None
+        # Button value -> (channel, thread_ts, file diff) for collapsed diffs
+        self._diff_cache: "OrderedDict[str, Tuple[str, str, FileDiff]]" = OrderedDict()
         self._client.socket_mode_request_listeners.append(self._handle_socket_request)
+        self._client.on_close_listeners.append(self._handle_socket_close)
+        self._watchdog: Optional[DisconnectWatchdog] = None
+        if disconnect_alert_secs > 0 and (self._alert_user_ids or self._alert_webhook_url):
+            self._watchdog = DisconnectWatchdog(
+                self._health,
+                probe=self._client.is_connected,
+                alert=self._send_alert,
+                threshold_secs=disconnect_alert_secs,
+            )
 
     async def send_message(
-        self, channel: str, thread_ts: str, text: str
+        self, channel: str, thread_ts: Optional[str], text: str
     ) -> Optional[str]:
         try:
             response = await self._web_client.chat_postMessage(
-                channel=channel, text=text, thread_ts=thread_ts
+                channel=channel, text=format_for_slack(text), thread_ts=thread_ts, **PRIVACY.message_options()
             )
             return response.get("ts")
         except SlackApiError as exc:
             raise SlackError(f"Failed to send Slack message: {exc}") from exc
 
+    async def update_message(self, channel: str, ts: str, text: str) -> None:
+        """Replace the text of a message the bot posted."""
+        try:
+            await self._web_client.chat_update(channel=channel, ts=ts, text=format_for_slack(text))
+        except SlackApiError as exc:
+            raise SlackError(f"Failed to update Slack message: {exc}") from exc
+
+    async def upload_file(
+        self, channel: str, thread_ts: str, filename: str, content: str, comment: str
+    ) -> None:
+        """Upload a text file to a thread (requires the `files:write` scope)."""
+        try:
+            await self._web_client.files_upload_v2(
+                channel=channel,
+                thread_ts=thread_ts,
+                filename=filename,
+                content=content,
+                initial_comment=format_for_slack(comment),
+            )
+        except SlackApiError as exc:
+            raise SlackError(f"Failed to upload file to Slack: {exc}") from exc
+
+    async def send_diff(self, channel: str, thread_ts: str, files: Sequence[FileDiff]) -> None:
+        """Post a collapsed per-file diff overview; each file's Expand button uploads its hunks."""
+        overview = format_diff_overview(files)
+        blocks: list[Dict[str, Any]] = [{"type": "section", "text": {"type": "mrkdwn", "text": overview}}]
+        for file_diff in files[:MAX_DIFF_FILE_BLOCKS]:
+            key = uuid.uuid4().hex
+            self._diff_cache[key] = (channel, thread_ts, file_diff)
+            blocks.append(
+                {
+                    "type": "section",
+                    "text": {"type": "mrkdwn", "text": file_diff.summary},
+                    "accessory": {
+                        "type": "button",
+                        "text": {"type": "plain_text", "text": "Expand"},
+                        "action_id": DIFF_EXPAND_ACTION_ID,
+                        "value": key,
+                    },
+                }
+            )
+        while len(self._diff_cache) > self._diff_cache_size:
+            self._diff_cache.popitem(last=False)
+        if len(files) > MAX_DIFF_FILE_BLOCKS:
+            blocks.append(
+                {
+                    "type": "context",
+                    "elements": [
+                        {
+                            "type": "mrkdwn",
+                            "text": f"…and {len(files) - MAX_DIFF_FILE_BLOCKS} more file(s); use `!patch` for the full diff.",
+                        }
+                    ],
+                }
+            )
+        try:
+            await self._web_client.chat_postMessage(
+                channel=channel, thread_ts=thread_ts, text=overview, blocks=blocks, **PRIVACY.message_options()
+            )
+        except SlackApiError as exc:
+            raise SlackError(f"Failed to send Slack diff: {exc}") from exc
+
+    async def send_confirmation(self, channel: str, thread_ts: str, text: str, key: str) -> Optional[str]:
+        """Post an agent's confirmation prompt with Approve and Deny buttons whose value is `key`."""
+        text = format_for_slack(text)
+        blocks: list[Dict[str, Any]] = [
+            {"type": "section", "text": {"type": "mrkdwn", "text": text}},
+            {
+                "type": "actions",
+                "elements": [
+                    {
+                        "type": "button",
+                        "text": {"type": "plain_text", "text": "Approve"},
+                        "style": "primary",
+                        "action_id": CONFIRM_APPROVE_ACTION_ID,
+                        "value": key,
+                    },
+                    {
+                        "type": "button",
+                        "text": {"type": "plain_text", "text": "Deny"},
+                        "style": "danger",
+                        "action_id": CONFIRM_DENY_ACTION_ID,
+                        "value": key,
+                    },
+                ],
+            },
+        ]
+        try:
+            response = await self._web_client.chat_postMessage(
+                channel=channel, thread_ts=thread_ts, text=text, blocks=blocks, **PRIVACY.message_options()
+            )
+        except SlackApiError as exc:
+            raise SlackError(f"Failed to send Slack confirmation: {exc}") from exc
+        return response.get("ts")
+
+    async def send_run_summary(self, channel: str, thread_ts: str, text: str, steps: Sequence[NextStep]) -> None:
+        """Post a finished run's summary with a button for each next step."""
+        text = format_for_slack(text)
+        buttons: list[Dict[str, Any]] = []
+        for step in steps:
+            button: Dict[str, Any] = {"type": "button", "text": {"type": "plain_text", "text": step.label}}
+            if step.url:
+                button.update(action_id=OPEN_LINK_ACTION_ID, url=step.url)
+            else:
+                button.update(action_id=NEXT_STEP_ACTION_ID, value=step.command or "")
+            buttons.append(button)
+        blocks: list[Dict[str, Any]] = [{"type": "section", "text": {"type": "mrkdwn", "text": text}}]
+        if buttons:
+            blocks.append({"type": "actions", "elements": buttons})
+        try:
+            await self._web_client.chat_postMessage(
+                channel=channel, thread_ts=thread_ts, text=text, blocks=blocks, **PRIVACY.message_options()
+            )
+        except SlackApiError as exc:
+            raise SlackError(f"Failed to send Slack run summary: {exc}") from exc
+
     async def start(self) -> None:
         LOGGER.info("Connecting to Slack via Socket Mode")
-        await self._client.connect()
-        await self._stop_event.wait()
+        watchdog_task = (
+            asyncio.create_task(self._watchdog.run(self._stop_event)) if self._watchdog else None
+        )
+        try:
+            await self._resolve_bot_user_id()
+            await self._client.connect()
+            await self._stop_event.wait()
+        finally:
+            if watchdog_task:
+                watchdog_task.cancel()
 
     async def stop(self) -> None:
         if not self._stop_event.is_set():
             self._stop_event.set()
         await self._client.close()
 
+    def thread_link(self, channel: str, thread_ts: str) -> Optional[str]:
+        """A permalink to the thread, once the workspace URL is known."""
+        if not self._team_url:
+            return None
+        return f"{self._team_url.rstrip('/')}/archives/{channel}/p{thread_ts.replace('.', '')}"
+
     def update_allowed_users(self, allowed_user_ids: list[str]) -> None:
         """Update the list of Slack user IDs allowed to interact with the bot."""
         self._allowed_user_ids = allowed_user_ids
 
+    def connection_health(self) -> ConnectionHealthSnapshot:
+        """Return Socket Mode connection metrics for `!status --system`."""
+        return self._health.snapshot()
+
+    async def register_commands(self, hints: Sequence[CommandHint]) -> bool:
+        """Update the app manifest's `/rc-*` slash commands; False when no config token is set."""
+        if not (self._app_id and self._config_token):
+            LOGGER.debug("SLACK_APP_ID or SLACK_APP_CONFIG_TOKEN not set; not registering slash commands")
+            return False
+        client = AsyncWebClient(token=self._config_token)
+        try:
+            exported = await client.api_call("apps.manifest.export", params={"app_id": self._app_id})
+            manifest = merge_slash_commands(exported.get("manifest") or {}, slack_slash_commands(hints))
+            if manifest is None:
+                return True
+            await client.api_call(
+                "apps.manifest.update", params={"app_id": self._app_id, "manifest": json.dumps(manifest)}
+            )
+        except (SlackApiError, aiohttp.ClientError) as exc:
+            raise SlackError(f"Failed to update Slack slash commands: {exc}") from exc
+        LOGGER.info("Registered %d slash command(s) with Slack", len(manifest["features"]["slash_commands"]))
+        return True
+
+    async def _resolve_bot_user_id(self) -> None:
+        """Look up the bot's own user id so invites to new channels can be recognized, and the workspace URL."""
+        try:
+            response = await self._web_client.auth_test()
+        except SlackApiError as exc:
+            LOGGER.warning("Failed to look up the bot user id; channel invites will be ignored: %s", exc)
+            return
+        self._bot_user_id = response.get("user_id")
+        self._team_url = response.get("url")
+
+    async def _handle_socket_close(self, *_: Any) -> None:
+        self._health.record_disconnected()
+
+    async def _send_alert(self, text: str) -> None:
+        """Deliver an operator alert by DM and/or webhook; these do not depend on the socket."""
+        for user_id in self._alert_user_ids:
+            try:
+                await self._web_client.chat_postMessage(channel=user_id, text=text, **PRIVACY.message_options())
+            except (SlackApiError, aiohttp.ClientError) as exc:
+                LOGGER.warning("Failed to DM alert to %s: %s", user_id, exc)
+        if self._alert_webhook_url and PRIVACY.allows(Egress.ALERT_WEBHOOK):
+            try:
+                await post_webhook_alert(self._alert_webhook_url, text)
+            except (aiohttp.ClientError, ValueError) as exc:
+                LOGGER.warning("Failed to post alert webhook: %s", exc)
+
     async def _handle_socket_request(
         self,
         client: SocketModeClient,
         req: SocketModeRequest,
     ) -> None:
+        if req.type == "interactive":
+            await client.send_socket_mode_response(SocketModeResponse(envelope_id=req.envelope_id))
+            await self._handle_interaction(req.payload or {})
+            return
+        if req.type == "slash_commands":
+            await client.send_socket_mode_response(SocketModeResponse(envelope_id=req.envelope_id))
+            await self._handle_slash_command(req.payload or {})
+            return
         if req.type != "events_api":
             await client.send_socket_mode_response(SocketModeResponse(envelope_id=req.envelope_id))
             return
@@ -72,11 +354,16 @@ class SlackAdapter(IChatAdapter):
         payload = req.payload or {}
         event = payload.get("event", {})
         await client.send_socket_mode_response(SocketModeResponse(envelope_id=req.envelope_id))
+        self._health.record_event(payload.get("event_time"))
 
         event_type = event.get("type")
         subtype = event.get("subtype")
         bot_id = event.get("bot_id")
 
+        if event_type == "member_joined_channel":
+            await self._handle_member_joined(event)
+            return
+
         # Ignore non-message events and bot messages
         if event_type != "message" or subtype == "bot_message" or bot_id:
             LOGGER.debug("Ignoring Slack event type %s with subtype %s, bot_id %s", event_type, subtype, bot_id)
@@ -88,8 +375,132 @@ class SlackAdapter(IChatAdapter):
             return
 
         await self._inject_channel_name(event)
+        await self._inject_user_timezone(event)
+        await self._inject_file_contents(event)
         await self._router.handle_message(event)
 
+    async def _handle_member_joined(self, event: Dict[str, Any]) -> None:
+        """Start onboarding when an allowed user invites the bot to a channel."""
+        if not self._bot_user_id or event.get("user") != self._bot_user_id:
+            return
+        inviter = event.get("inviter")
+        if inviter and inviter not in self._allowed_user_ids:
+            LOGGER.debug("Ignoring invite from unauthorized user %s", inviter)
+            return
+        await self._inject_channel_name(event)
+        await self._router.handle_channel_joined(event)
+
+    async def _handle_slash_command(self, payload: Dict[str, Any]) -> None:
+        """Run `/rc-<name> text` as `!<name> text` in a new thread of the channel it was typed in.
+
+        Slack sends slash commands without the thread they were typed in, so each one starts a session.
+        """
+        user_id = payload.get("user_id")
+        if user_id not in self._allowed_user_ids:
+            LOGGER.debug("Ignoring slash command from unauthorized user %s", user_id)
+            return
+        text = command_text_from_slash(str(payload.get("command") or ""), str(payload.get("text") or ""))
+        channel_id = payload.get("channel_id")
+        if not text or not channel_id:
+            return
+        try:
+            ts = await self.send_message(channel_id, None, f"<@{user_id}> ran `{text}`")
+        except SlackError as exc:
+            LOGGER.warning("Cannot run %s in %s (is the bot in the channel?): %s", text, channel_id, exc)
+            return
+        event: Dict[str, Any] = {"type": "message", "channel": channel_id, "user": user_id, "text": text, "ts": ts}
+        await self._inject_channel_name(event)
+        await self._inject_user_timezone(event)
+        await self._router.handle_message(event)
+
+    async def _handle_interaction(self, payload: Dict[str, Any]) -> None:
+        """Handle Block Kit button clicks (requires Interactivity to be enabled for the app)."""
+        if payload.get("type") != "block_actions":
+            return
+        user_id = (payload.get("user") or {}).get("id")
+        if user_id not in self._allowed_user_ids:
+            LOGGER.debug("Ignoring interaction from unauthorized user %s", user_id)
+            return
+        for action in payload.get("actions") or []:
+            if action.get("action_id") == DIFF_EXPAND_ACTION_ID:
+                await self._expand_diff(payload, str(action.get("value") or ""))
+            elif action.get("action_id") in (CONFIRM_APPROVE_ACTION_ID, CONFIRM_DENY_ACTION_ID):
+                approved = action.get("action_id") == CONFIRM_APPROVE_ACTION_ID
+                await self._answer_confirmation(payload, str(action.get("value") or ""), approved, user_id)
+            elif action.get("action_id") == NEXT_STEP_ACTION_ID:
+                await self._run_next_step(payload, str(action.get("value") or ""), user_id)
+
+    async def _run_next_step(self, payload: Dict[str, Any], command: str, user_id: str) -> None:
+        """Post a run summary button's command in its thread, then handle it as if the user had typed it."""
+        message = payload.get("message") or {}
+        channel = (payload.get("channel") or {}).get("id")
+        thread_ts = message.get("thread_ts") or message.get("ts")
+        if not command or not channel or not thread_ts:
+            return
+        try:
+            ts = await self.send_message(channel, thread_ts, f"<@{user_id}> chose `{command}`")
+        except SlackError as exc:
+            LOGGER.warning("Cannot run next step %s in %s: %s", command, channel, exc)
+            return
+        event: Dict[str, Any] = {
+            "type": "message",
+            "channel": channel,
+            "user": user_id,
+            "text": command,
+            "ts": ts,
+            "thread_ts": thread_ts,
+        }
+        await self._inject_channel_name(event)
+        await self._inject_user_timezone(event)
+        await self._router.handle_message(event)
+
+    async def _answer_confirmation(self, payload: Dict[str, Any], key: str, approved: bool, user_id: str) -> None:
+        """Pass the answer on and replace the buttons with who answered."""
+        answered = self._router.answer_confirmation(key, approved)
+        if answered:
+            outcome = f"{'Approved' if approved else 'Denied'} by <@{user_id}>."
+        else:
+            outcome = "The agent is no longer waiting for this answer."
+        message = payload.get("message") or {}
+        channel = (payload.get("channel") or {}).get("id")
+        if not channel or not message.get("ts"):
+            return
+        text = f"{message.get('text') or ''}\n{outcome}".strip()
+        try:
+            await self._web_client.chat_update(
+                channel=channel,
+                ts=message["ts"],
+                text=text,
+                blocks=[{"type": "section", "text": {"type": "mrkdwn", "text": text}}],
+            )
+        except SlackApiError as exc:
+            LOGGER.warning("Failed to update the confirmation message: %s", exc)
+
+    async def _expand_diff(self, payload: Dict[str, Any], key: str) -> None:
+        cached = self._diff_cache.get(key)
+        if not cached:
+            message = payload.get("message") or {}
+            channel = (payload.get("channel") or {}).get("id")
+            thread_ts = message.get("thread_ts") or message.get("ts")
+            if channel and thread_ts:
+                await self.send_message(
+                    channel, thread_ts, "That diff is no longer available; use `!patch` for the full changes."
+                )
+            return
+        channel, thread_ts, file_diff = cached
+        try:
+            # Uploaded as a `diff` snippet so Slack highlights added and removed lines
+            await self._web_client.files_upload_v2(
+                channel=channel,
+                thread_ts=thread_ts,
+                filename=snippet_filename(file_diff),
+                content=file_diff.hunks or file_diff.text,
+                snippet_type="diff",
+                initial_comment=file_diff.summary,
+            )
+        except SlackApiError as exc:
+            LOGGER.warning("Failed to expand diff for %s: %s", file_diff.path, exc)
+
     async def _inject_channel_name(self, event: Dict[str, Any]) -> None:
         channel_id = event.get("channel")
         if not channel_id:
@@ -108,3 +519,74 @@ class SlackAdapter(IChatAdapter):
         if name:
             self._channel_name_cache[channel_id] = name
             event["channel_name"] = name
+
+    async def resolve_channel(self, name: str) -> Optional[str]:
+        """Channel id for a channel name, or None if the bot cannot see it.
+
+        Requires the `channels:read` scope, plus `groups:read` for private channels.
+        """
+        name = name.lstrip("#")
+        for channel_id in self._channel_name_cache:
+            if self._channel_name_cache.get(channel_id) == name:
+                return channel_id
+        cursor: Optional[str] = None
+        while True:
+            try:
+                result = await self._web_client.conversations_list(
+                    types="public_channel,private_channel", exclude_archived=True, limit=200, cursor=cursor
+                )
+            except SlackApiError as exc:
+                raise SlackError(f"Failed to list Slack channels: {exc}") from exc
+            for channel in result.get("channels") or []:
+                if channel.get("name") == name and channel.get("id"):
+                    self._channel_name_cache[channel["id"]] = name
+                    return channel["id"]
+            cursor = (result.get("response_metadata") or {}).get("next_cursor")
+            if not cursor:
+                return None
+
+    async def _inject_user_timezone(self, event: Dict[str, Any]) -> None:
+        """Add the sender's profile timezone as `user_tz` (requires the `users:read` scope)."""
+        user_id = event.get("user")
+        if not user_id:
+            return
+        if user_id not in self._user_tz_cache:
+            try:
+                result = await self._web_client.users_info(user=user_id)
+            except SlackApiError as exc:
+                LOGGER.debug("Failed to look up timezone of user %s: %s", user_id, exc)
+                return
+            self._user_tz_cache[user_id] = (result.get("user") or {}).get("tz") or ""
+        user_tz = self._user_tz_cache[user_id]
+        if user_tz:
+            event["user_tz"] = user_tz
+
+    async def _inject_file_contents(self, event: Dict[str, Any]) -> None:
+        """Download small text files shared with the message (requires the `files:read` scope)."""
+        files = event.get("files") or []
+        if not files:
+            return
+        downloaded = []
+        headers = {"Authorization": f"Bearer {self._bot_token}"}
+        async with aiohttp.ClientSession(headers=headers) as http:
+            for info in files:
+                url = info.get("url_private_download") or info.get("url_private")
+                size = info.get("size") or 0
+                if not url or size > MAX_DOWNLOAD_BYTES:
+                    continue
+                try:
+                    async with http.get(url) as response:
+                        if response.status != 200:
+                            LOGGER.debug("Failed to download Slack file %s: HTTP %s", info.get("name"), response.status)
+                            continue
+                        data = await response.read()
+                except aiohttp.ClientError as exc:
+                    LOGGER.debug("Failed to download Slack file %s: %s", info.get("name"), exc)
+                    continue
+                try:
+                    content = data.decode("utf-8")
+                except UnicodeDecodeError:
+                    continue
+                downloaded.append({"name": info.get("name") or "", "content": content})
+        if downloaded:
+            event["downloaded_files"] = downloaded
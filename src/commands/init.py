@@ -12,6 +12,7 @@ from typing import Callable
 import requests
 import yaml
 
+from ..core.migrations import CONFIG_VERSIONS
 from .config_github import (
     run_config_github_command,
     update_env_github_config,
@@ -624,7 +625,7 @@ def generate_env_file(path: Path, config: ConfigData) -> None:
 
 def generate_projects_yaml(path: Path, config: ConfigData) -> None:
     """Generate projects.yaml from config data."""
-    data = {"base_dir": config.base_dir, "projects": {}}
+    data = {"version": CONFIG_VERSIONS["projects.yaml"], "base_dir": config.base_dir, "projects": {}}
 
     for project in config.projects:
         project_data = {
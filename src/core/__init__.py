@@ -2,6 +2,7 @@
 
 from .config import Config, load_config
 from .errors import (
+    AgentNotAllowed,
     AgentNotFound,
     RemoteCoderError,
     CommandNotFound,
@@ -20,6 +21,7 @@ from .models import (
     GitHubRepoConfig,
     Project,
     PullRequestRef,
+    PullRequestRole,
     Session,
     SessionStatus,
     WorkingDirMode,
@@ -37,11 +39,13 @@ __all__ = [
     "GitHubRepoConfig",
     "Project",
     "PullRequestRef",
+    "PullRequestRole",
     "Session",
     "SessionStatus",
     "WorkingDirMode",
     "RemoteCoderError",
     "ProjectNotFound",
+    "AgentNotAllowed",
     "AgentNotFound",
     "CommandNotFound",
     "ProcessError",
@@ -0,0 +1,289 @@
+"""Detection of missing agent credentials and the flows that set them up.
+
+Each agent type knows where its CLI or API looks for credentials (environment
+variables and credential files). Missing ones are reported to the operators
+at startup with setup instructions. Failed runs whose output looks like an
+authentication error get the same instructions instead of the raw process
+error. Tokens are stored with `remote-coder auth <agent>` (see `secrets`), and
+CLIs that support it can log in with a device code posted to the operators
+(`!auth <agent>`).
+"""
+
+from __future__ import annotations
+
+import asyncio
+import logging
+import os
+import re
+import sys
+from dataclasses import dataclass, field
+from enum import Enum
+from pathlib import Path
+from typing import Dict, List, Mapping, Optional, Tuple
+
+from .models import Agent, AgentType
+
+LOGGER = logging.getLogger(__name__)
+
+DEVICE_PROMPT_TIMEOUT_SECS = 30
+DEVICE_LOGIN_TIMEOUT_SECS = 15 * 60
+
+AUTH_FAILURE_PATTERN = re.compile(
+    r"not logged in|please (?:run )?/?login|invalid (?:api[ _-]?key|x-api-key|bearer token)"
+    r"|authentication[ _](?:failed|error|required)|unauthori[sz]ed|(?:status|http|error|code)[ :]*401\b"
+    r"|(?:token|credentials?) (?:has |have )?expired|no (?:api key|credentials) (?:found|provided)",
+    re.IGNORECASE,
+)
+ANSI_ESCAPE = re.compile(r"\x1b\[[0-9;]*[A-Za-z]")
+URL_PATTERN = re.compile(r"https://\S+")
+DEVICE_CODE_PATTERN = re.compile(r"\b[A-Z0-9]{4,5}-[A-Z0-9]{4,5}\b")
+
+
+class AuthState(str, Enum):
+    OK = "ok"
+    MISSING = "missing"
+    UNKNOWN = "unknown"  # Credentials may exist where they cannot be checked (e.g. the macOS keychain)
+
+
+@dataclass(frozen=True)
+class AuthMethod:
+    """Where an agent type finds its credentials and how to provide them."""
+
+    env_vars: Tuple[str, ...]  # Any one of these being set counts as logged in
+    token_env: Optional[str]  # Variable `remote-coder auth` stores a token under
+    instructions: str  # `{agent}` and `{token_env}` are filled in
+    device_login: Tuple[str, ...] = ()  # Arguments for the CLI's device-code login, if it has one
+
+
+@dataclass(frozen=True)
+class AuthCheck:
+    agent_id: str
+    state: AuthState
+    source: Optional[str] = None  # The variable or file the credentials were found in
+    looked_in: Tuple[str, ...] = field(default_factory=tuple)
+    instructions: str = ""
+    device_login: bool = False
+
+    @property
+    def ok(self) -> bool:
+        return self.state != AuthState.MISSING
+
+
+AUTH_METHODS: Dict[AgentType, AuthMethod] = {
+    AgentType.CLAUDE: AuthMethod(
+        env_vars=("ANTHROPIC_API_KEY", "CLAUDE_CODE_OAUTH_TOKEN", "CLAUDE_CODE_USE_BEDROCK", "CLAUDE_CODE_USE_VERTEX"),
+        token_env="CLAUDE_CODE_OAUTH_TOKEN",
+        instructions=(
+            "Run `claude` once on the daemon host and log in, or create a token with `claude setup-token` "
+            "on any machine and store it on the host with `remote-coder auth {agent}` (saved as `{token_env}`)."
+        ),
+    ),
+    AgentType.CODEX: AuthMethod(
+        env_vars=("OPENAI_API_KEY", "CODEX_API_KEY"),
+        token_env="OPENAI_API_KEY",
+        instructions=(
+            "Run `!auth {agent}` to log in with a device code, or store an API key on the daemon host "
+            "with `remote-coder auth {agent}` (saved as `{token_env}`)."
+        ),
+        device_login=("login", "--device-auth"),
+    ),
+    AgentType.GEMINI: AuthMethod(
+        env_vars=("GEMINI_API_KEY", "GOOGLE_API_KEY", "GOOGLE_APPLICATION_CREDENTIALS", "GOOGLE_GENAI_USE_VERTEXAI"),
+        token_env="GEMINI_API_KEY",
+        instructions=(
+            "Create an API key at https://aistudio.google.com/apikey and store it on the daemon host with "
+            "`remote-coder auth {agent}` (saved as `{token_env}`), or run `gemini` once on the host to log in."
+        ),
+    ),
+    AgentType.OPENROUTER: AuthMethod(
+        env_vars=("OPENROUTER_API_KEY",),
+        token_env="OPENROUTER_API_KEY",
+        instructions=(
+            "Create an API key at https://openrouter.ai/keys and store it on the daemon host with "
+            "`remote-coder auth {agent}` (saved as `{token_env}`)."
+        ),
+    ),
+}
+
+
+def auth_method_for(agent: Agent) -> Optional[AuthMethod]:
+    """The agent's credential requirements; None when it needs none (e.g. Ollama, WASM, mock)."""
+    method = AUTH_METHODS.get(agent.type)
+    if agent.api_key_env and agent.type in (AgentType.OPENROUTER, AgentType.HTTP):
+        return AuthMethod(
+            env_vars=(agent.api_key_env,),
+            token_env=agent.api_key_env,
+            instructions=(
+                method.instructions
+                if method
+                else "Store the endpoint's API key on the daemon host with `remote-coder auth {agent}` "
+                "(saved as `{token_env}`)."
+            ),
+        )
+    return method
+
+
+def _credential_files(agent_type: AgentType, env: Mapping[str, str], home: Path) -> List[Path]:
+    if agent_type == AgentType.CLAUDE:
+        config_dir = Path(env["CLAUDE_CONFIG_DIR"]) if env.get("CLAUDE_CONFIG_DIR") else home / ".claude"
+        return [config_dir / ".credentials.json"]
+    if agent_type == AgentType.CODEX:
+        codex_home = Path(env["CODEX_HOME"]) if env.get("CODEX_HOME") else home / ".codex"
+        return [codex_home / "auth.json"]
+    if agent_type == AgentType.GEMINI:
+        return [home / ".gemini" / "oauth_creds.json"]
+    return []
+
+
+def _display_path(path: Path, home: Path) -> str:
+    try:
+        return f"~/{path.relative_to(home)}"
+    except ValueError:
+        return str(path)
+
+
+def check_agent_auth(agent: Agent, *, home: Optional[Path] = None) -> Optional[AuthCheck]:
+    """Report whether the agent's credentials are present; None for agents that need none."""
+    method = auth_method_for(agent)
+    if method is None:
+        return None
+    env = {**os.environ, **agent.env}
+    home = home or Path(env.get("HOME") or Path.home())
+    instructions = method.instructions.format(agent=agent.id, token_env=method.token_env)
+
+    for name in method.env_vars:
+        if env.get(name):
+            return AuthCheck(agent_id=agent.id, state=AuthState.OK, source=name, instructions=instructions)
+    files = _credential_files(agent.type, env, home)
+    for path in files:
+        if path.is_file():
+            source = _display_path(path, home)
+            return AuthCheck(agent_id=agent.id, state=AuthState.OK, source=source, instructions=instructions)
+
+    looked_in = (*method.env_vars, *(_display_path(path, home) for path in files))
+    # On macOS the Claude CLI keeps its login in the keychain, which cannot be inspected here
+    state = AuthState.UNKNOWN if agent.type == AgentType.CLAUDE and sys.platform == "darwin" else AuthState.MISSING
+    return AuthCheck(
+        agent_id=agent.id,
+        state=state,
+        looked_in=looked_in,
+        instructions=instructions,
+        device_login=bool(method.device_login) and bool(agent.command),
+    )
+
+
+def looks_like_auth_failure(text: str) -> bool:
+    return bool(AUTH_FAILURE_PATTERN.search(text or ""))
+
+
+def describe_missing(check: AuthCheck) -> str:
+    """E.g. "No credentials found for `codex` (checked OPENAI_API_KEY, ~/.codex/auth.json). Run ..."."""
+    checked = f" (checked {', '.join(check.looked_in)})" if check.looked_in else ""
+    return f"No credentials found for `{check.agent_id}`{checked}. {check.instructions}"
+
+
+def auth_failure_hint(agent: Agent, output: str) -> Optional[str]:
+    """Setup instructions when a failed run's output or the agent's credentials point to an auth problem."""
+    check = check_agent_auth(agent)
+    if check is None:
+        return None
+    if check.state == AuthState.MISSING:
+        return describe_missing(check)
+    if looks_like_auth_failure(output):
+        return (
+            f"This looks like an authentication problem with `{agent.id}` (credentials from "
+            f"{check.source or 'the CLI login'}). {check.instructions}"
+        )
+    return None
+
+
+def format_auth_checks(checks: List[AuthCheck]) -> str:
+    """Render checks for `!auth`."""
+    if not checks:
+        return "No configured agent needs credentials."
+    lines = ["Agent credentials:"]
+    for check in checks:
+        if check.state == AuthState.OK:
+            lines.append(f"• `{check.agent_id}`: found ({check.source})")
+        elif check.state == AuthState.UNKNOWN:
+            lines.append(f"• `{check.agent_id}`: not in the environment; may be in the system keychain")
+        else:
+            login = f" `!auth {check.agent_id}` starts a device-code login." if check.device_login else ""
+            lines.append(f"• `{check.agent_id}`: MISSING.{login}")
+    return "\n".join(lines)
+
+
+@dataclass
+class DeviceLogin:
+    """A running device-code login of an agent CLI (e.g. `codex login --device-auth`)."""
+
+    agent_id: str
+    url: str
+    code: Optional[str]
+    process: asyncio.subprocess.Process
+    output: List[str] = field(default_factory=list)
+
+    @classmethod
+    async def start(cls, agent: Agent, *, prompt_timeout: float = DEVICE_PROMPT_TIMEOUT_SECS) -> "DeviceLogin":
+        """Start the login and wait for its verification link; raises RuntimeError if none appears."""
+        method = auth_method_for(agent)
+        if method is None or not method.device_login or not agent.command:
+            raise RuntimeError(f"`{agent.id}` has no device-code login")
+        command = [agent.command[0], *method.device_login]
+        LOGGER.info("Starting device-code login for %s: %s", agent.id, " ".join(command))
+        process = await asyncio.create_subprocess_exec(
+            *command,
+            stdin=asyncio.subprocess.DEVNULL,
+            stdout=asyncio.subprocess.PIPE,
+            stderr=asyncio.subprocess.STDOUT,
+            env={**os.environ, **agent.env},
+        )
+        output: List[str] = []
+        url: Optional[str] = None
+        code: Optional[str] = None
+
+        async def read_prompt() -> None:
+            nonlocal url, code
+            assert process.stdout is not None
+            while url is None or code is None:
+                raw = await process.stdout.readline()
+                if not raw:
+                    return
+                line = ANSI_ESCAPE.sub("", raw.decode("utf-8", errors="replace")).strip()
+                output.append(line)
+                url = url or _first_match(URL_PATTERN, line)
+                code = code or _first_match(DEVICE_CODE_PATTERN, line)
+
+        try:
+            await asyncio.wait_for(read_prompt(), prompt_timeout)
+        except asyncio.TimeoutError:
+            pass
+        if url is None:
+            if process.returncode is None:
+                process.kill()
+            await process.wait()
+            detail = " ".join(output[-3:]) or "no output"
+            raise RuntimeError(f"`{' '.join(command)}` did not print a verification link ({detail})")
+        return cls(agent_id=agent.id, url=url, code=code, process=process, output=output)
+
+    async def wait(self, timeout: float = DEVICE_LOGIN_TIMEOUT_SECS) -> bool:
+        """Wait for the user to finish logging in; False when the CLI fails or the code expires."""
+        assert self.process.stdout is not None
+        try:
+            rest = await asyncio.wait_for(self.process.stdout.read(), timeout)
+            await self.process.wait()
+        except asyncio.TimeoutError:
+            self.process.kill()
+            await self.process.wait()
+            return False
+        self.output.extend(ANSI_ESCAPE.sub("", rest.decode("utf-8", errors="replace")).splitlines())
+        return self.process.returncode == 0
+
+    def prompt(self) -> str:
+        code = f" and enter the code `{self.code}`" if self.code else ""
+        return f"To log `{self.agent_id}` in, open {self.url}{code}. The code expires after a few minutes."
+
+
+def _first_match(pattern: re.Pattern[str], text: str) -> Optional[str]:
+    match = pattern.search(text)
+    return match.group(0) if match else None
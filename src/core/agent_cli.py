@@ -0,0 +1,168 @@
+"""Detection of the CLIs that command-based agents shell out to.
+
+Each CLI agent's binary (the first element of its `command`) is looked up on
+PATH and asked for `--version`. Agents whose CLI is missing, or older than the
+agent's `min_version`, are refused with an actionable error instead of failing
+mid-run. Results are logged at startup and shown by `!status --system`.
+"""
+
+from __future__ import annotations
+
+import asyncio
+import logging
+import os
+import re
+import shutil
+import subprocess
+from dataclasses import dataclass
+from typing import Callable, Dict, Iterable, List, Optional, Tuple
+
+from ..agent_adapters.bubblewrap import BWRAP
+from .config import API_AGENT_TYPES
+from .models import Agent, SandboxMode
+
+LOGGER = logging.getLogger(__name__)
+
+VERSION_TIMEOUT_SECS = 10
+VERSION_PATTERN = re.compile(r"\d+(?:\.\d+)+")
+
+
+@dataclass(frozen=True)
+class CliCheck:
+    """Outcome of probing one agent's CLI."""
+
+    agent_id: str
+    binary: str
+    path: Optional[str] = None
+    version: Optional[str] = None
+    min_version: Optional[str] = None
+    problem: Optional[str] = None
+
+    @property
+    def ok(self) -> bool:
+        return self.problem is None
+
+
+ProbeFn = Callable[[Agent], Optional[CliCheck]]
+
+
+def parse_version(text: str) -> Optional[str]:
+    """Return the first dotted version number in `text` (e.g. "codex-cli 0.46.0" -> "0.46.0")."""
+    match = VERSION_PATTERN.search(text)
+    return match.group(0) if match else None
+
+
+def version_tuple(version: str) -> Tuple[int, ...]:
+    return tuple(int(part) for part in version.split("."))
+
+
+def is_older(version: str, minimum: str) -> bool:
+    current, required = version_tuple(version), version_tuple(minimum)
+    width = max(len(current), len(required))
+    return current + (0,) * (width - len(current)) < required + (0,) * (width - len(required))
+
+
+def probe_agent_cli(agent: Agent) -> Optional[CliCheck]:
+    """Locate the agent's CLI and read its version; None for agents without a CLI."""
+    if agent.type in API_AGENT_TYPES or not agent.command:
+        return None
+    binary = agent.command[0]
+    env = {**os.environ, **agent.env}
+    path = shutil.which(binary, path=env.get("PATH"))
+    if not path:
+        return CliCheck(
+            agent_id=agent.id,
+            binary=binary,
+            min_version=agent.min_version,
+            problem=f"`{binary}` was not found on PATH; install it or point `command` in agents.yaml at it.",
+        )
+
+    try:
+        completed = subprocess.run(
+            [path, "--version"],
+            capture_output=True,
+            text=True,
+            env=env,
+            timeout=VERSION_TIMEOUT_SECS,
+            check=False,
+        )
+        version = parse_version(f"{completed.stdout}\n{completed.stderr}")
+    except (OSError, subprocess.TimeoutExpired) as exc:
+        LOGGER.warning("Could not read the version of %s: %s", path, exc)
+        version = None
+
+    problem = None
+    if agent.sandbox.mode == SandboxMode.BUBBLEWRAP and not shutil.which(BWRAP):
+        problem = f"its sandbox is bubblewrap, but `{BWRAP}` was not found on PATH; install bubblewrap."
+    elif agent.min_version and version and is_older(version, agent.min_version):
+        problem = f"`{binary}` {version} is older than the required {agent.min_version}; upgrade it."
+    elif agent.min_version and not version:
+        LOGGER.warning(
+            "Could not determine the version of %s for agent %s; skipping the min_version check",
+            binary,
+            agent.id,
+        )
+    return CliCheck(
+        agent_id=agent.id,
+        binary=binary,
+        path=path,
+        version=version,
+        min_version=agent.min_version,
+        problem=problem,
+    )
+
+
+class AgentCliChecker:
+    """Caches CLI checks per agent until the config is reloaded."""
+
+    def __init__(self, probe: ProbeFn = probe_agent_cli) -> None:
+        self._probe = probe
+        self._results: Dict[str, Optional[CliCheck]] = {}
+
+    async def check(self, agent: Agent) -> Optional[CliCheck]:
+        """Return the (cached) check for `agent`; None if it has no CLI."""
+        if agent.id not in self._results:
+            self._results[agent.id] = await asyncio.to_thread(self._probe, agent)
+        return self._results[agent.id]
+
+    async def check_all(self, agents: Iterable[Agent]) -> List[CliCheck]:
+        """Re-probe every agent and log the outcome."""
+        self._results.clear()
+        checks = []
+        for agent in agents:
+            result = await self.check(agent)
+            if result is None:
+                continue
+            checks.append(result)
+            if result.ok:
+                LOGGER.info(
+                    "Agent %s: %s %s (%s)",
+                    result.agent_id,
+                    result.binary,
+                    result.version or "(unknown version)",
+                    result.path,
+                )
+            else:
+                LOGGER.error("Agent %s is unavailable: %s", result.agent_id, result.problem)
+        return checks
+
+    def results(self) -> List[CliCheck]:
+        return [result for result in self._results.values() if result is not None]
+
+    def clear(self) -> None:
+        self._results.clear()
+
+
+def format_cli_checks(checks: Iterable[CliCheck]) -> str:
+    """Render checks for `!status --system`."""
+    checks = list(checks)
+    if not checks:
+        return "Agent CLIs: none checked yet"
+    lines = ["Agent CLIs:"]
+    for check in checks:
+        if check.ok:
+            version = check.version or "unknown version"
+            lines.append(f"• `{check.agent_id}`: {check.binary} {version}")
+        else:
+            lines.append(f"• `{check.agent_id}`: UNAVAILABLE: {check.problem}")
+    return "\n".join(lines)
@@ -3,18 +3,157 @@
 from __future__ import annotations
 
 import asyncio
+import contextlib
 import logging
 import time
-from typing import Dict, Optional, Sequence
+from contextvars import ContextVar
+from dataclasses import dataclass, field
+from pathlib import Path
+from typing import Awaitable, Callable, ContextManager, Dict, Iterator, List, Mapping, Optional, Sequence, Tuple
+from uuid import uuid4
 
 from ..agent_adapters import AgentAdapter, AgentResult
+from ..agent_adapters.base import interrupt_processes, observe_run_processes, track_run_processes
+from ..agent_adapters.confirmations import answer_confirmations
+from ..agent_adapters.containers import AgentContainer, run_agents_in_container
+from ..agent_adapters.dev_env import run_in_dev_env
+from ..agent_adapters.progress import ProgressEvent
+from ..agent_adapters.warm_pool import WarmPool
+from ..agent_adapters.wasm_plugins import WasmPlugin
+from ..chat_adapters.i_chat_adapter import ChatCapabilities
+from .agent_auth import auth_failure_hint
+from .agent_cli import AgentCliChecker
+from .ask_mode import (
+    ASK_MODE_DISCARDED,
+    ASK_NOTE,
+    AskCheckoutError,
+    discard_ask_changes,
+    in_ask_mode,
+    prepare_ask_checkout,
+)
+from .automation import AutomationGate
 from .config import Config
+from .confirmations import ConfirmationBroker
+from .coordinator import CODER, PLANNER, REVIEWER, RoleCast, cast_for, format_review, review_task, role_model, step_note
+from .devcontainer import BuildNoticeFn, DevcontainerError, resolve_devcontainer
+from .errors import AgentNotFound
+from .events import RunStarted
+from .diff_render import format_diff_overview, split_diff
+from .discussions import DESIGN_NOTE, in_design_phase
 from .git_workflow import GitWorkflowService
-from .conversation import InteractionClassifier, SessionManager
-from .models import Agent, ConversationMessage, Project, Session
+from .incident import IncidentAudit, audit_path, incident_project, set_debug_logging, snippet
+from .conversation import InteractionClassifier, MessageIntent, SessionManager
+from .models import (
+    Agent,
+    CommandStep,
+    ConversationMessage,
+    PipelineStep,
+    Project,
+    PullRequestRole,
+    RunOverrides,
+    RunTimeouts,
+    SandboxConfig,
+    Session,
+    SessionStatus,
+)
+from .file_watch import FileChangeWatcher
+from .heartbeat import Heartbeat
+from .output_stream import OutputStream, UpdateMessageFn, format_partial_output
+from .pipeline import (
+    APPROVE_STEP,
+    DEFAULT_APPROVE_TIMEOUT_SECS,
+    DIFF_REVIEW_STEP,
+    EDIT_STEP,
+    FORMAT_STEP,
+    PLAN_NOTE,
+    PLAN_STEP,
+    PLANNED_NOTE,
+    PR_STEP,
+    REVIEW_STEP,
+    VERIFY_STEP,
+    pipeline_for,
+)
+from .preferences import LOCALE_NOTE, Verbosity, notify_target, session_preferences
+from .pricing import cost_for_result, estimate_run_cost, format_cost
+from .project_commands import describe_overrides
+from .run_journal import JournalEntry, RunJournal
+from .run_summary import RunSummary, SendRunSummaryFn, collect_change_stats, format_run_summary, next_steps
+from .sandbox import Sandbox
+from .shutdown import cancelled_by_shutdown
+from .snapshots import SnapshotError, SnapshotStore
+from .timeline import add_checkpoint, changed_files, checked_out_run
+from .transcripts import RunRecord
+from .transient import backoff_delay, classify_errors, classify_exception, short_detail
+from .verify import checks_passed, format_verify_results, has_project_checks, run_project_checks, run_verify_steps
 
 LOGGER = logging.getLogger(__name__)
 
+
+@dataclass
+class _PipelineRun:
+    """A run's state as it goes through its project's pipeline (see `src/core/pipeline.py`)."""
+
+    session: Session
+    project: Project
+    agent: Agent
+    channel_id: str
+    thread_ts: str
+    reply: List[str] = field(default_factory=list)  # Sent as one message when a step posts or the run ends
+    result: Optional[AgentResult] = None  # Set once `edit` has run
+    snapshot: Optional[SnapshotStore] = None
+    snapshot_diff: Optional[str] = None  # None until computed
+    published: bool = False  # The `pr` step ran
+    pr_message: Optional[str] = None
+    audit: Optional[IncidentAudit] = None  # Set while the project is in incident mode
+    cast: Optional[RoleCast] = None  # The agents in each role; None in ask mode
+    request: str = ""
+    plan: Optional[str] = None  # Set by the `plan` step
+
+
+# The project whose command is being handled, for runs outside a session (`run_isolated`)
+_PROJECT: ContextVar[Optional[Project]] = ContextVar("isolated_run_project", default=None)
+
+
+class RunPolicyViolation(Exception):
+    """Raised when a run is stopped by its `timeout_secs` or its agent's `max_cost_usd`."""
+
+    def __init__(self, message: str, output: str = "") -> None:
+        super().__init__(message)
+        self.output = output  # What the agent printed before it was stopped
+
+
+class RunTimeout(Exception):
+    """One attempt ran past its time limit; `output` is what the agent printed until then."""
+
+    def __init__(self, message: str, output: str = "") -> None:
+        super().__init__(message)
+        self.output = output
+
+
+def _with_exit_code(result: AgentResult, processes: Sequence[asyncio.subprocess.Process]) -> AgentResult:
+    """`result` with the exit code of the run's last agent process, unless the adapter set one."""
+    if isinstance(result, AgentResult) and result.exit_code is None:
+        finished = [process.returncode for process in processes if process.returncode is not None]
+        if finished:
+            result.exit_code = finished[-1]
+    return result
+
+
+PostDiffFn = Callable[[str, str, str], Awaitable[None]]
+# channel -> what the chat it belongs to can show
+ChatCapabilitiesFn = Callable[[str], ChatCapabilities]
+
+FEEDBACK_NOTE = (
+    "The current ask is feedback on your previous run: revise that work as asked instead of starting over."
+)
+
+CANCELLED_REPLY = "(Cancelled with `!cancel` before finishing.)"
+
+# Session context key holding the last prompt and the commit checked out before it ran (for `!redo`)
+LAST_RUN_CONTEXT_KEY = "last_run"
+# Session context key holding the commit the session's first run started from (used by `!replay`)
+START_COMMIT_CONTEXT_KEY = "start_commit"
+
 CODE_TASK_WRAPPER = """You are Remote Coder, an autonomous developer working inside the user's repository.
 
 1. Carefully read the latest Slack request and decide whether it requires code changes.
@@ -53,6 +192,16 @@ class AgentTaskRunner:
         adapter_cache: Dict[str, AgentAdapter],
         active_runs: Dict[str, Dict[str, object]],
         send_message,
+        update_message: Optional[UpdateMessageFn] = None,
+        post_diff: Optional[PostDiffFn] = None,
+        chat_capabilities: Optional[ChatCapabilitiesFn] = None,
+        plugins: Optional[Mapping[str, WasmPlugin]] = None,
+        gate: Optional[AutomationGate] = None,
+        cli_checker: Optional[AgentCliChecker] = None,
+        warm_pool: Optional[WarmPool] = None,
+        confirmations: Optional[ConfirmationBroker] = None,
+        send_run_summary: Optional[SendRunSummaryFn] = None,
+        journal: Optional[RunJournal] = None,
     ) -> None:
         self._config = config
         self._session_manager = session_manager
@@ -61,6 +210,16 @@ class AgentTaskRunner:
         self._adapter_cache = adapter_cache
         self._active_runs = active_runs
         self._send_message = send_message
+        self._update_message = update_message
+        self._post_diff = post_diff
+        self._chat_capabilities = chat_capabilities
+        self._plugins = dict(plugins or {})
+        self._gate = gate
+        self._cli_checker = cli_checker
+        self._warm_pool = warm_pool
+        self._confirmations = confirmations
+        self._send_run_summary = send_run_summary
+        self._journal = journal
 
     def update_config(self, config: Config) -> None:
         self._config = config
@@ -72,48 +231,301 @@ class AgentTaskRunner:
         channel_id: str,
         thread_ts: str,
         user_text: str,
+        overrides: Optional[RunOverrides] = None,
+        intent: MessageIntent = MessageIntent.TASK,
     ) -> None:
-        agent = self._config.get_agent(session.active_agent_id)
-        adapter = self._get_adapter(agent)
+        """Run the session's active agent on `user_text` and post the reply (and PR or diff) to the thread.
 
-        await self._send_message(
-            channel_id,
-            thread_ts,
-            f"Message received — running `{agent.id}` now.",
-        )
+        `overrides` (from a project command's frontmatter) change the model and
+        sampling parameters for this run only; ones the agent's adapter does not
+        support are ignored with a note. Feedback on the last run (`intent`) is
+        marked as such in the prompt. The steps around the agent's edit come
+        from the project's pipeline (see `src/core/pipeline.py`), and its `roles`
+        can give the plan, edit, and review steps to different agents (see
+        `src/core/coordinator.py`). Sessions in
+        ask mode run the agent alone in the project's read-only checkout and
+        discard what it changed (see `src/core/ask_mode.py`). During the project's
+        incident every step is audited and approved (see `src/core/incident.py`).
+        The session is `running` from the start of the run and `active` again
+        after it, or `failed` when the agent failed.
+        """
+        try:
+            await self._run(session, project, channel_id, thread_ts, user_text, overrides, intent)
+        finally:
+            self._session_manager.finish_run(session.id)
+
+    async def _run(
+        self,
+        session: Session,
+        project: Project,
+        channel_id: str,
+        thread_ts: str,
+        user_text: str,
+        overrides: Optional[RunOverrides],
+        intent: MessageIntent,
+    ) -> None:
+        blocked = self._gate.block_reason(project, session) if self._gate else None
+        if blocked:
+            LOGGER.info("Not running agent for session %s: %s", session.id, blocked)
+            await self._send_message(channel_id, thread_ts, blocked)
+            return
+        checked_out = checked_out_run(session)
+        if checked_out is not None:
+            await self._send_message(
+                channel_id,
+                thread_ts,
+                f"The worktree is at checkpoint {checked_out}; run `!checkout latest` before asking for more changes.",
+            )
+            return
+
+        incident = self._session_manager.incident_for(project.id)
+        set_debug_logging(bool(self._session_manager.active_incidents()))
+        if incident:
+            project = incident_project(project)
+
+        cast = None
+        if in_ask_mode(session):
+            agent = self._config.get_agent(session.active_agent_id)
+        else:
+            try:
+                cast = cast_for(project, session, self._config.agents)
+            except AgentNotFound as exc:
+                await self._send_message(
+                    channel_id, thread_ts, f"Cannot run: the project's `roles` name `{exc}`, which is not configured."
+                )
+                return
+            agent = cast.coder
+        for role_agent in cast.agents if cast else [agent]:
+            cli_problem = await self._cli_problem(role_agent, project.sandbox)
+            if cli_problem:
+                await self._send_message(channel_id, thread_ts, f"Cannot run `{role_agent.id}`: {cli_problem}")
+                return
+        ask_checkout = None
+        if in_ask_mode(session):
+            try:
+                ask_checkout = await prepare_ask_checkout(project)
+            except AskCheckoutError as exc:
+                await self._send_message(channel_id, thread_ts, f"Cannot answer here: {exc}")
+                return
+        adapter = self._get_adapter(agent)
+        overrides = overrides or RunOverrides()
+        model = overrides.model or role_model(agent, project, session)
+        requested_params = overrides.adapter_params()
+        supported = getattr(adapter, "supported_overrides", frozenset())
+        adapter_params = {name: value for name, value in requested_params.items() if name in supported}
+        ignored = sorted(set(requested_params) - set(adapter_params))
 
         history_snapshot = self._session_manager.get_conversation_history(session.id)
         adapter_history = self._format_history_for_adapter(history_snapshot)
 
         interaction_context = self._session_manager.get_context_for_agent(session.id)
-        task_text = self._build_task_text(interaction_context, user_text)
+        _, preferences = session_preferences(session)
+        task_text = self._build_task_text(
+            interaction_context,
+            user_text,
+            feedback=intent == MessageIntent.FEEDBACK,
+            design=in_design_phase(session),
+            ask=ask_checkout is not None,
+            locale=preferences.locale,
+        )
+
+        override_note = describe_overrides(RunOverrides(model=overrides.model, **adapter_params))
+        with_overrides = f" with {override_note}" if override_note else ""
+        read_only = " read-only" if ask_checkout else ""
+        received_message = f"Message received — running `{agent.id}`{read_only} now{with_overrides}."
+        if cast and cast.multi_agent:
+            received_message = f"Message received — {cast.describe()} are on it{with_overrides}."
+        if ignored:
+            names = " and ".join(name.replace("_", " ") for name in ignored)
+            received_message += f" `{agent.id}` does not support {names} overrides; using its defaults."
+        estimate = estimate_run_cost(agent, model, task_text)
+        if estimate and agent.max_cost_usd is not None and estimate.cost_usd > agent.max_cost_usd:
+            await self._send_message(
+                channel_id,
+                thread_ts,
+                f"Not running `{agent.id}`: the estimated cost of ~{format_cost(estimate.cost_usd)} "
+                f"exceeds its limit of {format_cost(agent.max_cost_usd)} (`max_cost_usd`).",
+            )
+            return
+        if estimate:
+            received_message = f"{received_message} Estimated cost: ~{format_cost(estimate.cost_usd)}"
+        await self._send_message(channel_id, thread_ts, received_message)
 
+        checkpoint = None
+        snapshot = None
+        if ask_checkout is None:
+            checkpoint = await self._git_workflow.current_commit(session.project_path)
+            context_delta: Dict[str, object] = {LAST_RUN_CONTEXT_KEY: {"prompt": user_text, "checkpoint": checkpoint}}
+            if checkpoint and START_COMMIT_CONTEXT_KEY not in session.session_context:
+                context_delta[START_COMMIT_CONTEXT_KEY] = checkpoint
+            self._session_manager.update_session_context(session.id, context_delta)
         self._session_manager.append_user_message(session.id, user_text)
+        if ask_checkout is None:
+            snapshot = await self._snapshot_workspace(session, project)
 
         run_id = f"{channel_id}_{thread_ts}_{int(time.time() * 1000)}"
         run_task = asyncio.current_task()
+        # Only runs that start count toward `max_runs_per_month`, not refused ones
+        self._session_manager.record_project_run(project.id)
+        self._session_manager.set_run_state(session.id, SessionStatus.RUNNING)
         self._active_runs[run_id] = {
             "task": run_task,
             "session_id": str(session.id),
             "agent_id": agent.id,
             "started_at": time.time(),
+            # The checkout the run edits, for parking its changes at shutdown; ask runs edit a throwaway one
+            "workdir": None if ask_checkout else session.project_path,
         }
+        self._session_manager.events.publish(RunStarted(session.id, project.id, agent.id, run_id))
+        journal_entry = None
+        if self._journal is not None:
+            journal_entry = await self._journal.start(
+                JournalEntry(
+                    run_id=run_id,
+                    session_id=str(session.id),
+                    project_id=project.id,
+                    channel_id=channel_id,
+                    thread_ts=thread_ts,
+                    agent_id=agent.id,
+                    workdir=str(ask_checkout.path if ask_checkout else session.project_path),
+                    read_only=ask_checkout is not None,
+                    checkpoint=checkpoint,
+                )
+            )
 
+        # Ask mode has nothing to format, verify, or publish
+        steps = [PipelineStep(EDIT_STEP)] if ask_checkout else pipeline_for(project)
+        edit_index = next(index for index, step in enumerate(steps) if step.name == EDIT_STEP)
+        pipeline = _PipelineRun(
+            session=session,
+            project=project,
+            agent=agent,
+            channel_id=channel_id,
+            thread_ts=thread_ts,
+            cast=cast,
+            request=user_text,
+        )
+        if incident:
+            pipeline.audit = IncidentAudit(audit_path(self._config.config_dir, project.id))
+        self._audit(
+            pipeline,
+            "run started",
+            agent=agent.id,
+            model=model,
+            read_only=ask_checkout is not None,
+            prompt=snippet(user_text),
+        )
+        quiet = preferences.verbosity == Verbosity.QUIET and not incident
+        stream = None if quiet else self._output_stream(adapter, agent, channel_id, thread_ts)
+        watcher = None if ask_checkout or quiet else self._file_watcher(session, channel_id, thread_ts)
+        heartbeat = None if quiet else self._heartbeat(agent, channel_id, thread_ts)
+        started_at = time.monotonic()
+        run_params = {**adapter_params, **self._run_callbacks(adapter, stream, heartbeat)}
         try:
-            result = await self._invoke_adapter(
-                adapter=adapter,
-                agent=agent,
-                session=session,
-                project=project,
-                task_text=task_text,
-                adapter_history=adapter_history,
-                channel_id=channel_id,
-                thread_ts=thread_ts,
-            )
+            for index, step in enumerate(steps[:edit_index]):
+                if step.name == PLAN_STEP:
+                    plan_text = self._build_task_text(
+                        interaction_context,
+                        user_text,
+                        feedback=intent == MessageIntent.FEEDBACK,
+                        plan_only=True,
+                        locale=preferences.locale,
+                    )
+                    planner = cast.planner if cast else agent
+                    if cast and cast.multi_agent:
+                        await self._send_message(channel_id, thread_ts, step_note(PLANNER, planner))
+                    plan = await self._plan(
+                        pipeline,
+                        agent=planner,
+                        adapter=adapter if planner is agent else self._get_adapter(planner),
+                        task_text=plan_text,
+                        adapter_history=adapter_history,
+                        model=model if planner is agent else role_model(planner, project, session),
+                        adapter_params=adapter_params if planner is agent else {},
+                    )
+                    self._audit(pipeline, "step", step=PLAN_STEP, passed=plan is not None)
+                    if plan is None:
+                        return
+                    pipeline.plan = plan
+                    task_text = self._build_task_text(
+                        interaction_context,
+                        user_text,
+                        feedback=intent == MessageIntent.FEEDBACK,
+                        design=in_design_phase(session),
+                        plan=plan,
+                        locale=preferences.locale,
+                    )
+                elif not await self._run_step(pipeline, step, steps[index + 1]):
+                    note = f"Stopped at the `{step.name}` step, so `{agent.id}` did not start on the changes."
+                    self._session_manager.append_agent_message(session.id, note)
+                    pipeline.reply.append(note)
+                    await self._flush(pipeline)
+                    return
+            if cast and cast.multi_agent:
+                await self._send_message(channel_id, thread_ts, step_note(CODER, agent))
+            async with (
+                stream or contextlib.nullcontext(),
+                watcher or contextlib.nullcontext(),
+                heartbeat or contextlib.nullcontext(),
+            ):
+                with self._journal_processes(journal_entry):
+                    result = await self._invoke_adapter(
+                        adapter=adapter,
+                        agent=agent,
+                        session=session,
+                        project=project,
+                        task_text=task_text,
+                        adapter_history=adapter_history,
+                        channel_id=channel_id,
+                        thread_ts=thread_ts,
+                        model=model,
+                        adapter_params=run_params,
+                        workdir=ask_checkout.path if ask_checkout else None,
+                    )
             if not result:
+                # Timed out, over its cost limit, or crashed; the reason is already in the thread
+                self._session_manager.record_run(
+                    session.id,
+                    RunRecord(agent_id=agent.id, success=False, wall_secs=time.monotonic() - started_at, model=model),
+                )
+                self._audit(pipeline, "step", step=EDIT_STEP, passed=False)
                 return
+        except asyncio.CancelledError as exc:
+            # `!cancel`: close the turn so the next run does not see an unanswered request.
+            # At shutdown the router closes it once it knows where the unfinished changes went.
+            if not cancelled_by_shutdown(exc):
+                self._session_manager.append_agent_message(session.id, CANCELLED_REPLY)
+            self._session_manager.record_run(
+                session.id,
+                RunRecord(
+                    agent_id=agent.id,
+                    success=False,
+                    wall_secs=time.monotonic() - started_at,
+                    model=model,
+                    cancelled=True,
+                ),
+            )
+            self._audit(pipeline, "run cancelled")
+            raise
         finally:
             self._active_runs.pop(run_id, None)
+            if self._journal is not None:
+                self._journal.finish(run_id)
+            discarded = await discard_ask_changes(ask_checkout) if ask_checkout else False
+
+        run_cost = cost_for_result(agent, model, task_text, result)
+        if run_cost:
+            self._session_manager.record_run_cost(session.id, run_cost.cost_usd)
+        over_budget = run_cost is not None and agent.max_cost_usd is not None and run_cost.cost_usd > agent.max_cost_usd
+        self._audit(
+            pipeline,
+            "step",
+            step=EDIT_STEP,
+            passed=result.success,
+            files=sorted({edit.path for edit in result.file_edits}),
+            cost_usd=run_cost.cost_usd if run_cost else None,
+            errors=result.errors,
+        )
 
         if result.structured_output:
             self._session_manager.update_session_context(
@@ -132,11 +544,28 @@ class AgentTaskRunner:
 
         if result.errors:
             response_text = f"{response_text}\n\nErrors:\n" + "\n".join(result.errors)
+        if not result.success:
+            hint = auth_failure_hint(agent, "\n".join([*result.errors, result.output_text]))
+            if hint:
+                response_text = f"{response_text}\n\n{hint}"
 
-        if result.file_edits and not result.structured_output:
+        if discarded:
+            response_text = f"{response_text}\n\n{ASK_MODE_DISCARDED}"
+        elif result.file_edits and not result.structured_output:
             edits_summary = ", ".join({edit.path for edit in result.file_edits})
             response_text = f"{response_text}\n\nDetected file edits: {edits_summary}"
 
+        if over_budget:
+            response_text = (
+                f"{response_text}\n\nThis run cost ~{format_cost(run_cost.cost_usd)}, "
+                f"over `{agent.id}`'s limit of {format_cost(agent.max_cost_usd)} (`max_cost_usd`)."
+            )
+        if preferences.verbosity == Verbosity.VERBOSE or incident:
+            model_display = f" `{model}`" if model else ""
+            cost_display = f", ~{format_cost(run_cost.cost_usd)}" if run_cost else ""
+            elapsed = f"{time.monotonic() - started_at:.0f}s"
+            response_text = f"{response_text}\n\nRun details: `{agent.id}`{model_display}, {elapsed}{cost_display}."
+
         user_message = ConversationMessage(role="user", content=user_text)
         self._session_manager.append_interaction(
             session.id,
@@ -146,14 +575,481 @@ class AgentTaskRunner:
         )
 
         self._session_manager.append_agent_message(session.id, response_text)
+        self._session_manager.record_run(
+            session.id,
+            RunRecord(
+                agent_id=agent.id,
+                success=result.success,
+                wall_secs=time.monotonic() - started_at,
+                model=model,
+                exit_code=result.exit_code,
+                cost_usd=run_cost.cost_usd if run_cost else None,
+                files=sorted({edit.path for edit in result.file_edits}),
+            ),
+        )
         self._session_manager.update_session_context(session.id, result.session_context)
 
-        pr_title = self._get_session_pr_title(session)
-        pr_message = await self._git_workflow.maybe_publish_code_changes(session, project, result, pr_title)
-        if pr_message:
-            response_text = f"{response_text}\n\n{pr_message}"
+        pipeline.result = result
+        pipeline.snapshot = snapshot
+        pipeline.reply.append(response_text)
+        after_edit = steps[edit_index + 1 :]
+        for index, step in enumerate(after_edit):
+            following = after_edit[index + 1] if index + 1 < len(after_edit) else None
+            if not await self._run_step(pipeline, step, following):
+                pipeline.reply.append(self._stop_note(step, after_edit[index + 1 :]))
+                break
+        if ask_checkout is None:
+            await self._record_checkpoint(session, user_text, checkpoint)
+            await self._snapshot_changes(pipeline)
+        await self._flush(pipeline)
+        self._audit(pipeline, "run finished", published=pipeline.published, reply=snippet(response_text))
+        await self._post_run_summary(pipeline, time.monotonic() - started_at, since=checkpoint)
+        await self._notify_finished(session, project, agent, thread_ts)
+
+    async def _post_run_summary(self, pipeline: _PipelineRun, wall_secs: float, since: Optional[str]) -> None:
+        """Post what the run did with next-step buttons, in chats that have them (see `src/core/run_summary.py`)."""
+        if not self._send_run_summary or pipeline.result is None:
+            return
+        session = pipeline.session
+        changes = await collect_change_stats(session.project_path, since) if since else None
+        pr_refs = self._session_manager.list_pr_refs(session.id, role=PullRequestRole.PRIMARY)
+        summary = RunSummary(
+            agent_id=pipeline.agent.id,
+            success=pipeline.result.success,
+            wall_secs=wall_secs,
+            exit_code=pipeline.result.exit_code,
+            changes=changes,
+            pr_url=pr_refs[0].url if pr_refs else None,
+        )
+        steps = next_steps(summary, has_checks=has_project_checks(pipeline.project))
+        try:
+            await self._send_run_summary(pipeline.channel_id, pipeline.thread_ts, format_run_summary(summary), steps)
+        except Exception:
+            LOGGER.warning("Could not post the run summary for session %s", session.id, exc_info=True)
+
+    async def _notify_finished(self, session: Session, project: Project, agent: Agent, thread_ts: str) -> None:
+        """Tell the session's starter that the run finished, where their `!prefs` `notify` says."""
+        target = notify_target(session)
+        if not target:
+            return
+        text = f"`{agent.id}` finished a run for `{project.id}` (thread {thread_ts})."
+        try:
+            await self._send_message(target, None, text)
+        except Exception:
+            LOGGER.warning("Could not notify %s that session %s finished a run", target, session.id, exc_info=True)
+
+    async def _plan(
+        self,
+        pipeline: _PipelineRun,
+        *,
+        agent: Agent,
+        adapter: AgentAdapter,
+        task_text: str,
+        adapter_history: list[Dict[str, str]],
+        model: Optional[str],
+        adapter_params: Mapping[str, object],
+    ) -> Optional[str]:
+        """The `plan` step: run `agent` (the planner) on the request without editing files and post its plan."""
+        session = pipeline.session
+        result = await self._invoke_adapter(
+            adapter=adapter,
+            agent=agent,
+            session=session,
+            project=pipeline.project,
+            task_text=task_text,
+            adapter_history=adapter_history,
+            channel_id=pipeline.channel_id,
+            thread_ts=pipeline.thread_ts,
+            model=model,
+            adapter_params=adapter_params,
+        )
+        if not result:
+            return None
+        run_cost = cost_for_result(agent, model, task_text, result)
+        if run_cost:
+            self._session_manager.record_run_cost(session.id, run_cost.cost_usd)
+        plan = result.structured_output.slack_message if result.structured_output else result.output_text
+        if not result.success or not plan.strip():
+            message = f"`{agent.id}` could not make a plan, so it did not start on the changes."
+            if result.errors:
+                message = f"{message}\n\nErrors:\n" + "\n".join(result.errors)
+            self._session_manager.append_agent_message(session.id, message)
+            await self._send_message(pipeline.channel_id, pipeline.thread_ts, message)
+            return None
+        await self._send_message(pipeline.channel_id, pipeline.thread_ts, f"Plan from `{agent.id}`:\n{plan}")
+        return plan
+
+    async def _run_step(self, pipeline: _PipelineRun, step: PipelineStep, following: Optional[PipelineStep]) -> bool:
+        """Run a pipeline step other than `plan` and `edit`; False stops the pipeline."""
+        passed = await self._run_pipeline_step(pipeline, step, following)
+        self._audit(pipeline, "step", step=step.name, passed=passed)
+        return passed
+
+    @staticmethod
+    def _audit(pipeline: _PipelineRun, event: str, **details: object) -> None:
+        """Add a run event to the project's incident audit log, when it is in incident mode."""
+        if pipeline.audit:
+            pipeline.audit.record(event, session=str(pipeline.session.id), thread=pipeline.thread_ts, **details)
+
+    async def _run_pipeline_step(
+        self, pipeline: _PipelineRun, step: PipelineStep, following: Optional[PipelineStep]
+    ) -> bool:
+        project = pipeline.project
+        if step.is_hook:
+            return await self._run_commands(
+                pipeline, step.name, [CommandStep(run=step.run or "", timeout_secs=step.timeout_secs)]
+            )
+        if step.name == FORMAT_STEP:
+            return await self._run_commands(pipeline, FORMAT_STEP, project.format_commands)
+        if step.name == VERIFY_STEP:
+            if not has_project_checks(project):
+                LOGGER.info("Skipping the verify step: project %s has no checks", project.id)
+                return True
+            results = await run_project_checks(project, pipeline.session.project_path)
+            pipeline.reply.append(format_verify_results(results))
+            passed = checks_passed(results)
+            self._session_manager.record_checks(pipeline.session.id, passed)
+            return passed
+        if step.name == APPROVE_STEP:
+            return await self._approve(pipeline, step, following)
+        if step.name == PR_STEP:
+            pr_title = self._get_session_pr_title(pipeline.session)
+            assert pipeline.result is not None
+            pipeline.pr_message = await self._git_workflow.maybe_publish_code_changes(
+                pipeline.session, project, pipeline.result, pr_title
+            )
+            pipeline.published = True
+            if pipeline.pr_message:
+                pipeline.reply.append(pipeline.pr_message)
+            return True
+        if step.name == REVIEW_STEP:
+            await self._review(pipeline)
+            return True
+        if step.name == DIFF_REVIEW_STEP:
+            await self._post_run_diff(pipeline)
+            return True
+        raise ValueError(f"Unknown pipeline step {step.name}")
+
+    async def _run_commands(self, pipeline: _PipelineRun, name: str, commands: List[CommandStep]) -> bool:
+        """Format and hook steps: run the commands in the project's sandbox; a failure is reported and stops."""
+        results = await run_verify_steps(
+            commands,
+            pipeline.session.project_path,
+            sandbox=Sandbox(pipeline.project.sandbox, pipeline.project.dev_env),
+            phase=name,
+        )
+        if checks_passed(results):
+            return True
+        title = "Format steps" if name == FORMAT_STEP else f"Hook `{name}`"
+        pipeline.reply.append(format_verify_results(results, title=title))
+        return False
+
+    async def _approve(self, pipeline: _PipelineRun, step: PipelineStep, following: Optional[PipelineStep]) -> bool:
+        """The `approve` step: ask the thread whether the run goes on, after posting what it has so far."""
+        await self._flush(pipeline)
+        if self._confirmations is None:
+            pipeline.reply.append("This chat cannot ask for approval, so the run was not approved.")
+            return False
+        question = f"Approve to continue with the {following.name} step." if following else "Approve this run."
+        timeout_secs = step.timeout_secs or DEFAULT_APPROVE_TIMEOUT_SECS
+        with self._session_manager.awaiting(pipeline.session.id, SessionStatus.AWAITING_APPROVAL):
+            return await self._confirmations.ask(
+                pipeline.channel_id, pipeline.thread_ts, pipeline.agent.id, question, timeout_secs
+            )
+
+    async def _review(self, pipeline: _PipelineRun) -> None:
+        """The `review` step: the project's reviewer critiques the run's changes in the thread."""
+        session, project = pipeline.session, pipeline.project
+        reviewer = pipeline.cast.reviewer if pipeline.cast else None
+        if reviewer is None:
+            LOGGER.info("Skipping the review step: project %s has no reviewer", project.id)
+            return
+        diff_text = await self._snapshot_changes(pipeline) or await self._git_workflow.pending_diff(
+            session.project_path
+        )
+        if not diff_text and pipeline.published:
+            diff_text = await self._git_workflow.session_diff(session, project)
+        await self._flush(pipeline)
+        if not diff_text:
+            await self._send_message(
+                pipeline.channel_id, pipeline.thread_ts, f"No changes for `{reviewer.id}` to review."
+            )
+            return
+        await self._send_message(pipeline.channel_id, pipeline.thread_ts, step_note(REVIEWER, reviewer))
+        task_text = review_task(pipeline.request, diff_text, plan=pipeline.plan)
+        model = role_model(reviewer, project, session)
+        result = await self._invoke_adapter(
+            adapter=self._get_adapter(reviewer),
+            agent=reviewer,
+            session=session,
+            project=project,
+            task_text=task_text,
+            adapter_history=[],
+            channel_id=pipeline.channel_id,
+            thread_ts=pipeline.thread_ts,
+            model=model,
+            adapter_params={},
+        )
+        if not result:
+            return
+        run_cost = cost_for_result(reviewer, model, task_text, result)
+        if run_cost:
+            self._session_manager.record_run_cost(session.id, run_cost.cost_usd)
+        review = result.structured_output.slack_message if result.structured_output else result.output_text
+        if not result.success or not review.strip():
+            message = f"`{reviewer.id}` could not review the changes."
+            if result.errors:
+                message = f"{message}\n\nErrors:\n" + "\n".join(result.errors)
+            await self._send_message(pipeline.channel_id, pipeline.thread_ts, message)
+            return
+        message = format_review(reviewer, review)
+        # In the history, so a follow-up can ask the coder to address it
+        self._session_manager.append_agent_message(session.id, message)
+        await self._send_message(pipeline.channel_id, pipeline.thread_ts, message)
+
+    async def _post_run_diff(self, pipeline: _PipelineRun) -> None:
+        """The `diff-review` step: post the run's changes when there is no PR to look at."""
+        if not self._post_diff:
+            return
+        session = pipeline.session
+        diff_text: Optional[str] = await self._snapshot_changes(pipeline)
+        if diff_text or not pipeline.published:
+            diff_text = diff_text or await self._git_workflow.pending_diff(session.project_path)
+        elif pipeline.pr_message:
+            # Committed changes of a local-only session; a PR shows its own
+            diff_text = await self._git_workflow.session_diff(session, pipeline.project)
+        if diff_text:
+            await self._flush(pipeline)
+            await self._post_diff(pipeline.channel_id, pipeline.thread_ts, diff_text)
 
-        await self._send_message(channel_id, thread_ts, response_text)
+    async def _snapshot_changes(self, pipeline: _PipelineRun) -> str:
+        """Diff a non-git project against its snapshot once, noting in the reply how to roll it back."""
+        if pipeline.snapshot is None:
+            return ""
+        if pipeline.snapshot_diff is None:
+            pipeline.snapshot_diff = await self._snapshot_diff(pipeline.snapshot, pipeline.session)
+            if pipeline.snapshot_diff:
+                pipeline.reply.append(
+                    f"{format_diff_overview(split_diff(pipeline.snapshot_diff))}. This folder is not a git "
+                    "repository, so nothing was committed; `!rollback` restores it to how it was before this run."
+                )
+        return pipeline.snapshot_diff
+
+    async def _flush(self, pipeline: _PipelineRun) -> None:
+        """Send the reply so far as one message."""
+        if pipeline.reply:
+            await self._send_message(pipeline.channel_id, pipeline.thread_ts, "\n\n".join(pipeline.reply))
+            pipeline.reply.clear()
+
+    @staticmethod
+    def _stop_note(step: PipelineStep, skipped: Sequence[PipelineStep]) -> str:
+        if not skipped:
+            return f"Stopped at the `{step.name}` step."
+        names = ", ".join(f"`{later.name}`" for later in skipped)
+        note = f"Stopped at the `{step.name}` step, so {names} did not run"
+        if any(later.name == PR_STEP for later in skipped):
+            return f"{note}; the changes are left uncommitted."
+        return f"{note}."
+
+    async def _record_checkpoint(self, session: Session, prompt: str, before: Optional[str]) -> None:
+        """Add where the run left the session branch to its `!timeline`."""
+        commit = await self._git_workflow.current_commit(session.project_path)
+        if not commit:
+            return
+        files = await changed_files(session.project_path, before, commit)
+        self._session_manager.update_session_context(session.id, add_checkpoint(session, prompt, before, commit, files))
+
+    def _output_stream(
+        self, adapter: AgentAdapter, agent: Agent, channel_id: str, thread_ts: str
+    ) -> Optional[OutputStream]:
+        """A live output message for the run, when the adapter reports output and messages can be edited."""
+        if not getattr(adapter, "streams_output", False):
+            return None
+        if self._update_message is None or self._config.stream_interval_secs <= 0:
+            return None
+        return OutputStream(
+            channel_id,
+            thread_ts,
+            label=agent.id,
+            send_message=self._send_message,
+            update_message=self._update_message,
+            interval_secs=self._config.stream_interval_secs,
+            message_chars=self._chat_capabilities(channel_id).max_message_chars if self._chat_capabilities else None,
+        )
+
+    def _heartbeat(self, agent: Agent, channel_id: str, thread_ts: str) -> Optional[Heartbeat]:
+        """A "still working" line for quiet stretches of the run, unless `REMOTE_CODER_HEARTBEAT_SECS` is 0."""
+        if self._config.heartbeat_secs <= 0:
+            return None
+        return Heartbeat(
+            channel_id,
+            thread_ts,
+            label=agent.id,
+            send_message=self._send_message,
+            update_message=self._update_message,
+            interval_secs=self._config.heartbeat_secs,
+        )
+
+    def _journal_processes(self, entry: Optional[JournalEntry]) -> ContextManager[None]:
+        """Record the pids of the run's agent processes in its journal entry, to stop them after a crash."""
+        journal = self._journal
+        if journal is None or entry is None:
+            return contextlib.nullcontext()
+        return observe_run_processes(lambda process: journal.add_process(entry, process.pid))
+
+    @staticmethod
+    def _run_callbacks(
+        adapter: AgentAdapter, stream: Optional[OutputStream], heartbeat: Optional[Heartbeat]
+    ) -> Dict[str, Callable]:
+        """`on_output` and `on_progress` for the adapters that report them, feeding the stream and the heartbeat."""
+        callbacks: Dict[str, Callable] = {}
+        if getattr(adapter, "streams_output", False) and (stream or heartbeat):
+
+            def on_output(text: str) -> None:
+                if heartbeat:
+                    heartbeat.touch()
+                if stream:
+                    stream.feed(text)
+
+            callbacks["on_output"] = on_output
+        if getattr(adapter, "reports_progress", False) and (stream or heartbeat):
+
+            def on_progress(event: ProgressEvent) -> None:
+                if heartbeat:
+                    heartbeat.touch()
+                if stream:
+                    stream.progress(event)
+
+            callbacks["on_progress"] = on_progress
+        return callbacks
+
+    def _file_watcher(self, session: Session, channel_id: str, thread_ts: str) -> Optional[FileChangeWatcher]:
+        """Progress notes for the files the run changes, unless `REMOTE_CODER_FILE_WATCH_SECS` is 0."""
+        if self._config.file_watch_secs <= 0:
+            return None
+
+        async def notify(text: str) -> None:
+            await self._send_message(channel_id, thread_ts, text)
+
+        return FileChangeWatcher(session.project_path, notify, interval_secs=self._config.file_watch_secs)
+
+    @contextlib.contextmanager
+    def _confirmations_for(self, agent: Agent, session: Session, channel_id: str, thread_ts: str) -> Iterator[None]:
+        """Let the agent's confirmation prompts be answered in the thread while its processes run."""
+        prompts = agent.confirm_prompts
+        if prompts is None or self._confirmations is None:
+            yield
+            return
+        broker = self._confirmations
+
+        async def ask(question: str) -> bool:
+            with self._session_manager.awaiting(session.id, SessionStatus.AWAITING_INPUT):
+                return await broker.ask(channel_id, thread_ts, agent.id, question, prompts.timeout_secs)
+
+        with answer_confirmations(ask):
+            yield
+
+    @staticmethod
+    @contextlib.contextmanager
+    def for_project(project: Project) -> Iterator[None]:
+        """Run the agents of `run_isolated` calls in this context the way `project` runs its own."""
+        token = _PROJECT.set(project)
+        try:
+            with run_in_dev_env(project.dev_env):
+                yield
+        finally:
+            _PROJECT.reset(token)
+
+    @contextlib.contextmanager
+    def _container_for(self, agent: Agent, sandbox: Optional[SandboxConfig]) -> Iterator[None]:
+        """Start the agent's CLI in the project's container while its processes run, if it asks for one."""
+        if sandbox is None or not sandbox.run_agent:
+            yield
+            return
+        container = AgentContainer(sandbox=sandbox, env_names=(*agent.env, *sandbox.env_passthrough))
+        with run_agents_in_container(container):
+            yield
+
+    @staticmethod
+    async def _agent_sandbox(
+        sandbox: Optional[SandboxConfig], workdir: Path, on_build: Optional[BuildNoticeFn] = None
+    ) -> Optional[SandboxConfig]:
+        """The sandbox the agent runs in, with the image of a devcontainer built first if it has none yet."""
+        if sandbox is None or not (sandbox.run_agent and sandbox.devcontainer):
+            return sandbox
+        return await resolve_devcontainer(sandbox, workdir, on_build)
+
+    async def _snapshot_workspace(self, session: Session, project: Project) -> Optional[SnapshotStore]:
+        """Snapshot a project that is not a git repository, so the run can be diffed and rolled back."""
+        if project.github or await self._git_workflow.is_git_repo(session.project_path):
+            return None
+        store = SnapshotStore.for_session(self._config.config_dir, session.id)
+        try:
+            await asyncio.to_thread(store.take, session.project_path)
+        except SnapshotError as exc:
+            LOGGER.warning("Not snapshotting session %s: %s", session.id, exc)
+            # An older snapshot would roll back to the wrong state
+            store.discard()
+            return None
+        return store
+
+    async def _snapshot_diff(self, snapshot: SnapshotStore, session: Session) -> str:
+        try:
+            return await asyncio.to_thread(snapshot.diff, session.project_path)
+        except SnapshotError as exc:
+            LOGGER.warning("Could not diff the workspace of session %s: %s", session.id, exc)
+            return ""
+
+    async def run_isolated(
+        self, agent: Agent, model: Optional[str], workdir: Path, user_text: str, context: str = ""
+    ) -> Tuple[AgentResult, Optional[float]]:
+        """Run an agent on a fresh prompt in `workdir`, outside any session history.
+
+        Used by `!compare`, `!bench`, and `!replay` (which passes the replayed
+        conversation so far as `context`); returns the result and its estimated
+        cost. Adapter errors propagate to the caller.
+        """
+        project = _PROJECT.get()
+        sandbox = project.sandbox if project else None
+        cli_problem = await self._cli_problem(agent, sandbox)
+        if cli_problem:
+            raise RuntimeError(cli_problem)
+        try:
+            sandbox = await self._agent_sandbox(sandbox, workdir)
+        except DevcontainerError as exc:
+            raise RuntimeError(f"cannot start the project's devcontainer: {exc}") from exc
+        if project is not None:
+            self._session_manager.record_project_run(project.id)
+        adapter = self._get_adapter(agent)
+        task_text = self._build_task_text(context, user_text)
+        session_id = str(uuid4())
+        try:
+            with self._container_for(agent, sandbox):
+                result = await self._run_attempt(
+                    adapter,
+                    agent,
+                    timeouts=agent.timeouts,
+                    task_text=task_text,
+                    project_path=str(workdir),
+                    session_id=session_id,
+                    conversation_history=[],
+                    model=model,
+                )
+        except RunTimeout as exc:
+            raise RuntimeError(f"timed out after {agent.timeout_secs}s") from exc
+        finally:
+            # One-off runs have no follow-up turn to keep a process warm for
+            if self._warm_pool is not None:
+                await self._warm_pool.discard_session(session_id)
+        run_cost = cost_for_result(agent, model, task_text, result)
+        return result, run_cost.cost_usd if run_cost else None
+
+    async def _cli_problem(self, agent: Agent, sandbox: Optional[SandboxConfig] = None) -> Optional[str]:
+        if not self._cli_checker or (sandbox is not None and sandbox.run_agent):
+            # The CLI of a containerized run comes with the image, not the host
+            return None
+        check = await self._cli_checker.check(agent)
+        return check.problem if check else None
 
     async def _invoke_adapter(
         self,
@@ -166,57 +1062,256 @@ class AgentTaskRunner:
         adapter_history: list[Dict[str, str]],
         channel_id: str,
         thread_ts: str,
+        model: Optional[str],
+        adapter_params: Mapping[str, object],
+        workdir: Optional[Path] = None,
     ) -> Optional[AgentResult]:
-        try:
-            return await adapter.run(
-                task_text=task_text,
-                project_path=str(session.project_path),
-                session_id=str(session.id),
-                conversation_history=adapter_history,
-                model=session.active_model,
-            )
-        except Exception as exc:  # pragma: no cover - defensive logging
-            LOGGER.exception("Adapter %s failed with model %s", agent.id, session.active_model)
+        """Run the agent in `workdir` (the session's checkout by default), reporting failures in the thread."""
+        timeouts = project.timeouts_for(agent)
+        workdir = workdir or session.project_path
 
-            default_model = agent.models.get("default") if agent.models else None
-            if session.active_model and default_model and session.active_model != default_model:
-                LOGGER.info("Retrying %s with default model %s", agent.id, default_model)
-                await self._send_message(
-                    channel_id,
-                    thread_ts,
-                    f"Failed with model `{session.active_model}`. Retrying with default model `{default_model}`...",
+        async def notify(message: str) -> None:
+            await self._send_message(channel_id, thread_ts, message)
+
+        try:
+            sandbox = await self._agent_sandbox(project.sandbox, workdir, notify)
+        except DevcontainerError as exc:
+            message = f"Cannot start `{agent.id}` in the project's devcontainer: {exc}"
+            self._session_manager.append_agent_message(session.id, message)
+            await self._send_message(channel_id, thread_ts, message)
+            return None
+        with (
+            self._confirmations_for(agent, session, channel_id, thread_ts),
+            self._container_for(agent, sandbox),
+            run_in_dev_env(project.dev_env),
+        ):
+            try:
+                return await self._run_with_retries(
+                    adapter,
+                    agent,
+                    timeouts=timeouts,
+                    task_text=task_text,
+                    project_path=str(workdir),
+                    session_id=str(session.id),
+                    conversation_history=adapter_history,
+                    model=model,
+                    channel_id=channel_id,
+                    thread_ts=thread_ts,
+                    **adapter_params,
                 )
-                try:
-                    result = await adapter.run(
-                        task_text=task_text,
-                        project_path=str(session.project_path),
-                        session_id=str(session.id),
-                        conversation_history=adapter_history,
-                        model=default_model,
-                    )
-                    self._session_manager.set_active_agent(
-                        session.id,
-                        agent.id,
-                        agent.type,
-                        default_model,
-                    )
-                    LOGGER.info("Fallback to default model succeeded for session %s", session.id)
-                    return result
-                except Exception as fallback_exc:
-                    LOGGER.exception("Fallback to default model also failed")
+            except RunPolicyViolation as exc:
+                LOGGER.warning("Stopped %s for session %s: %s", agent.id, session.id, exc)
+                message = f"Stopped `{agent.id}`: {exc}"
+                output = format_partial_output(exc.output)
+                if output:
+                    message = f"{message}\n\nOutput so far:\n{output}"
+                # Recorded as the run's reply, so the next turn knows this one did not finish
+                self._session_manager.append_agent_message(session.id, message)
+                await self._send_message(channel_id, thread_ts, message)
+                return None
+            except Exception as exc:  # pragma: no cover - defensive logging
+                LOGGER.exception("Adapter %s failed with model %s", agent.id, model)
+
+                default_model = agent.models.get("default") if agent.models else None
+                if model and default_model and model != default_model:
+                    LOGGER.info("Retrying %s with default model %s", agent.id, default_model)
                     await self._send_message(
                         channel_id,
                         thread_ts,
-                        f"Failed to run `{agent.id}` with both `{session.active_model}` and default model `{default_model}`: {fallback_exc}",
+                        f"Failed with model `{model}`. Retrying with default model `{default_model}`...",
                     )
-                    return None
+                    try:
+                        result = await self._run_attempt(
+                            adapter,
+                            agent,
+                            timeouts=timeouts,
+                            task_text=task_text,
+                            project_path=str(workdir),
+                            session_id=str(session.id),
+                            conversation_history=adapter_history,
+                            model=default_model,
+                            **adapter_params,
+                        )
+                        # A one-run model override does not change the session's model
+                        if model == session.active_model:
+                            self._session_manager.set_active_agent(
+                                session.id,
+                                agent.id,
+                                agent.type,
+                                default_model,
+                            )
+                        LOGGER.info("Fallback to default model succeeded for session %s", session.id)
+                        return result
+                    except Exception as fallback_exc:
+                        LOGGER.exception("Fallback to default model also failed")
+                        await self._send_message(
+                            channel_id,
+                            thread_ts,
+                            f"Failed to run `{agent.id}` with both `{model}` and default model `{default_model}`: "
+                            f"{fallback_exc}",
+                        )
+                        return None
+
+                message = f"Failed to run `{agent.id}`: {exc}"
+                hint = auth_failure_hint(agent, str(exc))
+                await self._send_message(channel_id, thread_ts, f"{message}\n\n{hint}" if hint else message)
+                return None
 
+    async def _run_with_retries(
+        self,
+        adapter: AgentAdapter,
+        agent: Agent,
+        *,
+        channel_id: str,
+        thread_ts: str,
+        timeouts: Optional[RunTimeouts],
+        **run_kwargs,
+    ) -> AgentResult:
+        """Run under the agent's policy: retry errors and timeouts up to `max_retries` within `max_cost_usd`.
+
+        Transient failures are retried first, with backoff (see `_attempt_with_backoff`).
+        Raises RunPolicyViolation once a timeout or the cost limit ends the run;
+        the last adapter error propagates when the retries run out.
+        """
+        attempts = agent.max_retries + 1
+        estimate = estimate_run_cost(agent, run_kwargs.get("model"), run_kwargs["task_text"])
+        spent = 0.0
+        status: Dict[str, str] = {}
+        for attempt in range(1, attempts):
+            try:
+                return await self._attempt_with_backoff(
+                    adapter, agent, channel_id, thread_ts, status, timeouts=timeouts, **run_kwargs
+                )
+            except RunTimeout as exc:
+                problem = str(exc)
+            except Exception as exc:
+                problem = f"it failed ({exc})"
+
+            # Failed attempts leave no usage report, so count each one at the pre-run estimate
+            if estimate:
+                spent += estimate.cost_usd
+                if agent.max_cost_usd is not None and spent + estimate.cost_usd > agent.max_cost_usd:
+                    raise RunPolicyViolation(
+                        f"{problem}, and another attempt would exceed its limit of "
+                        f"{format_cost(agent.max_cost_usd)} (`max_cost_usd`; ~{format_cost(spent)} spent)"
+                    )
+            LOGGER.info("Retrying %s (attempt %d of %d): %s", agent.id, attempt + 1, attempts, problem)
             await self._send_message(
                 channel_id,
                 thread_ts,
-                f"Failed to run `{agent.id}`: {exc}",
+                f"`{agent.id}` stopped: {problem}. Retrying (attempt {attempt + 1} of {attempts})…",
             )
-            return None
+
+        try:
+            return await self._attempt_with_backoff(
+                adapter, agent, channel_id, thread_ts, status, timeouts=timeouts, **run_kwargs
+            )
+        except RunTimeout as exc:
+            raise RunPolicyViolation(str(exc), exc.output) from None
+
+    async def _attempt_with_backoff(
+        self,
+        adapter: AgentAdapter,
+        agent: Agent,
+        channel_id: str,
+        thread_ts: str,
+        status: Dict[str, str],
+        *,
+        timeouts: Optional[RunTimeouts],
+        **run_kwargs,
+    ) -> AgentResult:
+        """`_run_attempt`, trying again after rate limits, provider errors, and network errors.
+
+        Waits `retry_backoff_secs`, doubling on each retry, for up to
+        `transient_retries` retries, and keeps the thread to one status message
+        (`status` holds its ts), edited on each retry. Once the retries run out,
+        the last failed result is returned or its error raised.
+        """
+        attempts = agent.transient_retries + 1
+        for attempt in range(1, attempts + 1):
+            try:
+                result = await self._run_attempt(adapter, agent, timeouts=timeouts, **run_kwargs)
+            except RunTimeout:
+                raise
+            except Exception as exc:
+                failure = classify_exception(exc)
+                if failure is None or attempt == attempts:
+                    raise
+                detail = str(exc) or failure.value
+            else:
+                failure = None if result.success else classify_errors(result.errors, result.output_text)
+                if failure is None or attempt == attempts:
+                    return result
+                detail = "; ".join(result.errors) or failure.value
+
+            delay = backoff_delay(agent.retry_backoff_secs, attempt)
+            LOGGER.info("Transient %s from %s, retrying in %.0fs: %s", failure.value, agent.id, delay, detail)
+            await self._post_retry_status(
+                channel_id,
+                thread_ts,
+                status,
+                f"`{agent.id}` hit a {failure.value} ({short_detail(detail)}); "
+                f"retrying ({attempt + 1}/{attempts}) in {delay:g}s…",
+            )
+            await asyncio.sleep(delay)
+        raise AssertionError("unreachable")  # pragma: no cover - the last attempt returns or raises
+
+    async def _post_retry_status(self, channel_id: str, thread_ts: str, status: Dict[str, str], text: str) -> None:
+        ts = status.get("ts")
+        if ts is not None and self._update_message is not None and await self._update_message(channel_id, ts, text):
+            return
+        ts = await self._send_message(channel_id, thread_ts, text)
+        if ts:
+            status["ts"] = ts
+
+    async def _run_attempt(
+        self, adapter: AgentAdapter, agent: Agent, *, timeouts: Optional[RunTimeouts], **run_kwargs
+    ) -> AgentResult:
+        """One adapter run within `timeouts`; raises RunTimeout with what the agent printed before it was stopped.
+
+        At the soft limit the agent's CLI gets SIGINT so it can stop cleanly; if
+        it is still running at the hard limit, the run is cancelled, which kills
+        the CLI. Adapters without a process are cancelled at the soft limit.
+        """
+        if timeouts is None:
+            with track_run_processes() as processes:
+                return _with_exit_code(await adapter.run(**run_kwargs), processes)
+        printed: List[str] = []
+        if getattr(adapter, "streams_output", False):
+            forward = run_kwargs.get("on_output")
+
+            def capture(text: str) -> None:
+                printed.append(text)
+                if forward:
+                    forward(text)
+
+            run_kwargs = {**run_kwargs, "on_output": capture}
+
+        with track_run_processes() as processes:
+            task = asyncio.ensure_future(adapter.run(**run_kwargs))
+            try:
+                done, _ = await asyncio.wait({task}, timeout=timeouts.soft_secs)
+                if done:
+                    return _with_exit_code(task.result(), processes)
+                limit = f"it ran longer than its limit of {timeouts.soft_secs}s (`timeout_secs`)"
+                if not interrupt_processes(processes):
+                    raise RunTimeout(limit, "".join(printed))
+                LOGGER.info("Interrupted %s; killing it at %ss", agent.id, timeouts.hard_secs)
+                done, _ = await asyncio.wait({task}, timeout=timeouts.hard_secs - timeouts.soft_secs)
+                if not done:
+                    raise RunTimeout(
+                        f"{limit} and was killed after ignoring the interrupt until {timeouts.hard_secs}s "
+                        "(`hard_timeout_secs`)",
+                        "".join(printed),
+                    )
+                output = "".join(printed)
+                if not output.strip() and not task.exception():
+                    output = task.result().output_text
+                raise RunTimeout(f"{limit} and was interrupted", output)
+            finally:
+                if not task.done():
+                    task.cancel()
+                    await asyncio.wait({task})
 
     def _get_adapter(self, agent: Agent) -> AgentAdapter:
         cached = self._adapter_cache.get(agent.id)
@@ -228,22 +1323,65 @@ class AgentTaskRunner:
         return adapter
 
     def _build_adapter(self, agent: Agent) -> AgentAdapter:
-        from ..agent_adapters import ClaudeAdapter, CodexAdapter, GeminiAdapter  # avoid circular import
+        from ..agent_adapters import (  # avoid circular import
+            ClaudeAdapter,
+            CodexAdapter,
+            GeminiAdapter,
+            HttpAgentAdapter,
+            MockAdapter,
+            OllamaAdapter,
+            OpenRouterAdapter,
+            WasmAgentAdapter,
+        )
         from .models import AgentType
 
         if agent.type == AgentType.CLAUDE:
-            return ClaudeAdapter(agent)
+            # Idle CLI processes are too costly to keep around in low-memory mode
+            warm_pool = None if self._config.resources.low_memory else self._warm_pool
+            return ClaudeAdapter(agent, warm_pool=warm_pool)
         if agent.type == AgentType.CODEX:
             return CodexAdapter(agent)
         if agent.type == AgentType.GEMINI:
             return GeminiAdapter(agent)
+        if agent.type == AgentType.OPENROUTER:
+            return OpenRouterAdapter(agent)
+        if agent.type == AgentType.OLLAMA:
+            return OllamaAdapter(agent)
+        if agent.type == AgentType.HTTP:
+            return HttpAgentAdapter(agent)
+        if agent.type == AgentType.WASM:
+            return WasmAgentAdapter(agent, self._plugins.get(agent.plugin or agent.id))
+        if agent.type == AgentType.MOCK:
+            return MockAdapter(agent)
         raise ValueError(f"No adapter available for agent type {agent.type}")
 
-    def _build_task_text(self, context: str, user_text: str) -> str:
+    def _build_task_text(
+        self,
+        context: str,
+        user_text: str,
+        *,
+        feedback: bool = False,
+        design: bool = False,
+        ask: bool = False,
+        plan_only: bool = False,
+        plan: Optional[str] = None,
+        locale: Optional[str] = None,
+    ) -> str:
         context_block = context if context else "No prior conversation."
+        planned = f"{PLANNED_NOTE}\n\nPLAN:\n{plan}" if plan else ""
+        wanted_notes = (
+            (DESIGN_NOTE, design),
+            (ASK_NOTE, ask),
+            (FEEDBACK_NOTE, feedback),
+            (PLAN_NOTE, plan_only),
+            (planned, bool(plan)),
+            (LOCALE_NOTE.format(locale=locale), bool(locale)),
+        )
+        notes = "".join(f"{note}\n\n" for note, wanted in wanted_notes if wanted)
         return (
             f"{CODE_TASK_WRAPPER}\n\n"
             f"## CONTEXT ON THE WORK SO FAR:\n{context_block}\n\n"
+            f"{notes}"
             f"CURRENT ASK:\nUSER:\n{user_text}\n"
             "Provide your answer below. If you changed code, summarize the edits and tests you ran."
         )
@@ -0,0 +1,140 @@
+"""Automatic agent selection from agent capabilities.
+
+Projects with `agent_selection: auto` have an agent picked for every request
+(until `!use` pins one). The policy is deliberately simple and predictable:
+
+1. Drop agents that cannot take the request at all: it carries images and the
+   agent does not support them, or it is larger than the agent's `max_context`.
+2. Prefer agents listing more of the project's languages.
+3. Among equals, keep the project's default agent, then take the cheaper
+   `cost_tier`, then the order of agents.yaml.
+"""
+
+from __future__ import annotations
+
+import logging
+from dataclasses import dataclass, field
+from pathlib import Path
+from typing import Dict, FrozenSet, Iterable, Optional, Sequence
+
+from .jobs import run_git
+from .models import Agent, CostTier
+
+LOGGER = logging.getLogger(__name__)
+
+COST_TIER_RANK = {CostTier.LOW: 0, CostTier.MEDIUM: 1, CostTier.HIGH: 2}
+
+LANGUAGE_EXTENSIONS: Dict[str, str] = {
+    ".py": "python",
+    ".rs": "rust",
+    ".go": "go",
+    ".ts": "typescript",
+    ".tsx": "typescript",
+    ".js": "javascript",
+    ".jsx": "javascript",
+    ".java": "java",
+    ".kt": "kotlin",
+    ".swift": "swift",
+    ".rb": "ruby",
+    ".php": "php",
+    ".cs": "csharp",
+    ".c": "c",
+    ".h": "c",
+    ".cc": "cpp",
+    ".cpp": "cpp",
+    ".hpp": "cpp",
+    ".scala": "scala",
+    ".ex": "elixir",
+    ".exs": "elixir",
+    ".dart": "dart",
+    ".sh": "shell",
+}
+# A language counts for a project once it makes up this share of its source files
+MIN_LANGUAGE_SHARE = 0.2
+
+
+@dataclass(frozen=True)
+class TaskRequirements:
+    """What a single request needs from the agent that runs it."""
+
+    needs_images: bool = False
+    estimated_tokens: int = 0
+    languages: FrozenSet[str] = field(default_factory=frozenset)
+
+
+@dataclass(frozen=True)
+class AgentChoice:
+    agent: Agent
+    reason: str
+
+
+def _rejection(agent: Agent, requirements: TaskRequirements) -> Optional[str]:
+    capabilities = agent.capabilities
+    if requirements.needs_images and not capabilities.supports_images:
+        return f"`{agent.id}` cannot read images"
+    if capabilities.max_context and requirements.estimated_tokens > capabilities.max_context:
+        return (
+            f"the request (~{requirements.estimated_tokens:,} tokens) exceeds "
+            f"`{agent.id}`'s {capabilities.max_context:,}-token context"
+        )
+    return None
+
+
+def _language_matches(agent: Agent, languages: FrozenSet[str]) -> FrozenSet[str]:
+    return languages & frozenset(agent.capabilities.languages)
+
+
+def select_agent(
+    agents: Sequence[Agent], requirements: TaskRequirements, default_agent_id: str
+) -> Optional[AgentChoice]:
+    """Pick the agent for a request, or None when no configured agent can take it."""
+    candidates = [agent for agent in agents if _rejection(agent, requirements) is None]
+    if not candidates:
+        return None
+
+    order = {agent.id: index for index, agent in enumerate(agents)}
+    best = min(
+        candidates,
+        key=lambda agent: (
+            -len(_language_matches(agent, requirements.languages)),
+            agent.id != default_agent_id,
+            COST_TIER_RANK[agent.capabilities.cost_tier],
+            order[agent.id],
+        ),
+    )
+
+    if best.id == default_agent_id:
+        return AgentChoice(best, "project default")
+    default = next((agent for agent in agents if agent.id == default_agent_id), None)
+    rejection = _rejection(default, requirements) if default else None
+    if rejection:
+        return AgentChoice(best, rejection)
+    matches = _language_matches(best, requirements.languages)
+    if matches:
+        return AgentChoice(best, f"best match for {', '.join(sorted(matches))}")
+    return AgentChoice(best, f"project default `{default_agent_id}` is not configured")
+
+
+def languages_for_files(paths: Iterable[str]) -> FrozenSet[str]:
+    """Return the languages that make up a meaningful share of `paths`."""
+    counts: Dict[str, int] = {}
+    for raw_path in paths:
+        language = LANGUAGE_EXTENSIONS.get(Path(raw_path).suffix.lower())
+        if language:
+            counts[language] = counts.get(language, 0) + 1
+    total = sum(counts.values())
+    if not total:
+        return frozenset()
+    return frozenset(language for language, count in counts.items() if count / total >= MIN_LANGUAGE_SHARE)
+
+
+async def detect_project_languages(project_path: Path) -> FrozenSet[str]:
+    """Guess a project's main languages from the files git tracks."""
+    try:
+        result = await run_git(project_path, "ls-files", check=False)
+    except OSError:
+        return frozenset()
+    if result.returncode != 0:
+        LOGGER.debug("Could not list files in %s for language detection", project_path)
+        return frozenset()
+    return languages_for_files(result.stdout.splitlines())
@@ -0,0 +1,234 @@
+"""How sessions turn out, per agent and per project (`!analytics`).
+
+Kept in `<config dir>/analytics.db` from the event bus (see
+`src/core/events.py`), so it outlives restarts and the sessions themselves:
+each session's project, every run (agent, success or cancellation, estimated
+cost), the latest result of the project's checks (`ChecksFinished`), and the
+PRs it opened and which of them were merged (`PrMerged`, from GitHub events).
+Only outcomes are stored, never prompts or code.
+
+A run counts for the agent that made it. A session's outcome, meaning its
+turns (runs), checks, PRs, merges, and cost, counts for the agent of its
+last run, the one whose work was kept. Sessions count from when they were
+first seen here; those without runs have no agent and only show per project.
+"""
+
+from __future__ import annotations
+
+import logging
+import os
+import sqlite3
+from dataclasses import dataclass
+from datetime import datetime, timezone
+from pathlib import Path
+from threading import Lock
+from typing import Callable, List, Optional
+
+from .errors import AnalyticsError
+from .events import ChecksFinished, EventBus, PrMerged, PrOpened, RunFinished, SessionCreated, SessionEvent
+
+LOGGER = logging.getLogger(__name__)
+
+ANALYTICS_DB_FILE_NAME = "analytics.db"
+SCHEMA_VERSION = 1
+BY_AGENT = "agent_id"
+BY_PROJECT = "project_id"
+
+_SCHEMA = """
+CREATE TABLE IF NOT EXISTS sessions (
+    session_id TEXT PRIMARY KEY,
+    project_id TEXT NOT NULL,
+    agent_id TEXT,
+    created_at TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS runs (
+    session_id TEXT NOT NULL,
+    project_id TEXT NOT NULL,
+    agent_id TEXT NOT NULL,
+    success INTEGER NOT NULL,
+    cancelled INTEGER NOT NULL,
+    cost_usd REAL NOT NULL,
+    finished_at TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS runs_by_session ON runs (session_id);
+CREATE TABLE IF NOT EXISTS checks (
+    session_id TEXT PRIMARY KEY,
+    passed INTEGER NOT NULL,
+    at TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS prs (
+    session_id TEXT NOT NULL,
+    number INTEGER NOT NULL,
+    merged INTEGER NOT NULL,
+    PRIMARY KEY (session_id, number)
+);
+"""
+
+_OUTCOMES = """
+SELECT s.{key}, COUNT(*), COALESCE(SUM(r.runs), 0), COALESCE(SUM(r.cost), 0),
+       COUNT(c.passed), COALESCE(SUM(c.passed), 0), COUNT(p.opened), COALESCE(SUM(p.merged > 0), 0)
+FROM sessions AS s
+LEFT JOIN (SELECT session_id, COUNT(*) AS runs, SUM(cost_usd) AS cost FROM runs GROUP BY session_id) AS r
+    ON r.session_id = s.session_id
+LEFT JOIN checks AS c ON c.session_id = s.session_id
+LEFT JOIN (SELECT session_id, COUNT(*) AS opened, SUM(merged) AS merged FROM prs GROUP BY session_id) AS p
+    ON p.session_id = s.session_id
+WHERE s.created_at >= ? AND s.{key} IS NOT NULL
+GROUP BY s.{key}
+"""
+
+_RUNS = """
+SELECT r.{key}, COUNT(*), SUM(r.success AND NOT r.cancelled), SUM(r.cancelled)
+FROM runs AS r JOIN sessions AS s ON s.session_id = r.session_id
+WHERE s.created_at >= ?
+GROUP BY r.{key}
+"""
+
+
+@dataclass
+class OutcomeRow:
+    """Totals for one agent or project."""
+
+    key: str
+    sessions: int = 0
+    turns: int = 0  # Runs in those sessions, whichever agent made them
+    cost_usd: float = 0.0
+    checked: int = 0  # Sessions whose checks ran
+    checks_passed: int = 0  # ...and passed the last time
+    with_pr: int = 0  # Sessions that opened a PR
+    merged: int = 0  # ...and got one merged
+    runs: int = 0  # Made by this agent (or in this project)
+    runs_succeeded: int = 0
+    runs_cancelled: int = 0
+
+    @property
+    def run_success_rate(self) -> Optional[float]:
+        """Succeeded out of runs that finished, leaving out cancelled ones; None without any."""
+        finished = self.runs - self.runs_cancelled
+        return self.runs_succeeded / finished if finished else None
+
+    @property
+    def turns_per_session(self) -> Optional[float]:
+        return self.turns / self.sessions if self.sessions else None
+
+
+def _iso(when: datetime) -> str:
+    return when.astimezone(timezone.utc).isoformat()
+
+
+class AnalyticsStore:
+    """The outcome database; its writes are small enough to make from event handlers."""
+
+    def __init__(self, path: Path) -> None:
+        self.path = path
+        self._lock = Lock()
+        self._unsubscribe: Callable[[], None] = lambda: None
+        try:
+            path.parent.mkdir(parents=True, exist_ok=True)
+            self._db = sqlite3.connect(str(path), check_same_thread=False, isolation_level=None)
+            self._db.execute("PRAGMA journal_mode=WAL")
+            version = self._db.execute("PRAGMA user_version").fetchone()[0]
+            if version > SCHEMA_VERSION:
+                raise AnalyticsError(f"{path} was written by a newer Remote Coder (schema {version})")
+            self._db.executescript(_SCHEMA)
+            self._db.execute(f"PRAGMA user_version={SCHEMA_VERSION}")
+            os.chmod(path, 0o600)
+        except (OSError, sqlite3.Error) as exc:
+            raise AnalyticsError(f"Failed to open {path}: {exc}") from exc
+
+    def follow(self, bus: EventBus) -> None:
+        """Record the bus's events until the store is closed."""
+        self._unsubscribe = bus.subscribe(self.record, SessionCreated, RunFinished, ChecksFinished, PrOpened, PrMerged)
+
+    def record(self, event: SessionEvent) -> None:
+        """Add an event to the database; a failed write is logged, as analytics never hold up a session."""
+        session_id = str(event.session_id)
+        try:
+            with self._lock, self._db:
+                self._db.execute("BEGIN")
+                # Sessions from before the database existed are added when they next do something
+                self._db.execute(
+                    "INSERT OR IGNORE INTO sessions (session_id, project_id, created_at) VALUES (?, ?, ?)",
+                    (session_id, event.project_id, _iso(event.at)),
+                )
+                if isinstance(event, RunFinished):
+                    run = event.run
+                    self._db.execute(
+                        "INSERT INTO runs "
+                        "(session_id, project_id, agent_id, success, cancelled, cost_usd, finished_at) "
+                        "VALUES (?, ?, ?, ?, ?, ?, ?)",
+                        (
+                            session_id,
+                            event.project_id,
+                            run.agent_id,
+                            run.success,
+                            run.cancelled,
+                            run.cost_usd or 0.0,
+                            _iso(event.at),
+                        ),
+                    )
+                    self._db.execute(
+                        "UPDATE sessions SET agent_id = ? WHERE session_id = ?", (run.agent_id, session_id)
+                    )
+                elif isinstance(event, ChecksFinished):
+                    self._db.execute(
+                        "INSERT OR REPLACE INTO checks (session_id, passed, at) VALUES (?, ?, ?)",
+                        (session_id, event.passed, _iso(event.at)),
+                    )
+                elif isinstance(event, PrOpened):
+                    self._db.execute(
+                        "INSERT OR IGNORE INTO prs (session_id, number, merged) VALUES (?, ?, 0)",
+                        (session_id, event.number),
+                    )
+                elif isinstance(event, PrMerged):
+                    self._db.execute(
+                        "INSERT OR REPLACE INTO prs (session_id, number, merged) VALUES (?, ?, 1)",
+                        (session_id, event.number),
+                    )
+        except sqlite3.Error as exc:
+            LOGGER.warning("Could not record %s for session %s: %s", type(event).__name__, session_id, exc)
+
+    def report(self, by: str, since: Optional[datetime] = None) -> List[OutcomeRow]:
+        """Totals per agent (`BY_AGENT`) or project (`BY_PROJECT`) of the sessions first seen since `since`."""
+        if by not in (BY_AGENT, BY_PROJECT):
+            raise ValueError(f"Unknown analytics grouping {by!r}")
+        cutoff = _iso(since) if since else ""
+        rows: dict[str, OutcomeRow] = {}
+        try:
+            with self._lock:
+                outcomes = self._db.execute(_OUTCOMES.format(key=by), (cutoff,)).fetchall()
+                runs = self._db.execute(_RUNS.format(key=by), (cutoff,)).fetchall()
+        except sqlite3.Error as exc:
+            raise AnalyticsError(f"Failed to read {self.path}: {exc}") from exc
+        for key, sessions, turns, cost, checked, passed, with_pr, merged in outcomes:
+            rows[key] = OutcomeRow(key, sessions, turns, cost, checked, passed, with_pr, merged)
+        for key, count, succeeded, cancelled in runs:
+            row = rows.setdefault(key, OutcomeRow(key))
+            row.runs, row.runs_succeeded, row.runs_cancelled = count, succeeded, cancelled
+        return sorted(rows.values(), key=lambda row: (-row.sessions, -row.runs, row.key))
+
+    def close(self) -> None:
+        self._unsubscribe()
+        with self._lock:
+            self._db.close()
+
+
+def _share(part: int, whole: int) -> str:
+    return f"{part}/{whole}" if whole else "-"
+
+
+def format_outcome(row: OutcomeRow) -> str:
+    """E.g. "12 session(s), ~3.3 turn(s) each · runs 85% succeeded (34/40) · checks passed 7/9 · …"."""
+    parts = [f"{row.sessions} session(s)"]
+    if row.turns_per_session is not None:
+        parts[0] += f", ~{row.turns_per_session:.1f} turn(s) each"
+    rate = row.run_success_rate
+    if rate is not None:
+        parts.append(f"runs {rate:.0%} succeeded ({row.runs_succeeded}/{row.runs - row.runs_cancelled})")
+    parts.append(f"checks passed {_share(row.checks_passed, row.checked)}")
+    parts.append(f"PRs merged {_share(row.merged, row.with_pr)}")
+    cost = f"~${row.cost_usd:.2f}"
+    if row.merged:
+        cost += f", ~${row.cost_usd / row.merged:.2f} per merge"
+    parts.append(cost)
+    return " · ".join(parts)
@@ -0,0 +1,63 @@
+"""Decide whether a run's changes may be published without asking first.
+
+Projects with `approval` enabled keep agent changes on the local session
+branch until someone runs `!publish`. Small diffs that touch no protected
+path take a fast path and open the PR straight away, so the friction stays
+proportional to the risk of the change.
+"""
+
+from __future__ import annotations
+
+from dataclasses import dataclass, field
+from fnmatch import fnmatch
+from typing import List, Optional
+
+from .models import ApprovalPolicy
+
+
+@dataclass
+class DiffSize:
+    files: List[str] = field(default_factory=list)
+    lines: int = 0  # Added plus removed; binary files count as zero lines
+
+
+def parse_numstat(output: str) -> DiffSize:
+    """Parse `git diff --numstat --no-renames` output."""
+    size = DiffSize()
+    for line in output.splitlines():
+        parts = line.split("\t", 2)
+        if len(parts) != 3:
+            continue
+        added, removed, path = parts
+        size.files.append(path)
+        size.lines += sum(int(count) for count in (added, removed) if count.isdigit())
+    return size
+
+
+def matches_protected(path: str, patterns: List[str]) -> bool:
+    """A pattern matches as a glob, or as a directory containing the path."""
+    return any(fnmatch(path, pattern) or path.startswith(pattern.rstrip("/") + "/") for pattern in patterns)
+
+
+def approval_reason(policy: ApprovalPolicy, size: DiffSize) -> Optional[str]:
+    """Why these changes need approval, or None when they may be published now."""
+    protected = [path for path in size.files if matches_protected(path, policy.protected_paths)]
+    if protected:
+        shown = ", ".join(f"`{path}`" for path in protected[:3])
+        more = f" and {len(protected) - 3} more" if len(protected) > 3 else ""
+        return f"it touches protected paths ({shown}{more})"
+    if not (policy.max_files and policy.max_lines):
+        return "this project requires approval for every change"
+    if len(size.files) > policy.max_files or size.lines > policy.max_lines:
+        return (
+            f"it changes {len(size.files)} file(s) and {size.lines} line(s), over the small-change limit of "
+            f"{policy.max_files} file(s) and {policy.max_lines} line(s)"
+        )
+    return None
+
+
+def describe_fast_path(size: DiffSize) -> str:
+    return (
+        f"Small change ({len(size.files)} file(s), {size.lines} line(s), no protected paths), "
+        "so it was published without waiting for approval."
+    )
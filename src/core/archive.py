@@ -0,0 +1,202 @@
+"""Keeping old sessions in S3-compatible object storage (`REMOTE_CODER_ARCHIVE_URL`).
+
+With `REMOTE_CODER_ARCHIVE_URL=s3://bucket/prefix` set, each cleanup pass
+(see `src/core/cleanup.py`) uploads the sessions an earlier pass archived
+and then removes them from the session store; the next pass removes their
+transcripts from disk. A session is uploaded as its `!export`: the Markdown
+and the JSON with its transcript (messages, replies, and run records), PRs,
+checkpoints, and diff, under `<prefix>/sessions/<session id>/`. A session whose
+upload fails stays archived in the store and is tried again the next pass.
+
+`!archived <session id>` and `remote-coder sessions fetch <session id>`
+pull an uploaded session back for inspection.
+
+Requests are signed with AWS Signature Version 4 using `AWS_ACCESS_KEY_ID`,
+`AWS_SECRET_ACCESS_KEY`, and, for temporary credentials, `AWS_SESSION_TOKEN`.
+`REMOTE_CODER_ARCHIVE_ENDPOINT` points at another S3-compatible service (MinIO,
+Cloudflare R2, ...), which is addressed path-style, and
+`REMOTE_CODER_ARCHIVE_REGION` sets the signing region (default `us-east-1`).
+"""
+
+from __future__ import annotations
+
+import hashlib
+import hmac
+import logging
+import urllib.error
+import urllib.request
+from dataclasses import dataclass
+from datetime import datetime, timezone
+from typing import Dict, Mapping, Optional, Tuple
+from urllib.parse import quote, urlsplit
+
+from .errors import ArchiveError, ConfigError
+from .export import SessionExport, export_json, export_markdown
+from .privacy import PRIVACY, Egress, EgressBlocked
+
+LOGGER = logging.getLogger(__name__)
+
+DEFAULT_ARCHIVE_REGION = "us-east-1"
+# Seconds one upload or download may take
+REQUEST_TIMEOUT_SECS = 60
+
+MARKDOWN_NAME = "session.md"
+JSON_NAME = "session.json"
+
+
+@dataclass(frozen=True)
+class ArchiveSettings:
+    bucket: str
+    access_key_id: str
+    secret_access_key: str
+    prefix: str = ""  # Without slashes at either end
+    region: str = DEFAULT_ARCHIVE_REGION
+    endpoint: Optional[str] = None  # None uses AWS S3 in `region`
+    session_token: Optional[str] = None
+
+    @property
+    def base_url(self) -> str:
+        return (self.endpoint or f"https://s3.{self.region}.amazonaws.com").rstrip("/")
+
+    def location(self, key: str) -> str:
+        return f"s3://{self.bucket}/{key}"
+
+    @classmethod
+    def from_env(cls, environ: Mapping[str, str]) -> Optional["ArchiveSettings"]:
+        """The settings in `environ`, or None when `REMOTE_CODER_ARCHIVE_URL` is not set."""
+        url = (environ.get("REMOTE_CODER_ARCHIVE_URL") or "").strip()
+        if not url:
+            return None
+        parts = urlsplit(url)
+        if parts.scheme != "s3" or not parts.netloc:
+            raise ConfigError("REMOTE_CODER_ARCHIVE_URL must look like s3://bucket or s3://bucket/prefix")
+        access_key_id = environ.get("AWS_ACCESS_KEY_ID") or ""
+        secret_access_key = environ.get("AWS_SECRET_ACCESS_KEY") or ""
+        if not access_key_id or not secret_access_key:
+            raise ConfigError("REMOTE_CODER_ARCHIVE_URL needs AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY")
+        endpoint = (environ.get("REMOTE_CODER_ARCHIVE_ENDPOINT") or "").strip() or None
+        if endpoint and urlsplit(endpoint).scheme not in ("http", "https"):
+            raise ConfigError("REMOTE_CODER_ARCHIVE_ENDPOINT must be an http:// or https:// URL")
+        return cls(
+            bucket=parts.netloc,
+            prefix=parts.path.strip("/"),
+            access_key_id=access_key_id,
+            secret_access_key=secret_access_key,
+            session_token=environ.get("AWS_SESSION_TOKEN") or None,
+            region=(environ.get("REMOTE_CODER_ARCHIVE_REGION") or "").strip() or DEFAULT_ARCHIVE_REGION,
+            endpoint=endpoint,
+        )
+
+
+def _hmac(key: bytes, text: str) -> bytes:
+    return hmac.new(key, text.encode("utf-8"), hashlib.sha256).digest()
+
+
+class ObjectStorage:
+    """GET and PUT of single objects in an S3-compatible bucket; blocking, so call it off the event loop."""
+
+    def __init__(self, settings: ArchiveSettings) -> None:
+        self._settings = settings
+
+    def put(self, key: str, body: bytes, content_type: str) -> None:
+        self._request("PUT", key, body, content_type)
+
+    def get(self, key: str) -> Optional[bytes]:
+        """The object's content, or None when there is no such object."""
+        try:
+            return self._request("GET", key)
+        except urllib.error.HTTPError as exc:
+            if exc.code == 404:
+                return None
+            raise ArchiveError(f"{self._settings.location(key)}: HTTP {exc.code} {exc.reason}") from exc
+
+    def _request(self, method: str, key: str, body: bytes = b"", content_type: Optional[str] = None) -> bytes:
+        try:
+            PRIVACY.require(Egress.SESSION_ARCHIVE)
+        except EgressBlocked as exc:
+            raise ArchiveError(str(exc)) from exc
+        settings = self._settings
+        path = "/" + quote(f"{settings.bucket}/{key}", safe="/-_.~")
+        url = f"{settings.base_url}{path}"
+        headers = self.signed_headers(method, url, body, content_type)
+        request = urllib.request.Request(url, data=body if method == "PUT" else None, method=method, headers=headers)
+        try:
+            with urllib.request.urlopen(request, timeout=REQUEST_TIMEOUT_SECS) as response:
+                return response.read()
+        except urllib.error.HTTPError as exc:
+            if method == "GET":
+                raise
+            raise ArchiveError(f"{settings.location(key)}: HTTP {exc.code} {exc.reason}") from exc
+        except (urllib.error.URLError, OSError) as exc:
+            raise ArchiveError(f"{settings.location(key)}: {exc}") from exc
+
+    def signed_headers(
+        self, method: str, url: str, body: bytes, content_type: Optional[str], now: Optional[datetime] = None
+    ) -> Dict[str, str]:
+        """The request's headers with its Signature Version 4 `Authorization`."""
+        settings = self._settings
+        now = now or datetime.now(timezone.utc)
+        amz_date = now.strftime("%Y%m%dT%H%M%SZ")
+        day = amz_date[:8]
+        parts = urlsplit(url)
+        payload_hash = hashlib.sha256(body).hexdigest()
+        headers = {"host": parts.netloc, "x-amz-content-sha256": payload_hash, "x-amz-date": amz_date}
+        if content_type:
+            headers["content-type"] = content_type
+        if settings.session_token:
+            headers["x-amz-security-token"] = settings.session_token
+        names = sorted(headers)
+        signed_names = ";".join(names)
+        canonical_request = "\n".join(
+            [
+                method,
+                parts.path,
+                parts.query,
+                "".join(f"{name}:{headers[name].strip()}\n" for name in names),
+                signed_names,
+                payload_hash,
+            ]
+        )
+        scope = f"{day}/{settings.region}/s3/aws4_request"
+        string_to_sign = "\n".join(
+            ["AWS4-HMAC-SHA256", amz_date, scope, hashlib.sha256(canonical_request.encode("utf-8")).hexdigest()]
+        )
+        key = _hmac(f"AWS4{settings.secret_access_key}".encode("utf-8"), day)
+        for part in (settings.region, "s3", "aws4_request"):
+            key = _hmac(key, part)
+        signature = hmac.new(key, string_to_sign.encode("utf-8"), hashlib.sha256).hexdigest()
+        headers["authorization"] = (
+            f"AWS4-HMAC-SHA256 Credential={settings.access_key_id}/{scope}, "
+            f"SignedHeaders={signed_names}, Signature={signature}"
+        )
+        return headers
+
+
+class SessionArchive:
+    """Uploaded sessions, under `<prefix>/sessions/<session id>/`."""
+
+    def __init__(self, settings: ArchiveSettings, storage: Optional[ObjectStorage] = None) -> None:
+        self._settings = settings
+        self._storage = storage or ObjectStorage(settings)
+
+    def _key(self, session_id: str, name: str) -> str:
+        prefix = f"{self._settings.prefix}/" if self._settings.prefix else ""
+        return f"{prefix}sessions/{session_id}/{name}"
+
+    def upload(self, export: SessionExport) -> str:
+        """Upload the session's export; returns where it went. Raises ArchiveError."""
+        session_id = str(export.session.id)
+        # The JSON last: `fetch` finds a session by it, so a half-finished upload is not found
+        self._storage.put(self._key(session_id, MARKDOWN_NAME), export_markdown(export).encode(), "text/markdown")
+        self._storage.put(self._key(session_id, JSON_NAME), export_json(export).encode(), "application/json")
+        location = self._settings.location(self._key(session_id, ""))
+        LOGGER.info("Uploaded session %s to %s", session_id, location)
+        return location
+
+    def fetch(self, session_id: str) -> Optional[Tuple[str, str]]:
+        """The uploaded session's Markdown and JSON, or None when it was not uploaded. Raises ArchiveError."""
+        data = self._storage.get(self._key(session_id, JSON_NAME))
+        if data is None:
+            return None
+        markdown = self._storage.get(self._key(session_id, MARKDOWN_NAME)) or b""
+        return markdown.decode("utf-8", "replace"), data.decode("utf-8", "replace")
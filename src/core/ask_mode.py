@@ -0,0 +1,111 @@
+"""Question-only channels: ask about a project's code without changing it.
+
+Channels listed in a project's `ask_channels` start sessions in ask mode. The
+agent answers from the code but works in a separate detached `git worktree`
+of the project's default branch (its `github.default_base_branch`, or the
+checkout's current commit for local-only projects), kept under the system
+temp directory and shared by the project's ask sessions. The worktree is
+reset to the latest commit before each run and anything the agent changed in
+it is discarded afterwards, so nothing is committed, pushed, or left in the
+project's own checkout. Commands that change code or publish are not offered
+in ask mode, and ask runs do not wait for the edit runs of the project.
+"""
+
+from __future__ import annotations
+
+import shutil
+import subprocess
+import tempfile
+from dataclasses import dataclass
+from pathlib import Path
+
+from .jobs import run_git
+from .models import Project, Session
+
+ASK_MODE_CONTEXT_KEY = "ask_mode"
+ASK_WORKTREES_DIR = "remote-coder-ask"
+ASK_NOTE = (
+    "This is a question-only channel: answer from the code and explain where you found the answer. "
+    "Do not edit files, commit, or run commands that change the repository; nothing you change is kept."
+)
+ASK_MODE_DISCARDED = "Ask mode is read-only, so the file changes from this run were discarded."
+# Commands that neither change code nor publish
+ASK_MODE_COMMANDS = frozenset(
+    {
+        "use",
+        "status",
+        "sessions",
+        "tag",
+        "untag",
+        "move",
+        "end",
+        "pause",
+        "resume",
+        "cost",
+        "agents",
+        "models",
+        "cancel",
+        "prefs",
+        "incident",
+        "help",
+    }
+)
+
+
+class AskCheckoutError(Exception):
+    """The read-only checkout could not be prepared, e.g. the project is not a git repository."""
+
+
+@dataclass(frozen=True)
+class AskCheckout:
+    path: Path
+    commit: str  # What the run starts from
+
+
+def in_ask_mode(session: Session) -> bool:
+    return bool(session.session_context.get(ASK_MODE_CONTEXT_KEY))
+
+
+def ask_checkout_path(project: Project) -> Path:
+    return Path(tempfile.gettempdir()) / ASK_WORKTREES_DIR / project.id
+
+
+async def _git(cwd: Path, *args: str) -> subprocess.CompletedProcess:
+    return await run_git(cwd, *args, check=False)
+
+
+async def prepare_ask_checkout(project: Project) -> AskCheckout:
+    """The project's ask worktree, at the latest commit of its default branch and without changes."""
+    ref = project.github.default_base_branch if project.github else "HEAD"
+    resolved = await _git(project.path, "rev-parse", "--verify", "--quiet", f"{ref}^{{commit}}")
+    if resolved.returncode != 0 and ref != "HEAD":
+        resolved = await _git(project.path, "rev-parse", "--verify", "--quiet", "HEAD^{commit}")
+    if resolved.returncode != 0:
+        raise AskCheckoutError(f"ask mode needs `{project.path}` to be a git repository with at least one commit")
+    commit = resolved.stdout.strip()
+
+    worktree = ask_checkout_path(project)
+    if not (worktree / ".git").exists():
+        shutil.rmtree(worktree, ignore_errors=True)
+        worktree.parent.mkdir(parents=True, exist_ok=True)
+        await _git(project.path, "worktree", "prune")
+        added = await _git(project.path, "worktree", "add", "--detach", str(worktree), commit)
+        if added.returncode != 0:
+            raise AskCheckoutError(f"could not create the read-only checkout: {added.stderr.strip()}")
+        return AskCheckout(worktree, commit)
+    moved = await _git(worktree, "checkout", "--detach", "--force", commit)
+    if moved.returncode != 0:
+        raise AskCheckoutError(f"could not update the read-only checkout: {moved.stderr.strip()}")
+    await _git(worktree, "clean", "-fd")
+    return AskCheckout(worktree, commit)
+
+
+async def discard_ask_changes(checkout: AskCheckout) -> bool:
+    """Drop whatever a run changed or committed in the ask worktree; returns whether there was anything."""
+    status = await _git(checkout.path, "status", "--porcelain")
+    head = await _git(checkout.path, "rev-parse", "HEAD")
+    if not status.stdout.strip() and head.stdout.strip() == checkout.commit:
+        return False
+    await _git(checkout.path, "checkout", "--detach", "--force", "--quiet", checkout.commit)
+    await _git(checkout.path, "clean", "-fd")
+    return True
@@ -0,0 +1,114 @@
+"""Decide whether agents may run for a project right now.
+
+Four things stop automation: the project's group was paused with
+`!group <name> pause` (e.g. during a release freeze), the project is inside
+its `quiet_hours`, the session spent its `max_session_cost_usd` budget, or
+the project used up its `max_runs_per_month` quota. Chat commands keep
+working in all of these cases. Quiet hours without a `timezone` follow the
+session user's timezone (see `local_time`); run quotas follow calendar
+months in the daemon's local time and are counted in the state file.
+"""
+
+from __future__ import annotations
+
+from datetime import date, datetime, time
+from typing import Callable, Optional, Set
+
+from .local_time import to_local
+from .models import Project, QuietHours, Session
+from .pricing import format_cost
+
+Clock = Callable[[], datetime]
+# Agent runs a project started in the month of the given time (`SessionManager.project_runs`)
+RunCounter = Callable[[str, datetime], int]
+
+
+def _now() -> datetime:
+    return datetime.now().astimezone()
+
+
+def in_quiet_hours(quiet_hours: QuietHours, now: datetime, user_timezone: Optional[str] = None) -> bool:
+    """Quiet hours are read in their own `timezone`, else the user's, else the daemon's local time."""
+    zone_name = quiet_hours.timezone or user_timezone
+    if zone_name:
+        now = to_local(now, zone_name)
+    current = now.time().replace(tzinfo=None)
+    start, end = quiet_hours.start, quiet_hours.end
+    if start < end:
+        return start <= current < end
+    # The window wraps past midnight, e.g. 22:00-07:00
+    return current >= start or current < end
+
+
+def _format_time(value: time) -> str:
+    return value.strftime("%H:%M")
+
+
+def _next_month(now: datetime) -> date:
+    return date(now.year + 1, 1, 1) if now.month == 12 else date(now.year, now.month + 1, 1)
+
+
+class AutomationGate:
+    """Tracks paused project groups and applies per-project run limits."""
+
+    def __init__(self, clock: Clock = _now, run_counter: Optional[RunCounter] = None) -> None:
+        self._clock = clock
+        self._run_counter = run_counter
+        self._paused_groups: Set[str] = set()
+
+    def pause(self, group: str) -> bool:
+        """Pause a group; returns False if it was already paused."""
+        if group in self._paused_groups:
+            return False
+        self._paused_groups.add(group)
+        return True
+
+    def resume(self, group: str) -> bool:
+        """Resume a group; returns False if it was not paused."""
+        if group not in self._paused_groups:
+            return False
+        self._paused_groups.discard(group)
+        return True
+
+    def is_paused(self, group: Optional[str]) -> bool:
+        return group is not None and group in self._paused_groups
+
+    def block_reason(self, project: Project, session: Session) -> Optional[str]:
+        """Return why an agent must not run for this session, or None if it may."""
+        if self.is_paused(project.group):
+            return (
+                f"Automation is paused for the `{project.group}` group; "
+                f"run `!group {project.group} resume` to continue."
+            )
+        quiet_hours = project.quiet_hours
+        if quiet_hours and in_quiet_hours(quiet_hours, self._clock(), session.timezone):
+            zone_name = quiet_hours.timezone or session.timezone
+            zone = f" {zone_name}" if zone_name else ""
+            return (
+                f"Quiet hours for `{project.id}` ({_format_time(quiet_hours.start)}-"
+                f"{_format_time(quiet_hours.end)}{zone}); agents will run again after "
+                f"{_format_time(quiet_hours.end)}."
+            )
+        budget = project.max_session_cost_usd
+        if budget is not None and session.estimated_cost_usd >= budget:
+            return (
+                f"This session reached its budget of {format_cost(budget)} "
+                f"(spent ~{format_cost(session.estimated_cost_usd)}). Start a new thread to continue."
+            )
+        quota = project.max_runs_per_month
+        if quota is not None and self._run_counter is not None:
+            now = self._clock().astimezone()
+            if self._run_counter(project.id, now) >= quota:
+                resumes = _next_month(now)
+                return (
+                    f"`{project.id}` used its quota of {quota} agent runs for {now:%B}; "
+                    f"the quota is exhausted until the 1st ({resumes:%B} 1)."
+                )
+        return None
+
+    def quota_status(self, project: Project) -> Optional[str]:
+        """This month's runs against the project's quota, e.g. `12/100 runs in October`, if it has one."""
+        if project.max_runs_per_month is None or self._run_counter is None:
+            return None
+        now = self._clock().astimezone()
+        return f"{self._run_counter(project.id, now)}/{project.max_runs_per_month} runs in {now:%B}"
@@ -0,0 +1,271 @@
+"""Run a list of small, independent tasks as separate agent runs.
+
+`!batch` takes one task per line. Every task runs in its own `git worktree`
+of the same start commit, on a branch of its own, so no task sees another's
+edits and the session branch is left untouched; up to `parallel` tasks run
+at once (one after another by default). What a task changed is committed to
+its branch when it finishes and the worktree is removed. The branches can
+then be published as one PR per task, or cherry-picked onto one combined
+branch for a single PR, leaving out tasks whose changes conflict.
+"""
+
+from __future__ import annotations
+
+import asyncio
+import logging
+import re
+import shutil
+import tempfile
+import time
+from dataclasses import dataclass, field
+from enum import Enum
+from pathlib import Path
+from typing import Awaitable, Callable, List, Optional, Sequence, Tuple
+
+from ..chat_adapters.health import format_duration
+from .compare import RunAgentFn, _git
+from .diff_render import FileDiff, split_diff
+from .models import Agent
+from .pricing import format_cost
+
+LOGGER = logging.getLogger(__name__)
+
+WORKTREE_PREFIX = "remote-coder-batch-"
+MAX_BATCH_TASKS = 20
+MAX_PARALLEL = 4
+# Characters of a task kept in commit messages, PR titles, and result lines
+MAX_TITLE_CHARS = 72
+MAX_REPLY_CHARS = 300
+
+BATCH_USAGE = (
+    "Usage: `!batch [--parallel <n>] [--pr none|separate|combined]` followed by one task per line, e.g.\n"
+    "```\n!batch --parallel 2 --pr separate\nFix the typo in the README\nBump requests to 2.32\n```"
+)
+
+_BULLET = re.compile(r"^(?:[-*•]|\d+[.)])\s+")
+
+# Called with each task's run as soon as it finishes
+TaskDoneFn = Callable[["BatchTaskRun"], Awaitable[None]]
+
+
+class BatchError(Exception):
+    """The batch could not be parsed or set up, e.g. the project is not a git repository."""
+
+
+class BatchPrMode(str, Enum):
+    """What to open for the tasks' branches once they all finished."""
+
+    NONE = "none"  # Keep the branches local
+    SEPARATE = "separate"  # One PR per task that changed something
+    COMBINED = "combined"  # All tasks' commits on one branch, one PR
+
+
+@dataclass
+class BatchRequest:
+    tasks: List[str]
+    parallel: int = 1
+    pr_mode: BatchPrMode = BatchPrMode.NONE
+
+
+@dataclass
+class BatchTaskRun:
+    """What the agent did with one task of the batch."""
+
+    number: int  # 1-based position in the batch
+    task: str
+    branch: str
+    commit: Optional[str] = None  # Set when the task changed something
+    diff_text: str = ""
+    duration_secs: float = 0.0
+    cost_usd: Optional[float] = None
+    reply: str = ""
+    errors: List[str] = field(default_factory=list)
+
+    @property
+    def files(self) -> List[FileDiff]:
+        return split_diff(self.diff_text)
+
+    @property
+    def title(self) -> str:
+        return task_title(self.task)
+
+
+def task_title(task: str) -> str:
+    line = task.strip().splitlines()[0] if task.strip() else task
+    return line if len(line) <= MAX_TITLE_CHARS else line[: MAX_TITLE_CHARS - 1] + "…"
+
+
+def parse_batch(raw_args: str) -> BatchRequest:
+    """Options from the start of the first line, then one task per non-empty line (list bullets dropped)."""
+    first, _, rest = raw_args.strip().partition("\n")
+    tokens = first.split()
+    request = BatchRequest(tasks=[])
+    while tokens and tokens[0].startswith("--"):
+        option = tokens.pop(0)
+        if not tokens:
+            raise BatchError(f"`{option}` needs a value")
+        value = tokens.pop(0)
+        if option == "--parallel":
+            if not value.isdigit() or not 1 <= int(value) <= MAX_PARALLEL:
+                raise BatchError(f"`--parallel` must be a number from 1 to {MAX_PARALLEL}")
+            request.parallel = int(value)
+        elif option == "--pr":
+            try:
+                request.pr_mode = BatchPrMode(value.lower())
+            except ValueError as exc:
+                raise BatchError("`--pr` must be none, separate, or combined") from exc
+        else:
+            raise BatchError(f"Unknown option `{option}`")
+
+    lines = [" ".join(tokens), *rest.splitlines()]
+    request.tasks = [_BULLET.sub("", line.strip()) for line in lines if line.strip()]
+    if len(request.tasks) > MAX_BATCH_TASKS:
+        raise BatchError(f"A batch takes at most {MAX_BATCH_TASKS} tasks; split it up")
+    return request
+
+
+async def start_commit(repo_path: Path) -> str:
+    head = await _git(repo_path, "rev-parse", "HEAD", check=False)
+    if head.returncode != 0:
+        raise BatchError(f"{repo_path} is not a git repository with at least one commit")
+    return head.stdout.strip()
+
+
+async def run_batch(
+    repo_path: Path,
+    agent: Agent,
+    model: Optional[str],
+    request: BatchRequest,
+    run_agent: RunAgentFn,
+    *,
+    base: str,
+    branch_prefix: str,
+    on_done: Optional[TaskDoneFn] = None,
+) -> List[BatchTaskRun]:
+    """Run every task of `request` from commit `base`, at most `request.parallel` at a time.
+
+    Task N works on branch `<branch_prefix>-N`, which is deleted again when the
+    task changed nothing. Runs are returned in task order; a task that fails
+    still gets a run with its error and whatever it changed before failing.
+    """
+    root = Path(tempfile.mkdtemp(prefix=WORKTREE_PREFIX))
+    slots = asyncio.Semaphore(request.parallel)
+
+    async def _run(number: int, task: str) -> BatchTaskRun:
+        async with slots:
+            run = await _run_task(repo_path, root, agent, model, base, number, task, branch_prefix, run_agent)
+        if on_done:
+            await on_done(run)
+        return run
+
+    try:
+        runs = await asyncio.gather(*(_run(number, task) for number, task in enumerate(request.tasks, start=1)))
+    finally:
+        await _git(repo_path, "worktree", "prune", check=False)
+        shutil.rmtree(root, ignore_errors=True)
+    return list(runs)
+
+
+async def _run_task(
+    repo_path: Path,
+    root: Path,
+    agent: Agent,
+    model: Optional[str],
+    base: str,
+    number: int,
+    task: str,
+    branch_prefix: str,
+    run_agent: RunAgentFn,
+) -> BatchTaskRun:
+    run = BatchTaskRun(number=number, task=task, branch=f"{branch_prefix}-{number}")
+    worktree = root / str(number)
+    added = await _git(repo_path, "worktree", "add", "-b", run.branch, str(worktree), base, check=False)
+    if added.returncode != 0:
+        run.errors.append(f"Could not create a worktree: {added.stderr.strip()}")
+        return run
+
+    started = time.monotonic()
+    try:
+        try:
+            result, run.cost_usd = await run_agent(agent, model, worktree, task)
+        except Exception as exc:
+            LOGGER.exception("Batch task %d failed", number)
+            run.errors.append(str(exc))
+        else:
+            run.reply = (
+                result.structured_output.slack_message if result.structured_output else result.output_text
+            ).strip()
+            run.errors.extend(result.errors)
+        run.duration_secs = time.monotonic() - started
+
+        # Stage everything so new files count; diffing from `base` also covers agents that commit themselves
+        await _git(worktree, "add", "-A", check=False)
+        staged = await _git(worktree, "diff", "--cached", "--quiet", check=False)
+        if staged.returncode != 0:
+            committed = await _git(worktree, "commit", "-q", "-m", run.title, check=False)
+            if committed.returncode != 0:
+                run.errors.append(f"Could not commit the changes: {committed.stderr.strip()}")
+        diff = await _git(worktree, "diff", base, "HEAD", check=False)
+        run.diff_text = diff.stdout if diff.returncode == 0 else ""
+        if run.diff_text.strip():
+            head = await _git(worktree, "rev-parse", "HEAD", check=False)
+            run.commit = head.stdout.strip() or None
+    finally:
+        await _git(repo_path, "worktree", "remove", "--force", str(worktree), check=False)
+        if run.commit is None:
+            await _git(repo_path, "branch", "-D", run.branch, check=False)
+    return run
+
+
+async def combine_runs(
+    repo_path: Path, base: str, runs: Sequence[BatchTaskRun], branch: str
+) -> Tuple[List[BatchTaskRun], List[BatchTaskRun]]:
+    """Cherry-pick the tasks' changes onto a new `branch` from `base`; returns (included, conflicting)."""
+    root = Path(tempfile.mkdtemp(prefix=WORKTREE_PREFIX))
+    worktree = root / "combined"
+    added = await _git(repo_path, "worktree", "add", "-b", branch, str(worktree), base, check=False)
+    if added.returncode != 0:
+        shutil.rmtree(root, ignore_errors=True)
+        raise BatchError(f"Could not create a worktree: {added.stderr.strip()}")
+    included: List[BatchTaskRun] = []
+    conflicting: List[BatchTaskRun] = []
+    try:
+        for run in runs:
+            if not run.commit:
+                continue
+            # The task branch may hold several commits (agents that commit themselves) on top of `base`
+            picked = await _git(worktree, "cherry-pick", f"{base}..{run.commit}", check=False)
+            if picked.returncode == 0:
+                included.append(run)
+                continue
+            await _git(worktree, "cherry-pick", "--abort", check=False)
+            conflicting.append(run)
+    finally:
+        await _git(repo_path, "worktree", "remove", "--force", str(worktree), check=False)
+        await _git(repo_path, "worktree", "prune", check=False)
+        shutil.rmtree(root, ignore_errors=True)
+    return included, conflicting
+
+
+def format_task_result(run: BatchTaskRun, total: int) -> str:
+    files = run.files
+    additions = sum(file_diff.additions for file_diff in files)
+    deletions = sum(file_diff.deletions for file_diff in files)
+    status = "failed" if run.errors and not files else ("changed nothing" if not files else "done")
+    details = [status]
+    if files:
+        details.append(f"{len(files)} file{'' if len(files) == 1 else 's'}, +{additions} -{deletions}")
+    details.append(format_duration(run.duration_secs))
+    if run.cost_usd is not None:
+        details.append(f"~{format_cost(run.cost_usd)}")
+    lines = [f"*Task {run.number}/{total}* `{run.title}`: {', '.join(details)}"]
+    if run.reply:
+        reply = run.reply if len(run.reply) <= MAX_REPLY_CHARS else run.reply[: MAX_REPLY_CHARS - 1] + "…"
+        lines.append(reply)
+    lines.extend(f"  error: {error}" for error in run.errors)
+    return "\n".join(lines)
+
+
+def format_pr_body(runs: Sequence[BatchTaskRun], origin: str) -> str:
+    tasks = "\n".join(f"- {run.title}" for run in runs)
+    return f"Batch of {len(runs)} task(s):\n{tasks}\n\n---\n{origin}"
@@ -0,0 +1,315 @@
+"""Benchmark agents on a project's own suite of prompts.
+
+A suite is a set of Markdown files in `.cockpit/bench/`, one prompt per
+file. Each case runs against every selected agent in a fresh worktree of the
+current commit (one agent at a time, so durations stay comparable), then the
+project's setup and verify steps run in that worktree to tell whether the
+change works. Optional frontmatter describes the case and can replace the
+project's verify steps for it:
+
+    ---
+    description: Paginate the users endpoint
+    verify:
+      - pytest tests/test_users.py
+    ---
+
+The report compares pass rate, duration, and estimated cost per agent, which
+helps pick a project's `default_agent` from evidence rather than habit.
+"""
+
+from __future__ import annotations
+
+import logging
+import statistics
+from dataclasses import dataclass, field, replace
+from pathlib import Path
+from typing import Awaitable, Callable, Dict, List, Optional, Sequence, Tuple
+
+from ..chat_adapters.health import format_duration
+from .compare import ComparisonRun, RunAgentFn, run_comparison
+from .config import Config, _parse_command_steps
+from .errors import AgentNotFound, ConfigError
+from .models import Agent, CommandStep, Project, WorkingDirMode
+from .pricing import format_cost
+from .project_commands import COMMAND_NAME, split_frontmatter
+from .verify import checks_passed, has_project_checks, run_project_checks
+
+LOGGER = logging.getLogger(__name__)
+
+BENCH_DIR = Path(".cockpit") / "bench"
+BENCH_FRONTMATTER_KEYS = frozenset({"description", "verify"})
+
+# Reports each finished run; called once per agent after each case
+ProgressFn = Callable[["BenchRun"], Awaitable[None]]
+
+
+@dataclass
+class BenchCase:
+    name: str
+    prompt: str
+    description: Optional[str] = None
+    verify: Optional[List[CommandStep]] = None  # None uses the project's verify steps
+
+
+@dataclass
+class BenchRun:
+    """One agent's attempt at one case."""
+
+    case: str
+    agent_id: str
+    model: Optional[str]
+    duration_secs: float = 0.0
+    cost_usd: Optional[float] = None
+    files_changed: int = 0
+    lines_changed: int = 0
+    passed: Optional[bool] = None  # None when the case has no checks to run
+    errors: List[str] = field(default_factory=list)
+
+    @property
+    def label(self) -> str:
+        return f"{self.agent_id} ({self.model})" if self.model else self.agent_id
+
+
+@dataclass
+class AgentSummary:
+    agent_id: str
+    label: str
+    runs: int
+    passed: int
+    checked: int  # Runs whose checks ran
+    median_secs: float
+    cost_usd: Optional[float]  # None when any run's cost is unknown
+
+
+@dataclass
+class BenchReport:
+    project_id: str
+    base: str
+    runs: List[BenchRun]
+    default_agent_id: Optional[str] = None
+
+    def summaries(self) -> List[AgentSummary]:
+        """Per-agent totals, best first: most checks passed, then cheapest, then fastest."""
+        by_agent: Dict[str, List[BenchRun]] = {}
+        for run in self.runs:
+            by_agent.setdefault(run.agent_id, []).append(run)
+        summaries = []
+        for agent_id, runs in by_agent.items():
+            costs = [run.cost_usd for run in runs]
+            summaries.append(
+                AgentSummary(
+                    agent_id=agent_id,
+                    label=runs[0].label,
+                    runs=len(runs),
+                    passed=sum(1 for run in runs if run.passed),
+                    checked=sum(1 for run in runs if run.passed is not None),
+                    median_secs=statistics.median(run.duration_secs for run in runs),
+                    cost_usd=None if None in costs else sum(cost for cost in costs if cost is not None),
+                )
+            )
+        summaries.sort(
+            key=lambda item: (
+                -item.passed,
+                item.cost_usd if item.cost_usd is not None else float("inf"),
+                item.median_secs,
+            )
+        )
+        return summaries
+
+
+def bench_dir(project_path: Path) -> Path:
+    return project_path / BENCH_DIR
+
+
+def load_bench_suite(project_path: Path, names: Optional[Sequence[str]] = None) -> List[BenchCase]:
+    """The project's cases sorted by name, or only `names`; raises ConfigError for invalid or unknown cases."""
+    directory = bench_dir(project_path)
+    paths = sorted(directory.glob("*.md")) if directory.is_dir() else []
+    available = {path.stem: path for path in paths if COMMAND_NAME.match(path.stem)}
+    if names:
+        unknown = [name for name in names if name not in available]
+        if unknown:
+            raise ConfigError(f"No bench case named {', '.join(unknown)} in {BENCH_DIR}")
+        selected = [available[name] for name in names]
+    else:
+        selected = list(available.values())
+    return [parse_bench_case(path.stem, _read_case(path)) for path in selected]
+
+
+def select_contenders(
+    config: Config, project: Project, agent_ids: Sequence[str] = ()
+) -> List[Tuple[Agent, Optional[str]]]:
+    """The agents to benchmark with the project's model for each; defaults to every agent the project allows.
+
+    Raises ConfigError for unknown or disallowed agents, and for agents that can't run in a worktree.
+    """
+    if not agent_ids:
+        agent_ids = [
+            agent.id
+            for agent in config.agents.values()
+            if project.allows_agent(agent.id) and agent.working_dir_mode != WorkingDirMode.FIXED
+        ]
+    contenders = []
+    for agent_id in dict.fromkeys(agent_ids):
+        try:
+            agent = config.get_agent(agent_id)
+        except AgentNotFound:
+            raise ConfigError(f"Unknown agent `{agent_id}`") from None
+        if not project.allows_agent(agent_id):
+            raise ConfigError(f"Agent `{agent_id}` is not allowed in project `{project.id}`")
+        if agent.working_dir_mode == WorkingDirMode.FIXED:
+            raise ConfigError(f"`{agent_id}` works in a fixed directory, so it cannot run in a separate worktree")
+        contenders.append((agent, project.model_for(agent)))
+    if not contenders:
+        raise ConfigError(f"No agent of project `{project.id}` can run in a worktree")
+    return contenders
+
+
+def _read_case(path: Path) -> str:
+    try:
+        return path.read_text(encoding="utf-8")
+    except (OSError, UnicodeDecodeError) as exc:
+        raise ConfigError(f"Cannot read {BENCH_DIR / path.name}: {exc}") from exc
+
+
+def parse_bench_case(name: str, text: str) -> BenchCase:
+    owner = f"{BENCH_DIR / name}.md"
+    meta, prompt = split_frontmatter(owner, text, BENCH_FRONTMATTER_KEYS)
+    verify = _parse_command_steps(f"{owner} verify", meta["verify"]) if "verify" in meta else None
+    return BenchCase(
+        name=name,
+        prompt=prompt,
+        description=str(meta["description"]) if meta.get("description") else None,
+        verify=verify,
+    )
+
+
+async def run_bench(
+    project: Project,
+    repo_path: Path,
+    contenders: Sequence[Tuple[Agent, Optional[str]]],
+    cases: Sequence[BenchCase],
+    run_agent: RunAgentFn,
+    *,
+    progress: Optional[ProgressFn] = None,
+) -> BenchReport:
+    """Run every case against every contender; raises `ComparisonError` when worktrees can't be made."""
+    runs: List[BenchRun] = []
+    base = ""
+    for case in cases:
+        checked_project = project if case.verify is None else replace(project, verify_commands=case.verify)
+        outcomes: Dict[int, Optional[bool]] = {}
+
+        async def check(run: ComparisonRun, worktree: Path) -> None:
+            if not has_project_checks(checked_project):
+                return
+            if run.errors:
+                outcomes[id(run)] = False  # A failed agent run fails the case
+                return
+            results = await run_project_checks(checked_project, worktree)
+            outcomes[id(run)] = checks_passed(results)
+
+        LOGGER.info("Running bench case %s of project %s", case.name, project.id)
+        base, comparison = await run_comparison(
+            repo_path, contenders, case.prompt, run_agent, concurrent=False, after_run=check
+        )
+        for compared in comparison:
+            files = compared.files
+            run = BenchRun(
+                case=case.name,
+                agent_id=compared.agent_id,
+                model=compared.model,
+                duration_secs=compared.duration_secs,
+                cost_usd=compared.cost_usd,
+                files_changed=len(files),
+                lines_changed=sum(file_diff.additions + file_diff.deletions for file_diff in files),
+                passed=outcomes.get(id(compared)),
+                errors=list(compared.errors),
+            )
+            runs.append(run)
+            if progress:
+                await progress(run)
+    return BenchReport(project_id=project.id, base=base, runs=runs, default_agent_id=project.default_agent_id)
+
+
+def _passed_cell(run: BenchRun) -> str:
+    if run.errors:
+        return "error"
+    if run.passed is None:
+        return "n/a"
+    return "pass" if run.passed else "fail"
+
+
+def _cost_cell(cost_usd: Optional[float]) -> str:
+    return f"~{format_cost(cost_usd)}" if cost_usd is not None else "n/a"
+
+
+def recommendation(report: BenchReport) -> Optional[str]:
+    summaries = report.summaries()
+    if len(summaries) < 2:
+        return None
+    best = summaries[0]
+    if best.checked == 0:
+        return (
+            "No case had checks to run, so quality could not be compared; "
+            "add `verify` steps to the project or its cases."
+        )
+    current = " (already the project's default)" if best.agent_id == report.default_agent_id else ""
+    return (
+        f"Best on this suite: `{best.agent_id}`{current}, passing {best.passed}/{best.checked} "
+        f"at {_cost_cell(best.cost_usd)} with a median of {format_duration(best.median_secs)} per case."
+    )
+
+
+def format_bench_run(run: BenchRun) -> str:
+    """One progress line, e.g. "`pagination` / codex (base): pass in 1m 4s, ~$0.03"."""
+    duration = format_duration(run.duration_secs)
+    return f"`{run.case}` / {run.label}: {_passed_cell(run)} in {duration}, {_cost_cell(run.cost_usd)}"
+
+
+def format_bench_summary(report: BenchReport) -> str:
+    """Short per-agent summary for a chat reply; the full report is `format_bench_report`."""
+    lines = [f"Benchmark of `{report.project_id}` from `{report.base[:12]}`:"]
+    for summary in report.summaries():
+        passed = f"passed {summary.passed}/{summary.checked}" if summary.checked else "no checks"
+        lines.append(
+            f"• `{summary.label}`: {passed}, median {format_duration(summary.median_secs)}, "
+            f"total {_cost_cell(summary.cost_usd)}"
+        )
+    advice = recommendation(report)
+    if advice:
+        lines.append(advice)
+    return "\n".join(lines)
+
+
+def format_bench_report(report: BenchReport) -> str:
+    """Markdown report: per-agent summary, then every run."""
+    lines = [
+        f"# Agent benchmark for `{report.project_id}`",
+        "",
+        f"Started from `{report.base[:12]}`; {len({run.case for run in report.runs})} case(s).",
+        "",
+        "| Agent | Passed | Median time | Total cost |",
+        "| --- | --- | --- | --- |",
+    ]
+    for summary in report.summaries():
+        passed = f"{summary.passed}/{summary.checked}" if summary.checked else "n/a"
+        median = format_duration(summary.median_secs)
+        lines.append(f"| {summary.label} | {passed} | {median} | {_cost_cell(summary.cost_usd)} |")
+    advice = recommendation(report)
+    if advice:
+        lines.extend(["", advice])
+
+    lines.extend(
+        ["", "| Case | Agent | Result | Time | Cost | Files | Lines |", "| --- | --- | --- | --- | --- | --- | --- |"]
+    )
+    for run in report.runs:
+        lines.append(
+            f"| {run.case} | {run.label} | {_passed_cell(run)} | {format_duration(run.duration_secs)} "
+            f"| {_cost_cell(run.cost_usd)} | {run.files_changed} | {run.lines_changed} |"
+        )
+    errors = [run for run in report.runs if run.errors]
+    if errors:
+        lines.extend(["", "Errors:"])
+        lines.extend(f"- {run.case} / {run.label}: {'; '.join(run.errors)}" for run in errors)
+    return "\n".join(lines)
@@ -0,0 +1,184 @@
+"""Archiving stale sessions and removing the files they leave behind.
+
+Every `REMOTE_CODER_CLEANUP_INTERVAL_SECS` (default an hour; 0 turns it
+off) the daemon archives the sessions not updated for
+`REMOTE_CODER_ARCHIVE_AFTER_HOURS` (default a week) with
+`SessionManager.cleanup_ended`, then runs its disk hooks over what is left:
+
+- workspace snapshots (`<config dir>/snapshots/<session id>/`) of archived
+  and unknown sessions; they only serve `!rollback` of a session's last run
+- transcripts (`<transcript dir>/<session id>.jsonl`) of sessions the daemon
+  no longer has; archived sessions keep theirs, so a restored session still
+  has its history
+
+With object storage set up (`REMOTE_CODER_ARCHIVE_URL`), each pass first
+uploads the sessions archived before it and removes them from the store
+(see `src/core/archive.py`); their transcripts go with the disk hooks.
+
+What each pass archived and removed is added up in `CleanupStats`, shown in
+`!status --system` and on the status page.
+"""
+
+from __future__ import annotations
+
+import logging
+import shutil
+import time
+from dataclasses import dataclass
+from datetime import timedelta
+from pathlib import Path
+from typing import Awaitable, Callable, Optional, Sequence, Set
+
+from .conversation import SessionManager
+from .models import Session, SessionStatus
+
+LOGGER = logging.getLogger(__name__)
+
+DEFAULT_CLEANUP_INTERVAL_SECS = 3600
+DEFAULT_ARCHIVE_AFTER_HOURS = 7 * 24
+
+
+@dataclass
+class Removed:
+    files: int = 0
+    bytes: int = 0
+
+    def add(self, other: "Removed") -> None:
+        self.files += other.files
+        self.bytes += other.bytes
+
+
+# (ids of the sessions the daemon has, ids of the archived ones among them) -> what was removed
+DiskHook = Callable[[Set[str], Set[str]], Removed]
+# Uploads an archived session to object storage; False (after logging why) keeps it in the store for the next pass
+OffloadFn = Callable[[Session], Awaitable[bool]]
+
+
+def _remove(path: Path) -> Removed:
+    """Delete the file or directory at `path`, counting what went."""
+    removed = Removed()
+    files = [path] if path.is_file() else [item for item in path.rglob("*") if item.is_file()]
+    for item in files:
+        try:
+            size = item.stat().st_size
+        except OSError:
+            continue
+        removed.files += 1
+        removed.bytes += size
+    try:
+        if path.is_dir():
+            shutil.rmtree(path)
+        else:
+            path.unlink()
+    except OSError as exc:
+        LOGGER.warning("Could not remove %s: %s", path, exc)
+        return Removed()
+    return removed
+
+
+def snapshot_hook(directory: Path) -> DiskHook:
+    """Removes the workspace snapshots of archived sessions and of sessions the daemon no longer has."""
+
+    def clean(known: Set[str], archived: Set[str]) -> Removed:
+        removed = Removed()
+        if not directory.is_dir():
+            return removed
+        for entry in directory.iterdir():
+            if entry.is_dir() and (entry.name not in known or entry.name in archived):
+                removed.add(_remove(entry))
+        return removed
+
+    return clean
+
+
+def transcript_hook(directory: Path) -> DiskHook:
+    """Removes the transcripts of sessions the daemon no longer has."""
+
+    def clean(known: Set[str], archived: Set[str]) -> Removed:
+        removed = Removed()
+        if not directory.is_dir():
+            return removed
+        for entry in directory.glob("*.jsonl"):
+            if entry.stem not in known:
+                removed.add(_remove(entry))
+        return removed
+
+    return clean
+
+
+@dataclass
+class CleanupStats:
+    """Totals since startup."""
+
+    passes: int = 0
+    archived: int = 0
+    offloaded: int = 0  # Archived sessions uploaded to object storage and removed from the store
+    files_removed: int = 0
+    bytes_freed: int = 0
+    last_run_at: Optional[float] = None
+
+    def describe(self) -> str:
+        if not self.passes:
+            return "Cleanup: not run yet"
+        offloaded = f", {self.offloaded} uploaded to the archive" if self.offloaded else ""
+        return (
+            f"Cleanup: {self.passes} pass(es), {self.archived} session(s) archived{offloaded}, "
+            f"{self.files_removed} file(s) removed ({self.bytes_freed / (1024 * 1024):.1f} MB)"
+        )
+
+
+@dataclass(frozen=True)
+class CleanupPass:
+    archived: int
+    removed: Removed
+    offloaded: int = 0
+
+
+class SessionCleaner:
+    """Archives stale sessions, offloads archived ones, and runs the disk hooks, keeping count in `stats`."""
+
+    def __init__(
+        self,
+        session_manager: SessionManager,
+        archive_after: timedelta,
+        hooks: Sequence[DiskHook] = (),
+        offload: Optional[OffloadFn] = None,
+    ):
+        self._session_manager = session_manager
+        self._archive_after = archive_after
+        self._hooks = list(hooks)
+        self._offload = offload
+        self.stats = CleanupStats()
+
+    async def offload_archived(self) -> int:
+        """Hand each archived session to `offload` and remove the ones it uploaded from the store; returns how many."""
+        if self._offload is None:
+            return 0
+        offloaded = 0
+        for session in self._session_manager.list_archived():
+            if await self._offload(session) and self._session_manager.forget_session(session.id):
+                offloaded += 1
+        self.stats.offloaded += offloaded
+        if offloaded:
+            LOGGER.info("Cleanup uploaded %d archived session(s) and removed them from the store", offloaded)
+        return offloaded
+
+    def run(self) -> CleanupPass:
+        archived = self._session_manager.cleanup_ended(self._archive_after)
+        statuses = self._session_manager.session_statuses()
+        known = {str(session_id) for session_id in statuses}
+        archived_ids = {str(session_id) for session_id, status in statuses.items() if status == SessionStatus.ARCHIVED}
+        removed = Removed()
+        for hook in self._hooks:
+            try:
+                removed.add(hook(known, archived_ids))
+            except OSError as exc:
+                LOGGER.warning("Session file cleanup failed: %s", exc)
+        self.stats.passes += 1
+        self.stats.archived += archived
+        self.stats.files_removed += removed.files
+        self.stats.bytes_freed += removed.bytes
+        self.stats.last_run_at = time.time()
+        if archived or removed.files:
+            LOGGER.info("Cleanup archived %d session(s) and removed %d file(s)", archived, removed.files)
+        return CleanupPass(archived, removed)
@@ -0,0 +1,68 @@
+"""Handler for the session analytics report (see `src/core/analytics.py`)."""
+
+from __future__ import annotations
+
+import asyncio
+import logging
+from datetime import datetime, timedelta, timezone
+from typing import Callable, List, Optional
+
+from ..analytics import BY_AGENT, BY_PROJECT, AnalyticsStore, format_outcome
+from ..config import Config
+from ..errors import AnalyticsError
+from .base import BaseCommandHandler
+from .context import CommandContext
+from .parser import ParsedCommand
+
+LOGGER = logging.getLogger(__name__)
+
+DEFAULT_REPORT_DAYS = 30
+
+# The analytics database, if it could be opened
+AnalyticsFn = Callable[[], Optional[AnalyticsStore]]
+
+
+class AnalyticsCommandHandler(BaseCommandHandler):
+    """Implements the analytics command."""
+
+    def __init__(self, *, config: Config, analytics: AnalyticsFn, send_message) -> None:
+        super().__init__(send_message)
+        self._config = config
+        self._analytics = analytics
+
+    def update_config(self, config: Config) -> None:
+        self._config = config
+
+    async def handle_analytics(self, command: ParsedCommand, context: CommandContext) -> None:
+        LOGGER.info("Executing !analytics command in channel %s, thread %s", context.channel, context.thread_ts)
+        store = self._analytics()
+        if store is None:
+            await self._reply(context, "Analytics are not available; the daemon log says why.")
+            return
+        arg = command.args[0].lower() if command.args else str(DEFAULT_REPORT_DAYS)
+        if arg == "all":
+            since, period, empty = None, "all sessions", "No sessions to report on yet."
+        elif arg.isdigit() and int(arg) > 0:
+            days = int(arg)
+            since = datetime.now(timezone.utc) - timedelta(days=days)
+            period, empty = f"sessions of the last {days} day(s)", f"No sessions in the last {days} day(s)."
+        else:
+            await self._reply(context, "Usage: `!analytics [days|all]`, e.g. `!analytics 7`")
+            return
+        try:
+            agents = await asyncio.to_thread(store.report, BY_AGENT, since)
+            projects = await asyncio.to_thread(store.report, BY_PROJECT, since)
+        except AnalyticsError as exc:
+            await self._reply(context, f"Could not read analytics: {exc}")
+            return
+        if not projects:
+            await self._reply(context, empty)
+            return
+        lines: List[str] = [f"*Outcomes, {period}*", "By agent (a session counts for the agent of its last run):"]
+        lines += [f"- `{row.key}`: {format_outcome(row)}" for row in agents] or ["- No runs yet."]
+        lines.append("By project:")
+        for row in projects:
+            project = self._config.projects.get(row.key)
+            default = f" (default `{project.default_agent_id}`)" if project else ""
+            lines.append(f"- `{row.key}`{default}: {format_outcome(row)}")
+        await self._reply(context, "\n".join(lines))
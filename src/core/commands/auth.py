@@ -0,0 +1,99 @@
+"""Handler for the `!auth` command."""
+
+from __future__ import annotations
+
+import asyncio
+import logging
+from typing import Awaitable, Callable, Dict
+
+from ..agent_auth import AuthState, DeviceLogin, check_agent_auth, describe_missing, format_auth_checks
+from ..config import Config
+from ..errors import AgentNotFound
+from .base import BaseCommandHandler
+from .context import CommandContext
+from .parser import ParsedCommand
+
+LOGGER = logging.getLogger(__name__)
+
+# Sends text to the operators (REMOTE_CODER_ALERT_USER_IDS); False when none are configured
+NotifyOperatorsFn = Callable[[str], Awaitable[bool]]
+StartLoginFn = Callable[..., Awaitable[DeviceLogin]]
+
+
+class AuthCommandHandler(BaseCommandHandler):
+    """Reports agent credentials and starts device-code logins."""
+
+    def __init__(
+        self,
+        *,
+        config: Config,
+        notify_operators: NotifyOperatorsFn,
+        send_message,
+        start_login: StartLoginFn = DeviceLogin.start,
+    ) -> None:
+        super().__init__(send_message)
+        self._config = config
+        self._notify_operators = notify_operators
+        self._start_login = start_login
+        self._logins: Dict[str, asyncio.Task] = {}
+
+    def update_config(self, config: Config) -> None:
+        self._config = config
+
+    async def handle_auth(self, command: ParsedCommand, context: CommandContext) -> None:
+        LOGGER.info("Executing !auth command in channel %s, thread %s", context.channel, context.thread_ts)
+        if not command.args:
+            checks = [
+                check
+                for agent in self._config.agents.values()
+                if context.project.allows_agent(agent.id) and (check := check_agent_auth(agent)) is not None
+            ]
+            await self._reply(context, format_auth_checks(checks))
+            return
+
+        agent_id = command.args[0]
+        try:
+            agent = self._config.get_agent(agent_id)
+        except AgentNotFound:
+            await self._reply(context, f"Unknown agent `{agent_id}`. Use `!agents` to list the configured agents.")
+            return
+        check = check_agent_auth(agent)
+        if check is None:
+            await self._reply(context, f"`{agent.id}` does not need credentials.")
+            return
+        if not check.device_login:
+            if check.state == AuthState.OK:
+                await self._reply(context, f"`{agent.id}` has credentials ({check.source}). {check.instructions}")
+            elif check.state == AuthState.UNKNOWN:
+                await self._reply(
+                    context, f"`{agent.id}` may be logged in through the system keychain. {check.instructions}"
+                )
+            else:
+                await self._reply(context, describe_missing(check))
+            return
+
+        running = self._logins.get(agent.id)
+        if running and not running.done():
+            await self._reply(context, f"A login for `{agent.id}` is already waiting for its code to be entered.")
+            return
+        try:
+            login = await self._start_login(agent)
+        except (OSError, RuntimeError) as exc:
+            await self._reply(context, f"Could not start a login for `{agent.id}`: {exc}")
+            return
+
+        if await self._notify_operators(login.prompt()):
+            await self._reply(context, f"Sent a device-code login link for `{agent.id}` to the operators by DM.")
+        else:
+            # Without configured operators the person asking gets the link
+            await self._reply(context, login.prompt())
+        self._logins[agent.id] = asyncio.create_task(self._finish_login(login, context))
+
+    async def _finish_login(self, login: DeviceLogin, context: CommandContext) -> None:
+        if await login.wait():
+            LOGGER.info("Device-code login for %s completed", login.agent_id)
+            await self._reply(context, f"`{login.agent_id}` is logged in.")
+            return
+        detail = " ".join(line for line in login.output[-2:] if line) or "the code expired or the CLI failed"
+        LOGGER.warning("Device-code login for %s failed: %s", login.agent_id, detail)
+        await self._reply(context, f"Login for `{login.agent_id}` did not complete: {detail}")
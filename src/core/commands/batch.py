@@ -0,0 +1,197 @@
+"""Handler for running a list of small tasks as separate agent runs."""
+
+from __future__ import annotations
+
+import asyncio
+import logging
+import subprocess
+import time
+import uuid
+from typing import Awaitable, Callable, Dict, List, Optional
+
+from ..automation import AutomationGate
+from ..batch import (
+    BATCH_USAGE,
+    BatchError,
+    BatchPrMode,
+    BatchTaskRun,
+    combine_runs,
+    format_pr_body,
+    format_task_result,
+    parse_batch,
+    run_batch,
+    start_commit,
+)
+from ..compare import RunAgentFn
+from ..config import Config
+from ..conversation import SessionManager
+from ..errors import GitHubError
+from ..git_workflow import GitWorkflowService
+from ..models import WorkingDirMode
+from ..pricing import estimate_run_cost, format_cost
+from .base import BaseCommandHandler
+from .context import CommandContext
+from .parser import ParsedCommand
+
+LOGGER = logging.getLogger(__name__)
+
+PostDiffFn = Callable[[str, str, str], Awaitable[None]]
+
+
+class BatchCommandHandler(BaseCommandHandler):
+    """Implements the batch command."""
+
+    def __init__(
+        self,
+        *,
+        config: Config,
+        session_manager: SessionManager,
+        git_workflow: GitWorkflowService,
+        run_agent: RunAgentFn,
+        post_diff: PostDiffFn,
+        active_runs: Dict[str, Dict[str, object]],
+        send_message,
+        gate: Optional[AutomationGate] = None,
+    ) -> None:
+        super().__init__(send_message)
+        self._config = config
+        self._session_manager = session_manager
+        self._git_workflow = git_workflow
+        self._run_agent = run_agent
+        self._post_diff = post_diff
+        self._active_runs = active_runs
+        self._gate = gate
+
+    def update_config(self, config: Config) -> None:
+        self._config = config
+
+    async def handle_batch(self, command: ParsedCommand, context: CommandContext) -> None:
+        LOGGER.info("Executing !batch command in channel %s, thread %s", context.channel, context.thread_ts)
+        try:
+            request = parse_batch(command.raw_args)
+        except BatchError as exc:
+            await self._reply(context, f"{exc}.\n{BATCH_USAGE}")
+            return
+        if not request.tasks:
+            await self._reply(context, BATCH_USAGE)
+            return
+
+        session = context.session
+        project = context.project
+        agent = self._config.get_agent(session.active_agent_id)
+        if agent.working_dir_mode == WorkingDirMode.FIXED:
+            await self._reply(context, f"`{agent.id}` works in a fixed directory, so it cannot run in worktrees.")
+            return
+        if request.pr_mode != BatchPrMode.NONE and self._git_workflow.is_local_only(project):
+            await self._reply(context, "Opening PRs needs GitHub configured for this project; use `--pr none`.")
+            return
+        blocked = self._gate.block_reason(project, session) if self._gate else None
+        if blocked:
+            await self._reply(context, blocked)
+            return
+        session_id = str(session.id)
+        if any(run.get("session_id") == session_id for run in self._active_runs.values()):
+            await self._reply(context, "An agent is still running in this session; wait for it to finish first.")
+            return
+
+        model = session.active_model
+        total = len(request.tasks)
+        pace = "one at a time" if request.parallel == 1 else f"{request.parallel} at a time"
+        message = f"Running {total} task(s) with `{agent.id}` in separate worktrees, {pace}..."
+        estimates = [estimate_run_cost(agent, model, task) for task in request.tasks]
+        if all(estimates):
+            message = f"{message} Estimated cost: ~{format_cost(sum(e.cost_usd for e in estimates if e))}"
+        await self._reply(context, message)
+
+        run_id = f"{context.channel}_{context.thread_ts}_{int(time.time() * 1000)}"
+        self._active_runs[run_id] = {
+            "task": asyncio.current_task(),
+            "session_id": session_id,
+            "agent_id": f"{agent.id} (batch of {total})",
+            "started_at": time.time(),
+        }
+
+        async def _task_done(run: BatchTaskRun) -> None:
+            if run.cost_usd:
+                self._session_manager.record_run_cost(session.id, run.cost_usd)
+            await self._reply(context, format_task_result(run, total))
+            if run.diff_text.strip():
+                await self._post_diff(context.channel, context.thread_ts, run.diff_text)
+
+        branch_prefix = f"remote-coder-batch-{uuid.uuid4().hex[:8]}"
+        try:
+            if request.pr_mode == BatchPrMode.NONE:
+                base = await start_commit(session.project_path)
+            else:
+                base = await self._git_workflow.fetch_base_commit(project, session.project_path)
+            runs = await run_batch(
+                session.project_path,
+                agent,
+                model,
+                request,
+                self._run_agent,
+                base=base,
+                branch_prefix=branch_prefix,
+                on_done=_task_done,
+            )
+        except (BatchError, subprocess.CalledProcessError) as exc:
+            detail = getattr(exc, "stderr", None) or str(exc)
+            await self._reply(context, f"Could not run the batch: {detail.strip()}")
+            return
+        finally:
+            self._active_runs.pop(run_id, None)
+
+        changed = [run for run in runs if run.commit]
+        if not changed:
+            await self._reply(context, "No task changed anything.")
+            return
+        try:
+            summary = await self._publish(context, request.pr_mode, base, changed, branch_prefix)
+        except (BatchError, GitHubError, subprocess.CalledProcessError) as exc:
+            detail = getattr(exc, "stderr", None) or str(exc)
+            summary = f"Could not open PRs: {detail.strip()}; {_branch_list(changed)}"
+        await self._reply(context, summary)
+
+    async def _publish(
+        self,
+        context: CommandContext,
+        pr_mode: BatchPrMode,
+        base: str,
+        changed: List[BatchTaskRun],
+        branch_prefix: str,
+    ) -> str:
+        session = context.session
+        origin = f"Batch run via Slack thread {session.thread_ts} in channel {session.channel_id}."
+        if pr_mode == BatchPrMode.NONE:
+            return (
+                f"{len(changed)} task(s) changed something. Nothing was published; {_branch_list(changed)}\n"
+                "Merge or cherry-pick the ones you want, or run the batch again with `--pr separate|combined`."
+            )
+        if pr_mode == BatchPrMode.SEPARATE:
+            lines = ["Opened one PR per task:"]
+            for run in changed:
+                pr = await self._git_workflow.publish_branch(
+                    session, context.project, run.branch, run.title, format_pr_body([run], origin)
+                )
+                lines.append(f"• Task {run.number} `{run.title}`: {pr.url}")
+            return "\n".join(lines)
+
+        included, conflicting = await combine_runs(session.project_path, base, changed, branch_prefix)
+        if not included:
+            raise BatchError("no task's changes applied cleanly to the base branch")
+        title = f"Batch: {len(included)} small change(s)" if len(included) > 1 else included[0].title
+        pr = await self._git_workflow.publish_branch(
+            session, context.project, branch_prefix, title, format_pr_body(included, origin)
+        )
+        lines = [f"Opened a combined PR with {len(included)} task(s): {pr.url}"]
+        if conflicting:
+            numbers = ", ".join(str(run.number) for run in conflicting)
+            lines.append(
+                f"Left out task(s) {numbers}, whose changes conflict with earlier ones; {_branch_list(conflicting)}"
+            )
+        return "\n".join(lines)
+
+
+def _branch_list(runs: List[BatchTaskRun]) -> str:
+    branches = ", ".join(f"`{run.branch}`" for run in runs)
+    return f"the changes are on local branch(es) {branches}."
@@ -0,0 +1,137 @@
+"""Handler for benchmarking agents on a project's `.cockpit/bench` suite."""
+
+from __future__ import annotations
+
+import asyncio
+import logging
+import time
+from typing import Dict, List, Optional
+
+from ..automation import AutomationGate
+from ..bench import (
+    BENCH_DIR,
+    BenchRun,
+    format_bench_report,
+    format_bench_run,
+    format_bench_summary,
+    load_bench_suite,
+    run_bench,
+    select_contenders,
+)
+from ..compare import ComparisonError, RunAgentFn
+from ..config import Config
+from ..conversation import SessionManager
+from ..errors import ConfigError
+from .base import BaseCommandHandler
+from .context import CommandContext
+from .parser import ParsedCommand
+from .patches import UploadFileFn
+
+LOGGER = logging.getLogger(__name__)
+
+BENCH_USAGE = "Usage: `!bench [agent ...] [--cases <name>,<name>]`"
+
+
+class BenchCommandHandler(BaseCommandHandler):
+    """Implements the bench command."""
+
+    def __init__(
+        self,
+        *,
+        config: Config,
+        session_manager: SessionManager,
+        run_agent: RunAgentFn,
+        upload_file: UploadFileFn,
+        active_runs: Dict[str, Dict[str, object]],
+        send_message,
+        gate: Optional[AutomationGate] = None,
+    ) -> None:
+        super().__init__(send_message)
+        self._config = config
+        self._session_manager = session_manager
+        self._run_agent = run_agent
+        self._upload_file = upload_file
+        self._active_runs = active_runs
+        self._gate = gate
+
+    def update_config(self, config: Config) -> None:
+        self._config = config
+
+    async def handle_bench(self, command: ParsedCommand, context: CommandContext) -> None:
+        LOGGER.info("Executing !bench command in channel %s, thread %s", context.channel, context.thread_ts)
+        agent_ids: List[str] = []
+        case_names: List[str] = []
+        args = iter(command.args)
+        for arg in args:
+            if arg == "--cases":
+                case_names.extend(name for name in next(args, "").split(",") if name)
+            elif arg.startswith("--cases="):
+                case_names.extend(name for name in arg.partition("=")[2].split(",") if name)
+            elif arg.startswith("--"):
+                await self._reply(context, BENCH_USAGE)
+                return
+            else:
+                agent_ids.append(arg)
+
+        session = context.session
+        project = context.project
+        try:
+            contenders = select_contenders(self._config, project, agent_ids)
+            cases = load_bench_suite(session.project_path, case_names)
+        except ConfigError as exc:
+            await self._reply(context, f"Cannot run the benchmark: {exc}.")
+            return
+        if not cases:
+            await self._reply(
+                context,
+                f"No bench cases in `{BENCH_DIR}/`. Add one Markdown prompt per file; "
+                "the project's verify steps decide whether each change passed.",
+            )
+            return
+
+        blocked = self._gate.block_reason(project, session) if self._gate else None
+        if blocked:
+            await self._reply(context, blocked)
+            return
+        session_id = str(session.id)
+        if any(run.get("session_id") == session_id for run in self._active_runs.values()):
+            await self._reply(context, "An agent is still running in this session; wait for it to finish first.")
+            return
+
+        agent_list = ", ".join(f"`{agent.id}`" for agent, _ in contenders)
+        await self._reply(
+            context,
+            f"Benchmarking {agent_list} on {len(cases)} case(s), one agent at a time in separate worktrees. "
+            "Nothing is committed.",
+        )
+
+        async def report_progress(run: BenchRun) -> None:
+            if run.cost_usd:
+                self._session_manager.record_run_cost(session.id, run.cost_usd)
+            await self._reply(context, format_bench_run(run))
+
+        run_id = f"{context.channel}_{context.thread_ts}_{int(time.time() * 1000)}"
+        self._active_runs[run_id] = {
+            "task": asyncio.current_task(),
+            "session_id": session_id,
+            "agent_id": "bench",
+            "started_at": time.time(),
+        }
+        try:
+            report = await run_bench(
+                project, session.project_path, contenders, cases, self._run_agent, progress=report_progress
+            )
+        except ComparisonError as exc:
+            await self._reply(context, f"Could not run the benchmark: {exc}")
+            return
+        finally:
+            self._active_runs.pop(run_id, None)
+
+        await self._reply(context, format_bench_summary(report))
+        await self._upload_file(
+            context.channel,
+            context.thread_ts,
+            f"bench-{project.id}.md",
+            format_bench_report(report),
+            "Full benchmark report.",
+        )
@@ -0,0 +1,49 @@
+"""Handler for stopping the agent run in a session before it finishes."""
+
+from __future__ import annotations
+
+import asyncio
+import logging
+from typing import Dict, List
+
+from .base import BaseCommandHandler
+from .context import CommandContext
+from .parser import ParsedCommand
+
+LOGGER = logging.getLogger(__name__)
+
+# How long to wait for a cancelled run to kill its CLI and clean up
+CANCEL_WAIT_SECS = 15
+
+
+class CancelCommandHandler(BaseCommandHandler):
+    """Implements the cancel command."""
+
+    def __init__(self, *, active_runs: Dict[str, Dict[str, object]], send_message) -> None:
+        super().__init__(send_message)
+        self._active_runs = active_runs
+
+    async def handle_cancel(self, command: ParsedCommand, context: CommandContext) -> None:
+        LOGGER.info("Executing !cancel command in channel %s, thread %s", context.channel, context.thread_ts)
+        session_id = str(context.session.id)
+        runs = [run for run in self._active_runs.values() if run.get("session_id") == session_id]
+        tasks: List[asyncio.Task] = [
+            run["task"] for run in runs if isinstance(run.get("task"), asyncio.Task) and not run["task"].done()
+        ]
+        if not tasks:
+            await self._reply(context, "No agent is running in this session.")
+            return
+
+        labels = ", ".join(f"`{run.get('agent_id') or 'agent'}`" for run in runs)
+        LOGGER.info("Cancelling %d run(s) of session %s", len(tasks), session_id)
+        for task in tasks:
+            task.cancel()
+        # Cancelling kills the agent's CLI; wait for that so the worktree is no longer being written to
+        _, pending = await asyncio.wait(tasks, timeout=CANCEL_WAIT_SECS)
+        if pending:
+            LOGGER.warning("%d cancelled run(s) of session %s are still shutting down", len(pending), session_id)
+        await self._reply(
+            context,
+            f"Cancelled {labels}. Files it already changed are left as they are; `!redo` resets the session "
+            "branch to before the run and tries again.",
+        )
@@ -4,6 +4,7 @@ from __future__ import annotations
 
 from .parser import ParsedCommand
 from ..config import Config
+from ..project_commands import PROJECT_COMMANDS_DIR, list_project_commands
 from .base import BaseCommandHandler
 from .context import CommandContext
 from .dispatcher import CommandDispatcher
@@ -27,15 +28,18 @@ class CatalogCommandHandler(BaseCommandHandler):
 
     async def handle_agents(self, command: ParsedCommand, context: CommandContext) -> None:
         agent_lines = ["Available agents:"]
-        for _, agent in self._config.agents.items():
-            agent_lines.append(f"- `{agent.type.value}`")
+        for agent_id, agent in self._config.agents.items():
+            if context.project.allows_agent(agent_id):
+                agent_lines.append(f"- `{agent.type.value}`")
         await self._reply(context, "\n".join(agent_lines))
 
     async def handle_models(self, command: ParsedCommand, context: CommandContext) -> None:
         lines = ["Available models by agent:"]
         for agent_id, agent in self._config.agents.items():
+            if not context.project.allows_agent(agent_id):
+                continue
             if agent.models:
-                default = agent.models.get("default", "")
+                default = context.project.model_for(agent) or ""
                 available = agent.models.get("available", [])
                 if available:
                     models_str = ", ".join(f"`{m}`" for m in available)
@@ -49,4 +53,11 @@ class CatalogCommandHandler(BaseCommandHandler):
 
     async def handle_help(self, command: ParsedCommand, context: CommandContext) -> None:
         lines = self._dispatcher.build_help_lines()
+        project_commands = list_project_commands(context.session.project_path)
+        if project_commands:
+            lines.append("")
+            lines.append(f"Project commands (from `{PROJECT_COMMANDS_DIR}`):")
+            for definition in project_commands:
+                description = f" – {definition.description}" if definition.description else ""
+                lines.append(f"- `!{definition.id}`{description}")
         await self._reply(context, "\n".join(lines))
@@ -0,0 +1,134 @@
+"""Handler for running two agents side by side on the same prompt."""
+
+from __future__ import annotations
+
+import asyncio
+import logging
+import time
+from typing import Awaitable, Callable, Dict, List, Optional, Tuple
+
+from ..automation import AutomationGate
+from ..compare import ComparisonError, RunAgentFn, format_comparison, run_comparison
+from ..config import Config
+from ..conversation import SessionManager
+from ..errors import AgentNotFound
+from ..models import Agent, WorkingDirMode
+from ..pricing import estimate_run_cost, format_cost
+from .base import BaseCommandHandler
+from .context import CommandContext
+from .parser import ParsedCommand
+
+LOGGER = logging.getLogger(__name__)
+
+COMPARE_USAGE = "Usage: `!compare <agentA> <agentB> <prompt>`"
+
+PostDiffFn = Callable[[str, str, str], Awaitable[None]]
+
+
+class CompareCommandHandler(BaseCommandHandler):
+    """Implements the compare command."""
+
+    def __init__(
+        self,
+        *,
+        config: Config,
+        session_manager: SessionManager,
+        run_agent: RunAgentFn,
+        post_diff: PostDiffFn,
+        active_runs: Dict[str, Dict[str, object]],
+        send_message,
+        gate: Optional[AutomationGate] = None,
+    ) -> None:
+        super().__init__(send_message)
+        self._config = config
+        self._session_manager = session_manager
+        self._run_agent = run_agent
+        self._post_diff = post_diff
+        self._active_runs = active_runs
+        self._gate = gate
+
+    def update_config(self, config: Config) -> None:
+        self._config = config
+
+    async def handle_compare(self, command: ParsedCommand, context: CommandContext) -> None:
+        LOGGER.info("Executing !compare command in channel %s, thread %s", context.channel, context.thread_ts)
+        parts = command.raw_args.split(None, 2)
+        if len(parts) < 3:
+            await self._reply(context, COMPARE_USAGE)
+            return
+        first_id, second_id, prompt = parts
+        if first_id == second_id:
+            await self._reply(context, "Pick two different agents to compare.")
+            return
+
+        agents: List[Agent] = []
+        for agent_id in (first_id, second_id):
+            error = self._check_agent(agent_id, context)
+            if error:
+                await self._reply(context, error)
+                return
+            agents.append(self._config.get_agent(agent_id))
+
+        session = context.session
+        blocked = self._gate.block_reason(context.project, session) if self._gate else None
+        if blocked:
+            await self._reply(context, blocked)
+            return
+        session_id = str(session.id)
+        if any(run.get("session_id") == session_id for run in self._active_runs.values()):
+            await self._reply(context, "An agent is still running in this session; wait for it to finish first.")
+            return
+
+        contenders: List[Tuple[Agent, Optional[str]]] = [
+            (agent, context.project.model_for(agent)) for agent in agents
+        ]
+        estimates = [estimate_run_cost(agent, model, prompt) for agent, model in contenders]
+        message = f"Running `{first_id}` and `{second_id}` in separate worktrees..."
+        if all(estimates):
+            total = sum(estimate.cost_usd for estimate in estimates if estimate)
+            message = f"{message} Estimated cost: ~{format_cost(total)}"
+        await self._reply(context, message)
+
+        run_id = f"{context.channel}_{context.thread_ts}_{int(time.time() * 1000)}"
+        self._active_runs[run_id] = {
+            "task": asyncio.current_task(),
+            "session_id": session_id,
+            "agent_id": f"{first_id} vs {second_id}",
+            "started_at": time.time(),
+        }
+        try:
+            base, runs = await run_comparison(session.project_path, contenders, prompt, self._run_agent)
+        except ComparisonError as exc:
+            await self._reply(context, f"Could not compare agents: {exc}")
+            return
+        finally:
+            self._active_runs.pop(run_id, None)
+
+        for run in runs:
+            if run.cost_usd:
+                self._session_manager.record_run_cost(session.id, run.cost_usd)
+
+        await self._reply(context, format_comparison(base, runs))
+        for run in runs:
+            if not run.diff_text.strip():
+                await self._reply(context, f"*{run.label}* made no changes.")
+                continue
+            await self._reply(context, f"*{run.label}* changes:")
+            await self._post_diff(context.channel, context.thread_ts, run.diff_text)
+        await self._reply(
+            context,
+            "Nothing was committed. To keep one approach, `!use` that agent and send the prompt again.",
+        )
+
+    def _check_agent(self, agent_id: str, context: CommandContext) -> Optional[str]:
+        try:
+            agent = self._config.get_agent(agent_id)
+        except AgentNotFound:
+            return f"Unknown agent `{agent_id}`. Use `!agents` to see what's available."
+        project = context.project
+        if not project.allows_agent(agent_id):
+            allowed = ", ".join(f"`{allowed_id}`" for allowed_id in project.allowed_agents)
+            return f"Agent `{agent_id}` is not allowed in project `{project.id}`. Allowed: {allowed}"
+        if agent.working_dir_mode == WorkingDirMode.FIXED:
+            return f"`{agent_id}` works in a fixed directory, so it cannot run in a separate worktree."
+        return None
@@ -3,13 +3,24 @@
 from __future__ import annotations
 
 from dataclasses import dataclass
+from typing import Optional, Tuple
 
 from ..models import Project, Session
 
 
+@dataclass(frozen=True)
+class ChatAttachment:
+    """A text file uploaded together with a chat message."""
+
+    name: str
+    content: str
+
+
 @dataclass(frozen=True)
 class CommandContext:
     session: Session
     project: Project
     channel: str
     thread_ts: str
+    attachments: Tuple[ChatAttachment, ...] = ()
+    user_id: Optional[str] = None  # Chat user who sent the command, when the chat says
@@ -0,0 +1,57 @@
+"""Handler for moving a discussion session from design to implementation."""
+
+from __future__ import annotations
+
+import logging
+from typing import Awaitable, Callable, Dict
+
+from ..conversation import SessionManager
+from ..discussions import IMPLEMENT_PROMPT, IMPLEMENTATION_PHASE, PHASE_CONTEXT_KEY, in_design_phase
+from ..models import Project, Session
+from .base import BaseCommandHandler
+from .context import CommandContext
+from .parser import ParsedCommand
+
+LOGGER = logging.getLogger(__name__)
+
+ExecuteAgentFn = Callable[[Session, Project, str, str, str], Awaitable[None]]
+
+
+class DiscussionCommandHandler(BaseCommandHandler):
+    """Implements the implement command."""
+
+    def __init__(
+        self,
+        *,
+        session_manager: SessionManager,
+        execute_agent_task: ExecuteAgentFn,
+        active_runs: Dict[str, Dict[str, object]],
+        send_message,
+    ) -> None:
+        super().__init__(send_message)
+        self._session_manager = session_manager
+        self._execute_agent_task = execute_agent_task
+        self._active_runs = active_runs
+
+    async def handle_implement(self, command: ParsedCommand, context: CommandContext) -> None:
+        LOGGER.info("Executing !implement command in channel %s, thread %s", context.channel, context.thread_ts)
+        session = context.session
+        if not in_design_phase(session):
+            await self._reply(
+                context,
+                "`!implement` ends the design phase of sessions started from a GitHub Discussion; "
+                "this session already commits its changes.",
+            )
+            return
+        session_id = str(session.id)
+        if any(run.get("session_id") == session_id for run in self._active_runs.values()):
+            await self._reply(context, "An agent is still running in this session; wait for it to finish first.")
+            return
+
+        self._session_manager.update_session_context(session.id, {PHASE_CONTEXT_KEY: IMPLEMENTATION_PHASE})
+        await self._reply(
+            context,
+            f"Design phase over: `{session.active_agent_id}` now implements it, and the changes go to a pull request.",
+        )
+        prompt = f"{IMPLEMENT_PROMPT}\n\n{command.raw_args}" if command.raw_args else IMPLEMENT_PROMPT
+        await self._execute_agent_task(session, context.project, context.channel, context.thread_ts, prompt)
@@ -47,7 +47,7 @@ class CommandDispatcher:
         name = parts[0].lower()
         if name not in self._lookup:
             return None
-        return ParsedCommand(name=name, args=parts[1:])
+        return ParsedCommand(name=name, args=parts[1:], raw_args=normalized[len(parts[0]) :].strip())
 
     def build_help_lines(self) -> list[str]:
         """Render help text for all commands."""
@@ -0,0 +1,102 @@
+"""Handlers for exporting a session as Markdown and JSON, and for fetching uploaded sessions back."""
+
+from __future__ import annotations
+
+import asyncio
+import logging
+from typing import Awaitable, Callable, Optional
+from uuid import UUID
+
+from ..archive import SessionArchive
+from ..conversation import SessionManager
+from ..errors import ArchiveError, SessionNotFound
+from ..export import collect_export, export_json, export_markdown
+from ..models import Project, Session
+from .base import BaseCommandHandler
+from .context import CommandContext
+from .parser import ParsedCommand
+from .patches import UploadFileFn
+
+LOGGER = logging.getLogger(__name__)
+
+# Largest export posted as a message when the chat adapter cannot upload files
+INLINE_EXPORT_LIMIT = 3500
+
+BuildDiffFn = Callable[[Session, Project], Awaitable[Optional[str]]]
+# The configured session archive, if any; a getter so a config reload is picked up
+ArchiveFn = Callable[[], Optional[SessionArchive]]
+
+
+class ExportCommandHandler(BaseCommandHandler):
+    """Implements the export and archived commands."""
+
+    def __init__(
+        self,
+        *,
+        session_manager: SessionManager,
+        build_diff: BuildDiffFn,
+        upload_file: UploadFileFn,
+        send_message,
+        session_archive: ArchiveFn = lambda: None,
+    ) -> None:
+        super().__init__(send_message)
+        self._session_manager = session_manager
+        self._build_diff = build_diff
+        self._upload_file = upload_file
+        self._session_archive = session_archive
+
+    async def handle_export(self, command: ParsedCommand, context: CommandContext) -> None:
+        LOGGER.info("Executing !export command in channel %s, thread %s", context.channel, context.thread_ts)
+        session = context.session
+        export = collect_export(self._session_manager, session, await self._build_diff(session, context.project))
+        await self._post(context, str(session.id), export_markdown(export), export_json(export), "Session export.")
+
+    async def handle_archived(self, command: ParsedCommand, context: CommandContext) -> None:
+        LOGGER.info("Executing !archived command in channel %s, thread %s", context.channel, context.thread_ts)
+        archive = self._session_archive()
+        if archive is None:
+            await self._reply(context, "No session archive is set up (`REMOTE_CODER_ARCHIVE_URL`).")
+            return
+        if len(command.args) != 1:
+            await self._reply(context, "Usage: `!archived <session id>`")
+            return
+        try:
+            session_uuid = UUID(command.args[0])
+        except ValueError:
+            await self._reply(context, f"`{command.args[0]}` is not a session id; `!archived` needs the full id.")
+            return
+
+        session_id = str(session_uuid)
+        try:
+            fetched = await asyncio.to_thread(archive.fetch, session_id)
+        except ArchiveError as exc:
+            await self._reply(context, f"Could not fetch session `{session_id}` from the archive: {exc}")
+            return
+        if fetched is None:
+            try:
+                self._session_manager.get_session(session_uuid)
+                note = "It has not been uploaded yet; `!export` in its thread has the same content."
+            except SessionNotFound:
+                note = "No such session has been uploaded."
+            await self._reply(context, f"Session `{session_id}` is not in the archive. {note}")
+            return
+        markdown, json_text = fetched
+        await self._post(context, session_id, markdown, json_text, f"Archived session `{session_id}`.")
+
+    async def _post(self, context: CommandContext, session_id: str, markdown: str, json_text: str, title: str) -> None:
+        """Upload the Markdown and the JSON to the thread, or post the Markdown when the chat cannot take files."""
+        stem = f"remote-coder-{session_id}"
+        if await self._upload_file(context.channel, context.thread_ts, f"{stem}.md", markdown, title):
+            await self._upload_file(
+                context.channel, context.thread_ts, f"{stem}.json", json_text, "The same export as JSON."
+            )
+            return
+
+        if len(markdown) > INLINE_EXPORT_LIMIT:
+            await self._reply(
+                context,
+                f"The export is {len(markdown)} characters, too large to post here, and file uploads are not "
+                "supported by this chat adapter.",
+            )
+            return
+        await self._reply(context, markdown)
@@ -0,0 +1,124 @@
+"""Handlers for project groups and bulk operations on them."""
+
+from __future__ import annotations
+
+import logging
+from typing import Dict, List
+from uuid import UUID
+
+from ..automation import AutomationGate
+from ..config import Config
+from ..conversation import SessionManager
+from ..errors import SessionNotFound
+from ..models import Project
+from ..pricing import format_cost
+from .base import BaseCommandHandler
+from .context import CommandContext
+from .parser import ParsedCommand
+
+LOGGER = logging.getLogger(__name__)
+
+GROUP_USAGE = "Usage: `!group [<name> [pause|resume|status]]`"
+
+
+class GroupCommandHandler(BaseCommandHandler):
+    """Implements the group command."""
+
+    def __init__(
+        self,
+        *,
+        config: Config,
+        gate: AutomationGate,
+        session_manager: SessionManager,
+        active_runs: Dict[str, Dict[str, object]],
+        send_message,
+    ) -> None:
+        super().__init__(send_message)
+        self._config = config
+        self._gate = gate
+        self._session_manager = session_manager
+        self._active_runs = active_runs
+
+    def update_config(self, config: Config) -> None:
+        self._config = config
+
+    async def handle_group(self, command: ParsedCommand, context: CommandContext) -> None:
+        LOGGER.info("Executing !group command in channel %s, thread %s", context.channel, context.thread_ts)
+        groups = self._config.group_projects()
+        if not command.args:
+            await self._reply(context, self._format_group_list(groups))
+            return
+
+        name = command.args[0]
+        action = command.args[1].lower() if len(command.args) > 1 else "status"
+        projects = groups.get(name)
+        if projects is None:
+            known = ", ".join(f"`{group}`" for group in sorted(groups)) or "none configured"
+            await self._reply(context, f"Unknown group `{name}`. Groups: {known}")
+            return
+
+        if action == "pause":
+            changed = self._gate.pause(name)
+            LOGGER.info("Paused automation for group %s", name)
+            lines = [
+                f"Paused automation for `{name}` ({len(projects)} projects)."
+                if changed
+                else f"`{name}` is already paused."
+            ]
+            running = len(self._runs_in(projects))
+            if running:
+                lines.append(f"{running} agent run(s) already in progress will finish; use `!purge` to stop them.")
+            await self._reply(context, "\n".join(lines))
+        elif action == "resume":
+            changed = self._gate.resume(name)
+            LOGGER.info("Resumed automation for group %s", name)
+            await self._reply(
+                context, f"Resumed automation for `{name}`." if changed else f"`{name}` is not paused."
+            )
+        elif action == "status":
+            await self._reply(context, self._format_group_status(name, projects))
+        else:
+            await self._reply(context, GROUP_USAGE)
+
+    def _format_group_list(self, groups: Dict[str, List[Project]]) -> str:
+        if not groups:
+            return "No project groups configured. Add `groups:` and per-project `group:` keys to projects.yaml."
+        lines = ["Project groups:"]
+        for name in sorted(groups):
+            state = " (paused)" if self._gate.is_paused(name) else ""
+            members = ", ".join(f"`{project.id}`" for project in groups[name])
+            lines.append(f"- `{name}`{state}: {members}")
+        return "\n".join(lines)
+
+    def _format_group_status(self, name: str, projects: List[Project]) -> str:
+        state = "paused" if self._gate.is_paused(name) else "active"
+        runs = self._runs_in(projects)
+        lines = [f"Group `{name}`: {state}"]
+        for project in projects:
+            details = [f"default agent `{project.default_agent_id}`"]
+            if project.max_session_cost_usd is not None:
+                details.append(f"budget {format_cost(project.max_session_cost_usd)}/session")
+            quota = self._gate.quota_status(project)
+            if quota:
+                details.append(f"quota {quota}")
+            if project.quiet_hours:
+                quiet = project.quiet_hours
+                details.append(f"quiet {quiet.start.strftime('%H:%M')}-{quiet.end.strftime('%H:%M')}")
+            running = sum(1 for project_id in runs if project_id == project.id)
+            if running:
+                details.append(f"{running} running")
+            lines.append(f"- `{project.id}`: {', '.join(details)}")
+        return "\n".join(lines)
+
+    def _runs_in(self, projects: List[Project]) -> List[str]:
+        """Project ids of the active runs that belong to `projects`, one entry per run."""
+        project_ids = {project.id for project in projects}
+        matches = []
+        for run in self._active_runs.values():
+            try:
+                session = self._session_manager.get_session(UUID(str(run.get("session_id"))))
+            except (SessionNotFound, ValueError):
+                continue
+            if session.project_id in project_ids:
+                matches.append(session.project_id)
+        return matches
@@ -0,0 +1,167 @@
+"""Command metadata for native autocompletion in chat clients.
+
+Built-in commands and every project's `.cockpit/commands` are described as
+`CommandHint`s, which render to Slack slash commands (`/rc-<name>`, with a
+usage hint) or Discord application commands (with one option per argument).
+Chat adapters that can register commands implement `register_commands`; the
+router calls it whenever the rendered hints change.
+"""
+
+from __future__ import annotations
+
+import hashlib
+import json
+import logging
+import re
+from dataclasses import asdict, dataclass
+from typing import Any, Dict, Iterable, List, Optional, Sequence, Tuple
+
+from ..models import CommandArg, Project
+from ..project_commands import list_project_commands
+from .registry import CommandSpec
+
+LOGGER = logging.getLogger(__name__)
+
+SLASH_COMMAND_PREFIX = "rc-"
+# Slack and Discord both cut command names at 32 characters; Discord descriptions at 100
+MAX_NAME_LENGTH = 32
+MAX_DESCRIPTION_LENGTH = 100
+DISCORD_NAME = re.compile(r"^[-_a-z0-9]{1,32}$")
+DISCORD_CHAT_INPUT = 1
+DISCORD_STRING_OPTION = 3
+
+
+@dataclass(frozen=True)
+class CommandHint:
+    name: str
+    description: str
+    usage_hint: str  # E.g. "<agent> [model]"
+    args: Tuple[CommandArg, ...] = ()
+    projects: Tuple[str, ...] = ()  # Projects defining this command; empty for built-ins
+
+
+def build_command_hints(specs: Sequence[CommandSpec], projects: Iterable[Project]) -> List[CommandHint]:
+    """Built-ins in registry order, then project commands by name; built-ins win on a name clash."""
+    hints = [
+        CommandHint(
+            name=spec.name,
+            description=spec.description,
+            usage_hint=spec.usage.partition(" ")[2],
+            args=spec.args,
+        )
+        for spec in specs
+    ]
+    reserved = {name for spec in specs for name in spec.all_names}
+    project_hints: Dict[str, CommandHint] = {}
+    seen_paths = set()
+    for project in projects:
+        if project.path in seen_paths:
+            continue
+        seen_paths.add(project.path)
+        for definition in list_project_commands(project.path):
+            if definition.id in reserved:
+                continue
+            existing = project_hints.get(definition.id)
+            if existing:
+                project_hints[definition.id] = CommandHint(
+                    name=existing.name,
+                    description=existing.description,
+                    usage_hint=existing.usage_hint,
+                    args=existing.args,
+                    projects=(*existing.projects, project.id),
+                )
+                continue
+            args = tuple(definition.args)
+            project_hints[definition.id] = CommandHint(
+                name=definition.id,
+                description=definition.description or f"Run the `{definition.id}` project command.",
+                usage_hint=" ".join(f"[{arg.description or arg.name}]" for arg in args),
+                args=args,
+                projects=(project.id,),
+            )
+    hints.extend(project_hints[name] for name in sorted(project_hints))
+    return hints
+
+
+def hints_fingerprint(hints: Sequence[CommandHint]) -> str:
+    payload = json.dumps([asdict(hint) for hint in hints], sort_keys=True)
+    return hashlib.sha256(payload.encode("utf-8")).hexdigest()
+
+
+def _describe(hint: CommandHint) -> str:
+    description = hint.description
+    if hint.projects:
+        description = f"{description} ({', '.join(hint.projects)})"
+    if len(description) > MAX_DESCRIPTION_LENGTH:
+        description = description[: MAX_DESCRIPTION_LENGTH - 1].rstrip() + "…"
+    return description
+
+
+def slack_slash_commands(hints: Sequence[CommandHint], prefix: str = SLASH_COMMAND_PREFIX) -> List[Dict[str, Any]]:
+    """`features.slash_commands` entries of a Slack app manifest."""
+    entries = []
+    for hint in hints:
+        command = f"/{prefix}{hint.name}"
+        if len(command) > MAX_NAME_LENGTH:
+            LOGGER.warning("Skipping slash command %s: longer than %d characters", command, MAX_NAME_LENGTH)
+            continue
+        entry: Dict[str, Any] = {"command": command, "description": _describe(hint), "should_escape": False}
+        if hint.usage_hint:
+            entry["usage_hint"] = hint.usage_hint
+        entries.append(entry)
+    return entries
+
+
+def merge_slash_commands(
+    manifest: Dict[str, Any], entries: List[Dict[str, Any]], prefix: str = SLASH_COMMAND_PREFIX
+) -> Optional[Dict[str, Any]]:
+    """Replace the manifest's `/<prefix>*` commands with `entries`; None when it already has them.
+
+    Slash commands the app defines for other purposes are kept.
+    """
+    features = dict(manifest.get("features") or {})
+    current = list(features.get("slash_commands") or [])
+    others = [entry for entry in current if not str(entry.get("command", "")).startswith(f"/{prefix}")]
+    ours = [entry for entry in current if entry not in others]
+    if ours == entries:
+        return None
+    features["slash_commands"] = [*others, *entries]
+    return {**manifest, "features": features}
+
+
+def command_text_from_slash(command: str, text: str, prefix: str = SLASH_COMMAND_PREFIX) -> Optional[str]:
+    """`/rc-status` with text `--system` becomes `!status --system`; None for other slash commands."""
+    if not command.startswith(f"/{prefix}"):
+        return None
+    name = command[len(prefix) + 1 :]
+    if not name:
+        return None
+    return f"!{name} {text.strip()}".rstrip()
+
+
+def discord_application_commands(hints: Sequence[CommandHint]) -> List[Dict[str, Any]]:
+    """Payload for Discord's bulk-overwrite application commands endpoint."""
+    commands = []
+    for hint in hints:
+        if not DISCORD_NAME.match(hint.name):
+            LOGGER.warning("Skipping Discord command %s: not a valid command name", hint.name)
+            continue
+        # Discord requires every required option to come before the optional ones
+        ordered = sorted(hint.args, key=lambda arg: not arg.required)
+        commands.append(
+            {
+                "name": hint.name,
+                "type": DISCORD_CHAT_INPUT,
+                "description": _describe(hint),
+                "options": [
+                    {
+                        "type": DISCORD_STRING_OPTION,
+                        "name": arg.name.lower(),
+                        "description": (arg.description or arg.name)[:MAX_DESCRIPTION_LENGTH],
+                        "required": arg.required,
+                    }
+                    for arg in ordered
+                ],
+            }
+        )
+    return commands
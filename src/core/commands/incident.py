@@ -0,0 +1,97 @@
+"""Handler for turning a project's incident mode on and off."""
+
+from __future__ import annotations
+
+import logging
+
+from ..config import Config
+from ..conversation import SessionManager
+from ..incident import (
+    DEFAULT_INCIDENT_MINUTES,
+    MAX_INCIDENT_MINUTES,
+    IncidentWindow,
+    audit_path,
+    cheapest_agent,
+    open_window,
+    set_debug_logging,
+)
+from ..local_time import format_local_time
+from .base import BaseCommandHandler
+from .context import CommandContext
+from .parser import ParsedCommand
+
+LOGGER = logging.getLogger(__name__)
+
+USAGE = f"Usage: `!incident`, `!incident on [minutes]` (default {DEFAULT_INCIDENT_MINUTES}), or `!incident off`"
+
+
+class IncidentCommandHandler(BaseCommandHandler):
+    """Implements the incident command."""
+
+    def __init__(self, *, session_manager: SessionManager, config: Config, send_message) -> None:
+        super().__init__(send_message)
+        self._session_manager = session_manager
+        self._config = config
+
+    def update_config(self, config: Config) -> None:
+        self._config = config
+
+    async def handle_incident(self, command: ParsedCommand, context: CommandContext) -> None:
+        LOGGER.info("Executing !incident command in channel %s, thread %s", context.channel, context.thread_ts)
+        project = context.project
+        action = command.args[0].lower() if command.args else "status"
+
+        if action == "status":
+            window = self._session_manager.incident_for(project.id)
+            set_debug_logging(bool(self._session_manager.active_incidents()))
+            if window:
+                await self._reply(context, self._describe(window, context))
+            else:
+                await self._reply(context, f"`{project.id}` is not in incident mode. {USAGE}")
+            return
+
+        if action == "off":
+            window = self._session_manager.end_incident(project.id)
+            set_debug_logging(bool(self._session_manager.active_incidents()))
+            if window:
+                LOGGER.warning("Incident mode ended for project %s", project.id)
+                await self._reply(
+                    context,
+                    f"Incident mode is off for `{project.id}`. Threads started during it stay read-only; "
+                    f"the audit log is kept at `{audit_path(self._config.config_dir, project.id)}`.",
+                )
+            else:
+                await self._reply(context, f"`{project.id}` is not in incident mode.")
+            return
+
+        if action != "on" or len(command.args) > 2:
+            await self._reply(context, USAGE)
+            return
+        minutes = DEFAULT_INCIDENT_MINUTES
+        if len(command.args) == 2:
+            if not command.args[1].isdigit() or not 0 < int(command.args[1]) <= MAX_INCIDENT_MINUTES:
+                await self._reply(context, f"The window must be 1 to {MAX_INCIDENT_MINUTES} minutes. {USAGE}")
+                return
+            minutes = int(command.args[1])
+
+        window = open_window(project.id, minutes, context.user_id)
+        self._session_manager.start_incident(window)
+        set_debug_logging(True)
+        LOGGER.warning("Incident mode started for project %s for %d minute(s)", project.id, minutes)
+        await self._reply(context, self._describe(window, context))
+
+    def _describe(self, window: IncidentWindow, context: CommandContext) -> str:
+        project = context.project
+        agent = cheapest_agent(list(self._config.agents.values()), project)
+        agent_note = f" with `{agent.id}`" if agent else ""
+        until = format_local_time(window.ends_at, context.session.timezone)
+        return "\n".join(
+            [
+                f"Incident mode is on for `{project.id}` until {until}. While it lasts:",
+                "- every run asks for approval before the agent starts, and changes wait for `!publish`",
+                "- the daemon logs at DEBUG, and replies show each run's agent, model, duration, and cost",
+                f"- every run step is written to `{audit_path(self._config.config_dir, project.id)}`",
+                f"- new threads are read-only diagnosis threads{agent_note}",
+                "`!incident off` ends it early.",
+            ]
+        )
@@ -4,6 +4,7 @@ from __future__ import annotations
 
 import asyncio
 import logging
+import shutil
 import subprocess
 from pathlib import Path
 from typing import Awaitable, Callable, Dict
@@ -13,6 +14,7 @@ from ..config import Config
 from ..errors import ConfigError, GitHubError
 from ..models import Project, Session
 from ..conversation import SessionManager
+from ..snapshots import snapshots_dir
 from .base import BaseCommandHandler
 from .context import CommandContext
 
@@ -98,6 +100,8 @@ class MaintenanceCommandHandler(BaseCommandHandler):
             LOGGER.info("No active agent runs to cancel")
 
         num_sessions = self._session_manager.clear_all()
+        # Rollback snapshots belong to the cleared sessions
+        shutil.rmtree(snapshots_dir(self._get_current_config().config_dir), ignore_errors=True)
         if num_sessions > 0:
             LOGGER.info("Cleared %d session(s)", num_sessions)
         else:
@@ -0,0 +1,125 @@
+"""Handler for moving a session to a new thread (`!move`).
+
+`!move` starts a new thread in the same channel (for a thread that got
+noisy); `!move #channel` starts it in another channel of the same project
+(for a conversation begun in the wrong place). The session keeps its
+history, agent, branch, PRs, and transcript, and both threads link to each
+other. A message in the old thread afterwards starts a new session.
+"""
+
+from __future__ import annotations
+
+import logging
+import re
+from typing import Awaitable, Callable, Dict, Optional, Tuple
+
+from ..ask_mode import in_ask_mode
+from ..config import Config
+from ..conversation import SessionManager
+from ..discussions import is_discussion_channel
+from ..errors import ProjectNotFound, SessionMoveError, SlackError
+from .base import BaseCommandHandler
+from .context import CommandContext
+from .parser import ParsedCommand
+
+LOGGER = logging.getLogger(__name__)
+
+USAGE = "Usage: `!move` for a new thread here, or `!move #channel` for one in another channel of this project"
+MOVED_HERE_NOTE = (
+    "Session moved here from {old}, with its history, agent, and branch. Send your next request in this thread."
+)
+MOVED_AWAY_NOTE = "Moved to {new}. Carry on there; a new message here starts a new session."
+
+# Slack renders `#name` in a message as `<#C0123|name>` (the name can be empty)
+_CHANNEL_MENTION = re.compile(r"<#(?P<id>[A-Z0-9]+)(?:\|(?P<name>[^>]*))?>")
+
+ResolveChannelFn = Callable[[str], Awaitable[Optional[str]]]
+ThreadLinkFn = Callable[[str, str], Optional[str]]
+
+
+class MoveCommandHandler(BaseCommandHandler):
+    """Implements the move command."""
+
+    def __init__(
+        self,
+        *,
+        session_manager: SessionManager,
+        config: Config,
+        send_message,
+        resolve_channel: ResolveChannelFn,
+        thread_link: ThreadLinkFn,
+        active_runs: Optional[Dict[str, Dict[str, object]]] = None,
+    ) -> None:
+        super().__init__(send_message)
+        self._session_manager = session_manager
+        self._config = config
+        self._resolve_channel = resolve_channel
+        self._thread_link = thread_link
+        self._active_runs = active_runs if active_runs is not None else {}
+
+    def update_config(self, config: Config) -> None:
+        self._config = config
+
+    async def handle_move(self, command: ParsedCommand, context: CommandContext) -> None:
+        LOGGER.info("Executing !move command in channel %s, thread %s", context.channel, context.thread_ts)
+        session = context.session
+        if is_discussion_channel(context.channel):
+            await self._reply(context, "Sessions on GitHub Discussions stay with their discussion.")
+            return
+        if any(run.get("session_id") == str(session.id) for run in self._active_runs.values()):
+            await self._reply(context, "A run is in progress; let it finish or `!cancel` it before moving the session.")
+            return
+        if len(command.args) > 1:
+            await self._reply(context, USAGE)
+            return
+
+        if command.args:
+            target = await self._target_channel(command.args[0], context)
+            if target is None:
+                return
+            channel_id, channel_name = target
+        else:
+            channel_id, channel_name = context.channel, None
+
+        old = self._where(context.channel, context.thread_ts)
+        try:
+            thread_ts = await self._send_message(channel_id, None, MOVED_HERE_NOTE.format(old=old))
+        except SlackError as exc:
+            await self._reply(context, f"Could not start a thread in `#{channel_name or channel_id}`: {exc}")
+            return
+        if not thread_ts:
+            await self._reply(context, "Could not start a new thread: the chat connection is not ready.")
+            return
+        try:
+            self._session_manager.move_session(session.id, channel_id, thread_ts)
+        except SessionMoveError as exc:
+            await self._reply(context, f"Could not move the session: {exc}")
+            return
+        await self._reply(context, MOVED_AWAY_NOTE.format(new=self._where(channel_id, thread_ts)))
+
+    async def _target_channel(self, arg: str, context: CommandContext) -> Optional[Tuple[str, str]]:
+        """The (id, name) of the channel in `arg`, when it is one of the project's; otherwise replies why not."""
+        mention = _CHANNEL_MENTION.fullmatch(arg)
+        name = (mention.group("name") if mention else arg).lstrip("#")
+        if not name:
+            await self._reply(context, f"Name the channel, e.g. `!move #{context.project.channel_name}`.")
+            return None
+        try:
+            project = self._config.get_project_by_channel(name)
+        except ProjectNotFound:
+            project = None
+        if project is None or project.id != context.project.id:
+            await self._reply(context, f"`#{name}` is not a channel of `{context.project.id}`. {USAGE}.")
+            return None
+        if (name in project.ask_channels) != in_ask_mode(context.session):
+            kind = "question" if in_ask_mode(context.session) else "change"
+            await self._reply(context, f"This is a {kind} session; move it to another {kind} channel of the project.")
+            return None
+        channel_id = mention.group("id") if mention else await self._resolve_channel(name)
+        if not channel_id:
+            await self._reply(context, f"Cannot find `#{name}`; is the bot in it?")
+            return None
+        return channel_id, name
+
+    def _where(self, channel_id: str, thread_ts: str) -> str:
+        return self._thread_link(channel_id, thread_ts) or f"thread `{thread_ts}`"
@@ -1,7 +1,7 @@
 from __future__ import annotations
 
 import re
-from dataclasses import dataclass
+from dataclasses import dataclass, field
 from typing import List, Optional
 
 MENTION_PREFIX = re.compile(r"^<@[^>]+>\s*")
@@ -11,6 +11,7 @@ MENTION_PREFIX = re.compile(r"^<@[^>]+>\s*")
 class ParsedCommand:
     name: str
     args: List[str]
+    raw_args: str = field(default="", compare=False)  # Text after the command name, whitespace preserved
 
 
 def parse_command(text: str) -> Optional[ParsedCommand]:
@@ -26,9 +27,10 @@ def parse_command(text: str) -> Optional[ParsedCommand]:
     if not normalized.startswith("!"):
         return None
 
-    parts = normalized[1:].strip().split()
+    body = normalized[1:].strip()
+    parts = body.split()
     if not parts:
         return None
     name = parts[0].lower()
     args = parts[1:]
-    return ParsedCommand(name=name, args=args)
+    return ParsedCommand(name=name, args=args, raw_args=body[len(parts[0]) :].strip())
@@ -0,0 +1,191 @@
+"""Handlers for exchanging session changes as patch files."""
+
+from __future__ import annotations
+
+import html
+import logging
+import re
+import subprocess
+from typing import Awaitable, Callable, Dict, Optional, Tuple
+
+from ...agent_adapters import AgentResult
+from ..errors import GitHubError
+from ..models import Project, Session
+from ..verify import checks_passed, format_verify_results, has_project_checks, run_project_checks
+from .base import BaseCommandHandler
+from .context import ChatAttachment, CommandContext
+from .parser import ParsedCommand
+from .verify import RecordChecksFn
+
+LOGGER = logging.getLogger(__name__)
+
+# Largest patch posted as a message when the chat adapter cannot upload files
+INLINE_PATCH_LIMIT = 3500
+
+PATCH_EXTENSIONS = (".patch", ".diff")
+CODE_FENCE = re.compile(r"^```[\w-]*\n?(.*?)\n?```$", re.DOTALL)
+APPLY_COMMIT_MESSAGE = "Apply patch from chat"
+SKIP_CHECKS_FLAG = "--skip-checks"
+
+BuildPatchFn = Callable[[Session, Project], Awaitable[Optional[str]]]
+UploadFileFn = Callable[[str, str, str, str, str], Awaitable[bool]]
+ApplyPatchFn = Callable[[Session, Project, str], Awaitable[Tuple[bool, str]]]
+RevertPatchFn = Callable[[Session, Project, str], Awaitable[None]]
+ContainsSecretFn = Callable[[str], bool]
+PublishFn = Callable[[Session, Project, AgentResult, str], Awaitable[Optional[str]]]
+
+
+def extract_patch_text(raw_args: str, attachments: Tuple[ChatAttachment, ...]) -> Optional[str]:
+    """Pick the patch from an uploaded .patch/.diff file, or from text pasted after the command."""
+    for attachment in attachments:
+        if attachment.name.lower().endswith(PATCH_EXTENSIONS):
+            return attachment.content
+    if attachments:
+        return attachments[0].content
+
+    # Chat platforms escape <, > and & in message text
+    text = html.unescape(raw_args.strip())
+    fenced = CODE_FENCE.match(text)
+    if fenced:
+        text = fenced.group(1)
+    if not text.strip():
+        return None
+    return text if text.endswith("\n") else f"{text}\n"
+
+
+class PatchCommandHandler(BaseCommandHandler):
+    """Implements the patch export and apply commands."""
+
+    def __init__(
+        self,
+        *,
+        build_patch: BuildPatchFn,
+        upload_file: UploadFileFn,
+        apply_patch: ApplyPatchFn,
+        revert_patch: RevertPatchFn,
+        publish_changes: PublishFn,
+        active_runs: Dict[str, Dict[str, object]],
+        send_message,
+        record_checks: Optional[RecordChecksFn] = None,
+        contains_secret: Optional[ContainsSecretFn] = None,
+    ) -> None:
+        super().__init__(send_message)
+        self._build_patch = build_patch
+        self._upload_file = upload_file
+        self._apply_patch = apply_patch
+        self._revert_patch = revert_patch
+        self._publish_changes = publish_changes
+        self._active_runs = active_runs
+        self._record_checks = record_checks
+        self._contains_secret = contains_secret
+
+    async def handle_patch(self, command: ParsedCommand, context: CommandContext) -> None:
+        LOGGER.info("Executing !patch command in channel %s, thread %s", context.channel, context.thread_ts)
+        try:
+            patch = await self._build_patch(context.session, context.project)
+        except subprocess.CalledProcessError as exc:
+            detail = (exc.stderr or exc.stdout or str(exc)).strip()
+            await self._reply(context, f"Failed to build patch: {detail or 'git error'}")
+            return
+
+        if not patch:
+            await self._reply(context, "No commits on this session's branch yet; nothing to export.")
+            return
+        # Redacting a patch would break `git am`, so one that holds a secret is not posted at all
+        if self._contains_secret and self._contains_secret(patch):
+            await self._reply(
+                context,
+                "The patch contains what looks like a secret, so it is not posted here. "
+                "Fetch the session branch with git instead.",
+            )
+            return
+
+        filename = f"remote-coder-{context.session.id}.patch"
+        uploaded = await self._upload_file(
+            context.channel,
+            context.thread_ts,
+            filename,
+            patch,
+            "Apply with `git am < " + filename + "`.",
+        )
+        if uploaded:
+            return
+
+        if len(patch) > INLINE_PATCH_LIMIT:
+            await self._reply(
+                context,
+                f"The patch is {len(patch)} characters, too large to post here, and file uploads are not "
+                "supported by this chat adapter. Fetch the session branch with git instead.",
+            )
+            return
+        await self._reply(context, f"```\n{patch.rstrip()}\n```\nApply with `git am`.")
+
+    async def handle_apply(self, command: ParsedCommand, context: CommandContext) -> None:
+        LOGGER.info("Executing !apply command in channel %s, thread %s", context.channel, context.thread_ts)
+        raw_args = command.raw_args
+        skip_checks = raw_args.split(maxsplit=1)[:1] == [SKIP_CHECKS_FLAG]
+        if skip_checks:
+            raw_args = raw_args[len(SKIP_CHECKS_FLAG) :]
+        patch = extract_patch_text(raw_args, context.attachments)
+        if not patch:
+            await self._reply(
+                context,
+                "Usage: `!apply [--skip-checks]` with a `.patch`/`.diff` file attached, "
+                "or paste the diff after the command.",
+            )
+            return
+
+        session_id = str(context.session.id)
+        if any(run.get("session_id") == session_id for run in self._active_runs.values()):
+            await self._reply(context, "An agent is still running in this session; wait for it to finish first.")
+            return
+
+        try:
+            applied, detail = await self._apply_patch(context.session, context.project, patch)
+        except (GitHubError, subprocess.CalledProcessError) as exc:
+            detail = (
+                exc if isinstance(exc, GitHubError) else (exc.stderr or exc.stdout or str(exc)).strip() or "git error"
+            )
+            await self._reply(context, f"Failed to prepare session branch: {detail}")
+            return
+        if not applied:
+            await self._reply(context, f"Patch does not apply to the session branch:\n```\n{detail}\n```")
+            return
+
+        lines = ["Patch applied to the session branch."]
+        if detail:
+            lines.append(f"```\n{detail}\n```")
+
+        project = context.project
+        if has_project_checks(project) and not skip_checks:
+            results = await run_project_checks(project, context.session.project_path)
+            passed = checks_passed(results)
+            if self._record_checks:
+                self._record_checks(context.session.id, passed)
+            lines.append(format_verify_results(results))
+            if not passed:
+                # Nothing is committed or pushed on top of failing checks unless asked for
+                try:
+                    await self._revert_patch(context.session, project, patch)
+                    lines.append(
+                        "The patch was taken back out and nothing was published. "
+                        f"To publish it anyway, send it again with `!apply {SKIP_CHECKS_FLAG}`."
+                    )
+                except subprocess.CalledProcessError:
+                    LOGGER.warning("Could not revert the patch applied in session %s", context.session.id)
+                    lines.append(
+                        "Nothing was published. The checks changed the patched files, so the patch could not be "
+                        "taken back out; it is left uncommitted in the checkout."
+                    )
+                await self._reply(context, "\n".join(lines))
+                return
+
+        publish_message = await self._publish_changes(
+            context.session,
+            project,
+            AgentResult(success=True, output_text=""),
+            APPLY_COMMIT_MESSAGE,
+        )
+        if publish_message:
+            lines.append(publish_message)
+        await self._reply(context, "\n".join(lines))
@@ -0,0 +1,61 @@
+"""Handler for the per-user defaults that new sessions start with."""
+
+from __future__ import annotations
+
+import logging
+
+from ..config import Config
+from ..conversation import SessionManager
+from ..preferences import PreferenceError, UserPreferences, with_preference, without_preference
+from .base import BaseCommandHandler
+from .context import CommandContext
+from .parser import ParsedCommand
+
+LOGGER = logging.getLogger(__name__)
+
+USAGE = "Usage: `!prefs`, `!prefs <agent|verbosity|notify|locale> <value>`, or `!prefs clear [name]`"
+
+
+class PreferencesCommandHandler(BaseCommandHandler):
+    """Implements the prefs command."""
+
+    def __init__(self, *, session_manager: SessionManager, config: Config, send_message) -> None:
+        super().__init__(send_message)
+        self._session_manager = session_manager
+        self._config = config
+
+    def update_config(self, config: Config) -> None:
+        self._config = config
+
+    async def handle_prefs(self, command: ParsedCommand, context: CommandContext) -> None:
+        LOGGER.info("Executing !prefs command in channel %s, thread %s", context.channel, context.thread_ts)
+        if not context.user_id:
+            await self._reply(context, "This chat does not say who sent the message, so there are no preferences here.")
+            return
+        current = self._session_manager.user_preferences(context.user_id)
+        if not command.args:
+            lines = ["Your defaults for new sessions:", *(f"- {line}" for line in current.describe())]
+            await self._reply(context, "\n".join(lines))
+            return
+
+        name = command.args[0].lower()
+        try:
+            if name == "clear":
+                updated = without_preference(current, command.args[1]) if len(command.args) > 1 else UserPreferences()
+            elif len(command.args) != 2:
+                await self._reply(context, USAGE)
+                return
+            else:
+                updated = with_preference(current, name, command.args[1], agent_ids=self._config.agents.keys())
+        except PreferenceError as exc:
+            await self._reply(context, f"{exc}. {USAGE}")
+            return
+
+        self._session_manager.set_user_preferences(context.user_id, updated)
+        LOGGER.info("Updated preferences of user %s", context.user_id)
+        lines = [
+            "Saved. Sessions you start from now on use:",
+            *(f"- {line}" for line in updated.describe()),
+            "Threads that are already running keep their settings.",
+        ]
+        await self._reply(context, "\n".join(lines))
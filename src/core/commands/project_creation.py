@@ -5,6 +5,7 @@ from __future__ import annotations
 import logging
 import time
 from dataclasses import dataclass, field
+from pathlib import Path
 from typing import Awaitable, Callable, Dict, List, Optional
 
 from ..config import Config, load_config
@@ -14,13 +15,20 @@ from ...github import GitHubManager
 LOGGER = logging.getLogger(__name__)
 
 # Type alias for the message sender callback
-SendMessage = Callable[[str, str, str], Awaitable[Optional[str]]]
+SendMessage = Callable[[str, Optional[str], str], Awaitable[Optional[str]]]
 
 # States for the project creation flow
 STATE_AWAITING_CONFIRMATION = "awaiting_confirmation"
+STATE_AWAITING_PATH = "awaiting_path"
+STATE_AWAITING_PROJECT = "awaiting_project"
 STATE_AWAITING_AGENT = "awaiting_agent"
 STATE_AWAITING_MODEL = "awaiting_model"
 
+# Ways to connect a channel, in the order they are offered
+SETUP_CREATE = "create"
+SETUP_REGISTER = "register"
+SETUP_LINK = "link"
+
 
 @dataclass
 class PendingProjectCreation:
@@ -28,9 +36,12 @@ class PendingProjectCreation:
 
     channel_id: str
     channel_name: str
-    thread_ts: str
+    thread_ts: Optional[str]
     created_at: float
     state: str = STATE_AWAITING_CONFIRMATION
+    setup_options: List[str] = field(default_factory=list)
+    repo_path: Optional[Path] = None  # Set when registering a repository outside base_dir
+    project_options: List[str] = field(default_factory=list)
     selected_agent_id: Optional[str] = None
     agent_options: List[str] = field(default_factory=list)
     model_options: List[str] = field(default_factory=list)
@@ -44,9 +55,11 @@ class ProjectCreationHandler:
         config: Config,
         github_manager: GitHubManager,
         config_root,
+        config_loader: Optional[Callable[[], Config]] = None,
     ) -> None:
         self._config = config
         self._config_root = config_root
+        self._config_loader = config_loader or (lambda: load_config(self._config_root))
         self._pending_projects: Dict[str, PendingProjectCreation] = {}
         self._project_creator = ProjectCreationService(
             config=config,
@@ -62,35 +75,48 @@ class ProjectCreationHandler:
         self,
         channel_id: str,
         channel_name: str,
-        thread_ts: str,
+        thread_ts: Optional[str],
         send_message: SendMessage,
     ) -> None:
         """
-        Handle a message to a channel without a configured project.
+        Handle a channel without a configured project.
 
-        Sends a prompt asking the user if they want to create the project.
+        Called for the first message in the channel, or with no thread when the
+        bot is invited; the prompt then starts a thread of its own. Offers to
+        create the project, register a repository already on this machine, or
+        connect the channel to a configured project.
         """
         # Check if we already prompted for this channel
         if channel_id in self._pending_projects:
             LOGGER.debug("Project creation already pending for %s", channel_name)
             return
 
-        # Send prompt message
-        await send_message(
-            channel_id,
-            thread_ts,
-            f"I couldn't find a project named `{channel_name}`. "
-            "Is this a new idea, or something that already exists on your computer?\n\n"
-            "Reply with \"Y\" to set it up, or \"N\" to cancel.",
-        )
-
-        # Track pending creation
-        self._pending_projects[channel_id] = PendingProjectCreation(
+        setup_options = [SETUP_CREATE, SETUP_REGISTER]
+        if self._config.projects:
+            setup_options.append(SETUP_LINK)
+        labels = {
+            SETUP_CREATE: f"Create `{channel_name}` in `{self._config.base_dir}` (or use the checkout already there)",
+            SETUP_REGISTER: "Register a repository that's somewhere else on this machine",
+            SETUP_LINK: "Connect this channel to a project that's already configured",
+        }
+        lines = [f"I couldn't find a project named `{channel_name}`. How should I set it up?\n"]
+        for i, option in enumerate(setup_options, 1):
+            lines.append(f"{i}. {labels[option]}")
+        lines.append('\nReply with the number (or "Y" for 1), or "N" to cancel.')
+
+        # Track pending creation before sending so a fast reply finds it
+        pending = PendingProjectCreation(
             channel_id=channel_id,
             channel_name=channel_name,
             thread_ts=thread_ts,
             created_at=time.time(),
+            setup_options=setup_options,
         )
+        self._pending_projects[channel_id] = pending
+
+        message_ts = await send_message(channel_id, thread_ts, "\n".join(lines))
+        if thread_ts is None:
+            pending.thread_ts = message_ts
 
     async def handle_response(
         self,
@@ -112,6 +138,10 @@ class ProjectCreationHandler:
 
         if pending.state == STATE_AWAITING_CONFIRMATION:
             return await self._handle_confirmation_response(pending, response, send_message)
+        elif pending.state == STATE_AWAITING_PATH:
+            return await self._handle_path_response(pending, text.strip(), send_message)
+        elif pending.state == STATE_AWAITING_PROJECT:
+            return await self._handle_project_response(pending, response, send_message)
         elif pending.state == STATE_AWAITING_AGENT:
             return await self._handle_agent_response(pending, response, send_message)
         elif pending.state == STATE_AWAITING_MODEL:
@@ -125,22 +155,95 @@ class ProjectCreationHandler:
         response: str,
         send_message: SendMessage,
     ) -> tuple[bool, Optional[Config]]:
-        """Handle Y/N confirmation response."""
-        if response in ("y", "yes"):
-            await self._show_agent_options(pending, send_message)
-            return True, None
-        elif response in ("n", "no"):
+        """Handle the choice of how to set up the channel."""
+        if response in ("n", "no"):
             del self._pending_projects[pending.channel_id]
             await self._handle_rejection(pending, send_message)
             return True, None
+
+        option = SETUP_CREATE if response in ("y", "yes") else self._pick(response, pending.setup_options)
+        if option == SETUP_CREATE:
+            await self._show_agent_options(pending, send_message)
+        elif option == SETUP_REGISTER:
+            pending.state = STATE_AWAITING_PATH
+            await send_message(
+                pending.channel_id,
+                pending.thread_ts,
+                "What's the path to the repository on this machine? (for example `~/code/my-app`)",
+            )
+        elif option == SETUP_LINK:
+            await self._show_project_options(pending, send_message)
         else:
             await send_message(
                 pending.channel_id,
                 pending.thread_ts,
-                f"Please reply with \"Y\" to create project `{pending.channel_name}`, or \"N\" to cancel.",
+                f"Please reply with a number from 1 to {len(pending.setup_options)}, or \"N\" to cancel.",
+            )
+        return True, None
+
+    async def _handle_path_response(
+        self,
+        pending: PendingProjectCreation,
+        response: str,
+        send_message: SendMessage,
+    ) -> tuple[bool, Optional[Config]]:
+        """Handle the path of a repository to register."""
+        if response.lower() in ("n", "no"):
+            del self._pending_projects[pending.channel_id]
+            await self._handle_rejection(pending, send_message)
+            return True, None
+
+        repo_path = Path(response.strip("`")).expanduser()
+        if not repo_path.is_absolute() or not repo_path.is_dir():
+            await send_message(
+                pending.channel_id,
+                pending.thread_ts,
+                f"I can't find a directory at `{response}`. Reply with an absolute path (or `~/...`), "
+                "or \"N\" to cancel.",
             )
             return True, None
 
+        pending.repo_path = repo_path
+        await self._show_agent_options(pending, send_message)
+        return True, None
+
+    async def _handle_project_response(
+        self,
+        pending: PendingProjectCreation,
+        response: str,
+        send_message: SendMessage,
+    ) -> tuple[bool, Optional[Config]]:
+        """Handle the choice of a configured project to connect the channel to."""
+        project_id = self._pick(response, pending.project_options)
+        if not project_id:
+            await send_message(
+                pending.channel_id,
+                pending.thread_ts,
+                "Please reply with the number corresponding to the project.",
+            )
+            return True, None
+
+        del self._pending_projects[pending.channel_id]
+        try:
+            project = self._project_creator.link_channel(project_id, pending.channel_name)
+            new_config = self._config_loader()
+        except Exception as e:
+            LOGGER.exception("Failed to connect channel %s to %s", pending.channel_name, project_id)
+            await send_message(
+                pending.channel_id,
+                pending.thread_ts,
+                f"Sorry, I couldn't connect this channel: {e}",
+            )
+            return True, None
+
+        await send_message(
+            pending.channel_id,
+            pending.thread_ts,
+            f"Okay great, this channel now works on `{project.id}` (default agent `{project.default_agent_id}`). "
+            "What do you want me to do?",
+        )
+        return True, new_config
+
     async def _handle_agent_response(
         self,
         pending: PendingProjectCreation,
@@ -190,6 +293,33 @@ class ProjectCreationHandler:
         )
         return True, None
 
+    def _pick(self, response: str, options: List[str]) -> Optional[str]:
+        """Return the option a numbered reply refers to, if any."""
+        try:
+            choice = int(response)
+        except ValueError:
+            return None
+        if 1 <= choice <= len(options):
+            return options[choice - 1]
+        return None
+
+    async def _show_project_options(
+        self,
+        pending: PendingProjectCreation,
+        send_message: SendMessage,
+    ) -> None:
+        """Show configured projects the channel can be connected to."""
+        pending.project_options = list(self._config.projects.keys())
+        pending.state = STATE_AWAITING_PROJECT
+
+        lines = ["Which project should this channel work on?\n"]
+        for i, project_id in enumerate(pending.project_options, 1):
+            project = self._config.projects[project_id]
+            lines.append(f"{i}. {project_id} (`{project.path}`)")
+        lines.append("\nReply with the number.")
+
+        await send_message(pending.channel_id, pending.thread_ts, "\n".join(lines))
+
     async def _show_agent_options(
         self,
         pending: PendingProjectCreation,
@@ -262,9 +392,12 @@ class ProjectCreationHandler:
                 default_model=model,
             )
 
-            await self._project_creator.create_project(request)
+            if pending.repo_path:
+                await self._project_creator.register_existing_repo(request, pending.repo_path)
+            else:
+                await self._project_creator.create_project(request)
 
-            new_config = load_config(self._config_root)
+            new_config = self._config_loader()
 
             await send_message(
                 pending.channel_id,
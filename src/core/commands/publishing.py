@@ -0,0 +1,60 @@
+"""Handlers for pausing and resuming PR publishing for a session."""
+
+from __future__ import annotations
+
+import logging
+import subprocess
+from typing import Awaitable, Callable
+
+from ..errors import GitHubError
+from ..models import Project, Session
+from .base import BaseCommandHandler
+from .context import CommandContext
+from .parser import ParsedCommand
+
+LOGGER = logging.getLogger(__name__)
+
+UnpublishFn = Callable[[Session, Project], Awaitable[str]]
+RepublishFn = Callable[[Session, Project, str], Awaitable[str]]
+PRTitleFn = Callable[[Session], str]
+
+
+class PublishingCommandHandler(BaseCommandHandler):
+    """Implements unpublish and publish commands."""
+
+    def __init__(
+        self,
+        *,
+        unpublish: UnpublishFn,
+        republish: RepublishFn,
+        get_pr_title: PRTitleFn,
+        send_message,
+    ) -> None:
+        super().__init__(send_message)
+        self._unpublish = unpublish
+        self._republish = republish
+        self._get_pr_title = get_pr_title
+
+    async def handle_unpublish(self, command: ParsedCommand, context: CommandContext) -> None:
+        LOGGER.info("Executing !unpublish command in channel %s, thread %s", context.channel, context.thread_ts)
+        try:
+            message = await self._unpublish(context.session, context.project)
+        except GitHubError as exc:
+            await self._reply(context, f"Failed to unpublish: {exc}")
+            return
+        await self._reply(context, message)
+
+    async def handle_publish(self, command: ParsedCommand, context: CommandContext) -> None:
+        LOGGER.info("Executing !publish command in channel %s, thread %s", context.channel, context.thread_ts)
+        try:
+            message = await self._republish(
+                context.session, context.project, self._get_pr_title(context.session)
+            )
+        except GitHubError as exc:
+            await self._reply(context, f"GitHub integration failed: {exc}")
+            return
+        except subprocess.CalledProcessError as exc:
+            detail = (exc.stderr or exc.stdout or str(exc)).strip()
+            await self._reply(context, f"Git command failed while publishing: {detail or 'git error'}")
+            return
+        await self._reply(context, message)
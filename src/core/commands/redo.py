@@ -0,0 +1,95 @@
+"""Handler for re-running the last prompt of a session, optionally edited."""
+
+from __future__ import annotations
+
+import logging
+import re
+from typing import Awaitable, Callable, Dict, Optional
+
+from ..agent_runner import LAST_RUN_CONTEXT_KEY
+from ..conversation import SessionManager
+from ..models import Project, Session
+from .base import BaseCommandHandler
+from .context import CommandContext
+from .parser import ParsedCommand
+
+LOGGER = logging.getLogger(__name__)
+
+ResetFn = Callable[[Session, Project, str], Awaitable[tuple[bool, str]]]
+ExecuteAgentFn = Callable[[Session, Project, str, str, str], Awaitable[None]]
+
+SAME_PROMPT_FLAG = "--same"
+# Slack escapes ">" in message text, so a pasted quote arrives as "&gt; "
+_QUOTE_PREFIX = re.compile(r"^(?:>|&gt;) ?")
+
+
+def unquote_prompt(text: str) -> str:
+    """Strip chat quote markers from a prompt copied out of the `!redo` reply."""
+    lines = text.strip().splitlines()
+    if lines and all(_QUOTE_PREFIX.match(line) for line in lines if line.strip()):
+        lines = [_QUOTE_PREFIX.sub("", line, count=1) for line in lines]
+    return "\n".join(lines).strip()
+
+
+def _quote(text: str) -> str:
+    return "\n".join(f"> {line}" for line in text.splitlines())
+
+
+class RedoCommandHandler(BaseCommandHandler):
+    """Implements the redo command."""
+
+    def __init__(
+        self,
+        *,
+        session_manager: SessionManager,
+        reset_to_checkpoint: ResetFn,
+        execute_agent_task: ExecuteAgentFn,
+        active_runs: Dict[str, Dict[str, object]],
+        send_message,
+    ) -> None:
+        super().__init__(send_message)
+        self._session_manager = session_manager
+        self._reset_to_checkpoint = reset_to_checkpoint
+        self._execute_agent_task = execute_agent_task
+        self._active_runs = active_runs
+
+    async def handle_redo(self, command: ParsedCommand, context: CommandContext) -> None:
+        LOGGER.info("Executing !redo command in channel %s, thread %s", context.channel, context.thread_ts)
+        session = context.session
+        last_run = session.session_context.get(LAST_RUN_CONTEXT_KEY)
+        if not isinstance(last_run, dict) or not last_run.get("prompt"):
+            await self._reply(context, "Nothing to redo yet; `!redo` re-runs the last prompt sent in this thread.")
+            return
+
+        previous_prompt = str(last_run["prompt"])
+        if not command.raw_args:
+            await self._reply(
+                context,
+                f"Last prompt:\n{_quote(previous_prompt)}\n\n"
+                "Reply with `!redo` followed by the edited prompt (pasting the quote above is fine), "
+                f"or `!redo {SAME_PROMPT_FLAG}` to run it unchanged. Either way the session branch is "
+                "reset to before that run first.",
+            )
+            return
+
+        session_id = str(session.id)
+        if any(run.get("session_id") == session_id for run in self._active_runs.values()):
+            await self._reply(context, "An agent is still running in this session; wait for it to finish first.")
+            return
+
+        prompt = previous_prompt if command.raw_args == SAME_PROMPT_FLAG else unquote_prompt(command.raw_args)
+        if not prompt:
+            await self._reply(context, "The edited prompt is empty.")
+            return
+
+        checkpoint: Optional[str] = last_run.get("checkpoint")
+        if checkpoint:
+            reset, message = await self._reset_to_checkpoint(session, context.project, checkpoint)
+            await self._reply(context, message)
+            if not reset:
+                return
+        else:
+            await self._reply(context, "No checkpoint was recorded for the last run; re-running without a reset.")
+
+        self._session_manager.rewind_last_run(session.id, previous_prompt)
+        await self._execute_agent_task(session, context.project, context.channel, context.thread_ts, prompt)
@@ -5,6 +5,8 @@ from __future__ import annotations
 from dataclasses import dataclass
 from typing import Dict, Iterable, Optional, Sequence, Tuple
 
+from ..models import CommandArg
+
 
 @dataclass(frozen=True)
 class CommandSpec:
@@ -15,6 +17,8 @@ class CommandSpec:
     usage: str
     description: str
     aliases: Tuple[str, ...] = ()
+    # Described in chat-client autocompletion (see `hints`); `text` arguments take the rest of the message
+    args: Tuple[CommandArg, ...] = ()
 
     @property
     def all_names(self) -> Tuple[str, ...]:
@@ -34,13 +38,24 @@ def _build_specs() -> Tuple[CommandSpec, ...]:
             name="use",
             handler_id="session.use",
             usage="!use <agent> [model]",
-            description="Switch to a different agent and optionally specify model.",
+            description="Switch to a different agent and optionally specify model (`!use auto` resumes automatic selection).",
+            args=(
+                CommandArg("agent", "string", True, "Agent id, or `auto` to resume automatic selection"),
+                CommandArg("model", "string", False, "Model to switch to"),
+            ),
         ),
         CommandSpec(
             name="status",
             handler_id="session.status",
-            usage="!status",
-            description="Show session metadata and stored message count.",
+            usage="!status [--system]",
+            description="Show session metadata and stored message count; `--system` shows daemon and chat connection health.",
+            args=(CommandArg("scope", "string", False, "`--system` for daemon and connection health"),),
+        ),
+        CommandSpec(
+            name="sessions",
+            handler_id="session.list",
+            usage="!sessions [tag ...]",
+            description="List active sessions across projects, or those with every tag given: agent, state, and link.",
         ),
         CommandSpec(
             name="end",
@@ -48,12 +63,66 @@ def _build_specs() -> Tuple[CommandSpec, ...]:
             usage="!end",
             description="End the current session (start a new Slack thread to reset).",
         ),
+        CommandSpec(
+            name="pause",
+            handler_id="session.pause",
+            usage="!pause",
+            description="Park the session: messages are not run and its checkout is left as it is until `!resume`.",
+        ),
+        CommandSpec(
+            name="resume",
+            handler_id="session.resume",
+            usage="!resume",
+            description="Carry on with a paused session, with its full history.",
+        ),
+        CommandSpec(
+            name="move",
+            handler_id="move.session",
+            usage="!move [#channel]",
+            description="Move the session, history and all, to a new thread here or in another project channel.",
+        ),
+        CommandSpec(
+            name="tag",
+            handler_id="tags.add",
+            usage="!tag [tag ...]",
+            description="Tag the session (`!tag bugfix urgent`) and label its PRs with the tags; alone, list them.",
+        ),
+        CommandSpec(
+            name="untag",
+            handler_id="tags.remove",
+            usage="!untag <tag> [tag ...]",
+            description="Remove tags from the session.",
+        ),
+        CommandSpec(
+            name="takeover",
+            handler_id="session.takeover",
+            usage="!takeover",
+            description="End the project's other sessions so this thread has its checkout to itself.",
+        ),
+        CommandSpec(
+            name="cost",
+            handler_id="session.cost",
+            usage="!cost",
+            description="Show the estimated spend for this session and the active agent's pricing.",
+        ),
         CommandSpec(
             name="review",
             handler_id="review.pending",
             usage="!review",
             description="List unresolved GitHub review comments for the session's PR.",
         ),
+        CommandSpec(
+            name="unpublish",
+            handler_id="publish.unpublish",
+            usage="!unpublish",
+            description="Close the session's PR and delete the remote branch; keep iterating locally.",
+        ),
+        CommandSpec(
+            name="publish",
+            handler_id="publish.publish",
+            usage="!publish",
+            description="Resume publishing: push the session branch and open a PR again.",
+        ),
         CommandSpec(
             name="purge",
             handler_id="maintenance.purge",
@@ -84,6 +153,171 @@ def _build_specs() -> Tuple[CommandSpec, ...]:
             usage="!stash",
             description="Stash local changes to allow the session to start.",
         ),
+        CommandSpec(
+            name="patch",
+            handler_id="patch.export",
+            usage="!patch",
+            description="Download this session's commits as a .patch file.",
+        ),
+        CommandSpec(
+            name="export",
+            handler_id="export.session",
+            usage="!export",
+            description="Upload the session's transcript, PRs, checkpoints, and diff as Markdown and JSON.",
+        ),
+        CommandSpec(
+            name="archived",
+            handler_id="export.archived",
+            usage="!archived <session id>",
+            description="Fetch a session uploaded to the session archive, as Markdown and JSON.",
+            args=(CommandArg("session_id", "string", True, "Full id of the archived session"),),
+        ),
+        CommandSpec(
+            name="search",
+            handler_id="search.sessions",
+            usage="!search [--all] <words>",
+            description="Search this project's session prompts, replies, and diffs (`--all`: every project).",
+            args=(CommandArg("query", "text", True, "Words to look for"),),
+        ),
+        CommandSpec(
+            name="analytics",
+            handler_id="analytics.report",
+            usage="!analytics [days|all]",
+            description="Success rates per agent and project: runs, checks, merged PRs, turns, cost (last 30 days).",
+            args=(CommandArg("days", "string", False, "Days to report on, or `all`"),),
+        ),
+        CommandSpec(
+            name="apply",
+            handler_id="patch.apply",
+            usage="!apply [--skip-checks] [diff]",
+            description="Apply an attached .patch/.diff (or pasted diff) to the session branch and run verify steps.",
+            args=(CommandArg("diff", "text", False, "Diff to apply when no file is attached"),),
+        ),
+        CommandSpec(
+            name="group",
+            handler_id="group.manage",
+            usage="!group [<name> [pause|resume|status]]",
+            description="List project groups, or pause/resume agent runs for every project in a group.",
+            args=(
+                CommandArg("name", "string", False, "Group name"),
+                CommandArg("action", "string", False, "pause, resume, or status"),
+            ),
+        ),
+        CommandSpec(
+            name="cancel",
+            handler_id="run.cancel",
+            usage="!cancel",
+            description="Stop the agent running in this thread; its CLI is killed and the session stays open.",
+            aliases=("stop",),
+        ),
+        CommandSpec(
+            name="implement",
+            handler_id="discussion.implement",
+            usage="!implement [notes]",
+            description="End the design phase of a GitHub Discussion session: implement the design and open a PR.",
+            args=(CommandArg("notes", "text", False, "Anything to add to the agreed design"),),
+        ),
+        CommandSpec(
+            name="redo",
+            handler_id="redo.run",
+            usage="!redo [--same | <edited prompt>]",
+            description="Reset the session branch to before the last run and run its prompt again, edited or unchanged.",
+            args=(CommandArg("prompt", "text", False, "Edited prompt, or `--same` to run it unchanged"),),
+        ),
+        CommandSpec(
+            name="timeline",
+            handler_id="timeline.list",
+            usage="!timeline",
+            description="List the session's checkpoints: one per run, with its prompt and the files it changed.",
+        ),
+        CommandSpec(
+            name="checkout",
+            handler_id="timeline.checkout",
+            usage="!checkout <n> | latest",
+            description="Move the worktree to a checkpoint from `!timeline` to look around, or back to the latest.",
+            args=(CommandArg("checkpoint", "string", True, "Checkpoint number, or `latest`"),),
+        ),
+        CommandSpec(
+            name="rollback",
+            handler_id="rollback.run",
+            usage="!rollback",
+            description="Restore a project that is not a git repository to how it was before the last run.",
+        ),
+        CommandSpec(
+            name="compare",
+            handler_id="compare.run",
+            usage="!compare <agentA> <agentB> <prompt>",
+            description="Run two agents on the same prompt in separate worktrees and post both diffs with stats.",
+            args=(
+                CommandArg("agent_a", "string", True, "First agent id"),
+                CommandArg("agent_b", "string", True, "Second agent id"),
+                CommandArg("prompt", "text", True, "Task for both agents"),
+            ),
+        ),
+        CommandSpec(
+            name="batch",
+            handler_id="batch.run",
+            usage="!batch [--parallel <n>] [--pr none|separate|combined] <task per line>",
+            description="Run small independent tasks, one per line, as separate runs in worktrees; post each result.",
+            args=(
+                CommandArg("parallel", "string", False, "`--parallel <n>` tasks at once (default 1, at most 4)"),
+                CommandArg("pr", "string", False, "`--pr separate` for a PR per task, `--pr combined` for one"),
+                CommandArg("tasks", "text", True, "One task per line"),
+            ),
+        ),
+        CommandSpec(
+            name="bench",
+            handler_id="bench.run",
+            usage="!bench [agent ...] [--cases <name>,<name>]",
+            description="Run the project's `.cockpit/bench` prompts against agents and compare pass rate, time, and cost.",
+            args=(
+                CommandArg("agents", "string", False, "Agents to compare (default: all the project allows)"),
+                CommandArg("cases", "string", False, "`--cases a,b` to run only some cases"),
+            ),
+        ),
+        CommandSpec(
+            name="replay",
+            handler_id="replay.run",
+            usage="!replay <session> --agent <agent>",
+            description="Re-run a past session's requests with another agent in a worktree and compare the results.",
+            args=(
+                CommandArg("session", "string", True, "Session ID or its first characters (see `!status`)"),
+                CommandArg("agent", "string", True, "`--agent <agent>` to replay the requests with"),
+            ),
+        ),
+        CommandSpec(
+            name="test",
+            handler_id="verify.test",
+            usage="!test",
+            description="Run the project's setup, verify, and teardown steps in its sandbox.",
+        ),
+        CommandSpec(
+            name="auth",
+            handler_id="auth.check",
+            usage="!auth [agent]",
+            description="Check agent credentials, or show how to set one up (a device-code login where supported).",
+            args=(CommandArg("agent", "string", False, "Agent to check or log in"),),
+        ),
+        CommandSpec(
+            name="prefs",
+            handler_id="prefs.manage",
+            usage="!prefs [<name> <value> | clear [name]]",
+            description="Show or set your defaults for new sessions: agent, verbosity, notify, and locale.",
+            args=(
+                CommandArg("name", "string", False, "`agent`, `verbosity`, `notify`, `locale`, or `clear`"),
+                CommandArg("value", "string", False, "New value; `clear` takes a preference name"),
+            ),
+        ),
+        CommandSpec(
+            name="incident",
+            handler_id="incident.manage",
+            usage="!incident [on [minutes] | off]",
+            description="Switch the project to its safest settings during a production incident, or show or end it.",
+            args=(
+                CommandArg("action", "string", False, "`on` or `off`; without one, shows whether it is on"),
+                CommandArg("minutes", "string", False, "How long incident mode lasts (default 60)"),
+            ),
+        ),
         CommandSpec(
             name="help",
             handler_id="catalog.help",
@@ -0,0 +1,146 @@
+"""Handler for replaying a past session against a different agent."""
+
+from __future__ import annotations
+
+import asyncio
+import logging
+import time
+from typing import Dict, Optional
+
+from ..automation import AutomationGate
+from ..config import Config
+from ..conversation import SessionManager
+from ..errors import AgentNotFound
+from ..models import WorkingDirMode
+from ..replay import ReplayAgentFn, ReplayError, ReplayTurn, format_replay, format_replay_turn, run_replay
+from .base import BaseCommandHandler
+from .compare import PostDiffFn
+from .context import CommandContext
+from .parser import ParsedCommand
+
+LOGGER = logging.getLogger(__name__)
+
+REPLAY_USAGE = "Usage: `!replay <session> --agent <agent>`"
+
+
+class ReplayCommandHandler(BaseCommandHandler):
+    """Implements the replay command."""
+
+    def __init__(
+        self,
+        *,
+        config: Config,
+        session_manager: SessionManager,
+        run_agent: ReplayAgentFn,
+        post_diff: PostDiffFn,
+        active_runs: Dict[str, Dict[str, object]],
+        send_message,
+        gate: Optional[AutomationGate] = None,
+    ) -> None:
+        super().__init__(send_message)
+        self._config = config
+        self._session_manager = session_manager
+        self._run_agent = run_agent
+        self._post_diff = post_diff
+        self._active_runs = active_runs
+        self._gate = gate
+
+    def update_config(self, config: Config) -> None:
+        self._config = config
+
+    async def handle_replay(self, command: ParsedCommand, context: CommandContext) -> None:
+        LOGGER.info("Executing !replay command in channel %s, thread %s", context.channel, context.thread_ts)
+        session_ref: Optional[str] = None
+        agent_id: Optional[str] = None
+        args = iter(command.args)
+        for arg in args:
+            if arg == "--agent":
+                agent_id = next(args, None)
+            elif arg.startswith("--agent="):
+                agent_id = arg.partition("=")[2]
+            elif arg.startswith("--") or session_ref is not None:
+                await self._reply(context, REPLAY_USAGE)
+                return
+            else:
+                session_ref = arg
+        if not session_ref or not agent_id:
+            await self._reply(context, REPLAY_USAGE)
+            return
+
+        matches = self._session_manager.find_sessions(session_ref)
+        if not matches:
+            await self._reply(context, f"No session with ID `{session_ref}`. `!status` shows a thread's session ID.")
+            return
+        if len(matches) > 1:
+            await self._reply(context, f"`{session_ref}` matches {len(matches)} sessions; use more of the ID.")
+            return
+        target = matches[0]
+        if not target.interactions:
+            await self._reply(context, f"Session `{target.id}` has no stored requests to replay.")
+            return
+        project = context.project if target.project_id == context.project.id else None
+        project = project or self._config.projects.get(target.project_id)
+        if project is None:
+            await self._reply(
+                context, f"Session `{target.id}` belongs to project `{target.project_id}`, which no longer exists."
+            )
+            return
+
+        try:
+            agent = self._config.get_agent(agent_id)
+        except AgentNotFound:
+            await self._reply(context, f"Unknown agent `{agent_id}`. Use `!agents` to see what's available.")
+            return
+        if not project.allows_agent(agent_id):
+            await self._reply(context, f"Agent `{agent_id}` is not allowed in project `{project.id}`.")
+            return
+        if agent.working_dir_mode == WorkingDirMode.FIXED:
+            await self._reply(
+                context, f"`{agent_id}` works in a fixed directory, so it cannot run in a separate worktree."
+            )
+            return
+
+        session = context.session
+        blocked = self._gate.block_reason(project, session) if self._gate else None
+        if blocked:
+            await self._reply(context, blocked)
+            return
+        session_id = str(session.id)
+        if any(run.get("session_id") == session_id for run in self._active_runs.values()):
+            await self._reply(context, "An agent is still running in this session; wait for it to finish first.")
+            return
+
+        total = target.interactions[-1].interaction_number
+        model = project.model_for(agent)
+        await self._reply(
+            context,
+            f"Replaying {len(target.interactions)} request(s) of session `{target.id}` with `{agent.id}` "
+            "in a separate worktree. Nothing is committed.",
+        )
+
+        async def report_progress(turn: ReplayTurn) -> None:
+            if turn.cost_usd:
+                self._session_manager.record_run_cost(session.id, turn.cost_usd)
+            await self._reply(context, format_replay_turn(turn, total))
+
+        run_id = f"{context.channel}_{context.thread_ts}_{int(time.time() * 1000)}"
+        self._active_runs[run_id] = {
+            "task": asyncio.current_task(),
+            "session_id": session_id,
+            "agent_id": f"replay with {agent.id}",
+            "started_at": time.time(),
+        }
+        try:
+            result = await run_replay(target, agent, model, self._run_agent, progress=report_progress)
+        except ReplayError as exc:
+            await self._reply(context, f"Could not replay the session: {exc}")
+            return
+        finally:
+            self._active_runs.pop(run_id, None)
+
+        await self._reply(context, format_replay(result))
+        if result.diff_text.strip():
+            await self._reply(context, f"*{result.label}* changes:")
+            await self._post_diff(context.channel, context.thread_ts, result.diff_text)
+        else:
+            await self._reply(context, f"*{result.label}* made no changes.")
@@ -41,14 +41,18 @@ class ReviewCommandHandler(BaseCommandHandler):
     async def handle_review(self, command: ParsedCommand, context: CommandContext) -> None:
         LOGGER.info("Executing !review command in channel %s, thread %s", context.channel, context.thread_ts)
         if not context.project.github:
-            await self._reply(context, "This project has no GitHub configuration.")
+            await self._reply(
+                context,
+                "This project has no GitHub configuration, so there is no PR to review. "
+                "Changes are committed to the session's local branch; use `!patch` to download them.",
+            )
             return
         if not self._github_manager.is_configured():
             await self._reply(context, "GitHub token is not configured; cannot fetch PR comments.")
             return
 
         try:
-            pr_ref = self._session_manager.get_pr_ref(context.session.id)
+            pr_ref = self._session_manager.get_pr_ref(context.session.id, repo=context.project.github.full_name)
         except SessionNotFound:
             await self._reply(context, "No pull request exists yet for this session.")
             return
@@ -0,0 +1,53 @@
+"""Handler for rolling a non-git project back to before the last agent run."""
+
+from __future__ import annotations
+
+import asyncio
+import logging
+from typing import Dict
+
+from ..config import Config
+from ..snapshots import SnapshotError, SnapshotStore, format_restore_result
+from .base import BaseCommandHandler
+from .context import CommandContext
+from .parser import ParsedCommand
+
+LOGGER = logging.getLogger(__name__)
+
+
+class RollbackCommandHandler(BaseCommandHandler):
+    """Implements the rollback command."""
+
+    def __init__(self, *, config: Config, active_runs: Dict[str, Dict[str, object]], send_message) -> None:
+        super().__init__(send_message)
+        self._config = config
+        self._active_runs = active_runs
+
+    def update_config(self, config: Config) -> None:
+        self._config = config
+
+    async def handle_rollback(self, command: ParsedCommand, context: CommandContext) -> None:
+        LOGGER.info("Executing !rollback command in channel %s, thread %s", context.channel, context.thread_ts)
+        session = context.session
+        session_id = str(session.id)
+        if any(run.get("session_id") == session_id for run in self._active_runs.values()):
+            await self._reply(context, "An agent is still running in this session; wait for it to finish first.")
+            return
+
+        store = SnapshotStore.for_session(self._config.config_dir, session.id)
+        try:
+            if await asyncio.to_thread(store.load) is None:
+                await self._reply(
+                    context,
+                    "There is no snapshot to roll back to. Snapshots are taken before each run in projects "
+                    "that are not git repositories; in git projects, `!redo` resets the last run.",
+                )
+                return
+            result = await asyncio.to_thread(store.restore, session.project_path)
+        except SnapshotError as exc:
+            await self._reply(context, f"Could not roll back: {exc}")
+            return
+        LOGGER.info(
+            "Rolled back session %s: %d restored, %d removed", session.id, len(result.restored), len(result.removed)
+        )
+        await self._reply(context, format_restore_result(result))
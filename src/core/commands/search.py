@@ -0,0 +1,82 @@
+"""Handler for searching session transcripts and diffs (see `src/core/search.py`)."""
+
+from __future__ import annotations
+
+import asyncio
+import logging
+from datetime import datetime, timezone
+from typing import Callable, Optional
+
+from ...chat_adapters.health import format_duration
+from ..errors import SearchError
+from ..search import DEFAULT_SEARCH_LIMIT, SearchHit, SearchIndex
+from .base import BaseCommandHandler
+from .context import CommandContext
+from .parser import ParsedCommand
+
+LOGGER = logging.getLogger(__name__)
+
+# The search index, if transcripts are kept; a getter so a config reload is picked up
+SearchIndexFn = Callable[[], Optional[SearchIndex]]
+ThreadLinkFn = Callable[[str, str], Optional[str]]
+
+ALL_PROJECTS_FLAG = "--all"
+
+
+class SearchCommandHandler(BaseCommandHandler):
+    """Implements the search command."""
+
+    def __init__(
+        self,
+        *,
+        search_index: SearchIndexFn,
+        send_message,
+        thread_link: Optional[ThreadLinkFn] = None,
+    ) -> None:
+        super().__init__(send_message)
+        self._search_index = search_index
+        self._thread_link = thread_link
+
+    async def handle_search(self, command: ParsedCommand, context: CommandContext) -> None:
+        LOGGER.info("Executing !search command in channel %s, thread %s", context.channel, context.thread_ts)
+        index = self._search_index()
+        if index is None:
+            await self._reply(context, "Search needs transcripts, which are off (`REMOTE_CODER_DISABLE_TRANSCRIPTS`).")
+            return
+        query = command.raw_args
+        # Other projects' threads can be private channels, so searching them is asked for explicitly
+        every_project = query.split(maxsplit=1)[:1] == [ALL_PROJECTS_FLAG]
+        if every_project:
+            query = query[len(ALL_PROJECTS_FLAG) :].strip()
+        if not query:
+            await self._reply(
+                context,
+                f'Usage: `!search [{ALL_PROJECTS_FLAG}] <words>`, e.g. `!search flaky test` or `!search "rate limit"`',
+            )
+            return
+        project_id = None if every_project else context.project.id
+        try:
+            hits = await asyncio.to_thread(index.search, query, DEFAULT_SEARCH_LIMIT, project_id)
+        except SearchError as exc:
+            await self._reply(context, f"Search failed: {exc}")
+            return
+        where = "" if every_project else f" in `{context.project.id}`"
+        if not hits:
+            hint = "" if every_project else f" `!search {ALL_PROJECTS_FLAG} {query}` looks in every project."
+            await self._reply(context, f"No sessions{where} mention {query}.{hint}")
+            return
+        lines = [f"Sessions{where} matching {query} (best first):"]
+        lines += [self._describe_hit(hit, context) for hit in hits]
+        await self._reply(context, "\n".join(lines))
+
+    def _describe_hit(self, hit: SearchHit, context: CommandContext) -> str:
+        """E.g. "- `api` · "Fix the login redirect" · 2h 5m ago · PR #12 · <thread link>" and the matching text."""
+        age = format_duration((datetime.now(timezone.utc) - hit.updated_at).total_seconds())
+        if hit.channel_id == context.channel and hit.thread_ts == context.thread_ts:
+            where = "this thread"
+        else:
+            link = self._thread_link(hit.channel_id, hit.thread_ts) if self._thread_link else None
+            where = link or f"thread `{hit.thread_ts}`"
+        prs = "".join(f" · PR #{number} {url}" for number, url in hit.prs)
+        title = f' · "{hit.title}"' if hit.title else ""
+        return f"- `{hit.project_id}`{title} · {age} ago{prs} · {where}\n  > {hit.snippet}"
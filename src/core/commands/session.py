@@ -3,10 +3,19 @@
 from __future__ import annotations
 
 import logging
+import time
+from datetime import datetime, timezone
+from typing import Awaitable, Callable, Dict, Optional, Sequence
 
+from ...chat_adapters.health import format_duration
+from ..automation import AutomationGate
 from ..config import Config
-from ..errors import AgentNotFound
-from ..models import SessionStatus
+from ..errors import AgentNotAllowed, AgentNotFound
+from ..git_workflow import APPROVAL_PENDING_CONTEXT_KEY
+from ..models import AgentSelection, Session, SessionStatus
+from ..pricing import format_cost
+from ..tags import format_tags, has_tags, parse_tags
+from ..transcripts import TranscriptKind
 from ..conversation import SessionManager
 from .parser import ParsedCommand
 from .base import BaseCommandHandler
@@ -14,6 +23,17 @@ from .context import CommandContext
 
 LOGGER = logging.getLogger(__name__)
 
+SystemStatusFn = Callable[[], str]
+# The daemon's run queue as seen from a thread (`<channel>:<thread_ts>`)
+QueueStatusFn = Callable[[str], str]
+# (channel, thread_ts) -> a web link to the thread, where the chat adapter has one
+ThreadLinkFn = Callable[[str, str], Optional[str]]
+# (sessions to end, channel, thread_ts) -> whether they were ended (see `src/core/takeover.py`)
+TakeOverFn = Callable[[Sequence[Session], str, str], Awaitable[bool]]
+
+# `!sessions` lists at most this many, most recently active first
+MAX_LISTED_SESSIONS = 20
+
 
 class SessionCommandHandler(BaseCommandHandler):
     """Implements commands that manipulate session state."""
@@ -23,10 +43,22 @@ class SessionCommandHandler(BaseCommandHandler):
         session_manager: SessionManager,
         config: Config,
         send_message,
+        get_system_status: Optional[SystemStatusFn] = None,
+        gate: Optional[AutomationGate] = None,
+        get_queue_status: Optional[QueueStatusFn] = None,
+        active_runs: Optional[Dict[str, Dict[str, object]]] = None,
+        thread_link: Optional[ThreadLinkFn] = None,
+        take_over: Optional[TakeOverFn] = None,
     ) -> None:
         super().__init__(send_message)
         self._session_manager = session_manager
         self._config = config
+        self._get_system_status = get_system_status
+        self._gate = gate
+        self._get_queue_status = get_queue_status
+        self._active_runs = active_runs if active_runs is not None else {}
+        self._thread_link = thread_link
+        self._take_over = take_over
 
     def update_config(self, config: Config) -> None:
         self._config = config
@@ -38,6 +70,10 @@ class SessionCommandHandler(BaseCommandHandler):
             return
 
         agent_id = command.args[0].lower()
+        if agent_id == "auto":
+            await self._unpin_agent(context)
+            return
+
         model = command.args[1].lower() if len(command.args) > 1 else None
 
         try:
@@ -56,15 +92,37 @@ class SessionCommandHandler(BaseCommandHandler):
                 )
                 return
 
-        if not model and agent.models:
-            model = agent.models.get("default")
+        if not model:
+            model = context.project.model_for(agent)
 
-        self._session_manager.set_active_agent(context.session.id, agent_id, agent.type, model)
+        try:
+            self._session_manager.set_active_agent(
+                context.session.id, agent_id, agent.type, model, project=context.project
+            )
+        except AgentNotAllowed:
+            allowed = ", ".join(f"`{allowed_id}`" for allowed_id in context.project.allowed_agents)
+            await self._reply(
+                context,
+                f"Agent `{agent_id}` is not allowed in project `{context.project.id}`. Allowed: {allowed}",
+            )
+            return
+        self._session_manager.set_agent_pinned(context.session.id, True)
         LOGGER.info("Switched session %s to agent %s model %s", context.session.id, agent_id, model)
 
         model_display = f" `{model}`" if model else ""
         await self._reply(context, f"Switched to `{agent_id}`{model_display}")
 
+    async def _unpin_agent(self, context: CommandContext) -> None:
+        if context.project.agent_selection != AgentSelection.AUTO:
+            await self._reply(
+                context,
+                f"Project `{context.project.id}` does not use automatic agent selection "
+                "(set `agent_selection: auto` in projects.yaml).",
+            )
+            return
+        self._session_manager.set_agent_pinned(context.session.id, False)
+        await self._reply(context, "Automatic agent selection re-enabled; an agent is picked for each request.")
+
     async def handle_end(self, command: ParsedCommand, context: CommandContext) -> None:
         LOGGER.info("Executing !end command in channel %s, thread %s", context.channel, context.thread_ts)
         if context.session.status == SessionStatus.ENDED:
@@ -74,14 +132,154 @@ class SessionCommandHandler(BaseCommandHandler):
         LOGGER.info("Ended session %s", context.session.id)
         await self._reply(context, "Session ended. Start a new thread to begin again.")
 
+    async def handle_pause(self, command: ParsedCommand, context: CommandContext) -> None:
+        LOGGER.info("Executing !pause command in channel %s, thread %s", context.channel, context.thread_ts)
+        status = context.session.status
+        if status == SessionStatus.PAUSED:
+            await self._reply(context, "Session already paused; `!resume` carries on.")
+            return
+        if not status.is_open:
+            await self._reply(context, f"Only active sessions can be paused; this one is {status.label}.")
+            return
+        self._session_manager.update_status(context.session.id, SessionStatus.PAUSED)
+        LOGGER.info("Paused session %s", context.session.id)
+        lines = ["Session paused. Messages here are not run, and its checkout and branch are left as they are."]
+        if any(run.get("session_id") == str(context.session.id) for run in self._active_runs.values()):
+            lines.append("The run in progress will finish; `!cancel` stops it.")
+        lines.append("Send `!resume` to carry on where you left off.")
+        await self._reply(context, " ".join(lines))
+
+    async def handle_resume(self, command: ParsedCommand, context: CommandContext) -> None:
+        LOGGER.info("Executing !resume command in channel %s, thread %s", context.channel, context.thread_ts)
+        if context.session.status != SessionStatus.PAUSED:
+            await self._reply(context, "Session is not paused.")
+            return
+        self._session_manager.update_status(context.session.id, SessionStatus.ACTIVE)
+        self._session_manager.record_activity(context.session.id)
+        LOGGER.info("Resumed session %s", context.session.id)
+        history = self._session_manager.get_conversation_history(context.session.id)
+        await self._reply(
+            context,
+            f"Session resumed with `{context.session.active_agent_id}` and its {len(history)} stored message(s). "
+            "Send your next request.",
+        )
+
+    async def handle_takeover(self, command: ParsedCommand, context: CommandContext) -> None:
+        LOGGER.info("Executing !takeover command in channel %s, thread %s", context.channel, context.thread_ts)
+        others = [
+            session
+            for session in self._session_manager.checkout_holders(context.project.id)
+            if session.id != context.session.id
+        ]
+        if not others:
+            await self._reply(context, f"No other session is using `{context.project.id}`'s checkout.")
+            return
+        if not self._take_over:
+            await self._reply(context, "Taking over is not available.")
+            return
+        await self._take_over(others, context.channel, context.thread_ts)
+
     async def handle_status(self, command: ParsedCommand, context: CommandContext) -> None:
         LOGGER.debug("Executing !status command in channel %s, thread %s", context.channel, context.thread_ts)
+        if "--system" in command.args:
+            if not self._get_system_status:
+                await self._reply(context, "System status is not available.")
+                return
+            await self._reply(context, self._get_system_status())
+            return
+
         history = self._session_manager.get_conversation_history(context.session.id)
         status_lines = [
             f"Session ID: `{context.session.id}`",
             f"Project: `{context.session.project_id}`",
             f"Active agent: `{context.session.active_agent_id}` ({context.session.active_agent_type.value})",
             f"Messages stored: {len(history)}",
-            f"Status: {context.session.status.value}",
+            f"Status: {context.session.status.label}",
+            f"Tags: {format_tags(context.session.tags) or 'none'}",
+            f"Timezone: {context.session.timezone or 'server local time'}",
         ]
+        if context.project.agent_selection == AgentSelection.AUTO:
+            mode = "pinned by `!use`" if context.session.agent_pinned else "automatic"
+            status_lines.append(f"Agent selection: {mode}")
+        if context.session.session_context.get(APPROVAL_PENDING_CONTEXT_KEY):
+            status_lines.append("Publishing: waiting for approval (run `!publish` to push and open a PR)")
+        elif context.session.publishing_paused:
+            status_lines.append("Publishing: paused (run `!publish` to push and open a PR)")
+        quota = self._gate.quota_status(context.project) if self._gate else None
+        if quota:
+            status_lines.append(f"Run quota: {quota}")
+        if self._get_queue_status:
+            status_lines.append(f"Run queue: {self._get_queue_status(f'{context.channel}:{context.thread_ts}')}")
         await self._reply(context, "\n".join(status_lines))
+
+    async def handle_sessions(self, command: ParsedCommand, context: CommandContext) -> None:
+        LOGGER.debug("Executing !sessions command in channel %s, thread %s", context.channel, context.thread_ts)
+        tags, invalid = parse_tags(command.args)
+        if invalid:
+            await self._reply(context, f"Not a tag: {', '.join(invalid)}. Usage: `!sessions [tag ...]`")
+            return
+        sessions = sorted(self._session_manager.list_active(), key=lambda s: s.last_activity_at, reverse=True)
+        sessions = [session for session in sessions if has_tags(session, tags)]
+        tagged = f" tagged {format_tags(tags)}" if tags else ""
+        if not sessions:
+            await self._reply(context, f"No active sessions{tagged}.")
+            return
+        lines = [f"Active sessions{tagged} ({len(sessions)}):"]
+        lines += [f"- {self._describe_session(session, context)}" for session in sessions[:MAX_LISTED_SESSIONS]]
+        if len(sessions) > MAX_LISTED_SESSIONS:
+            lines.append(f"...and {len(sessions) - MAX_LISTED_SESSIONS} more")
+        await self._reply(context, "\n".join(lines))
+
+    def _describe_session(self, session: Session, context: CommandContext) -> str:
+        """E.g. "`api` · `claude` (`sonnet`) · running for 2m 5s · opened 3h 10m ago · `bugfix` · <thread link>"."""
+        model = f" (`{session.active_model}`)" if session.active_model else ""
+        now = datetime.now(timezone.utc)
+        started = [
+            float(run["started_at"])  # type: ignore[arg-type]
+            for run in self._active_runs.values()
+            if run.get("session_id") == str(session.id) and run.get("started_at") is not None
+        ]
+        if session.status in (SessionStatus.QUEUED, SessionStatus.AWAITING_APPROVAL, SessionStatus.AWAITING_INPUT):
+            state = session.status.label
+        elif started:
+            state = f"running for {format_duration(time.time() - min(started))}"
+        else:
+            runs = self._session_manager.transcript(session.id, [TranscriptKind.RUN])
+            last = runs[-1] if runs else None
+            if last is None or last.run is None:
+                state = "no runs yet"
+            else:
+                outcome = "cancelled" if last.run.cancelled else "succeeded" if last.run.success else "failed"
+                state = f"last run {outcome} {format_duration((now - last.timestamp).total_seconds())} ago"
+        opened = f"opened {format_duration((now - session.created_at).total_seconds())} ago"
+        if session.channel_id == context.channel and session.thread_ts == context.thread_ts:
+            where = "this thread"
+        else:
+            link = self._thread_link(session.channel_id, session.thread_ts) if self._thread_link else None
+            where = link or f"thread `{session.thread_ts}`"
+        tags = f" · {format_tags(session.tags)}" if session.tags else ""
+        return f"`{session.project_id}` · `{session.active_agent_id}`{model} · {state} · {opened}{tags} · {where}"
+
+    async def handle_cost(self, command: ParsedCommand, context: CommandContext) -> None:
+        LOGGER.debug("Executing !cost command in channel %s, thread %s", context.channel, context.thread_ts)
+        session = self._session_manager.get_session(context.session.id)
+        lines = [f"Estimated session cost: {format_cost(session.estimated_cost_usd)}"]
+
+        try:
+            agent = self._config.get_agent(session.active_agent_id)
+        except AgentNotFound:
+            await self._reply(context, "\n".join(lines))
+            return
+
+        model_display = f" `{session.active_model}`" if session.active_model else ""
+        if not agent.pricing:
+            lines.append(f"No pricing configured for `{agent.id}` in agents.yaml.")
+        else:
+            rates = agent.pricing.for_model(session.active_model)
+            lines.append(
+                f"Pricing for `{agent.id}`{model_display}: "
+                f"{format_cost(rates.input_per_1k_tokens_usd)} / 1K input tokens, "
+                f"{format_cost(rates.output_per_1k_tokens_usd)} / 1K output tokens, "
+                f"{format_cost(rates.per_run_usd)} per run"
+            )
+        await self._reply(context, "\n".join(lines))
@@ -0,0 +1,80 @@
+"""Handlers for tagging sessions (see `src/core/tags.py`)."""
+
+from __future__ import annotations
+
+import logging
+from typing import Awaitable, Callable, List, Optional
+
+from ..conversation import SessionManager
+from ..models import Project, Session
+from ..tags import MAX_TAGS, format_tags, parse_tags
+from .base import BaseCommandHandler
+from .context import CommandContext
+from .parser import ParsedCommand
+
+LOGGER = logging.getLogger(__name__)
+
+TAG_RULES = "Tags are lowercase letters, digits, `.`, `_`, and `-`, up to 32 characters."
+
+# (session, project, labels) -> a line per PR labelled, or not, for the thread
+LabelPullRequestsFn = Callable[[Session, Project, List[str]], Awaitable[List[str]]]
+
+
+class TagCommandHandler(BaseCommandHandler):
+    """Implements the tag and untag commands."""
+
+    def __init__(
+        self,
+        *,
+        session_manager: SessionManager,
+        send_message,
+        label_pull_requests: Optional[LabelPullRequestsFn] = None,
+    ) -> None:
+        super().__init__(send_message)
+        self._session_manager = session_manager
+        self._label_pull_requests = label_pull_requests
+
+    async def handle_tag(self, command: ParsedCommand, context: CommandContext) -> None:
+        LOGGER.info("Executing !tag command in channel %s, thread %s", context.channel, context.thread_ts)
+        session = context.session
+        if not command.args:
+            if session.tags:
+                await self._reply(context, f"Tags: {format_tags(session.tags)}")
+            else:
+                await self._reply(context, "No tags yet. Add some with `!tag bugfix urgent`.")
+            return
+        tags, invalid = parse_tags(command.args)
+        if invalid:
+            await self._reply(context, f"Not a tag: {', '.join(invalid)}. {TAG_RULES}")
+            return
+        added = [tag for tag in tags if tag not in session.tags]
+        if not added:
+            await self._reply(context, f"Already tagged: {format_tags(tags)}")
+            return
+        if len(session.tags) + len(added) > MAX_TAGS:
+            await self._reply(context, f"A session can have up to {MAX_TAGS} tags; `!untag` some first.")
+            return
+        self._session_manager.set_tags(session.id, [*session.tags, *added])
+        LOGGER.info("Tagged session %s with %s", session.id, ", ".join(added))
+        lines = [f"Tagged: {format_tags(session.tags)}"]
+        if self._label_pull_requests:
+            lines += await self._label_pull_requests(session, context.project, added)
+        await self._reply(context, "\n".join(lines))
+
+    async def handle_untag(self, command: ParsedCommand, context: CommandContext) -> None:
+        LOGGER.info("Executing !untag command in channel %s, thread %s", context.channel, context.thread_ts)
+        session = context.session
+        tags, _ = parse_tags(command.args)
+        if not tags:
+            await self._reply(context, "Usage: `!untag <tag> [tag ...]`")
+            return
+        missing = [tag for tag in tags if tag not in session.tags]
+        if len(missing) == len(tags):
+            await self._reply(context, f"Not tagged: {format_tags(missing)}")
+            return
+        self._session_manager.set_tags(session.id, [tag for tag in session.tags if tag not in tags])
+        LOGGER.info("Removed tags %s from session %s", ", ".join(tags), session.id)
+        reply = f"Tags: {format_tags(session.tags)}" if session.tags else "No tags left."
+        if self._session_manager.list_pr_refs(session.id):
+            reply += " Labels already on the session's PRs stay."
+        await self._reply(context, reply)
@@ -0,0 +1,89 @@
+"""Handlers for browsing a session's checkpoints without changing its branch."""
+
+from __future__ import annotations
+
+import logging
+from typing import Dict
+
+from ..conversation import SessionManager
+from ..timeline import (
+    CHECKED_OUT_CONTEXT_KEY,
+    TimelineError,
+    checked_out_run,
+    checkout_checkpoint,
+    format_timeline,
+    load_timeline,
+    return_to_latest,
+)
+from .base import BaseCommandHandler
+from .context import CommandContext
+from .parser import ParsedCommand
+
+LOGGER = logging.getLogger(__name__)
+
+LATEST = "latest"
+
+
+class TimelineCommandHandler(BaseCommandHandler):
+    """Implements the timeline and checkout commands."""
+
+    def __init__(
+        self,
+        *,
+        session_manager: SessionManager,
+        active_runs: Dict[str, Dict[str, object]],
+        send_message,
+    ) -> None:
+        super().__init__(send_message)
+        self._session_manager = session_manager
+        self._active_runs = active_runs
+
+    async def handle_timeline(self, command: ParsedCommand, context: CommandContext) -> None:
+        LOGGER.info("Executing !timeline command in channel %s, thread %s", context.channel, context.thread_ts)
+        session = context.session
+        await self._reply(context, format_timeline(load_timeline(session), checked_out_run(session)))
+
+    async def handle_checkout(self, command: ParsedCommand, context: CommandContext) -> None:
+        LOGGER.info("Executing !checkout command in channel %s, thread %s", context.channel, context.thread_ts)
+        session = context.session
+        if not command.args:
+            await self._reply(context, "Usage: `!checkout <n>` with a checkpoint number from `!timeline`, or `latest`.")
+            return
+        session_id = str(session.id)
+        if any(run.get("session_id") == session_id for run in self._active_runs.values()):
+            await self._reply(context, "An agent is still running in this session; wait for it to finish first.")
+            return
+
+        target = command.args[0].lower()
+        if target == LATEST:
+            try:
+                ref = await return_to_latest(session)
+            except TimelineError as exc:
+                await self._reply(context, f"Cannot return to the latest checkpoint: {exc}.")
+                return
+            self._session_manager.update_session_context(session.id, {CHECKED_OUT_CONTEXT_KEY: None})
+            await self._reply(context, f"Back on `{ref}`; agent runs continue from here.")
+            return
+
+        checkpoints = {entry.run: entry for entry in load_timeline(session)}
+        if not checkpoints:
+            await self._reply(context, format_timeline([]))
+            return
+        checkpoint = checkpoints.get(int(target)) if target.isdigit() else None
+        if checkpoint is None:
+            await self._reply(
+                context,
+                f"No checkpoint `{command.args[0]}`; `!timeline` lists {min(checkpoints)} to {max(checkpoints)}.",
+            )
+            return
+        try:
+            delta = await checkout_checkpoint(session, checkpoint)
+        except TimelineError as exc:
+            await self._reply(context, f"Cannot check out checkpoint {checkpoint.run}: {exc}.")
+            return
+        self._session_manager.update_session_context(session.id, delta)
+        await self._reply(
+            context,
+            f"The worktree is at checkpoint {checkpoint.run} (`{checkpoint.commit[:7]}`), detached from the session "
+            "branch, which is unchanged. Agent runs wait until `!checkout latest`.",
+        )
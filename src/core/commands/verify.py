@@ -0,0 +1,53 @@
+"""Handler for running a project's setup/verify/teardown steps on demand."""
+
+from __future__ import annotations
+
+import logging
+from typing import Callable, Dict, Optional
+from uuid import UUID
+
+from ..verify import checks_passed, format_verify_results, has_project_checks, run_project_checks
+from .base import BaseCommandHandler
+from .context import CommandContext
+from .parser import ParsedCommand
+
+LOGGER = logging.getLogger(__name__)
+
+# (session id, passed) -> noted for the session's analytics (`SessionManager.record_checks`)
+RecordChecksFn = Callable[[UUID, bool], None]
+
+
+class VerifyCommandHandler(BaseCommandHandler):
+    """Implements the test command."""
+
+    def __init__(
+        self,
+        *,
+        active_runs: Dict[str, Dict[str, object]],
+        send_message,
+        record_checks: Optional[RecordChecksFn] = None,
+    ) -> None:
+        super().__init__(send_message)
+        self._active_runs = active_runs
+        self._record_checks = record_checks
+
+    async def handle_test(self, command: ParsedCommand, context: CommandContext) -> None:
+        LOGGER.info("Executing !test command in channel %s, thread %s", context.channel, context.thread_ts)
+        project = context.project
+        if not has_project_checks(project):
+            await self._reply(
+                context,
+                f"No verify steps configured for project `{project.id}`. Add `verify:` to its entry in projects.yaml.",
+            )
+            return
+
+        session_id = str(context.session.id)
+        if any(run.get("session_id") == session_id for run in self._active_runs.values()):
+            await self._reply(context, "An agent is still running in this session; wait for it to finish first.")
+            return
+
+        await self._reply(context, f"Running verify steps in a `{project.sandbox.mode.value}` sandbox...")
+        results = await run_project_checks(project, context.session.project_path)
+        if self._record_checks:
+            self._record_checks(context.session.id, checks_passed(results))
+        await self._reply(context, format_verify_results(results))
@@ -0,0 +1,180 @@
+"""Run two agents on the same prompt and compare what they changed.
+
+Each agent works in its own detached `git worktree` of the session's current
+commit, so neither sees the other's edits and the session branch is left
+untouched. Once both finish, the changes are collected as diffs against that
+commit and the worktrees are removed.
+"""
+
+from __future__ import annotations
+
+import asyncio
+import logging
+import shutil
+import subprocess
+import tempfile
+import time
+from dataclasses import dataclass, field
+from pathlib import Path
+from typing import Awaitable, Callable, List, Optional, Sequence, Tuple
+
+from ..agent_adapters import AgentResult
+from ..chat_adapters.health import format_duration
+from .diff_render import FileDiff, split_diff
+from .jobs import run_git
+from .models import Agent
+from .pricing import format_cost
+
+LOGGER = logging.getLogger(__name__)
+
+WORKTREE_PREFIX = "remote-coder-compare-"
+# Characters of each agent's reply kept in the comparison summary
+MAX_REPLY_CHARS = 500
+
+# Runs one agent in a worktree: (agent, model, workdir, prompt) -> (result, cost in USD)
+RunAgentFn = Callable[[Agent, Optional[str], Path, str], Awaitable[Tuple[AgentResult, Optional[float]]]]
+# Inspects a finished run while its worktree still exists, e.g. to run the project's tests in it
+AfterRunFn = Callable[["ComparisonRun", Path], Awaitable[None]]
+
+
+class ComparisonError(Exception):
+    """The comparison could not be set up, e.g. the project is not a git repository."""
+
+
+@dataclass
+class ComparisonRun:
+    """What one agent did with the prompt."""
+
+    agent_id: str
+    model: Optional[str]
+    diff_text: str = ""
+    duration_secs: float = 0.0
+    cost_usd: Optional[float] = None
+    reply: str = ""
+    errors: List[str] = field(default_factory=list)
+
+    @property
+    def files(self) -> List[FileDiff]:
+        return split_diff(self.diff_text)
+
+    @property
+    def label(self) -> str:
+        return f"{self.agent_id} ({self.model})" if self.model else self.agent_id
+
+
+async def _git(cwd: Path, *args: str, check: bool = True) -> subprocess.CompletedProcess:
+    return await run_git(cwd, *args, check=check)
+
+
+async def run_comparison(
+    repo_path: Path,
+    contenders: Sequence[Tuple[Agent, Optional[str]]],
+    prompt: str,
+    run_agent: RunAgentFn,
+    *,
+    concurrent: bool = True,
+    after_run: Optional[AfterRunFn] = None,
+) -> Tuple[str, List[ComparisonRun]]:
+    """Run every (agent, model) on `prompt` in its own worktree, concurrently unless `concurrent` is False.
+
+    Returns the commit the worktrees started from and one `ComparisonRun` per
+    contender, in order. An agent that fails still gets a run with its error
+    and whatever it changed before failing.
+    """
+    head = await _git(repo_path, "rev-parse", "HEAD", check=False)
+    if head.returncode != 0:
+        raise ComparisonError(f"{repo_path} is not a git repository with at least one commit")
+    base = head.stdout.strip()
+
+    root = Path(tempfile.mkdtemp(prefix=WORKTREE_PREFIX))
+    worktrees: List[Path] = []
+    try:
+        for index, (agent, _) in enumerate(contenders):
+            worktree = root / f"{index}-{agent.id}"
+            added = await _git(repo_path, "worktree", "add", "--detach", str(worktree), base, check=False)
+            if added.returncode != 0:
+                raise ComparisonError(f"Could not create a worktree: {added.stderr.strip()}")
+            worktrees.append(worktree)
+
+        pending = [
+            _run_contender(agent, model, worktree, base, prompt, run_agent, after_run)
+            for (agent, model), worktree in zip(contenders, worktrees)
+        ]
+        if concurrent:
+            runs = await asyncio.gather(*pending)
+        else:
+            # One at a time, so the agents don't compete for the host and their durations stay comparable
+            runs = [await run for run in pending]
+    finally:
+        for worktree in worktrees:
+            await _git(repo_path, "worktree", "remove", "--force", str(worktree), check=False)
+        await _git(repo_path, "worktree", "prune", check=False)
+        shutil.rmtree(root, ignore_errors=True)
+    return base, list(runs)
+
+
+async def _run_contender(
+    agent: Agent,
+    model: Optional[str],
+    worktree: Path,
+    base: str,
+    prompt: str,
+    run_agent: RunAgentFn,
+    after_run: Optional[AfterRunFn] = None,
+) -> ComparisonRun:
+    run = ComparisonRun(agent_id=agent.id, model=model)
+    started = time.monotonic()
+    try:
+        result, run.cost_usd = await run_agent(agent, model, worktree, prompt)
+    except Exception as exc:
+        LOGGER.exception("Comparison run of %s failed", agent.id)
+        run.errors.append(str(exc))
+    else:
+        run.reply = (
+            result.structured_output.slack_message if result.structured_output else result.output_text
+        ).strip()
+        run.errors.extend(result.errors)
+    run.duration_secs = time.monotonic() - started
+
+    # Stage everything so new files show up; diffing against `base` also covers agents that commit
+    await _git(worktree, "add", "-A", check=False)
+    diff = await _git(worktree, "diff", "--cached", base, check=False)
+    run.diff_text = diff.stdout if diff.returncode == 0 else ""
+    if after_run:
+        await after_run(run, worktree)
+    return run
+
+
+def _stats_row(run: ComparisonRun) -> List[str]:
+    files = run.files
+    additions = sum(file_diff.additions for file_diff in files)
+    deletions = sum(file_diff.deletions for file_diff in files)
+    cost = f"~{format_cost(run.cost_usd)}" if run.cost_usd is not None else "n/a"
+    status = f"{len(run.errors)} error(s)" if run.errors else "ok"
+    return [status, str(len(files)), f"+{additions} -{deletions}", format_duration(run.duration_secs), cost]
+
+
+def format_comparison(base: str, runs: Sequence[ComparisonRun]) -> str:
+    """Render the agents' stats next to each other, followed by a short excerpt of each reply."""
+    row_names = ["status", "files", "lines", "time", "cost"]
+    columns = [[run.label, *_stats_row(run)] for run in runs]
+    name_width = max(len(name) for name in row_names)
+    widths = [max(len(cell) for cell in column) for column in columns]
+
+    def _row(name: str, cells: Sequence[str]) -> str:
+        padded = "  ".join(cell.ljust(width) for cell, width in zip(cells, widths))
+        return f"{name.ljust(name_width)}  {padded}".rstrip()
+
+    table = [_row("", [column[0] for column in columns])]
+    for index, name in enumerate(row_names, start=1):
+        table.append(_row(name, [column[index] for column in columns]))
+
+    lines = [f"Comparison from `{base[:12]}`:", "```", *table, "```"]
+    for run in runs:
+        reply = run.reply or "(no reply)"
+        if len(reply) > MAX_REPLY_CHARS:
+            reply = reply[: MAX_REPLY_CHARS - 1] + "…"
+        lines.append(f"*{run.label}*: {reply}")
+        for error in run.errors:
+            lines.append(f"  error: {error}")
+    return "\n".join(lines)
@@ -4,15 +4,69 @@ from __future__ import annotations
 
 import logging
 import os
-from dataclasses import dataclass
+import re
+import sys
+from dataclasses import dataclass, field
+from datetime import datetime
 from pathlib import Path
-from typing import Dict, Tuple
+from typing import Dict, List, Optional, Tuple
+from zoneinfo import ZoneInfo, ZoneInfoNotFoundError
 
 import yaml
 from dotenv import load_dotenv
 
+from ..agent_adapters.bubblewrap import CLI_STATE_PATHS
+from ..agent_adapters.command_template import TEMPLATE_VARIABLES, unknown_placeholders, uses_placeholder
+from ..agent_adapters.dev_env import dev_env_sandbox_problem
+from ..agent_adapters.windows import native_path
+from ..chat_adapters.health import DEFAULT_DISCONNECT_ALERT_SECS
+from .archive import ArchiveSettings
 from .errors import AgentNotFound, ConfigError, ProjectNotFound
-from .models import Agent, AgentType, GitHubRepoConfig, Project, WorkingDirMode
+from .file_watch import DEFAULT_FILE_WATCH_SECS
+from .cleanup import DEFAULT_ARCHIVE_AFTER_HOURS, DEFAULT_CLEANUP_INTERVAL_SECS
+from .heartbeat import DEFAULT_HEARTBEAT_SECS
+from .idle import DEFAULT_SESSION_IDLE_HOURS
+from .migrations import migrate_config
+from .models import (
+    Agent,
+    AgentCapabilities,
+    AgentPricing,
+    AgentSelection,
+    ApprovalPolicy,
+    AgentType,
+    CommandStep,
+    ConfirmPrompts,
+    CostTier,
+    DevEnvironment,
+    DevEnvKind,
+    DiscussionsConfig,
+    GitHubRepoConfig,
+    McpServer,
+    PipelineStep,
+    ProcessLimits,
+    Project,
+    ProjectRoles,
+    QuietHours,
+    SandboxConfig,
+    SandboxMode,
+    SpawnMode,
+    WebhookConfig,
+    WorkingDirMode,
+)
+from .coordinator import ROLES
+from .output_stream import DEFAULT_STREAM_INTERVAL_SECS
+from .pipeline import APPROVE_STEP, BUILTIN_STEPS, FORMAT_STEP, REVIEW_STEP, pipeline_problem
+from .privacy import PRIVACY_MODES, STANDARD
+from .resources import ResourceSettings
+from .run_journal import ADOPT, RECOVERY_MODES
+from .shutdown import DEFAULT_RESUME_NOTICE_HOURS, DEFAULT_SHUTDOWN_GRACE_SECS
+from .run_queue import DEFAULT_MAX_CONCURRENT_RUNS
+from .jobs import DEFAULT_MAX_JOBS
+from .scheduler import DEFAULT_MAX_RUNS
+from .secrets import SecretStore
+from .session_store import SESSION_STORE_KINDS, SQLITE
+from .transient import DEFAULT_RETRY_BACKOFF_SECS, DEFAULT_TRANSIENT_RETRIES
+from .updates import DEFAULT_UPDATE_CHECK_HOURS
 
 LOGGER = logging.getLogger(__name__)
 
@@ -20,6 +74,42 @@ DEFAULT_CONFIG_DIR = Path("~/.remote-coder").expanduser()
 ENV_FILE_NAME = ".env"
 PROJECTS_FILE = "projects.yaml"
 AGENTS_FILE = "agents.yaml"
+DEFAULT_WEBHOOK_HOST = "127.0.0.1"
+DEFAULT_DISCUSSIONS_POLL_SECS = 60
+
+# Project settings a `groups:` entry can provide defaults for
+GROUP_DEFAULT_KEYS = frozenset(
+    {
+        "default_agent",
+        "default_model",
+        "agent_models",
+        "env",
+        "allowed_agents",
+        "agent_selection",
+        "approval",
+        "max_active_sessions",
+        "max_concurrent_runs",
+        "max_runs_per_month",
+        "max_session_cost_usd",
+        "pipeline",
+        "quiet_hours",
+        "roles",
+        "sandbox",
+        "timeout_secs",
+        "hard_timeout_secs",
+    }
+)
+
+# Agent types that run in-process or over HTTP instead of spawning a CLI command
+API_AGENT_TYPES = frozenset(
+    {AgentType.OPENROUTER, AgentType.OLLAMA, AgentType.HTTP, AgentType.WASM, AgentType.MOCK}
+)
+# `${VAR}` references in agent env values
+ENV_REF = re.compile(r"\$\{(\w+)\}")
+# Agent types that can be given MCP servers
+MCP_AGENT_TYPES = frozenset({AgentType.CLAUDE, AgentType.CODEX, AgentType.OPENROUTER, AgentType.OLLAMA})
+# Agent types run as a CLI process, which may use `spawn_mode: pty` and `confirm_prompts`
+CLI_AGENT_TYPES = frozenset({AgentType.CLAUDE, AgentType.CODEX, AgentType.GEMINI})
 
 
 @dataclass
@@ -32,12 +122,72 @@ class Config:
     base_dir: Path
     config_dir: Path
     github_token: str | None = None
+    # Lets the daemon keep the app's slash commands in sync with the command list (see `commands.hints`)
+    slack_app_id: str | None = None
+    slack_config_token: str | None = None
+    # Who to alert when the chat connection stays down (see `DisconnectWatchdog`)
+    alert_user_ids: list[str] = field(default_factory=list)
+    alert_webhook_url: str | None = None
+    disconnect_alert_secs: int = DEFAULT_DISCONNECT_ALERT_SECS
+    # Memory limits for small hosts and where transcripts go (see `src/core/resources.py`)
+    resources: ResourceSettings = field(default_factory=ResourceSettings)
+    # HTTP endpoint for project webhooks (see `src/core/webhooks.py`); port 0 leaves it off
+    webhook_host: str = DEFAULT_WEBHOOK_HOST
+    webhook_port: int = 0
+    # Secret of the GitHub webhook posting PR events into session threads (see `src/core/github_events.py`)
+    github_webhook_secret: str | None = None
+    # Status page (see `src/core/status_page.py`); port 0 leaves it off
+    status_host: str = DEFAULT_WEBHOOK_HOST
+    status_port: int = 0
+    status_token: str | None = None
+    # How often the live output message of a run is edited (see `src/core/output_stream.py`); 0 turns it off
+    stream_interval_secs: int = DEFAULT_STREAM_INTERVAL_SECS
+    # How often a run's worktree is checked for changed files to announce (see `src/core/file_watch.py`); 0 turns it off
+    file_watch_secs: int = DEFAULT_FILE_WATCH_SECS
+    # Seconds without output before a run posts a "still working" line (see `src/core/heartbeat.py`); 0 turns it off
+    heartbeat_secs: int = DEFAULT_HEARTBEAT_SECS
+    # Seconds running agents get to finish when the daemon stops (see `src/core/shutdown.py`)
+    shutdown_grace_secs: int = DEFAULT_SHUTDOWN_GRACE_SECS
+    # Active sessions updated this recently are told in their thread that a restart resumed them; 0 turns it off
+    resume_notice_hours: int = DEFAULT_RESUME_NOTICE_HOURS
+    # Hours without activity after which a session is ended (see `src/core/idle.py`); 0 keeps sessions open
+    session_idle_hours: int = DEFAULT_SESSION_IDLE_HOURS
+    idle_notice: bool = True  # Tell the thread when its session is ended for being idle
+    # How often stale sessions are archived and their files removed (see `src/core/cleanup.py`); 0 turns it off
+    cleanup_interval_secs: int = DEFAULT_CLEANUP_INTERVAL_SECS
+    archive_after_hours: int = DEFAULT_ARCHIVE_AFTER_HOURS  # Sessions not updated for this long are archived
+    # Object storage archived sessions are uploaded to and removed from the store (see `src/core/archive.py`)
+    archive: Optional[ArchiveSettings] = None
+    # Skip agent runs for acknowledgements like "thanks!" (see `src/core/conversation/intent.py`)
+    intent_detection: bool = True
+    # GitHub logins whose discussion posts start and drive sessions (see `src/chat_adapters/github_discussions.py`)
+    github_allowed_users: list[str] = field(default_factory=list)
+    discussions_poll_secs: int = DEFAULT_DISCUSSIONS_POLL_SECS
+    # Checks for newer Remote Coder releases (see `src/core/updates.py`); 0 turns it off
+    update_check_hours: int = DEFAULT_UPDATE_CHECK_HOURS
+    update_channel: str | None = None  # Where the update notice goes; DMs to `alert_user_ids` without one
+    # Agent runs at once across all projects (see `src/core/scheduler.py`)
+    max_runs: int = DEFAULT_MAX_RUNS
+    # Stop webhook and background runs to make room for chat messages (see `src/core/scheduler.py`)
+    preemption: bool = True
+    # What happens to the changes of runs a crash interrupted: "adopt" or "clean" (see `src/core/run_journal.py`)
+    recovery: str = ADOPT
+    # Where sessions are written through to: "sqlite" or "memory" (see `src/core/session_store.py`)
+    session_store: str = SQLITE
+    # Non-agent subprocesses (checks, git, image builds) at once (see `src/core/jobs.py`)
+    max_jobs: int = DEFAULT_MAX_JOBS
+    # Replace secrets in messages, diffs, and uploads before they are posted (see `src/core/redaction.py`)
+    redact_secrets: bool = True
+    # "strict" turns off outbound calls beyond chat, forge, and model providers (see `src/core/privacy.py`)
+    privacy: str = STANDARD
 
     def get_project_by_channel(self, channel: str) -> Project:
-        try:
+        if channel in self.projects:
             return self.projects[channel]
-        except KeyError as exc:
-            raise ProjectNotFound(channel) from exc
+        for project in self.projects.values():
+            if channel in project.channels or channel in project.ask_channels:
+                return project
+        raise ProjectNotFound(channel)
 
     def get_project(self, project_id: str) -> Project:
         try:
@@ -51,6 +201,16 @@ class Config:
         except KeyError as exc:
             raise AgentNotFound(agent_id) from exc
 
+    def group_projects(self) -> Dict[str, List[Project]]:
+        """Projects by group name, each project listed once."""
+        groups: Dict[str, List[Project]] = {}
+        seen = set()
+        for project in self.projects.values():
+            if project.group and project.id not in seen:
+                seen.add(project.id)
+                groups.setdefault(project.group, []).append(project)
+        return groups
+
 
 def resolve_config_dir(config_dir: Path | str | None) -> Path:
     """Resolve and validate the directory containing .env + YAML files."""
@@ -67,22 +227,101 @@ def resolve_config_dir(config_dir: Path | str | None) -> Path:
     return target
 
 
-def load_config(config_dir: Path | str | None = None) -> Config:
-    """Load Remote Coder configuration from the provided or default directory."""
+def load_config(config_dir: Path | str | None = None, *, require_slack: bool = True) -> Config:
+    """Load Remote Coder configuration from the provided or default directory.
+
+    Embedders that drive the router through their own chat adapter can pass
+    `require_slack=False` so the Slack tokens may be left out of `.env`.
+    """
     root = resolve_config_dir(config_dir)
-    return _load_config_from_root(root)
+    return _load_config_from_root(root, require_slack=require_slack)
 
 
-def _load_config_from_root(root: Path) -> Config:
+def _load_config_from_root(root: Path, *, require_slack: bool = True) -> Config:
     _load_env_file(root / ENV_FILE_NAME)
 
     projects, base_dir = _load_projects(root / PROJECTS_FILE)
     agents = _select_agents(_load_agents(root / AGENTS_FILE))
+    SecretStore(root).apply(agents)
+    _check_project_agents(projects, agents)
 
-    slack_bot_token = _require_env("SLACK_BOT_TOKEN")
-    slack_app_token = _require_env("SLACK_APP_TOKEN")
-    slack_allowed_user_ids = _load_allowed_user_ids()
+    if require_slack:
+        slack_bot_token = _require_env("SLACK_BOT_TOKEN")
+        slack_app_token = _require_env("SLACK_APP_TOKEN")
+        slack_allowed_user_ids = _load_allowed_user_ids()
+    else:
+        slack_bot_token = os.getenv("SLACK_BOT_TOKEN") or ""
+        slack_app_token = os.getenv("SLACK_APP_TOKEN") or ""
+        slack_allowed_user_ids = _load_allowed_user_ids(required=False)
     github_token = os.getenv("GITHUB_TOKEN")
+    slack_app_id = os.getenv("SLACK_APP_ID") or None
+    slack_config_token = os.getenv("SLACK_APP_CONFIG_TOKEN") or None
+    alert_user_ids = [
+        uid.strip() for uid in (os.getenv("REMOTE_CODER_ALERT_USER_IDS") or "").split(",") if uid.strip()
+    ]
+    alert_webhook_url = os.getenv("REMOTE_CODER_ALERT_WEBHOOK_URL") or None
+    disconnect_alert_secs = _load_int_env("REMOTE_CODER_DISCONNECT_ALERT_SECS", DEFAULT_DISCONNECT_ALERT_SECS)
+    webhook_host = os.getenv("REMOTE_CODER_WEBHOOK_HOST") or DEFAULT_WEBHOOK_HOST
+    webhook_port = _load_int_env("REMOTE_CODER_WEBHOOK_PORT", 0)
+    if webhook_port > 65535:
+        raise ConfigError("REMOTE_CODER_WEBHOOK_PORT must be a port number")
+    github_webhook_secret = os.getenv("REMOTE_CODER_GITHUB_WEBHOOK_SECRET") or None
+    if github_webhook_secret and not webhook_port:
+        LOGGER.warning("REMOTE_CODER_GITHUB_WEBHOOK_SECRET has no effect without REMOTE_CODER_WEBHOOK_PORT")
+    status_host = os.getenv("REMOTE_CODER_STATUS_HOST") or DEFAULT_WEBHOOK_HOST
+    status_port = _load_int_env("REMOTE_CODER_STATUS_PORT", 0)
+    status_token = os.getenv("REMOTE_CODER_STATUS_TOKEN") or None
+    if status_port > 65535:
+        raise ConfigError("REMOTE_CODER_STATUS_PORT must be a port number")
+    if status_port and not status_token:
+        raise ConfigError("REMOTE_CODER_STATUS_PORT needs REMOTE_CODER_STATUS_TOKEN; the page is only served with one")
+    stream_interval_secs = _load_int_env("REMOTE_CODER_STREAM_INTERVAL_SECS", DEFAULT_STREAM_INTERVAL_SECS)
+    file_watch_secs = _load_int_env("REMOTE_CODER_FILE_WATCH_SECS", DEFAULT_FILE_WATCH_SECS)
+    heartbeat_secs = _load_int_env("REMOTE_CODER_HEARTBEAT_SECS", DEFAULT_HEARTBEAT_SECS)
+    github_allowed_users = [
+        login.strip() for login in (os.getenv("REMOTE_CODER_GITHUB_ALLOWED_USERS") or "").split(",") if login.strip()
+    ]
+    discussions_poll_secs = _load_int_env("REMOTE_CODER_DISCUSSIONS_POLL_SECS", DEFAULT_DISCUSSIONS_POLL_SECS)
+    if discussions_poll_secs < 1:
+        raise ConfigError("REMOTE_CODER_DISCUSSIONS_POLL_SECS must be at least 1")
+    update_check_hours = _load_int_env("REMOTE_CODER_UPDATE_CHECK_HOURS", DEFAULT_UPDATE_CHECK_HOURS)
+    if update_check_hours < 0:
+        raise ConfigError("REMOTE_CODER_UPDATE_CHECK_HOURS must be 0 (off) or a number of hours")
+    update_channel = os.getenv("REMOTE_CODER_UPDATE_CHANNEL") or None
+    resume_notice_hours = _load_int_env("REMOTE_CODER_RESUME_NOTICE_HOURS", DEFAULT_RESUME_NOTICE_HOURS)
+    if resume_notice_hours < 0:
+        raise ConfigError("REMOTE_CODER_RESUME_NOTICE_HOURS must be 0 (off) or a number of hours")
+    session_idle_hours = _load_int_env("REMOTE_CODER_SESSION_IDLE_HOURS", DEFAULT_SESSION_IDLE_HOURS)
+    if session_idle_hours < 0:
+        raise ConfigError("REMOTE_CODER_SESSION_IDLE_HOURS must be 0 (off) or a number of hours")
+    cleanup_interval_secs = _load_int_env("REMOTE_CODER_CLEANUP_INTERVAL_SECS", DEFAULT_CLEANUP_INTERVAL_SECS)
+    if cleanup_interval_secs < 0:
+        raise ConfigError("REMOTE_CODER_CLEANUP_INTERVAL_SECS must be 0 (off) or a number of seconds")
+    archive_after_hours = _load_int_env("REMOTE_CODER_ARCHIVE_AFTER_HOURS", DEFAULT_ARCHIVE_AFTER_HOURS)
+    if archive_after_hours < 1:
+        raise ConfigError("REMOTE_CODER_ARCHIVE_AFTER_HOURS must be a positive number of hours")
+    max_runs = _load_int_env("REMOTE_CODER_MAX_RUNS", DEFAULT_MAX_RUNS)
+    if max_runs < 1:
+        raise ConfigError("REMOTE_CODER_MAX_RUNS must be a positive number of runs")
+    max_jobs = _load_int_env("REMOTE_CODER_MAX_JOBS", DEFAULT_MAX_JOBS)
+    privacy = (os.getenv("REMOTE_CODER_PRIVACY") or STANDARD).strip().lower()
+    if privacy not in PRIVACY_MODES:
+        raise ConfigError(f"REMOTE_CODER_PRIVACY must be one of: {', '.join(PRIVACY_MODES)}")
+    recovery = (os.getenv("REMOTE_CODER_RECOVERY") or ADOPT).strip().lower()
+    if recovery not in RECOVERY_MODES:
+        raise ConfigError(f"REMOTE_CODER_RECOVERY must be one of: {', '.join(RECOVERY_MODES)}")
+    if max_jobs < 1:
+        raise ConfigError("REMOTE_CODER_MAX_JOBS must be a positive number of jobs")
+    session_store = (os.getenv("REMOTE_CODER_SESSION_STORE") or SQLITE).strip().lower()
+    if session_store not in SESSION_STORE_KINDS:
+        raise ConfigError(f"REMOTE_CODER_SESSION_STORE must be one of: {', '.join(SESSION_STORE_KINDS)}")
+    resources = ResourceSettings.for_mode(
+        low_memory=_load_bool_env("REMOTE_CODER_LOW_MEMORY"),
+        config_dir=root,
+        indexing=not _load_bool_env("REMOTE_CODER_DISABLE_INDEXING"),
+        summarization=not _load_bool_env("REMOTE_CODER_DISABLE_SUMMARIZATION"),
+        transcripts=not _load_bool_env("REMOTE_CODER_DISABLE_TRANSCRIPTS"),
+    )
 
     return Config(
         projects=projects,
@@ -93,9 +332,70 @@ def _load_config_from_root(root: Path) -> Config:
         base_dir=base_dir,
         config_dir=root,
         github_token=github_token,
+        slack_app_id=slack_app_id,
+        slack_config_token=slack_config_token,
+        alert_user_ids=alert_user_ids,
+        alert_webhook_url=alert_webhook_url,
+        disconnect_alert_secs=disconnect_alert_secs,
+        resources=resources,
+        webhook_host=webhook_host,
+        webhook_port=webhook_port,
+        github_webhook_secret=github_webhook_secret,
+        status_host=status_host,
+        status_port=status_port,
+        status_token=status_token,
+        stream_interval_secs=stream_interval_secs,
+        file_watch_secs=file_watch_secs,
+        heartbeat_secs=heartbeat_secs,
+        shutdown_grace_secs=_load_int_env("REMOTE_CODER_SHUTDOWN_GRACE_SECS", DEFAULT_SHUTDOWN_GRACE_SECS),
+        resume_notice_hours=resume_notice_hours,
+        session_idle_hours=session_idle_hours,
+        idle_notice=not _load_bool_env("REMOTE_CODER_DISABLE_IDLE_NOTICE"),
+        cleanup_interval_secs=cleanup_interval_secs,
+        archive_after_hours=archive_after_hours,
+        archive=ArchiveSettings.from_env(os.environ),
+        intent_detection=not _load_bool_env("REMOTE_CODER_DISABLE_INTENT_DETECTION"),
+        github_allowed_users=github_allowed_users,
+        discussions_poll_secs=discussions_poll_secs,
+        update_check_hours=update_check_hours,
+        update_channel=update_channel,
+        max_runs=max_runs,
+        preemption=not _load_bool_env("REMOTE_CODER_DISABLE_PREEMPTION"),
+        recovery=recovery,
+        session_store=session_store,
+        max_jobs=max_jobs,
+        redact_secrets=not _load_bool_env("REMOTE_CODER_DISABLE_REDACTION"),
+        privacy=privacy,
     )
 
 
+def _check_project_agents(projects: Dict[str, Project], agents: Dict[str, Agent]) -> None:
+    """Warn about per-project agent settings that refer to unknown agents or models."""
+    for project in projects.values():
+        for agent_id in project.allowed_agents:
+            if agent_id not in agents:
+                LOGGER.warning("Project %s allows agent %s, which is not configured", project.id, agent_id)
+        for role in ROLES:
+            agent_id = getattr(project.roles, role)
+            if agent_id and agent_id not in agents:
+                LOGGER.warning(
+                    "Project %s gives the %s role to agent %s, which is not configured", project.id, role, agent_id
+                )
+        for agent_id, model in project.agent_models.items():
+            agent = agents.get(agent_id)
+            if agent is None:
+                LOGGER.warning("Project %s sets a model for agent %s, which is not configured", project.id, agent_id)
+                continue
+            available = agent.models.get("available") or []
+            if available and model not in available:
+                LOGGER.warning(
+                    "Project %s sets model %s for agent %s, which is not in its available models",
+                    project.id,
+                    model,
+                    agent_id,
+                )
+
+
 def _load_env_file(path: Path) -> None:
     if not path.exists():
         LOGGER.warning("No .env file found at %s; relying on shell environment.", path)
@@ -110,9 +410,35 @@ def _require_env(name: str) -> str:
     return value
 
 
-def _load_allowed_user_ids() -> list[str]:
+def _load_int_env(name: str, default: int) -> int:
+    raw_value = os.getenv(name)
+    if not raw_value:
+        return default
+    try:
+        value = int(raw_value)
+    except ValueError as exc:
+        raise ConfigError(f"{name} must be an integer, got {raw_value!r}") from exc
+    if value < 0:
+        raise ConfigError(f"{name} must not be negative")
+    return value
+
+
+def _load_bool_env(name: str, default: bool = False) -> bool:
+    raw_value = (os.getenv(name) or "").strip().lower()
+    if not raw_value:
+        return default
+    if raw_value in ("1", "true", "yes", "on"):
+        return True
+    if raw_value in ("0", "false", "no", "off"):
+        return False
+    raise ConfigError(f"{name} must be a boolean (1/0, true/false), got {raw_value!r}")
+
+
+def _load_allowed_user_ids(required: bool = True) -> list[str]:
     raw_value = os.getenv("SLACK_ALLOWED_USER_IDS") or os.getenv("SLACK_ALLOWED_USER_ID")
     if not raw_value:
+        if not required:
+            return []
         raise ConfigError("SLACK_ALLOWED_USER_IDS (or SLACK_ALLOWED_USER_ID) must be set")
     return [uid.strip() for uid in raw_value.split(",") if uid.strip()]
 
@@ -127,6 +453,7 @@ def _load_projects(path: Path) -> Tuple[Dict[str, Project], Path]:
 
     if not isinstance(data, dict):
         raise ConfigError(f"Invalid projects.yaml structure at {path}")
+    data = migrate_config(path, data, PROJECTS_FILE)
 
     base_dir_raw = data.get("base_dir")
     if not base_dir_raw:
@@ -137,10 +464,18 @@ def _load_projects(path: Path) -> Tuple[Dict[str, Project], Path]:
     else:
         base_dir = base_dir.resolve()
 
+    groups = _load_groups(data.get("groups"))
+
     projects = {}
     for project_id, cfg in (data.get("projects") or {}).items():
         if not isinstance(cfg, dict):
             raise ConfigError(f"Project {project_id} must be a mapping")
+        group = cfg.get("group")
+        if group is not None:
+            if group not in groups:
+                raise ConfigError(f"Project {project_id} refers to unknown group {group}")
+            # Settings on the project win over the group's defaults
+            cfg = {**groups[group], **cfg}
 
         rel_path = cfg.get("path")
         if not rel_path:
@@ -167,6 +502,41 @@ def _load_projects(path: Path) -> Tuple[Dict[str, Project], Path]:
             except KeyError as exc:
                 raise ConfigError(f"Incomplete GitHub config for {project_id}") from exc
 
+        steps = {
+            phase: _parse_command_steps(f"Project {project_id} {phase}", cfg.get(phase))
+            for phase in ("setup", "verify", "teardown", "format")
+        }
+        sandbox = _parse_sandbox(f"Project {project_id}", cfg.get("sandbox"), allow_run_agent=True)
+
+        selection_raw = cfg.get("agent_selection") or AgentSelection.DEFAULT.value
+        try:
+            agent_selection = AgentSelection(str(selection_raw).lower())
+        except ValueError as exc:
+            raise ConfigError(f"Project {project_id} agent_selection must be 'default' or 'auto'") from exc
+        languages = _parse_languages(f"languages for project {project_id}", cfg.get("languages"))
+
+        agent_models = cfg.get("agent_models") or {}
+        if not isinstance(agent_models, dict) or not all(
+            isinstance(model, str) and model.strip() for model in agent_models.values()
+        ):
+            raise ConfigError(f"Project {project_id} agent_models must map agent ids to model names")
+        allowed_agents = cfg.get("allowed_agents") or []
+        if not isinstance(allowed_agents, list) or not all(
+            isinstance(agent_id, str) and agent_id for agent_id in allowed_agents
+        ):
+            raise ConfigError(f"Project {project_id} allowed_agents must be a list of agent ids")
+        if allowed_agents and default_agent not in allowed_agents:
+            raise ConfigError(f"Project {project_id} default_agent {default_agent} is not in its allowed_agents")
+        channels = cfg.get("channels") or []
+        if not isinstance(channels, list) or not all(isinstance(channel, str) and channel for channel in channels):
+            raise ConfigError(f"Project {project_id} channels must be a list of channel names")
+        ask_channels = cfg.get("ask_channels") or []
+        if not isinstance(ask_channels, list) or not all(
+            isinstance(channel, str) and channel for channel in ask_channels
+        ):
+            raise ConfigError(f"Project {project_id} ask_channels must be a list of channel names")
+        roles = _parse_roles(project_id, cfg.get("roles"), allowed_agents)
+
         projects[project_id] = Project(
             id=project_id,
             channel_name=project_id,
@@ -174,12 +544,305 @@ def _load_projects(path: Path) -> Tuple[Dict[str, Project], Path]:
             default_agent_id=default_agent,
             default_model=default_model,
             github=github,
+            verify_commands=steps["verify"],
+            setup_commands=steps["setup"],
+            teardown_commands=steps["teardown"],
+            format_commands=steps["format"],
+            sandbox=sandbox,
+            agent_selection=agent_selection,
+            languages=languages,
+            agent_models={str(agent_id): model for agent_id, model in agent_models.items()},
+            allowed_agents=list(allowed_agents),
+            group=group,
+            max_session_cost_usd=_parse_budget(project_id, cfg.get("max_session_cost_usd")),
+            max_runs_per_month=_parse_run_quota(project_id, cfg.get("max_runs_per_month")),
+            max_concurrent_runs=_parse_concurrency(project_id, cfg.get("max_concurrent_runs")),
+            max_active_sessions=_parse_session_limit(project_id, cfg.get("max_active_sessions")),
+            quiet_hours=_parse_quiet_hours(project_id, cfg.get("quiet_hours")),
+            approval=_parse_approval(project_id, cfg.get("approval")),
+            channels=list(channels),
+            ask_channels=list(ask_channels),
+            roles=roles,
+            webhook=_parse_webhook(project_id, cfg.get("webhook")),
+            discussions=_parse_discussions(project_id, cfg.get("discussions"), github),
+            pipeline=_parse_pipeline(project_id, cfg.get("pipeline"), bool(steps["format"]), bool(roles.reviewer)),
+            dev_env=_parse_dev_env(project_id, cfg.get("env"), sandbox),
+            **_parse_timeouts(f"project {project_id}", cfg),
         )
+    _check_project_channels(projects)
     if not projects:
         LOGGER.warning("No projects configured in %s", path)
     return projects, base_dir
 
 
+def _load_groups(raw: object) -> Dict[str, Dict[str, object]]:
+    if raw is None:
+        return {}
+    if not isinstance(raw, dict):
+        raise ConfigError("groups in projects.yaml must be a mapping of group names")
+
+    groups = {}
+    for name, cfg in raw.items():
+        cfg = cfg or {}
+        if not isinstance(cfg, dict):
+            raise ConfigError(f"Group {name} must be a mapping")
+        unknown = sorted(set(cfg) - GROUP_DEFAULT_KEYS)
+        if unknown:
+            raise ConfigError(
+                f"Group {name} has unsupported keys {', '.join(unknown)}; "
+                f"groups can set {', '.join(sorted(GROUP_DEFAULT_KEYS))}"
+            )
+        groups[str(name)] = cfg
+    return groups
+
+
+def _parse_budget(project_id: str, raw: object) -> Optional[float]:
+    if raw is None:
+        return None
+    if isinstance(raw, bool) or not isinstance(raw, (int, float)) or raw <= 0:
+        raise ConfigError(f"Project {project_id} max_session_cost_usd must be a positive amount")
+    return float(raw)
+
+
+def _parse_run_quota(project_id: str, raw: object) -> Optional[int]:
+    if raw is None:
+        return None
+    if isinstance(raw, bool) or not isinstance(raw, int) or raw <= 0:
+        raise ConfigError(f"Project {project_id} max_runs_per_month must be a positive number of runs")
+    return raw
+
+
+def _parse_concurrency(project_id: str, raw: object) -> int:
+    if raw is None:
+        return DEFAULT_MAX_CONCURRENT_RUNS
+    if isinstance(raw, bool) or not isinstance(raw, int) or raw <= 0:
+        raise ConfigError(f"Project {project_id} max_concurrent_runs must be a positive number of runs")
+    return raw
+
+
+def _parse_session_limit(project_id: str, raw: object) -> Optional[int]:
+    if raw is None:
+        return None
+    if isinstance(raw, bool) or not isinstance(raw, int) or raw <= 0:
+        raise ConfigError(f"Project {project_id} max_active_sessions must be a positive number of sessions")
+    return raw
+
+
+def _parse_quiet_hours(project_id: str, raw: object) -> Optional[QuietHours]:
+    if raw is None:
+        return None
+    if isinstance(raw, str):
+        start_raw, _, end_raw = raw.partition("-")
+        raw = {"start": start_raw, "end": end_raw}
+    if not isinstance(raw, dict):
+        raise ConfigError(f"Project {project_id} quiet_hours must be \"HH:MM-HH:MM\" or a mapping")
+
+    try:
+        start = datetime.strptime(str(raw.get("start") or "").strip(), "%H:%M").time()
+        end = datetime.strptime(str(raw.get("end") or "").strip(), "%H:%M").time()
+    except ValueError as exc:
+        raise ConfigError(f"Project {project_id} quiet_hours start and end must be HH:MM times") from exc
+    if start == end:
+        raise ConfigError(f"Project {project_id} quiet_hours start and end must differ")
+
+    tz_name = raw.get("timezone")
+    if tz_name is not None:
+        try:
+            ZoneInfo(str(tz_name))
+        except (ZoneInfoNotFoundError, ValueError) as exc:
+            raise ConfigError(f"Project {project_id} quiet_hours timezone {tz_name} is unknown") from exc
+    return QuietHours(start=start, end=end, timezone=str(tz_name) if tz_name else None)
+
+
+def _parse_discussions(
+    project_id: str, raw: object, github: Optional[GitHubRepoConfig]
+) -> Optional[DiscussionsConfig]:
+    """`discussions: true` or `discussions: {category: Ideas}`; needs the project's `github` section."""
+    if raw is None or raw is False:
+        return None
+    if raw is True:
+        raw = {}
+    if not isinstance(raw, dict):
+        raise ConfigError(f"Project {project_id} discussions must be true or a mapping")
+    unknown = sorted(set(raw) - {"category"})
+    if unknown:
+        raise ConfigError(f"Project {project_id} discussions has unsupported keys {', '.join(map(str, unknown))}")
+    if github is None:
+        raise ConfigError(f"Project {project_id} discussions needs the project's github section")
+    category = raw.get("category")
+    if category is not None and (not isinstance(category, str) or not category.strip()):
+        raise ConfigError(f"Project {project_id} discussions category must be a category name")
+    return DiscussionsConfig(category=category.strip() if category else None)
+
+
+def _parse_webhook(project_id: str, raw: object) -> Optional[WebhookConfig]:
+    """`webhook: {token: ${VAR}, commands: [...]}`; the token is required and may reference the environment."""
+    if raw is None:
+        return None
+    if not isinstance(raw, dict):
+        raise ConfigError(f"Project {project_id} webhook must be a mapping")
+    unknown = sorted(set(raw) - {"token", "commands"})
+    if unknown:
+        raise ConfigError(f"Project {project_id} webhook has unsupported keys {', '.join(map(str, unknown))}")
+    token = raw.get("token")
+    if not isinstance(token, str) or not token.strip():
+        raise ConfigError(f"Project {project_id} webhook needs a token")
+    token = _expand_env_refs(f"Project {project_id} webhook token", token).strip()
+    if not token:
+        raise ConfigError(f"Project {project_id} webhook token is empty")
+    commands = raw.get("commands") or []
+    if not isinstance(commands, list) or not all(isinstance(name, str) and name for name in commands):
+        raise ConfigError(f"Project {project_id} webhook commands must be a list of command names")
+    return WebhookConfig(token=token, commands=list(commands))
+
+
+def _parse_approval(project_id: str, raw: object) -> ApprovalPolicy:
+    """`approval: true`, or a mapping; a mapping requires approval unless it sets `required: false`."""
+    if raw is None or raw is False:
+        return ApprovalPolicy()
+    if raw is True:
+        return ApprovalPolicy(required=True)
+    if not isinstance(raw, dict):
+        raise ConfigError(f"Project {project_id} approval must be true, false, or a mapping")
+    unknown = sorted(set(raw) - {"required", "max_files", "max_lines", "protected_paths"})
+    if unknown:
+        raise ConfigError(f"Project {project_id} approval has unsupported keys {', '.join(unknown)}")
+
+    required = raw.get("required", True)
+    if not isinstance(required, bool):
+        raise ConfigError(f"Project {project_id} approval required must be true or false")
+    limits = {}
+    for key in ("max_files", "max_lines"):
+        value = raw.get(key, getattr(ApprovalPolicy, key))
+        if isinstance(value, bool) or not isinstance(value, int) or value < 0:
+            raise ConfigError(f"Project {project_id} approval {key} must be a non-negative integer")
+        limits[key] = value
+    protected = raw.get("protected_paths") or []
+    if not isinstance(protected, list) or not all(isinstance(item, str) and item.strip() for item in protected):
+        raise ConfigError(f"Project {project_id} approval protected_paths must be a list of path globs")
+    return ApprovalPolicy(required=required, protected_paths=[item.strip() for item in protected], **limits)
+
+
+def _parse_roles(project_id: str, raw: object, allowed_agents: List[str]) -> ProjectRoles:
+    """`roles: {planner: claude, coder: codex, reviewer: gemini}`; see `src/core/coordinator.py`."""
+    if raw is None:
+        return ProjectRoles()
+    owner = f"Project {project_id} roles"
+    if not isinstance(raw, dict):
+        raise ConfigError(f"{owner} must map {', '.join(ROLES)} to agent ids")
+    unknown = sorted(set(map(str, raw)) - set(ROLES))
+    if unknown:
+        raise ConfigError(f"{owner} has unknown roles {', '.join(unknown)}; roles are {', '.join(ROLES)}")
+    roles = {}
+    for role, agent_id in raw.items():
+        if not isinstance(agent_id, str) or not agent_id.strip():
+            raise ConfigError(f"{owner} {role} must be an agent id")
+        if allowed_agents and agent_id.strip() not in allowed_agents:
+            raise ConfigError(f"{owner} {role} {agent_id} is not in its allowed_agents")
+        roles[role] = agent_id.strip()
+    return ProjectRoles(**roles)
+
+
+def _parse_pipeline(
+    project_id: str, raw: object, has_format_commands: bool, has_reviewer: bool
+) -> List[PipelineStep]:
+    """`pipeline: [plan, edit, {run: make generate}, verify, pr]`; see `src/core/pipeline.py`."""
+    if raw is None:
+        return []
+    owner = f"Project {project_id} pipeline"
+    if not isinstance(raw, list):
+        raise ConfigError(f"{owner} must be a list of steps")
+
+    steps = []
+    for item in raw:
+        if isinstance(item, str):
+            item = {"name": item}
+        if not isinstance(item, dict):
+            raise ConfigError(f"{owner} entries must be step names or mappings")
+        unknown = sorted(set(item) - {"name", "run", "timeout_secs"})
+        if unknown:
+            raise ConfigError(f"{owner} step has unsupported keys {', '.join(map(str, unknown))}")
+        name, run = item.get("name"), item.get("run")
+        if name is not None and (not isinstance(name, str) or not name.strip()):
+            raise ConfigError(f"{owner} step name must be a string")
+        if run is not None and (not isinstance(run, str) or not run.strip()):
+            raise ConfigError(f"{owner} hook step run must be a shell command")
+        if run is None and name not in BUILTIN_STEPS:
+            raise ConfigError(
+                f"{owner} step {name} is unknown; steps are {', '.join(BUILTIN_STEPS)}, or a hook with `run`"
+            )
+        if run is not None and name in BUILTIN_STEPS:
+            raise ConfigError(f"{owner} hook step cannot be named {name}, which is a built-in step")
+        timeout_secs = item.get("timeout_secs")
+        if timeout_secs is not None and (
+            isinstance(timeout_secs, bool) or not isinstance(timeout_secs, int) or timeout_secs <= 0
+        ):
+            raise ConfigError(f"{owner} timeout_secs for step {name or run} must be a positive number of seconds")
+        if timeout_secs is not None and run is None and name != APPROVE_STEP:
+            raise ConfigError(f"{owner} timeout_secs only applies to approve and hook steps")
+        steps.append(PipelineStep(name=name or run, run=run, timeout_secs=timeout_secs))
+
+    problem = pipeline_problem(steps)
+    if problem:
+        raise ConfigError(f"{owner} {problem}")
+    if not has_format_commands and any(step.name == FORMAT_STEP for step in steps):
+        raise ConfigError(f"{owner} has a format step, but the project has no format commands")
+    if not has_reviewer and any(step.name == REVIEW_STEP for step in steps):
+        raise ConfigError(f"{owner} has a review step, but the project's roles have no reviewer")
+    return steps
+
+
+def _parse_dev_env(project_id: str, raw: object, sandbox: SandboxConfig) -> Optional[DevEnvironment]:
+    """`env: nix|mise|asdf`, or `env: {type: nix, flake: ".#ci"}` / `{type: mise, trust: true}`; see `dev_env.py`."""
+    if raw is None:
+        return None
+    owner = f"Project {project_id} env"
+    if isinstance(raw, str):
+        raw = {"type": raw}
+    if not isinstance(raw, dict):
+        raise ConfigError(f"{owner} must be nix, mise, asdf, or a mapping with type")
+    unknown = sorted(set(raw) - {"type", "flake", "trust"})
+    if unknown:
+        raise ConfigError(f"{owner} has unsupported keys {', '.join(map(str, unknown))}")
+    try:
+        kind = DevEnvKind(str(raw.get("type")).lower())
+    except ValueError as exc:
+        raise ConfigError(f"{owner} type must be one of {', '.join(item.value for item in DevEnvKind)}") from exc
+    flake = raw.get("flake")
+    if flake is not None and (not isinstance(flake, str) or not flake.strip()):
+        raise ConfigError(f"{owner} flake must be a flake reference such as .#ci")
+    if flake is not None and kind != DevEnvKind.NIX:
+        raise ConfigError(f"{owner} flake only applies to type nix")
+    trust = raw.get("trust", False)
+    if not isinstance(trust, bool):
+        raise ConfigError(f"{owner} trust must be true or false")
+    if trust and kind != DevEnvKind.MISE:
+        raise ConfigError(f"{owner} trust only applies to type mise")
+    if sandbox.mode == SandboxMode.CONTAINER:
+        # The shell is entered on the host; a container has its own toolchain
+        raise ConfigError(f"{owner} {kind.value} cannot be combined with a container sandbox; use one or the other")
+    dev_env = DevEnvironment(kind=kind, flake=flake.strip() if flake else None, trust=trust)
+    problem = dev_env_sandbox_problem(dev_env, sandbox)
+    if problem:
+        raise ConfigError(f"{owner}: {problem}")
+    return dev_env
+
+
+def _check_project_channels(projects: Dict[str, Project]) -> None:
+    """Each channel maps to one project: `channels` and `ask_channels` entries may not repeat or shadow a project id."""
+    owners: Dict[str, str] = {}
+    for project in projects.values():
+        shared = sorted(set(project.channels) & set(project.ask_channels))
+        if shared:
+            raise ConfigError(f"Project {project.id} lists {', '.join(shared)} in both channels and ask_channels")
+        for channel in [*project.channels, *project.ask_channels]:
+            if channel in projects and channel != project.id:
+                raise ConfigError(f"Project {project.id} lists channel {channel}, which is another project's id")
+            if channel in owners and owners[channel] != project.id:
+                raise ConfigError(f"Channel {channel} is listed by both {owners[channel]} and {project.id}")
+            owners[channel] = project.id
+
+
 def _load_agents(path: Path) -> Dict[str, Agent]:
     try:
         data = yaml.safe_load(path.read_text(encoding="utf-8"))
@@ -190,6 +853,7 @@ def _load_agents(path: Path) -> Dict[str, Agent]:
 
     if not isinstance(data, dict):
         raise ConfigError(f"Invalid agents.yaml structure at {path}")
+    data = migrate_config(path, data, AGENTS_FILE)
 
     agents = {}
     for agent_id, cfg in (data.get("agents") or {}).items():
@@ -206,31 +870,471 @@ def _load_agents(path: Path) -> Dict[str, Agent]:
             raise ConfigError(f"Unsupported agent type {agent_type_raw} for {agent_id}") from exc
 
         command = cfg.get("command")
-        if not isinstance(command, list) or not command:
+        if agent_type in API_AGENT_TYPES:
+            if command is not None and not isinstance(command, list):
+                raise ConfigError(f"command for agent {agent_id} must be a list")
+            command = command or []
+        elif not isinstance(command, list) or not command:
             raise ConfigError(f"Agent {agent_id} must supply a non-empty command list")
 
+        unknown = unknown_placeholders(command)
+        if unknown:
+            raise ConfigError(
+                f"Unknown placeholder(s) in command for agent {agent_id}: "
+                + ", ".join(f"{{{{{name}}}}}" for name in unknown)
+                + "; supported: "
+                + ", ".join(f"{{{{{name}}}}}" for name in TEMPLATE_VARIABLES)
+            )
+
+        if agent_type == AgentType.HTTP and not cfg.get("endpoint"):
+            raise ConfigError(f"Agent {agent_id} of type http must set endpoint")
+
+        plugin = cfg.get("plugin")
+        if plugin is not None and (agent_type != AgentType.WASM or not isinstance(plugin, str)):
+            raise ConfigError(f"plugin for agent {agent_id} must be a string and is only valid for wasm agents")
+
         env = cfg.get("env") or {}
         if not isinstance(env, dict):
             raise ConfigError(f"env for agent {agent_id} must be a mapping")
+        env = {str(key): _expand_env_refs(f"env {key} for agent {agent_id}", str(value)) for key, value in env.items()}
 
         models = cfg.get("models") or {}
         if not isinstance(models, dict):
             raise ConfigError(f"models for agent {agent_id} must be a mapping")
 
+        pricing = _parse_pricing(agent_id, cfg.get("pricing"))
+        capabilities = _parse_capabilities(agent_id, cfg.get("capabilities"))
+        sandbox = _parse_sandbox(f"Agent {agent_id}", cfg.get("sandbox"), agent_type=agent_type)
+
+        options = cfg.get("options") or {}
+        if not isinstance(options, dict):
+            raise ConfigError(f"options for agent {agent_id} must be a mapping")
+        if agent_type == AgentType.MOCK and not isinstance(options.get("files") or {}, dict):
+            raise ConfigError(f"options.files for mock agent {agent_id} must be a mapping of paths to contents")
+        idle_secs = options.get("warm_idle_secs")
+        if idle_secs is not None and (
+            isinstance(idle_secs, bool) or not isinstance(idle_secs, (int, float)) or idle_secs <= 0
+        ):
+            raise ConfigError(f"options.warm_idle_secs for agent {agent_id} must be a positive number of seconds")
+
+        min_version = cfg.get("min_version")
+        if min_version is not None:
+            if agent_type in API_AGENT_TYPES:
+                raise ConfigError(f"min_version for agent {agent_id} is only valid for CLI agents")
+            min_version = str(min_version)
+            if not re.fullmatch(r"\d+(?:\.\d+)*", min_version):
+                raise ConfigError(f"min_version for agent {agent_id} must be a version like \"1.2.0\"")
+
+        spawn_mode, pty_size = _parse_spawn_mode(agent_id, agent_type, cfg)
+        confirm_prompts = _parse_confirm_prompts(agent_id, agent_type, command, cfg.get("confirm_prompts"))
+        limits = _parse_limits(agent_id, agent_type, cfg.get("limits"))
+        if spawn_mode == SpawnMode.PTY and options.get("warm_pool"):
+            LOGGER.warning("Agent %s runs in a pty, which warm processes do not use; running one-shot", agent_id)
+            options = {key: value for key, value in options.items() if key != "warm_pool"}
+
+        max_retries = cfg.get("max_retries", 0)
+        if isinstance(max_retries, bool) or not isinstance(max_retries, int) or max_retries < 0:
+            raise ConfigError(f"max_retries for agent {agent_id} must be a non-negative integer")
+        transient_retries = cfg.get("transient_retries", DEFAULT_TRANSIENT_RETRIES)
+        if isinstance(transient_retries, bool) or not isinstance(transient_retries, int) or transient_retries < 0:
+            raise ConfigError(f"transient_retries for agent {agent_id} must be a non-negative integer")
+        retry_backoff_secs = cfg.get("retry_backoff_secs", DEFAULT_RETRY_BACKOFF_SECS)
+        if (
+            isinstance(retry_backoff_secs, bool)
+            or not isinstance(retry_backoff_secs, (int, float))
+            or retry_backoff_secs < 0
+        ):
+            raise ConfigError(f"retry_backoff_secs for agent {agent_id} must be a non-negative number of seconds")
+        max_cost_usd = _optional_positive(cfg.get("max_cost_usd"), f"max_cost_usd for agent {agent_id}", float)
+        if max_cost_usd is not None and pricing is None:
+            LOGGER.warning("Agent %s sets max_cost_usd without pricing; its runs cannot be priced", agent_id)
+
+        mcp_servers = _parse_mcp_servers(agent_id, cfg.get("mcp_servers"))
+        if mcp_servers and agent_type not in MCP_AGENT_TYPES:
+            LOGGER.warning("Agent %s (%s) does not support MCP servers; ignoring mcp_servers", agent_id, agent_type.value)
+            mcp_servers = []
+        if options.get("warm_pool") and agent_type != AgentType.CLAUDE:
+            LOGGER.warning("Agent %s (%s) cannot be kept warm; ignoring options.warm_pool", agent_id, agent_type.value)
+            options = {key: value for key, value in options.items() if key != "warm_pool"}
+        elif options.get("warm_pool") and mcp_servers:
+            LOGGER.warning("Agent %s uses mcp_servers, which warm processes do not support; running one-shot", agent_id)
+
         agents[agent_id] = Agent(
             id=agent_id,
             type=agent_type,
             command=command,
             working_dir_mode=working_mode,
             fixed_path=fixed_path,
-            env={str(k): str(v) for k, v in env.items()},
+            env=env,
             models=models,
+            pricing=pricing,
+            endpoint=cfg.get("endpoint"),
+            api_key_env=cfg.get("api_key_env"),
+            options=options,
+            mcp_servers=mcp_servers,
+            plugin=plugin,
+            capabilities=capabilities,
+            sandbox=sandbox,
+            min_version=min_version,
+            spawn_mode=spawn_mode,
+            pty_size=pty_size,
+            confirm_prompts=confirm_prompts,
+            limits=limits,
+            **_parse_timeouts(f"agent {agent_id}", cfg),
+            max_retries=max_retries,
+            max_cost_usd=max_cost_usd,
+            transient_retries=transient_retries,
+            retry_backoff_secs=float(retry_backoff_secs),
         )
     if not agents:
         LOGGER.warning("No agents configured in %s", path)
     return agents
 
 
+def _expand_env_refs(owner: str, value: str) -> str:
+    """Replace `${VAR}` references with values from the environment (including `.env`)."""
+
+    def _lookup(match: re.Match) -> str:
+        name = match.group(1)
+        if name not in os.environ:
+            raise ConfigError(f"{owner} references ${{{name}}}, which is not set")
+        return os.environ[name]
+
+    return ENV_REF.sub(_lookup, value)
+
+
+def _optional_positive(raw: object, label: str, kind: type) -> Optional[float]:
+    """Validate an optional positive number; `kind` is int for whole numbers only."""
+    if raw is None:
+        return None
+    allowed = (int,) if kind is int else (int, float)
+    if isinstance(raw, bool) or not isinstance(raw, allowed) or raw <= 0:
+        noun = "a positive integer" if kind is int else "a positive number"
+        raise ConfigError(f"{label} must be {noun}")
+    return kind(raw)
+
+
+def _parse_spawn_mode(
+    agent_id: str, agent_type: AgentType, cfg: Dict[str, object]
+) -> Tuple[SpawnMode, Optional[Tuple[int, int]]]:
+    """`spawn_mode` and `pty_size` of an agent."""
+    raw_mode = cfg.get("spawn_mode")
+    try:
+        spawn_mode = SpawnMode(str(raw_mode).lower()) if raw_mode is not None else SpawnMode.PIPE
+    except ValueError as exc:
+        raise ConfigError(f"spawn_mode for agent {agent_id} must be pipe or pty") from exc
+    if spawn_mode == SpawnMode.PTY and agent_type not in CLI_AGENT_TYPES:
+        raise ConfigError(f"spawn_mode pty for agent {agent_id} is only valid for claude, codex, and gemini agents")
+    raw_size = cfg.get("pty_size")
+    if raw_size is None:
+        return spawn_mode, None
+    if spawn_mode != SpawnMode.PTY:
+        raise ConfigError(f"pty_size for agent {agent_id} needs spawn_mode: pty")
+    if (
+        not isinstance(raw_size, list)
+        or len(raw_size) != 2
+        or any(isinstance(value, bool) or not isinstance(value, int) or not 0 < value < 65536 for value in raw_size)
+    ):
+        raise ConfigError(f"pty_size for agent {agent_id} must be [columns, rows]")
+    return spawn_mode, (raw_size[0], raw_size[1])
+
+
+def _parse_confirm_prompts(
+    agent_id: str, agent_type: AgentType, command: List[str], raw: object
+) -> Optional[ConfirmPrompts]:
+    """`confirm_prompts: true` (the default yes/no patterns) or a mapping of patterns and answers."""
+    if raw is None or raw is False:
+        return None
+    owner = f"confirm_prompts for agent {agent_id}"
+    if agent_type not in CLI_AGENT_TYPES:
+        raise ConfigError(f"{owner} is only valid for claude, codex, and gemini agents")
+    if agent_type != AgentType.GEMINI and not uses_placeholder(command, "prompt_file"):
+        # Claude and Codex read the prompt from stdin until it is closed
+        raise ConfigError(f"{owner} needs {{{{prompt_file}}}} in the command, so stdin is free for answers")
+    if raw is True:
+        return ConfirmPrompts()
+    if not isinstance(raw, dict):
+        raise ConfigError(f"{owner} must be true or a mapping")
+    unknown = sorted(set(raw) - {"patterns", "approve", "deny", "timeout_secs"})
+    if unknown:
+        raise ConfigError(f"{owner} has unsupported keys: {', '.join(unknown)}")
+    prompts = ConfirmPrompts()
+    patterns = raw.get("patterns")
+    if patterns is not None:
+        if not isinstance(patterns, list) or not patterns or not all(isinstance(item, str) for item in patterns):
+            raise ConfigError(f"{owner} patterns must be a non-empty list of regular expressions")
+        for pattern in patterns:
+            try:
+                re.compile(pattern)
+            except re.error as exc:
+                raise ConfigError(f"{owner} pattern {pattern!r} is not a valid regular expression: {exc}") from exc
+        prompts.patterns = list(patterns)
+    for key in ("approve", "deny"):
+        answer = raw.get(key)
+        if answer is not None:
+            if not isinstance(answer, (str, int)) or isinstance(answer, bool) or "\n" in str(answer):
+                raise ConfigError(f"{owner} {key} must be the text to answer with")
+            setattr(prompts, key, str(answer))
+    timeout_secs = _optional_positive(raw.get("timeout_secs"), f"{owner} timeout_secs", int)
+    if timeout_secs is not None:
+        prompts.timeout_secs = int(timeout_secs)
+    return prompts
+
+
+def _parse_limits(agent_id: str, agent_type: AgentType, raw: object) -> Optional[ProcessLimits]:
+    """`limits` of a CLI agent: cpu_secs, memory_mb, open_files, and (with `cgroup: true`) cpu_percent."""
+    if raw is None:
+        return None
+    owner = f"limits for agent {agent_id}"
+    if agent_type not in CLI_AGENT_TYPES:
+        raise ConfigError(f"{owner} are only valid for claude, codex, and gemini agents (API agents: use sandbox)")
+    if not isinstance(raw, dict):
+        raise ConfigError(f"{owner} must be a mapping")
+    unknown = sorted(set(raw) - {"cpu_secs", "memory_mb", "open_files", "cpu_percent", "cgroup"})
+    if unknown:
+        raise ConfigError(f"{owner} has unsupported keys: {', '.join(unknown)}")
+    cgroup = raw.get("cgroup", False)
+    if not isinstance(cgroup, bool):
+        raise ConfigError(f"{owner} cgroup must be true or false")
+    if cgroup and not sys.platform.startswith("linux"):
+        raise ConfigError(f"{owner} cgroup needs Linux with systemd")
+    values = {}
+    for key in ("cpu_secs", "memory_mb", "open_files", "cpu_percent"):
+        value = _optional_positive(raw.get(key), f"{owner} {key}", int)
+        values[key] = int(value) if value is not None else None
+    if values["cpu_percent"] is not None and not cgroup:
+        raise ConfigError(f"{owner} cpu_percent needs cgroup: true")
+    return ProcessLimits(**values, cgroup=cgroup)
+
+
+def _parse_timeouts(owner: str, cfg: Dict[str, object]) -> Dict[str, Optional[int]]:
+    """`timeout_secs` and `hard_timeout_secs` of an agent or project."""
+    timeout_secs = _optional_positive(cfg.get("timeout_secs"), f"timeout_secs for {owner}", int)
+    hard_timeout_secs = _optional_positive(cfg.get("hard_timeout_secs"), f"hard_timeout_secs for {owner}", int)
+    if hard_timeout_secs is not None and (timeout_secs is None or hard_timeout_secs <= timeout_secs):
+        raise ConfigError(f"hard_timeout_secs for {owner} must be longer than its timeout_secs")
+    return {"timeout_secs": timeout_secs, "hard_timeout_secs": hard_timeout_secs}
+
+
+def _parse_mcp_servers(agent_id: str, raw: object) -> List[McpServer]:
+    if raw is None:
+        return []
+    if not isinstance(raw, dict):
+        raise ConfigError(f"mcp_servers for agent {agent_id} must be a mapping of server names")
+
+    servers = []
+    for name, cfg in raw.items():
+        if not isinstance(cfg, dict):
+            raise ConfigError(f"MCP server {name} for agent {agent_id} must be a mapping")
+        command = cfg.get("command")
+        url = cfg.get("url")
+        if bool(command) == bool(url):
+            raise ConfigError(f"MCP server {name} for agent {agent_id} needs exactly one of command or url")
+        if command is not None and (
+            not isinstance(command, list) or not all(isinstance(part, str) for part in command)
+        ):
+            raise ConfigError(f"command for MCP server {name} must be a list of strings")
+        env = cfg.get("env") or {}
+        if not isinstance(env, dict):
+            raise ConfigError(f"env for MCP server {name} must be a mapping")
+        servers.append(
+            McpServer(
+                name=str(name),
+                command=list(command or []),
+                env={str(k): str(v) for k, v in env.items()},
+                url=str(url) if url else None,
+            )
+        )
+    return servers
+
+
+def _parse_command_steps(owner: str, raw: object) -> List[CommandStep]:
+    if raw is None:
+        return []
+    if not isinstance(raw, list):
+        raise ConfigError(f"{owner} must be a list of shell commands")
+
+    steps = []
+    for item in raw:
+        if isinstance(item, str) and item.strip():
+            steps.append(CommandStep(run=item))
+            continue
+        if not isinstance(item, dict) or not isinstance(item.get("run"), str) or not item["run"].strip():
+            raise ConfigError(f"{owner} entries must be shell commands or mappings with a `run` command")
+        timeout_secs = item.get("timeout_secs")
+        if timeout_secs is not None and (
+            isinstance(timeout_secs, bool) or not isinstance(timeout_secs, int) or timeout_secs <= 0
+        ):
+            raise ConfigError(f"{owner} timeout_secs for `{item['run']}` must be a positive number of seconds")
+        steps.append(CommandStep(run=item["run"], timeout_secs=timeout_secs))
+    return steps
+
+
+# Mode names that mean: run the agent itself in a container of this runtime
+AGENT_CONTAINER_RUNTIMES = ("docker", "podman")
+# Like docker, with the image built from the repository's devcontainer.json (see `src/core/devcontainer.py`)
+DEVCONTAINER_MODE = "devcontainer"
+
+
+def _parse_sandbox(
+    owner: str, raw: object, *, allow_run_agent: bool = False, agent_type: Optional[AgentType] = None
+) -> SandboxConfig:
+    if raw is None:
+        return SandboxConfig()
+    if isinstance(raw, str):
+        raw = {"mode": raw}
+    if not isinstance(raw, dict):
+        raise ConfigError(f"{owner} sandbox must be a mode name or a mapping")
+
+    mode_name = str(raw.get("mode") or SandboxMode.PROCESS.value).lower()
+    runtime = str(raw.get("runtime") or "docker")
+    run_agent = raw.get("run_agent")
+    devcontainer = mode_name == DEVCONTAINER_MODE
+    if mode_name in AGENT_CONTAINER_RUNTIMES or devcontainer:
+        if not allow_run_agent:
+            raise ConfigError(f"{owner} sandbox mode {mode_name} is only available for projects; use container")
+        mode = SandboxMode.CONTAINER
+        runtime = runtime if devcontainer else mode_name
+        run_agent = True if run_agent is None else run_agent
+    else:
+        try:
+            mode = SandboxMode(mode_name)
+        except ValueError as exc:
+            project_modes = (*AGENT_CONTAINER_RUNTIMES, DEVCONTAINER_MODE) if allow_run_agent else ()
+            modes = [mode.value for mode in SandboxMode] + list(project_modes)
+            raise ConfigError(f"{owner} sandbox mode must be {', '.join(modes[:-1])}, or {modes[-1]}") from exc
+
+    image = raw.get("image")
+    if devcontainer and image is not None:
+        raise ConfigError(f"{owner} sandbox mode devcontainer takes its image from devcontainer.json; remove image")
+    if mode == SandboxMode.CONTAINER and not devcontainer and not (isinstance(image, str) and image.strip()):
+        raise ConfigError(f"{owner} sandbox mode {mode_name} requires an image")
+
+    if run_agent is None:
+        run_agent = False
+    elif not allow_run_agent:
+        raise ConfigError(f"{owner} sandbox run_agent is only available for projects")
+    elif not isinstance(run_agent, bool):
+        raise ConfigError(f"{owner} sandbox run_agent must be true or false")
+    if run_agent and mode != SandboxMode.CONTAINER:
+        raise ConfigError(f"{owner} sandbox run_agent requires mode docker, podman, devcontainer, or container")
+    if mode == SandboxMode.BUBBLEWRAP and not sys.platform.startswith("linux"):
+        raise ConfigError(f"{owner} sandbox mode bubblewrap is only available on Linux")
+    # A bubblewrapped CLI agent keeps its login and sessions, which it stores outside the project
+    default_writable = CLI_STATE_PATHS.get(agent_type.value, ()) if agent_type else ()
+    paths = {}
+    for key, default in (("read_only_paths", ()), ("writable_paths", default_writable)):
+        value = raw.get(key)
+        if value is not None and mode != SandboxMode.BUBBLEWRAP:
+            raise ConfigError(f"{owner} sandbox {key} requires mode bubblewrap")
+        if value is not None and (
+            not isinstance(value, list) or not all(isinstance(path, str) and path.strip() for path in value)
+        ):
+            raise ConfigError(f"{owner} sandbox {key} must be a list of paths")
+        paths[key] = list(default if value is None else value) if mode == SandboxMode.BUBBLEWRAP else []
+
+    limits = {}
+    for key in ("memory_mb", "cpu_secs"):
+        value = raw.get(key)
+        if value is not None and (isinstance(value, bool) or not isinstance(value, int) or value <= 0):
+            raise ConfigError(f"{owner} sandbox {key} must be a positive integer")
+        limits[key] = value
+
+    # Agent CLIs call their model's API, so a sandbox running one is online unless told otherwise
+    network = raw.get("network", run_agent or (mode == SandboxMode.BUBBLEWRAP and agent_type in CLI_AGENT_TYPES))
+    if not isinstance(network, bool):
+        raise ConfigError(f"{owner} sandbox network must be true or false")
+
+    env_passthrough = raw.get("env_passthrough") or []
+    if not isinstance(env_passthrough, list) or not all(isinstance(name, str) and name for name in env_passthrough):
+        raise ConfigError(f"{owner} sandbox env_passthrough must be a list of environment variable names")
+
+    return SandboxConfig(
+        mode=mode,
+        image=image.strip() if isinstance(image, str) else None,
+        runtime=runtime,
+        network=network,
+        env_passthrough=list(env_passthrough),
+        run_agent=run_agent,
+        devcontainer=devcontainer,
+        **paths,
+        **limits,
+    )
+
+
+PRICING_RATE_KEYS = ("per_run_usd", "input_per_1k_tokens_usd", "output_per_1k_tokens_usd")
+
+
+def _parse_capabilities(agent_id: str, raw: object) -> AgentCapabilities:
+    if raw is None:
+        return AgentCapabilities()
+    if not isinstance(raw, dict):
+        raise ConfigError(f"capabilities for agent {agent_id} must be a mapping")
+
+    supports_images = raw.get("supports_images", False)
+    if not isinstance(supports_images, bool):
+        raise ConfigError(f"capabilities.supports_images for agent {agent_id} must be true or false")
+
+    max_context = raw.get("max_context")
+    if max_context is not None and (isinstance(max_context, bool) or not isinstance(max_context, int) or max_context <= 0):
+        raise ConfigError(f"capabilities.max_context for agent {agent_id} must be a positive number of tokens")
+
+    try:
+        cost_tier = CostTier(str(raw.get("cost_tier") or CostTier.MEDIUM.value).lower())
+    except ValueError as exc:
+        raise ConfigError(f"capabilities.cost_tier for agent {agent_id} must be low, medium, or high") from exc
+
+    return AgentCapabilities(
+        supports_images=supports_images,
+        max_context=max_context,
+        cost_tier=cost_tier,
+        languages=_parse_languages(f"capabilities.languages for agent {agent_id}", raw.get("languages")),
+    )
+
+
+def _parse_languages(owner: str, raw: object) -> List[str]:
+    if raw is None:
+        return []
+    if not isinstance(raw, list) or not all(isinstance(item, str) and item.strip() for item in raw):
+        raise ConfigError(f"{owner} must be a list of language names")
+    return [item.strip().lower() for item in raw]
+
+
+def _parse_pricing(agent_id: str, raw) -> AgentPricing | None:
+    if raw is None:
+        return None
+    if not isinstance(raw, dict):
+        raise ConfigError(f"pricing for agent {agent_id} must be a mapping")
+
+    base = AgentPricing(**_parse_pricing_rates(agent_id, raw, defaults={}))
+
+    overrides_raw = raw.get("models") or {}
+    if not isinstance(overrides_raw, dict):
+        raise ConfigError(f"pricing.models for agent {agent_id} must be a mapping")
+    defaults = {key: getattr(base, key) for key in PRICING_RATE_KEYS}
+    for model_name, model_raw in overrides_raw.items():
+        if not isinstance(model_raw, dict):
+            raise ConfigError(f"pricing for model {model_name} of agent {agent_id} must be a mapping")
+        base.model_overrides[str(model_name)] = AgentPricing(
+            **_parse_pricing_rates(agent_id, model_raw, defaults=defaults)
+        )
+    return base
+
+
+def _parse_pricing_rates(agent_id: str, raw: dict, defaults: Dict[str, float]) -> Dict[str, float]:
+    rates: Dict[str, float] = {}
+    for key in PRICING_RATE_KEYS:
+        value = raw.get(key, defaults.get(key, 0.0))
+        try:
+            rate = float(value)
+        except (TypeError, ValueError) as exc:
+            raise ConfigError(f"pricing.{key} for agent {agent_id} must be a number") from exc
+        if rate < 0:
+            raise ConfigError(f"pricing.{key} for agent {agent_id} cannot be negative")
+        rates[key] = rate
+    return rates
+
+
 def _select_agents(all_agents: Dict[str, Agent]) -> Dict[str, Agent]:
     raw = (os.getenv("REMOTE_CODER_AGENTS") or "").strip()
     if not raw:
@@ -254,8 +1358,15 @@ def _parse_working_dir_mode(value) -> Tuple[WorkingDirMode, Path | None]:
         if lowered == "project":
             return WorkingDirMode.PROJECT, None
         if lowered.startswith("fixed:"):
-            fixed_path = Path(value.split(":", 1)[1]).expanduser().resolve()
-            return WorkingDirMode.FIXED, fixed_path
+            return WorkingDirMode.FIXED, _fixed_working_dir(value.split(":", 1)[1])
     if isinstance(value, dict) and "fixed" in value:
-        return WorkingDirMode.FIXED, Path(value["fixed"]).expanduser().resolve()
+        return WorkingDirMode.FIXED, _fixed_working_dir(str(value["fixed"]))
     raise ConfigError(f"Unsupported working_dir_mode: {value}")
+
+
+def _fixed_working_dir(raw: str) -> Path:
+    """The directory of `fixed:<path>`; `~`, `$VAR` (`%VAR%` on Windows), and Git Bash drives are expanded."""
+    path = native_path(os.path.expandvars(raw.strip().strip('"')))
+    if not path:
+        raise ConfigError("working_dir_mode fixed needs a path")
+    return Path(path).expanduser().resolve()
@@ -0,0 +1,69 @@
+"""Ask the thread about an agent's confirmation prompts and wait for the answer.
+
+`ConfirmationBroker.ask` posts the prompt with Approve and Deny buttons via
+the chat adapter's optional `send_confirmation(channel, thread_ts, text, key)`
+and waits until `answer(key, approved)` is called for the button clicked, or
+the agent's `timeout_secs` passes. Chats without buttons (and timeouts) get a
+note in the thread, and the prompt is denied.
+"""
+
+from __future__ import annotations
+
+import asyncio
+import logging
+import uuid
+from typing import Awaitable, Callable, Dict, Optional
+
+LOGGER = logging.getLogger(__name__)
+
+# (channel, thread_ts, text, key) -> the posted message's ts, or None when the chat has no buttons
+SendConfirmationFn = Callable[[str, str, str, str], Awaitable[Optional[str]]]
+PROMPT_CHARS = 2500
+
+
+def format_confirmation(agent_id: str, question: str) -> str:
+    if len(question) > PROMPT_CHARS:
+        question = "…" + question[-(PROMPT_CHARS - 1) :]
+    return f"`{agent_id}` is waiting for an answer:\n```\n{question}\n```"
+
+
+class ConfirmationBroker:
+    """Pending confirmation prompts, keyed by the value of their buttons."""
+
+    def __init__(self, send_confirmation: SendConfirmationFn, send_message) -> None:
+        self._send_confirmation = send_confirmation
+        self._send_message = send_message
+        self._pending: Dict[str, asyncio.Future] = {}
+
+    async def ask(self, channel: str, thread_ts: str, agent_id: str, question: str, timeout_secs: float) -> bool:
+        """Whether the prompt was approved: False when denied, unanswered, or not askable here."""
+        key = uuid.uuid4().hex
+        future: asyncio.Future = asyncio.get_running_loop().create_future()
+        self._pending[key] = future
+        try:
+            posted = await self._send_confirmation(channel, thread_ts, format_confirmation(agent_id, question), key)
+            if posted is None:
+                await self._send_message(
+                    channel,
+                    thread_ts,
+                    f"`{agent_id}` asked for confirmation, but this chat has no buttons to answer with, so it was "
+                    f"denied:\n```\n{question.splitlines()[-1]}\n```",
+                )
+                return False
+            try:
+                return bool(await asyncio.wait_for(future, timeout_secs))
+            except asyncio.TimeoutError:
+                await self._send_message(
+                    channel, thread_ts, f"No answer for `{agent_id}` within {timeout_secs:g}s; it was denied."
+                )
+                return False
+        finally:
+            self._pending.pop(key, None)
+
+    def answer(self, key: str, approved: bool) -> bool:
+        """Resolve a pending prompt; False when it is no longer waiting (answered, timed out, or run over)."""
+        future = self._pending.pop(key, None)
+        if future is None or future.done():
+            return False
+        future.set_result(approved)
+        return True
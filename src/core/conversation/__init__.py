@@ -1,13 +1,18 @@
 """Conversation management - session state, context, and summarization."""
 
 from .classifier import InteractionClassifier
+from .compressor import TranscriptCompressor
 from .context_builder import ContextBuilder
+from .intent import MessageIntent, classify_message
 from .session_manager import SessionManager
 from .summarizer import ConversationSummarizer
 
 __all__ = [
     "InteractionClassifier",
     "ContextBuilder",
+    "MessageIntent",
+    "classify_message",
     "SessionManager",
     "ConversationSummarizer",
+    "TranscriptCompressor",
 ]
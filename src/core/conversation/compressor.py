@@ -0,0 +1,125 @@
+"""Compress older conversation interactions into a compact brief.
+
+One-shot agents receive the conversation as text on every run, so long
+sessions would otherwise resend every prior message. Interactions that fall
+out of the detailed window are reduced to three sections:
+
+- Decisions: choices and constraints stated by the user or the agent
+- Current task state: the latest ask in the compressed span and what was done
+- Outstanding todos: follow-ups and problems that were left open
+
+Extraction is rule-based and deterministic: the same interactions always
+compress to the same text, so a session's prompts stay reproducible.
+"""
+
+from __future__ import annotations
+
+import re
+from typing import TYPE_CHECKING, List, Sequence
+
+from .summarizer import ConversationSummarizer
+
+if TYPE_CHECKING:
+    from src.core.models import ConversationInteraction
+
+MAX_DECISIONS = 8
+MAX_TODOS = 8
+MAX_ITEM_LENGTH = 160
+
+DECISION_PATTERN = re.compile(
+    r"\b(?:decided|decision|let'?s (?:use|go|keep|stick)|go(?:ing)? with|went with|chose|choose"
+    r"|instead of|switch(?:ed)? to|stick with|agreed|prefer|don'?t|do not|never|always|must|should not"
+    r"|shouldn'?t|make sure)\b",
+    re.IGNORECASE,
+)
+TODO_PATTERN = re.compile(
+    r"\b(?:todo|to-do|next steps?|still (?:need|needs|to)|remaining|not yet|follow[- ]up|left to"
+    r"|pending|haven'?t|didn'?t|couldn'?t|unable to|still fail\w*|tests? fail\w*|later)\b",
+    re.IGNORECASE,
+)
+UNCHECKED_BOX = re.compile(r"^[-*]\s*\[ \]\s*")
+LIST_MARKER = re.compile(r"^(?:[-*•]|\d+[.)])\s+")
+SENTENCE_END = re.compile(r"(?<=[.!?])\s+")
+
+
+class TranscriptCompressor:
+    """Reduces interactions to decisions, current task state, and outstanding todos."""
+
+    @staticmethod
+    def compress_interactions(interactions: Sequence[ConversationInteraction]) -> str:
+        """
+        Compress interactions into a brief with one section per kind of fact.
+
+        Items appear once, ordered by their latest mention; sections with
+        nothing to report are left out.
+
+        Args:
+            interactions: Interactions to compress, oldest first
+
+        Returns:
+            The compressed brief (empty if there are no interactions)
+        """
+        if not interactions:
+            return ""
+
+        decisions: List[str] = []
+        todos: List[str] = []
+        for interaction in interactions:
+            for message in (interaction.user_message.content, interaction.agent_message.content):
+                for sentence in TranscriptCompressor._sentences(message):
+                    if UNCHECKED_BOX.match(sentence):
+                        TranscriptCompressor._add(todos, UNCHECKED_BOX.sub("", sentence))
+                    elif TODO_PATTERN.search(sentence):
+                        TranscriptCompressor._add(todos, sentence)
+                    elif DECISION_PATTERN.search(sentence):
+                        TranscriptCompressor._add(decisions, sentence)
+
+        lines = []
+        if decisions:
+            lines.append("Decisions:")
+            lines.extend(f"- {item}" for item in decisions[-MAX_DECISIONS:])
+        lines.append("Current task state:")
+        lines.extend(f"- {item}" for item in TranscriptCompressor._task_state(interactions[-1]))
+        if todos:
+            lines.append("Outstanding todos:")
+            lines.extend(f"- {item}" for item in todos[-MAX_TODOS:])
+        return "\n".join(lines)
+
+    @staticmethod
+    def _task_state(interaction: ConversationInteraction) -> List[str]:
+        ask = ConversationSummarizer._extract_details(interaction.user_message.content)
+        done = ConversationSummarizer._extract_actions(interaction.agent_message.content)
+        state = []
+        if ask:
+            state.append(f"Last ask: {ask}")
+        if done:
+            state.append(f"Last result: {done}")
+        return state or ["No details recorded."]
+
+    @staticmethod
+    def _sentences(text: str) -> List[str]:
+        """Split text into list items and sentences, without list markers."""
+        sentences = []
+        for line in (text or "").splitlines():
+            line = line.strip()
+            if not line:
+                continue
+            if UNCHECKED_BOX.match(line):
+                sentences.append(line)
+                continue
+            line = LIST_MARKER.sub("", line)
+            sentences.extend(part.strip() for part in SENTENCE_END.split(line) if part.strip())
+        return sentences
+
+    @staticmethod
+    def _add(items: List[str], sentence: str) -> None:
+        item = " ".join(sentence.split())
+        if len(item) > MAX_ITEM_LENGTH:
+            item = item[:MAX_ITEM_LENGTH].rsplit(" ", 1)[0] + "..."
+        key = item.lower()
+        # A repeated item moves to its latest mention so the most recent ones survive the cap
+        for index, existing in enumerate(items):
+            if existing.lower() == key:
+                del items[index]
+                break
+        items.append(item)
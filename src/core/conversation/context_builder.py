@@ -5,11 +5,18 @@ from __future__ import annotations
 import logging
 from typing import TYPE_CHECKING
 
+from .compressor import TranscriptCompressor
+
 if TYPE_CHECKING:
     from src.core.models import ConversationInteraction
 
 LOGGER = logging.getLogger(__name__)
 
+# Interactions after the summary are sent in detail up to these limits; older
+# ones are compressed (see `TranscriptCompressor`)
+MAX_DETAILED_INTERACTIONS = 10
+MAX_DETAILED_CHARS = 16_000
+
 
 class ContextBuilder:
     """Builds formatted context strings for agent task inclusion."""
@@ -18,19 +25,26 @@ class ContextBuilder:
     def build_context_for_agent(
         interactions: list[ConversationInteraction],
         summary: str | None = None,
-        summarized_count: int = 0
+        summarized_count: int = 0,
+        compress: bool = True,
     ) -> str:
         """
         Build the context section to prepend to agent task.
 
-        If there's a summary and summarized_count > 0, includes the summary
-        plus the most recent interactions in detail. Otherwise, shows all
-        interactions chronologically.
+        If there's a summary, includes it plus the interactions after the
+        summarized ones in detail. Otherwise, shows all interactions
+        chronologically. `summarized_count` can drop to 0 while a summary
+        exists once the summarized interactions were trimmed from memory.
+
+        With `compress`, only the most recent interactions within
+        MAX_DETAILED_INTERACTIONS and MAX_DETAILED_CHARS stay in detail; the
+        older unsummarized ones are compressed into the summary section.
 
         Args:
             interactions: List of ConversationInteraction objects
             summary: Optional summary of early interactions
             summarized_count: How many interactions were summarized (0 if no summary)
+            compress: Whether to compress interactions beyond the detailed window
 
         Returns:
             Formatted context string ready to include in task_text
@@ -38,13 +52,46 @@ class ContextBuilder:
         if not interactions:
             return ""
 
-        if summary and summarized_count > 0:
+        if compress:
+            start = ContextBuilder._detailed_start(interactions, summarized_count)
+            if start > summarized_count:
+                compressed = TranscriptCompressor.compress_interactions(interactions[summarized_count:start])
+                LOGGER.debug("Compressed %d interaction(s) older than the detailed window", start - summarized_count)
+                summary = f"{summary}\n\n{compressed}" if summary else compressed
+                summarized_count = start
+
+        if summary:
             return ContextBuilder._build_with_summary(
                 interactions, summary, summarized_count
             )
         else:
             return ContextBuilder._build_without_summary(interactions)
 
+    @staticmethod
+    def _detailed_start(interactions: list[ConversationInteraction], summarized_count: int) -> int:
+        """
+        Index of the oldest interaction to send in detail.
+
+        The newest interaction is always kept in detail, however long.
+
+        Args:
+            interactions: Full list of interactions
+            summarized_count: How many interactions the summary already covers
+
+        Returns:
+            Index into `interactions`; everything before it is summarized or compressed
+        """
+        start = len(interactions)
+        chars = 0
+        while start > summarized_count:
+            interaction = interactions[start - 1]
+            chars += len(interaction.user_message.content) + len(interaction.agent_message.content)
+            kept = len(interactions) - start
+            if kept and (kept >= MAX_DETAILED_INTERACTIONS or chars > MAX_DETAILED_CHARS):
+                break
+            start -= 1
+        return start
+
     @staticmethod
     def _build_without_summary(interactions: list[ConversationInteraction]) -> str:
         """
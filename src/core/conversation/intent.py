@@ -0,0 +1,80 @@
+"""Guess what a non-command message in a session thread is for.
+
+Every message in a thread used to start an agent run, including "thanks!" or
+"lgtm 👍" after a run, which costs a full turn just to get a polite reply.
+`classify_message` sorts messages into new tasks, feedback on the last run,
+and chatter using word lists only, so it is instant and free. It errs on the
+side of running the agent: a message counts as chatter only when every word
+in it is an acknowledgement (or an emoji), and answers such as "ok" or
+"yes, go ahead" to a question the agent just asked are feedback.
+"""
+
+from __future__ import annotations
+
+import re
+import unicodedata
+from enum import Enum
+from typing import List, Optional
+
+# Acknowledgements that never need an agent run on their own
+ACK_WORDS = frozenset(
+    {
+        "ack", "amazing", "awesome", "brilliant", "cheers", "cool", "done", "excellent", "fantastic",
+        "fine", "good", "got", "great", "haha", "it", "k", "kk", "lgtm", "lol", "looks", "love", "lovely",
+        "merged", "much", "neat", "nice", "noted", "ok", "okay", "perfect", "shipit", "so", "sounds",
+        "sweet", "thank", "thanks", "thx", "ty", "tysm", "very", "wonderful", "works", "wow", "yay", "you",
+    }
+)
+# Words that confirm a proposal the agent asked about
+CONFIRM_WORDS = frozenset({"ahead", "do", "go", "k", "ok", "okay", "please", "pls", "sure", "yeah", "yep", "yes"})
+# How feedback on the last run tends to start
+FEEDBACK_OPENERS = frozenset(
+    {
+        "actually", "again", "almost", "also", "but", "close", "hmm", "instead", "it", "no", "nope", "not",
+        "now", "oops", "revert", "still", "that", "this", "undo", "wait", "why", "you",
+    }
+)
+FEEDBACK_PHRASES = (
+    "didn't", "doesn't", "did not", "does not", "broke", "broken", "try again", "you missed", "you forgot",
+    "wrong", "instead of", "not what", "revert", "undo", "last change", "your change", "same error",
+    "still fail",
+)
+# Longer messages are never taken for chatter
+MAX_CHATTER_WORDS = 6
+
+EMOJI_CODE = re.compile(r":[a-z0-9_+\-]+:")
+MENTION = re.compile(r"<[@#!][^>]*>")
+WORD = re.compile(r"[a-z0-9']+")
+
+
+class MessageIntent(str, Enum):
+    TASK = "task"  # A new request
+    FEEDBACK = "feedback"  # A correction or follow-up to the last run
+    CHATTER = "chatter"  # An acknowledgement that needs no run
+
+
+def _words(text: str) -> List[str]:
+    text = MENTION.sub(" ", text.lower())
+    text = EMOJI_CODE.sub(" ", text)
+    text = "".join(" " if unicodedata.category(char) in ("So", "Sk", "Cf", "Mn") else char for char in text)
+    return [word.strip("'") for word in WORD.findall(text) if word.strip("'")]
+
+
+def _asks_question(reply: str) -> bool:
+    lines = [line for line in reply.strip().splitlines() if line.strip()]
+    return bool(lines) and "?" in lines[-1]
+
+
+def classify_message(text: str, last_reply: Optional[str] = None) -> MessageIntent:
+    """Classify a message; `last_reply` is the agent's latest reply in the thread, None before its first run."""
+    if last_reply is None:
+        return MessageIntent.TASK
+    words = _words(text)
+    if _asks_question(last_reply) and words and any(word in CONFIRM_WORDS for word in words):
+        return MessageIntent.FEEDBACK
+    if "?" not in text and len(words) <= MAX_CHATTER_WORDS and all(word in ACK_WORDS for word in words):
+        return MessageIntent.CHATTER
+    lowered = text.lower()
+    if (words and words[0] in FEEDBACK_OPENERS) or any(phrase in lowered for phrase in FEEDBACK_PHRASES):
+        return MessageIntent.FEEDBACK
+    return MessageIntent.TASK
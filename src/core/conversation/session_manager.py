@@ -2,16 +2,31 @@
 
 from __future__ import annotations
 
+import contextlib
+import copy
 import logging
 from datetime import datetime, timedelta, timezone
 from threading import RLock
-from typing import TYPE_CHECKING, Any, Dict, Sequence, Tuple
+from typing import TYPE_CHECKING, Any, Callable, Collection, Dict, Iterator, List, Optional, Sequence, Set, Tuple
 from uuid import UUID
 
-from ..errors import SessionNotFound
+from ..errors import (
+    AgentNotAllowed,
+    InvalidStatusTransition,
+    ProjectLocked,
+    RemoteCoderError,
+    SessionMoveError,
+    SessionNotFound,
+    SessionRestoreError,
+)
+from ..events import ChecksFinished, EventBus, PrOpened, RunFinished, SessionCreated, SessionEnded
+from ..incident import INCIDENT_CONTEXT_KEY, IncidentWindow
+from ..preferences import UserPreferences
+from ..transcripts import RunRecord, TranscriptEntry, TranscriptKind, TranscriptStore
 
 if TYPE_CHECKING:
     from src.agent_adapters.base import AgentResult
+    from src.core.session_store import SessionStore
 from .classifier import InteractionClassifier
 from .summarizer import ConversationSummarizer
 from .context_builder import ContextBuilder
@@ -21,22 +36,82 @@ from ..models import (
     ConversationMessage,
     Project,
     PullRequestRef,
+    PullRequestRole,
     Session,
     SessionStatus,
 )
 
 LOGGER = logging.getLogger(__name__)
 
+_S = SessionStatus
+# Status -> the statuses a session may move to from it. Any open session can be paused, ended, or archived;
+# runs go queued -> running (-> awaiting approval or input -> running) -> active or failed.
+TRANSITIONS: Dict[SessionStatus, frozenset] = {
+    _S.ACTIVE: frozenset({_S.QUEUED, _S.RUNNING, _S.PAUSED, _S.ENDED, _S.ARCHIVED}),
+    _S.QUEUED: frozenset({_S.RUNNING, _S.ACTIVE, _S.PAUSED, _S.ENDED, _S.ARCHIVED}),
+    _S.RUNNING: frozenset(
+        {_S.ACTIVE, _S.FAILED, _S.AWAITING_APPROVAL, _S.AWAITING_INPUT, _S.QUEUED, _S.PAUSED, _S.ENDED, _S.ARCHIVED}
+    ),
+    _S.AWAITING_APPROVAL: frozenset({_S.RUNNING, _S.ACTIVE, _S.FAILED, _S.PAUSED, _S.ENDED, _S.ARCHIVED}),
+    _S.AWAITING_INPUT: frozenset({_S.RUNNING, _S.ACTIVE, _S.FAILED, _S.PAUSED, _S.ENDED, _S.ARCHIVED}),
+    _S.FAILED: frozenset({_S.QUEUED, _S.RUNNING, _S.ACTIVE, _S.PAUSED, _S.ENDED, _S.ARCHIVED}),
+    _S.PAUSED: frozenset({_S.ACTIVE, _S.ENDED, _S.ARCHIVED}),
+    _S.ENDED: frozenset({_S.ACTIVE, _S.ARCHIVED}),
+    _S.ARCHIVED: frozenset({_S.ACTIVE}),
+}
+
+
+def can_transition(current: SessionStatus, requested: SessionStatus) -> bool:
+    return requested == current or requested in TRANSITIONS[current]
+
+
+def _settled(session: Session) -> Session:
+    """A session loaded after a restart: runs do not survive one, so one left part way through is active again."""
+    if session.status.in_run:
+        session.status = SessionStatus.ACTIVE
+    return session
+
 
 class SessionManager:
-    """Thread-safe in-memory session store that tracks history."""
+    """Thread-safe in-memory session store that tracks history.
 
-    def __init__(self, history_limit: int = 20) -> None:
+    With a `store`, every change to a session, its thread, or its PRs is
+    written through to it, and the maps here act as its cache.
+    """
+
+    def __init__(
+        self,
+        history_limit: int = 20,
+        *,
+        max_interactions: int = 0,
+        summarization: bool = True,
+        transcript: TranscriptStore | None = None,
+        store: SessionStore | None = None,
+        events: EventBus | None = None,
+    ) -> None:
+        # Lifecycle events for the subsystems that follow sessions (see `src/core/events.py`)
+        self.events = events or EventBus()
         self._sessions: Dict[UUID, Session] = {}
         self._thread_index: Dict[Tuple[str, str], UUID] = {}
-        self._pr_refs: Dict[UUID, PullRequestRef] = {}
+        # Session id -> its PRs, in the order they were first recorded
+        self._pr_refs: Dict[UUID, List[PullRequestRef]] = {}
+        # PR number -> sessions with a PR of that number (in any repository), for GitHub events
+        self._pr_index: Dict[int, Set[UUID]] = {}
+        # A thread lock, not an asyncio one: cleanup passes call in from worker threads (`asyncio.to_thread`).
+        # It is never held across an `await`, and an exception inside a `with` block releases it.
         self._lock = RLock()
         self._history_limit = history_limit
+        # 0 keeps every interaction; otherwise the oldest ones are dropped (the summary stays)
+        self._max_interactions = max_interactions
+        self._summarization = summarization
+        self._transcript = transcript
+        self._store = store
+        # Project id -> month (`YYYY-MM`, daemon's local time) -> agent runs started, for `max_runs_per_month`
+        self._run_counts: Dict[str, Dict[str, int]] = {}
+        # Chat user id -> defaults for the sessions they start (`!prefs`)
+        self._user_preferences: Dict[str, UserPreferences] = {}
+        # Project id -> its incident window (`!incident`), kept until it is ended or found expired
+        self._incidents: Dict[str, IncidentWindow] = {}
 
     def create_session(
         self,
@@ -47,7 +122,14 @@ class SessionManager:
         agent_id: str,
         agent_type: AgentType,
         active_model: str | None = None,
+        read_only: bool = False,
     ) -> Session:
+        """Start a session for the thread.
+
+        Raises ProjectLocked when the project already has `max_active_sessions`
+        sessions on its checkout; `read_only` sessions (ask mode and incident
+        diagnosis) work in a checkout of their own and are not counted.
+        """
         session = Session(
             project_id=project.id,
             channel_id=channel_id,
@@ -58,17 +140,46 @@ class SessionManager:
             active_model=active_model,
         )
         with self._lock:
+            holders = self.checkout_holders(project.id)
+            if not read_only and project.max_active_sessions and len(holders) >= project.max_active_sessions:
+                raise ProjectLocked(project.id, holders)
             self._sessions[session.id] = session
             self._thread_index[(channel_id, thread_ts)] = session.id
+            self._persist(session)
+            self._persist_thread((channel_id, thread_ts))
         LOGGER.info("Session %s created for project %s", session.id, project.id)
+        self.events.publish(SessionCreated(session.id, project.id, channel_id, thread_ts))
         return session
 
+    def checkout_holders(self, project_id: str) -> list[Session]:
+        """The project's active and paused sessions that work in its checkout, most recently active first."""
+        from ..ask_mode import ASK_MODE_CONTEXT_KEY  # avoid circular import
+
+        with self._lock:
+            holders = [
+                session
+                for session in self._sessions.values()
+                if session.project_id == project_id
+                and (session.status.is_open or session.status == SessionStatus.PAUSED)
+                and not session.session_context.get(ASK_MODE_CONTEXT_KEY)
+                and not session.session_context.get(INCIDENT_CONTEXT_KEY)
+            ]
+        return sorted(holders, key=lambda session: session.last_activity_at, reverse=True)
+
     def get_session(self, session_id: UUID) -> Session:
         with self._lock:
             if session_id not in self._sessions:
                 raise SessionNotFound(session_id)
             return self._sessions[session_id]
 
+    def find_sessions(self, id_prefix: str) -> list[Session]:
+        """Sessions whose ID starts with `id_prefix` (case-insensitive), active or ended."""
+        prefix = id_prefix.strip().lower()
+        if not prefix:
+            return []
+        with self._lock:
+            return [session for session in self._sessions.values() if str(session.id).startswith(prefix)]
+
     def get_by_thread(self, channel_id: str, thread_ts: str) -> Session:
         key = (channel_id, thread_ts)
         with self._lock:
@@ -83,7 +194,12 @@ class SessionManager:
         agent_id: str,
         agent_type: AgentType,
         model: str | None = None,
+        *,
+        project: Project | None = None,
     ) -> None:
+        """Switch the session's agent. Raises AgentNotAllowed if `project` does not allow it."""
+        if project is not None and not project.allows_agent(agent_id):
+            raise AgentNotAllowed(agent_id)
         with self._lock:
             session = self._sessions.get(session_id)
             if not session:
@@ -93,6 +209,7 @@ class SessionManager:
             if model is not None:
                 session.active_model = model
             session.updated_at = datetime.now(timezone.utc)
+            self._persist(session)
 
     def append_user_message(self, session_id: UUID, text: str) -> None:
         self._append_message(session_id, role="user", content=text)
@@ -105,10 +222,70 @@ class SessionManager:
             session = self._sessions.get(session_id)
             if not session:
                 raise SessionNotFound(session_id)
-            session.conversation_history.append(ConversationMessage(role=role, content=content))
+            message = ConversationMessage(role=role, content=content)
+            session.conversation_history.append(message)
+            if self._transcript:
+                self._transcript.append(session_id, message)
             if len(session.conversation_history) > self._history_limit:
                 session.conversation_history = session.conversation_history[-self._history_limit :]
-            session.updated_at = datetime.now(timezone.utc)
+            session.updated_at = session.last_activity_at = datetime.now(timezone.utc)
+            self._persist(session)
+
+    def record_run(self, session_id: UUID, run: RunRecord) -> None:
+        """Add how an agent run went to the session's transcript; a failed run leaves the session failed."""
+        with self._lock:
+            session = self._sessions.get(session_id)
+            if session is None:
+                raise SessionNotFound(session_id)
+        if self._transcript:
+            self._transcript.record_run(session_id, run)
+        if not run.success and not run.cancelled:
+            self.set_run_state(session_id, SessionStatus.FAILED)
+        self.events.publish(RunFinished(session_id, session.project_id, run))
+
+    def record_checks(self, session_id: UUID, passed: bool) -> None:
+        """Note that the project's verify steps ran in the session, for its analytics (`ChecksFinished`)."""
+        with self._lock:
+            session = self._sessions.get(session_id)
+            if session is None:
+                raise SessionNotFound(session_id)
+        self.events.publish(ChecksFinished(session_id, session.project_id, passed))
+
+    @property
+    def keeps_transcripts(self) -> bool:
+        """Whether sessions are recorded in full in a transcript store, or only in memory."""
+        return self._transcript is not None
+
+    def transcript(self, session_id: UUID, kinds: Optional[Sequence[TranscriptKind]] = None) -> List[TranscriptEntry]:
+        """Everything recorded in the session, oldest first.
+
+        Without a transcript store only the messages still in memory are there.
+        """
+        with self._lock:
+            session = self._sessions.get(session_id)
+            if not session:
+                raise SessionNotFound(session_id)
+            history = list(session.conversation_history)
+        if self._transcript:
+            return self._transcript.read(session_id, kinds)
+        entries = [
+            TranscriptEntry(
+                TranscriptKind.PROMPT if message.role == "user" else TranscriptKind.REPLY,
+                message.timestamp,
+                content=message.content,
+            )
+            for message in history
+        ]
+        return [entry for entry in entries if kinds is None or entry.kind in kinds]
+
+    def record_activity(self, session_id: UUID) -> None:
+        """Note that someone wrote in the session's thread, e.g. a command that adds nothing to its history."""
+        with self._lock:
+            session = self._sessions.get(session_id)
+            if not session:
+                raise SessionNotFound(session_id)
+            session.last_activity_at = datetime.now(timezone.utc)
+            self._persist(session)
 
     def get_conversation_history(self, session_id: UUID) -> list[ConversationMessage]:
         with self._lock:
@@ -126,28 +303,345 @@ class SessionManager:
                 raise SessionNotFound(session_id)
             session.session_context.update(context_delta)
             session.updated_at = datetime.now(timezone.utc)
+            self._persist(session)
+
+    def rewind_last_run(self, session_id: UUID, prompt: str) -> None:
+        """Forget the most recent run of `prompt`: its messages and, if recorded, its interaction."""
+        with self._lock:
+            session = self._sessions.get(session_id)
+            if not session:
+                raise SessionNotFound(session_id)
+            history = session.conversation_history
+            for index in range(len(history) - 1, -1, -1):
+                if history[index].role == "user" and history[index].content == prompt:
+                    del history[index:]
+                    break
+            interactions = session.interactions
+            if interactions and not interactions[-1].is_summarized and interactions[-1].user_message.content == prompt:
+                interactions.pop()
+            session.updated_at = datetime.now(timezone.utc)
+            self._persist(session)
+
+    def record_run_cost(self, session_id: UUID, cost_usd: float) -> None:
+        """Add the cost of a finished run to the session's running total."""
+        with self._lock:
+            session = self._sessions.get(session_id)
+            if not session:
+                raise SessionNotFound(session_id)
+            session.estimated_cost_usd += cost_usd
+            session.updated_at = datetime.now(timezone.utc)
+            self._persist(session)
+
+    def record_project_run(self, project_id: str, when: Optional[datetime] = None) -> int:
+        """Count an agent run against the project's month; returns the month's runs so far."""
+        month = run_month(when)
+        with self._lock:
+            months = self._run_counts.setdefault(project_id, {})
+            months[month] = months.get(month, 0) + 1
+            return months[month]
+
+    def project_runs(self, project_id: str, when: Optional[datetime] = None) -> int:
+        """Agent runs the project started in the month of `when` (default: this month)."""
+        with self._lock:
+            return self._run_counts.get(project_id, {}).get(run_month(when), 0)
+
+    def export_run_counts(self) -> Dict[str, Dict[str, int]]:
+        with self._lock:
+            return {project_id: dict(months) for project_id, months in self._run_counts.items()}
+
+    def import_run_counts(self, counts: Dict[str, Dict[str, int]]) -> None:
+        """Restore exported run counts, replacing the ones of the same projects."""
+        with self._lock:
+            for project_id, months in counts.items():
+                self._run_counts[project_id] = dict(months)
+
+    def user_preferences(self, user_id: str) -> UserPreferences:
+        with self._lock:
+            return self._user_preferences.get(user_id) or UserPreferences()
+
+    def set_user_preferences(self, user_id: str, preferences: UserPreferences) -> None:
+        """Store `user_id`'s preferences; defaults are not kept."""
+        with self._lock:
+            if preferences.is_default:
+                self._user_preferences.pop(user_id, None)
+            else:
+                self._user_preferences[user_id] = preferences
+
+    def export_user_preferences(self) -> Dict[str, UserPreferences]:
+        with self._lock:
+            return dict(self._user_preferences)
+
+    def import_user_preferences(self, preferences: Dict[str, UserPreferences]) -> None:
+        """Restore exported preferences, replacing the ones of the same users."""
+        with self._lock:
+            self._user_preferences.update(preferences)
+
+    def start_incident(self, window: IncidentWindow) -> None:
+        with self._lock:
+            self._incidents[window.project_id] = window
+
+    def end_incident(self, project_id: str) -> Optional[IncidentWindow]:
+        """End the project's incident early; returns it, or None when there was none."""
+        with self._lock:
+            window = self._incidents.pop(project_id, None)
+            return window if window and window.is_active() else None
+
+    def incident_for(self, project_id: str, now: Optional[datetime] = None) -> Optional[IncidentWindow]:
+        """The project's incident window while it lasts; expired windows are dropped."""
+        with self._lock:
+            window = self._incidents.get(project_id)
+            if window and not window.is_active(now):
+                del self._incidents[project_id]
+                return None
+            return window
+
+    def active_incidents(self, now: Optional[datetime] = None) -> List[IncidentWindow]:
+        with self._lock:
+            return [window for project_id in list(self._incidents) if (window := self.incident_for(project_id, now))]
+
+    def export_incidents(self) -> Dict[str, IncidentWindow]:
+        with self._lock:
+            return dict(self._incidents)
+
+    def import_incidents(self, incidents: Dict[str, IncidentWindow]) -> None:
+        """Restore exported incident windows, replacing the ones of the same projects."""
+        with self._lock:
+            self._incidents.update(incidents)
 
     def update_status(self, session_id: UUID, status: SessionStatus) -> None:
+        """Move the session to `status`; raises InvalidStatusTransition when `TRANSITIONS` does not allow it."""
         with self._lock:
             session = self._sessions.get(session_id)
             if not session:
                 raise SessionNotFound(session_id)
+            if not can_transition(session.status, status):
+                raise InvalidStatusTransition(session_id, session.status, status)
+            ended = session.status.is_open and status in (SessionStatus.ENDED, SessionStatus.ARCHIVED)
+            session.status = status
+            session.updated_at = datetime.now(timezone.utc)
+            self._persist(session)
+        if ended:
+            self.events.publish(SessionEnded(session_id, session.project_id, status))
+
+    def set_run_state(
+        self, session_id: UUID, status: SessionStatus, *, expect: Optional[Collection[SessionStatus]] = None
+    ) -> bool:
+        """Move the session along a run (queued, running, ...) when it is open and, with `expect`, in one of those.
+
+        Returns whether it moved. Unlike `update_status` this never raises: a
+        session paused or ended while its run was going stays as it is.
+        """
+        with self._lock:
+            session = self._sessions.get(session_id)
+            if not session or not session.status.is_open or session.status == status:
+                return False
+            if expect is not None and session.status not in expect:
+                return False
+            if not can_transition(session.status, status):
+                LOGGER.debug("Session %s stays %s rather than going to %s", session_id, session.status, status)
+                return False
             session.status = status
             session.updated_at = datetime.now(timezone.utc)
+            self._persist(session)
+        return True
+
+    def finish_run(self, session_id: UUID) -> None:
+        """Make the session active again after a run, unless the run left it failed (or it was paused or ended)."""
+        with self._lock:
+            session = self._sessions.get(session_id)
+            if session and session.status.in_run:
+                self.set_run_state(session_id, SessionStatus.ACTIVE)
+
+    @contextlib.contextmanager
+    def awaiting(self, session_id: UUID, status: SessionStatus) -> Iterator[None]:
+        """Show a running session as waiting on its thread (`status`) for the block, then as running again."""
+        waiting = self.set_run_state(session_id, status, expect=(SessionStatus.RUNNING,))
+        try:
+            yield
+        finally:
+            if waiting:
+                self.set_run_state(session_id, SessionStatus.RUNNING, expect=(status,))
+
+    def session_statuses(self) -> Dict[UUID, SessionStatus]:
+        """The status of every session, active or not."""
+        with self._lock:
+            return {session_id: session.status for session_id, session in self._sessions.items()}
 
     def list_active(self) -> list[Session]:
         with self._lock:
-            return [s for s in self._sessions.values() if s.status == SessionStatus.ACTIVE]
+            return [s for s in self._sessions.values() if s.status.is_open]
 
     def cleanup_ended(self, older_than: timedelta) -> int:
+        """Archive sessions not updated within `older_than`; returns how many.
+
+        Archived sessions stay in the store with their context, PR refs, and
+        history, so `restore_session` can bring one back, but no longer own
+        their thread: a new message there starts a new session. Paused
+        sessions were parked on purpose and are left alone.
+        """
         cutoff = datetime.now(timezone.utc) - older_than
+        now = datetime.now(timezone.utc)
+        with self._lock:
+            stale = [
+                session
+                for session in self._sessions.values()
+                if session.status not in (SessionStatus.ARCHIVED, SessionStatus.PAUSED) and session.updated_at < cutoff
+            ]
+            # Sessions already ended were announced then
+            closing = [session for session in stale if session.status.is_open]
+            for session in stale:
+                session.status = SessionStatus.ARCHIVED
+                session.archived_at = now
+                if self._thread_index.get((session.channel_id, session.thread_ts)) == session.id:
+                    del self._thread_index[(session.channel_id, session.thread_ts)]
+                    self._persist_thread((session.channel_id, session.thread_ts))
+                self._persist(session)
+        for session in stale:
+            LOGGER.info("Archived session %s (last updated %s)", session.id, session.updated_at.isoformat())
+        for session in closing:
+            self.events.publish(SessionEnded(session.id, session.project_id, SessionStatus.ARCHIVED))
+        return len(stale)
+
+    def list_archived(self) -> list[Session]:
+        with self._lock:
+            return [s for s in self._sessions.values() if s.status == SessionStatus.ARCHIVED]
+
+    def forget_session(self, session_id: UUID) -> bool:
+        """Remove an archived session, with its PRs, from the manager and the store (once it is kept elsewhere).
+
+        Returns False, leaving it, when the session is gone or no longer archived (e.g. restored meanwhile).
+        """
+        with self._lock:
+            session = self._sessions.get(session_id)
+            if session is None or session.status != SessionStatus.ARCHIVED:
+                return False
+            del self._sessions[session_id]
+            self._set_pr_refs(session_id, [])
+            self._write_through(lambda store: store.delete_session(session_id), f"session {session_id}")
+        LOGGER.info("Removed archived session %s from the store", session_id)
+        return True
+
+    def expire_idle(
+        self, idle_for: timedelta, *, busy: Collection[str] = (), now: Optional[datetime] = None
+    ) -> List[Session]:
+        """End active sessions without activity within `idle_for`, except those in `busy` (ids); returns them."""
+        now = now or datetime.now(timezone.utc)
+        cutoff = now - idle_for
+        with self._lock:
+            idle = [
+                session
+                for session in self._sessions.values()
+                if session.status.is_open
+                and session.last_activity_at < cutoff
+                and str(session.id) not in busy
+            ]
+            for session in idle:
+                session.status = SessionStatus.ENDED
+                session.updated_at = now
+                self._persist(session)
+        for session in idle:
+            LOGGER.info("Ended idle session %s (last active %s)", session.id, session.last_activity_at.isoformat())
+            self.events.publish(SessionEnded(session.id, session.project_id, SessionStatus.ENDED))
+        return idle
+
+    def restore_session(self, session_id: UUID) -> Session:
+        """Make an archived or ended session active again in its thread.
+
+        Raises SessionNotFound, or SessionRestoreError when it is already active or its thread has
+        another active session.
+        """
+        with self._lock:
+            session = self._sessions.get(session_id)
+            if not session:
+                raise SessionNotFound(session_id)
+            if session.status.is_open:
+                raise SessionRestoreError(f"Session {session_id} is already active")
+            key = (session.channel_id, session.thread_ts)
+            holder_id = self._thread_index.get(key)
+            holder = self._sessions.get(holder_id) if holder_id else None
+            if holder and holder.id != session.id and holder.status.is_open:
+                raise SessionRestoreError(f"Its thread has another active session ({holder.id}); end that one first")
+            session.status = SessionStatus.ACTIVE
+            session.archived_at = None
+            session.updated_at = session.last_activity_at = datetime.now(timezone.utc)
+            self._thread_index[key] = session.id
+            self._persist(session)
+            self._persist_thread(key)
+        LOGGER.info("Restored session %s", session_id)
+        return session
+
+    def move_session(self, session_id: UUID, channel_id: str, thread_ts: str) -> Session:
+        """Bind the session to another thread (`!move`), history and all; its old thread no longer has a session.
+
+        Raises SessionNotFound, or SessionMoveError when the new thread already has a session.
+        """
+        key = (channel_id, thread_ts)
+        with self._lock:
+            session = self._sessions.get(session_id)
+            if not session:
+                raise SessionNotFound(session_id)
+            holder = self._thread_index.get(key)
+            if holder and holder != session_id:
+                raise SessionMoveError(f"Thread {thread_ts} already has session {holder}")
+            old_key = (session.channel_id, session.thread_ts)
+            if self._thread_index.get(old_key) == session_id:
+                del self._thread_index[old_key]
+                self._persist_thread(old_key)
+            session.channel_id, session.thread_ts = key
+            session.updated_at = datetime.now(timezone.utc)
+            self._thread_index[key] = session_id
+            self._persist(session)
+            self._persist_thread(key)
+        LOGGER.info("Moved session %s to thread %s in %s", session_id, thread_ts, channel_id)
+        return session
+
+    def export_sessions(self) -> Tuple[list[Session], list[PullRequestRef]]:
+        """Return copies of all sessions and PR refs, e.g. for persisting them."""
+        with self._lock:
+            return [copy.deepcopy(session) for session in self._sessions.values()], [
+                copy.deepcopy(pr_ref) for refs in self._pr_refs.values() for pr_ref in refs
+            ]
+
+    def import_sessions(self, sessions: Sequence[Session], pr_refs: Sequence[PullRequestRef]) -> None:
+        """Add previously exported sessions, replacing any with the same id or thread (archived ones have none)."""
+        with self._lock:
+            for session in sessions:
+                self._sessions[session.id] = _settled(session)
+                self._persist(session)
+                key = (session.channel_id, session.thread_ts)
+                if session.status == SessionStatus.ARCHIVED:
+                    # Kept for restoring, without its thread
+                    if self._thread_index.get(key) == session.id:
+                        del self._thread_index[key]
+                        self._persist_thread(key)
+                    continue
+                previous = self._thread_index.get(key)
+                if previous and previous != session.id:
+                    self._sessions.pop(previous, None)
+                    self._set_pr_refs(previous, [])
+                    self._write_through(lambda store: store.delete_session(previous), f"session {previous}")
+                self._thread_index[key] = session.id
+                self._persist_thread(key)
+            imported = {session.id for session in sessions}
+            for session_id in imported:
+                self._set_pr_refs(session_id, [])
+                self._persist_pr_refs(session_id)
+            for pr_ref in pr_refs:
+                if pr_ref.session_id in imported:
+                    self._store_pr_ref(pr_ref)
+
+    def load_from_store(self) -> int:
+        """Fill the cache from the store (at startup, before anything else changes it); returns the sessions loaded."""
+        if self._store is None:
+            return 0
+        stored = self._store.load()
         with self._lock:
-            to_remove = [sid for sid, session in self._sessions.items() if session.updated_at < cutoff]
-            for sid in to_remove:
-                self._sessions.pop(sid, None)
-                self._pr_refs.pop(sid, None)
-            self._thread_index = {k: v for k, v in self._thread_index.items() if v not in to_remove}
-        return len(to_remove)
+            for session in stored.sessions:
+                self._sessions[session.id] = _settled(session)
+            self._thread_index.update(stored.threads)
+            for session_id, refs in stored.pr_refs.items():
+                self._set_pr_refs(session_id, refs)
+        return len(stored.sessions)
 
     def clear_all(self) -> int:
         """Remove all sessions and associated references."""
@@ -156,6 +650,8 @@ class SessionManager:
             self._sessions.clear()
             self._thread_index.clear()
             self._pr_refs.clear()
+            self._pr_index.clear()
+            self._write_through(lambda store: store.clear(), "all sessions")
         return count
 
     def append_interaction(
@@ -189,8 +685,8 @@ class SessionManager:
             if not session:
                 raise SessionNotFound(session_id)
 
-            # Create interaction with 1-indexed number
-            interaction_number = len(session.interactions) + 1
+            # Create interaction with 1-indexed number (kept counting when old ones are trimmed)
+            interaction_number = session.interactions[-1].interaction_number + 1 if session.interactions else 1
             interaction = ConversationInteraction(
                 interaction_number=interaction_number,
                 user_message=user_message,
@@ -207,13 +703,20 @@ class SessionManager:
             )
 
             # Check if we should trigger summarization
-            if len(session.interactions) == 10:
+            if self._summarization and len(session.interactions) == 10:
                 LOGGER.info(
                     "Session %s reached 10 interactions, triggering summarization",
                     session_id
                 )
                 self._perform_summarization_locked(session)
 
+            if self._max_interactions and len(session.interactions) > self._max_interactions:
+                dropped = len(session.interactions) - self._max_interactions
+                dropped_summarized = sum(1 for item in session.interactions[:dropped] if item.is_summarized)
+                del session.interactions[:dropped]
+                session.summary_interaction_count -= dropped_summarized
+            self._persist(session)
+
     def _perform_summarization_locked(self, session: Session) -> None:
         """
         Perform summarization on a session (assumes lock is held).
@@ -266,7 +769,8 @@ class SessionManager:
 
             # Summarize if we have exactly 10 interactions and no summary yet
             return (
-                len(session.interactions) == 10
+                self._summarization
+                and len(session.interactions) == 10
                 and session.conversation_summary is None
             )
 
@@ -285,6 +789,7 @@ class SessionManager:
             if not session:
                 raise SessionNotFound(session_id)
             self._perform_summarization_locked(session)
+            self._persist(session)
 
     def get_context_for_agent(self, session_id: UUID) -> str:
         """
@@ -309,16 +814,163 @@ class SessionManager:
                 interactions=session.interactions,
                 summary=session.conversation_summary,
                 summarized_count=session.summary_interaction_count,
+                compress=self._summarization,
             )
 
             return context
 
     def set_pr_ref(self, pr_ref: PullRequestRef) -> None:
+        """Record a PR of the session, replacing the entry for the same PR.
+
+        A new primary PR also replaces the previous primary PR of the same repository.
+        """
         with self._lock:
-            self._pr_refs[pr_ref.session_id] = pr_ref
+            opened = all(
+                ref.number != pr_ref.number or not _same_repo(ref.repo, pr_ref.repo)
+                for ref in self._pr_refs.get(pr_ref.session_id, [])
+            )
+            self._store_pr_ref(pr_ref)
+        if opened:
+            self.events.publish(PrOpened(pr_ref.session_id, pr_ref.project_id, pr_ref.number, pr_ref.url))
+
+    def _store_pr_ref(self, pr_ref: PullRequestRef) -> None:
+        refs = [
+            ref
+            for ref in self._pr_refs.get(pr_ref.session_id, [])
+            if not (
+                _same_repo(ref.repo, pr_ref.repo)
+                and (ref.number == pr_ref.number or ref.role == pr_ref.role == PullRequestRole.PRIMARY)
+            )
+        ]
+        refs.append(pr_ref)
+        self._set_pr_refs(pr_ref.session_id, refs)
+        self._persist_pr_refs(pr_ref.session_id)
+
+    def _set_pr_refs(self, session_id: UUID, refs: List[PullRequestRef]) -> None:
+        """Replace the session's PRs in the cache and in `_pr_index`; an empty list forgets them."""
+        for pr_ref in self._pr_refs.pop(session_id, []):
+            holders = self._pr_index.get(pr_ref.number)
+            if holders is not None:
+                holders.discard(session_id)
+                if not holders:
+                    del self._pr_index[pr_ref.number]
+        if refs:
+            self._pr_refs[session_id] = list(refs)
+            for pr_ref in refs:
+                self._pr_index.setdefault(pr_ref.number, set()).add(session_id)
+
+    def get_pr_ref(
+        self, session_id: UUID, role: PullRequestRole = PullRequestRole.PRIMARY, repo: Optional[str] = None
+    ) -> PullRequestRef:
+        """The session's first PR with `role` (in `repo`, when given); raises SessionNotFound without one."""
+        for pr_ref in self.list_pr_refs(session_id, role=role, repo=repo):
+            return pr_ref
+        raise SessionNotFound(session_id)
+
+    def list_pr_refs(
+        self, session_id: UUID, role: Optional[PullRequestRole] = None, repo: Optional[str] = None
+    ) -> list[PullRequestRef]:
+        """The session's PRs in the order they were recorded, optionally only those with `role` or in `repo`."""
+        with self._lock:
+            return [
+                pr_ref
+                for pr_ref in self._pr_refs.get(session_id, [])
+                if (role is None or pr_ref.role == role) and (repo is None or _same_repo(pr_ref.repo, repo))
+            ]
+
+    def find_pr_refs(self, number: int, repo: Optional[str] = None) -> list[PullRequestRef]:
+        """Every session's entries for PR `number` (in `repo`, when given), e.g. to route a GitHub event to its thread.
 
-    def get_pr_ref(self, session_id: UUID) -> PullRequestRef:
+        Entries recorded without a repository are for their project's repository and match any `repo`.
+        """
+        with self._lock:
+            return [
+                pr_ref
+                for session_id in sorted(self._pr_index.get(number, ()), key=str)
+                for pr_ref in self._pr_refs.get(session_id, [])
+                if pr_ref.number == number and (repo is None or _same_repo(pr_ref.repo, repo))
+            ]
+
+    def clear_pr_ref(self, session_id: UUID, number: Optional[int] = None, repo: Optional[str] = None) -> None:
+        """Forget PR `number` of the session (in `repo`, when given), or all of its PRs without a number."""
         with self._lock:
-            if session_id not in self._pr_refs:
+            if number is None:
+                self._set_pr_refs(session_id, [])
+                self._persist_pr_refs(session_id)
+                return
+            refs = [
+                pr_ref
+                for pr_ref in self._pr_refs.get(session_id, [])
+                if not (pr_ref.number == number and (repo is None or _same_repo(pr_ref.repo, repo)))
+            ]
+            self._set_pr_refs(session_id, refs)
+            self._persist_pr_refs(session_id)
+
+    def set_agent_pinned(self, session_id: UUID, pinned: bool) -> None:
+        """Keep the session on its current agent instead of selecting one per request."""
+        with self._lock:
+            session = self._sessions.get(session_id)
+            if not session:
+                raise SessionNotFound(session_id)
+            session.agent_pinned = pinned
+            session.updated_at = datetime.now(timezone.utc)
+            self._persist(session)
+
+    def set_timezone(self, session_id: UUID, timezone_name: str) -> None:
+        """Remember the IANA timezone of the user invoking the session."""
+        with self._lock:
+            session = self._sessions.get(session_id)
+            if not session:
                 raise SessionNotFound(session_id)
-            return self._pr_refs[session_id]
+            session.timezone = timezone_name
+            self._persist(session)
+
+    def set_tags(self, session_id: UUID, tags: Sequence[str]) -> None:
+        """Replace the session's tags (`!tag`, `!untag`)."""
+        with self._lock:
+            session = self._sessions.get(session_id)
+            if not session:
+                raise SessionNotFound(session_id)
+            session.tags = list(tags)
+            session.updated_at = datetime.now(timezone.utc)
+            self._persist(session)
+
+    def set_publishing_paused(self, session_id: UUID, paused: bool) -> None:
+        """Pause or resume pushing the session's changes to GitHub."""
+        with self._lock:
+            session = self._sessions.get(session_id)
+            if not session:
+                raise SessionNotFound(session_id)
+            session.publishing_paused = paused
+            session.updated_at = datetime.now(timezone.utc)
+            self._persist(session)
+
+    def _persist(self, session: Session) -> None:
+        self._write_through(lambda store: store.save_session(session), f"session {session.id}")
+
+    def _persist_thread(self, key: Tuple[str, str]) -> None:
+        session_id = self._thread_index.get(key)
+        self._write_through(lambda store: store.save_thread(key[0], key[1], session_id), f"thread {key[1]}")
+
+    def _persist_pr_refs(self, session_id: UUID) -> None:
+        refs = list(self._pr_refs.get(session_id, []))
+        self._write_through(lambda store: store.save_pr_refs(session_id, refs), f"PRs of session {session_id}")
+
+    def _write_through(self, write: Callable[[SessionStore], None], what: str) -> None:
+        """Apply a change to the store; a failed write is logged and left to the next `state.json` save."""
+        if self._store is None:
+            return
+        try:
+            write(self._store)
+        except RemoteCoderError as exc:
+            LOGGER.warning("Could not store %s: %s", what, exc)
+
+
+def run_month(when: Optional[datetime] = None) -> str:
+    """The month a run counts against, `YYYY-MM` in the daemon's local time."""
+    return (when or datetime.now()).astimezone().strftime("%Y-%m")
+
+
+def _same_repo(left: Optional[str], right: Optional[str]) -> bool:
+    """Whether two PR repositories match; None stands for the project's repository."""
+    return left is None or right is None or left.lower() == right.lower()
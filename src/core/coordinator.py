@@ -0,0 +1,122 @@
+"""Runs shared by several agents, each in a role (`roles:` in projects.yaml).
+
+A project's `roles` name the agent for each step of its runs:
+
+- `planner` writes the plan, in the pipeline's `plan` step (added first when
+  the pipeline does not list it);
+- `coder` makes the changes, in the `edit` step. Without one, the
+  session's agent (`!use`) does;
+- `reviewer` critiques the run's diff in the `review` step (added before
+  `pr` when the pipeline does not list it). The review is posted to the
+  thread and kept in the session's history, so a follow-up can ask for it
+  to be addressed. It never stops the run.
+
+The thread is told who plays each role when the run starts and as each step
+begins. The session's model applies to the session's agent; the other
+agents use their model for the project. Sessions in ask mode run the
+session's agent alone.
+"""
+
+from __future__ import annotations
+
+from dataclasses import dataclass
+from typing import List, Mapping, Optional
+
+from .errors import AgentNotFound
+from .models import Agent, Project, Session
+
+PLANNER = "planner"
+CODER = "coder"
+REVIEWER = "reviewer"
+ROLES = (PLANNER, CODER, REVIEWER)
+
+# Characters of the diff the reviewer is given; the rest is cut
+MAX_REVIEW_DIFF_CHARS = 50_000
+
+_STEP_NOTES = {
+    PLANNER: "`{agent}` (planner) is writing a plan.",
+    CODER: "`{agent}` (coder) is making the changes.",
+    REVIEWER: "`{agent}` (reviewer) is reviewing the changes.",
+}
+
+REVIEW_TASK = """You are reviewing changes another agent made in this repository. Do not edit any files.
+
+Point out bugs, missing cases, risky changes, and anything that does not do what the request asked, with file \
+paths and lines. If the changes look right, say so in a sentence. Keep it short: the review is posted to a chat \
+thread, and the author may be asked to address it.
+
+Request:
+{request}
+{plan}
+Changes:
+```diff
+{diff}
+```
+"""
+
+
+@dataclass(frozen=True)
+class RoleCast:
+    """The agents playing the roles of one run."""
+
+    planner: Agent
+    coder: Agent
+    reviewer: Optional[Agent] = None
+    multi_agent: bool = False  # The project sets `roles`, so the steps are announced
+
+    @property
+    def agents(self) -> List[Agent]:
+        """Each agent of the run once, in role order."""
+        agents: List[Agent] = []
+        for agent in (self.planner, self.coder, self.reviewer):
+            if agent is not None and all(agent.id != other.id for other in agents):
+                agents.append(agent)
+        return agents
+
+    def describe(self) -> str:
+        """E.g. "planner `claude`, coder `codex`, and reviewer `gemini`"."""
+        planner, coder = f"planner `{self.planner.id}`", f"coder `{self.coder.id}`"
+        if self.reviewer is None:
+            return f"{planner} and {coder}"
+        return f"{planner}, {coder}, and reviewer `{self.reviewer.id}`"
+
+
+def cast_for(project: Project, session: Session, agents: Mapping[str, Agent]) -> RoleCast:
+    """The agents for a run of `session`; raises AgentNotFound when a role names an agent that is not configured."""
+
+    def agent(agent_id: str) -> Agent:
+        if agent_id not in agents:
+            raise AgentNotFound(agent_id)
+        return agents[agent_id]
+
+    roles = project.roles
+    coder = agent(roles.coder or session.active_agent_id)
+    return RoleCast(
+        planner=agent(roles.planner) if roles.planner else coder,
+        coder=coder,
+        reviewer=agent(roles.reviewer) if roles.reviewer else None,
+        multi_agent=roles.is_set,
+    )
+
+
+def role_model(agent: Agent, project: Project, session: Session) -> Optional[str]:
+    """The session's model for the session's agent, otherwise the agent's model for the project."""
+    if agent.id == session.active_agent_id:
+        return session.active_model
+    return project.model_for(agent)
+
+
+def step_note(role: str, agent: Agent) -> str:
+    return _STEP_NOTES[role].format(agent=agent.id)
+
+
+def review_task(request: str, diff_text: str, plan: Optional[str] = None) -> str:
+    """The reviewer's prompt: the request, the plan it was made from (if any), and the diff."""
+    if len(diff_text) > MAX_REVIEW_DIFF_CHARS:
+        diff_text = diff_text[:MAX_REVIEW_DIFF_CHARS] + "\n... (the rest of the diff was cut)"
+    plan_text = f"\nPlan it was made from:\n{plan}\n" if plan else ""
+    return REVIEW_TASK.format(request=request, plan=plan_text, diff=diff_text.rstrip("\n"))
+
+
+def format_review(agent: Agent, review: str) -> str:
+    return f"Review from `{agent.id}`:\n{review.strip()}"
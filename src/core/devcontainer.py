@@ -0,0 +1,277 @@
+"""Run a project's commands and agent in the environment its devcontainer.json describes.
+
+`sandbox: devcontainer` in projects.yaml works like `sandbox: docker`, except
+that the image comes from the repository's `.devcontainer/devcontainer.json`
+(or `.devcontainer.json`), so toolchains match what the repo expects. The
+file's `image`, or `build` (`dockerfile`, `context`, `args`), is used as the
+base. The create-time lifecycle commands (`onCreateCommand`,
+`updateContentCommand`, `postCreateCommand`) run once in a container of that
+base with the checkout mounted, and the result is committed as the image
+every run starts from. The image is tagged with a digest of devcontainer.json
+and the Dockerfile, so it is reused until one of them changes; changes to
+other files the Dockerfile copies need the image removed to take effect.
+`containerEnv` is set in every container. Runs use throwaway containers, so
+start-time commands (`postStartCommand`, `postAttachCommand`) and
+`remoteUser` are ignored: commands run as the daemon's user, as in the other
+container modes. Docker Compose setups are not supported.
+"""
+
+from __future__ import annotations
+
+import asyncio
+import dataclasses
+import hashlib
+import json
+import logging
+import shlex
+import uuid
+from dataclasses import dataclass, field
+from pathlib import Path
+from typing import Awaitable, Callable, Dict, List, Optional, Sequence
+
+from .jobs import JOBS
+from .models import SandboxConfig
+
+LOGGER = logging.getLogger(__name__)
+
+DEVCONTAINER_FILES = (".devcontainer/devcontainer.json", ".devcontainer.json")
+IMAGE_REPOSITORY = "remote-coder-devcontainer"
+# Lifecycle commands baked into the image, in the order the devcontainer spec runs them
+CREATE_COMMANDS = ("onCreateCommand", "updateContentCommand", "postCreateCommand")
+BUILD_TIMEOUT_SECS = 1800
+# Build output kept for the error message when a build fails
+ERROR_OUTPUT_CHARS = 1500
+
+# Told (with a message for the thread) when an image has to be built before the run
+BuildNoticeFn = Callable[[str], Awaitable[None]]
+
+_BUILD_LOCKS: Dict[str, asyncio.Lock] = {}
+
+
+class DevcontainerError(RuntimeError):
+    """The devcontainer.json is missing or unusable, or building its image failed."""
+
+
+@dataclass(frozen=True)
+class Devcontainer:
+    """What Remote Coder uses from a devcontainer.json."""
+
+    path: Path
+    image: Optional[str] = None
+    dockerfile: Optional[Path] = None
+    context: Optional[Path] = None
+    build_args: Dict[str, str] = field(default_factory=dict)
+    env: Dict[str, str] = field(default_factory=dict)
+    create_commands: List[str] = field(default_factory=list)
+    digest: str = ""
+
+    @property
+    def needs_build(self) -> bool:
+        return self.dockerfile is not None or bool(self.create_commands)
+
+    @property
+    def tag(self) -> str:
+        return f"{IMAGE_REPOSITORY}:{self.digest[:16]}"
+
+
+def find_devcontainer(repo_path: Path) -> Optional[Path]:
+    for name in DEVCONTAINER_FILES:
+        path = repo_path / name
+        if path.is_file():
+            return path
+    return None
+
+
+def load_devcontainer(repo_path: Path) -> Devcontainer:
+    path = find_devcontainer(repo_path)
+    if path is None:
+        raise DevcontainerError(f"`{repo_path}` has no {' or '.join(DEVCONTAINER_FILES)}")
+    text = path.read_text(encoding="utf-8")
+    try:
+        data = json.loads(strip_jsonc(text))
+    except json.JSONDecodeError as exc:
+        raise DevcontainerError(f"{path.name} is not valid JSON: {exc}") from exc
+    if not isinstance(data, dict):
+        raise DevcontainerError(f"{path.name} must be a JSON object")
+    if "dockerComposeFile" in data:
+        raise DevcontainerError("Docker Compose devcontainers are not supported; use `image` or `build`")
+
+    build = data.get("build") or {}
+    if not isinstance(build, dict):
+        raise DevcontainerError("`build` in devcontainer.json must be an object")
+    dockerfile_name = build.get("dockerfile") or data.get("dockerFile")
+    image = data.get("image")
+    if not dockerfile_name and not (isinstance(image, str) and image.strip()):
+        raise DevcontainerError("devcontainer.json needs an `image` or a `build.dockerfile`")
+    digest = hashlib.sha256(text.encode("utf-8"))
+    dockerfile = context = None
+    if dockerfile_name:
+        # Paths in devcontainer.json are relative to the file itself
+        dockerfile = (path.parent / str(dockerfile_name)).resolve()
+        if not dockerfile.is_file():
+            raise DevcontainerError(f"The devcontainer's Dockerfile `{dockerfile_name}` does not exist")
+        context = (path.parent / str(build.get("context") or data.get("context") or ".")).resolve()
+        digest.update(dockerfile.read_bytes())
+
+    return Devcontainer(
+        path=path,
+        image=None if dockerfile else str(image).strip(),
+        dockerfile=dockerfile,
+        context=context,
+        build_args=_string_map("build.args", build.get("args")),
+        env=_string_map("containerEnv", data.get("containerEnv")),
+        create_commands=[command for key in CREATE_COMMANDS for command in _shell_commands(key, data.get(key))],
+        digest=digest.hexdigest(),
+    )
+
+
+async def resolve_devcontainer(
+    sandbox: SandboxConfig, repo_path: Path, on_build: Optional[BuildNoticeFn] = None
+) -> SandboxConfig:
+    """`sandbox` with the image (built first, if needed) and environment of the repo's devcontainer."""
+    devcontainer = await asyncio.to_thread(load_devcontainer, repo_path)
+    image = devcontainer.image
+    if devcontainer.needs_build:
+        image = devcontainer.tag
+        lock = _BUILD_LOCKS.setdefault(image, asyncio.Lock())
+        async with lock:
+            if not await _image_exists(sandbox.runtime, image):
+                if on_build:
+                    await on_build(
+                        "Building the devcontainer image (first run, or devcontainer.json changed); "
+                        "this can take a few minutes."
+                    )
+                await _build_image(sandbox.runtime, devcontainer, repo_path)
+    return dataclasses.replace(sandbox, image=image, env={**devcontainer.env, **sandbox.env})
+
+
+async def _image_exists(runtime: str, image: str) -> bool:
+    try:
+        result = await JOBS.run("devcontainer", [runtime, "image", "inspect", image])
+    except OSError as exc:
+        raise DevcontainerError(f"Cannot run `{runtime}`: {exc}") from exc
+    return result.passed
+
+
+async def _build_image(runtime: str, devcontainer: Devcontainer, repo_path: Path) -> None:
+    tag = devcontainer.tag
+    base = devcontainer.image
+    if devcontainer.dockerfile is not None:
+        base = f"{tag}-base" if devcontainer.create_commands else tag
+        build_args = [
+            arg for name, value in devcontainer.build_args.items() for arg in ("--build-arg", f"{name}={value}")
+        ]
+        dockerfile, context = str(devcontainer.dockerfile), str(devcontainer.context)
+        LOGGER.info("Building devcontainer image %s from %s", base, dockerfile)
+        await _run([runtime, "build", "--file", dockerfile, "--tag", base, *build_args, context])
+
+    if not devcontainer.create_commands:
+        return
+
+    assert base is not None
+    workdir = str(repo_path.resolve())
+    container = f"{IMAGE_REPOSITORY}-setup-{uuid.uuid4().hex[:12]}"
+    env = [arg for name, value in devcontainer.env.items() for arg in ("--env", f"{name}={value}")]
+    LOGGER.info("Running devcontainer create commands for %s in %s", tag, container)
+    try:
+        await _run(
+            [
+                runtime,
+                "run",
+                "--name",
+                container,
+                "--volume",
+                f"{workdir}:{workdir}",
+                "--workdir",
+                workdir,
+                *env,
+                base,
+                "sh",
+                "-c",
+                " && ".join(f"({command})" for command in devcontainer.create_commands),
+            ]
+        )
+        await _run([runtime, "commit", container, tag])
+    finally:
+        await JOBS.run("devcontainer", [runtime, "rm", "--force", container])
+
+
+async def _run(argv: Sequence[str]) -> None:
+    try:
+        result = await JOBS.run("devcontainer", argv, timeout_secs=BUILD_TIMEOUT_SECS, merge_stderr=True)
+    except OSError as exc:
+        raise DevcontainerError(f"Cannot run `{argv[0]}`: {exc}") from exc
+    if result.timed_out:
+        raise DevcontainerError(f"`{argv[0]} {argv[1]}` timed out after {BUILD_TIMEOUT_SECS}s")
+    if result.returncode != 0:
+        tail = result.stdout.strip()[-ERROR_OUTPUT_CHARS:]
+        raise DevcontainerError(f"`{argv[0]} {argv[1]}` failed (exit code {result.returncode}):\n```\n{tail}\n```")
+
+
+def _string_map(key: str, raw: object) -> Dict[str, str]:
+    if raw is None:
+        return {}
+    if not isinstance(raw, dict):
+        raise DevcontainerError(f"`{key}` in devcontainer.json must be an object")
+    return {str(name): str(value) for name, value in raw.items()}
+
+
+def _shell_commands(key: str, raw: object) -> List[str]:
+    """A lifecycle command as shell commands: a string, an argv list, or an object of named commands."""
+    if raw is None:
+        return []
+    if isinstance(raw, str):
+        return [raw] if raw.strip() else []
+    if isinstance(raw, list) and all(isinstance(arg, str) for arg in raw):
+        return [shlex.join(raw)] if raw else []
+    if isinstance(raw, dict):
+        return [command for value in raw.values() for command in _shell_commands(key, value)]
+    raise DevcontainerError(f"`{key}` in devcontainer.json must be a string, an array, or an object")
+
+
+def strip_jsonc(text: str) -> str:
+    """JSON with comments (the devcontainer.json dialect) as plain JSON: drop comments, then trailing commas."""
+    return _drop_trailing_commas(_drop_comments(text))
+
+
+def _drop_comments(text: str) -> str:
+    out: List[str] = []
+    index = 0
+    while index < len(text):
+        if text[index] == '"':
+            end = _string_end(text, index)
+            out.append(text[index:end])
+            index = end
+        elif text.startswith("//", index):
+            newline = text.find("\n", index)
+            index = len(text) if newline == -1 else newline
+        elif text.startswith("/*", index):
+            close = text.find("*/", index + 2)
+            index = len(text) if close == -1 else close + 2
+        else:
+            out.append(text[index])
+            index += 1
+    return "".join(out)
+
+
+def _drop_trailing_commas(text: str) -> str:
+    out: List[str] = []
+    index = 0
+    while index < len(text):
+        if text[index] == '"':
+            end = _string_end(text, index)
+            out.append(text[index:end])
+            index = end
+            continue
+        if text[index] != "," or text[index + 1 :].lstrip()[:1] not in ("]", "}"):
+            out.append(text[index])
+        index += 1
+    return "".join(out)
+
+
+def _string_end(text: str, start: int) -> int:
+    """Index just past the JSON string starting at `start`."""
+    index = start + 1
+    while index < len(text) and text[index] != '"':
+        index += 2 if text[index] == "\\" else 1
+    return index + 1
@@ -0,0 +1,126 @@
+"""Split unified diffs per file and render them for chat.
+
+Chat adapters that support interactive messages (Slack) post a collapsed file
+list and expand single files on request; others get one message per file.
+Hunks are rendered in ```diff fences (or uploaded as `diff` snippets) so
+clients that highlight code color the added/removed lines.
+"""
+
+from __future__ import annotations
+
+import re
+from dataclasses import dataclass
+from pathlib import PurePosixPath
+from typing import List, Optional, Sequence
+
+# Per-file messages longer than this are cut off with a pointer to !patch
+MAX_FILE_MESSAGE_CHARS = 3000
+# Adapters without a collapsed view post at most this many per-file messages
+MAX_FILE_MESSAGES = 10
+
+_DIFF_HEADER = re.compile(r"^diff --git a/(?P<old>.+?) b/(?P<new>.+)$")
+
+
+@dataclass(frozen=True)
+class FileDiff:
+    """The part of a unified diff that touches one file."""
+
+    path: str
+    old_path: Optional[str]
+    status: str  # "added", "deleted", "renamed", or "modified"
+    additions: int
+    deletions: int
+    binary: bool
+    text: str  # The full per-file diff, headers included
+
+    @property
+    def hunks(self) -> str:
+        """The diff body without git's `diff --git`/`index`/`---`/`+++` header lines."""
+        lines = self.text.splitlines()
+        for index, line in enumerate(lines):
+            if line.startswith("@@"):
+                return "\n".join(lines[index:])
+        return ""
+
+    @property
+    def summary(self) -> str:
+        if self.binary:
+            counts = "binary"
+        else:
+            counts = f"+{self.additions} -{self.deletions}"
+        if self.status == "renamed" and self.old_path:
+            return f"`{self.old_path}` → `{self.path}` ({self.status}, {counts})"
+        return f"`{self.path}` ({self.status}, {counts})"
+
+
+def split_diff(diff_text: str) -> List[FileDiff]:
+    """Split `git diff` output into per-file pieces."""
+    chunks: List[List[str]] = []
+    for line in diff_text.splitlines():
+        if line.startswith("diff --git ") or not chunks:
+            chunks.append([])
+        chunks[-1].append(line)
+    return [file_diff for file_diff in (_parse_chunk(chunk) for chunk in chunks) if file_diff]
+
+
+def _parse_chunk(lines: List[str]) -> Optional[FileDiff]:
+    header = _DIFF_HEADER.match(lines[0]) if lines else None
+    if not header:
+        return None
+    old_path: Optional[str] = header.group("old")
+    path = header.group("new")
+    status = "modified"
+    additions = deletions = 0
+    binary = False
+    in_hunk = False
+    for line in lines[1:]:
+        if line.startswith("@@"):
+            in_hunk = True
+        elif in_hunk:
+            if line.startswith("+"):
+                additions += 1
+            elif line.startswith("-"):
+                deletions += 1
+        elif line.startswith("new file mode"):
+            status = "added"
+        elif line.startswith("deleted file mode"):
+            status = "deleted"
+        elif line.startswith("rename from "):
+            status = "renamed"
+            old_path = line[len("rename from ") :]
+        elif line.startswith("rename to "):
+            path = line[len("rename to ") :]
+        elif line.startswith("Binary files ") or line == "GIT binary patch":
+            binary = True
+    return FileDiff(
+        path=path,
+        old_path=old_path if status == "renamed" else None,
+        status=status,
+        additions=additions,
+        deletions=deletions,
+        binary=binary,
+        text="\n".join(lines) + "\n",
+    )
+
+
+def format_diff_overview(files: Sequence[FileDiff]) -> str:
+    additions = sum(file_diff.additions for file_diff in files)
+    deletions = sum(file_diff.deletions for file_diff in files)
+    noun = "file" if len(files) == 1 else "files"
+    return f"*Changes:* {len(files)} {noun}, +{additions} -{deletions}"
+
+
+def render_file_diff(file_diff: FileDiff, limit: int = MAX_FILE_MESSAGE_CHARS) -> str:
+    """Render one file as a header line plus a ```diff block, truncated to `limit` characters."""
+    header = file_diff.summary
+    if file_diff.binary:
+        return f"{header}\nBinary file; use `!patch` to download it."
+    body = file_diff.hunks or "(no textual changes)"
+    budget = max(0, limit - len(header) - 20)
+    if len(body) > budget:
+        body = body[:budget].rsplit("\n", 1)[0] + "\n… (truncated; use `!patch` for the full diff)"
+    return f"{header}\n```diff\n{body}\n```"
+
+
+def snippet_filename(file_diff: FileDiff) -> str:
+    return f"{PurePosixPath(file_diff.path).name}.diff"
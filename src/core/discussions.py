@@ -0,0 +1,56 @@
+"""Sessions that live in a GitHub Discussion: a design phase, then an implementation phase with a PR.
+
+A project with `discussions:` in projects.yaml is also reachable from its
+repository's Discussions (see `src/chat_adapters/github_discussions.py`).
+Each discussion is a session and each comment a message, so long design
+threads stay where the team already reads them. Sessions started there begin
+in the design phase: the agent discusses and plans but nothing is committed
+or published. `!implement` moves the session to the implementation phase,
+where the agent makes the changes and they go to a pull request that links
+back to the discussion, exactly like a Slack session.
+"""
+
+from __future__ import annotations
+
+from typing import Optional
+
+from .models import Session
+
+# Channel ids of discussion sessions are `github:<owner>/<repo>`; the thread is the discussion number
+DISCUSSION_CHANNEL_PREFIX = "github:"
+# Session context keys: "design" or "implementation", and the discussion's web URL
+PHASE_CONTEXT_KEY = "discussion_phase"
+DISCUSSION_URL_CONTEXT_KEY = "discussion_url"
+DESIGN_PHASE = "design"
+IMPLEMENTATION_PHASE = "implementation"
+
+DESIGN_NOTE = (
+    "This conversation is a design discussion on GitHub. Explore the code, then discuss the approach, "
+    "trade-offs, and open questions; do not edit files yet. The user will ask for the implementation "
+    "with `!implement` once the design is agreed."
+)
+IMPLEMENT_PROMPT = (
+    "The design discussed above is agreed. Implement it now: make the code changes, run the relevant "
+    "checks, and summarize what you changed."
+)
+DESIGN_PHASE_HOLD = (
+    "This session is still in the design phase, so these file changes were not committed. "
+    "Send `!implement` when the design is agreed to make the changes and open a pull request."
+)
+
+
+def discussion_channel(repo_full_name: str) -> str:
+    return f"{DISCUSSION_CHANNEL_PREFIX}{repo_full_name}"
+
+
+def is_discussion_channel(channel_id: Optional[str]) -> bool:
+    return bool(channel_id) and str(channel_id).startswith(DISCUSSION_CHANNEL_PREFIX)
+
+
+def in_design_phase(session: Session) -> bool:
+    return session.session_context.get(PHASE_CONTEXT_KEY) == DESIGN_PHASE
+
+
+def discussion_url(session: Session) -> Optional[str]:
+    url = session.session_context.get(DISCUSSION_URL_CONTEXT_KEY)
+    return url if isinstance(url, str) and url else None
@@ -13,10 +13,53 @@ class SessionNotFound(RemoteCoderError):
     pass
 
 
+class SessionRestoreError(RemoteCoderError):
+    """Raised when an archived or ended session cannot be made active again."""
+
+
+class ArchiveError(RemoteCoderError):
+    """An upload to or download from the session archive failed (see `src/core/archive.py`)."""
+
+
+class AnalyticsError(RemoteCoderError):
+    """The analytics database could not be opened or read (see `src/core/analytics.py`)."""
+
+
+class SearchError(RemoteCoderError):
+    """The search index could not be opened, read, or written, or a query had no words (see `src/core/search.py`)."""
+
+
+class SessionMoveError(RemoteCoderError):
+    """Raised when a session cannot be moved to another thread, e.g. one that has a session of its own."""
+
+
+class InvalidStatusTransition(RemoteCoderError):
+    """Raised when a session is moved to a status it cannot reach from its current one."""
+
+    def __init__(self, session_id: object, current: object, requested: object) -> None:
+        names = [getattr(status, "value", status) for status in (current, requested)]
+        super().__init__(f"Session {session_id} cannot go from {names[0]} to {names[1]}")
+        self.current = current
+        self.requested = requested
+
+
+class ProjectLocked(RemoteCoderError):
+    """Raised when a project already has as many sessions on its checkout as `max_active_sessions` allows."""
+
+    def __init__(self, project_id: str, holders: list) -> None:
+        super().__init__(f"Project {project_id} already has {len(holders)} active session(s)")
+        self.project_id = project_id
+        self.holders = holders  # The sessions holding the checkout, most recently active first
+
+
 class AgentNotFound(RemoteCoderError):
     pass
 
 
+class AgentNotAllowed(RemoteCoderError):
+    """Raised when a project's `allowed_agents` excludes the requested agent."""
+
+
 class CommandNotFound(RemoteCoderError):
     pass
 
@@ -0,0 +1,175 @@
+"""Session lifecycle events, broadcast to the subsystems that follow them.
+
+`SessionManager.events` publishes:
+
+- `SessionCreated` when a thread gets a session;
+- `RunStarted` when an agent run begins (published by the agent runner);
+- `RunFinished` when a run is recorded, succeeded, failed, or cancelled;
+- `ChecksFinished` when the project's verify steps ran for a session (the
+  pipeline's verify step, `!test`, `!apply`);
+- `PrOpened` the first time a PR is recorded for a session;
+- `PrMerged` when GitHub reports one of a session's PRs merged (published by
+  the router, from `POST /hooks/github`);
+- `SessionEnded` when an open session is ended (`!end`, idle expiry) or
+  archived by cleanup.
+
+Subscribers register with `EventBus.subscribe`, optionally for some event
+types only. A handler may be a plain function or a coroutine function;
+coroutines run as tasks on the daemon's event loop, so a slow subscriber never
+holds up the change that published the event. Errors in handlers are logged
+and do not reach the publisher. Events can be published from worker threads
+(cleanup passes run in one), which is why the bus remembers the loop it was
+subscribed on.
+"""
+
+from __future__ import annotations
+
+import asyncio
+import inspect
+import logging
+import threading
+from dataclasses import dataclass, field
+from datetime import datetime, timezone
+from typing import Callable, List, Optional, Set, Tuple, Type, Union
+from uuid import UUID
+
+from .models import SessionStatus
+from .transcripts import RunRecord
+
+LOGGER = logging.getLogger(__name__)
+
+
+def _now() -> datetime:
+    return datetime.now(timezone.utc)
+
+
+@dataclass(frozen=True)
+class SessionCreated:
+    session_id: UUID
+    project_id: str
+    channel_id: str
+    thread_ts: str
+    at: datetime = field(default_factory=_now)
+
+
+@dataclass(frozen=True)
+class RunStarted:
+    session_id: UUID
+    project_id: str
+    agent_id: str
+    run_id: str
+    at: datetime = field(default_factory=_now)
+
+
+@dataclass(frozen=True)
+class RunFinished:
+    session_id: UUID
+    project_id: str
+    run: RunRecord
+    at: datetime = field(default_factory=_now)
+
+
+@dataclass(frozen=True)
+class PrOpened:
+    session_id: UUID
+    project_id: str
+    number: int
+    url: str
+    at: datetime = field(default_factory=_now)
+
+
+@dataclass(frozen=True)
+class ChecksFinished:
+    session_id: UUID
+    project_id: str
+    passed: bool
+    at: datetime = field(default_factory=_now)
+
+
+@dataclass(frozen=True)
+class PrMerged:
+    session_id: UUID
+    project_id: str
+    number: int
+    at: datetime = field(default_factory=_now)
+
+
+@dataclass(frozen=True)
+class SessionEnded:
+    session_id: UUID
+    project_id: str
+    status: SessionStatus  # ENDED or ARCHIVED
+    at: datetime = field(default_factory=_now)
+
+
+SessionEvent = Union[SessionCreated, RunStarted, RunFinished, ChecksFinished, PrOpened, PrMerged, SessionEnded]
+# Returns None, or an awaitable the bus runs on the event loop
+EventHandler = Callable[[SessionEvent], object]
+
+
+class EventBus:
+    """Delivers each published event to every subscriber of its type, in the order they subscribed."""
+
+    def __init__(self) -> None:
+        self._subscribers: List[Tuple[EventHandler, Tuple[Type, ...]]] = []
+        self._lock = threading.Lock()
+        self._loop: Optional[asyncio.AbstractEventLoop] = None
+        self._tasks: Set[asyncio.Future] = set()
+
+    def subscribe(self, handler: EventHandler, *kinds: Type) -> Callable[[], None]:
+        """Call `handler` with every event (or those of `kinds`); returns a function that unsubscribes it."""
+        entry = (handler, kinds)
+        with self._lock:
+            self._subscribers.append(entry)
+        try:
+            self._loop = asyncio.get_running_loop()
+        except RuntimeError:
+            pass
+
+        def unsubscribe() -> None:
+            with self._lock:
+                if entry in self._subscribers:
+                    self._subscribers.remove(entry)
+
+        return unsubscribe
+
+    def publish(self, event: SessionEvent) -> None:
+        with self._lock:
+            handlers = [handler for handler, kinds in self._subscribers if not kinds or isinstance(event, kinds)]
+        for handler in handlers:
+            try:
+                result = handler(event)
+            except Exception:
+                LOGGER.exception("Event handler %r failed on %s", handler, type(event).__name__)
+                continue
+            if inspect.isawaitable(result):
+                self._schedule(result, event)
+
+    def _schedule(self, awaitable, event: SessionEvent) -> None:
+        try:
+            running = asyncio.get_running_loop()
+        except RuntimeError:
+            running = None
+        if running is not None:
+            self._loop = running
+            task: asyncio.Future = asyncio.ensure_future(awaitable)
+        elif self._loop is not None and not self._loop.is_closed():
+            task = asyncio.run_coroutine_threadsafe(awaitable, self._loop)  # type: ignore[arg-type]
+        else:
+            LOGGER.warning("Dropped async handling of %s: no event loop to run it on", type(event).__name__)
+            if inspect.iscoroutine(awaitable):
+                awaitable.close()
+            return
+        self._tasks.add(task)
+        task.add_done_callback(self._finished)
+
+    def _finished(self, task) -> None:
+        self._tasks.discard(task)
+        if not task.cancelled() and task.exception() is not None:
+            LOGGER.error("Event handler failed", exc_info=task.exception())
+
+    async def drain(self) -> None:
+        """Wait for the async handlers running now, e.g. before shutting down or in tests."""
+        pending = [asyncio.wrap_future(task) if not isinstance(task, asyncio.Task) else task for task in self._tasks]
+        if pending:
+            await asyncio.gather(*pending, return_exceptions=True)
@@ -0,0 +1,163 @@
+"""Writing a session out for postmortems and sharing (`!export`).
+
+An export collects the session's transcript (`src/core/transcripts.py`), its
+PRs, its checkpoints (`!timeline`), and the diff of its branch against where
+it started, and renders them twice: a Markdown document for people and a
+JSON document for scripts. Both are uploaded to the thread; chats without
+file uploads get the Markdown inline when it is short enough.
+"""
+
+from __future__ import annotations
+
+import json
+import re
+from dataclasses import asdict, dataclass, field
+from datetime import datetime, timezone
+from typing import TYPE_CHECKING, Any, Dict, List, Optional
+
+from .models import PullRequestRef, Session
+from .pricing import format_cost
+from .tags import format_tags
+from .timeline import Checkpoint, load_timeline
+from .transcripts import RunRecord, TranscriptEntry, TranscriptKind
+
+if TYPE_CHECKING:
+    from .conversation import SessionManager
+
+# Bumped when the JSON layout changes in a way scripts would notice
+EXPORT_VERSION = 1
+
+_BACKTICK_RUN = re.compile(r"`{3,}")
+
+
+@dataclass(frozen=True)
+class SessionExport:
+    session: Session
+    transcript: List[TranscriptEntry] = field(default_factory=list)
+    pr_refs: List[PullRequestRef] = field(default_factory=list)
+    checkpoints: List[Checkpoint] = field(default_factory=list)
+    diff: Optional[str] = None  # None when the session has no branch or nothing changed on it
+    exported_at: datetime = field(default_factory=lambda: datetime.now(timezone.utc))
+
+
+def collect_export(session_manager: "SessionManager", session: Session, diff: Optional[str]) -> SessionExport:
+    """What `session_manager` has on the session, with the diff of its branch."""
+    return SessionExport(
+        session=session,
+        transcript=session_manager.transcript(session.id),
+        pr_refs=session_manager.list_pr_refs(session.id),
+        checkpoints=load_timeline(session),
+        diff=diff,
+    )
+
+
+def _when(moment: datetime) -> str:
+    return moment.astimezone(timezone.utc).strftime("%Y-%m-%d %H:%M UTC")
+
+
+def _fenced(text: str, language: str = "") -> str:
+    """A code block that the text cannot close early, however many backticks it contains."""
+    longest = max((len(run) for run in _BACKTICK_RUN.findall(text)), default=2)
+    fence = "`" * (longest + 1)
+    return f"{fence}{language}\n{text.rstrip()}\n{fence}"
+
+
+def describe_run(run: RunRecord) -> str:
+    """E.g. "`claude` (`sonnet`) succeeded in 12s, exit code 0, ~$0.05; edited README.md"."""
+    model = f" (`{run.model}`)" if run.model else ""
+    if run.cancelled:
+        outcome = "was cancelled after"
+    else:
+        outcome = "succeeded in" if run.success else "failed after"
+    line = f"`{run.agent_id}`{model} {outcome} {run.wall_secs:.0f}s"
+    if run.exit_code is not None:
+        line += f", exit code {run.exit_code}"
+    if run.cost_usd is not None:
+        line += f", ~{format_cost(run.cost_usd)}"
+    if run.files:
+        line += f"; edited {', '.join(run.files)}"
+    return line
+
+
+def export_markdown(export: SessionExport) -> str:
+    session = export.session
+    model = f" (`{session.active_model}`)" if session.active_model else ""
+    lines = [
+        f"# Session {str(session.id)[:8]} in `{session.project_id}`",
+        "",
+        f"- Agent: `{session.active_agent_id}`{model}",
+        f"- Status: {session.status.value}",
+        f"- Started: {_when(session.created_at)}; last updated {_when(session.updated_at)}",
+        f"- Estimated cost: ~{format_cost(session.estimated_cost_usd)}",
+        f"- Exported: {_when(export.exported_at)}",
+    ]
+    if session.tags:
+        lines.append(f"- Tags: {format_tags(session.tags)}")
+    if export.pr_refs:
+        lines += ["", "## Pull requests", ""]
+        lines += [f"- #{ref.number} ({ref.role.value}, {ref.base_branch}): {ref.url}" for ref in export.pr_refs]
+
+    lines += ["", "## Transcript"]
+    if not export.transcript:
+        lines += ["", "Nothing was recorded."]
+    for entry in export.transcript:
+        if entry.kind == TranscriptKind.RUN and entry.run is not None:
+            lines += ["", f"_Run, {_when(entry.timestamp)}: {describe_run(entry.run)}._"]
+            continue
+        heading = "Prompt" if entry.kind == TranscriptKind.PROMPT else "Reply"
+        lines += ["", f"### {heading}, {_when(entry.timestamp)}", "", entry.content.strip()]
+
+    if export.checkpoints:
+        lines += ["", "## Checkpoints", ""]
+        for checkpoint in export.checkpoints:
+            files = f" ({', '.join(checkpoint.files)})" if checkpoint.files else ""
+            lines.append(f"{checkpoint.run}. `{checkpoint.commit[:8]}` {checkpoint.prompt}{files}")
+
+    if export.diff:
+        lines += ["", "## Diff", "", _fenced(export.diff, "diff")]
+    return "\n".join(lines) + "\n"
+
+
+def _entry_to_dict(entry: TranscriptEntry) -> Dict[str, Any]:
+    data: Dict[str, Any] = {"kind": entry.kind.value, "timestamp": entry.timestamp.isoformat()}
+    if entry.run is not None:
+        data["run"] = asdict(entry.run)
+    else:
+        data["content"] = entry.content
+    return data
+
+
+def export_json(export: SessionExport) -> str:
+    session = export.session
+    data = {
+        "version": EXPORT_VERSION,
+        "exported_at": export.exported_at.isoformat(),
+        "session": {
+            "id": str(session.id),
+            "project_id": session.project_id,
+            "channel_id": session.channel_id,
+            "thread_ts": session.thread_ts,
+            "agent_id": session.active_agent_id,
+            "model": session.active_model,
+            "status": session.status.value,
+            "created_at": session.created_at.isoformat(),
+            "updated_at": session.updated_at.isoformat(),
+            "estimated_cost_usd": session.estimated_cost_usd,
+            "tags": list(session.tags),
+        },
+        "pull_requests": [
+            {
+                "number": ref.number,
+                "url": ref.url,
+                "role": ref.role.value,
+                "repo": ref.repo,
+                "head_branch": ref.head_branch,
+                "base_branch": ref.base_branch,
+            }
+            for ref in export.pr_refs
+        ],
+        "transcript": [_entry_to_dict(entry) for entry in export.transcript],
+        "checkpoints": [asdict(checkpoint) for checkpoint in export.checkpoints],
+        "diff": export.diff,
+    }
+    return json.dumps(data, indent=2) + "\n"
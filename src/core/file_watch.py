@@ -0,0 +1,137 @@
+"""File-by-file progress while an agent runs.
+
+Every `interval_secs` (`REMOTE_CODER_FILE_WATCH_SECS`, default 10) during a
+run, the session's worktree is compared with its last commit, and the files
+whose changes moved since the last look are announced in one short thread
+message, e.g. "modified `src/router.py` (+42/-7)". What was already changed
+when the run started is the baseline, so leftovers from earlier runs are not
+announced again. Counts are against the last commit (new files count every
+line as added). A run posts at most `MAX_NOTICES` of these; the full
+summary still comes at the end.
+"""
+
+from __future__ import annotations
+
+import asyncio
+import logging
+from dataclasses import dataclass
+from pathlib import Path
+from typing import Awaitable, Callable, Dict, List, Optional
+
+from .compare import _git
+
+LOGGER = logging.getLogger(__name__)
+
+DEFAULT_FILE_WATCH_SECS = 10
+MAX_NOTICES = 20
+MAX_FILES_PER_NOTICE = 8
+# New files this large are announced without a line count
+MAX_COUNTED_BYTES = 1_000_000
+
+NoticeFn = Callable[[str], Awaitable[None]]
+
+
+@dataclass(frozen=True)
+class FileChange:
+    path: str
+    added: Optional[int]  # None for binary files
+    deleted: Optional[int]
+    kind: str  # "added", "modified", or "deleted"
+
+    def describe(self) -> str:
+        if self.added is None or self.deleted is None:
+            counts = "binary"
+        elif self.kind == "added":
+            counts = f"+{self.added}"
+        elif self.kind == "deleted":
+            counts = f"-{self.deleted}"
+        else:
+            counts = f"+{self.added}/-{self.deleted}"
+        return f"{self.kind} `{self.path}` ({counts})"
+
+
+async def worktree_changes(repo_path: Path) -> Optional[Dict[str, FileChange]]:
+    """The uncommitted changes in `repo_path` by file, or None when it is not a git checkout."""
+    numstat = await _git(repo_path, "diff", "--numstat", "--no-renames", "HEAD", check=False)
+    if numstat.returncode != 0:
+        return None
+    changes: Dict[str, FileChange] = {}
+    for line in numstat.stdout.splitlines():
+        parts = line.split("\t", 2)
+        if len(parts) != 3:
+            continue
+        added, deleted, path = parts
+        counts = (None, None) if added == "-" else (int(added), int(deleted))
+        kind = "modified" if (repo_path / path).exists() else "deleted"
+        changes[path] = FileChange(path, *counts, kind)
+    untracked = await _git(repo_path, "ls-files", "--others", "--exclude-standard", "-z", check=False)
+    for path in filter(None, untracked.stdout.split("\0")):
+        changes[path] = FileChange(path, _count_lines(repo_path / path), 0, "added")
+    return changes
+
+
+def _count_lines(path: Path) -> Optional[int]:
+    try:
+        if path.stat().st_size > MAX_COUNTED_BYTES:
+            return None
+        data = path.read_bytes()
+    except OSError:
+        return None
+    if b"\0" in data:
+        return None
+    return data.count(b"\n") + (0 if not data or data.endswith(b"\n") else 1)
+
+
+def format_changes(changes: List[FileChange]) -> str:
+    shown = [change.describe() for change in changes[:MAX_FILES_PER_NOTICE]]
+    if len(changes) > MAX_FILES_PER_NOTICE:
+        shown.append(f"and {len(changes) - MAX_FILES_PER_NOTICE} more")
+    return "Progress: " + ", ".join(shown)
+
+
+class FileChangeWatcher:
+    """Announces the files an agent changes while it runs; use as `async with watcher:` around the run."""
+
+    def __init__(self, repo_path: Path, notify: NoticeFn, *, interval_secs: float = DEFAULT_FILE_WATCH_SECS) -> None:
+        self._repo_path = repo_path
+        self._notify = notify
+        self._interval_secs = interval_secs
+        self._seen: Dict[str, FileChange] = {}
+        self._notices = 0
+        self._task: Optional[asyncio.Task] = None
+
+    async def __aenter__(self) -> "FileChangeWatcher":
+        baseline = await worktree_changes(self._repo_path)
+        if baseline is not None:
+            self._seen = baseline
+            self._task = asyncio.create_task(self._watch())
+        return self
+
+    async def __aexit__(self, *exc_info: object) -> None:
+        if self._task:
+            self._task.cancel()
+            try:
+                await self._task
+            except asyncio.CancelledError:
+                pass
+            self._task = None
+
+    async def _watch(self) -> None:
+        while self._notices < MAX_NOTICES:
+            await asyncio.sleep(self._interval_secs)
+            try:
+                await self.check()
+            except Exception:  # pragma: no cover - progress notes must not break the run
+                LOGGER.warning("Could not check %s for changed files", self._repo_path, exc_info=True)
+
+    async def check(self) -> List[FileChange]:
+        """Announce the files that changed since the last check; returns them."""
+        current = await worktree_changes(self._repo_path)
+        if current is None:
+            return []
+        moved = [change for path, change in sorted(current.items()) if self._seen.get(path) != change]
+        self._seen = current
+        if moved:
+            self._notices += 1
+            await self._notify(format_changes(moved))
+        return moved
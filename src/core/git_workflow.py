@@ -2,23 +2,39 @@
 
 from __future__ import annotations
 
-import asyncio
 import logging
+import socket
 import subprocess
 from datetime import datetime, timezone
 from pathlib import Path
-from typing import Optional
+from typing import List, Optional
 from uuid import UUID
 
 from ..agent_adapters import AgentResult
 from ..github import GitHubManager
 from ..github.client import EnsurePROptions
+from .approval import DiffSize, approval_reason, describe_fast_path, parse_numstat
+from .discussions import DESIGN_PHASE_HOLD, discussion_url, in_design_phase
 from .errors import GitHubError, SessionNotFound
-from .models import Project, Session
+from .jobs import run_git
+from .models import Project, PullRequestRef, Session
+from .preflight import PreflightIssue, check_repository
 from .conversation import SessionManager
 
 LOGGER = logging.getLogger(__name__)
 
+# Session context key holding the commit a local-only session branch started from
+LOCAL_BASE_CONTEXT_KEY = "local_base_commit"
+# Session context flag set by `reset_to_checkpoint`: the next push must overwrite the remote branch
+FORCE_PUSH_CONTEXT_KEY = "force_push_next"
+# Session context flag: publishing is paused because changes are waiting for approval (`!publish`)
+APPROVAL_PENDING_CONTEXT_KEY = "approval_pending"
+
+
+def session_branch_name(session_id: UUID) -> str:
+    """Return the git branch used for a session's changes."""
+    return f"remote-coder-{session_id}"
+
 
 def _get_authenticated_url(project: Project, token: Optional[str]) -> Optional[str]:
     """Get HTTPS URL with token authentication for a project."""
@@ -47,11 +63,12 @@ class GitWorkflowService:
         result: AgentResult,
         pr_title: str,
     ) -> Optional[str]:
-        if not project.github or not self._github_manager.is_configured():
-            LOGGER.debug("Skipping PR creation: no GitHub config")
+        repo_path = session.project_path
+        local_only = self.is_local_only(project)
+        if local_only and not await self.is_git_repo(repo_path):
+            LOGGER.debug("Skipping commit: no GitHub config and %s is not a git repository", repo_path)
             return None
 
-        repo_path = session.project_path
         has_changes = bool(result.file_edits)
         if not has_changes:
             has_changes = await self._repo_has_changes(repo_path)
@@ -59,6 +76,9 @@ class GitWorkflowService:
         if not has_changes:
             LOGGER.info("No file changes detected - skipping commit/push")
             return None
+        if in_design_phase(session):
+            LOGGER.info("Session %s is in the design phase - leaving changes uncommitted", session.id)
+            return DESIGN_PHASE_HOLD
 
         if result.structured_output:
             LOGGER.info(
@@ -67,9 +87,33 @@ class GitWorkflowService:
                 len(result.structured_output.pr_summary),
             )
 
+        hold_reason: Optional[str] = None
+        fast_path_note: Optional[str] = None
+        if project.approval.required and not (local_only or session.publishing_paused):
+            size = await self._pending_diff_size(repo_path)
+            hold_reason = approval_reason(project.approval, size)
+            if hold_reason:
+                LOGGER.info("Holding changes for approval in session %s: %s", session.id, hold_reason)
+                self._session_manager.set_publishing_paused(session.id, True)
+                self._session_manager.update_session_context(session.id, {APPROVAL_PENDING_CONTEXT_KEY: True})
+            else:
+                fast_path_note = describe_fast_path(size)
+
+        if local_only or session.publishing_paused:
+            LOGGER.info("Changes detected - committing to local branch only")
+            try:
+                return await self._commit_local_branch(session, project, pr_title, hold_reason=hold_reason)
+            except subprocess.CalledProcessError as exc:
+                detail = (exc.stderr or exc.stdout or str(exc)).strip()
+                LOGGER.exception("Git command failed for session %s", session.id)
+                return f"Git command failed while committing locally: {detail or 'Unknown git error.'}"
+
         LOGGER.info("Changes detected - proceeding with commit and push")
         try:
-            return await self._publish_branch_update(session, project, pr_title)
+            message = await self._publish_branch_update(session, project, pr_title)
+            if message and fast_path_note:
+                return f"{message}\n{fast_path_note}"
+            return message
         except GitHubError as exc:
             LOGGER.exception("GitHub workflow failed for session %s", session.id)
             return f"GitHub integration failed: {exc}"
@@ -79,10 +123,224 @@ class GitWorkflowService:
             message = detail or "Unknown git error."
             return f"Git command failed while preparing PR: {message}"
 
+    def is_local_only(self, project: Project) -> bool:
+        """Return True when changes for this project cannot be published to GitHub."""
+        return not project.github or not self._github_manager.is_configured()
+
+    async def build_patch(self, session: Session, project: Project) -> Optional[str]:
+        """Return the session branch's commits as `git format-patch` output, or None if there are none."""
+        repo_path = session.project_path
+        if not await self.is_git_repo(repo_path):
+            return None
+        branch = session_branch_name(session.id)
+        rev_parse = await self._run_git(repo_path, ["rev-parse", "--verify", branch], check=False)
+        if rev_parse.returncode != 0:
+            return None
+
+        base = self._session_base(session, project)
+        if base:
+            args = ["format-patch", "--stdout", f"{base}..{branch}"]
+        else:
+            args = ["format-patch", "--stdout", "--root", branch]
+        result = await self._run_git(repo_path, args)
+        return result.stdout or None
+
+    async def session_diff(self, session: Session, project: Project) -> Optional[str]:
+        """Return the diff of a local-only session branch against where it started.
+
+        Returns None for sessions that publish to GitHub (the PR shows the diff)
+        or when there is nothing to diff.
+        """
+        if not (self.is_local_only(project) or session.publishing_paused):
+            return None
+        return await self.branch_diff(session, project)
+
+    async def branch_diff(self, session: Session, project: Project) -> Optional[str]:
+        """Return the diff of the session branch against where it started, or None if there is nothing to diff."""
+        base = self._session_base(session, project)
+        branch = session_branch_name(session.id)
+        if not base or not await self.is_git_repo(session.project_path):
+            return None
+        diff = await self._run_git(session.project_path, ["diff", f"{base}..{branch}"], check=False)
+        if diff.returncode != 0 or not diff.stdout.strip():
+            return None
+        return diff.stdout
+
+    async def pending_diff(self, repo_path: Path) -> Optional[str]:
+        """Return the uncommitted changes in the worktree, including new files, or None if there are none."""
+        if not await self.is_git_repo(repo_path):
+            return None
+        await self._run_git(repo_path, ["add", "-A"])
+        diff = await self._run_git(repo_path, ["diff", "--cached"], check=False)
+        if diff.returncode != 0 or not diff.stdout.strip():
+            return None
+        return diff.stdout
+
+    async def apply_patch(self, session: Session, project: Project, patch_text: str) -> tuple[bool, str]:
+        """Apply a unified diff or format-patch text to the session branch's working tree.
+
+        Returns (applied, detail) where detail is a diffstat on success or git's error output.
+        Changes are left uncommitted so the caller can verify and publish them.
+        """
+        repo_path = session.project_path
+        if not await self.is_git_repo(repo_path):
+            return False, "The project directory is not a git repository."
+
+        branch = session_branch_name(session.id)
+        if self.is_local_only(project):
+            await self._ensure_local_branch(session, branch)
+        else:
+            await self._ensure_branch(repo_path, project, branch)
+
+        check = await self._run_git(repo_path, ["apply", "--check", "-"], check=False, stdin=patch_text)
+        if check.returncode != 0:
+            return False, (check.stderr or check.stdout).strip() or "git apply rejected the patch."
+
+        await self._run_git(repo_path, ["apply", "-"], stdin=patch_text)
+        stat = await self._run_git(repo_path, ["apply", "--stat", "-"], stdin=patch_text)
+        return True, stat.stdout.rstrip()
+
+    async def revert_patch(self, session: Session, project: Project, patch_text: str) -> None:
+        """Take a patch applied by `apply_patch` back out of the session's working tree."""
+        await self._run_git(session.project_path, ["apply", "-R", "-"], stdin=patch_text)
+
+    async def label_pull_requests(self, session: Session, project: Project, labels: List[str]) -> List[str]:
+        """Add `labels` to the session's PRs in the project's repository; returns a line per PR for the thread."""
+        if self.is_local_only(project) or not labels:
+            return []
+        lines = []
+        for ref in self._session_manager.list_pr_refs(session.id):
+            if ref.repo not in (None, project.github.full_name):
+                continue
+            try:
+                await self._github_manager.add_labels(project, ref.number, labels)
+            except GitHubError as exc:
+                lines.append(f"Could not label PR #{ref.number}: {exc}")
+                continue
+            lines.append(f"Labelled PR #{ref.number}.")
+        return lines
+
+    async def unpublish(self, session: Session, project: Project) -> str:
+        """Close the session's PR and delete its remote branch, keeping the local branch.
+
+        Publishing stays paused for the session until `republish` is called.
+        """
+        branch = session_branch_name(session.id)
+        if self.is_local_only(project):
+            return (
+                f"Nothing to unpublish: GitHub is not configured for this project, "
+                f"so changes only live on the local branch `{branch}`."
+            )
+
+        lines = []
+        pr_number = self._get_existing_pr_number(session.id, project)
+        if pr_number:
+            await self._github_manager.close_pull_request(project, pr_number)
+            self._session_manager.clear_pr_ref(session.id, pr_number, repo=project.github.full_name)
+            lines.append(f"Closed PR #{pr_number}.")
+
+        remote = _get_authenticated_url(project, self._github_manager.token) or "origin"
+        deleted = await self._run_git(session.project_path, ["push", remote, "--delete", branch], check=False)
+        if deleted.returncode == 0:
+            lines.append(f"Deleted remote branch `{branch}`.")
+        elif "remote ref does not exist" not in (deleted.stderr or ""):
+            detail = (deleted.stderr or deleted.stdout).strip() or "git error"
+            lines.append(f"Could not delete remote branch `{branch}`: {detail}")
+
+        self._session_manager.set_publishing_paused(session.id, True)
+        if not lines:
+            lines.append("This session had no open PR or remote branch.")
+        lines.append(
+            f"Local branch `{branch}` and its commits are kept. New changes will be committed locally "
+            "until you run `!publish`."
+        )
+        return "\n".join(lines)
+
+    async def republish(self, session: Session, project: Project, pr_title: str) -> str:
+        """Resume publishing and push the session branch, opening a new PR if needed."""
+        if self.is_local_only(project):
+            return "GitHub is not configured for this project; use `!patch` to download the changes instead."
+
+        self._session_manager.set_publishing_paused(session.id, False)
+        self._session_manager.update_session_context(session.id, {APPROVAL_PENDING_CONTEXT_KEY: False})
+        branch = session_branch_name(session.id)
+        rev_parse = await self._run_git(session.project_path, ["rev-parse", "--verify", branch], check=False)
+        if rev_parse.returncode != 0:
+            return "Publishing resumed. There are no commits on the session branch yet; the next change will open a PR."
+
+        message = await self._publish_branch_update(session, project, pr_title, require_commit=False)
+        return message or "Publishing resumed."
+
+    async def fetch_base_commit(self, project: Project, repo_path: Path) -> str:
+        """Fetch the project's base branch and return its latest commit, for work that becomes a PR against it."""
+        base = project.github.default_base_branch if project.github else "main"
+        remote = _get_authenticated_url(project, self._github_manager.token) or "origin"
+        await self._run_git(repo_path, ["fetch", remote, base])
+        commit = await self._run_git(repo_path, ["rev-parse", "FETCH_HEAD"])
+        return commit.stdout.strip()
+
+    async def publish_branch(
+        self, session: Session, project: Project, branch: str, title: str, body: str
+    ) -> PullRequestRef:
+        """Push a branch that is already committed (e.g. by `!batch`) and open a PR for it.
+
+        The PR is not recorded as the session's; the session branch keeps its own.
+        """
+        remote = _get_authenticated_url(project, self._github_manager.token) or "origin"
+        await self._run_git(session.project_path, ["push", "-u", remote, branch])
+        return await self._github_manager.ensure_pull_request(
+            project=project,
+            session_id=session.id,
+            branch=branch,
+            options=EnsurePROptions(title=title, body=body, labels=list(session.tags)),
+        )
+
+    async def current_commit(self, repo_path: Path) -> Optional[str]:
+        """Return the commit checked out in `repo_path`, or None outside a git repository."""
+        head = await self._run_git(repo_path, ["rev-parse", "HEAD"], check=False)
+        if head.returncode != 0:
+            return None
+        return head.stdout.strip() or None
+
+    async def reset_to_checkpoint(self, session: Session, project: Project, checkpoint: str) -> tuple[bool, str]:
+        """Throw away uncommitted edits and the session branch's commits since `checkpoint`.
+
+        A published branch is overwritten on the next push. Returns (reset, message).
+        """
+        repo_path = session.project_path
+        if not await self.is_git_repo(repo_path):
+            return False, f"`{repo_path}` is not a git repository, so there is nothing to reset."
+
+        branch = session_branch_name(session.id)
+        rev_parse = await self._run_git(repo_path, ["rev-parse", "--verify", branch], check=False)
+        has_branch = rev_parse.returncode == 0
+        if has_branch:
+            ancestor = await self._run_git(repo_path, ["merge-base", "--is-ancestor", checkpoint, branch], check=False)
+            if ancestor.returncode != 0:
+                return False, f"`{branch}` no longer contains the checkpoint `{checkpoint[:7]}`; not resetting."
+
+        await self._run_git(repo_path, ["reset", "--hard"])
+        await self._run_git(repo_path, ["clean", "-fd"])
+        if not has_branch:
+            return True, "Discarded the uncommitted changes from the last attempt."
+
+        count = await self._run_git(repo_path, ["rev-list", "--count", f"{checkpoint}..{branch}"])
+        dropped = int(count.stdout.strip() or 0)
+        await self._run_git(repo_path, ["checkout", branch])
+        await self._run_git(repo_path, ["reset", "--hard", checkpoint])
+        message = f"Reset `{branch}` to `{checkpoint[:7]}`"
+        if dropped:
+            message += f", dropping {dropped} commit(s) from the last attempt"
+            pushed = self._get_existing_pr_number(session.id, project) is not None
+            if pushed and not (self.is_local_only(project) or session.publishing_paused):
+                self._session_manager.update_session_context(session.id, {FORCE_PUSH_CONTEXT_KEY: True})
+                message += "; the PR is updated on the next push"
+        return True, message + "."
+
     async def setup_session_branch(self, session: Session, project: Project) -> None:
         if not project.github:
             return
-        branch = f"remote-coder-{session.id}"
+        branch = session_branch_name(session.id)
         repo_path = session.project_path
         rev_parse = await self._run_git(repo_path, ["rev-parse", "--verify", branch], check=False)
         if rev_parse.returncode == 0:
@@ -93,6 +351,10 @@ class GitWorkflowService:
         await self._prepare_base_branch(repo_path, base, require_clean=True, project=project)
         await self._run_git(repo_path, ["checkout", "-B", branch, base])
 
+    async def preflight(self, session: Session, project: Project) -> List[PreflightIssue]:
+        """Check the session's checkout for problems that would make runs or publishing fail."""
+        return await check_repository(project, session.project_path)
+
     async def stash_changes(self, repo_path: Path) -> bool:
         await self._run_git(repo_path, ["add", "-A"])
         if not await self._repo_has_changes(repo_path):
@@ -105,29 +367,123 @@ class GitWorkflowService:
         )
         return True
 
+    async def park_changes(self, session: Session, message: str) -> Optional[str]:
+        """Commit the uncommitted changes in the session's checkout to its branch; returns the commit, if any.
+
+        For runs stopped at shutdown (see `src/core/shutdown.py`); hooks are skipped so the commit cannot stall it.
+        """
+        repo_path = session.project_path
+        if not await self.is_git_repo(repo_path) or not await self._repo_has_changes(repo_path):
+            return None
+        await self._ensure_local_branch(session, session_branch_name(session.id))
+        await self._run_git(repo_path, ["add", "-A"])
+        await self._run_git(repo_path, ["commit", "--no-verify", "-m", message])
+        return await self.current_commit(repo_path)
+
+    async def _pending_diff_size(self, repo_path: Path) -> DiffSize:
+        """Size of the uncommitted changes a run left, including new files."""
+        await self._run_git(repo_path, ["add", "-A"])
+        numstat = await self._run_git(repo_path, ["diff", "--cached", "--numstat", "--no-renames"])
+        return parse_numstat(numstat.stdout)
+
     async def _repo_has_changes(self, repo_path: Path) -> bool:
         result = await self._run_git(repo_path, ["status", "--porcelain"])
         return bool(result.stdout.strip())
 
-    async def _publish_branch_update(self, session: Session, project: Project, pr_title: str) -> Optional[str]:
-        branch = f"remote-coder-{session.id}"
+    async def is_git_repo(self, repo_path: Path) -> bool:
+        result = await self._run_git(repo_path, ["rev-parse", "--is-inside-work-tree"], check=False)
+        return result.returncode == 0 and result.stdout.strip() == "true"
+
+    def _session_base(self, session: Session, project: Project) -> Optional[str]:
+        """Return the ref the session branch was started from, if known."""
+        base = session.session_context.get(LOCAL_BASE_CONTEXT_KEY)
+        if base:
+            return str(base)
+        if project.github:
+            return project.github.default_base_branch
+        return None
+
+    async def _commit_local_branch(
+        self, session: Session, project: Project, message: str, hold_reason: Optional[str] = None
+    ) -> Optional[str]:
+        repo_path = session.project_path
+        branch = session_branch_name(session.id)
+        await self._ensure_local_branch(session, branch)
+        await self._run_git(repo_path, ["add", "-A"])
+        if not await self._commit_changes(repo_path, message):
+            return None
+
+        base = self._session_base(session, project)
+        if base:
+            stat = await self._run_git(repo_path, ["diff", "--stat", f"{base}..{branch}"], check=False)
+        else:
+            # Branch started in an empty repository; there is nothing to diff against
+            stat = await self._run_git(repo_path, ["show", "--stat", "--format=", branch], check=False)
+        if hold_reason:
+            reason = f"it needs approval because {hold_reason}; run `!publish` to approve, push, and open a PR"
+        elif session.session_context.get(APPROVAL_PENDING_CONTEXT_KEY) and not self.is_local_only(project):
+            reason = "earlier changes are waiting for approval; run `!publish` to approve, push, and open a PR"
+        elif session.publishing_paused and not self.is_local_only(project):
+            reason = "publishing is paused for this session; run `!publish` to push and open a PR"
+        else:
+            reason = "GitHub is not configured for this project, so no PR was opened"
+        lines = [f"Committed changes to local branch `{branch}` ({reason})."]
+        if stat.stdout.strip():
+            lines.append(f"```\n{stat.stdout.rstrip()}\n```")
+        lines.append(
+            f"Fetch it with `git fetch {socket.gethostname()}:{repo_path} {branch}`, "
+            "or use `!patch` to download the changes as a .patch file."
+        )
+        return "\n".join(lines)
+
+    async def _ensure_local_branch(self, session: Session, branch: str) -> None:
+        repo_path = session.project_path
+        rev_parse = await self._run_git(repo_path, ["rev-parse", "--verify", branch], check=False)
+        if rev_parse.returncode == 0:
+            current = await self._run_git(repo_path, ["rev-parse", "--abbrev-ref", "HEAD"], check=False)
+            if current.stdout.strip() != branch:
+                await self._run_git(repo_path, ["checkout", branch])
+            return
+
+        # Remember where the branch started so diffs and patches only cover this session
+        head = await self._run_git(repo_path, ["rev-parse", "HEAD"], check=False)
+        if head.returncode == 0 and head.stdout.strip():
+            self._session_manager.update_session_context(
+                session.id, {LOCAL_BASE_CONTEXT_KEY: head.stdout.strip()}
+            )
+        await self._run_git(repo_path, ["checkout", "-b", branch])
+
+    async def _publish_branch_update(
+        self, session: Session, project: Project, pr_title: str, require_commit: bool = True
+    ) -> Optional[str]:
+        branch = session_branch_name(session.id)
         await self._ensure_branch(session.project_path, project, branch)
         await self._run_git(session.project_path, ["add", "-A"])
-        if not await self._commit_changes(session.project_path, pr_title):
+        if not await self._commit_changes(session.project_path, pr_title) and require_commit:
             return None
 
         remote = _get_authenticated_url(project, self._github_manager.token) or "origin"
-        await self._run_git(session.project_path, ["push", "-u", remote, branch])
+        if session.session_context.get(FORCE_PUSH_CONTEXT_KEY):
+            # The branch was reset by `!redo`; replace the attempt that was pushed before
+            await self._run_git(session.project_path, ["push", "--force", "-u", remote, branch])
+            self._session_manager.update_session_context(session.id, {FORCE_PUSH_CONTEXT_KEY: False})
+        else:
+            await self._run_git(session.project_path, ["push", "-u", remote, branch])
 
-        existing_pr_number = self._get_existing_pr_number(session.id)
+        existing_pr_number = self._get_existing_pr_number(session.id, project)
 
         pr_summary = session.session_context.get("pr_summary", [])
+        design_url = discussion_url(session)
         if pr_summary and isinstance(pr_summary, list):
             summary_text = "\n".join(f"- {item}" for item in pr_summary)
-            body = (
-                f"{summary_text}\n\n---\nAutomated changes via Slack thread "
-                f"{session.thread_ts} in channel {session.channel_id}."
+            origin = (
+                f"Implements the design discussed in {design_url}."
+                if design_url
+                else f"Automated changes via Slack thread {session.thread_ts} in channel {session.channel_id}."
             )
+            body = f"{summary_text}\n\n---\n{origin}"
+        elif design_url:
+            body = f"Implements the design discussed in {design_url}."
         else:
             body = (
                 f"Automated changes requested via Slack thread {session.thread_ts} "
@@ -137,6 +493,7 @@ class GitWorkflowService:
         options = EnsurePROptions(
             title=pr_title,
             body=body,
+            labels=list(session.tags),
         )
         pr_ref = await self._github_manager.ensure_pull_request(
             project=project,
@@ -172,8 +529,9 @@ class GitWorkflowService:
             await self._run_git(repo_path, ["commit", "-m", message])
             return True
         except subprocess.CalledProcessError as exc:
-            stderr = (exc.stderr or "").lower()
-            if "nothing to commit" in stderr:
+            # git reports "nothing to commit" on stdout
+            output = f"{exc.stdout or ''}{exc.stderr or ''}".lower()
+            if "nothing to commit" in output:
                 return False
             raise
 
@@ -192,21 +550,16 @@ class GitWorkflowService:
         await self._prepare_base_branch(repo_path, base, project=project)
         await self._run_git(repo_path, ["checkout", "-B", branch, base])
 
-    def _get_existing_pr_number(self, session_id: UUID) -> Optional[int]:
+    def _get_existing_pr_number(self, session_id: UUID, project: Project) -> Optional[int]:
+        """The number of the session branch's PR in the project's repository, if one was opened."""
+        repo = project.github.full_name if project.github else None
         try:
-            pr_ref = self._session_manager.get_pr_ref(session_id)
+            pr_ref = self._session_manager.get_pr_ref(session_id, repo=repo)
             return pr_ref.number
         except SessionNotFound:
             return None
 
-    async def _run_git(self, cwd: Path, args: list[str], check: bool = True) -> subprocess.CompletedProcess:
-        def _execute() -> subprocess.CompletedProcess:
-            return subprocess.run(
-                ["git", *args],
-                cwd=str(cwd),
-                capture_output=True,
-                text=True,
-                check=check,
-            )
-
-        return await asyncio.to_thread(_execute)
+    async def _run_git(
+        self, cwd: Path, args: list[str], check: bool = True, stdin: Optional[str] = None
+    ) -> subprocess.CompletedProcess:
+        return await run_git(cwd, *args, check=check, stdin=stdin)
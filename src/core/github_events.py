@@ -0,0 +1,136 @@
+"""GitHub events about a session's PRs, posted into the session's thread.
+
+Point a GitHub webhook (repository or organization settings, content type
+`application/json`) at
+
+    POST /hooks/github
+
+on the webhook endpoint (`REMOTE_CODER_WEBHOOK_PORT`), with the secret in
+`REMOTE_CODER_GITHUB_WEBHOOK_SECRET`; requests without a valid
+`X-Hub-Signature-256` are refused. These events are understood:
+
+- `pull_request_review_comment` (created): a comment on a line of the diff;
+- `pull_request_review` (submitted): an approval, requested changes, or a
+  review with a summary;
+- `issue_comment` (created) on a pull request;
+- `check_suite` (completed): the CI result for the PR's head commit;
+- `pull_request` (closed): the PR was merged, or closed without merging.
+
+The PR is looked up among the sessions' PRs (see
+`SessionManager.find_pr_refs`). An archived or ended session is made active
+again in its thread, and the event is posted there and kept in the session's
+history, so a follow-up can ask the agent to address it. A merged or closed
+PR is only noted in the thread, without reactivating the session; merges
+count towards `!analytics`. Other events, and events about PRs no session
+opened, are acknowledged and ignored.
+"""
+
+from __future__ import annotations
+
+import hashlib
+import hmac
+import json
+from dataclasses import dataclass
+from typing import Any, Dict, List, Optional
+
+from .webhooks import WebhookError
+
+SIGNATURE_PREFIX = "sha256="
+# Longest comment or review body quoted in the thread
+MAX_QUOTED_CHARS = 1500
+
+_REVIEW_STATES = {
+    "approved": "approved the PR",
+    "changes_requested": "requested changes",
+    "commented": "reviewed the PR",
+}
+
+
+@dataclass(frozen=True)
+class PullRequestEvent:
+    repo: str  # "owner/repo"
+    number: int
+    text: str  # What to post in the session's thread
+    merged: Optional[bool] = None  # For a closed PR: whether it was merged; None for other events
+
+
+def verify_signature(secret: str, body: bytes, signature: Optional[str]) -> None:
+    """Check `X-Hub-Signature-256` against the webhook secret; raises WebhookError."""
+    expected = SIGNATURE_PREFIX + hmac.new(secret.encode(), body, hashlib.sha256).hexdigest()
+    if not signature or not hmac.compare_digest(signature.strip().encode(), expected.encode()):
+        raise WebhookError(401, "missing or wrong X-Hub-Signature-256")
+
+
+def _quote(body: Optional[str]) -> str:
+    text = (body or "").strip()
+    if not text:
+        return ""
+    if len(text) > MAX_QUOTED_CHARS:
+        text = text[:MAX_QUOTED_CHARS].rstrip() + " …"
+    return "\n" + "\n".join(f"> {line}" for line in text.splitlines())
+
+
+def _login(data: Dict[str, Any]) -> str:
+    return str((data.get("user") or {}).get("login") or "someone")
+
+
+def _event(
+    repo: str, number: object, text: str, url: object = None, merged: Optional[bool] = None
+) -> List[PullRequestEvent]:
+    if not isinstance(number, int) or number <= 0:
+        return []
+    return [PullRequestEvent(repo, number, f"{text}\n{url}" if url else text, merged)]
+
+
+def parse_github_event(event: Optional[str], body: bytes) -> List[PullRequestEvent]:
+    """What a GitHub webhook delivery means for each PR it is about; empty for events not handled."""
+    try:
+        payload = json.loads(body)
+    except (UnicodeDecodeError, ValueError) as exc:
+        raise WebhookError(400, f"body is not valid JSON: {exc}") from exc
+    if not isinstance(payload, dict):
+        raise WebhookError(400, "body must be a JSON object")
+    repo = str((payload.get("repository") or {}).get("full_name") or "")
+    action = payload.get("action")
+    if not repo:
+        return []
+
+    if event == "pull_request_review_comment" and action == "created":
+        comment, pull = payload.get("comment") or {}, payload.get("pull_request") or {}
+        where = f"`{comment.get('path')}`" + (f" line {comment['line']}" if comment.get("line") else "")
+        text = f"Review comment from @{_login(comment)} on {where}:{_quote(comment.get('body'))}"
+        return _event(repo, pull.get("number"), text, comment.get("html_url"))
+
+    if event == "pull_request_review" and action == "submitted":
+        review, pull = payload.get("review") or {}, payload.get("pull_request") or {}
+        summary = _quote(review.get("body"))
+        state = str(review.get("state") or "").lower()
+        if state == "commented" and not summary:
+            return []  # The comments themselves arrive as `pull_request_review_comment` events
+        text = f"@{_login(review)} {_REVIEW_STATES.get(state, 'reviewed the PR')}{':' if summary else '.'}{summary}"
+        return _event(repo, pull.get("number"), text, review.get("html_url"))
+
+    if event == "issue_comment" and action == "created":
+        issue, comment = payload.get("issue") or {}, payload.get("comment") or {}
+        if issue.get("pull_request") is None:
+            return []
+        text = f"Comment from @{_login(comment)} on the PR:{_quote(comment.get('body'))}"
+        return _event(repo, issue.get("number"), text, comment.get("html_url"))
+
+    if event == "check_suite" and action == "completed":
+        suite = payload.get("check_suite") or {}
+        conclusion = str(suite.get("conclusion") or "unknown").replace("_", " ")
+        app = (suite.get("app") or {}).get("name") or "CI"
+        commit = str(suite.get("head_sha") or "")[:7]
+        text = f"{app} finished on `{commit}`: {conclusion}."
+        pulls = [pull for pull in suite.get("pull_requests") or [] if isinstance(pull, dict)]
+        return [found for pull in pulls for found in _event(repo, pull.get("number"), text)]
+
+    if event == "pull_request" and action == "closed":
+        pull = payload.get("pull_request") or {}
+        merged = bool(pull.get("merged"))
+        by = _login({"user": pull.get("merged_by") if merged else payload.get("sender")})
+        text = f"Merged by @{by}." if merged else f"Closed without merging by @{by}."
+        return _event(repo, pull.get("number"), text, merged=merged)
+
+    return []
@@ -0,0 +1,110 @@
+"""A "still working" status line for long, quiet runs.
+
+An agent can work for minutes without printing anything (thinking, a long
+test run, an adapter that does not stream), and from the thread that looks
+the same as a daemon that died. While a run is alive, `Heartbeat` checks
+every `interval_secs` whether the run produced output or progress since the
+last check; when it did not, it posts one status message, e.g.
+"`claude` is still working, 4m 0s elapsed…", and edits that message on
+every later check. When the run ends the message is edited one last time to
+say how long it took, so no stale "still working" is left behind.
+
+In chats that cannot edit messages, a new line is posted only after a full
+interval without output, and at most `MAX_POSTS` times per run.
+"""
+
+from __future__ import annotations
+
+import asyncio
+import logging
+import time
+from typing import Optional
+
+from ..chat_adapters.health import format_duration
+from .output_stream import SendMessageFn, UpdateMessageFn
+
+LOGGER = logging.getLogger(__name__)
+
+DEFAULT_HEARTBEAT_SECS = 60
+MAX_POSTS = 5
+
+
+class Heartbeat:
+    """Posts and refreshes a status line while a run is quiet; use as `async with heartbeat:` around the run.
+
+    Call `touch()` whenever the run shows signs of life (output, a tool call).
+    """
+
+    def __init__(
+        self,
+        channel: str,
+        thread_ts: str,
+        *,
+        label: str,
+        send_message: SendMessageFn,
+        update_message: Optional[UpdateMessageFn] = None,
+        interval_secs: float = DEFAULT_HEARTBEAT_SECS,
+    ) -> None:
+        self._channel = channel
+        self._thread_ts = thread_ts
+        self._label = label
+        self._send_message = send_message
+        self._update_message = update_message
+        self._interval_secs = interval_secs
+        self._started = time.monotonic()
+        self._active = False  # Set by `touch`, cleared at each check
+        self._message_ts: Optional[str] = None
+        self._editable = update_message is not None
+        self._posts = 0
+        self._task: Optional[asyncio.Task] = None
+
+    def touch(self) -> None:
+        self._active = True
+
+    def render(self, *, finished: bool = False) -> str:
+        elapsed = format_duration(time.monotonic() - self._started)
+        if finished:
+            return f"`{self._label}` finished after {elapsed}."
+        return f"`{self._label}` is still working, {elapsed} elapsed…"
+
+    async def __aenter__(self) -> "Heartbeat":
+        self._started = time.monotonic()
+        self._task = asyncio.create_task(self._beat())
+        return self
+
+    async def __aexit__(self, *exc_info: object) -> None:
+        if self._task:
+            self._task.cancel()
+            try:
+                await self._task
+            except asyncio.CancelledError:
+                pass
+            self._task = None
+        if self._message_ts is not None and self._editable:
+            try:
+                await self._post(self.render(finished=True))
+            except Exception:  # pragma: no cover - the status line must not break the run
+                LOGGER.warning("Could not update the heartbeat of %s", self._label, exc_info=True)
+
+    async def _beat(self) -> None:
+        while self._editable or self._posts < MAX_POSTS:
+            await asyncio.sleep(self._interval_secs)
+            quiet = not self._active
+            self._active = False
+            # Once posted, an editable line keeps counting up; otherwise only silence earns a new line
+            if not quiet and not (self._editable and self._message_ts is not None):
+                continue
+            try:
+                await self._post(self.render())
+            except Exception:  # pragma: no cover - the status line must not break the run
+                LOGGER.warning("Could not post the heartbeat of %s", self._label, exc_info=True)
+
+    async def _post(self, text: str) -> None:
+        if self._message_ts is not None and self._editable and self._update_message is not None:
+            if await self._update_message(self._channel, self._message_ts, text):
+                return
+            self._editable = False
+            if self._posts >= MAX_POSTS:
+                return
+        self._message_ts = await self._send_message(self._channel, self._thread_ts, text)
+        self._posts += 1
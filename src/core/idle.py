@@ -0,0 +1,18 @@
+"""Ending sessions nobody has used for a while.
+
+With `REMOTE_CODER_SESSION_IDLE_HOURS` set, a session whose thread has seen
+no message and no agent reply for that long is ended, as `!end` would. Its
+thread is told (`EXPIRED_NOTE`) unless `REMOTE_CODER_DISABLE_IDLE_NOTICE` is
+set, and a new message there gets the usual "this session has ended" reply.
+
+Idleness is measured from `Session.last_activity_at`, not `updated_at`:
+bookkeeping such as recording a timezone, a PR, or a cost changes the
+latter without anyone using the session. Sessions with a run in progress
+are never ended, however long the run takes.
+"""
+
+from __future__ import annotations
+
+DEFAULT_SESSION_IDLE_HOURS = 0  # Off
+IDLE_CHECK_INTERVAL_SECS = 300
+EXPIRED_NOTE = "Session expired after {idle} without activity. Start a new thread to continue."
@@ -0,0 +1,149 @@
+"""Incident mode: the safest settings for a project while production is on fire.
+
+`!incident on [minutes]` (or `/rc-incident`) switches a project to incident
+mode for a window (an hour by default); `!incident off` ends it early. The
+window is kept in the daemon state, so a restart does not end it. While it
+lasts:
+
+- every change needs approval: edit runs ask the thread before the agent
+  starts, and their changes stay on the session branch until `!publish`,
+  whatever the project's `approval` fast path says;
+- the daemon logs at DEBUG, and every reply carries the run's agent, model,
+  duration, and cost;
+- each step of each run (the agent's, the pipeline's) is appended to
+  `<config dir>/audit/<project>.jsonl`;
+- threads started during the window are diagnosis threads: they run in ask
+  mode (see `src/core/ask_mode.py`), read-only, with the cheapest agent the
+  project allows. Threads that were already open keep their agent.
+"""
+
+from __future__ import annotations
+
+import json
+import logging
+import math
+import os
+from dataclasses import dataclass, replace
+from datetime import datetime, timedelta, timezone
+from pathlib import Path
+from typing import Any, Dict, List, Optional, Sequence
+
+from .agent_selection import COST_TIER_RANK
+from .models import Agent, ApprovalPolicy, PipelineStep, Project
+from .pipeline import APPROVE_STEP, EDIT_STEP, pipeline_for
+from .pricing import DEFAULT_EXPECTED_OUTPUT_TOKENS, cost_for_usage
+
+# Session context key marking a thread started during an incident
+INCIDENT_CONTEXT_KEY = "incident"
+DEFAULT_INCIDENT_MINUTES = 60
+MAX_INCIDENT_MINUTES = 24 * 60
+AUDIT_DIR = "audit"
+# Input size of the run the agents' prices are compared on
+REFERENCE_INPUT_TOKENS = 20_000
+PROMPT_SNIPPET_CHARS = 200
+
+LOGGER = logging.getLogger(__name__)
+
+
+@dataclass(frozen=True)
+class IncidentWindow:
+    project_id: str
+    started_at: datetime
+    ends_at: datetime
+    started_by: Optional[str] = None
+
+    def is_active(self, now: Optional[datetime] = None) -> bool:
+        return (now or datetime.now(timezone.utc)) < self.ends_at
+
+    def to_dict(self) -> Dict[str, Any]:
+        return {
+            "project_id": self.project_id,
+            "started_at": self.started_at.isoformat(),
+            "ends_at": self.ends_at.isoformat(),
+            "started_by": self.started_by,
+        }
+
+    @classmethod
+    def from_dict(cls, data: Dict[str, Any]) -> "IncidentWindow":
+        """Raises KeyError or ValueError for a malformed window."""
+        return cls(
+            project_id=str(data["project_id"]),
+            started_at=datetime.fromisoformat(data["started_at"]),
+            ends_at=datetime.fromisoformat(data["ends_at"]),
+            started_by=data.get("started_by"),
+        )
+
+
+def open_window(
+    project_id: str, minutes: int, started_by: Optional[str] = None, now: Optional[datetime] = None
+) -> IncidentWindow:
+    started_at = now or datetime.now(timezone.utc)
+    return IncidentWindow(project_id, started_at, started_at + timedelta(minutes=minutes), started_by)
+
+
+def incident_pipeline(steps: Sequence[PipelineStep]) -> List[PipelineStep]:
+    """`steps` with an `approve` step right before `edit`, unless there is one already."""
+    steps = list(steps)
+    edit_index = next(index for index, step in enumerate(steps) if step.name == EDIT_STEP)
+    if edit_index and steps[edit_index - 1].name == APPROVE_STEP:
+        return steps
+    return [*steps[:edit_index], PipelineStep(APPROVE_STEP), *steps[edit_index:]]
+
+
+def incident_project(project: Project) -> Project:
+    """`project` as its runs see it during an incident: every run approved, every change held."""
+    approval = ApprovalPolicy(
+        required=True, max_files=0, max_lines=0, protected_paths=list(project.approval.protected_paths)
+    )
+    return replace(project, approval=approval, pipeline=incident_pipeline(pipeline_for(project)))
+
+
+def cheapest_agent(agents: Sequence[Agent], project: Project) -> Optional[Agent]:
+    """The project's cheapest agent: lowest `cost_tier`, then lowest priced run, then agents.yaml order."""
+
+    def price(agent: Agent) -> float:
+        estimate = cost_for_usage(
+            agent, project.model_for(agent), REFERENCE_INPUT_TOKENS, DEFAULT_EXPECTED_OUTPUT_TOKENS
+        )
+        return estimate.cost_usd if estimate else math.inf
+
+    candidates = [(index, agent) for index, agent in enumerate(agents) if project.allows_agent(agent.id)]
+    if not candidates:
+        return None
+    _, best = min(
+        candidates,
+        key=lambda item: (COST_TIER_RANK[item[1].capabilities.cost_tier], price(item[1]), item[0]),
+    )
+    return best
+
+
+def set_debug_logging(active: bool) -> None:
+    """Log everything of the daemon at DEBUG while an incident is on; otherwise at the configured level."""
+    logging.getLogger("src").setLevel(logging.DEBUG if active else logging.NOTSET)
+
+
+def audit_path(config_dir: Path, project_id: str) -> Path:
+    return config_dir / AUDIT_DIR / f"{project_id}.jsonl"
+
+
+class IncidentAudit:
+    """Appends one JSON line per run step to a project's audit log."""
+
+    def __init__(self, path: Path) -> None:
+        self.path = path
+
+    def record(self, event: str, **details: Any) -> None:
+        entry = {"at": datetime.now(timezone.utc).isoformat(), "event": event, **details}
+        try:
+            self.path.parent.mkdir(parents=True, exist_ok=True)
+            # Prompts and replies can contain anything pasted into chat; keep the file private
+            fd = os.open(self.path, os.O_WRONLY | os.O_CREAT | os.O_APPEND, 0o600)
+            with os.fdopen(fd, "a", encoding="utf-8") as fp:
+                fp.write(json.dumps(entry, default=str) + "\n")
+        except OSError as exc:
+            LOGGER.warning("Could not write to the audit log %s: %s", self.path, exc)
+
+
+def snippet(text: str) -> str:
+    text = " ".join(text.split())
+    return text if len(text) <= PROMPT_SNIPPET_CHARS else text[: PROMPT_SNIPPET_CHARS - 1] + "…"
@@ -0,0 +1,313 @@
+"""One runner for the daemon's own subprocesses, everything that is not an agent CLI.
+
+Setup, verify, and teardown steps and the shell tool of API agents (through
+`sandbox`), git commands of the session workflow, `!batch` worktrees, ask
+mode, run summaries, and the timeline, project creation, and devcontainer
+image builds all start their processes with `JobRunner.run`. Each job gets:
+
+- a timeout, `DEFAULT_JOB_TIMEOUT_SECS` unless the caller passes one; a job
+  that runs over is killed along with its process group (its process tree
+  on Windows);
+- cancellation: when the task awaiting a job is cancelled (`!cancel`, a
+  shutdown), the process is killed instead of being left running;
+- a slot: at most `REMOTE_CODER_MAX_JOBS` (default 8) jobs run at once, and
+  the others wait in arrival order;
+- a log line when it fails or times out, and per-kind metrics (runs,
+  failures, timeouts, cancellations, time spent) shown by `!status --system`.
+
+The daemon shares `JOBS`; tests can make their own runner.
+"""
+
+from __future__ import annotations
+
+import asyncio
+import collections
+import logging
+import os
+import signal
+import subprocess
+import time
+from dataclasses import dataclass
+from pathlib import Path
+from typing import Awaitable, Callable, Deque, Dict, List, Mapping, Optional, Sequence, Union
+
+from ..agent_adapters.windows import IS_WINDOWS, create_process, kill_process_tree
+
+LOGGER = logging.getLogger(__name__)
+
+DEFAULT_MAX_JOBS = 8
+DEFAULT_JOB_TIMEOUT_SECS = 600
+MAX_CAPTURE_BYTES = 1_000_000
+# After a kill, wait this long for the output pipes to drain
+DRAIN_TIMEOUT_SECS = 5
+# Exit code reported for a timed-out job where a CompletedProcess is expected, as `timeout(1)` does
+TIMEOUT_EXIT_CODE = 124
+
+# Kills a job's process on timeout or cancellation; the runner still kills the process itself afterwards
+KillFn = Callable[[asyncio.subprocess.Process], Awaitable[None]]
+
+
+@dataclass
+class JobResult:
+    command: Union[str, List[str]]
+    returncode: Optional[int]  # None when the job timed out
+    stdout: str
+    stderr: str = ""  # Empty when stderr was merged into stdout
+    timed_out: bool = False
+    truncated: bool = False
+    duration_secs: float = 0.0
+
+    @property
+    def passed(self) -> bool:
+        return not self.timed_out and self.returncode == 0
+
+
+@dataclass
+class JobStats:
+    runs: int = 0
+    failures: int = 0
+    timeouts: int = 0
+    cancelled: int = 0
+    total_secs: float = 0.0
+
+
+class JobRunner:
+    """Runs subprocesses with a shared concurrency limit, timeouts, cancellation, logging, and metrics."""
+
+    def __init__(self, limit: int = DEFAULT_MAX_JOBS, default_timeout_secs: float = DEFAULT_JOB_TIMEOUT_SECS) -> None:
+        self._limit = limit
+        self._active = 0
+        self._waiting: Deque[asyncio.Future] = collections.deque()
+        self.default_timeout_secs = default_timeout_secs
+        self._stats: Dict[str, JobStats] = {}
+
+    @property
+    def limit(self) -> int:
+        return self._limit
+
+    def set_limit(self, limit: int) -> None:
+        """Apply a new cap (on config reload); extra running jobs finish, while free slots start waiting ones."""
+        self._limit = limit
+        self._wake()
+
+    def stats(self) -> Dict[str, JobStats]:
+        return dict(sorted(self._stats.items()))
+
+    def describe(self) -> str:
+        return f"{self._active} of {self._limit} job slots in use, {len(self._waiting)} waiting"
+
+    async def run(
+        self,
+        kind: str,
+        command: Union[str, Sequence[str]],
+        *,
+        cwd: Optional[Path] = None,
+        timeout_secs: Optional[float] = None,
+        env: Optional[Mapping[str, str]] = None,
+        stdin: Optional[str] = None,
+        merge_stderr: bool = False,
+        own_process_group: bool = True,
+        preexec_fn: Optional[Callable[[], None]] = None,
+        kill: Optional[KillFn] = None,
+        capture_bytes: int = MAX_CAPTURE_BYTES,
+    ) -> JobResult:
+        """Run `command` (a shell command line when it is a string) and capture its output.
+
+        Raises OSError when the program cannot be started, and CancelledError after killing the
+        process when the awaiting task is cancelled.
+        """
+        timeout = self.default_timeout_secs if timeout_secs is None else timeout_secs
+        label = command if isinstance(command, str) else " ".join(command)
+        await self._acquire()
+        started = time.monotonic()
+        stats = self._stats.setdefault(kind, JobStats())
+        stats.runs += 1
+        try:
+            options = dict(
+                cwd=str(cwd) if cwd is not None else None,
+                stdin=asyncio.subprocess.PIPE if stdin is not None else asyncio.subprocess.DEVNULL,
+                stdout=asyncio.subprocess.PIPE,
+                stderr=asyncio.subprocess.STDOUT if merge_stderr else asyncio.subprocess.PIPE,
+                env=dict(env) if env is not None else None,
+                start_new_session=own_process_group,
+                preexec_fn=preexec_fn,
+            )
+            LOGGER.debug("Starting %s job in %s: %s", kind, cwd, label)
+            try:
+                if isinstance(command, str):
+                    process = await asyncio.create_subprocess_shell(command, **options)
+                else:
+                    process = await create_process(*command, **options)
+            except OSError:
+                stats.failures += 1
+                raise
+
+            assert process.stdout is not None
+            captures = [asyncio.create_task(_capture(process.stdout, capture_bytes))]
+            if process.stderr is not None:
+                captures.append(asyncio.create_task(_capture(process.stderr, capture_bytes)))
+            feed = None
+            if stdin is not None and process.stdin is not None:
+                feed = asyncio.create_task(_feed(process.stdin, stdin))
+            timed_out = False
+            try:
+                await asyncio.wait_for(process.wait(), timeout=timeout)
+            except asyncio.TimeoutError:
+                timed_out = True
+                await _kill(process, own_process_group, kill)
+            except asyncio.CancelledError:
+                stats.cancelled += 1
+                LOGGER.info("Killing %s job cancelled after %.1fs: %s", kind, time.monotonic() - started, label)
+                await _kill(process, own_process_group, kill)
+                for task in [*captures, feed]:
+                    if task:
+                        task.cancel()
+                raise
+            if feed:
+                feed.cancel()
+            done, pending = await asyncio.wait(captures, timeout=DRAIN_TIMEOUT_SECS)
+            for capture in pending:
+                # A detached grandchild is still holding the pipe open
+                capture.cancel()
+            outputs = [capture.result() if capture in done else ("", True) for capture in captures]
+            stdout, stdout_truncated = outputs[0]
+            stderr, stderr_truncated = outputs[1] if len(outputs) > 1 else ("", False)
+        finally:
+            stats.total_secs += time.monotonic() - started
+            self._release()
+
+        duration = time.monotonic() - started
+        result = JobResult(
+            command=command if isinstance(command, str) else list(command),
+            returncode=None if timed_out else process.returncode,
+            stdout=stdout,
+            stderr=stderr,
+            timed_out=timed_out,
+            truncated=stdout_truncated or stderr_truncated,
+            duration_secs=duration,
+        )
+        if timed_out:
+            stats.timeouts += 1
+            LOGGER.warning("%s job timed out after %ss and was killed: %s", kind, timeout, label)
+        elif not result.passed:
+            stats.failures += 1
+            LOGGER.info("%s job exited with %s after %.1fs: %s", kind, result.returncode, duration, label)
+        return result
+
+    async def _acquire(self) -> None:
+        if self._active < self._limit and not self._waiting:
+            self._active += 1
+            return
+        # Plain futures rather than an asyncio.Semaphore, whose limit cannot change
+        waiter = asyncio.get_running_loop().create_future()
+        self._waiting.append(waiter)
+        try:
+            await waiter
+        except asyncio.CancelledError:
+            if waiter in self._waiting:
+                self._waiting.remove(waiter)
+            elif waiter.done() and not waiter.cancelled():
+                # The slot was handed over just as the job was cancelled
+                self._release()
+            raise
+
+    def _release(self) -> None:
+        self._active -= 1
+        self._wake()
+
+    def _wake(self) -> None:
+        while self._waiting and self._active < self._limit:
+            waiter = self._waiting.popleft()
+            if waiter.done():
+                continue
+            self._active += 1
+            waiter.set_result(None)
+
+
+JOBS = JobRunner()
+
+
+async def run_git(
+    cwd: Path,
+    *args: str,
+    check: bool = True,
+    stdin: Optional[str] = None,
+    timeout_secs: Optional[float] = None,
+) -> subprocess.CompletedProcess:
+    """`git <args>` in `cwd` as a job, returned like `subprocess.run(..., capture_output=True, text=True)`.
+
+    A timed-out command comes back with `TIMEOUT_EXIT_CODE`, so `check` raises CalledProcessError for it too.
+    """
+    result = await JOBS.run("git", ["git", *args], cwd=cwd, stdin=stdin, timeout_secs=timeout_secs)
+    stderr = result.stderr
+    if result.timed_out:
+        stderr = f"{stderr}\ngit {args[0] if args else ''} timed out".strip()
+    completed = subprocess.CompletedProcess(
+        ["git", *args],
+        TIMEOUT_EXIT_CODE if result.timed_out else result.returncode,
+        result.stdout,
+        stderr,
+    )
+    if check:
+        completed.check_returncode()
+    return completed
+
+
+def format_job_stats(runner: JobRunner) -> str:
+    """Jobs in use and per-kind totals for `!status --system`."""
+    lines = [f"Jobs: {runner.describe()}"]
+    for kind, stats in runner.stats().items():
+        problems = [
+            f"{count} {label}"
+            for count, label in (
+                (stats.failures, "failed"),
+                (stats.timeouts, "timed out"),
+                (stats.cancelled, "cancelled"),
+            )
+            if count
+        ]
+        details = f" ({', '.join(problems)})" if problems else ""
+        lines.append(f"  {kind}: {stats.runs} run(s), {stats.total_secs:.1f}s{details}")
+    return "\n".join(lines)
+
+
+async def _kill(process: asyncio.subprocess.Process, own_process_group: bool, kill: Optional[KillFn]) -> None:
+    if kill is not None:
+        await kill(process)
+    elif IS_WINDOWS:
+        await kill_process_tree(process)
+    elif own_process_group:
+        try:
+            os.killpg(process.pid, signal.SIGKILL)
+        except (ProcessLookupError, PermissionError):
+            pass
+    if process.returncode is None:
+        try:
+            process.kill()
+        except ProcessLookupError:
+            pass
+    await process.wait()
+
+
+async def _feed(stream: asyncio.StreamWriter, text: str) -> None:
+    try:
+        stream.write(text.encode())
+        await stream.drain()
+    except (BrokenPipeError, ConnectionResetError):
+        pass
+    finally:
+        stream.close()
+
+
+async def _capture(stream: asyncio.StreamReader, limit: int) -> tuple[str, bool]:
+    buffer = bytearray()
+    truncated = False
+    while True:
+        chunk = await stream.read(65536)
+        if not chunk:
+            break
+        buffer += chunk
+        if len(buffer) > limit:
+            del buffer[: len(buffer) - limit]
+            truncated = True
+    return buffer.decode("utf-8", errors="replace"), truncated
@@ -0,0 +1,41 @@
+"""Resolve the timezone that times are shown and interpreted in.
+
+Sessions remember the IANA timezone from the Slack profile of the user who
+last wrote in the thread. Times without an explicit zone in the config (such
+as `quiet_hours` without `timezone`) are then read in that user's time, and
+the daemon's local time is only the fallback when no profile zone is known.
+"""
+
+from __future__ import annotations
+
+from datetime import datetime, tzinfo
+from typing import Optional
+from zoneinfo import ZoneInfo, ZoneInfoNotFoundError
+
+
+def is_valid_timezone(name: Optional[str]) -> bool:
+    if not name:
+        return False
+    try:
+        ZoneInfo(name)
+    except (ZoneInfoNotFoundError, ValueError):
+        return False
+    return True
+
+
+def zone_for(name: Optional[str]) -> tzinfo:
+    """`name` as a tzinfo, or the daemon's local zone when it is empty or unknown."""
+    if is_valid_timezone(name):
+        return ZoneInfo(name)
+    return datetime.now().astimezone().tzinfo
+
+
+def to_local(moment: datetime, zone_name: Optional[str]) -> datetime:
+    return moment.astimezone(zone_for(zone_name))
+
+
+def format_local_time(moment: datetime, zone_name: Optional[str]) -> str:
+    """E.g. "09:00 Europe/Berlin", or "09:00 (server time)" without a zone."""
+    local = to_local(moment, zone_name)
+    suffix = zone_name if is_valid_timezone(zone_name) else "(server time)"
+    return f"{local.strftime('%H:%M')} {suffix}"
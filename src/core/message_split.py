@@ -0,0 +1,56 @@
+"""Fit outgoing text into a chat platform's message length limit.
+
+`split_message` cuts long replies into several messages at line breaks; a
+code block cut in two is closed at the end of one message and reopened (with
+its language) at the start of the next, so every part renders on its own.
+Edits cannot be split, so `clip_message` cuts them short instead.
+"""
+
+from __future__ import annotations
+
+from typing import Iterator, List, Optional
+
+FENCE = "```"
+CLOSE_FENCE = f"\n{FENCE}"
+
+
+def split_message(text: str, limit: int) -> List[str]:
+    """`text` as messages of at most `limit` characters each."""
+    if len(text) <= limit:
+        return [text]
+    # Longer lines are cut so a part can also hold a reopened fence line and the closing fence
+    width = max((limit - len(CLOSE_FENCE) - 1) // 2, 1)
+    parts: List[str] = []
+    current = ""
+    fence: Optional[str] = None  # Opening line of the code block `current` ends inside
+    for line in _cut_lines(text, width):
+        after = _fence_after(fence, line)
+        candidate = f"{current}\n{line}" if current else line
+        if current and len(candidate) + (len(CLOSE_FENCE) if after else 0) > limit:
+            parts.append(current + (CLOSE_FENCE if fence else ""))
+            candidate = f"{fence}\n{line}" if fence else line
+        current = candidate
+        fence = after
+    if current:
+        parts.append(current)
+    return parts
+
+
+def clip_message(text: str, limit: int) -> str:
+    """`text` cut to `limit` characters, ending in an ellipsis when it was longer."""
+    return text if len(text) <= limit else text[: limit - 1] + "…"
+
+
+def _cut_lines(text: str, width: int) -> Iterator[str]:
+    for line in text.split("\n"):
+        while len(line) > width:
+            yield line[:width]
+            line = line[width:]
+        yield line
+
+
+def _fence_after(fence: Optional[str], line: str) -> Optional[str]:
+    """The open code block after `line`: `line` opens one, closes `fence`, or leaves it as is."""
+    if not line.lstrip().startswith(FENCE):
+        return fence
+    return None if fence else line.strip()
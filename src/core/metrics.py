@@ -0,0 +1,56 @@
+"""Session lifecycle counts since startup, kept from the event bus (see `src/core/events.py`).
+
+`!status --system` shows them in a line and the status page JSON under
+`sessions`.
+"""
+
+from __future__ import annotations
+
+from dataclasses import dataclass
+
+from .events import EventBus, PrOpened, RunFinished, RunStarted, SessionCreated, SessionEnded, SessionEvent
+from .models import SessionStatus
+
+
+@dataclass
+class SessionMetrics:
+    """Totals since startup."""
+
+    sessions_created: int = 0
+    sessions_ended: int = 0
+    sessions_archived: int = 0
+    runs_started: int = 0
+    runs_failed: int = 0
+    runs_cancelled: int = 0
+    prs_opened: int = 0
+    cost_usd: float = 0.0  # Estimated, for agents with pricing
+
+    def follow(self, bus: EventBus) -> None:
+        bus.subscribe(self.record, SessionCreated, SessionEnded, RunStarted, RunFinished, PrOpened)
+
+    def record(self, event: SessionEvent) -> None:
+        if isinstance(event, SessionCreated):
+            self.sessions_created += 1
+        elif isinstance(event, SessionEnded):
+            if event.status == SessionStatus.ARCHIVED:
+                self.sessions_archived += 1
+            else:
+                self.sessions_ended += 1
+        elif isinstance(event, RunStarted):
+            self.runs_started += 1
+        elif isinstance(event, RunFinished):
+            if event.run.cancelled:
+                self.runs_cancelled += 1
+            elif not event.run.success:
+                self.runs_failed += 1
+            self.cost_usd += event.run.cost_usd or 0.0
+        elif isinstance(event, PrOpened):
+            self.prs_opened += 1
+
+    def describe(self) -> str:
+        return (
+            f"Since startup: {self.sessions_created} session(s) started, "
+            f"{self.sessions_ended + self.sessions_archived} ended or archived; "
+            f"{self.runs_started} run(s) ({self.runs_failed} failed, {self.runs_cancelled} cancelled), "
+            f"{self.prs_opened} PR(s) opened, ~${self.cost_usd:.2f}"
+        )
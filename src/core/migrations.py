@@ -0,0 +1,109 @@
+"""Versioned config files and their automatic migration.
+
+`projects.yaml` and `agents.yaml` carry a top-level `version:`; files written
+before it existed are version 1. When the daemon loads a file older than the
+version it reads, it applies the registered migrations in order, copies the
+original to `<file>.v<old version>.bak`, and writes the upgraded file back
+(comments are not kept; the backup has them). A file newer than the installed
+Remote Coder understands is refused with a message saying so.
+
+A format change bumps the file's entry in `CONFIG_VERSIONS` and appends a
+`Migration` from the previous version to `MIGRATIONS`. Migrations get the
+parsed YAML and return it in the next version's shape; raising `ConfigError`
+(or any KeyError/TypeError/ValueError) stops the load with the file untouched.
+"""
+
+from __future__ import annotations
+
+import logging
+import shutil
+from dataclasses import dataclass
+from pathlib import Path
+from typing import Any, Callable, Dict, List
+
+import yaml
+
+from .errors import ConfigError
+
+LOGGER = logging.getLogger(__name__)
+
+VERSION_KEY = "version"
+# Unversioned files predate the key
+UNVERSIONED = 1
+
+# The version of each config file this Remote Coder reads
+CONFIG_VERSIONS: Dict[str, int] = {
+    "projects.yaml": 1,
+    "agents.yaml": 1,
+}
+
+
+@dataclass(frozen=True)
+class Migration:
+    from_version: int  # Upgrades to from_version + 1
+    summary: str
+    apply: Callable[[Dict[str, Any]], Dict[str, Any]]
+
+
+MIGRATIONS: Dict[str, List[Migration]] = {
+    "projects.yaml": [],
+    "agents.yaml": [],
+}
+
+
+def config_version(data: Dict[str, Any], path: Path) -> int:
+    raw = data.get(VERSION_KEY, UNVERSIONED)
+    if isinstance(raw, bool) or not isinstance(raw, int) or raw < 1:
+        raise ConfigError(f"{path} version must be a positive whole number")
+    return raw
+
+
+def migrate_config(path: Path, data: Dict[str, Any], name: str) -> Dict[str, Any]:
+    """`data`, read from `path` (a `name` file), upgraded to the version this Remote Coder reads.
+
+    An upgrade also rewrites `path`.
+    """
+    current = CONFIG_VERSIONS[name]
+    version = config_version(data, path)
+    if version > current:
+        raise ConfigError(
+            f"{path} is version {version}, but this Remote Coder reads {name} up to version {current}; "
+            "upgrade Remote Coder (`pip install -U remote-coder`)"
+        )
+    if version == current:
+        return data
+
+    steps = {migration.from_version: migration for migration in MIGRATIONS[name]}
+    migrated = dict(data)
+    for step in range(version, current):
+        migration = steps.get(step)
+        if migration is None:
+            raise ConfigError(f"No migration of {name} from version {step} to {step + 1}")
+        try:
+            migrated = migration.apply(migrated)
+        except ConfigError:
+            raise
+        except (KeyError, TypeError, ValueError) as exc:
+            raise ConfigError(
+                f"Could not migrate {path} to version {step + 1} ({migration.summary}): {exc}; "
+                "update it by hand or restore a version Remote Coder can read"
+            ) from exc
+        LOGGER.info("Migrated %s to version %d: %s", path, step + 1, migration.summary)
+    migrated = {VERSION_KEY: current, **{key: value for key, value in migrated.items() if key != VERSION_KEY}}
+    _write_back(path, version, migrated)
+    return migrated
+
+
+def _write_back(path: Path, old_version: int, data: Dict[str, Any]) -> None:
+    """Keep the original next to the file and replace it with the upgraded one."""
+    backup = path.with_name(f"{path.name}.v{old_version}.bak")
+    try:
+        shutil.copy2(path, backup)
+        path.write_text(yaml.dump(data, default_flow_style=False, sort_keys=False), encoding="utf-8")
+    except OSError as exc:
+        # The upgraded config is still used for this run; the next start migrates again
+        LOGGER.warning("Could not write the migrated %s back: %s", path, exc)
+        return
+    LOGGER.warning(
+        "Upgraded %s from version %d to %d; the original is in %s", path, old_version, data[VERSION_KEY], backup
+    )
@@ -3,10 +3,10 @@
 from __future__ import annotations
 
 from dataclasses import dataclass, field
-from datetime import datetime, timezone
+from datetime import datetime, time, timezone
 from enum import Enum
 from pathlib import Path
-from typing import Any, Dict, List, Optional
+from typing import Any, Dict, List, Optional, Tuple
 from uuid import UUID, uuid4
 
 
@@ -14,6 +14,35 @@ class AgentType(str, Enum):
     CLAUDE = "claude"
     CODEX = "codex"
     GEMINI = "gemini"
+    OPENROUTER = "openrouter"
+    OLLAMA = "ollama"
+    HTTP = "http"
+    WASM = "wasm"
+    MOCK = "mock"
+
+
+class CostTier(str, Enum):
+    LOW = "low"
+    MEDIUM = "medium"
+    HIGH = "high"
+
+
+class AgentSelection(str, Enum):
+    DEFAULT = "default"  # Always run the project's default agent (or whatever `!use` picked)
+    AUTO = "auto"  # Pick an agent per request from the agents' capabilities
+
+
+class SandboxMode(str, Enum):
+    NONE = "none"  # Plain subprocess with the daemon's environment
+    PROCESS = "process"  # Own process group, scrubbed environment, optional rlimits
+    CONTAINER = "container"  # `docker run` with the working directory mounted
+    BUBBLEWRAP = "bubblewrap"  # `bwrap` namespaces: read-only system dirs, only the working directory writable
+
+
+class DevEnvKind(str, Enum):
+    NIX = "nix"  # `nix develop` on the project's flake
+    MISE = "mise"  # `mise exec` with the repo's .mise.toml or .tool-versions
+    ASDF = "asdf"  # asdf's shims first on PATH, following .tool-versions
 
 
 class WorkingDirMode(Enum):
@@ -21,12 +50,166 @@ class WorkingDirMode(Enum):
     FIXED = "fixed"
 
 
+class SpawnMode(str, Enum):
+    PIPE = "pipe"  # stdout and stderr are pipes
+    PTY = "pty"  # stdout is a pseudo-terminal, for CLIs that need a TTY
+
+
 @dataclass
 class GitHubRepoConfig:
     owner: str
     repo: str
     default_base_branch: str
 
+    @property
+    def full_name(self) -> str:
+        """`owner/repo`, as GitHub names the repository."""
+        return f"{self.owner}/{self.repo}"
+
+
+# Lines that ask for a yes/no answer on stdin, e.g. "Apply this change? (y/n)" or "Continue? [Y/n]"
+DEFAULT_CONFIRM_PATTERNS = (
+    r"\((?:y/n|yes/no)\)\s*[:?]?\s*$",
+    r"\[(?:y/n|yes/no)\]\s*[:?]?\s*$",
+)
+DEFAULT_CONFIRM_TIMEOUT_SECS = 600
+
+
+@dataclass
+class ConfirmPrompts:
+    """Confirmation prompts an agent CLI prints and waits on, forwarded to the thread as buttons."""
+
+    patterns: List[str] = field(default_factory=lambda: list(DEFAULT_CONFIRM_PATTERNS))  # Case-insensitive regexes
+    approve: str = "y"  # Written to stdin (with a newline) when approved
+    deny: str = "n"  # ...when denied, unanswered after `timeout_secs`, or asked where there are no buttons
+    timeout_secs: int = DEFAULT_CONFIRM_TIMEOUT_SECS
+
+
+@dataclass
+class ProcessLimits:
+    """CPU, memory, and open-file limits for an agent's CLI process (see agent_adapters/limits.py)."""
+
+    cpu_secs: Optional[int] = None  # CPU time per process (RLIMIT_CPU)
+    memory_mb: Optional[int] = None  # Address space per process (RLIMIT_AS), or the cgroup's MemoryMax
+    open_files: Optional[int] = None  # RLIMIT_NOFILE
+    cpu_percent: Optional[int] = None  # Cgroup only: CPUQuota across the process tree, 100 = one core
+    cgroup: bool = False  # Run in a transient systemd scope so memory and CPU limits cover the whole tree
+
+
+@dataclass
+class SandboxConfig:
+    """How untrusted shell commands (agent shell tools, verify steps) are run."""
+
+    mode: SandboxMode = SandboxMode.PROCESS
+    image: Optional[str] = None  # Required for container mode, unless it comes from a devcontainer
+    runtime: str = "docker"  # Container CLI: docker or podman
+    network: bool = False  # Container and bubblewrap modes; process mode cannot block the network
+    memory_mb: Optional[int] = None
+    cpu_secs: Optional[int] = None  # Process mode only
+    env_passthrough: List[str] = field(default_factory=list)  # Daemon env vars the command may see
+    run_agent: bool = False  # Projects only: also run the agent's CLI in the container
+    read_only_paths: List[str] = field(default_factory=list)  # Bubblewrap only: extra host paths to show
+    writable_paths: List[str] = field(default_factory=list)  # Bubblewrap only: host paths it may write besides cwd
+    devcontainer: bool = False  # Projects only: the image is built from the repo's devcontainer.json
+    env: Dict[str, str] = field(default_factory=dict)  # Set in the container; a devcontainer's `containerEnv`
+
+
+@dataclass
+class DevEnvironment:
+    """A project's development shell, entered for its agent runs and steps (see agent_adapters/dev_env.py)."""
+
+    kind: DevEnvKind = DevEnvKind.NIX
+    flake: Optional[str] = None  # Nix only: flake reference, e.g. `.#ci`; None uses the flake in the working directory
+    trust: bool = False  # mise only: trust the repository's config (`mise --yes`) rather than require `mise trust`
+
+
+@dataclass
+class CommandStep:
+    run: str
+    timeout_secs: Optional[int] = None  # None uses the default for the phase
+
+
+@dataclass
+class PipelineStep:
+    """One step of a project's run pipeline (see `src/core/pipeline.py`): a built-in step or a hook command."""
+
+    name: str
+    run: Optional[str] = None  # Hook steps: the shell command, run through the project's sandbox
+    timeout_secs: Optional[int] = None  # Hook and approve steps
+
+    @property
+    def is_hook(self) -> bool:
+        return self.run is not None
+
+
+@dataclass
+class QuietHours:
+    """A daily window in which agents are not started; may wrap past midnight."""
+
+    start: time
+    end: time
+    timezone: Optional[str] = None  # IANA name; None uses the daemon's local time
+
+
+@dataclass
+class ApprovalPolicy:
+    """Hold agent changes on the session branch until `!publish`, except for small, low-risk diffs."""
+
+    required: bool = False
+    max_files: int = 3  # Fast path: diffs within both limits skip approval; 0 disables the fast path
+    max_lines: int = 30  # Added plus removed lines
+    protected_paths: List[str] = field(default_factory=list)  # Globs that always need approval
+
+
+# Seconds an agent CLI gets to exit after the SIGINT at its soft timeout, unless `hard_timeout_secs` is set
+DEFAULT_KILL_GRACE_SECS = 30
+# Columns and rows of the pseudo-terminal agents with `spawn_mode: pty` run in
+DEFAULT_PTY_SIZE: Tuple[int, int] = (200, 50)
+
+
+@dataclass(frozen=True)
+class RunTimeouts:
+    """Wall-clock limits for one run attempt, in seconds from its start."""
+
+    soft_secs: float  # The agent's CLI gets SIGINT
+    hard_secs: float  # The run is cancelled and the CLI gets SIGKILL
+
+    @classmethod
+    def from_settings(
+        cls, timeout_secs: Optional[float], hard_timeout_secs: Optional[float]
+    ) -> Optional["RunTimeouts"]:
+        if timeout_secs is None:
+            return None
+        return cls(timeout_secs, hard_timeout_secs or timeout_secs + DEFAULT_KILL_GRACE_SECS)
+
+
+@dataclass
+class WebhookConfig:
+    """Lets external systems trigger a project's `.cockpit` commands over HTTP (see `src/core/webhooks.py`)."""
+
+    token: str  # Callers send it as `Authorization: Bearer <token>`
+    commands: List[str] = field(default_factory=list)  # Commands it may trigger; empty allows every one
+
+
+@dataclass
+class DiscussionsConfig:
+    """Design conversations in the repository's GitHub Discussions (see `src/chat_adapters/github_discussions.py`)."""
+
+    category: Optional[str] = None  # Only discussions in this category start sessions; None takes every one
+
+
+@dataclass
+class ProjectRoles:
+    """The agents for the steps of a project's runs (see `src/core/coordinator.py`)."""
+
+    planner: Optional[str] = None  # Writes the plan (the pipeline's `plan` step)
+    coder: Optional[str] = None  # Makes the changes (`edit`); None leaves it to the session's agent
+    reviewer: Optional[str] = None  # Critiques the changes (the pipeline's `review` step)
+
+    @property
+    def is_set(self) -> bool:
+        return any((self.planner, self.coder, self.reviewer))
+
 
 @dataclass
 class Project:
@@ -36,11 +219,118 @@ class Project:
     default_agent_id: str
     github: Optional[GitHubRepoConfig] = None
     default_model: Optional[str] = None
+    verify_commands: List[CommandStep] = field(default_factory=list)  # Run after changes are applied
+    setup_commands: List[CommandStep] = field(default_factory=list)  # Run before the verify steps
+    teardown_commands: List[CommandStep] = field(default_factory=list)  # Always run after the verify steps
+    format_commands: List[CommandStep] = field(default_factory=list)  # Run by the pipeline's `format` step
+    sandbox: SandboxConfig = field(default_factory=SandboxConfig)  # Applies to setup/verify/teardown
+    agent_selection: AgentSelection = AgentSelection.DEFAULT
+    languages: List[str] = field(default_factory=list)  # Overrides language detection for agent selection
+    agent_models: Dict[str, str] = field(default_factory=dict)  # Agent id -> default model in this project
+    allowed_agents: List[str] = field(default_factory=list)  # Empty allows every configured agent
+    group: Optional[str] = None  # Project group from projects.yaml `groups:`
+    max_session_cost_usd: Optional[float] = None  # Runs stop once a session's estimated cost reaches this
+    max_runs_per_month: Optional[int] = None  # Agent runs per calendar month (daemon's local time)
+    max_concurrent_runs: int = 1  # Agent runs sharing the checkout at once; later ones queue (see run_queue.py)
+    max_active_sessions: Optional[int] = None  # Sessions on the checkout at once; `!takeover` ends the others
+    quiet_hours: Optional[QuietHours] = None
+    approval: ApprovalPolicy = field(default_factory=ApprovalPolicy)
+    channels: List[str] = field(default_factory=list)  # Extra chat channels mapped to this project
+    ask_channels: List[str] = field(default_factory=list)  # Question-only channels (see `src/core/ask_mode.py`)
+    webhook: Optional[WebhookConfig] = None
+    discussions: Optional[DiscussionsConfig] = None
+    # Replace the agents' `timeout_secs`/`hard_timeout_secs` for runs in this project
+    timeout_secs: Optional[int] = None
+    hard_timeout_secs: Optional[int] = None
+    pipeline: List[PipelineStep] = field(default_factory=list)  # Empty uses the default (see `src/core/pipeline.py`)
+    dev_env: Optional[DevEnvironment] = None  # `env:` in projects.yaml
+    roles: ProjectRoles = field(default_factory=ProjectRoles)
+
+    def allows_agent(self, agent_id: str) -> bool:
+        return not self.allowed_agents or agent_id in self.allowed_agents
+
+    def model_for(self, agent: "Agent") -> Optional[str]:
+        """The model an agent starts with in this project, before any `!use` override."""
+        if agent.id in self.agent_models:
+            return self.agent_models[agent.id]
+        if agent.id == self.default_agent_id and self.default_model:
+            return self.default_model
+        return agent.models.get("default") if agent.models else None
+
+    def timeouts_for(self, agent: "Agent") -> Optional[RunTimeouts]:
+        """The time limits of a run of `agent` in this project; None runs without a limit."""
+        if self.timeout_secs is not None:
+            return RunTimeouts.from_settings(self.timeout_secs, self.hard_timeout_secs)
+        return agent.timeouts
 
 
 class SessionStatus(str, Enum):
-    ACTIVE = "active"
+    """Where a session is; `SessionManager` checks each change against the transitions it allows."""
+
+    ACTIVE = "active"  # Idle, waiting for the next request
+    QUEUED = "queued"  # A run is waiting for the project's checkout or a daemon run slot
+    RUNNING = "running"
+    AWAITING_APPROVAL = "awaiting_approval"  # A pipeline `approve` step is waiting for the thread's answer
+    AWAITING_INPUT = "awaiting_input"  # The agent asked the thread a question (`confirm_prompts`)
+    FAILED = "failed"  # The last run failed; the next request runs as usual
     ENDED = "ended"
+    PAUSED = "paused"  # Parked by `!pause`: messages are not run until `!resume`
+    ARCHIVED = "archived"  # Soft-deleted by cleanup; `remote-coder sessions restore` brings it back
+
+    @property
+    def is_open(self) -> bool:
+        """Whether the session takes requests: active, failed, or part way through a run."""
+        return self not in (SessionStatus.ENDED, SessionStatus.PAUSED, SessionStatus.ARCHIVED)
+
+    @property
+    def in_run(self) -> bool:
+        return self in (
+            SessionStatus.QUEUED,
+            SessionStatus.RUNNING,
+            SessionStatus.AWAITING_APPROVAL,
+            SessionStatus.AWAITING_INPUT,
+        )
+
+    @property
+    def label(self) -> str:
+        """E.g. "awaiting approval"."""
+        return self.value.replace("_", " ")
+
+
+@dataclass
+class AgentPricing:
+    """Cost rates (USD) used to estimate what a run of an agent costs."""
+
+    per_run_usd: float = 0.0
+    input_per_1k_tokens_usd: float = 0.0
+    output_per_1k_tokens_usd: float = 0.0
+    model_overrides: Dict[str, "AgentPricing"] = field(default_factory=dict)
+
+    def for_model(self, model: Optional[str]) -> "AgentPricing":
+        """Return the rates for a specific model, falling back to the agent-wide rates."""
+        if model and model in self.model_overrides:
+            return self.model_overrides[model]
+        return self
+
+
+@dataclass
+class AgentCapabilities:
+    """What an agent is good at, used by automatic agent selection."""
+
+    supports_images: bool = False
+    max_context: Optional[int] = None  # Tokens; None means unknown (never ruled out on size)
+    cost_tier: CostTier = CostTier.MEDIUM
+    languages: List[str] = field(default_factory=list)  # Empty means a generalist
+
+
+@dataclass
+class McpServer:
+    """An MCP server made available to an agent, launched over stdio or reached by URL."""
+
+    name: str
+    command: List[str] = field(default_factory=list)
+    env: Dict[str, str] = field(default_factory=dict)
+    url: Optional[str] = None
 
 
 @dataclass
@@ -52,6 +342,38 @@ class Agent:
     fixed_path: Optional[Path] = None
     env: Dict[str, str] = field(default_factory=dict)
     models: Dict[str, Any] = field(default_factory=dict)  # {"default": "sonnet", "available": [...]}
+    pricing: Optional[AgentPricing] = None
+    endpoint: Optional[str] = None  # Base URL for API-backed agents
+    api_key_env: Optional[str] = None  # Env var holding the API key for API-backed agents
+    options: Dict[str, Any] = field(default_factory=dict)  # Adapter-specific settings
+    mcp_servers: List[McpServer] = field(default_factory=list)
+    plugin: Optional[str] = None  # WASM plugin name for wasm agents (defaults to the agent id)
+    capabilities: AgentCapabilities = field(default_factory=AgentCapabilities)
+    sandbox: SandboxConfig = field(default_factory=SandboxConfig)  # Applies to the run_shell tool
+    min_version: Optional[str] = None  # Oldest supported CLI version for command-based agents
+    spawn_mode: SpawnMode = SpawnMode.PIPE
+    pty_size: Optional[Tuple[int, int]] = None  # (columns, rows) of the pseudo-terminal; see DEFAULT_PTY_SIZE
+    confirm_prompts: Optional[ConfirmPrompts] = None
+    limits: Optional[ProcessLimits] = None
+    # Run policy: wall-clock limit per attempt, extra attempts after errors or timeouts, and a spend cap
+    timeout_secs: Optional[int] = None  # Soft limit: the CLI gets SIGINT
+    hard_timeout_secs: Optional[int] = None  # The CLI gets SIGKILL; defaults to DEFAULT_KILL_GRACE_SECS later
+    max_retries: int = 0
+    max_cost_usd: Optional[float] = None
+    # Rate limits, provider 5xx, and network errors are retried with backoff (see transient.py)
+    transient_retries: int = 2
+    retry_backoff_secs: float = 5.0
+
+    @property
+    def timeouts(self) -> Optional[RunTimeouts]:
+        return RunTimeouts.from_settings(self.timeout_secs, self.hard_timeout_secs)
+
+    @property
+    def terminal_size(self) -> Optional[Tuple[int, int]]:
+        """Size of the pseudo-terminal the CLI runs in, or None when it runs with pipes."""
+        if self.spawn_mode != SpawnMode.PTY:
+            return None
+        return self.pty_size or DEFAULT_PTY_SIZE
 
 
 @dataclass
@@ -85,10 +407,26 @@ class Session:
     conversation_summary: Optional[str] = None  # Cached summary of early interactions
     summary_interaction_count: int = 0  # How many interactions were summarized
     session_context: Dict[str, Any] = field(default_factory=dict)
+    estimated_cost_usd: float = 0.0  # Running total of run costs from the pricing estimator
+    publishing_paused: bool = False  # Set by !unpublish; changes stay on the local branch until !publish
+    agent_pinned: bool = False  # Set by !use; turns off automatic agent selection for the session
+    timezone: Optional[str] = None  # IANA zone from the Slack profile of the user who last wrote in the thread
+    tags: List[str] = field(default_factory=list)  # Set by !tag; added as labels to the session's PRs
     status: SessionStatus = SessionStatus.ACTIVE
+    archived_at: Optional[datetime] = None
     id: UUID = field(default_factory=uuid4)
     created_at: datetime = field(default_factory=lambda: datetime.now(timezone.utc))
     updated_at: datetime = field(default_factory=lambda: datetime.now(timezone.utc))
+    # Last message in the thread or agent reply; other changes only move `updated_at` (see `src/core/idle.py`)
+    last_activity_at: datetime = field(default_factory=lambda: datetime.now(timezone.utc))
+
+
+class PullRequestRole(str, Enum):
+    """How a pull request relates to the session's work."""
+
+    PRIMARY = "primary"  # Opened from the session branch; one per repository
+    STACKED = "stacked"  # Based on another PR's branch
+    BACKPORT = "backport"  # Carries the changes to a release branch
 
 
 @dataclass
@@ -99,6 +437,8 @@ class PullRequestRef:
     url: str
     head_branch: str
     base_branch: str
+    role: PullRequestRole = PullRequestRole.PRIMARY
+    repo: Optional[str] = None  # "owner/repo"; None for the project's repository
     created_at: datetime = field(default_factory=lambda: datetime.now(timezone.utc))
     updated_at: datetime = field(default_factory=lambda: datetime.now(timezone.utc))
 
@@ -111,6 +451,23 @@ class CommandArg:
     description: Optional[str] = None
 
 
+@dataclass
+class RunOverrides:
+    """Agent parameters that apply to a single run only."""
+
+    model: Optional[str] = None
+    temperature: Optional[float] = None
+    reasoning_effort: Optional[str] = None  # One of REASONING_EFFORTS
+
+    def adapter_params(self) -> Dict[str, Any]:
+        """The set parameters other than `model`, as adapter `run` keyword arguments."""
+        params = {"temperature": self.temperature, "reasoning_effort": self.reasoning_effort}
+        return {name: value for name, value in params.items() if value is not None}
+
+
+REASONING_EFFORTS = ("minimal", "low", "medium", "high")
+
+
 @dataclass
 class CommandDefinition:
     id: str
@@ -119,3 +476,4 @@ class CommandDefinition:
     body: str
     description: Optional[str] = None
     category: Optional[str] = None
+    overrides: RunOverrides = field(default_factory=RunOverrides)
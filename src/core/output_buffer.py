@@ -0,0 +1,153 @@
+"""Buffering for agent output on its way to the chat.
+
+Agents report output a few characters or a line at a time. `LineBuffer`
+assembles it into lines the way a terminal shows them: a carriage return
+without a newline starts the line over, so a progress bar redrawn a hundred
+times is one line showing its latest state rather than a hundred lines.
+
+`OutputBuffer` batches those lines into chunks for chats that cannot edit
+messages (see `OutputStream`). A chunk is flushed once it holds `max_chars`
+or its first line has waited `interval_secs`, and chunks are sent at least
+`MIN_FLUSH_GAP_SECS` apart. After `MAX_CHUNKS` chunks the rest of the run's
+output is only counted; the last flush says how many lines were left out.
+"""
+
+from __future__ import annotations
+
+import asyncio
+import time
+from typing import Awaitable, Callable, List, Optional
+
+DEFAULT_CHUNK_CHARS = 2800
+DEFAULT_FLUSH_INTERVAL_SECS = 15
+MIN_FLUSH_GAP_SECS = 2
+MAX_CHUNKS = 20
+
+# Sends one chunk of output lines
+FlushFn = Callable[[str], Awaitable[None]]
+
+
+def visible_line(raw: str) -> str:
+    """What a terminal shows for `raw` after its carriage returns: the last text written over the line."""
+    segments = [segment for segment in raw.split("\r") if segment]
+    return segments[-1] if segments else ""
+
+
+class LineBuffer:
+    """Splits fed output into finished lines, keeping the unfinished one open."""
+
+    def __init__(self) -> None:
+        self._partial = ""
+
+    @property
+    def partial(self) -> str:
+        return visible_line(self._partial)
+
+    def feed(self, text: str) -> List[str]:
+        """The lines `text` finishes, as shown after carriage returns."""
+        *finished, partial = (self._partial + text).replace("\r\n", "\n").split("\n")
+        # Only the latest redraw of an unfinished line matters; keep its trailing \r for the \r\n check
+        head, separator, tail = partial.rpartition("\r")
+        self._partial = tail if tail else f"{visible_line(head)}{separator}"
+        return [visible_line(line) for line in finished]
+
+    def close(self) -> Optional[str]:
+        """The unfinished line, if any, ending the buffer."""
+        line, self._partial = self.partial, ""
+        return line or None
+
+
+class OutputBuffer:
+    """Batches output lines into chunks flushed on size and time thresholds.
+
+    Use as `async with buffer:` around the run; what is still buffered is flushed on exit.
+    """
+
+    def __init__(
+        self,
+        flush: FlushFn,
+        *,
+        max_chars: int = DEFAULT_CHUNK_CHARS,
+        interval_secs: float = DEFAULT_FLUSH_INTERVAL_SECS,
+    ) -> None:
+        self._flush = flush
+        self._max_chars = max_chars
+        self._interval_secs = interval_secs
+        self._lines = LineBuffer()
+        self._pending: List[str] = []
+        self._pending_chars = 0
+        self._first_pending_at: Optional[float] = None
+        self._chunks = 0
+        self._dropped = 0
+        self._wake = asyncio.Event()
+        self._full = asyncio.Event()
+        self._task: Optional[asyncio.Task] = None
+
+    def feed(self, text: str) -> None:
+        for line in self._lines.feed(text):
+            self._add(line)
+
+    def _add(self, line: str) -> None:
+        line = line.rstrip()
+        if not line:
+            return
+        if self._chunks >= MAX_CHUNKS:
+            self._dropped += 1
+            return
+        if self._first_pending_at is None:
+            self._first_pending_at = time.monotonic()
+        self._pending.append(line)
+        self._pending_chars += len(line) + 1
+        self._wake.set()
+        if self._pending_chars >= self._max_chars:
+            self._full.set()
+
+    async def __aenter__(self) -> "OutputBuffer":
+        self._task = asyncio.create_task(self._flush_periodically())
+        return self
+
+    async def __aexit__(self, *exc_info: object) -> None:
+        if self._task:
+            self._task.cancel()
+            try:
+                await self._task
+            except asyncio.CancelledError:
+                pass
+            self._task = None
+        last = self._lines.close()
+        if last:
+            self._add(last)
+        while self._pending and self._chunks < MAX_CHUNKS:
+            await self._send_chunk()
+        self._dropped += len(self._pending)
+        if self._dropped:
+            await self._flush(f"… {self._dropped} more line{'' if self._dropped == 1 else 's'} of output not shown")
+
+    async def _flush_periodically(self) -> None:
+        while self._chunks < MAX_CHUNKS:
+            await self._wake.wait()
+            deadline = (self._first_pending_at or time.monotonic()) + self._interval_secs
+            try:
+                await asyncio.wait_for(self._full.wait(), timeout=max(deadline - time.monotonic(), 0))
+            except asyncio.TimeoutError:
+                pass
+            await self._send_chunk()
+            await asyncio.sleep(MIN_FLUSH_GAP_SECS)
+
+    async def _send_chunk(self) -> None:
+        """Send the oldest `max_chars` of buffered lines; a longer single line is cut to fit."""
+        chunk: List[str] = []
+        size = 0
+        while self._pending and (not chunk or size + len(self._pending[0]) + 1 <= self._max_chars):
+            line = self._pending.pop(0)
+            chunk.append(line[: self._max_chars])
+            size += len(line) + 1
+        self._pending_chars = sum(len(line) + 1 for line in self._pending)
+        self._first_pending_at = time.monotonic() if self._pending else None
+        if not self._pending:
+            self._wake.clear()
+        if self._pending_chars < self._max_chars:
+            self._full.clear()
+        if chunk:
+            self._chunks += 1
+            await self._flush("\n".join(chunk))
@@ -0,0 +1,220 @@
+"""Live agent output in the session thread.
+
+While an agent runs, the text it produces (its messages, not the raw JSON
+events of its CLI) is shown in a single status message in the thread. The
+message is posted when the first output arrives and then edited with the
+latest lines at most every `interval_secs`, so long runs show progress
+without flooding the thread or running into the chat's rate limits. The
+agent's final reply is still posted as its own message; the structured
+REMOTE_CODER_OUTPUT block at the end of a run is left out of the stream.
+
+Lines are assembled by `LineBuffer`, so progress bars redrawn with carriage
+returns show as one line. In chats that cannot edit messages, the output
+after the first message is posted in chunks instead (see `OutputBuffer`),
+flushed on size and time thresholds rather than line by line.
+
+Adapters that report tool calls (`on_progress`) get a checklist of the
+run's steps in the message instead of its output (see `progress`).
+"""
+
+from __future__ import annotations
+
+import asyncio
+import logging
+from collections import deque
+from typing import Awaitable, Callable, Deque, Optional
+
+from ..agent_adapters.progress import ProgressEvent, StepStatus
+from .errors import SlackError
+from .output_buffer import DEFAULT_FLUSH_INTERVAL_SECS, LineBuffer, OutputBuffer
+from .progress import ProgressChecklist
+
+LOGGER = logging.getLogger(__name__)
+
+DEFAULT_STREAM_INTERVAL_SECS = 3
+MAX_LINES = 15
+MAX_LINE_CHARS = 300
+MAX_CHARS = 2800
+# Room the message around the output takes (label, line count, code fence)
+FRAME_CHARS = 120
+STRUCTURED_OUTPUT_MARKER = "REMOTE_CODER_OUTPUT:"
+
+# (channel, thread_ts, text) -> ts of the posted message
+SendMessageFn = Callable[[str, Optional[str], str], Awaitable[Optional[str]]]
+# (channel, ts, text) -> False when the chat adapter cannot edit messages
+UpdateMessageFn = Callable[[str, str, str], Awaitable[bool]]
+
+
+class OutputStream:
+    """Coalesces an agent's output into one status message that is edited as the run goes on.
+
+    Use as `async with stream:` around the run and pass `stream.feed` to the adapter as `on_output`
+    (and `stream.progress` as `on_progress` to adapters that report tool calls).
+    """
+
+    def __init__(
+        self,
+        channel: str,
+        thread_ts: str,
+        *,
+        label: str,
+        send_message: SendMessageFn,
+        update_message: UpdateMessageFn,
+        interval_secs: float = DEFAULT_STREAM_INTERVAL_SECS,
+        message_chars: Optional[int] = None,
+    ) -> None:
+        """`message_chars` is the chat's message length limit; the output shown is kept short enough for it."""
+        self._channel = channel
+        self._thread_ts = thread_ts
+        self._label = label
+        self._send_message = send_message
+        self._update_message = update_message
+        self._interval_secs = interval_secs
+        self._max_chars = MAX_CHARS
+        if message_chars is not None:
+            self._max_chars = max(min(MAX_CHARS, message_chars - FRAME_CHARS - len(label)), MAX_LINE_CHARS)
+        self._lines: Deque[str] = deque(maxlen=MAX_LINES)
+        self._buffer = LineBuffer()
+        self._checklist = ProgressChecklist()
+        self._line_count = 0
+        self._hidden = False  # Set once the structured output block starts
+        self._dirty = False
+        self._message_ts: Optional[str] = None
+        self._editable = True
+        self._chunks: Optional[OutputBuffer] = None  # Set once the message turns out not to be editable
+        self._first_message_lines = 0  # Finished lines shown in the first message
+        self._wake = asyncio.Event()
+        self._task: Optional[asyncio.Task] = None
+
+    @property
+    def line_count(self) -> int:
+        return self._line_count + (1 if self._buffer.partial.strip() else 0)
+
+    def feed(self, text: str) -> None:
+        """Add output; text after the last newline stays open until more arrives."""
+        if self._hidden or not text:
+            return
+        marker = text.find(STRUCTURED_OUTPUT_MARKER)
+        if marker != -1:
+            text = text[:marker]
+            self._hidden = True
+        if self._chunks is not None:
+            self._chunks.feed(text)
+            return
+        for line in self._buffer.feed(text):
+            line = line.rstrip()
+            if line:
+                self._lines.append(_clip(line))
+                self._line_count += 1
+        self._dirty = True
+        self._wake.set()
+
+    def progress(self, event: ProgressEvent) -> None:
+        """Record a tool call starting or finishing; the message shows the checklist from then on."""
+        self._checklist.apply(event)
+        self._dirty = True
+        self._wake.set()
+
+    def render(self, *, finished: bool = False) -> str:
+        lines = list(self._lines)
+        partial = self._buffer.partial
+        if partial.strip():
+            lines.append(_clip(partial.rstrip()))
+        if self._checklist:
+            return self._render_checklist(lines[-1] if lines else "", finished=finished)
+        shown = "\n".join(lines).replace("```", "'''")
+        if len(shown) > self._max_chars:
+            shown = "…" + shown[-(self._max_chars - 1) :]
+        count = self.line_count
+        details = f"{'finished' if finished else 'running'}, {count} line{'' if count == 1 else 's'}"
+        if count > len(lines):
+            details += f", last {len(lines)}"
+        return f"`{self._label}` output ({details}):\n```\n{shown}\n```"
+
+    def _render_checklist(self, latest: str, *, finished: bool) -> str:
+        counts = self._checklist.counts()
+        steps = sum(counts.values())
+        details = f"{'finished' if finished else 'running'}, {steps} step{'' if steps == 1 else 's'}"
+        if counts[StepStatus.FAILED]:
+            details += f", {counts[StepStatus.FAILED]} failed"
+        lines = [f"`{self._label}` progress ({details}):", *self._checklist.render()]
+        if latest:
+            lines.append(f"> {latest}")
+        text = "\n".join(lines)
+        return text if len(text) <= self._max_chars else text[: self._max_chars - 1] + "…"
+
+    async def __aenter__(self) -> "OutputStream":
+        self._task = asyncio.create_task(self._publish_periodically())
+        return self
+
+    async def __aexit__(self, *exc_info: object) -> None:
+        if self._task:
+            self._task.cancel()
+            try:
+                await self._task
+            except asyncio.CancelledError:
+                pass
+            self._task = None
+        if self._chunks is not None:
+            await self._chunks.__aexit__(*exc_info)
+            return
+        # Only a message that was posted gets its final state; runs that end before their first update post nothing
+        if self._message_ts is not None:
+            self._dirty = True
+            await self._publish(finished=True)
+
+    async def _publish_periodically(self) -> None:
+        while self._editable:
+            await self._wake.wait()
+            self._wake.clear()
+            await self._publish()
+            await asyncio.sleep(self._interval_secs)
+
+    async def _publish(self, *, finished: bool = False) -> None:
+        has_content = bool(self._lines or self._buffer.partial.strip() or self._checklist)
+        if not self._dirty or not self._editable or not has_content:
+            return
+        self._dirty = False
+        text = self.render(finished=finished)
+        try:
+            if self._message_ts is None:
+                self._first_message_lines = self._line_count
+                self._message_ts = await self._send_message(self._channel, self._thread_ts, text)
+                self._editable = self._message_ts is not None
+            elif not await self._update_message(self._channel, self._message_ts, text):
+                self._editable = False
+                await self._post_in_chunks()
+        except SlackError as exc:
+            LOGGER.warning("Failed to update the live output message: %s", exc)
+
+    async def _post_in_chunks(self) -> None:
+        """Continue in new messages, batched, since this chat cannot edit the first one."""
+        async def post(chunk: str) -> None:
+            try:
+                await self._send_message(self._channel, self._thread_ts, f"`{self._label}` output:\n```\n{chunk}\n```")
+            except SlackError as exc:
+                LOGGER.warning("Failed to post agent output: %s", exc)
+
+        self._chunks = OutputBuffer(
+            post, max_chars=self._max_chars, interval_secs=max(self._interval_secs, DEFAULT_FLUSH_INTERVAL_SECS)
+        )
+        await self._chunks.__aenter__()
+        # The lines since the first message, and the unfinished one, continue in the chunks
+        unsent = min(self._line_count - self._first_message_lines, len(self._lines))
+        for line in list(self._lines)[len(self._lines) - unsent :] if unsent > 0 else []:
+            self._chunks.feed(f"{line}\n")
+        self._chunks.feed(self._buffer.partial)
+
+
+def format_partial_output(text: str) -> str:
+    """The end of what a stopped run printed, as a code block; empty when it printed nothing."""
+    text = text.split(STRUCTURED_OUTPUT_MARKER)[0].strip().replace("```", "'''")
+    if not text:
+        return ""
+    if len(text) > MAX_CHARS:
+        text = "…" + text[-(MAX_CHARS - 1) :]
+    return f"```\n{text}\n```"
+
+
+def _clip(line: str) -> str:
+    return line if len(line) <= MAX_LINE_CHARS else line[: MAX_LINE_CHARS - 1] + "…"
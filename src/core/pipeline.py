@@ -0,0 +1,101 @@
+"""The steps a run goes through, as set by a project's `pipeline:` in projects.yaml.
+
+Built-in steps:
+
+- `plan`: the agent first writes a plan without editing files; it is posted
+  to the thread and the `edit` step gets it with the request.
+- `approve`: the thread is asked to approve (with buttons) before the next
+  step; a denial, no answer within `timeout_secs`, or a chat without buttons
+  stops the run there.
+- `edit`: the agent works on the request. Every pipeline has it, once.
+- `format`: the project's `format` commands (for example `ruff format .`).
+- `verify`: the project's setup, verify, and teardown commands; the results
+  are added to the reply.
+- `review`: the project's `reviewer` agent critiques the run's changes in the
+  thread (see `src/core/coordinator.py`); it never stops the run.
+- `diff-review`: the run's changes are posted to the thread, file by file,
+  when there is no pull request to look at.
+- `pr`: the changes are committed and published (subject to `approval`).
+
+Other entries are hook steps, `{run: <command>}`, run through the project's
+sandbox like verify steps. A failing format, verify, or hook step stops the
+run; steps after `edit` that did not run leave the changes uncommitted.
+Without `pipeline:`, runs go through `edit`, `format` (if the project has
+`format` commands), `pr`, and `diff-review`. A project with a `planner` role
+gets a `plan` step first, and one with a `reviewer` a `review` step before
+`pr`, when its pipeline does not list them.
+"""
+
+from __future__ import annotations
+
+from typing import List, Optional, Sequence
+
+from .models import PipelineStep, Project, ProjectRoles
+
+PLAN_STEP = "plan"
+APPROVE_STEP = "approve"
+EDIT_STEP = "edit"
+FORMAT_STEP = "format"
+VERIFY_STEP = "verify"
+DIFF_REVIEW_STEP = "diff-review"
+PR_STEP = "pr"
+REVIEW_STEP = "review"
+BUILTIN_STEPS = (
+    PLAN_STEP,
+    APPROVE_STEP,
+    EDIT_STEP,
+    FORMAT_STEP,
+    VERIFY_STEP,
+    REVIEW_STEP,
+    DIFF_REVIEW_STEP,
+    PR_STEP,
+)
+# Steps that work on the agent's changes, so they come after `edit`
+AFTER_EDIT_STEPS = frozenset({FORMAT_STEP, VERIFY_STEP, REVIEW_STEP, DIFF_REVIEW_STEP, PR_STEP})
+# Steps that may be listed more than once
+REPEATABLE_STEPS = frozenset({APPROVE_STEP})
+
+DEFAULT_APPROVE_TIMEOUT_SECS = 3600
+
+PLAN_NOTE = (
+    "Plan only: explore the code and reply with the steps you would take and the files you would change. "
+    "Do not edit any files yet; the plan is reviewed before you implement it."
+)
+PLANNED_NOTE = "You already planned this work; implement the plan below, adjusting it only where the code requires."
+
+
+def pipeline_for(project: Project) -> List[PipelineStep]:
+    """The project's `pipeline`, or the default one, with the steps its roles need."""
+    if project.pipeline:
+        return role_steps(project.pipeline, project.roles)
+    names = [EDIT_STEP, FORMAT_STEP] if project.format_commands else [EDIT_STEP]
+    return role_steps([PipelineStep(name) for name in (*names, PR_STEP, DIFF_REVIEW_STEP)], project.roles)
+
+
+def role_steps(steps: Sequence[PipelineStep], roles: ProjectRoles) -> List[PipelineStep]:
+    """`steps` with a `plan` step for a planner and a `review` step for a reviewer, unless they have them."""
+    steps = list(steps)
+    names = [step.name for step in steps]
+    if roles.planner and PLAN_STEP not in names:
+        steps.insert(0, PipelineStep(PLAN_STEP))
+    if roles.reviewer and REVIEW_STEP not in names:
+        pr_index = next((index for index, step in enumerate(steps) if step.name == PR_STEP), len(steps))
+        steps.insert(pr_index, PipelineStep(REVIEW_STEP))
+    return steps
+
+
+def pipeline_problem(steps: Sequence[PipelineStep]) -> Optional[str]:
+    """Why the steps do not make a pipeline, or None when they do."""
+    names = [step.name for step in steps if not step.is_hook]
+    if names.count(EDIT_STEP) != 1:
+        return f"must list `{EDIT_STEP}` exactly once"
+    repeated = sorted({name for name in names if names.count(name) > 1} - REPEATABLE_STEPS)
+    if repeated:
+        return f"lists {', '.join(f'`{name}`' for name in repeated)} more than once"
+    edit_index = names.index(EDIT_STEP)
+    if PLAN_STEP in names and names.index(PLAN_STEP) > edit_index:
+        return f"must list `{PLAN_STEP}` before `{EDIT_STEP}`"
+    early = [name for name in names[:edit_index] if name in AFTER_EDIT_STEPS]
+    if early:
+        return f"must list {', '.join(f'`{name}`' for name in early)} after `{EDIT_STEP}`"
+    return None
@@ -0,0 +1,150 @@
+"""Per-user defaults for new sessions.
+
+Users set their preferences with `!prefs` (or `/rc-prefs`); they are kept in
+the daemon state with the sessions and apply to every session the user
+starts afterwards, in any project. Sessions keep what they started with, so
+changing a preference does not change threads that are already running.
+
+- `agent`: the agent new sessions start with, pinned as with `!use`, when
+  the project allows it; otherwise the project's default is used.
+- `verbosity`: `quiet` leaves out the live output message and the file
+  progress notes, `verbose` adds the run's model, duration, and cost to each
+  reply; `normal` is neither.
+- `notify`: `dm`, or a channel, gets a note whenever a run of the user's
+  sessions finishes.
+- `locale`: e.g. `de-DE`; the agent is asked to write its replies for it.
+"""
+
+from __future__ import annotations
+
+import re
+from dataclasses import dataclass, fields, replace
+from enum import Enum
+from typing import Any, Collection, Dict, List, Optional, Tuple
+
+from .errors import RemoteCoderError
+from .models import Session
+
+# Session context key holding the preferences of the user who started the session
+PREFERENCES_CONTEXT_KEY = "user_preferences"
+STARTED_BY_CONTEXT_KEY = "started_by"
+NOTIFY_DM = "dm"
+
+_LOCALE = re.compile(r"^[A-Za-z]{2,3}(?:-[A-Za-z0-9]{2,8})*$")
+_CHANNEL = re.compile(r"^#?[\w.-]{1,80}$")
+
+LOCALE_NOTE = (
+    "Write your reply for a reader using the `{locale}` locale: its language and its date and number formats. "
+    "Keep code, identifiers, and commit messages in the style the project already uses."
+)
+
+
+class PreferenceError(RemoteCoderError):
+    """Raised for an unknown preference or a value it does not accept."""
+
+
+class Verbosity(str, Enum):
+    QUIET = "quiet"
+    NORMAL = "normal"
+    VERBOSE = "verbose"
+
+
+@dataclass(frozen=True)
+class UserPreferences:
+    agent: Optional[str] = None
+    verbosity: Verbosity = Verbosity.NORMAL
+    notify: Optional[str] = None  # `dm` or a channel
+    locale: Optional[str] = None
+
+    @property
+    def is_default(self) -> bool:
+        return self == UserPreferences()
+
+    def to_dict(self) -> Dict[str, Any]:
+        return {
+            "agent": self.agent,
+            "verbosity": self.verbosity.value,
+            "notify": self.notify,
+            "locale": self.locale,
+        }
+
+    @classmethod
+    def from_dict(cls, data: Dict[str, Any]) -> "UserPreferences":
+        """Raises ValueError for a verbosity it does not know."""
+        return cls(
+            agent=data.get("agent"),
+            verbosity=Verbosity(data.get("verbosity") or Verbosity.NORMAL.value),
+            notify=data.get("notify"),
+            locale=data.get("locale"),
+        )
+
+    def describe(self) -> List[str]:
+        return [
+            f"agent: `{self.agent}`" if self.agent else "agent: the project's default",
+            f"verbosity: `{self.verbosity.value}`",
+            f"notify: `{self.notify}`" if self.notify else "notify: off",
+            f"locale: `{self.locale}`" if self.locale else "locale: not set",
+        ]
+
+
+PREFERENCE_NAMES = tuple(field.name for field in fields(UserPreferences))
+
+
+def with_preference(
+    preferences: UserPreferences, name: str, value: str, *, agent_ids: Collection[str]
+) -> UserPreferences:
+    """`preferences` with `name` set to `value`, checked against what the preference accepts."""
+    name = name.lower()
+    if name == "agent":
+        agent_id = value.lower()
+        if agent_id not in agent_ids:
+            raise PreferenceError(f"Unknown agent `{agent_id}`")
+        return replace(preferences, agent=agent_id)
+    if name == "verbosity":
+        try:
+            return replace(preferences, verbosity=Verbosity(value.lower()))
+        except ValueError:
+            options = ", ".join(f"`{level.value}`" for level in Verbosity)
+            raise PreferenceError(f"Verbosity must be one of {options}") from None
+    if name == "notify":
+        notify = value.lower() if value.lower() == NOTIFY_DM else value
+        if notify != NOTIFY_DM and not _CHANNEL.match(notify):
+            raise PreferenceError("Notify must be `dm` or a channel, e.g. `#builds` or `C0123456789`")
+        return replace(preferences, notify=notify)
+    if name == "locale":
+        locale = value.replace("_", "-")
+        if not _LOCALE.match(locale):
+            raise PreferenceError("Locale must be a language tag such as `en-US`, `de-DE`, or `ja`")
+        return replace(preferences, locale=locale)
+    raise PreferenceError(f"Unknown preference `{name}`; use one of {', '.join(PREFERENCE_NAMES)}")
+
+
+def without_preference(preferences: UserPreferences, name: str) -> UserPreferences:
+    """`preferences` with `name` back at its default."""
+    name = name.lower()
+    if name not in PREFERENCE_NAMES:
+        raise PreferenceError(f"Unknown preference `{name}`; use one of {', '.join(PREFERENCE_NAMES)}")
+    default = getattr(UserPreferences(), name)
+    return replace(preferences, **{name: default})
+
+
+def session_preferences(session: Session) -> Tuple[Optional[str], UserPreferences]:
+    """Who started `session` and the preferences it started with; defaults for sessions started without any."""
+    data = session.session_context.get(PREFERENCES_CONTEXT_KEY)
+    started_by = session.session_context.get(STARTED_BY_CONTEXT_KEY)
+    if not isinstance(data, dict):
+        return started_by, UserPreferences()
+    try:
+        return started_by, UserPreferences.from_dict(data)
+    except ValueError:
+        return started_by, UserPreferences()
+
+
+def notify_target(session: Session) -> Optional[str]:
+    """Where to note that a run of `session` finished: its starter's DMs, a channel, or nowhere."""
+    started_by, preferences = session_preferences(session)
+    if preferences.notify == NOTIFY_DM:
+        return started_by
+    if preferences.notify:
+        return preferences.notify.lstrip("#")
+    return None
@@ -0,0 +1,243 @@
+"""Repository health check before a session's first run.
+
+Problems such as a half-finished merge or a local default branch that
+diverged from origin otherwise surface mid-run as a failed `pull --ff-only`
+or push, after the agent already did its work. `check_repository` looks for
+them up front; each issue comes with a suggested fix, and blocking ones keep
+the session from starting until they are fixed (see `Router`).
+"""
+
+from __future__ import annotations
+
+import os
+from dataclasses import dataclass
+from pathlib import Path
+from typing import List, Optional
+
+from ..agent_adapters.dev_env import MISE_CONFIG_FILES, TOOL_VERSIONS_FILE, dev_env_available, dev_env_tool
+from .compare import _git
+from .devcontainer import DEVCONTAINER_FILES, find_devcontainer
+from .models import DevEnvironment, DevEnvKind, Project
+
+# Session context flag: the preflight check found blocking issues, so the session branch is not set up yet
+PREFLIGHT_BLOCKED_CONTEXT_KEY = "preflight_blocked"
+# Untracked files at least this large are reported; `git add -A` would commit them (GitHub rejects >100 MB)
+LARGE_UNTRACKED_BYTES = 50 * 1024 * 1024
+MAX_LISTED_FILES = 5
+
+# In-progress operations: the file git keeps while one runs, and how to abort it
+_OPERATIONS = (
+    ("MERGE_HEAD", "a merge", "git merge --abort"),
+    ("rebase-merge", "a rebase", "git rebase --abort"),
+    ("rebase-apply", "a rebase", "git rebase --abort"),
+    ("CHERRY_PICK_HEAD", "a cherry-pick", "git cherry-pick --abort"),
+    ("REVERT_HEAD", "a revert", "git revert --abort"),
+    ("BISECT_LOG", "a bisect", "git bisect reset"),
+)
+
+
+@dataclass
+class PreflightIssue:
+    problem: str
+    fix: str
+    blocking: bool = False  # The session cannot start until it is fixed
+
+
+async def check_repository(project: Project, repo_path: Path) -> List[PreflightIssue]:
+    """Issues with the project's checkout that would make runs or publishing fail."""
+    issues: List[PreflightIssue] = []
+    if project.sandbox.devcontainer and find_devcontainer(repo_path) is None:
+        issues.append(
+            PreflightIssue(
+                f"The project's sandbox is `devcontainer`, but `{repo_path}` has no "
+                f"{' or '.join(f'`{name}`' for name in DEVCONTAINER_FILES)}.",
+                "Add one, or pick another `sandbox` mode for the project in projects.yaml.",
+                blocking=True,
+            )
+        )
+    if project.dev_env is not None:
+        issues.extend(_dev_env_issues(project.dev_env, repo_path))
+    inside = await _git(repo_path, "rev-parse", "--is-inside-work-tree", check=False)
+    if inside.returncode != 0 or inside.stdout.strip() != "true":
+        if not project.github:
+            return issues  # Non-git projects are snapshotted instead
+        return issues + [
+            PreflightIssue(
+                f"`{repo_path}` is not a git repository.",
+                f"Clone it with `git clone https://github.com/{project.github.full_name}.git {repo_path}`, "
+                "or fix the project's `path` in projects.yaml.",
+                blocking=True,
+            )
+        ]
+
+    operation = await _operation_in_progress(repo_path)
+    if operation:
+        name, abort = operation
+        issues.append(
+            PreflightIssue(
+                f"The checkout is in the middle of {name}.",
+                f"Finish it, or run `{abort}` in `{repo_path}`.",
+                blocking=True,
+            )
+        )
+
+    head = await _git(repo_path, "rev-parse", "--verify", "--quiet", "HEAD", check=False)
+    if head.returncode != 0:
+        issues.append(
+            PreflightIssue(
+                "The repository has no commits yet.",
+                "Commit the existing files (or an empty commit) so sessions have something to branch from.",
+                blocking=True,
+            )
+        )
+        return issues
+
+    base = project.github.default_base_branch if project.github else None
+    branch = await _git(repo_path, "symbolic-ref", "--quiet", "--short", "HEAD", check=False)
+    if branch.returncode != 0:
+        issues.append(
+            PreflightIssue(
+                f"HEAD is detached at `{head.stdout.strip()[:7]}`.",
+                f"Run `git checkout {base or '<branch>'}` so the session branch starts from a branch.",
+            )
+        )
+
+    if project.github:
+        issues.extend(await _remote_issues(project, repo_path))
+        issues.extend(await _base_branch_issues(repo_path, project.github.default_base_branch))
+    issues.extend(await _large_untracked_issues(repo_path))
+    return issues
+
+
+def _dev_env_issues(dev_env: DevEnvironment, repo_path: Path) -> List[PreflightIssue]:
+    issues = []
+    kind = dev_env.kind.value
+    if not dev_env_available(dev_env):
+        missing = (
+            f"asdf's shims directory `{dev_env_tool(dev_env)}` does not exist"
+            if dev_env.kind == DevEnvKind.ASDF
+            else f"`{dev_env_tool(dev_env)}` is not on the daemon's PATH"
+        )
+        issues.append(
+            PreflightIssue(
+                f"The project's `env` is `{kind}`, but {missing}.",
+                f"Install {'Nix' if dev_env.kind == DevEnvKind.NIX else kind} on this machine, "
+                "or remove `env` from the project in projects.yaml.",
+                blocking=True,
+            )
+        )
+    if dev_env.kind == DevEnvKind.NIX and dev_env.flake is None and not (repo_path / "flake.nix").is_file():
+        issues.append(
+            PreflightIssue(
+                f"The project's `env` is `nix`, but `{repo_path}` has no `flake.nix`.",
+                "Add a flake with a dev shell, or point `env.flake` at one in projects.yaml.",
+                blocking=True,
+            )
+        )
+    config_files = MISE_CONFIG_FILES if dev_env.kind == DevEnvKind.MISE else (TOOL_VERSIONS_FILE,)
+    if dev_env.kind != DevEnvKind.NIX and not any((repo_path / name).is_file() for name in config_files):
+        issues.append(
+            PreflightIssue(
+                f"The project's `env` is `{kind}`, but `{repo_path}` has no "
+                f"{' or '.join(f'`{name}`' for name in config_files)}, so the host's default toolchain is used.",
+                "Pin the project's tool versions in the repository, or remove `env` from the project in projects.yaml.",
+            )
+        )
+    return issues
+
+
+def format_preflight(project: Project, issues: List[PreflightIssue]) -> str:
+    lines = [f"Repository check for `{project.id}` found {len(issues)} issue{'' if len(issues) == 1 else 's'}:"]
+    for issue in issues:
+        marker = "*blocking* " if issue.blocking else ""
+        lines.append(f"• {marker}{issue.problem}\n  Fix: {issue.fix}")
+    if any(issue.blocking for issue in issues):
+        lines.append("Fix the blocking issues and send your request again; the session starts once the check passes.")
+    return "\n".join(lines)
+
+
+async def _operation_in_progress(repo_path: Path) -> Optional[tuple[str, str]]:
+    for marker, name, abort in _OPERATIONS:
+        path = await _git(repo_path, "rev-parse", "--git-path", marker, check=False)
+        if path.returncode == 0 and (repo_path / path.stdout.strip()).exists():
+            return name, abort
+    return None
+
+
+async def _remote_issues(project: Project, repo_path: Path) -> List[PreflightIssue]:
+    assert project.github is not None
+    expected = project.github.full_name
+    url = await _git(repo_path, "remote", "get-url", "origin", check=False)
+    if url.returncode != 0:
+        return [
+            PreflightIssue(
+                "The repository has no `origin` remote, so the session branch cannot be pushed.",
+                f"Run `git remote add origin https://github.com/{expected}.git`.",
+                blocking=True,
+            )
+        ]
+    remote = url.stdout.strip()
+    if expected.lower() not in remote.lower().removesuffix(".git"):
+        return [
+            PreflightIssue(
+                f"`origin` points at `{remote}`, but the project's PRs open in `{expected}`.",
+                f"Run `git remote set-url origin https://github.com/{expected}.git`, "
+                "or fix `github` in projects.yaml.",
+            )
+        ]
+    return []
+
+
+async def _base_branch_issues(repo_path: Path, base: str) -> List[PreflightIssue]:
+    """Compare the local base branch with origin's, as of the last fetch."""
+    local = await _git(repo_path, "rev-parse", "--verify", "--quiet", f"refs/heads/{base}", check=False)
+    tracking = await _git(repo_path, "rev-parse", "--verify", "--quiet", f"refs/remotes/origin/{base}", check=False)
+    if local.returncode != 0 or tracking.returncode != 0:
+        return []
+    counts = await _git(repo_path, "rev-list", "--left-right", "--count", f"{base}...origin/{base}", check=False)
+    if counts.returncode != 0:
+        return []
+    ahead, behind = (int(part) for part in counts.stdout.split())
+    if ahead and behind:
+        return [
+            PreflightIssue(
+                f"Local `{base}` has diverged from `origin/{base}` ({ahead} local commit(s), {behind} new upstream), "
+                "so updating it before the session fails.",
+                f"Save the local commits on another branch if you need them (`git branch my-work {base}`), "
+                f"then run `git checkout {base} && git reset --hard origin/{base}`.",
+                blocking=True,
+            )
+        ]
+    if ahead:
+        return [
+            PreflightIssue(
+                f"Local `{base}` has {ahead} commit(s) that are not on `origin/{base}`; "
+                "they would end up in the session's PR.",
+                f"Push them, or run `git checkout {base} && git reset --hard origin/{base}` to drop them.",
+            )
+        ]
+    return []
+
+
+async def _large_untracked_issues(repo_path: Path) -> List[PreflightIssue]:
+    listing = await _git(repo_path, "ls-files", "--others", "--exclude-standard", "-z", check=False)
+    if listing.returncode != 0:
+        return []
+    large = []
+    for name in filter(None, listing.stdout.split("\0")):
+        try:
+            size = os.path.getsize(repo_path / name)
+        except OSError:
+            continue
+        if size >= LARGE_UNTRACKED_BYTES:
+            large.append((name, size))
+    if not large:
+        return []
+    listed = ", ".join(f"`{name}` ({size // (1024 * 1024)} MB)" for name, size in large[:MAX_LISTED_FILES])
+    more = f" and {len(large) - MAX_LISTED_FILES} more" if len(large) > MAX_LISTED_FILES else ""
+    return [
+        PreflightIssue(
+            f"Large untracked files would be committed with the agent's changes: {listed}{more}.",
+            "Add them to `.gitignore` (or `.git/info/exclude`), or move them out of the project.",
+        )
+    ]
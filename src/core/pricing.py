@@ -0,0 +1,108 @@
+"""Cost estimation for agent runs.
+
+Every cost number Remote Coder shows (pre-run estimates, per-session totals,
+`!cost`) is computed here from the `pricing` section in agents.yaml so the
+figures stay consistent with each other.
+"""
+
+from __future__ import annotations
+
+import math
+from dataclasses import dataclass
+from typing import TYPE_CHECKING, Optional
+
+from .models import Agent
+
+if TYPE_CHECKING:
+    from ..agent_adapters.base import AgentResult
+
+# Rough heuristic used when an agent does not report token usage
+CHARS_PER_TOKEN = 4
+# Output size assumed for pre-run estimates, before the agent has replied
+DEFAULT_EXPECTED_OUTPUT_TOKENS = 2000
+
+
+@dataclass
+class CostEstimate:
+    """Cost of a single agent run, either estimated or based on reported usage."""
+
+    agent_id: str
+    model: Optional[str]
+    input_tokens: int
+    output_tokens: int
+    cost_usd: float
+    from_reported_usage: bool = False
+
+
+def estimate_tokens(text: str) -> int:
+    """Approximate the token count of a piece of text."""
+    if not text:
+        return 0
+    return math.ceil(len(text) / CHARS_PER_TOKEN)
+
+
+def cost_for_usage(
+    agent: Agent,
+    model: Optional[str],
+    input_tokens: int,
+    output_tokens: int,
+    from_reported_usage: bool = False,
+) -> Optional[CostEstimate]:
+    """Price a run with known token counts. Returns None if the agent has no pricing."""
+    if not agent.pricing:
+        return None
+    rates = agent.pricing.for_model(model)
+    cost = (
+        rates.per_run_usd
+        + input_tokens / 1000 * rates.input_per_1k_tokens_usd
+        + output_tokens / 1000 * rates.output_per_1k_tokens_usd
+    )
+    return CostEstimate(
+        agent_id=agent.id,
+        model=model,
+        input_tokens=input_tokens,
+        output_tokens=output_tokens,
+        cost_usd=cost,
+        from_reported_usage=from_reported_usage,
+    )
+
+
+def estimate_run_cost(
+    agent: Agent,
+    model: Optional[str],
+    prompt_text: str,
+    expected_output_tokens: int = DEFAULT_EXPECTED_OUTPUT_TOKENS,
+) -> Optional[CostEstimate]:
+    """Estimate the cost of a run before it starts."""
+    return cost_for_usage(agent, model, estimate_tokens(prompt_text), expected_output_tokens)
+
+
+def cost_for_result(
+    agent: Agent,
+    model: Optional[str],
+    prompt_text: str,
+    result: AgentResult,
+) -> Optional[CostEstimate]:
+    """Price a finished run, preferring token usage reported by the agent CLI."""
+    usage = result.token_usage
+    if usage.get("input_tokens") or usage.get("output_tokens"):
+        return cost_for_usage(
+            agent,
+            model,
+            int(usage.get("input_tokens", 0)),
+            int(usage.get("output_tokens", 0)),
+            from_reported_usage=True,
+        )
+    return cost_for_usage(
+        agent,
+        model,
+        estimate_tokens(prompt_text),
+        estimate_tokens(result.output_text),
+    )
+
+
+def format_cost(amount: float) -> str:
+    """Render a USD amount for Slack, keeping precision for small values."""
+    if amount < 0.01:
+        return f"${amount:.4f}"
+    return f"${amount:.2f}"
@@ -0,0 +1,102 @@
+"""`REMOTE_CODER_PRIVACY=strict`: no outbound calls beyond the ones a run needs.
+
+The daemon has to talk to the chat platform, the forge (GitHub), and the
+model providers of its agents. Everything else it or its agent CLIs would
+send out is optional, and strict mode turns all of it off:
+
+- the daily check for newer Remote Coder releases (`updates.py`);
+- connection alerts posted to `REMOTE_CODER_ALERT_WEBHOOK_URL` (DMs to
+  `REMOTE_CODER_ALERT_USER_IDS` still go out, they use the chat platform);
+- uploads to and downloads from the session archive in object storage
+  (`archive.py`); sessions then stay archived in the store;
+- link previews: Slack otherwise fetches the links in the daemon's messages
+  to unfurl them;
+- telemetry, error reporting, and update checks of the agent CLIs, through
+  the opt-out variables in `CLI_OPT_OUT_ENV` set on every CLI it starts.
+
+Each of these asks `PRIVACY.allows(...)` before it goes out, and the
+functions that make the calls refuse with `EgressBlocked`, so one setting
+covers them all. `!status --system` lists what is blocked.
+"""
+
+from __future__ import annotations
+
+from enum import Enum
+from typing import Dict, List, Mapping
+
+from .errors import RemoteCoderError
+
+STANDARD = "standard"
+STRICT = "strict"
+PRIVACY_MODES = (STANDARD, STRICT)
+
+# Honoured by Claude Code, the Gemini CLI, and npm's update notifier; other CLIs ignore them
+CLI_OPT_OUT_ENV: Dict[str, str] = {
+    "DO_NOT_TRACK": "1",
+    "NO_UPDATE_NOTIFIER": "1",
+    "DISABLE_TELEMETRY": "1",
+    "DISABLE_ERROR_REPORTING": "1",
+    "DISABLE_AUTOUPDATER": "1",
+    "CLAUDE_CODE_DISABLE_NONESSENTIAL_TRAFFIC": "1",
+    "GEMINI_TELEMETRY_ENABLED": "false",
+}
+
+
+class Egress(str, Enum):
+    """Optional outbound traffic; strict mode blocks all of it."""
+
+    UPDATE_CHECK = "update checks"
+    ALERT_WEBHOOK = "alert webhooks"
+    LINK_UNFURLS = "link previews"
+    CLI_TELEMETRY = "agent CLI telemetry"
+    SESSION_ARCHIVE = "session archive transfers"
+
+
+class EgressBlocked(RemoteCoderError):
+    """An optional outbound call was attempted in strict privacy mode."""
+
+
+class PrivacyPolicy:
+    """Which optional outbound calls are allowed; the daemon shares `PRIVACY`."""
+
+    def __init__(self, mode: str = STANDARD) -> None:
+        self.mode = mode
+
+    def set_mode(self, mode: str) -> None:
+        """Apply the mode from the config (at startup and on reload)."""
+        self.mode = mode
+
+    @property
+    def strict(self) -> bool:
+        return self.mode == STRICT
+
+    def allows(self, egress: Egress) -> bool:
+        return not self.strict
+
+    def require(self, egress: Egress) -> None:
+        if not self.allows(egress):
+            raise EgressBlocked(f"{egress.value} are off in strict privacy mode")
+
+    def blocked(self) -> List[Egress]:
+        return [egress for egress in Egress if not self.allows(egress)]
+
+    def cli_env(self, env: Mapping[str, str]) -> Dict[str, str]:
+        """`env` for an agent CLI, with the opt-out variables added unless CLI telemetry is allowed."""
+        if self.allows(Egress.CLI_TELEMETRY):
+            return dict(env)
+        return {**env, **CLI_OPT_OUT_ENV}
+
+    def message_options(self) -> Dict[str, bool]:
+        """Extra `chat.postMessage` arguments for Slack; off-by-default unfurls when previews are blocked."""
+        if self.allows(Egress.LINK_UNFURLS):
+            return {}
+        return {"unfurl_links": False, "unfurl_media": False}
+
+    def describe(self) -> str:
+        blocked = self.blocked()
+        if not blocked:
+            return f"Privacy: {self.mode}"
+        return f"Privacy: {self.mode} (off: {', '.join(egress.value for egress in blocked)})"
+
+
+PRIVACY = PrivacyPolicy()
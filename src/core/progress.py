@@ -0,0 +1,75 @@
+"""The checklist of tool calls shown in a run's live message.
+
+Adapters that parse their CLI's tool events (see
+`src/agent_adapters/progress.py`) report each step as it starts and
+finishes. Once a run reports its first step, its live message shows the
+latest steps as a checklist, with the agent's last line of text below it,
+instead of the raw output:
+
+    ✓ Read `src/app.py`
+    ✓ Edit `src/app.py`
+    … Run `pytest -q`
+
+A step that finishes without having been seen starting (e.g. a Codex file
+change) is added as finished.
+"""
+
+from __future__ import annotations
+
+from dataclasses import dataclass
+from typing import Dict, List
+
+from ..agent_adapters.progress import ProgressEvent, StepKind, StepStatus
+
+MAX_STEPS = 10
+
+STATUS_MARKS = {StepStatus.RUNNING: "…", StepStatus.DONE: "✓", StepStatus.FAILED: "✗"}
+KIND_VERBS = {
+    StepKind.READ: "Read",
+    StepKind.EDIT: "Edit",
+    StepKind.COMMAND: "Run",
+    StepKind.SEARCH: "Search",
+    StepKind.OTHER: "Use",
+}
+
+
+@dataclass
+class _Step:
+    kind: StepKind
+    target: str
+    status: StepStatus
+
+
+class ProgressChecklist:
+    """The steps of one run, in the order they started."""
+
+    def __init__(self) -> None:
+        self._steps: Dict[str, _Step] = {}
+
+    def __bool__(self) -> bool:
+        return bool(self._steps)
+
+    def apply(self, event: ProgressEvent) -> None:
+        step = self._steps.get(event.step_id)
+        if step is None:
+            self._steps[event.step_id] = _Step(event.kind, event.target, event.status)
+        else:
+            step.status = event.status
+
+    def counts(self) -> Dict[StepStatus, int]:
+        counts = {status: 0 for status in StepStatus}
+        for step in self._steps.values():
+            counts[step.status] += 1
+        return counts
+
+    def render(self) -> List[str]:
+        steps = list(self._steps.values())
+        lines = []
+        if len(steps) > MAX_STEPS:
+            earlier = steps[: len(steps) - MAX_STEPS]
+            failed = sum(step.status == StepStatus.FAILED for step in earlier)
+            lines.append(f"… {len(earlier)} earlier step(s)" + (f", {failed} failed" if failed else ""))
+        for step in steps[-MAX_STEPS:]:
+            target = step.target.replace("`", "'")
+            lines.append(f"{STATUS_MARKS[step.status]} {KIND_VERBS[step.kind]} `{target}`")
+        return lines
@@ -0,0 +1,159 @@
+"""Prompt commands defined by a project in `.cockpit/commands/<name>.md`.
+
+`!<name> <text>` in a session thread runs the session's agent on the file's
+body, with `$ARGUMENTS` replaced by `<text>` (or `<text>` appended when the
+body has no placeholder). Optional YAML frontmatter describes the command and
+overrides agent parameters for that run only:
+
+    ---
+    description: Refactor the named module
+    argument_hint: module path
+    model: opus
+    temperature: 0.2
+    reasoning_effort: high
+    ---
+
+Built-in commands always win over a project command with the same name.
+"""
+
+from __future__ import annotations
+
+import logging
+import re
+from pathlib import Path
+from typing import List, Optional, Tuple
+
+import yaml
+
+from .errors import CommandNotFound, ConfigError
+from .models import REASONING_EFFORTS, CommandArg, CommandDefinition, RunOverrides
+
+LOGGER = logging.getLogger(__name__)
+
+PROJECT_COMMANDS_DIR = Path(".cockpit") / "commands"
+ARGUMENTS_PLACEHOLDER = "$ARGUMENTS"
+FRONTMATTER_KEYS = frozenset(
+    {"description", "argument_hint", "title", "category", "model", "temperature", "reasoning_effort"}
+)
+COMMAND_NAME = re.compile(r"^[a-z0-9][a-z0-9_-]*$")
+
+
+def commands_dir(project_path: Path) -> Path:
+    return project_path / PROJECT_COMMANDS_DIR
+
+
+def load_project_command(project_path: Path, name: str) -> CommandDefinition:
+    """Raises CommandNotFound when the project has no such command, ConfigError when its file is invalid."""
+    if not COMMAND_NAME.match(name):
+        raise CommandNotFound(name)
+    path = commands_dir(project_path) / f"{name}.md"
+    try:
+        text = path.read_text(encoding="utf-8")
+    except FileNotFoundError:
+        raise CommandNotFound(name) from None
+    except (OSError, UnicodeDecodeError) as exc:
+        raise ConfigError(f"Cannot read {PROJECT_COMMANDS_DIR / path.name}: {exc}") from exc
+    return parse_command_file(name, text)
+
+
+def list_project_commands(project_path: Path) -> List[CommandDefinition]:
+    """Valid commands of a project, sorted by name; invalid files are logged and skipped."""
+    directory = commands_dir(project_path)
+    if not directory.is_dir():
+        return []
+    commands = []
+    for path in sorted(directory.glob("*.md")):
+        try:
+            commands.append(load_project_command(project_path, path.stem))
+        except CommandNotFound:
+            continue
+        except ConfigError as exc:
+            LOGGER.warning("Skipping project command %s: %s", path, exc)
+    return commands
+
+
+def split_frontmatter(owner: str, text: str, allowed_keys: frozenset) -> Tuple[dict, str]:
+    """Split a Markdown prompt file into its YAML frontmatter and its stripped, non-empty body."""
+    meta: dict = {}
+    body = text
+    if text.startswith("---"):
+        header, separator, rest = text[3:].partition("\n---")
+        if not separator:
+            raise ConfigError(f"{owner} has an unterminated frontmatter block")
+        try:
+            meta = yaml.safe_load(header) or {}
+        except yaml.YAMLError as exc:
+            raise ConfigError(f"{owner} has invalid frontmatter: {exc}") from exc
+        if not isinstance(meta, dict):
+            raise ConfigError(f"{owner} frontmatter must be a mapping")
+        body = rest.partition("\n")[2]
+
+    unknown = sorted(set(meta) - allowed_keys)
+    if unknown:
+        raise ConfigError(f"{owner} has unsupported frontmatter keys {', '.join(map(str, unknown))}")
+    body = body.strip()
+    if not body:
+        raise ConfigError(f"{owner} has no prompt")
+    return meta, body
+
+
+def parse_command_file(name: str, text: str) -> CommandDefinition:
+    owner = f"{PROJECT_COMMANDS_DIR / name}.md"
+    meta, body = split_frontmatter(owner, text, FRONTMATTER_KEYS)
+
+    return CommandDefinition(
+        id=name,
+        title=str(meta.get("title") or name),
+        args=[
+            CommandArg(
+                name="arguments",
+                arg_type="text",
+                required=False,
+                description=str(meta.get("argument_hint") or "Text for the prompt's $ARGUMENTS"),
+            )
+        ],
+        body=body,
+        description=str(meta["description"]) if meta.get("description") else None,
+        category=str(meta["category"]) if meta.get("category") else None,
+        overrides=_parse_overrides(owner, meta),
+    )
+
+
+def _parse_overrides(owner: str, meta: dict) -> RunOverrides:
+    model = meta.get("model")
+    if model is not None and (not isinstance(model, str) or not model.strip()):
+        raise ConfigError(f"{owner} model must be a model name")
+
+    temperature = meta.get("temperature")
+    if temperature is not None:
+        if isinstance(temperature, bool) or not isinstance(temperature, (int, float)) or not 0 <= temperature <= 2:
+            raise ConfigError(f"{owner} temperature must be a number from 0 to 2")
+        temperature = float(temperature)
+
+    effort = meta.get("reasoning_effort")
+    if effort is not None:
+        effort = str(effort).strip().lower()
+        if effort not in REASONING_EFFORTS:
+            raise ConfigError(f"{owner} reasoning_effort must be one of {', '.join(REASONING_EFFORTS)}")
+
+    return RunOverrides(model=model.strip() if model else None, temperature=temperature, reasoning_effort=effort)
+
+
+def render_prompt(command: CommandDefinition, arguments: str) -> str:
+    if ARGUMENTS_PLACEHOLDER in command.body:
+        return command.body.replace(ARGUMENTS_PLACEHOLDER, arguments)
+    if arguments:
+        return f"{command.body}\n\n{arguments}"
+    return command.body
+
+
+def describe_overrides(overrides: RunOverrides) -> Optional[str]:
+    """E.g. "model `opus`, temperature 0.2, reasoning effort high"; None when nothing is overridden."""
+    parts = []
+    if overrides.model:
+        parts.append(f"model `{overrides.model}`")
+    if overrides.temperature is not None:
+        parts.append(f"temperature {overrides.temperature:g}")
+    if overrides.reasoning_effort:
+        parts.append(f"reasoning effort {overrides.reasoning_effort}")
+    return ", ".join(parts) or None
@@ -8,7 +8,7 @@ import re
 import shutil
 from dataclasses import dataclass
 from pathlib import Path
-from typing import Optional
+from typing import Optional, Tuple
 
 import yaml
 
@@ -20,6 +20,7 @@ from .errors import (
     ProjectNotFound,
     RepoExistsError,
 )
+from .jobs import JOBS
 from .models import GitHubRepoConfig, Project
 from ..github import GitHubManager
 
@@ -112,18 +113,8 @@ class ProjectCreationService:
             LOGGER.info("Found existing git repo at %s, adding to config", local_path)
 
             # Try to parse owner from remote URL
-            github_owner = None
-            try:
-                remote_url = await self._run_git(local_path, ["remote", "get-url", "origin"])
-                if "github.com" in remote_url:
-                    if remote_url.startswith("git@"):
-                        parts = remote_url.split(":")[-1].replace(".git", "").split("/")
-                    else:
-                        parts = remote_url.replace(".git", "").split("/")[-2:]
-                    if len(parts) >= 2:
-                        github_owner = parts[0]
-            except ProjectCreationError:
-                pass
+            remote = await self._github_remote(local_path)
+            github_owner = remote[0] if remote else None
 
             if not github_owner:
                 try:
@@ -147,6 +138,90 @@ class ProjectCreationService:
                 "This isn't supported yet, sorry :("
             )
 
+    async def register_existing_repo(self, request: ProjectCreationRequest, repo_path: Path) -> Project:
+        """
+        Add a git repository that already exists anywhere on this machine.
+
+        GitHub settings come from the `origin` remote when it points at GitHub;
+        otherwise the project is local-only.
+
+        Raises:
+            ProjectCreationError: If the path is not a git repository or the project exists
+        """
+        repo_path = repo_path.expanduser().resolve()
+        if not repo_path.is_dir():
+            raise ProjectCreationError(f"'{repo_path}' is not a directory on this machine.")
+        try:
+            top_level = Path(await self._run_git(repo_path, ["rev-parse", "--show-toplevel"]))
+        except ProjectCreationError as exc:
+            raise LocalDirNotGitRepoError(f"'{repo_path}' isn't inside a git repository.") from exc
+
+        project_id = self._sanitize_repo_name(request.channel_name)
+        try:
+            self._config.get_project_by_channel(request.channel_name)
+            raise ProjectCreationError(f"Project '{request.channel_name}' is already configured")
+        except ProjectNotFound:
+            pass
+        if project_id in self._config.projects:
+            raise ProjectCreationError(f"A project named '{project_id}' is already configured")
+
+        remote = await self._github_remote(top_level)
+        base_branch = request.default_base_branch
+        if remote:
+            try:
+                origin_head = await self._run_git(top_level, ["symbolic-ref", "--short", "refs/remotes/origin/HEAD"])
+                base_branch = origin_head.split("/", 1)[-1]
+            except ProjectCreationError:
+                pass
+        return await self._add_to_config(
+            project_id=project_id,
+            channel_name=request.channel_name,
+            local_path=top_level,
+            github_owner=remote[0] if remote else None,
+            repo_name=remote[1] if remote else project_id,
+            default_base_branch=base_branch,
+            default_agent_id=request.default_agent_id,
+            default_model=request.default_model,
+        )
+
+    def link_channel(self, project_id: str, channel_name: str) -> Project:
+        """Map another chat channel to a configured project by adding it to the project's `channels`."""
+        project = self._config.get_project(project_id)
+        try:
+            existing = self._config.get_project_by_channel(channel_name)
+            raise ProjectCreationError(f"Channel '{channel_name}' is already connected to '{existing.id}'")
+        except ProjectNotFound:
+            pass
+
+        projects_yaml = self._config.config_dir / "projects.yaml"
+        with open(projects_yaml, "r") as f:
+            data = yaml.safe_load(f) or {}
+        entry = (data.get("projects") or {}).get(project_id)
+        if not isinstance(entry, dict):
+            raise ProjectCreationError(f"Project '{project_id}' is not in {projects_yaml}")
+        channels = entry.setdefault("channels", [])
+        channels.append(channel_name)
+        with open(projects_yaml, "w") as f:
+            yaml.dump(data, f, default_flow_style=False, sort_keys=False)
+        LOGGER.info("Linked channel %s to project %s", channel_name, project_id)
+        return project
+
+    async def _github_remote(self, repo_path: Path) -> Optional[Tuple[str, str]]:
+        """Return (owner, repo) when the repository's `origin` remote is on GitHub."""
+        try:
+            remote_url = await self._run_git(repo_path, ["remote", "get-url", "origin"])
+        except ProjectCreationError:
+            return None
+        if "github.com" not in remote_url:
+            return None
+        if remote_url.startswith("git@"):
+            parts = remote_url.split(":")[-1].replace(".git", "").split("/")
+        else:
+            parts = remote_url.replace(".git", "").split("/")[-2:]
+        if len(parts) >= 2:
+            return parts[0], parts[1]
+        return None
+
     async def _create_new_project(
         self,
         local_path: Path,
@@ -318,13 +393,13 @@ class ProjectCreationService:
         project_id: str,
         channel_name: str,
         local_path: Path,
-        github_owner: str,
+        github_owner: Optional[str],
         repo_name: str,
         default_base_branch: str,
         default_agent_id: str,
         default_model: Optional[str] = None,
     ) -> Project:
-        """Add project entry to projects.yaml."""
+        """Add project entry to projects.yaml; without a GitHub owner the project is local-only."""
         projects_yaml = self._config.config_dir / "projects.yaml"
 
         with open(projects_yaml, "r") as f:
@@ -341,12 +416,19 @@ class ProjectCreationService:
         project_data = {
             "path": str(relative_path),
             "default_agent": default_agent_id,
-            "github": {
+        }
+        github = None
+        if github_owner:
+            github = GitHubRepoConfig(
+                owner=github_owner,
+                repo=repo_name,
+                default_base_branch=default_base_branch,
+            )
+            project_data["github"] = {
                 "owner": github_owner,
                 "repo": repo_name,
                 "default_base_branch": default_base_branch,
-            },
-        }
+            }
         if default_model:
             project_data["default_model"] = default_model
 
@@ -361,29 +443,18 @@ class ProjectCreationService:
             path=local_path,
             default_agent_id=default_agent_id,
             default_model=default_model,
-            github=GitHubRepoConfig(
-                owner=github_owner,
-                repo=repo_name,
-                default_base_branch=default_base_branch,
-            ),
+            github=github,
         )
 
     async def _run_git(self, cwd: Path, args: list[str]) -> str:
         """Run a git command asynchronously."""
-        proc = await asyncio.create_subprocess_exec(
-            "git",
-            *args,
-            cwd=cwd,
-            stdout=asyncio.subprocess.PIPE,
-            stderr=asyncio.subprocess.PIPE,
-        )
-        stdout, stderr = await proc.communicate()
+        result = await JOBS.run("git", ["git", *args], cwd=cwd)
 
-        if proc.returncode != 0:
-            error_msg = stderr.decode().strip() if stderr else "Unknown error"
+        if not result.passed:
+            error_msg = "timed out" if result.timed_out else result.stderr.strip() or "Unknown error"
             raise ProjectCreationError(f"Git command failed: git {' '.join(args)}: {error_msg}")
 
-        return stdout.decode().strip() if stdout else ""
+        return result.stdout.strip()
 
     def _cleanup_failed_creation(self, path: Path, repo_full_name: Optional[str] = None) -> None:
         """Clean up local directory and GitHub repo on failure."""
@@ -0,0 +1,133 @@
+"""Keep secrets out of what is posted to chat.
+
+Agents can print anything they read, including a `.env` file, so every
+message, edit, diff, and upload the router sends is scanned first (unless
+`REMOTE_CODER_DISABLE_REDACTION` is set). Three things are replaced with a
+`[redacted …]` marker:
+
+- the values of the daemon's own secrets: the Slack and GitHub tokens, and
+  environment variables (the daemon's and each agent's `env`) whose names
+  look secret, such as `OPENAI_API_KEY` or `DB_PASSWORD`;
+- strings shaped like well-known credentials: AWS access keys, GitHub,
+  Slack, OpenAI, Anthropic, and Google API tokens, and private key blocks;
+- values assigned to secret-looking names (`STRIPE_SECRET=...`), and other
+  long random-looking strings, by their character entropy.
+
+Git commit hashes and UUIDs are not random enough to be caught.
+"""
+
+from __future__ import annotations
+
+import logging
+import math
+import re
+from collections import Counter
+from typing import TYPE_CHECKING, Dict, List, Mapping, Optional, Tuple
+
+if TYPE_CHECKING:
+    from .config import Config
+
+LOGGER = logging.getLogger(__name__)
+
+# Shorter values are too likely to be ordinary words
+MIN_SECRET_CHARS = 8
+MIN_RANDOM_CHARS = 32
+# Bits per character; hex (a commit hash) tops out at 4
+MIN_RANDOM_ENTROPY = 4.3
+
+SECRET_NAME = re.compile(r"SECRET|TOKEN|PASSWORD|PASSWD|API_?KEY|ACCESS_?KEY|PRIVATE_?KEY|CREDENTIAL", re.I)
+
+_PATTERNS: Tuple[Tuple[str, re.Pattern[str]], ...] = (
+    (
+        "private key",
+        re.compile(r"-----BEGIN [A-Z ]*PRIVATE KEY-----.*?(?:-----END [A-Z ]*PRIVATE KEY-----|\Z)", re.S),
+    ),
+    ("AWS access key", re.compile(r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b")),
+    ("GitHub token", re.compile(r"\b(?:gh[pousr]_[A-Za-z0-9]{30,}|github_pat_[A-Za-z0-9_]{40,})\b")),
+    ("Slack token", re.compile(r"\bx(?:ox[abposr]|app)-[A-Za-z0-9-]{10,}")),
+    ("API key", re.compile(r"\bsk-(?:ant-|proj-)?[A-Za-z0-9_-]{20,}")),
+    ("Google API key", re.compile(r"\bAIza[0-9A-Za-z_-]{35}\b")),
+)
+# `NAME=value` / `NAME: "value"` with a secret-looking upper-case name, as in `.env` files and YAML
+_ASSIGNMENT = re.compile(
+    r"\b([A-Z0-9_]*(?:SECRET|TOKEN|PASSWORD|PASSWD|API_?KEY|ACCESS_?KEY|CREDENTIALS?)[A-Z0-9_]*)"
+    r"(\s*[:=]\s*[\"']?)([^\s\"'`]{%d,})" % MIN_SECRET_CHARS
+)
+_RANDOM = re.compile(r"[A-Za-z0-9+/_=-]{%d,}" % MIN_RANDOM_CHARS)
+
+
+def looks_secret(name: str) -> bool:
+    """Whether an environment variable called `name` probably holds a secret."""
+    return bool(SECRET_NAME.search(name))
+
+
+def _entropy(text: str) -> float:
+    counts = Counter(text)
+    return -sum(count / len(text) * math.log2(count / len(text)) for count in counts.values())
+
+
+def _looks_random(text: str) -> bool:
+    classes = (any(char.islower() for char in text), any(char.isupper() for char in text))
+    return all(classes) and any(char.isdigit() for char in text) and _entropy(text) >= MIN_RANDOM_ENTROPY
+
+
+class SecretRedactor:
+    """Replaces known secret values and credential-shaped strings in outgoing text."""
+
+    def __init__(self, known: Optional[Mapping[str, str]] = None) -> None:
+        # Longest first, so a secret containing another one is replaced whole
+        self._known: List[Tuple[str, str]] = sorted(
+            ((value, name) for name, value in (known or {}).items() if value and len(value) >= MIN_SECRET_CHARS),
+            key=lambda item: len(item[0]),
+            reverse=True,
+        )
+
+    def redact(self, text: str) -> str:
+        if not text:
+            return text
+        redacted = self._replace(text)
+        if redacted != text:
+            LOGGER.warning("Redacted what looked like secrets from a chat message")
+        return redacted
+
+    def contains_secret(self, text: str) -> bool:
+        """Whether `redact` would change `text`, for output that must be posted whole or not at all."""
+        return bool(text) and self._replace(text) != text
+
+    def _replace(self, text: str) -> str:
+        redacted = text
+        for value, name in self._known:
+            redacted = redacted.replace(value, f"[redacted {name}]")
+        for label, pattern in _PATTERNS:
+            redacted = pattern.sub(f"[redacted {label}]", redacted)
+        redacted = _ASSIGNMENT.sub(self._redact_assignment, redacted)
+        return _RANDOM.sub(self._redact_random, redacted)
+
+    @staticmethod
+    def _redact_assignment(match: re.Match[str]) -> str:
+        name, separator, value = match.groups()
+        if value.startswith("[redacted"):
+            return match.group(0)
+        return f"{name}{separator}[redacted]"
+
+    @staticmethod
+    def _redact_random(match: re.Match[str]) -> str:
+        return "[redacted]" if _looks_random(match.group(0)) else match.group(0)
+
+
+def redactor_for(config: "Config", environ: Mapping[str, str]) -> SecretRedactor:
+    """A redactor knowing the daemon's tokens and the secret-looking variables of `environ` and the agents' `env`."""
+    known: Dict[str, str] = {name: value for name, value in environ.items() if looks_secret(name)}
+    for agent in config.agents.values():
+        known.update((name, value) for name, value in agent.env.items() if looks_secret(name))
+        if agent.api_key_env and environ.get(agent.api_key_env):
+            known[agent.api_key_env] = environ[agent.api_key_env]
+    daemon_secrets = {
+        "SLACK_BOT_TOKEN": config.slack_bot_token,
+        "SLACK_APP_TOKEN": config.slack_app_token,
+        "SLACK_APP_CONFIG_TOKEN": config.slack_config_token,
+        "GITHUB_TOKEN": config.github_token,
+        "REMOTE_CODER_ALERT_WEBHOOK_URL": config.alert_webhook_url,
+    }
+    known.update((name, value) for name, value in daemon_secrets.items() if value)
+    return SecretRedactor(known)
@@ -0,0 +1,229 @@
+"""Replay a past session's requests against a different agent.
+
+The user turns of a stored session are fed, in order, to another agent in a
+detached `git worktree` of the commit the session started from. Each turn
+gets the replayed conversation so far as context, so the agent sees its own
+earlier answers rather than the original agent's. The replay's final diff is
+then set against what the original session changed on its branch, which
+shows whether the other agent would have handled the real task better.
+"""
+
+from __future__ import annotations
+
+import logging
+import shutil
+import tempfile
+import time
+from dataclasses import dataclass, field
+from pathlib import Path
+from typing import Awaitable, Callable, List, Optional, Tuple
+
+from ..agent_adapters import AgentResult
+from ..chat_adapters.health import format_duration
+from .agent_runner import START_COMMIT_CONTEXT_KEY
+from .compare import MAX_REPLY_CHARS, _git
+from .conversation import ContextBuilder
+from .diff_render import FileDiff, split_diff
+from .git_workflow import LOCAL_BASE_CONTEXT_KEY, session_branch_name
+from .models import Agent, ConversationInteraction, ConversationMessage, Session
+from .pricing import format_cost
+
+LOGGER = logging.getLogger(__name__)
+
+WORKTREE_PREFIX = "remote-coder-replay-"
+
+# Runs one turn in the worktree: (agent, model, workdir, prompt, context) -> (result, cost in USD)
+ReplayAgentFn = Callable[[Agent, Optional[str], Path, str, str], Awaitable[Tuple[AgentResult, Optional[float]]]]
+# Reports each finished turn
+ReplayProgressFn = Callable[["ReplayTurn"], Awaitable[None]]
+
+
+class ReplayError(Exception):
+    """The replay could not be set up, e.g. the session has no stored requests."""
+
+
+@dataclass
+class ReplayTurn:
+    number: int
+    prompt: str
+    original_reply: str
+    reply: str = ""
+    duration_secs: float = 0.0
+    cost_usd: Optional[float] = None
+    errors: List[str] = field(default_factory=list)
+
+
+@dataclass
+class ReplayResult:
+    session_id: str
+    agent_id: str
+    model: Optional[str]
+    base: str
+    turns: List[ReplayTurn]
+    diff_text: str = ""
+    original_diff_text: Optional[str] = None  # None when the session branch is gone
+    skipped_turns: int = 0  # Early turns no longer in memory (trimmed from the session)
+
+    @property
+    def label(self) -> str:
+        return f"{self.agent_id} ({self.model})" if self.model else self.agent_id
+
+    @property
+    def files(self) -> List[FileDiff]:
+        return split_diff(self.diff_text)
+
+    @property
+    def cost_usd(self) -> Optional[float]:
+        costs = [turn.cost_usd for turn in self.turns]
+        return None if None in costs else sum(cost for cost in costs if cost is not None)
+
+
+def replay_turns(session: Session) -> Tuple[List[ReplayTurn], int]:
+    """The session's user turns with the original replies, plus how many early turns were trimmed."""
+    turns = [
+        ReplayTurn(
+            number=interaction.interaction_number,
+            prompt=interaction.user_message.content,
+            original_reply=interaction.agent_message.content,
+        )
+        for interaction in session.interactions
+    ]
+    skipped = session.interactions[0].interaction_number - 1 if session.interactions else 0
+    return turns, skipped
+
+
+def replay_base(session: Session) -> Optional[str]:
+    """The commit the session started from, if it was recorded."""
+    base = session.session_context.get(START_COMMIT_CONTEXT_KEY) or session.session_context.get(
+        LOCAL_BASE_CONTEXT_KEY
+    )
+    return base if isinstance(base, str) and base else None
+
+
+async def run_replay(
+    session: Session,
+    agent: Agent,
+    model: Optional[str],
+    run_agent: ReplayAgentFn,
+    *,
+    progress: Optional[ReplayProgressFn] = None,
+) -> ReplayResult:
+    """Feed the session's user turns to `agent` in a worktree of the session's starting commit.
+
+    A failing turn is recorded with its error and the replay continues with the
+    next one. Raises ReplayError when there is nothing to replay or no worktree
+    can be made.
+    """
+    turns, skipped = replay_turns(session)
+    if not turns:
+        raise ReplayError(f"session `{session.id}` has no stored requests to replay")
+    repo_path = session.project_path
+    base = replay_base(session)
+    if base is None:
+        head = await _git(repo_path, "rev-parse", "HEAD", check=False)
+        if head.returncode != 0:
+            raise ReplayError(f"{repo_path} is not a git repository with at least one commit")
+        base = head.stdout.strip()
+        LOGGER.info("Session %s has no recorded start commit; replaying from %s", session.id, base)
+
+    root = Path(tempfile.mkdtemp(prefix=WORKTREE_PREFIX))
+    worktree = root / agent.id
+    try:
+        added = await _git(repo_path, "worktree", "add", "--detach", str(worktree), base, check=False)
+        if added.returncode != 0:
+            raise ReplayError(f"Could not create a worktree at `{base[:12]}`: {added.stderr.strip()}")
+
+        history: List[ConversationInteraction] = []
+        for turn in turns:
+            context = ContextBuilder.build_context_for_agent(history)
+            started = time.monotonic()
+            try:
+                result, turn.cost_usd = await run_agent(agent, model, worktree, turn.prompt, context)
+            except Exception as exc:
+                LOGGER.exception("Replay of turn %d with %s failed", turn.number, agent.id)
+                turn.errors.append(str(exc))
+            else:
+                turn.reply = (
+                    result.structured_output.slack_message if result.structured_output else result.output_text
+                ).strip()
+                turn.errors.extend(result.errors)
+            turn.duration_secs = time.monotonic() - started
+            history.append(
+                ConversationInteraction(
+                    interaction_number=turn.number,
+                    user_message=ConversationMessage(role="user", content=turn.prompt),
+                    agent_message=ConversationMessage(role="assistant", content=turn.reply),
+                )
+            )
+            if progress:
+                await progress(turn)
+
+        # Stage everything so new files show up; diffing against `base` also covers agents that commit
+        await _git(worktree, "add", "-A", check=False)
+        diff = await _git(worktree, "diff", "--cached", base, check=False)
+        diff_text = diff.stdout if diff.returncode == 0 else ""
+    finally:
+        await _git(repo_path, "worktree", "remove", "--force", str(worktree), check=False)
+        await _git(repo_path, "worktree", "prune", check=False)
+        shutil.rmtree(root, ignore_errors=True)
+
+    original = await _git(repo_path, "diff", f"{base}..{session_branch_name(session.id)}", check=False)
+    return ReplayResult(
+        session_id=str(session.id),
+        agent_id=agent.id,
+        model=model,
+        base=base,
+        turns=turns,
+        diff_text=diff_text,
+        original_diff_text=original.stdout if original.returncode == 0 else None,
+        skipped_turns=skipped,
+    )
+
+
+def _cost_text(cost_usd: Optional[float]) -> str:
+    return f"~{format_cost(cost_usd)}" if cost_usd is not None else "n/a"
+
+
+def _diff_stats(diff_text: str) -> str:
+    files = split_diff(diff_text)
+    additions = sum(file_diff.additions for file_diff in files)
+    deletions = sum(file_diff.deletions for file_diff in files)
+    return f"{len(files)} file(s), +{additions} -{deletions}"
+
+
+def _excerpt(text: str) -> str:
+    text = text or "(no reply)"
+    return text if len(text) <= MAX_REPLY_CHARS else text[: MAX_REPLY_CHARS - 1] + "…"
+
+
+def format_replay_turn(turn: ReplayTurn, total: int) -> str:
+    """One progress line, e.g. "Turn 2/5: ok in 41s, ~$0.03"."""
+    status = f"{len(turn.errors)} error(s)" if turn.errors else "ok"
+    return f"Turn {turn.number}/{total}: {status} in {format_duration(turn.duration_secs)}, {_cost_text(turn.cost_usd)}"
+
+
+def format_replay(result: ReplayResult) -> str:
+    """Summary of the replay next to the original session, followed by each turn's replies."""
+    lines = [f"Replay of session `{result.session_id}` with `{result.label}` from `{result.base[:12]}`:"]
+    if result.skipped_turns:
+        lines.append(
+            f"The first {result.skipped_turns} turn(s) are no longer stored, so the replay starts at "
+            f"turn {result.turns[0].number}."
+        )
+    failed = sum(1 for turn in result.turns if turn.errors)
+    duration = format_duration(sum(turn.duration_secs for turn in result.turns))
+    lines.append(
+        f"• Replay: {len(result.turns)} turn(s), {failed} with errors, {_diff_stats(result.diff_text)}, "
+        f"{duration}, {_cost_text(result.cost_usd)}"
+    )
+    if result.original_diff_text is None:
+        lines.append("• Original: session branch not found")
+    else:
+        lines.append(f"• Original: {_diff_stats(result.original_diff_text)}")
+    for turn in result.turns:
+        lines.append(f"*Turn {turn.number}*: {_excerpt(turn.prompt)}")
+        lines.append(f"  original: {_excerpt(turn.original_reply)}")
+        lines.append(f"  {result.agent_id}: {_excerpt(turn.reply)}")
+        for error in turn.errors:
+            lines.append(f"  error: {error}")
+    return "\n".join(lines)
@@ -0,0 +1,164 @@
+"""Resource limits for small always-on hosts such as a Raspberry Pi.
+
+`REMOTE_CODER_LOW_MEMORY=1` switches the daemon to low-resource mode:
+
+- sessions keep fewer messages and interactions in memory; their full
+  transcripts stay on disk (`src/core/transcripts.py`)
+- in-memory caches (project languages, Slack channel names, collapsed diffs)
+  are bounded and drop their least recently used entries
+- agent processes are not kept warm between turns (`options.warm_pool`)
+
+Independently of that, `REMOTE_CODER_DISABLE_INDEXING=1` skips detecting
+project languages from tracked files for automatic agent selection, and
+`REMOTE_CODER_DISABLE_SUMMARIZATION=1` stops long sessions from being
+summarized. `!status --system` reports the daemon's memory usage.
+"""
+
+from __future__ import annotations
+
+import logging
+import os
+import sys
+from collections import OrderedDict
+from dataclasses import dataclass
+from pathlib import Path
+from typing import Generic, Hashable, Iterator, Optional, TypeVar
+
+from .transcripts import TRANSCRIPTS_DIR_NAME
+
+LOGGER = logging.getLogger(__name__)
+
+DEFAULT_HISTORY_LIMIT = 20
+LOW_MEMORY_HISTORY_LIMIT = 6
+LOW_MEMORY_MAX_INTERACTIONS = 10
+LOW_MEMORY_CACHE_SIZE = 32
+
+K = TypeVar("K", bound=Hashable)
+V = TypeVar("V")
+
+
+@dataclass(frozen=True)
+class ResourceSettings:
+    """Memory-related limits; zero means unbounded."""
+
+    low_memory: bool = False
+    history_limit: int = DEFAULT_HISTORY_LIMIT
+    max_interactions: int = 0
+    cache_size: int = 0
+    transcript_dir: Optional[Path] = None  # None when transcripts are turned off
+    indexing: bool = True
+    summarization: bool = True
+
+    @classmethod
+    def for_mode(
+        cls,
+        *,
+        low_memory: bool,
+        config_dir: Path,
+        indexing: bool = True,
+        summarization: bool = True,
+        transcripts: bool = True,
+    ) -> "ResourceSettings":
+        transcript_dir = config_dir / TRANSCRIPTS_DIR_NAME if transcripts else None
+        if not low_memory:
+            return cls(transcript_dir=transcript_dir, indexing=indexing, summarization=summarization)
+        return cls(
+            low_memory=True,
+            history_limit=LOW_MEMORY_HISTORY_LIMIT,
+            max_interactions=LOW_MEMORY_MAX_INTERACTIONS,
+            cache_size=LOW_MEMORY_CACHE_SIZE,
+            transcript_dir=transcript_dir,
+            indexing=indexing,
+            summarization=summarization,
+        )
+
+
+class BoundedCache(Generic[K, V]):
+    """Dict-like LRU cache holding at most `max_size` entries (unbounded when 0)."""
+
+    def __init__(self, max_size: int = 0) -> None:
+        self.max_size = max_size
+        self._items: "OrderedDict[K, V]" = OrderedDict()
+
+    def get(self, key: K, default: Optional[V] = None) -> Optional[V]:
+        if key not in self._items:
+            return default
+        self._items.move_to_end(key)
+        return self._items[key]
+
+    def __setitem__(self, key: K, value: V) -> None:
+        self._items[key] = value
+        self._items.move_to_end(key)
+        while self.max_size and len(self._items) > self.max_size:
+            self._items.popitem(last=False)
+
+    def __getitem__(self, key: K) -> V:
+        value = self._items[key]
+        self._items.move_to_end(key)
+        return value
+
+    def __contains__(self, key: object) -> bool:
+        return key in self._items
+
+    def __len__(self) -> int:
+        return len(self._items)
+
+    def __iter__(self) -> Iterator[K]:
+        return iter(self._items)
+
+    def clear(self) -> None:
+        self._items.clear()
+
+
+@dataclass(frozen=True)
+class MemoryUsage:
+    rss_bytes: Optional[int] = None
+    peak_rss_bytes: Optional[int] = None
+
+
+def memory_usage() -> MemoryUsage:
+    """Current and peak resident memory of the daemon process, where the platform reports them."""
+    rss_bytes: Optional[int] = None
+    peak_bytes: Optional[int] = None
+    try:
+        import resource  # Unix only
+
+        peak = resource.getrusage(resource.RUSAGE_SELF).ru_maxrss
+        # ru_maxrss is in bytes on macOS and in kilobytes on Linux
+        peak_bytes = peak if sys.platform == "darwin" else peak * 1024
+    except ImportError:
+        pass
+    try:
+        with open("/proc/self/statm", encoding="ascii") as fp:
+            rss_bytes = int(fp.read().split()[1]) * os.sysconf("SC_PAGE_SIZE")
+    except (OSError, ValueError, IndexError, AttributeError):
+        pass
+    return MemoryUsage(rss_bytes=rss_bytes, peak_rss_bytes=peak_bytes)
+
+
+def _megabytes(value: int) -> str:
+    return f"{value / (1024 * 1024):.1f} MB"
+
+
+def format_memory_usage(usage: MemoryUsage, settings: ResourceSettings) -> str:
+    if usage.rss_bytes is not None:
+        line = f"Memory: {_megabytes(usage.rss_bytes)} resident"
+        if usage.peak_rss_bytes is not None:
+            line += f" (peak {_megabytes(usage.peak_rss_bytes)})"
+    elif usage.peak_rss_bytes is not None:
+        line = f"Memory: peak {_megabytes(usage.peak_rss_bytes)} resident"
+    else:
+        line = "Memory: not reported on this platform"
+    if settings.low_memory:
+        line += (
+            f"; low-memory mode (history {settings.history_limit} messages, "
+            f"{settings.max_interactions} interactions, caches {settings.cache_size} entries)"
+        )
+    disabled = [
+        name
+        for name, enabled in (("indexing", settings.indexing), ("summarization", settings.summarization))
+        if not enabled
+    ]
+    if disabled:
+        line += f"; disabled: {', '.join(disabled)}"
+    return line
@@ -4,34 +4,127 @@ from __future__ import annotations
 
 import asyncio
 import logging
+import os
 import subprocess
+import time
+from contextvars import ContextVar
+from dataclasses import asdict, replace
+from datetime import datetime, timedelta, timezone
 from pathlib import Path
-from typing import Any, Awaitable, Callable, Dict, Optional
+from typing import Any, AsyncContextManager, Awaitable, Callable, Dict, Optional, Sequence, Tuple
 from uuid import UUID
 
-from ..chat_adapters.i_chat_adapter import IChatAdapter
+from ..agent_adapters.base import AgentResult
+from ..agent_adapters.warm_pool import WarmPool
+from ..agent_adapters.wasm_plugins import PLUGINS_DIR_NAME, discover_plugins
+from ..chat_adapters.health import format_connection_health, format_duration
+from ..chat_adapters.i_chat_adapter import ChatCapabilities, IChatAdapter, capabilities_of
 from ..github import GitHubManager
 from ..github.client import PRComment
+from .agent_auth import AuthState, check_agent_auth, describe_missing
+from .agent_cli import AgentCliChecker, format_cli_checks
 from .agent_runner import AgentTaskRunner
+from .commands.analytics import AnalyticsCommandHandler
+from .commands.auth import AuthCommandHandler
+from .commands.batch import BatchCommandHandler
+from .commands.bench import BenchCommandHandler
+from .commands.cancel import CancelCommandHandler
+from .commands.hints import build_command_hints, hints_fingerprint
 from .commands.parser import ParsedCommand, parse_command
 from .commands.catalog import CatalogCommandHandler
-from .commands.context import CommandContext
+from .commands.compare import CompareCommandHandler
+from .commands.context import ChatAttachment, CommandContext
 from .commands.dispatcher import CommandDispatcher
+from .commands.discussions import DiscussionCommandHandler
+from .commands.export import ExportCommandHandler
+from .commands.groups import GroupCommandHandler
 from .commands.maintenance import MaintenanceCommandHandler
+from .commands.move import MoveCommandHandler
+from .commands.patches import PatchCommandHandler
+from .commands.incident import IncidentCommandHandler
+from .commands.preferences import PreferencesCommandHandler
+from .commands.publishing import PublishingCommandHandler
+from .commands.redo import RedoCommandHandler
+from .commands.replay import ReplayCommandHandler
+from .commands.rollback import RollbackCommandHandler
 from .commands.project_creation import ProjectCreationHandler
 from .commands.registry import CommandSpec
 from .commands.review import ReviewCommandHandler
+from .commands.search import SearchCommandHandler
 from .commands.session import SessionCommandHandler
+from .commands.tags import TagCommandHandler
+from .commands.timeline import TimelineCommandHandler
+from .commands.verify import VerifyCommandHandler
+from .automation import AutomationGate
+from .bench import BenchCase, BenchReport, ProgressFn, run_bench
+from .agent_selection import TaskRequirements, detect_project_languages, select_agent
+from .ask_mode import ASK_MODE_COMMANDS, ASK_MODE_CONTEXT_KEY, in_ask_mode
 from .config import Config, load_config
-from .errors import GitHubError, ProjectNotFound, SessionNotFound
-from .git_workflow import GitWorkflowService
-from .conversation import InteractionClassifier, SessionManager
-from .models import Project, Session, SessionStatus
+from .confirmations import ConfirmationBroker
+from .diff_render import MAX_FILE_MESSAGE_CHARS, MAX_FILE_MESSAGES, format_diff_overview, render_file_diff, split_diff
+from .discussions import (
+    DESIGN_PHASE,
+    DISCUSSION_URL_CONTEXT_KEY,
+    PHASE_CONTEXT_KEY,
+    in_design_phase,
+    is_discussion_channel,
+)
+from .errors import (
+    AgentNotAllowed,
+    AnalyticsError,
+    ArchiveError,
+    AgentNotFound,
+    CommandNotFound,
+    ConfigError,
+    GitHubError,
+    ProjectLocked,
+    ProjectNotFound,
+    SearchError,
+    SessionNotFound,
+    SessionRestoreError,
+    SlackError,
+)
+from .analytics import ANALYTICS_DB_FILE_NAME, AnalyticsStore
+from .archive import SessionArchive
+from .cleanup import CleanupPass, SessionCleaner, snapshot_hook, transcript_hook
+from .events import PrMerged, RunFinished
+from .export import collect_export
+from .git_workflow import GitWorkflowService, session_branch_name
+from .github_events import parse_github_event, verify_signature
+from .idle import EXPIRED_NOTE
+from .incident import INCIDENT_CONTEXT_KEY, cheapest_agent
+from .jobs import JOBS, format_job_stats
+from .privacy import PRIVACY
+from .local_time import is_valid_timezone
+from .message_split import clip_message, split_message
+from .metrics import SessionMetrics
+from .conversation import InteractionClassifier, MessageIntent, SessionManager, classify_message
+from .models import Agent, AgentSelection, CommandDefinition, Project, RunOverrides, Session, SessionStatus
+from .preferences import PREFERENCES_CONTEXT_KEY, STARTED_BY_CONTEXT_KEY
+from .preflight import PREFLIGHT_BLOCKED_CONTEXT_KEY, format_preflight
+from .pricing import estimate_tokens
+from .redaction import SecretRedactor, redactor_for
+from .project_commands import load_project_command, render_prompt
+from .resources import BoundedCache, format_memory_usage, memory_usage
+from .run_queue import PositionFn, ProjectRunQueue
+from .run_summary import NextStep
+from .search import SEARCH_DB_FILE_NAME, SearchIndex
+from .run_journal import RUN_JOURNAL_DIR_NAME, Recovery, RunJournal, recover_orphaned_run
+from .scheduler import RESUMED_NOTE, Job, PreemptedFn, RunPriority, RunScheduler
+from .shutdown import NOT_STARTING_NOTE, PARK_COMMIT_MESSAGE, RESTART_NOTE, RunInfo, drain_runs, running
+from .snapshots import snapshots_dir
+from .status_page import IntegrationError
+from .takeover import LOCKED_NOTE, TAKEN_OVER_NOTE, TAKEOVER_COMMAND, TAKEOVER_COMMIT_MESSAGE
+from .updates import installed_version
+from .webhooks import WebhookError, resolve_webhook
 
 LOGGER = logging.getLogger(__name__)
 
 CommandHandler = Callable[[ParsedCommand, CommandContext], Awaitable[None]]
 
+# The project and thread of the command being handled, for the background runs it starts
+_COMMAND_ORIGIN: ContextVar[Tuple[str, str]] = ContextVar("command_origin", default=("", ""))
+
 
 class Router:
     """Central orchestrator translating Slack messages into agent executions."""
@@ -42,21 +135,57 @@ class Router:
         config: Config,
         github_manager: GitHubManager,
         config_root: Path,
+        config_loader: Optional[Callable[[], Config]] = None,
     ) -> None:
+        """`config_loader` re-reads the config on reloads; defaults to `load_config(config_root)`."""
         self._session_manager = session_manager
         self._config = config
         self._github_manager = github_manager
+        self._started_at = time.time()
         self._config_root = Path(config_root)
+        self._config_loader = config_loader or (lambda: load_config(self._config_root))
         self._chat_adapter: Optional[IChatAdapter] = None
+        # Adapters for channels with their own prefix, e.g. `github:` for discussions; the rest go to `_chat_adapter`
+        self._channel_adapters: Dict[str, IChatAdapter] = {}
+        self._command_hints_fingerprint: Optional[str] = None
+        self._hints_sync_task: Optional[asyncio.Task] = None
+        self._webhook_tasks: set[asyncio.Task] = set()
+        # Set at shutdown: no new runs start (see `src/core/shutdown.py`)
+        self._draining = False
+        self._hurry_shutdown = asyncio.Event()
         self._adapter_cache: Dict[str, AgentAdapter] = {}
         self._session_locks: Dict[str, asyncio.Lock] = {}
+        self._run_queue = ProjectRunQueue()
+        self._scheduler = RunScheduler(config.max_runs, preemption=config.preemption)
+        self._journal = RunJournal(self._config_root / RUN_JOURNAL_DIR_NAME)
+        self._session_archive = SessionArchive(config.archive) if config.archive else None
+        self._cleaner = self._build_cleaner(config)
+        self._session_metrics = SessionMetrics()
+        self._session_metrics.follow(session_manager.events)
+        self._search_index = self._open_search_index(config) if session_manager.keeps_transcripts else None
+        if self._search_index:
+            session_manager.events.subscribe(self._index_finished_run, RunFinished)
+        self._analytics = self._open_analytics(config)
+        if self._analytics:
+            self._analytics.follow(session_manager.events)
+        # Sessions told about their interrupted run at startup, which need no resume notice as well
+        self._recovered_sessions: set[str] = set()
+        JOBS.set_limit(config.max_jobs)
+        PRIVACY.set_mode(config.privacy)
+        self._redactor = self._build_redactor(config)
         self.active_runs: Dict[str, Dict[str, Any]] = {}
+        self._language_cache: BoundedCache[str, frozenset[str]] = BoundedCache(config.resources.cache_size)
         self._interaction_classifier = InteractionClassifier()
+        self._automation_gate = AutomationGate(run_counter=self._session_manager.project_runs)
+        self._cli_checker = AgentCliChecker()
+        self._warm_pool = WarmPool()
+        self._confirmations = ConfirmationBroker(self._send_confirmation, self._send_message)
         self._command_dispatcher = CommandDispatcher()
         self._project_creation_handler = ProjectCreationHandler(
             config=self._config,
             github_manager=self._github_manager,
             config_root=self._config_root,
+            config_loader=self._config_loader,
         )
         self._git_workflow = GitWorkflowService(
             github_manager=self._github_manager,
@@ -70,11 +199,27 @@ class Router:
             adapter_cache=self._adapter_cache,
             active_runs=self.active_runs,
             send_message=self._send_message,
+            update_message=self._update_message,
+            post_diff=self._post_diff,
+            chat_capabilities=self._capabilities_for,
+            plugins=discover_plugins(self._config_root / PLUGINS_DIR_NAME),
+            gate=self._automation_gate,
+            cli_checker=self._cli_checker,
+            warm_pool=self._warm_pool,
+            confirmations=self._confirmations,
+            send_run_summary=self._send_run_summary,
+            journal=self._journal,
         )
         self._session_commands = SessionCommandHandler(
             session_manager=self._session_manager,
             config=self._config,
             send_message=self._send_message,
+            get_system_status=self._system_status,
+            gate=self._automation_gate,
+            get_queue_status=self._queue_status,
+            active_runs=self.active_runs,
+            thread_link=self._thread_link,
+            take_over=self.take_over,
         )
         self._catalog_commands = CatalogCommandHandler(
             config=self._config,
@@ -83,7 +228,7 @@ class Router:
         )
         self._maintenance_commands = MaintenanceCommandHandler(
             session_manager=self._session_manager,
-            config_loader=lambda: load_config(self._config_root),
+            config_loader=self._config_loader,
             apply_new_config=self._apply_new_config,
             get_current_config=lambda: self._config,
             active_runs=self.active_runs,
@@ -96,35 +241,276 @@ class Router:
             session_manager=self._session_manager,
             github_manager=self._github_manager,
             build_review_prompt=self._build_review_prompt,
-            execute_agent_task=self._agent_runner.run,
+            execute_agent_task=self._run_agent_queued,
+            send_message=self._send_message,
+        )
+        self._patch_commands = PatchCommandHandler(
+            build_patch=self._git_workflow.build_patch,
+            upload_file=self._upload_file,
+            apply_patch=self._git_workflow.apply_patch,
+            revert_patch=self._git_workflow.revert_patch,
+            publish_changes=self._git_workflow.maybe_publish_code_changes,
+            active_runs=self.active_runs,
+            send_message=self._send_message,
+            record_checks=self._session_manager.record_checks,
+            contains_secret=self._contains_secret,
+        )
+        self._export_commands = ExportCommandHandler(
+            session_manager=self._session_manager,
+            build_diff=self._git_workflow.branch_diff,
+            upload_file=self._upload_file,
+            send_message=self._send_message,
+            session_archive=lambda: self._session_archive,
+        )
+        self._move_commands = MoveCommandHandler(
+            session_manager=self._session_manager,
+            config=self._config,
+            send_message=self._send_message,
+            resolve_channel=self._resolve_channel,
+            thread_link=self._thread_link,
+            active_runs=self.active_runs,
+        )
+        self._analytics_commands = AnalyticsCommandHandler(
+            config=self._config,
+            analytics=lambda: self._analytics,
+            send_message=self._send_message,
+        )
+        self._search_commands = SearchCommandHandler(
+            search_index=lambda: self._search_index,
+            send_message=self._send_message,
+            thread_link=self._thread_link,
+        )
+        self._tag_commands = TagCommandHandler(
+            session_manager=self._session_manager,
+            send_message=self._send_message,
+            label_pull_requests=self._git_workflow.label_pull_requests,
+        )
+        self._publishing_commands = PublishingCommandHandler(
+            unpublish=self._git_workflow.unpublish,
+            republish=self._git_workflow.republish,
+            get_pr_title=self._get_session_pr_title,
+            send_message=self._send_message,
+        )
+        self._verify_commands = VerifyCommandHandler(
+            active_runs=self.active_runs,
+            send_message=self._send_message,
+            record_checks=self._session_manager.record_checks,
+        )
+        self._group_commands = GroupCommandHandler(
+            config=self._config,
+            gate=self._automation_gate,
+            session_manager=self._session_manager,
+            active_runs=self.active_runs,
+            send_message=self._send_message,
+        )
+        self._discussion_commands = DiscussionCommandHandler(
+            session_manager=self._session_manager,
+            execute_agent_task=self._run_agent_queued,
+            active_runs=self.active_runs,
+            send_message=self._send_message,
+        )
+        self._cancel_commands = CancelCommandHandler(
+            active_runs=self.active_runs,
+            send_message=self._send_message,
+        )
+        self._redo_commands = RedoCommandHandler(
+            session_manager=self._session_manager,
+            reset_to_checkpoint=self._git_workflow.reset_to_checkpoint,
+            execute_agent_task=self._run_agent_queued,
+            active_runs=self.active_runs,
+            send_message=self._send_message,
+        )
+        self._timeline_commands = TimelineCommandHandler(
+            session_manager=self._session_manager,
+            active_runs=self.active_runs,
+            send_message=self._send_message,
+        )
+        self._rollback_commands = RollbackCommandHandler(
+            config=self._config,
+            active_runs=self.active_runs,
+            send_message=self._send_message,
+        )
+        self._compare_commands = CompareCommandHandler(
+            config=self._config,
+            session_manager=self._session_manager,
+            run_agent=self._run_background,
+            post_diff=self._post_diff,
+            active_runs=self.active_runs,
+            send_message=self._send_message,
+            gate=self._automation_gate,
+        )
+        self._batch_commands = BatchCommandHandler(
+            config=self._config,
+            session_manager=self._session_manager,
+            git_workflow=self._git_workflow,
+            run_agent=self._run_background,
+            post_diff=self._post_diff,
+            active_runs=self.active_runs,
+            send_message=self._send_message,
+            gate=self._automation_gate,
+        )
+        self._bench_commands = BenchCommandHandler(
+            config=self._config,
+            session_manager=self._session_manager,
+            run_agent=self._run_background,
+            upload_file=self._upload_file,
+            active_runs=self.active_runs,
+            send_message=self._send_message,
+            gate=self._automation_gate,
+        )
+        self._replay_commands = ReplayCommandHandler(
+            config=self._config,
+            session_manager=self._session_manager,
+            run_agent=self._run_background,
+            post_diff=self._post_diff,
+            active_runs=self.active_runs,
+            send_message=self._send_message,
+            gate=self._automation_gate,
+        )
+        self._auth_commands = AuthCommandHandler(
+            config=self._config,
+            notify_operators=self._notify_operators,
+            send_message=self._send_message,
+        )
+        self._preference_commands = PreferencesCommandHandler(
+            session_manager=self._session_manager,
+            config=self._config,
+            send_message=self._send_message,
+        )
+        self._incident_commands = IncidentCommandHandler(
+            session_manager=self._session_manager,
+            config=self._config,
             send_message=self._send_message,
         )
         self._command_handlers: Dict[str, CommandHandler] = {
             "session.use": self._session_commands.handle_use,
             "session.end": self._session_commands.handle_end,
+            "session.pause": self._session_commands.handle_pause,
+            "session.resume": self._session_commands.handle_resume,
+            "session.takeover": self._session_commands.handle_takeover,
             "session.status": self._session_commands.handle_status,
+            "session.list": self._session_commands.handle_sessions,
+            "session.cost": self._session_commands.handle_cost,
             "review.pending": self._review_commands.handle_review,
             "maintenance.purge": self._maintenance_commands.handle_purge,
             "catalog.agents": self._catalog_commands.handle_agents,
             "catalog.models": self._catalog_commands.handle_models,
             "maintenance.reload_projects": self._maintenance_commands.handle_reload_projects,
             "maintenance.stash": self._maintenance_commands.handle_stash,
+            "patch.export": self._patch_commands.handle_patch,
+            "patch.apply": self._patch_commands.handle_apply,
+            "export.session": self._export_commands.handle_export,
+            "export.archived": self._export_commands.handle_archived,
+            "search.sessions": self._search_commands.handle_search,
+            "analytics.report": self._analytics_commands.handle_analytics,
+            "move.session": self._move_commands.handle_move,
+            "tags.add": self._tag_commands.handle_tag,
+            "tags.remove": self._tag_commands.handle_untag,
+            "publish.unpublish": self._publishing_commands.handle_unpublish,
+            "publish.publish": self._publishing_commands.handle_publish,
+            "verify.test": self._verify_commands.handle_test,
+            "group.manage": self._group_commands.handle_group,
+            "compare.run": self._compare_commands.handle_compare,
+            "batch.run": self._batch_commands.handle_batch,
+            "bench.run": self._bench_commands.handle_bench,
+            "replay.run": self._replay_commands.handle_replay,
+            "run.cancel": self._cancel_commands.handle_cancel,
+            "discussion.implement": self._discussion_commands.handle_implement,
+            "redo.run": self._redo_commands.handle_redo,
+            "rollback.run": self._rollback_commands.handle_rollback,
+            "timeline.list": self._timeline_commands.handle_timeline,
+            "timeline.checkout": self._timeline_commands.handle_checkout,
+            "auth.check": self._auth_commands.handle_auth,
+            "prefs.manage": self._preference_commands.handle_prefs,
+            "incident.manage": self._incident_commands.handle_incident,
             "catalog.help": self._catalog_commands.handle_help,
         }
 
-    def bind_adapter(self, adapter: IChatAdapter) -> None:
-        """Attach the chat adapter so the router can send replies."""
+    def bind_adapter(self, adapter: IChatAdapter, channel_prefix: Optional[str] = None) -> None:
+        """Attach the chat adapter so the router can send replies.
 
+        With `channel_prefix`, the adapter only handles channels whose id starts with it (e.g. `github:`
+        for GitHub Discussions) and the main adapter keeps the rest.
+        """
+        if channel_prefix:
+            self._channel_adapters[channel_prefix] = adapter
+            return
         self._chat_adapter = adapter
+        self._command_hints_fingerprint = None
+
+    def _adapter_for(self, channel: Optional[str]) -> Optional[IChatAdapter]:
+        for prefix, adapter in self._channel_adapters.items():
+            if channel and channel.startswith(prefix):
+                return adapter
+        return self._chat_adapter
+
+    def _thread_link(self, channel: str, thread_ts: str) -> Optional[str]:
+        """A web link to the thread, from chat adapters that implement `thread_link`."""
+        adapter = self._adapter_for(channel)
+        if not adapter or not hasattr(adapter, "thread_link"):
+            return None
+        return adapter.thread_link(channel, thread_ts)
+
+    def _capabilities_for(self, channel: Optional[str]) -> ChatCapabilities:
+        """What the chat that `channel` belongs to can show (message length, threads, buttons, uploads)."""
+        return capabilities_of(self._adapter_for(channel))
+
+    async def sync_command_hints(self) -> None:
+        """Register built-in and project commands with the chat client when they changed since the last sync.
+
+        Adapters opt in by implementing `register_commands(hints)` (Slack updates its slash commands).
+        """
+        if not self._chat_adapter or not hasattr(self._chat_adapter, "register_commands"):
+            return
+        hints = build_command_hints(self._command_dispatcher.specs, self._config.projects.values())
+        fingerprint = hints_fingerprint(hints)
+        if fingerprint == self._command_hints_fingerprint:
+            return
+        try:
+            await self._chat_adapter.register_commands(hints)
+        except SlackError as exc:
+            LOGGER.warning("Failed to register chat commands: %s", exc)
+            return
+        self._command_hints_fingerprint = fingerprint
 
     def _apply_new_config(self, new_config: Config) -> None:
         self._config = new_config
         self._github_manager.update_token(new_config.github_token)
         self._adapter_cache.clear()
+        # Re-probe CLIs on the next run, in case agents.yaml or the installs changed
+        self._cli_checker.clear()
         self._session_commands.update_config(new_config)
         self._catalog_commands.update_config(new_config)
+        self._group_commands.update_config(new_config)
+        self._compare_commands.update_config(new_config)
+        self._batch_commands.update_config(new_config)
+        self._bench_commands.update_config(new_config)
+        self._replay_commands.update_config(new_config)
+        self._rollback_commands.update_config(new_config)
+        self._auth_commands.update_config(new_config)
+        self._preference_commands.update_config(new_config)
+        self._incident_commands.update_config(new_config)
+        self._move_commands.update_config(new_config)
+        self._analytics_commands.update_config(new_config)
+        self._redactor = self._build_redactor(new_config)
+        self._session_archive = SessionArchive(new_config.archive) if new_config.archive else None
         self._agent_runner.update_config(new_config)
         self._project_creation_handler.update_config(new_config)
+        self._language_cache.clear()
+        self._language_cache.max_size = new_config.resources.cache_size
+        self._scheduler.set_limit(new_config.max_runs)
+        self._scheduler.preemption = new_config.preemption
+        JOBS.set_limit(new_config.max_jobs)
+        PRIVACY.set_mode(new_config.privacy)
+        for adapter in self._channel_adapters.values():
+            if hasattr(adapter, "update_config"):
+                adapter.update_config(new_config)
+
+        try:
+            # Projects may have been added or removed, and with them their `.cockpit` commands
+            self._hints_sync_task = asyncio.get_running_loop().create_task(self.sync_command_hints())
+        except RuntimeError:
+            pass  # No event loop (config applied outside the daemon)
 
         if self._chat_adapter and hasattr(self._chat_adapter, "update_allowed_users"):
             try:
@@ -132,7 +518,35 @@ class Router:
             except Exception:  # pragma: no cover - defensive
                 LOGGER.warning("Failed to update Slack allowed users during config reload", exc_info=True)
 
+    async def handle_channel_joined(self, event: Dict[str, Any]) -> None:
+        """Start onboarding when the bot is invited to a channel without a project."""
+        channel_id = event.get("channel")
+        if not channel_id:
+            return
+        channel_lookup = event.get("channel_name") or channel_id
+        try:
+            project = self._config.get_project_by_channel(channel_lookup)
+        except ProjectNotFound:
+            LOGGER.info("Invited to unmapped channel %s; starting onboarding", channel_lookup)
+            await self._project_creation_handler.handle_missing_project(
+                channel_id, channel_lookup, None, self._send_message
+            )
+            return
+        LOGGER.info("Invited to channel %s for project %s", channel_lookup, project.id)
+
+    def answer_confirmation(self, key: str, approved: bool) -> bool:
+        """Answer an agent's confirmation prompt from its button; False if it is no longer waiting."""
+        return self._confirmations.answer(key, approved)
+
     async def handle_message(self, event: Dict[str, Any]) -> None:
+        """Handle one chat message.
+
+        `event` carries `channel` (id), `channel_name` (matched against project
+        channels; defaults to the id), `text`, `ts`, and `thread_ts` for replies
+        in a thread, plus optionally `user_tz`, the sender's IANA timezone. The
+        first message of a thread starts the session; later ones run commands
+        or the agent. Replies go through the bound adapter.
+        """
         channel_id = event.get("channel")
         channel_lookup = event.get("channel_name") or channel_id
         text = (event.get("text") or "").strip()
@@ -174,18 +588,38 @@ class Router:
             )
             return
 
-        session, created = self._get_or_create_session(project, channel_id, thread_ts)
+        read_only = channel_lookup in project.ask_channels or self._session_manager.incident_for(project.id) is not None
+        try:
+            session, created = self._get_or_create_session(project, channel_id, thread_ts, read_only=read_only)
+        except ProjectLocked as locked:
+            if not await self._refuse_or_take_over(locked, project, channel_id, thread_ts, text):
+                return
+            session, created = self._get_or_create_session(project, channel_id, thread_ts)
+            text = ""  # `!takeover` is done; the session starts without a run
+        if not created:
+            self._session_manager.record_activity(session.id)
+        if created and event.get("user"):
+            self._apply_user_preferences(session, project, event["user"])
+        if created and channel_lookup in project.ask_channels:
+            self._session_manager.update_session_context(session.id, {ASK_MODE_CONTEXT_KEY: True})
+        if created and self._session_manager.incident_for(project.id):
+            self._start_diagnosis_session(session, project)
+        if created and is_discussion_channel(channel_id):
+            await self._start_discussion_session(session, project, channel_id, thread_ts, event)
+            created = False  # The discussion's opening post is a request, not just a greeting
+        user_tz = event.get("user_tz")
+        if user_tz and user_tz != session.timezone:
+            if is_valid_timezone(user_tz):
+                self._session_manager.set_timezone(session.id, user_tz)
+            else:
+                LOGGER.debug("Ignoring unknown timezone %s for session %s", user_tz, session.id)
 
         command = parse_command(text)
         command_spec: Optional[CommandSpec] = None
         if command:
             command_spec = self._command_dispatcher.get_spec(command.name)
             if not command_spec:
-                await self._send_message(
-                    channel_id,
-                    thread_ts,
-                    f"Unknown command `{command.name}`. Use `!help` to see supported commands.",
-                )
+                await self._run_project_command(command, session, project, channel_id, thread_ts, created)
                 return
         else:
             command = self._command_dispatcher.parse_bot_command(text)
@@ -193,13 +627,101 @@ class Router:
                 command_spec = self._command_dispatcher.get_spec(command.name)
 
         if command and command_spec:
-            await self._handle_command(command, command_spec, session, project, channel_id, thread_ts)
+            await self._handle_command(
+                command,
+                command_spec,
+                session,
+                project,
+                channel_id,
+                thread_ts,
+                attachments=self._extract_attachments(event),
+                user_id=event.get("user"),
+            )
             return
 
         if not text:
             LOGGER.debug("Ignoring empty Slack message in %s", channel_lookup)
             return
 
+        needs_images = _has_image_files(event)
+        intent = MessageIntent.TASK
+        if not created and not needs_images and self._config.intent_detection:
+            intent = classify_message(text, self._last_agent_reply(session))
+        if intent == MessageIntent.CHATTER and session.status != SessionStatus.ENDED:
+            LOGGER.info("Not running an agent for chatter in session %s", session.id)
+            await self._send_message(
+                channel_id,
+                thread_ts,
+                f"👍 Not running `{session.active_agent_id}` for that. Send a request when you want more changes.",
+            )
+            return
+
+        await self._run_agent_locked(
+            session, project, channel_id, thread_ts, text, created, needs_images=needs_images, intent=intent
+        )
+
+    async def _start_discussion_session(
+        self, session: Session, project: Project, channel_id: str, thread_ts: str, event: Dict[str, Any]
+    ) -> None:
+        """Sessions from GitHub Discussions start in the design phase (see `src/core/discussions.py`)."""
+        context: Dict[str, object] = {PHASE_CONTEXT_KEY: DESIGN_PHASE}
+        if event.get("discussion_url"):
+            context[DISCUSSION_URL_CONTEXT_KEY] = event["discussion_url"]
+        self._session_manager.update_session_context(session.id, context)
+        await self._run_agent_locked(session, project, channel_id, thread_ts, "", True)
+
+    def _last_agent_reply(self, session: Session) -> Optional[str]:
+        """The agent's latest reply in the session, None before its first run."""
+        history = self._session_manager.get_conversation_history(session.id)
+        return next((message.content for message in reversed(history) if message.role == "assistant"), None)
+
+    async def _run_project_command(
+        self,
+        command: ParsedCommand,
+        session: Session,
+        project: Project,
+        channel_id: str,
+        thread_ts: str,
+        session_created: bool,
+    ) -> None:
+        """Run the project's `.cockpit/commands/<name>.md` prompt, if it has one."""
+        try:
+            definition = load_project_command(session.project_path, command.name)
+        except CommandNotFound:
+            await self._send_message(
+                channel_id,
+                thread_ts,
+                f"Unknown command `{command.name}`. Use `!help` to see supported commands.",
+            )
+            return
+        except ConfigError as exc:
+            await self._send_message(channel_id, thread_ts, f"Cannot run `!{command.name}`: {exc}")
+            return
+
+        LOGGER.info("Running project command %s in session %s", command.name, session.id)
+        await self._run_agent_locked(
+            session,
+            project,
+            channel_id,
+            thread_ts,
+            render_prompt(definition, command.raw_args),
+            session_created,
+            overrides=definition.overrides,
+        )
+
+    async def _run_agent_locked(
+        self,
+        session: Session,
+        project: Project,
+        channel_id: str,
+        thread_ts: str,
+        user_text: str,
+        session_created: bool,
+        needs_images: bool = False,
+        overrides: Optional[RunOverrides] = None,
+        intent: MessageIntent = MessageIntent.TASK,
+        priority: RunPriority = RunPriority.INTERACTIVE,
+    ) -> None:
         if session.status == SessionStatus.ENDED:
             await self._send_message(
                 channel_id,
@@ -207,18 +729,324 @@ class Router:
                 "This session has ended. Start a new Slack thread to begin another run.",
             )
             return
+        if await self._refuse_while_paused(session, channel_id, thread_ts):
+            return
+        if await self._refuse_while_draining(channel_id, thread_ts):
+            return
 
         lock = self._get_session_lock(str(session.id))
-        async with lock:
-            await self._run_agent_interaction(session, project, channel_id, thread_ts, text, created)
+        ask = in_ask_mode(session)
+        self._session_manager.set_run_state(session.id, SessionStatus.QUEUED)
+        try:
+            async with lock, self._project_slot(project, channel_id, thread_ts, ask=ask, priority=priority):
+                # Runs that waited for the checkout through a shutdown request or `!pause` do not start
+                if await self._refuse_while_draining(channel_id, thread_ts):
+                    return
+                if await self._refuse_while_paused(session, channel_id, thread_ts):
+                    return
+                await self._run_agent_interaction(
+                    session,
+                    project,
+                    channel_id,
+                    thread_ts,
+                    user_text,
+                    session_created,
+                    needs_images=needs_images,
+                    overrides=overrides,
+                    intent=intent,
+                    priority=priority,
+                )
+        finally:
+            # Still queued when the run did not start
+            self._session_manager.finish_run(session.id)
+        # The run may have added or edited `.cockpit/commands`
+        await self.sync_command_hints()
+
+    async def _run_agent_queued(
+        self, session: Session, project: Project, channel_id: str, thread_ts: str, user_text: str
+    ) -> None:
+        """Run the agent for a command (`!review`, `!redo`, ...) once the project's checkout is free."""
+        self._session_manager.set_run_state(session.id, SessionStatus.QUEUED)
+        try:
+            async with self._project_slot(project, channel_id, thread_ts), self._run_slot(
+                Job(RunPriority.INTERACTIVE, project.id, f"{channel_id}:{thread_ts}"), channel_id, thread_ts
+            ):
+                if await self._refuse_while_draining(channel_id, thread_ts):
+                    return
+                if await self._refuse_while_paused(session, channel_id, thread_ts):
+                    return
+                await self._agent_runner.run(session, project, channel_id, thread_ts, user_text)
+        finally:
+            self._session_manager.finish_run(session.id)
+
+    async def _refuse_while_paused(self, session: Session, channel_id: str, thread_ts: str) -> bool:
+        if session.status != SessionStatus.PAUSED:
+            return False
+        await self._send_message(channel_id, thread_ts, "This session is paused. Send `!resume` to carry on.")
+        return True
+
+    async def _refuse_while_draining(self, channel_id: str, thread_ts: str) -> bool:
+        if not self._draining:
+            return False
+        await self._send_message(channel_id, thread_ts, NOT_STARTING_NOTE)
+        return True
+
+    def _project_slot(
+        self,
+        project: Project,
+        channel_id: str,
+        thread_ts: str,
+        *,
+        ask: bool = False,
+        priority: RunPriority = RunPriority.INTERACTIVE,
+    ) -> AsyncContextManager[None]:
+        notice = self._queue_notice(channel_id, thread_ts, f"another run is using the `{project.id}` checkout")
+        if ask:
+            # Question runs share the project's read-only checkout, not the one edit runs use
+            return self._run_queue.slot(f"{project.id}/ask", 1, notice)
+        if priority == RunPriority.INTERACTIVE and self._run_queue.running(project.id) >= project.max_concurrent_runs:
+            # A webhook run holding the checkout gives it up; it queues again behind this run
+            self._scheduler.preempt(Job(priority, project.id, f"{channel_id}:{thread_ts}"), project_id=project.id)
+        return self._run_queue.slot(project.id, project.max_concurrent_runs, notice)
+
+    def _run_slot(self, job: Job, channel_id: str, thread_ts: str) -> AsyncContextManager[None]:
+        """One of the daemon's run slots (`REMOTE_CODER_MAX_RUNS`), taken once the project's checkout is free."""
+        busy = f"all {self._scheduler.limit} run slots of Remote Coder are busy"
+        return self._scheduler.slot(job, self._queue_notice(channel_id, thread_ts, busy))
+
+    async def _run_background(
+        self, agent: Agent, model: Optional[str], workdir: Path, user_text: str, context: str = ""
+    ) -> Tuple[AgentResult, Optional[float]]:
+        """`run_isolated` for `!batch`, `!compare`, `!bench`, and `!replay`, behind the runs of chat messages."""
+        project_id, thread = _COMMAND_ORIGIN.get()
+        job = Job(RunPriority.BATCH, project_id, thread)
+
+        async def attempt(number: int) -> Tuple[AgentResult, Optional[float]]:
+            # The workdir keeps what a stopped attempt did, so later attempts pick up from there
+            resumed = "\n\n".join(filter(None, [context, RESUMED_NOTE])) if number else context
+            async with self._scheduler.slot(job):
+                return await self._agent_runner.run_isolated(agent, model, workdir, user_text, resumed)
+
+        return await self._scheduler.run_preemptible(job, attempt, self._preempted_notice(thread))
+
+    def _preempted_notice(self, thread: str) -> Optional[PreemptedFn]:
+        """Tell `thread` (`<channel>:<thread_ts>`) that its run was stopped for a chat message and will start over."""
+        if not thread:
+            return None
+        channel_id, thread_ts = thread.split(":", 1)
+
+        async def notify(_: int) -> None:
+            await self._send_message(
+                channel_id,
+                thread_ts,
+                "Paused this run to make room for a chat message; it starts over, keeping its changes so far, "
+                "once a slot is free.",
+            )
+
+        return notify
+
+    def _queue_notice(self, channel_id: str, thread_ts: str, waiting_for: str) -> PositionFn:
+        """Show a waiting run's place in line in one thread message, edited as it moves up."""
+        posted: Dict[str, str] = {}
+
+        async def show(position: int) -> None:
+            text = (
+                f"Queued (#{position}): {waiting_for}. This one starts when it is its turn."
+                if position
+                else "No longer queued; starting now."
+            )
+            ts = posted.get("ts")
+            if ts is not None:
+                await self._update_message(channel_id, ts, text)
+            elif position:
+                ts = await self._send_message(channel_id, thread_ts, text)
+                if ts:
+                    posted["ts"] = ts
+
+        return show
+
+    async def handle_webhook(
+        self, project_id: str, command_name: str, authorization: Optional[str], body: bytes
+    ) -> Tuple[int, Dict[str, object]]:
+        """Run a project command for an external system in a new thread of the project's channel.
+
+        Returns the HTTP status and JSON payload for the caller (see `src/core/webhooks.py`); the run
+        itself continues in the background.
+        """
+        try:
+            request = resolve_webhook(self._config, project_id, command_name, authorization, body)
+            definition = load_project_command(request.project.path, request.command)
+        except WebhookError as exc:
+            return exc.status, {"error": str(exc)}
+        except CommandNotFound:
+            return 404, {"error": f"project {project_id} has no command {command_name}"}
+        except ConfigError as exc:
+            return 422, {"error": str(exc)}
+
+        project = request.project
+        if self._draining:
+            return 503, {"error": "Remote Coder is shutting down"}
+        invocation = f"!{request.command} {request.arguments}".strip()
+        try:
+            channel_id = await self._resolve_project_channel(project)
+            if not channel_id:
+                return 503, {"error": f"channel {project.channel_name} not found (is the bot in it?)"}
+            thread_ts = await self._send_message(channel_id, None, f"Webhook ran `{invocation}`")
+        except SlackError as exc:
+            LOGGER.warning("Cannot post webhook run of %s in project %s: %s", invocation, project.id, exc)
+            return 502, {"error": f"could not post to the project channel: {exc}"}
+        if not thread_ts:
+            return 503, {"error": "the chat connection is not ready"}
+
+        try:
+            session, _ = self._get_or_create_session(project, channel_id, thread_ts)
+        except ProjectLocked as locked:
+            await self._refuse_or_take_over(locked, project, channel_id, thread_ts, "")
+            return 409, {"error": f"project {project.id} already has {len(locked.holders)} active session(s)"}
+        LOGGER.info("Webhook running project command %s in session %s", request.command, session.id)
+        task = asyncio.create_task(
+            self._run_webhook_command(
+                session, project, channel_id, thread_ts, render_prompt(definition, request.arguments), definition
+            )
+        )
+        self._webhook_tasks.add(task)
+        task.add_done_callback(self._webhook_tasks.discard)
+        return 202, {"session_id": str(session.id), "channel": channel_id, "thread_ts": thread_ts}
 
-    def _get_or_create_session(self, project: Project, channel_id: str, thread_ts: str) -> tuple[Session, bool]:
+    async def handle_github_event(
+        self, event_name: Optional[str], signature: Optional[str], body: bytes
+    ) -> Tuple[int, Dict[str, object]]:
+        """Post a GitHub event about a session's PR into the session's thread (see `src/core/github_events.py`)."""
+        secret = self._config.github_webhook_secret
+        if not secret:
+            return 404, {"error": "GitHub events are not set up"}
+        try:
+            verify_signature(secret, body, signature)
+            events = parse_github_event(event_name, body)
+        except WebhookError as exc:
+            return exc.status, {"error": str(exc)}
+
+        delivered: list[str] = []
+        for event in events:
+            for session in self._sessions_for_pr(event.repo, event.number):
+                message = f"GitHub, PR #{event.number}: {event.text}"
+                if event.merged is not None:
+                    # Nothing left for the agent to do: a note in the thread, and the merge for analytics
+                    if event.merged:
+                        self._session_manager.events.publish(PrMerged(session.id, session.project_id, event.number))
+                    await self._notify_thread(session.channel_id, session.thread_ts, message)
+                    delivered.append(str(session.id))
+                    continue
+                if not session.status.is_open:
+                    try:
+                        self._session_manager.restore_session(session.id)
+                    except SessionRestoreError as exc:
+                        LOGGER.info("Not posting PR #%d event to session %s: %s", event.number, session.id, exc)
+                        continue
+                    LOGGER.info("Reactivated session %s for an event on PR #%d", session.id, event.number)
+                # In the history, so a follow-up can ask the agent to address it
+                self._session_manager.append_agent_message(session.id, message)
+                await self._notify_thread(session.channel_id, session.thread_ts, message)
+                delivered.append(str(session.id))
+        return 200, {"delivered": delivered}
+
+    def _sessions_for_pr(self, repo: str, number: int) -> list[Session]:
+        """The sessions that recorded PR `number` of `repo` ("owner/repo")."""
+        sessions: Dict[UUID, Session] = {}
+        for pr_ref in self._session_manager.find_pr_refs(number, repo):
+            if pr_ref.repo is None:
+                project = self._config.projects.get(pr_ref.project_id)
+                if project is None or project.github is None or project.github.full_name.lower() != repo.lower():
+                    continue
+            try:
+                sessions.setdefault(pr_ref.session_id, self._session_manager.get_session(pr_ref.session_id))
+            except SessionNotFound:
+                continue
+        return list(sessions.values())
+
+    async def _run_webhook_command(
+        self,
+        session: Session,
+        project: Project,
+        channel_id: str,
+        thread_ts: str,
+        prompt: str,
+        definition: CommandDefinition,
+    ) -> None:
+        try:
+            # The first call only starts the session (branch and greeting), like a thread's first message
+            await self._run_agent_locked(session, project, channel_id, thread_ts, prompt, True)
+            thread = f"{channel_id}:{thread_ts}"
+            await self._scheduler.run_preemptible(
+                Job(RunPriority.SCHEDULED, project.id, thread),
+                lambda _: self._run_agent_locked(
+                    session,
+                    project,
+                    channel_id,
+                    thread_ts,
+                    prompt,
+                    False,
+                    overrides=definition.overrides,
+                    priority=RunPriority.SCHEDULED,
+                ),
+                self._preempted_notice(thread),
+            )
+        except Exception:
+            LOGGER.exception("Webhook run of %s failed in session %s", definition.id, session.id)
+
+    async def _resolve_project_channel(self, project: Project) -> Optional[str]:
+        """The channel id for the project's channel name."""
+        return await self._resolve_channel(project.channel_name)
+
+    async def _resolve_channel(self, name: str) -> Optional[str]:
+        """The channel id for a channel name; adapters opt in with `resolve_channel(name)`."""
+        if self._chat_adapter and hasattr(self._chat_adapter, "resolve_channel"):
+            return await self._chat_adapter.resolve_channel(name)
+        return name
+
+    async def _preflight(self, session: Session, project: Project, channel_id: str, thread_ts: str) -> bool:
+        """Check the checkout before the session's first run; False while blocking issues remain."""
+        try:
+            issues = await self._git_workflow.preflight(session, project)
+        except OSError as exc:
+            LOGGER.warning("Could not check the repository of session %s: %s", session.id, exc)
+            return True
+        if issues:
+            await self._send_message(channel_id, thread_ts, format_preflight(project, issues))
+        blocked = any(issue.blocking for issue in issues)
+        if blocked or session.session_context.get(PREFLIGHT_BLOCKED_CONTEXT_KEY):
+            self._session_manager.update_session_context(session.id, {PREFLIGHT_BLOCKED_CONTEXT_KEY: blocked})
+        return not blocked
+
+    async def _setup_session_branch(self, session: Session, project: Project, channel_id: str, thread_ts: str) -> bool:
+        try:
+            await self._git_workflow.setup_session_branch(session, project)
+        except GitHubError as exc:
+            await self._send_message(
+                channel_id,
+                thread_ts,
+                f"Failed to prepare session branch: {exc}",
+            )
+        except subprocess.CalledProcessError as exc:
+            detail = (exc.stderr or exc.stdout or str(exc)).strip()
+            await self._send_message(
+                channel_id,
+                thread_ts,
+                f"Failed to prepare session branch: {detail or 'git error'}",
+            )
+        else:
+            return True
+        return False
+
+    def _get_or_create_session(
+        self, project: Project, channel_id: str, thread_ts: str, *, read_only: bool = False
+    ) -> tuple[Session, bool]:
+        """The thread's session, or a new one; raises ProjectLocked when the project has no room for it."""
         try:
             return self._session_manager.get_by_thread(channel_id, thread_ts), False
         except SessionNotFound:
             default_agent = self._config.get_agent(project.default_agent_id)
-            # Prefer project's default_model (set during creation), fall back to agent's default
-            default_model = project.default_model or (default_agent.models.get("default") if default_agent.models else None)
+            default_model = project.model_for(default_agent)
             session = self._session_manager.create_session(
                 project=project,
                 channel_id=channel_id,
@@ -226,9 +1054,101 @@ class Router:
                 agent_id=default_agent.id,
                 agent_type=default_agent.type,
                 active_model=default_model,
+                read_only=read_only,
             )
             return session, True
 
+    def _where(self, session: Session) -> str:
+        """A link to the session's thread, or its id where the chat has no links."""
+        return self._thread_link(session.channel_id, session.thread_ts) or f"thread `{session.thread_ts}`"
+
+    async def _refuse_or_take_over(
+        self, locked: ProjectLocked, project: Project, channel_id: str, thread_ts: str, text: str
+    ) -> bool:
+        """Answer a thread the project has no room for: `!takeover` makes room (True), anything else is refused."""
+        command = parse_command(text)
+        # The least recently active sessions go first, as many as it takes to make room
+        ending = locked.holders[max((project.max_active_sessions or 1) - 1, 0) :]
+        if command and command.name == TAKEOVER_COMMAND:
+            return await self.take_over(ending, channel_id, thread_ts)
+        note = LOCKED_NOTE.format(
+            project=project.id,
+            count=len(locked.holders),
+            threads=", ".join(self._where(holder) for holder in locked.holders),
+            ending="it" if len(locked.holders) == 1 else f"the one in {self._where(ending[0])}",
+        )
+        await self._send_message(channel_id, thread_ts, note)
+        return False
+
+    async def take_over(self, sessions: Sequence[Session], channel_id: str, thread_ts: str) -> bool:
+        """End `sessions` so the thread can have their project's checkout (see `src/core/takeover.py`).
+
+        Nothing is ended, and the thread is told why, when one of them has a run
+        in progress or its uncommitted changes cannot be committed.
+        """
+        for session in sessions:
+            if any(run.get("session_id") == str(session.id) for run in self.active_runs.values()):
+                await self._send_message(
+                    channel_id,
+                    thread_ts,
+                    f"Not taking over: an agent is running in {self._where(session)}. Let it finish or `!cancel` it.",
+                )
+                return False
+        here = self._thread_link(channel_id, thread_ts) or "another thread"
+        ended = []
+        for session in sessions:
+            try:
+                commit = await self._git_workflow.park_changes(session, TAKEOVER_COMMIT_MESSAGE)
+            except (OSError, subprocess.CalledProcessError) as exc:
+                detail = exc.stderr.strip() if isinstance(exc, subprocess.CalledProcessError) and exc.stderr else exc
+                await self._send_message(
+                    channel_id,
+                    thread_ts,
+                    f"Not taking over: could not commit the uncommitted changes in {self._where(session)}: {detail}",
+                )
+                return False
+            self._session_manager.update_status(session.id, SessionStatus.ENDED)
+            LOGGER.info("Session %s ended by a takeover from thread %s", session.id, thread_ts)
+            note = TAKEN_OVER_NOTE.format(thread=here)
+            if commit:
+                note += f" Uncommitted changes were committed to `{session_branch_name(session.id)}` ({commit[:8]})."
+            await self._notify_thread(session.channel_id, session.thread_ts, note)
+            ended.append(self._where(session))
+        await self._send_message(
+            channel_id, thread_ts, f"Took over: ended the session in {', '.join(ended)}. Send your request here."
+        )
+        return True
+
+    def _apply_user_preferences(self, session: Session, project: Project, user_id: str) -> None:
+        """Start a new session with the `!prefs` of the user who opened its thread (see `src/core/preferences.py`)."""
+        preferences = self._session_manager.user_preferences(user_id)
+        self._session_manager.update_session_context(
+            session.id, {STARTED_BY_CONTEXT_KEY: user_id, PREFERENCES_CONTEXT_KEY: preferences.to_dict()}
+        )
+        if not preferences.agent or preferences.agent == session.active_agent_id:
+            return
+        try:
+            agent = self._config.get_agent(preferences.agent)
+            self._session_manager.set_active_agent(
+                session.id, agent.id, agent.type, project.model_for(agent), project=project
+            )
+        except (AgentNotFound, AgentNotAllowed):
+            LOGGER.info("Preferred agent %s of user %s is not available in %s", preferences.agent, user_id, project.id)
+            return
+        self._session_manager.set_agent_pinned(session.id, True)
+
+    def _start_diagnosis_session(self, session: Session, project: Project) -> None:
+        """Make a thread started during the project's incident read-only, with its cheapest agent."""
+        self._session_manager.update_session_context(
+            session.id, {ASK_MODE_CONTEXT_KEY: True, INCIDENT_CONTEXT_KEY: True}
+        )
+        agent = cheapest_agent(list(self._config.agents.values()), project)
+        if agent is None or agent.id == session.active_agent_id:
+            return
+        model = project.model_for(agent)
+        self._session_manager.set_active_agent(session.id, agent.id, agent.type, model, project=project)
+        self._session_manager.set_agent_pinned(session.id, True)
+
     async def _run_agent_interaction(
         self,
         session: Session,
@@ -237,34 +1157,57 @@ class Router:
         thread_ts: str,
         user_text: str,
         session_created: bool,
-        ) -> None:
+        needs_images: bool = False,
+        overrides: Optional[RunOverrides] = None,
+        intent: MessageIntent = MessageIntent.TASK,
+        priority: RunPriority = RunPriority.INTERACTIVE,
+    ) -> None:
         if session_created:
             model_display = f" `{session.active_model}`" if session.active_model else ""
-            await self._send_message(
-                channel_id,
-                thread_ts,
+            greeting = (
                 f"Starting session for `{project.id}` with `{session.active_agent_id}`{model_display}. "
-                "Send a message with your request, or use `!help` for common commands.",
+                "Send a message with your request, or use `!help` for common commands."
             )
-            try:
-                await self._git_workflow.setup_session_branch(session, project)
-            except GitHubError as exc:
-                await self._send_message(
-                    channel_id,
-                    thread_ts,
-                    f"Failed to prepare session branch: {exc}",
+            if session.session_context.get(INCIDENT_CONTEXT_KEY):
+                greeting = (
+                    f"Starting a diagnosis session for `{project.id}` with `{session.active_agent_id}`{model_display}. "
+                    f"`{project.id}` is in incident mode, so this thread is read-only; describe what you are seeing."
                 )
-            except subprocess.CalledProcessError as exc:
-                detail = (exc.stderr or exc.stdout or str(exc)).strip()
-                await self._send_message(
-                    channel_id,
-                    thread_ts,
-                    f"Failed to prepare session branch: {detail or 'git error'}",
+                await self._send_message(channel_id, thread_ts, greeting)
+                return
+            if in_ask_mode(session):
+                greeting = (
+                    f"Starting a question session for `{project.id}` with `{session.active_agent_id}`{model_display}. "
+                    f"Ask about the code; this channel never changes it. Use `#{project.channel_name}` for changes."
+                )
+                await self._send_message(channel_id, thread_ts, greeting)
+                return
+            if in_design_phase(session):
+                greeting = (
+                    f"Starting a design session for `{project.id}` with `{session.active_agent_id}`{model_display}. "
+                    "Nothing is committed until you send `!implement`; then the changes go to a pull request."
                 )
+            await self._send_message(channel_id, thread_ts, greeting)
+            if await self._preflight(session, project, channel_id, thread_ts):
+                await self._setup_session_branch(session, project, channel_id, thread_ts)
             return
 
+        if session.session_context.get(PREFLIGHT_BLOCKED_CONTEXT_KEY):
+            if not await self._preflight(session, project, channel_id, thread_ts):
+                return
+            if not await self._setup_session_branch(session, project, channel_id, thread_ts):
+                return
+
+        if not project.allows_agent(session.active_agent_id):
+            await self._reset_disallowed_agent(session, project, channel_id, thread_ts)
+        if project.agent_selection == AgentSelection.AUTO and not session.agent_pinned:
+            await self._auto_select_agent(session, project, channel_id, thread_ts, user_text, needs_images)
+
         try:
-            await self._agent_runner.run(session, project, channel_id, thread_ts, user_text)
+            async with self._run_slot(Job(priority, project.id, f"{channel_id}:{thread_ts}"), channel_id, thread_ts):
+                await self._agent_runner.run(
+                    session, project, channel_id, thread_ts, user_text, overrides=overrides, intent=intent
+                )
         except Exception as exc:
             LOGGER.exception("Unexpected error during agent interaction for session %s", session.id)
             await self._send_message(
@@ -281,19 +1224,44 @@ class Router:
         project: Project,
         channel_id: str,
         thread_ts: str,
+        attachments: Tuple[ChatAttachment, ...] = (),
+        user_id: Optional[str] = None,
     ) -> None:
         handler = self._command_handlers.get(spec.handler_id)
         if not handler:
             LOGGER.error("No handler registered for command %s (%s)", command.name, spec.handler_id)
             await self._send_message(channel_id, thread_ts, f"No handler found for `{command.name}`.")
             return
+        if in_ask_mode(session) and spec.name not in ASK_MODE_COMMANDS:
+            await self._send_message(
+                channel_id,
+                thread_ts,
+                f"`!{spec.name}` is not available in a question channel, which never changes the code. "
+                f"Use `#{project.channel_name}` for it.",
+            )
+            return
         context = CommandContext(
             session=session,
             project=project,
             channel=channel_id,
             thread_ts=thread_ts,
+            attachments=attachments,
+            user_id=user_id,
         )
-        await handler(command, context)
+        origin = _COMMAND_ORIGIN.set((project.id, f"{channel_id}:{thread_ts}"))
+        try:
+            with self._agent_runner.for_project(project):
+                await handler(command, context)
+        finally:
+            _COMMAND_ORIGIN.reset(origin)
+
+    def _extract_attachments(self, event: Dict[str, Any]) -> Tuple[ChatAttachment, ...]:
+        """Collect text files the chat adapter downloaded for this message."""
+        attachments = []
+        for item in event.get("downloaded_files") or []:
+            if isinstance(item, dict) and isinstance(item.get("content"), str):
+                attachments.append(ChatAttachment(name=str(item.get("name") or ""), content=item["content"]))
+        return tuple(attachments)
 
 
     def _build_review_prompt(self, pr_url: str, comments: list[PRComment]) -> str:
@@ -328,12 +1296,532 @@ class Router:
             self._session_locks[session_key] = lock
         return lock
 
+    @staticmethod
+    def _build_redactor(config: Config) -> Optional[SecretRedactor]:
+        """Scrubs secrets from everything posted to chat, unless `REMOTE_CODER_DISABLE_REDACTION` is set."""
+        return redactor_for(config, os.environ) if config.redact_secrets else None
+
+    def _redact(self, text: str) -> str:
+        return self._redactor.redact(text) if self._redactor else text
+
+    def _contains_secret(self, text: str) -> bool:
+        return bool(self._redactor) and self._redactor.contains_secret(text)
+
     async def _send_message(
-        self, channel: str, thread_ts: str, text: str
+        self, channel: str, thread_ts: Optional[str], text: str
     ) -> Optional[str]:
-        if not self._chat_adapter:
+        text = self._redact(text)
+        adapter = self._adapter_for(channel)
+        if not adapter:
             LOGGER.warning("Chat adapter not bound; dropping message: %s", text)
             return None
-        return await self._chat_adapter.send_message(
-            channel=channel, thread_ts=thread_ts, text=text
+        capabilities = capabilities_of(adapter)
+        if not capabilities.supports_threads:
+            thread_ts = None
+        # Long replies become several messages; the first one's ts is the one that gets edited or replied to
+        first_ts: Optional[str] = None
+        for index, part in enumerate(split_message(text, capabilities.max_message_chars)):
+            ts = await adapter.send_message(channel=channel, thread_ts=thread_ts, text=part)
+            if index == 0:
+                first_ts = ts
+        return first_ts
+
+    async def _reset_disallowed_agent(
+        self, session: Session, project: Project, channel_id: str, thread_ts: str
+    ) -> None:
+        """Move a session off an agent the project stopped allowing (e.g. after `!reload-projects`)."""
+        previous = session.active_agent_id
+        agent = self._config.get_agent(project.default_agent_id)
+        self._session_manager.set_active_agent(
+            session.id, agent.id, agent.type, project.model_for(agent), project=project
+        )
+        self._session_manager.set_agent_pinned(session.id, False)
+        LOGGER.info(
+            "Agent %s is no longer allowed in %s; switched session %s to %s", previous, project.id, session.id, agent.id
         )
+        await self._send_message(
+            channel_id,
+            thread_ts,
+            f"`{previous}` is no longer allowed in project `{project.id}`; switched to `{agent.id}`.",
+        )
+
+    async def _auto_select_agent(
+        self,
+        session: Session,
+        project: Project,
+        channel_id: str,
+        thread_ts: str,
+        user_text: str,
+        needs_images: bool,
+    ) -> None:
+        context = self._session_manager.get_context_for_agent(session.id)
+        requirements = TaskRequirements(
+            needs_images=needs_images,
+            estimated_tokens=estimate_tokens(f"{context}\n{user_text}"),
+            languages=await self._project_languages(project),
+        )
+        agents = [agent for agent in self._config.agents.values() if project.allows_agent(agent.id)]
+        choice = select_agent(agents, requirements, project.default_agent_id)
+        if not choice:
+            LOGGER.info("No agent satisfies %s; keeping %s", requirements, session.active_agent_id)
+            return
+        agent = choice.agent
+        if agent.id == session.active_agent_id:
+            return
+
+        self._session_manager.set_active_agent(
+            session.id, agent.id, agent.type, project.model_for(agent), project=project
+        )
+        LOGGER.info("Auto-selected agent %s for session %s (%s)", agent.id, session.id, choice.reason)
+        await self._send_message(
+            channel_id,
+            thread_ts,
+            f"Auto-selected `{agent.id}` for this request ({choice.reason}). Use `!use <agent>` to pin one.",
+        )
+
+    async def _project_languages(self, project: Project) -> frozenset[str]:
+        if project.languages:
+            return frozenset(project.languages)
+        if not self._config.resources.indexing:
+            return frozenset()
+        if project.id not in self._language_cache:
+            self._language_cache[project.id] = await detect_project_languages(project.path)
+        return self._language_cache[project.id]
+
+    async def _post_diff(self, channel: str, thread_ts: str, diff_text: str) -> None:
+        """Post a diff split per file: collapsed with expand buttons where supported, else one message per file."""
+        files = split_diff(self._redact(diff_text))
+        if not files:
+            return
+        adapter = self._adapter_for(channel)
+        capabilities = capabilities_of(adapter)
+        if adapter and capabilities.supports_buttons and hasattr(adapter, "send_diff"):
+            try:
+                await adapter.send_diff(channel, thread_ts, files)
+                return
+            except SlackError:
+                LOGGER.warning("Collapsed diff failed; falling back to per-file messages", exc_info=True)
+
+        await self._send_message(channel, thread_ts, format_diff_overview(files))
+        for file_diff in files[:MAX_FILE_MESSAGES]:
+            limit = min(MAX_FILE_MESSAGE_CHARS, capabilities.max_message_chars)
+            await self._send_message(channel, thread_ts, render_file_diff(file_diff, limit=limit))
+        if len(files) > MAX_FILE_MESSAGES:
+            await self._send_message(
+                channel,
+                thread_ts,
+                f"…and {len(files) - MAX_FILE_MESSAGES} more file(s); use `!patch` for the full diff.",
+            )
+
+    def _system_status(self) -> str:
+        lines = [f"Active agent runs: {len(self.active_runs)}"]
+        if self._chat_adapter and hasattr(self._chat_adapter, "connection_health"):
+            lines.append(format_connection_health(self._chat_adapter.connection_health()))
+        else:
+            lines.append("Chat adapter does not report connection health.")
+        lines.append(format_memory_usage(memory_usage(), self._config.resources))
+        lines.append(f"Warm agent processes: {len(self._warm_pool)}")
+        lines.append(format_cli_checks(self._cli_checker.results()))
+        lines.append(f"Run queue: {self._scheduler.describe()}")
+        lines.extend(
+            f"  #{position} {job.priority.name.lower()} run for `{job.project_id}`"
+            for position, job in enumerate(self._scheduler.waiting(), start=1)
+        )
+        lines.append(format_job_stats(JOBS))
+        lines.append(self._cleaner.stats.describe())
+        lines.append(self._session_metrics.describe())
+        lines.append(PRIVACY.describe())
+        return "\n".join(lines)
+
+    def status_snapshot(self, errors: Sequence[IntegrationError] = ()) -> Dict[str, Any]:
+        """What the status page shows (see `src/core/status_page.py`), with `errors` being each integration's last."""
+        adapters = []
+        for adapter in [self._chat_adapter, *self._channel_adapters.values()]:
+            if adapter is None:
+                continue
+            entry: Dict[str, Any] = {"name": type(adapter).__name__.removesuffix("Adapter"), "connected": None}
+            if hasattr(adapter, "connection_health"):
+                health = adapter.connection_health()
+                entry.update(connected=health.connected, last_event_at=health.last_event_at)
+            adapters.append(entry)
+
+        running, waiting = self._scheduler.running(), self._scheduler.waiting()
+        projects = {project.id: project for project in self._config.projects.values()}
+        runs = []
+        for run in self.active_runs.values():
+            session = self._session_manager.find_sessions(str(run.get("session_id") or ""))
+            runs.append(
+                {
+                    "project": session[0].project_id if session else None,
+                    "agent": run.get("agent_id"),
+                    "thread": session[0].thread_ts if session else None,
+                    "started_at": run.get("started_at") or self._started_at,
+                }
+            )
+        return {
+            "ok": bool(adapters) and all(adapter["connected"] is not False for adapter in adapters),
+            "version": installed_version() or "unknown (source checkout)",
+            "started_at": self._started_at,
+            "adapters": adapters,
+            "github_configured": self._github_manager.is_configured(),
+            "projects": [
+                {
+                    "id": project_id,
+                    "running": sum(job.project_id == project_id for job in running),
+                    "queued": sum(job.project_id == project_id for job in waiting),
+                    "incident": self._session_manager.incident_for(project_id) is not None,
+                }
+                for project_id in sorted(projects)
+            ],
+            "active_runs": sorted(runs, key=lambda run: run["started_at"]),
+            "cleanup": asdict(self._cleaner.stats),
+            "sessions": asdict(self._session_metrics),
+            "errors": [
+                {"integration": error.integration, "at": error.at, "message": self._redact(error.message)}
+                for error in errors
+            ],
+        }
+
+    def _queue_status(self, thread: str) -> str:
+        status = self._scheduler.describe()
+        position = self._scheduler.position(thread)
+        return f"{status}; this thread's run is #{position}" if position else status
+
+    async def check_agent_clis(self) -> None:
+        """Probe the CLI of every configured agent (run at startup); results show in `!status --system`."""
+        await self._cli_checker.check_all(self._config.agents.values())
+
+    async def recover_interrupted_runs(self) -> int:
+        """Clean up after the runs a crashed daemon left unfinished (run at startup); returns how many there were."""
+        orphans = self._journal.orphans()
+        for entry in orphans:
+            LOGGER.warning("Run %s of session %s did not finish before the daemon stopped", entry.run_id, entry.session_id)
+            try:
+                recovery = await recover_orphaned_run(entry, self._config.recovery)
+            except OSError as exc:
+                LOGGER.warning("Could not clean up after run %s: %s", entry.run_id, exc)
+                recovery = Recovery(entry)
+            note = recovery.describe()
+            try:
+                session = self._session_manager.get_session(UUID(entry.session_id))
+            except (SessionNotFound, ValueError):
+                session = None
+            if session is not None:
+                # Closes the request the run was working on, as `!cancel` does
+                self._session_manager.append_agent_message(session.id, note)
+                self._recovered_sessions.add(str(session.id))
+            try:
+                await self._send_message(entry.channel_id, entry.thread_ts, note)
+            except SlackError:
+                LOGGER.warning("Could not tell thread %s about its interrupted run", entry.thread_ts, exc_info=True)
+            self._journal.finish(entry.run_id)
+        return len(orphans)
+
+    async def announce_resumed_sessions(self) -> int:
+        """Tell the threads of recently active sessions that they carry on after the restart (run at startup).
+
+        Returns how many were told.
+        """
+        hours = self._config.resume_notice_hours
+        if not hours:
+            return 0
+        cutoff = datetime.now(timezone.utc) - timedelta(hours=hours)
+        resumed = [
+            session
+            for session in self._session_manager.list_active()
+            if session.updated_at >= cutoff
+            and str(session.id) not in self._recovered_sessions
+            and self._owns_thread(session.id, session.channel_id, session.thread_ts)
+        ]
+        for session in resumed:
+            await self._notify_thread(session.channel_id, session.thread_ts, RESTART_NOTE)
+        if resumed:
+            LOGGER.info("Resumed %d session(s) after the restart", len(resumed))
+        return len(resumed)
+
+    def _build_cleaner(self, config: Config) -> SessionCleaner:
+        hooks = [snapshot_hook(snapshots_dir(config.config_dir))]
+        if config.resources.transcript_dir:
+            hooks.append(transcript_hook(config.resources.transcript_dir))
+        return SessionCleaner(
+            self._session_manager, timedelta(hours=config.archive_after_hours), hooks, offload=self._offload_session
+        )
+
+    async def clean_up(self) -> CleanupPass:
+        """Upload archived sessions, archive stale ones, and remove leftover files (each cleanup interval)."""
+        offloaded = await self._cleaner.offload_archived()
+        cleanup = await asyncio.to_thread(self._cleaner.run)
+        return replace(cleanup, offloaded=offloaded)
+
+    async def _offload_session(self, session: Session) -> bool:
+        """Upload an archived session to the session archive, if one is set up; returns whether it was."""
+        archive = self._session_archive
+        if archive is None:
+            return False
+        try:
+            project = self._config.get_project(session.project_id)
+        except ProjectNotFound:
+            diff = None
+        else:
+            diff = await self._git_workflow.branch_diff(session, project)
+        try:
+            await asyncio.to_thread(archive.upload, collect_export(self._session_manager, session, diff))
+        except ArchiveError as exc:
+            LOGGER.warning("Could not upload archived session %s; keeping it for the next pass: %s", session.id, exc)
+            return False
+        return True
+
+    @staticmethod
+    def _open_search_index(config: Config) -> Optional[SearchIndex]:
+        """The search index, kept when transcripts are; None without them or when it cannot be opened."""
+        if not config.resources.transcript_dir:
+            return None
+        try:
+            return SearchIndex(config.config_dir / SEARCH_DB_FILE_NAME)
+        except SearchError as exc:
+            LOGGER.warning("Search is off: %s", exc)
+            return None
+
+    @staticmethod
+    def _open_analytics(config: Config) -> Optional[AnalyticsStore]:
+        try:
+            return AnalyticsStore(config.config_dir / ANALYTICS_DB_FILE_NAME)
+        except AnalyticsError as exc:
+            LOGGER.warning("Analytics are off: %s", exc)
+            return None
+
+    async def index_sessions(self) -> int:
+        """Index what the search index is missing from the transcripts (at startup); returns sessions indexed."""
+        if self._search_index is None:
+            return 0
+        sessions, _ = self._session_manager.export_sessions()
+        indexed = 0
+        for session in sessions:
+            indexed += await self._index_session(session, with_diff=False)
+        if indexed:
+            LOGGER.info("Added %d session(s) to the search index", indexed)
+        return indexed
+
+    async def _index_finished_run(self, event: RunFinished) -> None:
+        try:
+            session = self._session_manager.get_session(event.session_id)
+        except SessionNotFound:
+            return
+        await self._index_session(session, with_diff=True)
+
+    async def _index_session(self, session: Session, *, with_diff: bool) -> bool:
+        """Bring the session's entries in the search index up to date; returns whether anything was added."""
+        index = self._search_index
+        if index is None:
+            return False
+        diff = None
+        if with_diff:
+            try:
+                project = self._config.get_project(session.project_id)
+            except ProjectNotFound:
+                pass
+            else:
+                diff = await self._git_workflow.branch_diff(session, project)
+        try:
+            entries = await asyncio.to_thread(self._session_manager.transcript, session.id)
+            pr_refs = self._session_manager.list_pr_refs(session.id)
+            added = await asyncio.to_thread(index.update, session, entries, pr_refs, diff)
+        except (SessionNotFound, SearchError) as exc:
+            LOGGER.warning("Could not index session %s for search: %s", session.id, exc)
+            return False
+        return bool(added)
+
+    async def expire_idle_sessions(self, now: Optional[datetime] = None) -> int:
+        """End the sessions idle for `REMOTE_CODER_SESSION_IDLE_HOURS` and tell their threads; returns how many."""
+        hours = self._config.session_idle_hours
+        if not hours:
+            return 0
+        busy = {str(run.get("session_id")) for run in self.active_runs.values()}
+        expired = self._session_manager.expire_idle(timedelta(hours=hours), busy=busy, now=now)
+        if self._config.idle_notice:
+            note = EXPIRED_NOTE.format(idle=f"{hours} hour{'' if hours == 1 else 's'}")
+            for session in expired:
+                await self._notify_thread(session.channel_id, session.thread_ts, note)
+        return len(expired)
+
+    def _owns_thread(self, session_id: UUID, channel_id: str, thread_ts: str) -> bool:
+        try:
+            return self._session_manager.get_by_thread(channel_id, thread_ts).id == session_id
+        except SessionNotFound:
+            return False
+
+    async def check_agent_credentials(self) -> None:
+        """Tell the operators about agents without credentials (run at startup), with setup instructions."""
+        missing = [
+            check
+            for agent in self._config.agents.values()
+            if (check := check_agent_auth(agent)) is not None and check.state == AuthState.MISSING
+        ]
+        for check in missing:
+            LOGGER.warning("Agent %s has no credentials: %s", check.agent_id, describe_missing(check))
+        if missing:
+            lines = ["Some agents have no credentials yet and will fail until they are set up:"]
+            lines.extend(f"• {describe_missing(check)}" for check in missing)
+            await self._notify_operators("\n".join(lines))
+
+    async def announce_update(self, text: str) -> bool:
+        """Post a new-release notice to `REMOTE_CODER_UPDATE_CHANNEL`, or DM it to the operators without one."""
+        channel = self._config.update_channel
+        if not channel:
+            return await self._notify_operators(text)
+        try:
+            await self._send_message(channel, None, text)
+        except SlackError:
+            LOGGER.warning("Failed to post the update notice to %s", channel, exc_info=True)
+            return False
+        return True
+
+    async def _notify_operators(self, text: str) -> bool:
+        """DM everyone in `REMOTE_CODER_ALERT_USER_IDS`; returns False when nobody is configured."""
+        if not self._config.alert_user_ids:
+            return False
+        for user_id in self._config.alert_user_ids:
+            try:
+                await self._send_message(user_id, None, text)
+            except SlackError:
+                LOGGER.warning("Failed to DM operator %s", user_id, exc_info=True)
+        return True
+
+    async def run_bench(
+        self,
+        project: Project,
+        contenders: Sequence[Tuple[Agent, Optional[str]]],
+        cases: Sequence[BenchCase],
+        progress: Optional[ProgressFn] = None,
+    ) -> BenchReport:
+        """Benchmark agents on a project's `.cockpit/bench` suite outside any session (`remote-coder bench`)."""
+        origin = _COMMAND_ORIGIN.set((project.id, ""))
+        try:
+            with self._agent_runner.for_project(project):
+                return await run_bench(
+                    project, project.path, contenders, cases, self._run_background, progress=progress
+                )
+        finally:
+            _COMMAND_ORIGIN.reset(origin)
+
+    async def drain(self) -> None:
+        """Stop starting runs, give the running ones the grace period, and park the rest (run at shutdown).
+
+        See `src/core/shutdown.py`.
+        """
+        self._draining = True
+        grace_secs = self._config.shutdown_grace_secs
+        for run in running(self.active_runs) if grace_secs else []:
+            session = self._session_of(run)
+            if session is None:
+                continue
+            await self._notify_thread(
+                session.channel_id,
+                session.thread_ts,
+                f"Remote Coder is shutting down; `{run.get('agent_id') or 'the agent'}` has up to "
+                f"{format_duration(grace_secs)} to finish this run.",
+            )
+        drain = await drain_runs(self.active_runs, grace_secs, self._hurry_shutdown)
+        for run in drain.interrupted:
+            await self._park_interrupted_run(run)
+        # Webhook runs still waiting for a slot would only be turned away
+        for task in list(self._webhook_tasks):
+            task.cancel()
+
+    def skip_grace_period(self) -> None:
+        """Stop the remaining runs now instead of at the end of the grace period (a second Ctrl+C)."""
+        self._hurry_shutdown.set()
+
+    def _session_of(self, run: RunInfo) -> Optional[Session]:
+        try:
+            return self._session_manager.get_session(UUID(str(run.get("session_id"))))
+        except (SessionNotFound, ValueError):
+            return None
+
+    async def _park_interrupted_run(self, run: RunInfo) -> None:
+        """Keep what a run stopped at shutdown left in its checkout, and close its request in the thread."""
+        session = self._session_of(run)
+        if session is None:
+            return
+        agent_label = f"`{run.get('agent_id') or 'the agent'}`"
+        commit = None
+        if run.get("workdir"):
+            try:
+                commit = await self._git_workflow.park_changes(session, PARK_COMMIT_MESSAGE)
+            except (OSError, subprocess.CalledProcessError) as exc:
+                LOGGER.warning("Could not park the changes of session %s: %s", session.id, exc)
+        if commit:
+            where = f"commit `{commit[:7]}` on `{session_branch_name(session.id)}`"
+            history_note = f"(Stopped by a Remote Coder shutdown before finishing; its changes so far are in {where}.)"
+            note = (
+                f"Remote Coder shut down before {agent_label} finished, so the run was stopped. "
+                f"Its changes so far are parked in {where}: send a message once it is back to carry on from "
+                "them, or `!redo` to drop them and run the request again."
+            )
+        else:
+            history_note = "(Stopped by a Remote Coder shutdown before finishing.)"
+            note = (
+                f"Remote Coder shut down before {agent_label} finished, so the run was stopped. "
+                "Send your request again once it is back."
+            )
+        self._session_manager.append_agent_message(session.id, history_note)
+        await self._notify_thread(session.channel_id, session.thread_ts, note)
+
+    async def _notify_thread(self, channel_id: str, thread_ts: str, text: str) -> None:
+        try:
+            await self._send_message(channel_id, thread_ts, text)
+        except SlackError:
+            LOGGER.warning("Could not post a notice in thread %s", thread_ts, exc_info=True)
+
+    async def close(self) -> None:
+        """Stop the agent processes kept warm between turns and close the search and analytics databases."""
+        await self._warm_pool.close_all()
+        await self._session_manager.events.drain()
+        if self._search_index:
+            self._search_index.close()
+            self._search_index = None
+        if self._analytics:
+            self._analytics.close()
+            self._analytics = None
+
+    async def _upload_file(
+        self, channel: str, thread_ts: str, filename: str, content: str, comment: str
+    ) -> bool:
+        """Upload a file to the thread. Returns False if the chat adapter cannot upload files."""
+        adapter = self._adapter_for(channel)
+        if not adapter or not capabilities_of(adapter).supports_file_upload or not hasattr(adapter, "upload_file"):
+            return False
+        try:
+            await adapter.upload_file(channel, thread_ts, filename, self._redact(content), self._redact(comment))
+        except SlackError:
+            LOGGER.warning("File upload failed; falling back to an inline message", exc_info=True)
+            return False
+        return True
+
+    async def _send_confirmation(self, channel: str, thread_ts: str, text: str, key: str) -> Optional[str]:
+        """Post an agent's prompt with Approve and Deny buttons; None when the chat has no buttons."""
+        adapter = self._adapter_for(channel)
+        if not adapter or not capabilities_of(adapter).supports_buttons or not hasattr(adapter, "send_confirmation"):
+            return None
+        return await adapter.send_confirmation(channel, thread_ts, self._redact(text), key)
+
+    async def _send_run_summary(self, channel: str, thread_ts: str, text: str, steps: Sequence[NextStep]) -> None:
+        """Post a finished run's summary with next-step buttons; chats without buttons do not get one."""
+        adapter = self._adapter_for(channel)
+        if not adapter or not capabilities_of(adapter).supports_buttons or not hasattr(adapter, "send_run_summary"):
+            return
+        await adapter.send_run_summary(channel, thread_ts, self._redact(text), steps)
+
+    async def _update_message(self, channel: str, ts: str, text: str) -> bool:
+        """Edit a posted message. Returns False if the chat adapter cannot edit messages."""
+        adapter = self._adapter_for(channel)
+        if not adapter or not hasattr(adapter, "update_message"):
+            return False
+        await adapter.update_message(
+            channel, ts, clip_message(self._redact(text), capabilities_of(adapter).max_message_chars)
+        )
+        return True
+
+
+def _has_image_files(event: Dict[str, Any]) -> bool:
+    return any(str(info.get("mimetype") or "").startswith("image/") for info in event.get("files") or [])
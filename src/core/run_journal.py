@@ -0,0 +1,224 @@
+"""A journal of the agent runs in flight, for recovering from a crash.
+
+Every run writes `<config dir>/runs/<run id>.json` when it starts (its
+session and thread, agent, branch, workdir, the commit it started from, the
+daemon's pid, and the pids of the agent processes it starts) and deletes it
+when it ends, however it ends. An entry that is still there when the daemon
+starts again belongs to a run that never finished: the daemon was killed,
+crashed, or the host went down mid-run.
+
+At startup the router recovers those (`recover_orphaned_run`): agent processes that
+outlived the daemon are stopped, and the run's uncommitted changes are either
+kept in the checkout for the session to carry on from (`adopt`, the default)
+or stashed (`clean`, with `REMOTE_CODER_RECOVERY=clean`; `git stash list`
+shows them). The session's history gets a reply that closes the unanswered
+request, and the thread is told what happened. Entries written by a daemon
+that is still running (another one sharing the config dir) are left alone.
+"""
+
+from __future__ import annotations
+
+import json
+import logging
+import os
+import re
+import signal
+import time
+from dataclasses import asdict, dataclass, field
+from pathlib import Path
+from typing import Any, Dict, List, Optional
+
+from ..agent_adapters.windows import IS_WINDOWS, process_exists
+from .jobs import run_git
+
+LOGGER = logging.getLogger(__name__)
+
+RUN_JOURNAL_DIR_NAME = "runs"
+ADOPT = "adopt"
+CLEAN = "clean"
+RECOVERY_MODES = (ADOPT, CLEAN)
+STASH_MESSAGE = "remote-coder: interrupted run {run_id}"
+
+_UNSAFE = re.compile(r"[^A-Za-z0-9_.-]")
+
+
+def process_started(pid: int) -> Optional[str]:
+    """When `pid` started, in clock ticks since boot, so a reused pid is not mistaken for it; None if unknown."""
+    try:
+        stat = Path(f"/proc/{pid}/stat").read_text()
+    except OSError:
+        return None
+    # The command name in parentheses may contain spaces; the start time is the 20th field after it
+    fields = stat.rsplit(")", 1)[-1].split()
+    return fields[19] if len(fields) > 19 else None
+
+
+def process_alive(pid: int, started: Optional[str]) -> bool:
+    """Whether the process that was `pid` (started at `started`) still runs."""
+    if IS_WINDOWS:
+        return process_exists(pid)
+    try:
+        os.kill(pid, 0)
+    except ProcessLookupError:
+        return False
+    except PermissionError:
+        pass
+    if started is None:
+        return True
+    return process_started(pid) == started
+
+
+@dataclass
+class JournalEntry:
+    run_id: str
+    session_id: str
+    project_id: str
+    channel_id: str
+    thread_ts: str
+    agent_id: str
+    workdir: str
+    read_only: bool = False  # Ask mode, in a throwaway checkout
+    branch: Optional[str] = None
+    checkpoint: Optional[str] = None  # The commit the run started from
+    daemon_pid: int = 0
+    daemon_started: Optional[str] = None
+    # pid -> its `process_started` time
+    agent_pids: Dict[str, Optional[str]] = field(default_factory=dict)
+    started_at: float = 0.0
+
+    @classmethod
+    def from_dict(cls, data: Dict[str, Any]) -> "JournalEntry":
+        known = {name: data[name] for name in cls.__dataclass_fields__ if name in data}
+        return cls(**known)
+
+
+class RunJournal:
+    """Run entries on disk, one file per run in flight."""
+
+    def __init__(self, directory: Path) -> None:
+        self.directory = directory
+        self._pid = os.getpid()
+        self._started = process_started(self._pid)
+
+    def _path(self, run_id: str) -> Path:
+        return self.directory / f"{_UNSAFE.sub('_', run_id)}.json"
+
+    def _write(self, entry: JournalEntry) -> None:
+        path = self._path(entry.run_id)
+        tmp_path = path.with_name(f".{path.name}.tmp")
+        try:
+            self.directory.mkdir(parents=True, exist_ok=True)
+            fd = os.open(tmp_path, os.O_WRONLY | os.O_CREAT | os.O_TRUNC, 0o600)
+            with os.fdopen(fd, "w", encoding="utf-8") as fp:
+                json.dump(asdict(entry), fp, indent=2)
+            os.replace(tmp_path, path)
+        except OSError as exc:
+            LOGGER.warning("Could not write the journal entry of run %s: %s", entry.run_id, exc)
+
+    async def start(self, entry: JournalEntry) -> JournalEntry:
+        """Record a run that is starting; fills in the branch and this daemon's pid."""
+        if entry.branch is None and not entry.read_only and Path(entry.workdir).is_dir():
+            head = await run_git(Path(entry.workdir), "rev-parse", "--abbrev-ref", "HEAD", check=False)
+            name = head.stdout.strip()
+            entry.branch = name if head.returncode == 0 and name != "HEAD" else None
+        entry.daemon_pid = self._pid
+        entry.daemon_started = self._started
+        entry.started_at = entry.started_at or time.time()
+        self._write(entry)
+        return entry
+
+    def add_process(self, entry: JournalEntry, pid: int) -> None:
+        entry.agent_pids[str(pid)] = process_started(pid)
+        self._write(entry)
+
+    def finish(self, run_id: str) -> None:
+        try:
+            self._path(run_id).unlink()
+        except FileNotFoundError:
+            pass
+        except OSError as exc:
+            LOGGER.warning("Could not remove the journal entry of run %s: %s", run_id, exc)
+
+    def entries(self) -> List[JournalEntry]:
+        found = []
+        for path in sorted(self.directory.glob("*.json")):
+            try:
+                found.append(JournalEntry.from_dict(json.loads(path.read_text(encoding="utf-8"))))
+            except (OSError, ValueError, TypeError) as exc:
+                LOGGER.warning("Ignoring unreadable run journal entry %s: %s", path, exc)
+        return found
+
+    def orphans(self) -> List[JournalEntry]:
+        """Entries of runs whose daemon is gone."""
+        return [
+            entry
+            for entry in self.entries()
+            if not (entry.daemon_pid == self._pid and entry.daemon_started == self._started)
+            and not process_alive(entry.daemon_pid, entry.daemon_started)
+        ]
+
+
+@dataclass
+class Recovery:
+    entry: JournalEntry
+    stopped_pids: List[int] = field(default_factory=list)
+    changed_files: int = 0
+    stashed: bool = False
+
+    def describe(self) -> str:
+        entry = self.entry
+        lines = [
+            f"Remote Coder restarted while `{entry.agent_id}` was working on this thread, so that run did not finish."
+        ]
+        if self.stopped_pids:
+            lines.append(f"Stopped {len(self.stopped_pids)} leftover agent process(es).")
+        where = f" on `{entry.branch}`" if entry.branch else ""
+        if entry.read_only or not self.changed_files:
+            lines.append("It left no uncommitted changes behind. Send your request again to retry it.")
+        elif self.stashed:
+            lines.append(
+                f"Its {self.changed_files} uncommitted change(s){where} were stashed "
+                "(`git stash list` in the checkout). Send your request again to start over."
+            )
+        else:
+            lines.append(
+                f"Its {self.changed_files} uncommitted change(s){where} are still in the checkout; "
+                "send a message to carry on from them, or `!redo --same` to undo them and run the request again."
+            )
+        return " ".join(lines)
+
+
+async def recover_orphaned_run(entry: JournalEntry, mode: str = ADOPT) -> Recovery:
+    """Stop what the run left running and adopt or stash its changes (see the module docstring)."""
+    recovery = Recovery(entry)
+    for raw_pid, started in entry.agent_pids.items():
+        pid = int(raw_pid)
+        # Without a start time the pid may belong to another process by now
+        if started is None or not process_alive(pid, started):
+            continue
+        try:
+            os.kill(pid, signal.SIGTERM)
+        except (ProcessLookupError, PermissionError):
+            continue
+        recovery.stopped_pids.append(pid)
+    workdir = Path(entry.workdir)
+    if entry.read_only or not workdir.is_dir():
+        return recovery
+    status = await run_git(workdir, "status", "--porcelain", check=False)
+    if status.returncode != 0:
+        return recovery
+    recovery.changed_files = len([line for line in status.stdout.splitlines() if line.strip()])
+    if recovery.changed_files and mode == CLEAN:
+        stash = await run_git(
+            workdir,
+            "stash",
+            "push",
+            "--include-untracked",
+            "-m",
+            STASH_MESSAGE.format(run_id=entry.run_id),
+            check=False,
+        )
+        recovery.stashed = stash.returncode == 0
+        if not recovery.stashed:
+            LOGGER.warning("Could not stash the changes of run %s: %s", entry.run_id, stash.stderr.strip())
+    return recovery
@@ -0,0 +1,101 @@
+"""Per-project queue for agent runs on a shared checkout.
+
+Every session of a project works in the project's one checkout, so two
+threads running the agent at once would switch branches and edit files under
+each other. Runs therefore take a slot of their project first: at most
+`max_concurrent_runs` (default 1) hold one at a time, and the rest wait in
+arrival order. A waiting run's position counts the runs ahead of it, running
+or waiting, plus itself, so the second run of a busy project is `#2`.
+"""
+
+from __future__ import annotations
+
+import asyncio
+from contextlib import asynccontextmanager
+from dataclasses import dataclass, field
+from typing import AsyncIterator, Awaitable, Callable, Dict, List, Optional
+
+DEFAULT_MAX_CONCURRENT_RUNS = 1
+
+# Called with the run's position when it starts waiting and whenever it moves up; 0 once it runs
+PositionFn = Callable[[int], Awaitable[None]]
+
+
+@dataclass
+class _Waiter:
+    granted: bool = False
+    moved: asyncio.Event = field(default_factory=asyncio.Event)
+
+
+class ProjectRunQueue:
+    """Lets a limited number of runs per project use its checkout; the others wait their turn."""
+
+    def __init__(self) -> None:
+        self._running: Dict[str, int] = {}
+        self._limits: Dict[str, int] = {}
+        self._waiting: Dict[str, List[_Waiter]] = {}
+
+    def running(self, project_id: str) -> int:
+        return self._running.get(project_id, 0)
+
+    def waiting(self, project_id: str) -> int:
+        return len(self._waiting.get(project_id, []))
+
+    @asynccontextmanager
+    async def slot(self, project_id: str, limit: int, on_position: Optional[PositionFn] = None) -> AsyncIterator[None]:
+        """Hold one of the project's `limit` run slots, waiting in line for it when they are taken."""
+        self._limits[project_id] = limit
+        waiting = self._waiting.setdefault(project_id, [])
+        if not waiting and self.running(project_id) < limit:
+            self._running[project_id] = self.running(project_id) + 1
+        else:
+            await self._wait(project_id, on_position)
+        try:
+            yield
+        finally:
+            self._release(project_id)
+
+    async def _wait(self, project_id: str, on_position: Optional[PositionFn]) -> None:
+        waiting = self._waiting[project_id]
+        waiter = _Waiter()
+        waiting.append(waiter)
+        try:
+            position = self._position(project_id, waiter)
+            if on_position is not None:
+                await on_position(position)
+            while not waiter.granted:
+                await waiter.moved.wait()
+                waiter.moved.clear()
+                if waiter.granted or on_position is None:
+                    continue
+                moved_to = self._position(project_id, waiter)
+                if moved_to != position:
+                    position = moved_to
+                    await on_position(position)
+            if on_position is not None:
+                await on_position(0)
+        except BaseException:
+            # A cancelled run gives up its place, or the slot it was just handed
+            if waiter.granted:
+                self._release(project_id)
+            else:
+                waiting.remove(waiter)
+                self._wake(project_id)
+            raise
+
+    def _position(self, project_id: str, waiter: _Waiter) -> int:
+        return self.running(project_id) + self._waiting[project_id].index(waiter) + 1
+
+    def _release(self, project_id: str) -> None:
+        self._running[project_id] = self.running(project_id) - 1
+        waiting = self._waiting.get(project_id, [])
+        while waiting and self.running(project_id) < self._limits.get(project_id, DEFAULT_MAX_CONCURRENT_RUNS):
+            waiter = waiting.pop(0)
+            waiter.granted = True
+            self._running[project_id] += 1
+            waiter.moved.set()
+        self._wake(project_id)
+
+    def _wake(self, project_id: str) -> None:
+        for waiter in self._waiting.get(project_id, []):
+            waiter.moved.set()
@@ -0,0 +1,124 @@
+"""The summary posted when a run finishes, with buttons for what to do next.
+
+After each run the agent runner collects what it did from git: the files
+changed since the run started (committed or not, new files included), their
+insertions and deletions, and the branch, and adds the agent CLI's exit code
+and the wall time. Chats with buttons (Slack) get it as one short message
+with next steps: Open PR when the session has one, Run tests (`!test`)
+when the project has setup or verify commands, and Continue, which asks
+the agent to go on. A button posts its command in the thread as if it had
+been typed.
+Chats without buttons do not get the summary; the reply already says the
+essentials.
+"""
+
+from __future__ import annotations
+
+from dataclasses import dataclass, field
+from pathlib import Path
+from typing import Awaitable, Callable, List, Optional, Sequence
+
+from .compare import _git
+
+CONTINUE_PROMPT = "continue"
+LISTED_FILES = 5
+
+
+@dataclass(frozen=True)
+class NextStep:
+    """A button: `command` is posted in the thread when it is clicked; `url` buttons open a link instead."""
+
+    label: str
+    command: Optional[str] = None
+    url: Optional[str] = None
+
+
+@dataclass(frozen=True)
+class ChangeStats:
+    files: List[str] = field(default_factory=list)
+    insertions: int = 0
+    deletions: int = 0
+    branch: Optional[str] = None
+
+
+@dataclass(frozen=True)
+class RunSummary:
+    agent_id: str
+    success: bool
+    wall_secs: float
+    exit_code: Optional[int] = None  # None for agents without a CLI process
+    changes: Optional[ChangeStats] = None  # None outside git projects and in ask mode
+    pr_url: Optional[str] = None
+
+
+# (channel, thread_ts, text, next steps)
+SendRunSummaryFn = Callable[[str, str, str, Sequence[NextStep]], Awaitable[None]]
+
+
+def _count_lines(path: Path) -> int:
+    try:
+        with path.open("rb") as handle:
+            data = handle.read()
+    except OSError:
+        return 0
+    if b"\0" in data[:8000]:
+        return 0  # Binary files count as zero lines, as in `git diff --numstat`
+    return data.count(b"\n") + (1 if data and not data.endswith(b"\n") else 0)
+
+
+async def collect_change_stats(repo_path: Path, since: Optional[str]) -> Optional[ChangeStats]:
+    """What changed in `repo_path` since commit `since`, or None when it is not a git repository."""
+    if not repo_path.is_dir():
+        return None
+    branch = await _git(repo_path, "rev-parse", "--abbrev-ref", "HEAD", check=False)
+    if branch.returncode != 0:
+        return None
+    numstat = await _git(repo_path, "diff", "--numstat", "--no-renames", *([since] if since else []), check=False)
+    files: List[str] = []
+    insertions = deletions = 0
+    for line in numstat.stdout.splitlines():
+        parts = line.split("\t", 2)
+        if len(parts) != 3:
+            continue
+        added, removed, path = parts
+        files.append(path)
+        insertions += int(added) if added.isdigit() else 0
+        deletions += int(removed) if removed.isdigit() else 0
+    untracked = await _git(repo_path, "ls-files", "--others", "--exclude-standard", check=False)
+    for path in untracked.stdout.splitlines():
+        if path and path not in files:
+            files.append(path)
+            insertions += _count_lines(repo_path / path)
+    name = branch.stdout.strip()
+    return ChangeStats(
+        files=files, insertions=insertions, deletions=deletions, branch=None if name == "HEAD" else name
+    )
+
+
+def format_run_summary(summary: RunSummary) -> str:
+    outcome = "Run finished" if summary.success else "Run failed"
+    exit_note = f", exit code {summary.exit_code}" if summary.exit_code is not None else ""
+    lines = [f"*{outcome}*: `{summary.agent_id}`, {summary.wall_secs:.0f}s{exit_note}"]
+    changes = summary.changes
+    if changes is not None:
+        if changes.files:
+            shown = ", ".join(f"`{path}`" for path in changes.files[:LISTED_FILES])
+            more = f" and {len(changes.files) - LISTED_FILES} more" if len(changes.files) > LISTED_FILES else ""
+            lines.append(
+                f"{len(changes.files)} file(s) changed, +{changes.insertions} -{changes.deletions}: {shown}{more}"
+            )
+        else:
+            lines.append("No files changed")
+        if changes.branch:
+            lines.append(f"Branch: `{changes.branch}`")
+    return "\n".join(lines)
+
+
+def next_steps(summary: RunSummary, *, has_checks: bool) -> List[NextStep]:
+    steps = []
+    if summary.pr_url:
+        steps.append(NextStep("Open PR", url=summary.pr_url))
+    if has_checks and summary.changes is not None:
+        steps.append(NextStep("Run tests", command="!test"))
+    steps.append(NextStep("Continue", command=CONTINUE_PROMPT))
+    return steps
@@ -0,0 +1,216 @@
+"""Sandboxed execution of untrusted shell commands.
+
+Repo-provided setup/verify/teardown commands are as untrusted as
+agent-generated code, so they run through the same layer as the shell tool
+of API agents. Modes (see `SandboxMode`):
+
+- `process`: own process group (killed as a whole on timeout), stdin closed,
+  an environment stripped down to basics plus an explicit allowlist (so
+  tokens such as SLACK_BOT_TOKEN or GITHUB_TOKEN never reach the command),
+  and optional CPU/memory rlimits.
+- `container`: `docker run` (or podman) with the working directory mounted
+  at /workspace, no network unless enabled, and memory/pid limits. With
+  `devcontainer`, the image is built from the repository's devcontainer.json
+  first (see `devcontainer.py`).
+- `bubblewrap`: like `process`, inside `bwrap` namespaces that show only the
+  system directories (read-only) and the working directory, with no network
+  unless enabled (see `agent_adapters/bubblewrap.py`).
+- `none`: a plain subprocess with the daemon's environment.
+
+Outside container mode, a project's `env` (nix, mise, or asdf) starts each
+command with the toolchain the repository pins (see `agent_adapters/dev_env.py`).
+
+Output is captured per command, keeping the last `MAX_CAPTURE_BYTES`.
+Commands run as jobs (see `jobs`), so they count against the daemon's job
+slots and are killed when the run awaiting them is cancelled.
+"""
+
+from __future__ import annotations
+
+import asyncio
+import logging
+import os
+import uuid
+from dataclasses import dataclass
+from pathlib import Path
+from typing import Callable, Dict, List, Optional
+
+from ..agent_adapters.bubblewrap import bubblewrap_argv
+from ..agent_adapters.dev_env import DevEnvUnavailable, check_dev_env_sandbox, dev_env_command
+from ..agent_adapters.windows import IS_WINDOWS
+from .devcontainer import DevcontainerError, resolve_devcontainer
+from .jobs import JOBS, JobRunner
+from .models import DevEnvironment, SandboxConfig, SandboxMode
+
+LOGGER = logging.getLogger(__name__)
+
+MAX_CAPTURE_BYTES = 1_000_000
+CONTAINER_WORKDIR = "/workspace"
+CONTAINER_PIDS_LIMIT = 512
+# Exit code reported when the container could not be started, as docker run does
+CONTAINER_ERROR_EXIT_CODE = 125
+# Exit code reported when the development shell's tool is missing, as a shell does for unknown commands
+COMMAND_NOT_FOUND_EXIT_CODE = 127
+# Variables commands always get in process mode, when the daemon has them
+BASE_ENV_VARS = ("PATH", "HOME", "USER", "LOGNAME", "SHELL", "LANG", "LC_ALL", "TERM", "TMPDIR", "TZ")
+
+
+@dataclass
+class SandboxResult:
+    command: str
+    returncode: Optional[int]
+    output: str
+    timed_out: bool = False
+    truncated: bool = False
+
+    @property
+    def passed(self) -> bool:
+        return not self.timed_out and self.returncode == 0
+
+
+def sandbox_env(config: SandboxConfig, environ: Optional[Dict[str, str]] = None) -> Dict[str, str]:
+    """Environment for a process-mode command: base variables plus the configured allowlist."""
+    environ = os.environ if environ is None else environ
+    names = [*BASE_ENV_VARS, *config.env_passthrough]
+    return {name: environ[name] for name in names if name in environ}
+
+
+def container_argv(config: SandboxConfig, command: str, cwd: Path, name: str) -> List[str]:
+    """Build the `docker run` invocation for a container-mode command."""
+    if not config.image:
+        raise ValueError("Container sandbox requires an image")
+    argv = [
+        config.runtime,
+        "run",
+        "--rm",
+        "--name",
+        name,
+        "--volume",
+        f"{cwd.resolve()}:{CONTAINER_WORKDIR}",
+        "--workdir",
+        CONTAINER_WORKDIR,
+        "--pids-limit",
+        str(CONTAINER_PIDS_LIMIT),
+    ]
+    if hasattr(os, "getuid"):
+        # Files the command creates stay owned by the daemon user
+        argv.extend(["--user", f"{os.getuid()}:{os.getgid()}"])
+    if not config.network:
+        argv.extend(["--network", "none"])
+    if config.memory_mb:
+        argv.extend(["--memory", f"{config.memory_mb}m"])
+    for var in config.env_passthrough:
+        # `-e NAME` copies the value from the CLI's environment without putting it in argv
+        argv.extend(["--env", var])
+    for name, value in config.env.items():
+        argv.extend(["--env", f"{name}={value}"])
+    argv.extend([config.image, "sh", "-c", command])
+    return argv
+
+
+def _resource_limits(config: SandboxConfig) -> Optional[Callable[[], None]]:
+    if not (config.cpu_secs or config.memory_mb):
+        return None
+    if IS_WINDOWS:
+        LOGGER.warning("cpu_secs and memory_mb are not supported on Windows; running the command without them")
+        return None
+    import resource
+
+    def _apply() -> None:
+        if config.cpu_secs:
+            resource.setrlimit(resource.RLIMIT_CPU, (config.cpu_secs, config.cpu_secs))
+        if config.memory_mb:
+            limit = config.memory_mb * 1024 * 1024
+            resource.setrlimit(resource.RLIMIT_AS, (limit, limit))
+
+    return _apply
+
+
+class Sandbox:
+    """Runs shell commands according to a `SandboxConfig`."""
+
+    def __init__(
+        self,
+        config: Optional[SandboxConfig] = None,
+        dev_env: Optional[DevEnvironment] = None,
+        jobs: Optional[JobRunner] = None,
+    ) -> None:
+        self.config = config or SandboxConfig()
+        self.dev_env = dev_env
+        self._jobs = jobs or JOBS
+
+    async def run(self, command: str, cwd: Path, timeout_secs: float, kind: str = "command") -> SandboxResult:
+        """Run `command` as a `kind` job (`verify`, `shell`, ...) that is killed after `timeout_secs`."""
+        mode = self.config.mode
+        LOGGER.info("Running %s-sandboxed command in %s: %s", mode.value, cwd, command)
+        container_name: Optional[str] = None
+        if mode == SandboxMode.CONTAINER:
+            config = self.config
+            if config.devcontainer:
+                try:
+                    config = await resolve_devcontainer(config, cwd)
+                except DevcontainerError as exc:
+                    return SandboxResult(command=command, returncode=CONTAINER_ERROR_EXIT_CODE, output=str(exc))
+            container_name = f"remote-coder-{uuid.uuid4().hex[:12]}"
+            name = container_name
+
+            async def kill_container(process: asyncio.subprocess.Process) -> None:
+                killer = await asyncio.create_subprocess_exec(
+                    self.config.runtime,
+                    "kill",
+                    name,
+                    stdout=asyncio.subprocess.DEVNULL,
+                    stderr=asyncio.subprocess.DEVNULL,
+                )
+                await killer.wait()
+
+            result = await self._jobs.run(
+                kind,
+                container_argv(config, command, cwd, container_name),
+                timeout_secs=timeout_secs,
+                merge_stderr=True,
+                own_process_group=False,
+                kill=kill_container,
+                capture_bytes=MAX_CAPTURE_BYTES,
+            )
+        elif mode == SandboxMode.BUBBLEWRAP or self.dev_env is not None:
+            argv = ["sh", "-c", command]
+            try:
+                check_dev_env_sandbox(self.dev_env, self.config)
+                if mode == SandboxMode.BUBBLEWRAP:
+                    argv = bubblewrap_argv(self.config, argv, cwd)
+                argv = dev_env_command(argv, self.dev_env)
+            except DevEnvUnavailable as exc:
+                return SandboxResult(command=command, returncode=COMMAND_NOT_FOUND_EXIT_CODE, output=str(exc))
+            # Killing the process group takes bwrap's namespace, or what nix develop or mise exec started, with it
+            restricted = mode != SandboxMode.NONE
+            result = await self._jobs.run(
+                kind,
+                argv,
+                cwd=cwd,
+                timeout_secs=timeout_secs,
+                env=sandbox_env(self.config) if restricted else None,
+                merge_stderr=True,
+                preexec_fn=_resource_limits(self.config) if restricted else None,
+                capture_bytes=MAX_CAPTURE_BYTES,
+            )
+        else:
+            restricted = mode == SandboxMode.PROCESS
+            result = await self._jobs.run(
+                kind,
+                command,
+                cwd=cwd,
+                timeout_secs=timeout_secs,
+                env=sandbox_env(self.config) if restricted else None,
+                merge_stderr=True,
+                preexec_fn=_resource_limits(self.config) if restricted else None,
+                capture_bytes=MAX_CAPTURE_BYTES,
+            )
+
+        return SandboxResult(
+            command=command,
+            returncode=result.returncode,
+            output=result.stdout,
+            timed_out=result.timed_out,
+            truncated=result.truncated,
+        )
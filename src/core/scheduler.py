@@ -0,0 +1,213 @@
+"""Daemon-wide scheduler for agent runs.
+
+The per-project queue (see `src/core/run_queue.py`) keeps runs from sharing a
+checkout; this one caps how many agents run at once across all projects
+(`REMOTE_CODER_MAX_RUNS`, default 4). When every slot is taken, runs wait by
+priority and then in arrival order: runs for a message in a thread come
+first, then runs triggered from outside chat (webhooks), then background work
+(`!batch`, `!compare`, `!bench`, `!replay`). A run that arrives with a higher
+priority moves ahead of the waiting ones. A waiting run's position counts the
+waiting runs ahead of it, plus itself.
+
+Background and webhook runs are preemptible (see `run_preemptible`): when a
+run for a chat message would have to wait because they hold every slot, or
+the slot of its project, the lowest-priority one that started last is
+stopped, the chat run takes its slot, and the stopped run starts over when
+it is its turn again. What it did before being stopped stays where it was
+(the session's checkout, a `!batch` task's worktree), and background runs
+are told so when they resume (`RESUMED_NOTE`). Set
+`REMOTE_CODER_DISABLE_PREEMPTION=1` to let started runs finish instead.
+"""
+
+from __future__ import annotations
+
+import asyncio
+from contextlib import asynccontextmanager
+from dataclasses import dataclass, field
+from enum import IntEnum
+from typing import AsyncIterator, Awaitable, Callable, List, Optional, TypeVar
+
+from .run_queue import PositionFn
+
+DEFAULT_MAX_RUNS = 4
+RESUMED_NOTE = (
+    "This task was paused partway through to make room for another run and is resuming now. "
+    "The changes already in the working directory are your own from before the pause; "
+    "check them and carry on from there instead of starting over."
+)
+
+T = TypeVar("T")
+# Called with the number of times the run has been stopped so far, before it starts over
+PreemptedFn = Callable[[int], Awaitable[None]]
+
+
+class RunPriority(IntEnum):
+    """Lower values run first."""
+
+    INTERACTIVE = 0
+    SCHEDULED = 1
+    BATCH = 2
+
+
+@dataclass(frozen=True)
+class Job:
+    priority: RunPriority
+    project_id: str
+    thread: str = ""  # `<channel>:<thread_ts>`, empty for runs outside a thread
+
+
+@dataclass
+class _Waiter:
+    job: Job
+    granted: bool = False
+    moved: asyncio.Event = field(default_factory=asyncio.Event)
+
+
+@dataclass(eq=False)
+class _Preemptible:
+    job: Job
+    task: asyncio.Future
+    preempted: bool = False
+
+
+class RunScheduler:
+    """Lets up to `limit` agent runs go at once; the others wait in priority order."""
+
+    def __init__(self, limit: int = DEFAULT_MAX_RUNS, *, preemption: bool = True) -> None:
+        self.limit = limit
+        self.preemption = preemption
+        self._running: List[Job] = []
+        self._waiting: List[_Waiter] = []
+        self._preemptible: List[_Preemptible] = []
+
+    def running(self) -> List[Job]:
+        return list(self._running)
+
+    def waiting(self) -> List[Job]:
+        return [waiter.job for waiter in self._waiting]
+
+    def position(self, thread: str) -> Optional[int]:
+        """Where the first waiting run of `thread` is in line, if it has one."""
+        for index, waiter in enumerate(self._waiting):
+            if waiter.job.thread == thread:
+                return index + 1
+        return None
+
+    def set_limit(self, limit: int) -> None:
+        """Apply a new cap (on config reload); extra running jobs finish, while free slots start waiting ones."""
+        self.limit = limit
+        self._grant()
+
+    @asynccontextmanager
+    async def slot(self, job: Job, on_position: Optional[PositionFn] = None) -> AsyncIterator[None]:
+        """Hold one of the daemon's run slots, waiting in line for it when they are taken."""
+        if not self._waiting and len(self._running) < self.limit:
+            self._running.append(job)
+        else:
+            await self._wait(job, on_position)
+        try:
+            yield
+        finally:
+            self._release(job)
+
+    async def run_preemptible(
+        self, job: Job, work: Callable[[int], Awaitable[T]], on_preempted: Optional[PreemptedFn] = None
+    ) -> T:
+        """Run `work(attempt)`, which takes its slots as `job`, starting it over whenever `preempt` stops it.
+
+        `attempt` counts the earlier, stopped attempts. Cancelling the caller, or the work itself any
+        other way (`!cancel`), still cancels it for good.
+        """
+        attempt = 0
+        while True:
+            entry = _Preemptible(job, asyncio.ensure_future(work(attempt)))
+            self._preemptible.append(entry)
+            try:
+                return await entry.task
+            except asyncio.CancelledError:
+                if not entry.preempted:
+                    raise
+            finally:
+                self._preemptible.remove(entry)
+            attempt += 1
+            if on_preempted is not None:
+                await on_preempted(attempt)
+
+    def preempt(self, job: Job, *, project_id: Optional[str] = None) -> Optional[Job]:
+        """Stop a started preemptible run with a lower priority than `job`, of `project_id` when given.
+
+        Picks the lowest priority and, among those, the run that started last; returns its job, or
+        None when there is none to stop.
+        """
+        if not self.preemption:
+            return None
+        candidates = [
+            entry
+            for entry in self._preemptible
+            if not entry.preempted
+            and entry.job.priority > job.priority
+            and entry.job in self._running
+            and (project_id is None or entry.job.project_id == project_id)
+        ]
+        if not candidates:
+            return None
+        victim = max(candidates, key=lambda entry: (entry.job.priority, self._preemptible.index(entry)))
+        victim.preempted = True
+        victim.task.cancel()
+        return victim.job
+
+    async def _wait(self, job: Job, on_position: Optional[PositionFn]) -> None:
+        waiter = _Waiter(job)
+        # Ahead of every waiter with a lower priority, behind the ones with the same or a higher one
+        index = next(
+            (index for index, other in enumerate(self._waiting) if other.job.priority > job.priority),
+            len(self._waiting),
+        )
+        self._waiting.insert(index, waiter)
+        if job.priority == RunPriority.INTERACTIVE and len(self._running) >= self.limit:
+            # The stopped run's slot goes to the highest-priority waiter, which is this one or one ahead of it
+            self.preempt(job)
+        self._wake()
+        try:
+            position = self._waiting.index(waiter) + 1
+            if on_position is not None:
+                await on_position(position)
+            while not waiter.granted:
+                await waiter.moved.wait()
+                waiter.moved.clear()
+                if waiter.granted or on_position is None:
+                    continue
+                moved_to = self._waiting.index(waiter) + 1
+                if moved_to != position:
+                    position = moved_to
+                    await on_position(position)
+            if on_position is not None:
+                await on_position(0)
+        except BaseException:
+            # A cancelled run gives up its place, or the slot it was just handed
+            if waiter.granted:
+                self._release(job)
+            else:
+                self._waiting.remove(waiter)
+                self._wake()
+            raise
+
+    def _release(self, job: Job) -> None:
+        self._running.remove(job)
+        self._grant()
+
+    def _grant(self) -> None:
+        while self._waiting and len(self._running) < self.limit:
+            waiter = self._waiting.pop(0)
+            waiter.granted = True
+            self._running.append(waiter.job)
+            waiter.moved.set()
+        self._wake()
+
+    def _wake(self) -> None:
+        for waiter in self._waiting:
+            waiter.moved.set()
+
+    def describe(self) -> str:
+        """One line with the queue depth, e.g. for `!status`."""
+        return f"{len(self._running)} of {self.limit} run slots in use, {len(self._waiting)} waiting"
@@ -0,0 +1,232 @@
+"""Full-text search over session transcripts and diffs (`!search`).
+
+The index is an SQLite FTS5 database at `<config dir>/search.db`, kept next
+to the transcripts and off whenever they are (`REMOTE_CODER_DISABLE_TRANSCRIPTS`).
+It holds each session's prompts and replies, the files its runs edited, and
+the latest diff of its branch, plus what a result needs to point back at
+the session: its project, thread, first request, and PRs. Sessions are
+indexed after each run (from `RunFinished` on the event bus, see
+`src/core/events.py`) and, at startup, any transcript the index has not seen
+yet. Entries stay in the index after cleanup uploads a session and removes
+it from the store, so old threads can still be found (and fetched with
+`!archived`).
+
+Queries match every word given (`migration rollback`), with `"..."` for a
+phrase and a trailing `*` for a prefix; results are ranked by BM25, best
+session first. `!search` only looks in the project of the channel it is used
+in, so snippets of private channels do not show up elsewhere, unless asked
+for every project with `--all`.
+"""
+
+from __future__ import annotations
+
+import json
+import logging
+import os
+import re
+import sqlite3
+from dataclasses import dataclass, field
+from datetime import datetime, timezone
+from pathlib import Path
+from threading import Lock
+from typing import List, Optional, Sequence, Tuple
+from uuid import UUID
+
+from .errors import SearchError
+from .models import PullRequestRef, Session
+from .transcripts import TranscriptEntry, TranscriptKind
+
+LOGGER = logging.getLogger(__name__)
+
+SEARCH_DB_FILE_NAME = "search.db"
+SCHEMA_VERSION = 1
+DEFAULT_SEARCH_LIMIT = 10
+# Characters of a branch diff indexed; the rest is left out
+MAX_INDEXED_DIFF_CHARS = 200_000
+TITLE_CHARS = 80
+
+# Sources of indexed text
+PROMPT = "prompt"
+REPLY = "reply"
+FILES = "files"
+DIFF = "diff"
+
+_SCHEMA = """
+CREATE VIRTUAL TABLE IF NOT EXISTS documents USING fts5(
+    session_id UNINDEXED,
+    source UNINDEXED,
+    content,
+    tokenize = 'porter unicode61'
+);
+CREATE TABLE IF NOT EXISTS sessions (
+    session_id TEXT PRIMARY KEY,
+    project_id TEXT NOT NULL,
+    channel_id TEXT NOT NULL,
+    thread_ts TEXT NOT NULL,
+    title TEXT NOT NULL,
+    prs TEXT NOT NULL,
+    entries INTEGER NOT NULL,
+    updated_at TEXT NOT NULL
+);
+"""
+
+_TOKEN = re.compile(r'"[^"]*"|\S+')
+
+
+@dataclass(frozen=True)
+class SearchHit:
+    session_id: str
+    project_id: str
+    channel_id: str
+    thread_ts: str
+    title: str  # The session's first request, shortened
+    snippet: str  # The best match, with the matched words in `*bold*`
+    updated_at: datetime
+    prs: List[Tuple[int, str]] = field(default_factory=list)  # (number, url)
+
+
+def fts_query(text: str) -> str:
+    """`text` as an FTS5 query matching every word, so user input never hits FTS5's own syntax."""
+    terms = []
+    for token in _TOKEN.findall(text):
+        prefix = token.endswith("*") and not token.startswith('"')
+        words = token.strip('"').rstrip("*") if prefix else token.strip('"')
+        if not words.strip():
+            continue
+        quoted = '"' + words.replace('"', '""') + '"'
+        terms.append(quoted + "*" if prefix else quoted)
+    return " ".join(terms)
+
+
+class SearchIndex:
+    """The FTS5 index; blocking, so call it off the event loop."""
+
+    def __init__(self, path: Path) -> None:
+        self.path = path
+        self._lock = Lock()
+        try:
+            path.parent.mkdir(parents=True, exist_ok=True)
+            self._db = sqlite3.connect(str(path), check_same_thread=False, isolation_level=None)
+            self._db.execute("PRAGMA journal_mode=WAL")
+            version = self._db.execute("PRAGMA user_version").fetchone()[0]
+            if version > SCHEMA_VERSION:
+                raise SearchError(f"{path} was written by a newer Remote Coder (schema {version})")
+            self._db.executescript(_SCHEMA)
+            self._db.execute(f"PRAGMA user_version={SCHEMA_VERSION}")
+            # Holds everything in the transcripts; keep it as private as they are
+            os.chmod(path, 0o600)
+        except (OSError, sqlite3.Error) as exc:
+            raise SearchError(f"Failed to open {path}: {exc}") from exc
+
+    def indexed_entries(self, session_id: UUID) -> int:
+        """How many of the session's transcript entries are in the index."""
+        with self._lock:
+            row = self._db.execute("SELECT entries FROM sessions WHERE session_id = ?", (str(session_id),)).fetchone()
+        return row[0] if row else 0
+
+    def update(
+        self,
+        session: Session,
+        entries: Sequence[TranscriptEntry],
+        pr_refs: Sequence[PullRequestRef] = (),
+        diff: Optional[str] = None,
+    ) -> int:
+        """Add the session's transcript entries past those indexed, and its diff when given; returns entries added.
+
+        `entries` is the whole transcript, oldest first, as `SessionManager.transcript` returns it.
+        """
+        session_id = str(session.id)
+        with self._lock:
+            row = self._db.execute("SELECT entries, title FROM sessions WHERE session_id = ?", (session_id,)).fetchone()
+            done, title = row if row else (0, "")
+            new = list(entries[done:])
+            rows = []
+            for entry in new:
+                if entry.kind == TranscriptKind.RUN:
+                    if entry.run and entry.run.files:
+                        rows.append((session_id, FILES, " ".join(entry.run.files)))
+                elif entry.content.strip():
+                    rows.append((session_id, PROMPT if entry.kind == TranscriptKind.PROMPT else REPLY, entry.content))
+                    if not title and entry.kind == TranscriptKind.PROMPT:
+                        title = " ".join(entry.content.split())[:TITLE_CHARS]
+            prs = json.dumps([[ref.number, ref.url] for ref in pr_refs])
+            try:
+                with self._db:
+                    self._db.execute("BEGIN")
+                    self._db.executemany("INSERT INTO documents (session_id, source, content) VALUES (?, ?, ?)", rows)
+                    if diff is not None:
+                        self._db.execute(
+                            "DELETE FROM documents WHERE session_id = ? AND source = ?", (session_id, DIFF)
+                        )
+                        if diff.strip():
+                            self._db.execute(
+                                "INSERT INTO documents (session_id, source, content) VALUES (?, ?, ?)",
+                                (session_id, DIFF, diff[:MAX_INDEXED_DIFF_CHARS]),
+                            )
+                    self._db.execute(
+                        "INSERT OR REPLACE INTO sessions "
+                        "(session_id, project_id, channel_id, thread_ts, title, prs, entries, updated_at) "
+                        "VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+                        (
+                            session_id,
+                            session.project_id,
+                            session.channel_id,
+                            session.thread_ts,
+                            title,
+                            prs,
+                            done + len(new),
+                            session.updated_at.isoformat(),
+                        ),
+                    )
+            except sqlite3.Error as exc:
+                raise SearchError(f"Failed to write {self.path}: {exc}") from exc
+        return len(new)
+
+    def search(
+        self, text: str, limit: int = DEFAULT_SEARCH_LIMIT, project_id: Optional[str] = None
+    ) -> List[SearchHit]:
+        """The sessions matching `text`, best first, in `project_id` (None: every project).
+
+        Raises SearchError for an empty query.
+        """
+        query = fts_query(text)
+        if not query:
+            raise SearchError("Nothing to search for")
+        in_project = "" if project_id is None else "AND s.project_id = ? "
+        params = (query,) + (() if project_id is None else (project_id,)) + (limit * 20,)
+        with self._lock:
+            try:
+                rows = self._db.execute(
+                    "SELECT d.session_id, snippet(documents, 2, '*', '*', '…', 16), s.project_id, s.channel_id, "
+                    "s.thread_ts, s.title, s.prs, s.updated_at "
+                    "FROM documents AS d JOIN sessions AS s ON s.session_id = d.session_id "
+                    f"WHERE documents MATCH ? {in_project}ORDER BY bm25(documents) LIMIT ?",
+                    params,
+                ).fetchall()
+            except sqlite3.Error as exc:
+                raise SearchError(f"Search failed: {exc}") from exc
+        hits: List[SearchHit] = []
+        seen = set()
+        for session_id, snippet, project_id, channel_id, thread_ts, title, prs, updated_at in rows:
+            if session_id in seen:
+                continue
+            seen.add(session_id)
+            hits.append(
+                SearchHit(
+                    session_id=session_id,
+                    project_id=project_id,
+                    channel_id=channel_id,
+                    thread_ts=thread_ts,
+                    title=title,
+                    snippet=" ".join(snippet.split()),
+                    updated_at=datetime.fromisoformat(updated_at).astimezone(timezone.utc),
+                    prs=[(int(number), str(url)) for number, url in json.loads(prs)],
+                )
+            )
+            if len(hits) >= limit:
+                break
+        return hits
+
+    def close(self) -> None:
+        with self._lock:
+            self._db.close()
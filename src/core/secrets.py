@@ -0,0 +1,81 @@
+"""Agent credentials stored by `remote-coder auth`.
+
+Tokens live in `<config dir>/secrets.json` (mode 0600), keyed by agent id
+and the environment variable the agent's CLI or API reads them from. They
+are merged into each agent's `env` when the config is loaded, so values set
+explicitly in agents.yaml or the process environment still win.
+"""
+
+from __future__ import annotations
+
+import json
+import logging
+import os
+from pathlib import Path
+from typing import Dict, Mapping
+
+from .errors import ConfigError
+from .models import Agent
+
+LOGGER = logging.getLogger(__name__)
+
+SECRETS_FILE_NAME = "secrets.json"
+
+
+class SecretStore:
+    """File-backed store of per-agent environment secrets."""
+
+    def __init__(self, config_dir: Path) -> None:
+        self.path = config_dir / SECRETS_FILE_NAME
+
+    def _read(self) -> Dict[str, Dict[str, str]]:
+        try:
+            data = json.loads(self.path.read_text(encoding="utf-8"))
+        except FileNotFoundError:
+            return {}
+        except (OSError, ValueError) as exc:
+            raise ConfigError(f"Cannot read {self.path}: {exc}") from exc
+        agents = data.get("agents") if isinstance(data, dict) else None
+        if not isinstance(agents, dict):
+            raise ConfigError(f"{self.path} must contain an `agents` mapping")
+        return {
+            str(agent_id): {str(name): str(value) for name, value in values.items()}
+            for agent_id, values in agents.items()
+            if isinstance(values, dict)
+        }
+
+    def _write(self, agents: Mapping[str, Mapping[str, str]]) -> None:
+        self.path.parent.mkdir(parents=True, exist_ok=True)
+        tmp_path = self.path.with_suffix(".tmp")
+        fd = os.open(tmp_path, os.O_WRONLY | os.O_CREAT | os.O_TRUNC, 0o600)
+        with os.fdopen(fd, "w", encoding="utf-8") as fp:
+            json.dump({"agents": agents}, fp, indent=2, sort_keys=True)
+        os.replace(tmp_path, self.path)
+
+    def env_for(self, agent_id: str) -> Dict[str, str]:
+        return dict(self._read().get(agent_id, {}))
+
+    def set(self, agent_id: str, name: str, value: str) -> None:
+        agents = self._read()
+        agents.setdefault(agent_id, {})[name] = value
+        self._write(agents)
+
+    def remove(self, agent_id: str) -> bool:
+        """Forget every secret stored for `agent_id`; returns whether there were any."""
+        agents = self._read()
+        if agents.pop(agent_id, None) is None:
+            return False
+        self._write(agents)
+        return True
+
+    def apply(self, agents: Mapping[str, Agent]) -> None:
+        """Add stored secrets to agents' `env` unless agents.yaml or the environment already set them."""
+        stored = self._read()
+        for agent_id, values in stored.items():
+            agent = agents.get(agent_id)
+            if agent is None:
+                LOGGER.debug("Ignoring stored secrets for unknown agent %s", agent_id)
+                continue
+            for name, value in values.items():
+                if name not in agent.env and name not in os.environ:
+                    agent.env[name] = value
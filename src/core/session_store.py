@@ -0,0 +1,219 @@
+"""Durable storage for sessions, their threads, and their PRs.
+
+`SessionManager` keeps everything in memory and, when it has a store, writes
+every change through to it, so a daemon that crashes or is killed between
+state saves comes back with the sessions it had. `SqliteSessionStore` keeps
+them in `<config dir>/sessions.db`; `REMOTE_CODER_SESSION_STORE=memory`
+leaves only the periodic `state.json` saves.
+
+The manager's maps stay the cache that reads are served from: the store is
+only read once, at startup (`SessionManager.load_from_store`).
+"""
+
+from __future__ import annotations
+
+import json
+import logging
+import os
+import sqlite3
+from abc import ABC, abstractmethod
+from dataclasses import dataclass, field
+from pathlib import Path
+from threading import Lock
+from typing import Dict, List, Optional, Sequence, Tuple
+from uuid import UUID
+
+from .errors import RemoteCoderError
+from .models import PullRequestRef, Session
+from .state import pr_ref_from_dict, pr_ref_to_dict, session_from_dict, session_to_dict
+
+LOGGER = logging.getLogger(__name__)
+
+SESSION_DB_FILE_NAME = "sessions.db"
+SQLITE = "sqlite"
+MEMORY = "memory"
+SESSION_STORE_KINDS = (SQLITE, MEMORY)
+SCHEMA_VERSION = 1
+
+_SCHEMA = """
+CREATE TABLE IF NOT EXISTS sessions (
+    id TEXT PRIMARY KEY,
+    data TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS threads (
+    channel_id TEXT NOT NULL,
+    thread_ts TEXT NOT NULL,
+    session_id TEXT NOT NULL,
+    PRIMARY KEY (channel_id, thread_ts)
+);
+CREATE TABLE IF NOT EXISTS pr_refs (
+    session_id TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    data TEXT NOT NULL,
+    PRIMARY KEY (session_id, position)
+);
+"""
+
+
+class SessionStoreError(RemoteCoderError):
+    """Raised when the session store cannot be opened, read, or written."""
+
+
+@dataclass
+class StoredSessions:
+    """Everything a store holds, as `SessionManager.load_from_store` restores it."""
+
+    sessions: List[Session] = field(default_factory=list)
+    # (channel id, thread ts) -> the session that owns the thread
+    threads: Dict[Tuple[str, str], UUID] = field(default_factory=dict)
+    # Session id -> its PRs, in the order they were first recorded
+    pr_refs: Dict[UUID, List[PullRequestRef]] = field(default_factory=dict)
+
+
+class SessionStore(ABC):
+    """Where a SessionManager writes its sessions through to."""
+
+    @abstractmethod
+    def load(self) -> StoredSessions:
+        """Everything in the store."""
+
+    @abstractmethod
+    def save_session(self, session: Session) -> None:
+        """Insert or replace `session`."""
+
+    @abstractmethod
+    def delete_session(self, session_id: UUID) -> None:
+        """Remove the session with its thread and PRs."""
+
+    @abstractmethod
+    def save_thread(self, channel_id: str, thread_ts: str, session_id: Optional[UUID]) -> None:
+        """Record which session owns the thread; None means no session does."""
+
+    @abstractmethod
+    def save_pr_refs(self, session_id: UUID, pr_refs: Sequence[PullRequestRef]) -> None:
+        """Replace the session's PRs."""
+
+    @abstractmethod
+    def clear(self) -> None:
+        """Remove everything."""
+
+    def close(self) -> None:
+        """Release the store's resources."""
+
+
+class SqliteSessionStore(SessionStore):
+    """A SessionStore in an SQLite database; sessions and PRs are stored as their `state.json` form."""
+
+    def __init__(self, path: Path) -> None:
+        self.path = path
+        self._lock = Lock()
+        try:
+            path.parent.mkdir(parents=True, exist_ok=True)
+            self._db = sqlite3.connect(str(path), check_same_thread=False, isolation_level=None)
+            self._db.execute("PRAGMA journal_mode=WAL")
+            version = self._db.execute("PRAGMA user_version").fetchone()[0]
+            if version > SCHEMA_VERSION:
+                raise SessionStoreError(f"{path} was written by a newer Remote Coder (schema {version})")
+            self._db.executescript(_SCHEMA)
+            self._db.execute(f"PRAGMA user_version={SCHEMA_VERSION}")
+            os.chmod(path, 0o600)
+        except (OSError, sqlite3.Error) as exc:
+            raise SessionStoreError(f"Failed to open {path}: {exc}") from exc
+
+    def load(self) -> StoredSessions:
+        stored = StoredSessions()
+        with self._lock:
+            try:
+                session_rows = self._db.execute("SELECT data FROM sessions").fetchall()
+                thread_rows = self._db.execute("SELECT channel_id, thread_ts, session_id FROM threads").fetchall()
+                pr_rows = self._db.execute(
+                    "SELECT session_id, data FROM pr_refs ORDER BY session_id, position"
+                ).fetchall()
+            except sqlite3.Error as exc:
+                raise SessionStoreError(f"Failed to read {self.path}: {exc}") from exc
+        for (data,) in session_rows:
+            try:
+                stored.sessions.append(session_from_dict(json.loads(data)))
+            except (KeyError, TypeError, ValueError) as exc:
+                LOGGER.warning("Skipping an unreadable session in %s: %s", self.path, exc)
+        known = {session.id for session in stored.sessions}
+        for channel_id, thread_ts, session_id in thread_rows:
+            if UUID(session_id) in known:
+                stored.threads[(channel_id, thread_ts)] = UUID(session_id)
+        for session_id, data in pr_rows:
+            if UUID(session_id) not in known:
+                continue
+            try:
+                pr_ref = pr_ref_from_dict(json.loads(data))
+            except (KeyError, TypeError, ValueError) as exc:
+                LOGGER.warning("Skipping an unreadable PR of session %s: %s", session_id, exc)
+                continue
+            stored.pr_refs.setdefault(pr_ref.session_id, []).append(pr_ref)
+        return stored
+
+    def save_session(self, session: Session) -> None:
+        data = json.dumps(session_to_dict(session))
+        self._write("INSERT OR REPLACE INTO sessions (id, data) VALUES (?, ?)", [(str(session.id), data)])
+
+    def delete_session(self, session_id: UUID) -> None:
+        with self._lock:
+            try:
+                with self._db:
+                    self._db.execute("BEGIN")
+                    for table, column in (("sessions", "id"), ("threads", "session_id"), ("pr_refs", "session_id")):
+                        self._db.execute(f"DELETE FROM {table} WHERE {column} = ?", (str(session_id),))
+            except sqlite3.Error as exc:
+                raise SessionStoreError(f"Failed to write {self.path}: {exc}") from exc
+
+    def save_thread(self, channel_id: str, thread_ts: str, session_id: Optional[UUID]) -> None:
+        if session_id is None:
+            self._write("DELETE FROM threads WHERE channel_id = ? AND thread_ts = ?", [(channel_id, thread_ts)])
+            return
+        self._write(
+            "INSERT OR REPLACE INTO threads (channel_id, thread_ts, session_id) VALUES (?, ?, ?)",
+            [(channel_id, thread_ts, str(session_id))],
+        )
+
+    def save_pr_refs(self, session_id: UUID, pr_refs: Sequence[PullRequestRef]) -> None:
+        rows = [(str(session_id), position, json.dumps(pr_ref_to_dict(ref))) for position, ref in enumerate(pr_refs)]
+        with self._lock:
+            try:
+                with self._db:
+                    self._db.execute("BEGIN")
+                    self._db.execute("DELETE FROM pr_refs WHERE session_id = ?", (str(session_id),))
+                    self._db.executemany("INSERT INTO pr_refs (session_id, position, data) VALUES (?, ?, ?)", rows)
+            except sqlite3.Error as exc:
+                raise SessionStoreError(f"Failed to write {self.path}: {exc}") from exc
+
+    def clear(self) -> None:
+        with self._lock:
+            try:
+                with self._db:
+                    self._db.execute("BEGIN")
+                    for table in ("sessions", "threads", "pr_refs"):
+                        self._db.execute(f"DELETE FROM {table}")
+            except sqlite3.Error as exc:
+                raise SessionStoreError(f"Failed to write {self.path}: {exc}") from exc
+
+    def close(self) -> None:
+        with self._lock:
+            self._db.close()
+
+    def _write(self, statement: str, rows: List[tuple]) -> None:
+        with self._lock:
+            try:
+                with self._db:
+                    self._db.executemany(statement, rows)
+            except sqlite3.Error as exc:
+                raise SessionStoreError(f"Failed to write {self.path}: {exc}") from exc
+
+
+def set_aside(path: Path) -> Optional[Path]:
+    """Move the database at `path` (and its WAL files) out of the way; returns where it went, if it existed."""
+    if not path.exists():
+        return None
+    backup = path.with_name(f"{path.name}.bak")
+    path.replace(backup)
+    for suffix in ("-wal", "-shm"):
+        path.with_name(f"{path.name}{suffix}").unlink(missing_ok=True)
+    return backup
@@ -0,0 +1,99 @@
+"""Draining agent runs when the daemon stops (Ctrl+C, SIGTERM, `systemctl stop`).
+
+On a shutdown request the router stops starting runs: new messages and
+webhook calls are turned away, and runs still waiting for a slot give up.
+Runs already going get `REMOTE_CODER_SHUTDOWN_GRACE_SECS` (default 60) to
+finish, and each of their threads is told so. A second Ctrl+C ends the
+grace period early.
+
+Runs still going after that are cancelled with `SHUTDOWN_CANCEL_MESSAGE`,
+which kills their agent CLIs like `!cancel` does. What a stopped run left
+uncommitted in the session's checkout is committed to the session branch
+(`PARK_COMMIT_MESSAGE`), so it survives the restart and the next message
+carries on from it; `!redo` drops it again. The session's history gets a
+reply that closes the interrupted request, and the thread is told where
+the work went.
+
+When the daemon starts again, the sessions come back from the session
+store with their threads, and each active session's thread that saw
+activity in the last `REMOTE_CODER_RESUME_NOTICE_HOURS` (default 24) is
+told it was resumed (`RESTART_NOTE`), so the people in it know they can
+carry on.
+"""
+
+from __future__ import annotations
+
+import asyncio
+import logging
+from dataclasses import dataclass, field
+from typing import Dict, List, Optional
+
+from .commands.cancel import CANCEL_WAIT_SECS
+
+LOGGER = logging.getLogger(__name__)
+
+DEFAULT_SHUTDOWN_GRACE_SECS = 60
+DEFAULT_RESUME_NOTICE_HOURS = 24
+SHUTDOWN_CANCEL_MESSAGE = "remote-coder shutdown"
+PARK_COMMIT_MESSAGE = "WIP: unfinished run parked at Remote Coder shutdown"
+NOT_STARTING_NOTE = "Remote Coder is shutting down, so it is not starting new runs. Send this again once it is back."
+RESTART_NOTE = "Daemon restarted, session resumed: reply here to carry on where you left off."
+
+RunInfo = Dict[str, object]
+
+
+def cancelled_by_shutdown(exc: asyncio.CancelledError) -> bool:
+    return bool(exc.args) and exc.args[0] == SHUTDOWN_CANCEL_MESSAGE
+
+
+@dataclass
+class Drain:
+    """The runs that were going when the daemon started shutting down."""
+
+    finished: List[RunInfo] = field(default_factory=list)
+    interrupted: List[RunInfo] = field(default_factory=list)
+
+
+def running(active_runs: Dict[str, RunInfo]) -> List[RunInfo]:
+    return [
+        run
+        for run in active_runs.values()
+        if isinstance(run.get("task"), asyncio.Task) and not run["task"].done()  # type: ignore[union-attr]
+    ]
+
+
+async def drain_runs(
+    active_runs: Dict[str, RunInfo], grace_secs: float, hurry: Optional[asyncio.Event] = None
+) -> Drain:
+    """Wait up to `grace_secs` (or until `hurry` is set) for the active runs, then cancel the rest."""
+    runs = running(active_runs)
+    tasks: Dict[asyncio.Task, RunInfo] = {run["task"]: run for run in runs}  # type: ignore[misc]
+    if not tasks:
+        return Drain()
+    loop = asyncio.get_running_loop()
+    deadline = loop.time() + grace_secs
+    hurry_task = asyncio.create_task(hurry.wait()) if hurry else None
+    try:
+        waiting = set(tasks)
+        while waiting and loop.time() < deadline and not (hurry and hurry.is_set()):
+            done, _ = await asyncio.wait(
+                waiting | ({hurry_task} if hurry_task else set()),
+                timeout=deadline - loop.time(),
+                return_when=asyncio.FIRST_COMPLETED,
+            )
+            waiting -= done
+    finally:
+        if hurry_task:
+            hurry_task.cancel()
+    pending = [task for task in tasks if not task.done()]
+    drain = Drain(finished=[tasks[task] for task in tasks if task.done()])
+    if pending:
+        LOGGER.info("Stopping %d run(s) that did not finish within the shutdown grace period", len(pending))
+        for task in pending:
+            task.cancel(SHUTDOWN_CANCEL_MESSAGE)
+        # Cancelling kills the agents' CLIs; wait for that so the checkouts are no longer being written to
+        _, still_going = await asyncio.wait(pending, timeout=CANCEL_WAIT_SECS)
+        if still_going:
+            LOGGER.warning("%d stopped run(s) are still shutting down", len(still_going))
+        drain.interrupted = [tasks[task] for task in pending]
+    return drain
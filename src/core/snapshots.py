@@ -0,0 +1,276 @@
+"""Workspace snapshots for projects that are not git repositories.
+
+Scripts folders and note vaults have no branch to commit to, so before each
+agent run the project directory is snapshotted instead: a manifest of every
+file's size, modification time, and SHA-256, with the contents kept in a
+content-addressed blob store under `<config dir>/snapshots/<session id>/`.
+Unchanged files are recognised by size and mtime and not read again, and
+blobs are shared between runs, so repeated snapshots stay cheap.
+
+After the run the directory is compared with the snapshot to build a
+git-style unified diff for the thread, and `!rollback` copies the snapshot
+back, deleting files the run created. Files over MAX_FILE_BYTES are only
+hashed, so changes to them are reported but cannot be rolled back.
+"""
+
+from __future__ import annotations
+
+import difflib
+import hashlib
+import json
+import logging
+import os
+import shutil
+from dataclasses import asdict, dataclass, field
+from pathlib import Path
+from typing import Dict, Iterator, List, Optional, Tuple
+
+LOGGER = logging.getLogger(__name__)
+
+SNAPSHOTS_DIR_NAME = "snapshots"
+MANIFEST_NAME = "manifest.json"
+BLOBS_DIR_NAME = "blobs"
+# Contents of larger files are not kept; they are hashed to detect changes only
+MAX_FILE_BYTES = 5 * 1024 * 1024
+# Directories with more files than this are not snapshotted
+MAX_FILES = 20_000
+IGNORED_NAMES = frozenset({".git", "node_modules", "__pycache__", ".venv", ".DS_Store"})
+
+
+class SnapshotError(Exception):
+    """The workspace could not be snapshotted or restored."""
+
+
+@dataclass(frozen=True)
+class SnapshotEntry:
+    size: int
+    mtime_ns: int
+    sha256: str
+    stored: bool  # False when the file was too large to keep
+
+
+@dataclass
+class RestoreResult:
+    restored: List[str] = field(default_factory=list)
+    removed: List[str] = field(default_factory=list)
+    unrecoverable: List[str] = field(default_factory=list)  # Changed files whose contents were not kept
+
+    @property
+    def changed(self) -> bool:
+        return bool(self.restored or self.removed)
+
+
+def snapshots_dir(config_dir: Path) -> Path:
+    return config_dir / SNAPSHOTS_DIR_NAME
+
+
+def _iter_files(workdir: Path, skip: Path) -> Iterator[Tuple[str, Path]]:
+    for root, dirs, files in os.walk(workdir):
+        # `skip` keeps the snapshots out of themselves when the config dir is inside the project
+        dirs[:] = sorted(name for name in dirs if name not in IGNORED_NAMES and Path(root, name).resolve() != skip)
+        for name in sorted(files):
+            if name in IGNORED_NAMES:
+                continue
+            path = Path(root) / name
+            if path.is_symlink() or not path.is_file():
+                continue
+            yield path.relative_to(workdir).as_posix(), path
+
+
+def _hash_file(path: Path) -> str:
+    digest = hashlib.sha256()
+    with path.open("rb") as handle:
+        for chunk in iter(lambda: handle.read(1024 * 1024), b""):
+            digest.update(chunk)
+    return digest.hexdigest()
+
+
+def _scan(workdir: Path, known: Dict[str, SnapshotEntry], skip: Path) -> Dict[str, Tuple[Path, SnapshotEntry]]:
+    """Current files with their entries; hashes are reused for files whose size and mtime match `known`."""
+    current: Dict[str, Tuple[Path, SnapshotEntry]] = {}
+    for relative, path in _iter_files(workdir, skip):
+        if len(current) >= MAX_FILES:
+            raise SnapshotError(f"{workdir} has more than {MAX_FILES} files")
+        stat = path.stat()
+        previous = known.get(relative)
+        if previous and previous.size == stat.st_size and previous.mtime_ns == stat.st_mtime_ns:
+            sha256 = previous.sha256
+        else:
+            sha256 = _hash_file(path)
+        entry = SnapshotEntry(stat.st_size, stat.st_mtime_ns, sha256, stat.st_size <= MAX_FILE_BYTES)
+        current[relative] = (path, entry)
+    return current
+
+
+class SnapshotStore:
+    """The snapshot taken before a session's latest run."""
+
+    def __init__(self, root: Path) -> None:
+        self._root = root
+        self._skip = root.parent.resolve()
+
+    @classmethod
+    def for_session(cls, config_dir: Path, session_id: object) -> "SnapshotStore":
+        return cls(snapshots_dir(config_dir) / str(session_id))
+
+    @property
+    def _manifest_path(self) -> Path:
+        return self._root / MANIFEST_NAME
+
+    def _blob_path(self, sha256: str) -> Path:
+        return self._root / BLOBS_DIR_NAME / sha256[:2] / sha256
+
+    def load(self) -> Optional[Dict[str, SnapshotEntry]]:
+        """The snapshot's entries by relative path; None when there is none."""
+        try:
+            data = json.loads(self._manifest_path.read_text(encoding="utf-8"))
+        except FileNotFoundError:
+            return None
+        except (OSError, ValueError) as exc:
+            raise SnapshotError(f"Cannot read snapshot {self._manifest_path}: {exc}") from exc
+        return {path: SnapshotEntry(**entry) for path, entry in data.get("files", {}).items()}
+
+    def take(self, workdir: Path) -> Dict[str, SnapshotEntry]:
+        """Snapshot `workdir`, replacing the previous snapshot; raises SnapshotError."""
+        try:
+            known = self.load() or {}
+        except SnapshotError:
+            known = {}
+        try:
+            current = _scan(workdir, known, self._skip)
+            for path, entry in current.values():
+                blob = self._blob_path(entry.sha256)
+                if entry.stored and not blob.exists():
+                    blob.parent.mkdir(parents=True, exist_ok=True)
+                    shutil.copyfile(path, blob)
+            entries = {relative: entry for relative, (_, entry) in current.items()}
+            self._root.mkdir(parents=True, exist_ok=True)
+            payload = {"workdir": str(workdir), "files": {path: asdict(entry) for path, entry in entries.items()}}
+            self._manifest_path.write_text(json.dumps(payload), encoding="utf-8")
+            self._prune_blobs(entries)
+        except OSError as exc:
+            raise SnapshotError(f"Cannot snapshot {workdir}: {exc}") from exc
+        LOGGER.debug("Snapshotted %d file(s) of %s", len(entries), workdir)
+        return entries
+
+    def _prune_blobs(self, entries: Dict[str, SnapshotEntry]) -> None:
+        keep = {entry.sha256 for entry in entries.values()}
+        blobs = self._root / BLOBS_DIR_NAME
+        if not blobs.is_dir():
+            return
+        for blob in blobs.glob("*/*"):
+            if blob.name not in keep:
+                blob.unlink(missing_ok=True)
+
+    def diff(self, workdir: Path) -> str:
+        """Git-style unified diff from the snapshot to the current files; empty without changes or a snapshot."""
+        before = self.load()
+        if before is None:
+            return ""
+        try:
+            after = _scan(workdir, before, self._skip)
+            chunks = []
+            for relative in sorted(set(before) | set(after)):
+                old = before.get(relative)
+                new = after[relative][1] if relative in after else None
+                if old and new and old.sha256 == new.sha256:
+                    continue
+                new_path = after[relative][0] if relative in after else None
+                chunks.append(self._file_diff(relative, old, new, new_path))
+        except OSError as exc:
+            raise SnapshotError(f"Cannot compare {workdir} with its snapshot: {exc}") from exc
+        return "".join(chunks)
+
+    def _read_blob(self, entry: Optional[SnapshotEntry]) -> Optional[bytes]:
+        if entry is None:
+            return b""
+        if not entry.stored:
+            return None
+        return self._blob_path(entry.sha256).read_bytes()
+
+    def _file_diff(
+        self, relative: str, old: Optional[SnapshotEntry], new: Optional[SnapshotEntry], new_path: Optional[Path]
+    ) -> str:
+        header = [f"diff --git a/{relative} b/{relative}\n"]
+        if old is None:
+            header.append("new file mode 100644\n")
+        elif new is None:
+            header.append("deleted file mode 100644\n")
+        old_bytes = self._read_blob(old)
+        new_bytes = b"" if new_path is None else (new_path.read_bytes() if new and new.stored else None)
+        old_text = _as_text(old_bytes)
+        new_text = _as_text(new_bytes)
+        if old_text is None or new_text is None:
+            old_name = f"a/{relative}" if old else "/dev/null"
+            new_name = f"b/{relative}" if new else "/dev/null"
+            return "".join(header) + f"Binary files {old_name} and {new_name} differ\n"
+        lines = difflib.unified_diff(
+            old_text.splitlines(keepends=True),
+            new_text.splitlines(keepends=True),
+            fromfile=f"a/{relative}" if old else "/dev/null",
+            tofile=f"b/{relative}" if new else "/dev/null",
+        )
+        body = []
+        for line in lines:
+            body.append(line if line.endswith("\n") else f"{line}\n\\ No newline at end of file\n")
+        return "".join(header + body)
+
+    def restore(self, workdir: Path) -> RestoreResult:
+        """Put `workdir` back the way the snapshot found it; raises SnapshotError without a snapshot."""
+        before = self.load()
+        if before is None:
+            raise SnapshotError("there is no snapshot to roll back to")
+        result = RestoreResult()
+        try:
+            after = _scan(workdir, before, self._skip)
+            for relative in sorted(set(after) - set(before)):
+                path = after[relative][0]
+                path.unlink()
+                result.removed.append(relative)
+                _remove_empty_parents(path.parent, workdir)
+            for relative, entry in sorted(before.items()):
+                current = after.get(relative)
+                if current and current[1].sha256 == entry.sha256:
+                    continue
+                if not entry.stored:
+                    result.unrecoverable.append(relative)
+                    continue
+                target = workdir / relative
+                target.parent.mkdir(parents=True, exist_ok=True)
+                shutil.copyfile(self._blob_path(entry.sha256), target)
+                result.restored.append(relative)
+        except OSError as exc:
+            raise SnapshotError(f"Cannot restore {workdir}: {exc}") from exc
+        return result
+
+    def discard(self) -> None:
+        shutil.rmtree(self._root, ignore_errors=True)
+
+
+def _as_text(data: Optional[bytes]) -> Optional[str]:
+    if data is None or b"\0" in data:
+        return None
+    try:
+        return data.decode("utf-8")
+    except UnicodeDecodeError:
+        return None
+
+
+def _remove_empty_parents(directory: Path, workdir: Path) -> None:
+    while directory != workdir and directory.is_dir() and not any(directory.iterdir()):
+        directory.rmdir()
+        directory = directory.parent
+
+
+def format_restore_result(result: RestoreResult) -> str:
+    """Reply for `!rollback`."""
+    if not result.changed and not result.unrecoverable:
+        return "The workspace already matches the snapshot from before the last run; nothing to roll back."
+    lines = [
+        f"Rolled the workspace back to before the last run: restored {len(result.restored)} file(s), "
+        f"removed {len(result.removed)} new file(s)."
+    ]
+    if result.unrecoverable:
+        listed = ", ".join(f"`{path}`" for path in result.unrecoverable[:10])
+        lines.append(f"Too large to keep in the snapshot, so left as they are: {listed}")
+    return "\n".join(lines)
@@ -0,0 +1,445 @@
+"""Persist daemon state and move it between machines.
+
+The daemon keeps sessions (with their transcripts) and PR refs in memory and
+mirrors them to `<config dir>/state.json`: loaded at startup, written
+periodically and on shutdown. `remote-coder export-state` packs that file
+into a portable archive together with references to the config it was used
+with (project paths and GitHub repos, agent ids; never `.env` secrets), and
+`remote-coder import-state` unpacks it on the new machine, pointing sessions
+at the project paths from the local projects.yaml.
+"""
+
+from __future__ import annotations
+
+import io
+import json
+import logging
+import os
+import socket
+import tarfile
+from dataclasses import dataclass, field
+from datetime import datetime, timezone
+from pathlib import Path
+from typing import Any, Dict, List, Optional, Tuple
+from uuid import UUID
+
+from .conversation import SessionManager
+from .errors import RemoteCoderError, SessionNotFound, SessionRestoreError
+from .models import (
+    AgentType,
+    ConversationInteraction,
+    ConversationMessage,
+    Project,
+    PullRequestRef,
+    PullRequestRole,
+    Session,
+    SessionStatus,
+)
+from .incident import IncidentWindow
+from .preferences import UserPreferences
+
+LOGGER = logging.getLogger(__name__)
+
+STATE_FILE_NAME = "state.json"
+STATE_FORMAT_VERSION = 1
+STATE_SAVE_INTERVAL_SECS = 30
+ARCHIVE_STATE_MEMBER = "state.json"
+ARCHIVE_MANIFEST_MEMBER = "manifest.json"
+
+
+class StateError(RemoteCoderError):
+    """Raised when a state file or archive cannot be read or applied."""
+
+
+def _time(value: datetime) -> str:
+    return value.isoformat()
+
+
+def _parse_time(value: str) -> datetime:
+    return datetime.fromisoformat(value)
+
+
+def _message_to_dict(message: ConversationMessage) -> Dict[str, Any]:
+    return {"role": message.role, "content": message.content, "timestamp": _time(message.timestamp)}
+
+
+def _message_from_dict(data: Dict[str, Any]) -> ConversationMessage:
+    return ConversationMessage(role=data["role"], content=data["content"], timestamp=_parse_time(data["timestamp"]))
+
+
+def session_to_dict(session: Session) -> Dict[str, Any]:
+    return {
+        "id": str(session.id),
+        "project_id": session.project_id,
+        "channel_id": session.channel_id,
+        "thread_ts": session.thread_ts,
+        "active_agent_id": session.active_agent_id,
+        "active_agent_type": session.active_agent_type.value,
+        "active_model": session.active_model,
+        "project_path": str(session.project_path),
+        "conversation_history": [_message_to_dict(message) for message in session.conversation_history],
+        "interactions": [
+            {
+                "interaction_number": interaction.interaction_number,
+                "user_message": _message_to_dict(interaction.user_message),
+                "agent_message": _message_to_dict(interaction.agent_message),
+                "is_summarized": interaction.is_summarized,
+                "created_at": _time(interaction.created_at),
+            }
+            for interaction in session.interactions
+        ],
+        "conversation_summary": session.conversation_summary,
+        "summary_interaction_count": session.summary_interaction_count,
+        "session_context": session.session_context,
+        "estimated_cost_usd": session.estimated_cost_usd,
+        "publishing_paused": session.publishing_paused,
+        "agent_pinned": session.agent_pinned,
+        "timezone": session.timezone,
+        "tags": list(session.tags),
+        "status": session.status.value,
+        "archived_at": _time(session.archived_at) if session.archived_at else None,
+        "created_at": _time(session.created_at),
+        "updated_at": _time(session.updated_at),
+        "last_activity_at": _time(session.last_activity_at),
+    }
+
+
+def session_from_dict(data: Dict[str, Any]) -> Session:
+    return Session(
+        id=UUID(data["id"]),
+        project_id=data["project_id"],
+        channel_id=data["channel_id"],
+        thread_ts=data["thread_ts"],
+        active_agent_id=data["active_agent_id"],
+        active_agent_type=AgentType(data["active_agent_type"]),
+        active_model=data.get("active_model"),
+        project_path=Path(data["project_path"]),
+        conversation_history=[_message_from_dict(message) for message in data.get("conversation_history", [])],
+        interactions=[
+            ConversationInteraction(
+                interaction_number=item["interaction_number"],
+                user_message=_message_from_dict(item["user_message"]),
+                agent_message=_message_from_dict(item["agent_message"]),
+                is_summarized=item.get("is_summarized", False),
+                created_at=_parse_time(item["created_at"]),
+            )
+            for item in data.get("interactions", [])
+        ],
+        conversation_summary=data.get("conversation_summary"),
+        summary_interaction_count=data.get("summary_interaction_count", 0),
+        session_context=dict(data.get("session_context") or {}),
+        estimated_cost_usd=float(data.get("estimated_cost_usd", 0.0)),
+        publishing_paused=bool(data.get("publishing_paused", False)),
+        agent_pinned=bool(data.get("agent_pinned", False)),
+        timezone=data.get("timezone"),
+        tags=list(data.get("tags") or []),
+        status=SessionStatus(data.get("status", SessionStatus.ACTIVE.value)),
+        archived_at=_parse_time(data["archived_at"]) if data.get("archived_at") else None,
+        created_at=_parse_time(data["created_at"]),
+        updated_at=_parse_time(data["updated_at"]),
+        last_activity_at=_parse_time(data.get("last_activity_at") or data["updated_at"]),
+    )
+
+
+def pr_ref_to_dict(pr_ref: PullRequestRef) -> Dict[str, Any]:
+    return {
+        "project_id": pr_ref.project_id,
+        "session_id": str(pr_ref.session_id),
+        "number": pr_ref.number,
+        "url": pr_ref.url,
+        "head_branch": pr_ref.head_branch,
+        "base_branch": pr_ref.base_branch,
+        "role": pr_ref.role.value,
+        "repo": pr_ref.repo,
+        "created_at": _time(pr_ref.created_at),
+        "updated_at": _time(pr_ref.updated_at),
+    }
+
+
+def pr_ref_from_dict(data: Dict[str, Any]) -> PullRequestRef:
+    return PullRequestRef(
+        project_id=data["project_id"],
+        session_id=UUID(data["session_id"]),
+        number=int(data["number"]),
+        url=data["url"],
+        head_branch=data["head_branch"],
+        base_branch=data["base_branch"],
+        role=PullRequestRole(data.get("role") or PullRequestRole.PRIMARY.value),
+        repo=data.get("repo"),
+        created_at=_parse_time(data["created_at"]),
+        updated_at=_parse_time(data["updated_at"]),
+    )
+
+
+def dump_state(session_manager: SessionManager) -> Dict[str, Any]:
+    sessions, pr_refs = session_manager.export_sessions()
+    return {
+        "version": STATE_FORMAT_VERSION,
+        "saved_at": _time(datetime.now(timezone.utc)),
+        "sessions": [session_to_dict(session) for session in sessions],
+        "pr_refs": [pr_ref_to_dict(pr_ref) for pr_ref in pr_refs],
+        "run_counts": session_manager.export_run_counts(),
+        "user_preferences": {
+            user_id: preferences.to_dict()
+            for user_id, preferences in session_manager.export_user_preferences().items()
+        },
+        "incidents": {
+            project_id: window.to_dict() for project_id, window in session_manager.export_incidents().items()
+        },
+    }
+
+
+def parse_state(data: Any) -> Tuple[List[Session], List[PullRequestRef]]:
+    if not isinstance(data, dict):
+        raise StateError("State must be a JSON object")
+    version = data.get("version")
+    if version != STATE_FORMAT_VERSION:
+        raise StateError(f"Unsupported state format version {version} (expected {STATE_FORMAT_VERSION})")
+    try:
+        sessions = [session_from_dict(item) for item in data.get("sessions", [])]
+        pr_refs = [pr_ref_from_dict(item) for item in data.get("pr_refs", [])]
+    except (KeyError, TypeError, ValueError) as exc:
+        raise StateError(f"Malformed state: {exc}") from exc
+    return sessions, pr_refs
+
+
+def parse_run_counts(data: Dict[str, Any]) -> Dict[str, Dict[str, int]]:
+    """Per-project monthly run counts of a parsed state; files from before quotas have none."""
+    counts = data.get("run_counts") or {}
+    if not isinstance(counts, dict) or not all(
+        isinstance(months, dict) and all(isinstance(runs, int) for runs in months.values())
+        for months in counts.values()
+    ):
+        raise StateError("Malformed state: run_counts must map project ids to monthly run counts")
+    return {
+        str(project_id): {str(month): runs for month, runs in months.items()} for project_id, months in counts.items()
+    }
+
+
+def parse_user_preferences(data: Dict[str, Any]) -> Dict[str, UserPreferences]:
+    """Per-user `!prefs` of a parsed state; files from before preferences have none."""
+    preferences = data.get("user_preferences") or {}
+    if not isinstance(preferences, dict) or not all(isinstance(item, dict) for item in preferences.values()):
+        raise StateError("Malformed state: user_preferences must map user ids to preferences")
+    try:
+        return {str(user_id): UserPreferences.from_dict(item) for user_id, item in preferences.items()}
+    except ValueError as exc:
+        raise StateError(f"Malformed state: {exc}") from exc
+
+
+def parse_incidents(data: Dict[str, Any]) -> Dict[str, IncidentWindow]:
+    """Per-project `!incident` windows of a parsed state; files from before incident mode have none."""
+    incidents = data.get("incidents") or {}
+    if not isinstance(incidents, dict) or not all(isinstance(item, dict) for item in incidents.values()):
+        raise StateError("Malformed state: incidents must map project ids to incident windows")
+    try:
+        return {str(project_id): IncidentWindow.from_dict(item) for project_id, item in incidents.items()}
+    except (KeyError, TypeError, ValueError) as exc:
+        raise StateError(f"Malformed state: {exc}") from exc
+
+
+def _write_json_atomic(path: Path, data: Dict[str, Any]) -> None:
+    tmp_path = path.with_name(f".{path.name}.tmp")
+    # Transcripts can contain anything pasted into chat; keep the file private
+    fd = os.open(tmp_path, os.O_WRONLY | os.O_CREAT | os.O_TRUNC, 0o600)
+    with os.fdopen(fd, "w", encoding="utf-8") as fp:
+        json.dump(data, fp, indent=2, default=str)
+    os.replace(tmp_path, path)
+
+
+class StateStore:
+    """Loads and saves a SessionManager's contents as a JSON file."""
+
+    def __init__(self, path: Path) -> None:
+        self.path = path
+
+    def load(self, session_manager: SessionManager, *, keep_newer: bool = False) -> int:
+        """Restore sessions from the state file; returns how many were loaded.
+
+        With `keep_newer`, sessions the manager already has a copy of that is at least as recent
+        (loaded from its session store) are left as they are.
+        """
+        if not self.path.exists():
+            return 0
+        try:
+            data = json.loads(self.path.read_text(encoding="utf-8"))
+        except (OSError, json.JSONDecodeError) as exc:
+            raise StateError(f"Failed to read {self.path}: {exc}") from exc
+        sessions, pr_refs = parse_state(data)
+        if keep_newer:
+            sessions = [session for session in sessions if not _has_newer(session_manager, session)]
+        session_manager.import_sessions(sessions, pr_refs)
+        session_manager.import_run_counts(parse_run_counts(data))
+        session_manager.import_user_preferences(parse_user_preferences(data))
+        session_manager.import_incidents(parse_incidents(data))
+        LOGGER.info("Restored %d session(s) from %s", len(sessions), self.path)
+        return len(sessions)
+
+    def save(self, session_manager: SessionManager) -> None:
+        _write_json_atomic(self.path, dump_state(session_manager))
+        LOGGER.debug("Saved daemon state to %s", self.path)
+
+
+def _has_newer(session_manager: SessionManager, session: Session) -> bool:
+    try:
+        current = session_manager.get_session(session.id)
+    except SessionNotFound:
+        return False
+    return current.updated_at >= session.updated_at
+
+
+def saved_sessions(state_path: Path) -> List[Session]:
+    """Sessions in a state file, most recently updated first."""
+    manager = SessionManager()
+    StateStore(state_path).load(manager)
+    sessions, _ = manager.export_sessions()
+    return sorted(sessions, key=lambda session: session.updated_at, reverse=True)
+
+
+def restore_saved_session(state_path: Path, id_prefix: str) -> Session:
+    """Make an archived or ended session in the state file active again; run while the daemon is stopped."""
+    store = StateStore(state_path)
+    manager = SessionManager()
+    if not store.load(manager):
+        raise StateError(f"No saved sessions in {state_path}")
+    matches = manager.find_sessions(id_prefix)
+    if not matches:
+        raise StateError(f"No saved session has an ID starting with {id_prefix!r}")
+    if len(matches) > 1:
+        raise StateError(f"{len(matches)} sessions have an ID starting with {id_prefix!r}; give more of it")
+    try:
+        session = manager.restore_session(matches[0].id)
+    except SessionRestoreError as exc:
+        raise StateError(str(exc)) from exc
+    store.save(manager)
+    return session
+
+
+def _config_references(projects: Dict[str, Project], agent_ids: List[str], config_dir: Path) -> Dict[str, Any]:
+    return {
+        "config_dir": str(config_dir),
+        "projects": {
+            project.id: {
+                "path": str(project.path),
+                "github": project.github.full_name if project.github else None,
+            }
+            for project in projects.values()
+        },
+        "agents": sorted(agent_ids),
+    }
+
+
+def _add_json_member(archive: tarfile.TarFile, name: str, data: Dict[str, Any]) -> None:
+    payload = json.dumps(data, indent=2, default=str).encode("utf-8")
+    info = tarfile.TarInfo(name)
+    info.size = len(payload)
+    info.mode = 0o600
+    info.mtime = int(datetime.now(timezone.utc).timestamp())
+    archive.addfile(info, io.BytesIO(payload))
+
+
+def export_archive(
+    state_path: Path,
+    output: Path,
+    *,
+    projects: Dict[str, Project],
+    agent_ids: List[str],
+    config_dir: Path,
+) -> int:
+    """Write `state_path` plus config references to a .tar.gz; returns the number of sessions."""
+    if not state_path.exists():
+        raise StateError(f"No daemon state found at {state_path}; start the daemon at least once first")
+    try:
+        state = json.loads(state_path.read_text(encoding="utf-8"))
+    except (OSError, json.JSONDecodeError) as exc:
+        raise StateError(f"Failed to read {state_path}: {exc}") from exc
+    sessions, _ = parse_state(state)
+
+    manifest = {
+        "version": STATE_FORMAT_VERSION,
+        "exported_at": _time(datetime.now(timezone.utc)),
+        "source_host": socket.gethostname(),
+        "config": _config_references(projects, agent_ids, config_dir),
+    }
+    with tarfile.open(output, "w:gz") as archive:
+        _add_json_member(archive, ARCHIVE_MANIFEST_MEMBER, manifest)
+        _add_json_member(archive, ARCHIVE_STATE_MEMBER, state)
+    os.chmod(output, 0o600)
+    return len(sessions)
+
+
+def _read_json_member(archive: tarfile.TarFile, name: str) -> Any:
+    try:
+        member = archive.extractfile(name)
+    except KeyError as exc:
+        raise StateError(f"Archive is missing {name}") from exc
+    if member is None:
+        raise StateError(f"Archive member {name} is not a file")
+    try:
+        return json.loads(member.read().decode("utf-8"))
+    except (UnicodeDecodeError, json.JSONDecodeError) as exc:
+        raise StateError(f"Archive member {name} is not valid JSON") from exc
+
+
+@dataclass
+class ImportReport:
+    sessions: int = 0
+    pr_refs: int = 0
+    source_host: Optional[str] = None
+    backup_path: Optional[Path] = None
+    relocated_projects: List[str] = field(default_factory=list)
+    missing_projects: List[str] = field(default_factory=list)
+    missing_agents: List[str] = field(default_factory=list)
+
+
+def import_archive(
+    archive_path: Path,
+    state_path: Path,
+    *,
+    projects: Dict[str, Project],
+    agent_ids: List[str],
+    force: bool = False,
+) -> ImportReport:
+    """Unpack an exported archive into `state_path`, pointing sessions at the local project paths."""
+    try:
+        with tarfile.open(archive_path, "r:gz") as archive:
+            manifest = _read_json_member(archive, ARCHIVE_MANIFEST_MEMBER)
+            state = _read_json_member(archive, ARCHIVE_STATE_MEMBER)
+    except (OSError, tarfile.TarError) as exc:
+        raise StateError(f"Failed to read archive {archive_path}: {exc}") from exc
+    sessions, pr_refs = parse_state(state)
+
+    report = ImportReport(
+        sessions=len(sessions),
+        pr_refs=len(pr_refs),
+        source_host=manifest.get("source_host") if isinstance(manifest, dict) else None,
+    )
+    if state_path.exists():
+        if not force:
+            raise StateError(f"{state_path} already exists; pass --force to replace it (a backup is kept)")
+        report.backup_path = state_path.with_name(f"{state_path.name}.bak")
+        os.replace(state_path, report.backup_path)
+
+    by_id = {project.id: project for project in projects.values()}
+    relocated, missing_projects, missing_agents = set(), set(), set()
+    for session in sessions:
+        project = by_id.get(session.project_id)
+        if project is None:
+            missing_projects.add(session.project_id)
+        elif session.project_path != project.path:
+            session.project_path = project.path
+            relocated.add(project.id)
+        if session.active_agent_id not in agent_ids:
+            missing_agents.add(session.active_agent_id)
+    report.relocated_projects = sorted(relocated)
+    report.missing_projects = sorted(missing_projects)
+    report.missing_agents = sorted(missing_agents)
+
+    manager = SessionManager()
+    manager.import_sessions(sessions, pr_refs)
+    manager.import_run_counts(parse_run_counts(state))
+    manager.import_user_preferences(parse_user_preferences(state))
+    manager.import_incidents(parse_incidents(state))
+    StateStore(state_path).save(manager)
+    return report
+
@@ -0,0 +1,193 @@
+"""A status page to bookmark: is the daemon connected, busy, or failing?
+
+With `REMOTE_CODER_STATUS_PORT` set, the daemon serves
+
+    GET /status?token=<token>        a small HTML page that refreshes itself
+    GET /status.json?token=<token>   the same as JSON
+
+(the token can also be sent as `Authorization: Bearer <token>`). It shows the
+version and uptime, whether each chat adapter is connected and GitHub is
+configured, each project's running and queued runs, the runs in progress,
+and the last error logged by each integration (Slack, GitHub, ...). Errors
+are recorded from the daemon's own log, and everything is redacted like chat
+messages. The page needs `REMOTE_CODER_STATUS_TOKEN`, since it is meant to
+be opened from a phone.
+"""
+
+from __future__ import annotations
+
+import hmac
+import html
+import json
+import logging
+import time
+from dataclasses import dataclass
+from typing import Any, Callable, Dict, List, Mapping, Optional, Tuple
+
+from aiohttp import web
+
+from ..chat_adapters.health import format_duration
+from .webhooks import bearer_token
+
+LOGGER = logging.getLogger(__name__)
+
+STATUS_ROUTE = "/status"
+STATUS_JSON_ROUTE = "/status.json"
+REFRESH_SECS = 30
+MAX_ERROR_CHARS = 300
+
+# Logger name prefix -> the integration its errors are shown under; the first match wins
+INTEGRATION_LOGGERS: Tuple[Tuple[str, str], ...] = (
+    ("src.chat_adapters.github_discussions", "GitHub Discussions"),
+    ("src.chat_adapters", "Slack"),
+    ("src.github", "GitHub"),
+    ("src.core.git_workflow", "GitHub"),
+    ("src.agent_adapters", "Agents"),
+    ("src.core.webhooks", "Webhooks"),
+    ("src.core.updates", "Updates"),
+)
+OTHER_INTEGRATION = "Daemon"
+
+StatusFn = Callable[[], Dict[str, Any]]
+
+
+@dataclass(frozen=True)
+class IntegrationError:
+    integration: str
+    at: float
+    message: str
+
+
+def integration_for(logger_name: str) -> str:
+    return next(
+        (name for prefix, name in INTEGRATION_LOGGERS if logger_name == prefix or logger_name.startswith(prefix + ".")),
+        OTHER_INTEGRATION,
+    )
+
+
+class IntegrationErrors(logging.Handler):
+    """Keeps the last error each integration logged: records at ERROR and above, or with an exception."""
+
+    def __init__(self, clock: Callable[[], float] = time.time) -> None:
+        super().__init__(logging.WARNING)
+        self._clock = clock
+        self._latest: Dict[str, IntegrationError] = {}
+
+    def emit(self, record: logging.LogRecord) -> None:
+        if record.levelno < logging.ERROR and not record.exc_info:
+            return
+        try:
+            message = record.getMessage()
+        except Exception:
+            message = str(record.msg)
+        if record.exc_info and record.exc_info[1] is not None:
+            message = f"{message}: {record.exc_info[1]}"
+        message = message.strip().splitlines()[0] if message.strip() else "(no message)"
+        integration = integration_for(record.name)
+        self._latest[integration] = IntegrationError(integration, self._clock(), message[:MAX_ERROR_CHARS])
+
+    def latest(self) -> List[IntegrationError]:
+        return sorted(self._latest.values(), key=lambda error: error.integration)
+
+
+def token_matches(expected: str, query_token: Optional[str], authorization: Optional[str]) -> bool:
+    """Whether a request carries the status token, as `?token=` or a bearer token."""
+    token = query_token or bearer_token(authorization)
+    return token is not None and hmac.compare_digest(token.encode(), expected.encode())
+
+
+def render_status_html(status: Mapping[str, Any], now: Optional[float] = None) -> str:
+    """`status` (as built by the router) as a page that fits a phone screen."""
+    now = time.time() if now is None else now
+
+    def ago(moment: Optional[float]) -> str:
+        return f"{format_duration(now - moment)} ago" if moment else "never"
+
+    def rows(items: List[List[str]]) -> str:
+        return "".join("<tr>" + "".join(f"<td>{html.escape(cell)}</td>" for cell in row) + "</tr>" for row in items)
+
+    def connection(connected: Optional[bool]) -> str:
+        return "not tracked" if connected is None else "connected" if connected else "DISCONNECTED"
+
+    state = "OK" if status["ok"] else "Needs attention"
+    adapters = [
+        [
+            adapter["name"],
+            connection(adapter["connected"]),
+            f"last event {ago(adapter.get('last_event_at'))}" if "last_event_at" in adapter else "",
+        ]
+        for adapter in status["adapters"]
+    ]
+    adapters.append(["GitHub", "configured" if status["github_configured"] else "not configured", ""])
+    projects = [
+        [
+            project["id"],
+            f"{project['running']} running",
+            f"{project['queued']} queued",
+            "incident mode" if project.get("incident") else "",
+        ]
+        for project in status["projects"]
+    ]
+    runs = [
+        [run["project"] or "?", run["agent"] or "?", format_duration(now - run["started_at"]), run["thread"] or ""]
+        for run in status["active_runs"]
+    ]
+    errors = [[error["integration"], ago(error["at"]), error["message"]] for error in status["errors"]]
+    sections = [
+        ("Connections", adapters, "None"),
+        ("Projects", projects, "No projects"),
+        ("Runs in progress", runs, "None"),
+        ("Last errors", errors, "None since startup"),
+    ]
+    body = "".join(
+        f"<h2>{title}</h2>" + (f"<table>{rows(items)}</table>" if items else f"<p>{empty}</p>")
+        for title, items, empty in sections
+    )
+    return (
+        "<!doctype html><html><head><meta charset=\"utf-8\">"
+        "<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">"
+        f"<meta http-equiv=\"refresh\" content=\"{REFRESH_SECS}\"><title>Remote Coder: {state}</title>"
+        "<style>body{font-family:sans-serif;margin:1em}td{padding:.2em .6em .2em 0;vertical-align:top}"
+        "h2{font-size:1.1em;margin-top:1.2em}</style></head><body>"
+        f"<h1>Remote Coder: {html.escape(state)}</h1>"
+        f"<p>Version {html.escape(status['version'])}, up {format_duration(now - status['started_at'])}</p>"
+        f"{body}</body></html>"
+    )
+
+
+class StatusServer:
+    """Serves `GET /status` and `GET /status.json` to requests carrying the status token."""
+
+    def __init__(self, host: str, port: int, token: str, get_status: StatusFn) -> None:
+        self._host = host
+        self._port = port
+        self._token = token
+        self._get_status = get_status
+        self._runner: Optional[web.AppRunner] = None
+
+    async def start(self) -> None:
+        app = web.Application()
+        app.router.add_get(STATUS_ROUTE, self._handle_html)
+        app.router.add_get(STATUS_JSON_ROUTE, self._handle_json)
+        self._runner = web.AppRunner(app)
+        await self._runner.setup()
+        await web.TCPSite(self._runner, self._host, self._port).start()
+        LOGGER.info("Serving the status page on http://%s:%d%s", self._host, self._port, STATUS_ROUTE)
+
+    async def stop(self) -> None:
+        if self._runner:
+            await self._runner.cleanup()
+            self._runner = None
+
+    def _authorized(self, request: web.Request) -> bool:
+        return token_matches(self._token, request.query.get("token"), request.headers.get("Authorization"))
+
+    async def _handle_html(self, request: web.Request) -> web.Response:
+        if not self._authorized(request):
+            return web.Response(status=401, text="Missing or wrong status token")
+        return web.Response(text=render_status_html(self._get_status()), content_type="text/html")
+
+    async def _handle_json(self, request: web.Request) -> web.Response:
+        if not self._authorized(request):
+            return web.json_response({"error": "missing or wrong status token"}, status=401)
+        return web.json_response(self._get_status(), dumps=lambda data: json.dumps(data, default=str))
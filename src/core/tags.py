@@ -0,0 +1,40 @@
+"""Tags on sessions (`!tag bugfix urgent`).
+
+Tags are short lowercase words saved with the session. `!sessions bugfix`
+and `remote-coder sessions list --tag bugfix` list only the sessions with
+every tag given, `!status` and `!export` show them, and they are added as
+labels to the PRs opened for the session, including ones already open when
+the tag is added. Removing a tag (`!untag`) leaves the labels on the PRs.
+"""
+
+from __future__ import annotations
+
+import re
+from typing import Iterable, List, Sequence, Tuple
+
+from .models import Session
+
+MAX_TAGS = 10
+_TAG = re.compile(r"[a-z0-9][a-z0-9._-]{0,31}")
+
+
+def parse_tags(words: Iterable[str]) -> Tuple[List[str], List[str]]:
+    """Split `words` into tags (lowercased, without a leading `#`, in order, once each) and the words that are not."""
+    tags: List[str] = []
+    invalid: List[str] = []
+    for word in words:
+        tag = word.strip().lstrip("#").lower()
+        if not _TAG.fullmatch(tag):
+            invalid.append(word)
+        elif tag not in tags:
+            tags.append(tag)
+    return tags, invalid
+
+
+def has_tags(session: Session, tags: Sequence[str]) -> bool:
+    return all(tag in session.tags for tag in tags)
+
+
+def format_tags(tags: Sequence[str]) -> str:
+    """E.g. "`bugfix` `urgent`"."""
+    return " ".join(f"`{tag}`" for tag in tags)
@@ -0,0 +1,23 @@
+"""One session per checkout: `max_active_sessions` and `!takeover`.
+
+All sessions of a project work in the same checkout, each on its own
+branch, so two of them interleaving runs can leave each other's changes on
+the wrong branch. A project with `max_active_sessions` in projects.yaml
+refuses to start more sessions than that (active and paused ones count;
+ask-mode and incident diagnosis sessions work in a checkout of their own and
+do not). The refused thread is told where the session holding the checkout
+is, and `!takeover` there ends it safely: a session with a run in progress
+is left alone, uncommitted changes are committed to the ended session's
+branch first, and its thread is told who took over.
+"""
+
+from __future__ import annotations
+
+TAKEOVER_COMMAND = "takeover"
+TAKEOVER_COMMIT_MESSAGE = "WIP: uncommitted changes parked by !takeover"
+# `ending` is "it" with one holder, else the least recently active one the takeover would end
+LOCKED_NOTE = (
+    "`{project}` already has {count} active session(s) on its checkout: {threads}. "
+    "Carry on there, or send `!takeover` here to end {ending} and start a session in this thread."
+)
+TAKEN_OVER_NOTE = "Session ended: `!takeover` in {thread} took over this project's checkout."
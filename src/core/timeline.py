@@ -0,0 +1,160 @@
+"""Checkpoints of a session's branch, one per agent run, for `!timeline` and `!checkout`.
+
+After each run in a git project the commit the worktree ended up at is
+recorded in the session context, with the run's prompt and the files it
+changed. `!timeline` lists them, and `!checkout <n>` detaches the worktree
+at checkpoint n so it can be looked at (files opened, tests run) without
+touching the session branch; `!checkout latest` goes back. Agent runs wait
+while a checkpoint is checked out, so nothing is committed on top of an old
+state. Unlike `!redo`, browsing never throws work away.
+"""
+
+from __future__ import annotations
+
+from dataclasses import asdict, dataclass, field
+from pathlib import Path
+from typing import Dict, List, Optional
+
+from .compare import _git
+from .models import Session
+
+# Session context key holding the recorded checkpoints, oldest first
+TIMELINE_CONTEXT_KEY = "timeline"
+# Session context key set while `!checkout` has the worktree at a checkpoint: {"run": n, "return_to": ref}
+CHECKED_OUT_CONTEXT_KEY = "checked_out_checkpoint"
+# Older checkpoints are dropped beyond this many
+MAX_CHECKPOINTS = 100
+PROMPT_SNIPPET_CHARS = 80
+LISTED_FILES = 3
+
+
+class TimelineError(Exception):
+    """The worktree cannot be moved, e.g. because it has uncommitted changes."""
+
+
+@dataclass(frozen=True)
+class Checkpoint:
+    run: int
+    prompt: str
+    commit: str  # Where the worktree was after the run
+    before: Optional[str] = None  # Where it was before the run
+    files: List[str] = field(default_factory=list)
+
+
+def load_timeline(session: Session) -> List[Checkpoint]:
+    raw = session.session_context.get(TIMELINE_CONTEXT_KEY)
+    if not isinstance(raw, list):
+        return []
+    checkpoints = []
+    for entry in raw:
+        try:
+            checkpoints.append(
+                Checkpoint(
+                    run=int(entry["run"]),
+                    prompt=str(entry.get("prompt") or ""),
+                    commit=str(entry["commit"]),
+                    before=entry.get("before"),
+                    files=[str(path) for path in entry.get("files") or []],
+                )
+            )
+        except (KeyError, TypeError, ValueError):
+            continue
+    return checkpoints
+
+
+def checked_out_run(session: Session) -> Optional[int]:
+    """The checkpoint `!checkout` moved the worktree to, if any."""
+    state = session.session_context.get(CHECKED_OUT_CONTEXT_KEY)
+    return int(state["run"]) if isinstance(state, dict) and state.get("run") else None
+
+
+async def changed_files(repo_path: Path, before: Optional[str], after: str) -> List[str]:
+    if not before or before == after:
+        return []
+    diff = await _git(repo_path, "diff", "--name-only", before, after, check=False)
+    return [line for line in diff.stdout.splitlines() if line.strip()] if diff.returncode == 0 else []
+
+
+def add_checkpoint(session: Session, prompt: str, before: Optional[str], commit: str, files: List[str]) -> Dict:
+    """The session context delta that records a run's checkpoint.
+
+    A run that starts where an earlier one did, rather than where the last
+    one ended (after `!redo` reset the branch), replaces that run and
+    everything after it.
+    """
+    checkpoints = load_timeline(session)
+    if before and checkpoints and before != checkpoints[-1].commit:
+        restarted = next((index for index, entry in enumerate(checkpoints) if entry.before == before), None)
+        if restarted is not None:
+            checkpoints = checkpoints[:restarted]
+    run = checkpoints[-1].run + 1 if checkpoints else 1
+    checkpoints.append(Checkpoint(run=run, prompt=prompt, commit=commit, before=before, files=files))
+    return {TIMELINE_CONTEXT_KEY: [asdict(entry) for entry in checkpoints[-MAX_CHECKPOINTS:]]}
+
+
+async def checkout_checkpoint(session: Session, checkpoint: Checkpoint) -> Dict:
+    """Detach the worktree at `checkpoint`; returns the session context delta. Raises TimelineError."""
+    repo_path = session.project_path
+    await _require_clean(repo_path)
+    state = session.session_context.get(CHECKED_OUT_CONTEXT_KEY)
+    return_to = state.get("return_to") if isinstance(state, dict) else None
+    if not return_to:
+        branch = await _git(repo_path, "rev-parse", "--abbrev-ref", "HEAD", check=False)
+        head = await _git(repo_path, "rev-parse", "HEAD", check=False)
+        name = branch.stdout.strip()
+        return_to = name if branch.returncode == 0 and name != "HEAD" else head.stdout.strip()
+    moved = await _git(repo_path, "checkout", "--detach", checkpoint.commit, check=False)
+    if moved.returncode != 0:
+        raise TimelineError(f"git could not check out `{checkpoint.commit[:7]}`: {moved.stderr.strip()}")
+    return {CHECKED_OUT_CONTEXT_KEY: {"run": checkpoint.run, "return_to": return_to}}
+
+
+async def return_to_latest(session: Session) -> str:
+    """Check out the ref `!checkout` left; returns its name. Raises TimelineError."""
+    state = session.session_context.get(CHECKED_OUT_CONTEXT_KEY)
+    return_to = state.get("return_to") if isinstance(state, dict) else None
+    if not return_to:
+        raise TimelineError("the worktree is not at a checkpoint")
+    await _require_clean(session.project_path)
+    moved = await _git(session.project_path, "checkout", return_to, check=False)
+    if moved.returncode != 0:
+        raise TimelineError(f"git could not check out `{return_to}`: {moved.stderr.strip()}")
+    return return_to
+
+
+async def _require_clean(repo_path: Path) -> None:
+    status = await _git(repo_path, "status", "--porcelain", check=False)
+    if status.returncode != 0:
+        raise TimelineError(f"`{repo_path}` is not a git repository")
+    if status.stdout.strip():
+        raise TimelineError("the worktree has uncommitted changes; commit them or run `!stash` first")
+
+
+def _snippet(prompt: str) -> str:
+    line = " ".join(prompt.split())
+    return line if len(line) <= PROMPT_SNIPPET_CHARS else line[: PROMPT_SNIPPET_CHARS - 1] + "…"
+
+
+def _files_text(files: List[str]) -> str:
+    if not files:
+        return "no changes"
+    listed = ", ".join(f"`{path}`" for path in files[:LISTED_FILES])
+    more = f" and {len(files) - LISTED_FILES} more" if len(files) > LISTED_FILES else ""
+    return f"{len(files)} file{'' if len(files) == 1 else 's'}: {listed}{more}"
+
+
+def format_timeline(checkpoints: List[Checkpoint], checked_out: Optional[int] = None) -> str:
+    """Reply for `!timeline`."""
+    if not checkpoints:
+        return "No checkpoints yet; one is recorded after each agent run in a git project."
+    lines = [f"Checkpoints of this session ({len(checkpoints)}):"]
+    for entry in checkpoints:
+        marker = "  ← checked out" if entry.run == checked_out else ""
+        lines.append(
+            f"{entry.run}. `{entry.commit[:7]}` “{_snippet(entry.prompt)}” — {_files_text(entry.files)}{marker}"
+        )
+    if checked_out is not None:
+        lines.append("`!checkout latest` returns to the session branch; agent runs wait until then.")
+    else:
+        lines.append("`!checkout <n>` moves the worktree to a checkpoint to look around; the branch is left as is.")
+    return "\n".join(lines)
@@ -0,0 +1,138 @@
+"""The full record of each session, kept on disk.
+
+Sessions keep only their last messages in memory (`history_limit`, fewer in
+low-memory mode), so everything that happens in a session is also appended
+to `<config dir>/transcripts/<session id>.jsonl` as it happens:
+
+- `prompt`: a request sent to the agent
+- `reply`: what the agent replied in the thread (its summary of the run)
+- `run`: how the run went: agent and model, whether it succeeded, exit code,
+  wall time, estimated cost, and the files it edited
+
+`SessionManager.transcript` reads it back, for features that need more than
+the in-memory history (context passing, export, search, replay).
+`REMOTE_CODER_DISABLE_TRANSCRIPTS=1` stops writing transcripts. Lines
+written before runs were recorded have no `kind` and are read as prompts
+and replies by their `role`.
+"""
+
+from __future__ import annotations
+
+import json
+import logging
+import os
+from dataclasses import asdict, dataclass, field
+from datetime import datetime, timezone
+from enum import Enum
+from pathlib import Path
+from typing import Any, Dict, List, Optional, Sequence
+from uuid import UUID
+
+from .models import ConversationMessage
+
+LOGGER = logging.getLogger(__name__)
+
+TRANSCRIPTS_DIR_NAME = "transcripts"
+
+
+class TranscriptKind(str, Enum):
+    PROMPT = "prompt"
+    REPLY = "reply"
+    RUN = "run"
+
+
+@dataclass(frozen=True)
+class RunRecord:
+    agent_id: str
+    success: bool
+    wall_secs: float
+    model: Optional[str] = None
+    exit_code: Optional[int] = None  # None for agents without a CLI process
+    cost_usd: Optional[float] = None  # None without pricing for the agent
+    files: List[str] = field(default_factory=list)  # Edited by the agent, as it reported them
+    cancelled: bool = False
+
+
+@dataclass(frozen=True)
+class TranscriptEntry:
+    kind: TranscriptKind
+    timestamp: datetime
+    content: str = ""  # The prompt or reply; empty for runs
+    run: Optional[RunRecord] = None
+
+
+_KIND_OF_ROLE = {"user": TranscriptKind.PROMPT, "assistant": TranscriptKind.REPLY}
+
+
+class TranscriptStore:
+    """Appends to and reads one JSON-lines file per session."""
+
+    def __init__(self, directory: Path) -> None:
+        self.directory = directory
+
+    def path_for(self, session_id: UUID) -> Path:
+        return self.directory / f"{session_id}.jsonl"
+
+    def append(self, session_id: UUID, message: ConversationMessage) -> None:
+        """Record a message of the session's history as a prompt or reply."""
+        kind = _KIND_OF_ROLE.get(message.role, TranscriptKind.REPLY)
+        self._write(
+            session_id,
+            {
+                "kind": kind.value,
+                "role": message.role,
+                "content": message.content,
+                "timestamp": message.timestamp.isoformat(),
+            },
+        )
+
+    def record_run(self, session_id: UUID, run: RunRecord, when: Optional[datetime] = None) -> None:
+        moment = when or datetime.now(timezone.utc)
+        self._write(session_id, {"kind": TranscriptKind.RUN.value, "timestamp": moment.isoformat(), **asdict(run)})
+
+    def read(self, session_id: UUID, kinds: Optional[Sequence[TranscriptKind]] = None) -> List[TranscriptEntry]:
+        """The session's entries, oldest first, optionally only those of `kinds`."""
+        try:
+            lines = self.path_for(session_id).read_text(encoding="utf-8").splitlines()
+        except FileNotFoundError:
+            return []
+        except OSError as exc:
+            LOGGER.warning("Failed to read the transcript of session %s: %s", session_id, exc)
+            return []
+        entries = []
+        for line in lines:
+            try:
+                entry = _parse(json.loads(line))
+            except (KeyError, TypeError, ValueError):
+                continue  # A line cut short by a crash
+            if kinds is None or entry.kind in kinds:
+                entries.append(entry)
+        return entries
+
+    def _write(self, session_id: UUID, data: Dict[str, Any]) -> None:
+        try:
+            self.directory.mkdir(parents=True, exist_ok=True)
+            # Transcripts can contain anything pasted into chat; keep them private
+            fd = os.open(self.path_for(session_id), os.O_WRONLY | os.O_CREAT | os.O_APPEND, 0o600)
+            with os.fdopen(fd, "a", encoding="utf-8") as fp:
+                fp.write(json.dumps(data) + "\n")
+        except OSError as exc:
+            LOGGER.warning("Failed to write transcript for session %s: %s", session_id, exc)
+
+
+def _parse(data: Dict[str, Any]) -> TranscriptEntry:
+    kind = TranscriptKind(data["kind"]) if "kind" in data else _KIND_OF_ROLE[data["role"]]
+    timestamp = datetime.fromisoformat(data["timestamp"])
+    if kind != TranscriptKind.RUN:
+        return TranscriptEntry(kind, timestamp, content=data["content"])
+    run = RunRecord(
+        agent_id=data["agent_id"],
+        success=bool(data["success"]),
+        wall_secs=float(data["wall_secs"]),
+        model=data.get("model"),
+        exit_code=data.get("exit_code"),
+        cost_usd=data.get("cost_usd"),
+        files=list(data.get("files") or []),
+        cancelled=bool(data.get("cancelled", False)),
+    )
+    return TranscriptEntry(kind, timestamp, run=run)
@@ -0,0 +1,82 @@
+"""Tell transient agent failures from real ones.
+
+A run that fails because the provider rate-limited it, answered with a 5xx,
+or could not be reached is likely to work a little later, so the runner
+tries it again with exponential backoff (`transient_retries`, waiting
+`retry_backoff_secs`, then twice that, up to `MAX_BACKOFF_SECS`). Anything
+else is a real error and fails the run as before, or goes to the agent's
+`max_retries`. Failures are recognised from the exception an adapter raised
+or, for runs that returned a failed result, from its errors, since CLI agents
+report provider errors in what they print.
+"""
+
+from __future__ import annotations
+
+import asyncio
+import re
+from enum import Enum
+from typing import Optional, Sequence
+
+DEFAULT_TRANSIENT_RETRIES = 2
+DEFAULT_RETRY_BACKOFF_SECS = 5.0
+MAX_BACKOFF_SECS = 120.0
+# Characters of the provider's error shown in the retry status
+MAX_DETAIL_CHARS = 160
+
+
+class TransientFailure(str, Enum):
+    RATE_LIMIT = "rate limit"
+    PROVIDER_ERROR = "provider error"
+    NETWORK = "network error"
+
+
+_PATTERNS = (
+    (TransientFailure.RATE_LIMIT, re.compile(r"\b429\b|rate[ _-]?limit|too many requests", re.IGNORECASE)),
+    (
+        TransientFailure.PROVIDER_ERROR,
+        re.compile(
+            r"\b(?:http|status|error|code)\W{0,3}5(?:0[0-4]|29)\b|\b5(?:0[0-4]|29),? message="
+            r"|overloaded|internal server error|bad gateway|service unavailable|gateway time-?out",
+            re.IGNORECASE,
+        ),
+    ),
+    (
+        TransientFailure.NETWORK,
+        re.compile(
+            r"connection (?:reset|refused|aborted|closed|error)|econnreset|econnrefused|etimedout|eai_again"
+            r"|socket hang up|network is unreachable|temporary failure in name resolution|could not resolve host"
+            r"|server disconnected|remote end closed connection",
+            re.IGNORECASE,
+        ),
+    ),
+)
+
+
+def classify_text(text: str) -> Optional[TransientFailure]:
+    """The kind of transient failure `text` (an error message or a CLI's output) reports, if any."""
+    for kind, pattern in _PATTERNS:
+        if pattern.search(text):
+            return kind
+    return None
+
+
+def classify_exception(exc: BaseException) -> Optional[TransientFailure]:
+    if isinstance(exc, (ConnectionError, asyncio.TimeoutError)):
+        return TransientFailure.NETWORK
+    return classify_text(str(exc))
+
+
+def classify_errors(errors: Sequence[str], output: str = "") -> Optional[TransientFailure]:
+    """Classify a failed result by its errors, falling back to the output when it has none."""
+    return classify_text("\n".join(errors) if errors else output)
+
+
+def backoff_delay(base_secs: float, retry: int) -> float:
+    """Seconds to wait before the `retry`th retry (1-based): `base_secs`, doubling each time, capped."""
+    return min(base_secs * 2 ** (retry - 1), MAX_BACKOFF_SECS)
+
+
+def short_detail(text: str) -> str:
+    """The first line of an error, shortened for the thread."""
+    line = text.strip().splitlines()[0] if text.strip() else ""
+    return line if len(line) <= MAX_DETAIL_CHARS else f"{line[: MAX_DETAIL_CHARS - 1]}…"
@@ -0,0 +1,267 @@
+"""Tell the operators when a newer Remote Coder release is out.
+
+Every `REMOTE_CODER_UPDATE_CHECK_HOURS` (default 24, 0 disables) the daemon
+reads the GitHub releases of remote-coder itself and, when one is newer than
+the installed version, posts a note to `REMOTE_CODER_UPDATE_CHANNEL` (a
+channel ID) or, without one, as a DM to `REMOTE_CODER_ALERT_USER_IDS`. The
+note has a condensed changelog of every release since the installed one and
+says whether any of them needs a config migration: release notes list those
+under a heading containing "migration" (e.g. `## Config migrations`) or on
+lines starting with `Migration:`. Drafts are skipped, and prereleases count
+only while a prerelease is installed. Each version is announced once; the
+last one announced is kept in `<config dir>/update_check.json`.
+"""
+
+from __future__ import annotations
+
+import asyncio
+import json
+import logging
+import re
+from dataclasses import dataclass, field
+from importlib import metadata
+from pathlib import Path
+from typing import Any, Awaitable, Callable, List, Optional, Sequence, Tuple
+
+import aiohttp
+
+from .privacy import PRIVACY, Egress, EgressBlocked
+
+LOGGER = logging.getLogger(__name__)
+
+PACKAGE_NAME = "remote-coder"
+RELEASES_REPO = "PeterShin23/remote-coder"
+RELEASES_URL = f"https://api.github.com/repos/{RELEASES_REPO}/releases"
+RELEASES_PAGE_URL = f"https://github.com/{RELEASES_REPO}/releases"
+UPDATE_STATE_FILE_NAME = "update_check.json"
+DEFAULT_UPDATE_CHECK_HOURS = 24
+REQUEST_TIMEOUT_SECS = 30
+# Changelog lines in a notice, across all the releases it covers
+MAX_CHANGELOG_LINES = 12
+
+_VERSION = re.compile(r"^v?(\d+)\.(\d+)(?:\.(\d+))?(?:[-.+]?([0-9A-Za-z][0-9A-Za-z.-]*))?$")
+_LIST_ITEM = re.compile(r"^\s*[-*+]\s+(.*\S)")
+_MIGRATION_LINE = re.compile(r"^\s*(?:[-*+]\s+)?\**migration\**\s*:\s*(.*\S)", re.IGNORECASE)
+
+VersionKey = Tuple[Any, ...]
+FetchFn = Callable[[], Awaitable[List["Release"]]]
+NotifyFn = Callable[[str], Awaitable[bool]]
+
+
+@dataclass
+class Release:
+    tag: str
+    url: str
+    body: str = ""
+    prerelease: bool = False
+    draft: bool = False
+
+    @property
+    def version(self) -> Optional[VersionKey]:
+        return version_key(self.tag)
+
+
+@dataclass
+class UpdateNotice:
+    """The releases newer than the installed version, newest first."""
+
+    current: str
+    releases: List[Release] = field(default_factory=list)
+
+    @property
+    def latest(self) -> Release:
+        return self.releases[0]
+
+    @property
+    def migrations(self) -> List[str]:
+        return [note for release in reversed(self.releases) for note in migration_notes(release.body)]
+
+
+def version_key(text: str) -> Optional[VersionKey]:
+    """A sortable key for `1.2.3`, `v1.2.3`, or `0.0.1-alpha.1`; prereleases sort before their release."""
+    match = _VERSION.match(text.strip())
+    if not match:
+        return None
+    major, minor, patch, pre = match.groups()
+    release = (int(major), int(minor), int(patch or 0))
+    if not pre:
+        return (*release, 1, ())
+    parts = tuple((0, int(part), "") if part.isdigit() else (1, 0, part) for part in re.split(r"[.-]", pre) if part)
+    return (*release, 0, parts)
+
+
+def is_prerelease(text: str) -> bool:
+    key = version_key(text)
+    return key is not None and key[3] == 0
+
+
+def installed_version() -> Optional[str]:
+    """The installed remote-coder version, or None when running from a source tree without installing it."""
+    try:
+        return metadata.version(PACKAGE_NAME)
+    except metadata.PackageNotFoundError:
+        return None
+
+
+def newer_releases(releases: Sequence[Release], current: str) -> List[Release]:
+    current_key = version_key(current)
+    if current_key is None:
+        return []
+    include_prereleases = is_prerelease(current)
+    newer = [
+        release
+        for release in releases
+        if not release.draft
+        and (include_prereleases or not release.prerelease)
+        and release.version is not None
+        and release.version > current_key
+    ]
+    return sorted(newer, key=lambda release: release.version or (), reverse=True)
+
+
+def migration_notes(body: str) -> List[str]:
+    """Lines of release notes that describe a needed config migration."""
+    notes = []
+    in_section = False
+    for line in body.splitlines():
+        stripped = line.strip()
+        if stripped.startswith("#"):
+            in_section = "migration" in stripped.lower()
+            continue
+        marked = _MIGRATION_LINE.match(line)
+        if marked:
+            notes.append(marked.group(1))
+        elif in_section and stripped:
+            item = _LIST_ITEM.match(line)
+            notes.append(item.group(1) if item else stripped)
+    return notes
+
+
+def changelog_lines(body: str) -> List[str]:
+    """The list items of release notes, outside their migration sections."""
+    lines = []
+    in_migrations = False
+    for line in body.splitlines():
+        stripped = line.strip()
+        if stripped.startswith("#"):
+            in_migrations = "migration" in stripped.lower()
+            continue
+        item = _LIST_ITEM.match(line)
+        if item and not in_migrations and not _MIGRATION_LINE.match(line):
+            lines.append(item.group(1))
+    return lines
+
+
+def format_update_notice(notice: UpdateNotice) -> str:
+    latest = notice.latest
+    lines = [f"Remote Coder {latest.tag} is available (this daemon runs {notice.current}): {latest.url}"]
+    changes = [(release.tag, line) for release in notice.releases for line in changelog_lines(release.body)]
+    if changes:
+        lines.append("")
+        lines.append(f"Changes since {notice.current}:")
+        tagged = len(notice.releases) > 1
+        for tag, line in changes[:MAX_CHANGELOG_LINES]:
+            lines.append(f"• {tag}: {line}" if tagged else f"• {line}")
+        if len(changes) > MAX_CHANGELOG_LINES:
+            lines.append(f"…and {len(changes) - MAX_CHANGELOG_LINES} more: {RELEASES_PAGE_URL}")
+    migrations = notice.migrations
+    lines.append("")
+    if migrations:
+        lines.append("Config migrations needed before upgrading:")
+        lines.extend(f"• {note}" for note in migrations)
+    else:
+        lines.append("No config migrations needed.")
+    return "\n".join(lines)
+
+
+async def fetch_releases(token: Optional[str] = None) -> List[Release]:
+    """The latest releases of remote-coder on GitHub; a token only raises the rate limit.
+
+    Raises EgressBlocked in strict privacy mode.
+    """
+    PRIVACY.require(Egress.UPDATE_CHECK)
+    headers = {"Accept": "application/vnd.github+json"}
+    if token:
+        headers["Authorization"] = f"Bearer {token}"
+    timeout = aiohttp.ClientTimeout(total=REQUEST_TIMEOUT_SECS)
+    async with aiohttp.ClientSession(timeout=timeout) as http:
+        async with http.get(RELEASES_URL, params={"per_page": "30"}, headers=headers) as response:
+            response.raise_for_status()
+            payload = await response.json()
+    if not isinstance(payload, list):
+        raise ValueError("GitHub returned releases in an unexpected shape")
+    return [
+        Release(
+            tag=str(item.get("tag_name") or ""),
+            url=str(item.get("html_url") or RELEASES_PAGE_URL),
+            body=str(item.get("body") or ""),
+            prerelease=bool(item.get("prerelease")),
+            draft=bool(item.get("draft")),
+        )
+        for item in payload
+        if isinstance(item, dict)
+    ]
+
+
+class UpdateChecker:
+    """Checks for newer releases now and then and announces each new version once."""
+
+    def __init__(
+        self,
+        current: str,
+        *,
+        fetch: FetchFn,
+        notify: NotifyFn,
+        state_path: Path,
+        interval_secs: float = DEFAULT_UPDATE_CHECK_HOURS * 3600,
+    ) -> None:
+        self._current = current
+        self._fetch = fetch
+        self._notify = notify
+        self._state_path = state_path
+        self._interval_secs = interval_secs
+
+    async def check(self) -> Optional[UpdateNotice]:
+        """Look for newer releases and announce them unless the newest was announced already."""
+        try:
+            releases = await self._fetch()
+        except (aiohttp.ClientError, asyncio.TimeoutError, ValueError, EgressBlocked) as exc:
+            LOGGER.warning("Could not check for Remote Coder updates: %s", exc)
+            return None
+        newer = newer_releases(releases, self._current)
+        if not newer:
+            return None
+        notice = UpdateNotice(current=self._current, releases=newer)
+        if self._announced() == notice.latest.tag:
+            return None
+        LOGGER.info("Remote Coder %s is available (running %s)", notice.latest.tag, self._current)
+        try:
+            delivered = await self._notify(format_update_notice(notice))
+        except Exception:
+            LOGGER.warning("Failed to deliver the update notice", exc_info=True)
+            return None
+        if delivered:
+            self._remember(notice.latest.tag)
+        return notice
+
+    async def run(self, stop_event: asyncio.Event) -> None:
+        while not stop_event.is_set():
+            await self.check()
+            try:
+                await asyncio.wait_for(stop_event.wait(), timeout=self._interval_secs)
+            except asyncio.TimeoutError:
+                continue
+
+    def _announced(self) -> Optional[str]:
+        try:
+            data = json.loads(self._state_path.read_text(encoding="utf-8"))
+        except (OSError, json.JSONDecodeError):
+            return None
+        announced = data.get("announced") if isinstance(data, dict) else None
+        return announced if isinstance(announced, str) else None
+
+    def _remember(self, tag: str) -> None:
+        try:
+            self._state_path.write_text(json.dumps({"announced": tag}) + "\n", encoding="utf-8")
+        except OSError as exc:
+            LOGGER.warning("Could not record the announced version in %s: %s", self._state_path, exc)
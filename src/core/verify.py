@@ -0,0 +1,125 @@
+"""Project checks (setup, verify, and teardown steps) run after changes are applied.
+
+Steps come from the repository's project config, so they are treated as
+untrusted and run through the project's sandbox (see `sandbox`).
+"""
+
+from __future__ import annotations
+
+import logging
+from dataclasses import dataclass
+from pathlib import Path
+from typing import List, Optional, Sequence
+
+from .models import CommandStep, Project
+from .sandbox import Sandbox
+
+LOGGER = logging.getLogger(__name__)
+
+DEFAULT_VERIFY_TIMEOUT_SECS = 600
+# Output kept per step when reporting results to chat
+MAX_STEP_OUTPUT_CHARS = 1500
+
+SETUP_PHASE = "setup"
+VERIFY_PHASE = "verify"
+TEARDOWN_PHASE = "teardown"
+
+
+@dataclass
+class VerifyStepResult:
+    command: str
+    returncode: int | None
+    output: str
+    timed_out: bool = False
+    phase: str = VERIFY_PHASE
+    timeout_secs: int = DEFAULT_VERIFY_TIMEOUT_SECS
+    truncated: bool = False
+
+    @property
+    def passed(self) -> bool:
+        return not self.timed_out and self.returncode == 0
+
+
+async def run_verify_steps(
+    steps: Sequence[CommandStep],
+    cwd: Path,
+    *,
+    sandbox: Optional[Sandbox] = None,
+    phase: str = VERIFY_PHASE,
+    timeout_secs: int = DEFAULT_VERIFY_TIMEOUT_SECS,
+    stop_on_failure: bool = True,
+) -> List[VerifyStepResult]:
+    """Run each step in order, by default stopping at the first failure."""
+    sandbox = sandbox or Sandbox()
+    results: List[VerifyStepResult] = []
+    for step in steps:
+        step_timeout = step.timeout_secs or timeout_secs
+        LOGGER.info("Running %s step in %s: %s", phase, cwd, step.run)
+        outcome = await sandbox.run(step.run, cwd, step_timeout, kind=phase)
+        result = VerifyStepResult(
+            command=step.run,
+            returncode=outcome.returncode,
+            output=outcome.output,
+            timed_out=outcome.timed_out,
+            phase=phase,
+            timeout_secs=step_timeout,
+            truncated=outcome.truncated,
+        )
+        results.append(result)
+        if stop_on_failure and not result.passed:
+            break
+    return results
+
+
+def has_project_checks(project: Project) -> bool:
+    return bool(project.setup_commands or project.verify_commands)
+
+
+async def run_project_checks(project: Project, cwd: Path) -> List[VerifyStepResult]:
+    """Run setup, then verify if setup passed, then teardown regardless of the outcome."""
+    sandbox = Sandbox(project.sandbox, project.dev_env)
+    results = await run_verify_steps(project.setup_commands, cwd, sandbox=sandbox, phase=SETUP_PHASE)
+    if all(result.passed for result in results):
+        results += await run_verify_steps(project.verify_commands, cwd, sandbox=sandbox, phase=VERIFY_PHASE)
+    results += await run_verify_steps(
+        project.teardown_commands,
+        cwd,
+        sandbox=sandbox,
+        phase=TEARDOWN_PHASE,
+        stop_on_failure=False,
+    )
+    return results
+
+
+def checks_passed(results: Sequence[VerifyStepResult]) -> bool:
+    # Teardown failures don't make a change wrong
+    return all(result.passed for result in results if result.phase != TEARDOWN_PHASE)
+
+
+def format_verify_results(results: Sequence[VerifyStepResult], title: str = "Verify steps") -> str:
+    """Render check results for a chat reply, including output of the failing step.
+
+    Teardown failures are listed but do not fail the run.
+    """
+    if not results:
+        return "No verify steps configured for this project."
+
+    lines = []
+    for result in results:
+        label = f"`{result.command}`" if result.phase == VERIFY_PHASE else f"`{result.command}` ({result.phase})"
+        if result.timed_out:
+            lines.append(f"- {label}: timed out after {result.timeout_secs}s")
+        elif result.passed:
+            lines.append(f"- {label}: passed")
+        else:
+            lines.append(f"- {label}: failed (exit code {result.returncode})")
+
+    checks = [result for result in results if result.phase != TEARDOWN_PHASE]
+    failed = next((result for result in checks if not result.passed), None)
+    if failed is None:
+        failed = next((result for result in results if not result.passed), None)
+    if failed and failed.output.strip():
+        tail = failed.output.strip()[-MAX_STEP_OUTPUT_CHARS:]
+        lines.append(f"```\n{tail}\n```")
+    header = f"{title} passed:" if all(r.passed for r in checks) else f"{title} failed:"
+    return "\n".join([header, *lines])
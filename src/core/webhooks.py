@@ -0,0 +1,176 @@
+"""HTTP endpoint that lets external systems trigger a project's `.cockpit` commands.
+
+A monitoring system can run `!diagnose service=payments` in a project with
+
+    POST /hooks/<project>/diagnose
+    Authorization: Bearer <token>
+
+    {"service": "payments"}
+
+The token and the commands it may trigger come from the project's `webhook:`
+settings in projects.yaml. The JSON body's fields become the command's
+arguments as `key=value` pairs. Each accepted request posts a new message in
+the project's channel and runs the command in a session on that thread, so
+anyone can follow up there; the response is `202` with the session ID and
+thread once the run has started. The endpoint is off unless
+`REMOTE_CODER_WEBHOOK_PORT` is set.
+
+The same endpoint takes GitHub's webhook deliveries at `POST /hooks/github`
+when `REMOTE_CODER_GITHUB_WEBHOOK_SECRET` is set (see `src/core/github_events.py`).
+"""
+
+from __future__ import annotations
+
+import hmac
+import json
+import logging
+import re
+from dataclasses import dataclass
+from typing import Awaitable, Callable, Dict, Mapping, Optional, Tuple
+
+from aiohttp import web
+
+from .config import Config
+from .models import Project
+
+LOGGER = logging.getLogger(__name__)
+
+WEBHOOK_ROUTE = "/hooks/{project}/{command}"
+GITHUB_EVENTS_ROUTE = "/hooks/github"
+MAX_BODY_BYTES = 64 * 1024
+# GitHub's deliveries carry the whole PR and repository
+MAX_GITHUB_BODY_BYTES = 1024 * 1024
+ARG_NAME = re.compile(r"^[A-Za-z0-9_.-]+$")
+
+# (project id, command name, Authorization header, body) -> (HTTP status, JSON payload)
+WebhookHandler = Callable[[str, str, Optional[str], bytes], Awaitable[Tuple[int, Dict[str, object]]]]
+# (X-GitHub-Event, X-Hub-Signature-256, body) -> (HTTP status, JSON payload)
+GitHubEventHandler = Callable[[Optional[str], Optional[str], bytes], Awaitable[Tuple[int, Dict[str, object]]]]
+
+
+class WebhookError(Exception):
+    """A webhook request was rejected; `status` is the HTTP status to answer with."""
+
+    def __init__(self, status: int, message: str) -> None:
+        super().__init__(message)
+        self.status = status
+
+
+@dataclass(frozen=True)
+class WebhookRequest:
+    project: Project
+    command: str
+    arguments: str  # Raw arguments for the command, e.g. `service=payments`
+
+
+def bearer_token(authorization: Optional[str]) -> Optional[str]:
+    scheme, _, token = (authorization or "").strip().partition(" ")
+    if scheme.lower() != "bearer":
+        return None
+    return token.strip() or None
+
+
+def format_arguments(args: Mapping[str, object]) -> str:
+    """`key=value` pairs in the body's order.
+
+    Strings with spaces or quotes are JSON-quoted, lists and objects become compact JSON, and nulls are dropped.
+    """
+    parts = []
+    for name, value in args.items():
+        if not ARG_NAME.match(name):
+            raise WebhookError(400, f"argument name {name!r} may only use letters, digits, `_`, `.` and `-`")
+        if value is None:
+            continue
+        if isinstance(value, bool):
+            text = "true" if value else "false"
+        elif isinstance(value, (int, float)):
+            text = str(value)
+        elif isinstance(value, str):
+            quote = not value or any(char.isspace() or char in "\"'" for char in value)
+            text = json.dumps(value) if quote else value
+        else:
+            text = json.dumps(value, separators=(",", ":"))
+        parts.append(f"{name}={text}")
+    return " ".join(parts)
+
+
+def parse_body(body: bytes) -> Dict[str, object]:
+    """The JSON object of arguments; an empty body means no arguments."""
+    if not body.strip():
+        return {}
+    try:
+        data = json.loads(body)
+    except (UnicodeDecodeError, ValueError) as exc:
+        raise WebhookError(400, f"body is not valid JSON: {exc}") from exc
+    if not isinstance(data, dict):
+        raise WebhookError(400, "body must be a JSON object of arguments")
+    return data
+
+
+def resolve_webhook(
+    config: Config, project_id: str, command: str, authorization: Optional[str], body: bytes
+) -> WebhookRequest:
+    """Authenticate a request and turn it into the command to run; raises WebhookError."""
+    project = config.projects.get(project_id)
+    # Unknown projects and projects without a webhook look the same to callers
+    if project is None or project.id != project_id or project.webhook is None:
+        raise WebhookError(404, f"project {project_id} has no webhook")
+    token = bearer_token(authorization)
+    if token is None or not hmac.compare_digest(token.encode(), project.webhook.token.encode()):
+        raise WebhookError(401, "missing or wrong bearer token")
+    if project.webhook.commands and command not in project.webhook.commands:
+        raise WebhookError(403, f"the webhook of project {project_id} may not trigger {command}")
+    return WebhookRequest(project=project, command=command, arguments=format_arguments(parse_body(body)))
+
+
+class WebhookServer:
+    """Serves `POST /hooks/<project>/<command>`, and `POST /hooks/github` with a `github_handler`."""
+
+    def __init__(
+        self, host: str, port: int, handler: WebhookHandler, github_handler: Optional[GitHubEventHandler] = None
+    ) -> None:
+        self._host = host
+        self._port = port
+        self._handler = handler
+        self._github_handler = github_handler
+        self._runner: Optional[web.AppRunner] = None
+
+    async def start(self) -> None:
+        app = web.Application(client_max_size=MAX_GITHUB_BODY_BYTES if self._github_handler else MAX_BODY_BYTES)
+        if self._github_handler:
+            app.router.add_post(GITHUB_EVENTS_ROUTE, self._handle_github)
+        app.router.add_post(WEBHOOK_ROUTE, self._handle)
+        self._runner = web.AppRunner(app)
+        await self._runner.setup()
+        await web.TCPSite(self._runner, self._host, self._port).start()
+        LOGGER.info("Listening for project webhooks on http://%s:%d", self._host, self._port)
+
+    async def stop(self) -> None:
+        if self._runner:
+            await self._runner.cleanup()
+            self._runner = None
+
+    async def _handle(self, request: web.Request) -> web.Response:
+        body = await request.read()
+        if len(body) > MAX_BODY_BYTES:
+            return web.json_response({"error": "body too large"}, status=413)
+        status, payload = await self._handler(
+            request.match_info["project"],
+            request.match_info["command"],
+            request.headers.get("Authorization"),
+            body,
+        )
+        if status >= 400:
+            LOGGER.warning("Rejected webhook %s: %s %s", request.path, status, payload.get("error"))
+        return web.json_response(payload, status=status)
+
+    async def _handle_github(self, request: web.Request) -> web.Response:
+        handler = self._github_handler
+        if handler is None:
+            return web.json_response({"error": "GitHub events are not set up"}, status=404)
+        status, payload = await handler(
+            request.headers.get("X-GitHub-Event"), request.headers.get("X-Hub-Signature-256"), await request.read()
+        )
+        if status >= 400:
+            LOGGER.warning("Rejected GitHub delivery %s: %s %s", request.path, status, payload.get("error"))
+        return web.json_response(payload, status=status)
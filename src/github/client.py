@@ -4,24 +4,45 @@ from __future__ import annotations
 
 import asyncio
 import logging
-import requests
-from dataclasses import dataclass
-from typing import Any, List, Optional
+from dataclasses import dataclass, field
+from typing import TYPE_CHECKING, Any, Dict, List, Optional
 from uuid import UUID
 
-from github import Github
-from github.PullRequest import PullRequest
-
 from ..core.errors import GitHubError
 from ..core.models import Project, PullRequestRef
 
+try:  # PyGithub (which brings requests) is installed by the `github` extra
+    import requests
+    from github import Github
+except ImportError:  # pragma: no cover - depends on the installed extras
+    requests = None  # type: ignore[assignment]
+    Github = None  # type: ignore[assignment,misc]
+
+if TYPE_CHECKING:
+    from github.PullRequest import PullRequest
+
 LOGGER = logging.getLogger(__name__)
 
+GRAPHQL_URL = "https://api.github.com/graphql"
+
+
+def _new_client(token: Optional[str]) -> Any:
+    if not token:
+        return None
+    if Github is None:
+        LOGGER.warning(
+            "GITHUB_TOKEN is set but PyGithub is not installed; install remote-coder[github] "
+            "to open pull requests. Projects run in local-only mode until then."
+        )
+        return None
+    return Github(token)
+
 
 @dataclass
 class EnsurePROptions:
     title: str
     body: str
+    labels: List[str] = field(default_factory=list)  # The session's tags; added to the PR whether it is new or not
 
 
 @dataclass
@@ -34,16 +55,19 @@ class PRComment:
 
 
 class GitHubManager:
-    """Wrapper around PyGithub that exposes async helpers."""
+    """Wrapper around PyGithub that exposes async helpers.
+
+    Without PyGithub installed the manager reports itself as not configured.
+    """
 
     def __init__(self, token: Optional[str]) -> None:
         self._token = token
-        self._client = Github(token) if token else None
+        self._client = _new_client(token)
 
     def update_token(self, token: Optional[str]) -> None:
         """Update the GitHub token and underlying client."""
         self._token = token
-        self._client = Github(token) if token else None
+        self._client = _new_client(token)
 
     def is_configured(self) -> bool:
         return self._client is not None
@@ -70,6 +94,12 @@ class GitHubManager:
             existing_number,
         )
 
+    async def add_labels(self, project: Project, pull_number: int, labels: List[str]) -> None:
+        await asyncio.to_thread(self._add_labels_sync, project, pull_number, labels)
+
+    async def close_pull_request(self, project: Project, pull_number: int) -> None:
+        await asyncio.to_thread(self._close_pull_request_sync, project, pull_number)
+
     async def get_unresolved_comments(
         self,
         project: Project,
@@ -81,6 +111,28 @@ class GitHubManager:
             pull_number,
         )
 
+    async def graphql(self, query: str, variables: Dict[str, Any]) -> Dict[str, Any]:
+        """Run a GraphQL query or mutation and return its `data`; raises GitHubError."""
+        return await asyncio.to_thread(self._graphql_sync, query, variables)
+
+    def _graphql_sync(self, query: str, variables: Dict[str, Any]) -> Dict[str, Any]:
+        if not self._token or requests is None:
+            raise GitHubError("GitHub token is not configured.")
+        try:
+            response = requests.post(
+                GRAPHQL_URL,
+                json={"query": query, "variables": variables},
+                headers={"Authorization": f"Bearer {self._token}", "Content-Type": "application/json"},
+                timeout=30,
+            )
+            response.raise_for_status()
+            data = response.json()
+        except Exception as exc:  # requests raises many exception types; callers only need GitHubError
+            raise GitHubError(f"GitHub GraphQL request failed: {exc}") from exc
+        if data.get("errors"):
+            raise GitHubError(f"GraphQL errors: {data['errors']}")
+        return data.get("data") or {}
+
     def _ensure_pull_request_sync(
         self,
         project: Project,
@@ -94,7 +146,7 @@ class GitHubManager:
         if not project.github:
             raise GitHubError(f"Project {project.id} is missing GitHub metadata.")
 
-        repo_name = f"{project.github.owner}/{project.github.repo}"
+        repo_name = project.github.full_name
         repo = self._client.get_repo(repo_name)
 
         pull = None
@@ -113,6 +165,12 @@ class GitHubManager:
                 head=branch,
                 base=project.github.default_base_branch,
             )
+        if options.labels:
+            try:
+                pull.add_to_labels(*options.labels)
+            except Exception as exc:  # pragma: no cover - PyGithub raises generic exceptions
+                # The PR is open either way; labels can be added again with `!tag`
+                LOGGER.warning("Could not label pull request #%s: %s", pull.number, exc)
 
         return PullRequestRef(
             project_id=project.id,
@@ -121,8 +179,27 @@ class GitHubManager:
             url=pull.html_url,
             head_branch=pull.head.ref,
             base_branch=pull.base.ref,
+            repo=repo_name,
         )
 
+    def _add_labels_sync(self, project: Project, pull_number: int, labels: List[str]) -> None:
+        pull = self._get_pull(project, pull_number)
+        try:
+            pull.add_to_labels(*labels)
+        except Exception as exc:  # pragma: no cover - PyGithub raises generic exceptions
+            raise GitHubError(f"Failed to label pull request #{pull_number}: {exc}") from exc
+
+    def _close_pull_request_sync(self, project: Project, pull_number: int) -> None:
+        pull = self._get_pull(project, pull_number)
+        if pull.is_merged():
+            raise GitHubError(f"Pull request #{pull.number} is already merged.")
+        if pull.state != "open":
+            return
+        try:
+            pull.edit(state="closed")
+        except Exception as exc:  # pragma: no cover - PyGithub raises generic exceptions
+            raise GitHubError(f"Failed to close pull request #{pull_number}: {exc}") from exc
+
     def _get_unresolved_comments_sync(
         self,
         project: Project,
@@ -187,7 +264,7 @@ class GitHubManager:
         }
 
         response = requests.post(
-            "https://api.github.com/graphql",
+            GRAPHQL_URL,
             json={"query": query, "variables": variables},
             headers={
                 "Authorization": f"Bearer {self._token}",
@@ -268,7 +345,7 @@ class GitHubManager:
             raise GitHubError("GitHub token is not configured.")
         if not project.github:
             raise GitHubError(f"Project {project.id} is missing GitHub metadata.")
-        repo_name = f"{project.github.owner}/{project.github.repo}"
+        repo_name = project.github.full_name
         repo = self._client.get_repo(repo_name)
         try:
             return repo.get_pull(pull_number)
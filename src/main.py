@@ -4,15 +4,63 @@ from __future__ import annotations
 
 import argparse
 import asyncio
+import getpass
+import json
 import logging
 import os
 import signal
+from datetime import datetime
 from pathlib import Path
 from typing import Sequence
+from uuid import UUID
 
+import yaml
+
+from .chat_adapters.github_discussions import GitHubDiscussionsAdapter
 from .chat_adapters.slack_adapter import SlackAdapter
 from .core import Config, ConfigError, Router, SessionManager, load_config
+from .core.agent_auth import auth_method_for
+from .core.archive import SessionArchive
+from .core.bench import (
+    BENCH_DIR,
+    BenchRun,
+    format_bench_report,
+    format_bench_run,
+    load_bench_suite,
+    select_contenders,
+)
+from .core.commands.hints import build_command_hints, discord_application_commands, slack_slash_commands
+from .core.commands.registry import iter_command_specs
 from .core.config import resolve_config_dir
+from .core.compare import ComparisonError
+from .core.discussions import DISCUSSION_CHANNEL_PREFIX
+from .core.errors import AgentNotFound, ArchiveError, ProjectNotFound
+from .core.idle import IDLE_CHECK_INTERVAL_SECS
+from .core.privacy import PRIVACY, Egress
+from .core.secrets import SecretStore
+from .core.session_store import (
+    SESSION_DB_FILE_NAME,
+    SQLITE,
+    SessionStore,
+    SessionStoreError,
+    SqliteSessionStore,
+    set_aside,
+)
+from .core.state import (
+    STATE_FILE_NAME,
+    STATE_SAVE_INTERVAL_SECS,
+    StateError,
+    StateStore,
+    export_archive,
+    import_archive,
+    restore_saved_session,
+    saved_sessions,
+)
+from .core.updates import UPDATE_STATE_FILE_NAME, UpdateChecker, fetch_releases, installed_version
+from .core.status_page import IntegrationErrors, StatusServer
+from .core.tags import has_tags, parse_tags
+from .core.transcripts import TranscriptStore
+from .core.webhooks import WebhookServer
 from .github import GitHubManager
 
 LOGGER = logging.getLogger(__name__)
@@ -76,6 +124,86 @@ def cli(argv: Sequence[str] | None = None) -> int:
     projects_subparsers.add_parser("remove", help="Remove a project")
     projects_subparsers.add_parser("edit", help="Edit a project")
 
+    # State migration subcommands
+    export_parser = subparsers.add_parser(
+        "export-state",
+        help="Export sessions, transcripts, and PR refs to a portable archive",
+    )
+    export_parser.add_argument("--config-dir", help="Config directory (default: ~/.remote-coder)")
+    export_parser.add_argument(
+        "-o",
+        "--output",
+        help="Archive to write (default: remote-coder-state-<timestamp>.tar.gz)",
+    )
+    import_parser = subparsers.add_parser(
+        "import-state",
+        help="Import an archive written by export-state (run while the daemon is stopped)",
+    )
+    import_parser.add_argument("archive", help="Archive written by export-state")
+    import_parser.add_argument("--config-dir", help="Config directory (default: ~/.remote-coder)")
+    import_parser.add_argument(
+        "--force",
+        action="store_true",
+        help="Replace existing daemon state (the old file is kept as state.json.bak)",
+    )
+
+    # Archived and ended sessions
+    sessions_parser = subparsers.add_parser(
+        "sessions",
+        help="List archived and ended sessions and restore them (run while the daemon is stopped)",
+    )
+    sessions_subparsers = sessions_parser.add_subparsers(dest="sessions_command", help="Session operations")
+    sessions_list_parser = sessions_subparsers.add_parser("list", help="List archived and ended sessions")
+    sessions_list_parser.add_argument("--all", action="store_true", help="Include active sessions")
+    sessions_list_parser.add_argument(
+        "--tag", action="append", default=[], help="Only sessions with this tag (repeat for several)"
+    )
+    sessions_list_parser.add_argument("--config-dir", help="Config directory (default: ~/.remote-coder)")
+    sessions_restore_parser = sessions_subparsers.add_parser(
+        "restore", help="Make an archived or ended session active again in its thread"
+    )
+    sessions_restore_parser.add_argument("session_id", help="Session ID, or a unique prefix of it")
+    sessions_restore_parser.add_argument("--config-dir", help="Config directory (default: ~/.remote-coder)")
+    sessions_fetch_parser = sessions_subparsers.add_parser(
+        "fetch", help="Download a session uploaded to the session archive (REMOTE_CODER_ARCHIVE_URL)"
+    )
+    sessions_fetch_parser.add_argument("session_id", help="Full session ID")
+    sessions_fetch_parser.add_argument("--output", help="Directory to write the Markdown and JSON to (default: .)")
+    sessions_fetch_parser.add_argument("--config-dir", help="Config directory (default: ~/.remote-coder)")
+
+    # Agent credentials
+    auth_parser = subparsers.add_parser(
+        "auth",
+        help="Store an agent's API key or token on this host (read by the daemon at startup)",
+    )
+    auth_parser.add_argument("agent", help="Agent id from agents.yaml")
+    auth_parser.add_argument("--config-dir", help="Config directory (default: ~/.remote-coder)")
+    auth_parser.add_argument("--remove", action="store_true", help="Forget the credentials stored for the agent")
+
+    # Chat command registrations
+    commands_parser = subparsers.add_parser(
+        "commands",
+        help="Print built-in and project commands as Slack slash commands or Discord application commands",
+    )
+    commands_parser.add_argument("--config-dir", help="Config directory (default: ~/.remote-coder)")
+    commands_parser.add_argument(
+        "--format",
+        choices=("slack", "discord"),
+        default="slack",
+        help="slack: manifest YAML to paste into the app config; discord: JSON for the bulk-overwrite endpoint",
+    )
+
+    # Agent benchmarks
+    bench_parser = subparsers.add_parser(
+        "bench",
+        help="Run a project's .cockpit/bench prompts against agents and report pass rate, time, and cost",
+    )
+    bench_parser.add_argument("project", help="Project id from projects.yaml")
+    bench_parser.add_argument("--agents", help="Comma-separated agent ids (default: every agent the project allows)")
+    bench_parser.add_argument("--cases", help="Comma-separated case names (default: the whole suite)")
+    bench_parser.add_argument("--config-dir", help="Config directory (default: ~/.remote-coder)")
+    bench_parser.add_argument("-o", "--output", help="Write the Markdown report here instead of printing it")
+
     args = parser.parse_args(argv)
 
     # Route to appropriate handler
@@ -104,6 +232,25 @@ def cli(argv: Sequence[str] | None = None) -> int:
         else:
             config_parser.print_help()
             return 1
+    elif args.command == "export-state":
+        return _export_state(args)
+    elif args.command == "import-state":
+        return _import_state(args)
+    elif args.command == "sessions":
+        if args.sessions_command == "list":
+            return _list_sessions(args)
+        if args.sessions_command == "restore":
+            return _restore_session(args)
+        if args.sessions_command == "fetch":
+            return _fetch_archived_session(args)
+        sessions_parser.print_help()
+        return 1
+    elif args.command == "auth":
+        return _store_agent_auth(args)
+    elif args.command == "commands":
+        return _print_command_hints(args)
+    elif args.command == "bench":
+        return _run_bench(args)
     else:
         # Default behavior: start daemon
         try:
@@ -122,12 +269,244 @@ def run() -> None:
     cli()
 
 
+def _export_state(args: argparse.Namespace) -> int:
+    try:
+        root = resolve_config_dir(args.config_dir)
+        config = load_config(root)
+        output = Path(
+            args.output or f"remote-coder-state-{datetime.now().strftime('%Y%m%d-%H%M%S')}.tar.gz"
+        ).expanduser()
+        count = export_archive(
+            root / STATE_FILE_NAME,
+            output,
+            projects=config.projects,
+            agent_ids=list(config.agents),
+            config_dir=root,
+        )
+    except (ConfigError, StateError, OSError) as exc:
+        print(f"Export failed: {exc}")
+        return 1
+    print(f"Exported {count} session(s) to {output}")
+    print("Stop the daemon before exporting to include its latest state; config files and .env are not included.")
+    return 0
+
+
+def _import_state(args: argparse.Namespace) -> int:
+    try:
+        root = resolve_config_dir(args.config_dir)
+        config = load_config(root)
+        report = import_archive(
+            Path(args.archive).expanduser(),
+            root / STATE_FILE_NAME,
+            projects=config.projects,
+            agent_ids=list(config.agents),
+            force=args.force,
+        )
+    except (ConfigError, StateError, OSError) as exc:
+        print(f"Import failed: {exc}")
+        return 1
+
+    source = f" from {report.source_host}" if report.source_host else ""
+    print(f"Imported {report.sessions} session(s) and {report.pr_refs} PR ref(s){source}.")
+    if report.backup_path:
+        print(f"Previous state kept at {report.backup_path}")
+        database = set_aside(root / SESSION_DB_FILE_NAME)
+        if database:
+            print(f"Previous session database kept at {database}")
+    if report.relocated_projects:
+        print("Pointed sessions at local paths for: " + ", ".join(report.relocated_projects))
+    if report.missing_projects:
+        print("Not in projects.yaml (sessions kept, but unusable until added): " + ", ".join(report.missing_projects))
+    if report.missing_agents:
+        print("Agents not configured here: " + ", ".join(report.missing_agents))
+    return 0
+
+
+def _list_sessions(args: argparse.Namespace) -> int:
+    try:
+        sessions = saved_sessions(resolve_config_dir(args.config_dir) / STATE_FILE_NAME)
+    except (StateError, OSError) as exc:
+        print(f"Listing sessions failed: {exc}")
+        return 1
+    if not args.all:
+        sessions = [session for session in sessions if not session.status.is_open]
+    tags, _ = parse_tags(args.tag)
+    sessions = [session for session in sessions if has_tags(session, tags)]
+    if not sessions:
+        print("No sessions to list." if args.all else "No archived or ended sessions.")
+        return 0
+    for session in sessions:
+        when = session.archived_at or session.updated_at
+        first = next((message.content for message in session.conversation_history if message.role == "user"), "")
+        snippet = " ".join(first.split())[:60]
+        print(f"{session.id}  {session.status.value:<8}  {session.project_id:<16}  {when:%Y-%m-%d %H:%M}  {snippet}")
+    return 0
+
+
+def _restore_session(args: argparse.Namespace) -> int:
+    try:
+        session = restore_saved_session(resolve_config_dir(args.config_dir) / STATE_FILE_NAME, args.session_id)
+    except (StateError, OSError) as exc:
+        print(f"Restore failed: {exc}")
+        return 1
+    print(f"Restored session {session.id} ({session.project_id}) in thread {session.thread_ts}.")
+    print("Its history, context, and PR refs are back; start the daemon and continue in the thread.")
+    return 0
+
+
+def _fetch_archived_session(args: argparse.Namespace) -> int:
+    try:
+        session_id = str(UUID(args.session_id))
+    except ValueError:
+        print(f"{args.session_id} is not a session ID; fetch needs the full ID.")
+        return 1
+    try:
+        config = load_config(resolve_config_dir(args.config_dir), require_slack=False)
+        if config.archive is None:
+            print("No session archive is set up; set REMOTE_CODER_ARCHIVE_URL in .env.")
+            return 1
+        PRIVACY.set_mode(config.privacy)
+        fetched = SessionArchive(config.archive).fetch(session_id)
+        if fetched is None:
+            print(f"Session {session_id} is not in the archive.")
+            return 1
+        output = Path(args.output or ".").expanduser()
+        output.mkdir(parents=True, exist_ok=True)
+        markdown, json_text = fetched
+        paths = [output / f"remote-coder-{session_id}.md", output / f"remote-coder-{session_id}.json"]
+        paths[0].write_text(markdown, encoding="utf-8")
+        paths[1].write_text(json_text, encoding="utf-8")
+    except (ConfigError, ArchiveError, OSError) as exc:
+        print(f"Fetch failed: {exc}")
+        return 1
+    print(f"Wrote {paths[0]} and {paths[1]}")
+    return 0
+
+
+def _store_agent_auth(args: argparse.Namespace) -> int:
+    try:
+        root = resolve_config_dir(args.config_dir)
+        store = SecretStore(root)
+        if args.remove:
+            removed = store.remove(args.agent)
+            print(f"Removed stored credentials for {args.agent}." if removed else f"Nothing stored for {args.agent}.")
+            return 0
+        agent = load_config(root, require_slack=False).get_agent(args.agent)
+        method = auth_method_for(agent)
+        if method is None or method.token_env is None:
+            print(f"{agent.id} does not need credentials.")
+            return 0
+        if os.environ.get(method.token_env):
+            print(f"{method.token_env} is set in the environment, which takes precedence over a stored token.")
+        token = getpass.getpass(f"{method.token_env} for {agent.id}: ").strip()
+        if not token:
+            print("No token entered; nothing stored.")
+            return 1
+        store.set(agent.id, method.token_env, token)
+    except (ConfigError, AgentNotFound, OSError) as exc:
+        print(f"Storing credentials failed: {exc}")
+        return 1
+    print(f"Stored {method.token_env} for {agent.id} in {store.path}.")
+    print("Restart the daemon or run `!reload-projects` in Slack to use it.")
+    return 0
+
+
+def _print_command_hints(args: argparse.Namespace) -> int:
+    try:
+        config = load_config(resolve_config_dir(args.config_dir), require_slack=False)
+    except ConfigError as exc:
+        print(f"Loading config failed: {exc}")
+        return 1
+    hints = build_command_hints(iter_command_specs(), config.projects.values())
+    if args.format == "discord":
+        print(json.dumps(discord_application_commands(hints), indent=2, ensure_ascii=False))
+    else:
+        manifest = {"features": {"slash_commands": slack_slash_commands(hints)}}
+        print(yaml.safe_dump(manifest, sort_keys=False, allow_unicode=True), end="")
+    return 0
+
+
+def _split_ids(raw: str | None) -> list[str]:
+    return [item.strip() for item in (raw or "").split(",") if item.strip()]
+
+
+def _run_bench(args: argparse.Namespace) -> int:
+    try:
+        config = load_config(resolve_config_dir(args.config_dir), require_slack=False)
+        project = config.get_project(args.project)
+        contenders = select_contenders(config, project, _split_ids(args.agents))
+        cases = load_bench_suite(project.path, _split_ids(args.cases))
+    except (ConfigError, ProjectNotFound) as exc:
+        print(f"Cannot run the benchmark: {exc}")
+        return 1
+    if not cases:
+        print(f"No bench cases in {project.path / BENCH_DIR}; add one Markdown prompt per file.")
+        return 1
+
+    async def _bench():
+        router = Router(SessionManager(), config, GitHubManager(config.github_token), config.config_dir)
+
+        async def _progress(run: BenchRun) -> None:
+            print(format_bench_run(run).replace("`", ""), flush=True)
+
+        try:
+            return await router.run_bench(project, contenders, cases, progress=_progress)
+        finally:
+            await router.close()
+
+    print(f"Benchmarking {', '.join(agent.id for agent, _ in contenders)} on {len(cases)} case(s)...")
+    try:
+        report = asyncio.run(_bench())
+    except ComparisonError as exc:
+        print(f"Benchmark failed: {exc}")
+        return 1
+    text = format_bench_report(report)
+    if args.output:
+        output = Path(args.output).expanduser()
+        output.write_text(text + "\n", encoding="utf-8")
+        print(f"Wrote the report to {output}")
+    else:
+        print(text)
+    return 0
+
+
+async def _save_state_periodically(store: StateStore, session_manager: SessionManager) -> None:
+    while True:
+        await asyncio.sleep(STATE_SAVE_INTERVAL_SECS)
+        try:
+            store.save(session_manager)
+        except OSError as exc:
+            LOGGER.warning("Failed to save daemon state: %s", exc)
+
+
+async def _expire_idle_sessions_periodically(router: Router) -> None:
+    while True:
+        await asyncio.sleep(IDLE_CHECK_INTERVAL_SECS)
+        try:
+            await router.expire_idle_sessions()
+        except Exception:  # Keep checking; one bad pass must not stop expiry for good
+            LOGGER.exception("Failed to end idle sessions")
+
+
+async def _clean_up_periodically(router: Router, interval_secs: int) -> None:
+    while True:
+        await asyncio.sleep(interval_secs)
+        try:
+            await router.clean_up()
+        except Exception:  # Keep cleaning up; one bad pass must not stop it for good
+            LOGGER.exception("Failed to clean up stale sessions")
+
+
 async def _run_async(config_dir: str | Path | None) -> None:
     logging.basicConfig(
         level=logging.INFO,
         format="%(asctime)s %(levelname)s %(name)s: %(message)s",
     )
 
+    # The status page shows the last error of each integration
+    integration_errors = IntegrationErrors()
+    logging.getLogger("src").addHandler(integration_errors)
+
     resolved_dir = resolve_config_dir(config_dir)
     LOGGER.info("Using config directory: %s", resolved_dir)
 
@@ -143,7 +522,29 @@ async def _run_async(config_dir: str | Path | None) -> None:
         len(config.agents),
     )
 
-    session_manager = SessionManager()
+    resources = config.resources
+    if resources.low_memory:
+        LOGGER.info("Low-memory mode: keeping %d message(s) per session in memory", resources.history_limit)
+    session_store = _open_session_store(config, resolved_dir)
+    session_manager = SessionManager(
+        resources.history_limit,
+        max_interactions=resources.max_interactions,
+        summarization=resources.summarization,
+        transcript=TranscriptStore(resources.transcript_dir) if resources.transcript_dir else None,
+        store=session_store,
+    )
+    try:
+        stored = session_manager.load_from_store()
+        if stored:
+            LOGGER.info("Restored %d session(s) from %s", stored, resolved_dir / SESSION_DB_FILE_NAME)
+    except SessionStoreError as exc:
+        LOGGER.warning("Starting without the sessions in the session database: %s", exc)
+    state_store = StateStore(resolved_dir / STATE_FILE_NAME)
+    try:
+        # Sessions written through to the database are fresher than the last save of the state file
+        state_store.load(session_manager, keep_newer=session_store is not None)
+    except StateError as exc:
+        LOGGER.warning("Starting without saved sessions: %s", exc)
     github_manager = GitHubManager(config.github_token)
     router = Router(session_manager, config, github_manager, resolved_dir)
     slack_adapter = SlackAdapter(
@@ -151,31 +552,148 @@ async def _run_async(config_dir: str | Path | None) -> None:
         app_token=config.slack_app_token,
         allowed_user_ids=config.slack_allowed_user_ids,
         router=router,
+        alert_user_ids=config.alert_user_ids,
+        alert_webhook_url=config.alert_webhook_url,
+        disconnect_alert_secs=config.disconnect_alert_secs,
+        cache_size=resources.cache_size,
+        app_id=config.slack_app_id,
+        config_token=config.slack_config_token,
     )
     router.bind_adapter(slack_adapter)
+    discussions_adapter = _discussions_adapter(config, github_manager, router)
+    if discussions_adapter:
+        router.bind_adapter(discussions_adapter, channel_prefix=DISCUSSION_CHANNEL_PREFIX)
+    await router.recover_interrupted_runs()
+    await router.announce_resumed_sessions()
+    await router.check_agent_clis()
+    await router.check_agent_credentials()
+    await router.sync_command_hints()
+    await router.index_sessions()
 
     loop = asyncio.get_running_loop()
     stop_event = asyncio.Event()
 
     def _request_shutdown() -> None:
-        LOGGER.info("Shutdown requested")
+        if stop_event.is_set():
+            LOGGER.info("Shutdown requested again; stopping the remaining runs now")
+            router.skip_grace_period()
+            return
+        LOGGER.info("Shutdown requested; press Ctrl+C again to stop running agents without waiting")
         stop_event.set()
 
     for sig in (signal.SIGINT, signal.SIGTERM):
         try:
             loop.add_signal_handler(sig, _request_shutdown)
         except NotImplementedError:
-            # Windows event loops before 3.11 do not support signal handlers.
-            pass
+            # Windows event loops have no signal handlers; Ctrl+C still arrives as SIGINT in the main thread
+            if sig == signal.SIGINT:
+                signal.signal(sig, lambda *_: loop.call_soon_threadsafe(_request_shutdown))
+
+    webhook_server = None
+    if config.webhook_port:
+        webhook_server = WebhookServer(
+            config.webhook_host, config.webhook_port, router.handle_webhook, github_handler=router.handle_github_event
+        )
+        await webhook_server.start()
+    status_server = None
+    if config.status_port and config.status_token:
+        status_server = StatusServer(
+            config.status_host,
+            config.status_port,
+            config.status_token,
+            lambda: router.status_snapshot(integration_errors.latest()),
+        )
+        await status_server.start()
 
     slack_task = asyncio.create_task(slack_adapter.start())
+    discussions_task = asyncio.create_task(discussions_adapter.start()) if discussions_adapter else None
+    save_task = asyncio.create_task(_save_state_periodically(state_store, session_manager))
+    idle_task = asyncio.create_task(_expire_idle_sessions_periodically(router)) if config.session_idle_hours else None
+    cleanup_task = (
+        asyncio.create_task(_clean_up_periodically(router, config.cleanup_interval_secs))
+        if config.cleanup_interval_secs
+        else None
+    )
+    update_checker = _update_checker(config, router)
+    update_task = asyncio.create_task(update_checker.run(stop_event)) if update_checker else None
     LOGGER.info("Remote Coder daemon started")
 
     await stop_event.wait()
+    save_task.cancel()
+    if idle_task:
+        idle_task.cancel()
+    if cleanup_task:
+        cleanup_task.cancel()
+    if update_task:
+        update_task.cancel()
+    if webhook_server:
+        await webhook_server.stop()
+    # Runs still post to their threads while they finish, so the chat connection stays up until they have
+    await router.drain()
+    if status_server:
+        await status_server.stop()
     await slack_adapter.stop()
     await slack_task
+    if discussions_adapter and discussions_task:
+        await discussions_adapter.stop()
+        await discussions_task
+    await router.close()
+    try:
+        state_store.save(session_manager)
+    except OSError as exc:
+        LOGGER.error("Failed to save daemon state on shutdown: %s", exc)
+    if session_store:
+        session_store.close()
     LOGGER.info("Shutdown complete")
 
 
+def _open_session_store(config: Config, config_dir: Path) -> SessionStore | None:
+    """The database sessions are written through to, unless REMOTE_CODER_SESSION_STORE is `memory`."""
+    if config.session_store != SQLITE:
+        return None
+    try:
+        return SqliteSessionStore(config_dir / SESSION_DB_FILE_NAME)
+    except SessionStoreError as exc:
+        LOGGER.warning("Keeping sessions in memory only (saved to the state file): %s", exc)
+        return None
+
+
+def _update_checker(config: Config, router: Router) -> UpdateChecker | None:
+    """The periodic check for newer releases, when it is on and its notice has somewhere to go."""
+    if not config.update_check_hours or not (config.update_channel or config.alert_user_ids):
+        return None
+    if not PRIVACY.allows(Egress.UPDATE_CHECK):
+        LOGGER.info("Not checking for updates: REMOTE_CODER_PRIVACY is strict")
+        return None
+    current = installed_version()
+    if current is None:
+        LOGGER.info("Not checking for updates: remote-coder is not installed as a package")
+        return None
+    return UpdateChecker(
+        current,
+        fetch=lambda: fetch_releases(config.github_token),
+        notify=router.announce_update,
+        state_path=config.config_dir / UPDATE_STATE_FILE_NAME,
+        interval_secs=config.update_check_hours * 3600,
+    )
+
+
+def _discussions_adapter(
+    config: Config, github_manager: GitHubManager, router: Router
+) -> GitHubDiscussionsAdapter | None:
+    """The GitHub Discussions adapter, when a project has `discussions:` and it can run."""
+    if not any(project.discussions for project in config.projects.values()):
+        return None
+    if not config.github_token:
+        LOGGER.warning("Projects have discussions enabled but GITHUB_TOKEN is not set; not polling discussions")
+        return None
+    if not config.github_allowed_users:
+        LOGGER.warning(
+            "Projects have discussions enabled but REMOTE_CODER_GITHUB_ALLOWED_USERS is empty; not polling discussions"
+        )
+        return None
+    return GitHubDiscussionsAdapter(github_manager, router, config)
+
+
 if __name__ == "__main__":
     raise SystemExit(cli())
@@ -0,0 +1,59 @@
+"""Tests for the analytics command handler."""
+
+from __future__ import annotations
+
+import pytest
+
+from src.core.analytics import AnalyticsStore
+from src.core.commands.analytics import AnalyticsCommandHandler
+from src.core.commands.parser import ParsedCommand
+from src.core.config import Config
+from src.core.transcripts import RunRecord
+
+
+def _config(test_project) -> Config:
+    return Config(
+        projects={test_project.id: test_project},
+        agents={},
+        slack_bot_token="x",
+        slack_app_token="y",
+        slack_allowed_user_ids=[],
+        base_dir=test_project.path,
+        config_dir=test_project.path,
+    )
+
+
+class TestAnalyticsCommand:
+    """Test cases for the !analytics command."""
+
+    @pytest.mark.asyncio
+    async def test_reports_per_agent_and_project(
+        self, tmp_path, test_project, session_manager, command_context, mock_send_message
+    ):
+        store = AnalyticsStore(tmp_path / "analytics.db")
+        store.follow(session_manager.events)
+        session = command_context.session
+        session_manager.record_run(session.id, RunRecord(agent_id="claude", success=True, wall_secs=1.0))
+        handler = AnalyticsCommandHandler(
+            config=_config(test_project), analytics=lambda: store, send_message=mock_send_message
+        )
+
+        await handler.handle_analytics(ParsedCommand(name="analytics", args=[]), command_context)
+        await handler.handle_analytics(ParsedCommand(name="analytics", args=["soon"]), command_context)
+
+        report, usage = [message["text"] for message in mock_send_message.messages]
+        assert report.splitlines()[0] == "*Outcomes, sessions of the last 30 day(s)*"
+        assert "- `claude`: 1 session(s), ~1.0 turn(s) each · runs 100% succeeded (1/1)" in report
+        assert f"- `{test_project.id}` (default `{test_project.default_agent_id}`): 1 session(s)" in report
+        assert usage.startswith("Usage: `!analytics [days|all]`")
+
+    @pytest.mark.asyncio
+    async def test_says_when_there_is_nothing_yet(self, tmp_path, test_project, command_context, mock_send_message):
+        store = AnalyticsStore(tmp_path / "analytics.db")
+        handler = AnalyticsCommandHandler(
+            config=_config(test_project), analytics=lambda: store, send_message=mock_send_message
+        )
+
+        await handler.handle_analytics(ParsedCommand(name="analytics", args=["all"]), command_context)
+
+        assert mock_send_message.messages[-1]["text"] == "No sessions to report on yet."
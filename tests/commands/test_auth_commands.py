@@ -0,0 +1,130 @@
+"""Tests for the !auth command handler."""
+
+from __future__ import annotations
+
+import asyncio
+from unittest.mock import AsyncMock
+
+import pytest
+
+from src.core.agent_auth import AUTH_METHODS
+from src.core.commands.auth import AuthCommandHandler
+from src.core.commands.parser import parse_command
+
+
+class FakeLogin:
+    def __init__(self, agent_id: str, succeed: bool = True) -> None:
+        self.agent_id = agent_id
+        self.output = ["Error: code expired"]
+        self.finished = asyncio.Event()
+        self._succeed = succeed
+
+    async def wait(self) -> bool:
+        await self.finished.wait()
+        return self._succeed
+
+    def prompt(self) -> str:
+        return f"To log `{self.agent_id}` in, open https://auth.example.com and enter the code `ABCD-1234`."
+
+
+@pytest.fixture(autouse=True)
+def no_credentials(monkeypatch, tmp_path):
+    for method in AUTH_METHODS.values():
+        for name in method.env_vars:
+            monkeypatch.delenv(name, raising=False)
+    monkeypatch.delenv("CODEX_HOME", raising=False)
+    monkeypatch.setenv("HOME", str(tmp_path))
+
+
+def _handler(test_config, mock_send_message, *, operators=True, login=None):
+    notify = AsyncMock(return_value=operators)
+    start_login = AsyncMock(return_value=login or FakeLogin("codex"))
+    handler = AuthCommandHandler(
+        config=test_config,
+        notify_operators=notify,
+        send_message=mock_send_message,
+        start_login=start_login,
+    )
+    return handler, notify, start_login
+
+
+class TestAuthCommand:
+    """Test cases for the !auth command."""
+
+    @pytest.mark.asyncio
+    async def test_lists_agent_credentials(self, test_config, command_context, mock_send_message):
+        test_config.agents["codex"].env["OPENAI_API_KEY"] = "sk-test"
+        handler, _, _ = _handler(test_config, mock_send_message)
+        await handler.handle_auth(parse_command("!auth"), command_context)
+
+        text = mock_send_message.messages[-1]["text"]
+        assert "`codex`: found (OPENAI_API_KEY)" in text
+        assert "`claude`:" in text
+
+    @pytest.mark.asyncio
+    async def test_unknown_agent(self, test_config, command_context, mock_send_message):
+        handler, _, start_login = _handler(test_config, mock_send_message)
+        await handler.handle_auth(parse_command("!auth nope"), command_context)
+
+        assert "Unknown agent `nope`" in mock_send_message.messages[-1]["text"]
+        start_login.assert_not_awaited()
+
+    @pytest.mark.asyncio
+    async def test_agent_without_device_login_gets_instructions(
+        self, test_config, command_context, mock_send_message, monkeypatch
+    ):
+        monkeypatch.setenv("CLAUDE_CONFIG_DIR", str(test_config.config_dir))
+        handler, _, start_login = _handler(test_config, mock_send_message)
+        await handler.handle_auth(parse_command("!auth claude"), command_context)
+
+        text = mock_send_message.messages[-1]["text"]
+        assert "claude setup-token" in text
+        start_login.assert_not_awaited()
+
+    @pytest.mark.asyncio
+    async def test_device_login_link_goes_to_operators(self, test_config, command_context, mock_send_message):
+        login = FakeLogin("codex")
+        handler, notify, _ = _handler(test_config, mock_send_message, login=login)
+        await handler.handle_auth(parse_command("!auth codex"), command_context)
+
+        notify.assert_awaited_once_with(login.prompt())
+        assert "to the operators by DM" in mock_send_message.messages[-1]["text"]
+        assert "ABCD-1234" not in mock_send_message.messages[-1]["text"]
+
+        login.finished.set()
+        await handler._logins["codex"]
+        assert mock_send_message.messages[-1]["text"] == "`codex` is logged in."
+
+    @pytest.mark.asyncio
+    async def test_device_login_without_operators_replies_in_thread(
+        self, test_config, command_context, mock_send_message
+    ):
+        login = FakeLogin("codex", succeed=False)
+        handler, _, _ = _handler(test_config, mock_send_message, operators=False, login=login)
+        await handler.handle_auth(parse_command("!auth codex"), command_context)
+        assert "ABCD-1234" in mock_send_message.messages[-1]["text"]
+
+        login.finished.set()
+        await handler._logins["codex"]
+        assert "did not complete: Error: code expired" in mock_send_message.messages[-1]["text"]
+
+    @pytest.mark.asyncio
+    async def test_one_login_at_a_time(self, test_config, command_context, mock_send_message):
+        login = FakeLogin("codex")
+        handler, _, start_login = _handler(test_config, mock_send_message, login=login)
+        await handler.handle_auth(parse_command("!auth codex"), command_context)
+        await handler.handle_auth(parse_command("!auth codex"), command_context)
+
+        assert start_login.await_count == 1
+        assert "already waiting" in mock_send_message.messages[-1]["text"]
+        login.finished.set()
+        await handler._logins["codex"]
+
+    @pytest.mark.asyncio
+    async def test_reports_login_start_failure(self, test_config, command_context, mock_send_message):
+        handler, notify, start_login = _handler(test_config, mock_send_message)
+        start_login.side_effect = RuntimeError("`codex login --device-auth` did not print a verification link")
+        await handler.handle_auth(parse_command("!auth codex"), command_context)
+
+        assert "Could not start a login for `codex`" in mock_send_message.messages[-1]["text"]
+        notify.assert_not_awaited()
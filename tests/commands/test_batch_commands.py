@@ -0,0 +1,202 @@
+"""Tests for the !batch command handler."""
+
+from __future__ import annotations
+
+from dataclasses import replace
+from types import SimpleNamespace
+from unittest.mock import AsyncMock, MagicMock
+
+import pytest
+
+from src.agent_adapters import AgentResult
+from src.core.batch import BatchError, BatchPrMode, parse_batch
+from src.core.commands.batch import BatchCommandHandler
+from src.core.commands.parser import parse_command
+from src.core.models import WorkingDirMode
+from tests.conftest import git
+
+
+@pytest.fixture
+def git_context(command_context, tmp_path, git_identity):
+    """A command context whose session points at a git repository with one commit."""
+    repo = tmp_path / "repo"
+    repo.mkdir()
+    (repo / "notes.txt").write_text("start\n")
+    for args in (["init", "-q", "-b", "main"], ["add", "-A"], ["commit", "-q", "-m", "initial"]):
+        git(repo, *args)
+    return replace(command_context, session=replace(command_context.session, project_path=repo))
+
+
+async def _write_task_file(agent, model, workdir, prompt):
+    """Each task writes its own file; tasks mentioning notes also edit the shared file."""
+    name = prompt.split()[-1]
+    (workdir / f"{name}.txt").write_text(prompt + "\n")
+    if "notes" in prompt:
+        (workdir / "notes.txt").write_text(f"{prompt}\n")
+    return AgentResult(success=True, output_text=f"did {name}"), 0.25
+
+
+def _handler(test_config, session_manager, mock_send_message, run_agent=None, **kwargs):
+    git_workflow = MagicMock()
+    git_workflow.is_local_only.return_value = kwargs.pop("local_only", False)
+    post_diff = AsyncMock()
+    handler = BatchCommandHandler(
+        config=test_config,
+        session_manager=session_manager,
+        git_workflow=git_workflow,
+        run_agent=run_agent or _write_task_file,
+        post_diff=post_diff,
+        active_runs=kwargs.pop("active_runs", {}),
+        send_message=mock_send_message,
+        **kwargs,
+    )
+    return handler, git_workflow, post_diff
+
+
+class TestParseBatch:
+    """Test cases for reading tasks and options."""
+
+    def test_options_and_one_task_per_line(self):
+        request = parse_batch("--parallel 2 --pr combined\n- fix the typo\n\n2. bump requests\n* add a badge")
+
+        assert request.parallel == 2
+        assert request.pr_mode == BatchPrMode.COMBINED
+        assert request.tasks == ["fix the typo", "bump requests", "add a badge"]
+
+    def test_first_line_can_be_a_task(self):
+        assert parse_batch("fix the typo\nbump requests").tasks == ["fix the typo", "bump requests"]
+
+    @pytest.mark.parametrize(
+        "raw,message",
+        [
+            ("--parallel 9\ntask", "from 1 to 4"),
+            ("--pr all\ntask", "none, separate, or combined"),
+            ("--retries 2\ntask", "Unknown option `--retries`"),
+            ("--parallel", "needs a value"),
+            ("\n".join(f"task {n}" for n in range(21)), "at most 20 tasks"),
+        ],
+    )
+    def test_rejects_invalid_requests(self, raw, message):
+        with pytest.raises(BatchError, match=message):
+            parse_batch(raw)
+
+
+class TestBatchCommand:
+    """Test cases for the !batch command."""
+
+    @pytest.mark.asyncio
+    async def test_runs_each_task_on_its_own_branch(
+        self, git_context, test_config, session_manager, mock_send_message
+    ):
+        handler, _, post_diff = _handler(test_config, session_manager, mock_send_message)
+        repo = git_context.session.project_path
+
+        await handler.handle_batch(parse_command("!batch --parallel 2\nwrite alpha\nwrite beta"), git_context)
+
+        texts = [message["text"] for message in mock_send_message.messages]
+        assert "Running 2 task(s) with `claude` in separate worktrees, 2 at a time" in texts[0]
+        results = sorted(text.split(":")[0] for text in texts[1:3])
+        assert results == ["*Task 1/2* `write alpha`", "*Task 2/2* `write beta`"]
+        assert "Nothing was published" in texts[-1]
+        assert len(post_diff.await_args_list) == 2
+        branches = git(repo, "branch", "--list", "remote-coder-batch-*").split()
+        assert len(branches) == 2
+        assert git(repo, "show", f"{branches[0]}:alpha.txt") == "write alpha\n"
+        assert git(repo, "status", "--porcelain") == "" and not (repo / "alpha.txt").exists()
+        assert session_manager.get_session(git_context.session.id).estimated_cost_usd == 0.5
+
+    @pytest.mark.asyncio
+    async def test_tasks_without_changes_leave_no_branch(
+        self, git_context, test_config, session_manager, mock_send_message
+    ):
+        async def _no_changes(agent, model, workdir, prompt):
+            return AgentResult(success=True, output_text="nothing to do"), None
+
+        handler, _, _ = _handler(test_config, session_manager, mock_send_message, run_agent=_no_changes)
+
+        await handler.handle_batch(parse_command("!batch look around"), git_context)
+
+        assert "changed nothing" in mock_send_message.messages[1]["text"]
+        assert mock_send_message.messages[-1]["text"] == "No task changed anything."
+        assert git(git_context.session.project_path, "branch", "--list", "remote-coder-batch-*") == ""
+
+    @pytest.mark.asyncio
+    async def test_separate_prs(self, git_context, test_config, session_manager, mock_send_message):
+        handler, git_workflow, _ = _handler(test_config, session_manager, mock_send_message)
+        head = git(git_context.session.project_path, "rev-parse", "HEAD").strip()
+        git_workflow.fetch_base_commit = AsyncMock(return_value=head)
+        git_workflow.publish_branch = AsyncMock(
+            side_effect=lambda session, project, branch, title, body: SimpleNamespace(url=f"https://pr/{title}")
+        )
+
+        await handler.handle_batch(parse_command("!batch --pr separate\nwrite alpha\nwrite beta"), git_context)
+
+        titles = [call.args[3] for call in git_workflow.publish_branch.await_args_list]
+        assert titles == ["write alpha", "write beta"]
+        assert "- write alpha" in git_workflow.publish_branch.await_args_list[0].args[4]
+        assert "Task 2 `write beta`: https://pr/write beta" in mock_send_message.messages[-1]["text"]
+
+    @pytest.mark.asyncio
+    async def test_combined_pr_leaves_out_conflicting_tasks(
+        self, git_context, test_config, session_manager, mock_send_message
+    ):
+        handler, git_workflow, _ = _handler(test_config, session_manager, mock_send_message)
+        repo = git_context.session.project_path
+        git_workflow.fetch_base_commit = AsyncMock(return_value=git(repo, "rev-parse", "HEAD").strip())
+        git_workflow.publish_branch = AsyncMock(return_value=SimpleNamespace(url="https://pr/1"))
+        tasks = "write alpha\nchange notes to beta\nchange notes to gamma"
+
+        await handler.handle_batch(parse_command(f"!batch --pr combined\n{tasks}"), git_context)
+
+        session, project, branch, title, body = git_workflow.publish_branch.await_args.args
+        assert title == "Batch: 2 small change(s)"
+        assert "- write alpha\n- change notes to beta" in body and "gamma" not in body
+        assert git(repo, "show", f"{branch}:notes.txt") == "change notes to beta\n"
+        summary = mock_send_message.messages[-1]["text"]
+        assert "Opened a combined PR with 2 task(s): https://pr/1" in summary
+        assert "Left out task(s) 3" in summary
+
+    @pytest.mark.asyncio
+    async def test_prs_need_github(self, git_context, test_config, session_manager, mock_send_message):
+        handler, _, _ = _handler(test_config, session_manager, mock_send_message, local_only=True)
+
+        await handler.handle_batch(parse_command("!batch --pr separate\nwrite alpha"), git_context)
+
+        assert "needs GitHub configured" in mock_send_message.messages[-1]["text"]
+
+    @pytest.mark.asyncio
+    async def test_usage_without_tasks(self, command_context, test_config, session_manager, mock_send_message):
+        handler, _, _ = _handler(test_config, session_manager, mock_send_message)
+
+        await handler.handle_batch(parse_command("!batch --parallel 2"), command_context)
+
+        assert "Usage: `!batch" in mock_send_message.messages[-1]["text"]
+
+    @pytest.mark.asyncio
+    async def test_refuses_fixed_directory_agents(
+        self, command_context, test_config, session_manager, mock_send_message
+    ):
+        test_config.agents["claude"].working_dir_mode = WorkingDirMode.FIXED
+        handler, _, _ = _handler(test_config, session_manager, mock_send_message)
+
+        await handler.handle_batch(parse_command("!batch write alpha"), command_context)
+
+        assert "cannot run in worktrees" in mock_send_message.messages[-1]["text"]
+
+    @pytest.mark.asyncio
+    async def test_refuses_during_agent_run(self, command_context, test_config, session_manager, mock_send_message):
+        active_runs = {"run": {"session_id": str(command_context.session.id)}}
+        handler, _, _ = _handler(test_config, session_manager, mock_send_message, active_runs=active_runs)
+
+        await handler.handle_batch(parse_command("!batch write alpha"), command_context)
+
+        assert "still running" in mock_send_message.messages[-1]["text"]
+
+    @pytest.mark.asyncio
+    async def test_reports_non_git_project(self, command_context, test_config, session_manager, mock_send_message):
+        handler, _, post_diff = _handler(test_config, session_manager, mock_send_message)
+
+        await handler.handle_batch(parse_command("!batch write alpha"), command_context)
+
+        assert "Could not run the batch" in mock_send_message.messages[-1]["text"]
+        post_diff.assert_not_awaited()
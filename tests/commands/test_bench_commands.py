@@ -0,0 +1,117 @@
+"""Tests for the !bench command handler."""
+
+from __future__ import annotations
+
+import subprocess
+from dataclasses import replace
+from unittest.mock import AsyncMock
+
+import pytest
+
+from src.agent_adapters import AgentResult
+from src.core.commands.bench import BenchCommandHandler
+from src.core.commands.parser import parse_command
+from src.core.models import CommandStep
+
+
+@pytest.fixture
+def bench_context(command_context, tmp_path, monkeypatch):
+    """A session in a git repository with two bench cases and a verify step."""
+    for key, value in {
+        "GIT_AUTHOR_NAME": "Test",
+        "GIT_AUTHOR_EMAIL": "test@example.com",
+        "GIT_COMMITTER_NAME": "Test",
+        "GIT_COMMITTER_EMAIL": "test@example.com",
+    }.items():
+        monkeypatch.setenv(key, value)
+    repo = tmp_path / "repo"
+    repo.mkdir()
+    for args in (["init", "-q", "-b", "main"], ["commit", "-q", "--allow-empty", "-m", "initial"]):
+        subprocess.run(["git", *args], cwd=repo, check=True, capture_output=True)
+    bench = repo / ".cockpit" / "bench"
+    bench.mkdir(parents=True)
+    (bench / "feature.md").write_text("Add the feature.")
+    (bench / "docs.md").write_text("Document it.")
+    project = replace(command_context.project, path=repo, verify_commands=[CommandStep(run="test -f claude.txt")])
+    return replace(command_context, project=project, session=replace(command_context.session, project_path=repo))
+
+
+def _handler(test_config, session_manager, mock_send_message, **kwargs):
+    async def run_agent(agent, model, workdir, prompt):
+        (workdir / f"{agent.id}.txt").write_text(prompt)
+        return AgentResult(success=True, output_text="done"), 0.25
+
+    upload = AsyncMock(return_value=True)
+    handler = BenchCommandHandler(
+        config=test_config,
+        session_manager=session_manager,
+        run_agent=run_agent,
+        upload_file=upload,
+        active_runs=kwargs.pop("active_runs", {}),
+        send_message=mock_send_message,
+        **kwargs,
+    )
+    return handler, upload
+
+
+class TestBenchCommand:
+    """Test cases for the !bench command."""
+
+    @pytest.mark.asyncio
+    async def test_runs_suite_and_uploads_report(self, bench_context, test_config, session_manager, mock_send_message):
+        handler, upload = _handler(test_config, session_manager, mock_send_message)
+
+        await handler.handle_bench(parse_command("!bench"), bench_context)
+
+        texts = [message["text"] for message in mock_send_message.messages]
+        assert texts[0].startswith("Benchmarking `claude`, `codex` on 2 case(s)")
+        assert "`docs` / claude (sonnet): pass in 0s, ~$0.25" in texts
+        assert "`feature` / codex (base): fail in 0s, ~$0.25" in texts
+        assert texts[-1].startswith("Benchmark of `test-project`")
+        assert "Best on this suite: `claude` (already the project's default), passing 2/2" in texts[-1]
+        filename, report = upload.await_args.args[2:4]
+        assert filename == "bench-test-project.md"
+        assert "| claude (sonnet) | 2/2 |" in report
+        assert session_manager.get_session(bench_context.session.id).estimated_cost_usd == pytest.approx(1.0)
+
+    @pytest.mark.asyncio
+    async def test_selects_agents_and_cases(self, bench_context, test_config, session_manager, mock_send_message):
+        handler, upload = _handler(test_config, session_manager, mock_send_message)
+
+        await handler.handle_bench(parse_command("!bench codex --cases docs"), bench_context)
+
+        texts = [message["text"] for message in mock_send_message.messages]
+        assert texts[0].startswith("Benchmarking `codex` on 1 case(s)")
+        assert texts[1] == "`docs` / codex (base): fail in 0s, ~$0.25"
+
+    @pytest.mark.asyncio
+    async def test_reports_invalid_selection(self, bench_context, test_config, session_manager, mock_send_message):
+        handler, upload = _handler(test_config, session_manager, mock_send_message)
+
+        await handler.handle_bench(parse_command("!bench nope"), bench_context)
+        assert mock_send_message.messages[-1]["text"] == "Cannot run the benchmark: Unknown agent `nope`."
+
+        await handler.handle_bench(parse_command("!bench --cases missing"), bench_context)
+        assert "No bench case named missing" in mock_send_message.messages[-1]["text"]
+
+        await handler.handle_bench(parse_command("!bench --fast"), bench_context)
+        assert mock_send_message.messages[-1]["text"].startswith("Usage: `!bench")
+        upload.assert_not_awaited()
+
+    @pytest.mark.asyncio
+    async def test_explains_empty_suite(self, command_context, test_config, session_manager, mock_send_message):
+        handler, _ = _handler(test_config, session_manager, mock_send_message)
+
+        await handler.handle_bench(parse_command("!bench"), command_context)
+
+        assert "No bench cases in `.cockpit/bench/`" in mock_send_message.messages[-1]["text"]
+
+    @pytest.mark.asyncio
+    async def test_refuses_during_agent_run(self, bench_context, test_config, session_manager, mock_send_message):
+        active_runs = {"run": {"session_id": str(bench_context.session.id)}}
+        handler, upload = _handler(test_config, session_manager, mock_send_message, active_runs=active_runs)
+
+        await handler.handle_bench(parse_command("!bench"), bench_context)
+
+        assert "still running" in mock_send_message.messages[-1]["text"]
+        upload.assert_not_awaited()
@@ -0,0 +1,39 @@
+"""Tests for the !cancel command handler."""
+
+from __future__ import annotations
+
+import asyncio
+
+import pytest
+
+from src.core.commands.cancel import CancelCommandHandler
+from src.core.commands.parser import parse_command
+
+
+class TestCancelCommand:
+    """Test cases for the !cancel command."""
+
+    @pytest.mark.asyncio
+    async def test_cancels_only_this_sessions_runs(self, command_context, mock_send_message):
+        mine = asyncio.create_task(asyncio.sleep(30))
+        other = asyncio.create_task(asyncio.sleep(30))
+        active_runs = {
+            "a": {"task": mine, "session_id": str(command_context.session.id), "agent_id": "claude"},
+            "b": {"task": other, "session_id": "another-session", "agent_id": "codex"},
+        }
+        handler = CancelCommandHandler(active_runs=active_runs, send_message=mock_send_message)
+
+        await handler.handle_cancel(parse_command("!cancel"), command_context)
+
+        assert mine.cancelled()
+        assert not other.done()
+        assert mock_send_message.messages[-1]["text"].startswith("Cancelled `claude`.")
+        other.cancel()
+
+    @pytest.mark.asyncio
+    async def test_nothing_running(self, command_context, mock_send_message):
+        handler = CancelCommandHandler(active_runs={}, send_message=mock_send_message)
+
+        await handler.handle_cancel(parse_command("!cancel"), command_context)
+
+        assert mock_send_message.messages[-1]["text"] == "No agent is running in this session."
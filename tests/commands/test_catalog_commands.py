@@ -41,6 +41,17 @@ class TestCatalogCommands:
         assert "Available models" in output
         assert "sonnet" in output
 
+    @pytest.mark.asyncio
+    async def test_models_respect_project_overrides(self, handler, command_context, mock_send_message):
+        command_context.project.allowed_agents = ["claude"]
+        command_context.project.agent_models = {"claude": "haiku"}
+
+        await handler.handle_models(ParsedCommand(name="models", args=[]), command_context)
+
+        output = mock_send_message.messages[-1]["text"]
+        assert "(default: `haiku`)" in output
+        assert "`codex`" not in output
+
     @pytest.mark.asyncio
     async def test_handle_help_uses_dispatcher(self, handler, command_context, mock_send_message):
         command = ParsedCommand(name="help", args=[])
@@ -0,0 +1,140 @@
+"""Tests for chat-client command registrations built from the command registry."""
+
+from __future__ import annotations
+
+from src.core.commands.hints import (
+    CommandHint,
+    build_command_hints,
+    command_text_from_slash,
+    discord_application_commands,
+    hints_fingerprint,
+    merge_slash_commands,
+    slack_slash_commands,
+)
+from src.core.commands.registry import iter_command_specs
+from src.core.models import CommandArg, Project
+
+
+def _project(tmp_path, project_id: str, commands: dict) -> Project:
+    path = tmp_path / project_id
+    directory = path / ".cockpit" / "commands"
+    directory.mkdir(parents=True)
+    for name, text in commands.items():
+        (directory / f"{name}.md").write_text(text)
+    return Project(id=project_id, channel_name=project_id, path=path, default_agent_id="claude")
+
+
+class TestBuildCommandHints:
+    """Test cases for collecting built-in and project commands."""
+
+    def test_builtins_come_with_usage_and_arguments(self):
+        hints = {hint.name: hint for hint in build_command_hints(iter_command_specs(), [])}
+
+        assert hints["use"].usage_hint == "<agent> [model]"
+        assert [(arg.name, arg.required) for arg in hints["use"].args] == [("agent", True), ("model", False)]
+        assert hints["end"].usage_hint == ""
+        assert "commands" not in hints  # aliases are not registered separately
+
+    def test_project_commands_follow_builtins(self, tmp_path):
+        web = _project(
+            tmp_path,
+            "web",
+            {
+                "refactor": "---\ndescription: Refactor carefully\nargument_hint: module\n---\nRefactor $ARGUMENTS.",
+                "status": "Shadowed by the built-in.",
+            },
+        )
+        api = _project(tmp_path, "api", {"refactor": "Refactor $ARGUMENTS.", "bump": "Bump the version."})
+
+        hints = build_command_hints(iter_command_specs(), [web, web, api])
+        names = [hint.name for hint in hints]
+        by_name = {hint.name: hint for hint in hints}
+
+        assert names[-2:] == ["bump", "refactor"]
+        assert names.count("status") == 1
+        assert by_name["refactor"].projects == ("web", "api")
+        assert by_name["refactor"].description == "Refactor carefully"
+        assert by_name["refactor"].usage_hint == "[module]"
+        assert by_name["bump"].description == "Run the `bump` project command."
+
+    def test_fingerprint_changes_with_definitions(self, tmp_path):
+        project = _project(tmp_path, "web", {"bump": "Bump the version."})
+        before = hints_fingerprint(build_command_hints(iter_command_specs(), [project]))
+        assert before == hints_fingerprint(build_command_hints(iter_command_specs(), [project]))
+
+        (project.path / ".cockpit" / "commands" / "bump.md").write_text("---\ndescription: Bump it\n---\nBump.")
+        assert before != hints_fingerprint(build_command_hints(iter_command_specs(), [project]))
+
+
+class TestSlackSlashCommands:
+    """Test cases for Slack manifest entries."""
+
+    def test_renders_prefixed_commands(self):
+        hint = CommandHint(name="refactor", description="Refactor carefully", usage_hint="[module]", projects=("web",))
+
+        assert slack_slash_commands([hint]) == [
+            {
+                "command": "/rc-refactor",
+                "description": "Refactor carefully (web)",
+                "should_escape": False,
+                "usage_hint": "[module]",
+            }
+        ]
+
+    def test_truncates_long_descriptions_and_skips_long_names(self):
+        hints = [
+            CommandHint(name="long", description="x" * 150, usage_hint=""),
+            CommandHint(name="a" * 40, description="too long a name", usage_hint=""),
+        ]
+        entries = slack_slash_commands(hints)
+
+        assert len(entries) == 1
+        assert len(entries[0]["description"]) == 100
+        assert entries[0]["description"].endswith("…")
+
+    def test_merge_keeps_other_commands(self):
+        manifest = {
+            "display_information": {"name": "Remote Coder"},
+            "features": {"slash_commands": [{"command": "/deploy"}, {"command": "/rc-old"}]},
+        }
+        entries = [{"command": "/rc-status", "description": "Show status", "should_escape": False}]
+
+        merged = merge_slash_commands(manifest, entries)
+
+        assert merged["display_information"] == {"name": "Remote Coder"}
+        assert merged["features"]["slash_commands"] == [{"command": "/deploy"}, *entries]
+        assert merge_slash_commands(merged, entries) is None
+
+    def test_command_text_from_slash(self):
+        assert command_text_from_slash("/rc-status", " --system ") == "!status --system"
+        assert command_text_from_slash("/rc-help", "") == "!help"
+        assert command_text_from_slash("/deploy", "now") is None
+        assert command_text_from_slash("/rc-", "") is None
+
+
+class TestDiscordApplicationCommands:
+    """Test cases for Discord application command payloads."""
+
+    def test_options_follow_arguments_with_required_first(self):
+        hint = CommandHint(
+            name="group",
+            description="Manage groups",
+            usage_hint="",
+            args=(
+                CommandArg("name", "string", False, "Group name"),
+                CommandArg("action", "string", True, "pause or resume"),
+            ),
+        )
+
+        [command] = discord_application_commands([hint])
+
+        assert command["name"] == "group"
+        assert command["type"] == 1
+        assert [(option["name"], option["required"]) for option in command["options"]] == [
+            ("action", True),
+            ("name", False),
+        ]
+        assert all(option["type"] == 3 for option in command["options"])
+
+    def test_skips_invalid_names(self):
+        assert discord_application_commands([CommandHint(name="Bad Name", description="x", usage_hint="")]) == []
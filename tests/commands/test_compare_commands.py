@@ -0,0 +1,137 @@
+"""Tests for the !compare command handler."""
+
+from __future__ import annotations
+
+import subprocess
+from dataclasses import replace
+from unittest.mock import AsyncMock
+
+import pytest
+
+from src.agent_adapters import AgentResult
+from src.core.automation import AutomationGate
+from src.core.commands.compare import CompareCommandHandler
+from src.core.commands.parser import parse_command
+from src.core.models import WorkingDirMode
+
+
+@pytest.fixture
+def git_context(command_context, tmp_path, monkeypatch):
+    """A command context whose session points at a git repository with one commit."""
+    for key, value in {
+        "GIT_AUTHOR_NAME": "Test",
+        "GIT_AUTHOR_EMAIL": "test@example.com",
+        "GIT_COMMITTER_NAME": "Test",
+        "GIT_COMMITTER_EMAIL": "test@example.com",
+    }.items():
+        monkeypatch.setenv(key, value)
+    repo = tmp_path / "repo"
+    repo.mkdir()
+    for args in (["init", "-q", "-b", "main"], ["commit", "-q", "--allow-empty", "-m", "initial"]):
+        subprocess.run(["git", *args], cwd=repo, check=True, capture_output=True)
+    return replace(command_context, session=replace(command_context.session, project_path=repo))
+
+
+def _handler(test_config, session_manager, mock_send_message, run_agent=None, **kwargs):
+    async def _default_run_agent(agent, model, workdir, prompt):
+        (workdir / f"{agent.id}.txt").write_text(prompt)
+        return AgentResult(success=True, output_text=f"{agent.id} reply"), 0.5
+
+    post_diff = AsyncMock()
+    handler = CompareCommandHandler(
+        config=test_config,
+        session_manager=session_manager,
+        run_agent=run_agent or _default_run_agent,
+        post_diff=post_diff,
+        active_runs=kwargs.pop("active_runs", {}),
+        send_message=mock_send_message,
+        **kwargs,
+    )
+    return handler, post_diff
+
+
+class TestCompareCommand:
+    """Test cases for the !compare command."""
+
+    @pytest.mark.asyncio
+    async def test_runs_both_agents_and_posts_diffs(
+        self, git_context, test_config, session_manager, mock_send_message
+    ):
+        handler, post_diff = _handler(test_config, session_manager, mock_send_message)
+
+        await handler.handle_compare(parse_command("!compare claude codex write  a  note"), git_context)
+
+        texts = [message["text"] for message in mock_send_message.messages]
+        assert "Running `claude` and `codex` in separate worktrees" in texts[0]
+        assert "claude (sonnet)" in texts[1] and "codex (base)" in texts[1]
+        diffs = [call.args[2] for call in post_diff.await_args_list]
+        assert len(diffs) == 2
+        assert "claude.txt" in diffs[0] and "+write  a  note" in diffs[0]
+        assert "codex.txt" in diffs[1]
+        assert "Nothing was committed" in texts[-1]
+        assert session_manager.get_session(git_context.session.id).estimated_cost_usd == 1.0
+
+    @pytest.mark.asyncio
+    async def test_usage_without_prompt(self, command_context, test_config, session_manager, mock_send_message):
+        handler, _ = _handler(test_config, session_manager, mock_send_message)
+
+        await handler.handle_compare(parse_command("!compare claude codex"), command_context)
+
+        assert "Usage: `!compare" in mock_send_message.messages[-1]["text"]
+
+    @pytest.mark.asyncio
+    async def test_unknown_agent(self, command_context, test_config, session_manager, mock_send_message):
+        handler, _ = _handler(test_config, session_manager, mock_send_message)
+
+        await handler.handle_compare(parse_command("!compare claude gpt fix it"), command_context)
+
+        assert "Unknown agent `gpt`" in mock_send_message.messages[-1]["text"]
+
+    @pytest.mark.asyncio
+    async def test_respects_allowed_agents(self, command_context, test_config, session_manager, mock_send_message):
+        handler, _ = _handler(test_config, session_manager, mock_send_message)
+        context = replace(command_context, project=replace(command_context.project, allowed_agents=["claude"]))
+
+        await handler.handle_compare(parse_command("!compare claude codex fix it"), context)
+
+        assert "`codex` is not allowed in project `test-project`" in mock_send_message.messages[-1]["text"]
+
+    @pytest.mark.asyncio
+    async def test_refuses_fixed_directory_agents(
+        self, command_context, test_config, session_manager, mock_send_message
+    ):
+        test_config.agents["codex"].working_dir_mode = WorkingDirMode.FIXED
+        handler, _ = _handler(test_config, session_manager, mock_send_message)
+
+        await handler.handle_compare(parse_command("!compare claude codex fix it"), command_context)
+
+        assert "cannot run in a separate worktree" in mock_send_message.messages[-1]["text"]
+
+    @pytest.mark.asyncio
+    async def test_respects_paused_group(self, command_context, test_config, session_manager, mock_send_message):
+        gate = AutomationGate()
+        gate.pause("web")
+        handler, _ = _handler(test_config, session_manager, mock_send_message, gate=gate)
+        context = replace(command_context, project=replace(command_context.project, group="web"))
+
+        await handler.handle_compare(parse_command("!compare claude codex fix it"), context)
+
+        assert "paused for the `web` group" in mock_send_message.messages[-1]["text"]
+
+    @pytest.mark.asyncio
+    async def test_refuses_during_agent_run(self, command_context, test_config, session_manager, mock_send_message):
+        active_runs = {"run": {"session_id": str(command_context.session.id)}}
+        handler, _ = _handler(test_config, session_manager, mock_send_message, active_runs=active_runs)
+
+        await handler.handle_compare(parse_command("!compare claude codex fix it"), command_context)
+
+        assert "still running" in mock_send_message.messages[-1]["text"]
+
+    @pytest.mark.asyncio
+    async def test_reports_non_git_project(self, command_context, test_config, session_manager, mock_send_message):
+        handler, post_diff = _handler(test_config, session_manager, mock_send_message)
+
+        await handler.handle_compare(parse_command("!compare claude codex fix it"), command_context)
+
+        assert "Could not compare agents" in mock_send_message.messages[-1]["text"]
+        post_diff.assert_not_awaited()
@@ -0,0 +1,97 @@
+"""Tests for the export command handler."""
+
+from __future__ import annotations
+
+import json
+from unittest.mock import AsyncMock, MagicMock
+
+import pytest
+
+from src.core.commands.export import INLINE_EXPORT_LIMIT, ExportCommandHandler
+from src.core.commands.parser import ParsedCommand
+
+ARCHIVED_ID = "0b5c1d2e-3f40-4a5b-8c6d-7e8f9a0b1c2d"
+
+
+def _handler(session_manager, mock_send_message, **overrides) -> ExportCommandHandler:
+    deps = {
+        "build_diff": AsyncMock(return_value=None),
+        "upload_file": AsyncMock(return_value=True),
+    }
+    deps.update(overrides)
+    return ExportCommandHandler(session_manager=session_manager, send_message=mock_send_message, **deps)
+
+
+class TestExportCommand:
+    """Test cases for the !export command."""
+
+    @pytest.mark.asyncio
+    async def test_uploads_markdown_and_json(self, session_manager, command_context, mock_send_message):
+        """Both documents are uploaded to the thread, named after the session."""
+        session = command_context.session
+        session_manager.append_user_message(session.id, "add a readme")
+        session_manager.append_agent_message(session.id, "Added README.md")
+        upload = AsyncMock(return_value=True)
+        handler = _handler(
+            session_manager,
+            mock_send_message,
+            build_diff=AsyncMock(return_value="+# Readme\n"),
+            upload_file=upload,
+        )
+
+        await handler.handle_export(ParsedCommand(name="export", args=[]), command_context)
+
+        (_, _, md_name, markdown, _), (_, _, json_name, raw, _) = [call.args for call in upload.await_args_list]
+        assert (md_name, json_name) == (f"remote-coder-{session.id}.md", f"remote-coder-{session.id}.json")
+        assert "add a readme" in markdown and "+# Readme" in markdown
+        data = json.loads(raw)
+        assert [entry["kind"] for entry in data["transcript"]] == ["prompt", "reply"]
+        assert data["session"]["id"] == str(session.id) and data["diff"] == "+# Readme\n"
+        assert mock_send_message.messages == []
+
+    @pytest.mark.asyncio
+    async def test_inline_fallback_without_uploads(self, session_manager, command_context, mock_send_message):
+        """Short exports are posted inline when the adapter cannot upload files."""
+        handler = _handler(session_manager, mock_send_message, upload_file=AsyncMock(return_value=False))
+
+        await handler.handle_export(ParsedCommand(name="export", args=[]), command_context)
+
+        assert mock_send_message.messages[-1]["text"].startswith(f"# Session {str(command_context.session.id)[:8]}")
+
+        handler._build_diff = AsyncMock(return_value="+x\n" * INLINE_EXPORT_LIMIT)
+        await handler.handle_export(ParsedCommand(name="export", args=[]), command_context)
+
+        assert "too large" in mock_send_message.messages[-1]["text"]
+
+
+class TestArchivedCommand:
+    """Test cases for the !archived command."""
+
+    @pytest.mark.asyncio
+    async def test_uploads_the_archived_session(self, session_manager, command_context, mock_send_message):
+        archive = MagicMock()
+        archive.fetch.return_value = ("# Session 0b5c1d2e", '{"session": {}}')
+        upload = AsyncMock(return_value=True)
+        handler = _handler(session_manager, mock_send_message, upload_file=upload, session_archive=lambda: archive)
+
+        await handler.handle_archived(ParsedCommand(name="archived", args=[ARCHIVED_ID.upper()]), command_context)
+
+        archive.fetch.assert_called_once_with(ARCHIVED_ID)
+        names = [call.args[2] for call in upload.await_args_list]
+        assert names == [f"remote-coder-{ARCHIVED_ID}.md", f"remote-coder-{ARCHIVED_ID}.json"]
+
+    @pytest.mark.asyncio
+    async def test_explains_what_it_cannot_fetch(self, session_manager, command_context, mock_send_message):
+        handler = _handler(session_manager, mock_send_message)
+        await handler.handle_archived(ParsedCommand(name="archived", args=[ARCHIVED_ID]), command_context)
+        assert "No session archive is set up" in mock_send_message.messages[-1]["text"]
+
+        archive = MagicMock()
+        archive.fetch.return_value = None
+        handler = _handler(session_manager, mock_send_message, session_archive=lambda: archive)
+        await handler.handle_archived(ParsedCommand(name="archived", args=["0b5c1d2e"]), command_context)
+        assert "needs the full id" in mock_send_message.messages[-1]["text"]
+
+        current = str(command_context.session.id)
+        await handler.handle_archived(ParsedCommand(name="archived", args=[current]), command_context)
+        assert "has not been uploaded yet" in mock_send_message.messages[-1]["text"]
@@ -0,0 +1,64 @@
+"""Tests for the !group command handler."""
+
+from __future__ import annotations
+
+from dataclasses import replace
+
+import pytest
+
+from src.core.automation import AutomationGate
+from src.core.commands.groups import GroupCommandHandler
+from src.core.commands.parser import ParsedCommand
+
+
+class TestGroupCommand:
+    """Test cases for the !group command."""
+
+    @pytest.fixture
+    def gate(self):
+        return AutomationGate()
+
+    @pytest.fixture
+    def handler(self, test_config, test_project, session_manager, gate, mock_send_message):
+        work = replace(test_project, group="work")
+        test_config.projects = {work.id: work, "docs": replace(test_project, id="docs", group="work")}
+        return GroupCommandHandler(
+            config=test_config,
+            gate=gate,
+            session_manager=session_manager,
+            active_runs={},
+            send_message=mock_send_message,
+        )
+
+    @pytest.mark.asyncio
+    async def test_lists_groups(self, handler, command_context, mock_send_message):
+        await handler.handle_group(ParsedCommand(name="group", args=[]), command_context)
+
+        assert "- `work`: `test-project`, `docs`" in mock_send_message.messages[-1]["text"]
+
+    @pytest.mark.asyncio
+    async def test_pause_and_resume(self, handler, gate, command_context, mock_send_message):
+        await handler.handle_group(ParsedCommand(name="group", args=["work", "pause"]), command_context)
+
+        assert gate.is_paused("work")
+        assert "Paused automation for `work` (2 projects)" in mock_send_message.messages[-1]["text"]
+
+        await handler.handle_group(ParsedCommand(name="group", args=["work"]), command_context)
+        assert "Group `work`: paused" in mock_send_message.messages[-1]["text"]
+
+        await handler.handle_group(ParsedCommand(name="group", args=["work", "resume"]), command_context)
+        assert not gate.is_paused("work")
+
+    @pytest.mark.asyncio
+    async def test_pause_reports_running_agents(self, handler, command_context, mock_send_message):
+        handler._active_runs["run"] = {"session_id": str(command_context.session.id)}
+
+        await handler.handle_group(ParsedCommand(name="group", args=["work", "pause"]), command_context)
+
+        assert "1 agent run(s) already in progress" in mock_send_message.messages[-1]["text"]
+
+    @pytest.mark.asyncio
+    async def test_unknown_group(self, handler, command_context, mock_send_message):
+        await handler.handle_group(ParsedCommand(name="group", args=["play", "pause"]), command_context)
+
+        assert "Unknown group `play`. Groups: `work`" in mock_send_message.messages[-1]["text"]
@@ -0,0 +1,91 @@
+"""Tests for the move command handler."""
+
+from __future__ import annotations
+
+from unittest.mock import AsyncMock
+
+import pytest
+
+from src.core.ask_mode import ASK_MODE_CONTEXT_KEY
+from src.core.commands.move import MOVED_AWAY_NOTE, MoveCommandHandler
+from src.core.commands.parser import ParsedCommand
+
+
+class Chat:
+    """Records messages; top-level ones start a thread with ts `new.<n>`."""
+
+    def __init__(self) -> None:
+        self.messages: list[dict] = []
+
+    async def send(self, channel: str, thread_ts, text: str) -> str:
+        self.messages.append({"channel": channel, "thread_ts": thread_ts, "text": text})
+        return thread_ts or f"new.{len(self.messages)}"
+
+
+@pytest.fixture
+def chat():
+    return Chat()
+
+
+@pytest.fixture
+def handler(session_manager, test_config, test_project, chat):
+    test_project.channels = ["test-api"]
+    test_project.ask_channels = ["test-questions"]
+    test_config.projects = {test_project.channel_name: test_project, test_project.id: test_project}
+    return MoveCommandHandler(
+        session_manager=session_manager,
+        config=test_config,
+        send_message=chat.send,
+        resolve_channel=AsyncMock(return_value="C999"),
+        thread_link=lambda channel, thread_ts: None,
+        active_runs={},
+    )
+
+
+class TestMoveCommand:
+    """Test cases for !move."""
+
+    @pytest.mark.asyncio
+    async def test_moves_to_another_channel_of_the_project(self, handler, command_context, session_manager, chat):
+        session = command_context.session
+
+        await handler.handle_move(ParsedCommand(name="move", args=["<#C777|test-api>"]), command_context)
+
+        assert chat.messages[0]["channel"] == "C777" and chat.messages[0]["thread_ts"] is None
+        assert (session.channel_id, session.thread_ts) == ("C777", "new.1")
+        assert session_manager.get_by_thread("C777", "new.1").id == session.id
+        assert chat.messages[-1]["text"] == MOVED_AWAY_NOTE.format(new="thread `new.1`")
+        assert chat.messages[-1]["thread_ts"] == command_context.thread_ts
+
+    @pytest.mark.asyncio
+    async def test_resolves_channel_names(self, handler, command_context, chat):
+        await handler.handle_move(ParsedCommand(name="move", args=["#test-api"]), command_context)
+
+        assert chat.messages[0]["channel"] == "C999"
+        assert command_context.session.channel_id == "C999"
+
+    @pytest.mark.asyncio
+    async def test_refuses_channels_of_other_projects_and_kinds(self, handler, command_context, chat):
+        await handler.handle_move(ParsedCommand(name="move", args=["#elsewhere"]), command_context)
+        assert chat.messages[-1]["text"].startswith("`#elsewhere` is not a channel of `test-project`.")
+
+        await handler.handle_move(ParsedCommand(name="move", args=["#test-questions"]), command_context)
+        assert chat.messages[-1]["text"] == (
+            "This is a change session; move it to another change channel of the project."
+        )
+
+        command_context.session.session_context[ASK_MODE_CONTEXT_KEY] = True
+        await handler.handle_move(ParsedCommand(name="move", args=["#test-api"]), command_context)
+        assert chat.messages[-1]["text"] == (
+            "This is a question session; move it to another question channel of the project."
+        )
+        assert command_context.session.channel_id == "C123456"
+
+    @pytest.mark.asyncio
+    async def test_refuses_while_a_run_is_in_progress(self, handler, command_context, chat):
+        handler._active_runs["r1"] = {"session_id": str(command_context.session.id)}
+
+        await handler.handle_move(ParsedCommand(name="move", args=[]), command_context)
+
+        assert chat.messages[-1]["text"].startswith("A run is in progress")
+        assert command_context.session.thread_ts == "1234567890.123456"
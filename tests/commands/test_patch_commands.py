@@ -0,0 +1,206 @@
+"""Tests for patch command handlers."""
+
+from __future__ import annotations
+
+from unittest.mock import AsyncMock, Mock
+
+import pytest
+
+from dataclasses import replace
+
+from src.core.commands.context import ChatAttachment
+from src.core.commands.parser import ParsedCommand
+from src.core.commands.patches import INLINE_PATCH_LIMIT, PatchCommandHandler, extract_patch_text
+from src.core.models import CommandStep
+
+
+def _handler(mock_send_message, **overrides) -> PatchCommandHandler:
+    deps = {
+        "build_patch": AsyncMock(return_value=None),
+        "upload_file": AsyncMock(return_value=True),
+        "apply_patch": AsyncMock(return_value=(True, " x | 1 +")),
+        "revert_patch": AsyncMock(),
+        "publish_changes": AsyncMock(return_value="Pushed updates to branch `b`"),
+        "active_runs": {},
+    }
+    deps.update(overrides)
+    return PatchCommandHandler(send_message=mock_send_message, **deps)
+
+
+class TestPatchCommand:
+    """Test cases for the !patch command."""
+
+    @pytest.mark.asyncio
+    async def test_uploads_patch_file(self, command_context, mock_send_message):
+        """The patch is uploaded as a file named after the session."""
+        upload = AsyncMock(return_value=True)
+        handler = _handler(
+            mock_send_message,
+            build_patch=AsyncMock(return_value="From abc\nSubject: [PATCH] x\n"),
+            upload_file=upload,
+        )
+
+        await handler.handle_patch(ParsedCommand(name="patch", args=[]), command_context)
+
+        upload.assert_awaited_once()
+        channel, thread_ts, filename, content, _ = upload.await_args.args
+        assert (channel, thread_ts) == (command_context.channel, command_context.thread_ts)
+        assert filename == f"remote-coder-{command_context.session.id}.patch"
+        assert content.startswith("From abc")
+        assert mock_send_message.messages == []
+
+    @pytest.mark.asyncio
+    async def test_inline_fallback_without_uploads(self, command_context, mock_send_message):
+        """Small patches are posted inline when the adapter cannot upload files."""
+        handler = _handler(
+            mock_send_message,
+            build_patch=AsyncMock(return_value="From abc\n+line\n"),
+            upload_file=AsyncMock(return_value=False),
+        )
+
+        await handler.handle_patch(ParsedCommand(name="patch", args=[]), command_context)
+
+        assert "+line" in mock_send_message.messages[-1]["text"]
+
+        handler._build_patch = AsyncMock(return_value="x" * (INLINE_PATCH_LIMIT + 1))
+        await handler.handle_patch(ParsedCommand(name="patch", args=[]), command_context)
+
+        assert "too large" in mock_send_message.messages[-1]["text"]
+
+    @pytest.mark.asyncio
+    async def test_patch_with_a_secret_is_not_posted(self, command_context, mock_send_message):
+        """A patch that would need redacting is refused instead of uploaded in a form `git am` rejects."""
+        upload = AsyncMock(return_value=True)
+        handler = _handler(
+            mock_send_message,
+            build_patch=AsyncMock(return_value="From abc\n+API_KEY=hunter2hunter2hunter2\n"),
+            upload_file=upload,
+            contains_secret=lambda text: "API_KEY=" in text,
+        )
+
+        await handler.handle_patch(ParsedCommand(name="patch", args=[]), command_context)
+
+        upload.assert_not_awaited()
+        assert "looks like a secret" in mock_send_message.messages[-1]["text"]
+        assert "hunter2" not in mock_send_message.messages[-1]["text"]
+
+    @pytest.mark.asyncio
+    async def test_no_commits(self, command_context, mock_send_message):
+        """An empty session branch reports that there is nothing to export."""
+        handler = _handler(mock_send_message)
+
+        await handler.handle_patch(ParsedCommand(name="patch", args=[]), command_context)
+
+        assert "nothing to export" in mock_send_message.messages[-1]["text"]
+
+
+class TestApplyCommand:
+    """Test cases for the !apply command."""
+
+    def test_extract_prefers_patch_attachment(self):
+        """An attached .patch file wins over other files and pasted text."""
+        attachments = (ChatAttachment(name="notes.txt", content="notes"), ChatAttachment(name="fix.PATCH", content="diff"))
+        assert extract_patch_text("ignored", attachments) == "diff"
+
+    def test_extract_pasted_fenced_diff(self):
+        """Pasted diffs are unfenced and unescaped."""
+        text = extract_patch_text("```diff\n--- a/x\n+++ b/x\n-a &lt; b\n+a &gt; b\n```", ())
+        assert text == "--- a/x\n+++ b/x\n-a < b\n+a > b\n"
+        assert extract_patch_text("   ", ()) is None
+
+    @pytest.mark.asyncio
+    async def test_apply_runs_verify_and_publishes(self, command_context, mock_send_message):
+        """A clean patch whose checks pass is verified, published, and reported in one reply."""
+        apply_patch = AsyncMock(return_value=(True, " x | 1 +"))
+        publish = AsyncMock(return_value="Pushed updates to branch `b`")
+        handler = _handler(mock_send_message, apply_patch=apply_patch, publish_changes=publish)
+        context = replace(
+            command_context, project=replace(command_context.project, verify_commands=[CommandStep("true")])
+        )
+
+        await handler.handle_apply(ParsedCommand(name="apply", args=[], raw_args="--- a/x\n+++ b/x\n"), context)
+
+        apply_patch.assert_awaited_once()
+        publish.assert_awaited_once()
+        text = mock_send_message.messages[-1]["text"]
+        assert "Patch applied" in text
+        assert "`true`: passed" in text
+        assert "Pushed updates" in text
+
+    @pytest.mark.asyncio
+    async def test_apply_with_failing_checks_is_not_published(self, command_context, mock_send_message):
+        """Failing checks are reported, the patch is taken back out, and the reply says how to publish anyway."""
+        revert = AsyncMock()
+        publish = AsyncMock()
+        record_checks = Mock()
+        handler = _handler(
+            mock_send_message, revert_patch=revert, publish_changes=publish, record_checks=record_checks
+        )
+        context = replace(
+            command_context,
+            project=replace(
+                command_context.project, verify_commands=[CommandStep("true"), CommandStep("echo broken && false")]
+            ),
+        )
+
+        await handler.handle_apply(ParsedCommand(name="apply", args=[], raw_args="--- a/x\n+++ b/x\n"), context)
+
+        publish.assert_not_awaited()
+        revert.assert_awaited_once_with(context.session, context.project, "--- a/x\n+++ b/x\n")
+        record_checks.assert_called_once_with(context.session.id, False)
+        text = mock_send_message.messages[-1]["text"]
+        assert "Verify steps failed" in text
+        assert "broken" in text
+        assert "nothing was published" in text
+        assert "`!apply --skip-checks`" in text
+
+    @pytest.mark.asyncio
+    async def test_apply_skip_checks_publishes_without_verifying(self, command_context, mock_send_message):
+        """`--skip-checks` is not part of the pasted diff, and the checks do not run."""
+        apply_patch = AsyncMock(return_value=(True, " x | 1 +"))
+        publish = AsyncMock(return_value="Pushed updates to branch `b`")
+        handler = _handler(mock_send_message, apply_patch=apply_patch, publish_changes=publish)
+        context = replace(
+            command_context,
+            project=replace(command_context.project, verify_commands=[CommandStep("echo broken && false")]),
+        )
+
+        await handler.handle_apply(
+            ParsedCommand(name="apply", args=[], raw_args="--skip-checks\n--- a/x\n+++ b/x\n"), context
+        )
+
+        assert apply_patch.await_args.args[2] == "--- a/x\n+++ b/x\n"
+        publish.assert_awaited_once()
+        text = mock_send_message.messages[-1]["text"]
+        assert "broken" not in text
+        assert "Pushed updates" in text
+
+    @pytest.mark.asyncio
+    async def test_apply_rejected_patch(self, command_context, mock_send_message):
+        """Patches that do not apply are reported without publishing."""
+        publish = AsyncMock()
+        handler = _handler(
+            mock_send_message,
+            apply_patch=AsyncMock(return_value=(False, "error: patch failed: x:1")),
+            publish_changes=publish,
+        )
+
+        await handler.handle_apply(ParsedCommand(name="apply", args=[], raw_args="--- a/x\n"), command_context)
+
+        publish.assert_not_awaited()
+        assert "patch failed" in mock_send_message.messages[-1]["text"]
+
+    @pytest.mark.asyncio
+    async def test_apply_refuses_during_active_run(self, command_context, mock_send_message):
+        """Applying while an agent edits the same tree is refused."""
+        apply_patch = AsyncMock()
+        handler = _handler(
+            mock_send_message,
+            apply_patch=apply_patch,
+            active_runs={"run": {"session_id": str(command_context.session.id)}},
+        )
+
+        await handler.handle_apply(ParsedCommand(name="apply", args=[], raw_args="--- a/x\n"), command_context)
+
+        apply_patch.assert_not_awaited()
+        assert "still running" in mock_send_message.messages[-1]["text"]
@@ -0,0 +1,115 @@
+"""Tests for the !redo command handler."""
+
+from __future__ import annotations
+
+from unittest.mock import AsyncMock
+
+import pytest
+
+from src.core.agent_runner import LAST_RUN_CONTEXT_KEY
+from src.core.commands.parser import parse_command
+from src.core.commands.redo import RedoCommandHandler, unquote_prompt
+
+
+def _handler(session_manager, mock_send_message, *, reset_result=(True, "Reset to `abc1234`."), active_runs=None):
+    reset = AsyncMock(return_value=reset_result)
+    execute = AsyncMock()
+    handler = RedoCommandHandler(
+        session_manager=session_manager,
+        reset_to_checkpoint=reset,
+        execute_agent_task=execute,
+        active_runs=active_runs or {},
+        send_message=mock_send_message,
+    )
+    return handler, reset, execute
+
+
+@pytest.fixture
+def redo_context(command_context, session_manager):
+    """A session whose last run was 'add a buton' with a recorded checkpoint."""
+    session = command_context.session
+    session_manager.append_user_message(session.id, "add a buton")
+    session_manager.append_agent_message(session.id, "Added a button")
+    session_manager.update_session_context(
+        session.id, {LAST_RUN_CONTEXT_KEY: {"prompt": "add a buton", "checkpoint": "abc1234"}}
+    )
+    return command_context
+
+
+class TestRedoCommand:
+    """Test cases for the !redo command."""
+
+    @pytest.mark.asyncio
+    async def test_without_previous_run(self, command_context, session_manager, mock_send_message):
+        handler, reset, execute = _handler(session_manager, mock_send_message)
+
+        await handler.handle_redo(parse_command("!redo"), command_context)
+
+        assert "Nothing to redo yet" in mock_send_message.messages[-1]["text"]
+        execute.assert_not_awaited()
+
+    @pytest.mark.asyncio
+    async def test_shows_last_prompt_for_editing(self, redo_context, session_manager, mock_send_message):
+        handler, reset, execute = _handler(session_manager, mock_send_message)
+
+        await handler.handle_redo(parse_command("!redo"), redo_context)
+
+        text = mock_send_message.messages[-1]["text"]
+        assert "> add a buton" in text
+        assert "`!redo --same`" in text
+        reset.assert_not_awaited()
+
+    @pytest.mark.asyncio
+    async def test_resets_and_runs_edited_prompt(self, redo_context, session_manager, mock_send_message):
+        handler, reset, execute = _handler(session_manager, mock_send_message)
+
+        await handler.handle_redo(parse_command("!redo &gt; add a blue button"), redo_context)
+
+        session = redo_context.session
+        reset.assert_awaited_once_with(session, redo_context.project, "abc1234")
+        assert mock_send_message.messages[-1]["text"] == "Reset to `abc1234`."
+        execute.assert_awaited_once_with(
+            session, redo_context.project, redo_context.channel, redo_context.thread_ts, "add a blue button"
+        )
+        assert session.conversation_history == []
+
+    @pytest.mark.asyncio
+    async def test_same_reruns_previous_prompt(self, redo_context, session_manager, mock_send_message):
+        handler, _, execute = _handler(session_manager, mock_send_message)
+
+        await handler.handle_redo(parse_command("!redo --same"), redo_context)
+
+        assert execute.await_args.args[4] == "add a buton"
+
+    @pytest.mark.asyncio
+    async def test_failed_reset_does_not_run(self, redo_context, session_manager, mock_send_message):
+        handler, _, execute = _handler(
+            session_manager, mock_send_message, reset_result=(False, "no longer contains the checkpoint")
+        )
+
+        await handler.handle_redo(parse_command("!redo --same"), redo_context)
+
+        assert "no longer contains the checkpoint" in mock_send_message.messages[-1]["text"]
+        execute.assert_not_awaited()
+        assert len(redo_context.session.conversation_history) == 2
+
+    @pytest.mark.asyncio
+    async def test_refuses_during_agent_run(self, redo_context, session_manager, mock_send_message):
+        active_runs = {"run": {"session_id": str(redo_context.session.id)}}
+        handler, reset, _ = _handler(session_manager, mock_send_message, active_runs=active_runs)
+
+        await handler.handle_redo(parse_command("!redo --same"), redo_context)
+
+        assert "still running" in mock_send_message.messages[-1]["text"]
+        reset.assert_not_awaited()
+
+
+class TestUnquotePrompt:
+    """Test cases for cleaning up prompts pasted from the !redo reply."""
+
+    def test_strips_quote_markers(self):
+        assert unquote_prompt("> first line\n> second line") == "first line\nsecond line"
+        assert unquote_prompt("&gt; escaped by Slack") == "escaped by Slack"
+
+    def test_keeps_partially_quoted_text(self):
+        assert unquote_prompt("fix it like this:\n> quoted detail") == "fix it like this:\n> quoted detail"
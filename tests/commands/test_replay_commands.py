@@ -0,0 +1,121 @@
+"""Tests for the !replay command handler."""
+
+from __future__ import annotations
+
+import subprocess
+from unittest.mock import AsyncMock
+
+import pytest
+
+from src.agent_adapters import AgentResult
+from src.core.commands.parser import parse_command
+from src.core.commands.replay import ReplayCommandHandler
+from src.core.conversation import InteractionClassifier
+from src.core.models import AgentType, ConversationMessage
+
+
+@pytest.fixture
+def past_session(session_manager, test_project, monkeypatch):
+    """A finished session of the test project, in a git repository, with two requests."""
+    for key, value in {
+        "GIT_AUTHOR_NAME": "Test",
+        "GIT_AUTHOR_EMAIL": "test@example.com",
+        "GIT_COMMITTER_NAME": "Test",
+        "GIT_COMMITTER_EMAIL": "test@example.com",
+    }.items():
+        monkeypatch.setenv(key, value)
+    for args in (["init", "-q", "-b", "main"], ["commit", "-q", "--allow-empty", "-m", "initial"]):
+        subprocess.run(["git", *args], cwd=test_project.path, check=True, capture_output=True)
+    session = session_manager.create_session(
+        project=test_project, channel_id="C999", thread_ts="1.5", agent_id="claude", agent_type=AgentType.CLAUDE
+    )
+    for prompt in ("Add a.txt", "Add b.txt"):
+        session_manager.append_interaction(
+            session.id,
+            user_message=ConversationMessage(role="user", content=prompt),
+            agent_result=AgentResult(success=True, output_text="done"),
+            classifier=InteractionClassifier(),
+        )
+    return session
+
+
+def _handler(test_config, session_manager, mock_send_message, **kwargs):
+    async def run_agent(agent, model, workdir, prompt, context):
+        (workdir / prompt.split()[-1]).write_text(prompt)
+        return AgentResult(success=True, output_text=f"{agent.id} did it"), 0.25
+
+    post_diff = AsyncMock()
+    handler = ReplayCommandHandler(
+        config=test_config,
+        session_manager=session_manager,
+        run_agent=run_agent,
+        post_diff=post_diff,
+        active_runs=kwargs.pop("active_runs", {}),
+        send_message=mock_send_message,
+        **kwargs,
+    )
+    return handler, post_diff
+
+
+class TestReplayCommand:
+    """Test cases for the !replay command."""
+
+    @pytest.mark.asyncio
+    async def test_replays_session_by_id_prefix(
+        self, past_session, command_context, test_config, session_manager, mock_send_message
+    ):
+        handler, post_diff = _handler(test_config, session_manager, mock_send_message)
+        prefix = str(past_session.id)[:8]
+
+        await handler.handle_replay(parse_command(f"!replay {prefix} --agent codex"), command_context)
+
+        texts = [message["text"] for message in mock_send_message.messages]
+        assert texts[0].startswith(f"Replaying 2 request(s) of session `{past_session.id}` with `codex`")
+        assert texts[1] == "Turn 1/2: ok in 0s, ~$0.25"
+        assert texts[2] == "Turn 2/2: ok in 0s, ~$0.25"
+        assert texts[3].startswith(f"Replay of session `{past_session.id}` with `codex (base)`")
+        assert "  codex: codex did it" in texts[3]
+        assert texts[4] == "*codex (base)* changes:"
+        diff_text = post_diff.await_args.args[2]
+        assert "a.txt" in diff_text and "b.txt" in diff_text
+        # Costs count toward the session the command ran in
+        assert session_manager.get_session(command_context.session.id).estimated_cost_usd == pytest.approx(0.5)
+
+    @pytest.mark.asyncio
+    async def test_reports_bad_arguments(
+        self, past_session, command_context, test_config, session_manager, mock_send_message
+    ):
+        handler, post_diff = _handler(test_config, session_manager, mock_send_message)
+
+        await handler.handle_replay(parse_command(f"!replay {past_session.id}"), command_context)
+        assert mock_send_message.messages[-1]["text"].startswith("Usage: `!replay")
+
+        await handler.handle_replay(parse_command("!replay zzzz --agent codex"), command_context)
+        assert mock_send_message.messages[-1]["text"].startswith("No session with ID `zzzz`")
+
+        await handler.handle_replay(parse_command(f"!replay {past_session.id} --agent nope"), command_context)
+        assert mock_send_message.messages[-1]["text"].startswith("Unknown agent `nope`")
+        post_diff.assert_not_awaited()
+
+    @pytest.mark.asyncio
+    async def test_refuses_session_without_requests(
+        self, command_context, test_config, session_manager, mock_send_message
+    ):
+        handler, _ = _handler(test_config, session_manager, mock_send_message)
+
+        command = parse_command(f"!replay {command_context.session.id} --agent codex")
+        await handler.handle_replay(command, command_context)
+
+        assert mock_send_message.messages[-1]["text"].endswith("has no stored requests to replay.")
+
+    @pytest.mark.asyncio
+    async def test_refuses_during_agent_run(
+        self, past_session, command_context, test_config, session_manager, mock_send_message
+    ):
+        active_runs = {"run": {"session_id": str(command_context.session.id)}}
+        handler, post_diff = _handler(test_config, session_manager, mock_send_message, active_runs=active_runs)
+
+        await handler.handle_replay(parse_command(f"!replay {past_session.id} --agent codex"), command_context)
+
+        assert "still running" in mock_send_message.messages[-1]["text"]
+        post_diff.assert_not_awaited()
@@ -0,0 +1,45 @@
+"""Tests for the !rollback command handler."""
+
+from __future__ import annotations
+
+import pytest
+
+from src.core.commands.parser import parse_command
+from src.core.commands.rollback import RollbackCommandHandler
+from src.core.snapshots import SnapshotStore
+
+
+def _handler(test_config, mock_send_message, active_runs=None):
+    return RollbackCommandHandler(config=test_config, active_runs=active_runs or {}, send_message=mock_send_message)
+
+
+class TestRollbackCommand:
+    """Test cases for the !rollback command."""
+
+    @pytest.mark.asyncio
+    async def test_restores_last_snapshot(self, command_context, test_config, mock_send_message):
+        workdir = command_context.session.project_path
+        (workdir / "notes.md").write_text("before\n")
+        SnapshotStore.for_session(test_config.config_dir, command_context.session.id).take(workdir)
+        (workdir / "notes.md").write_text("after\n")
+
+        await _handler(test_config, mock_send_message).handle_rollback(parse_command("!rollback"), command_context)
+
+        assert (workdir / "notes.md").read_text() == "before\n"
+        assert mock_send_message.messages[-1]["text"].startswith("Rolled the workspace back to before the last run")
+
+    @pytest.mark.asyncio
+    async def test_explains_missing_snapshot(self, command_context, test_config, mock_send_message):
+        await _handler(test_config, mock_send_message).handle_rollback(parse_command("!rollback"), command_context)
+
+        assert mock_send_message.messages[-1]["text"].startswith("There is no snapshot to roll back to.")
+
+    @pytest.mark.asyncio
+    async def test_refuses_during_agent_run(self, command_context, test_config, mock_send_message):
+        active_runs = {"run": {"session_id": str(command_context.session.id)}}
+
+        await _handler(test_config, mock_send_message, active_runs).handle_rollback(
+            parse_command("!rollback"), command_context
+        )
+
+        assert "still running" in mock_send_message.messages[-1]["text"]
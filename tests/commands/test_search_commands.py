@@ -0,0 +1,78 @@
+"""Tests for the search command handler."""
+
+from __future__ import annotations
+
+from dataclasses import replace
+
+import pytest
+
+from src.core.commands.parser import ParsedCommand
+from src.core.commands.search import SearchCommandHandler
+from src.core.models import AgentType
+from src.core.search import SearchIndex
+
+
+def _search(query: str) -> ParsedCommand:
+    return ParsedCommand(name="search", args=query.split(), raw_args=query)
+
+
+class TestSearchCommand:
+    """Test cases for the !search command."""
+
+    @pytest.mark.asyncio
+    async def test_lists_matching_sessions(self, tmp_path, session_manager, command_context, mock_send_message):
+        session = command_context.session
+        session_manager.append_user_message(session.id, "Speed up the flaky migration test")
+        index = SearchIndex(tmp_path / "search.db")
+        index.update(session, session_manager.transcript(session.id))
+        handler = SearchCommandHandler(
+            search_index=lambda: index,
+            send_message=mock_send_message,
+            thread_link=lambda channel, ts: f"https://chat.example/{channel}/{ts}",
+        )
+
+        await handler.handle_search(_search("migrations"), command_context)
+        await handler.handle_search(_search("rollback"), command_context)
+
+        found, missing = [message["text"] for message in mock_send_message.messages]
+        assert found.splitlines()[0] == f"Sessions in `{command_context.project.id}` matching migrations (best first):"
+        assert '"Speed up the flaky migration test"' in found and "this thread" in found
+        assert "*migration*" in found
+        assert missing == (
+            f"No sessions in `{command_context.project.id}` mention rollback. "
+            "`!search --all rollback` looks in every project."
+        )
+
+    @pytest.mark.asyncio
+    async def test_other_projects_need_all(
+        self, tmp_path, session_manager, test_project, command_context, mock_send_message
+    ):
+        other = session_manager.create_session(
+            project=replace(test_project, id="billing", channel_name="billing"),
+            channel_id="C999",
+            thread_ts="2.0",
+            agent_id="claude",
+            agent_type=AgentType.CLAUDE,
+        )
+        session_manager.append_user_message(other.id, "Rotate the incident webhook secret")
+        index = SearchIndex(tmp_path / "search.db")
+        index.update(other, session_manager.transcript(other.id))
+        handler = SearchCommandHandler(search_index=lambda: index, send_message=mock_send_message)
+
+        await handler.handle_search(_search("webhook"), command_context)
+        await handler.handle_search(_search("--all webhook"), command_context)
+        await handler.handle_search(_search("--all"), command_context)
+
+        here, everywhere, usage = [message["text"] for message in mock_send_message.messages]
+        assert here.startswith(f"No sessions in `{test_project.id}` mention webhook.")
+        assert everywhere.splitlines()[0] == "Sessions matching webhook (best first):"
+        assert "- `billing`" in everywhere and "Rotate the incident *webhook* secret" in everywhere
+        assert usage.startswith("Usage: `!search [--all] <words>`")
+
+    @pytest.mark.asyncio
+    async def test_explains_when_search_is_off(self, command_context, mock_send_message):
+        handler = SearchCommandHandler(search_index=lambda: None, send_message=mock_send_message)
+
+        await handler.handle_search(_search("anything"), command_context)
+
+        assert "REMOTE_CODER_DISABLE_TRANSCRIPTS" in mock_send_message.messages[-1]["text"]
@@ -2,14 +2,19 @@
 
 from __future__ import annotations
 
+import time
 from unittest.mock import MagicMock
 
 import pytest
 
+from src.core.automation import AutomationGate
+from src.core.commands.context import CommandContext
 from src.core.commands.parser import ParsedCommand
 from src.core.commands.session import SessionCommandHandler
+from src.core.conversation import SessionManager
 from src.core.errors import AgentNotFound
-from src.core.models import SessionStatus
+from src.core.models import AgentPricing, AgentSelection, AgentType, SessionStatus
+from src.core.transcripts import RunRecord, TranscriptStore
 
 
 class TestSessionCommands:
@@ -44,6 +49,47 @@ class TestSessionCommands:
         assert stored.active_model == "opus"
         assert "`opus`" in mock_send_message.messages[-1]["text"]
 
+    @pytest.mark.asyncio
+    async def test_handle_use_pins_agent(self, handler, command_context, session_manager):
+        await handler.handle_use(ParsedCommand(name="use", args=["codex"]), command_context)
+
+        assert session_manager.get_session(command_context.session.id).agent_pinned
+
+    @pytest.mark.asyncio
+    async def test_handle_use_auto_unpins(self, handler, command_context, session_manager, mock_send_message):
+        command_context.project.agent_selection = AgentSelection.AUTO
+        session_manager.set_agent_pinned(command_context.session.id, True)
+
+        await handler.handle_use(ParsedCommand(name="use", args=["auto"]), command_context)
+
+        assert not session_manager.get_session(command_context.session.id).agent_pinned
+        assert "re-enabled" in mock_send_message.messages[-1]["text"]
+
+    @pytest.mark.asyncio
+    async def test_handle_use_auto_without_auto_project(self, handler, command_context, mock_send_message):
+        await handler.handle_use(ParsedCommand(name="use", args=["auto"]), command_context)
+
+        assert "agent_selection: auto" in mock_send_message.messages[-1]["text"]
+
+    @pytest.mark.asyncio
+    async def test_handle_use_uses_project_model_override(self, handler, command_context, session_manager):
+        command_context.project.agent_models = {"codex": "mini"}
+
+        await handler.handle_use(ParsedCommand(name="use", args=["codex"]), command_context)
+
+        assert session_manager.get_session(command_context.session.id).active_model == "mini"
+
+    @pytest.mark.asyncio
+    async def test_handle_use_rejects_disallowed_agent(self, handler, command_context, session_manager, mock_send_message):
+        command_context.project.allowed_agents = ["claude"]
+
+        await handler.handle_use(ParsedCommand(name="use", args=["codex"]), command_context)
+
+        stored = session_manager.get_session(command_context.session.id)
+        assert stored.active_agent_id == "claude"
+        assert not stored.agent_pinned
+        assert "not allowed in project `test-project`. Allowed: `claude`" in mock_send_message.messages[-1]["text"]
+
     @pytest.mark.asyncio
     async def test_handle_use_unknown_agent(self, handler, command_context, test_config, mock_send_message):
         test_config.get_agent = MagicMock(side_effect=AgentNotFound("unknown"))
@@ -79,6 +125,47 @@ class TestSessionCommands:
         assert "Session ID" in output
         assert command_context.session.active_agent_id in output
 
+    @pytest.mark.asyncio
+    async def test_handle_status_shows_the_run_quota(
+        self, session_manager, test_config, command_context, mock_send_message
+    ):
+        handler = SessionCommandHandler(
+            session_manager=session_manager,
+            config=test_config,
+            send_message=mock_send_message,
+            gate=AutomationGate(run_counter=session_manager.project_runs),
+        )
+        command_context.project.max_runs_per_month = 100
+        session_manager.record_project_run(command_context.project.id)
+
+        await handler.handle_status(ParsedCommand(name="status", args=[]), command_context)
+
+        assert "Run quota: 1/100 runs in" in mock_send_message.messages[-1]["text"]
+
+    @pytest.mark.asyncio
+    async def test_handle_status_system_view(self, session_manager, test_config, command_context, mock_send_message):
+        handler = SessionCommandHandler(
+            session_manager=session_manager,
+            config=test_config,
+            send_message=mock_send_message,
+            get_system_status=lambda: "Chat connection: connected for 5m 0s",
+        )
+        command = ParsedCommand(name="status", args=["--system"])
+
+        await handler.handle_status(command, command_context)
+
+        output = mock_send_message.messages[-1]["text"]
+        assert "Chat connection" in output
+        assert "Session ID" not in output
+
+    @pytest.mark.asyncio
+    async def test_handle_status_system_unavailable(self, handler, command_context, mock_send_message):
+        command = ParsedCommand(name="status", args=["--system"])
+
+        await handler.handle_status(command, command_context)
+
+        assert "not available" in mock_send_message.messages[-1]["text"]
+
     @pytest.mark.asyncio
     async def test_handle_end_active_session(self, handler, command_context, session_manager, mock_send_message):
         command = ParsedCommand(name="end", args=[])
@@ -96,3 +183,136 @@ class TestSessionCommands:
         await handler.handle_end(command, command_context)
 
         assert "Session already ended" in mock_send_message.messages[-1]["text"]
+
+    @pytest.mark.asyncio
+    async def test_pause_and_resume(self, handler, command_context, session_manager, mock_send_message):
+        session_id = command_context.session.id
+
+        await handler.handle_pause(ParsedCommand(name="pause", args=[]), command_context)
+        assert session_manager.get_session(session_id).status == SessionStatus.PAUSED
+        assert mock_send_message.messages[-1]["text"].startswith("Session paused.")
+        await handler.handle_pause(ParsedCommand(name="pause", args=[]), command_context)
+        assert "already paused" in mock_send_message.messages[-1]["text"]
+
+        await handler.handle_resume(ParsedCommand(name="resume", args=[]), command_context)
+        assert session_manager.get_session(session_id).status == SessionStatus.ACTIVE
+        assert mock_send_message.messages[-1]["text"].startswith("Session resumed with `claude`")
+        await handler.handle_resume(ParsedCommand(name="resume", args=[]), command_context)
+        assert mock_send_message.messages[-1]["text"] == "Session is not paused."
+
+    @pytest.mark.asyncio
+    async def test_ended_sessions_cannot_be_paused(self, handler, command_context, mock_send_message):
+        command_context.session.status = SessionStatus.ENDED
+
+        await handler.handle_pause(ParsedCommand(name="pause", args=[]), command_context)
+
+        assert mock_send_message.messages[-1]["text"] == "Only active sessions can be paused; this one is ended."
+
+    @pytest.mark.asyncio
+    async def test_handle_sessions_lists_active_work(self, test_project, test_config, tmp_path, mock_send_message):
+        manager = SessionManager(transcript=TranscriptStore(tmp_path / "transcripts"))
+
+        def start(thread_ts: str):
+            return manager.create_session(
+                project=test_project,
+                channel_id="C1",
+                thread_ts=thread_ts,
+                agent_id="claude",
+                agent_type=AgentType.CLAUDE,
+            )
+
+        here, running, finished, ended = start("1.0"), start("2.0"), start("3.0"), start("4.0")
+        manager.record_run(finished.id, RunRecord(agent_id="claude", success=False, wall_secs=5))
+        manager.update_status(ended.id, SessionStatus.ENDED)
+        started_at = time.time() - 125
+        handler = SessionCommandHandler(
+            session_manager=manager,
+            config=test_config,
+            send_message=mock_send_message,
+            active_runs={"r1": {"session_id": str(running.id), "started_at": started_at}},
+            thread_link=lambda channel, thread_ts: f"https://chat.example/{channel}/{thread_ts}",
+        )
+        context = CommandContext(session=here, project=test_project, channel="C1", thread_ts="1.0")
+
+        await handler.handle_sessions(ParsedCommand(name="sessions", args=[]), context)
+
+        lines = mock_send_message.messages[-1]["text"].splitlines()
+        assert lines[0] == "Active sessions (3):"
+        by_thread = {line.rsplit(" · ", 1)[-1]: line for line in lines[1:]}
+        assert set(by_thread) == {"this thread", "https://chat.example/C1/2.0", "https://chat.example/C1/3.0"}
+        assert " · running for 2m " in by_thread["https://chat.example/C1/2.0"]
+        assert " · last run failed 0s ago · " in by_thread["https://chat.example/C1/3.0"]
+        assert by_thread["this thread"].startswith("- `test-project` · `claude` · no runs yet · opened 0s ago")
+
+    @pytest.mark.asyncio
+    async def test_handle_sessions_filters_by_tag(self, handler, command_context, session_manager, mock_send_message):
+        session_manager.set_tags(command_context.session.id, ["bugfix", "urgent"])
+
+        await handler.handle_sessions(ParsedCommand(name="sessions", args=["#bugfix"]), command_context)
+        lines = mock_send_message.messages[-1]["text"].splitlines()
+        assert lines[0] == "Active sessions tagged `bugfix` (1):"
+        assert " · `bugfix` `urgent` · this thread" in lines[1]
+
+        await handler.handle_sessions(ParsedCommand(name="sessions", args=["bugfix", "ui"]), command_context)
+        assert mock_send_message.messages[-1]["text"] == "No active sessions tagged `bugfix` `ui`."
+
+    @pytest.mark.asyncio
+    async def test_handle_sessions_without_any(self, handler, command_context, session_manager, mock_send_message):
+        session_manager.update_status(command_context.session.id, SessionStatus.ENDED)
+
+        await handler.handle_sessions(ParsedCommand(name="sessions", args=[]), command_context)
+
+        assert mock_send_message.messages[-1]["text"] == "No active sessions."
+
+    @pytest.mark.asyncio
+    async def test_handle_takeover_ends_the_other_sessions(
+        self, test_config, command_context, session_manager, mock_send_message
+    ):
+        other = session_manager.create_session(
+            project=command_context.project,
+            channel_id="C1",
+            thread_ts="9.0",
+            agent_id="claude",
+            agent_type=AgentType.CLAUDE,
+        )
+        taken = []
+
+        async def take_over(sessions, channel, thread_ts):
+            taken.append(([session.id for session in sessions], channel, thread_ts))
+            return True
+
+        handler = SessionCommandHandler(
+            session_manager=session_manager, config=test_config, send_message=mock_send_message, take_over=take_over
+        )
+
+        await handler.handle_takeover(ParsedCommand(name="takeover", args=[]), command_context)
+
+        assert taken == [([other.id], command_context.channel, command_context.thread_ts)]
+
+    @pytest.mark.asyncio
+    async def test_handle_takeover_without_other_sessions(self, handler, command_context, mock_send_message):
+        await handler.handle_takeover(ParsedCommand(name="takeover", args=[]), command_context)
+
+        assert mock_send_message.messages[-1]["text"] == "No other session is using `test-project`'s checkout."
+
+    @pytest.mark.asyncio
+    async def test_handle_cost_without_pricing(self, handler, command_context, mock_send_message):
+        command = ParsedCommand(name="cost", args=[])
+
+        await handler.handle_cost(command, command_context)
+
+        output = mock_send_message.messages[-1]["text"]
+        assert "Estimated session cost: $0.0000" in output
+        assert "No pricing configured for `claude`" in output
+
+    @pytest.mark.asyncio
+    async def test_handle_cost_with_pricing(self, handler, command_context, session_manager, test_config, mock_send_message):
+        test_config.agents["claude"].pricing = AgentPricing(input_per_1k_tokens_usd=0.003, output_per_1k_tokens_usd=0.015)
+        session_manager.record_run_cost(command_context.session.id, 0.25)
+        command = ParsedCommand(name="cost", args=[])
+
+        await handler.handle_cost(command, command_context)
+
+        output = mock_send_message.messages[-1]["text"]
+        assert "Estimated session cost: $0.25" in output
+        assert "Pricing for `claude` `sonnet`" in output
@@ -0,0 +1,68 @@
+"""Tests for the tag command handler."""
+
+from __future__ import annotations
+
+from unittest.mock import AsyncMock
+
+import pytest
+
+from src.core.commands.parser import ParsedCommand
+from src.core.commands.tags import TagCommandHandler
+from src.core.tags import MAX_TAGS, parse_tags
+
+
+class TestTagCommands:
+    """Test cases for !tag and !untag."""
+
+    @pytest.mark.asyncio
+    async def test_tags_are_saved_and_label_the_prs(self, session_manager, command_context, mock_send_message):
+        label = AsyncMock(return_value=["Labelled PR #7."])
+        handler = TagCommandHandler(
+            session_manager=session_manager, send_message=mock_send_message, label_pull_requests=label
+        )
+        session = command_context.session
+        session_manager.set_tags(session.id, ["bugfix"])
+
+        await handler.handle_tag(ParsedCommand(name="tag", args=["#Urgent", "bugfix", "ui"]), command_context)
+
+        assert session_manager.get_session(session.id).tags == ["bugfix", "urgent", "ui"]
+        label.assert_awaited_once_with(session, command_context.project, ["urgent", "ui"])
+        assert mock_send_message.messages[-1]["text"] == "Tagged: `bugfix` `urgent` `ui`\nLabelled PR #7."
+
+    @pytest.mark.asyncio
+    async def test_tag_alone_lists_the_tags(self, session_manager, command_context, mock_send_message):
+        handler = TagCommandHandler(session_manager=session_manager, send_message=mock_send_message)
+
+        await handler.handle_tag(ParsedCommand(name="tag", args=[]), command_context)
+        assert mock_send_message.messages[-1]["text"] == "No tags yet. Add some with `!tag bugfix urgent`."
+
+        session_manager.set_tags(command_context.session.id, ["bugfix"])
+        await handler.handle_tag(ParsedCommand(name="tag", args=[]), command_context)
+        assert mock_send_message.messages[-1]["text"] == "Tags: `bugfix`"
+
+    @pytest.mark.asyncio
+    async def test_rejects_invalid_and_too_many_tags(self, session_manager, command_context, mock_send_message):
+        handler = TagCommandHandler(session_manager=session_manager, send_message=mock_send_message)
+
+        await handler.handle_tag(ParsedCommand(name="tag", args=["ok", "not/a/tag"]), command_context)
+        assert mock_send_message.messages[-1]["text"].startswith("Not a tag: not/a/tag.")
+
+        many = [f"t{n}" for n in range(MAX_TAGS + 1)]
+        await handler.handle_tag(ParsedCommand(name="tag", args=many), command_context)
+        assert mock_send_message.messages[-1]["text"] == f"A session can have up to {MAX_TAGS} tags; `!untag` some first."
+        assert command_context.session.tags == []
+
+    @pytest.mark.asyncio
+    async def test_untag_removes_tags(self, session_manager, command_context, mock_send_message):
+        handler = TagCommandHandler(session_manager=session_manager, send_message=mock_send_message)
+        session_manager.set_tags(command_context.session.id, ["bugfix", "urgent"])
+
+        await handler.handle_untag(ParsedCommand(name="untag", args=["urgent", "other"]), command_context)
+        assert mock_send_message.messages[-1]["text"] == "Tags: `bugfix`"
+
+        await handler.handle_untag(ParsedCommand(name="untag", args=["urgent"]), command_context)
+        assert mock_send_message.messages[-1]["text"] == "Not tagged: `urgent`"
+
+
+def test_parse_tags_normalizes_and_reports_invalid_words():
+    assert parse_tags(["#BugFix", "bugfix", "v1.2", "-x", "a b"]) == (["bugfix", "v1.2"], ["-x", "a b"])
@@ -0,0 +1,98 @@
+"""Tests for the !timeline and !checkout command handlers."""
+
+from __future__ import annotations
+
+import pytest
+
+from src.core.commands.parser import parse_command
+from src.core.commands.timeline import TimelineCommandHandler
+from src.core.timeline import CHECKED_OUT_CONTEXT_KEY, add_checkpoint, checked_out_run
+from tests.conftest import git
+
+
+def _commit(repo, name: str, text: str) -> str:
+    (repo / name).write_text(text)
+    git(repo, "add", "-A")
+    git(repo, "commit", "-q", "-m", f"edit {name}")
+    return git(repo, "rev-parse", "HEAD").strip()
+
+
+@pytest.fixture
+def timeline_context(command_context, session_manager, git_identity):
+    """A git project on `main` whose session recorded two runs."""
+    repo = command_context.session.project_path
+    git(repo, "init", "-q", "-b", "main")
+    start = _commit(repo, "README.md", "hello\n")
+    first = _commit(repo, "app.py", "print(1)\n")
+    second = _commit(repo, "app.py", "print(2)\n")
+    session = command_context.session
+    session_manager.update_session_context(session.id, add_checkpoint(session, "add app", start, first, ["app.py"]))
+    session_manager.update_session_context(session.id, add_checkpoint(session, "print 2", first, second, ["app.py"]))
+    return command_context
+
+
+def _handler(session_manager, mock_send_message, active_runs=None):
+    return TimelineCommandHandler(
+        session_manager=session_manager, active_runs=active_runs or {}, send_message=mock_send_message
+    )
+
+
+class TestTimelineCommands:
+    """Test cases for browsing checkpoints."""
+
+    @pytest.mark.asyncio
+    async def test_timeline_lists_runs(self, timeline_context, session_manager, mock_send_message):
+        await _handler(session_manager, mock_send_message).handle_timeline(parse_command("!timeline"), timeline_context)
+
+        text = mock_send_message.messages[-1]["text"]
+        assert "1. `" in text and "“add app” — 1 file: `app.py`" in text
+        assert "2. `" in text and "“print 2”" in text
+
+    @pytest.mark.asyncio
+    async def test_checkout_and_back_to_latest(self, timeline_context, session_manager, mock_send_message):
+        handler = _handler(session_manager, mock_send_message)
+        session = timeline_context.session
+        repo = session.project_path
+
+        await handler.handle_checkout(parse_command("!checkout 1"), timeline_context)
+
+        assert "checkpoint 1" in mock_send_message.messages[-1]["text"]
+        assert (repo / "app.py").read_text() == "print(1)\n"
+        assert checked_out_run(session) == 1
+        assert git(repo, "rev-parse", "main").strip() == session.session_context["timeline"][1]["commit"]
+
+        await handler.handle_checkout(parse_command("!checkout latest"), timeline_context)
+
+        assert "Back on `main`" in mock_send_message.messages[-1]["text"]
+        assert git(repo, "rev-parse", "--abbrev-ref", "HEAD").strip() == "main"
+        assert (repo / "app.py").read_text() == "print(2)\n"
+        assert checked_out_run(session) is None
+
+    @pytest.mark.asyncio
+    async def test_checkout_refuses_uncommitted_changes(self, timeline_context, session_manager, mock_send_message):
+        (timeline_context.session.project_path / "app.py").write_text("wip\n")
+
+        await _handler(session_manager, mock_send_message).handle_checkout(
+            parse_command("!checkout 1"), timeline_context
+        )
+
+        assert "uncommitted changes" in mock_send_message.messages[-1]["text"]
+        assert CHECKED_OUT_CONTEXT_KEY not in timeline_context.session.session_context
+
+    @pytest.mark.asyncio
+    async def test_unknown_checkpoint(self, timeline_context, session_manager, mock_send_message):
+        await _handler(session_manager, mock_send_message).handle_checkout(
+            parse_command("!checkout 7"), timeline_context
+        )
+
+        assert "No checkpoint `7`; `!timeline` lists 1 to 2." == mock_send_message.messages[-1]["text"]
+
+    @pytest.mark.asyncio
+    async def test_refuses_while_agent_runs(self, timeline_context, session_manager, mock_send_message):
+        active_runs = {"run": {"session_id": str(timeline_context.session.id)}}
+
+        await _handler(session_manager, mock_send_message, active_runs).handle_checkout(
+            parse_command("!checkout 1"), timeline_context
+        )
+
+        assert "still running" in mock_send_message.messages[-1]["text"]
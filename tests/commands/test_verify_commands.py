@@ -0,0 +1,50 @@
+"""Tests for the !test command handler."""
+
+from __future__ import annotations
+
+from dataclasses import replace
+
+import pytest
+
+from src.core.commands.parser import ParsedCommand
+from src.core.commands.verify import VerifyCommandHandler
+from src.core.models import CommandStep
+
+
+class TestTestCommand:
+    """Test cases for the !test command."""
+
+    @pytest.mark.asyncio
+    async def test_runs_project_checks(self, command_context, mock_send_message, tmp_path):
+        handler = VerifyCommandHandler(active_runs={}, send_message=mock_send_message)
+        context = replace(
+            command_context,
+            project=replace(command_context.project, verify_commands=[CommandStep("echo ok")]),
+            session=replace(command_context.session, project_path=tmp_path),
+        )
+
+        await handler.handle_test(ParsedCommand(name="test", args=[], raw_args=""), context)
+
+        assert "`process` sandbox" in mock_send_message.messages[0]["text"]
+        assert "`echo ok`: passed" in mock_send_message.messages[-1]["text"]
+
+    @pytest.mark.asyncio
+    async def test_without_steps(self, command_context, mock_send_message):
+        handler = VerifyCommandHandler(active_runs={}, send_message=mock_send_message)
+
+        await handler.handle_test(ParsedCommand(name="test", args=[], raw_args=""), command_context)
+
+        assert "No verify steps configured" in mock_send_message.messages[-1]["text"]
+
+    @pytest.mark.asyncio
+    async def test_refuses_during_agent_run(self, command_context, mock_send_message):
+        active_runs = {"run": {"session_id": str(command_context.session.id)}}
+        handler = VerifyCommandHandler(active_runs=active_runs, send_message=mock_send_message)
+        context = replace(
+            command_context,
+            project=replace(command_context.project, verify_commands=[CommandStep("true")]),
+        )
+
+        await handler.handle_test(ParsedCommand(name="test", args=[], raw_args=""), context)
+
+        assert "still running" in mock_send_message.messages[-1]["text"]
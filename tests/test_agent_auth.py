@@ -0,0 +1,203 @@
+"""Tests for agent credential detection, stored secrets, and device-code logins."""
+
+from __future__ import annotations
+
+import json
+import stat
+
+import pytest
+
+from src.core import agent_auth
+from src.core.agent_auth import (
+    AUTH_METHODS,
+    AuthState,
+    DeviceLogin,
+    auth_failure_hint,
+    check_agent_auth,
+    format_auth_checks,
+    looks_like_auth_failure,
+)
+from src.core.errors import ConfigError
+from src.core.models import Agent, AgentType, WorkingDirMode
+from src.core.secrets import SecretStore
+
+
+def _agent(agent_type=AgentType.CODEX, *, agent_id="codex", command=("codex",), env=None, api_key_env=None) -> Agent:
+    return Agent(
+        id=agent_id,
+        type=agent_type,
+        command=list(command),
+        working_dir_mode=WorkingDirMode.PROJECT,
+        env=env or {},
+        api_key_env=api_key_env,
+    )
+
+
+@pytest.fixture(autouse=True)
+def clean_env(monkeypatch, tmp_path):
+    """No credentials in the environment and an empty home directory."""
+    for method in AUTH_METHODS.values():
+        for name in method.env_vars:
+            monkeypatch.delenv(name, raising=False)
+    for name in ("CLAUDE_CONFIG_DIR", "CODEX_HOME", "CUSTOM_KEY"):
+        monkeypatch.delenv(name, raising=False)
+    home = tmp_path / "home"
+    home.mkdir()
+    monkeypatch.setenv("HOME", str(home))
+    return home
+
+
+class TestCheckAgentAuth:
+    """Test cases for finding an agent's credentials."""
+
+    def test_missing_credentials_list_where_they_were_looked_for(self, clean_env):
+        check = check_agent_auth(_agent())
+        assert check.state == AuthState.MISSING
+        assert "OPENAI_API_KEY" in check.looked_in
+        assert "~/.codex/auth.json" in check.looked_in
+        assert check.device_login
+
+    def test_env_var_in_agent_env(self):
+        check = check_agent_auth(_agent(env={"OPENAI_API_KEY": "sk-test"}))
+        assert check.state == AuthState.OK
+        assert check.source == "OPENAI_API_KEY"
+
+    def test_credential_file(self, clean_env):
+        (clean_env / ".codex").mkdir()
+        (clean_env / ".codex" / "auth.json").write_text("{}")
+        check = check_agent_auth(_agent())
+        assert check.state == AuthState.OK
+        assert check.source == "~/.codex/auth.json"
+
+    def test_claude_config_dir_override(self, tmp_path, monkeypatch):
+        config_dir = tmp_path / "claude-config"
+        config_dir.mkdir()
+        (config_dir / ".credentials.json").write_text("{}")
+        monkeypatch.setattr(agent_auth.sys, "platform", "linux")
+        agent = _agent(AgentType.CLAUDE, agent_id="claude", env={"CLAUDE_CONFIG_DIR": str(config_dir)})
+        assert check_agent_auth(agent).state == AuthState.OK
+
+    def test_claude_on_macos_may_use_the_keychain(self, monkeypatch):
+        monkeypatch.setattr(agent_auth.sys, "platform", "darwin")
+        assert check_agent_auth(_agent(AgentType.CLAUDE, agent_id="claude")).state == AuthState.UNKNOWN
+
+    def test_agents_without_credentials(self):
+        assert check_agent_auth(_agent(AgentType.OLLAMA, agent_id="ollama")) is None
+        assert check_agent_auth(_agent(AgentType.HTTP, agent_id="local-http")) is None
+
+    def test_http_agent_with_api_key_env(self):
+        check = check_agent_auth(_agent(AgentType.HTTP, agent_id="vllm", api_key_env="CUSTOM_KEY"))
+        assert check.state == AuthState.MISSING
+        assert check.looked_in == ("CUSTOM_KEY",)
+        assert "remote-coder auth vllm" in check.instructions
+
+    def test_format_auth_checks(self):
+        text = format_auth_checks(
+            [check_agent_auth(_agent()), check_agent_auth(_agent(AgentType.OPENROUTER, agent_id="or"))]
+        )
+        assert "`codex`: MISSING. `!auth codex` starts a device-code login." in text
+        assert "`or`: MISSING." in text
+
+
+class TestAuthFailureHint:
+    """Test cases for explaining failed runs."""
+
+    @pytest.mark.parametrize(
+        "output",
+        ["Error: 401 Unauthorized", "Invalid API key · Please run /login", "HTTP 401", "Your access token has expired"],
+    )
+    def test_recognises_auth_failures(self, output):
+        assert looks_like_auth_failure(output)
+
+    def test_ignores_other_failures(self):
+        assert not looks_like_auth_failure("error[E0308]: mismatched types at line 401:12")
+
+    def test_hint_for_missing_credentials(self):
+        hint = auth_failure_hint(_agent(), "process exited with code 1")
+        assert hint.startswith("No credentials found for `codex`")
+
+    def test_hint_for_rejected_credentials(self):
+        hint = auth_failure_hint(_agent(env={"OPENAI_API_KEY": "sk-old"}), "401 Unauthorized")
+        assert "authentication problem with `codex` (credentials from OPENAI_API_KEY)" in hint
+
+    def test_no_hint_for_unrelated_failures(self):
+        assert auth_failure_hint(_agent(env={"OPENAI_API_KEY": "sk-test"}), "tests failed") is None
+
+
+class TestSecretStore:
+    """Test cases for tokens stored by `remote-coder auth`."""
+
+    def test_set_writes_a_private_file(self, tmp_path):
+        store = SecretStore(tmp_path)
+        store.set("codex", "OPENAI_API_KEY", "sk-test")
+        assert stat.S_IMODE(store.path.stat().st_mode) == 0o600
+        assert json.loads(store.path.read_text()) == {"agents": {"codex": {"OPENAI_API_KEY": "sk-test"}}}
+        assert store.env_for("codex") == {"OPENAI_API_KEY": "sk-test"}
+
+    def test_apply_keeps_explicit_values(self, tmp_path, monkeypatch):
+        store = SecretStore(tmp_path)
+        store.set("codex", "OPENAI_API_KEY", "sk-stored")
+        store.set("codex", "CODEX_API_KEY", "stored")
+        store.set("gone", "TOKEN", "x")
+        monkeypatch.setenv("CODEX_API_KEY", "from-env")
+        agent = _agent(env={"OPENAI_API_KEY": "sk-yaml"})
+        store.apply({"codex": agent})
+        assert agent.env == {"OPENAI_API_KEY": "sk-yaml"}
+
+        fresh = _agent()
+        store.apply({"codex": fresh})
+        assert fresh.env == {"OPENAI_API_KEY": "sk-stored"}
+
+    def test_remove(self, tmp_path):
+        store = SecretStore(tmp_path)
+        store.set("codex", "OPENAI_API_KEY", "sk-test")
+        assert store.remove("codex")
+        assert not store.remove("codex")
+        assert store.env_for("codex") == {}
+
+    def test_invalid_file(self, tmp_path):
+        (tmp_path / "secrets.json").write_text("[1, 2]")
+        with pytest.raises(ConfigError):
+            SecretStore(tmp_path).env_for("codex")
+
+
+@pytest.fixture
+def fake_login_cli(tmp_path):
+    """A `codex` CLI whose device login prints a link and code, then succeeds."""
+    script = tmp_path / "codex"
+    script.write_text(
+        "#!/bin/sh\n"
+        "echo 'Open this link in your browser:'\n"
+        "echo '  \033[94mhttps://auth.example.com/codex/device\033[0m'\n"
+        "echo 'Enter this one-time code: ABCD-12345'\n"
+        "sleep 0.1\n"
+        "echo 'Successfully logged in'\n"
+    )
+    script.chmod(0o755)
+    return script
+
+
+class TestDeviceLogin:
+    """Test cases for device-code logins of agent CLIs."""
+
+    @pytest.mark.asyncio
+    async def test_parses_link_and_code_then_waits(self, fake_login_cli):
+        login = await DeviceLogin.start(_agent(command=(str(fake_login_cli),)))
+        assert login.url == "https://auth.example.com/codex/device"
+        assert login.code == "ABCD-12345"
+        assert "ABCD-12345" in login.prompt()
+        assert await login.wait(timeout=10)
+        assert "Successfully logged in" in login.output
+
+    @pytest.mark.asyncio
+    async def test_fails_without_a_link(self, tmp_path):
+        script = tmp_path / "codex"
+        script.write_text("#!/bin/sh\necho 'unknown flag --device-auth'\nexit 2\n")
+        script.chmod(0o755)
+        with pytest.raises(RuntimeError, match="did not print a verification link"):
+            await DeviceLogin.start(_agent(command=(str(script),)))
+
+    @pytest.mark.asyncio
+    async def test_agents_without_device_login(self):
+        with pytest.raises(RuntimeError):
+            await DeviceLogin.start(_agent(AgentType.OPENROUTER, agent_id="or"))
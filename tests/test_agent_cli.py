@@ -0,0 +1,140 @@
+"""Tests for agent CLI detection and version checks."""
+
+from __future__ import annotations
+
+import pytest
+
+from src.core.agent_cli import AgentCliChecker, CliCheck, format_cli_checks, is_older, parse_version, probe_agent_cli
+from src.core.config import _load_agents
+from src.core.errors import ConfigError
+from src.core.models import Agent, AgentType, WorkingDirMode
+
+
+def _agent(command, *, agent_type=AgentType.CODEX, min_version=None, env=None) -> Agent:
+    return Agent(
+        id="codex",
+        type=agent_type,
+        command=command,
+        working_dir_mode=WorkingDirMode.PROJECT,
+        env=env or {},
+        min_version=min_version,
+    )
+
+
+@pytest.fixture
+def fake_cli(tmp_path):
+    """A `codex` executable on a private PATH that reports version 0.46.0."""
+    bin_dir = tmp_path / "bin"
+    bin_dir.mkdir()
+    script = bin_dir / "codex"
+    script.write_text("#!/bin/sh\necho 'codex-cli 0.46.0'\n")
+    script.chmod(0o755)
+    return {"PATH": str(bin_dir)}
+
+
+class TestVersions:
+    """Test cases for version parsing and comparison."""
+
+    def test_parse_version(self):
+        assert parse_version("codex-cli 0.46.0") == "0.46.0"
+        assert parse_version("2.0.14 (Claude Code)") == "2.0.14"
+        assert parse_version("no version here") is None
+
+    def test_is_older(self):
+        assert is_older("0.46.0", "0.50")
+        assert is_older("1.9.9", "1.10.0")
+        assert not is_older("1.10", "1.10.0")
+        assert not is_older("2.0.0", "1.99")
+
+
+class TestProbeAgentCli:
+    """Test cases for probe_agent_cli."""
+
+    def test_reports_path_and_version(self, fake_cli):
+        check = probe_agent_cli(_agent(["codex", "exec"], env=fake_cli))
+
+        assert check.ok
+        assert check.version == "0.46.0"
+        assert check.path.endswith("/bin/codex")
+
+    def test_missing_binary(self, tmp_path):
+        check = probe_agent_cli(_agent(["codex"], env={"PATH": str(tmp_path)}))
+
+        assert not check.ok
+        assert "`codex` was not found on PATH" in check.problem
+
+    def test_below_min_version(self, fake_cli):
+        check = probe_agent_cli(_agent(["codex"], env=fake_cli, min_version="0.50.0"))
+
+        assert check.problem == "`codex` 0.46.0 is older than the required 0.50.0; upgrade it."
+
+    def test_api_agents_have_no_cli(self):
+        assert probe_agent_cli(_agent([], agent_type=AgentType.OPENROUTER)) is None
+
+
+class TestAgentCliChecker:
+    """Test cases for AgentCliChecker."""
+
+    @pytest.mark.asyncio
+    async def test_caches_until_cleared(self):
+        calls = []
+
+        def probe(agent):
+            calls.append(agent.id)
+            return CliCheck(agent_id=agent.id, binary="codex", version="1.0")
+
+        checker = AgentCliChecker(probe=probe)
+        agent = _agent(["codex"])
+        await checker.check(agent)
+        await checker.check(agent)
+        assert calls == ["codex"]
+
+        checker.clear()
+        await checker.check(agent)
+        assert calls == ["codex", "codex"]
+
+    @pytest.mark.asyncio
+    async def test_check_all_skips_agents_without_cli(self):
+        checker = AgentCliChecker(probe=lambda agent: None)
+
+        assert await checker.check_all([_agent([], agent_type=AgentType.HTTP)]) == []
+        assert checker.results() == []
+
+    def test_format(self):
+        text = format_cli_checks(
+            [
+                CliCheck(agent_id="claude", binary="claude", version="2.0.14", path="/usr/bin/claude"),
+                CliCheck(agent_id="codex", binary="codex", problem="`codex` was not found on PATH"),
+            ]
+        )
+
+        assert text.splitlines() == [
+            "Agent CLIs:",
+            "• `claude`: claude 2.0.14",
+            "• `codex`: UNAVAILABLE: `codex` was not found on PATH",
+        ]
+        assert format_cli_checks([]) == "Agent CLIs: none checked yet"
+
+
+class TestMinVersionConfig:
+    """Test cases for parsing min_version from agents.yaml."""
+
+    def test_parses_min_version(self, tmp_path):
+        agents_yaml = tmp_path / "agents.yaml"
+        agents_yaml.write_text('agents:\n  codex:\n    type: codex\n    command: [codex]\n    min_version: "0.46"\n')
+
+        assert _load_agents(agents_yaml)["codex"].min_version == "0.46"
+
+    def test_rejects_invalid_min_version(self, tmp_path):
+        agents_yaml = tmp_path / "agents.yaml"
+        agents_yaml.write_text("agents:\n  codex:\n    type: codex\n    command: [codex]\n    min_version: latest\n")
+
+        with pytest.raises(ConfigError, match="min_version"):
+            _load_agents(agents_yaml)
+
+    def test_rejects_min_version_for_api_agents(self, tmp_path):
+        agents_yaml = tmp_path / "agents.yaml"
+        agents_yaml.write_text('agents:\n  demo:\n    type: mock\n    min_version: "1.0"\n')
+
+        with pytest.raises(ConfigError, match="only valid for CLI agents"):
+            _load_agents(agents_yaml)
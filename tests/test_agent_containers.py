@@ -0,0 +1,159 @@
+"""Tests for running agent CLIs inside a project's container."""
+
+from __future__ import annotations
+
+import os
+import sys
+from pathlib import Path
+
+import pytest
+
+from src.agent_adapters.base import spawn_cli
+from src.agent_adapters.containers import AgentContainer, agent_container_argv, run_agents_in_container
+from src.core.config import _load_agents, _load_projects
+from src.core.errors import ConfigError
+from src.core.models import ProcessLimits, SandboxConfig, SandboxMode
+
+# Stands in for docker: logs each invocation, runs nothing, and echoes what `run` would start
+FAKE_RUNTIME = (
+    "import sys\n"
+    "with open(sys.argv[0] + '.log', 'a') as log:\n"
+    "    log.write(' '.join(sys.argv[1:]) + '\\n')\n"
+    "if sys.argv[1] == 'run':\n"
+    "    print('started', sys.argv[-1])\n"
+)
+
+
+def _sandbox(**overrides) -> SandboxConfig:
+    fields = {"mode": SandboxMode.CONTAINER, "image": "coder:1", "network": True, "run_agent": True, **overrides}
+    return SandboxConfig(**fields)
+
+
+def _fake_runtime(tmp_path: Path) -> Path:
+    runtime = tmp_path / "fake-docker"
+    runtime.write_text(f"#!{sys.executable}\n{FAKE_RUNTIME}")
+    runtime.chmod(0o755)
+    return runtime
+
+
+class TestAgentContainer:
+    """Test cases for starting agent processes in a container."""
+
+    def test_argv_mounts_the_workdir_at_the_same_path(self, tmp_path):
+        container = AgentContainer(sandbox=_sandbox(network=False, memory_mb=2048), env_names=("ANTHROPIC_API_KEY",))
+
+        argv = agent_container_argv(container, ["claude", "-p", "hi"], tmp_path, "agent-1")
+
+        workdir = str(tmp_path.resolve())
+        assert argv[:5] == ["docker", "run", "--rm", "--interactive", "--name"]
+        assert argv[argv.index("--volume") + 1] == f"{workdir}:{workdir}"
+        assert argv[argv.index("--workdir") + 1] == workdir
+        assert argv[argv.index("--network") + 1] == "none"
+        assert argv[argv.index("--memory") + 1] == "2048m"
+        assert "ANTHROPIC_API_KEY" in argv and "HOME=/tmp" in argv
+        assert argv[-4:] == ["coder:1", "claude", "-p", "hi"]
+
+    def test_argv_mounts_files_outside_the_workdir_read_only(self, tmp_path):
+        workdir = tmp_path / "repo"
+        workdir.mkdir()
+        (workdir / "notes.md").write_text("inside")
+        prompt = tmp_path / "prompt.txt"
+        prompt.write_text("task")
+        container = AgentContainer(sandbox=_sandbox())
+
+        argv = agent_container_argv(
+            container, ["cli", f"--prompt-file={prompt}", str(workdir / "notes.md")], workdir, "agent-1"
+        )
+
+        volumes = [argv[index + 1] for index, arg in enumerate(argv) if arg == "--volume"]
+        assert volumes[1:] == [f"{prompt.resolve()}:{prompt.resolve()}:ro"]
+        assert "--network" not in argv
+
+    def test_limits_become_container_limits(self, tmp_path):
+        limits = ProcessLimits(cpu_secs=600, memory_mb=4096, open_files=512, cpu_percent=150, cgroup=True)
+
+        argv = agent_container_argv(AgentContainer(sandbox=_sandbox()), ["cli"], tmp_path, "agent-1", limits)
+
+        assert argv[argv.index("--memory") + 1] == "4096m"
+        assert argv[argv.index("--cpus") + 1] == "1.5"
+        assert [argv[index + 1] for index, arg in enumerate(argv) if arg == "--ulimit"] == ["cpu=600", "nofile=512"]
+
+    @pytest.mark.asyncio
+    async def test_spawn_cli_runs_in_the_container_and_removes_it(self, tmp_path):
+        runtime = _fake_runtime(tmp_path)
+        container = AgentContainer(sandbox=_sandbox(runtime=str(runtime)))
+
+        with run_agents_in_container(container):
+            async with spawn_cli(["claude", "-p"], cwd=tmp_path, env=os.environ, stdin=False) as process:
+                output = (await process.stdout.read()).decode()
+                await process.wait()
+
+        assert output.strip() == "started -p"
+        run, remove = Path(f"{runtime}.log").read_text().splitlines()
+        name = run.split()[run.split().index("--name") + 1]
+        assert run.endswith("coder:1 claude -p")
+        assert remove == f"rm --force {name}"
+
+    @pytest.mark.asyncio
+    async def test_spawn_cli_runs_on_the_host_outside_a_container(self, tmp_path):
+        command = [sys.executable, "-c", "print('host')"]
+
+        async with spawn_cli(command, cwd=tmp_path, env=os.environ, stdin=False) as process:
+            output = (await process.stdout.read()).decode()
+            await process.wait()
+
+        assert output.strip() == "host"
+
+
+class TestAgentContainerConfig:
+    """Test cases for `sandbox: docker` in projects.yaml."""
+
+    @staticmethod
+    def _load(tmp_path: Path, sandbox: str):
+        (tmp_path / "repo").mkdir(exist_ok=True)
+        projects_yaml = tmp_path / "projects.yaml"
+        projects_yaml.write_text(
+            f"base_dir: {tmp_path}\nprojects:\n  demo:\n    path: repo\n    default_agent: claude\n"
+            f"    sandbox:\n{sandbox}"
+        )
+        projects, _ = _load_projects(projects_yaml)
+        return projects["demo"].sandbox
+
+    def test_docker_mode_runs_the_agent_online(self, tmp_path):
+        sandbox = self._load(tmp_path, "      mode: docker\n      image: coder:1\n")
+
+        assert sandbox.mode == SandboxMode.CONTAINER
+        assert sandbox.runtime == "docker"
+        assert sandbox.run_agent
+        assert sandbox.network
+
+    def test_podman_mode_without_network(self, tmp_path):
+        sandbox = self._load(tmp_path, "      mode: podman\n      image: coder:1\n      network: false\n")
+
+        assert sandbox.runtime == "podman"
+        assert sandbox.run_agent and not sandbox.network
+
+    def test_container_mode_keeps_the_agent_on_the_host(self, tmp_path):
+        sandbox = self._load(tmp_path, "      mode: container\n      image: coder:1\n")
+
+        assert not sandbox.run_agent
+        assert not sandbox.network
+
+    @pytest.mark.parametrize(
+        "sandbox,message",
+        [
+            ("      mode: docker\n", "mode docker requires an image"),
+            ("      mode: process\n      run_agent: true\n", "run_agent requires mode docker"),
+            ("      mode: docker\n      image: coder:1\n      run_agent: sure\n", "run_agent must be true or false"),
+        ],
+    )
+    def test_rejects_invalid_values(self, tmp_path, sandbox, message):
+        with pytest.raises(ConfigError, match=message):
+            self._load(tmp_path, sandbox)
+
+    def test_agents_cannot_use_docker_mode(self, tmp_path):
+        agents_yaml = tmp_path / "agents.yaml"
+        agents_yaml.write_text("agents:\n  cli:\n    type: codex\n    command: [cli]\n    sandbox: docker\n")
+
+        with pytest.raises(ConfigError, match="only available for projects"):
+            _load_agents(agents_yaml)
@@ -0,0 +1,145 @@
+"""Tests for capability-based agent selection."""
+
+from __future__ import annotations
+
+import pytest
+
+from src.core.agent_selection import TaskRequirements, languages_for_files, select_agent
+from src.core.config import _load_agents, _load_projects
+from src.core.errors import ConfigError
+from src.core.models import Agent, AgentCapabilities, AgentType, CostTier, WorkingDirMode
+
+
+def _agent(agent_id: str, **capabilities) -> Agent:
+    return Agent(
+        id=agent_id,
+        type=AgentType.CLAUDE,
+        command=[agent_id],
+        working_dir_mode=WorkingDirMode.PROJECT,
+        capabilities=AgentCapabilities(**capabilities),
+    )
+
+
+class TestSelectAgent:
+    """Test cases for select_agent."""
+
+    def test_keeps_default_when_nothing_is_better(self):
+        agents = [_agent("claude"), _agent("codex", cost_tier=CostTier.LOW)]
+
+        choice = select_agent(agents, TaskRequirements(), "claude")
+
+        assert choice is not None
+        assert choice.agent.id == "claude"
+        assert choice.reason == "project default"
+
+    def test_images_rule_out_agents_without_support(self):
+        agents = [_agent("codex"), _agent("claude", supports_images=True)]
+
+        choice = select_agent(agents, TaskRequirements(needs_images=True), "codex")
+
+        assert choice.agent.id == "claude"
+        assert "`codex` cannot read images" in choice.reason
+
+    def test_large_requests_skip_small_context_agents(self):
+        agents = [
+            _agent("small", max_context=8_000),
+            _agent("large-pricey", max_context=200_000, cost_tier=CostTier.HIGH),
+            _agent("large-cheap", max_context=128_000, cost_tier=CostTier.LOW),
+        ]
+
+        choice = select_agent(agents, TaskRequirements(estimated_tokens=50_000), "small")
+
+        assert choice.agent.id == "large-cheap"
+        assert "8,000-token context" in choice.reason
+
+    def test_language_match_beats_default(self):
+        agents = [_agent("claude"), _agent("rusty", languages=["rust"])]
+
+        choice = select_agent(agents, TaskRequirements(languages=frozenset({"rust"})), "claude")
+
+        assert choice.agent.id == "rusty"
+        assert choice.reason == "best match for rust"
+
+    def test_no_agent_can_take_the_request(self):
+        agents = [_agent("claude"), _agent("codex")]
+
+        assert select_agent(agents, TaskRequirements(needs_images=True), "claude") is None
+
+
+class TestLanguageDetection:
+    """Project language detection from tracked files."""
+
+    def test_ignores_minor_languages(self):
+        paths = [f"src/mod{i}.py" for i in range(8)] + ["scripts/run.sh", "README.md", "web/a.ts", "web/b.ts", "web/c.ts"]
+
+        assert languages_for_files(paths) == frozenset({"python", "typescript"})
+
+    def test_no_source_files(self):
+        assert languages_for_files(["README.md", "LICENSE"]) == frozenset()
+
+
+class TestCapabilitiesConfig:
+    """Parsing `capabilities` from agents.yaml."""
+
+    def test_parses_capabilities(self, tmp_path):
+        agents_yaml = tmp_path / "agents.yaml"
+        agents_yaml.write_text(
+            "agents:\n"
+            "  claude:\n"
+            "    type: claude\n"
+            "    command: [claude]\n"
+            "    capabilities:\n"
+            "      supports_images: true\n"
+            "      max_context: 200000\n"
+            "      cost_tier: HIGH\n"
+            "      languages: [Python, rust]\n"
+        )
+
+        capabilities = _load_agents(agents_yaml)["claude"].capabilities
+
+        assert capabilities.supports_images
+        assert capabilities.max_context == 200_000
+        assert capabilities.cost_tier == CostTier.HIGH
+        assert capabilities.languages == ["python", "rust"]
+
+    def test_defaults_without_capabilities(self, tmp_path):
+        agents_yaml = tmp_path / "agents.yaml"
+        agents_yaml.write_text("agents:\n  claude:\n    type: claude\n    command: [claude]\n")
+
+        assert _load_agents(agents_yaml)["claude"].capabilities == AgentCapabilities()
+
+    def test_rejects_unknown_cost_tier(self, tmp_path):
+        agents_yaml = tmp_path / "agents.yaml"
+        agents_yaml.write_text(
+            "agents:\n  claude:\n    type: claude\n    command: [claude]\n    capabilities:\n      cost_tier: free\n"
+        )
+
+        with pytest.raises(ConfigError, match="cost_tier"):
+            _load_agents(agents_yaml)
+
+
+class TestProjectAgentConfig:
+    """Test cases for per-project agent_models and allowed_agents."""
+
+    def _write(self, tmp_path, body: str):
+        (tmp_path / "repo").mkdir(exist_ok=True)
+        projects_yaml = tmp_path / "projects.yaml"
+        projects_yaml.write_text(
+            f"base_dir: {tmp_path}\nprojects:\n  demo:\n    path: repo\n    default_agent: claude\n" + body
+        )
+        return projects_yaml
+
+    def test_parses_overrides(self, tmp_path):
+        projects, _ = _load_projects(
+            self._write(tmp_path, "    agent_models:\n      claude: haiku\n    allowed_agents: [claude, codex]\n")
+        )
+
+        project = projects["demo"]
+        assert project.agent_models == {"claude": "haiku"}
+        assert project.allows_agent("codex")
+        assert not project.allows_agent("gemini")
+        assert project.model_for(_agent("claude")) == "haiku"
+
+    def test_default_agent_must_be_allowed(self, tmp_path):
+        with pytest.raises(ConfigError, match="not in its allowed_agents"):
+            _load_projects(self._write(tmp_path, "    allowed_agents: [codex]\n"))
@@ -0,0 +1,99 @@
+"""Tests for session analytics."""
+
+from __future__ import annotations
+
+from datetime import datetime, timedelta, timezone
+
+from src.core.analytics import BY_AGENT, BY_PROJECT, AnalyticsStore, OutcomeRow, format_outcome
+from src.core.conversation import SessionManager
+from src.core.events import PrMerged
+from src.core.models import AgentType, Project, PullRequestRef
+from src.core.transcripts import RunRecord
+
+
+def _start(manager: SessionManager, tmp_path, project_id: str, thread_ts: str):
+    project = Project(id=project_id, channel_name=project_id, path=tmp_path, default_agent_id="claude")
+    return manager.create_session(
+        project=project, channel_id="C1", thread_ts=thread_ts, agent_id="claude", agent_type=AgentType.CLAUDE
+    )
+
+
+def _run(manager: SessionManager, session, agent_id: str, success: bool = True, cost: float = 0.0, **extra):
+    manager.record_run(session.id, RunRecord(agent_id=agent_id, success=success, wall_secs=1.0, cost_usd=cost, **extra))
+
+
+def _pr(session, number: int) -> PullRequestRef:
+    return PullRequestRef(
+        project_id=session.project_id,
+        session_id=session.id,
+        number=number,
+        url=f"https://github.com/o/r/pull/{number}",
+        head_branch="b",
+        base_branch="main",
+    )
+
+
+class TestAnalyticsStore:
+    """Test cases for recording outcomes and reporting on them."""
+
+    def test_reports_outcomes_per_agent_and_project(self, tmp_path):
+        manager = SessionManager()
+        store = AnalyticsStore(tmp_path / "analytics.db")
+        store.follow(manager.events)
+
+        fixed = _start(manager, tmp_path, "api", "1.0")
+        _run(manager, fixed, "codex", success=False, cost=0.5)
+        _run(manager, fixed, "claude", cost=1.0)
+        manager.record_checks(fixed.id, False)
+        manager.record_checks(fixed.id, True)  # Only the latest result counts
+        manager.set_pr_ref(_pr(fixed, 7))
+        for _ in range(2):  # GitHub retries deliveries
+            manager.events.publish(PrMerged(fixed.id, "api", 7))
+        abandoned = _start(manager, tmp_path, "api", "2.0")
+        _run(manager, abandoned, "codex", success=False, cancelled=True)
+        _start(manager, tmp_path, "web", "3.0")  # No runs
+
+        codex, claude = store.report(BY_AGENT)  # Busiest first: both have a session, codex made more runs
+        assert (claude.key, claude.sessions, claude.turns, claude.checks_passed) == ("claude", 1, 2, 1)
+        assert claude.merged == 1
+        assert (claude.runs, claude.run_success_rate, claude.cost_usd) == (1, 1.0, 1.5)
+        assert (codex.sessions, codex.runs, codex.runs_cancelled, codex.run_success_rate) == (1, 2, 1, 0.0)
+        api, web = store.report(BY_PROJECT)
+        assert (api.key, api.sessions, api.turns, api.with_pr, api.merged, api.runs_succeeded) == ("api", 2, 3, 1, 1, 1)
+        assert (web.key, web.sessions, web.runs, web.run_success_rate) == ("web", 1, 0, None)
+        assert store.report(BY_PROJECT, since=datetime.now(timezone.utc) + timedelta(minutes=1)) == []
+
+    def test_keeps_outcomes_across_restarts_and_stops_at_close(self, tmp_path):
+        manager = SessionManager()
+        store = AnalyticsStore(tmp_path / "analytics.db")
+        store.follow(manager.events)
+        session = _start(manager, tmp_path, "api", "1.0")
+        _run(manager, session, "claude")
+        store.close()
+        _run(manager, session, "claude")  # Not recorded, and no error
+
+        [row] = AnalyticsStore(tmp_path / "analytics.db").report(BY_AGENT)
+        assert (row.sessions, row.runs) == (1, 1)
+
+    def test_formats_a_row(self):
+        row = OutcomeRow(
+            "claude",
+            sessions=4,
+            turns=10,
+            cost_usd=3.0,
+            checked=3,
+            checks_passed=2,
+            with_pr=3,
+            merged=2,
+            runs=9,
+            runs_succeeded=6,
+            runs_cancelled=1,
+        )
+
+        assert format_outcome(row) == (
+            "4 session(s), ~2.5 turn(s) each · runs 75% succeeded (6/8) · checks passed 2/3 · PRs merged 2/3 · "
+            "~$3.00, ~$1.50 per merge"
+        )
+        assert format_outcome(OutcomeRow("web", sessions=1)) == (
+            "1 session(s), ~0.0 turn(s) each · checks passed - · PRs merged - · ~$0.00"
+        )
@@ -0,0 +1,65 @@
+"""Tests for the approval policy and its small-change fast path."""
+
+from __future__ import annotations
+
+import pytest
+
+from src.core.approval import DiffSize, approval_reason, matches_protected, parse_numstat
+from src.core.config import _parse_approval
+from src.core.errors import ConfigError
+from src.core.models import ApprovalPolicy
+
+
+class TestApprovalReason:
+    """Test cases for deciding whether changes need approval."""
+
+    def test_parses_numstat(self):
+        size = parse_numstat("3\t1\tsrc/app.py\n-\t-\tlogo.png\n10\t0\tdocs/guide.md\n")
+
+        assert size.files == ["src/app.py", "logo.png", "docs/guide.md"]
+        assert size.lines == 14
+
+    def test_protected_paths(self):
+        patterns = ["migrations/", "*.lock", ".github/**"]
+
+        assert matches_protected("migrations/0001_init.sql", patterns)
+        assert matches_protected("Cargo.lock", patterns)
+        assert matches_protected(".github/workflows/ci.yml", patterns)
+        assert not matches_protected("src/migrations.py", patterns)
+
+    def test_small_change_skips_approval(self):
+        policy = ApprovalPolicy(required=True)
+
+        assert approval_reason(policy, DiffSize(files=["a.py", "b.py"], lines=30)) is None
+        assert "over the small-change limit" in approval_reason(policy, DiffSize(files=["a.py"], lines=31))
+
+    def test_zero_limit_disables_fast_path(self):
+        policy = ApprovalPolicy(required=True, max_files=0)
+
+        assert approval_reason(policy, DiffSize(files=["a.py"], lines=1)) == (
+            "this project requires approval for every change"
+        )
+
+
+class TestApprovalConfig:
+    """Test cases for the `approval` project setting."""
+
+    def test_mapping_requires_approval(self):
+        policy = _parse_approval("api", {"max_lines": 50, "protected_paths": ["infra/"]})
+
+        assert policy == ApprovalPolicy(required=True, max_files=3, max_lines=50, protected_paths=["infra/"])
+        assert not _parse_approval("api", None).required
+        assert not _parse_approval("api", {"required": False}).required
+
+    @pytest.mark.parametrize(
+        "raw, message",
+        [
+            ("yes", "approval must be true, false, or a mapping"),
+            ({"max_files": -1}, "approval max_files must be a non-negative integer"),
+            ({"protected_paths": "infra/"}, "approval protected_paths must be a list of path globs"),
+            ({"max_size": 3}, "approval has unsupported keys max_size"),
+        ],
+    )
+    def test_rejects_invalid_values(self, raw, message):
+        with pytest.raises(ConfigError, match=message):
+            _parse_approval("api", raw)
@@ -0,0 +1,146 @@
+"""Tests for uploading sessions to S3-compatible object storage."""
+
+from __future__ import annotations
+
+import json
+import threading
+from datetime import datetime, timezone
+from http.server import BaseHTTPRequestHandler, ThreadingHTTPServer
+
+import pytest
+
+from src.core.archive import ArchiveSettings, ObjectStorage, SessionArchive
+from src.core.errors import ArchiveError, ConfigError
+from src.core.export import SessionExport
+from src.core.models import AgentType, Session
+from src.core.privacy import PRIVACY, STANDARD, STRICT
+
+CREDENTIALS = {"AWS_ACCESS_KEY_ID": "AKIDEXAMPLE", "AWS_SECRET_ACCESS_KEY": "secret"}
+
+
+class _Bucket(BaseHTTPRequestHandler):
+    """Keeps PUT objects in `objects` by path; GET returns them or 404."""
+
+    objects: dict = {}
+
+    def do_PUT(self):
+        assert self.headers["Authorization"].startswith("AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/")
+        self.objects[self.path] = self.rfile.read(int(self.headers["Content-Length"]))
+        self.send_response(200)
+        self.end_headers()
+
+    def do_GET(self):
+        body = self.objects.get(self.path)
+        self.send_response(200 if body is not None else 404)
+        self.end_headers()
+        self.wfile.write(body or b"")
+
+    def log_message(self, *args):
+        pass
+
+
+@pytest.fixture
+def bucket():
+    _Bucket.objects = {}
+    server = ThreadingHTTPServer(("127.0.0.1", 0), _Bucket)
+    thread = threading.Thread(target=server.serve_forever, daemon=True)
+    thread.start()
+    try:
+        yield server
+    finally:
+        server.shutdown()
+        server.server_close()
+
+
+def _session(tmp_path) -> Session:
+    return Session(
+        project_id="api",
+        channel_id="C1",
+        thread_ts="1.0",
+        active_agent_id="claude",
+        active_agent_type=AgentType.CLAUDE,
+        project_path=tmp_path,
+    )
+
+
+class TestArchiveSettings:
+    """Test cases for reading the archive settings from the environment."""
+
+    def test_parses_bucket_prefix_and_endpoint(self):
+        settings = ArchiveSettings.from_env(
+            {
+                **CREDENTIALS,
+                "REMOTE_CODER_ARCHIVE_URL": "s3://team-bucket/remote-coder/",
+                "REMOTE_CODER_ARCHIVE_ENDPOINT": "https://minio.internal:9000/",
+            }
+        )
+
+        assert (settings.bucket, settings.prefix, settings.region) == ("team-bucket", "remote-coder", "us-east-1")
+        assert settings.base_url == "https://minio.internal:9000"
+        assert ArchiveSettings.from_env({}) is None
+
+    @pytest.mark.parametrize(
+        "environ,message",
+        [
+            ({**CREDENTIALS, "REMOTE_CODER_ARCHIVE_URL": "https://bucket"}, "must look like s3://bucket"),
+            ({"REMOTE_CODER_ARCHIVE_URL": "s3://bucket"}, "needs AWS_ACCESS_KEY_ID"),
+            (
+                {**CREDENTIALS, "REMOTE_CODER_ARCHIVE_URL": "s3://bucket", "REMOTE_CODER_ARCHIVE_ENDPOINT": "minio"},
+                "must be an http",
+            ),
+        ],
+    )
+    def test_rejects_invalid_settings(self, environ, message):
+        with pytest.raises(ConfigError, match=message):
+            ArchiveSettings.from_env(environ)
+
+
+class TestSessionArchive:
+    """Test cases for uploading and fetching sessions."""
+
+    def test_signs_requests(self):
+        settings = ArchiveSettings(bucket="b", access_key_id="AKIDEXAMPLE", secret_access_key="s", session_token="t")
+        now = datetime(2026, 1, 2, 3, 4, 5, tzinfo=timezone.utc)
+        url = "https://s3.us-east-1.amazonaws.com/b/k"
+
+        headers = ObjectStorage(settings).signed_headers("PUT", url, b"x", None, now)
+
+        assert headers["x-amz-date"] == "20260102T030405Z" and headers["x-amz-security-token"] == "t"
+        assert headers["authorization"].startswith(
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20260102/us-east-1/s3/aws4_request, "
+            "SignedHeaders=host;x-amz-content-sha256;x-amz-date;x-amz-security-token, Signature="
+        )
+        again = ObjectStorage(settings).signed_headers("PUT", url, b"y", None, now)
+        assert again["authorization"] != headers["authorization"]
+
+    def test_upload_and_fetch_round_trip(self, bucket, tmp_path):
+        host, port = bucket.server_address
+        settings = ArchiveSettings.from_env(
+            {
+                **CREDENTIALS,
+                "REMOTE_CODER_ARCHIVE_URL": "s3://archive/team",
+                "REMOTE_CODER_ARCHIVE_ENDPOINT": f"http://{host}:{port}",
+            }
+        )
+        archive = SessionArchive(settings)
+        session = _session(tmp_path)
+
+        location = archive.upload(SessionExport(session=session, diff="+x\n"))
+
+        assert location == f"s3://archive/team/sessions/{session.id}/"
+        assert sorted(_Bucket.objects) == [
+            f"/archive/team/sessions/{session.id}/session.json",
+            f"/archive/team/sessions/{session.id}/session.md",
+        ]
+        markdown, raw = archive.fetch(str(session.id))
+        assert "+x" in markdown and json.loads(raw)["session"]["id"] == str(session.id)
+        assert archive.fetch("00000000-0000-0000-0000-000000000000") is None
+
+    def test_strict_privacy_refuses_uploads(self, tmp_path):
+        settings = ArchiveSettings(bucket="b", access_key_id="a", secret_access_key="s", endpoint="http://127.0.0.1:9")
+        PRIVACY.set_mode(STRICT)
+        try:
+            with pytest.raises(ArchiveError, match="session archive transfers"):
+                SessionArchive(settings).upload(SessionExport(session=_session(tmp_path)))
+        finally:
+            PRIVACY.set_mode(STANDARD)
@@ -0,0 +1,110 @@
+"""Tests for question-only channels and their read-only checkout."""
+
+from __future__ import annotations
+
+import pytest
+
+from src.core import ask_mode
+from src.core.ask_mode import AskCheckoutError, discard_ask_changes, prepare_ask_checkout
+from src.core.config import _load_projects
+from src.core.errors import ConfigError
+from src.core.models import GitHubRepoConfig, Project
+from tests.conftest import git
+
+
+@pytest.fixture
+def repo(tmp_path, monkeypatch):
+    monkeypatch.setattr(ask_mode.tempfile, "gettempdir", lambda: str(tmp_path / "tmp"))
+    path = tmp_path / "repo"
+    path.mkdir()
+    git(path, "init", "--quiet", "--initial-branch=main")
+    git(path, "config", "user.email", "dev@example.com")
+    git(path, "config", "user.name", "Dev")
+    (path / "app.py").write_text("print('v1')\n")
+    git(path, "add", "app.py")
+    git(path, "commit", "--quiet", "-m", "v1")
+    return path
+
+
+def _project(path, github: bool = True) -> Project:
+    return Project(
+        id="demo",
+        channel_name="demo",
+        path=path,
+        default_agent_id="claude",
+        github=GitHubRepoConfig(owner="owner", repo="repo", default_base_branch="main") if github else None,
+    )
+
+
+class TestAskCheckout:
+    """Test cases for preparing and discarding the ask worktree."""
+
+    @pytest.mark.asyncio
+    async def test_checks_out_the_default_branch_away_from_the_project(self, repo):
+        git(repo, "checkout", "--quiet", "-b", "feature")
+        (repo / "app.py").write_text("print('unsaved work')\n")
+
+        checkout = await prepare_ask_checkout(_project(repo))
+
+        assert checkout.path != repo
+        assert checkout.commit == git(repo, "rev-parse", "main").strip()
+        assert (checkout.path / "app.py").read_text() == "print('v1')\n"
+        assert (repo / "app.py").read_text() == "print('unsaved work')\n"
+
+    @pytest.mark.asyncio
+    async def test_discards_edits_and_commits_from_the_run(self, repo):
+        checkout = await prepare_ask_checkout(_project(repo))
+        assert not await discard_ask_changes(checkout)
+
+        (checkout.path / "app.py").write_text("print('changed')\n")
+        (checkout.path / "notes.txt").write_text("scratch\n")
+        git(checkout.path, "commit", "--quiet", "-am", "sneaky")
+
+        assert await discard_ask_changes(checkout)
+        assert (checkout.path / "app.py").read_text() == "print('v1')\n"
+        assert not (checkout.path / "notes.txt").exists()
+        assert git(repo, "log", "--format=%s", "main") == "v1\n"
+
+    @pytest.mark.asyncio
+    async def test_moves_to_new_commits_between_runs(self, repo):
+        await prepare_ask_checkout(_project(repo, github=False))
+        (repo / "app.py").write_text("print('v2')\n")
+        git(repo, "commit", "--quiet", "-am", "v2")
+
+        checkout = await prepare_ask_checkout(_project(repo, github=False))
+
+        assert (checkout.path / "app.py").read_text() == "print('v2')\n"
+
+    @pytest.mark.asyncio
+    async def test_needs_a_git_repository(self, tmp_path, monkeypatch):
+        monkeypatch.setattr(ask_mode.tempfile, "gettempdir", lambda: str(tmp_path / "tmp"))
+        plain = tmp_path / "plain"
+        plain.mkdir()
+
+        with pytest.raises(AskCheckoutError, match="to be a git repository with at least one commit"):
+            await prepare_ask_checkout(_project(plain))
+
+
+class TestAskChannelsConfig:
+    """Test cases for `ask_channels` in projects.yaml."""
+
+    @staticmethod
+    def _load(tmp_path, extra: str):
+        projects_yaml = tmp_path / "projects.yaml"
+        projects_yaml.write_text(
+            f"base_dir: {tmp_path}\nprojects:\n  demo:\n    path: .\n    default_agent: claude\n{extra}"
+        )
+        projects, _ = _load_projects(projects_yaml)
+        return projects["demo"]
+
+    def test_reads_ask_channels(self, tmp_path):
+        assert self._load(tmp_path, "").ask_channels == []
+        assert self._load(tmp_path, "    ask_channels: [demo-questions]\n").ask_channels == ["demo-questions"]
+
+    def test_rejects_invalid_ask_channels(self, tmp_path):
+        with pytest.raises(ConfigError, match="ask_channels must be a list of channel names"):
+            self._load(tmp_path, "    ask_channels: demo-questions\n")
+
+    def test_a_channel_is_either_for_changes_or_questions(self, tmp_path):
+        with pytest.raises(ConfigError, match="lists demo-dev in both channels and ask_channels"):
+            self._load(tmp_path, "    channels: [demo-dev]\n    ask_channels: [demo-dev]\n")
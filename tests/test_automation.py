@@ -0,0 +1,210 @@
+"""Tests for project groups, quiet hours, budgets, and the automation gate."""
+
+from __future__ import annotations
+
+from datetime import datetime, time, timezone
+from unittest.mock import AsyncMock, MagicMock
+
+import pytest
+
+from src.core.agent_runner import AgentTaskRunner
+from src.core.automation import AutomationGate, in_quiet_hours
+from src.core.config import _load_projects
+from src.core.conversation import SessionManager
+from src.core.errors import ConfigError
+from src.core.models import AgentType, Project, QuietHours, Session
+
+
+def _project(tmp_path, **overrides) -> Project:
+    return Project(id="api", channel_name="api", path=tmp_path, default_agent_id="claude", **overrides)
+
+
+def _session(tmp_path, cost: float = 0.0) -> Session:
+    return Session(
+        project_id="api",
+        channel_id="C1",
+        thread_ts="1.0",
+        active_agent_id="claude",
+        active_agent_type=AgentType.CLAUDE,
+        project_path=tmp_path,
+        estimated_cost_usd=cost,
+    )
+
+
+class TestQuietHours:
+    """Test cases for in_quiet_hours."""
+
+    def test_same_day_window(self):
+        quiet = QuietHours(start=time(12, 0), end=time(13, 0), timezone="UTC")
+
+        assert in_quiet_hours(quiet, datetime(2025, 1, 1, 12, 30, tzinfo=timezone.utc))
+        assert not in_quiet_hours(quiet, datetime(2025, 1, 1, 13, 0, tzinfo=timezone.utc))
+
+    def test_window_wrapping_midnight(self):
+        quiet = QuietHours(start=time(22, 0), end=time(7, 0), timezone="UTC")
+
+        assert in_quiet_hours(quiet, datetime(2025, 1, 1, 23, 0, tzinfo=timezone.utc))
+        assert in_quiet_hours(quiet, datetime(2025, 1, 2, 6, 59, tzinfo=timezone.utc))
+        assert not in_quiet_hours(quiet, datetime(2025, 1, 2, 7, 0, tzinfo=timezone.utc))
+
+    def test_uses_configured_timezone(self):
+        quiet = QuietHours(start=time(22, 0), end=time(7, 0), timezone="Asia/Tokyo")
+
+        # 14:00 UTC is 23:00 in Tokyo
+        assert in_quiet_hours(quiet, datetime(2025, 1, 1, 14, 0, tzinfo=timezone.utc))
+
+    def test_falls_back_to_user_timezone(self):
+        quiet = QuietHours(start=time(22, 0), end=time(7, 0))
+        now = datetime(2025, 1, 1, 14, 0, tzinfo=timezone.utc)
+
+        assert in_quiet_hours(quiet, now, "Asia/Tokyo")
+        assert not in_quiet_hours(quiet, now, "Europe/London")
+        # An explicit zone on the quiet hours wins over the user's
+        assert not in_quiet_hours(QuietHours(start=time(22, 0), end=time(7, 0), timezone="UTC"), now, "Asia/Tokyo")
+
+
+class TestAutomationGate:
+    """Test cases for AutomationGate.block_reason."""
+
+    def test_paused_group_blocks(self, tmp_path):
+        gate = AutomationGate()
+        project = _project(tmp_path, group="work")
+
+        assert gate.pause("work")
+        assert not gate.pause("work")
+        assert "`!group work resume`" in gate.block_reason(project, _session(tmp_path))
+        assert gate.resume("work")
+        assert gate.block_reason(project, _session(tmp_path)) is None
+
+    def test_quiet_hours_block(self, tmp_path):
+        gate = AutomationGate(clock=lambda: datetime(2025, 1, 1, 23, 0, tzinfo=timezone.utc))
+        project = _project(tmp_path, quiet_hours=QuietHours(start=time(22, 0), end=time(7, 0), timezone="UTC"))
+
+        reason = gate.block_reason(project, _session(tmp_path))
+
+        assert "Quiet hours for `api` (22:00-07:00 UTC)" in reason
+
+    def test_quiet_hours_in_session_timezone(self, tmp_path):
+        gate = AutomationGate(clock=lambda: datetime(2025, 1, 1, 14, 0, tzinfo=timezone.utc))
+        project = _project(tmp_path, quiet_hours=QuietHours(start=time(22, 0), end=time(7, 0)))
+        session = _session(tmp_path)
+        session.timezone = "Asia/Tokyo"
+
+        assert "Quiet hours for `api` (22:00-07:00 Asia/Tokyo)" in gate.block_reason(project, session)
+
+    def test_budget_blocks_once_spent(self, tmp_path):
+        gate = AutomationGate()
+        project = _project(tmp_path, max_session_cost_usd=2.0)
+
+        assert gate.block_reason(project, _session(tmp_path, cost=1.5)) is None
+        assert "budget of $2.00" in gate.block_reason(project, _session(tmp_path, cost=2.1))
+
+    def test_run_quota_blocks_until_next_month(self, tmp_path):
+        manager = SessionManager()
+        now = datetime(2026, 12, 14, 12, 0).astimezone()
+        gate = AutomationGate(clock=lambda: now, run_counter=manager.project_runs)
+        project = _project(tmp_path, max_runs_per_month=2)
+        manager.record_project_run("api", datetime(2026, 11, 30, 12, 0))
+        manager.record_project_run("api", now)
+
+        assert gate.block_reason(project, _session(tmp_path)) is None
+        assert gate.quota_status(project) == "1/2 runs in December"
+
+        manager.record_project_run("api", now)
+        reason = gate.block_reason(project, _session(tmp_path))
+
+        assert "used its quota of 2 agent runs for December" in reason
+        assert "exhausted until the 1st (January 1)" in reason
+        assert gate.quota_status(_project(tmp_path)) is None
+
+    @pytest.mark.asyncio
+    async def test_runner_does_not_start_blocked_runs(self, tmp_path):
+        gate = AutomationGate()
+        gate.pause("work")
+        send_message = AsyncMock()
+        config = MagicMock()
+        runner = AgentTaskRunner(
+            config=config,
+            session_manager=MagicMock(),
+            interaction_classifier=MagicMock(),
+            git_workflow=MagicMock(),
+            adapter_cache={},
+            active_runs={},
+            send_message=send_message,
+            gate=gate,
+        )
+
+        await runner.run(_session(tmp_path), _project(tmp_path, group="work"), "C1", "1.0", "fix it")
+
+        config.get_agent.assert_not_called()
+        assert "paused for the `work` group" in send_message.await_args.args[2]
+
+
+class TestGroupConfig:
+    """Test cases for parsing `groups:` in projects.yaml."""
+
+    def _load(self, tmp_path, body: str):
+        (tmp_path / "api").mkdir(exist_ok=True)
+        (tmp_path / "blog").mkdir(exist_ok=True)
+        projects_yaml = tmp_path / "projects.yaml"
+        projects_yaml.write_text(f"base_dir: {tmp_path}\n" + body)
+        projects, _ = _load_projects(projects_yaml)
+        return projects
+
+    def test_projects_inherit_group_defaults(self, tmp_path):
+        projects = self._load(
+            tmp_path,
+            "groups:\n"
+            "  work:\n"
+            "    default_agent: codex\n"
+            "    max_session_cost_usd: 5\n"
+            "    quiet_hours: 22:00-07:00\n"
+            "projects:\n"
+            "  api:\n"
+            "    path: api\n"
+            "    group: work\n"
+            "  blog:\n"
+            "    path: blog\n"
+            "    group: work\n"
+            "    default_agent: claude\n"
+            "    max_session_cost_usd: 1.5\n",
+        )
+
+        api, blog = projects["api"], projects["blog"]
+        assert api.group == "work"
+        assert api.default_agent_id == "codex"
+        assert api.max_session_cost_usd == 5.0
+        assert api.quiet_hours == QuietHours(start=time(22, 0), end=time(7, 0))
+        assert blog.default_agent_id == "claude"
+        assert blog.max_session_cost_usd == 1.5
+
+    def test_run_quota_comes_from_the_group(self, tmp_path):
+        projects = self._load(
+            tmp_path,
+            "groups:\n  personal:\n    max_runs_per_month: 100\n"
+            "projects:\n  api:\n    path: api\n    default_agent: claude\n    group: personal\n",
+        )
+
+        assert projects["api"].max_runs_per_month == 100
+
+    def test_rejects_bad_run_quota(self, tmp_path):
+        with pytest.raises(ConfigError, match="max_runs_per_month must be a positive number of runs"):
+            self._load(
+                tmp_path,
+                "projects:\n  api:\n    path: api\n    default_agent: claude\n    max_runs_per_month: 0\n",
+            )
+
+    def test_unknown_group(self, tmp_path):
+        with pytest.raises(ConfigError, match="unknown group"):
+            self._load(tmp_path, "projects:\n  api:\n    path: api\n    default_agent: claude\n    group: play\n")
+
+    def test_rejects_project_only_keys_in_group(self, tmp_path):
+        with pytest.raises(ConfigError, match="unsupported keys path"):
+            self._load(tmp_path, "groups:\n  work:\n    path: api\nprojects: {}\n")
+
+    def test_rejects_bad_quiet_hours(self, tmp_path):
+        with pytest.raises(ConfigError, match="HH:MM"):
+            self._load(
+                tmp_path,
+                "projects:\n  api:\n    path: api\n    default_agent: claude\n    quiet_hours: late\n",
+            )
@@ -0,0 +1,198 @@
+"""Tests for benchmarking agents on a project's `.cockpit/bench` suite."""
+
+from __future__ import annotations
+
+from dataclasses import replace
+
+import pytest
+
+from src.agent_adapters import AgentResult
+from src.core.bench import (
+    BenchReport,
+    BenchRun,
+    format_bench_report,
+    load_bench_suite,
+    parse_bench_case,
+    recommendation,
+    run_bench,
+    select_contenders,
+)
+from src.core.config import Config
+from src.core.errors import ConfigError
+from src.core.models import Agent, AgentType, CommandStep, Project, WorkingDirMode
+from tests.conftest import git
+
+
+def _agent(agent_id: str, mode: WorkingDirMode = WorkingDirMode.PROJECT) -> Agent:
+    return Agent(id=agent_id, type=AgentType.CLAUDE, command=[agent_id], working_dir_mode=mode)
+
+
+def _write_case(repo, name: str, text: str) -> None:
+    directory = repo / ".cockpit" / "bench"
+    directory.mkdir(parents=True, exist_ok=True)
+    (directory / f"{name}.md").write_text(text)
+
+
+@pytest.fixture
+def project(git_repo):
+    return Project(
+        id="web",
+        channel_name="web",
+        path=git_repo,
+        default_agent_id="claude",
+        verify_commands=[CommandStep(run="test -f feature.txt")],
+    )
+
+
+class TestBenchSuite:
+    """Test cases for loading bench cases."""
+
+    def test_parses_description_and_verify_override(self):
+        case = parse_bench_case("paging", "---\ndescription: Paginate\nverify:\n  - pytest -q\n---\nAdd paging.\n")
+
+        assert case.prompt == "Add paging."
+        assert case.description == "Paginate"
+        assert case.verify == [CommandStep(run="pytest -q")]
+        assert parse_bench_case("plain", "Just do it.").verify is None
+
+    def test_loads_cases_by_name(self, git_repo):
+        _write_case(git_repo, "b-case", "Second.")
+        _write_case(git_repo, "a-case", "First.")
+
+        assert [case.name for case in load_bench_suite(git_repo)] == ["a-case", "b-case"]
+        assert [case.name for case in load_bench_suite(git_repo, ["b-case"])] == ["b-case"]
+        with pytest.raises(ConfigError, match="No bench case named missing"):
+            load_bench_suite(git_repo, ["missing"])
+
+    def test_rejects_invalid_cases(self, git_repo):
+        _write_case(git_repo, "bad", "---\nmodel: opus\n---\nGo.")
+
+        with pytest.raises(ConfigError, match="unsupported frontmatter keys model"):
+            load_bench_suite(git_repo)
+
+    def test_empty_suite(self, git_repo):
+        assert load_bench_suite(git_repo) == []
+
+
+class TestSelectContenders:
+    """Test cases for choosing the agents to benchmark."""
+
+    def _config(self, tmp_path, *agents: Agent) -> Config:
+        return Config(
+            projects={},
+            agents={agent.id: agent for agent in agents},
+            slack_bot_token="",
+            slack_app_token="",
+            slack_allowed_user_ids=[],
+            base_dir=tmp_path,
+            config_dir=tmp_path,
+        )
+
+    def test_defaults_to_allowed_worktree_agents(self, tmp_path, project):
+        config = self._config(
+            tmp_path, _agent("claude"), _agent("codex"), _agent("fixed", WorkingDirMode.FIXED), _agent("gemini")
+        )
+        limited = replace(project, allowed_agents=["claude", "codex", "fixed"], agent_models={"codex": "mini"})
+
+        contenders = select_contenders(config, limited)
+
+        assert [(agent.id, model) for agent, model in contenders] == [("claude", None), ("codex", "mini")]
+
+    def test_rejects_unusable_agents(self, tmp_path, project):
+        config = self._config(tmp_path, _agent("claude"), _agent("fixed", WorkingDirMode.FIXED))
+
+        with pytest.raises(ConfigError, match="Unknown agent `nope`"):
+            select_contenders(config, project, ["nope"])
+        with pytest.raises(ConfigError, match="fixed directory"):
+            select_contenders(config, project, ["fixed"])
+        with pytest.raises(ConfigError, match="not allowed"):
+            select_contenders(config, replace(project, allowed_agents=["claude"]), ["fixed"])
+
+
+class TestRunBench:
+    """Test cases for running the suite."""
+
+    @pytest.mark.asyncio
+    async def test_checks_each_change_in_its_worktree(self, git_repo, project):
+        _write_case(git_repo, "feature", "Add the feature.")
+        _write_case(git_repo, "docs", "---\nverify:\n  - grep -q docs README.md\n---\nDocument it.")
+        order = []
+
+        async def run_agent(agent, model, workdir, prompt):
+            order.append(agent.id)
+            if agent.id == "codex":
+                (workdir / "feature.txt").write_text("done\n")
+                (workdir / "README.md").write_text("hello\ndocs\n")
+                return AgentResult(success=True, output_text="done"), 0.5
+            return AgentResult(success=True, output_text="I could not do it"), 0.1
+
+        report = await run_bench(
+            project,
+            git_repo,
+            [(_agent("claude"), "sonnet"), (_agent("codex"), None)],
+            load_bench_suite(git_repo),
+            run_agent,
+        )
+
+        results = {(run.case, run.agent_id): run.passed for run in report.runs}
+        assert results == {
+            ("docs", "claude"): False,
+            ("docs", "codex"): True,
+            ("feature", "claude"): False,
+            ("feature", "codex"): True,
+        }
+        assert order == ["claude", "codex", "claude", "codex"]
+        assert report.base == git(git_repo, "rev-parse", "HEAD").strip()
+        assert git(git_repo, "status", "--porcelain") == "?? .cockpit/\n"
+
+        [best, other] = report.summaries()
+        assert (best.agent_id, best.passed, best.checked, best.cost_usd) == ("codex", 2, 2, 1.0)
+        assert other.label == "claude (sonnet)"
+        assert recommendation(report).startswith("Best on this suite: `codex`, passing 2/2 at ~$1.00")
+
+    @pytest.mark.asyncio
+    async def test_failed_runs_fail_and_unchecked_cases_are_unknown(self, git_repo, project):
+        _write_case(git_repo, "feature", "Add the feature.")
+        progress = []
+
+        async def run_agent(agent, model, workdir, prompt):
+            raise RuntimeError("claude CLI not found")
+
+        async def record(run):
+            progress.append(run)
+
+        cases = load_bench_suite(git_repo)
+        report = await run_bench(project, git_repo, [(_agent("claude"), None)], cases, run_agent, progress=record)
+        unchecked = await run_bench(
+            replace(project, verify_commands=[]), git_repo, [(_agent("claude"), None)], cases, run_agent
+        )
+
+        assert report.runs[0].passed is False
+        assert report.runs[0].errors == ["claude CLI not found"]
+        assert progress == report.runs
+        assert unchecked.runs[0].passed is None
+
+
+class TestBenchReport:
+    """Test cases for rendering the report."""
+
+    def test_markdown_report(self):
+        report = BenchReport(
+            project_id="web",
+            base="0123456789abcdef",
+            default_agent_id="claude",
+            runs=[
+                BenchRun(case="feature", agent_id="claude", model=None, duration_secs=65, cost_usd=0.2, passed=True),
+                BenchRun(case="feature", agent_id="codex", model="mini", duration_secs=30, passed=False),
+                BenchRun(case="docs", agent_id="codex", model="mini", errors=["timed out after 600s"]),
+            ],
+        )
+
+        text = format_bench_report(report)
+
+        assert "Started from `0123456789ab`; 2 case(s)." in text
+        assert "| claude | 1/1 | 1m 5s | ~$0.20 |" in text
+        assert "| codex (mini) | 0/1 | 15s | n/a |" in text
+        assert "(already the project's default)" in text
+        assert "| docs | codex (mini) | error | 0s | n/a | 0 | 0 |" in text
+        assert "- docs / codex (mini): timed out after 600s" in text
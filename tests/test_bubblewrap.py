@@ -0,0 +1,183 @@
+"""Tests for the bubblewrap sandbox."""
+
+from __future__ import annotations
+
+import os
+import sys
+from pathlib import Path
+
+import pytest
+
+from src.agent_adapters import bubblewrap as bubblewrap_module
+from src.agent_adapters.base import spawn_cli
+from src.agent_adapters.bubblewrap import BubblewrapUnavailable, bubblewrap_argv, sandboxed_command
+from src.core import agent_cli as agent_cli_module
+from src.core.agent_cli import probe_agent_cli
+from src.core.config import _load_agents
+from src.core.errors import ConfigError
+from src.core.models import Agent, AgentType, SandboxConfig, SandboxMode, WorkingDirMode
+from src.core.sandbox import Sandbox
+
+# Stands in for bwrap: logs its options and runs the command after `--` without isolating it
+FAKE_BWRAP = (
+    "import os, sys\n"
+    "with open(sys.argv[0] + '.log', 'a') as log:\n"
+    "    log.write(' '.join(sys.argv[1:]) + '\\n')\n"
+    "command = sys.argv[sys.argv.index('--') + 1:]\n"
+    "os.execvp(command[0], command)\n"
+)
+
+
+@pytest.fixture
+def fake_bwrap(tmp_path, monkeypatch) -> Path:
+    bwrap = tmp_path / "bwrap"
+    bwrap.write_text(f"#!{sys.executable}\n{FAKE_BWRAP}")
+    bwrap.chmod(0o755)
+    monkeypatch.setattr(bubblewrap_module, "BWRAP", str(bwrap))
+    return bwrap
+
+
+def _bubblewrap(**overrides) -> SandboxConfig:
+    return SandboxConfig(mode=SandboxMode.BUBBLEWRAP, **overrides)
+
+
+def _bound(argv, option: str):
+    return [argv[index + 1] for index, arg in enumerate(argv) if arg == option]
+
+
+class TestBubblewrap:
+    """Test cases for starting commands through bubblewrap."""
+
+    def test_argv_shows_only_system_dirs_and_the_workdir(self, tmp_path, fake_bwrap):
+        workdir = tmp_path / "repo"
+        workdir.mkdir()
+
+        argv = bubblewrap_argv(_bubblewrap(), ["sh", "-c", "make"], workdir)
+
+        assert argv[:3] == [str(fake_bwrap), "--die-with-parent", "--unshare-all"]
+        assert "--share-net" not in argv
+        assert "/usr" in _bound(argv, "--ro-bind-try")
+        assert _bound(argv, "--bind") == [str(workdir.resolve())]
+        assert argv[argv.index("--chdir") + 1] == str(workdir.resolve())
+        assert argv[-4:] == ["--", "sh", "-c", "make"]
+
+    def test_argv_for_an_agent_cli(self, tmp_path, fake_bwrap, monkeypatch):
+        prefix = tmp_path / "node"
+        (prefix / "bin").mkdir(parents=True)
+        cli = prefix / "bin" / "claude"
+        cli.write_text("#!/bin/sh\n")
+        cli.chmod(0o755)
+        monkeypatch.setenv("PATH", f"{prefix / 'bin'}{os.pathsep}{os.environ['PATH']}")
+        monkeypatch.setenv("HOME", str(tmp_path / "home"))
+        prompt = tmp_path / "prompt.txt"
+        prompt.write_text("task")
+        workdir = tmp_path / "repo"
+        workdir.mkdir()
+        sandbox = _bubblewrap(network=True, writable_paths=["~/.claude"], read_only_paths=["/srv/cache"])
+
+        argv = bubblewrap_argv(sandbox, ["claude", f"--prompt-file={prompt}"], workdir)
+
+        assert "--share-net" in argv
+        assert _bound(argv, "--ro-bind") == [str(prefix), str(prompt.resolve())]
+        assert "/srv/cache" in _bound(argv, "--ro-bind-try")
+        assert _bound(argv, "--bind-try") == [str(tmp_path / "home" / ".claude")]
+
+    def test_missing_bwrap_refuses_to_run(self, tmp_path, monkeypatch):
+        monkeypatch.setattr(bubblewrap_module, "BWRAP", str(tmp_path / "no-bwrap"))
+
+        with pytest.raises(BubblewrapUnavailable, match="install bubblewrap"):
+            sandboxed_command(["claude"], tmp_path, _bubblewrap())
+
+    def test_other_modes_are_left_alone(self, tmp_path):
+        assert sandboxed_command(["claude", "-p"], tmp_path, SandboxConfig()) == ["claude", "-p"]
+        assert sandboxed_command(["claude", "-p"], tmp_path, None) == ["claude", "-p"]
+
+    @pytest.mark.asyncio
+    async def test_shell_commands_run_through_bwrap_with_a_scrubbed_env(self, tmp_path, fake_bwrap, monkeypatch):
+        monkeypatch.setenv("GITHUB_TOKEN", "secret")
+
+        result = await Sandbox(_bubblewrap()).run('echo "token=${GITHUB_TOKEN:-unset}"', tmp_path, 10)
+
+        assert result.passed
+        assert "token=unset" in result.output
+        assert Path(f"{fake_bwrap}.log").read_text().rstrip().endswith('-- sh -c echo "token=${GITHUB_TOKEN:-unset}"')
+
+    @pytest.mark.asyncio
+    async def test_spawn_cli_runs_the_agent_through_bwrap(self, tmp_path, fake_bwrap):
+        command = [sys.executable, "-c", "print('sandboxed')"]
+
+        async with spawn_cli(command, cwd=tmp_path, env=os.environ, stdin=False, sandbox=_bubblewrap()) as process:
+            output = (await process.stdout.read()).decode()
+            await process.wait()
+
+        assert output.strip() == "sandboxed"
+        assert "--unshare-all" in Path(f"{fake_bwrap}.log").read_text()
+
+    def test_probe_reports_a_missing_bwrap(self, tmp_path, monkeypatch):
+        monkeypatch.setattr(bubblewrap_module, "BWRAP", str(tmp_path / "no-bwrap"))
+        monkeypatch.setattr(agent_cli_module, "BWRAP", str(tmp_path / "no-bwrap"))
+        agent = Agent(
+            id="codex",
+            type=AgentType.CODEX,
+            command=[sys.executable],
+            working_dir_mode=WorkingDirMode.PROJECT,
+            sandbox=_bubblewrap(),
+        )
+
+        check = probe_agent_cli(agent)
+
+        assert check is not None and "install bubblewrap" in check.problem
+
+
+class TestBubblewrapConfig:
+    """Test cases for `sandbox: bubblewrap` in agents.yaml."""
+
+    @staticmethod
+    def _load(tmp_path: Path, agent_type: str, sandbox: str) -> SandboxConfig:
+        agents_yaml = tmp_path / "agents.yaml"
+        extra = "    model: llama3\n" if agent_type == "ollama" else "    command: [cli]\n"
+        agents_yaml.write_text(f"agents:\n  agent:\n    type: {agent_type}\n{extra}    sandbox:{sandbox}")
+        return _load_agents(agents_yaml)["agent"].sandbox
+
+    def test_cli_agents_keep_network_and_their_state(self, tmp_path):
+        sandbox = self._load(tmp_path, "claude", " bubblewrap\n")
+
+        assert sandbox.mode == SandboxMode.BUBBLEWRAP
+        assert sandbox.network
+        assert "~/.claude" in sandbox.writable_paths
+
+    def test_api_agent_shells_get_no_network(self, tmp_path):
+        sandbox = self._load(tmp_path, "ollama", " bubblewrap\n")
+
+        assert not sandbox.network
+        assert sandbox.writable_paths == []
+
+    def test_paths_can_be_overridden(self, tmp_path):
+        sandbox = self._load(
+            tmp_path,
+            "codex",
+            "\n      mode: bubblewrap\n      network: false\n"
+            "      writable_paths: [~/.codex/sessions]\n      read_only_paths: [~/.nvm]\n",
+        )
+
+        assert not sandbox.network
+        assert sandbox.writable_paths == ["~/.codex/sessions"]
+        assert sandbox.read_only_paths == ["~/.nvm"]
+
+    @pytest.mark.parametrize(
+        "sandbox,message",
+        [
+            ("\n      mode: process\n      writable_paths: [/tmp]\n", "writable_paths requires mode bubblewrap"),
+            ("\n      mode: bubblewrap\n      read_only_paths: /srv\n", "read_only_paths must be a list of paths"),
+            (" jail\n", "mode must be none, process, container, or bubblewrap"),
+        ],
+    )
+    def test_rejects_invalid_values(self, tmp_path, sandbox, message):
+        with pytest.raises(ConfigError, match=message):
+            self._load(tmp_path, "codex", sandbox)
+
+    def test_requires_linux(self, tmp_path, monkeypatch):
+        monkeypatch.setattr(sys, "platform", "darwin")
+
+        with pytest.raises(ConfigError, match="only available on Linux"):
+            self._load(tmp_path, "codex", " bubblewrap\n")
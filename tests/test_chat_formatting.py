@@ -0,0 +1,46 @@
+"""Tests for cleaning up agent output before it is posted."""
+
+from __future__ import annotations
+
+from src.chat_adapters.formatting import format_for_slack, strip_ansi, to_mrkdwn
+
+
+class TestStripAnsi:
+    """Test cases for removing terminal formatting."""
+
+    def test_colors_titles_and_control_characters_are_removed(self):
+        text = "\x1b[1;32mPASS\x1b[0m tests\x1b]0;title\x07 done\x08\n\tindented"
+
+        assert strip_ansi(text) == "PASS tests done\n\tindented"
+
+    def test_redrawn_lines_keep_their_last_state(self):
+        assert strip_ansi("[#  ] 30%\r[###] 100%\r\nnext\r\n") == "[###] 100%\nnext\n"
+
+
+class TestToMrkdwn:
+    """Test cases for converting Markdown to Slack mrkdwn."""
+
+    def test_common_markdown_is_converted(self):
+        text = (
+            "## Summary\n"
+            "**Fixed** the ~~old~~ parser, see [the docs](https://example.com/docs).\n"
+            "* first\n"
+            "  + nested\n"
+            "- dashes stay"
+        )
+
+        assert to_mrkdwn(text) == (
+            "*Summary*\n"
+            "*Fixed* the ~old~ parser, see <https://example.com/docs|the docs>.\n"
+            "• first\n"
+            "  • nested\n"
+            "- dashes stay"
+        )
+
+    def test_code_is_left_alone(self):
+        text = "Run `**not bold**`:\n```python\n# comment\nprint(**kwargs)\n```\n*Already bold* stays"
+
+        assert to_mrkdwn(text) == "Run `**not bold**`:\n```\n# comment\nprint(**kwargs)\n```\n*Already bold* stays"
+
+    def test_format_for_slack_does_both(self):
+        assert format_for_slack("\x1b[31m**error**\x1b[0m: see `log`") == "*error*: see `log`"
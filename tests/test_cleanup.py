@@ -0,0 +1,81 @@
+"""Tests for archiving stale sessions and removing their files."""
+
+from __future__ import annotations
+
+from datetime import timedelta
+
+import pytest
+
+from src.core.cleanup import SessionCleaner, snapshot_hook, transcript_hook
+from src.core.conversation import SessionManager
+from src.core.models import AgentType, Project, SessionStatus
+
+
+def _start(manager: SessionManager, tmp_path, thread_ts: str):
+    project = Project(id="api", channel_name="api", path=tmp_path, default_agent_id="claude")
+    return manager.create_session(
+        project=project, channel_id="C1", thread_ts=thread_ts, agent_id="claude", agent_type=AgentType.CLAUDE
+    )
+
+
+def _write(path, size: int) -> None:
+    path.parent.mkdir(parents=True, exist_ok=True)
+    path.write_bytes(b"x" * size)
+
+
+class TestSessionCleaner:
+    """Test cases for what a cleanup pass archives and removes."""
+
+    def test_stale_sessions_are_archived_and_their_snapshots_removed(self, tmp_path):
+        manager = SessionManager()
+        stale = _start(manager, tmp_path, "1.1")
+        fresh = _start(manager, tmp_path, "1.2")
+        stale.updated_at -= timedelta(days=8)
+        snapshots, transcripts = tmp_path / "snapshots", tmp_path / "transcripts"
+        for session in (stale, fresh):
+            _write(snapshots / str(session.id) / "blobs" / "ab", 10)
+            _write(transcripts / f"{session.id}.jsonl", 10)
+        _write(snapshots / "gone" / "manifest.json", 5)
+        _write(transcripts / "gone.jsonl", 5)
+        cleaner = SessionCleaner(manager, timedelta(days=7), [snapshot_hook(snapshots), transcript_hook(transcripts)])
+
+        done = cleaner.run()
+
+        assert done.archived == 1 and manager.get_session(stale.id).status == SessionStatus.ARCHIVED
+        assert sorted(path.name for path in snapshots.iterdir()) == [str(fresh.id)]
+        # Archived sessions keep their transcripts for restoring
+        assert sorted(path.stem for path in transcripts.iterdir()) == sorted([str(stale.id), str(fresh.id)])
+        assert (done.removed.files, done.removed.bytes) == (3, 20)
+
+    def test_totals_add_up_across_passes(self, tmp_path):
+        manager = SessionManager()
+        cleaner = SessionCleaner(manager, timedelta(days=7), [snapshot_hook(tmp_path / "missing")])
+        assert cleaner.stats.describe() == "Cleanup: not run yet"
+        _start(manager, tmp_path, "1.1").updated_at -= timedelta(days=8)
+
+        cleaner.run()
+        cleaner.run()
+
+        assert (cleaner.stats.passes, cleaner.stats.archived) == (2, 1)
+        assert cleaner.stats.describe() == "Cleanup: 2 pass(es), 1 session(s) archived, 0 file(s) removed (0.0 MB)"
+
+    @pytest.mark.asyncio
+    async def test_uploaded_sessions_are_removed_from_the_store(self, tmp_path):
+        manager = SessionManager()
+        uploaded, failed, active = (_start(manager, tmp_path, ts) for ts in ("1.1", "1.2", "1.3"))
+        for session in (uploaded, failed):
+            session.updated_at -= timedelta(days=8)
+        manager.cleanup_ended(timedelta(days=7))
+
+        async def offload(session):
+            return session.id == uploaded.id
+
+        cleaner = SessionCleaner(manager, timedelta(days=7), offload=offload)
+
+        assert await cleaner.offload_archived() == 1
+        assert set(manager.session_statuses()) == {failed.id, active.id}
+        assert cleaner.stats.offloaded == 1
+        cleaner.run()
+        assert cleaner.stats.describe() == (
+            "Cleanup: 1 pass(es), 0 session(s) archived, 1 uploaded to the archive, 0 file(s) removed (0.0 MB)"
+        )
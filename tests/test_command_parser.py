@@ -28,6 +28,13 @@ class TestCommandParser:
         print(f" OUTPUT: {result}")
         assert result == ParsedCommand(name="use", args=["codex"])
 
+    def test_parse_command_keeps_raw_args(self):
+        """Raw argument text keeps newlines for commands that take pasted content."""
+        result = parse_command("!apply ```\n--- a/x\n+++ b/x\n```")
+        assert result is not None
+        assert result.name == "apply"
+        assert result.raw_args == "```\n--- a/x\n+++ b/x\n```"
+
     def test_parse_command_case_insensitive(self):
         """Command names should be lowercased."""
         print("\n INPUT: '!HELP'")
@@ -0,0 +1,116 @@
+"""Tests for command placeholders and agent env references."""
+
+from __future__ import annotations
+
+import pytest
+
+from src.agent_adapters.codex_adapter import CodexAdapter
+from src.agent_adapters.command_template import render_command, unknown_placeholders
+from src.agent_adapters.gemini_adapter import GeminiAdapter
+from src.core.config import _load_agents
+from src.core.errors import ConfigError
+from src.core.models import Agent, AgentType, WorkingDirMode
+
+
+def _agent(agent_type: AgentType, command: list[str], env=None) -> Agent:
+    return Agent(
+        id=agent_type.value,
+        type=agent_type,
+        command=command,
+        working_dir_mode=WorkingDirMode.PROJECT,
+        env=env or {},
+    )
+
+
+class TestRenderCommand:
+    """Test cases for render_command."""
+
+    def test_fills_placeholders(self, tmp_path):
+        rendered = render_command(
+            ["cli", "--cwd={{workdir}}", "--model", "{{ model }}", "--session", "{{session_id}}", "--keep"],
+            task_text="do it",
+            workdir=tmp_path,
+            model="gpt-5.1-codex",
+            session_id="s1",
+        )
+
+        assert rendered.args == ["cli", f"--cwd={tmp_path}", "--model", "gpt-5.1-codex", "--session", "s1", "--keep"]
+        assert rendered.model_templated
+        assert not rendered.prompt_in_file
+
+    def test_prompt_file_holds_task_until_cleanup(self, tmp_path):
+        rendered = render_command(
+            ["cli", "--prompt-file={{prompt_file}}"], task_text="do it", workdir=tmp_path, model=None, session_id="s1"
+        )
+
+        assert rendered.prompt_file is not None
+        assert rendered.args == ["cli", f"--prompt-file={rendered.prompt_file}"]
+        assert rendered.prompt_file.read_text() == "do it"
+        rendered.cleanup()
+        assert not rendered.prompt_file.exists()
+
+    def test_unknown_placeholders(self):
+        assert unknown_placeholders(["cli", "{{prompt}}", "{{workdir}}", "{{ api_key }}"]) == ["api_key", "prompt"]
+
+
+class TestTemplatedAdapters:
+    """CLI adapters honour placeholders when spawning the command."""
+
+    @pytest.mark.asyncio
+    async def test_codex_reads_prompt_file_and_templated_model(self, tmp_path):
+        command = ["sh", "-c", 'cat "$1"; echo; echo "model=$2 stdin=$(cat)"', "sh", "{{prompt_file}}", "{{model}}"]
+        adapter = CodexAdapter(_agent(AgentType.CODEX, command))
+
+        result = await adapter.run(
+            task_text="add a button", project_path=str(tmp_path), session_id="s1", conversation_history=[], model="base"
+        )
+
+        assert result.success
+        assert result.output_text == "add a button\nmodel=gpt-5.1-codex stdin="
+        assert not list(tmp_path.iterdir())
+
+    @pytest.mark.asyncio
+    async def test_gemini_skips_positional_prompt_with_prompt_file(self, tmp_path):
+        command = ["sh", "-c", 'echo "args=$# $API_TOKEN"', "sh", "{{prompt_file}}"]
+        adapter = GeminiAdapter(_agent(AgentType.GEMINI, command, env={"API_TOKEN": "secret"}))
+
+        result = await adapter.run(
+            task_text="add a button", project_path=str(tmp_path), session_id="s1", conversation_history=[]
+        )
+
+        assert result.output_text == "args=1 secret"
+
+
+class TestAgentConfigTemplating:
+    """Test cases for placeholders and env references in agents.yaml."""
+
+    def test_rejects_unknown_placeholders(self, tmp_path):
+        agents_yaml = tmp_path / "agents.yaml"
+        agents_yaml.write_text('agents:\n  codex:\n    type: codex\n    command: [codex, "{{prompt}}"]\n')
+
+        with pytest.raises(ConfigError, match=r"Unknown placeholder\(s\) in command for agent codex: \{\{prompt\}\}"):
+            _load_agents(agents_yaml)
+
+    def test_expands_env_references(self, tmp_path, monkeypatch):
+        monkeypatch.setenv("MY_CODEX_KEY", "sk-test")
+        agents_yaml = tmp_path / "agents.yaml"
+        agents_yaml.write_text(
+            "agents:\n  codex:\n    type: codex\n    command: [codex]\n"
+            '    env:\n      OPENAI_API_KEY: "${MY_CODEX_KEY}"\n      MODE: "fast-${MY_CODEX_KEY}"\n      RETRIES: 3\n'
+        )
+
+        assert _load_agents(agents_yaml)["codex"].env == {
+            "OPENAI_API_KEY": "sk-test",
+            "MODE": "fast-sk-test",
+            "RETRIES": "3",
+        }
+
+    def test_missing_env_reference(self, tmp_path, monkeypatch):
+        monkeypatch.delenv("MY_MISSING_KEY", raising=False)
+        agents_yaml = tmp_path / "agents.yaml"
+        agents_yaml.write_text(
+            'agents:\n  codex:\n    type: codex\n    command: [codex]\n    env:\n      KEY: "${MY_MISSING_KEY}"\n'
+        )
+
+        with pytest.raises(ConfigError, match=r"references \$\{MY_MISSING_KEY\}, which is not set"):
+            _load_agents(agents_yaml)
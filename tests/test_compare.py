@@ -0,0 +1,100 @@
+"""Tests for running agents side by side in separate worktrees."""
+
+from __future__ import annotations
+
+
+import pytest
+
+from src.agent_adapters import AgentResult
+from src.core.compare import ComparisonError, ComparisonRun, format_comparison, run_comparison
+from src.core.models import Agent, AgentType, WorkingDirMode
+from tests.conftest import git
+
+
+def _agent(agent_id: str) -> Agent:
+    return Agent(
+        id=agent_id,
+        type=AgentType.CLAUDE,
+        command=[agent_id],
+        working_dir_mode=WorkingDirMode.PROJECT,
+    )
+
+
+class TestRunComparison:
+    """Test cases for run_comparison."""
+
+    @pytest.mark.asyncio
+    async def test_agents_work_in_isolated_worktrees(self, git_repo):
+        """Each agent sees only its own edits, and the repository itself is untouched."""
+        seen = {}
+
+        async def run_agent(agent, model, workdir, prompt):
+            seen[agent.id] = sorted(path.name for path in workdir.iterdir() if path.name != ".git")
+            (workdir / f"{agent.id}.txt").write_text(f"{prompt}\n")
+            if agent.id == "codex":
+                (workdir / "README.md").write_text("hello\nworld\n")
+            return AgentResult(success=True, output_text=f"{agent.id} done"), 0.25
+
+        base, runs = await run_comparison(
+            git_repo, [(_agent("claude"), "sonnet"), (_agent("codex"), None)], "add a file", run_agent
+        )
+
+        assert base == git(git_repo, "rev-parse", "HEAD").strip()
+        assert seen == {"claude": ["README.md"], "codex": ["README.md"]}
+        assert [file_diff.path for file_diff in runs[0].files] == ["claude.txt"]
+        assert sorted(file_diff.path for file_diff in runs[1].files) == ["README.md", "codex.txt"]
+        assert runs[0].reply == "claude done"
+        assert runs[0].cost_usd == 0.25
+        assert git(git_repo, "status", "--porcelain") == ""
+        assert git(git_repo, "worktree", "list").count("\n") == 1
+
+    @pytest.mark.asyncio
+    async def test_failed_agent_keeps_its_error(self, git_repo):
+        """An agent that raises is reported with its error instead of aborting the comparison."""
+
+        async def run_agent(agent, model, workdir, prompt):
+            if agent.id == "codex":
+                raise RuntimeError("codex CLI not found")
+            return AgentResult(success=True, output_text="ok"), None
+
+        _, runs = await run_comparison(
+            git_repo, [(_agent("claude"), None), (_agent("codex"), None)], "prompt", run_agent
+        )
+
+        assert runs[0].errors == []
+        assert runs[1].errors == ["codex CLI not found"]
+        assert runs[1].diff_text == ""
+
+    @pytest.mark.asyncio
+    async def test_requires_git_repository(self, tmp_path):
+        """Directories that are not git repositories cannot be compared."""
+
+        async def run_agent(agent, model, workdir, prompt):  # pragma: no cover - never called
+            raise AssertionError
+
+        with pytest.raises(ComparisonError):
+            await run_comparison(tmp_path, [(_agent("claude"), None)], "prompt", run_agent)
+
+
+class TestFormatComparison:
+    """Test cases for format_comparison."""
+
+    def test_stats_are_side_by_side(self):
+        """Each agent gets a column with status, file count, line counts, time, and cost."""
+        diff_text = "diff --git a/a.py b/a.py\n--- a/a.py\n+++ b/a.py\n@@ -1 +1,2 @@\n-x\n+y\n+z\n"
+        runs = [
+            ComparisonRun(agent_id="claude", model="sonnet", diff_text=diff_text, duration_secs=75, cost_usd=0.12),
+            ComparisonRun(agent_id="codex", model=None, duration_secs=5, errors=["boom"], reply="nothing"),
+        ]
+
+        text = format_comparison("0123456789abcdef", runs)
+
+        assert "Comparison from `0123456789ab`" in text
+        lines = text.splitlines()
+        assert "claude (sonnet)" in lines[2] and "codex" in lines[2]
+        assert lines[3].split() == ["status", "ok", "1", "error(s)"]
+        assert lines[5].split() == ["lines", "+2", "-1", "+0", "-0"]
+        assert lines[6].split() == ["time", "1m", "15s", "5s"]
+        assert lines[7].split() == ["cost", "~$0.12", "n/a"]
+        assert "*codex*: nothing" in text
+        assert "error: boom" in text
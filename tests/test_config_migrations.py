@@ -0,0 +1,95 @@
+"""Tests for versioned config files and their migration on load."""
+
+from __future__ import annotations
+
+from pathlib import Path
+
+import pytest
+import yaml
+
+from src.core import migrations
+from src.core.config import _load_projects
+from src.core.errors import ConfigError
+from src.core.migrations import Migration, migrate_config
+
+PROJECTS = "base_dir: {base}\nprojects:\n  demo:\n    # the main repo\n    path: .\n    agent: claude\n"
+
+
+def _rename_agent(data):
+    for cfg in data["projects"].values():
+        cfg["default_agent"] = cfg.pop("agent")
+    return data
+
+
+@pytest.fixture
+def version_two(monkeypatch):
+    """Pretend projects.yaml version 2 renamed `agent` to `default_agent`."""
+    monkeypatch.setattr(migrations, "CONFIG_VERSIONS", {"projects.yaml": 2, "agents.yaml": 1})
+    monkeypatch.setattr(
+        migrations,
+        "MIGRATIONS",
+        {"projects.yaml": [Migration(1, "rename agent to default_agent", _rename_agent)], "agents.yaml": []},
+    )
+
+
+def _write(tmp_path: Path, text: str) -> Path:
+    path = tmp_path / "projects.yaml"
+    path.write_text(text.format(base=tmp_path))
+    return path
+
+
+class TestConfigMigrations:
+    """Test cases for upgrading config files on load."""
+
+    def test_current_files_are_left_alone(self, tmp_path):
+        path = _write(tmp_path, "version: 1\nbase_dir: {base}\nprojects: {{}}\n")
+        before = path.read_text()
+
+        _load_projects(path)
+
+        assert path.read_text() == before
+        assert not list(tmp_path.glob("*.bak"))
+
+    def test_upgrades_unversioned_files_and_keeps_a_backup(self, tmp_path, version_two):
+        path = _write(tmp_path, PROJECTS)
+        original = path.read_text()
+
+        projects, _ = _load_projects(path)
+
+        assert projects["demo"].default_agent_id == "claude"
+        assert (tmp_path / "projects.yaml.v1.bak").read_text() == original
+        upgraded = yaml.safe_load(path.read_text())
+        assert list(upgraded)[0] == "version"
+        assert upgraded["version"] == 2
+        assert upgraded["projects"]["demo"] == {"path": ".", "default_agent": "claude"}
+        # Loading the upgraded file again changes nothing
+        _load_projects(path)
+        assert yaml.safe_load(path.read_text()) == upgraded
+
+    def test_refuses_files_from_a_newer_release(self, tmp_path):
+        path = _write(tmp_path, "version: 7\nbase_dir: {base}\nprojects: {{}}\n")
+
+        with pytest.raises(ConfigError, match="is version 7, but this Remote Coder reads projects.yaml up to"):
+            _load_projects(path)
+
+    def test_failed_migrations_leave_the_file_untouched(self, tmp_path, version_two):
+        path = _write(tmp_path, "base_dir: {base}\nprojects:\n  demo:\n    path: .\n")
+        original = path.read_text()
+
+        with pytest.raises(ConfigError, match="Could not migrate .* to version 2 \\(rename agent to default_agent\\)"):
+            _load_projects(path)
+
+        assert path.read_text() == original
+        assert not (tmp_path / "projects.yaml.v1.bak").exists()
+
+    @pytest.mark.parametrize("version", ["two", 0, True])
+    def test_rejects_invalid_versions(self, tmp_path, version):
+        with pytest.raises(ConfigError, match="version must be a positive whole number"):
+            migrate_config(tmp_path / "projects.yaml", {"version": version}, "projects.yaml")
+
+    def test_reports_a_gap_in_the_migrations(self, tmp_path, monkeypatch):
+        monkeypatch.setattr(migrations, "CONFIG_VERSIONS", {"agents.yaml": 3})
+        monkeypatch.setattr(migrations, "MIGRATIONS", {"agents.yaml": [Migration(1, "first", lambda data: data)]})
+
+        with pytest.raises(ConfigError, match="No migration of agents.yaml from version 2 to 3"):
+            migrate_config(tmp_path / "agents.yaml", {"agents": {}}, "agents.yaml")
@@ -0,0 +1,175 @@
+"""Tests for forwarding agent confirmation prompts to the thread."""
+
+from __future__ import annotations
+
+import asyncio
+import os
+import sys
+
+import pytest
+
+from src.agent_adapters.base import spawn_cli
+from src.agent_adapters.confirmations import answer_confirmations
+from src.core.config import _load_agents
+from src.core.confirmations import ConfirmationBroker
+from src.core.errors import ConfigError
+from src.core.models import DEFAULT_CONFIRM_PATTERNS, ConfirmPrompts
+
+ASKING_CLI = (
+    "print('Edit src/app.py: +3 -1')\n"
+    "print('Apply this change? (y/n) ', end='', flush=True)\n"
+    "print('got', input())\n"
+)
+
+
+async def _run(tmp_path, *, stdin: bool = True, terminal=None, prompts=None) -> str:
+    prompts = prompts or ConfirmPrompts()
+    command = [sys.executable, "-c", ASKING_CLI]
+    async with spawn_cli(
+        command, cwd=tmp_path, env=os.environ, stdin=stdin, terminal=terminal, confirm_prompts=prompts
+    ) as process:
+        output = await asyncio.wait_for(process.stdout.read(), 10)
+        await process.wait()
+    return output.decode()
+
+
+def _write_agents(tmp_path, extra: str, agent_type: str = "codex", command: str = "[cli, '{{prompt_file}}']"):
+    agents_yaml = tmp_path / "agents.yaml"
+    agents_yaml.write_text(f"agents:\n  cli:\n    type: {agent_type}\n    command: {command}\n{extra}")
+    return agents_yaml
+
+
+class TestPromptForwarding:
+    """Test cases for answering prompts printed by agent processes."""
+
+    @pytest.mark.asyncio
+    async def test_prompts_are_asked_about_and_answered_on_stdin(self, tmp_path):
+        questions = []
+
+        async def ask(question: str) -> bool:
+            questions.append(question)
+            return True
+
+        with answer_confirmations(ask):
+            output = await _run(tmp_path)
+
+        assert questions == ["Edit src/app.py: +3 -1\nApply this change? (y/n)"]
+        assert output.endswith("Apply this change? (y/n) got y\n")
+
+    @pytest.mark.asyncio
+    async def test_prompts_are_denied_when_there_is_nobody_to_ask(self, tmp_path):
+        output = await _run(tmp_path, prompts=ConfirmPrompts(approve="yes", deny="no"))
+
+        assert output.endswith("got no\n")
+
+    @pytest.mark.asyncio
+    async def test_answers_go_to_the_terminal_when_stdin_is_one(self, tmp_path):
+        async def ask(question: str) -> bool:
+            return True
+
+        with answer_confirmations(ask):
+            output = await _run(tmp_path, stdin=False, terminal=(120, 30))
+
+        assert output.endswith("got y\n")
+
+    @pytest.mark.asyncio
+    async def test_custom_patterns(self, tmp_path):
+        async def ask(question: str) -> bool:
+            return False
+
+        with answer_confirmations(ask):
+            output = await _run(tmp_path, prompts=ConfirmPrompts(patterns=[r"apply this change\?"], deny="skip"))
+
+        assert output.endswith("got skip\n")
+
+
+class TestConfirmationBroker:
+    """Test cases for asking in the thread."""
+
+    @pytest.mark.asyncio
+    async def test_button_answers_resolve_the_prompt(self):
+        posted = []
+
+        async def send_confirmation(channel, thread_ts, text, key):
+            posted.append((channel, thread_ts, text, key))
+            return "200.1"
+
+        async def send_message(channel, thread_ts, text):
+            raise AssertionError(text)
+
+        broker = ConfirmationBroker(send_confirmation, send_message)
+        asking = asyncio.ensure_future(broker.ask("C1", "100.1", "aider", "Apply? (y/n)", 60))
+        while not posted:
+            await asyncio.sleep(0)
+
+        assert broker.answer(posted[0][3], True) is True
+        assert await asking is True
+        assert broker.answer(posted[0][3], False) is False
+        assert "`aider` is waiting for an answer" in posted[0][2] and "Apply? (y/n)" in posted[0][2]
+
+    @pytest.mark.asyncio
+    async def test_unanswered_prompts_are_denied(self):
+        messages = []
+
+        async def send_confirmation(channel, thread_ts, text, key):
+            return "200.1"
+
+        async def send_message(channel, thread_ts, text):
+            messages.append(text)
+
+        broker = ConfirmationBroker(send_confirmation, send_message)
+
+        assert await broker.ask("C1", "100.1", "aider", "Apply? (y/n)", 0.01) is False
+        assert messages == ["No answer for `aider` within 0.01s; it was denied."]
+
+    @pytest.mark.asyncio
+    async def test_chats_without_buttons_deny_with_a_note(self):
+        messages = []
+
+        async def send_confirmation(channel, thread_ts, text, key):
+            return None
+
+        async def send_message(channel, thread_ts, text):
+            messages.append(text)
+
+        broker = ConfirmationBroker(send_confirmation, send_message)
+
+        assert await broker.ask("github:o/r", "7", "aider", "Diff...\nApply? (y/n)", 60) is False
+        assert "no buttons to answer with" in messages[0] and "Apply? (y/n)" in messages[0]
+        assert "Diff..." not in messages[0]
+
+
+class TestConfirmPromptsConfig:
+    """Test cases for confirm_prompts in agents.yaml."""
+
+    def test_true_uses_the_default_patterns(self, tmp_path):
+        agent = _load_agents(_write_agents(tmp_path, "    confirm_prompts: true\n"))["cli"]
+
+        assert agent.confirm_prompts == ConfirmPrompts(patterns=list(DEFAULT_CONFIRM_PATTERNS))
+
+    def test_mapping(self, tmp_path):
+        extra = (
+            "    confirm_prompts:\n      patterns: ['Proceed\\?']\n      approve: 'yes'\n"
+            "      deny: 'no'\n      timeout_secs: 120\n"
+        )
+
+        prompts = _load_agents(_write_agents(tmp_path, extra, agent_type="gemini", command="[gemini]"))[
+            "cli"
+        ].confirm_prompts
+
+        assert prompts == ConfirmPrompts(patterns=["Proceed\\?"], approve="yes", deny="no", timeout_secs=120)
+
+    @pytest.mark.parametrize(
+        "extra,agent_type,command,message",
+        [
+            ("    confirm_prompts: true\n", "codex", "[codex, exec]", "needs {{prompt_file}}"),
+            ("    confirm_prompts: true\n", "mock", "[mock]", "only valid for claude, codex, and gemini"),
+            ("    confirm_prompts: yes-please\n", "gemini", "[gemini]", "must be true or a mapping"),
+            ("    confirm_prompts: {answer: y}\n", "gemini", "[gemini]", "unsupported keys: answer"),
+            ("    confirm_prompts: {patterns: ['(']}\n", "gemini", "[gemini]", "not a valid regular expression"),
+            ("    confirm_prompts: {timeout_secs: 0}\n", "gemini", "[gemini]", "timeout_secs must be a positive"),
+        ],
+    )
+    def test_rejects_invalid_values(self, tmp_path, extra, agent_type, command, message):
+        with pytest.raises(ConfigError, match=message.replace("{", r"\{").replace("}", r"\}")):
+            _load_agents(_write_agents(tmp_path, extra, agent_type=agent_type, command=command))
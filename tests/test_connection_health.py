@@ -0,0 +1,166 @@
+"""Tests for chat adapter connection health tracking and disconnect alerts."""
+
+from __future__ import annotations
+
+import pytest
+
+from src.chat_adapters.health import (
+    ConnectionHealth,
+    DisconnectWatchdog,
+    format_connection_health,
+    format_duration,
+)
+
+
+class FakeClock:
+    def __init__(self, now: float = 1000.0) -> None:
+        self.now = now
+
+    def __call__(self) -> float:
+        return self.now
+
+
+class TestConnectionHealth:
+    """Metrics recorded by ConnectionHealth."""
+
+    def test_counts_reconnect_attempts_after_first_connect(self):
+        clock = FakeClock()
+        health = ConnectionHealth(clock=clock)
+
+        health.record_connect_attempt()
+        health.record_connected()
+        clock.now += 60
+        health.record_disconnected()
+        health.record_connect_attempt()
+        health.record_connect_failed()
+        health.record_connect_attempt()
+        health.record_connected()
+
+        snapshot = health.snapshot()
+        assert snapshot.connected
+        assert snapshot.connects == 2
+        assert snapshot.disconnects == 1
+        assert snapshot.reconnect_attempts == 2
+        assert snapshot.failed_connects == 1
+
+    def test_disconnect_is_idempotent(self):
+        clock = FakeClock()
+        health = ConnectionHealth(clock=clock)
+        health.record_connected()
+
+        clock.now += 10
+        health.record_disconnected()
+        clock.now += 10
+        health.record_disconnected()
+
+        assert health.snapshot().disconnects == 1
+        assert health.disconnected_for() == 10
+
+    def test_never_connected_counts_as_down_since_start(self):
+        clock = FakeClock()
+        health = ConnectionHealth(clock=clock)
+        clock.now += 42
+
+        assert health.disconnected_for() == 42
+
+    def test_event_lag(self):
+        clock = FakeClock()
+        health = ConnectionHealth(clock=clock)
+
+        health.record_event(clock.now - 1)
+        health.record_event(clock.now - 3)
+        health.record_event(None)
+
+        snapshot = health.snapshot()
+        assert snapshot.events_received == 3
+        assert snapshot.last_event_lag_secs == 3
+        assert snapshot.avg_event_lag_secs == 2
+        assert snapshot.max_event_lag_secs == 3
+
+    def test_format_connection_health(self):
+        clock = FakeClock()
+        health = ConnectionHealth(clock=clock)
+        health.record_connect_attempt()
+        health.record_connected()
+        health.record_event(clock.now - 0.5)
+        clock.now += 125
+
+        text = format_connection_health(health.snapshot(), now=clock.now)
+
+        assert "Chat connection: connected for 2m 5s" in text
+        assert "reconnect attempts: 0" in text
+        assert "Event lag: last 0.5s" in text
+
+    def test_format_duration(self):
+        assert format_duration(5) == "5s"
+        assert format_duration(3700) == "1h 1m"
+        assert format_duration(90000) == "1d 1h 0m"
+
+
+class TestDisconnectWatchdog:
+    """Alerting when the connection stays down."""
+
+    def _watchdog(self, health, state, alerts, threshold=60):
+        async def _probe():
+            return state["connected"]
+
+        async def _alert(text):
+            alerts.append(text)
+
+        return DisconnectWatchdog(health, probe=_probe, alert=_alert, threshold_secs=threshold)
+
+    @pytest.mark.asyncio
+    async def test_alerts_once_after_threshold_then_recovers(self):
+        clock = FakeClock()
+        health = ConnectionHealth(clock=clock)
+        health.record_connected()
+        state = {"connected": False}
+        alerts: list[str] = []
+        watchdog = self._watchdog(health, state, alerts)
+
+        await watchdog.check()
+        assert alerts == []
+
+        clock.now += 61
+        await watchdog.check()
+        clock.now += 30
+        await watchdog.check()
+        assert len(alerts) == 1
+        assert "disconnected from chat for 1m 1s" in alerts[0]
+
+        state["connected"] = True
+        await watchdog.check()
+        assert len(alerts) == 2
+        assert "reconnected" in alerts[1]
+        assert health.connected
+
+    @pytest.mark.asyncio
+    async def test_short_outage_does_not_alert(self):
+        clock = FakeClock()
+        health = ConnectionHealth(clock=clock)
+        health.record_connected()
+        state = {"connected": False}
+        alerts: list[str] = []
+        watchdog = self._watchdog(health, state, alerts)
+
+        await watchdog.check()
+        clock.now += 30
+        state["connected"] = True
+        await watchdog.check()
+
+        assert alerts == []
+
+    @pytest.mark.asyncio
+    async def test_alert_failures_are_swallowed(self):
+        clock = FakeClock()
+        health = ConnectionHealth(clock=clock)
+
+        async def _probe():
+            return False
+
+        async def _alert(text):
+            raise RuntimeError("webhook down")
+
+        watchdog = DisconnectWatchdog(health, probe=_probe, alert=_alert, threshold_secs=0)
+
+        await watchdog.check()
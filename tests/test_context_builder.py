@@ -164,3 +164,74 @@ class TestContextBuilder:
 
         assert "SUMMARY" not in context
         assert "USER:" in context
+
+    def test_build_context_compresses_beyond_detailed_window(self):
+        """Interactions older than the detailed window are compressed, not sent in full."""
+        interactions = [
+            ConversationInteraction(
+                interaction_number=i + 1,
+                user_message=ConversationMessage(role="user", content=f"Request {i+1}"),
+                agent_message=ConversationMessage(role="assistant", content=f"Response {i+1}"),
+            )
+            for i in range(14)
+        ]
+        interactions[0].user_message.content = "Request 1. Let's use SQLite instead of Postgres."
+
+        context = ContextBuilder.build_context_for_agent(interactions)
+
+        assert context.startswith("### SUMMARY BEFORE THESE MESSAGES\nDecisions:")
+        assert "- Let's use SQLite instead of Postgres." in context
+        assert "- Last ask: Request 4" in context
+        assert context.count("USER:") == 10
+        assert "USER:\nRequest 5\n" in context
+        assert "USER:\nRequest 4\n" not in context
+
+    def test_build_context_compresses_after_summary(self):
+        """The compressed brief follows the existing summary."""
+        interactions = [
+            ConversationInteraction(
+                interaction_number=i + 1,
+                user_message=ConversationMessage(role="user", content=f"Request {i+1}"),
+                agent_message=ConversationMessage(role="assistant", content=f"Response {i+1}"),
+            )
+            for i in range(17)
+        ]
+
+        context = ContextBuilder.build_context_for_agent(interactions, summary="Early work.", summarized_count=5)
+
+        assert context.startswith("### SUMMARY BEFORE THESE MESSAGES\nEarly work.\n\nCurrent task state:")
+        assert "- Last ask: Request 7" in context
+        assert context.count("USER:") == 10
+
+    def test_build_context_compresses_over_character_budget(self):
+        """Long messages shrink the detailed window; the newest interaction always stays."""
+        interactions = [
+            ConversationInteraction(
+                interaction_number=i + 1,
+                user_message=ConversationMessage(role="user", content=f"Request {i+1}"),
+                agent_message=ConversationMessage(role="assistant", content="x" * 9000),
+            )
+            for i in range(3)
+        ]
+
+        context = ContextBuilder.build_context_for_agent(interactions)
+
+        assert context.count("USER:") == 1
+        assert "USER:\nRequest 3" in context
+        assert "- Last ask: Request 2" in context
+
+    def test_build_context_without_compression(self):
+        """With compression off every interaction after the summary is sent in detail."""
+        interactions = [
+            ConversationInteraction(
+                interaction_number=i + 1,
+                user_message=ConversationMessage(role="user", content=f"Request {i+1}"),
+                agent_message=ConversationMessage(role="assistant", content=f"Response {i+1}"),
+            )
+            for i in range(14)
+        ]
+
+        context = ContextBuilder.build_context_for_agent(interactions, compress=False)
+
+        assert "SUMMARY" not in context
+        assert context.count("USER:") == 14
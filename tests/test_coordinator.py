@@ -0,0 +1,104 @@
+"""Tests for project roles (planner, coder, reviewer) and the runs they share."""
+
+from __future__ import annotations
+
+from pathlib import Path
+
+import pytest
+
+from src.core.config import _load_projects
+from src.core.coordinator import MAX_REVIEW_DIFF_CHARS, cast_for, review_task
+from src.core.errors import AgentNotFound, ConfigError
+from src.core.models import Agent, AgentType, Project, ProjectRoles, Session, WorkingDirMode
+from src.core.pipeline import pipeline_for
+
+
+def _load(tmp_path: Path, extra: str) -> Project:
+    projects_yaml = tmp_path / "projects.yaml"
+    projects_yaml.write_text(
+        f"base_dir: {tmp_path}\nprojects:\n  demo:\n    path: .\n    default_agent: claude\n{extra}"
+    )
+    projects, _ = _load_projects(projects_yaml)
+    return projects["demo"]
+
+
+def _agent(agent_id: str) -> Agent:
+    return Agent(id=agent_id, type=AgentType.MOCK, command=[], working_dir_mode=WorkingDirMode.PROJECT)
+
+
+class TestRoles:
+    """Test cases for `roles:` in projects.yaml."""
+
+    def test_parses_roles_and_adds_their_steps(self, tmp_path):
+        project = _load(tmp_path, "    roles: {planner: claude, coder: codex, reviewer: gemini}\n")
+
+        assert project.roles == ProjectRoles(planner="claude", coder="codex", reviewer="gemini")
+        assert [step.name for step in pipeline_for(project)] == ["plan", "edit", "review", "pr", "diff-review"]
+
+    def test_keeps_the_steps_a_pipeline_places_itself(self, tmp_path):
+        project = _load(
+            tmp_path, "    roles: {planner: claude, reviewer: gemini}\n    pipeline: [approve, plan, edit, pr, review]\n"
+        )
+
+        assert [step.name for step in pipeline_for(project)] == ["approve", "plan", "edit", "pr", "review"]
+
+    @pytest.mark.parametrize(
+        "roles,message",
+        [
+            ("{tester: claude}", "unknown roles tester"),
+            ("{coder: ''}", "coder must be an agent id"),
+            ("[claude]", "must map planner, coder, reviewer to agent ids"),
+        ],
+    )
+    def test_rejects_invalid_roles(self, tmp_path, roles, message):
+        with pytest.raises(ConfigError, match=message):
+            _load(tmp_path, f"    roles: {roles}\n")
+
+    def test_roles_must_use_allowed_agents(self, tmp_path):
+        with pytest.raises(ConfigError, match="reviewer gemini is not in its allowed_agents"):
+            _load(tmp_path, "    allowed_agents: [claude]\n    roles: {reviewer: gemini}\n")
+
+
+class TestCast:
+    """Test cases for picking the agents of a run."""
+
+    def _session(self, tmp_path) -> Session:
+        return Session(
+            project_id="demo",
+            channel_id="C1",
+            thread_ts="1.0",
+            active_agent_id="claude",
+            active_agent_type=AgentType.MOCK,
+            project_path=tmp_path,
+        )
+
+    def test_the_session_agent_fills_unset_roles(self, tmp_path):
+        project = Project(id="demo", channel_name="demo", path=tmp_path, default_agent_id="claude")
+        agents = {agent_id: _agent(agent_id) for agent_id in ("claude", "gemini")}
+
+        cast = cast_for(project, self._session(tmp_path), agents)
+        assert (cast.planner.id, cast.coder.id, cast.reviewer, cast.multi_agent) == ("claude", "claude", None, False)
+
+        project.roles = ProjectRoles(reviewer="gemini")
+        cast = cast_for(project, self._session(tmp_path), agents)
+        assert [agent.id for agent in cast.agents] == ["claude", "gemini"]
+        assert cast.describe() == "planner `claude`, coder `claude`, and reviewer `gemini`"
+
+    def test_unconfigured_agents_are_reported(self, tmp_path):
+        project = Project(
+            id="demo",
+            channel_name="demo",
+            path=tmp_path,
+            default_agent_id="claude",
+            roles=ProjectRoles(planner="gone"),
+        )
+
+        with pytest.raises(AgentNotFound, match="gone"):
+            cast_for(project, self._session(tmp_path), {"claude": _agent("claude")})
+
+    def test_review_task_cuts_long_diffs(self):
+        task = review_task("add a cache", "+x\n" * MAX_REVIEW_DIFF_CHARS, plan="1. add it")
+
+        assert "Request:\nadd a cache" in task and "Plan it was made from:\n1. add it" in task
+        assert "(the rest of the diff was cut)" in task
+        assert len(task) < MAX_REVIEW_DIFF_CHARS + 2000
@@ -0,0 +1,253 @@
+"""Tests for running agents and project steps in a Nix, mise, or asdf environment."""
+
+from __future__ import annotations
+
+import os
+import sys
+from pathlib import Path
+
+import pytest
+
+from src.agent_adapters import dev_env as dev_env_module
+from src.agent_adapters.base import spawn_cli
+from src.agent_adapters.dev_env import DevEnvUnavailable, dev_env_command, run_in_dev_env
+from src.core.config import _load_projects
+from src.core.errors import ConfigError
+from src.core.models import DevEnvironment, DevEnvKind, Project, SandboxConfig, SandboxMode
+from src.core.preflight import check_repository
+from src.core.sandbox import Sandbox
+
+# Stands in for nix: logs its arguments and runs the command after `--command` with a marker in its environment
+FAKE_NIX = (
+    "import os, sys\n"
+    "with open(sys.argv[0] + '.log', 'a') as log:\n"
+    "    log.write(' '.join(sys.argv[1:]) + '\\n')\n"
+    "command = sys.argv[sys.argv.index('--command') + 1:]\n"
+    "os.environ['IN_NIX_SHELL'] = 'impure'\n"
+    "os.execvp(command[0], command)\n"
+)
+
+
+@pytest.fixture
+def fake_nix(tmp_path, monkeypatch) -> Path:
+    nix = tmp_path / "nix"
+    nix.write_text(f"#!{sys.executable}\n{FAKE_NIX}")
+    nix.chmod(0o755)
+    monkeypatch.setattr(dev_env_module, "NIX", str(nix))
+    return nix
+
+
+NIX_ENV = DevEnvironment(kind=DevEnvKind.NIX)
+MISE_ENV = DevEnvironment(kind=DevEnvKind.MISE)
+ASDF_ENV = DevEnvironment(kind=DevEnvKind.ASDF)
+
+
+class TestDevEnvCommand:
+    """Test cases for wrapping commands in `nix develop`."""
+
+    def test_wraps_the_command(self, fake_nix):
+        argv = dev_env_command(["claude", "-p", "task"], DevEnvironment(flake=".#ci"))
+
+        assert argv[:3] == [str(fake_nix), "develop", ".#ci"]
+        assert argv[-4:] == ["--command", "claude", "-p", "task"]
+        assert "nix-command flakes" in argv
+
+    def test_leaves_the_command_alone_without_an_env(self):
+        assert dev_env_command(["claude"], None) == ["claude"]
+
+    def test_reports_a_missing_nix(self, monkeypatch, tmp_path):
+        monkeypatch.setattr(dev_env_module, "NIX", str(tmp_path / "missing-nix"))
+
+        with pytest.raises(DevEnvUnavailable, match="needs .* on PATH"):
+            dev_env_command(["claude"], NIX_ENV)
+
+    @pytest.mark.asyncio
+    async def test_agent_cli_starts_in_the_shell(self, tmp_path, fake_nix):
+        with run_in_dev_env(NIX_ENV):
+            async with spawn_cli(
+                [sys.executable, "-c", "import os; print(os.environ.get('IN_NIX_SHELL'))"],
+                cwd=tmp_path,
+                env=os.environ,
+                stdin=False,
+            ) as process:
+                assert process.stdout is not None
+                output = await process.stdout.read()
+                await process.wait()
+
+        assert output.decode().strip() == "impure"
+        assert Path(f"{fake_nix}.log").read_text().startswith("develop . ")
+
+    @pytest.mark.asyncio
+    async def test_steps_run_in_the_shell(self, tmp_path, fake_nix):
+        sandbox = Sandbox(SandboxConfig(mode=SandboxMode.PROCESS), NIX_ENV)
+
+        result = await sandbox.run('echo "shell=$IN_NIX_SHELL"', tmp_path, 10)
+
+        assert result.passed
+        assert result.output.strip() == "shell=impure"
+
+    @pytest.mark.asyncio
+    async def test_bubblewrapped_runs_never_start_nix_on_the_host(self, tmp_path, fake_nix):
+        """`nix develop` would evaluate the flake outside bwrap, so neither steps nor agent CLIs start it."""
+        bubblewrap = SandboxConfig(mode=SandboxMode.BUBBLEWRAP)
+
+        result = await Sandbox(bubblewrap, NIX_ENV).run("make test", tmp_path, 10)
+
+        assert not result.passed
+        assert "cannot be combined with a bubblewrap sandbox" in result.output
+        with run_in_dev_env(NIX_ENV), pytest.raises(DevEnvUnavailable, match="on the host"):
+            async with spawn_cli(["claude"], cwd=tmp_path, env=os.environ, stdin=False, sandbox=bubblewrap):
+                pass
+        assert not Path(f"{fake_nix}.log").exists()
+
+    @pytest.mark.asyncio
+    async def test_steps_fail_without_nix(self, tmp_path, monkeypatch):
+        monkeypatch.setattr(dev_env_module, "NIX", str(tmp_path / "missing-nix"))
+
+        result = await Sandbox(SandboxConfig(), NIX_ENV).run("make test", tmp_path, 10)
+
+        assert result.returncode == 127
+        assert "install Nix" in result.output
+
+
+class TestToolVersionManagers:
+    """Test cases for `env: mise` and `env: asdf`."""
+
+    @staticmethod
+    def _fake_mise(tmp_path: Path, monkeypatch) -> Path:
+        """Logs its arguments and runs the command after `--` with them in MISE_ACTIVE."""
+        mise = tmp_path / "mise"
+        mise.write_text(
+            f"#!{sys.executable}\n"
+            "import os, sys\n"
+            "open(sys.argv[0] + '.log', 'a').write(' '.join(sys.argv[1:]) + '\\n')\n"
+            "command = sys.argv[sys.argv.index('--') + 1:]\n"
+            "os.environ['MISE_ACTIVE'] = ' '.join(sys.argv[1:3])\n"
+            "os.execvp(command[0], command)\n"
+        )
+        mise.chmod(0o755)
+        monkeypatch.setattr(dev_env_module, "MISE", str(mise))
+        return mise
+
+    @pytest.mark.asyncio
+    async def test_mise_execs_the_command(self, tmp_path, monkeypatch):
+        self._fake_mise(tmp_path, monkeypatch)
+
+        sandbox = Sandbox(SandboxConfig(mode=SandboxMode.PROCESS), MISE_ENV)
+
+        result = await sandbox.run('echo "$MISE_ACTIVE"', tmp_path, 10)
+
+        assert result.output.strip() == "exec --"  # The repository's config is not trusted for it
+
+    @pytest.mark.asyncio
+    async def test_mise_trusts_the_config_only_when_told_to(self, tmp_path, monkeypatch):
+        self._fake_mise(tmp_path, monkeypatch)
+        trusted = DevEnvironment(kind=DevEnvKind.MISE, trust=True)
+
+        sandbox = Sandbox(SandboxConfig(mode=SandboxMode.PROCESS), trusted)
+
+        result = await sandbox.run('echo "$MISE_ACTIVE"', tmp_path, 10)
+
+        assert result.output.strip() == "--yes exec"
+
+    @pytest.mark.asyncio
+    async def test_bubblewrapped_runs_never_start_mise_on_the_host(self, tmp_path, monkeypatch):
+        mise = self._fake_mise(tmp_path, monkeypatch)
+        bubblewrap = SandboxConfig(mode=SandboxMode.BUBBLEWRAP)
+
+        result = await Sandbox(bubblewrap, MISE_ENV).run("make test", tmp_path, 10)
+
+        assert "`env: mise` cannot be combined with a bubblewrap sandbox" in result.output
+        with run_in_dev_env(MISE_ENV), pytest.raises(DevEnvUnavailable, match="on the host"):
+            async with spawn_cli(["claude"], cwd=tmp_path, env=os.environ, stdin=False, sandbox=bubblewrap):
+                pass
+        assert not Path(f"{mise}.log").exists()
+
+    @pytest.mark.asyncio
+    async def test_asdf_shims_come_first_on_path(self, tmp_path, monkeypatch):
+        shims = tmp_path / "asdf" / "shims"
+        shims.mkdir(parents=True)
+        (shims / "node").write_text("#!/bin/sh\necho pinned node\n")
+        (shims / "node").chmod(0o755)
+        monkeypatch.setenv("ASDF_DATA_DIR", str(tmp_path / "asdf"))
+
+        result = await Sandbox(SandboxConfig(mode=SandboxMode.PROCESS), ASDF_ENV).run("node", tmp_path, 10)
+
+        assert result.output.strip() == "pinned node"
+
+    def test_reports_missing_tools(self, tmp_path, monkeypatch):
+        monkeypatch.setattr(dev_env_module, "MISE", str(tmp_path / "missing-mise"))
+        monkeypatch.setenv("ASDF_DATA_DIR", str(tmp_path / "missing-asdf"))
+
+        with pytest.raises(DevEnvUnavailable, match="install mise"):
+            dev_env_command(["claude"], MISE_ENV)
+        with pytest.raises(DevEnvUnavailable, match="needs asdf's shims"):
+            dev_env_command(["claude"], ASDF_ENV)
+
+    @pytest.mark.asyncio
+    async def test_preflight_warns_without_pinned_versions(self, tmp_path, monkeypatch):
+        monkeypatch.setenv("ASDF_DATA_DIR", str(tmp_path / "asdf"))
+        (tmp_path / "asdf" / "shims").mkdir(parents=True)
+        project = Project(id="demo", channel_name="demo", path=tmp_path, default_agent_id="claude", dev_env=ASDF_ENV)
+
+        issues = await check_repository(project, tmp_path)
+
+        assert [issue.blocking for issue in issues] == [False]
+        assert "has no `.tool-versions`" in issues[0].problem
+
+        (tmp_path / ".tool-versions").write_text("nodejs 20.11.0\n")
+
+        assert await check_repository(project, tmp_path) == []
+
+
+class TestDevEnvConfig:
+    """Test cases for `env:` in projects.yaml."""
+
+    @staticmethod
+    def _load(tmp_path: Path, extra: str) -> Project:
+        projects_yaml = tmp_path / "projects.yaml"
+        projects_yaml.write_text(
+            f"base_dir: {tmp_path}\nprojects:\n  demo:\n    path: .\n    default_agent: claude\n{extra}"
+        )
+        projects, _ = _load_projects(projects_yaml)
+        return projects["demo"]
+
+    def test_parses_the_short_and_long_forms(self, tmp_path):
+        assert self._load(tmp_path, "    env: nix\n").dev_env == DevEnvironment(kind=DevEnvKind.NIX)
+        assert self._load(tmp_path, "").dev_env is None
+
+        project = self._load(tmp_path, "    env:\n      type: nix\n      flake: .#ci\n")
+
+        assert project.dev_env == DevEnvironment(kind=DevEnvKind.NIX, flake=".#ci")
+        assert self._load(tmp_path, "    env: mise\n").dev_env == MISE_ENV
+        assert self._load(tmp_path, "    env: {type: ASDF}\n").dev_env == ASDF_ENV
+        assert self._load(tmp_path, "    env: {type: mise, trust: true}\n").dev_env.trust
+
+    @pytest.mark.parametrize(
+        "extra,message",
+        [
+            ("    env: conda\n", "type must be one of nix, mise, asdf"),
+            ("    env: {type: mise, flake: .#ci}\n", "flake only applies to type nix"),
+            ("    env: {type: nix, shell: ci}\n", "unsupported keys shell"),
+            ("    env: {type: nix, flake: 3}\n", "flake must be a flake reference"),
+            ("    env: nix\n    sandbox: {mode: docker, image: node:20}\n", "cannot be combined with a container"),
+            ("    env: nix\n    sandbox: {mode: bubblewrap}\n", "cannot be combined with a bubblewrap sandbox"),
+            ("    env: mise\n    sandbox: {mode: bubblewrap}\n", "cannot be combined with a bubblewrap sandbox"),
+            ("    env: {type: nix, trust: true}\n", "trust only applies to type mise"),
+            ("    env: {type: mise, trust: yes please}\n", "trust must be true or false"),
+        ],
+    )
+    def test_rejects_invalid_envs(self, tmp_path, extra, message):
+        with pytest.raises(ConfigError, match=message):
+            self._load(tmp_path, extra)
+
+    @pytest.mark.asyncio
+    async def test_preflight_blocks_without_nix_or_a_flake(self, tmp_path, monkeypatch):
+        monkeypatch.setattr(dev_env_module, "NIX", str(tmp_path / "missing-nix"))
+        project = Project(id="demo", channel_name="demo", path=tmp_path, default_agent_id="claude", dev_env=NIX_ENV)
+
+        issues = await check_repository(project, tmp_path)
+
+        assert [issue.blocking for issue in issues] == [True, True]
+        assert "is not on the daemon's PATH" in issues[0].problem
+        assert "has no `flake.nix`" in issues[1].problem
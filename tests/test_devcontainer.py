@@ -0,0 +1,223 @@
+"""Tests for running a project's commands in its devcontainer."""
+
+from __future__ import annotations
+
+import json
+import sys
+from pathlib import Path
+
+import pytest
+
+from src.core.config import _load_projects
+from src.core.devcontainer import DevcontainerError, load_devcontainer, resolve_devcontainer, strip_jsonc
+from src.core.errors import ConfigError
+from src.core.models import Project, SandboxConfig, SandboxMode
+from src.core.preflight import check_repository
+from src.core.sandbox import Sandbox
+
+# Stands in for docker: logs each invocation and remembers the images it built or committed
+FAKE_RUNTIME = (
+    "import os, sys\n"
+    "with open(sys.argv[0] + '.log', 'a') as log:\n"
+    "    log.write(' '.join(sys.argv[1:]) + '\\n')\n"
+    "images = sys.argv[0] + '.images'\n"
+    "known = open(images).read().split() if os.path.exists(images) else []\n"
+    "if sys.argv[1] == 'image':\n"
+    "    sys.exit(0 if sys.argv[3] in known else 1)\n"
+    "if sys.argv[1] in ('build', 'commit'):\n"
+    "    tag = sys.argv[sys.argv.index('--tag') + 1] if sys.argv[1] == 'build' else sys.argv[3]\n"
+    "    open(images, 'a').write(tag + '\\n')\n"
+    "if sys.argv[1] == 'run':\n"
+    "    print('ran', sys.argv[-1])\n"
+)
+
+
+def _runtime(tmp_path: Path) -> Path:
+    runtime = tmp_path / "fake-docker"
+    runtime.write_text(f"#!{sys.executable}\n{FAKE_RUNTIME}")
+    runtime.chmod(0o755)
+    return runtime
+
+
+def _repo(tmp_path: Path, config: str, dockerfile: str | None = None) -> Path:
+    repo = tmp_path / "repo"
+    (repo / ".devcontainer").mkdir(parents=True)
+    (repo / ".devcontainer" / "devcontainer.json").write_text(config)
+    if dockerfile is not None:
+        (repo / ".devcontainer" / "Dockerfile").write_text(dockerfile)
+    return repo
+
+
+def _calls(runtime: Path) -> list[str]:
+    log = Path(f"{runtime}.log")
+    return [line.split()[0] for line in log.read_text().splitlines()] if log.exists() else []
+
+
+class TestDevcontainerFile:
+    """Test cases for reading devcontainer.json."""
+
+    def test_strips_comments_and_trailing_commas(self):
+        text = '{\n  // the image\n  "image": "node:20", /* inline */\n  "url": "http://x//y",\n  "a": [1, 2,],\n}'
+
+        assert json.loads(strip_jsonc(text)) == {"image": "node:20", "url": "http://x//y", "a": [1, 2]}
+
+    def test_reads_the_build_env_and_create_commands(self, tmp_path):
+        repo = _repo(
+            tmp_path,
+            '{"build": {"dockerfile": "Dockerfile", "context": "..", "args": {"NODE": 20}},'
+            ' "containerEnv": {"CI": "1"},'
+            ' "onCreateCommand": ["npm", "ci"],'
+            ' "postCreateCommand": {"hooks": "make hooks", "docs": "make docs"},'
+            ' "postStartCommand": "ignored"}',
+            dockerfile="FROM node:20\n",
+        )
+
+        devcontainer = load_devcontainer(repo)
+
+        assert devcontainer.dockerfile == (repo / ".devcontainer" / "Dockerfile").resolve()
+        assert devcontainer.context == repo.resolve()
+        assert devcontainer.build_args == {"NODE": "20"}
+        assert devcontainer.env == {"CI": "1"}
+        assert devcontainer.create_commands == ["npm ci", "make hooks", "make docs"]
+
+    def test_digest_follows_the_dockerfile(self, tmp_path):
+        repo = _repo(tmp_path, '{"build": {"dockerfile": "Dockerfile"}}', dockerfile="FROM node:20\n")
+        before = load_devcontainer(repo).tag
+
+        (repo / ".devcontainer" / "Dockerfile").write_text("FROM node:22\n")
+
+        assert load_devcontainer(repo).tag != before
+
+    @pytest.mark.parametrize(
+        "config,message",
+        [
+            ('{"dockerComposeFile": "compose.yml"}', "Docker Compose devcontainers are not supported"),
+            ('{"features": {}}', "needs an `image` or a `build.dockerfile`"),
+            ('{"build": {"dockerfile": "Missing"}}', "`Missing` does not exist"),
+            ('{"image": "node:20", "postCreateCommand": 3}', "`postCreateCommand` in devcontainer.json must be"),
+            ("{image: node}", "is not valid JSON"),
+        ],
+    )
+    def test_rejects_unusable_files(self, tmp_path, config, message):
+        with pytest.raises(DevcontainerError, match=message):
+            load_devcontainer(_repo(tmp_path, config))
+
+    def test_missing_file(self, tmp_path):
+        with pytest.raises(DevcontainerError, match="has no .devcontainer/devcontainer.json"):
+            load_devcontainer(tmp_path)
+
+
+class TestDevcontainerImage:
+    """Test cases for building and reusing the devcontainer image."""
+
+    @pytest.mark.asyncio
+    async def test_plain_image_is_used_as_is(self, tmp_path):
+        runtime = _runtime(tmp_path)
+        repo = _repo(tmp_path, '{"image": "python:3.12", "containerEnv": {"PIP_NO_CACHE_DIR": "1"}}')
+
+        sandbox = await resolve_devcontainer(_sandbox(runtime), repo)
+
+        assert sandbox.image == "python:3.12"
+        assert sandbox.env == {"PIP_NO_CACHE_DIR": "1"}
+        assert _calls(runtime) == []
+
+    @pytest.mark.asyncio
+    async def test_builds_once_then_reuses_the_image(self, tmp_path):
+        runtime = _runtime(tmp_path)
+        repo = _repo(
+            tmp_path,
+            '{"build": {"dockerfile": "Dockerfile"}, "postCreateCommand": "pip install -e ."}',
+            dockerfile="FROM python:3.12\n",
+        )
+        notices: list[str] = []
+
+        async def on_build(message: str) -> None:
+            notices.append(message)
+
+        first = await resolve_devcontainer(_sandbox(runtime), repo, on_build)
+        second = await resolve_devcontainer(_sandbox(runtime), repo, on_build)
+
+        assert first.image == second.image == load_devcontainer(repo).tag
+        assert len(notices) == 1
+        assert _calls(runtime) == ["image", "build", "run", "commit", "rm", "image"]
+        run = Path(f"{runtime}.log").read_text().splitlines()[2]
+        assert run.endswith(f"{first.image}-base sh -c (pip install -e .)")
+
+    @pytest.mark.asyncio
+    async def test_failed_build_reports_the_output(self, tmp_path):
+        runtime = tmp_path / "broken-docker"
+        runtime.write_text("#!/bin/sh\nif [ \"$1\" = image ]; then exit 1; fi\necho 'no space left'; exit 2\n")
+        runtime.chmod(0o755)
+        repo = _repo(tmp_path, '{"build": {"dockerfile": "Dockerfile"}}', dockerfile="FROM scratch\n")
+
+        with pytest.raises(DevcontainerError, match="(?s)build` failed.*no space left"):
+            await resolve_devcontainer(_sandbox(runtime), repo)
+
+    @pytest.mark.asyncio
+    async def test_sandbox_runs_steps_in_the_devcontainer(self, tmp_path):
+        runtime = _runtime(tmp_path)
+        repo = _repo(tmp_path, '{"image": "python:3.12", "containerEnv": {"CI": "1"}}')
+
+        result = await Sandbox(_sandbox(runtime)).run("pytest -q", repo, 10)
+
+        assert result.passed
+        run = Path(f"{runtime}.log").read_text().splitlines()[-1]
+        assert "--env CI=1 python:3.12 sh -c pytest -q" in run
+
+    @pytest.mark.asyncio
+    async def test_sandbox_reports_a_missing_devcontainer_as_a_failed_step(self, tmp_path):
+        result = await Sandbox(_sandbox(_runtime(tmp_path))).run("pytest -q", tmp_path, 10)
+
+        assert not result.passed
+        assert "has no .devcontainer/devcontainer.json" in result.output
+
+
+def _sandbox(runtime: Path) -> SandboxConfig:
+    return SandboxConfig(
+        mode=SandboxMode.CONTAINER, runtime=str(runtime), network=True, run_agent=True, devcontainer=True
+    )
+
+
+class TestDevcontainerConfig:
+    """Test cases for `sandbox: devcontainer` in projects.yaml."""
+
+    @staticmethod
+    def _load(tmp_path: Path, sandbox: str) -> SandboxConfig:
+        projects_yaml = tmp_path / "projects.yaml"
+        projects_yaml.write_text(
+            f"base_dir: {tmp_path}\nprojects:\n  demo:\n    path: .\n    default_agent: claude\n    sandbox:{sandbox}"
+        )
+        projects, _ = _load_projects(projects_yaml)
+        return projects["demo"].sandbox
+
+    def test_runs_the_agent_online_in_the_devcontainer(self, tmp_path):
+        sandbox = self._load(tmp_path, " devcontainer\n")
+
+        assert sandbox.mode == SandboxMode.CONTAINER
+        assert sandbox.devcontainer and sandbox.run_agent and sandbox.network
+        assert sandbox.image is None
+
+    def test_can_keep_the_agent_on_the_host(self, tmp_path):
+        sandbox = self._load(tmp_path, "\n      mode: devcontainer\n      run_agent: false\n      runtime: podman\n")
+
+        assert sandbox.devcontainer and not sandbox.run_agent
+        assert sandbox.runtime == "podman"
+
+    def test_rejects_an_image(self, tmp_path):
+        with pytest.raises(ConfigError, match="takes its image from devcontainer.json"):
+            self._load(tmp_path, "\n      mode: devcontainer\n      image: node:20\n")
+
+    @pytest.mark.asyncio
+    async def test_preflight_blocks_without_a_devcontainer_file(self, tmp_path):
+        project = Project(
+            id="demo",
+            channel_name="demo",
+            path=tmp_path,
+            default_agent_id="claude",
+            sandbox=SandboxConfig(mode=SandboxMode.CONTAINER, devcontainer=True),
+        )
+
+        issues = await check_repository(project, tmp_path)
+
+        assert [issue.blocking for issue in issues] == [True]
+        assert "sandbox is `devcontainer`" in issues[0].problem
@@ -0,0 +1,89 @@
+"""Tests for per-file diff splitting and rendering."""
+
+from __future__ import annotations
+
+from src.core.diff_render import format_diff_overview, render_file_diff, snippet_filename, split_diff
+
+SAMPLE_DIFF = """diff --git a/src/app.py b/src/app.py
+index 1111111..2222222 100644
+--- a/src/app.py
++++ b/src/app.py
+@@ -1,3 +1,4 @@
+ import os
+-print("hi")
++print("hello")
++print("world")
+diff --git a/docs/new.md b/docs/new.md
+new file mode 100644
+index 0000000..3333333
+--- /dev/null
++++ b/docs/new.md
+@@ -0,0 +1 @@
++# New
+diff --git a/old_name.txt b/new_name.txt
+similarity index 100%
+rename from old_name.txt
+rename to new_name.txt
+diff --git a/logo.png b/logo.png
+index 4444444..5555555 100644
+Binary files a/logo.png and b/logo.png differ
+"""
+
+
+class TestSplitDiff:
+    """Parsing `git diff` output into per-file pieces."""
+
+    def test_splits_files_with_status_and_counts(self):
+        files = split_diff(SAMPLE_DIFF)
+
+        assert [f.path for f in files] == ["src/app.py", "docs/new.md", "new_name.txt", "logo.png"]
+        app, new, renamed, logo = files
+        assert (app.status, app.additions, app.deletions) == ("modified", 2, 1)
+        assert (new.status, new.additions, new.deletions) == ("added", 1, 0)
+        assert renamed.status == "renamed"
+        assert renamed.old_path == "old_name.txt"
+        assert logo.binary
+
+    def test_hunks_drop_git_headers(self):
+        app = split_diff(SAMPLE_DIFF)[0]
+
+        assert app.hunks.startswith("@@ -1,3 +1,4 @@")
+        assert "index 1111111" not in app.hunks
+        assert app.text.startswith("diff --git a/src/app.py")
+
+    def test_empty_or_non_git_diff(self):
+        assert split_diff("") == []
+        assert split_diff("just some text\n") == []
+
+    def test_overview_and_summary(self):
+        files = split_diff(SAMPLE_DIFF)
+
+        assert format_diff_overview(files) == "*Changes:* 4 files, +3 -1"
+        assert files[0].summary == "`src/app.py` (modified, +2 -1)"
+        assert "`old_name.txt` → `new_name.txt`" in files[2].summary
+        assert snippet_filename(files[0]) == "app.py.diff"
+
+
+class TestRenderFileDiff:
+    """Rendering a single file for chat."""
+
+    def test_renders_diff_fence(self):
+        rendered = render_file_diff(split_diff(SAMPLE_DIFF)[0])
+
+        assert rendered.startswith("`src/app.py` (modified, +2 -1)\n```diff\n@@")
+        assert rendered.endswith("```")
+
+    def test_truncates_long_files(self):
+        body = "\n".join(f"+line {i}" for i in range(2000))
+        diff = f"diff --git a/big.txt b/big.txt\n--- a/big.txt\n+++ b/big.txt\n@@ -0,0 +1,2000 @@\n{body}\n"
+
+        rendered = render_file_diff(split_diff(diff)[0], limit=500)
+
+        assert len(rendered) <= 600
+        assert "truncated" in rendered
+
+    def test_binary_file(self):
+        rendered = render_file_diff(split_diff(SAMPLE_DIFF)[3])
+
+        assert "Binary file" in rendered
+        assert "```" not in rendered
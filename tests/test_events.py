@@ -0,0 +1,108 @@
+"""Tests for the session lifecycle event bus."""
+
+from __future__ import annotations
+
+import asyncio
+from datetime import timedelta
+from uuid import uuid4
+
+import pytest
+
+from src.core.conversation import SessionManager
+from src.core.events import EventBus, PrOpened, RunFinished, SessionCreated, SessionEnded
+from src.core.metrics import SessionMetrics
+from src.core.models import AgentType, Project, PullRequestRef, SessionStatus
+from src.core.transcripts import RunRecord
+
+
+def _start(manager: SessionManager, tmp_path, thread_ts: str = "1.0"):
+    project = Project(id="api", channel_name="api", path=tmp_path, default_agent_id="claude")
+    return manager.create_session(
+        project=project, channel_id="C1", thread_ts=thread_ts, agent_id="claude", agent_type=AgentType.CLAUDE
+    )
+
+
+def _pr(session, number: int) -> PullRequestRef:
+    return PullRequestRef(
+        project_id="api",
+        session_id=session.id,
+        number=number,
+        url=f"https://github.com/o/api/pull/{number}",
+        head_branch="b",
+        base_branch="main",
+    )
+
+
+class TestEventBus:
+    """Test cases for subscribing and publishing."""
+
+    def test_delivers_events_by_type_until_unsubscribed(self):
+        bus = EventBus()
+        everything, created = [], []
+        bus.subscribe(everything.append)
+        stop = bus.subscribe(created.append, SessionCreated)
+
+        def broken(event):
+            raise RuntimeError("boom")
+
+        bus.subscribe(broken)
+        first = SessionCreated(session_id=uuid4(), project_id="api", channel_id="C1", thread_ts="1.0")
+        bus.publish(first)
+        stop()
+        bus.publish(SessionEnded(first.session_id, "api", SessionStatus.ENDED))
+
+        assert [type(event) for event in everything] == [SessionCreated, SessionEnded]
+        assert created == [first]
+
+    @pytest.mark.asyncio
+    async def test_runs_async_handlers_on_the_loop_even_from_threads(self):
+        bus = EventBus()
+        seen = []
+
+        async def handler(event):
+            await asyncio.sleep(0)
+            seen.append(event.status)
+
+        bus.subscribe(handler, SessionEnded)
+        session_id = uuid4()
+        bus.publish(SessionEnded(session_id, "api", SessionStatus.ENDED))
+        await asyncio.to_thread(bus.publish, SessionEnded(session_id, "api", SessionStatus.ARCHIVED))
+        await bus.drain()
+
+        assert sorted(seen) == [SessionStatus.ARCHIVED, SessionStatus.ENDED]
+
+
+class TestSessionEvents:
+    """Test cases for what the session manager publishes."""
+
+    def test_publishes_the_lifecycle_of_a_session(self, tmp_path):
+        manager = SessionManager()
+        events = []
+        manager.events.subscribe(events.append)
+
+        session = _start(manager, tmp_path)
+        manager.set_pr_ref(_pr(session, 7))
+        manager.set_pr_ref(_pr(session, 7))  # An update of the same PR
+        manager.record_run(session.id, RunRecord(agent_id="claude", success=False, wall_secs=1.0))
+        manager.update_status(session.id, SessionStatus.ENDED)
+        manager.cleanup_ended(timedelta(0))  # Already ended: archived without a second event
+
+        assert [type(event) for event in events] == [SessionCreated, PrOpened, RunFinished, SessionEnded]
+        assert events[-1].status == SessionStatus.ENDED
+
+    def test_metrics_follow_the_bus(self, tmp_path):
+        manager = SessionManager()
+        metrics = SessionMetrics()
+        metrics.follow(manager.events)
+
+        session = _start(manager, tmp_path)
+        _start(manager, tmp_path, "2.0")
+        manager.record_run(session.id, RunRecord(agent_id="claude", success=True, wall_secs=1.0, cost_usd=0.25))
+        manager.record_run(session.id, RunRecord(agent_id="claude", success=False, wall_secs=1.0, cancelled=True))
+        manager.cleanup_ended(timedelta(0))
+
+        assert (metrics.sessions_created, metrics.sessions_archived, metrics.runs_cancelled) == (2, 2, 1)
+        assert metrics.describe() == (
+            "Since startup: 2 session(s) started, 2 ended or archived; 0 run(s) (0 failed, 1 cancelled), "
+            "0 PR(s) opened, ~$0.25"
+        )
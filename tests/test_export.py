@@ -0,0 +1,81 @@
+"""Tests for rendering session exports."""
+
+from __future__ import annotations
+
+import json
+from datetime import datetime, timezone
+
+from src.core.export import SessionExport, describe_run, export_json, export_markdown
+from src.core.models import AgentType, PullRequestRef, Session
+from src.core.timeline import Checkpoint
+from src.core.transcripts import RunRecord, TranscriptEntry, TranscriptKind
+
+WHEN = datetime(2026, 3, 4, 5, 6, tzinfo=timezone.utc)
+
+
+def _export(**overrides) -> SessionExport:
+    session = Session(
+        project_id="api",
+        channel_id="C1",
+        thread_ts="100.1",
+        active_agent_id="claude",
+        active_agent_type=AgentType.CLAUDE,
+        project_path="/tmp/api",
+        active_model="sonnet",
+    )
+    run = RunRecord(agent_id="claude", success=True, wall_secs=12.4, model="sonnet", exit_code=0, files=["a.py"])
+    fields = {
+        "session": session,
+        "transcript": [
+            TranscriptEntry(TranscriptKind.PROMPT, WHEN, content="fix `a.py`"),
+            TranscriptEntry(TranscriptKind.REPLY, WHEN, content="Fixed it"),
+            TranscriptEntry(TranscriptKind.RUN, WHEN, run=run),
+        ],
+        "pr_refs": [
+            PullRequestRef(
+                project_id="api",
+                session_id=session.id,
+                number=7,
+                url="https://github.com/o/r/pull/7",
+                head_branch="remote-coder/x",
+                base_branch="main",
+            )
+        ],
+        "checkpoints": [Checkpoint(run=1, prompt="fix `a.py`", commit="0123456789abcdef", files=["a.py"])],
+        "diff": "+print('```')\n",
+        "exported_at": WHEN,
+    }
+    fields.update(overrides)
+    return SessionExport(**fields)
+
+
+class TestSessionExport:
+    """Test cases for the Markdown and JSON documents."""
+
+    def test_markdown(self):
+        markdown = export_markdown(_export())
+
+        assert "- #7 (primary, main): https://github.com/o/r/pull/7" in markdown
+        assert "### Prompt, 2026-03-04 05:06 UTC\n\nfix `a.py`" in markdown
+        run_line = "_Run, 2026-03-04 05:06 UTC: `claude` (`sonnet`) succeeded in 12s, exit code 0; edited a.py._"
+        assert run_line in markdown
+        assert "1. `01234567` fix `a.py` (a.py)" in markdown
+        # The diff's own backticks cannot close its code block
+        assert markdown.endswith("````diff\n+print('```')\n````\n")
+        assert "Nothing was recorded." in export_markdown(_export(transcript=[], pr_refs=[], diff=None))
+
+    def test_json(self):
+        data = json.loads(export_json(_export()))
+
+        assert data["version"] == 1 and data["session"]["agent_id"] == "claude"
+        assert data["pull_requests"][0]["number"] == 7
+        assert data["transcript"][0] == {"kind": "prompt", "timestamp": WHEN.isoformat(), "content": "fix `a.py`"}
+        assert data["transcript"][2]["run"]["files"] == ["a.py"]
+        assert data["checkpoints"][0]["commit"] == "0123456789abcdef"
+
+    def test_describe_run(self):
+        cancelled = RunRecord(agent_id="codex", success=False, wall_secs=3, cancelled=True)
+        failed = RunRecord(agent_id="codex", success=False, wall_secs=61, exit_code=1, cost_usd=0.25)
+
+        assert describe_run(cancelled) == "`codex` was cancelled after 3s"
+        assert describe_run(failed) == "`codex` failed after 61s, exit code 1, ~$0.25"
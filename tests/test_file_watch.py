@@ -0,0 +1,88 @@
+"""Tests for announcing changed files while an agent runs."""
+
+from __future__ import annotations
+
+import asyncio
+
+import pytest
+
+from src.core.file_watch import MAX_FILES_PER_NOTICE, FileChange, FileChangeWatcher, format_changes
+from tests.conftest import git
+
+
+@pytest.fixture
+def repo(tmp_path, git_identity):
+    git(tmp_path, "init", "--quiet")
+    (tmp_path / "app.py").write_text("one\ntwo\nthree\n")
+    (tmp_path / "old.py").write_text("gone\nsoon\n")
+    (tmp_path / "notes.md").write_text("draft\n")
+    git(tmp_path, "add", ".")
+    git(tmp_path, "commit", "--quiet", "-m", "init")
+    return tmp_path
+
+
+class TestFileChangeWatcher:
+    """Test cases for file-by-file progress notes."""
+
+    @pytest.mark.asyncio
+    async def test_announces_files_as_they_change(self, repo):
+        notices: list[str] = []
+
+        async def notify(text: str) -> None:
+            notices.append(text)
+
+        (repo / "notes.md").write_text("changed before the run\n")
+        watcher = FileChangeWatcher(repo, notify, interval_secs=3600)
+        async with watcher:
+            assert await watcher.check() == []
+
+            (repo / "app.py").write_text("one\n2\nthree\nfour\n")
+            (repo / "new.py").write_text("a\nb")
+            (repo / "old.py").unlink()
+            (repo / "logo.png").write_bytes(b"\x89PNG\0\0")
+            await watcher.check()
+
+            (repo / "app.py").write_text("one\n")
+            await watcher.check()
+            await watcher.check()
+
+        assert notices == [
+            "Progress: modified `app.py` (+2/-1), added `logo.png` (binary), added `new.py` (+2), "
+            "deleted `old.py` (-2)",
+            "Progress: modified `app.py` (+0/-2)",
+        ]
+
+    @pytest.mark.asyncio
+    async def test_polls_in_the_background(self, repo):
+        notices: list[str] = []
+
+        async def notify(text: str) -> None:
+            notices.append(text)
+
+        async with FileChangeWatcher(repo, notify, interval_secs=0.01):
+            (repo / "app.py").write_text("rewritten\n")
+            for _ in range(100):
+                if notices:
+                    break
+                await asyncio.sleep(0.01)
+
+        assert notices == ["Progress: modified `app.py` (+1/-3)"]
+
+    @pytest.mark.asyncio
+    async def test_stays_quiet_outside_git(self, tmp_path):
+        notices: list[str] = []
+
+        async def notify(text: str) -> None:
+            notices.append(text)
+
+        watcher = FileChangeWatcher(tmp_path, notify, interval_secs=0.01)
+        async with watcher:
+            (tmp_path / "file.txt").write_text("x\n")
+            assert await watcher.check() == []
+
+        assert notices == []
+
+    def test_long_lists_are_shortened(self):
+        changes = [FileChange(f"f{index}.py", 1, 0, "modified") for index in range(MAX_FILES_PER_NOTICE + 3)]
+
+        assert format_changes(changes).endswith("modified `f7.py` (+1/-0), and 3 more")
@@ -0,0 +1,374 @@
+"""Tests for git workflow paths that do not need the GitHub API: local-only commits, patches, unpublish."""
+
+from __future__ import annotations
+
+from unittest.mock import AsyncMock, MagicMock
+
+import pytest
+
+from src.agent_adapters import AgentResult
+from src.agent_adapters.base import FileEdit
+from src.core.conversation import SessionManager
+from src.core.discussions import DESIGN_PHASE_HOLD, DISCUSSION_URL_CONTEXT_KEY, PHASE_CONTEXT_KEY
+from src.core.git_workflow import (
+    APPROVAL_PENDING_CONTEXT_KEY,
+    FORCE_PUSH_CONTEXT_KEY,
+    LOCAL_BASE_CONTEXT_KEY,
+    GitWorkflowService,
+    session_branch_name,
+)
+from src.core.models import AgentType, ApprovalPolicy, GitHubRepoConfig, Project, PullRequestRef
+from src.github import GitHubManager
+from tests.conftest import git
+
+
+@pytest.fixture
+def local_setup(git_repo):
+    session_manager = SessionManager(history_limit=20)
+    project = Project(id="local", channel_name="local", path=git_repo, default_agent_id="claude")
+    session = session_manager.create_session(
+        project=project,
+        channel_id="C1",
+        thread_ts="1.0",
+        agent_id="claude",
+        agent_type=AgentType.CLAUDE,
+    )
+    workflow = GitWorkflowService(github_manager=GitHubManager(None), session_manager=session_manager)
+    return workflow, session, project
+
+
+@pytest.fixture
+def github_setup(git_repo, tmp_path_factory):
+    remote = tmp_path_factory.mktemp("remote")
+    git(remote, "init", "-q", "--bare")
+    git(git_repo, "remote", "add", "origin", str(remote))
+    session_manager = SessionManager(history_limit=20)
+    project = Project(
+        id="gh",
+        channel_name="gh",
+        path=git_repo,
+        default_agent_id="claude",
+        github=GitHubRepoConfig(owner="o", repo="r", default_base_branch="main"),
+    )
+    session = session_manager.create_session(
+        project=project, channel_id="C1", thread_ts="1.0", agent_id="claude", agent_type=AgentType.CLAUDE
+    )
+    github_manager = MagicMock()
+    github_manager.is_configured.return_value = True
+    github_manager.token = None
+    github_manager.close_pull_request = AsyncMock()
+    github_manager.ensure_pull_request = AsyncMock(
+        return_value=PullRequestRef(
+            project_id="gh", session_id=session.id, number=8, url="https://example.test/pr/8",
+            head_branch=session_branch_name(session.id), base_branch="main",
+        )
+    )
+    workflow = GitWorkflowService(github_manager=github_manager, session_manager=session_manager)
+    return workflow, session, project, session_manager, github_manager, remote
+
+
+class TestLocalOnlyWorkflow:
+    """Test cases for committing without a GitHub remote."""
+
+    @pytest.mark.asyncio
+    async def test_commits_to_session_branch(self, git_repo, local_setup):
+        """Changes land on the session branch and the reply explains how to get them."""
+        workflow, session, project = local_setup
+        (git_repo / "README.md").write_text("hello world\n")
+        result = AgentResult(success=True, output_text="done", file_edits=[FileEdit(path="README.md", type="edit")])
+
+        message = await workflow.maybe_publish_code_changes(session, project, result, "Update readme")
+
+        branch = session_branch_name(session.id)
+        assert message is not None
+        assert f"local branch `{branch}`" in message
+        assert "git fetch" in message
+        assert "!patch" in message
+        assert git(git_repo, "rev-parse", "--abbrev-ref", "HEAD").strip() == branch
+        assert git(git_repo, "log", "-1", "--format=%s").strip() == "Update readme"
+        assert session.session_context[LOCAL_BASE_CONTEXT_KEY] == git(git_repo, "rev-parse", "main").strip()
+
+    @pytest.mark.asyncio
+    async def test_session_diff_is_posted_separately(self, git_repo, local_setup):
+        """The commit reply carries only a diffstat; the full diff comes from session_diff."""
+        workflow, session, project = local_setup
+        assert await workflow.session_diff(session, project) is None
+
+        (git_repo / "README.md").write_text("hello world\n")
+        message = await workflow.maybe_publish_code_changes(
+            session, project, AgentResult(success=True, output_text=""), "Update readme"
+        )
+
+        diff = await workflow.session_diff(session, project)
+        assert message is not None and "+hello world" not in message
+        assert diff is not None and "+hello world" in diff
+
+    @pytest.mark.asyncio
+    async def test_build_patch_covers_session_commits(self, git_repo, local_setup):
+        """The exported patch contains only the session's commits."""
+        workflow, session, project = local_setup
+        assert await workflow.build_patch(session, project) is None
+
+        (git_repo / "new.txt").write_text("new file\n")
+        await workflow.maybe_publish_code_changes(session, project, AgentResult(success=True, output_text=""), "Add file")
+
+        patch = await workflow.build_patch(session, project)
+
+        assert patch is not None
+        assert "Subject: [PATCH] Add file" in patch
+        assert "+new file" in patch
+        assert "initial" not in patch
+
+    @pytest.mark.asyncio
+    async def test_apply_patch_to_session_branch(self, git_repo, local_setup):
+        """A pasted diff is applied to the session branch and reported with a diffstat."""
+        workflow, session, project = local_setup
+        patch = "--- a/README.md\n+++ b/README.md\n@@ -1 +1 @@\n-hello\n+hello from chat\n"
+
+        applied, detail = await workflow.apply_patch(session, project, patch)
+
+        assert applied
+        assert "README.md" in detail
+        assert (git_repo / "README.md").read_text() == "hello from chat\n"
+        assert git(git_repo, "rev-parse", "--abbrev-ref", "HEAD").strip() == session_branch_name(session.id)
+
+        applied, detail = await workflow.apply_patch(session, project, patch)
+
+        assert not applied
+        assert "patch" in detail
+
+        await workflow.revert_patch(session, project, patch)
+
+        assert (git_repo / "README.md").read_text() == "hello\n"
+
+    @pytest.mark.asyncio
+    async def test_skips_non_git_directory(self, tmp_path):
+        """Projects that are not git repositories are left untouched."""
+        session_manager = SessionManager(history_limit=20)
+        project = Project(id="plain", channel_name="plain", path=tmp_path, default_agent_id="claude")
+        session = session_manager.create_session(
+            project=project, channel_id="C1", thread_ts="1.0", agent_id="claude", agent_type=AgentType.CLAUDE
+        )
+        (tmp_path / "file.txt").write_text("x")
+        workflow = GitWorkflowService(github_manager=GitHubManager(None), session_manager=session_manager)
+
+        message = await workflow.maybe_publish_code_changes(
+            session, project, AgentResult(success=True, output_text=""), "title"
+        )
+
+        assert message is None
+
+
+class TestUnpublish:
+    """Test cases for unpublishing and republishing a session's PR."""
+
+    @pytest.mark.asyncio
+    async def test_unpublish_closes_pr_and_pauses_publishing(self, git_repo, github_setup):
+        """Unpublish closes the PR, deletes the remote branch, and later changes stay local."""
+        workflow, session, project, session_manager, github_manager, remote = github_setup
+        branch = session_branch_name(session.id)
+        git(git_repo, "checkout", "-q", "-b", branch)
+        (git_repo / "a.txt").write_text("a\n")
+        git(git_repo, "add", "-A")
+        git(git_repo, "commit", "-q", "-m", "work")
+        git(git_repo, "push", "-q", "origin", branch)
+        session_manager.set_pr_ref(github_manager.ensure_pull_request.return_value)
+        github_manager.ensure_pull_request.return_value.number = 7
+
+        message = await workflow.unpublish(session, project)
+
+        github_manager.close_pull_request.assert_awaited_once_with(project, 7)
+        assert "Closed PR #7" in message
+        assert f"Deleted remote branch `{branch}`" in message
+        assert git(remote, "branch", "--list", branch).strip() == ""
+        assert session.publishing_paused
+
+        (git_repo / "b.txt").write_text("b\n")
+        reply = await workflow.maybe_publish_code_changes(
+            session, project, AgentResult(success=True, output_text=""), "More work"
+        )
+        assert reply is not None and "publishing is paused" in reply
+        github_manager.ensure_pull_request.assert_not_awaited()
+
+        github_manager.ensure_pull_request.return_value.number = 8
+        message = await workflow.republish(session, project, "More work")
+
+        assert not session.publishing_paused
+        assert "Linked PR: https://example.test/pr/8" in message
+        assert git(remote, "branch", "--list", branch).strip() == branch
+
+
+class TestLabels:
+    """Test cases for the session's tags as labels on its PRs."""
+
+    @pytest.mark.asyncio
+    async def test_new_prs_get_the_tags_and_open_ones_are_labelled(self, git_repo, github_setup):
+        workflow, session, project, session_manager, github_manager, _ = github_setup
+        session_manager.set_tags(session.id, ["bugfix"])
+        (git_repo / "README.md").write_text("hello world\n")
+
+        await workflow.maybe_publish_code_changes(
+            session, project, AgentResult(success=True, output_text=""), "Tweak readme"
+        )
+
+        assert github_manager.ensure_pull_request.await_args.kwargs["options"].labels == ["bugfix"]
+        github_manager.add_labels = AsyncMock()
+        lines = await workflow.label_pull_requests(session, project, ["urgent"])
+        github_manager.add_labels.assert_awaited_once_with(project, 8, ["urgent"])
+        assert lines == ["Labelled PR #8."]
+
+
+class TestApproval:
+    """Test cases for holding changes until `!publish` and the small-change fast path."""
+
+    @pytest.mark.asyncio
+    async def test_small_change_is_published_with_note(self, git_repo, github_setup):
+        workflow, session, project, _, github_manager, _ = github_setup
+        project.approval = ApprovalPolicy(required=True, protected_paths=[".github/"])
+        (git_repo / "README.md").write_text("hello world\n")
+
+        reply = await workflow.maybe_publish_code_changes(
+            session, project, AgentResult(success=True, output_text=""), "Tweak readme"
+        )
+
+        assert reply is not None and "Linked PR" in reply
+        assert "Small change (1 file(s), 2 line(s), no protected paths)" in reply
+        github_manager.ensure_pull_request.assert_awaited_once()
+        assert not session.publishing_paused
+
+    @pytest.mark.asyncio
+    async def test_protected_path_waits_for_publish(self, git_repo, github_setup):
+        workflow, session, project, _, github_manager, remote = github_setup
+        project.approval = ApprovalPolicy(required=True, protected_paths=[".github/"])
+        (git_repo / ".github").mkdir()
+        (git_repo / ".github" / "ci.yml").write_text("on: push\n")
+
+        reply = await workflow.maybe_publish_code_changes(
+            session, project, AgentResult(success=True, output_text=""), "Add CI"
+        )
+
+        assert reply is not None
+        assert "needs approval because it touches protected paths (`.github/ci.yml`)" in reply
+        github_manager.ensure_pull_request.assert_not_awaited()
+        assert session.publishing_paused and session.session_context[APPROVAL_PENDING_CONTEXT_KEY]
+
+        (git_repo / "README.md").write_text("hello again\n")
+        follow_up = await workflow.maybe_publish_code_changes(
+            session, project, AgentResult(success=True, output_text=""), "Tweak readme"
+        )
+        assert follow_up is not None and "earlier changes are waiting for approval" in follow_up
+
+        message = await workflow.republish(session, project, "Add CI")
+
+        assert "Linked PR" in message
+        assert not session.session_context[APPROVAL_PENDING_CONTEXT_KEY]
+        branch = session_branch_name(session.id)
+        assert git(remote, "log", "--format=%s", branch).split() == ["Tweak", "readme", "Add", "CI", "initial"]
+
+    @pytest.mark.asyncio
+    async def test_large_change_waits_for_publish(self, git_repo, github_setup):
+        workflow, session, project, _, github_manager, _ = github_setup
+        project.approval = ApprovalPolicy(required=True, max_files=1, max_lines=30)
+        (git_repo / "a.txt").write_text("a\n")
+        (git_repo / "b.txt").write_text("b\n")
+
+        reply = await workflow.maybe_publish_code_changes(
+            session, project, AgentResult(success=True, output_text=""), "Two files"
+        )
+
+        assert reply is not None and "over the small-change limit of 1 file(s) and 30 line(s)" in reply
+        github_manager.ensure_pull_request.assert_not_awaited()
+
+
+class TestDiscussionPhases:
+    """Test cases for sessions started from a GitHub Discussion."""
+
+    @pytest.mark.asyncio
+    async def test_design_phase_leaves_changes_uncommitted(self, git_repo, github_setup):
+        workflow, session, project, _, github_manager, _ = github_setup
+        session.session_context[PHASE_CONTEXT_KEY] = "design"
+        (git_repo / "README.md").write_text("sketch\n")
+
+        reply = await workflow.maybe_publish_code_changes(
+            session, project, AgentResult(success=True, output_text=""), "Sketch"
+        )
+
+        assert reply == DESIGN_PHASE_HOLD
+        assert git(git_repo, "status", "--porcelain").strip() == "M README.md"
+        github_manager.ensure_pull_request.assert_not_awaited()
+
+    @pytest.mark.asyncio
+    async def test_pull_request_links_the_discussion(self, git_repo, github_setup):
+        workflow, session, project, _, github_manager, _ = github_setup
+        session.session_context.update(
+            {PHASE_CONTEXT_KEY: "implementation", DISCUSSION_URL_CONTEXT_KEY: "https://github.com/o/r/discussions/7"}
+        )
+        (git_repo / "README.md").write_text("cached\n")
+
+        reply = await workflow.maybe_publish_code_changes(
+            session, project, AgentResult(success=True, output_text=""), "Cache the feed"
+        )
+
+        assert reply is not None and "Linked PR" in reply
+        options = github_manager.ensure_pull_request.await_args.kwargs["options"]
+        assert options.body == "Implements the design discussed in https://github.com/o/r/discussions/7."
+
+
+class TestResetToCheckpoint:
+    """Test cases for resetting a session to the commit before its last run (`!redo`)."""
+
+    @pytest.mark.asyncio
+    async def test_drops_commits_and_leftovers(self, git_repo, local_setup):
+        """Commits since the checkpoint and uncommitted leftovers are thrown away."""
+        workflow, session, project = local_setup
+        checkpoint = await workflow.current_commit(git_repo)
+        (git_repo / "README.md").write_text("bad attempt\n")
+        await workflow.maybe_publish_code_changes(session, project, AgentResult(success=True, output_text=""), "Bad")
+        (git_repo / "stray.txt").write_text("left behind\n")
+
+        reset, message = await workflow.reset_to_checkpoint(session, project, checkpoint)
+
+        assert reset
+        assert "dropping 1 commit(s)" in message
+        branch = session_branch_name(session.id)
+        assert git(git_repo, "rev-parse", branch).strip() == checkpoint
+        assert (git_repo / "README.md").read_text() == "hello\n"
+        assert not (git_repo / "stray.txt").exists()
+
+    @pytest.mark.asyncio
+    async def test_refuses_unrelated_checkpoint(self, git_repo, local_setup):
+        """A checkpoint the session branch does not contain is not reset to."""
+        workflow, session, project = local_setup
+        (git_repo / "README.md").write_text("work\n")
+        await workflow.maybe_publish_code_changes(session, project, AgentResult(success=True, output_text=""), "Work")
+        git(git_repo, "checkout", "-q", "--orphan", "other")
+        git(git_repo, "commit", "-q", "--allow-empty", "-m", "unrelated")
+        unrelated = git(git_repo, "rev-parse", "HEAD").strip()
+
+        reset, message = await workflow.reset_to_checkpoint(session, project, unrelated)
+
+        assert not reset
+        assert "no longer contains the checkpoint" in message
+
+    @pytest.mark.asyncio
+    async def test_published_branch_is_force_pushed_next(self, git_repo, github_setup):
+        """After resetting a pushed branch, the next publish overwrites the remote branch."""
+        workflow, session, project, session_manager, github_manager, remote = github_setup
+        branch = session_branch_name(session.id)
+        git(git_repo, "checkout", "-q", "-b", branch)
+        checkpoint = await workflow.current_commit(git_repo)
+        (git_repo / "a.txt").write_text("bad\n")
+        await workflow.maybe_publish_code_changes(session, project, AgentResult(success=True, output_text=""), "Bad")
+
+        reset, message = await workflow.reset_to_checkpoint(session, project, checkpoint)
+
+        assert reset and "updated on the next push" in message
+        assert session.session_context[FORCE_PUSH_CONTEXT_KEY]
+        (git_repo / "a.txt").write_text("good\n")
+        reply = await workflow.maybe_publish_code_changes(
+            session, project, AgentResult(success=True, output_text=""), "Good"
+        )
+
+        assert reply is not None and "Linked PR" in reply
+        assert git(remote, "log", "--format=%s", branch).split() == ["Good", "initial"]
+        assert not session.session_context[FORCE_PUSH_CONTEXT_KEY]
@@ -0,0 +1,23 @@
+"""Tests for the GitHub client wrapper."""
+
+from __future__ import annotations
+
+from src.github import GitHubManager
+from src.github import client
+
+
+class TestGitHubManager:
+    """Test cases for GitHubManager."""
+
+    def test_configured_with_token(self):
+        assert GitHubManager("token").is_configured()
+        assert not GitHubManager(None).is_configured()
+
+    def test_without_pygithub_falls_back_to_local_only(self, monkeypatch):
+        """Without the `github` extra a token does not configure the manager."""
+        monkeypatch.setattr(client, "Github", None)
+
+        manager = GitHubManager("token")
+
+        assert not manager.is_configured()
+        assert manager.token == "token"
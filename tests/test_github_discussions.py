@@ -0,0 +1,199 @@
+"""Tests for GitHub Discussions sessions: the adapter and the `discussions` project setting."""
+
+from __future__ import annotations
+
+from datetime import datetime, timezone
+from typing import Any, Dict, List
+
+import pytest
+
+from src.chat_adapters.github_discussions import ADD_COMMENT_MUTATION, DISCUSSION_ID_QUERY, GitHubDiscussionsAdapter
+from src.core.config import Config, _parse_discussions
+from src.core.errors import ConfigError
+from src.core.models import DiscussionsConfig, GitHubRepoConfig, Project
+
+STARTED = datetime(2026, 10, 14, 12, 0, tzinfo=timezone.utc)
+REPO = GitHubRepoConfig(owner="acme", repo="app", default_base_branch="main")
+
+
+class FakeGitHub:
+    """Answers the discussions query with `discussions` and records every request."""
+
+    def __init__(self, discussions: List[Dict[str, Any]]) -> None:
+        self.discussions = discussions
+        self.calls: List[tuple] = []
+
+    async def graphql(self, query: str, variables: Dict[str, Any]) -> Dict[str, Any]:
+        self.calls.append((query, variables))
+        if query == ADD_COMMENT_MUTATION:
+            return {"addDiscussionComment": {"comment": {"id": f"bot-{len(self.calls)}"}}}
+        if query == DISCUSSION_ID_QUERY:
+            return {"repository": {"discussion": {"id": f"D_{variables['number']}"}}}
+        return {"repository": {"discussions": {"nodes": self.discussions}}}
+
+
+class FakeRouter:
+    def __init__(self) -> None:
+        self.events: List[Dict[str, Any]] = []
+
+    async def handle_message(self, event: Dict[str, Any]) -> None:
+        self.events.append(event)
+
+
+def _post(node_id: str, login: str, created: str, body: str, **extra: Any) -> Dict[str, Any]:
+    return {"id": node_id, "author": {"login": login}, "createdAt": created, "body": body, **extra}
+
+
+def _discussion(number: int, created: str, comments=(), *, login="ana", category="Ideas") -> Dict[str, Any]:
+    return _post(
+        f"D_{number}",
+        login,
+        created,
+        "Should we cache the feed?",
+        number=number,
+        title="Feed caching",
+        url=f"https://github.com/acme/app/discussions/{number}",
+        category={"name": category},
+        comments={"nodes": list(comments)},
+    )
+
+
+def _adapter(tmp_path, github: FakeGitHub, router: FakeRouter, category="ideas") -> GitHubDiscussionsAdapter:
+    project = Project(
+        id="app",
+        channel_name="app",
+        path=tmp_path,
+        default_agent_id="claude",
+        github=REPO,
+        discussions=DiscussionsConfig(category=category),
+    )
+    config = Config(
+        projects={"app": project},
+        agents={},
+        slack_bot_token="x",
+        slack_app_token="y",
+        slack_allowed_user_ids=[],
+        base_dir=tmp_path,
+        config_dir=tmp_path / "config",
+        github_allowed_users=["ana"],
+    )
+    return GitHubDiscussionsAdapter(github, router, config, started_at=STARTED)  # type: ignore[arg-type]
+
+
+class TestDiscussionsPolling:
+    """Test cases for turning discussion posts into router messages."""
+
+    @pytest.mark.asyncio
+    async def test_new_posts_become_messages_in_order(self, tmp_path):
+        reply = _post("R_1", "ana", "2026-10-14T12:07:00Z", "and invalidate on write")
+        comments = [
+            _post("C_1", "ana", "2026-10-14T12:05:00Z", "What about Redis?", replies={"nodes": [reply]}),
+            _post("C_2", "mallory", "2026-10-14T12:06:00Z", "!purge"),
+        ]
+        github = FakeGitHub([_discussion(7, "2026-10-14T12:01:00Z", comments)])
+        router = FakeRouter()
+        adapter = _adapter(tmp_path, github, router)
+
+        await adapter.poll()
+        await adapter.wait_idle()
+
+        assert [(event["ts"], event.get("thread_ts"), event["text"]) for event in router.events] == [
+            ("7", None, "Feed caching\n\nShould we cache the feed?"),
+            ("C_1", "7", "What about Redis?"),
+            ("R_1", "7", "and invalidate on write"),
+        ]
+        assert router.events[0]["channel"] == "github:acme/app"
+        assert router.events[0]["channel_name"] == "app"
+        assert router.events[0]["discussion_url"] == "https://github.com/acme/app/discussions/7"
+
+        await adapter.poll()
+        await adapter.wait_idle()
+
+        assert len(router.events) == 3
+
+    @pytest.mark.asyncio
+    async def test_ignores_posts_before_start_and_other_categories(self, tmp_path):
+        old = _discussion(1, "2026-10-13T09:00:00Z", [_post("C_9", "ana", "2026-10-14T12:02:00Z", "still there?")])
+        elsewhere = _discussion(2, "2026-10-14T12:01:00Z", category="Q&A")
+        github = FakeGitHub([old, elsewhere])
+        router = FakeRouter()
+        adapter = _adapter(tmp_path, github, router)
+
+        await adapter.poll()
+        await adapter.wait_idle()
+
+        # The old discussion's new comment still reaches it; the router starts the session from there
+        assert [(event["ts"], event.get("thread_ts")) for event in router.events] == [("C_9", "1")]
+
+    @pytest.mark.asyncio
+    async def test_ignores_comments_on_discussions_of_other_users(self, tmp_path):
+        comment = _post("C_1", "ana", "2026-10-14T12:05:00Z", "Sounds good, go ahead")
+        github = FakeGitHub([_discussion(7, "2026-10-14T12:01:00Z", [comment], login="mallory")])
+        router = FakeRouter()
+        adapter = _adapter(tmp_path, github, router)
+
+        await adapter.poll()
+        await adapter.wait_idle()
+
+        assert router.events == []
+
+
+class TestDiscussionsReplies:
+    """Test cases for posting and editing the bot's comments."""
+
+    @pytest.mark.asyncio
+    async def test_replies_are_comments_that_are_not_read_back(self, tmp_path):
+        github = FakeGitHub([_discussion(7, "2026-10-14T12:01:00Z")])
+        router = FakeRouter()
+        adapter = _adapter(tmp_path, github, router)
+        await adapter.poll()
+        await adapter.wait_idle()
+
+        comment_id = await adapter.send_message("github:acme/app", "7", "Caching sounds right.")
+        github.discussions[0]["comments"] = {
+            "nodes": [_post(comment_id, "ana", "2026-10-14T12:09:00Z", "Caching sounds right.")]
+        }
+        await adapter.poll()
+        await adapter.wait_idle()
+
+        assert github.calls[1] == (ADD_COMMENT_MUTATION, {"discussion": "D_7", "body": "Caching sounds right."})
+        assert len(router.events) == 1
+
+    @pytest.mark.asyncio
+    async def test_looks_up_discussions_it_has_not_polled(self, tmp_path):
+        github = FakeGitHub([])
+        adapter = _adapter(tmp_path, github, FakeRouter())
+
+        await adapter.send_message("github:acme/app", "12", "Back after a restart")
+
+        assert github.calls[0] == (DISCUSSION_ID_QUERY, {"owner": "acme", "repo": "app", "number": 12})
+        assert github.calls[1][1]["discussion"] == "D_12"
+        assert await adapter.send_message("C123", "1.0", "not a discussion") is None
+
+    def test_thread_links_are_the_discussion_pages(self, tmp_path):
+        adapter = _adapter(tmp_path, FakeGitHub([]), FakeRouter())
+
+        assert adapter.thread_link("github:acme/app", "7") == "https://github.com/acme/app/discussions/7"
+        assert adapter.thread_link("C123", "1.0") is None
+
+
+class TestDiscussionsConfig:
+    """Test cases for the `discussions` project setting."""
+
+    def test_parses_true_and_a_category(self):
+        assert _parse_discussions("app", True, REPO) == DiscussionsConfig()
+        assert _parse_discussions("app", {"category": " Ideas "}, REPO) == DiscussionsConfig(category="Ideas")
+        assert _parse_discussions("app", None, REPO) is None
+
+    @pytest.mark.parametrize(
+        "raw, github, message",
+        [
+            ("Ideas", REPO, "discussions must be true or a mapping"),
+            ({"category": ""}, REPO, "discussions category must be a category name"),
+            ({"labels": ["design"]}, REPO, "discussions has unsupported keys labels"),
+            (True, None, "discussions needs the project's github section"),
+        ],
+    )
+    def test_rejects_invalid_values(self, raw, github, message):
+        with pytest.raises(ConfigError, match=message):
+            _parse_discussions("app", raw, github)
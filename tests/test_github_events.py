@@ -0,0 +1,81 @@
+"""Tests for GitHub events about session PRs."""
+
+from __future__ import annotations
+
+import hashlib
+import hmac
+import json
+
+import pytest
+
+from src.core.github_events import PullRequestEvent, parse_github_event, verify_signature
+from src.core.webhooks import WebhookError
+
+REPO = {"full_name": "owner/repo"}
+
+
+def _body(**payload) -> bytes:
+    return json.dumps({"repository": REPO, **payload}).encode()
+
+
+class TestGitHubEvents:
+    """Test cases for reading GitHub webhook deliveries."""
+
+    def test_verifies_the_signature(self):
+        body = b'{"zen": "Keep it logically awesome."}'
+        signature = "sha256=" + hmac.new(b"s3cret", body, hashlib.sha256).hexdigest()
+
+        verify_signature("s3cret", body, signature)
+        for wrong in (None, "sha256=00", signature.replace("sha256=", "sha1=")):
+            with pytest.raises(WebhookError, match="X-Hub-Signature-256"):
+                verify_signature("s3cret", body, wrong)
+
+    def test_reads_review_comments_reviews_and_ci(self):
+        comment = _body(
+            action="created",
+            pull_request={"number": 12},
+            comment={"user": {"login": "ana"}, "path": "app.py", "line": 4, "body": "Off by one?", "html_url": "u"},
+        )
+        review = _body(
+            action="submitted", pull_request={"number": 12}, review={"user": {"login": "bo"}, "state": "APPROVED"}
+        )
+        ci = _body(
+            action="completed",
+            check_suite={
+                "conclusion": "failure",
+                "head_sha": "abcdef123",
+                "app": {"name": "GitHub Actions"},
+                "pull_requests": [{"number": 12}, {"number": 13}],
+            },
+        )
+
+        assert parse_github_event("pull_request_review_comment", comment) == [
+            PullRequestEvent("owner/repo", 12, "Review comment from @ana on `app.py` line 4:\n> Off by one?\nu")
+        ]
+        assert parse_github_event("pull_request_review", review)[0].text == "@bo approved the PR."
+        assert [(event.number, event.text) for event in parse_github_event("check_suite", ci)] == [
+            (12, "GitHub Actions finished on `abcdef1`: failure."),
+            (13, "GitHub Actions finished on `abcdef1`: failure."),
+        ]
+
+    def test_reads_merged_and_closed_prs(self):
+        merged = _body(action="closed", pull_request={"number": 12, "merged": True, "merged_by": {"login": "ana"}})
+        closed = _body(action="closed", pull_request={"number": 13, "merged": False}, sender={"login": "bo"})
+
+        assert parse_github_event("pull_request", merged) == [
+            PullRequestEvent("owner/repo", 12, "Merged by @ana.", merged=True)
+        ]
+        assert parse_github_event("pull_request", closed) == [
+            PullRequestEvent("owner/repo", 13, "Closed without merging by @bo.", merged=False)
+        ]
+        assert parse_github_event("pull_request", _body(action="opened", pull_request={"number": 14})) == []
+
+    def test_ignores_other_events(self):
+        issue = _body(action="created", issue={"number": 3}, comment={"body": "hi"})
+        empty_review = _body(action="submitted", pull_request={"number": 1}, review={"state": "commented"})
+
+        assert parse_github_event("issue_comment", issue) == []
+        assert parse_github_event("pull_request_review", empty_review) == []
+        assert parse_github_event("ping", _body(zen="Design for failure.")) == []
+        with pytest.raises(WebhookError, match="not valid JSON"):
+            parse_github_event("ping", b"{")
@@ -0,0 +1,82 @@
+"""Tests for the "still working" line of quiet runs."""
+
+from __future__ import annotations
+
+import asyncio
+from typing import List, Optional, Tuple
+
+import pytest
+
+from src.core.heartbeat import MAX_POSTS, Heartbeat
+
+
+class FakeChat:
+    def __init__(self, *, editable: bool = True) -> None:
+        self.posted: List[Tuple[str, Optional[str], str]] = []
+        self.edits: List[Tuple[str, str, str]] = []
+        self.editable = editable
+
+    async def send_message(self, channel: str, thread_ts: Optional[str], text: str) -> str:
+        self.posted.append((channel, thread_ts, text))
+        return f"{thread_ts}.{len(self.posted)}"
+
+    async def update_message(self, channel: str, ts: str, text: str) -> bool:
+        if not self.editable:
+            return False
+        self.edits.append((channel, ts, text))
+        return True
+
+
+def _heartbeat(chat: FakeChat, interval_secs: float = 0.05) -> Heartbeat:
+    return Heartbeat(
+        "C1",
+        "t1",
+        label="claude",
+        send_message=chat.send_message,
+        update_message=chat.update_message,
+        interval_secs=interval_secs,
+    )
+
+
+class TestHeartbeat:
+    """Test cases for posting, refreshing, and finishing the status line."""
+
+    @pytest.mark.asyncio
+    async def test_a_quiet_run_gets_one_line_that_is_refreshed_and_finished(self):
+        chat = FakeChat()
+
+        async with _heartbeat(chat):
+            await asyncio.sleep(0.18)
+
+        assert len(chat.posted) == 1
+        channel, thread_ts, text = chat.posted[0]
+        assert (channel, thread_ts) == ("C1", "t1")
+        assert text == "`claude` is still working, 0s elapsed…"
+        assert len(chat.edits) >= 2
+        assert all(ts == "t1.1" for _, ts, _ in chat.edits)
+        assert chat.edits[-1][2] == "`claude` finished after 0s."
+
+    @pytest.mark.asyncio
+    async def test_output_keeps_the_line_from_being_posted(self):
+        chat = FakeChat()
+
+        async with _heartbeat(chat) as heartbeat:
+            for _ in range(8):
+                heartbeat.touch()
+                await asyncio.sleep(0.02)
+
+        async with _heartbeat(chat, interval_secs=10):
+            pass
+
+        assert chat.posted == [] and chat.edits == []
+
+    @pytest.mark.asyncio
+    async def test_chats_without_edits_get_a_few_separate_lines(self):
+        chat = FakeChat(editable=False)
+
+        async with _heartbeat(chat, interval_secs=0.01):
+            await asyncio.sleep(0.3)
+
+        assert len(chat.posted) == MAX_POSTS
+        assert all("is still working" in text for _, _, text in chat.posted)
+        assert chat.edits == []
@@ -0,0 +1,103 @@
+"""Tests for the HTTP agent adapter."""
+
+from __future__ import annotations
+
+import subprocess
+from unittest.mock import AsyncMock, patch
+
+import pytest
+
+from src.agent_adapters.http_adapter import HttpAgentAdapter, file_edits_from_patch
+from src.core.config import _load_agents
+from src.core.errors import ConfigError
+from src.core.models import Agent, AgentType, WorkingDirMode
+
+PATCH = """--- a/README.md
++++ b/README.md
+@@ -1 +1 @@
+-hello
++hello from service
+--- /dev/null
++++ b/NEW.md
+@@ -0,0 +1 @@
++new
+"""
+
+
+@pytest.fixture
+def agent():
+    return Agent(
+        id="my-agent",
+        type=AgentType.HTTP,
+        command=[],
+        working_dir_mode=WorkingDirMode.PROJECT,
+        endpoint="https://agents.example.test/run",
+        models={"default": "default"},
+    )
+
+
+@pytest.fixture
+def repo(tmp_path):
+    subprocess.run(["git", "init", "-q"], cwd=tmp_path, check=True)
+    (tmp_path / "README.md").write_text("hello\n")
+    return tmp_path
+
+
+class TestHttpAgentAdapter:
+    """Test cases for HttpAgentAdapter."""
+
+    def test_file_edits_from_patch(self):
+        """File headers map to edit/create/delete entries."""
+        edits = file_edits_from_patch(PATCH + "--- a/old.txt\n+++ /dev/null\n")
+        assert [(e.path, e.type) for e in edits] == [
+            ("README.md", "edit"),
+            ("NEW.md", "create"),
+            ("old.txt", "delete"),
+        ]
+
+    @pytest.mark.asyncio
+    async def test_applies_returned_patch(self, agent, repo):
+        """The prompt is posted and the returned patch applied to the project."""
+        response = {"summary": "Updated readme", "patch": PATCH, "pr_title": "Update readme", "pr_summary": ["a"]}
+        with patch("src.agent_adapters.http_adapter.post_json", new_callable=AsyncMock) as mock_post:
+            mock_post.return_value = response
+            result = await HttpAgentAdapter(agent).run(
+                task_text="update the readme",
+                project_path=str(repo),
+                session_id="s1",
+                conversation_history=[{"role": "user", "content": "hi"}],
+            )
+
+        _, url, payload, _ = mock_post.await_args.args
+        assert url == "https://agents.example.test/run"
+        assert payload["prompt"] == "update the readme"
+        assert payload["conversation_history"] == [{"role": "user", "content": "hi"}]
+        assert result.success
+        assert result.output_text == "Updated readme"
+        assert (repo / "README.md").read_text() == "hello from service\n"
+        assert (repo / "NEW.md").read_text() == "new\n"
+        assert result.structured_output is not None
+        assert result.structured_output.pr_title == "Update readme"
+
+    @pytest.mark.asyncio
+    async def test_patch_that_does_not_apply(self, agent, repo):
+        """A bad patch fails the run without touching files."""
+        bad = PATCH.replace("-hello\n", "-goodbye\n")
+        with patch("src.agent_adapters.http_adapter.post_json", new_callable=AsyncMock) as mock_post:
+            mock_post.return_value = {"summary": "oops", "patch": bad}
+            result = await HttpAgentAdapter(agent).run(
+                task_text="x", project_path=str(repo), session_id="s1", conversation_history=[]
+            )
+
+        assert not result.success
+        assert "Failed to apply patch" in result.errors[0]
+        assert (repo / "README.md").read_text() == "hello\n"
+        assert result.file_edits == []
+
+    def test_config_requires_endpoint(self, tmp_path):
+        """HTTP agents without an endpoint are rejected."""
+        agents_yaml = tmp_path / "agents.yaml"
+        agents_yaml.write_text("agents:\n  svc:\n    type: http\n")
+
+        with pytest.raises(ConfigError, match="endpoint"):
+            _load_agents(agents_yaml)
@@ -0,0 +1,56 @@
+"""Tests for ending sessions that have been idle for too long."""
+
+from __future__ import annotations
+
+from datetime import datetime, timedelta, timezone
+
+from src.core.conversation import SessionManager
+from src.core.models import AgentType, Project, SessionStatus
+from src.core.state import session_from_dict, session_to_dict
+
+
+def _start(manager: SessionManager, tmp_path, thread_ts: str):
+    project = Project(id="api", channel_name="api", path=tmp_path, default_agent_id="claude")
+    return manager.create_session(
+        project=project, channel_id="C1", thread_ts=thread_ts, agent_id="claude", agent_type=AgentType.CLAUDE
+    )
+
+
+class TestExpireIdle:
+    """Test cases for which sessions count as idle."""
+
+    def test_sessions_without_activity_are_ended(self, tmp_path):
+        manager = SessionManager()
+        idle = _start(manager, tmp_path, "1.1")
+        talking = _start(manager, tmp_path, "1.2")
+        later = datetime.now(timezone.utc) + timedelta(hours=5)
+        talking.last_activity_at = later - timedelta(hours=1)
+
+        expired = manager.expire_idle(timedelta(hours=4), now=later)
+
+        assert expired == [idle]
+        assert idle.status == SessionStatus.ENDED and talking.status == SessionStatus.ACTIVE
+        assert manager.expire_idle(timedelta(hours=4), now=later) == []
+
+    def test_bookkeeping_is_not_activity_but_messages_are(self, tmp_path):
+        manager = SessionManager()
+        session = _start(manager, tmp_path, "1.1")
+        session.last_activity_at -= timedelta(hours=5)
+        manager.set_timezone(session.id, "Europe/Berlin")
+        manager.record_run_cost(session.id, 0.5)
+
+        assert manager.expire_idle(timedelta(hours=4), busy={str(session.id)}) == []  # A run is in progress
+        manager.append_user_message(session.id, "still here")
+        assert manager.expire_idle(timedelta(hours=4)) == []
+        session.last_activity_at -= timedelta(hours=5)
+        manager.record_activity(session.id)
+        assert manager.expire_idle(timedelta(hours=4)) == []
+
+    def test_last_activity_survives_a_restart(self, tmp_path):
+        session = _start(SessionManager(), tmp_path, "1.1")
+        session.last_activity_at -= timedelta(hours=3)
+        data = session_to_dict(session)
+
+        assert session_from_dict(data).last_activity_at == session.last_activity_at
+        del data["last_activity_at"]  # Saved before activity was tracked
+        assert session_from_dict(data).last_activity_at == session.updated_at
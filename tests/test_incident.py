@@ -0,0 +1,95 @@
+"""Tests for incident mode's windows, settings, and audit log."""
+
+from __future__ import annotations
+
+import json
+from datetime import datetime, timedelta, timezone
+from pathlib import Path
+
+from src.core.approval import DiffSize, approval_reason
+from src.core.conversation.session_manager import SessionManager
+from src.core.incident import IncidentAudit, cheapest_agent, incident_project, open_window
+from src.core.models import (
+    Agent,
+    AgentCapabilities,
+    AgentPricing,
+    AgentType,
+    CostTier,
+    PipelineStep,
+    Project,
+    WorkingDirMode,
+)
+from src.core.state import StateStore
+
+
+def _agent(agent_id: str, cost_tier: CostTier = CostTier.MEDIUM, per_run_usd: float = 0.0) -> Agent:
+    return Agent(
+        id=agent_id,
+        type=AgentType.CLAUDE,
+        command=["echo"],
+        working_dir_mode=WorkingDirMode.PROJECT,
+        pricing=AgentPricing(per_run_usd=per_run_usd),
+        capabilities=AgentCapabilities(cost_tier=cost_tier),
+    )
+
+
+def _project(**kwargs) -> Project:
+    return Project(id="api", channel_name="api", path=Path("/tmp/api"), default_agent_id="claude", **kwargs)
+
+
+class TestIncident:
+    """Test cases for what incident mode changes."""
+
+    def test_every_run_is_approved_and_every_change_held(self):
+        project = _project(pipeline=[PipelineStep("plan"), PipelineStep("edit"), PipelineStep("pr")])
+
+        during = incident_project(project)
+
+        assert [step.name for step in during.pipeline] == ["plan", "approve", "edit", "pr"]
+        assert [step.name for step in incident_project(during).pipeline] == ["plan", "approve", "edit", "pr"]
+        assert [step.name for step in incident_project(_project()).pipeline] == ["approve", "edit", "pr", "diff-review"]
+        assert approval_reason(during.approval, DiffSize(files=["README.md"], lines=1)) == (
+            "this project requires approval for every change"
+        )
+        assert project.pipeline[1].name == "edit" and not project.approval.required
+
+    def test_cheapest_agent_goes_by_tier_then_price_then_order(self):
+        agents = [
+            _agent("claude", per_run_usd=0.10),
+            _agent("codex", per_run_usd=0.05),
+            _agent("gemini", per_run_usd=0.05),
+            _agent("local", cost_tier=CostTier.LOW, per_run_usd=1.0),
+        ]
+
+        assert cheapest_agent(agents, _project()).id == "local"
+        assert cheapest_agent(agents, _project(allowed_agents=["claude", "codex", "gemini"])).id == "codex"
+        assert cheapest_agent(agents, _project(allowed_agents=["other"])) is None
+
+    def test_windows_expire_and_survive_a_restart(self, tmp_path):
+        now = datetime(2026, 3, 2, 9, 0, tzinfo=timezone.utc)
+        manager = SessionManager()
+        manager.start_incident(open_window("api", 30, "U1", now=datetime.now(timezone.utc)))
+        manager.start_incident(open_window("web", 30, now=now))
+
+        assert manager.incident_for("web", now + timedelta(minutes=29)) is not None
+        assert manager.incident_for("web", now + timedelta(minutes=30)) is None
+        assert [window.project_id for window in manager.active_incidents()] == ["api"]
+
+        store = StateStore(tmp_path / "state.json")
+        store.save(manager)
+        restored = SessionManager()
+        store.load(restored)
+        assert restored.incident_for("api") == manager.incident_for("api")
+        assert restored.end_incident("api") is not None
+        assert restored.incident_for("api") is None
+
+    def test_audit_appends_json_lines(self, tmp_path):
+        audit = IncidentAudit(tmp_path / "audit" / "api.jsonl")
+
+        audit.record("run started", agent="claude")
+        audit.record("step", step="edit", passed=True)
+
+        entries = [json.loads(line) for line in audit.path.read_text().splitlines()]
+        assert [entry["event"] for entry in entries] == ["run started", "step"]
+        assert entries[1]["passed"] is True and "at" in entries[0]
+        assert audit.path.stat().st_mode & 0o777 == 0o600
@@ -0,0 +1,71 @@
+"""Tests for the runner of the daemon's own subprocesses."""
+
+from __future__ import annotations
+
+import asyncio
+import subprocess
+import time
+
+import pytest
+
+from src.core.jobs import TIMEOUT_EXIT_CODE, JobRunner, format_job_stats, run_git
+
+
+class TestJobRunner:
+    """Test cases for limits, timeouts, cancellation, and metrics of jobs."""
+
+    @pytest.mark.asyncio
+    async def test_jobs_wait_for_a_free_slot(self, tmp_path):
+        runner = JobRunner(limit=2)
+        script = 'echo start >> log; sleep 0.3; echo end >> log'
+
+        results = await asyncio.gather(*(runner.run("verify", script, cwd=tmp_path) for _ in range(3)))
+
+        assert all(result.passed for result in results)
+        running = [0]
+        for line in (tmp_path / "log").read_text().split():
+            running.append(running[-1] + (1 if line == "start" else -1))
+        assert max(running) == 2 and running[-1] == 0
+        assert runner.describe() == "0 of 2 job slots in use, 0 waiting"
+
+    @pytest.mark.asyncio
+    async def test_timeout_and_cancellation_kill_the_process(self, tmp_path):
+        runner = JobRunner(default_timeout_secs=0.3)
+        started = time.monotonic()
+
+        timed_out = await runner.run("setup", "sleep 30 & echo started; sleep 30", cwd=tmp_path)
+
+        assert timed_out.timed_out and timed_out.returncode is None
+        assert "started" in timed_out.stdout
+        assert time.monotonic() - started < 5
+
+        task = asyncio.create_task(runner.run("verify", "touch began; sleep 30; touch finished", cwd=tmp_path))
+        await asyncio.sleep(0.3)
+        task.cancel()
+        with pytest.raises(asyncio.CancelledError):
+            await task
+        await asyncio.sleep(0.2)
+        assert (tmp_path / "began").exists() and not (tmp_path / "finished").exists()
+        assert runner.describe() == "0 of 8 job slots in use, 0 waiting"
+
+        failed = await runner.run("verify", ["sh", "-c", "echo oops >&2; exit 3"], cwd=tmp_path)
+        assert failed.returncode == 3 and failed.stderr == "oops\n" and failed.stdout == ""
+        stats = format_job_stats(runner)
+        assert stats.startswith("Jobs: 0 of 8 job slots in use, 0 waiting\n")
+        assert "  setup: 1 run(s), " in stats and "(1 timed out)\n" in stats
+        assert "  verify: 2 run(s), " in stats and stats.endswith("(1 failed, 1 cancelled)")
+
+    @pytest.mark.asyncio
+    async def test_git_returns_a_completed_process(self, tmp_path):
+        await run_git(tmp_path, "init", "--quiet")
+
+        hashed = await run_git(tmp_path, "hash-object", "--stdin", stdin="hello\n")
+        assert hashed.returncode == 0
+        assert hashed.stdout.strip() == "ce013625030ba8dba906f756967f9e9ca394464a"
+
+        with pytest.raises(subprocess.CalledProcessError) as error:
+            await run_git(tmp_path, "rev-parse", "--verify", "--quiet", "missing")
+        assert error.value.returncode == 1
+
+        slow = await run_git(tmp_path, "-c", "alias.wait=!sleep 30", "wait", check=False, timeout_secs=0.3)
+        assert slow.returncode == TIMEOUT_EXIT_CODE and "timed out" in slow.stderr
@@ -0,0 +1,100 @@
+"""Tests for the embeddable remote_coder_core API."""
+
+from __future__ import annotations
+
+import os
+import subprocess
+import sys
+
+import pytest
+
+import remote_coder_core
+from remote_coder_core import ConfigError, GitHubManager, IChatAdapter, Router, SessionManager, load_config
+
+
+class RecordingAdapter(IChatAdapter):
+    """Chat adapter that keeps the router's replies in memory."""
+
+    def __init__(self) -> None:
+        self.texts: list[str] = []
+
+    async def send_message(self, channel, thread_ts, text):
+        self.texts.append(text)
+        return None
+
+    async def start(self) -> None:
+        pass
+
+    async def stop(self) -> None:
+        pass
+
+
+@pytest.fixture
+def config_dir(tmp_path, monkeypatch):
+    """A config directory for one local project driven by a mock agent, without Slack settings."""
+    for key in ("SLACK_BOT_TOKEN", "SLACK_APP_TOKEN", "SLACK_ALLOWED_USER_IDS", "SLACK_ALLOWED_USER_ID"):
+        monkeypatch.delenv(key, raising=False)
+    monkeypatch.delenv("REMOTE_CODER_AGENTS", raising=False)
+    config_dir = tmp_path / "config"
+    config_dir.mkdir()
+    (tmp_path / "projects" / "demo").mkdir(parents=True)
+    (config_dir / ".env").write_text("")
+    (config_dir / "agents.yaml").write_text(
+        "agents:\n  demo:\n    type: mock\n    options:\n      delay_secs: 0\n      summary: Done\n"
+    )
+    (config_dir / "projects.yaml").write_text(
+        f"base_dir: {tmp_path / 'projects'}\nprojects:\n  demo:\n    path: demo\n    default_agent: demo\n"
+    )
+    return config_dir
+
+
+class TestLibraryApi:
+    """Test cases for embedding the engine through remote_coder_core."""
+
+    def test_exported_names_resolve(self):
+        """Every name in __all__ is importable from the package."""
+        for name in remote_coder_core.__all__:
+            assert getattr(remote_coder_core, name) is not None
+
+    def test_import_does_not_load_slack(self):
+        """Embedding never imports the Slack adapter or slack_sdk."""
+        code = (
+            "import sys, remote_coder_core; "
+            "print(any(m == 'slack_sdk' or m.endswith('slack_adapter') for m in sys.modules))"
+        )
+        env = {**os.environ, "PYTHONPATH": os.pathsep.join(filter(None, sys.path))}
+        result = subprocess.run([sys.executable, "-c", code], capture_output=True, text=True, env=env, check=True)
+
+        assert result.stdout.strip() == "False"
+
+    def test_slack_settings_are_optional(self, config_dir):
+        """require_slack=False loads a config without Slack tokens; the daemon default still needs them."""
+        config = load_config(config_dir, require_slack=False)
+        assert config.slack_bot_token == ""
+        assert config.slack_allowed_user_ids == []
+
+        with pytest.raises(ConfigError, match="SLACK_BOT_TOKEN"):
+            load_config(config_dir)
+
+    @pytest.mark.asyncio
+    async def test_router_runs_with_custom_adapter(self, config_dir):
+        """A router bound to a custom chat adapter runs the agent and replies through it."""
+        config = load_config(config_dir, require_slack=False)
+        router = Router(
+            SessionManager(),
+            config,
+            GitHubManager(None),
+            config.config_dir,
+            config_loader=lambda: load_config(config_dir, require_slack=False),
+        )
+        adapter = RecordingAdapter()
+        router.bind_adapter(adapter)
+
+        await router.handle_message({"channel": "C1", "channel_name": "demo", "text": "hi", "ts": "1"})
+        await router.handle_message({"channel": "C1", "channel_name": "demo", "text": "add a file", "thread_ts": "1"})
+        await router.handle_message({"channel": "C1", "channel_name": "demo", "text": "!reload-projects", "ts": "2"})
+
+        assert adapter.texts[0].startswith("Starting session for `demo` with `demo`")
+        assert "Message received — running `demo` now." in adapter.texts
+        assert any(text.startswith("Done") for text in adapter.texts)
+        assert "Failed to reload config" not in adapter.texts[-1]
@@ -0,0 +1,111 @@
+"""Tests for resource limits on agent CLI processes."""
+
+from __future__ import annotations
+
+import os
+import signal
+import sys
+
+import pytest
+
+from src.agent_adapters import base as base_module
+from src.agent_adapters import limits as limits_module
+from src.agent_adapters.base import spawn_cli
+from src.agent_adapters.limits import cgroup_command, describe_limit_exit, uses_cgroup
+from src.core.config import _load_agents
+from src.core.errors import ConfigError
+from src.core.models import ProcessLimits
+
+PROBE = (
+    "import resource\n"
+    "kinds = (resource.RLIMIT_CPU, resource.RLIMIT_NOFILE, resource.RLIMIT_AS)\n"
+    "print(*(resource.getrlimit(kind)[0] for kind in kinds))\n"
+)
+
+
+def _write_agents(tmp_path, extra: str, agent_type: str = "codex"):
+    agents_yaml = tmp_path / "agents.yaml"
+    agents_yaml.write_text(f"agents:\n  cli:\n    type: {agent_type}\n    command: [cli]\n{extra}")
+    return agents_yaml
+
+
+class TestProcessLimits:
+    """Test cases for starting agent processes with limits."""
+
+    @pytest.mark.asyncio
+    async def test_rlimits_are_set_in_the_child(self, tmp_path):
+        limits = ProcessLimits(cpu_secs=120, open_files=256, memory_mb=4096)
+        command = [sys.executable, "-c", PROBE]
+
+        async with spawn_cli(command, cwd=tmp_path, env=os.environ, stdin=False, limits=limits) as process:
+            output = (await process.stdout.read()).decode()
+            await process.wait()
+
+        assert output.split() == ["120", "256", str(4096 * 1024 * 1024)]
+
+    @pytest.mark.asyncio
+    async def test_memory_is_left_to_the_cgroup(self, tmp_path, monkeypatch):
+        # Pretend systemd-run exists but start the command directly
+        monkeypatch.setattr(limits_module.shutil, "which", lambda name: f"/usr/bin/{name}")
+        monkeypatch.setattr(base_module, "cgroup_command", lambda command, limits: list(command))
+        limits = ProcessLimits(memory_mb=4096, open_files=256, cgroup=True)
+
+        async with spawn_cli([sys.executable, "-c", PROBE], cwd=tmp_path, env=os.environ, limits=limits) as process:
+            process.stdin.close()
+            output = (await process.stdout.read()).decode()
+            await process.wait()
+
+        assert output.split()[1] == "256"
+        assert output.split()[2] != str(4096 * 1024 * 1024)
+
+    def test_cgroup_command(self):
+        limits = ProcessLimits(memory_mb=2048, cpu_percent=200, cgroup=True)
+
+        command = cgroup_command(["claude", "-p"], limits)
+
+        assert command[:5] == ["systemd-run", "--user", "--scope", "--quiet", "--collect"]
+        assert "MemoryMax=2048M" in command and "CPUQuota=200%" in command
+        assert command[-3:] == ["--", "claude", "-p"]
+
+    def test_cgroup_is_skipped_without_systemd_run(self, monkeypatch):
+        monkeypatch.setattr(limits_module.shutil, "which", lambda name: None)
+
+        assert uses_cgroup(ProcessLimits(memory_mb=512, cgroup=True)) is False
+        assert uses_cgroup(ProcessLimits(memory_mb=512)) is False
+
+    def test_describe_limit_exit(self):
+        limits = ProcessLimits(cpu_secs=60, memory_mb=512, cgroup=True)
+
+        assert "cpu_secs limit (60s" in describe_limit_exit(-signal.SIGXCPU, limits)
+        assert "memory_mb limit (512 MB)" in describe_limit_exit(-signal.SIGKILL, limits)
+        assert describe_limit_exit(1, limits) is None
+        assert describe_limit_exit(-signal.SIGKILL, ProcessLimits(memory_mb=512)) is None
+
+
+class TestLimitsConfig:
+    """Test cases for limits in agents.yaml."""
+
+    def test_no_limits_by_default(self, tmp_path):
+        assert _load_agents(_write_agents(tmp_path, ""))["cli"].limits is None
+
+    def test_mapping(self, tmp_path):
+        extra = "    limits:\n      cpu_secs: 3600\n      memory_mb: 8192\n      open_files: 4096\n"
+
+        agent = _load_agents(_write_agents(tmp_path, extra, agent_type="claude"))["cli"]
+
+        assert agent.limits == ProcessLimits(cpu_secs=3600, memory_mb=8192, open_files=4096)
+
+    @pytest.mark.parametrize(
+        "extra,agent_type,message",
+        [
+            ("    limits: {cpu_secs: 60}\n", "ollama", "only valid for claude, codex, and gemini"),
+            ("    limits: 512\n", "codex", "must be a mapping"),
+            ("    limits: {memory: 512}\n", "codex", "unsupported keys: memory"),
+            ("    limits: {memory_mb: 0}\n", "codex", "memory_mb must be a positive integer"),
+            ("    limits: {cpu_percent: 50}\n", "codex", "cpu_percent needs cgroup: true"),
+            ("    limits: {cgroup: yes-please}\n", "codex", "cgroup must be true or false"),
+        ],
+    )
+    def test_rejects_invalid_values(self, tmp_path, extra, agent_type, message):
+        with pytest.raises(ConfigError, match=message):
+            _load_agents(_write_agents(tmp_path, extra, agent_type=agent_type))
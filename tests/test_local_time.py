@@ -0,0 +1,28 @@
+"""Tests for timezone resolution helpers."""
+
+from __future__ import annotations
+
+from datetime import datetime, timezone
+
+from src.core.local_time import format_local_time, is_valid_timezone, to_local
+
+
+class TestLocalTime:
+    """Test cases for local_time."""
+
+    def test_valid_timezones(self):
+        assert is_valid_timezone("America/New_York")
+        assert not is_valid_timezone("Mars/Olympus_Mons")
+        assert not is_valid_timezone("")
+        assert not is_valid_timezone(None)
+
+    def test_formats_in_user_zone(self):
+        moment = datetime(2025, 6, 1, 7, 0, tzinfo=timezone.utc)
+
+        assert format_local_time(moment, "Europe/Berlin") == "09:00 Europe/Berlin"
+        assert format_local_time(moment, None).endswith(" (server time)")
+
+    def test_unknown_zone_falls_back_to_server_time(self):
+        moment = datetime(2025, 6, 1, 7, 0, tzinfo=timezone.utc)
+
+        assert to_local(moment, "Not/AZone") == to_local(moment, None)
@@ -0,0 +1,138 @@
+"""Tests for MCP server config and the stdio toolset used by API agents."""
+
+from __future__ import annotations
+
+import sys
+import textwrap
+
+import pytest
+
+from src.agent_adapters.claude_adapter import ClaudeAdapter
+from src.agent_adapters.mcp import McpToolset, claude_mcp_config, codex_mcp_overrides
+from src.core.config import _load_agents
+from src.core.errors import ConfigError
+from src.core.models import Agent, AgentType, McpServer, WorkingDirMode
+
+FAKE_SERVER = textwrap.dedent(
+    """
+    import json, sys
+    for line in sys.stdin:
+        msg = json.loads(line)
+        if "id" not in msg:
+            continue
+        if msg["method"] == "initialize":
+            result = {"protocolVersion": "2024-11-05", "capabilities": {}, "serverInfo": {"name": "fake"}}
+        elif msg["method"] == "tools/list":
+            result = {"tools": [{"name": "lookup", "description": "Look up a ticket",
+                                 "inputSchema": {"type": "object", "properties": {"id": {"type": "string"}}}}]}
+        else:
+            ticket = msg["params"]["arguments"]["id"]
+            result = {"content": [{"type": "text", "text": "ticket " + ticket + " is open"}]}
+        sys.stdout.write(json.dumps({"jsonrpc": "2.0", "id": msg["id"], "result": result}) + "\\n")
+        sys.stdout.flush()
+    """
+)
+
+
+class TestMcpConfig:
+    """Test cases for parsing and translating mcp_servers."""
+
+    def test_parse_mcp_servers(self, tmp_path):
+        """Stdio and URL servers are parsed from agents.yaml."""
+        agents_yaml = tmp_path / "agents.yaml"
+        agents_yaml.write_text(
+            """agents:
+  claude:
+    type: claude
+    command: ["claude"]
+    mcp_servers:
+      tickets:
+        command: ["tickets-mcp", "--readonly"]
+        env:
+          TICKETS_TOKEN: abc
+      docs:
+        url: https://mcp.example.test/mcp
+"""
+        )
+
+        servers = _load_agents(agents_yaml)["claude"].mcp_servers
+
+        assert [s.name for s in servers] == ["tickets", "docs"]
+        assert servers[0].command == ["tickets-mcp", "--readonly"]
+        assert servers[0].env == {"TICKETS_TOKEN": "abc"}
+        assert servers[1].url == "https://mcp.example.test/mcp"
+
+    def test_server_needs_command_or_url(self, tmp_path):
+        """A server must define exactly one transport."""
+        agents_yaml = tmp_path / "agents.yaml"
+        agents_yaml.write_text(
+            """agents:
+  claude:
+    type: claude
+    command: ["claude"]
+    mcp_servers:
+      broken: {}
+"""
+        )
+
+        with pytest.raises(ConfigError, match="exactly one of command or url"):
+            _load_agents(agents_yaml)
+
+    def test_cli_translations(self):
+        """Servers become Claude --mcp-config JSON and Codex -c overrides."""
+        servers = [McpServer(name="db", command=["db-mcp", "--ro"], env={"DSN": "x"})]
+
+        assert claude_mcp_config(servers) == {
+            "mcpServers": {"db": {"command": "db-mcp", "args": ["--ro"], "env": {"DSN": "x"}}}
+        }
+        assert codex_mcp_overrides(servers) == [
+            "-c", 'mcp_servers.db.command="db-mcp"',
+            "-c", 'mcp_servers.db.args=["--ro"]',
+            "-c", 'mcp_servers.db.env={"DSN" = "x"}',
+        ]
+
+    def test_claude_command_includes_mcp_config(self, tmp_path):
+        """Claude runs get a temp --mcp-config file and the servers are allowed."""
+        agent = Agent(
+            id="claude",
+            type=AgentType.CLAUDE,
+            command=["claude", "--print"],
+            working_dir_mode=WorkingDirMode.PROJECT,
+            mcp_servers=[McpServer(name="db", command=["db-mcp"])],
+        )
+        adapter = ClaudeAdapter(agent)
+        path = adapter._write_mcp_config()
+        try:
+            command = adapter._build_command("s1", None, path)
+            assert command[command.index("--mcp-config") + 1] == str(path)
+            assert command[command.index("--allowedTools") + 1] == "mcp__db"
+        finally:
+            path.unlink()
+
+
+class TestMcpToolset:
+    """Test cases for running stdio MCP servers during API agent runs."""
+
+    @pytest.mark.asyncio
+    async def test_lists_and_calls_tools(self, tmp_path):
+        """Tools are exposed with a server prefix and calls are routed to the server."""
+        script = tmp_path / "fake_mcp.py"
+        script.write_text(FAKE_SERVER)
+        servers = [McpServer(name="tickets", command=[sys.executable, str(script)])]
+
+        async with McpToolset(servers, tmp_path) as toolset:
+            names = [schema["function"]["name"] for schema in toolset.schemas]
+            assert names == ["mcp__tickets__lookup"]
+            assert toolset.handles("mcp__tickets__lookup")
+            output = await toolset.call("mcp__tickets__lookup", {"id": "T-1"})
+
+        assert output == "ticket T-1 is open"
+        assert not toolset.handles("mcp__tickets__lookup")
+
+    @pytest.mark.asyncio
+    async def test_unavailable_server_is_skipped(self, tmp_path):
+        """A server that cannot start does not break the run."""
+        servers = [McpServer(name="missing", command=["definitely-not-a-real-binary-xyz"])]
+
+        async with McpToolset(servers, tmp_path) as toolset:
+            assert toolset.schemas == []
@@ -0,0 +1,58 @@
+"""Tests for telling tasks, feedback, and chatter apart."""
+
+import pytest
+
+from src.core.conversation import MessageIntent, classify_message
+
+LAST_REPLY = "Added the button to the settings page."
+
+
+class TestClassifyMessage:
+    """Test cases for classify_message."""
+
+    @pytest.mark.parametrize(
+        "text",
+        ["thanks!", "lgtm 👍", "Thank you so much", ":+1:", "🙏", "looks good, merged", "<@U123> nice, thx", "ok"],
+    )
+    def test_acknowledgements_are_chatter(self, text):
+        assert classify_message(text, LAST_REPLY) == MessageIntent.CHATTER
+
+    @pytest.mark.parametrize(
+        "text",
+        [
+            "thanks, can you add tests?",
+            "add a dark mode toggle",
+            "great, now add a dark mode toggle too",
+            "looks good?",
+            "thanks thanks thanks thanks thanks thanks thanks",
+        ],
+    )
+    def test_anything_beyond_an_acknowledgement_runs_the_agent(self, text):
+        assert classify_message(text, LAST_REPLY) != MessageIntent.CHATTER
+
+    @pytest.mark.parametrize(
+        "text",
+        [
+            "that broke the header",
+            "but the button is still blue",
+            "It doesn't compile",
+            "wait, revert the CSS part",
+            "you missed the mobile layout",
+        ],
+    )
+    def test_corrections_are_feedback(self, text):
+        assert classify_message(text, LAST_REPLY) == MessageIntent.FEEDBACK
+
+    def test_new_requests_are_tasks(self):
+        assert classify_message("Add pagination to the users endpoint", LAST_REPLY) == MessageIntent.TASK
+
+    def test_answers_to_a_question_are_feedback(self):
+        """"ok" confirms a proposal when the agent just asked about one."""
+        question = "I can also migrate the old settings.\nShould I go ahead?"
+
+        assert classify_message("ok", question) == MessageIntent.FEEDBACK
+        assert classify_message("yes please 👍", question) == MessageIntent.FEEDBACK
+        assert classify_message("thanks", question) == MessageIntent.CHATTER
+
+    def test_everything_is_a_task_before_the_first_run(self):
+        assert classify_message("thanks!", None) == MessageIntent.TASK
@@ -0,0 +1,44 @@
+"""Tests for fitting outgoing messages into a chat's length limit."""
+
+from __future__ import annotations
+
+import pytest
+
+from src.core.message_split import clip_message, split_message
+
+
+class TestSplitMessage:
+    """Test cases for splitting long replies into several messages."""
+
+    def test_short_text_is_one_message(self):
+        assert split_message("hello\nworld", 20) == ["hello\nworld"]
+
+    def test_splits_at_line_breaks(self):
+        text = "\n".join(f"line {n}" for n in range(10))
+
+        parts = split_message(text, 20)
+
+        assert all(len(part) <= 20 for part in parts)
+        assert "\n".join(parts) == text
+
+    def test_code_blocks_are_closed_and_reopened(self):
+        text = "Intro\n```python\n" + "\n".join(f"x = {n}" for n in range(8)) + "\n```\nDone"
+
+        parts = split_message(text, 40)
+
+        assert len(parts) > 1
+        assert all(len(part) <= 40 for part in parts)
+        assert all(part.count("```") % 2 == 0 for part in parts)
+        assert parts[1].startswith("```python\n")
+        assert parts[-1].endswith("Done")
+
+    @pytest.mark.parametrize("limit", [15, 16, 50])
+    def test_long_lines_are_cut(self, limit):
+        parts = split_message("```\n" + "a" * 120 + "\n```", limit)
+
+        assert all(len(part) <= limit for part in parts)
+        assert "".join(part.replace("```", "").replace("\n", "") for part in parts) == "a" * 120
+
+    def test_clip_message(self):
+        assert clip_message("abcdef", 10) == "abcdef"
+        assert clip_message("abcdef", 4) == "abc…"
@@ -0,0 +1,134 @@
+"""Tests for the mock agent adapter."""
+
+from __future__ import annotations
+
+import subprocess
+from unittest.mock import AsyncMock, patch
+
+import pytest
+
+from src.agent_adapters.mock_adapter import MockAdapter, current_ask
+from src.core.config import _load_agents
+from src.core.errors import ConfigError
+from src.core.models import Agent, AgentType, WorkingDirMode
+
+TASK_TEXT = "preamble\n\nCURRENT ASK:\nUSER:\nadd a button\nto the page\nProvide your answer below."
+
+
+def _agent(**options) -> Agent:
+    return Agent(
+        id="demo",
+        type=AgentType.MOCK,
+        command=[],
+        working_dir_mode=WorkingDirMode.PROJECT,
+        options={"delay_secs": 0, **options},
+    )
+
+
+async def _run(agent: Agent, project_path, task_text: str = TASK_TEXT):
+    return await MockAdapter(agent).run(
+        task_text=task_text,
+        project_path=str(project_path),
+        session_id="s1",
+        conversation_history=[],
+    )
+
+
+class TestMockAdapter:
+    """Test cases for MockAdapter."""
+
+    def test_current_ask(self):
+        """The user's request is taken from the runner's task text, or the whole text otherwise."""
+        assert current_ask(TASK_TEXT) == "add a button\nto the page"
+        assert current_ask("  plain prompt ") == "plain prompt"
+
+    @pytest.mark.asyncio
+    async def test_default_run_logs_the_request(self, tmp_path):
+        """Without canned edits each run appends the request to MOCK_AGENT.md."""
+        first = await _run(_agent(), tmp_path)
+        second = await _run(_agent(), tmp_path, "another one")
+
+        assert first.success
+        assert first.output_text == "Mock agent handled: add a button\nto the page"
+        assert [(edit.path, edit.type) for edit in first.file_edits] == [("MOCK_AGENT.md", "create")]
+        assert [(edit.path, edit.type) for edit in second.file_edits] == [("MOCK_AGENT.md", "edit")]
+        assert (tmp_path / "MOCK_AGENT.md").read_text() == "# Mock agent runs\n\n- add a button\n- another one\n"
+
+    @pytest.mark.asyncio
+    async def test_canned_files_and_pr_details(self, tmp_path):
+        """Configured files are written and the summary/PR details are returned as given."""
+        agent = _agent(
+            summary="Added the button",
+            files={"src/button.py": "BUTTON = True\n"},
+            pr_title="Add button",
+            pr_summary=["Adds a button"],
+        )
+
+        result = await _run(agent, tmp_path)
+
+        assert (tmp_path / "src" / "button.py").read_text() == "BUTTON = True\n"
+        assert [(edit.path, edit.type) for edit in result.file_edits] == [("src/button.py", "create")]
+        assert result.output_text == "Added the button"
+        assert result.structured_output is not None
+        assert result.structured_output.pr_title == "Add button"
+        assert result.structured_output.pr_summary == ["Adds a button"]
+        assert not (tmp_path / "MOCK_AGENT.md").exists()
+
+    @pytest.mark.asyncio
+    async def test_files_outside_project_are_rejected(self, tmp_path):
+        """Canned files may not escape the project directory."""
+        project = tmp_path / "project"
+        project.mkdir()
+
+        result = await _run(_agent(files={"../escape.txt": "x"}), project)
+
+        assert not result.success
+        assert "outside the project" in result.errors[0]
+        assert not (tmp_path / "escape.txt").exists()
+
+    @pytest.mark.asyncio
+    async def test_applies_canned_patch(self, tmp_path):
+        """A canned patch is applied the same way as an http agent's patch."""
+        subprocess.run(["git", "init", "-q"], cwd=tmp_path, check=True)
+        (tmp_path / "README.md").write_text("hello\n")
+        patch_text = "--- a/README.md\n+++ b/README.md\n@@ -1 +1 @@\n-hello\n+hello from mock\n"
+
+        result = await _run(_agent(patch=patch_text), tmp_path)
+
+        assert result.success
+        assert (tmp_path / "README.md").read_text() == "hello from mock\n"
+        assert [(edit.path, edit.type) for edit in result.file_edits] == [("README.md", "edit")]
+
+    @pytest.mark.asyncio
+    async def test_canned_failure(self, tmp_path):
+        """`success: false` and `errors` simulate a failed run."""
+        result = await _run(_agent(success=False, errors=["simulated failure"], files={"a.txt": "a"}), tmp_path)
+
+        assert not result.success
+        assert result.errors == ["simulated failure"]
+
+    @pytest.mark.asyncio
+    async def test_waits_for_the_configured_delay(self, tmp_path):
+        """The reply is delayed by delay_secs."""
+        with patch("src.agent_adapters.mock_adapter.asyncio.sleep", new_callable=AsyncMock) as mock_sleep:
+            await _run(_agent(delay_secs=2.5), tmp_path)
+
+        mock_sleep.assert_awaited_once_with(2.5)
+
+    def test_config_needs_no_command(self, tmp_path):
+        """Mock agents load without a command."""
+        agents_yaml = tmp_path / "agents.yaml"
+        agents_yaml.write_text("agents:\n  demo:\n    type: mock\n    options:\n      delay_secs: 0\n")
+
+        agent = _load_agents(agents_yaml)["demo"]
+
+        assert agent.type == AgentType.MOCK
+        assert agent.command == []
+
+    def test_config_rejects_non_mapping_files(self, tmp_path):
+        """options.files must map paths to contents."""
+        agents_yaml = tmp_path / "agents.yaml"
+        agents_yaml.write_text("agents:\n  demo:\n    type: mock\n    options:\n      files: [a.txt]\n")
+
+        with pytest.raises(ConfigError, match="options.files"):
+            _load_agents(agents_yaml)
@@ -0,0 +1,92 @@
+"""Tests for the Ollama adapter."""
+
+from __future__ import annotations
+
+from unittest.mock import AsyncMock, patch
+
+import pytest
+
+from src.agent_adapters.ollama_adapter import OllamaAdapter
+from src.core.models import Agent, AgentType, WorkingDirMode
+
+
+@pytest.fixture
+def agent():
+    return Agent(
+        id="ollama",
+        type=AgentType.OLLAMA,
+        command=[],
+        working_dir_mode=WorkingDirMode.PROJECT,
+        endpoint="http://gpu-box:11434/",
+        models={"default": "qwen2.5-coder:14b", "available": ["qwen2.5-coder:14b"]},
+        options={"context_size": 16384},
+    )
+
+
+class TestOllamaAdapter:
+    """Test cases for OllamaAdapter."""
+
+    @pytest.mark.asyncio
+    async def test_tool_loop_with_object_arguments(self, agent, tmp_path):
+        """Ollama sends tool arguments as objects and needs no API key."""
+        responses = [
+            {
+                "message": {"role": "assistant", "content": "", "tool_calls": [
+                    {"function": {"name": "write_file", "arguments": {"path": "a.txt", "content": "local"}}}
+                ]},
+                "prompt_eval_count": 40,
+                "eval_count": 10,
+            },
+            {"message": {"role": "assistant", "content": "Wrote a.txt"}, "prompt_eval_count": 60, "eval_count": 5},
+        ]
+        adapter = OllamaAdapter(agent)
+
+        with patch.object(adapter, "_post_completion", new_callable=AsyncMock) as mock_post:
+            mock_post.side_effect = responses
+            result = await adapter.run(
+                task_text="write a.txt",
+                project_path=str(tmp_path),
+                session_id="s1",
+                conversation_history=[],
+            )
+
+        assert result.success
+        assert result.output_text == "Wrote a.txt"
+        assert (tmp_path / "a.txt").read_text() == "local"
+        assert result.token_usage == {"input_tokens": 100, "output_tokens": 15}
+        sent_messages = mock_post.await_args_list[1].args[3]
+        tool_messages = [m for m in sent_messages if m["role"] == "tool"]
+        assert tool_messages[0]["tool_name"] == "write_file"
+
+    @pytest.mark.asyncio
+    async def test_payload_uses_endpoint_and_context_size(self, agent):
+        """The per-agent endpoint and context size are sent to /api/chat."""
+        adapter = OllamaAdapter(agent)
+
+        with patch("src.agent_adapters.ollama_adapter.post_json", new_callable=AsyncMock) as mock_post:
+            mock_post.return_value = {"message": {"role": "assistant", "content": "ok"}}
+            await adapter._post_completion(None, None, "qwen2.5-coder:14b", [], [])
+
+        _, url, payload, headers = mock_post.await_args.args
+        assert url == "http://gpu-box:11434/api/chat"
+        assert payload["options"] == {"num_ctx": 16384}
+        assert payload["stream"] is False
+        assert "Authorization" not in headers
+
+    @pytest.mark.asyncio
+    async def test_temperature_override_joins_model_options(self, agent):
+        """A run's temperature override is sent next to the context size."""
+        adapter = OllamaAdapter(agent)
+
+        with patch("src.agent_adapters.ollama_adapter.post_json", new_callable=AsyncMock) as mock_post:
+            mock_post.return_value = {"message": {"role": "assistant", "content": "ok"}}
+            await adapter._post_completion(None, None, "qwen2.5-coder:14b", [], [], {"temperature": 0.2})
+
+        payload = mock_post.await_args.args[2]
+        assert payload["options"] == {"num_ctx": 16384, "temperature": 0.2}
+
+    def test_rejects_other_agent_types(self, agent):
+        """The adapter only accepts ollama agents."""
+        agent.type = AgentType.OPENROUTER
+        with pytest.raises(ValueError):
+            OllamaAdapter(agent)
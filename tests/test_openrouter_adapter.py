@@ -0,0 +1,145 @@
+"""Tests for the OpenRouter API adapter and its project-scoped tools."""
+
+from __future__ import annotations
+
+import json
+from unittest.mock import AsyncMock, patch
+
+import pytest
+
+from src.agent_adapters.openrouter_adapter import OpenRouterAdapter
+from src.agent_adapters.project_tools import ProjectToolbox
+from src.core.config import _load_agents
+from src.core.models import Agent, AgentType, WorkingDirMode
+
+
+def _tool_call(call_id: str, name: str, arguments: dict) -> dict:
+    return {"id": call_id, "type": "function", "function": {"name": name, "arguments": json.dumps(arguments)}}
+
+
+class TestProjectToolbox:
+    """Test cases for ProjectToolbox."""
+
+    @pytest.mark.asyncio
+    async def test_rejects_paths_outside_project(self, tmp_path):
+        """Tools cannot read files outside the project root."""
+        toolbox = ProjectToolbox(tmp_path / "repo")
+        (tmp_path / "repo").mkdir()
+        (tmp_path / "secret.txt").write_text("nope")
+
+        output = await toolbox.execute("read_file", {"path": "../secret.txt"})
+
+        assert output.startswith("Error:")
+        assert "outside the project" in output
+
+    @pytest.mark.asyncio
+    async def test_write_file_records_edit(self, tmp_path):
+        """Writing a file creates parents and records a FileEdit."""
+        toolbox = ProjectToolbox(tmp_path)
+
+        await toolbox.execute("write_file", {"path": "src/new.py", "content": "print('hi')\n"})
+
+        assert (tmp_path / "src" / "new.py").read_text() == "print('hi')\n"
+        assert [(edit.path, edit.type) for edit in toolbox.file_edits] == [("src/new.py", "create")]
+
+    @pytest.mark.asyncio
+    async def test_run_shell_uses_project_root(self, tmp_path):
+        """Shell commands run with the project as working directory."""
+        (tmp_path / "marker.txt").write_text("x")
+        toolbox = ProjectToolbox(tmp_path)
+
+        output = await toolbox.execute("run_shell", {"command": "ls"})
+
+        assert "exit code 0" in output
+        assert "marker.txt" in output
+
+
+class TestOpenRouterAdapter:
+    """Test cases for OpenRouterAdapter."""
+
+    @pytest.fixture
+    def agent(self):
+        return Agent(
+            id="openrouter",
+            type=AgentType.OPENROUTER,
+            command=[],
+            working_dir_mode=WorkingDirMode.PROJECT,
+            env={"OPENROUTER_API_KEY": "sk-test"},
+            models={"default": "anthropic/claude-sonnet-4.5", "available": ["anthropic/claude-sonnet-4.5"]},
+        )
+
+    @pytest.mark.asyncio
+    async def test_tool_loop_until_final_answer(self, agent, tmp_path):
+        """Tool calls are executed and fed back until the model answers."""
+        responses = [
+            {
+                "choices": [{"message": {"role": "assistant", "content": None, "tool_calls": [
+                    _tool_call("call-1", "write_file", {"path": "hello.txt", "content": "hello"})
+                ]}}],
+                "usage": {"prompt_tokens": 100, "completion_tokens": 20},
+            },
+            {
+                "choices": [{"message": {"role": "assistant", "content": (
+                    'Done. REMOTE_CODER_OUTPUT: {"slack_message": "Added hello.txt", '
+                    '"pr_title": "Add hello", "pr_summary": ["Add hello.txt"]}'
+                )}}],
+                "usage": {"prompt_tokens": 150, "completion_tokens": 30},
+            },
+        ]
+        adapter = OpenRouterAdapter(agent)
+
+        with patch.object(adapter, "_post_completion", new_callable=AsyncMock) as mock_post:
+            mock_post.side_effect = responses
+            result = await adapter.run(
+                task_text="Add hello.txt",
+                project_path=str(tmp_path),
+                session_id="s1",
+                conversation_history=[],
+            )
+
+        assert result.success
+        assert (tmp_path / "hello.txt").read_text() == "hello"
+        assert [edit.path for edit in result.file_edits] == ["hello.txt"]
+        assert result.structured_output is not None
+        assert result.structured_output.pr_title == "Add hello"
+        assert result.token_usage == {"input_tokens": 250, "output_tokens": 50}
+        # The tool result is sent back to the model for the matching call
+        sent_messages = mock_post.await_args_list[1].args[3]
+        tool_messages = [m for m in sent_messages if m["role"] == "tool"]
+        assert [m["tool_call_id"] for m in tool_messages] == ["call-1"]
+        assert "Wrote 5 characters" in tool_messages[0]["content"]
+
+    @pytest.mark.asyncio
+    async def test_missing_api_key(self, agent, tmp_path, monkeypatch):
+        """A missing API key fails fast with a clear error."""
+        agent.env = {}
+        monkeypatch.delenv("OPENROUTER_API_KEY", raising=False)
+
+        result = await OpenRouterAdapter(agent).run(
+            task_text="hi",
+            project_path=str(tmp_path),
+            session_id="s1",
+            conversation_history=[],
+        )
+
+        assert not result.success
+        assert "OPENROUTER_API_KEY is not set" in result.errors[0]
+
+    def test_config_allows_missing_command(self, tmp_path):
+        """API agents do not need a CLI command in agents.yaml."""
+        agents_yaml = tmp_path / "agents.yaml"
+        agents_yaml.write_text(
+            """agents:
+  openrouter:
+    type: openrouter
+    endpoint: https://example.test/api/v1
+    options:
+      max_tool_iterations: 5
+"""
+        )
+
+        agents = _load_agents(agents_yaml)
+
+        assert agents["openrouter"].command == []
+        assert agents["openrouter"].endpoint == "https://example.test/api/v1"
+        assert agents["openrouter"].options == {"max_tool_iterations": 5}
@@ -0,0 +1,97 @@
+"""Tests for line assembly and chunked flushing of agent output."""
+
+from __future__ import annotations
+
+import asyncio
+
+import pytest
+
+from src.core import output_buffer
+from src.core.output_buffer import LineBuffer, OutputBuffer, visible_line
+
+
+@pytest.fixture
+def no_gap(monkeypatch):
+    monkeypatch.setattr(output_buffer, "MIN_FLUSH_GAP_SECS", 0)
+
+
+class TestLineBuffer:
+    """Test cases for turning output into the lines a terminal would show."""
+
+    def test_lines_wait_for_their_newline(self):
+        lines = LineBuffer()
+
+        assert lines.feed("Reading the ") == []
+        assert lines.feed("router\nEdit") == ["Reading the router"]
+        assert lines.partial == "Edit"
+        assert lines.close() == "Edit"
+        assert lines.close() is None
+
+    def test_carriage_returns_overwrite_the_line(self):
+        lines = LineBuffer()
+
+        assert lines.feed("[#   ] 25%\r[##  ] 50%\r") == []
+        assert lines.partial == "[##  ] 50%"
+        assert lines.feed("\n") == ["[##  ] 50%"]
+        assert lines.feed("a\r\nb\r\n") == ["a", "b"]
+
+    def test_endless_redraws_do_not_grow_the_buffer(self):
+        lines = LineBuffer()
+        for percent in range(1000):
+            lines.feed(f"{percent}%\r")
+
+        assert lines._partial == "999%\r"
+        assert visible_line("old\rnew\r") == "new"
+
+
+class TestOutputBuffer:
+    """Test cases for flushing output in chunks."""
+
+    @pytest.mark.asyncio
+    async def test_flushes_when_a_chunk_is_full(self, no_gap):
+        chunks: list[str] = []
+
+        async def flush(text: str) -> None:
+            chunks.append(text)
+
+        async with OutputBuffer(flush, max_chars=12, interval_secs=60) as buffer:
+            buffer.feed("alpha\nbeta\n")
+            await asyncio.sleep(0.01)
+            assert chunks == []
+
+            buffer.feed("gamma\n")
+            await asyncio.sleep(0.01)
+            assert chunks == ["alpha\nbeta"]
+
+        assert chunks == ["alpha\nbeta", "gamma"]
+
+    @pytest.mark.asyncio
+    async def test_flushes_after_the_interval(self, no_gap):
+        chunks: list[str] = []
+
+        async def flush(text: str) -> None:
+            chunks.append(text)
+
+        async with OutputBuffer(flush, interval_secs=0.02) as buffer:
+            buffer.feed("one\n")
+            buffer.feed("two\n")
+            await asyncio.sleep(0.1)
+            assert chunks == ["one\ntwo"]
+            buffer.feed("three")
+
+        assert chunks == ["one\ntwo", "three"]
+
+    @pytest.mark.asyncio
+    async def test_stops_after_max_chunks_and_counts_the_rest(self, no_gap, monkeypatch):
+        monkeypatch.setattr(output_buffer, "MAX_CHUNKS", 2)
+        chunks: list[str] = []
+
+        async def flush(text: str) -> None:
+            chunks.append(text)
+
+        async with OutputBuffer(flush, max_chars=4, interval_secs=60) as buffer:
+            for number in range(6):
+                buffer.feed(f"l{number}\n")
+                await asyncio.sleep(0.01)
+
+        assert chunks == ["l0", "l1", "… 4 more lines of output not shown"]
@@ -0,0 +1,175 @@
+"""Tests for streaming agent output into an edited thread message."""
+
+from __future__ import annotations
+
+import asyncio
+from typing import List, Optional, Tuple
+
+import pytest
+
+from src.agent_adapters.progress import ProgressEvent, StepKind, StepStatus
+from src.core.errors import SlackError
+from src.core.output_stream import MAX_LINES, OutputStream
+
+
+class FakeChat:
+    def __init__(self, *, editable: bool = True) -> None:
+        self.posted: List[Tuple[str, Optional[str], str]] = []
+        self.edits: List[Tuple[str, str, str]] = []
+        self.editable = editable
+        self.fail_edits = False
+
+    async def send_message(self, channel: str, thread_ts: Optional[str], text: str) -> str:
+        self.posted.append((channel, thread_ts, text))
+        return f"{thread_ts}.{len(self.posted)}"
+
+    async def update_message(self, channel: str, ts: str, text: str) -> bool:
+        if self.fail_edits:
+            raise SlackError("rate limited")
+        if not self.editable:
+            return False
+        self.edits.append((channel, ts, text))
+        return True
+
+
+def _stream(chat: FakeChat, interval_secs: float = 0) -> OutputStream:
+    return OutputStream(
+        "C1",
+        "t1",
+        label="claude",
+        send_message=chat.send_message,
+        update_message=chat.update_message,
+        interval_secs=interval_secs,
+    )
+
+
+class TestOutputStreamRender:
+    """Test cases for turning fed output into the status message."""
+
+    def test_partial_lines_are_joined_until_the_newline(self):
+        stream = _stream(FakeChat())
+        stream.feed("Reading the ")
+        stream.feed("router\nEditing")
+
+        assert stream.line_count == 2
+        assert stream.render() == "`claude` output (running, 2 lines):\n```\nReading the router\nEditing\n```"
+
+    def test_only_the_latest_lines_are_shown(self):
+        stream = _stream(FakeChat())
+        for number in range(MAX_LINES + 5):
+            stream.feed(f"line {number}\n")
+
+        text = stream.render(finished=True)
+        assert text.startswith(f"`claude` output (finished, {MAX_LINES + 5} lines, last {MAX_LINES}):")
+        assert "line 4\n" not in text
+        assert f"line {MAX_LINES + 4}\n```" in text
+
+    def test_structured_output_block_is_hidden(self):
+        stream = _stream(FakeChat())
+        stream.feed("Done.\nREMOTE_CODER_OUTPUT: {\"slack_message\": ")
+        stream.feed("\"hi\"}\n")
+
+        assert stream.line_count == 1
+        assert "REMOTE_CODER_OUTPUT" not in stream.render()
+
+    def test_code_fences_in_output_cannot_close_the_block(self):
+        stream = _stream(FakeChat())
+        stream.feed("```python\nprint(1)\n```\n")
+
+        assert stream.render().count("```") == 2
+
+    def test_tool_calls_show_as_a_checklist(self):
+        stream = _stream(FakeChat())
+        stream.feed("Looking at the router\n")
+        stream.progress(ProgressEvent("1", StepKind.READ, "src/core/router.py"))
+        stream.progress(ProgressEvent("1", status=StepStatus.DONE))
+        stream.progress(ProgressEvent("2", StepKind.COMMAND, "pytest -q"))
+        stream.progress(ProgressEvent("2", status=StepStatus.FAILED))
+        stream.progress(ProgressEvent("3", StepKind.EDIT, "src/core/router.py"))
+        stream.feed("Fixing the `test`\n")
+
+        assert stream.render() == (
+            "`claude` progress (running, 3 steps, 1 failed):\n"
+            "✓ Read `src/core/router.py`\n"
+            "✗ Run `pytest -q`\n"
+            "… Edit `src/core/router.py`\n"
+            "> Fixing the `test`"
+        )
+
+
+class TestOutputStreamPublishing:
+    """Test cases for posting and editing the status message."""
+
+    @pytest.mark.asyncio
+    async def test_posts_on_first_output_then_edits(self):
+        chat = FakeChat()
+        async with _stream(chat) as stream:
+            stream.feed("first\n")
+            await asyncio.sleep(0.01)
+            stream.feed("second\n")
+            await asyncio.sleep(0.01)
+
+        assert len(chat.posted) == 1
+        assert chat.posted[0][:2] == ("C1", "t1")
+        assert "first" in chat.posted[0][2]
+        assert all(ts == "t1.1" for _, ts, _ in chat.edits)
+        assert chat.edits[-1][2].startswith("`claude` output (finished, 2 lines):")
+        assert "second" in chat.edits[-1][2]
+
+    @pytest.mark.asyncio
+    async def test_updates_are_coalesced_per_interval(self):
+        chat = FakeChat()
+        async with _stream(chat, interval_secs=60) as stream:
+            stream.feed("first\n")
+            await asyncio.sleep(0.01)
+            for number in range(10):
+                stream.feed(f"more {number}\n")
+            await asyncio.sleep(0.01)
+
+        # One post when output starts and one final edit; nothing in between
+        assert len(chat.posted) == 1
+        assert len(chat.edits) == 1
+        assert "more 9" in chat.edits[0][2]
+
+    @pytest.mark.asyncio
+    async def test_runs_without_output_post_nothing(self):
+        chat = FakeChat()
+        async with _stream(chat) as stream:
+            stream.feed("\n\n")
+            await asyncio.sleep(0.01)
+
+        assert chat.posted == []
+        assert chat.edits == []
+
+    @pytest.mark.asyncio
+    async def test_continues_in_chunks_when_the_adapter_cannot_edit(self):
+        chat = FakeChat(editable=False)
+        async with _stream(chat) as stream:
+            stream.feed("first\n")
+            await asyncio.sleep(0.01)
+            stream.feed("second\n")
+            await asyncio.sleep(0.01)
+            stream.feed("third\nfourth")
+
+        assert chat.edits == []
+        assert [text for _, _, text in chat.posted][1:] == ["`claude` output:\n```\nsecond\nthird\nfourth\n```"]
+
+    def test_carriage_returns_redraw_the_line(self):
+        stream = _stream(FakeChat())
+        stream.feed("Downloading  10%\rDownloading  60%\r")
+        stream.feed("Downloading 100%\r\nDone\n")
+
+        assert stream.line_count == 2
+        assert stream.render() == "`claude` output (running, 2 lines):\n```\nDownloading 100%\nDone\n```"
+
+    @pytest.mark.asyncio
+    async def test_edit_failures_do_not_break_the_run(self):
+        chat = FakeChat()
+        chat.fail_edits = True
+        async with _stream(chat) as stream:
+            stream.feed("first\n")
+            await asyncio.sleep(0.01)
+            stream.feed("second\n")
+            await asyncio.sleep(0.01)
+
+        assert len(chat.posted) == 1
@@ -0,0 +1,81 @@
+"""Tests for project run pipelines in projects.yaml."""
+
+from __future__ import annotations
+
+from pathlib import Path
+
+import pytest
+
+from src.core.config import _load_projects
+from src.core.errors import ConfigError
+from src.core.models import CommandStep, PipelineStep, Project
+from src.core.pipeline import pipeline_for
+
+
+def _load(tmp_path: Path, extra: str) -> Project:
+    projects_yaml = tmp_path / "projects.yaml"
+    projects_yaml.write_text(
+        f"base_dir: {tmp_path}\nprojects:\n  demo:\n    path: .\n    default_agent: claude\n{extra}"
+    )
+    projects, _ = _load_projects(projects_yaml)
+    return projects["demo"]
+
+
+class TestPipelineConfig:
+    """Test cases for `pipeline:` and `format:` in projects.yaml."""
+
+    def test_parses_steps_and_hooks(self, tmp_path):
+        project = _load(
+            tmp_path,
+            "    format: [ruff format .]\n"
+            "    pipeline:\n"
+            "      - plan\n      - {name: approve, timeout_secs: 600}\n      - edit\n"
+            "      - {name: generate, run: make generate, timeout_secs: 120}\n"
+            "      - format\n      - verify\n      - pr\n",
+        )
+
+        assert project.format_commands == [CommandStep(run="ruff format .")]
+        names = [step.name for step in project.pipeline]
+        assert names == ["plan", "approve", "edit", "generate", "format", "verify", "pr"]
+        assert project.pipeline[1] == PipelineStep(name="approve", timeout_secs=600)
+        assert project.pipeline[3] == PipelineStep(name="generate", run="make generate", timeout_secs=120)
+
+    def test_default_pipeline_matches_the_built_in_flow(self, tmp_path):
+        assert [step.name for step in pipeline_for(_load(tmp_path, ""))] == ["edit", "pr", "diff-review"]
+
+        project = _load(tmp_path, "    format: [gofmt -w .]\n")
+
+        assert [step.name for step in pipeline_for(project)] == ["edit", "format", "pr", "diff-review"]
+
+    @pytest.mark.parametrize(
+        "pipeline,message",
+        [
+            ("[plan, pr]", "must list `edit` exactly once"),
+            ("[edit, edit]", "must list `edit` exactly once"),
+            ("[edit, pr, pr]", "lists `pr` more than once"),
+            ("[edit, plan]", "must list `plan` before `edit`"),
+            ("[verify, edit]", "must list `verify` after `edit`"),
+            ("[edit, format]", "has a format step, but the project has no format commands"),
+            ("[edit, review]", "has a review step, but the project's roles have no reviewer"),
+            ("[edit, lint]", "step lint is unknown"),
+            ("[edit, {name: pr, run: make}]", "cannot be named pr"),
+            ("[edit, {name: verify, timeout_secs: 60}]", "timeout_secs only applies to approve and hook steps"),
+            ("[edit, {run: make, on_failure: skip}]", "unsupported keys on_failure"),
+            ("edit", "must be a list of steps"),
+        ],
+    )
+    def test_rejects_invalid_pipelines(self, tmp_path, pipeline, message):
+        with pytest.raises(ConfigError, match=message):
+            _load(tmp_path, f"    pipeline: {pipeline}\n")
+
+    def test_groups_can_set_a_pipeline(self, tmp_path):
+        projects_yaml = tmp_path / "projects.yaml"
+        projects_yaml.write_text(
+            f"base_dir: {tmp_path}\n"
+            "groups:\n  careful:\n    pipeline: [plan, approve, edit, pr]\n"
+            "projects:\n  demo:\n    path: .\n    default_agent: claude\n    group: careful\n"
+        )
+
+        projects, _ = _load_projects(projects_yaml)
+
+        assert [step.name for step in projects["demo"].pipeline] == ["plan", "approve", "edit", "pr"]
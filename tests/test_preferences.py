@@ -0,0 +1,75 @@
+"""Tests for per-user session defaults."""
+
+from __future__ import annotations
+
+from pathlib import Path
+
+import pytest
+
+from src.core.models import AgentType, Session
+from src.core.preferences import (
+    PREFERENCES_CONTEXT_KEY,
+    STARTED_BY_CONTEXT_KEY,
+    PreferenceError,
+    UserPreferences,
+    Verbosity,
+    notify_target,
+    session_preferences,
+    with_preference,
+    without_preference,
+)
+
+AGENTS = ("claude", "codex")
+
+
+def _session(context: dict) -> Session:
+    return Session(
+        project_id="api",
+        channel_id="C1",
+        thread_ts="1.0",
+        active_agent_id="claude",
+        active_agent_type=AgentType.CLAUDE,
+        project_path=Path("/tmp/api"),
+        session_context=context,
+    )
+
+
+class TestPreferences:
+    """Test cases for setting and reading preferences."""
+
+    def test_values_are_checked_and_normalised(self):
+        preferences = with_preference(UserPreferences(), "agent", "Codex", agent_ids=AGENTS)
+        preferences = with_preference(preferences, "Verbosity", "QUIET", agent_ids=AGENTS)
+        preferences = with_preference(preferences, "locale", "pt_BR", agent_ids=AGENTS)
+        preferences = with_preference(preferences, "notify", "#builds", agent_ids=AGENTS)
+
+        assert preferences == UserPreferences(
+            agent="codex", verbosity=Verbosity.QUIET, notify="#builds", locale="pt-BR"
+        )
+        assert without_preference(preferences, "agent").agent is None
+        assert without_preference(preferences, "verbosity").verbosity == Verbosity.NORMAL
+
+    @pytest.mark.parametrize(
+        ("name", "value", "message"),
+        [
+            ("agent", "gpt", "Unknown agent `gpt`"),
+            ("locale", "not a locale", "Locale must be"),
+            ("notify", "two words", "Notify must be"),
+            ("colour", "blue", "Unknown preference `colour`"),
+        ],
+    )
+    def test_rejects_bad_values(self, name, value, message):
+        with pytest.raises(PreferenceError, match=message):
+            with_preference(UserPreferences(), name, value, agent_ids=AGENTS)
+
+    def test_sessions_notify_their_starter(self):
+        dm = {STARTED_BY_CONTEXT_KEY: "U1", PREFERENCES_CONTEXT_KEY: UserPreferences(notify="dm").to_dict()}
+        channel = {STARTED_BY_CONTEXT_KEY: "U1", PREFERENCES_CONTEXT_KEY: UserPreferences(notify="#builds").to_dict()}
+
+        assert notify_target(_session(dm)) == "U1"
+        assert notify_target(_session(channel)) == "builds"
+        assert notify_target(_session({})) is None
+        assert session_preferences(_session({PREFERENCES_CONTEXT_KEY: {"verbosity": "loud"}})) == (
+            None,
+            UserPreferences(),
+        )
@@ -0,0 +1,122 @@
+"""Tests for the repository health check before a session's first run."""
+
+from __future__ import annotations
+
+import subprocess
+
+import pytest
+
+from src.core import preflight as preflight_module
+from src.core.models import GitHubRepoConfig, Project
+from src.core.preflight import check_repository, format_preflight
+from tests.conftest import git
+
+
+def _project(path, github: bool = False) -> Project:
+    return Project(
+        id="demo",
+        channel_name="demo",
+        path=path,
+        default_agent_id="claude",
+        github=GitHubRepoConfig(owner="acme", repo="demo", default_base_branch="main") if github else None,
+    )
+
+
+def _commit(repo, name: str, content: str) -> str:
+    (repo / name).write_text(content)
+    git(repo, "add", "-A")
+    git(repo, "commit", "-q", "-m", f"change {name}")
+    return git(repo, "rev-parse", "HEAD").strip()
+
+
+class TestPreflight:
+    """Test cases for check_repository."""
+
+    @pytest.mark.asyncio
+    async def test_healthy_repo_has_no_issues(self, git_repo):
+        git(git_repo, "remote", "add", "origin", "git@github.com:acme/demo.git")
+
+        assert await check_repository(_project(git_repo, github=True), git_repo) == []
+
+    @pytest.mark.asyncio
+    async def test_non_git_directory(self, tmp_path):
+        assert await check_repository(_project(tmp_path), tmp_path) == []
+
+        issues = await check_repository(_project(tmp_path, github=True), tmp_path)
+
+        assert [issue.blocking for issue in issues] == [True]
+        assert "git clone https://github.com/acme/demo.git" in issues[0].fix
+
+    @pytest.mark.asyncio
+    async def test_missing_and_mismatched_origin(self, git_repo):
+        project = _project(git_repo, github=True)
+
+        missing = await check_repository(project, git_repo)
+        git(git_repo, "remote", "add", "origin", "https://github.com/someone/else.git")
+        mismatched = await check_repository(project, git_repo)
+
+        assert missing[0].blocking and "git remote add origin https://github.com/acme/demo.git" in missing[0].fix
+        assert not mismatched[0].blocking and "someone/else" in mismatched[0].problem
+
+    @pytest.mark.asyncio
+    async def test_detached_head(self, git_repo):
+        _commit(git_repo, "a.txt", "a")
+        git(git_repo, "checkout", "-q", "HEAD~1")
+
+        issues = await check_repository(_project(git_repo), git_repo)
+
+        assert len(issues) == 1 and not issues[0].blocking
+        assert issues[0].problem.startswith("HEAD is detached at")
+
+    @pytest.mark.asyncio
+    async def test_merge_in_progress_blocks(self, git_repo):
+        git(git_repo, "checkout", "-q", "-b", "other")
+        _commit(git_repo, "README.md", "theirs\n")
+        git(git_repo, "checkout", "-q", "main")
+        _commit(git_repo, "README.md", "ours\n")
+        subprocess.run(["git", "merge", "other"], cwd=git_repo, capture_output=True, check=False)
+
+        issues = await check_repository(_project(git_repo), git_repo)
+
+        assert issues[0].blocking
+        assert "middle of a merge" in issues[0].problem and "git merge --abort" in issues[0].fix
+
+    @pytest.mark.asyncio
+    async def test_diverged_base_branch_blocks(self, git_repo):
+        git(git_repo, "remote", "add", "origin", "https://github.com/acme/demo.git")
+        start = git(git_repo, "rev-parse", "HEAD").strip()
+        upstream = _commit(git_repo, "upstream.txt", "new upstream")
+        git(git_repo, "update-ref", "refs/remotes/origin/main", upstream)
+        git(git_repo, "reset", "-q", "--hard", start)
+        project = _project(git_repo, github=True)
+
+        _commit(git_repo, "local.txt", "local")
+        diverged = await check_repository(project, git_repo)
+        git(git_repo, "update-ref", "refs/remotes/origin/main", start)
+        ahead = await check_repository(project, git_repo)
+
+        assert diverged[0].blocking and "1 local commit(s), 1 new upstream" in diverged[0].problem
+        assert not ahead[0].blocking and "1 commit(s) that are not on `origin/main`" in ahead[0].problem
+
+    @pytest.mark.asyncio
+    async def test_large_untracked_files(self, git_repo, monkeypatch):
+        monkeypatch.setattr(preflight_module, "LARGE_UNTRACKED_BYTES", 1024)
+        (git_repo / "dump.sql").write_bytes(b"x" * 4096)
+        (git_repo / ".gitignore").write_text("ignored.bin\n")
+        (git_repo / "ignored.bin").write_bytes(b"x" * 4096)
+        (git_repo / "small.txt").write_text("small")
+
+        issues = await check_repository(_project(git_repo), git_repo)
+
+        assert len(issues) == 1 and not issues[0].blocking
+        assert "`dump.sql`" in issues[0].problem and "ignored.bin" not in issues[0].problem
+
+    @pytest.mark.asyncio
+    async def test_format_marks_blocking_issues(self, tmp_path):
+        project = _project(tmp_path, github=True)
+
+        text = format_preflight(project, await check_repository(project, tmp_path))
+
+        assert text.startswith("Repository check for `demo` found 1 issue:")
+        assert "• *blocking* " in text
+        assert text.endswith("the session starts once the check passes.")
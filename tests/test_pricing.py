@@ -0,0 +1,136 @@
+"""Tests for agent pricing config and cost estimation."""
+
+import pytest
+
+from src.agent_adapters.base import AgentResult
+from src.core.config import _load_agents
+from src.core.errors import ConfigError
+from src.core.models import Agent, AgentPricing, AgentType, WorkingDirMode
+from src.core.pricing import (
+    cost_for_result,
+    cost_for_usage,
+    estimate_run_cost,
+    estimate_tokens,
+    format_cost,
+)
+
+
+def _agent(pricing: AgentPricing | None) -> Agent:
+    return Agent(
+        id="claude",
+        type=AgentType.CLAUDE,
+        command=["claude"],
+        working_dir_mode=WorkingDirMode.PROJECT,
+        pricing=pricing,
+    )
+
+
+class TestCostEstimation:
+    """Test cases for the pricing estimator."""
+
+    def test_no_pricing_returns_none(self):
+        """Agents without pricing produce no estimate."""
+        assert estimate_run_cost(_agent(None), None, "hello") is None
+
+    def test_cost_for_usage_combines_rates(self):
+        """Per-run and per-token rates are summed."""
+        agent = _agent(
+            AgentPricing(per_run_usd=0.5, input_per_1k_tokens_usd=1.0, output_per_1k_tokens_usd=2.0)
+        )
+
+        estimate = cost_for_usage(agent, None, input_tokens=2000, output_tokens=500)
+
+        assert estimate is not None
+        assert estimate.cost_usd == pytest.approx(0.5 + 2.0 + 1.0)
+
+    def test_model_override_used(self):
+        """Per-model rates take precedence over agent rates."""
+        pricing = AgentPricing(
+            input_per_1k_tokens_usd=1.0,
+            model_overrides={"opus": AgentPricing(input_per_1k_tokens_usd=10.0)},
+        )
+
+        estimate = cost_for_usage(_agent(pricing), "opus", input_tokens=1000, output_tokens=0)
+
+        assert estimate is not None
+        assert estimate.cost_usd == pytest.approx(10.0)
+
+    def test_result_prefers_reported_usage(self):
+        """Token usage reported by the CLI beats the text-length heuristic."""
+        agent = _agent(AgentPricing(output_per_1k_tokens_usd=1.0))
+        result = AgentResult(
+            success=True,
+            output_text="short",
+            token_usage={"input_tokens": 0, "output_tokens": 3000},
+        )
+
+        estimate = cost_for_result(agent, None, "prompt", result)
+
+        assert estimate is not None
+        assert estimate.from_reported_usage
+        assert estimate.cost_usd == pytest.approx(3.0)
+
+    def test_result_falls_back_to_text_estimate(self):
+        """Without reported usage, tokens are estimated from text length."""
+        agent = _agent(AgentPricing(input_per_1k_tokens_usd=1.0, output_per_1k_tokens_usd=1.0))
+        result = AgentResult(success=True, output_text="x" * 4000)
+
+        estimate = cost_for_result(agent, None, "y" * 400, result)
+
+        assert estimate is not None
+        assert not estimate.from_reported_usage
+        assert estimate.output_tokens == estimate_tokens("x" * 4000) == 1000
+        assert estimate.input_tokens == 100
+
+    def test_format_cost(self):
+        """Small amounts keep extra precision."""
+        assert format_cost(0.0042) == "$0.0042"
+        assert format_cost(1.5) == "$1.50"
+
+
+class TestPricingConfig:
+    """Test cases for parsing pricing from agents.yaml."""
+
+    def test_parse_pricing_with_model_overrides(self, tmp_path):
+        """Model overrides inherit unspecified rates from the agent."""
+        agents_yaml = tmp_path / "agents.yaml"
+        agents_yaml.write_text(
+            """agents:
+  claude:
+    type: claude
+    command: ["claude"]
+    pricing:
+      per_run_usd: 0.01
+      input_per_1k_tokens_usd: 0.003
+      output_per_1k_tokens_usd: 0.015
+      models:
+        opus:
+          output_per_1k_tokens_usd: 0.075
+"""
+        )
+
+        agents = _load_agents(agents_yaml)
+
+        pricing = agents["claude"].pricing
+        assert pricing is not None
+        assert pricing.per_run_usd == pytest.approx(0.01)
+        opus = pricing.for_model("opus")
+        assert opus.output_per_1k_tokens_usd == pytest.approx(0.075)
+        assert opus.input_per_1k_tokens_usd == pytest.approx(0.003)
+        assert pricing.for_model("sonnet") is pricing
+
+    def test_negative_rate_rejected(self, tmp_path):
+        """Negative rates are a config error."""
+        agents_yaml = tmp_path / "agents.yaml"
+        agents_yaml.write_text(
+            """agents:
+  claude:
+    type: claude
+    command: ["claude"]
+    pricing:
+      per_run_usd: -1
+"""
+        )
+
+        with pytest.raises(ConfigError):
+            _load_agents(agents_yaml)
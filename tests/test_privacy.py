@@ -0,0 +1,69 @@
+"""Tests for strict privacy mode."""
+
+from __future__ import annotations
+
+import os
+import sys
+
+import pytest
+
+from src.agent_adapters.base import spawn_cli
+from src.agent_adapters.containers import AgentContainer, agent_container_argv
+from src.chat_adapters.health import post_webhook_alert
+from src.core.models import SandboxConfig, SandboxMode
+from src.core.privacy import CLI_OPT_OUT_ENV, PRIVACY, STANDARD, STRICT, Egress, EgressBlocked, PrivacyPolicy
+from src.core.updates import fetch_releases
+
+
+@pytest.fixture
+def strict():
+    PRIVACY.set_mode(STRICT)
+    try:
+        yield PRIVACY
+    finally:
+        PRIVACY.set_mode(STANDARD)
+
+
+class TestPrivacyPolicy:
+    """Test cases for what each mode lets out."""
+
+    def test_standard_mode_allows_everything(self):
+        policy = PrivacyPolicy()
+
+        assert all(policy.allows(egress) for egress in Egress)
+        assert policy.cli_env({"A": "1"}) == {"A": "1"}
+        assert policy.message_options() == {}
+        assert policy.describe() == "Privacy: standard"
+
+    def test_strict_mode_blocks_the_optional_calls(self):
+        policy = PrivacyPolicy(STRICT)
+
+        assert policy.blocked() == list(Egress)
+        assert policy.cli_env({"A": "1"}) == {"A": "1", **CLI_OPT_OUT_ENV}
+        assert policy.message_options() == {"unfurl_links": False, "unfurl_media": False}
+        assert policy.describe() == (
+            "Privacy: strict (off: update checks, alert webhooks, link previews, agent CLI telemetry, "
+            "session archive transfers)"
+        )
+        with pytest.raises(EgressBlocked, match="update checks are off in strict privacy mode"):
+            policy.require(Egress.UPDATE_CHECK)
+
+    @pytest.mark.asyncio
+    async def test_outbound_helpers_refuse_before_connecting(self, strict):
+        with pytest.raises(EgressBlocked):
+            await fetch_releases()
+        with pytest.raises(EgressBlocked):
+            await post_webhook_alert("http://127.0.0.1:9/alert", "down")
+
+    @pytest.mark.asyncio
+    async def test_agent_clis_get_the_opt_out_variables(self, strict, tmp_path):
+        command = [sys.executable, "-c", "import os; print(os.environ.get('DISABLE_TELEMETRY'))"]
+
+        async with spawn_cli(command, cwd=tmp_path, env=os.environ, stdin=False) as process:
+            output = (await process.stdout.read()).decode()
+            await process.wait()
+
+        assert output.strip() == "1"
+        sandbox = SandboxConfig(mode=SandboxMode.CONTAINER, image="coder:1", network=True, run_agent=True)
+        argv = agent_container_argv(AgentContainer(sandbox=sandbox), ["cli"], tmp_path, "agent-1")
+        assert "DO_NOT_TRACK=1" in argv and argv[argv.index("DO_NOT_TRACK=1") - 1] == "--env"
@@ -0,0 +1,86 @@
+"""Tests for parsing agent tool calls into progress steps."""
+
+from __future__ import annotations
+
+from src.agent_adapters.progress import (
+    ProgressEvent,
+    StepKind,
+    StepStatus,
+    parse_claude_event,
+    parse_codex_event,
+    parse_gemini_event,
+)
+from src.core.progress import MAX_STEPS, ProgressChecklist
+
+
+class TestProgressParsers:
+    """Test cases for each CLI's tool events."""
+
+    def test_claude_tool_use_and_result(self):
+        started = {
+            "type": "assistant",
+            "message": {
+                "content": [
+                    {"type": "text", "text": "Let me look."},
+                    {"type": "tool_use", "id": "t1", "name": "Read", "input": {"file_path": "src/app.py"}},
+                    {"type": "tool_use", "id": "t2", "name": "Bash", "input": {"command": "pytest  -q\n"}},
+                    {"type": "tool_use", "id": "t3", "name": "mcp__jira__search", "input": {}},
+                ]
+            },
+        }
+        finished = {
+            "type": "user",
+            "message": {"content": [{"type": "tool_result", "tool_use_id": "t2", "is_error": True}]},
+        }
+
+        assert parse_claude_event(started) == [
+            ProgressEvent("t1", StepKind.READ, "src/app.py"),
+            ProgressEvent("t2", StepKind.COMMAND, "pytest -q"),
+            ProgressEvent("t3", StepKind.OTHER, "mcp__jira__search"),
+        ]
+        assert parse_claude_event(finished) == [ProgressEvent("t2", status=StepStatus.FAILED)]
+        assert parse_claude_event({"type": "result", "usage": {}}) == []
+
+    def test_codex_items(self):
+        command = {"id": "item_1", "type": "command_execution", "command": "bash -lc 'cat README.md'"}
+        change = {"id": "item_2", "type": "file_change", "changes": [{"path": "a.py", "kind": "update"}]}
+
+        assert parse_codex_event({"type": "item.started", "item": command}) == [
+            ProgressEvent("item_1", StepKind.COMMAND, "bash -lc 'cat README.md'")
+        ]
+        assert parse_codex_event({"type": "item.completed", "item": {**command, "exit_code": 1}}) == [
+            ProgressEvent("item_1", StepKind.COMMAND, "bash -lc 'cat README.md'", StepStatus.FAILED)
+        ]
+        assert parse_codex_event({"type": "item.completed", "item": {**change, "status": "completed"}}) == [
+            ProgressEvent("item_2", StepKind.EDIT, "a.py", StepStatus.DONE)
+        ]
+        message = {"id": "item_3", "type": "agent_message", "text": "Done."}
+        assert parse_codex_event({"type": "item.completed", "item": message}) == []
+
+    def test_gemini_tool_events(self):
+        started = {"type": "tool_use", "tool_name": "replace", "tool_id": "g1", "parameters": {"file_path": "b.ts"}}
+
+        assert parse_gemini_event(started) == [ProgressEvent("g1", StepKind.EDIT, "b.ts")]
+        assert parse_gemini_event({"type": "tool_result", "tool_id": "g1", "status": "success"}) == [
+            ProgressEvent("g1", status=StepStatus.DONE)
+        ]
+        assert parse_gemini_event({"type": "message", "role": "assistant", "content": "hi"}) == []
+
+
+class TestProgressChecklist:
+    """Test cases for the checklist of a run's steps."""
+
+    def test_keeps_the_latest_steps(self):
+        checklist = ProgressChecklist()
+        for number in range(MAX_STEPS + 2):
+            checklist.apply(ProgressEvent(str(number), StepKind.SEARCH, f"pattern {number}"))
+        checklist.apply(ProgressEvent("0", status=StepStatus.FAILED))
+        checklist.apply(ProgressEvent("late", StepKind.EDIT, "c.py", StepStatus.DONE))
+
+        lines = checklist.render()
+
+        assert lines[0] == "… 3 earlier step(s), 1 failed"
+        assert lines[1] == "… Search `pattern 3`"
+        assert lines[-1] == "✓ Edit `c.py`"
+        assert len(lines) == MAX_STEPS + 1
+        assert checklist.counts()[StepStatus.RUNNING] == MAX_STEPS + 1
@@ -0,0 +1,87 @@
+"""Tests for project-defined prompt commands in `.cockpit/commands`."""
+
+from __future__ import annotations
+
+import pytest
+
+from src.core.errors import CommandNotFound, ConfigError
+from src.core.models import RunOverrides
+from src.core.project_commands import (
+    describe_overrides,
+    list_project_commands,
+    load_project_command,
+    parse_command_file,
+    render_prompt,
+)
+
+
+def _write_command(project_path, name: str, text: str) -> None:
+    directory = project_path / ".cockpit" / "commands"
+    directory.mkdir(parents=True, exist_ok=True)
+    (directory / f"{name}.md").write_text(text)
+
+
+class TestProjectCommands:
+    """Test cases for loading and rendering project commands."""
+
+    def test_parses_frontmatter_overrides(self):
+        command = parse_command_file(
+            "refactor",
+            "---\ndescription: Bigger changes\nmodel: opus\ntemperature: 0\nreasoning_effort: High\n---\nGo.\n",
+        )
+
+        assert command.description == "Bigger changes"
+        assert command.body == "Go."
+        assert command.overrides == RunOverrides(model="opus", temperature=0.0, reasoning_effort="high")
+        assert describe_overrides(command.overrides) == "model `opus`, temperature 0, reasoning effort high"
+
+    def test_without_frontmatter(self):
+        command = parse_command_file("typo", "Fix the typo in\n")
+
+        assert command.overrides == RunOverrides()
+        assert describe_overrides(command.overrides) is None
+        assert render_prompt(command, "README.md") == "Fix the typo in\n\nREADME.md"
+
+    def test_argument_hint_describes_the_arguments(self):
+        command = parse_command_file("explain", "---\nargument_hint: module path\n---\nExplain $ARGUMENTS.")
+        default = parse_command_file("typo", "Fix the typo in")
+
+        assert [(arg.name, arg.arg_type, arg.description) for arg in command.args] == [
+            ("arguments", "text", "module path")
+        ]
+        assert default.args[0].description == "Text for the prompt's $ARGUMENTS"
+
+    def test_arguments_placeholder(self):
+        command = parse_command_file("explain", "Explain $ARGUMENTS in two sentences.")
+
+        assert render_prompt(command, "the router") == "Explain the router in two sentences."
+
+    @pytest.mark.parametrize(
+        "text, message",
+        [
+            ("---\nmodel: opus\nGo.\n", "unterminated frontmatter"),
+            ("---\ntemperature: 3\n---\nGo.\n", "temperature must be a number from 0 to 2"),
+            ("---\nreasoning_effort: extreme\n---\nGo.\n", "reasoning_effort must be one of minimal, low"),
+            ("---\nagent: codex\n---\nGo.\n", "unsupported frontmatter keys agent"),
+            ("---\nmodel: opus\n---\n\n", "has no prompt"),
+        ],
+    )
+    def test_rejects_invalid_files(self, text, message):
+        with pytest.raises(ConfigError, match=message):
+            parse_command_file("bad", text)
+
+    def test_lists_valid_commands(self, tmp_path):
+        _write_command(tmp_path, "typo", "Fix typos.")
+        _write_command(tmp_path, "broken", "---\ntemperature: hot\n---\nGo.")
+        _write_command(tmp_path, "Not A Name", "Ignored.")
+
+        assert [command.id for command in list_project_commands(tmp_path)] == ["typo"]
+        assert list_project_commands(tmp_path / "missing") == []
+
+    def test_unknown_command(self, tmp_path):
+        _write_command(tmp_path, "typo", "Fix typos.")
+
+        with pytest.raises(CommandNotFound):
+            load_project_command(tmp_path, "refactor")
+        with pytest.raises(CommandNotFound):
+            load_project_command(tmp_path, "../typo")
@@ -2,13 +2,16 @@
 
 from __future__ import annotations
 
+import subprocess
 from pathlib import Path
 from unittest.mock import AsyncMock, MagicMock, patch
 
 import pytest
+import yaml
 
-from src.core.config import Config
+from src.core.config import Config, _load_projects
 from src.core.errors import (
+    ConfigError,
     GitHubError,
     LocalDirNotGitRepoError,
     ProjectCreationError,
@@ -157,6 +160,128 @@ class TestExistingDirectoryHandling:
             await service.create_project(request)
 
 
+class TestRegisterExistingRepo:
+    """Tests for registering a repository outside base_dir."""
+
+    @pytest.mark.asyncio
+    async def test_reads_github_settings_from_origin(self, test_config, mock_github_manager, tmp_path):
+        """The origin remote provides owner, repo, and base branch; the path is stored as-is."""
+        service = ProjectCreationService(test_config, mock_github_manager)
+        repo = tmp_path / "code" / "app"
+        repo.mkdir(parents=True)
+
+        async def mock_git(cwd, args):
+            if "--show-toplevel" in args:
+                return str(repo)
+            if "get-url" in args:
+                return "https://github.com/acme/app.git"
+            if "symbolic-ref" in args:
+                return "origin/develop"
+            return ""
+
+        with patch.object(service, "_run_git", side_effect=mock_git):
+            project = await service.register_existing_repo(
+                ProjectCreationRequest(project_id="app-dev", channel_name="app-dev"), repo
+            )
+
+        assert project.id == "app-dev"
+        assert project.github == GitHubRepoConfig(owner="acme", repo="app", default_base_branch="develop")
+        data = yaml.safe_load((test_config.config_dir / "projects.yaml").read_text())
+        assert data["projects"]["app-dev"]["path"] == str(repo)
+
+    @pytest.mark.asyncio
+    async def test_without_github_remote_is_local_only(self, test_config, mock_github_manager, tmp_path):
+        """Repositories without a GitHub origin get no github section."""
+        service = ProjectCreationService(test_config, mock_github_manager)
+        repo = tmp_path / "local-app"
+        repo.mkdir()
+        subprocess.run(["git", "init", "-q"], cwd=repo, check=True)
+
+        project = await service.register_existing_repo(
+            ProjectCreationRequest(project_id="local-app", channel_name="local-app"), repo
+        )
+
+        assert project.github is None
+        data = yaml.safe_load((test_config.config_dir / "projects.yaml").read_text())
+        assert "github" not in data["projects"]["local-app"]
+
+    @pytest.mark.asyncio
+    async def test_not_a_git_repo_raises(self, test_config, mock_github_manager, tmp_path):
+        """Plain directories are rejected."""
+        service = ProjectCreationService(test_config, mock_github_manager)
+        plain = tmp_path / "plain"
+        plain.mkdir()
+
+        with pytest.raises(LocalDirNotGitRepoError):
+            await service.register_existing_repo(
+                ProjectCreationRequest(project_id="plain", channel_name="plain"), plain
+            )
+
+
+class TestLinkChannel:
+    """Tests for connecting another channel to a configured project."""
+
+    def _config_with_project(self, test_config):
+        (test_config.base_dir / "api").mkdir()
+        (test_config.config_dir / "projects.yaml").write_text(
+            f'base_dir: "{test_config.base_dir}"\n'
+            "projects:\n  api:\n    path: api\n    default_agent: claude\n"
+        )
+        projects, _ = _load_projects(test_config.config_dir / "projects.yaml")
+        test_config.projects = projects
+        return test_config
+
+    def test_adds_channel_to_project(self, test_config, mock_github_manager):
+        """The channel is appended to the project's `channels` and maps to it on reload."""
+        config = self._config_with_project(test_config)
+        service = ProjectCreationService(config, mock_github_manager)
+
+        service.link_channel("api", "api-dev")
+
+        projects, _ = _load_projects(config.config_dir / "projects.yaml")
+        assert projects["api"].channels == ["api-dev"]
+        config.projects = projects
+        assert config.get_project_by_channel("api-dev").id == "api"
+
+    def test_channel_already_connected_raises(self, test_config, mock_github_manager):
+        """A channel that already maps to a project cannot be linked again."""
+        config = self._config_with_project(test_config)
+        service = ProjectCreationService(config, mock_github_manager)
+
+        with pytest.raises(ProjectCreationError, match="already connected"):
+            service.link_channel("api", "api")
+
+
+class TestProjectChannels:
+    """Tests for the `channels` key in projects.yaml."""
+
+    def test_channels_cannot_repeat_across_projects(self, tmp_path):
+        """Two projects listing the same channel is a config error."""
+        projects_yaml = tmp_path / "projects.yaml"
+        projects_yaml.write_text(
+            f"base_dir: {tmp_path}\n"
+            "projects:\n"
+            "  api:\n    path: .\n    default_agent: claude\n    channels: [dev]\n"
+            "  web:\n    path: .\n    default_agent: claude\n    channels: [dev]\n"
+        )
+
+        with pytest.raises(ConfigError, match="listed by both"):
+            _load_projects(projects_yaml)
+
+    def test_channel_cannot_shadow_project_id(self, tmp_path):
+        """A project cannot claim another project's channel."""
+        projects_yaml = tmp_path / "projects.yaml"
+        projects_yaml.write_text(
+            f"base_dir: {tmp_path}\n"
+            "projects:\n"
+            "  api:\n    path: .\n    default_agent: claude\n    channels: [web]\n"
+            "  web:\n    path: .\n    default_agent: claude\n"
+        )
+
+        with pytest.raises(ConfigError, match="another project's id"):
+            _load_projects(projects_yaml)
+
+
 class TestGitHubRepoCreation:
     """Tests for GitHub repository creation."""
 
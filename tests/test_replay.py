@@ -0,0 +1,138 @@
+"""Tests for replaying a session's requests against another agent."""
+
+from __future__ import annotations
+
+
+import pytest
+
+from src.agent_adapters import AgentResult
+from src.core.agent_runner import START_COMMIT_CONTEXT_KEY
+from src.core.conversation import InteractionClassifier, SessionManager
+from src.core.git_workflow import session_branch_name
+from src.core.models import Agent, AgentType, ConversationMessage, Project, WorkingDirMode
+from src.core.replay import ReplayError, format_replay, format_replay_turn, replay_turns, run_replay
+from tests.conftest import git
+
+
+def _session(repo, manager: SessionManager, prompts):
+    project = Project(id="demo", channel_name="demo", path=repo, default_agent_id="claude")
+    session = manager.create_session(
+        project=project, channel_id="C1", thread_ts="1", agent_id="claude", agent_type=AgentType.CLAUDE
+    )
+    for prompt in prompts:
+        manager.append_interaction(
+            session.id,
+            user_message=ConversationMessage(role="user", content=prompt),
+            agent_result=AgentResult(success=True, output_text=f"claude did: {prompt}"),
+            classifier=InteractionClassifier(),
+        )
+    return session
+
+
+AGENT = Agent(id="codex", type=AgentType.CODEX, command=["codex"], working_dir_mode=WorkingDirMode.PROJECT)
+
+
+class TestRunReplay:
+    """Test cases for run_replay."""
+
+    @pytest.mark.asyncio
+    async def test_replays_turns_in_order_from_start_commit(self, git_repo):
+        """Each turn runs in one worktree with the replayed conversation so far as context."""
+        start = git(git_repo, "rev-parse", "HEAD").strip()
+        manager = SessionManager()
+        session = _session(git_repo, manager, ["Add a.txt", "Add b.txt"])
+        session.session_context[START_COMMIT_CONTEXT_KEY] = start
+        # The session moved on after it started; the replay must not see this commit
+        (git_repo / "later.txt").write_text("later\n")
+        git(git_repo, "add", "-A")
+        git(git_repo, "commit", "-q", "-m", "later")
+        calls = []
+
+        async def run_agent(agent, model, workdir, prompt, context):
+            calls.append((prompt, context, sorted(path.name for path in workdir.iterdir() if path.name != ".git")))
+            name = prompt.split()[-1]
+            (workdir / name).write_text(f"{name}\n")
+            return AgentResult(success=True, output_text=f"codex wrote {name}"), 0.1
+
+        result = await run_replay(session, AGENT, "base", run_agent)
+
+        assert result.base == start
+        assert [call[0] for call in calls] == ["Add a.txt", "Add b.txt"]
+        assert calls[0][1] == ""
+        assert calls[0][2] == ["README.md"]
+        assert "USER:\nAdd a.txt\nAGENT:\ncodex wrote a.txt" in calls[1][1]
+        assert "claude did" not in calls[1][1]
+        assert calls[1][2] == ["README.md", "a.txt"]
+        assert sorted(file_diff.path for file_diff in result.files) == ["a.txt", "b.txt"]
+        assert result.cost_usd == pytest.approx(0.2)
+        assert [turn.original_reply for turn in result.turns] == ["claude did: Add a.txt", "claude did: Add b.txt"]
+        assert result.original_diff_text is None
+        assert git(git_repo, "worktree", "list").count("\n") == 1
+
+    @pytest.mark.asyncio
+    async def test_compares_with_session_branch(self, git_repo):
+        """The original session's changes come from its branch."""
+        manager = SessionManager()
+        session = _session(git_repo, manager, ["Edit the README"])
+        session.session_context[START_COMMIT_CONTEXT_KEY] = git(git_repo, "rev-parse", "HEAD").strip()
+        git(git_repo, "checkout", "-q", "-b", session_branch_name(session.id))
+        (git_repo / "README.md").write_text("hello\nworld\n")
+        git(git_repo, "commit", "-q", "-am", "edit")
+
+        async def run_agent(agent, model, workdir, prompt, context):
+            return AgentResult(success=True, output_text="nothing to do"), None
+
+        result = await run_replay(session, AGENT, None, run_agent)
+
+        assert [file_diff.path for file_diff in result.files] == []
+        assert "README.md" in result.original_diff_text
+        assert result.cost_usd is None
+        summary = format_replay(result)
+        assert "• Replay: 1 turn(s), 0 with errors, 0 file(s), +0 -0" in summary
+        assert "• Original: 1 file(s), +1 -0" in summary
+
+    @pytest.mark.asyncio
+    async def test_failed_turn_is_recorded_and_replay_continues(self, git_repo):
+        """A turn that raises keeps its error; later turns still run."""
+        manager = SessionManager()
+        session = _session(git_repo, manager, ["first", "second"])
+        prompts = []
+
+        async def run_agent(agent, model, workdir, prompt, context):
+            prompts.append(prompt)
+            if prompt == "first":
+                raise RuntimeError("codex CLI not found")
+            return AgentResult(success=True, output_text="ok"), 0.05
+
+        result = await run_replay(session, AGENT, None, run_agent)
+
+        assert prompts == ["first", "second"]
+        assert result.turns[0].errors == ["codex CLI not found"]
+        assert result.cost_usd is None
+        assert format_replay_turn(result.turns[0], 2) == "Turn 1/2: 1 error(s) in 0s, n/a"
+        assert "error: codex CLI not found" in format_replay(result)
+
+    @pytest.mark.asyncio
+    async def test_session_without_requests_raises(self, git_repo):
+        manager = SessionManager()
+        session = _session(git_repo, manager, [])
+
+        async def run_agent(agent, model, workdir, prompt, context):
+            raise AssertionError("should not run")
+
+        with pytest.raises(ReplayError):
+            await run_replay(session, AGENT, None, run_agent)
+
+
+class TestReplayTurns:
+    """Test cases for replay_turns."""
+
+    def test_reports_trimmed_turns(self, git_repo):
+        """Turns dropped from memory are counted so the summary can say so."""
+        manager = SessionManager(max_interactions=2)
+        session = _session(git_repo, manager, ["one", "two", "three"])
+
+        turns, skipped = replay_turns(session)
+
+        assert [turn.prompt for turn in turns] == ["two", "three"]
+        assert skipped == 1
@@ -0,0 +1,129 @@
+"""Tests for low-memory mode and resource settings."""
+
+from __future__ import annotations
+
+import json
+
+import pytest
+
+from src.agent_adapters.base import AgentResult
+from src.core.config import _load_bool_env
+from src.core.conversation import InteractionClassifier, SessionManager
+from src.core.errors import ConfigError
+from src.core.models import AgentType, ConversationMessage, Project
+from src.core.resources import (
+    BoundedCache,
+    MemoryUsage,
+    ResourceSettings,
+    format_memory_usage,
+    memory_usage,
+)
+from src.core.transcripts import TranscriptStore
+
+
+def _session(manager: SessionManager, tmp_path):
+    project = Project(id="demo", channel_name="demo", path=tmp_path, default_agent_id="claude")
+    return manager.create_session(
+        project=project, channel_id="C1", thread_ts="1", agent_id="claude", agent_type=AgentType.CLAUDE
+    )
+
+
+def _add_interactions(manager: SessionManager, session_id, count: int) -> None:
+    classifier = InteractionClassifier()
+    for number in range(1, count + 1):
+        manager.append_interaction(
+            session_id,
+            user_message=ConversationMessage(role="user", content=f"Add feature {number}"),
+            agent_result=AgentResult(success=True, output_text=f"Added feature {number} to the project"),
+            classifier=classifier,
+        )
+
+
+class TestBoundedCache:
+    """Test cases for BoundedCache."""
+
+    def test_drops_least_recently_used(self):
+        cache: BoundedCache[str, int] = BoundedCache(2)
+        cache["a"] = 1
+        cache["b"] = 2
+        assert cache["a"] == 1
+        cache["c"] = 3
+
+        assert "b" not in cache
+        assert list(cache) == ["a", "c"]
+
+    def test_unbounded_when_zero(self):
+        cache: BoundedCache[int, int] = BoundedCache()
+        for key in range(100):
+            cache[key] = key
+
+        assert len(cache) == 100
+        assert cache.get(500, -1) == -1
+
+
+class TestLowMemorySessions:
+    """SessionManager limits used by low-memory mode."""
+
+    def test_transcript_keeps_messages_beyond_history_limit(self, tmp_path):
+        writer = TranscriptStore(tmp_path / "transcripts")
+        manager = SessionManager(2, transcript=writer)
+        session = _session(manager, tmp_path)
+
+        for text in ("one", "two", "three"):
+            manager.append_user_message(session.id, text)
+
+        assert [message.content for message in manager.get_conversation_history(session.id)] == ["two", "three"]
+        lines = writer.path_for(session.id).read_text().splitlines()
+        assert [json.loads(line)["content"] for line in lines] == ["one", "two", "three"]
+        assert writer.path_for(session.id).stat().st_mode & 0o777 == 0o600
+
+    def test_interactions_are_trimmed_and_summary_kept(self, tmp_path):
+        manager = SessionManager(max_interactions=10)
+        session = _session(manager, tmp_path)
+
+        _add_interactions(manager, session.id, 12)
+
+        assert [item.interaction_number for item in session.interactions] == list(range(3, 13))
+        assert session.summary_interaction_count == 3
+        context = manager.get_context_for_agent(session.id)
+        assert context.startswith("### SUMMARY BEFORE THESE MESSAGES")
+        assert "USER:\nAdd feature 6\n" in context
+        assert "USER:\nAdd feature 5\n" not in context
+
+    def test_summarization_can_be_disabled(self, tmp_path):
+        manager = SessionManager(summarization=False)
+        session = _session(manager, tmp_path)
+
+        _add_interactions(manager, session.id, 10)
+
+        assert session.conversation_summary is None
+        assert not manager.should_summarize(session.id)
+
+
+class TestResourceSettings:
+    """Test cases for loading and reporting resource settings."""
+
+    def test_low_memory_mode(self, tmp_path):
+        settings = ResourceSettings.for_mode(low_memory=True, config_dir=tmp_path, indexing=False)
+
+        assert settings.history_limit < ResourceSettings().history_limit
+        assert settings.cache_size > 0
+        assert settings.transcript_dir == tmp_path / "transcripts"
+        assert not settings.indexing
+
+    def test_bool_env(self, monkeypatch):
+        monkeypatch.setenv("REMOTE_CODER_LOW_MEMORY", "yes")
+        assert _load_bool_env("REMOTE_CODER_LOW_MEMORY")
+        monkeypatch.setenv("REMOTE_CODER_LOW_MEMORY", "maybe")
+        with pytest.raises(ConfigError, match="REMOTE_CODER_LOW_MEMORY must be a boolean"):
+            _load_bool_env("REMOTE_CODER_LOW_MEMORY")
+
+    def test_format_memory_usage(self, tmp_path):
+        usage = MemoryUsage(rss_bytes=80 * 1024 * 1024, peak_rss_bytes=96 * 1024 * 1024)
+        settings = ResourceSettings.for_mode(low_memory=True, config_dir=tmp_path, summarization=False)
+
+        assert format_memory_usage(usage, settings) == (
+            "Memory: 80.0 MB resident (peak 96.0 MB); "
+            "low-memory mode (history 6 messages, 10 interactions, caches 32 entries); disabled: summarization"
+        )
+        assert memory_usage().peak_rss_bytes
@@ -2,14 +2,57 @@
 
 from __future__ import annotations
 
+import asyncio
+import hashlib
+import hmac
+import json
+import os
+import subprocess
+import sys
+from datetime import datetime, timedelta, timezone
 from typing import Any, Dict
 from unittest.mock import AsyncMock
 
 import pytest
 
+from src.agent_adapters.base import AgentResult, emit_output, spawn_cli
+from src.chat_adapters.health import ConnectionHealth
+from src.chat_adapters.i_chat_adapter import ChatCapabilities
+from src.core.agent_runner import CANCELLED_REPLY, FEEDBACK_NOTE
+from src.core.analytics import BY_AGENT, AnalyticsStore
+from src.core.commands.move import MOVED_AWAY_NOTE, MOVED_HERE_NOTE
 from src.core.config import Config
-from src.core.models import Agent, AgentType, Project, WorkingDirMode, GitHubRepoConfig
+from src.core.conversation import MessageIntent
+from src.core.discussions import DESIGN_NOTE, IMPLEMENT_PROMPT, PHASE_CONTEXT_KEY
+from src.core.errors import SessionNotFound
+from src.core.heartbeat import MAX_POSTS
+from src.core.idle import EXPIRED_NOTE
+from src.core.pipeline import PLAN_NOTE, PLANNED_NOTE
+from src.core.resources import ResourceSettings
+from src.core.preflight import PREFLIGHT_BLOCKED_CONTEXT_KEY, PreflightIssue
+from src.core.models import (
+    Agent,
+    AgentCapabilities,
+    AgentPricing,
+    AgentSelection,
+    AgentType,
+    CommandStep,
+    CostTier,
+    GitHubRepoConfig,
+    PipelineStep,
+    Project,
+    ProjectRoles,
+    PullRequestRef,
+    SessionStatus,
+    WebhookConfig,
+    WorkingDirMode,
+)
 from src.core.router import Router
+from src.core.run_journal import JournalEntry, RunJournal
+from src.core.shutdown import NOT_STARTING_NOTE, PARK_COMMIT_MESSAGE, RESTART_NOTE
+from src.core.takeover import LOCKED_NOTE, TAKEN_OVER_NOTE
+from src.core.timeline import load_timeline
+from src.core.transcripts import TranscriptKind, TranscriptStore
 from src.core.conversation.session_manager import SessionManager
 
 
@@ -79,6 +122,7 @@ def router_setup(tmp_path):
     github_manager = StubGitHubManager()
     router = Router(session_manager, config, github_manager, config_root=tmp_path)
     router._git_workflow.setup_session_branch = AsyncMock(return_value=None)  # type: ignore[attr-defined]
+    router._git_workflow.preflight = AsyncMock(return_value=[])  # type: ignore[attr-defined]
     router._git_workflow.maybe_publish_code_changes = AsyncMock(return_value=None)  # type: ignore[attr-defined]
     router._agent_runner.run = AsyncMock()  # type: ignore[attr-defined]
     router._maintenance_commands._config_loader = lambda: router._config  # type: ignore[attr-defined]
@@ -127,6 +171,209 @@ async def test_status_command_flow(router_setup):
     assert any("Session ID" in msg["text"] for msg in adapter.messages)
 
 
+@pytest.mark.asyncio
+async def test_status_system_reports_adapter_health(router_setup):
+    router, adapter = router_setup
+    health = ConnectionHealth(clock=lambda: 1000.0)
+    health.record_connect_attempt()
+    health.record_connected()
+    adapter.connection_health = health.snapshot  # type: ignore[attr-defined]
+
+    event = {
+        "channel": "C123",
+        "channel_name": "test-channel",
+        "text": "!status --system",
+        "ts": "777.888",
+    }
+    await router.handle_message(event)
+
+    output = adapter.messages[-1]["text"]
+    assert "Active agent runs: 0" in output
+    assert "Chat connection: connected" in output
+    assert "Since startup: 1 session(s) started, 0 ended or archived; 0 run(s)" in output
+
+
+@pytest.mark.asyncio
+async def test_post_diff_falls_back_to_per_file_messages(router_setup):
+    router, adapter = router_setup
+    diff = (
+        "diff --git a/a.txt b/a.txt\n--- a/a.txt\n+++ b/a.txt\n@@ -1 +1 @@\n-old\n+new\n"
+        "diff --git a/b.txt b/b.txt\n--- a/b.txt\n+++ b/b.txt\n@@ -1 +1 @@\n-x\n+y\n"
+    )
+
+    await router._post_diff("C123", "1.0", diff)
+
+    texts = [msg["text"] for msg in adapter.messages]
+    assert texts[0] == "*Changes:* 2 files, +2 -2"
+    assert texts[1].startswith("`a.txt`") and "```diff" in texts[1]
+    assert texts[2].startswith("`b.txt`")
+
+
+class SmallChatAdapter(DummyChatAdapter):
+    """A chat without threads, buttons, or uploads that takes short messages."""
+
+    capabilities = ChatCapabilities(max_message_chars=60, supports_threads=False)
+
+    async def send_diff(self, *args: Any) -> None:
+        raise AssertionError("this chat has no buttons")
+
+    async def upload_file(self, *args: Any) -> None:
+        raise AssertionError("this chat cannot upload files")
+
+
+@pytest.mark.asyncio
+async def test_messages_follow_the_chat_capabilities(router_setup):
+    router, _ = router_setup
+    adapter = SmallChatAdapter()
+    router.bind_adapter(adapter)
+    diff = "diff --git a/a.txt b/a.txt\n--- a/a.txt\n+++ b/a.txt\n@@ -1 +1 @@\n" + "+new line\n" * 20
+
+    ts = await router._send_message("C123", "1.0", "\n".join(f"line {n}" for n in range(20)))
+    await router._post_diff("C123", "1.0", diff)
+
+    assert ts == "None.1"
+    assert all(msg["thread_ts"] is None and len(msg["text"]) <= 60 for msg in adapter.messages)
+    assert "`a.txt`" in adapter.messages[-2]["text"] + adapter.messages[-1]["text"]
+    assert await router._upload_file("C123", "1.0", "a.patch", "diff", "patch") is False
+
+
+@pytest.mark.asyncio
+async def test_auto_selection_switches_agent_for_images(router_setup):
+    router, adapter = router_setup
+    project = router._config.get_project("test-project")
+    project.agent_selection = AgentSelection.AUTO
+    project.languages = ["python"]
+    router._config.agents["vision"] = Agent(
+        id="vision",
+        type=AgentType.CODEX,
+        command=["echo"],
+        working_dir_mode=WorkingDirMode.PROJECT,
+        models={"default": "base"},
+        capabilities=AgentCapabilities(supports_images=True),
+    )
+    await router.handle_message({"channel": "C123", "channel_name": "test-channel", "text": "hi", "ts": "9.0"})
+
+    await router.handle_message(
+        {
+            "channel": "C123",
+            "channel_name": "test-channel",
+            "text": "what is in this screenshot?",
+            "thread_ts": "9.0",
+            "files": [{"name": "shot.png", "mimetype": "image/png"}],
+        }
+    )
+
+    session = router._session_manager.get_by_thread("C123", "9.0")
+    assert session.active_agent_id == "vision"
+    assert session.active_model == "base"
+    assert any("Auto-selected `vision`" in msg["text"] for msg in adapter.messages)
+    router._agent_runner.run.assert_awaited_once()
+
+
+@pytest.mark.asyncio
+async def test_auto_selection_respects_pinned_agent(router_setup):
+    router, adapter = router_setup
+    project = router._config.get_project("test-project")
+    project.agent_selection = AgentSelection.AUTO
+    await router.handle_message({"channel": "C123", "channel_name": "test-channel", "text": "hi", "ts": "10.0"})
+    session = router._session_manager.get_by_thread("C123", "10.0")
+    router._session_manager.set_agent_pinned(session.id, True)
+    router._config.agents["vision"] = Agent(
+        id="vision",
+        type=AgentType.CODEX,
+        command=["echo"],
+        working_dir_mode=WorkingDirMode.PROJECT,
+        capabilities=AgentCapabilities(supports_images=True),
+    )
+
+    await router.handle_message(
+        {
+            "channel": "C123",
+            "channel_name": "test-channel",
+            "text": "look at this",
+            "thread_ts": "10.0",
+            "files": [{"name": "shot.png", "mimetype": "image/png"}],
+        }
+    )
+
+    assert session.active_agent_id == "claude"
+
+
+@pytest.mark.asyncio
+async def test_auto_selection_skips_disallowed_agents(router_setup):
+    router, adapter = router_setup
+    project = router._config.get_project("test-project")
+    project.agent_selection = AgentSelection.AUTO
+    project.allowed_agents = ["claude"]
+    router._config.agents["vision"] = Agent(
+        id="vision",
+        type=AgentType.CODEX,
+        command=["echo"],
+        working_dir_mode=WorkingDirMode.PROJECT,
+        capabilities=AgentCapabilities(supports_images=True),
+    )
+    await router.handle_message({"channel": "C123", "channel_name": "test-channel", "text": "hi", "ts": "11.0"})
+
+    await router.handle_message(
+        {
+            "channel": "C123",
+            "channel_name": "test-channel",
+            "text": "look at this",
+            "thread_ts": "11.0",
+            "files": [{"name": "shot.png", "mimetype": "image/png"}],
+        }
+    )
+
+    assert router._session_manager.get_by_thread("C123", "11.0").active_agent_id == "claude"
+
+
+@pytest.mark.asyncio
+async def test_disallowed_active_agent_is_reset(router_setup):
+    router, adapter = router_setup
+    project = router._config.get_project("test-project")
+    project.agent_models = {"claude": "haiku"}
+    router._config.agents["codex"] = Agent(
+        id="codex", type=AgentType.CODEX, command=["echo"], working_dir_mode=WorkingDirMode.PROJECT
+    )
+    await router.handle_message({"channel": "C123", "channel_name": "test-channel", "text": "hi", "ts": "12.0"})
+    session = router._session_manager.get_by_thread("C123", "12.0")
+    assert session.active_model == "haiku"
+    router._session_manager.set_active_agent(session.id, "codex", AgentType.CODEX)
+    project.allowed_agents = ["claude"]
+
+    await router.handle_message(
+        {"channel": "C123", "channel_name": "test-channel", "text": "continue", "thread_ts": "12.0"}
+    )
+
+    assert session.active_agent_id == "claude"
+    assert session.active_model == "haiku"
+    assert any("`codex` is no longer allowed" in msg["text"] for msg in adapter.messages)
+    router._agent_runner.run.assert_awaited_once()
+
+
+@pytest.mark.asyncio
+async def test_preflight_issues_hold_the_session_until_fixed(router_setup):
+    router, adapter = router_setup
+    router._git_workflow.preflight.return_value = [
+        PreflightIssue("The checkout is in the middle of a merge.", "Run `git merge --abort`.", blocking=True)
+    ]
+    await router.handle_message({"channel": "C123", "channel_name": "test-channel", "text": "hi", "ts": "13.0"})
+    await router.handle_message({"channel": "C123", "channel_name": "test-channel", "text": "go", "thread_ts": "13.0"})
+
+    reports = [msg["text"] for msg in adapter.messages if msg["text"].startswith("Repository check")]
+    assert len(reports) == 2 and "*blocking* The checkout is in the middle of a merge." in reports[0]
+    router._git_workflow.setup_session_branch.assert_not_awaited()
+    router._agent_runner.run.assert_not_awaited()
+
+    router._git_workflow.preflight.return_value = []
+    await router.handle_message({"channel": "C123", "channel_name": "test-channel", "text": "go", "thread_ts": "13.0"})
+
+    router._git_workflow.setup_session_branch.assert_awaited_once()
+    router._agent_runner.run.assert_awaited_once()
+    session = router._session_manager.get_by_thread("C123", "13.0")
+    assert not session.session_context.get(PREFLIGHT_BLOCKED_CONTEXT_KEY)
+
+
 @pytest.mark.asyncio
 async def test_unknown_command(router_setup):
     router, adapter = router_setup
@@ -141,3 +388,1455 @@ async def test_unknown_command(router_setup):
     await router.handle_message(event)
 
     assert any("Unknown command" in msg["text"] for msg in adapter.messages)
+
+
+@pytest.mark.asyncio
+async def test_paused_sessions_run_nothing_until_resumed(router_setup):
+    router, adapter = router_setup
+
+    async def say(text: str) -> None:
+        event = {"channel": "C123", "channel_name": "test-channel", "text": text, "thread_ts": "13.5"}
+        await router.handle_message(event)
+
+    await router.handle_message({"channel": "C123", "channel_name": "test-channel", "text": "hi", "ts": "13.5"})
+    await say("!pause")
+    await say("go")
+
+    assert adapter.messages[-1]["text"] == "This session is paused. Send `!resume` to carry on."
+    router._agent_runner.run.assert_not_awaited()
+    await say("!resume")
+    await say("go")
+    router._agent_runner.run.assert_awaited_once()
+
+
+@pytest.mark.asyncio
+async def test_locked_projects_refuse_new_threads_until_taken_over(router_setup):
+    router, adapter = router_setup
+    project = router._config.get_project("test-project")
+    project.max_active_sessions = 1
+
+    await router.handle_message({"channel": "C123", "channel_name": "test-channel", "text": "hi", "ts": "14.0"})
+    first = router._session_manager.get_by_thread("C123", "14.0")
+    await router.handle_message({"channel": "C123", "channel_name": "test-channel", "text": "go", "ts": "15.0"})
+
+    assert adapter.messages[-1]["text"] == LOCKED_NOTE.format(
+        project="test-project", count=1, threads="thread `14.0`", ending="it"
+    )
+    with pytest.raises(SessionNotFound):
+        router._session_manager.get_by_thread("C123", "15.0")
+
+    await router.handle_message({"channel": "C123", "channel_name": "test-channel", "text": "!takeover", "ts": "15.0"})
+
+    assert first.status == SessionStatus.ENDED
+    ended_notes = [msg["text"] for msg in adapter.messages if msg["thread_ts"] == "14.0"]
+    assert ended_notes[-1] == TAKEN_OVER_NOTE.format(thread="another thread")
+    assert router._session_manager.get_by_thread("C123", "15.0").status == SessionStatus.ACTIVE
+    router._agent_runner.run.assert_not_awaited()
+
+
+@pytest.mark.asyncio
+async def test_move_takes_the_session_to_a_new_thread(router_setup):
+    router, adapter = router_setup
+    thread = {"channel": "C123", "channel_name": "test-channel", "thread_ts": "18.0"}
+    await router.handle_message({"channel": "C123", "channel_name": "test-channel", "text": "hi", "ts": "18.0"})
+    session = router._session_manager.get_by_thread("C123", "18.0")
+    router._session_manager.append_user_message(session.id, "add a cache")
+
+    await router.handle_message({**thread, "text": "!move"})
+
+    started = next(msg for msg in adapter.messages if msg["thread_ts"] is None)
+    assert started["text"] == MOVED_HERE_NOTE.format(old="thread `18.0`")
+    new_ts = router._session_manager.get_session(session.id).thread_ts
+    assert adapter.messages[-1] == {
+        "channel": "C123",
+        "thread_ts": "18.0",
+        "text": MOVED_AWAY_NOTE.format(new=f"thread `{new_ts}`"),
+    }
+    assert router._session_manager.get_by_thread("C123", new_ts).id == session.id
+    assert [m.content for m in router._session_manager.get_conversation_history(session.id)] == ["add a cache"]
+
+    await router.handle_message({**thread, "text": "hello again"})
+    assert router._session_manager.get_by_thread("C123", "18.0").id != session.id
+
+
+@pytest.mark.asyncio
+async def test_mock_agent_runs_end_to_end(router_setup):
+    router, adapter = router_setup
+    del router._agent_runner.run  # use the real runner with the mock adapter
+    router._config.agents["demo"] = Agent(
+        id="demo",
+        type=AgentType.MOCK,
+        command=[],
+        working_dir_mode=WorkingDirMode.PROJECT,
+        options={"delay_secs": 0, "summary": "Canned reply"},
+    )
+    project = router._config.get_project("test-project")
+    project.default_agent_id = "demo"
+
+    await router.handle_message({"channel": "C123", "channel_name": "test-channel", "text": "hi", "ts": "12.0"})
+    await router.handle_message(
+        {"channel": "C123", "channel_name": "test-channel", "text": "add a button", "thread_ts": "12.0"}
+    )
+
+    texts = [msg["text"] for msg in adapter.messages]
+    assert "Message received — running `demo` now." in texts
+    assert texts[-1] == "Canned reply\n\nDetected file edits: MOCK_AGENT.md"
+    assert (project.path / "MOCK_AGENT.md").read_text().endswith("- add a button\n")
+    router._git_workflow.maybe_publish_code_changes.assert_awaited_once()
+
+
+@pytest.mark.asyncio
+async def test_chatter_does_not_run_the_agent(router_setup):
+    router, adapter = router_setup
+    del router._agent_runner.run  # use the real runner with the mock adapter
+    router._config.agents["demo"] = Agent(
+        id="demo",
+        type=AgentType.MOCK,
+        command=[],
+        working_dir_mode=WorkingDirMode.PROJECT,
+        options={"delay_secs": 0, "summary": "Canned reply"},
+    )
+    project = router._config.get_project("test-project")
+    project.default_agent_id = "demo"
+    thread = {"channel": "C123", "channel_name": "test-channel", "thread_ts": "12.5"}
+
+    await router.handle_message({"channel": "C123", "channel_name": "test-channel", "text": "hi", "ts": "12.5"})
+    await router.handle_message({**thread, "text": "add a button"})
+    await router.handle_message({**thread, "text": "lgtm 👍"})
+
+    assert adapter.messages[-1]["text"] == (
+        "👍 Not running `demo` for that. Send a request when you want more changes."
+    )
+    assert (project.path / "MOCK_AGENT.md").read_text().count("- ") == 1
+
+    router._config.intent_detection = False
+    await router.handle_message({**thread, "text": "thanks!"})
+
+    assert adapter.messages[-1]["text"].startswith("Canned reply")
+
+
+@pytest.mark.asyncio
+async def test_feedback_is_marked_in_the_prompt(router_setup):
+    router, adapter = router_setup
+    router._agent_runner.run = AsyncMock()
+    thread = {"channel": "C123", "channel_name": "test-channel", "thread_ts": "12.6"}
+
+    await router.handle_message({"channel": "C123", "channel_name": "test-channel", "text": "hi", "ts": "12.6"})
+    session = router._session_manager.get_by_thread("C123", "12.6")
+    router._session_manager.append_agent_message(session.id, "Added the button.")
+    await router.handle_message({**thread, "text": "that broke the header"})
+
+    assert router._agent_runner.run.await_args.kwargs["intent"] == MessageIntent.FEEDBACK
+    task_text = router._agent_runner._build_task_text("", "that broke the header", feedback=True)
+    assert FEEDBACK_NOTE in task_text
+
+
+@pytest.mark.asyncio
+async def test_agent_output_streams_into_an_edited_message(router_setup):
+    router, adapter = router_setup
+    del router._agent_runner.run  # use the real runner with the mock adapter
+    edits: list[tuple[str, str, str]] = []
+
+    async def update_message(channel: str, ts: str, text: str) -> None:
+        edits.append((channel, ts, text))
+
+    adapter.update_message = update_message
+    router._config.agents["demo"] = Agent(
+        id="demo",
+        type=AgentType.MOCK,
+        command=[],
+        working_dir_mode=WorkingDirMode.PROJECT,
+        options={"delay_secs": 0.3, "summary": "Canned reply", "stream": ["Reading files", "Editing page"]},
+    )
+    project = router._config.get_project("test-project")
+    project.default_agent_id = "demo"
+
+    await router.handle_message({"channel": "C123", "channel_name": "test-channel", "text": "hi", "ts": "14.0"})
+    await router.handle_message(
+        {"channel": "C123", "channel_name": "test-channel", "text": "add a button", "thread_ts": "14.0"}
+    )
+
+    texts = [msg["text"] for msg in adapter.messages]
+    live = [text for text in texts if text.startswith("`demo` output (running")]
+    assert len(live) == 1
+    assert edits[-1][2] == "`demo` output (finished, 2 lines):\n```\nReading files\nEditing page\n```"
+    assert texts[-1] == "Canned reply\n\nDetected file edits: MOCK_AGENT.md"
+
+
+@pytest.mark.asyncio
+async def test_non_git_project_run_posts_snapshot_diff_and_rolls_back(router_setup):
+    router, adapter = router_setup
+    del router._agent_runner.run  # use the real runner with the mock adapter
+    router._config.agents["demo"] = Agent(
+        id="demo",
+        type=AgentType.MOCK,
+        command=[],
+        working_dir_mode=WorkingDirMode.PROJECT,
+        options={"delay_secs": 0, "summary": "Canned reply"},
+    )
+    project = router._config.get_project("test-project")
+    project.default_agent_id = "demo"
+    project.github = None
+    (project.path / "notes.md").write_text("keep me\n")
+
+    await router.handle_message({"channel": "C123", "channel_name": "test-channel", "text": "hi", "ts": "13.0"})
+    await router.handle_message(
+        {"channel": "C123", "channel_name": "test-channel", "text": "add a button", "thread_ts": "13.0"}
+    )
+
+    texts = [msg["text"] for msg in adapter.messages]
+    reply = next(text for text in texts if text.startswith("Canned reply"))
+    assert "*Changes:* 1 file, +" in reply
+    assert "`!rollback` restores it" in reply
+    assert any("`MOCK_AGENT.md` (added" in text for text in texts)
+
+    await router.handle_message(
+        {"channel": "C123", "channel_name": "test-channel", "text": "!rollback", "thread_ts": "13.0"}
+    )
+
+    assert adapter.messages[-1]["text"].startswith("Rolled the workspace back to before the last run")
+    assert not (project.path / "MOCK_AGENT.md").exists()
+    assert (project.path / "notes.md").read_text() == "keep me\n"
+
+
+@pytest.mark.asyncio
+async def test_missing_agent_cli_refuses_run(router_setup):
+    router, adapter = router_setup
+    del router._agent_runner.run  # use the real runner
+    router._config.agents["claude"].command = ["remote-coder-missing-cli"]
+
+    await router.check_agent_clis()
+    await router.handle_message({"channel": "C123", "channel_name": "test-channel", "text": "hi", "ts": "13.0"})
+    await router.handle_message(
+        {"channel": "C123", "channel_name": "test-channel", "text": "fix it", "thread_ts": "13.0"}
+    )
+
+    assert adapter.messages[-1]["text"].startswith(
+        "Cannot run `claude`: `remote-coder-missing-cli` was not found on PATH"
+    )
+
+    await router.handle_message(
+        {"channel": "C123", "channel_name": "test-channel", "text": "!status --system", "ts": "14.0"}
+    )
+    assert "• `claude`: UNAVAILABLE" in adapter.messages[-1]["text"]
+
+
+class FlakyAdapter:
+    """Raises on the first `failures` runs, then reports success."""
+
+    def __init__(self, failures: int) -> None:
+        self.failures = failures
+        self.calls = 0
+
+    async def run(self, **kwargs: Any) -> AgentResult:
+        self.calls += 1
+        if self.calls <= self.failures:
+            raise RuntimeError("boom")
+        return AgentResult(success=True, output_text="fixed")
+
+
+class ScriptAdapter:
+    """Runs a Python script as the agent CLI and streams its stdout."""
+
+    streams_output = True
+
+    def __init__(self, script: str) -> None:
+        self.script = script
+        self.pids: list[int] = []
+
+    async def run(self, *, project_path: str, on_output=None, **kwargs: Any) -> AgentResult:
+        command = [sys.executable, "-c", self.script]
+        async with spawn_cli(command, cwd=project_path, env=os.environ, stdin=False) as process:
+            self.pids.append(process.pid)
+            lines = []
+            assert process.stdout is not None
+            async for raw in process.stdout:
+                lines.append(raw.decode().rstrip())
+                emit_output(on_output, lines[-1:])
+            await process.wait()
+        return AgentResult(success=process.returncode == 0, output_text="\n".join(lines))
+
+
+# Prints a line, then sleeps; `{on_interrupt}` runs on SIGINT
+HANGING_CLI = """
+import signal, sys, time
+def stop(*_):
+    {on_interrupt}
+signal.signal(signal.SIGINT, stop)
+print("step 1 done", flush=True)
+time.sleep(30)
+"""
+
+
+async def _run_in_thread(router, text: str, ts: str) -> None:
+    await router.handle_message({"channel": "C123", "channel_name": "test-channel", "text": "hi", "ts": ts})
+    await router.handle_message({"channel": "C123", "channel_name": "test-channel", "text": text, "thread_ts": ts})
+
+
+@pytest.mark.asyncio
+async def test_agent_timeout_stops_run(router_setup):
+    router, adapter = router_setup
+    del router._agent_runner.run  # use the real runner
+    router._config.agents["demo"] = Agent(
+        id="demo",
+        type=AgentType.MOCK,
+        command=[],
+        working_dir_mode=WorkingDirMode.PROJECT,
+        options={"delay_secs": 5},
+        timeout_secs=0.05,
+    )
+    router._config.get_project("test-project").default_agent_id = "demo"
+
+    await _run_in_thread(router, "add a button", "15.0")
+
+    assert adapter.messages[-1]["text"] == "Stopped `demo`: it ran longer than its limit of 0.05s (`timeout_secs`)"
+    router._git_workflow.maybe_publish_code_changes.assert_not_awaited()
+    session = router._session_manager.get_by_thread("C123", "15.0")
+    assert session.status == SessionStatus.FAILED
+
+
+@pytest.mark.asyncio
+async def test_timed_out_runs_count_as_failed_in_analytics(router_setup, tmp_path):
+    router, _ = router_setup
+    del router._agent_runner.run  # use the real runner
+    store = AnalyticsStore(tmp_path / "analytics.db")
+    store.follow(router._session_manager.events)
+    router._config.agents["demo"] = Agent(
+        id="demo",
+        type=AgentType.MOCK,
+        command=[],
+        working_dir_mode=WorkingDirMode.PROJECT,
+        options={"delay_secs": 5},
+        timeout_secs=0.05,
+    )
+    router._config.get_project("test-project").default_agent_id = "demo"
+
+    await _run_in_thread(router, "add a button", "15.01")
+
+    [row] = store.report(BY_AGENT)
+    assert (row.key, row.runs, row.runs_succeeded, row.run_success_rate) == ("demo", 1, 0, 0.0)
+
+
+@pytest.mark.asyncio
+async def test_quiet_run_posts_a_heartbeat(router_setup):
+    router, adapter = router_setup
+    del router._agent_runner.run  # use the real runner
+    router._config.heartbeat_secs = 0.05
+    router._config.agents["demo"] = Agent(
+        id="demo",
+        type=AgentType.MOCK,
+        command=[],
+        working_dir_mode=WorkingDirMode.PROJECT,
+        options={"delay_secs": 0.2},
+    )
+    router._config.get_project("test-project").default_agent_id = "demo"
+
+    await _run_in_thread(router, "add a button", "15.2")
+
+    texts = [message["text"] for message in adapter.messages]
+    heartbeats = [text for text in texts if text.startswith("`demo` is still working, 0s elapsed")]
+    assert 1 <= len(heartbeats) <= MAX_POSTS  # this chat cannot edit, so each one is a new message
+    assert texts[-1].startswith("Mock agent handled: add a button")
+
+
+@pytest.mark.asyncio
+async def test_runs_are_recorded_in_the_session_transcript(router_setup, tmp_path):
+    router, _ = router_setup
+    del router._agent_runner.run  # use the real runner
+    router._session_manager._transcript = TranscriptStore(tmp_path / "transcripts")
+    router._config.agents["demo"] = Agent(
+        id="demo", type=AgentType.MOCK, command=[], working_dir_mode=WorkingDirMode.PROJECT
+    )
+    router._config.get_project("test-project").default_agent_id = "demo"
+
+    await _run_in_thread(router, "add a button", "15.3")
+
+    session = router._session_manager.get_by_thread("C123", "15.3")
+    prompt, reply, run = router._session_manager.transcript(session.id)[-3:]
+    assert (prompt.kind, prompt.content) == (TranscriptKind.PROMPT, "add a button")
+    assert reply.kind == TranscriptKind.REPLY and reply.content.startswith("Mock agent handled: add a button")
+    assert run.kind == TranscriptKind.RUN
+    assert (run.run.agent_id, run.run.success, run.run.cancelled) == ("demo", True, False)
+
+
+@pytest.mark.asyncio
+async def test_finished_runs_are_indexed_for_search(router_setup, tmp_path):
+    old, _ = router_setup
+    old._config.resources = ResourceSettings(transcript_dir=tmp_path / "transcripts")
+    old._config.agents["demo"] = Agent(
+        id="demo", type=AgentType.MOCK, command=[], working_dir_mode=WorkingDirMode.PROJECT
+    )
+    old._config.get_project("test-project").default_agent_id = "demo"
+    manager = SessionManager(transcript=TranscriptStore(tmp_path / "transcripts"))
+    router = Router(manager, old._config, StubGitHubManager(), config_root=tmp_path)
+    router._git_workflow.setup_session_branch = AsyncMock(return_value=None)  # type: ignore[attr-defined]
+    router._git_workflow.preflight = AsyncMock(return_value=[])  # type: ignore[attr-defined]
+    router._git_workflow.maybe_publish_code_changes = AsyncMock(return_value=None)  # type: ignore[attr-defined]
+    adapter = DummyChatAdapter()
+    router.bind_adapter(adapter)
+
+    await _run_in_thread(router, "tidy the billing webhooks", "15.4")
+    await manager.events.drain()
+    await router.handle_message(
+        {"channel": "C123", "channel_name": "test-channel", "text": "!search webhook", "thread_ts": "15.4"}
+    )
+
+    assert adapter.messages[-1]["text"].startswith(
+        "Sessions in `test-project` matching webhook (best first):\n- `test-project`"
+    )
+    assert "this thread" in adapter.messages[-1]["text"]
+    assert (tmp_path / "config" / "search.db").exists()
+    await router.close()
+
+
+@pytest.mark.asyncio
+async def test_shutdown_parks_runs_that_do_not_finish_in_time(router_setup, monkeypatch):
+    router, adapter = router_setup
+    del router._agent_runner.run  # use the real runner
+    for key in ("GIT_AUTHOR_NAME", "GIT_AUTHOR_EMAIL", "GIT_COMMITTER_NAME", "GIT_COMMITTER_EMAIL"):
+        monkeypatch.setenv(key, "test@example.com" if key.endswith("EMAIL") else "Test")
+    repo = router._config.get_project("test-project").path
+    subprocess.run(["git", "init", "-q", "-b", "main"], cwd=repo, check=True)
+    subprocess.run(["git", "commit", "-q", "--allow-empty", "-m", "initial"], cwd=repo, check=True)
+    router._config.agents["demo"] = Agent(
+        id="demo",
+        type=AgentType.MOCK,
+        command=[],
+        working_dir_mode=WorkingDirMode.PROJECT,
+        options={"delay_secs": 30},
+    )
+    router._config.get_project("test-project").default_agent_id = "demo"
+    router._config.shutdown_grace_secs = 0.1
+    run = asyncio.create_task(_run_in_thread(router, "add a button", "15.4"))
+    while not router.active_runs:
+        await asyncio.sleep(0.01)
+    (repo / "button.py").write_text("half done\n")
+
+    await router.drain()
+
+    assert run.cancelled()
+    texts = [message["text"] for message in adapter.messages]
+    assert "Remote Coder is shutting down; `demo` has up to 0s to finish this run." in texts
+    session = router._session_manager.get_by_thread("C123", "15.4")
+    log = subprocess.run(["git", "log", "-1", "--format=%h %s"], cwd=repo, capture_output=True, text=True).stdout
+    commit, subject = log.strip().split(" ", 1)
+    assert subject == PARK_COMMIT_MESSAGE
+    assert f"parked in commit `{commit}`" in texts[-1] and "`!redo`" in texts[-1]
+    assert session.conversation_history[-1].content.startswith("(Stopped by a Remote Coder shutdown")
+
+    await router.handle_message(
+        {"channel": "C123", "channel_name": "test-channel", "text": "one more", "thread_ts": "15.4"}
+    )
+    assert adapter.messages[-1]["text"] == NOT_STARTING_NOTE
+
+
+@pytest.mark.asyncio
+async def test_runs_interrupted_by_a_crash_are_recovered_at_startup(router_setup):
+    router, adapter = router_setup
+    del router._agent_runner.run  # use the real runner
+    await _run_in_thread(router, "add a button", "15.3")
+    assert router._journal.entries() == []  # finished runs leave no entry behind
+    session = router._session_manager.get_by_thread("C123", "15.3")
+    project_path = router._config.get_project("test-project").path
+    crashed = RunJournal(router._journal.directory)
+    gone = subprocess.Popen(["true"])
+    gone.wait()
+    crashed._pid, crashed._started = gone.pid, None  # written by a daemon that is gone
+    await crashed.start(
+        JournalEntry(
+            run_id="run-9",
+            session_id=str(session.id),
+            project_id="test-project",
+            channel_id="C123",
+            thread_ts="15.3",
+            agent_id="claude",
+            workdir=str(project_path),
+        )
+    )
+
+    assert await router.recover_interrupted_runs() == 1
+
+    note = adapter.messages[-1]
+    assert (note["channel"], note["thread_ts"]) == ("C123", "15.3")
+    assert note["text"].startswith("Remote Coder restarted while `claude` was working on this thread")
+    assert session.conversation_history[-1].content == note["text"]
+    assert router._journal.entries() == []
+    assert await router.recover_interrupted_runs() == 0
+    assert await router.announce_resumed_sessions() == 0  # its thread already heard about the restart
+
+
+@pytest.mark.asyncio
+async def test_recently_active_sessions_are_told_they_were_resumed(router_setup):
+    router, adapter = router_setup
+    await _run_in_thread(router, "add a button", "16.1")
+    await _run_in_thread(router, "old work", "16.2")
+    stale = router._session_manager.get_by_thread("C123", "16.2")
+    stale.updated_at -= timedelta(days=3)
+    posted = len(adapter.messages)
+
+    assert await router.announce_resumed_sessions() == 1
+
+    assert [(m["thread_ts"], m["text"]) for m in adapter.messages[posted:]] == [("16.1", RESTART_NOTE)]
+    router._config.resume_notice_hours = 0
+    assert await router.announce_resumed_sessions() == 0
+
+
+@pytest.mark.asyncio
+async def test_idle_sessions_are_ended_and_their_threads_told(router_setup):
+    router, adapter = router_setup
+    router._config.session_idle_hours = 2
+    await _run_in_thread(router, "add a button", "17.1")
+    session = router._session_manager.get_by_thread("C123", "17.1")
+
+    assert await router.expire_idle_sessions() == 0
+    assert await router.expire_idle_sessions(now=datetime.now(timezone.utc) + timedelta(hours=3)) == 1
+
+    assert session.status == SessionStatus.ENDED
+    assert adapter.messages[-1]["text"] == EXPIRED_NOTE.format(idle="2 hours")
+    await _run_in_thread(router, "one more thing", "17.1")
+    assert adapter.messages[-1]["text"].startswith("This session has ended")
+
+
+@pytest.mark.asyncio
+async def test_project_timeout_interrupts_cli_and_posts_output_so_far(router_setup):
+    router, adapter = router_setup
+    del router._agent_runner.run  # use the real runner
+    script = ScriptAdapter(HANGING_CLI.format(on_interrupt='print("saving work", flush=True); sys.exit(130)'))
+    router._adapter_cache["claude"] = script
+    project = router._config.get_project("test-project")
+    project.timeout_secs = 0.5  # replaces the agent's unlimited runs in this project
+    project.hard_timeout_secs = 10
+
+    await _run_in_thread(router, "fix it", "15.5")
+
+    expected = (
+        "Stopped `claude`: it ran longer than its limit of 0.5s (`timeout_secs`) and was interrupted\n\n"
+        "Output so far:\n```\nstep 1 done\nsaving work\n```"
+    )
+    assert adapter.messages[-1]["text"] == expected
+    session = router._session_manager.get_by_thread("C123", "15.5")
+    assert router._session_manager.get_conversation_history(session.id)[-1].content == expected
+    router._git_workflow.maybe_publish_code_changes.assert_not_awaited()
+
+
+@pytest.mark.asyncio
+async def test_agent_ignoring_interrupt_is_killed_at_hard_timeout(router_setup):
+    router, adapter = router_setup
+    del router._agent_runner.run  # use the real runner
+    script = ScriptAdapter(HANGING_CLI.format(on_interrupt="pass"))
+    router._adapter_cache["claude"] = script
+    agent = router._config.agents["claude"]
+    agent.timeout_secs = 0.5
+    agent.hard_timeout_secs = 1
+
+    await _run_in_thread(router, "fix it", "15.6")
+
+    assert adapter.messages[-1]["text"] == (
+        "Stopped `claude`: it ran longer than its limit of 0.5s (`timeout_secs`) and was killed after ignoring "
+        "the interrupt until 1s (`hard_timeout_secs`)\n\nOutput so far:\n```\nstep 1 done\n```"
+    )
+    with pytest.raises(ProcessLookupError):
+        os.kill(script.pids[0], 0)
+
+
+@pytest.mark.asyncio
+async def test_cancel_kills_the_running_agent(router_setup):
+    router, adapter = router_setup
+    del router._agent_runner.run  # use the real runner
+    script = ScriptAdapter(HANGING_CLI.format(on_interrupt="pass"))
+    router._adapter_cache["claude"] = script
+    thread = {"channel": "C123", "channel_name": "test-channel", "thread_ts": "15.7"}
+    await router.handle_message({"channel": "C123", "channel_name": "test-channel", "text": "hi", "ts": "15.7"})
+    run = asyncio.create_task(router.handle_message({**thread, "text": "fix it"}))
+    while not script.pids:
+        await asyncio.sleep(0.01)
+
+    await router.handle_message({**thread, "text": "!cancel"})
+
+    assert adapter.messages[-1]["text"].startswith("Cancelled `claude`.")
+    assert run.done()
+    with pytest.raises(ProcessLookupError):
+        os.kill(script.pids[0], 0)
+    session = router._session_manager.get_by_thread("C123", "15.7")
+    assert router._session_manager.get_conversation_history(session.id)[-1].content == CANCELLED_REPLY
+    assert router.active_runs == {}
+
+    await router.handle_message({**thread, "text": "!stop"})
+
+    assert adapter.messages[-1]["text"] == "No agent is running in this session."
+
+
+@pytest.mark.asyncio
+async def test_agent_retries_after_error(router_setup):
+    router, adapter = router_setup
+    del router._agent_runner.run  # use the real runner
+    router._config.agents["claude"].max_retries = 2
+    flaky = FlakyAdapter(failures=1)
+    router._adapter_cache["claude"] = flaky
+
+    await _run_in_thread(router, "fix it", "16.0")
+
+    texts = [msg["text"] for msg in adapter.messages]
+    assert "`claude` stopped: it failed (boom). Retrying (attempt 2 of 3)…" in texts
+    assert texts[-1] == "fixed"
+    assert flaky.calls == 2
+
+
+@pytest.mark.asyncio
+async def test_adapter_crash_leaves_the_session_failed(router_setup):
+    router, adapter = router_setup
+    del router._agent_runner.run  # use the real runner
+    router._adapter_cache["claude"] = FlakyAdapter(failures=1)
+
+    await _run_in_thread(router, "fix it", "16.1")
+
+    assert adapter.messages[-1]["text"] == "Failed to run `claude`: boom"
+    session = router._session_manager.get_by_thread("C123", "16.1")
+    assert session.status == SessionStatus.FAILED
+
+
+class OverloadedAdapter:
+    """Reports a provider overload on the first `failures` runs, then succeeds."""
+
+    def __init__(self, failures: int) -> None:
+        self.failures = failures
+        self.calls = 0
+
+    async def run(self, **kwargs: Any) -> AgentResult:
+        self.calls += 1
+        if self.calls <= self.failures:
+            return AgentResult(success=False, output_text="", errors=['API Error: 529 {"type":"overloaded_error"}'])
+        return AgentResult(success=True, output_text="fixed")
+
+
+@pytest.mark.asyncio
+async def test_transient_failures_retry_behind_one_status_message(router_setup):
+    router, adapter = router_setup
+    del router._agent_runner.run  # use the real runner
+    edits: list[str] = []
+
+    async def update_message(channel: str, ts: str, text: str) -> None:
+        edits.append(text)
+
+    adapter.update_message = update_message
+    router._config.agents["claude"].retry_backoff_secs = 0
+    overloaded = OverloadedAdapter(failures=2)
+    router._adapter_cache["claude"] = overloaded
+
+    await _run_in_thread(router, "fix it", "16.5")
+
+    texts = [msg["text"] for msg in adapter.messages]
+    error = 'API Error: 529 {"type":"overloaded_error"}'
+    assert f"`claude` hit a provider error ({error}); retrying (2/3) in 0s…" in texts
+    assert edits == [f"`claude` hit a provider error ({error}); retrying (3/3) in 0s…"]
+    assert texts[-1] == "fixed"
+    assert overloaded.calls == 3
+
+    router._adapter_cache["claude"] = OverloadedAdapter(failures=5)
+    await router.handle_message(
+        {"channel": "C123", "channel_name": "test-channel", "text": "again", "thread_ts": "16.5"}
+    )
+    assert adapter.messages[-1]["text"].startswith("Agent completed with no textual output.\n\nErrors:\nAPI Error: 529")
+
+
+@pytest.mark.asyncio
+async def test_agent_cost_limit(router_setup):
+    router, adapter = router_setup
+    del router._agent_runner.run  # use the real runner
+    agent = router._config.agents["claude"]
+    agent.pricing = AgentPricing(per_run_usd=0.4)
+    agent.max_cost_usd = 1.0
+    agent.max_retries = 3
+    router._adapter_cache["claude"] = FlakyAdapter(failures=5)
+
+    await _run_in_thread(router, "fix it", "17.0")
+
+    texts = [msg["text"] for msg in adapter.messages]
+    assert texts.count("`claude` stopped: it failed (boom). Retrying (attempt 2 of 4)…") == 1
+    assert texts[-1] == (
+        "Stopped `claude`: it failed (boom), and another attempt would exceed its limit of $1.00 "
+        "(`max_cost_usd`; ~$0.80 spent)"
+    )
+
+    agent.max_cost_usd = 0.3
+    runs = router._session_manager.project_runs("test-project")
+    await router.handle_message(
+        {"channel": "C123", "channel_name": "test-channel", "text": "try again", "thread_ts": "17.0"}
+    )
+    assert adapter.messages[-1]["text"] == (
+        "Not running `claude`: the estimated cost of ~$0.40 exceeds its limit of $0.30 (`max_cost_usd`)."
+    )
+    assert router._session_manager.project_runs("test-project") == runs == 1  # The refused run is not counted
+
+
+@pytest.mark.asyncio
+async def test_session_keeps_user_timezone(router_setup):
+    router, adapter = router_setup
+    event = {"channel": "C123", "channel_name": "test-channel", "text": "hi", "ts": "18.0", "user_tz": "Asia/Tokyo"}
+    await router.handle_message(event)
+    await router.handle_message({**event, "text": "!status", "thread_ts": "18.0", "user_tz": "Not/AZone"})
+
+    assert router._session_manager.get_by_thread("C123", "18.0").timezone == "Asia/Tokyo"
+    assert "Timezone: Asia/Tokyo" in adapter.messages[-1]["text"]
+
+
+
+class RecordingAdapter:
+    """Records the keyword arguments of each run."""
+
+    supported_overrides = frozenset({"reasoning_effort"})
+
+    def __init__(self) -> None:
+        self.calls: list[Dict[str, Any]] = []
+
+    async def run(self, **kwargs: Any) -> AgentResult:
+        self.calls.append(kwargs)
+        return AgentResult(success=True, output_text="refactored")
+
+
+@pytest.mark.asyncio
+async def test_project_command_applies_overrides(router_setup):
+    router, adapter = router_setup
+    del router._agent_runner.run  # use the real runner
+    recorder = RecordingAdapter()
+    router._adapter_cache["claude"] = recorder
+    commands = router._config.get_project("test-project").path / ".cockpit" / "commands"
+    commands.mkdir(parents=True)
+    (commands / "refactor.md").write_text(
+        "---\ndescription: Refactor carefully\nmodel: opus\ntemperature: 0.2\nreasoning_effort: high\n---\n"
+        "Refactor $ARGUMENTS without changing behavior.\n"
+    )
+
+    await _run_in_thread(router, "!refactor the parser", "19.0")
+
+    assert len(recorder.calls) == 1
+    call = recorder.calls[0]
+    assert (call["model"], call["reasoning_effort"]) == ("opus", "high")
+    assert "temperature" not in call
+    assert "Refactor the parser without changing behavior." in call["task_text"]
+    texts = [msg["text"] for msg in adapter.messages]
+    assert (
+        "Message received — running `claude` now with model `opus`, reasoning effort high. "
+        "`claude` does not support temperature overrides; using its defaults."
+    ) in texts
+    assert router._session_manager.get_by_thread("C123", "19.0").active_model == "sonnet"
+
+    await router.handle_message(
+        {"channel": "C123", "channel_name": "test-channel", "text": "!help", "thread_ts": "19.0"}
+    )
+    assert "- `!refactor` – Refactor carefully" in adapter.messages[-1]["text"]
+
+
+class RejectedCredentialsAdapter:
+    supported_overrides = frozenset()
+
+    async def run(self, **kwargs: Any) -> AgentResult:
+        return AgentResult(success=False, output_text="", errors=["API Error: 401 Unauthorized"])
+
+
+@pytest.mark.asyncio
+async def test_auth_failure_includes_setup_instructions(router_setup, monkeypatch):
+    router, adapter = router_setup
+    monkeypatch.setenv("ANTHROPIC_API_KEY", "sk-revoked")
+    del router._agent_runner.run  # use the real runner
+    router._adapter_cache["claude"] = RejectedCredentialsAdapter()
+
+    await _run_in_thread(router, "fix the bug", "20.0")
+
+    assert any(
+        "authentication problem with `claude` (credentials from ANTHROPIC_API_KEY)" in msg["text"]
+        and "claude setup-token" in msg["text"]
+        for msg in adapter.messages
+    ), [msg["text"] for msg in adapter.messages]
+
+
+class RegisteringChatAdapter(DummyChatAdapter):
+    """A chat adapter that supports native command registration."""
+
+    def __init__(self) -> None:
+        super().__init__()
+        self.registrations: list[list[str]] = []
+
+    async def register_commands(self, hints) -> bool:
+        self.registrations.append([hint.name for hint in hints])
+        return True
+
+
+@pytest.mark.asyncio
+async def test_command_hints_resync_when_project_commands_change(router_setup):
+    router, _ = router_setup
+    adapter = RegisteringChatAdapter()
+    router.bind_adapter(adapter)
+
+    await router.sync_command_hints()
+    await router.sync_command_hints()
+    assert len(adapter.registrations) == 1
+    assert "refactor" not in adapter.registrations[0]
+
+    commands = router._config.get_project("test-project").path / ".cockpit" / "commands"
+    commands.mkdir(parents=True)
+    (commands / "refactor.md").write_text("Refactor $ARGUMENTS.")
+    await _run_in_thread(router, "tidy up", "21.0")
+
+    assert len(adapter.registrations) == 2
+    assert adapter.registrations[-1][-1] == "refactor"
+
+
+@pytest.mark.asyncio
+async def test_webhook_runs_project_command_in_new_thread(router_setup):
+    router, adapter = router_setup
+    del router._agent_runner.run  # use the real runner
+    recorder = RecordingAdapter()
+    router._adapter_cache["claude"] = recorder
+    project = router._config.get_project("test-project")
+    project.webhook = WebhookConfig(token="s3cret", commands=["diagnose"])
+    commands = project.path / ".cockpit" / "commands"
+    commands.mkdir(parents=True)
+    (commands / "diagnose.md").write_text("Diagnose $ARGUMENTS.")
+
+    status, payload = await router.handle_webhook(
+        "test-project", "diagnose", "Bearer s3cret", b'{"service": "payments", "window": "last 1h"}'
+    )
+    await asyncio.gather(*list(router._webhook_tasks))
+
+    assert status == 202
+    session = router._session_manager.get_by_thread(payload["channel"], payload["thread_ts"])
+    assert payload["session_id"] == str(session.id)
+    assert adapter.messages[0] == {
+        "channel": "test-channel",
+        "thread_ts": None,
+        "text": 'Webhook ran `!diagnose service=payments window="last 1h"`',
+    }
+    assert len(recorder.calls) == 1
+    assert 'Diagnose service=payments window="last 1h".' in recorder.calls[0]["task_text"]
+    assert all(msg["thread_ts"] == payload["thread_ts"] for msg in adapter.messages[1:])
+
+
+@pytest.mark.asyncio
+async def test_webhook_rejects_bad_requests_without_posting(router_setup):
+    router, adapter = router_setup
+    project = router._config.get_project("test-project")
+    project.webhook = WebhookConfig(token="s3cret", commands=["diagnose", "deploy"])
+    commands = project.path / ".cockpit" / "commands"
+    commands.mkdir(parents=True)
+    (commands / "diagnose.md").write_text("Diagnose $ARGUMENTS.")
+
+    assert (await router.handle_webhook("test-project", "diagnose", "Bearer wrong", b""))[0] == 401
+    assert (await router.handle_webhook("test-project", "refactor", "Bearer s3cret", b""))[0] == 403
+    assert (await router.handle_webhook("test-project", "deploy", "Bearer s3cret", b""))[0] == 404
+    assert (await router.handle_webhook("test-project", "diagnose", "Bearer s3cret", b"[1]"))[0] == 400
+    assert (await router.handle_webhook("other", "diagnose", "Bearer s3cret", b""))[0] == 404
+    assert adapter.messages == []
+
+
+@pytest.mark.asyncio
+async def test_github_events_reach_the_thread_of_the_pr(router_setup):
+    router, adapter = router_setup
+    router._config.github_webhook_secret = "s3cret"
+    await router.handle_message({"channel": "C123", "channel_name": "test-channel", "text": "hello", "ts": "5.0"})
+    session = router._session_manager.get_by_thread("C123", "5.0")
+    router._session_manager.set_pr_ref(
+        PullRequestRef(
+            project_id="test-project",
+            session_id=session.id,
+            number=42,
+            url="https://github.com/owner/repo/pull/42",
+            head_branch="remote-coder/x",
+            base_branch="main",
+        )
+    )
+    router._session_manager.cleanup_ended(timedelta(0))
+    body = json.dumps(
+        {
+            "action": "created",
+            "repository": {"full_name": "owner/repo"},
+            "issue": {"number": 42, "pull_request": {}},
+            "comment": {"user": {"login": "ana"}, "body": "Please add a test."},
+        }
+    ).encode()
+    signature = "sha256=" + hmac.new(b"s3cret", body, hashlib.sha256).hexdigest()
+
+    assert (await router.handle_github_event("issue_comment", "sha256=00", body))[0] == 401
+    status, payload = await router.handle_github_event("issue_comment", signature, body)
+
+    assert (status, payload) == (200, {"delivered": [str(session.id)]})
+    assert router._session_manager.get_session(session.id).status == SessionStatus.ACTIVE
+    assert adapter.messages[-1]["thread_ts"] == "5.0"
+    assert adapter.messages[-1]["text"] == "GitHub, PR #42: Comment from @ana on the PR:\n> Please add a test."
+    other_repo = body.replace(b"owner/repo", b"owner/other")
+    signature = "sha256=" + hmac.new(b"s3cret", other_repo, hashlib.sha256).hexdigest()
+    assert await router.handle_github_event("issue_comment", signature, other_repo) == (200, {"delivered": []})
+    assert len([msg for msg in adapter.messages if "PR #42" in msg["text"]]) == 1
+
+
+@pytest.mark.asyncio
+async def test_merged_prs_are_noted_and_counted_without_reactivating(router_setup):
+    router, adapter = router_setup
+    router._config.github_webhook_secret = "s3cret"
+    await router.handle_message({"channel": "C123", "channel_name": "test-channel", "text": "hello", "ts": "5.1"})
+    session = router._session_manager.get_by_thread("C123", "5.1")
+    router._session_manager.set_pr_ref(
+        PullRequestRef(
+            project_id="test-project",
+            session_id=session.id,
+            number=43,
+            url="https://github.com/owner/repo/pull/43",
+            head_branch="remote-coder/y",
+            base_branch="main",
+        )
+    )
+    router._session_manager.cleanup_ended(timedelta(0))
+    body = json.dumps(
+        {
+            "action": "closed",
+            "repository": {"full_name": "owner/repo"},
+            "pull_request": {"number": 43, "merged": True, "merged_by": {"login": "ana"}},
+        }
+    ).encode()
+    signature = "sha256=" + hmac.new(b"s3cret", body, hashlib.sha256).hexdigest()
+
+    assert await router.handle_github_event("pull_request", signature, body) == (200, {"delivered": [str(session.id)]})
+    assert router._session_manager.get_session(session.id).status == SessionStatus.ARCHIVED
+    assert adapter.messages[-1]["text"] == "GitHub, PR #43: Merged by @ana."
+    await router.handle_message({"channel": "C123", "channel_name": "test-channel", "text": "!analytics", "ts": "5.2"})
+    assert "PRs merged 1/1" in adapter.messages[-1]["text"]
+
+
+@pytest.mark.asyncio
+async def test_runs_are_recorded_and_wait_while_a_checkpoint_is_checked_out(router_setup, monkeypatch):
+    router, adapter = router_setup
+    del router._agent_runner.run  # use the real runner with the mock adapter
+    router._config.agents["demo"] = Agent(
+        id="demo",
+        type=AgentType.MOCK,
+        command=[],
+        working_dir_mode=WorkingDirMode.PROJECT,
+        options={"delay_secs": 0, "summary": "Canned reply"},
+    )
+    project = router._config.get_project("test-project")
+    project.default_agent_id = "demo"
+    for key in ("GIT_AUTHOR_NAME", "GIT_COMMITTER_NAME"):
+        monkeypatch.setenv(key, "Test")
+    for key in ("GIT_AUTHOR_EMAIL", "GIT_COMMITTER_EMAIL"):
+        monkeypatch.setenv(key, "test@example.com")
+
+    def git(*args: str) -> str:
+        return subprocess.run(["git", *args], cwd=project.path, capture_output=True, text=True, check=True).stdout
+
+    git("init", "-q", "-b", "main")
+    (project.path / "README.md").write_text("hello\n")
+    git("add", "-A")
+    git("commit", "-q", "-m", "initial")
+    thread = {"channel": "C123", "channel_name": "test-channel", "thread_ts": "12.7"}
+
+    await router.handle_message({"channel": "C123", "channel_name": "test-channel", "text": "hi", "ts": "12.7"})
+    await router.handle_message({**thread, "text": "add a button"})
+    git("add", "-A")
+    git("commit", "-q", "-m", "button")
+
+    session = router._session_manager.get_by_thread("C123", "12.7")
+    assert [entry.prompt for entry in load_timeline(session)] == ["add a button"]
+
+    await router.handle_message({**thread, "text": "!checkout 1"})
+    await router.handle_message({**thread, "text": "add a dark mode toggle"})
+
+    assert adapter.messages[-1]["text"] == (
+        "The worktree is at checkpoint 1; run `!checkout latest` before asking for more changes."
+    )
+
+
+class PromptRecordingAdapter:
+    """Agent adapter that remembers the prompts it was given."""
+
+    def __init__(self) -> None:
+        self.prompts: list[str] = []
+
+    async def run(self, *, task_text: str, **kwargs: Any) -> AgentResult:
+        self.prompts.append(task_text)
+        return AgentResult(success=True, output_text=f"reply {len(self.prompts)}")
+
+
+@pytest.mark.asyncio
+async def test_discussion_sessions_design_first_then_implement(router_setup):
+    router, slack = router_setup
+    del router._agent_runner.run  # use the real runner
+    discussions = DummyChatAdapter()
+    router.bind_adapter(discussions, channel_prefix="github:")
+    agent = PromptRecordingAdapter()
+    router._adapter_cache["claude"] = agent
+    opening = {
+        "channel": "github:owner/repo",
+        "channel_name": "test-channel",
+        "ts": "7",
+        "text": "Feed caching\n\nShould we cache the feed?",
+        "discussion_url": "https://github.com/owner/repo/discussions/7",
+    }
+
+    await router.handle_message(opening)
+
+    assert slack.messages == []
+    assert discussions.messages[0]["text"].startswith("Starting a design session for `test-project`")
+    assert discussions.messages[-1]["text"] == "reply 1"
+    assert DESIGN_NOTE in agent.prompts[0]
+    assert "Should we cache the feed?" in agent.prompts[0]
+    session = router._session_manager.get_by_thread("github:owner/repo", "7")
+    assert session.session_context[PHASE_CONTEXT_KEY] == "design"
+
+    await router.handle_message({**opening, "ts": "C_1", "thread_ts": "7", "text": "!implement use a 5 minute TTL"})
+
+    assert session.session_context[PHASE_CONTEXT_KEY] == "implementation"
+    assert agent.prompts[-1].count(IMPLEMENT_PROMPT) == 1
+    assert "use a 5 minute TTL" in agent.prompts[-1]
+    assert DESIGN_NOTE not in agent.prompts[-1]
+    router._git_workflow.maybe_publish_code_changes.assert_awaited()
+
+
+class ApprovingChatAdapter(DummyChatAdapter):
+    """A chat with buttons whose user answers every confirmation at once."""
+
+    def __init__(self, router: Router, approve: bool) -> None:
+        super().__init__()
+        self._router = router
+        self._approve = approve
+        self.statuses: list[SessionStatus] = []  # The session's, as each confirmation is asked
+
+    async def send_confirmation(self, channel: str, thread_ts: str, text: str, key: str) -> str:
+        self.statuses.append(self._router._session_manager.get_by_thread(channel, thread_ts).status)
+        ts = await self.send_message(channel, thread_ts, text)
+        asyncio.get_running_loop().call_soon(self._router.answer_confirmation, key, self._approve)
+        return ts
+
+
+@pytest.mark.asyncio
+async def test_pipeline_plans_waits_for_approval_and_verifies(router_setup):
+    router, _ = router_setup
+    del router._agent_runner.run  # use the real runner
+    adapter = ApprovingChatAdapter(router, approve=True)
+    router.bind_adapter(adapter)
+    agent = PromptRecordingAdapter()
+    router._adapter_cache["claude"] = agent
+    project = router._config.get_project("test-project")
+    project.verify_commands = [CommandStep(run="echo checked")]
+    project.pipeline = [PipelineStep(name) for name in ("plan", "approve", "edit", "verify")]
+
+    thread = {"channel": "C123", "channel_name": "test-channel", "thread_ts": "15.0"}
+    await router.handle_message({"channel": "C123", "channel_name": "test-channel", "text": "hi", "ts": "15.0"})
+    await router.handle_message({**thread, "text": "add a cache"})
+
+    texts = [msg["text"] for msg in adapter.messages]
+    assert PLAN_NOTE in agent.prompts[0]
+    assert PLANNED_NOTE in agent.prompts[1] and "reply 1" in agent.prompts[1]
+    plan_at = texts.index("Plan from `claude`:\nreply 1")
+    assert "Approve to continue with the edit step." in texts[plan_at + 1]
+    assert texts[-1] == "reply 2\n\nVerify steps passed:\n- `echo checked`: passed"
+    router._git_workflow.maybe_publish_code_changes.assert_not_awaited()
+    assert adapter.statuses == [SessionStatus.AWAITING_APPROVAL]
+    assert router._session_manager.get_by_thread("C123", "15.0").status == SessionStatus.ACTIVE
+
+
+@pytest.mark.asyncio
+async def test_pipeline_stops_at_a_denied_approval(router_setup):
+    router, _ = router_setup
+    del router._agent_runner.run  # use the real runner
+    adapter = ApprovingChatAdapter(router, approve=False)
+    router.bind_adapter(adapter)
+    agent = PromptRecordingAdapter()
+    router._adapter_cache["claude"] = agent
+    project = router._config.get_project("test-project")
+    project.pipeline = [PipelineStep("approve"), PipelineStep("edit"), PipelineStep("pr")]
+
+    thread = {"channel": "C123", "channel_name": "test-channel", "thread_ts": "16.0"}
+    await router.handle_message({"channel": "C123", "channel_name": "test-channel", "text": "hi", "ts": "16.0"})
+    await router.handle_message({**thread, "text": "add a cache"})
+
+    assert agent.prompts == []
+    assert adapter.messages[-1]["text"] == (
+        "Stopped at the `approve` step, so `claude` did not start on the changes."
+    )
+    router._git_workflow.maybe_publish_code_changes.assert_not_awaited()
+
+
+@pytest.mark.asyncio
+async def test_roles_plan_code_and_review_with_their_own_agents(router_setup):
+    router, adapter = router_setup
+    del router._agent_runner.run  # use the real runner
+    router._config.agents["gemini"] = Agent(
+        id="gemini", type=AgentType.GEMINI, command=["echo"], working_dir_mode=WorkingDirMode.PROJECT
+    )
+    coder, gemini = PromptRecordingAdapter(), PromptRecordingAdapter()
+    router._adapter_cache.update({"claude": coder, "gemini": gemini})
+    router._git_workflow.pending_diff = AsyncMock(return_value="diff --git a/a.py b/a.py\n+cache = {}\n")
+    project = router._config.get_project("test-project")
+    project.roles = ProjectRoles(planner="gemini", reviewer="gemini")
+
+    thread = {"channel": "C123", "channel_name": "test-channel", "thread_ts": "15.5"}
+    await router.handle_message({"channel": "C123", "channel_name": "test-channel", "text": "hi", "ts": "15.5"})
+    await router.handle_message({**thread, "text": "add a cache"})
+
+    texts = [msg["text"] for msg in adapter.messages]
+    assert PLAN_NOTE in gemini.prompts[0] and "reply 1" in coder.prompts[0]
+    assert "Do not edit any files." in gemini.prompts[1] and "+cache = {}" in gemini.prompts[1]
+    start = texts.index("Message received — planner `gemini`, coder `claude`, and reviewer `gemini` are on it.")
+    assert texts[start + 1 : start + 7] == [
+        "`gemini` (planner) is writing a plan.",
+        "Plan from `gemini`:\nreply 1",
+        "`claude` (coder) is making the changes.",
+        "reply 1",
+        "`gemini` (reviewer) is reviewing the changes.",
+        "Review from `gemini`:\nreply 2",
+    ]
+    session = router._session_manager.get_by_thread("C123", "15.5")
+    assert session.conversation_history[-1].content == "Review from `gemini`:\nreply 2"
+    router._git_workflow.maybe_publish_code_changes.assert_awaited()
+
+
+@pytest.mark.asyncio
+async def test_failing_hook_step_keeps_changes_out_of_the_pr(router_setup):
+    router, adapter = router_setup
+    del router._agent_runner.run  # use the real runner
+    router._adapter_cache["claude"] = PromptRecordingAdapter()
+    project = router._config.get_project("test-project")
+    project.pipeline = [
+        PipelineStep("edit"),
+        PipelineStep("lint", run="echo 'missing semicolon'; exit 3"),
+        PipelineStep("pr"),
+        PipelineStep("diff-review"),
+    ]
+
+    thread = {"channel": "C123", "channel_name": "test-channel", "thread_ts": "17.0"}
+    await router.handle_message({"channel": "C123", "channel_name": "test-channel", "text": "hi", "ts": "17.0"})
+    await router.handle_message({**thread, "text": "add a cache"})
+
+    reply = adapter.messages[-1]["text"]
+    assert reply.startswith("reply 1\n\nHook `lint` failed:\n- `echo 'missing semicolon'; exit 3` (lint): failed")
+    assert "missing semicolon" in reply
+    assert reply.endswith(
+        "Stopped at the `lint` step, so `pr`, `diff-review` did not run; the changes are left uncommitted."
+    )
+    router._git_workflow.maybe_publish_code_changes.assert_not_awaited()
+
+
+@pytest.mark.asyncio
+async def test_runs_in_other_threads_queue_for_the_checkout(router_setup):
+    router, adapter = router_setup
+    release = asyncio.Event()
+    prompts: list[str] = []
+
+    async def run(session, project, channel_id, thread_ts, user_text, **kwargs):
+        prompts.append(user_text)
+        await release.wait()
+
+    router._agent_runner.run = run
+    for ts in ("18.0", "18.1"):
+        await router.handle_message({"channel": "C123", "channel_name": "test-channel", "text": "hi", "ts": ts})
+    first = asyncio.create_task(
+        router.handle_message({"channel": "C123", "channel_name": "test-channel", "text": "one", "thread_ts": "18.0"})
+    )
+    second = asyncio.create_task(
+        router.handle_message({"channel": "C123", "channel_name": "test-channel", "text": "two", "thread_ts": "18.1"})
+    )
+    while not adapter.messages[-1]["text"].startswith("Queued"):
+        await asyncio.sleep(0.01)
+
+    assert prompts == ["one"]
+    assert adapter.messages[-1] == {
+        "channel": "C123",
+        "thread_ts": "18.1",
+        "text": "Queued (#2): another run is using the `test-project` checkout. This one starts when it is its turn.",
+    }
+
+    release.set()
+    await asyncio.gather(first, second)
+
+    assert prompts == ["one", "two"]
+
+
+class FileWritingAdapter:
+    """Agent adapter that edits a file in the checkout it runs in."""
+
+    def __init__(self) -> None:
+        self.project_paths: list[str] = []
+
+    async def run(self, *, project_path: str, **kwargs: Any) -> AgentResult:
+        self.project_paths.append(project_path)
+        with open(os.path.join(project_path, "README.md"), "w") as handle:
+            handle.write("rewritten\n")
+        return AgentResult(success=True, output_text="The entry point is main.py.")
+
+
+@pytest.mark.asyncio
+async def test_ask_channels_answer_without_changing_the_checkout(router_setup, tmp_path, monkeypatch):
+    from src.core import ask_mode
+
+    router, adapter = router_setup
+    del router._agent_runner.run  # use the real runner
+    monkeypatch.setattr(ask_mode.tempfile, "gettempdir", lambda: str(tmp_path / "tmp"))
+    project = router._config.get_project("test-project")
+    project.ask_channels = ["test-questions"]
+    repo = project.path
+    for args in (
+        ["init", "--quiet", "--initial-branch=main"],
+        ["-c", "user.email=dev@example.com", "-c", "user.name=Dev", "commit", "--quiet", "--allow-empty", "-m", "init"],
+    ):
+        subprocess.run(["git", *args], cwd=repo, check=True, capture_output=True)
+    agent = FileWritingAdapter()
+    router._adapter_cache["claude"] = agent
+
+    thread = {"channel": "C9", "channel_name": "test-questions", "thread_ts": "20.0"}
+    await router.handle_message({"channel": "C9", "channel_name": "test-questions", "text": "hi", "ts": "20.0"})
+    assert adapter.messages[-1]["text"].startswith("Starting a question session for `test-project`")
+    router._git_workflow.setup_session_branch.assert_not_awaited()
+
+    await router.handle_message({**thread, "text": "where is the entry point?"})
+
+    assert agent.project_paths and agent.project_paths[0] != str(repo)
+    assert not (repo / "README.md").exists()
+    assert not os.path.exists(os.path.join(agent.project_paths[0], "README.md"))
+    assert adapter.messages[-1]["text"] == (
+        "The entry point is main.py.\n\n"
+        "Ask mode is read-only, so the file changes from this run were discarded."
+    )
+    router._git_workflow.maybe_publish_code_changes.assert_not_awaited()
+
+    await router.handle_message({**thread, "text": "!apply"})
+    assert adapter.messages[-1]["text"] == (
+        "`!apply` is not available in a question channel, which never changes the code. Use `#test-channel` for it."
+    )
+
+
+@pytest.mark.asyncio
+async def test_runs_past_the_daemon_limit_wait_and_show_in_status(router_setup, tmp_path):
+    router, adapter = router_setup
+    other = Project(
+        id="other-project",
+        channel_name="other-channel",
+        path=tmp_path,
+        default_agent_id="claude",
+        github=GitHubRepoConfig(owner="owner", repo="other", default_base_branch="main"),
+    )
+    router._config.projects.update({other.channel_name: other, other.id: other})
+    router._scheduler.set_limit(1)
+    release = asyncio.Event()
+    prompts: list[str] = []
+
+    async def run(session, project, channel_id, thread_ts, user_text, **kwargs):
+        prompts.append(user_text)
+        await release.wait()
+
+    router._agent_runner.run = run
+    await router.handle_message({"channel": "C1", "channel_name": "test-channel", "text": "hi", "ts": "21.0"})
+    await router.handle_message({"channel": "C2", "channel_name": "other-channel", "text": "hi", "ts": "21.1"})
+    first = asyncio.create_task(
+        router.handle_message({"channel": "C1", "channel_name": "test-channel", "text": "one", "thread_ts": "21.0"})
+    )
+    second = asyncio.create_task(
+        router.handle_message({"channel": "C2", "channel_name": "other-channel", "text": "two", "thread_ts": "21.1"})
+    )
+    while not adapter.messages[-1]["text"].startswith("Queued"):
+        await asyncio.sleep(0.01)
+
+    assert prompts == ["one"]
+    assert adapter.messages[-1]["text"] == (
+        "Queued (#1): all 1 run slots of Remote Coder are busy. This one starts when it is its turn."
+    )
+    await router.handle_message(
+        {"channel": "C2", "channel_name": "other-channel", "text": "!status", "thread_ts": "21.1"}
+    )
+    assert "Run queue: 1 of 1 run slots in use, 1 waiting; this thread's run is #1" in adapter.messages[-1]["text"]
+
+    release.set()
+    await asyncio.gather(first, second)
+
+    assert prompts == ["one", "two"]
+
+
+@pytest.mark.asyncio
+async def test_user_preferences_apply_to_the_sessions_they_start(router_setup):
+    router, adapter = router_setup
+    del router._agent_runner.run  # use the real runner
+    codex = Agent(
+        id="codex",
+        type=AgentType.CODEX,
+        command=["echo"],
+        working_dir_mode=WorkingDirMode.PROJECT,
+        models={"default": "gpt-5", "available": ["gpt-5"]},
+    )
+    router._config.agents[codex.id] = codex
+    recorder = PromptRecordingAdapter()
+    router._adapter_cache["codex"] = recorder
+    setup = {"channel": "C123", "channel_name": "test-channel", "user": "U1", "ts": "22.0"}
+    await router.handle_message({**setup, "text": "hi"})
+
+    for text in ("!prefs agent codex", "!prefs verbosity verbose", "!prefs notify dm", "!prefs locale de_DE"):
+        await router.handle_message({**setup, "text": text, "thread_ts": "22.0"})
+    assert adapter.messages[-1]["text"] == (
+        "Saved. Sessions you start from now on use:\n- agent: `codex`\n- verbosity: `verbose`\n"
+        "- notify: `dm`\n- locale: `de-DE`\nThreads that are already running keep their settings."
+    )
+    await router.handle_message({**setup, "text": "!prefs verbosity loud", "thread_ts": "22.0"})
+    assert adapter.messages[-1]["text"].startswith("Verbosity must be one of `quiet`, `normal`, `verbose`.")
+    assert router._session_manager.get_by_thread("C123", "22.0").active_agent_id == "claude"
+
+    await router.handle_message({**setup, "text": "hi", "ts": "22.1"})
+    assert adapter.messages[-1]["text"].startswith("Starting session for `test-project` with `codex` `gpt-5`.")
+    await router.handle_message({**setup, "text": "fix it", "ts": "22.2", "thread_ts": "22.1"})
+
+    assert "the `de-DE` locale" in recorder.prompts[0]
+    reply, note = adapter.messages[-2:]
+    assert reply["text"].startswith("reply 1\n\nRun details: `codex` `gpt-5`, ")
+    assert note == {
+        "channel": "U1",
+        "thread_ts": None,
+        "text": "`codex` finished a run for `test-project` (thread 22.1).",
+    }
+
+    await router.handle_message({**setup, "user": "U2", "text": "hi", "ts": "22.3"})
+    assert adapter.messages[-1]["text"].startswith("Starting session for `test-project` with `claude` `sonnet`.")
+
+
+class EnvEchoAdapter:
+    """Prints the project's `.env` back, as a careless agent might."""
+
+    async def run(self, **kwargs: Any) -> AgentResult:
+        return AgentResult(success=True, output_text="Your .env has:\nOPENAI_API_KEY=openai-value-1234\nDEBUG=1")
+
+
+@pytest.mark.asyncio
+async def test_secrets_are_redacted_before_posting(router_setup, monkeypatch):
+    router, adapter = router_setup
+    del router._agent_runner.run  # use the real runner
+    monkeypatch.setenv("OPENAI_API_KEY", "openai-value-1234")
+    router._redactor = router._build_redactor(router._config)
+    router._adapter_cache["claude"] = EnvEchoAdapter()
+    diffs: list[str] = []
+
+    async def send_diff(channel: str, thread_ts: str, files: Any) -> None:
+        diffs.extend(file_diff.text for file_diff in files)
+
+    await _run_in_thread(router, "what is in .env?", "23.0")
+    assert adapter.messages[-1]["text"] == "Your .env has:\nOPENAI_API_KEY=[redacted OPENAI_API_KEY]\nDEBUG=1"
+
+    adapter.send_diff = send_diff
+    await router._post_diff("C123", "23.0", "diff --git a/.env b/.env\n+GITHUB_TOKEN=ghp_" + "x" * 36 + "\n")
+    assert diffs and "ghp_" not in "".join(diffs) and "GITHUB_TOKEN=[redacted" in diffs[0]
+
+
+@pytest.mark.asyncio
+async def test_incident_mode_diagnoses_read_only_and_audits_each_step(router_setup, tmp_path, monkeypatch):
+    import json
+    import logging
+
+    from src.core import ask_mode
+
+    router, adapter = router_setup
+    del router._agent_runner.run  # use the real runner
+    monkeypatch.setattr(ask_mode.tempfile, "gettempdir", lambda: str(tmp_path / "tmp"))
+    repo = router._config.get_project("test-project").path
+    for args in (
+        ["init", "--quiet", "--initial-branch=main"],
+        ["-c", "user.email=dev@example.com", "-c", "user.name=Dev", "commit", "--quiet", "--allow-empty", "-m", "init"],
+    ):
+        subprocess.run(["git", *args], cwd=repo, check=True, capture_output=True)
+    cheap = Agent(
+        id="cheap",
+        type=AgentType.CODEX,
+        command=["echo"],
+        working_dir_mode=WorkingDirMode.PROJECT,
+        models={"default": "mini", "available": ["mini"]},
+        capabilities=AgentCapabilities(cost_tier=CostTier.LOW),
+    )
+    router._config.agents[cheap.id] = cheap
+    recorder = PromptRecordingAdapter()
+    router._adapter_cache["cheap"] = recorder
+    setup = {"channel": "C123", "channel_name": "test-channel", "user": "U1"}
+
+    await router.handle_message({**setup, "text": "hi", "ts": "24.0"})
+    await router.handle_message({**setup, "text": "!incident on 30", "thread_ts": "24.0"})
+    assert adapter.messages[-1]["text"].startswith("Incident mode is on for `test-project` until ")
+    assert "- new threads are read-only diagnosis threads with `cheap`" in adapter.messages[-1]["text"]
+    assert logging.getLogger("src").level == logging.DEBUG
+    assert router._session_manager.get_by_thread("C123", "24.0").active_agent_id == "claude"
+
+    await router.handle_message({**setup, "text": "hi", "ts": "24.1"})
+    assert adapter.messages[-1]["text"].startswith("Starting a diagnosis session for `test-project` with `cheap`")
+    await router.handle_message({**setup, "text": "why are requests failing?", "thread_ts": "24.1"})
+
+    assert ask_mode.ASK_NOTE in recorder.prompts[0]
+    assert adapter.messages[-1]["text"].startswith("reply 1\n\nRun details: `cheap` `mini`, ")
+    router._git_workflow.maybe_publish_code_changes.assert_not_awaited()
+    audit = router._config.config_dir / "audit" / "test-project.jsonl"
+    entries = [json.loads(line) for line in audit.read_text().splitlines()]
+    assert [(entry["event"], entry.get("step")) for entry in entries] == [
+        ("run started", None),
+        ("step", "edit"),
+        ("run finished", None),
+    ]
+    assert entries[0]["read_only"] is True and entries[0]["prompt"] == "why are requests failing?"
+
+    await router.handle_message({**setup, "text": "!incident off", "thread_ts": "24.1"})
+    assert adapter.messages[-1]["text"].startswith("Incident mode is off for `test-project`.")
+    assert logging.getLogger("src").level == logging.NOTSET
+    await router.handle_message({**setup, "text": "hi", "ts": "24.2"})
+    assert adapter.messages[-1]["text"].startswith("Starting session for `test-project` with `claude`")
+
+
+@pytest.mark.asyncio
+async def test_status_snapshot_summarizes_runs_and_redacts_errors(router_setup, monkeypatch):
+    from src.core.status_page import IntegrationError
+
+    router, adapter = router_setup
+    monkeypatch.setenv("OPENAI_API_KEY", "openai-value-1234")
+    router._redactor = router._build_redactor(router._config)
+    await router.handle_message({"channel": "C123", "channel_name": "test-channel", "text": "hi", "ts": "25.0"})
+    session = router._session_manager.get_by_thread("C123", "25.0")
+    router.active_runs["run"] = {"session_id": str(session.id), "agent_id": "claude", "started_at": 10.0}
+
+    status = router.status_snapshot([IntegrationError("Agents", 20.0, "bad key openai-value-1234")])
+
+    assert status["adapters"] == [{"name": "DummyChat", "connected": None}]
+    assert status["ok"] is True and status["github_configured"] is False
+    assert status["projects"] == [{"id": "test-project", "running": 0, "queued": 0, "incident": False}]
+    assert status["active_runs"] == [
+        {"project": "test-project", "agent": "claude", "thread": "25.0", "started_at": 10.0}
+    ]
+    assert status["errors"] == [{"integration": "Agents", "at": 20.0, "message": "bad key [redacted OPENAI_API_KEY]"}]
+
+
+class ButtonChatAdapter(DummyChatAdapter):
+    """A chat with buttons that records the run summaries it is sent."""
+
+    capabilities = ChatCapabilities(supports_buttons=True)
+
+    def __init__(self) -> None:
+        super().__init__()
+        self.summaries: list[tuple[str, list[Any]]] = []
+
+    async def send_run_summary(self, channel: str, thread_ts: str, text: str, steps: Any) -> None:
+        self.summaries.append((text, list(steps)))
+
+
+@pytest.mark.asyncio
+async def test_run_summary_lists_changes_and_next_steps(router_setup):
+    router, _ = router_setup
+    adapter = ButtonChatAdapter()
+    router.bind_adapter(adapter)
+    del router._agent_runner.run  # use the real runner
+    repo = router._config.get_project("test-project").path
+    (repo / "README.md").write_text("original\n")
+    for args in (
+        ["init", "--quiet", "--initial-branch=main"],
+        ["add", "README.md"],
+        ["-c", "user.email=dev@example.com", "-c", "user.name=Dev", "commit", "--quiet", "-m", "init"],
+    ):
+        subprocess.run(["git", *args], cwd=repo, check=True, capture_output=True)
+    router._adapter_cache["claude"] = FileWritingAdapter()
+    setup = {"channel": "C123", "channel_name": "test-channel", "user": "U1"}
+
+    await router.handle_message({**setup, "text": "hi", "ts": "26.0"})
+    await router.handle_message({**setup, "text": "rewrite the readme", "thread_ts": "26.0"})
+
+    assert len(adapter.summaries) == 1
+    text, steps = adapter.summaries[0]
+    assert text.startswith("*Run finished*: `claude`, ")
+    assert "1 file(s) changed, +1 -1: `README.md`" in text and "Branch: `main`" in text
+    assert [(step.label, step.command) for step in steps] == [("Continue", "continue")]
@@ -1,13 +1,15 @@
-"""Tests for Router project creation flow with Y/N text responses."""
+"""Tests for Router project creation flow with text responses."""
 
 from __future__ import annotations
 
+import subprocess
 from typing import Any, Dict, Optional
 from unittest.mock import AsyncMock, MagicMock, patch
 
 import pytest
+import yaml
 
-from src.core.config import Config
+from src.core.config import Config, load_config
 from src.core.models import Agent, AgentType, GitHubRepoConfig, Project, WorkingDirMode
 from src.core.commands.project_creation import PendingProjectCreation
 from src.core.router import Router
@@ -105,6 +107,7 @@ GITHUB_TOKEN=test-token
     github_manager = StubGitHubManager()
     router = Router(session_manager, config, github_manager, config_root=config_dir)
     router._git_workflow.setup_session_branch = AsyncMock(return_value=None)
+    router._git_workflow.preflight = AsyncMock(return_value=[])
     router._git_workflow.maybe_publish_code_changes = AsyncMock(return_value=None)
     router._agent_runner.run = AsyncMock()
 
@@ -154,7 +157,7 @@ class TestRouterMissingProject:
         # Should have sent the prompt message
         assert len(adapter.messages) == 1
         assert "new-idea" in adapter.messages[0]["text"]
-        assert "Reply with \"Y\"" in adapter.messages[0]["text"]
+        assert "Reply with the number (or \"Y\" for 1)" in adapter.messages[0]["text"]
 
     @pytest.mark.asyncio
     async def test_missing_project_tracks_pending(self, router_setup):
@@ -406,7 +409,7 @@ projects:
         await router.handle_message(event2)
 
         # Should have sent reminder message
-        assert any("Please reply with \"Y\"" in msg["text"] for msg in adapter.messages)
+        assert any("Please reply with a number from 1 to 2" in msg["text"] for msg in adapter.messages)
 
         # Pending should still be tracked
         assert "C123" in router._project_creation_handler._pending_projects
@@ -503,3 +506,81 @@ class TestProjectCreationHandler:
         assert pending.channel_name == "my-project"
         assert pending.thread_ts == "111.000"
         assert pending.created_at == 12345.0
+
+
+class TestChannelOnboarding:
+    """Tests for onboarding when the bot is invited, and the register/link setup options."""
+
+    @pytest.mark.asyncio
+    async def test_invite_to_unmapped_channel_starts_thread(self, router_setup):
+        """Being invited posts the setup prompt at the top level and continues in its thread."""
+        router, adapter, _ = router_setup
+
+        await router.handle_channel_joined({"channel": "C123", "channel_name": "new-idea"})
+
+        assert len(adapter.messages) == 1
+        assert adapter.messages[0]["thread_ts"] is None
+        assert "How should I set it up?" in adapter.messages[0]["text"]
+        pending = router._project_creation_handler._pending_projects["C123"]
+        assert pending.thread_ts == adapter.messages[0]["ts"]
+
+    @pytest.mark.asyncio
+    async def test_invite_to_mapped_channel_is_quiet(self, router_setup, tmp_path):
+        """Channels that already have a project get no prompt."""
+        router, adapter, _ = router_setup
+        router._config.projects["new-idea"] = Project(
+            id="new-idea", channel_name="new-idea", path=tmp_path, default_agent_id="claude"
+        )
+
+        await router.handle_channel_joined({"channel": "C123", "channel_name": "new-idea"})
+
+        assert adapter.messages == []
+
+    @pytest.mark.asyncio
+    async def test_register_existing_repository(self, router_setup):
+        """Option 2 registers a repository outside base_dir and starts a session for it."""
+        router, adapter, tmp_path = router_setup
+        repo = tmp_path / "elsewhere" / "app"
+        repo.mkdir(parents=True)
+        subprocess.run(["git", "init", "-q"], cwd=repo, check=True)
+
+        for text in ("Hello", "2", str(tmp_path / "missing"), str(repo), "1", "1"):
+            await router.handle_message(
+                {"channel": "C123", "channel_name": "new-idea", "text": text, "ts": "111.222"}
+            )
+
+        texts = [msg["text"] for msg in adapter.messages]
+        assert any("path to the repository" in text for text in texts)
+        assert any("I can't find a directory" in text for text in texts)
+        assert any("I've set up `new-idea`" in text for text in texts)
+        project = router._config.get_project_by_channel("new-idea")
+        assert project.path == repo.resolve()
+        assert project.github is None
+        assert project.default_model == "sonnet"
+        assert any("Starting session for `new-idea`" in text for text in texts)
+
+    @pytest.mark.asyncio
+    async def test_link_to_configured_project(self, router_setup):
+        """Option 3 adds the channel to an existing project's `channels` in projects.yaml."""
+        router, adapter, tmp_path = router_setup
+        config_dir = tmp_path / "config"
+        (tmp_path / "projects" / "api").mkdir()
+        (config_dir / "projects.yaml").write_text(
+            f'base_dir: "{tmp_path / "projects"}"\n'
+            "projects:\n  api:\n    path: api\n    default_agent: claude\n"
+        )
+        router._apply_new_config(load_config(config_dir))
+
+        for text in ("Hello", "3", "1"):
+            await router.handle_message(
+                {"channel": "C123", "channel_name": "api-dev", "text": text, "ts": "111.222"}
+            )
+
+        texts = [msg["text"] for msg in adapter.messages]
+        assert "3. Connect this channel" in texts[0]
+        assert "1. api" in texts[1]
+        assert any("this channel now works on `api`" in text for text in texts)
+        data = yaml.safe_load((config_dir / "projects.yaml").read_text())
+        assert data["projects"]["api"]["channels"] == ["api-dev"]
+        assert router._config.get_project_by_channel("api-dev").id == "api"
+        assert any("Starting session for `api`" in text for text in texts)
@@ -0,0 +1,111 @@
+"""Tests for the journal of in-flight runs and crash recovery."""
+
+from __future__ import annotations
+
+import os
+import subprocess
+
+import pytest
+
+from src.core.run_journal import ADOPT, CLEAN, JournalEntry, Recovery, RunJournal, recover_orphaned_run
+from tests.conftest import git
+
+
+def _entry(workdir, run_id: str = "run-1") -> JournalEntry:
+    return JournalEntry(
+        run_id=run_id,
+        session_id="00000000-0000-0000-0000-000000000001",
+        project_id="demo",
+        channel_id="C1",
+        thread_ts="t1",
+        agent_id="claude",
+        workdir=str(workdir),
+    )
+
+
+def _dead_pid() -> int:
+    process = subprocess.Popen(["true"])
+    process.wait()
+    return process.pid
+
+
+class TestRunJournal:
+    """Test cases for writing entries and telling orphans from live runs."""
+
+    @pytest.mark.asyncio
+    async def test_entries_of_this_daemon_are_not_orphans(self, tmp_path, git_repo):
+        journal = RunJournal(tmp_path / "runs")
+
+        entry = await journal.start(_entry(git_repo))
+
+        assert entry.branch == "main"
+        assert entry.daemon_pid == os.getpid()
+        assert oct((tmp_path / "runs" / "run-1.json").stat().st_mode & 0o777) == "0o600"
+        assert [found.run_id for found in journal.entries()] == ["run-1"]
+        assert journal.orphans() == []
+        journal.finish("run-1")
+        assert journal.entries() == []
+
+    @pytest.mark.asyncio
+    async def test_entries_of_a_dead_daemon_are_orphans(self, tmp_path, git_repo):
+        journal = RunJournal(tmp_path / "runs")
+        entry = await journal.start(_entry(git_repo))
+        entry.daemon_pid, entry.daemon_started = _dead_pid(), None
+        journal._write(entry)
+        (tmp_path / "runs" / "broken.json").write_text("{not json")
+
+        orphans = RunJournal(tmp_path / "runs").orphans()
+
+        assert [orphan.run_id for orphan in orphans] == ["run-1"]
+        assert orphans[0].branch == "main"
+
+
+class TestRecovery:
+    """Test cases for cleaning up after an orphaned run."""
+
+    @pytest.mark.asyncio
+    async def test_adopt_keeps_the_changes_and_clean_stashes_them(self, git_repo):
+        (git_repo / "README.md").write_text("changed\n")
+        (git_repo / "new.txt").write_text("new\n")
+        entry = _entry(git_repo)
+        entry.branch = "main"
+
+        adopted = await recover_orphaned_run(entry, ADOPT)
+
+        assert (adopted.changed_files, adopted.stashed) == (2, False)
+        assert (git_repo / "new.txt").exists()
+        assert "2 uncommitted change(s) on `main` are still in the checkout" in adopted.describe()
+
+        cleaned = await recover_orphaned_run(entry, CLEAN)
+
+        assert (cleaned.changed_files, cleaned.stashed) == (2, True)
+        assert git(git_repo, "status", "--porcelain") == ""
+        assert "interrupted run run-1" in git(git_repo, "stash", "list")
+        assert "were stashed" in cleaned.describe()
+
+    @pytest.mark.asyncio
+    async def test_leftover_agent_processes_are_stopped(self, tmp_path, git_repo):
+        journal = RunJournal(tmp_path / "runs")
+        entry = await journal.start(_entry(git_repo))
+        leftover = subprocess.Popen(["sleep", "30"])
+        try:
+            journal.add_process(entry, leftover.pid)
+            entry.agent_pids[str(_dead_pid())] = "1"
+
+            recovery = await recover_orphaned_run(entry)
+
+            assert recovery.stopped_pids == [leftover.pid]
+            assert leftover.wait(timeout=5) != 0
+        finally:
+            leftover.kill()
+        assert recovery.describe() == (
+            "Remote Coder restarted while `claude` was working on this thread, so that run did not finish. "
+            "Stopped 1 leftover agent process(es). It left no uncommitted changes behind. "
+            "Send your request again to retry it."
+        )
+
+    def test_read_only_runs_have_nothing_to_keep(self, tmp_path):
+        entry = _entry(tmp_path)
+        entry.read_only = True
+
+        assert "no uncommitted changes" in Recovery(entry, changed_files=3).describe()
@@ -0,0 +1,111 @@
+"""Tests for per-agent timeout, retry, and cost settings."""
+
+from __future__ import annotations
+
+import asyncio
+import os
+import sys
+
+import pytest
+
+from src.agent_adapters.base import spawn_cli
+from src.core.config import _load_agents, _load_projects
+from src.core.errors import ConfigError
+from src.core.models import DEFAULT_KILL_GRACE_SECS, RunTimeouts
+
+
+def _write_agents(tmp_path, extra: str):
+    agents_yaml = tmp_path / "agents.yaml"
+    agents_yaml.write_text(f"agents:\n  claude:\n    type: claude\n    command: [claude]\n{extra}")
+    return agents_yaml
+
+
+class TestRunPolicyConfig:
+    """Test cases for the run policy fields in agents.yaml."""
+
+    def test_parses_policy(self, tmp_path):
+        agents_yaml = _write_agents(tmp_path, "    timeout_secs: 900\n    max_retries: 2\n    max_cost_usd: 1.5\n")
+
+        agent = _load_agents(agents_yaml)["claude"]
+
+        assert (agent.timeout_secs, agent.max_retries, agent.max_cost_usd) == (900, 2, 1.5)
+
+    def test_defaults_are_unbounded(self, tmp_path):
+        agent = _load_agents(_write_agents(tmp_path, ""))["claude"]
+
+        assert (agent.timeout_secs, agent.max_retries, agent.max_cost_usd) == (None, 0, None)
+        assert (agent.transient_retries, agent.retry_backoff_secs) == (2, 5.0)
+
+    def test_parses_transient_retries(self, tmp_path):
+        agents_yaml = _write_agents(tmp_path, "    transient_retries: 4\n    retry_backoff_secs: 0.5\n")
+
+        agent = _load_agents(agents_yaml)["claude"]
+
+        assert (agent.transient_retries, agent.retry_backoff_secs) == (4, 0.5)
+
+    @pytest.mark.parametrize(
+        "extra, message",
+        [
+            ("    timeout_secs: 1.5\n", "timeout_secs for agent claude must be a positive integer"),
+            ("    timeout_secs: 0\n", "timeout_secs for agent claude must be a positive integer"),
+            ("    max_retries: -1\n", "max_retries for agent claude must be a non-negative integer"),
+            ("    max_cost_usd: free\n", "max_cost_usd for agent claude must be a positive number"),
+            ("    transient_retries: 1.5\n", "transient_retries for agent claude must be a non-negative integer"),
+            ("    retry_backoff_secs: -1\n", "retry_backoff_secs for agent claude must be a non-negative number"),
+            ("    hard_timeout_secs: 60\n", "hard_timeout_secs for agent claude must be longer than its timeout_secs"),
+            (
+                "    timeout_secs: 60\n    hard_timeout_secs: 60\n",
+                "hard_timeout_secs for agent claude must be longer than its timeout_secs",
+            ),
+        ],
+    )
+    def test_rejects_invalid_values(self, tmp_path, extra, message):
+        with pytest.raises(ConfigError, match=message):
+            _load_agents(_write_agents(tmp_path, extra))
+
+    def test_hard_timeout_defaults_to_a_grace_period(self, tmp_path):
+        agents = _load_agents(_write_agents(tmp_path, "    timeout_secs: 600\n"))
+        assert agents["claude"].timeouts == RunTimeouts(600, 600 + DEFAULT_KILL_GRACE_SECS)
+
+        agents = _load_agents(_write_agents(tmp_path, "    timeout_secs: 600\n    hard_timeout_secs: 900\n"))
+        assert agents["claude"].timeouts == RunTimeouts(600, 900)
+
+    def test_project_timeouts_replace_the_agents(self, tmp_path):
+        """A project's limits apply to every agent run in it, also when set through its group."""
+        agent = _load_agents(_write_agents(tmp_path, "    timeout_secs: 600\n"))["claude"]
+        projects_yaml = tmp_path / "projects.yaml"
+        projects_yaml.write_text(
+            f"base_dir: {tmp_path}\n"
+            "groups:\n  slow:\n    timeout_secs: 3600\n"
+            "projects:\n"
+            "  api:\n    path: .\n    default_agent: claude\n"
+            "  etl:\n    path: .\n    default_agent: claude\n    group: slow\n    hard_timeout_secs: 4000\n"
+        )
+
+        projects, _ = _load_projects(projects_yaml)
+
+        assert projects["api"].timeouts_for(agent) == RunTimeouts(600, 600 + DEFAULT_KILL_GRACE_SECS)
+        assert projects["etl"].timeouts_for(agent) == RunTimeouts(3600, 4000)
+
+
+class TestSpawnCli:
+    """Agent CLI processes do not outlive a cancelled or timed-out run."""
+
+    @pytest.mark.asyncio
+    async def test_kills_process_on_timeout(self, tmp_path):
+        started = asyncio.Event()
+        pids: list[int] = []
+
+        async def run() -> None:
+            command = [sys.executable, "-c", "import time; time.sleep(30)"]
+            async with spawn_cli(command, cwd=tmp_path, env=os.environ, stdin=False) as process:
+                pids.append(process.pid)
+                started.set()
+                await process.wait()
+
+        with pytest.raises(asyncio.TimeoutError):
+            await asyncio.wait_for(run(), 0.5)
+
+        assert started.is_set()
+        with pytest.raises(ProcessLookupError):
+            os.kill(pids[0], 0)
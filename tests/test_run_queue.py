@@ -0,0 +1,139 @@
+"""Tests for the per-project run queue."""
+
+from __future__ import annotations
+
+import asyncio
+
+import pytest
+
+from src.core.config import _load_projects
+from src.core.errors import ConfigError
+from src.core.run_queue import ProjectRunQueue
+
+
+async def _settle() -> None:
+    for _ in range(5):
+        await asyncio.sleep(0)
+
+
+class TestProjectRunQueue:
+    """Test cases for taking turns on a project's checkout."""
+
+    @pytest.mark.asyncio
+    async def test_runs_one_at_a_time_in_arrival_order(self):
+        queue = ProjectRunQueue()
+        order: list[str] = []
+        positions: dict[str, list[int]] = {"b": [], "c": []}
+        release = {name: asyncio.Event() for name in "abc"}
+
+        async def run(name: str) -> None:
+            async def on_position(position: int) -> None:
+                positions[name].append(position)
+
+            async with queue.slot("app", 1, on_position):
+                order.append(name)
+                await release[name].wait()
+
+        tasks = [asyncio.create_task(run(name)) for name in "abc"]
+        await _settle()
+
+        assert order == ["a"]
+        assert (queue.running("app"), queue.waiting("app")) == (1, 2)
+        assert positions == {"b": [2], "c": [3]}
+
+        release["a"].set()
+        await _settle()
+
+        assert order == ["a", "b"]
+        assert positions == {"b": [2, 0], "c": [3, 2]}
+
+        release["b"].set()
+        release["c"].set()
+        await asyncio.gather(*tasks)
+
+        assert order == ["a", "b", "c"]
+        assert positions["c"] == [3, 2, 0]
+        assert (queue.running("app"), queue.waiting("app")) == (0, 0)
+
+    @pytest.mark.asyncio
+    async def test_limit_and_projects_are_separate(self):
+        queue = ProjectRunQueue()
+        started: list[str] = []
+        done = asyncio.Event()
+
+        async def run(project_id: str, limit: int) -> None:
+            async with queue.slot(project_id, limit):
+                started.append(project_id)
+                await done.wait()
+
+        tasks = [
+            asyncio.create_task(run("app", 2)),
+            asyncio.create_task(run("app", 2)),
+            asyncio.create_task(run("app", 2)),
+            asyncio.create_task(run("docs", 1)),
+        ]
+        await _settle()
+
+        assert started == ["app", "app", "docs"]
+        assert queue.waiting("app") == 1
+
+        done.set()
+        await asyncio.gather(*tasks)
+
+        assert started.count("app") == 3
+
+    @pytest.mark.asyncio
+    async def test_cancelled_waiters_leave_the_line(self):
+        queue = ProjectRunQueue()
+        release = asyncio.Event()
+        positions: list[int] = []
+
+        async def hold() -> None:
+            async with queue.slot("app", 1):
+                await release.wait()
+
+        async def wait(on_position=None) -> None:
+            async with queue.slot("app", 1, on_position):
+                pass
+
+        async def track(position: int) -> None:
+            positions.append(position)
+
+        holder = asyncio.create_task(hold())
+        await _settle()
+        cancelled = asyncio.create_task(wait())
+        last = asyncio.create_task(wait(track))
+        await _settle()
+
+        cancelled.cancel()
+        await _settle()
+
+        assert positions == [3, 2]
+
+        release.set()
+        await asyncio.gather(holder, last)
+
+        assert positions == [3, 2, 0]
+        assert (queue.running("app"), queue.waiting("app")) == (0, 0)
+
+
+class TestConcurrencyConfig:
+    """Test cases for `max_concurrent_runs` in projects.yaml."""
+
+    @staticmethod
+    def _load(tmp_path, extra: str):
+        projects_yaml = tmp_path / "projects.yaml"
+        projects_yaml.write_text(
+            f"base_dir: {tmp_path}\nprojects:\n  demo:\n    path: .\n    default_agent: claude\n{extra}"
+        )
+        projects, _ = _load_projects(projects_yaml)
+        return projects["demo"]
+
+    def test_defaults_to_one_run_at_a_time(self, tmp_path):
+        assert self._load(tmp_path, "").max_concurrent_runs == 1
+        assert self._load(tmp_path, "    max_concurrent_runs: 3\n").max_concurrent_runs == 3
+
+    @pytest.mark.parametrize("value", ["0", "two", "true"])
+    def test_rejects_invalid_limits(self, tmp_path, value):
+        with pytest.raises(ConfigError, match="max_concurrent_runs must be a positive number of runs"):
+            self._load(tmp_path, f"    max_concurrent_runs: {value}\n")
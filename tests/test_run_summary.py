@@ -0,0 +1,68 @@
+"""Tests for the summary posted when a run finishes."""
+
+from __future__ import annotations
+
+import pytest
+
+from src.core.run_summary import (
+    ChangeStats,
+    RunSummary,
+    collect_change_stats,
+    format_run_summary,
+    next_steps,
+)
+from tests.conftest import git
+
+
+class TestRunSummary:
+    """Test cases for collecting and formatting what a run did."""
+
+    @pytest.mark.asyncio
+    async def test_collects_committed_uncommitted_and_new_files(self, tmp_path, git_identity):
+        git(tmp_path, "init", "--quiet", "--initial-branch=main")
+        (tmp_path / "app.py").write_text("a\nb\nc\n")
+        git(tmp_path, "add", "app.py")
+        git(tmp_path, "commit", "--quiet", "-m", "init")
+        start = git(tmp_path, "rev-parse", "HEAD").strip()
+        git(tmp_path, "checkout", "--quiet", "-b", "feature")
+        (tmp_path / "app.py").write_text("a\nc\nd\n")
+        git(tmp_path, "commit", "--quiet", "-am", "edit")
+        (tmp_path / "app.py").write_text("a\nc\nd\ne\n")
+        (tmp_path / "notes.md").write_text("one\ntwo")
+
+        stats = await collect_change_stats(tmp_path, start)
+
+        assert stats == ChangeStats(files=["app.py", "notes.md"], insertions=4, deletions=1, branch="feature")
+        assert await collect_change_stats(tmp_path / "missing", start) is None
+
+
+    def test_formats_the_summary(self):
+        files = [f"f{n}.py" for n in range(7)]
+        summary = RunSummary(
+            agent_id="claude",
+            success=True,
+            wall_secs=42.4,
+            exit_code=0,
+            changes=ChangeStats(files=files, insertions=10, deletions=3, branch="rc/fix"),
+        )
+
+        assert format_run_summary(summary) == (
+            "*Run finished*: `claude`, 42s, exit code 0\n"
+            "7 file(s) changed, +10 -3: `f0.py`, `f1.py`, `f2.py`, `f3.py`, `f4.py` and 2 more\n"
+            "Branch: `rc/fix`"
+        )
+        failed = RunSummary(agent_id="codex", success=False, wall_secs=3, changes=ChangeStats())
+        assert format_run_summary(failed) == "*Run failed*: `codex`, 3s\nNo files changed"
+
+    def test_next_steps_follow_the_session(self):
+        summary = RunSummary(
+            agent_id="claude", success=True, wall_secs=1, changes=ChangeStats(), pr_url="https://example.com/pr/1"
+        )
+
+        assert [(step.label, step.command, step.url) for step in next_steps(summary, has_checks=True)] == [
+            ("Open PR", None, "https://example.com/pr/1"),
+            ("Run tests", "!test", None),
+            ("Continue", "continue", None),
+        ]
+        ask = RunSummary(agent_id="claude", success=True, wall_secs=1)
+        assert [step.label for step in next_steps(ask, has_checks=True)] == ["Continue"]
@@ -0,0 +1,186 @@
+"""Tests for sandboxed command execution and project check phases."""
+
+from __future__ import annotations
+
+import time
+from pathlib import Path
+
+import pytest
+
+from src.core import sandbox as sandbox_module
+from src.core.config import _load_projects
+from src.core.errors import ConfigError
+from src.core.models import CommandStep, Project, SandboxConfig, SandboxMode
+from src.core.sandbox import Sandbox, container_argv, sandbox_env
+from src.core.verify import format_verify_results, run_project_checks
+
+
+def _project(tmp_path: Path, **overrides) -> Project:
+    return Project(id="demo", channel_name="demo", path=tmp_path, default_agent_id="claude", **overrides)
+
+
+class TestSandbox:
+    """Test cases for Sandbox.run and its helpers."""
+
+    def test_env_keeps_base_and_allowlisted_vars_only(self):
+        environ = {"PATH": "/bin", "HOME": "/home/u", "SLACK_BOT_TOKEN": "xoxb", "NPM_TOKEN": "npm"}
+
+        env = sandbox_env(SandboxConfig(env_passthrough=["NPM_TOKEN"]), environ)
+
+        assert env == {"PATH": "/bin", "HOME": "/home/u", "NPM_TOKEN": "npm"}
+
+    @pytest.mark.asyncio
+    async def test_process_mode_hides_daemon_secrets(self, tmp_path, monkeypatch):
+        monkeypatch.setenv("GITHUB_TOKEN", "secret")
+
+        result = await Sandbox().run('echo "token=${GITHUB_TOKEN:-unset}"', tmp_path, 10)
+
+        assert result.passed
+        assert "token=unset" in result.output
+
+    @pytest.mark.asyncio
+    async def test_none_mode_keeps_environment(self, tmp_path, monkeypatch):
+        monkeypatch.setenv("GITHUB_TOKEN", "secret")
+
+        result = await Sandbox(SandboxConfig(mode=SandboxMode.NONE)).run("echo $GITHUB_TOKEN", tmp_path, 10)
+
+        assert "secret" in result.output
+
+    @pytest.mark.asyncio
+    async def test_timeout_kills_background_children(self, tmp_path):
+        started = time.monotonic()
+
+        result = await Sandbox().run("sleep 30 & echo started; sleep 30", tmp_path, 0.5)
+
+        assert result.timed_out
+        assert not result.passed
+        assert result.returncode is None
+        assert "started" in result.output
+        assert time.monotonic() - started < 5
+
+    @pytest.mark.asyncio
+    async def test_output_keeps_the_tail(self, tmp_path, monkeypatch):
+        monkeypatch.setattr(sandbox_module, "MAX_CAPTURE_BYTES", 100)
+
+        result = await Sandbox().run("seq 1 1000", tmp_path, 10)
+
+        assert result.truncated
+        assert len(result.output) == 100
+        assert result.output.endswith("1000\n")
+
+    def test_container_argv(self, tmp_path):
+        config = SandboxConfig(
+            mode=SandboxMode.CONTAINER,
+            image="python:3.12",
+            runtime="podman",
+            memory_mb=512,
+            env_passthrough=["CI"],
+        )
+
+        argv = container_argv(config, "pytest -q", tmp_path, "rc-1")
+
+        assert argv[:4] == ["podman", "run", "--rm", "--name"]
+        assert f"{tmp_path.resolve()}:/workspace" in argv
+        assert argv[argv.index("--network") + 1] == "none"
+        assert argv[argv.index("--memory") + 1] == "512m"
+        assert argv[argv.index("--env") + 1] == "CI"
+        assert argv[-4:] == ["python:3.12", "sh", "-c", "pytest -q"]
+
+    def test_container_argv_with_network(self, tmp_path):
+        config = SandboxConfig(mode=SandboxMode.CONTAINER, image="node:20", network=True)
+
+        assert "--network" not in container_argv(config, "npm test", tmp_path, "rc-2")
+
+
+class TestProjectChecks:
+    """Test cases for setup/verify/teardown phases."""
+
+    @pytest.mark.asyncio
+    async def test_setup_failure_skips_verify_but_runs_teardown(self, tmp_path):
+        project = _project(
+            tmp_path,
+            setup_commands=[CommandStep("echo no deps && false")],
+            verify_commands=[CommandStep("touch verified")],
+            teardown_commands=[CommandStep("touch cleaned")],
+        )
+
+        results = await run_project_checks(project, tmp_path)
+
+        assert [result.phase for result in results] == ["setup", "teardown"]
+        assert not (tmp_path / "verified").exists()
+        assert (tmp_path / "cleaned").exists()
+        text = format_verify_results(results)
+        assert text.startswith("Verify steps failed:")
+        assert "(setup): failed (exit code 1)" in text
+        assert "no deps" in text
+
+    @pytest.mark.asyncio
+    async def test_teardown_failure_does_not_fail_run(self, tmp_path):
+        project = _project(
+            tmp_path,
+            verify_commands=[CommandStep("true")],
+            teardown_commands=[CommandStep("false"), CommandStep("touch cleaned")],
+        )
+
+        results = await run_project_checks(project, tmp_path)
+
+        assert (tmp_path / "cleaned").exists()
+        text = format_verify_results(results)
+        assert text.startswith("Verify steps passed:")
+        assert "`false` (teardown): failed" in text
+
+    @pytest.mark.asyncio
+    async def test_per_step_timeout(self, tmp_path):
+        project = _project(tmp_path, verify_commands=[CommandStep("sleep 10", timeout_secs=1)])
+
+        results = await run_project_checks(project, tmp_path)
+
+        assert results[0].timed_out
+        assert "`sleep 10`: timed out after 1s" in format_verify_results(results)
+
+
+class TestSandboxConfig:
+    """Test cases for parsing sandbox and step config."""
+
+    def _load(self, tmp_path: Path, body: str) -> Project:
+        (tmp_path / "repo").mkdir(exist_ok=True)
+        projects_yaml = tmp_path / "projects.yaml"
+        projects_yaml.write_text(
+            f"base_dir: {tmp_path}\nprojects:\n  demo:\n    path: repo\n    default_agent: claude\n" + body
+        )
+        projects, _ = _load_projects(projects_yaml)
+        return projects["demo"]
+
+    def test_steps_and_container_sandbox(self, tmp_path):
+        project = self._load(
+            tmp_path,
+            "    setup: [npm ci]\n"
+            "    verify:\n"
+            "      - npm test\n"
+            "      - run: npm run e2e\n"
+            "        timeout_secs: 1200\n"
+            "    sandbox:\n"
+            "      mode: container\n"
+            "      image: node:20\n"
+            "      memory_mb: 2048\n",
+        )
+
+        assert project.setup_commands == [CommandStep("npm ci")]
+        assert project.verify_commands == [CommandStep("npm test"), CommandStep("npm run e2e", timeout_secs=1200)]
+        assert project.sandbox.mode == SandboxMode.CONTAINER
+        assert project.sandbox.image == "node:20"
+        assert project.sandbox.memory_mb == 2048
+
+    def test_defaults_to_process_sandbox(self, tmp_path):
+        project = self._load(tmp_path, "")
+
+        assert project.sandbox == SandboxConfig()
+        assert project.sandbox.mode == SandboxMode.PROCESS
+
+    def test_container_requires_image(self, tmp_path):
+        with pytest.raises(ConfigError, match="requires an image"):
+            self._load(tmp_path, "    sandbox: container\n")
+
+    def test_rejects_bad_step_timeout(self, tmp_path):
+        with pytest.raises(ConfigError, match="timeout_secs"):
+            self._load(tmp_path, "    verify:\n      - run: make test\n        timeout_secs: 0\n")
@@ -0,0 +1,237 @@
+"""Tests for the daemon-wide run scheduler."""
+
+from __future__ import annotations
+
+import asyncio
+
+import pytest
+
+from src.core.config import load_config
+from src.core.errors import ConfigError
+from src.core.scheduler import DEFAULT_MAX_RUNS, Job, RunPriority, RunScheduler
+
+
+async def _settle() -> None:
+    for _ in range(5):
+        await asyncio.sleep(0)
+
+
+class TestRunScheduler:
+    """Test cases for capping and ordering agent runs across projects."""
+
+    @pytest.mark.asyncio
+    async def test_higher_priorities_go_first_once_a_slot_frees(self):
+        scheduler = RunScheduler(limit=1)
+        order: list[str] = []
+        positions: dict[str, list[int]] = {"batch": [], "webhook": [], "chat": []}
+        release = asyncio.Event()
+
+        async def run(name: str, priority: RunPriority) -> None:
+            async def on_position(position: int) -> None:
+                positions[name].append(position)
+
+            async with scheduler.slot(Job(priority, "app", name), on_position):
+                order.append(name)
+                await release.wait()
+
+        holder = asyncio.create_task(run("first", RunPriority.BATCH))
+        await _settle()
+        batch = asyncio.create_task(run("batch", RunPriority.BATCH))
+        await _settle()
+        webhook = asyncio.create_task(run("webhook", RunPriority.SCHEDULED))
+        await _settle()
+        chat = asyncio.create_task(run("chat", RunPriority.INTERACTIVE))
+        await _settle()
+
+        assert [job.thread for job in scheduler.waiting()] == ["chat", "webhook", "batch"]
+        assert positions == {"batch": [1, 2, 3], "webhook": [1, 2], "chat": [1]}
+        assert scheduler.position("batch") == 3
+        assert scheduler.position("first") is None
+        assert scheduler.describe() == "1 of 1 run slots in use, 3 waiting"
+
+        release.set()
+        await asyncio.gather(holder, batch, webhook, chat)
+
+        assert order == ["first", "chat", "webhook", "batch"]
+        assert positions["batch"][-1] == 0
+        assert (scheduler.running(), scheduler.waiting()) == ([], [])
+
+    @pytest.mark.asyncio
+    async def test_caps_runs_across_projects(self):
+        scheduler = RunScheduler(limit=2)
+        started: list[str] = []
+        done = asyncio.Event()
+
+        async def run(project_id: str) -> None:
+            async with scheduler.slot(Job(RunPriority.INTERACTIVE, project_id)):
+                started.append(project_id)
+                await done.wait()
+
+        tasks = [asyncio.create_task(run(project_id)) for project_id in ("app", "docs", "api")]
+        await _settle()
+
+        assert started == ["app", "docs"]
+        assert [job.project_id for job in scheduler.waiting()] == ["api"]
+
+        done.set()
+        await asyncio.gather(*tasks)
+
+        assert started == ["app", "docs", "api"]
+
+    @pytest.mark.asyncio
+    async def test_raising_the_limit_starts_waiting_runs(self):
+        scheduler = RunScheduler(limit=1)
+        started: list[str] = []
+        done = asyncio.Event()
+
+        async def run(name: str) -> None:
+            async with scheduler.slot(Job(RunPriority.BATCH, "app", name)):
+                started.append(name)
+                await done.wait()
+
+        tasks = [asyncio.create_task(run(name)) for name in ("a", "b")]
+        await _settle()
+        scheduler.set_limit(2)
+        await _settle()
+
+        assert started == ["a", "b"]
+
+        done.set()
+        await asyncio.gather(*tasks)
+
+    @pytest.mark.asyncio
+    async def test_cancelled_waiters_leave_the_line(self):
+        scheduler = RunScheduler(limit=1)
+        release = asyncio.Event()
+        positions: list[int] = []
+
+        async def hold() -> None:
+            async with scheduler.slot(Job(RunPriority.INTERACTIVE, "app")):
+                await release.wait()
+
+        async def wait(on_position=None) -> None:
+            async with scheduler.slot(Job(RunPriority.BATCH, "app"), on_position):
+                pass
+
+        async def track(position: int) -> None:
+            positions.append(position)
+
+        holder = asyncio.create_task(hold())
+        await _settle()
+        cancelled = asyncio.create_task(wait())
+        last = asyncio.create_task(wait(track))
+        await _settle()
+
+        cancelled.cancel()
+        await _settle()
+
+        assert positions == [2, 1]
+
+        release.set()
+        await asyncio.gather(holder, last)
+
+        assert positions == [2, 1, 0]
+        assert (scheduler.running(), scheduler.waiting()) == ([], [])
+
+
+
+class TestPreemption:
+    """Test cases for stopping background runs to make room for chat messages."""
+
+    @staticmethod
+    def _background(scheduler: RunScheduler, log: list[str], release: asyncio.Event, preempted: list[int]):
+        job = Job(RunPriority.BATCH, "app", "C1:1")
+
+        async def attempt(number: int) -> str:
+            async with scheduler.slot(job):
+                log.append(f"batch attempt {number}")
+                await release.wait()
+                return f"done after {number} stop(s)"
+
+        async def on_preempted(count: int) -> None:
+            preempted.append(count)
+
+        return scheduler.run_preemptible(job, attempt, on_preempted)
+
+    @pytest.mark.asyncio
+    async def test_chat_runs_stop_background_runs_which_start_over(self):
+        scheduler = RunScheduler(limit=1)
+        log: list[str] = []
+        preempted: list[int] = []
+        release = asyncio.Event()
+
+        background = asyncio.create_task(self._background(scheduler, log, release, preempted))
+        await _settle()
+
+        async with scheduler.slot(Job(RunPriority.INTERACTIVE, "web", "C2:2")):
+            log.append("chat")
+            await _settle()
+            assert [job.priority for job in scheduler.waiting()] == [RunPriority.BATCH]
+
+        release.set()
+
+        assert await background == "done after 1 stop(s)"
+        assert log == ["batch attempt 0", "chat", "batch attempt 1"]
+        assert preempted == [1]
+        assert (scheduler.running(), scheduler.waiting()) == ([], [])
+
+    @pytest.mark.asyncio
+    async def test_cancelling_a_preemptible_run_still_stops_it(self):
+        scheduler = RunScheduler(limit=1)
+        log: list[str] = []
+        release = asyncio.Event()
+
+        background = asyncio.create_task(self._background(scheduler, log, release, []))
+        await _settle()
+        background.cancel()
+
+        with pytest.raises(asyncio.CancelledError):
+            await background
+        assert log == ["batch attempt 0"]
+        assert scheduler.running() == []
+
+    @pytest.mark.asyncio
+    async def test_project_preemption_and_opting_out(self):
+        scheduler = RunScheduler(limit=2, preemption=False)
+        release = asyncio.Event()
+
+        background = asyncio.create_task(self._background(scheduler, [], release, []))
+        await _settle()
+        chat = Job(RunPriority.INTERACTIVE, "app", "C2:2")
+
+        assert scheduler.preempt(chat, project_id="app") is None
+        scheduler.preemption = True
+        assert scheduler.preempt(chat, project_id="web") is None
+        assert scheduler.preempt(Job(RunPriority.BATCH, "app"), project_id="app") is None
+        assert scheduler.preempt(chat, project_id="app") == Job(RunPriority.BATCH, "app", "C1:1")
+
+        release.set()
+        assert await background == "done after 1 stop(s)"
+
+class TestMaxRunsConfig:
+    """Test cases for `REMOTE_CODER_MAX_RUNS` and `REMOTE_CODER_DISABLE_PREEMPTION`."""
+
+    @staticmethod
+    def _load(tmp_path):
+        (tmp_path / "projects.yaml").write_text(f"base_dir: {tmp_path}\nprojects: {{}}\n")
+        (tmp_path / "agents.yaml").write_text("agents: {}\n")
+        return load_config(tmp_path, require_slack=False)
+
+    def test_defaults_and_override(self, tmp_path, monkeypatch):
+        monkeypatch.delenv("REMOTE_CODER_MAX_RUNS", raising=False)
+        assert self._load(tmp_path).max_runs == DEFAULT_MAX_RUNS
+
+        monkeypatch.setenv("REMOTE_CODER_MAX_RUNS", "2")
+        assert self._load(tmp_path).max_runs == 2
+
+    def test_preemption_can_be_turned_off(self, tmp_path, monkeypatch):
+        monkeypatch.delenv("REMOTE_CODER_DISABLE_PREEMPTION", raising=False)
+        assert self._load(tmp_path).preemption
+
+        monkeypatch.setenv("REMOTE_CODER_DISABLE_PREEMPTION", "1")
+        assert not self._load(tmp_path).preemption
+
+    def test_rejects_zero(self, tmp_path, monkeypatch):
+        monkeypatch.setenv("REMOTE_CODER_MAX_RUNS", "0")
+        with pytest.raises(ConfigError, match="REMOTE_CODER_MAX_RUNS must be a positive number of runs"):
+            self._load(tmp_path)
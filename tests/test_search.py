@@ -0,0 +1,101 @@
+"""Tests for the full-text search index over session transcripts."""
+
+from __future__ import annotations
+
+import sqlite3
+
+import pytest
+
+from src.core.conversation import SessionManager
+from src.core.errors import SearchError
+from src.core.models import AgentType, Project, PullRequestRef
+from src.core.search import SCHEMA_VERSION, SearchIndex, fts_query
+from src.core.transcripts import RunRecord, TranscriptStore
+
+
+def _manager(tmp_path) -> SessionManager:
+    return SessionManager(transcript=TranscriptStore(tmp_path / "transcripts"))
+
+
+def _start(manager: SessionManager, tmp_path, thread_ts: str, prompt: str, reply: str, project_id: str = "api"):
+    project = Project(id=project_id, channel_name=project_id, path=tmp_path, default_agent_id="claude")
+    session = manager.create_session(
+        project=project, channel_id="C1", thread_ts=thread_ts, agent_id="claude", agent_type=AgentType.CLAUDE
+    )
+    manager.append_user_message(session.id, prompt)
+    manager.append_agent_message(session.id, reply)
+    return session
+
+
+class TestSearchIndex:
+    """Test cases for indexing and querying sessions."""
+
+    def test_finds_sessions_by_prompt_reply_files_and_diff(self, tmp_path):
+        manager = _manager(tmp_path)
+        index = SearchIndex(tmp_path / "search.db")
+        login = _start(manager, tmp_path, "1.0", "Fix the login redirect loop", "Changed the session cookie path.")
+        manager.record_run(login.id, RunRecord(agent_id="claude", success=True, wall_secs=1.0, files=["auth/views.py"]))
+        pr = PullRequestRef(
+            project_id="api",
+            session_id=login.id,
+            number=12,
+            url="https://github.com/o/api/pull/12",
+            head_branch="b",
+            base_branch="main",
+        )
+        docs = _start(manager, tmp_path, "2.0", "Document the rate limit", "Added a section on retries.")
+        index.update(login, manager.transcript(login.id), [pr], diff="+LIMIT_RATE = 60\n")
+        index.update(docs, manager.transcript(docs.id))
+
+        [hit] = index.search("cookies")  # Stemmed
+        assert (hit.session_id, hit.thread_ts, hit.title) == (str(login.id), "1.0", "Fix the login redirect loop")
+        assert hit.prs == [(12, "https://github.com/o/api/pull/12")]
+        assert "*cookie*" in hit.snippet
+        assert [hit.session_id for hit in index.search("views")] == [str(login.id)]
+        assert {hit.session_id for hit in index.search("rate limit*")} == {str(login.id), str(docs.id)}
+        assert [hit.session_id for hit in index.search('"rate limit"')] == [str(docs.id)]
+
+    def test_limits_results_to_a_project(self, tmp_path):
+        manager = _manager(tmp_path)
+        index = SearchIndex(tmp_path / "search.db")
+        api = _start(manager, tmp_path, "1.0", "Retry the webhook deliveries", "Added backoff.")
+        web = _start(manager, tmp_path, "2.0", "Show webhook failures", "Added a banner.", project_id="web")
+        for session in (api, web):
+            index.update(session, manager.transcript(session.id))
+
+        assert [hit.session_id for hit in index.search("webhook", project_id="web")] == [str(web.id)]
+        assert {hit.project_id for hit in index.search("webhook")} == {"api", "web"}
+        assert index.search("webhook", project_id="docs") == []
+
+    def test_adds_only_new_entries_and_replaces_the_diff(self, tmp_path):
+        manager = _manager(tmp_path)
+        index = SearchIndex(tmp_path / "search.db")
+        session = _start(manager, tmp_path, "1.0", "Add a health check", "Added /healthz.")
+
+        assert index.update(session, manager.transcript(session.id), diff="+def liveness():\n") == 2
+        assert index.update(session, manager.transcript(session.id)) == 0
+        manager.append_user_message(session.id, "Now add readiness too")
+        assert index.update(session, manager.transcript(session.id), diff="+def readyz():\n") == 1
+
+        assert index.indexed_entries(session.id) == 3
+        assert index.search("liveness") == []  # Only in the old diff
+        assert index.search("readyz") and index.search("readiness") and index.search("healthz")
+        index.close()
+        reopened = SearchIndex(tmp_path / "search.db")
+        assert reopened.indexed_entries(session.id) == 3
+
+    def test_user_input_never_reaches_fts_syntax(self, tmp_path):
+        index = SearchIndex(tmp_path / "search.db")
+
+        assert fts_query('fix" OR (login NEAR x) auth*') == '"fix" "OR" "(login" "NEAR" "x)" "auth"*'
+        assert index.search('NOT "unbalanced') == []
+        with pytest.raises(SearchError):
+            index.search(' "" * ')
+
+    def test_refuses_an_index_from_a_newer_version(self, tmp_path):
+        path = tmp_path / "search.db"
+        with sqlite3.connect(path) as db:
+            db.execute(f"PRAGMA user_version={SCHEMA_VERSION + 1}")
+
+        with pytest.raises(SearchError, match="newer"):
+            SearchIndex(path)
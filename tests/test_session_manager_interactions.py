@@ -4,11 +4,14 @@ import pytest
 
 from src.agent_adapters.base import AgentResult, FileEdit
 from src.core.conversation import InteractionClassifier, SessionManager
+from src.core.errors import AgentNotAllowed, SessionNotFound
 from src.core.models import (
     Agent,
     AgentType,
     ConversationMessage,
     Project,
+    PullRequestRef,
+    PullRequestRole,
     WorkingDirMode,
 )
 from pathlib import Path
@@ -346,3 +349,130 @@ class TestSessionManagerInteractions:
         assert len(session.interactions) == 10
         # Summary should have been triggered
         assert session.conversation_summary is not None
+
+    def test_set_active_agent_enforces_allowed_agents(self, session_manager, test_project):
+        """Switching to an agent outside the project's allowed_agents is refused."""
+        test_project.allowed_agents = ["claude"]
+        session = session_manager.create_session(
+            project=test_project,
+            channel_id="C123",
+            thread_ts="1234567890.123456",
+            agent_id="claude",
+            agent_type=AgentType.CLAUDE,
+        )
+
+        with pytest.raises(AgentNotAllowed):
+            session_manager.set_active_agent(session.id, "codex", AgentType.CODEX, project=test_project)
+
+        assert session.active_agent_id == "claude"
+        session_manager.set_active_agent(session.id, "claude", AgentType.CLAUDE, "opus", project=test_project)
+        assert session.active_model == "opus"
+
+    def test_rewind_last_run(self, session_manager, classifier, test_project):
+        """Rewinding drops the last run's messages and interaction but keeps earlier ones."""
+        session = session_manager.create_session(
+            project=test_project,
+            channel_id="C123",
+            thread_ts="1234567890.123456",
+            agent_id="claude",
+            agent_type=AgentType.CLAUDE,
+        )
+        result = AgentResult(
+            success=True,
+            output_text="Changed things",
+            file_edits=[FileEdit(path="a.py", type="edit")],
+        )
+        for prompt in ("first ask", "badly worded ask"):
+            session_manager.append_user_message(session.id, prompt)
+            session_manager.append_interaction(
+                session.id,
+                user_message=ConversationMessage(role="user", content=prompt),
+                agent_result=result,
+                classifier=classifier,
+            )
+            session_manager.append_agent_message(session.id, "Changed things")
+
+        session_manager.rewind_last_run(session.id, "badly worded ask")
+
+        assert [msg.content for msg in session.conversation_history] == ["first ask", "Changed things"]
+        assert [item.user_message.content for item in session.interactions] == ["first ask"]
+
+
+class TestSessionManagerPullRequests:
+    """Test cases for tracking several PRs per session."""
+
+    @pytest.fixture
+    def session(self, tmp_path):
+        manager = SessionManager()
+        project = Project(id="api", channel_name="api", path=tmp_path, default_agent_id="claude")
+        session = manager.create_session(
+            project=project, channel_id="C1", thread_ts="1.0", agent_id="claude", agent_type=AgentType.CLAUDE
+        )
+        return manager, session
+
+    @staticmethod
+    def _pr(session, number, role=PullRequestRole.PRIMARY, repo="o/api") -> PullRequestRef:
+        return PullRequestRef(
+            project_id="api",
+            session_id=session.id,
+            number=number,
+            url=f"https://github.com/{repo}/pull/{number}",
+            head_branch=f"branch-{number}",
+            base_branch="main",
+            role=role,
+            repo=repo,
+        )
+
+    def test_keeps_prs_by_role_and_repo(self, session):
+        manager, session = session
+        manager.set_pr_ref(self._pr(session, 1))
+        manager.set_pr_ref(self._pr(session, 2, PullRequestRole.STACKED))
+        manager.set_pr_ref(self._pr(session, 3, PullRequestRole.BACKPORT))
+        manager.set_pr_ref(self._pr(session, 4, repo="o/web"))
+
+        assert [ref.number for ref in manager.list_pr_refs(session.id)] == [1, 2, 3, 4]
+        assert manager.get_pr_ref(session.id).number == 1
+        assert manager.get_pr_ref(session.id, repo="O/Web").number == 4
+        assert manager.get_pr_ref(session.id, role=PullRequestRole.BACKPORT).number == 3
+        assert [ref.number for ref in manager.list_pr_refs(session.id, role=PullRequestRole.PRIMARY)] == [1, 4]
+
+    def test_updates_replace_the_same_pr_and_the_primary(self, session):
+        manager, session = session
+        manager.set_pr_ref(self._pr(session, 1))
+        manager.set_pr_ref(self._pr(session, 2, PullRequestRole.STACKED))
+        manager.set_pr_ref(self._pr(session, 2, PullRequestRole.STACKED))
+        manager.set_pr_ref(self._pr(session, 5))
+
+        assert [ref.number for ref in manager.list_pr_refs(session.id)] == [2, 5]
+
+    def test_clear_one_or_all(self, session):
+        manager, session = session
+        manager.set_pr_ref(self._pr(session, 1))
+        manager.set_pr_ref(self._pr(session, 2, PullRequestRole.STACKED))
+
+        manager.clear_pr_ref(session.id, 1, repo="o/api")
+        with pytest.raises(SessionNotFound):
+            manager.get_pr_ref(session.id)
+        assert [ref.number for ref in manager.list_pr_refs(session.id)] == [2]
+
+        manager.clear_pr_ref(session.id)
+        assert manager.list_pr_refs(session.id) == []
+
+    def test_finds_the_sessions_of_a_pr_number(self, session):
+        manager, session = session
+        project = Project(id="api", channel_name="api", path=session.project_path, default_agent_id="claude")
+        other = manager.create_session(
+            project=project, channel_id="C1", thread_ts="2.0", agent_id="claude", agent_type=AgentType.CLAUDE
+        )
+        manager.set_pr_ref(self._pr(session, 7))
+        manager.set_pr_ref(self._pr(other, 7, repo="o/web"))
+        manager.set_pr_ref(self._pr(other, 8, PullRequestRole.STACKED, repo=None))
+
+        assert {ref.session_id for ref in manager.find_pr_refs(7)} == {session.id, other.id}
+        assert [ref.session_id for ref in manager.find_pr_refs(7, repo="O/API")] == [session.id]
+        assert [ref.session_id for ref in manager.find_pr_refs(8, repo="o/anything")] == [other.id]
+
+        manager.set_pr_ref(self._pr(session, 9))  # Replaces #7 as the primary PR of o/api
+        manager.clear_pr_ref(other.id, 8)
+        assert manager.find_pr_refs(7, repo="o/api") == [] and manager.find_pr_refs(8) == []
+        assert [ref.number for ref in manager.find_pr_refs(9)] == [9]
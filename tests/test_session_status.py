@@ -0,0 +1,84 @@
+"""Tests for the session status transitions checked by SessionManager."""
+
+from __future__ import annotations
+
+import pytest
+
+from src.core.conversation import SessionManager
+from src.core.conversation.session_manager import TRANSITIONS, can_transition
+from src.core.errors import InvalidStatusTransition
+from src.core.models import AgentType, Project, SessionStatus
+from src.core.session_store import SqliteSessionStore
+from src.core.transcripts import RunRecord
+
+
+def _start(manager: SessionManager, tmp_path):
+    project = Project(id="api", channel_name="api", path=tmp_path, default_agent_id="claude")
+    return manager.create_session(
+        project=project, channel_id="C1", thread_ts="1.0", agent_id="claude", agent_type=AgentType.CLAUDE
+    )
+
+
+class TestSessionStatus:
+    """Test cases for moving sessions between statuses."""
+
+    def test_every_status_has_transitions(self):
+        assert set(TRANSITIONS) == set(SessionStatus)
+        assert all(can_transition(status, SessionStatus.ACTIVE) for status in SessionStatus)
+        assert not can_transition(SessionStatus.ARCHIVED, SessionStatus.RUNNING)
+        assert not can_transition(SessionStatus.PAUSED, SessionStatus.RUNNING)
+
+    def test_invalid_transitions_are_refused(self, tmp_path):
+        manager = SessionManager()
+        session = _start(manager, tmp_path)
+        manager.update_status(session.id, SessionStatus.ENDED)
+
+        with pytest.raises(InvalidStatusTransition, match="cannot go from ended to running"):
+            manager.update_status(session.id, SessionStatus.RUNNING)
+        assert session.status == SessionStatus.ENDED
+
+    def test_a_run_goes_queued_running_and_back(self, tmp_path):
+        manager = SessionManager()
+        session = _start(manager, tmp_path)
+
+        assert manager.set_run_state(session.id, SessionStatus.QUEUED)
+        assert manager.set_run_state(session.id, SessionStatus.RUNNING)
+        with manager.awaiting(session.id, SessionStatus.AWAITING_INPUT):
+            assert session.status == SessionStatus.AWAITING_INPUT
+        assert session.status == SessionStatus.RUNNING
+        manager.finish_run(session.id)
+
+        assert session.status == SessionStatus.ACTIVE
+
+    def test_failed_runs_leave_the_session_failed(self, tmp_path):
+        manager = SessionManager()
+        session = _start(manager, tmp_path)
+        manager.set_run_state(session.id, SessionStatus.RUNNING)
+
+        manager.record_run(session.id, RunRecord(agent_id="claude", success=False, wall_secs=1))
+        manager.finish_run(session.id)
+
+        assert session.status == SessionStatus.FAILED
+        assert session.status.is_open
+        assert manager.set_run_state(session.id, SessionStatus.QUEUED)
+
+    def test_runs_leave_paused_sessions_paused(self, tmp_path):
+        manager = SessionManager()
+        session = _start(manager, tmp_path)
+        manager.set_run_state(session.id, SessionStatus.RUNNING)
+        manager.update_status(session.id, SessionStatus.PAUSED)
+
+        assert not manager.set_run_state(session.id, SessionStatus.ACTIVE)
+        manager.finish_run(session.id)
+
+        assert session.status == SessionStatus.PAUSED
+
+    def test_sessions_stopped_part_way_through_a_run_load_as_active(self, tmp_path):
+        manager = SessionManager(store=SqliteSessionStore(tmp_path / "sessions.db"))
+        session = _start(manager, tmp_path)
+        manager.set_run_state(session.id, SessionStatus.RUNNING)
+
+        reopened = SessionManager(store=SqliteSessionStore(tmp_path / "sessions.db"))
+        reopened.load_from_store()
+
+        assert reopened.get_session(session.id).status == SessionStatus.ACTIVE
@@ -0,0 +1,133 @@
+"""Tests for writing sessions through to the SQLite session store."""
+
+from __future__ import annotations
+
+from datetime import timedelta
+
+from src.core.conversation import SessionManager
+from src.core.models import AgentType, Project, PullRequestRef, PullRequestRole, SessionStatus
+from src.core.session_store import SqliteSessionStore, set_aside
+from src.core.state import StateStore
+
+
+def _project(path) -> Project:
+    return Project(id="api", channel_name="api", path=path, default_agent_id="claude")
+
+
+def _manager(tmp_path) -> SessionManager:
+    return SessionManager(store=SqliteSessionStore(tmp_path / "sessions.db"))
+
+
+def _reopened(tmp_path) -> SessionManager:
+    manager = _manager(tmp_path)
+    manager.load_from_store()
+    return manager
+
+
+def _start(manager: SessionManager, project: Project, thread_ts: str = "100.1"):
+    return manager.create_session(
+        project=project,
+        channel_id="C1",
+        thread_ts=thread_ts,
+        agent_id="claude",
+        agent_type=AgentType.CLAUDE,
+    )
+
+
+def _pr(project: Project, session_id, number: int, role: PullRequestRole = PullRequestRole.PRIMARY) -> PullRequestRef:
+    return PullRequestRef(
+        project_id=project.id,
+        session_id=session_id,
+        number=number,
+        url=f"https://github.com/o/r/pull/{number}",
+        head_branch="remote-coder/x",
+        base_branch="main",
+        role=role,
+    )
+
+
+class TestSqliteSessionStore:
+    """Test cases for sessions surviving a restart without a state.json save."""
+
+    def test_changes_are_written_through(self, tmp_path):
+        project = _project(tmp_path)
+        manager = _manager(tmp_path)
+        session = _start(manager, project)
+        manager.append_user_message(session.id, "add a readme")
+        manager.append_agent_message(session.id, "done")
+        manager.update_session_context(session.id, {"pr_title": "Add README"})
+        manager.set_active_agent(session.id, "codex", AgentType.CODEX, "o3")
+        manager.set_pr_ref(_pr(project, session.id, 7))
+        manager.set_pr_ref(_pr(project, session.id, 9, PullRequestRole.BACKPORT))
+
+        restored = _reopened(tmp_path)
+
+        again = restored.get_by_thread("C1", "100.1")
+        assert again == manager.get_session(session.id)
+        assert [message.content for message in again.conversation_history] == ["add a readme", "done"]
+        assert again.active_agent_id == "codex" and again.session_context["pr_title"] == "Add README"
+        assert [ref.number for ref in restored.list_pr_refs(session.id)] == [7, 9]
+        assert (tmp_path / "sessions.db").stat().st_mode & 0o777 == 0o600
+
+    def test_archiving_releases_the_thread_and_forgotten_prs_stay_forgotten(self, tmp_path):
+        project = _project(tmp_path)
+        manager = _manager(tmp_path)
+        session = _start(manager, project)
+        manager.set_pr_ref(_pr(project, session.id, 7))
+        manager.set_pr_ref(_pr(project, session.id, 9, PullRequestRole.BACKPORT))
+        manager.clear_pr_ref(session.id, 7)
+        assert manager.cleanup_ended(timedelta(seconds=-1)) == 1
+
+        restored = _reopened(tmp_path)
+
+        assert restored.get_session(session.id).status == SessionStatus.ARCHIVED
+        assert restored.list_active() == []
+        assert [ref.number for ref in restored.list_pr_refs(session.id)] == [9]
+        restored.restore_session(session.id)
+        assert _reopened(tmp_path).get_by_thread("C1", "100.1").id == session.id
+
+    def test_imported_sessions_replace_the_ones_holding_their_threads(self, tmp_path):
+        project = _project(tmp_path)
+        manager = _manager(tmp_path)
+        old = _start(manager, project)
+        manager.set_pr_ref(_pr(project, old.id, 7))
+        other = SessionManager()
+        new = _start(other, project)
+        sessions, pr_refs = other.export_sessions()
+
+        manager.import_sessions(sessions, pr_refs)
+
+        restored = _reopened(tmp_path)
+        assert restored.get_by_thread("C1", "100.1").id == new.id
+        assert restored.find_sessions(str(old.id)) == [] and restored.list_pr_refs(old.id) == []
+        manager.clear_all()
+        assert _reopened(tmp_path).find_sessions(str(new.id)[:8]) == []
+
+    def test_the_state_file_only_replaces_sessions_it_has_newer_copies_of(self, tmp_path):
+        project = _project(tmp_path)
+        manager = _manager(tmp_path)
+        session = _start(manager, project)
+        state = StateStore(tmp_path / "state.json")
+        state.save(manager)
+        manager.append_user_message(session.id, "written after the last save")
+
+        restored = _reopened(tmp_path)
+        assert state.load(restored, keep_newer=True) == 0
+        assert restored.get_session(session.id).conversation_history[-1].content == "written after the last save"
+
+        plain = SessionManager()
+        state.load(plain)
+        plain.update_status(session.id, SessionStatus.ENDED)
+        state.save(plain)
+        assert state.load(restored, keep_newer=True) == 1
+        assert _reopened(tmp_path).get_session(session.id).status == SessionStatus.ENDED
+
+    def test_set_aside_moves_the_database_out_of_the_way(self, tmp_path):
+        store = SqliteSessionStore(tmp_path / "sessions.db")
+        store.close()
+
+        backup = set_aside(tmp_path / "sessions.db")
+
+        assert backup == tmp_path / "sessions.db.bak" and backup.exists()
+        assert not (tmp_path / "sessions.db").exists()
+        assert set_aside(tmp_path / "sessions.db") is None
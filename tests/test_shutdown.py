@@ -0,0 +1,56 @@
+"""Tests for draining agent runs at shutdown."""
+
+from __future__ import annotations
+
+import asyncio
+
+import pytest
+
+from src.core.shutdown import cancelled_by_shutdown, drain_runs
+
+
+def _runs(*tasks: asyncio.Task):
+    return {f"run-{index}": {"task": task, "agent_id": "claude"} for index, task in enumerate(tasks)}
+
+
+class TestDrainRuns:
+    """Test cases for the grace period and what happens after it."""
+
+    @pytest.mark.asyncio
+    async def test_runs_that_finish_in_time_are_left_alone(self):
+        quick = asyncio.create_task(asyncio.sleep(0.05))
+
+        drain = await drain_runs(_runs(quick), grace_secs=5)
+
+        assert len(drain.finished) == 1 and drain.interrupted == []
+        assert quick.done() and not quick.cancelled()
+
+    @pytest.mark.asyncio
+    async def test_runs_still_going_after_the_grace_period_are_cancelled_for_shutdown(self):
+        reasons = []
+
+        async def slow() -> None:
+            try:
+                await asyncio.sleep(30)
+            except asyncio.CancelledError as exc:
+                reasons.append(cancelled_by_shutdown(exc))
+                raise
+
+        quick = asyncio.create_task(asyncio.sleep(0.01))
+        stuck = asyncio.create_task(slow())
+
+        drain = await drain_runs(_runs(quick, stuck), grace_secs=0.1)
+
+        assert [run["task"] for run in drain.interrupted] == [stuck]
+        assert [run["task"] for run in drain.finished] == [quick]
+        assert stuck.cancelled() and reasons == [True]
+
+    @pytest.mark.asyncio
+    async def test_hurrying_ends_the_grace_period(self):
+        hurry = asyncio.Event()
+        stuck = asyncio.create_task(asyncio.sleep(30))
+        asyncio.get_running_loop().call_later(0.05, hurry.set)
+
+        drain = await asyncio.wait_for(drain_runs(_runs(stuck), grace_secs=30, hurry=hurry), timeout=5)
+
+        assert len(drain.interrupted) == 1 and stuck.cancelled()
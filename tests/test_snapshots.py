@@ -0,0 +1,120 @@
+"""Tests for workspace snapshots of non-git projects."""
+
+from __future__ import annotations
+
+import pytest
+
+from src.core import snapshots
+from src.core.diff_render import split_diff
+from src.core.snapshots import SnapshotError, SnapshotStore, format_restore_result
+
+
+@pytest.fixture
+def workspace(tmp_path):
+    root = tmp_path / "vault"
+    (root / "notes").mkdir(parents=True)
+    (root / "notes" / "todo.md").write_text("- buy milk\n")
+    (root / "script.py").write_text("print('hi')\n")
+    (root / "image.png").write_bytes(b"\x89PNG\0\0data")
+    return root
+
+
+@pytest.fixture
+def store(tmp_path):
+    return SnapshotStore.for_session(tmp_path / "config", "session-1")
+
+
+class TestSnapshotDiff:
+    """Test cases for diffing a workspace against its snapshot."""
+
+    def test_reports_modified_added_and_deleted_files(self, workspace, store):
+        store.take(workspace)
+        (workspace / "notes" / "todo.md").write_text("- buy milk\n- call mum\n")
+        (workspace / "notes" / "new.md").write_text("fresh")
+        (workspace / "script.py").unlink()
+
+        files = {file_diff.path: file_diff for file_diff in split_diff(store.diff(workspace))}
+
+        assert files["notes/todo.md"].status == "modified"
+        assert (files["notes/todo.md"].additions, files["notes/todo.md"].deletions) == (1, 0)
+        assert files["notes/new.md"].status == "added"
+        assert "\\ No newline at end of file" in files["notes/new.md"].text
+        assert files["script.py"].status == "deleted"
+        assert files["script.py"].deletions == 1
+        assert "image.png" not in files
+
+    def test_binary_changes_are_not_inlined(self, workspace, store):
+        store.take(workspace)
+        (workspace / "image.png").write_bytes(b"\x89PNG\0\0other")
+
+        (file_diff,) = split_diff(store.diff(workspace))
+
+        assert file_diff.binary
+        assert "Binary files a/image.png and b/image.png differ" in file_diff.text
+
+    def test_no_changes_or_snapshot_gives_empty_diff(self, workspace, store):
+        assert store.diff(workspace) == ""
+        store.take(workspace)
+        assert store.diff(workspace) == ""
+
+    def test_ignores_vcs_and_dependency_directories(self, workspace, store):
+        store.take(workspace)
+        (workspace / "node_modules").mkdir()
+        (workspace / "node_modules" / "lib.js").write_text("x")
+
+        assert store.diff(workspace) == ""
+
+    def test_refuses_huge_directories(self, workspace, store, monkeypatch):
+        monkeypatch.setattr(snapshots, "MAX_FILES", 2)
+
+        with pytest.raises(SnapshotError):
+            store.take(workspace)
+
+
+class TestSnapshotRestore:
+    """Test cases for rolling a workspace back."""
+
+    def test_restores_files_and_removes_new_ones(self, workspace, store):
+        store.take(workspace)
+        (workspace / "notes" / "todo.md").write_text("changed\n")
+        (workspace / "script.py").unlink()
+        (workspace / "drafts").mkdir()
+        (workspace / "drafts" / "idea.md").write_text("new")
+
+        result = store.restore(workspace)
+
+        assert sorted(result.restored) == ["notes/todo.md", "script.py"]
+        assert result.removed == ["drafts/idea.md"]
+        assert (workspace / "notes" / "todo.md").read_text() == "- buy milk\n"
+        assert (workspace / "script.py").read_text() == "print('hi')\n"
+        assert not (workspace / "drafts").exists()
+        assert store.diff(workspace) == ""
+        assert format_restore_result(result).startswith(
+            "Rolled the workspace back to before the last run: restored 2 file(s), removed 1 new file(s)."
+        )
+
+    def test_large_files_are_reported_but_not_restored(self, workspace, store, monkeypatch):
+        monkeypatch.setattr(snapshots, "MAX_FILE_BYTES", 8)
+        store.take(workspace)
+        (workspace / "script.py").write_text("print('bye')\n")
+
+        result = store.restore(workspace)
+
+        assert result.unrecoverable == ["script.py"]
+        assert (workspace / "script.py").read_text() == "print('bye')\n"
+        assert "Too large to keep in the snapshot" in format_restore_result(result)
+
+    def test_later_snapshot_reuses_and_prunes_blobs(self, workspace, store, tmp_path):
+        store.take(workspace)
+        (workspace / "script.py").write_text("print('v2')\n")
+        store.take(workspace)
+
+        blobs = sorted((tmp_path / "config" / "snapshots" / "session-1" / "blobs").glob("*/*"))
+        assert len(blobs) == 3
+        (workspace / "script.py").write_text("print('v3')\n")
+        store.restore(workspace)
+        assert (workspace / "script.py").read_text() == "print('v2')\n"
+
+    def test_restore_without_snapshot_raises(self, workspace, store):
+        with pytest.raises(SnapshotError):
+            store.restore(workspace)
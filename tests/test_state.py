@@ -0,0 +1,305 @@
+"""Tests for persisting daemon state and migrating it with export/import archives."""
+
+from __future__ import annotations
+
+import json
+import tarfile
+from datetime import datetime, timedelta
+
+import pytest
+
+from src.core.conversation import SessionManager
+from src.core.errors import SessionNotFound, SessionRestoreError
+from src.core.models import (
+    AgentType,
+    ConversationInteraction,
+    ConversationMessage,
+    Project,
+    PullRequestRef,
+    PullRequestRole,
+    SessionStatus,
+)
+from src.core.preferences import UserPreferences, Verbosity
+from src.core.state import (
+    ARCHIVE_MANIFEST_MEMBER,
+    STATE_FORMAT_VERSION,
+    StateError,
+    StateStore,
+    export_archive,
+    import_archive,
+    restore_saved_session,
+    saved_sessions,
+)
+
+
+def _populated_manager(project: Project) -> SessionManager:
+    manager = SessionManager()
+    session = manager.create_session(
+        project=project,
+        channel_id="C1",
+        thread_ts="100.1",
+        agent_id="claude",
+        agent_type=AgentType.CLAUDE,
+        active_model="sonnet",
+    )
+    manager.append_user_message(session.id, "add a readme")
+    manager.append_agent_message(session.id, "done")
+    session.interactions.append(
+        ConversationInteraction(
+            interaction_number=1,
+            user_message=ConversationMessage(role="user", content="add a readme"),
+            agent_message=ConversationMessage(role="assistant", content="Added README.md"),
+        )
+    )
+    manager.update_session_context(session.id, {"pr_title": "Add README", "pr_summary": ["Add README.md"]})
+    manager.record_run_cost(session.id, 0.25)
+    manager.set_publishing_paused(session.id, True)
+    manager.set_timezone(session.id, "Europe/Berlin")
+    manager.set_tags(session.id, ["bugfix", "urgent"])
+    manager.set_pr_ref(
+        PullRequestRef(
+            project_id=project.id,
+            session_id=session.id,
+            number=7,
+            url="https://github.com/o/r/pull/7",
+            head_branch="remote-coder/x",
+            base_branch="main",
+        )
+    )
+    manager.set_pr_ref(
+        PullRequestRef(
+            project_id=project.id,
+            session_id=session.id,
+            number=9,
+            url="https://github.com/o/r/pull/9",
+            head_branch="backport/x",
+            base_branch="release-1.x",
+            role=PullRequestRole.BACKPORT,
+            repo="o/r",
+        )
+    )
+    return manager
+
+
+def _project(path, project_id: str = "api") -> Project:
+    return Project(id=project_id, channel_name=project_id, path=path, default_agent_id="claude")
+
+
+class TestStateStore:
+    """Test cases for saving and loading state.json."""
+
+    def test_round_trip(self, tmp_path):
+        project = _project(tmp_path)
+        original = _populated_manager(project)
+        store = StateStore(tmp_path / "state.json")
+
+        store.save(original)
+        restored = SessionManager()
+        assert store.load(restored) == 1
+
+        session = restored.get_by_thread("C1", "100.1")
+        before = original.get_by_thread("C1", "100.1")
+        assert session == before
+        assert [m.content for m in session.conversation_history] == ["add a readme", "done"]
+        assert session.interactions[0].agent_message.content == "Added README.md"
+        assert session.status == SessionStatus.ACTIVE
+        assert session.timezone == "Europe/Berlin"
+        assert session.tags == ["bugfix", "urgent"]
+        assert restored.get_pr_ref(session.id).number == 7
+        assert restored.list_pr_refs(session.id) == original.list_pr_refs(session.id)
+        assert restored.get_pr_ref(session.id, role=PullRequestRole.BACKPORT).repo == "o/r"
+        assert (tmp_path / "state.json").stat().st_mode & 0o777 == 0o600
+
+    def test_round_trips_monthly_run_counts(self, tmp_path):
+        original = SessionManager()
+        october, november = datetime(2026, 10, 14, 12, 0), datetime(2026, 11, 2, 12, 0)
+        original.record_project_run("api", october)
+        original.record_project_run("api", october)
+        original.record_project_run("api", november)
+        store = StateStore(tmp_path / "state.json")
+
+        store.save(original)
+        restored = SessionManager()
+        store.load(restored)
+
+        assert restored.project_runs("api", october) == 2
+        assert restored.project_runs("api", november) == 1
+        assert restored.project_runs("blog", october) == 0
+
+    def test_round_trips_user_preferences(self, tmp_path):
+        original = SessionManager()
+        preferences = UserPreferences(agent="codex", verbosity=Verbosity.QUIET, locale="de-DE")
+        original.set_user_preferences("U1", preferences)
+        original.set_user_preferences("U2", UserPreferences())
+        store = StateStore(tmp_path / "state.json")
+
+        store.save(original)
+        restored = SessionManager()
+        store.load(restored)
+
+        assert restored.export_user_preferences() == {"U1": preferences}
+        assert restored.user_preferences("U2") == UserPreferences()
+
+    def test_loads_state_from_before_run_counts(self, tmp_path):
+        path = tmp_path / "state.json"
+        path.write_text(json.dumps({"version": STATE_FORMAT_VERSION, "sessions": [], "pr_refs": []}))
+        manager = SessionManager()
+
+        StateStore(path).load(manager)
+
+        assert manager.export_run_counts() == {}
+
+    def test_missing_file_loads_nothing(self, tmp_path):
+        assert StateStore(tmp_path / "state.json").load(SessionManager()) == 0
+
+    def test_rejects_unknown_version(self, tmp_path):
+        path = tmp_path / "state.json"
+        path.write_text(json.dumps({"version": 99, "sessions": []}))
+
+        with pytest.raises(StateError, match="version 99"):
+            StateStore(path).load(SessionManager())
+
+
+class TestSessionArchive:
+    """Test cases for archiving stale sessions and restoring them."""
+
+    def test_cleanup_archives_instead_of_removing(self, tmp_path):
+        manager = _populated_manager(_project(tmp_path))
+        session = manager.get_by_thread("C1", "100.1")
+
+        assert manager.cleanup_ended(timedelta(0)) == 1
+
+        assert session.status == SessionStatus.ARCHIVED and session.archived_at is not None
+        with pytest.raises(SessionNotFound):
+            manager.get_by_thread("C1", "100.1")
+        assert manager.get_session(session.id).session_context["pr_title"] == "Add README"
+        assert manager.get_pr_ref(session.id).number == 7
+        assert manager.cleanup_ended(timedelta(0)) == 0
+
+    def test_restore_brings_the_session_back_to_its_thread(self, tmp_path):
+        manager = _populated_manager(_project(tmp_path))
+        session = manager.get_by_thread("C1", "100.1")
+        manager.cleanup_ended(timedelta(0))
+
+        manager.restore_session(session.id)
+
+        assert manager.get_by_thread("C1", "100.1") is session
+        assert (session.status, session.archived_at) == (SessionStatus.ACTIVE, None)
+        with pytest.raises(SessionRestoreError, match="already active"):
+            manager.restore_session(session.id)
+
+    def test_restore_refuses_when_the_thread_has_a_new_session(self, tmp_path):
+        project = _project(tmp_path)
+        manager = _populated_manager(project)
+        archived = manager.get_by_thread("C1", "100.1")
+        manager.cleanup_ended(timedelta(0))
+        newer = manager.create_session(
+            project=project, channel_id="C1", thread_ts="100.1", agent_id="claude", agent_type=AgentType.CLAUDE
+        )
+
+        with pytest.raises(SessionRestoreError, match=str(newer.id)):
+            manager.restore_session(archived.id)
+
+        manager.update_status(newer.id, SessionStatus.ENDED)
+        assert manager.restore_session(archived.id) is archived
+        assert manager.get_by_thread("C1", "100.1") is archived
+
+    def test_archived_sessions_are_saved_and_restored_in_the_state_file(self, tmp_path):
+        path = tmp_path / "state.json"
+        manager = _populated_manager(_project(tmp_path))
+        session_id = manager.get_by_thread("C1", "100.1").id
+        manager.cleanup_ended(timedelta(0))
+        StateStore(path).save(manager)
+
+        [saved] = saved_sessions(path)
+        assert saved.status == SessionStatus.ARCHIVED and saved.archived_at is not None
+
+        restored = restore_saved_session(path, str(session_id)[:8])
+
+        assert restored.id == session_id
+        reloaded = SessionManager()
+        StateStore(path).load(reloaded)
+        session = reloaded.get_by_thread("C1", "100.1")
+        assert session.status == SessionStatus.ACTIVE
+        assert [m.content for m in session.conversation_history] == ["add a readme", "done"]
+        assert reloaded.get_pr_ref(session.id).number == 7
+
+    def test_restore_reports_unknown_and_active_sessions(self, tmp_path):
+        path = tmp_path / "state.json"
+        StateStore(path).save(_populated_manager(_project(tmp_path)))
+        session_id = saved_sessions(path)[0].id
+
+        with pytest.raises(StateError, match="No saved session"):
+            restore_saved_session(path, "ffffffff")
+        with pytest.raises(StateError, match="already active"):
+            restore_saved_session(path, str(session_id))
+
+
+class TestStateArchive:
+    """Test cases for export_archive and import_archive."""
+
+    def _export(self, tmp_path):
+        laptop = tmp_path / "laptop"
+        laptop.mkdir()
+        project = _project(laptop / "api")
+        state_path = laptop / "state.json"
+        StateStore(state_path).save(_populated_manager(project))
+        archive = tmp_path / "state.tar.gz"
+        count = export_archive(
+            state_path, archive, projects={"api": project}, agent_ids=["claude"], config_dir=laptop
+        )
+        assert count == 1
+        return archive
+
+    def test_archive_has_config_references(self, tmp_path):
+        archive = self._export(tmp_path)
+
+        with tarfile.open(archive, "r:gz") as tar:
+            manifest = json.loads(tar.extractfile(ARCHIVE_MANIFEST_MEMBER).read())
+        assert manifest["config"]["projects"]["api"]["path"].endswith("laptop/api")
+        assert manifest["config"]["agents"] == ["claude"]
+
+    def test_import_points_sessions_at_local_projects(self, tmp_path):
+        archive = self._export(tmp_path)
+        server = tmp_path / "server"
+        server.mkdir()
+        state_path = server / "state.json"
+
+        report = import_archive(
+            archive,
+            state_path,
+            projects={"api": _project(server / "code" / "api")},
+            agent_ids=["codex"],
+        )
+
+        assert report.sessions == 1
+        assert report.pr_refs == 2
+        assert report.relocated_projects == ["api"]
+        assert report.missing_agents == ["claude"]
+        restored = SessionManager()
+        StateStore(state_path).load(restored)
+        assert restored.get_by_thread("C1", "100.1").project_path == server / "code" / "api"
+
+    def test_import_reports_unknown_projects(self, tmp_path):
+        archive = self._export(tmp_path)
+
+        report = import_archive(archive, tmp_path / "state.json", projects={}, agent_ids=["claude"])
+
+        assert report.missing_projects == ["api"]
+
+    def test_import_does_not_overwrite_without_force(self, tmp_path):
+        archive = self._export(tmp_path)
+        state_path = tmp_path / "state.json"
+        state_path.write_text("{}")
+
+        with pytest.raises(StateError, match="--force"):
+            import_archive(archive, state_path, projects={}, agent_ids=[])
+
+        report = import_archive(archive, state_path, projects={}, agent_ids=[], force=True)
+        assert report.backup_path.read_text() == "{}"
+
+    def test_export_without_state(self, tmp_path):
+        with pytest.raises(StateError, match="No daemon state"):
+            export_archive(
+                tmp_path / "state.json", tmp_path / "out.tar.gz", projects={}, agent_ids=[], config_dir=tmp_path
+            )
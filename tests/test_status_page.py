@@ -0,0 +1,74 @@
+"""Tests for the status page."""
+
+from __future__ import annotations
+
+import logging
+
+import pytest
+
+from src.core.config import load_config
+from src.core.errors import ConfigError
+from src.core.status_page import IntegrationErrors, integration_for, render_status_html, token_matches
+
+
+def _record(name: str, level: int, message: str, exc: BaseException | None = None) -> logging.LogRecord:
+    exc_info = (type(exc), exc, None) if exc else None
+    return logging.LogRecord(name, level, __file__, 1, message, (), exc_info)
+
+
+class TestStatusPage:
+    """Test cases for collecting and showing the daemon's status."""
+
+    def test_keeps_the_last_error_of_each_integration(self):
+        errors = IntegrationErrors(clock=lambda: 100.0)
+
+        errors.emit(_record("src.chat_adapters.slack_adapter", logging.ERROR, "Socket closed\ntraceback"))
+        errors.emit(_record("src.github.client", logging.WARNING, "Rate limited"))
+        errors.emit(_record("src.github.client", logging.WARNING, "PR failed", exc=RuntimeError("422")))
+        errors.emit(_record("src.core.router", logging.CRITICAL, "Crashed"))
+
+        assert [(error.integration, error.message) for error in errors.latest()] == [
+            ("Daemon", "Crashed"),
+            ("GitHub", "PR failed: 422"),
+            ("Slack", "Socket closed"),
+        ]
+        assert integration_for("src.chat_adapters.github_discussions") == "GitHub Discussions"
+        assert integration_for("src.githubs") == "Daemon"
+
+    def test_renders_an_escaped_page(self):
+        status = {
+            "ok": False,
+            "version": "1.4.0",
+            "started_at": 0.0,
+            "adapters": [{"name": "Slack", "connected": False, "last_event_at": 3000.0}],
+            "github_configured": True,
+            "projects": [{"id": "api", "running": 1, "queued": 2, "incident": True}],
+            "active_runs": [{"project": "api", "agent": "claude", "thread": "1.0", "started_at": 3540.0}],
+            "errors": [{"integration": "Slack", "at": 3590.0, "message": "bad <token>"}],
+        }
+
+        page = render_status_html(status, now=3600.0)
+
+        assert "<title>Remote Coder: Needs attention</title>" in page
+        assert "Version 1.4.0, up 1h 0m" in page
+        assert "<td>DISCONNECTED</td><td>last event 10m 0s ago</td>" in page
+        assert "<td>api</td><td>1 running</td><td>2 queued</td><td>incident mode</td>" in page
+        assert "<td>claude</td><td>1m 0s</td>" in page
+        assert "bad &lt;token&gt;" in page and "<token>" not in page
+
+    def test_token_from_query_or_header(self):
+        assert token_matches("secret", "secret", None)
+        assert token_matches("secret", None, "Bearer secret")
+        assert not token_matches("secret", "wrong", "Bearer secret")
+        assert not token_matches("secret", None, None)
+
+    def test_port_needs_a_token(self, tmp_path, monkeypatch):
+        (tmp_path / "projects.yaml").write_text(f"base_dir: {tmp_path}\nprojects: {{}}\n")
+        (tmp_path / "agents.yaml").write_text("agents: {}\n")
+        monkeypatch.setenv("REMOTE_CODER_STATUS_PORT", "8788")
+        monkeypatch.delenv("REMOTE_CODER_STATUS_TOKEN", raising=False)
+
+        with pytest.raises(ConfigError, match="REMOTE_CODER_STATUS_TOKEN"):
+            load_config(tmp_path, require_slack=False)
+        monkeypatch.setenv("REMOTE_CODER_STATUS_TOKEN", "secret")
+        assert load_config(tmp_path, require_slack=False).status_port == 8788
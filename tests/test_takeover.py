@@ -0,0 +1,89 @@
+"""Tests for the per-project session limit behind `!takeover`."""
+
+from __future__ import annotations
+
+import pytest
+
+from src.core.ask_mode import ASK_MODE_CONTEXT_KEY
+from src.core.config import _load_projects
+from src.core.conversation import SessionManager
+from src.core.errors import ConfigError, ProjectLocked
+from src.core.models import AgentType, Project, SessionStatus
+
+
+def _start(manager: SessionManager, project: Project, thread_ts: str, read_only: bool = False):
+    return manager.create_session(
+        project=project,
+        channel_id="C1",
+        thread_ts=thread_ts,
+        agent_id="claude",
+        agent_type=AgentType.CLAUDE,
+        read_only=read_only,
+    )
+
+
+class TestSessionLimit:
+    """Test cases for `SessionManager.create_session` with `max_active_sessions`."""
+
+    def test_unlimited_by_default(self, tmp_path):
+        manager = SessionManager()
+        project = Project(id="api", channel_name="api", path=tmp_path, default_agent_id="claude")
+
+        for n in range(3):
+            _start(manager, project, f"{n}.0")
+
+        assert len(manager.checkout_holders("api")) == 3
+
+    def test_refuses_sessions_past_the_limit(self, tmp_path):
+        manager = SessionManager()
+        project = Project(
+            id="api", channel_name="api", path=tmp_path, default_agent_id="claude", max_active_sessions=1
+        )
+        first = _start(manager, project, "1.0")
+
+        with pytest.raises(ProjectLocked) as locked:
+            _start(manager, project, "2.0")
+
+        assert locked.value.project_id == "api"
+        assert locked.value.holders == [first]
+        manager.update_status(first.id, SessionStatus.PAUSED)
+        with pytest.raises(ProjectLocked):
+            _start(manager, project, "2.0")
+        manager.update_status(first.id, SessionStatus.ENDED)
+        assert _start(manager, project, "2.0").status == SessionStatus.ACTIVE
+
+    def test_read_only_sessions_do_not_count(self, tmp_path):
+        manager = SessionManager()
+        project = Project(
+            id="api", channel_name="api", path=tmp_path, default_agent_id="claude", max_active_sessions=1
+        )
+        asking = _start(manager, project, "1.0", read_only=True)
+        manager.update_session_context(asking.id, {ASK_MODE_CONTEXT_KEY: True})
+
+        working = _start(manager, project, "2.0")
+        asking_again = _start(manager, project, "3.0", read_only=True)
+        manager.update_session_context(asking_again.id, {ASK_MODE_CONTEXT_KEY: True})
+
+        assert manager.checkout_holders("api") == [working]
+
+
+class TestSessionLimitConfig:
+    """Test cases for `max_active_sessions` in projects.yaml."""
+
+    @staticmethod
+    def _load(tmp_path, extra: str):
+        projects_yaml = tmp_path / "projects.yaml"
+        projects_yaml.write_text(
+            f"base_dir: {tmp_path}\nprojects:\n  demo:\n    path: .\n    default_agent: claude\n{extra}"
+        )
+        projects, _ = _load_projects(projects_yaml)
+        return projects["demo"]
+
+    def test_defaults_to_no_limit(self, tmp_path):
+        assert self._load(tmp_path, "").max_active_sessions is None
+        assert self._load(tmp_path, "    max_active_sessions: 1\n").max_active_sessions == 1
+
+    @pytest.mark.parametrize("value", ["0", "one", "true"])
+    def test_rejects_invalid_limits(self, tmp_path, value):
+        with pytest.raises(ConfigError, match="max_active_sessions must be a positive number of sessions"):
+            self._load(tmp_path, f"    max_active_sessions: {value}\n")
@@ -0,0 +1,96 @@
+"""Tests for running agent CLIs in a pseudo-terminal (`spawn_mode: pty`)."""
+
+from __future__ import annotations
+
+import os
+import sys
+
+import pytest
+
+from src.agent_adapters.base import spawn_cli
+from src.agent_adapters.terminal import _EscapeFilter
+from src.core.config import _load_agents
+from src.core.errors import ConfigError
+from src.core.models import DEFAULT_PTY_SIZE, SpawnMode
+
+PROBE = (
+    "import os, shutil, sys\n"
+    "print('tty', sys.stdin.isatty(), sys.stdout.isatty(), sys.stderr.isatty())\n"
+    "print('size', *shutil.get_terminal_size())\n"
+    "print('\\x1b[1;32mgreen\\x1b[0m')\n"
+)
+
+
+def _write_agents(tmp_path, extra: str, agent_type: str = "codex"):
+    agents_yaml = tmp_path / "agents.yaml"
+    agents_yaml.write_text(f"agents:\n  cli:\n    type: {agent_type}\n    command: [cli]\n{extra}")
+    return agents_yaml
+
+
+class TestSpawnInTerminal:
+    """Test cases for spawn_cli with a terminal size."""
+
+    @pytest.mark.asyncio
+    async def test_stdout_is_a_terminal_read_like_a_pipe(self, tmp_path):
+        command = [sys.executable, "-c", PROBE]
+        async with spawn_cli(command, cwd=tmp_path, env=os.environ, stdin=False, terminal=(150, 40)) as process:
+            output = (await process.stdout.read()).decode()
+            await process.wait()
+
+        assert output.splitlines() == ["tty True True False", "size 150 40", "green"]
+
+    @pytest.mark.asyncio
+    async def test_piped_prompts_still_reach_stdin(self, tmp_path):
+        command = [sys.executable, "-c", "import sys; print(sys.stdin.isatty(), sys.stdin.read().upper())"]
+        async with spawn_cli(command, cwd=tmp_path, env=os.environ, terminal=DEFAULT_PTY_SIZE) as process:
+            process.stdin.write(b"fix the bug\n")
+            await process.stdin.drain()
+            process.stdin.close()
+            output = (await process.stdout.read()).decode()
+            await process.wait()
+
+        assert output == "False FIX THE BUG\n\n"
+
+    def test_escape_sequences_cut_between_reads_are_removed(self):
+        escapes = _EscapeFilter()
+
+        assert escapes.feed(b'{"a": 1}\x1b[3') == b'{"a": 1}'
+        assert escapes.feed(b"2mred\x1b]0;title\x07!\n") == b"red!\n"
+        assert escapes.flush() == b""
+
+
+class TestSpawnModeConfig:
+    """Test cases for spawn_mode and pty_size in agents.yaml."""
+
+    def test_pipes_by_default(self, tmp_path):
+        agent = _load_agents(_write_agents(tmp_path, ""))["cli"]
+
+        assert (agent.spawn_mode, agent.terminal_size) == (SpawnMode.PIPE, None)
+
+    def test_pty_uses_the_default_size_unless_given_one(self, tmp_path):
+        agent = _load_agents(_write_agents(tmp_path, "    spawn_mode: pty\n"))["cli"]
+        sized = _load_agents(_write_agents(tmp_path, "    spawn_mode: PTY\n    pty_size: [120, 30]\n"))["cli"]
+
+        assert agent.terminal_size == DEFAULT_PTY_SIZE
+        assert sized.terminal_size == (120, 30)
+
+    def test_pty_agents_are_not_kept_warm(self, tmp_path):
+        extra = "    spawn_mode: pty\n    options:\n      warm_pool: true\n"
+
+        agent = _load_agents(_write_agents(tmp_path, extra, agent_type="claude"))["cli"]
+
+        assert "warm_pool" not in agent.options
+
+    @pytest.mark.parametrize(
+        "extra,agent_type,message",
+        [
+            ("    spawn_mode: tty\n", "codex", "must be pipe or pty"),
+            ("    spawn_mode: pty\n", "mock", "only valid for claude, codex, and gemini"),
+            ("    pty_size: [120, 30]\n", "codex", "needs spawn_mode: pty"),
+            ("    spawn_mode: pty\n    pty_size: [120]\n", "codex", "must be [columns, rows]"),
+            ("    spawn_mode: pty\n    pty_size: [0, 30]\n", "codex", "must be [columns, rows]"),
+        ],
+    )
+    def test_rejects_invalid_values(self, tmp_path, extra, agent_type, message):
+        with pytest.raises(ConfigError, match=message.replace("[", r"\[").replace("]", r"\]")):
+            _load_agents(_write_agents(tmp_path, extra, agent_type=agent_type))
@@ -0,0 +1,100 @@
+"""Tests for recording and listing a session's checkpoints."""
+
+from __future__ import annotations
+
+import pytest
+
+from src.core.conversation import SessionManager
+from src.core.models import AgentType, Project
+from src.core.timeline import (
+    TIMELINE_CONTEXT_KEY,
+    Checkpoint,
+    add_checkpoint,
+    changed_files,
+    format_timeline,
+    load_timeline,
+)
+from tests.conftest import git
+
+
+@pytest.fixture
+def session(tmp_path):
+    session_manager = SessionManager(history_limit=20)
+    project = Project(id="local", channel_name="local", path=tmp_path, default_agent_id="claude")
+    return session_manager.create_session(
+        project=project, channel_id="C1", thread_ts="1.0", agent_id="claude", agent_type=AgentType.CLAUDE
+    )
+
+
+def _record(session, prompt: str, before: str, commit: str, files=()) -> None:
+    session.session_context.update(add_checkpoint(session, prompt, before, commit, list(files)))
+
+
+class TestAddCheckpoint:
+    """Test cases for recording runs in the timeline."""
+
+    def test_runs_are_numbered_in_order(self, session):
+        _record(session, "add a button", "c0", "c1", ["app.py"])
+        _record(session, "make it blue", "c1", "c2", ["app.css"])
+
+        checkpoints = load_timeline(session)
+        assert [(entry.run, entry.commit) for entry in checkpoints] == [(1, "c1"), (2, "c2")]
+        assert checkpoints[1] == Checkpoint(run=2, prompt="make it blue", commit="c2", before="c1", files=["app.css"])
+
+    def test_a_redone_run_replaces_it_and_later_ones(self, session):
+        _record(session, "add a button", "c0", "c1")
+        _record(session, "make it blue", "c1", "c2")
+        _record(session, "and bigger", "c2", "c3")
+        # `!redo` reset the branch to c1 and ran again from there
+        _record(session, "make it green", "c1", "c4")
+
+        checkpoints = load_timeline(session)
+        assert [(entry.run, entry.prompt) for entry in checkpoints] == [(1, "add a button"), (2, "make it green")]
+
+    def test_runs_that_commit_nothing_are_kept(self, session):
+        _record(session, "explain the router", "c0", "c0")
+        _record(session, "and the runner", "c0", "c0")
+
+        assert [entry.run for entry in load_timeline(session)] == [1, 2]
+
+    def test_malformed_entries_are_skipped(self, session):
+        session.session_context[TIMELINE_CONTEXT_KEY] = [{"run": 1, "commit": "c1"}, {"prompt": "lost"}, "junk"]
+
+        assert [entry.commit for entry in load_timeline(session)] == ["c1"]
+
+
+class TestFormatTimeline:
+    """Test cases for the `!timeline` reply."""
+
+    def test_lists_prompt_snippets_and_files(self):
+        checkpoints = [
+            Checkpoint(run=1, prompt="add a\nbutton", commit="abcdef123456", files=["app.py"]),
+            Checkpoint(run=2, prompt="x" * 200, commit="0123456789ab", files=["a", "b", "c", "d", "e"]),
+            Checkpoint(run=3, prompt="explain the router", commit="0123456789ab"),
+        ]
+
+        text = format_timeline(checkpoints, checked_out=1)
+
+        lines = text.splitlines()
+        assert lines[1] == "1. `abcdef1` “add a button” — 1 file: `app.py`  ← checked out"
+        assert "x" * 79 + "…" in lines[2]
+        assert "5 files: `a`, `b`, `c` and 2 more" in lines[2]
+        assert lines[3].endswith("— no changes")
+        assert "`!checkout latest`" in lines[-1]
+
+    def test_empty_timeline(self):
+        assert "No checkpoints yet" in format_timeline([])
+
+
+@pytest.mark.asyncio
+async def test_changed_files_between_commits(git_repo):
+    before = git(git_repo, "rev-parse", "HEAD").strip()
+    (git_repo / "app.py").write_text("print(1)\n")
+    (git_repo / "README.md").write_text("hello again\n")
+    git(git_repo, "add", "-A")
+    git(git_repo, "commit", "-q", "-m", "change")
+    after = git(git_repo, "rev-parse", "HEAD").strip()
+
+    assert await changed_files(git_repo, before, after) == ["README.md", "app.py"]
+    assert await changed_files(git_repo, after, after) == []
+    assert await changed_files(git_repo, None, after) == []
@@ -0,0 +1,97 @@
+"""Tests for TranscriptCompressor."""
+
+from src.core.conversation import TranscriptCompressor
+from src.core.models import ConversationInteraction, ConversationMessage
+
+
+def _interaction(number: int, user: str, agent: str) -> ConversationInteraction:
+    return ConversationInteraction(
+        interaction_number=number,
+        user_message=ConversationMessage(role="user", content=user),
+        agent_message=ConversationMessage(role="assistant", content=agent),
+    )
+
+
+SESSION = [
+    _interaction(
+        1,
+        "Add pagination to the users endpoint. Let's use cursor pagination instead of offsets.",
+        "Added cursor pagination to GET /users. Tests for the admin listing still fail.",
+    ),
+    _interaction(
+        2,
+        "Don't change the public response shape.",
+        "Updated the serializer and kept the response shape.\n- [ ] Document the cursor parameter\n- [x] Add tests",
+    ),
+    _interaction(3, "Now fix the admin listing", "Fixed the admin listing query. Next step: benchmark the query."),
+]
+
+
+class TestTranscriptCompressor:
+    """Test cases for TranscriptCompressor."""
+
+    def test_compress_empty_interactions(self):
+        """Compressing nothing gives an empty brief."""
+        assert TranscriptCompressor.compress_interactions([]) == ""
+
+    def test_compress_extracts_sections(self):
+        """Decisions, current task state, and todos each get a section."""
+        brief = TranscriptCompressor.compress_interactions(SESSION)
+
+        assert brief == (
+            "Decisions:\n"
+            "- Let's use cursor pagination instead of offsets.\n"
+            "- Don't change the public response shape.\n"
+            "Current task state:\n"
+            "- Last ask: Now fix the admin listing\n"
+            "- Last result: Fixed the admin listing query.\n"
+            "Outstanding todos:\n"
+            "- Tests for the admin listing still fail.\n"
+            "- Document the cursor parameter\n"
+            "- Next step: benchmark the query."
+        )
+
+    def test_compress_is_deterministic(self):
+        """The same interactions always compress to the same text."""
+        briefs = {TranscriptCompressor.compress_interactions(list(SESSION)) for _ in range(5)}
+
+        assert len(briefs) == 1
+
+    def test_repeated_items_appear_once_at_latest_mention(self):
+        """An item mentioned twice is listed once, in the position of its last mention."""
+        interactions = [
+            _interaction(1, "Use tabs. Never touch the lockfile.", "Noted"),
+            _interaction(2, "Always run the linter. never touch the lockfile.", "Noted"),
+        ]
+
+        brief = TranscriptCompressor.compress_interactions(interactions)
+
+        assert brief.splitlines()[:3] == [
+            "Decisions:",
+            "- Always run the linter.",
+            "- never touch the lockfile.",
+        ]
+
+    def test_sections_are_capped_to_recent_items(self):
+        """Only the most recent decisions are kept."""
+        interactions = [_interaction(i, f"We must keep rule {i}.", "Done") for i in range(1, 21)]
+
+        brief = TranscriptCompressor.compress_interactions(interactions)
+
+        assert "rule 12." not in brief
+        assert "- We must keep rule 13." in brief
+        assert "- We must keep rule 20." in brief
+
+    def test_long_items_are_truncated(self):
+        """Items are cut at a word boundary."""
+        brief = TranscriptCompressor.compress_interactions([_interaction(1, "You must " + "really " * 60, "Done")])
+
+        decision = brief.splitlines()[1]
+        assert decision.endswith("...")
+        assert len(decision) <= 170
+
+    def test_without_details_reports_no_task_state(self):
+        """Empty messages still produce a task state line."""
+        brief = TranscriptCompressor.compress_interactions([_interaction(1, "", "")])
+
+        assert brief == "Current task state:\n- No details recorded."
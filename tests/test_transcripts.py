@@ -0,0 +1,73 @@
+"""Tests for the per-session transcripts kept on disk."""
+
+from __future__ import annotations
+
+import json
+
+from src.core.conversation import SessionManager
+from src.core.models import AgentType, Project
+from src.core.resources import ResourceSettings
+from src.core.transcripts import RunRecord, TranscriptKind, TranscriptStore
+
+
+def _session(manager: SessionManager, tmp_path):
+    project = Project(id="demo", channel_name="demo", path=tmp_path, default_agent_id="claude")
+    return manager.create_session(
+        project=project, channel_id="C1", thread_ts="1", agent_id="claude", agent_type=AgentType.CLAUDE
+    )
+
+
+class TestTranscripts:
+    """Test cases for recording and reading back everything a session did."""
+
+    def test_prompts_replies_and_runs_are_read_back_in_order(self, tmp_path):
+        manager = SessionManager(2, transcript=TranscriptStore(tmp_path / "transcripts"))
+        session = _session(manager, tmp_path)
+        manager.append_user_message(session.id, "add a readme")
+        manager.append_agent_message(session.id, "Added README.md")
+        run = RunRecord(agent_id="claude", success=True, wall_secs=12.5, model="sonnet", files=["README.md"])
+        manager.record_run(session.id, run)
+        manager.append_user_message(session.id, "now a license")
+
+        entries = manager.transcript(session.id)
+
+        assert [entry.kind for entry in entries] == [
+            TranscriptKind.PROMPT,
+            TranscriptKind.REPLY,
+            TranscriptKind.RUN,
+            TranscriptKind.PROMPT,
+        ]
+        assert entries[2].run == run
+        assert [entry.content for entry in manager.transcript(session.id, [TranscriptKind.PROMPT])] == [
+            "add a readme",
+            "now a license",
+        ]
+
+    def test_older_lines_and_torn_writes(self, tmp_path):
+        store = TranscriptStore(tmp_path)
+        manager = SessionManager(transcript=store)
+        session = _session(manager, tmp_path)
+        old = {"role": "user", "content": "from before runs were recorded", "timestamp": "2026-01-02T03:04:05+00:00"}
+        store.path_for(session.id).write_text(json.dumps(old) + '\n{"kind": "reply", "con')
+
+        entries = manager.transcript(session.id)
+
+        assert [(entry.kind, entry.content) for entry in entries] == [
+            (TranscriptKind.PROMPT, "from before runs were recorded")
+        ]
+
+    def test_without_a_store_only_the_history_in_memory_is_there(self, tmp_path):
+        manager = SessionManager(2)
+        session = _session(manager, tmp_path)
+        for text in ("one", "two", "three"):
+            manager.append_user_message(session.id, text)
+        manager.record_run(session.id, RunRecord(agent_id="claude", success=False, wall_secs=1, cancelled=True))
+
+        assert [entry.content for entry in manager.transcript(session.id)] == ["two", "three"]
+
+    def test_transcripts_are_kept_unless_turned_off(self, tmp_path):
+        kept = ResourceSettings.for_mode(low_memory=False, config_dir=tmp_path)
+        off = ResourceSettings.for_mode(low_memory=True, config_dir=tmp_path, transcripts=False)
+
+        assert kept.transcript_dir == tmp_path / "transcripts"
+        assert off.transcript_dir is None
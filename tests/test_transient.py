@@ -0,0 +1,65 @@
+"""Tests for recognising transient agent failures."""
+
+from __future__ import annotations
+
+import asyncio
+
+import pytest
+
+from src.core.transient import (
+    MAX_BACKOFF_SECS,
+    TransientFailure,
+    backoff_delay,
+    classify_errors,
+    classify_exception,
+    classify_text,
+    short_detail,
+)
+
+
+class TestTransientFailures:
+    """Test cases for telling provider hiccups from real errors."""
+
+    @pytest.mark.parametrize(
+        "text, kind",
+        [
+            ("API Error: 429 Too Many Requests", TransientFailure.RATE_LIMIT),
+            ("You have hit your rate limit, try again later", TransientFailure.RATE_LIMIT),
+            ('API Error: 529 {"type":"overloaded_error"}', TransientFailure.PROVIDER_ERROR),
+            ("HTTP 503: upstream unavailable", TransientFailure.PROVIDER_ERROR),
+            ("Agent service request failed: 502, message='Bad Gateway'", TransientFailure.PROVIDER_ERROR),
+            ("stream error: connection reset by peer", TransientFailure.NETWORK),
+            ("getaddrinfo EAI_AGAIN api.openai.com", TransientFailure.NETWORK),
+        ],
+    )
+    def test_recognises_transient_failures(self, text, kind):
+        assert classify_text(text) == kind
+
+    @pytest.mark.parametrize(
+        "text",
+        [
+            "Invalid API key",
+            "SyntaxError at line 500 of parser.py",
+            "error: the model `gpt-9` does not exist",
+        ],
+    )
+    def test_real_errors_are_not_transient(self, text):
+        assert classify_text(text) is None
+
+    def test_classifies_exceptions_and_results(self):
+        assert classify_exception(ConnectionResetError()) == TransientFailure.NETWORK
+        assert classify_exception(asyncio.TimeoutError()) == TransientFailure.NETWORK
+        assert classify_exception(ValueError("HTTP 500: oops")) == TransientFailure.PROVIDER_ERROR
+        assert classify_exception(RuntimeError("boom")) is None
+        assert classify_errors([], "Error: 429 rate limited") == TransientFailure.RATE_LIMIT
+        assert classify_errors(["exit code 1"], "429 mentioned in the output") is None
+
+    def test_backoff_doubles_up_to_the_cap(self):
+        assert [backoff_delay(5, retry) for retry in (1, 2, 3)] == [5, 10, 20]
+        assert backoff_delay(5, 10) == MAX_BACKOFF_SECS
+        assert backoff_delay(0, 3) == 0
+
+    def test_short_detail_keeps_the_first_line(self):
+        assert short_detail("HTTP 503\ntraceback...") == "HTTP 503"
+        assert short_detail("x" * 500).endswith("…")
+        assert short_detail("") == ""
@@ -0,0 +1,145 @@
+"""Tests for the new-release check and its notice."""
+
+from __future__ import annotations
+
+import pytest
+
+from src.core.updates import (
+    MAX_CHANGELOG_LINES,
+    Release,
+    UpdateChecker,
+    UpdateNotice,
+    format_update_notice,
+    migration_notes,
+    newer_releases,
+    version_key,
+)
+
+FEATURES = "## Features\n- Add `!batch`\n- Faster diffs\n\nThanks to everyone who reported bugs."
+MIGRATIONS = "## Changes\n- Rename `sandbox.image`\n\n## Config migrations\n- Move `image` under `sandbox:`\n"
+
+
+def _release(tag: str, body: str = "", **flags) -> Release:
+    return Release(tag=tag, url=f"https://github.com/PeterShin23/remote-coder/releases/tag/{tag}", body=body, **flags)
+
+
+class TestVersions:
+    """Test cases for comparing release versions."""
+
+    def test_orders_releases_and_prereleases(self):
+        ordered = ["0.0.1-alpha.1", "0.0.1-alpha.2", "0.0.1-beta", "0.0.1", "v0.1", "0.1.1", "0.10.0"]
+
+        assert sorted(ordered, key=version_key) == ordered
+        assert version_key("nightly") is None
+
+    def test_newer_releases_skip_drafts_and_prereleases(self):
+        releases = [
+            _release("v0.3.0-rc.1", prerelease=True),
+            _release("v0.2.0"),
+            _release("v0.4.0", draft=True),
+            _release("v0.1.0"),
+            _release("v0.2.1"),
+        ]
+
+        assert [release.tag for release in newer_releases(releases, "0.1.0")] == ["v0.2.1", "v0.2.0"]
+        # Someone on a prerelease also hears about the next one
+        newer = newer_releases(releases, "0.2.1-alpha.1")
+        assert [release.tag for release in newer] == ["v0.3.0-rc.1", "v0.2.1"]
+
+
+class TestUpdateNotice:
+    """Test cases for the notice text."""
+
+    def test_condenses_the_changelog_and_says_no_migration_is_needed(self):
+        text = format_update_notice(UpdateNotice(current="0.1.0", releases=[_release("v0.2.0", FEATURES)]))
+
+        assert text.startswith("Remote Coder v0.2.0 is available (this daemon runs 0.1.0): https://")
+        assert "• Add `!batch`\n• Faster diffs" in text
+        assert "Thanks to everyone" not in text
+        assert text.endswith("No config migrations needed.")
+
+    def test_lists_migrations_from_every_release_since_the_installed_one(self):
+        releases = [
+            _release("v0.3.0", "- Drop Python 3.10\n- Migration: rename `agents:` to `agent:`"),
+            _release("v0.2.0", MIGRATIONS),
+        ]
+
+        text = format_update_notice(UpdateNotice(current="0.1.0", releases=releases))
+
+        assert "• v0.3.0: Drop Python 3.10" in text
+        assert "• v0.2.0: Rename `sandbox.image`" in text
+        assert text.endswith(
+            "Config migrations needed before upgrading:\n"
+            "• Move `image` under `sandbox:`\n"
+            "• rename `agents:` to `agent:`"
+        )
+
+    def test_long_changelogs_link_to_the_releases(self):
+        body = "\n".join(f"- change {index}" for index in range(MAX_CHANGELOG_LINES + 3))
+
+        text = format_update_notice(UpdateNotice(current="0.1.0", releases=[_release("v0.2.0", body)]))
+
+        assert f"change {MAX_CHANGELOG_LINES - 1}" in text
+        assert "…and 3 more: https://github.com/PeterShin23/remote-coder/releases" in text
+
+    def test_migration_notes_ignore_other_sections(self):
+        assert migration_notes(MIGRATIONS) == ["Move `image` under `sandbox:`"]
+        assert migration_notes(FEATURES) == []
+
+
+class TestUpdateChecker:
+    """Test cases for checking and announcing once per version."""
+
+    @staticmethod
+    def _checker(tmp_path, releases, notes, delivered=True) -> UpdateChecker:
+        async def fetch():
+            return list(releases)
+
+        async def notify(text: str) -> bool:
+            notes.append(text)
+            return delivered
+
+        return UpdateChecker("0.1.0", fetch=fetch, notify=notify, state_path=tmp_path / "update_check.json")
+
+    @pytest.mark.asyncio
+    async def test_announces_each_version_once(self, tmp_path):
+        releases, notes = [_release("v0.2.0", FEATURES)], []
+        checker = self._checker(tmp_path, releases, notes)
+
+        assert (await checker.check()).latest.tag == "v0.2.0"
+        assert await checker.check() is None
+        # The announced version survives a restart
+        assert await self._checker(tmp_path, releases, notes).check() is None
+
+        releases.insert(0, _release("v0.3.0"))
+        await checker.check()
+
+        assert [note.splitlines()[0].split()[2] for note in notes] == ["v0.2.0", "v0.3.0"]
+
+    @pytest.mark.asyncio
+    async def test_retries_when_the_notice_could_not_be_delivered(self, tmp_path):
+        notes: list[str] = []
+        checker = self._checker(tmp_path, [_release("v0.2.0")], notes, delivered=False)
+
+        await checker.check()
+        await checker.check()
+
+        assert len(notes) == 2
+
+    @pytest.mark.asyncio
+    async def test_up_to_date_or_unreachable_posts_nothing(self, tmp_path):
+        notes: list[str] = []
+
+        assert await self._checker(tmp_path, [_release("v0.1.0")], notes).check() is None
+
+        async def unreachable():
+            raise ValueError("GitHub returned releases in an unexpected shape")
+
+        async def notify(text: str) -> bool:
+            notes.append(text)
+            return True
+
+        checker = UpdateChecker("0.1.0", fetch=unreachable, notify=notify, state_path=tmp_path / "update_check.json")
+
+        assert await checker.check() is None
+        assert notes == []
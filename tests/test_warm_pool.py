@@ -0,0 +1,188 @@
+"""Tests for keeping Claude CLI processes warm between turns."""
+
+from __future__ import annotations
+
+import os
+import sys
+
+import pytest
+
+from src.agent_adapters.claude_adapter import ClaudeAdapter
+from src.agent_adapters.warm_pool import WarmPool
+from src.core.config import _load_agents
+from src.core.errors import ConfigError
+from src.core.models import Agent, AgentType, WorkingDirMode
+
+# Answers each stream-json user message with the prompt, its turn number, and its pid
+FAKE_CLAUDE = """
+import json, os, sys
+for turn, line in enumerate(sys.stdin, start=1):
+    text = json.loads(line)["message"]["content"][0]["text"]
+    if text == "crash":
+        sys.exit(3)
+    reply = f"{text} turn={turn} pid={os.getpid()}"
+    print(json.dumps({"type": "assistant", "message": {"content": [{"type": "text", "text": reply}]}}), flush=True)
+    print(json.dumps({"type": "result", "is_error": text == "fail"}), flush=True)
+"""
+
+
+class FakeClock:
+    def __init__(self) -> None:
+        self.now = 0.0
+
+    def __call__(self) -> float:
+        return self.now
+
+
+@pytest.fixture
+def fake_claude(tmp_path):
+    script = tmp_path / "fake_claude.py"
+    script.write_text(FAKE_CLAUDE)
+    return [sys.executable, str(script)]
+
+
+def _agent(command: list[str], **options) -> Agent:
+    return Agent(
+        id="claude",
+        type=AgentType.CLAUDE,
+        command=command,
+        working_dir_mode=WorkingDirMode.PROJECT,
+        options={"warm_pool": True, **options},
+    )
+
+
+async def _run(adapter: ClaudeAdapter, text: str, workdir, session_id: str = "s1"):
+    return await adapter.run(task_text=text, project_path=str(workdir), session_id=session_id, conversation_history=[])
+
+
+class TestWarmPool:
+    """Test cases for WarmPool."""
+
+    @pytest.mark.asyncio
+    async def test_reuses_process_per_key(self, fake_claude, tmp_path):
+        pool = WarmPool()
+        try:
+            first = await pool.acquire(("s1", "a"), fake_claude, cwd=tmp_path, env=os.environ)
+            again = await pool.acquire(("s1", "a"), fake_claude, cwd=tmp_path, env=os.environ)
+            other = await pool.acquire(("s2", "a"), fake_claude, cwd=tmp_path, env=os.environ)
+
+            assert first is again
+            assert other is not first
+            assert len(pool) == 2
+        finally:
+            await pool.close_all()
+        assert len(pool) == 0
+        assert not first.alive
+
+    @pytest.mark.asyncio
+    async def test_evicts_idle_processes(self, fake_claude, tmp_path):
+        clock = FakeClock()
+        pool = WarmPool(clock=clock)
+        try:
+            idle = await pool.acquire(("s1",), fake_claude, cwd=tmp_path, env=os.environ, idle_secs=60)
+            busy = await pool.acquire(("s2",), fake_claude, cwd=tmp_path, env=os.environ, idle_secs=60)
+            clock.now = 59
+            assert await pool.evict_idle() == 0
+
+            clock.now = 61
+            async with busy.lock:
+                assert await pool.evict_idle() == 1
+            assert not idle.alive
+            assert busy.alive
+        finally:
+            await pool.close_all()
+
+    @pytest.mark.asyncio
+    async def test_discard_session(self, fake_claude, tmp_path):
+        pool = WarmPool()
+        try:
+            await pool.acquire(("s1", "a"), fake_claude, cwd=tmp_path, env=os.environ)
+            await pool.acquire(("s1", "b"), fake_claude, cwd=tmp_path, env=os.environ)
+            await pool.acquire(("s2", "a"), fake_claude, cwd=tmp_path, env=os.environ)
+
+            await pool.discard_session("s1")
+
+            assert len(pool) == 1
+        finally:
+            await pool.close_all()
+
+
+class TestWarmClaudeAdapter:
+    """The Claude adapter sends follow-up turns to the same warm process."""
+
+    @pytest.mark.asyncio
+    async def test_turns_share_a_process(self, fake_claude, tmp_path):
+        pool = WarmPool()
+        adapter = ClaudeAdapter(_agent(fake_claude), warm_pool=pool)
+        try:
+            first = await _run(adapter, "hello", tmp_path)
+            second = await _run(adapter, "again", tmp_path)
+            other_session = await _run(adapter, "hi", tmp_path, session_id="s2")
+        finally:
+            await pool.close_all()
+
+        assert first.success and second.success
+        pid = first.output_text.split("pid=")[1]
+        assert first.output_text == f"hello turn=1 pid={pid}"
+        assert second.output_text == f"again turn=2 pid={pid}"
+        assert other_session.output_text.startswith("hi turn=1 ")
+
+    @pytest.mark.asyncio
+    async def test_failed_turn_keeps_process(self, fake_claude, tmp_path):
+        pool = WarmPool()
+        adapter = ClaudeAdapter(_agent(fake_claude), warm_pool=pool)
+        try:
+            failed = await _run(adapter, "fail", tmp_path)
+            follow_up = await _run(adapter, "retry", tmp_path)
+        finally:
+            await pool.close_all()
+
+        assert not failed.success
+        assert follow_up.output_text.startswith("retry turn=2 ")
+
+    @pytest.mark.asyncio
+    async def test_exited_process_is_restarted(self, fake_claude, tmp_path):
+        pool = WarmPool()
+        adapter = ClaudeAdapter(_agent(fake_claude), warm_pool=pool)
+        try:
+            crashed = await _run(adapter, "crash", tmp_path)
+            assert len(pool) == 0
+            restarted = await _run(adapter, "back", tmp_path)
+        finally:
+            await pool.close_all()
+
+        assert not crashed.success
+        assert crashed.errors == ["The warm Claude process exited before finishing the turn"]
+        assert restarted.output_text.startswith("back turn=1 ")
+
+    @pytest.mark.asyncio
+    async def test_runs_one_shot_without_pool(self, tmp_path):
+        command = ["sh", "-c", 'cat >/dev/null; echo \'{"type": "assistant", "message": {"text": "one shot"}}\'']
+        adapter = ClaudeAdapter(_agent(command))
+
+        result = await _run(adapter, "hello", tmp_path)
+
+        assert result.success
+        assert result.output_text == "one shot"
+
+
+class TestWarmPoolConfig:
+    """Test cases for warm pool options in agents.yaml."""
+
+    def test_ignored_for_other_agents(self, tmp_path):
+        agents_yaml = tmp_path / "agents.yaml"
+        agents_yaml.write_text(
+            "agents:\n  codex:\n    type: codex\n    command: [codex]\n    options:\n      warm_pool: true\n"
+        )
+
+        assert "warm_pool" not in _load_agents(agents_yaml)["codex"].options
+
+    def test_rejects_bad_idle_timeout(self, tmp_path):
+        agents_yaml = tmp_path / "agents.yaml"
+        agents_yaml.write_text(
+            "agents:\n  claude:\n    type: claude\n    command: [claude]\n"
+            "    options:\n      warm_pool: true\n      warm_idle_secs: 0\n"
+        )
+
+        with pytest.raises(ConfigError, match="warm_idle_secs for agent claude must be a positive number"):
+            _load_agents(agents_yaml)
@@ -0,0 +1,180 @@
+"""Tests for WASM plugin discovery and the WASM agent adapter."""
+
+from __future__ import annotations
+
+import subprocess
+from pathlib import Path
+
+import pytest
+
+from src.agent_adapters import wasm_plugins
+from src.agent_adapters.wasm_adapter import WasmAgentAdapter
+from src.agent_adapters.wasm_plugins import PluginError, discover_plugins, pack_ptr_len, unpack_ptr_len
+from src.core.config import _load_agents
+from src.core.errors import ConfigError
+from src.core.models import Agent, AgentType, WorkingDirMode
+
+PATCH = """--- a/README.md
++++ b/README.md
+@@ -1 +1 @@
+-hello
++hello from plugin
+"""
+
+
+class FakePlugin:
+    """Stands in for a compiled plugin: runs a Python callable instead of WASM."""
+
+    def __init__(self, name, handler):
+        self.name = name
+        self.path = Path(f"/plugins/{name}.wasm")
+        self._handler = handler
+        self.requests = []
+
+    def run(self, request, call_tool, timeout_secs):
+        self.requests.append(request)
+        return self._handler(request, call_tool)
+
+
+@pytest.fixture
+def agent():
+    return Agent(
+        id="custom",
+        type=AgentType.WASM,
+        command=[],
+        working_dir_mode=WorkingDirMode.PROJECT,
+        plugin="my-plugin",
+        models={"default": "tiny"},
+    )
+
+
+@pytest.fixture
+def repo(tmp_path):
+    subprocess.run(["git", "init", "-q"], cwd=tmp_path, check=True)
+    (tmp_path / "README.md").write_text("hello\n")
+    return tmp_path
+
+
+class TestPluginAbi:
+    """Helpers shared by host and guest."""
+
+    def test_pack_round_trip(self):
+        assert unpack_ptr_len(pack_ptr_len(1024, 77)) == (1024, 77)
+
+    def test_unpack_negative_i64(self):
+        """wasmtime hands i64 results back as signed integers."""
+        packed = pack_ptr_len(0x8000_0000, 5)
+        assert unpack_ptr_len(packed - (1 << 64)) == (0x8000_0000, 5)
+
+
+class TestDiscoverPlugins:
+    """Plugin discovery from the plugins directory."""
+
+    def test_missing_directory(self, tmp_path):
+        assert discover_plugins(tmp_path / "plugins") == {}
+
+    def test_without_wasmtime(self, tmp_path, monkeypatch):
+        (tmp_path / "a.wasm").write_bytes(b"\0asm")
+
+        def _missing():
+            raise PluginError("wasmtime missing")
+
+        monkeypatch.setattr(wasm_plugins, "_import_wasmtime", _missing)
+        assert discover_plugins(tmp_path) == {}
+
+    def test_skips_broken_and_duplicate_plugins(self, tmp_path, monkeypatch):
+        for name in ("a.wasm", "b.wasm", "broken.wasm", "notes.txt"):
+            (tmp_path / name).write_bytes(b"")
+
+        def _load(path):
+            if path.stem == "broken":
+                raise PluginError("bad module")
+            plugin = FakePlugin("shared", lambda request, call_tool: {})
+            plugin.path = path
+            return plugin
+
+        monkeypatch.setattr(wasm_plugins, "_import_wasmtime", lambda: object())
+        monkeypatch.setattr(wasm_plugins.WasmPlugin, "load", staticmethod(_load))
+
+        plugins = discover_plugins(tmp_path)
+
+        assert list(plugins) == ["shared"]
+        assert plugins["shared"].path.name == "a.wasm"
+
+
+class TestWasmAgentAdapter:
+    """Test cases for WasmAgentAdapter."""
+
+    @pytest.mark.asyncio
+    async def test_applies_returned_patch(self, agent, repo):
+        plugin = FakePlugin("my-plugin", lambda request, call_tool: {"summary": "done", "patch": PATCH})
+        adapter = WasmAgentAdapter(agent, plugin)
+
+        result = await adapter.run(
+            task_text="update", project_path=str(repo), session_id="s1", conversation_history=[]
+        )
+
+        assert result.success, result.errors
+        assert result.output_text == "done"
+        assert (repo / "README.md").read_text() == "hello from plugin\n"
+        assert plugin.requests[0]["model"] == "tiny"
+        assert plugin.requests[0]["prompt"] == "update"
+
+    @pytest.mark.asyncio
+    async def test_tools_are_project_scoped(self, agent, repo):
+        outputs = {}
+
+        def _handler(request, call_tool):
+            outputs["read"] = call_tool("read_file", {"path": "README.md"})
+            outputs["write"] = call_tool("write_file", {"path": "out.txt", "content": "hi\n"})
+            outputs["shell"] = call_tool("run_shell", {"command": "echo nope"})
+            return {"summary": "wrote a file"}
+
+        adapter = WasmAgentAdapter(agent, FakePlugin("my-plugin", _handler))
+
+        result = await adapter.run(
+            task_text="write", project_path=str(repo), session_id="s1", conversation_history=[]
+        )
+
+        assert result.success
+        assert "hello" in outputs["read"]
+        assert (repo / "out.txt").read_text() == "hi\n"
+        assert "not available" in outputs["shell"]
+        assert [edit.path for edit in result.file_edits] == ["out.txt"]
+
+    @pytest.mark.asyncio
+    async def test_missing_plugin(self, agent, repo):
+        result = await WasmAgentAdapter(agent, None).run(
+            task_text="x", project_path=str(repo), session_id="s1", conversation_history=[]
+        )
+
+        assert not result.success
+        assert "`my-plugin` was not found" in result.errors[0]
+
+    @pytest.mark.asyncio
+    async def test_plugin_failure(self, agent, repo):
+        def _handler(request, call_tool):
+            raise PluginError("Plugin my-plugin failed in rc_run: unreachable")
+
+        result = await WasmAgentAdapter(agent, FakePlugin("my-plugin", _handler)).run(
+            task_text="x", project_path=str(repo), session_id="s1", conversation_history=[]
+        )
+
+        assert not result.success
+        assert "unreachable" in result.errors[0]
+
+    def test_config_accepts_wasm_agent_without_command(self, tmp_path):
+        agents_yaml = tmp_path / "agents.yaml"
+        agents_yaml.write_text("agents:\n  custom:\n    type: wasm\n    plugin: my-plugin\n")
+
+        agents = _load_agents(agents_yaml)
+
+        assert agents["custom"].type == AgentType.WASM
+        assert agents["custom"].plugin == "my-plugin"
+
+    def test_config_rejects_plugin_on_other_types(self, tmp_path):
+        agents_yaml = tmp_path / "agents.yaml"
+        agents_yaml.write_text("agents:\n  claude:\n    type: claude\n    command: [claude]\n    plugin: x\n")
+
+        with pytest.raises(ConfigError):
+            _load_agents(agents_yaml)
@@ -0,0 +1,110 @@
+"""Tests for project webhooks."""
+
+from __future__ import annotations
+
+import pytest
+
+from src.core.config import Config, _parse_webhook
+from src.core.errors import ConfigError
+from src.core.models import Project, WebhookConfig
+from src.core.webhooks import WebhookError, bearer_token, format_arguments, parse_body, resolve_webhook
+
+
+def _config(tmp_path, webhook=None) -> Config:
+    project = Project(id="api", channel_name="api", path=tmp_path, default_agent_id="claude", webhook=webhook)
+    return Config(
+        projects={"api": project},
+        agents={},
+        slack_bot_token="x",
+        slack_app_token="y",
+        slack_allowed_user_ids=[],
+        base_dir=tmp_path,
+        config_dir=tmp_path / "config",
+    )
+
+
+class TestWebhookArguments:
+    """Test cases for turning a JSON body into command arguments."""
+
+    def test_formats_key_value_pairs(self):
+        args = {"service": "payments", "minutes": 15, "page": True, "skip": None, "tags": ["a", "b"]}
+
+        assert format_arguments(args) == 'service=payments minutes=15 page=true tags=["a","b"]'
+
+    def test_quotes_values_with_spaces(self):
+        assert format_arguments({"summary": 'p99 "latency" up', "empty": ""}) == (
+            'summary="p99 \\"latency\\" up" empty=""'
+        )
+
+    def test_rejects_bad_names_and_bodies(self):
+        with pytest.raises(WebhookError, match="argument name"):
+            format_arguments({"two words": "x"})
+        with pytest.raises(WebhookError, match="not valid JSON"):
+            parse_body(b"{")
+        with pytest.raises(WebhookError, match="JSON object"):
+            parse_body(b'"payments"')
+        assert parse_body(b"  ") == {}
+
+    def test_bearer_token(self):
+        assert bearer_token("Bearer abc") == "abc"
+        assert bearer_token("bearer  abc ") == "abc"
+        assert bearer_token("Basic abc") is None
+        assert bearer_token(None) is None
+
+
+class TestResolveWebhook:
+    """Test cases for authenticating webhook requests."""
+
+    def test_accepts_the_project_token(self, tmp_path):
+        config = _config(tmp_path, WebhookConfig(token="s3cret"))
+
+        request = resolve_webhook(config, "api", "diagnose", "Bearer s3cret", b'{"service": "payments"}')
+
+        assert (request.project.id, request.command, request.arguments) == ("api", "diagnose", "service=payments")
+
+    @pytest.mark.parametrize(
+        "project_id, command, authorization, status",
+        [
+            ("api", "diagnose", "Bearer wrong", 401),
+            ("api", "diagnose", None, 401),
+            ("api", "deploy", "Bearer s3cret", 403),
+            ("web", "diagnose", "Bearer s3cret", 404),
+        ],
+    )
+    def test_rejects_requests(self, tmp_path, project_id, command, authorization, status):
+        config = _config(tmp_path, WebhookConfig(token="s3cret", commands=["diagnose"]))
+
+        with pytest.raises(WebhookError) as excinfo:
+            resolve_webhook(config, project_id, command, authorization, b"")
+        assert excinfo.value.status == status
+
+    def test_projects_without_webhook_are_not_found(self, tmp_path):
+        with pytest.raises(WebhookError) as excinfo:
+            resolve_webhook(_config(tmp_path), "api", "diagnose", "Bearer s3cret", b"")
+        assert excinfo.value.status == 404
+
+
+class TestWebhookConfig:
+    """Test cases for the `webhook` project setting."""
+
+    def test_expands_token_from_environment(self, monkeypatch):
+        monkeypatch.setenv("API_HOOK_TOKEN", "s3cret")
+
+        webhook = _parse_webhook("api", {"token": "${API_HOOK_TOKEN}", "commands": ["diagnose"]})
+
+        assert webhook == WebhookConfig(token="s3cret", commands=["diagnose"])
+        assert _parse_webhook("api", None) is None
+
+    @pytest.mark.parametrize(
+        "raw, message",
+        [
+            ("s3cret", "webhook must be a mapping"),
+            ({"commands": ["diagnose"]}, "webhook needs a token"),
+            ({"token": "s3cret", "commands": "diagnose"}, "webhook commands must be a list of command names"),
+            ({"token": "s3cret", "secret": "x"}, "webhook has unsupported keys secret"),
+            ({"token": "${REMOTE_CODER_UNSET_HOOK_TOKEN}"}, r"references \$\{REMOTE_CODER_UNSET_HOOK_TOKEN\}"),
+        ],
+    )
+    def test_rejects_invalid_values(self, raw, message):
+        with pytest.raises(ConfigError, match=message):
+            _parse_webhook("api", raw)
@@ -0,0 +1,117 @@
+"""Tests for starting processes on Windows; the parts that do not need Windows run everywhere."""
+
+from __future__ import annotations
+
+import os
+import subprocess
+import sys
+
+import pytest
+
+from src.agent_adapters import windows
+from src.agent_adapters.base import spawn_cli
+from src.agent_adapters.windows import native_path, windows_command
+from src.core.config import _parse_working_dir_mode
+from src.core.models import WorkingDirMode
+
+# What `npm install -g @anthropic-ai/claude-code` writes next to node on Windows
+NPM_SHIM = r"""@ECHO off
+GOTO start
+:find_dp0
+SET dp0=%~dp0
+EXIT /b
+:start
+SETLOCAL
+CALL :find_dp0
+
+IF EXIST "%dp0%\node.exe" (
+  SET "_prog=%dp0%\node.exe"
+) ELSE (
+  SET "_prog=node"
+  SET PATHEXT=%PATHEXT:;.JS;=;%
+)
+
+endLocal & goto #_undefined_# 2>NUL || title %COMSPEC% & "%_prog%"  "%dp0%\node_modules\@anthropic-ai\claude-code\cli.js" %*
+"""
+
+
+def _which(found: dict):
+    return lambda program: found.get(program)
+
+
+class TestWindowsCommand:
+    """Test cases for turning an agent command into something CreateProcess runs safely."""
+
+    def test_npm_shims_run_their_script_with_node(self, tmp_path):
+        shim = tmp_path / "claude.cmd"
+        shim.write_text(NPM_SHIM)
+        script = tmp_path / "node_modules" / "@anthropic-ai" / "claude-code" / "cli.js"
+        script.parent.mkdir(parents=True)
+        script.write_text("")
+        which = _which({"claude": str(shim), "node": "node.exe"})
+
+        argv = windows_command(["claude", "-p", "fix a & b | c"], which=which)
+
+        assert argv == ["node.exe", str(script), "-p", "fix a & b | c"]
+
+    def test_other_batch_files_get_every_argument_escaped_for_cmd(self, tmp_path):
+        batch = tmp_path / "agent.bat"
+        batch.write_text("@echo %*\n")
+
+        line = windows_command(["agent", 'say "hi" & exit', "100%"], which=_which({"agent": str(batch)}))
+
+        assert isinstance(line, str)
+        assert '^"say^ \\^"hi\\^"^ ^&^ exit^"' in line
+        assert line.endswith('^"100^%^"')
+
+    def test_executables_and_scripts(self):
+        which = _which({"codex": r"C:\tools\codex.exe", "agent": r"C:\tools\agent.ps1"})
+
+        assert windows_command(["codex", "exec"], which=which) == [r"C:\tools\codex.exe", "exec"]
+        assert windows_command(["agent", "x"], which=which)[:5] == [
+            "powershell.exe",
+            "-NoProfile",
+            "-ExecutionPolicy",
+            "Bypass",
+            "-File",
+        ]
+        assert windows_command(["missing", "x"], which=which) == ["missing", "x"]
+
+
+class TestPaths:
+    """Test cases for paths written in the config."""
+
+    def test_git_bash_drive_paths_become_windows_paths_on_windows(self, monkeypatch):
+        assert native_path("/c/Users/me") == "/c/Users/me"
+        monkeypatch.setattr(windows, "IS_WINDOWS", True)
+
+        assert native_path("/c/Users/me/code") == "C:\\Users\\me\\code"
+        assert native_path("/d") == "D:\\"
+        assert native_path("C:\\work") == "C:\\work"
+
+    def test_fixed_working_dirs_expand_variables(self, tmp_path, monkeypatch):
+        monkeypatch.setenv("AGENT_HOME", str(tmp_path))
+
+        mode, path = _parse_working_dir_mode("fixed: $AGENT_HOME/scratch")
+
+        assert mode == WorkingDirMode.FIXED
+        assert path == (tmp_path / "scratch").resolve()
+
+
+@pytest.mark.skipif(sys.platform != "win32", reason="needs Windows")
+class TestOnWindows:
+    """Test cases that start real processes on Windows (run in CI)."""
+
+    @pytest.mark.asyncio
+    async def test_cli_processes_are_killed_when_the_run_ends(self, tmp_path):
+        command = [sys.executable, "-c", "import time; print('up', flush=True); time.sleep(30)"]
+        async with spawn_cli(command, cwd=tmp_path, env=os.environ, stdin=False) as process:
+            assert (await process.stdout.readline()).strip() == b"up"
+        assert process.returncode is not None
+
+    def test_process_exists(self):
+        finished = subprocess.Popen([sys.executable, "-c", "pass"])
+        finished.wait()
+
+        assert windows.process_exists(os.getpid())
+        assert not windows.process_exists(finished.pid)